@@ -92,6 +92,10 @@ export function ExportModal({ projectId, onClose }: { projectId: number; onClose
           >
             <option value="mp4">MP4</option>
             <option value="mov">MOV</option>
+            <option value="high_quality">High quality (H.264, CRF 18)</option>
+            <option value="web">Web (H.264, 6 Mbps two-pass)</option>
+            <option value="hevc">HEVC (H.265)</option>
+            <option value="av1">AV1</option>
           </select>
         </div>
 
@@ -3,9 +3,12 @@ import { useState, useEffect, useRef } from 'react';
 const API_BASE = 'http://127.0.0.1:7777/api';
 
 interface JobEvent {
-  type: 'JobCompleted' | 'JobFailed' | 'AnalysisComplete';
+  type: 'JobStateChanged' | 'AnalysisComplete' | 'ProposalReady' | 'TimelineApplied';
   job_id?: number;
   job_type?: string;
+  status?: 'Pending' | 'Running' | 'Completed' | 'Failed' | 'Cancelled';
+  proposal_id?: number;
+  operations?: number;
   asset_id?: number;
   project_id?: number;
   readiness?: string;
@@ -21,17 +24,22 @@ export function useOrchestratorEvents(
   const eventSourceRef = useRef<EventSource | null>(null);
   const reconnectTimeoutRef = useRef<NodeJS.Timeout | null>(null);
   const reconnectAttempts = useRef(0);
+  // Outbox id of the last event received, so a reconnect replays what was missed
+  const lastEventId = useRef<string | null>(null);
   const maxReconnectAttempts = 10;
   const baseReconnectDelay = 1000; // 1 second
 
   useEffect(() => {
+    lastEventId.current = null;
+
     const connect = () => {
       // Close existing connection if any
       if (eventSourceRef.current) {
         eventSourceRef.current.close();
       }
 
-      const url = `${API_BASE}/projects/${projectId}/orchestrator/events`;
+      const after = lastEventId.current ? `?after=${lastEventId.current}` : '';
+      const url = `${API_BASE}/projects/${projectId}/orchestrator/events${after}`;
       const eventSource = new EventSource(url);
 
       eventSource.onopen = () => {
@@ -42,14 +50,17 @@ export function useOrchestratorEvents(
       };
 
       eventSource.onmessage = (e) => {
+        if (e.lastEventId) {
+          lastEventId.current = e.lastEventId;
+        }
         try {
           const event: JobEvent = JSON.parse(e.data);
           
           // Handle different event types
           if (event.type === 'AnalysisComplete' && event.project_id === projectId) {
             console.log('[SSE] Analysis complete for asset', event.asset_id);
-          } else if (event.type === 'JobCompleted' || event.type === 'JobFailed') {
-            console.log('[SSE] Job event:', event.type, event.job_id);
+          } else if (event.type === 'JobStateChanged') {
+            console.log('[SSE] Job event:', event.status, event.job_id);
           }
 
           if (onEvent) {
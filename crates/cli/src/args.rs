@@ -0,0 +1,98 @@
+use anyhow::{anyhow, Result};
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+
+/// Flags that take no value
+const SWITCHES: &[&str] = &[
+    "json", "wait", "unused-only", "captions", "music", "enhance-audio", "two-pass", "estimate", "once",
+    "help",
+];
+/// Flags that take every following non-flag argument
+const LISTS: &[&str] = &["files"];
+/// Short aliases
+const SHORT: &[(&str, &str)] = &[("-o", "out"), ("-h", "help")];
+
+/// Parsed command line: positionals in order plus `--flag value`, `--flag=value` and switches
+#[derive(Debug, Default)]
+pub struct Args {
+    positional: Vec<String>,
+    values: HashMap<String, Vec<String>>,
+    switches: HashSet<String>,
+}
+
+impl Args {
+    pub fn parse(raw: impl IntoIterator<Item = String>) -> Result<Args> {
+        let mut args = Args::default();
+        let mut raw = raw.into_iter().peekable();
+        while let Some(arg) = raw.next() {
+            let flag = match SHORT.iter().find(|(short, _)| *short == arg) {
+                Some((_, long)) => Some(long.to_string()),
+                None => arg.strip_prefix("--").map(String::from),
+            };
+            let Some(flag) = flag else {
+                args.positional.push(arg);
+                continue;
+            };
+
+            if let Some((name, value)) = flag.split_once('=') {
+                args.values.entry(name.to_string()).or_default().push(value.to_string());
+            } else if SWITCHES.contains(&flag.as_str()) {
+                args.switches.insert(flag);
+            } else if LISTS.contains(&flag.as_str()) {
+                let entry = args.values.entry(flag).or_default();
+                while let Some(value) = raw.next_if(|next| !next.starts_with("--")) {
+                    entry.push(value);
+                }
+            } else {
+                let value = raw.next().ok_or_else(|| anyhow!("--{} needs a value", flag))?;
+                args.values.entry(flag).or_default().push(value);
+            }
+        }
+        Ok(args)
+    }
+
+    /// Reject flags the command doesn't know, so typos don't silently do nothing
+    pub fn allow_flags(&self, allowed: &[&str]) -> Result<()> {
+        let global = ["json", "daemon-url", "help"];
+        for flag in self.values.keys().chain(self.switches.iter()) {
+            if !allowed.contains(&flag.as_str()) && !global.contains(&flag.as_str()) {
+                return Err(anyhow!("Unknown option --{}", flag));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn positional(&self, index: usize) -> Option<&str> {
+        self.positional.get(index).map(String::as_str)
+    }
+
+    pub fn positionals_from(&self, index: usize) -> &[String] {
+        self.positional.get(index..).unwrap_or(&[])
+    }
+
+    /// A required positional parsed as `T` (e.g. a project or job id)
+    pub fn required<T: FromStr>(&self, index: usize, name: &str) -> Result<T> {
+        let raw = self.positional(index).ok_or_else(|| anyhow!("Missing <{}>", name))?;
+        raw.parse().map_err(|_| anyhow!("Invalid <{}>: {}", name, raw))
+    }
+
+    pub fn switch(&self, name: &str) -> bool {
+        self.switches.contains(name)
+    }
+
+    /// Last value given for a flag
+    pub fn value(&self, name: &str) -> Option<&str> {
+        self.values.get(name).and_then(|v| v.last()).map(String::as_str)
+    }
+
+    /// Every value given for a repeatable flag
+    pub fn values(&self, name: &str) -> &[String] {
+        self.values.get(name).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    pub fn parsed<T: FromStr>(&self, name: &str) -> Result<Option<T>> {
+        self.value(name)
+            .map(|raw| raw.parse().map_err(|_| anyhow!("Invalid value for --{}: {}", name, raw)))
+            .transpose()
+    }
+}
@@ -0,0 +1,108 @@
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+
+/// Thin JSON client for the daemon's HTTP API (routes under /api)
+#[derive(Clone)]
+pub struct DaemonClient {
+    base_url: String,
+    http: reqwest::Client,
+    /// Remote worker token, sent as a bearer token on every request
+    token: Option<String>,
+}
+
+impl DaemonClient {
+    pub fn new(base_url: &str) -> Self {
+        DaemonClient {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            http: reqwest::Client::new(),
+            token: None,
+        }
+    }
+
+    pub fn with_token(mut self, token: &str) -> Self {
+        self.token = Some(token.to_string());
+        self
+    }
+
+    pub async fn get(&self, path: &str) -> Result<Value> {
+        let response = self.request(reqwest::Method::GET, path).send().await.map_err(|e| self.unreachable(e))?;
+        Self::read(response).await
+    }
+
+    pub async fn post(&self, path: &str, body: &Value) -> Result<Value> {
+        let response = self
+            .request(reqwest::Method::POST, path)
+            .json(body)
+            .send()
+            .await
+            .map_err(|e| self.unreachable(e))?;
+        Self::read(response).await
+    }
+
+    pub async fn delete(&self, path: &str) -> Result<Value> {
+        let response = self.request(reqwest::Method::DELETE, path).send().await.map_err(|e| self.unreachable(e))?;
+        Self::read(response).await
+    }
+
+    /// PUT raw bytes (a chunk of an uploaded file)
+    pub async fn put_bytes(&self, path: &str, bytes: Vec<u8>) -> Result<Value> {
+        let response = self
+            .request(reqwest::Method::PUT, path)
+            .header(reqwest::header::CONTENT_TYPE, "application/octet-stream")
+            .body(bytes)
+            .send()
+            .await
+            .map_err(|e| self.unreachable(e))?;
+        Self::read(response).await
+    }
+
+    /// Stream a file the daemon serves into `dest`
+    pub async fn download(&self, path: &str, dest: &std::path::Path) -> Result<()> {
+        use tokio::io::AsyncWriteExt;
+
+        let mut response = self.request(reqwest::Method::GET, path).send().await.map_err(|e| self.unreachable(e))?;
+        if !response.status().is_success() {
+            return Err(anyhow!("Daemon returned {} for {}", response.status(), path));
+        }
+        let mut file = tokio::fs::File::create(dest).await?;
+        while let Some(chunk) = response.chunk().await? {
+            file.write_all(&chunk).await?;
+        }
+        file.flush().await?;
+        Ok(())
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+        let request = self.http.request(method, self.url(path));
+        match &self.token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}/api{}", self.base_url, path)
+    }
+
+    fn unreachable(&self, e: reqwest::Error) -> anyhow::Error {
+        anyhow!("Couldn't reach the daemon at {} ({}). Is it running?", self.base_url, e)
+    }
+
+    /// Body as JSON on success; the status (and body text, if any) otherwise. The daemon reports
+    /// most failures as a bare status code.
+    async fn read(response: reqwest::Response) -> Result<Value> {
+        let status = response.status();
+        let text = response.text().await?;
+        if !status.is_success() {
+            return Err(if text.trim().is_empty() {
+                anyhow!("Daemon returned {}", status)
+            } else {
+                anyhow!("Daemon returned {}: {}", status, text.trim())
+            });
+        }
+        if text.trim().is_empty() {
+            return Ok(Value::Null);
+        }
+        Ok(serde_json::from_str(&text)?)
+    }
+}
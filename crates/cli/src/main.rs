@@ -0,0 +1,457 @@
+use anyhow::{anyhow, Result};
+use serde_json::{json, Value};
+use std::path::Path;
+use std::time::Duration;
+
+mod args;
+mod client;
+mod worker;
+
+use args::Args;
+use client::DaemonClient;
+
+/// How often `--wait` / `jobs watch` poll job status
+const POLL_INTERVAL: Duration = Duration::from_millis(750);
+
+const USAGE: &str = "\
+vibecut-cli - drive the VibeCut daemon from the terminal
+
+Usage: vibecut-cli [--daemon-url URL] [--json] <command> ...
+
+Commands:
+  projects list
+  projects create <name> --cache-dir <dir>
+  import <project> <folder> [--wait]
+  import <project> --files <file>... [--wait]
+  jobs show <job>
+  jobs watch <job>...
+  jobs cancel <job>
+  propose <project> <intent> [--unused-only] [--kind <kind>] [--quality <0-1>] [--asset <id>]...
+          (kinds: talking_head, b_roll, action, scenery, screen, interview, transition)
+  plan <project> --segments <id,id,...> [--target-length <sec>] [--vibe <vibe>] [--captions]
+       [--music] [--narrative <structure>] [-o <plan.json>]
+  plan <project> --request <request.json> [-o <plan.json>]
+  apply <project> --plan <plan.json> [--confirm overwrite|new_version]
+  export <project> -o <file> [--preset <preset>] [--codec h264|h265|av1] [--crf <n>]
+         [--bitrate <kbps>] [--two-pass] [--enhance-audio] [--language <code>]
+         [--meta <key=value>]... [--estimate] [--incremental] [--wait]
+  workers list
+  workers add <name> [--types <type,type,...>]
+  workers remove <worker>
+  worker --token <token> [--types <type,type,...>] [--ml-url URL] [--work-dir <dir>] [--once]
+
+Options:
+  --daemon-url  Daemon base URL (env VIBECUT_DAEMON_URL, default http://127.0.0.1:7777)
+  --json        Print raw JSON responses instead of summaries
+  --wait        Block until the started jobs finish (exit 1 if any fails)
+  --token       Remote worker token from `workers add` (env VIBECUT_WORKER_TOKEN)";
+
+#[tokio::main]
+async fn main() {
+    let result = match Args::parse(std::env::args().skip(1)) {
+        Ok(args) => run(args).await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = result {
+        eprintln!("error: {:#}", e);
+        eprintln!("Run `vibecut-cli help` for usage.");
+        std::process::exit(1);
+    }
+}
+
+async fn run(args: Args) -> Result<()> {
+    let daemon_url = args
+        .value("daemon-url")
+        .map(String::from)
+        .or_else(|| std::env::var("VIBECUT_DAEMON_URL").ok())
+        .unwrap_or_else(|| "http://127.0.0.1:7777".to_string());
+    let client = DaemonClient::new(&daemon_url);
+    let json_output = args.switch("json");
+
+    match (args.positional(0), args.positional(1)) {
+        (None, _) | (Some("help"), _) => println!("{}", USAGE),
+        _ if args.switch("help") => println!("{}", USAGE),
+        (Some("projects"), Some("list")) => {
+            args.allow_flags(&[])?;
+            let projects = client.get("/projects").await?;
+            print_or(json_output, &projects, |projects| {
+                for p in projects.as_array().into_iter().flatten() {
+                    println!("{:>4}  {}  ({})", p["id"].as_i64().unwrap_or_default(), p["name"].as_str().unwrap_or(""), p["cache_dir"].as_str().unwrap_or(""));
+                }
+            });
+        }
+        (Some("projects"), Some("create")) => {
+            args.allow_flags(&["cache-dir"])?;
+            let name: String = args.required(2, "name")?;
+            let cache_dir = args.value("cache-dir").ok_or_else(|| anyhow!("--cache-dir is required"))?;
+            let created = client
+                .post("/projects", &json!({ "name": name, "cache_dir": absolute(cache_dir)? }))
+                .await?;
+            print_or(json_output, &created, |created| println!("Created project {}", created["id"]));
+        }
+        (Some("import"), _) => {
+            args.allow_flags(&["files", "wait"])?;
+            let project_id: i64 = args.required(1, "project")?;
+            let body = match (args.positional(2), args.values("files")) {
+                (Some(folder), []) => json!({ "folder_path": absolute(folder)? }),
+                (None, files) if !files.is_empty() => json!({
+                    "file_paths": files.iter().map(|f| absolute(f)).collect::<Result<Vec<_>>>()?
+                }),
+                _ => return Err(anyhow!("Give either a folder or --files, not both")),
+            };
+            let response = client.post(&format!("/projects/{}/import_raw", project_id), &body).await?;
+            let job_ids: Vec<i64> = match response.get("job_ids").and_then(|ids| ids.as_array()) {
+                Some(ids) => ids.iter().filter_map(|id| id.as_i64()).collect(),
+                None => response["job_id"].as_i64().into_iter().collect(),
+            };
+            print_or(json_output, &response, |_| println!("Started import jobs: {:?}", job_ids));
+            if args.switch("wait") {
+                watch_jobs(&client, &job_ids, json_output).await?;
+            }
+        }
+        (Some("jobs"), Some("show")) => {
+            args.allow_flags(&[])?;
+            let job_id: i64 = args.required(2, "job")?;
+            let job = client.get(&format!("/jobs/{}", job_id)).await?;
+            print_or(json_output, &job, |job| println!("{}", job_line(job)));
+        }
+        (Some("jobs"), Some("watch")) => {
+            args.allow_flags(&[])?;
+            let job_ids = args
+                .positionals_from(2)
+                .iter()
+                .map(|id| id.parse().map_err(|_| anyhow!("Invalid <job>: {}", id)))
+                .collect::<Result<Vec<i64>>>()?;
+            if job_ids.is_empty() {
+                return Err(anyhow!("Missing <job>"));
+            }
+            watch_jobs(&client, &job_ids, json_output).await?;
+        }
+        (Some("jobs"), Some("cancel")) => {
+            args.allow_flags(&[])?;
+            let job_id: i64 = args.required(2, "job")?;
+            client.post(&format!("/jobs/{}/cancel", job_id), &json!({})).await?;
+            println!("Cancelled job {}", job_id);
+        }
+        (Some("propose"), _) => {
+            args.allow_flags(&["unused-only", "kind", "quality", "asset"])?;
+            let project_id: i64 = args.required(1, "project")?;
+            let intent = args.positionals_from(2).join(" ");
+            if intent.trim().is_empty() {
+                return Err(anyhow!("Missing <intent>"));
+            }
+            let asset_ids = args
+                .values("asset")
+                .iter()
+                .map(|id| id.parse().map_err(|_| anyhow!("Invalid --asset: {}", id)))
+                .collect::<Result<Vec<i64>>>()?;
+            let body = json!({
+                "user_intent": intent,
+                "filters": {
+                    "unused_only": args.switch("unused-only"),
+                    "segment_kind": args.value("kind"),
+                    "quality_threshold": args.parsed::<f64>("quality")?,
+                    "asset_ids": if asset_ids.is_empty() { None } else { Some(asset_ids) },
+                    "capture_time_range": null,
+                },
+            });
+            let response = client.post(&format!("/projects/{}/orchestrator/propose", project_id), &body).await?;
+            print_or(json_output, &response, print_agent_response);
+        }
+        (Some("plan"), _) => {
+            args.allow_flags(&["segments", "target-length", "vibe", "captions", "music", "narrative", "request", "out"])?;
+            let project_id: i64 = args.required(1, "project")?;
+            let body: Value = match args.value("request") {
+                Some(path) => serde_json::from_str(&std::fs::read_to_string(path)?)?,
+                None => {
+                    let segments = args
+                        .value("segments")
+                        .ok_or_else(|| anyhow!("--segments or --request is required"))?
+                        .split(',')
+                        .map(|id| id.trim().parse().map_err(|_| anyhow!("Invalid segment id: {}", id)))
+                        .collect::<Result<Vec<i64>>>()?;
+                    json!({
+                        "beats": [{ "beat_id": "beat_1", "segment_ids": segments }],
+                        "constraints": {
+                            "target_length": args.parsed::<i64>("target-length")?,
+                            "vibe": args.value("vibe"),
+                            "captions_on": args.switch("captions"),
+                            "music_on": args.switch("music"),
+                        },
+                        "narrative_structure": args.value("narrative").unwrap_or("linear"),
+                    })
+                }
+            };
+            let response = client.post(&format!("/projects/{}/orchestrator/plan", project_id), &body).await?;
+            if let Some(out) = args.value("out") {
+                let edit_plan = response
+                    .pointer("/data/edit_plan")
+                    .ok_or_else(|| anyhow!("Daemon didn't return an edit plan: {}", response["message"]))?;
+                std::fs::write(out, serde_json::to_string_pretty(edit_plan)?)?;
+                println!("Wrote edit plan to {}", out);
+            }
+            print_or(json_output, &response, print_agent_response);
+        }
+        (Some("apply"), _) => {
+            args.allow_flags(&["plan", "confirm"])?;
+            let project_id: i64 = args.required(1, "project")?;
+            let plan = args.value("plan").ok_or_else(|| anyhow!("--plan is required"))?;
+            let edit_plan: Value = serde_json::from_str(&std::fs::read_to_string(plan)?)?;
+            let path = match args.value("confirm") {
+                Some(confirm @ ("overwrite" | "new_version")) => {
+                    format!("/projects/{}/orchestrator/apply?confirm={}", project_id, confirm)
+                }
+                Some(other) => return Err(anyhow!("--confirm must be overwrite or new_version, not {}", other)),
+                None => format!("/projects/{}/orchestrator/apply", project_id),
+            };
+            let response = client.post(&path, &json!({ "edit_plan": edit_plan })).await?;
+            print_or(json_output, &response, print_agent_response);
+        }
+        (Some("export"), _) => {
+            args.allow_flags(&[
+                "out", "preset", "codec", "crf", "bitrate", "two-pass", "enhance-audio", "language", "meta", "estimate",
+                "incremental", "wait",
+            ])?;
+            let project_id: i64 = args.required(1, "project")?;
+            let out = args.value("out").ok_or_else(|| anyhow!("-o/--out is required"))?;
+            let mut body = json!({
+                "out_path": absolute(out)?,
+                "preset": args.value("preset"),
+                "encoder": {
+                    "codec": args.value("codec"),
+                    "crf": args.parsed::<u8>("crf")?,
+                    "bitrate_kbps": args.parsed::<u32>("bitrate")?,
+                    "two_pass": args.switch("two-pass").then_some(true),
+                },
+            });
+            if args.switch("estimate") {
+                let response = client.post(&format!("/projects/{}/export/estimate", project_id), &body).await?;
+                print_or(json_output, &response, print_estimate);
+                return Ok(());
+            }
+            if args.switch("enhance-audio") {
+                body["enhance_audio"] = json!({});
+            }
+            if args.switch("incremental") {
+                body["incremental"] = json!(true);
+            }
+            if let Some(language) = args.value("language") {
+                body["language"] = json!(language);
+            }
+            let mut metadata = serde_json::Map::new();
+            for entry in args.values("meta") {
+                let (key, value) = entry
+                    .split_once('=')
+                    .ok_or_else(|| anyhow!("--meta takes key=value, not {}", entry))?;
+                metadata.insert(key.to_string(), json!(value));
+            }
+            if !metadata.is_empty() {
+                body["metadata"] = serde_json::Value::Object(metadata);
+            }
+            let response = client.post(&format!("/projects/{}/export", project_id), &body).await?;
+            let job_id = response["job_id"].as_i64().ok_or_else(|| anyhow!("No job id in export response"))?;
+            print_or(json_output, &response, |response| {
+                println!("Started export job {}", job_id);
+                if let Some(bytes) = response["estimated_size_bytes"].as_u64() {
+                    println!("  estimated size {}", format_size(bytes));
+                }
+                let incremental = &response["incremental"];
+                if !incremental.is_null() {
+                    println!(
+                        "  building on export {}: {:.1}s reused, {:.1}s re-rendered",
+                        incremental["previous_job_id"],
+                        incremental["reused_seconds"].as_f64().unwrap_or(0.0),
+                        incremental["rendered_seconds"].as_f64().unwrap_or(0.0)
+                    );
+                }
+            });
+            if args.switch("wait") {
+                watch_jobs(&client, &[job_id], json_output).await?;
+                println!("Exported {}", out);
+            }
+        }
+        (Some("workers"), Some("list")) => {
+            args.allow_flags(&[])?;
+            let workers = client.get("/remote_workers").await?;
+            print_or(json_output, &workers, |workers| {
+                for w in workers.as_array().into_iter().flatten() {
+                    println!(
+                        "worker {:>3}  {:<20} {:<8} {}  jobs {:?}",
+                        w["id"],
+                        w["name"].as_str().unwrap_or(""),
+                        if w["online"].as_bool() == Some(true) { "online" } else { "offline" },
+                        job_types_list(&w["job_types"]),
+                        w["job_ids"].as_array().map(|ids| ids.len()).unwrap_or(0),
+                    );
+                }
+            });
+        }
+        (Some("workers"), Some("add")) => {
+            args.allow_flags(&["types"])?;
+            let name: String = args.required(2, "name")?;
+            let response = client
+                .post("/remote_workers", &json!({ "name": name, "job_types": args.value("types").map(split_list) }))
+                .await?;
+            print_or(json_output, &response, |response| {
+                println!("Registered worker {} ({})", response["id"], job_types_list(&response["job_types"]));
+                println!("  token: {}", response["token"].as_str().unwrap_or(""));
+                println!("  Run on the worker machine: vibecut-cli --daemon-url {} worker --token <token>", daemon_url);
+            });
+        }
+        (Some("workers"), Some("remove")) => {
+            args.allow_flags(&[])?;
+            let worker_id: i64 = args.required(2, "worker")?;
+            client.delete(&format!("/remote_workers/{}", worker_id)).await?;
+            println!("Removed worker {}; its jobs went back to the queue", worker_id);
+        }
+        (Some("worker"), _) => {
+            args.allow_flags(&["token", "types", "ml-url", "work-dir", "once"])?;
+            let token = args
+                .value("token")
+                .map(String::from)
+                .or_else(|| std::env::var("VIBECUT_WORKER_TOKEN").ok())
+                .ok_or_else(|| anyhow!("--token is required (from `vibecut-cli workers add`)"))?;
+            let options = worker::WorkerOptions {
+                job_types: args.value("types").map(split_list),
+                ml_url: args.value("ml-url").unwrap_or("http://127.0.0.1:8001").to_string(),
+                work_dir: args.value("work-dir").unwrap_or("vibecut-worker").into(),
+                once: args.switch("once"),
+            };
+            worker::run(&client.with_token(&token), &options).await?;
+        }
+        (Some(command), sub) => {
+            return Err(match sub {
+                Some(sub) if ["projects", "jobs", "workers"].contains(&command) => anyhow!("Unknown command: {} {}", command, sub),
+                _ => anyhow!("Unknown command: {}", command),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Poll jobs until none is pending or running, printing a line whenever one changes.
+/// Fails if any job ends failed or cancelled.
+async fn watch_jobs(client: &DaemonClient, job_ids: &[i64], json_output: bool) -> Result<()> {
+    let mut last_lines = vec![String::new(); job_ids.len()];
+    loop {
+        let mut finished = 0;
+        let mut failed = Vec::new();
+        for (i, job_id) in job_ids.iter().enumerate() {
+            let job = client.get(&format!("/jobs/{}", job_id)).await?;
+            let line = if json_output { job.to_string() } else { job_line(&job) };
+            if line != last_lines[i] {
+                println!("{}", line);
+                last_lines[i] = line;
+            }
+            match job_status(&job).as_str() {
+                "Completed" => finished += 1,
+                "Failed" | "Cancelled" => {
+                    finished += 1;
+                    failed.push(*job_id);
+                }
+                _ => {}
+            }
+        }
+        if finished == job_ids.len() {
+            if !failed.is_empty() {
+                return Err(anyhow!("Jobs didn't complete: {:?}", failed));
+            }
+            return Ok(());
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// The daemon serializes job enums as JSON strings inside the field ("\"Running\"")
+fn job_status(job: &Value) -> String {
+    job["status"].as_str().unwrap_or("").trim_matches('"').to_string()
+}
+
+fn job_line(job: &Value) -> String {
+    format!(
+        "job {:>5}  {:<14} {:<10} {:>5.1}%",
+        job["id"],
+        job["job_type"].as_str().unwrap_or("").trim_matches('"'),
+        job_status(job),
+        job["progress"].as_f64().unwrap_or(0.0) * 100.0,
+    )
+}
+
+/// Agent responses share one shape: mode, message, suggestions, optional data
+fn print_agent_response(response: &Value) {
+    println!("[{}] {}", response["mode"].as_str().unwrap_or(""), response["message"].as_str().unwrap_or(""));
+    if let Some(candidates) = response.pointer("/data/candidate_segments").and_then(|c| c.as_array()) {
+        for c in candidates {
+            println!(
+                "  segment {:>5}  {:.3}  {:>5.1}s  {}",
+                c["segment_id"],
+                c["similarity_score"].as_f64().unwrap_or(0.0),
+                c["duration_sec"].as_f64().unwrap_or(0.0),
+                c["summary_text"].as_str().unwrap_or(""),
+            );
+        }
+    }
+    if let Some(excluded) = response.pointer("/data/excluded_segments").and_then(|e| e.as_array()) {
+        for e in excluded {
+            println!("  excluded {:>5}  {}", e["segment_id"], e["reason"].as_str().unwrap_or(""));
+        }
+    }
+    for suggestion in response["suggestions"].as_array().into_iter().flatten() {
+        println!("  > {}", suggestion["label"].as_str().unwrap_or(""));
+    }
+}
+
+fn print_estimate(response: &Value) {
+    let encoder = &response["encoder"];
+    let rate = match (encoder["crf"].as_u64(), encoder["bitrate_kbps"].as_u64()) {
+        (Some(crf), _) => format!("crf {}", crf),
+        (_, Some(kbps)) => format!("{} kbps", kbps),
+        _ => "-".to_string(),
+    };
+    println!(
+        "{} ({}, {}, preset {}{})",
+        response["preset"].as_str().unwrap_or("-"),
+        encoder["codec"].as_str().unwrap_or("-"),
+        rate,
+        encoder["preset"].as_str().unwrap_or("-"),
+        if encoder["two_pass"].as_bool() == Some(true) { ", two-pass" } else { "" },
+    );
+    println!(
+        "  {:.0}s at ~{} kbps video: ~{}{}",
+        response["duration_seconds"].as_f64().unwrap_or(0.0),
+        response["video_kbps"].as_f64().unwrap_or(0.0),
+        format_size(response["size_bytes"].as_u64().unwrap_or(0)),
+        if response["basis"] == "crf" { " (CRF estimate, varies with content)" } else { "" },
+    );
+}
+
+fn format_size(bytes: u64) -> String {
+    if bytes >= 1_000_000_000 {
+        format!("{:.2} GB", bytes as f64 / 1e9)
+    } else {
+        format!("{:.1} MB", bytes as f64 / 1e6)
+    }
+}
+
+fn split_list(raw: &str) -> Vec<String> {
+    raw.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect()
+}
+
+fn job_types_list(types: &Value) -> String {
+    types.as_array().into_iter().flatten().filter_map(|t| t.as_str()).collect::<Vec<_>>().join(",")
+}
+
+fn print_or(json_output: bool, value: &Value, summary: impl FnOnce(&Value)) {
+    if json_output {
+        println!("{}", serde_json::to_string_pretty(value).unwrap_or_default());
+    } else {
+        summary(value);
+    }
+}
+
+/// The daemon resolves paths itself, so send absolute ones
+fn absolute(path: &str) -> Result<String> {
+    let path = Path::new(path);
+    let path = if path.is_absolute() { path.to_path_buf() } else { std::env::current_dir()?.join(path) };
+    Ok(path.to_string_lossy().into_owned())
+}
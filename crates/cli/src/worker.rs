@@ -0,0 +1,330 @@
+use anyhow::{anyhow, Context, Result};
+use media_process::{ProcessFailed, Tool};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::AsyncReadExt;
+use tokio::process::Command;
+
+use crate::client::DaemonClient;
+
+/// How often an idle worker asks for a job
+const CLAIM_INTERVAL: Duration = Duration::from_secs(5);
+/// How often a busy worker checks in; well inside the daemon's two-minute lease
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(30);
+/// Size of each chunk of an uploaded render
+const UPLOAD_CHUNK_BYTES: usize = 8 * 1024 * 1024;
+
+pub struct WorkerOptions {
+    /// Only claim these job types (default: whatever the worker was registered for)
+    pub job_types: Option<Vec<String>>,
+    /// ML service that runs transcription and vision analysis on this machine
+    pub ml_url: String,
+    /// Where inputs are downloaded and renders written
+    pub work_dir: PathBuf,
+    /// Run one job (or none, if the queue is empty) and exit
+    pub once: bool,
+}
+
+/// Claim jobs from the daemon and run them here until interrupted
+pub async fn run(client: &DaemonClient, options: &WorkerOptions) -> Result<()> {
+    tokio::fs::create_dir_all(&options.work_dir).await?;
+    println!("Waiting for jobs from the daemon (Ctrl-C to stop)");
+    loop {
+        let job = client
+            .post("/queue/claim", &json!({ "job_types": options.job_types }))
+            .await?;
+        if job.is_null() {
+            if options.once {
+                println!("No jobs waiting");
+                return Ok(());
+            }
+            tokio::time::sleep(CLAIM_INTERVAL).await;
+            continue;
+        }
+
+        let job_id = job["job_id"].as_i64().ok_or_else(|| anyhow!("Claimed job has no id"))?;
+        let job_type = job["job_type"].as_str().unwrap_or("").to_string();
+        println!("Running {} job {} (attempt {})", job_type, job_id, job["attempt"]);
+        let dir = options.work_dir.join(format!("job_{}", job_id));
+        tokio::fs::create_dir_all(&dir).await?;
+
+        let progress = Arc::new(Mutex::new(0.0));
+        let stopped = Arc::new(AtomicBool::new(false));
+        let heartbeat = tokio::spawn(keep_claim(client.clone(), job_id, progress.clone(), stopped.clone()));
+        let outcome = run_job(client, &job, &dir, options, &progress, &stopped).await;
+        heartbeat.abort();
+
+        match outcome {
+            Ok(result) => {
+                client
+                    .post(&format!("/queue/jobs/{}/complete", job_id), &json!({ "result": result }))
+                    .await?;
+                println!("Completed job {}", job_id);
+            }
+            Err(_) if stopped.load(Ordering::SeqCst) => {
+                println!("Job {} was cancelled or reassigned; dropped it", job_id);
+            }
+            Err(e) => {
+                eprintln!("Job {} failed: {:#}", job_id, e);
+                let response = client
+                    .post(&format!("/queue/jobs/{}/fail", job_id), &json!({ "error": format!("{:#}", e) }))
+                    .await?;
+                if response["requeued"].as_bool() == Some(true) {
+                    println!("  queued for another attempt");
+                }
+            }
+        }
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+        if options.once {
+            return Ok(());
+        }
+    }
+}
+
+/// Heartbeat with the latest progress until the job is done. A rejected heartbeat means the
+/// job was cancelled or given to another worker, so the work in progress is stopped.
+async fn keep_claim(client: DaemonClient, job_id: i64, progress: Arc<Mutex<f64>>, stopped: Arc<AtomicBool>) {
+    loop {
+        tokio::time::sleep(HEARTBEAT_INTERVAL).await;
+        let progress = *progress.lock().unwrap();
+        let body = json!({ "progress": progress });
+        if let Err(e) = client.post(&format!("/queue/jobs/{}/heartbeat", job_id), &body).await {
+            // 409: no longer ours. Anything else (the daemon restarting, a network blip) is retried.
+            if e.to_string().contains("409") {
+                stopped.store(true, Ordering::SeqCst);
+                return;
+            }
+            eprintln!("Heartbeat for job {} failed: {:#}", job_id, e);
+        }
+    }
+}
+
+async fn run_job(
+    client: &DaemonClient,
+    job: &Value,
+    dir: &Path,
+    options: &WorkerOptions,
+    progress: &Mutex<f64>,
+    stopped: &AtomicBool,
+) -> Result<Value> {
+    let job_id = job["job_id"].as_i64().unwrap_or_default();
+
+    // Fetch the inputs; the daemon's paths in the job are swapped for the local copies
+    let mut local_paths: Vec<(String, String)> = Vec::new();
+    for input in job["inputs"].as_array().into_iter().flatten() {
+        let remote_path = input["path"].as_str().unwrap_or("").to_string();
+        let name = Path::new(&remote_path)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "input".to_string());
+        let local = dir.join(format!("input_{}_{}", input["index"], name));
+        client
+            .download(&format!("/queue/jobs/{}/inputs/{}", job_id, input["index"]), &local)
+            .await
+            .with_context(|| format!("Downloading {}", remote_path))?;
+        local_paths.push((remote_path, absolute(&local)?));
+    }
+    *progress.lock().unwrap() = 0.1;
+
+    match job["job_type"].as_str() {
+        Some("Export") => render(client, job, dir, &local_paths, progress, stopped).await,
+        Some("TranscribeAsset") | Some("AnalyzeVisionAsset") => {
+            let request = &job["ml_request"];
+            let endpoint = request["endpoint"].as_str().ok_or_else(|| anyhow!("Job has no ML request"))?;
+            let body = replace_paths(&request["body"], &local_paths);
+            let response = reqwest::Client::new()
+                .post(format!("{}{}", options.ml_url.trim_end_matches('/'), endpoint))
+                .json(&body)
+                .send()
+                .await
+                .with_context(|| format!("Couldn't reach the ML service at {}", options.ml_url))?;
+            if !response.status().is_success() {
+                return Err(anyhow!("ML service {} failed: {}", endpoint, response.status()));
+            }
+            Ok(response.json().await?)
+        }
+        other => Err(anyhow!("This worker can't run {:?} jobs", other)),
+    }
+}
+
+/// Run an export's ffmpeg passes against the local inputs and upload the result
+async fn render(
+    client: &DaemonClient,
+    job: &Value,
+    dir: &Path,
+    local_paths: &[(String, String)],
+    progress: &Mutex<f64>,
+    stopped: &AtomicBool,
+) -> Result<Value> {
+    let job_id = job["job_id"].as_i64().unwrap_or_default();
+    let payload = &job["payload"];
+    let out_path = payload["out_path"].as_str().ok_or_else(|| anyhow!("Export has no out_path"))?;
+    let extension = Path::new(out_path).extension().map(|e| e.to_string_lossy().to_string()).unwrap_or_default();
+    // The output and its two-pass stats file are named after the daemon's out_path
+    let out_stem = Path::new(out_path).with_extension("").to_string_lossy().to_string();
+    let local_stem = absolute(&dir.join("output"))?;
+    // Clip intermediates and (for incremental exports) spans the passes read are rendered here
+    // first, then sent back for the daemon's render cache
+    let renders: Vec<&Value> = ["clip_renders", "span_renders"]
+        .iter()
+        .flat_map(|key| payload[*key].as_array().into_iter().flatten())
+        .collect();
+    let local_name = |path: &str| -> Result<String> {
+        let name = Path::new(path).file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        absolute(&dir.join(name))
+    };
+    let mut render_paths: Vec<(String, String)> = Vec::new();
+    for path in renders.iter().filter_map(|r| r["path"].as_str()) {
+        render_paths.push((path.to_string(), local_name(path)?));
+    }
+    // A stitched export reads an ffconcat list, written here with the local paths
+    let local_list = absolute(&dir.join("stitch.ffconcat"))?;
+    let local_paths: Vec<(String, String)> = payload["concat_list_path"]
+        .as_str()
+        .map(|path| (path.to_string(), local_list.clone()))
+        .into_iter()
+        .chain(local_paths.iter().cloned())
+        .chain(render_paths.iter().cloned())
+        .collect();
+    // Two-pass stats files are named after each output
+    let stems: Vec<(String, String)> = std::iter::once((out_stem.clone(), local_stem.clone()))
+        .chain(render_paths.iter().map(|(remote, local)| {
+            let stem = |p: &str| Path::new(p).with_extension("").to_string_lossy().to_string();
+            (stem(remote), stem(local))
+        }))
+        .collect();
+    let localize_path = |path: &str| -> String {
+        match local_paths.iter().find(|(remote, _)| remote == path) {
+            Some((_, local)) => local.clone(),
+            None => stems.iter().fold(path.to_string(), |arg, (remote, local)| arg.replace(remote, local)),
+        }
+    };
+    let localize = |args: &Value| -> Vec<String> {
+        args.as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|a| a.as_str())
+            .map(localize_path)
+            .collect()
+    };
+
+    for render in &renders {
+        if !render["first_pass_args"].is_null() {
+            run_ffmpeg(&localize(&render["first_pass_args"]), stopped).await.context("First pass")?;
+        }
+        run_ffmpeg(&localize(&render["ffmpeg_args"]), stopped)
+            .await
+            .with_context(|| format!("Rendering {}", render["path"]))?;
+    }
+    for (path, local) in &render_paths {
+        let name = Path::new(path).file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        upload(client, job_id, &name, Path::new(local)).await?;
+    }
+    if !renders.is_empty() {
+        *progress.lock().unwrap() = 0.3;
+    }
+    if let Some(entries) = payload["concat_list"].as_array() {
+        let mut list = String::from("ffconcat version 1.0\n");
+        for entry in entries {
+            let path = localize_path(entry["path"].as_str().unwrap_or_default());
+            list.push_str(&format!("file '{}'\n", path.replace('\'', "'\\''")));
+            for key in ["inpoint", "outpoint"] {
+                if let Some(seconds) = entry[key].as_f64() {
+                    list.push_str(&format!("{} {:.6}\n", key, seconds));
+                }
+            }
+        }
+        tokio::fs::write(&local_list, list).await?;
+    }
+
+    if !payload["first_pass_args"].is_null() {
+        run_ffmpeg(&localize(&payload["first_pass_args"]), stopped).await.context("First pass")?;
+        *progress.lock().unwrap() = 0.5;
+    }
+    run_ffmpeg(&localize(&payload["ffmpeg_args"]), stopped).await?;
+    *progress.lock().unwrap() = 0.9;
+
+    let output = PathBuf::from(format!("{}.{}", local_stem, extension));
+    let size_bytes = upload(client, job_id, "output", &output).await?;
+    Ok(json!({ "rendered_bytes": size_bytes }))
+}
+
+/// Run ffmpeg with the daemon's argument checks, timeout and output cap, killing it if the job
+/// is stopped
+async fn run_ffmpeg(args: &[String], stopped: &AtomicBool) -> Result<()> {
+    let args = ["-hide_banner", "-loglevel", "error"]
+        .iter()
+        .map(|a| a.to_string())
+        .chain(args.iter().cloned())
+        .collect();
+    let args = media_process::checked_args(Tool::Ffmpeg, args)?;
+    let run = media_process::run(Tool::Ffmpeg, Command::new(Tool::Ffmpeg.program()), &args, Tool::Ffmpeg.default_timeout());
+    tokio::pin!(run);
+    loop {
+        tokio::select! {
+            output = &mut run => {
+                let output = output?;
+                if !output.status.success() {
+                    return Err(ProcessFailed::exited(Tool::Ffmpeg, &output).into());
+                }
+                return Ok(());
+            }
+            _ = tokio::time::sleep(Duration::from_secs(1)) => {
+                // Dropping the run kills ffmpeg
+                if stopped.load(Ordering::SeqCst) {
+                    return Err(anyhow!("Stopped"));
+                }
+            }
+        }
+    }
+}
+
+/// Upload a file as a job artifact in chunks, resuming from what the daemon already has
+async fn upload(client: &DaemonClient, job_id: i64, name: &str, path: &Path) -> Result<u64> {
+    let artifact = format!("/queue/jobs/{}/artifacts/{}", job_id, name);
+    let size = tokio::fs::metadata(path).await.with_context(|| format!("No output at {}", path.display()))?.len();
+    let mut offset = client.get(&artifact).await?["received_bytes"].as_u64().unwrap_or(0);
+    let mut file = tokio::fs::File::open(path).await?;
+    let mut failures = 0;
+    while offset < size {
+        let mut chunk = vec![0; UPLOAD_CHUNK_BYTES.min((size - offset) as usize)];
+        tokio::io::AsyncSeekExt::seek(&mut file, std::io::SeekFrom::Start(offset)).await?;
+        file.read_exact(&mut chunk).await?;
+        match client.put_bytes(&format!("{}?offset={}", artifact, offset), chunk).await {
+            Ok(response) => offset = response["received_bytes"].as_u64().unwrap_or(offset),
+            Err(e) if failures < 3 => {
+                // Resume from whatever the daemon kept
+                failures += 1;
+                eprintln!("Upload of {} interrupted ({:#}), resuming", name, e);
+                offset = client.get(&artifact).await?["received_bytes"].as_u64().unwrap_or(0);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(size)
+}
+
+/// Swap the daemon's file paths for local copies wherever they appear as JSON strings
+fn replace_paths(value: &Value, local_paths: &[(String, String)]) -> Value {
+    match value {
+        Value::String(s) => match local_paths.iter().find(|(remote, _)| remote == s) {
+            Some((_, local)) => Value::String(local.clone()),
+            None => value.clone(),
+        },
+        Value::Array(items) => Value::Array(items.iter().map(|v| replace_paths(v, local_paths)).collect()),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(k, v)| (k.clone(), replace_paths(v, local_paths)))
+                .collect(),
+        ),
+        _ => value.clone(),
+    }
+}
+
+fn absolute(path: &Path) -> Result<String> {
+    let path = if path.is_absolute() { path.to_path_buf() } else { std::env::current_dir()?.join(path) };
+    Ok(path.to_string_lossy().to_string())
+}
@@ -0,0 +1,95 @@
+//! Retrieval throughput over synthetic projects of 10k and 100k segments (see
+//! `vibecut::simulation::dataset`): brute-force text and vision search with their scores fused,
+//! as propose runs it, the same search restricted to a prefiltered tenth of the project, and
+//! lexical (FTS5) search.
+//!
+//! ```bash
+//! cargo bench -p daemon --bench retrieval
+//! BENCH_SEGMENTS=10000 cargo bench -p daemon --bench retrieval -- fused_search
+//! ```
+//!
+//! Databases are generated once per size under the temp directory and reused by later runs.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use vibecut::db::Database;
+use vibecut::embeddings::{fused_search, SearchScope};
+use vibecut::simulation::dataset::{self, SyntheticProject};
+
+/// Project sizes benchmarked, unless BENCH_SEGMENTS lists others (comma separated)
+const DEFAULT_SIZES: &[usize] = &[10_000, 100_000];
+const SEED: u64 = 42;
+/// Candidates propose oversamples from the vector search, and lexical matches it blends in
+const VECTOR_LIMIT: usize = 200;
+const LEXICAL_LIMIT: usize = 50;
+const QUERY: &str = "the view from the top of the hill";
+
+fn sizes() -> Vec<usize> {
+    match std::env::var("BENCH_SEGMENTS") {
+        Ok(sizes) => sizes.split(',').filter_map(|s| s.trim().parse().ok()).collect(),
+        Err(_) => DEFAULT_SIZES.to_vec(),
+    }
+}
+
+/// Database holding a synthetic project of `segments`, generated on first use
+fn synthetic_db(segments: usize) -> (Arc<Database>, SyntheticProject) {
+    let path: PathBuf = std::env::temp_dir().join(format!("vibecut-retrieval-{}-{}.db", segments, SEED));
+    let db = Database::new(&path).expect("open benchmark database");
+    let project = match dataset::find(&db, segments, SEED).expect("read benchmark database") {
+        Some(project) => project,
+        None => {
+            eprintln!("Generating {} synthetic segments in {}", segments, path.display());
+            dataset::generate(&db, segments, SEED).expect("generate benchmark database")
+        }
+    };
+    (Arc::new(db), project)
+}
+
+fn retrieval(c: &mut Criterion) {
+    let text_query = dataset::query_embedding(QUERY);
+    let vision_query = dataset::vision_query_embedding(QUERY);
+    for segments in sizes() {
+        let (db, project) = synthetic_db(segments);
+        let mut group = c.benchmark_group("retrieval");
+        group.sample_size(10).measurement_time(Duration::from_secs(10));
+        group.throughput(Throughput::Elements(segments as u64));
+
+        group.bench_with_input(BenchmarkId::new("fused_search", segments), &segments, |b, _| {
+            b.iter(|| {
+                fused_search(
+                    db.clone(),
+                    black_box(&text_query),
+                    Some(black_box(&vision_query)),
+                    VECTOR_LIMIT,
+                    SearchScope::Project(project.project_id),
+                )
+                .unwrap()
+            })
+        });
+
+        // Prefiltered searches score an id list instead of scanning the project
+        let allowed: Vec<i64> = project.segment_ids.iter().step_by(10).copied().collect();
+        group.bench_with_input(BenchmarkId::new("fused_search_among_10pct", segments), &segments, |b, _| {
+            b.iter(|| {
+                fused_search(
+                    db.clone(),
+                    black_box(&text_query),
+                    Some(black_box(&vision_query)),
+                    VECTOR_LIMIT,
+                    SearchScope::Segments(&allowed),
+                )
+                .unwrap()
+            })
+        });
+
+        group.bench_with_input(BenchmarkId::new("fts_search", segments), &segments, |b, _| {
+            b.iter(|| db.search_segments_lexical(project.project_id, black_box(QUERY), LEXICAL_LIMIT).unwrap())
+        });
+        group.finish();
+    }
+}
+
+criterion_group!(benches, retrieval);
+criterion_main!(benches);
@@ -0,0 +1,181 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+    routing::{get, patch, post},
+    Router,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::api::media::PathError;
+use crate::brand::{import_asset, BrandKit};
+use crate::db::{BrandAsset, Database};
+use crate::media::roots;
+
+#[derive(Serialize)]
+pub struct BrandKitResponse {
+    kit: BrandKit,
+    assets: Vec<BrandAsset>,
+}
+
+#[derive(Deserialize)]
+pub struct AddAssetRequest {
+    /// "logo" or "font"
+    kind: String,
+    /// File to add; it's copied into the project cache
+    path: String,
+    /// Defaults to the file name
+    name: Option<String>,
+    /// The font's license allows using it in exported video
+    #[serde(default)]
+    licensed: bool,
+    /// Color to key out of a logo without transparency (e.g. "white")
+    key_color: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct AddAssetResponse {
+    asset: BrandAsset,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    warnings: Vec<String>,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateAssetRequest {
+    licensed: Option<bool>,
+    /// Empty string clears it
+    key_color: Option<String>,
+}
+
+pub fn router(db: Arc<Database>) -> Router {
+    Router::new()
+        .route("/:id/brand", get(get_brand_kit).put(set_brand_kit))
+        .route("/:id/brand/assets", post(add_asset))
+        .route("/:id/brand/assets/:aid", patch(update_asset).delete(delete_asset))
+        .with_state(db)
+}
+
+async fn get_brand_kit(
+    State(db): State<Arc<Database>>,
+    Path(id): Path<i64>,
+) -> Result<Json<BrandKitResponse>, StatusCode> {
+    find_project(&db, id)?;
+    let kit = BrandKit::for_project(&db, id).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let assets = db.list_brand_assets(id).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(BrandKitResponse { kit, assets }))
+}
+
+/// PUT /projects/:id/brand - Replace the palette, the logo and fonts used, and the watermark
+async fn set_brand_kit(
+    State(db): State<Arc<Database>>,
+    Path(id): Path<i64>,
+    Json(kit): Json<BrandKit>,
+) -> Result<Json<BrandKitResponse>, StatusCode> {
+    find_project(&db, id)?;
+    let assets = db.list_brand_assets(id).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if let Err(message) = kit.validate(&assets) {
+        eprintln!("[BRAND] Rejected brand kit for project {}: {}", id, message);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let json = serde_json::to_string(&kit).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    db.set_project_brand_kit(id, Some(&json))
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(BrandKitResponse { kit, assets }))
+}
+
+/// POST /projects/:id/brand/assets - Check a logo or font and add it to the project's brand kit
+async fn add_asset(
+    State(db): State<Arc<Database>>,
+    Path(id): Path<i64>,
+    Json(req): Json<AddAssetRequest>,
+) -> Result<Json<AddAssetResponse>, PathError> {
+    let project = find_project(&db, id)?;
+    let key_color = req.key_color.filter(|c| !c.is_empty());
+    if let Some(color) = &key_color {
+        if !color.chars().all(|c| c.is_ascii_alphanumeric() || c == '#') {
+            eprintln!("[BRAND] Invalid key color {:?} for project {}", color, id);
+            return Err(StatusCode::BAD_REQUEST.into());
+        }
+    }
+    let source = roots::resolve(&req.path)?;
+    if !source.is_file() {
+        eprintln!("[BRAND] Brand asset file not found: {}", req.path);
+        return Err(StatusCode::BAD_REQUEST.into());
+    }
+
+    let imported = import_asset(id, &project.cache_dir, &req.kind, &source, req.name, req.licensed, key_color)
+        .await
+        .map_err(|e| {
+            eprintln!("[BRAND] Can't add {} to project {}: {}", req.path, id, e);
+            StatusCode::BAD_REQUEST
+        })?;
+    let asset_id = db
+        .create_brand_asset(&imported.asset)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let asset = find_asset(&db, id, asset_id)?;
+    Ok(Json(AddAssetResponse { asset, warnings: imported.warnings }))
+}
+
+/// PATCH /projects/:id/brand/assets/:aid - Mark a font licensed (or not), or change the color
+/// keyed out of a logo
+async fn update_asset(
+    State(db): State<Arc<Database>>,
+    Path((project_id, asset_id)): Path<(i64, i64)>,
+    Json(req): Json<UpdateAssetRequest>,
+) -> Result<Json<BrandAsset>, StatusCode> {
+    let asset = find_asset(&db, project_id, asset_id)?;
+    let key_color = match req.key_color {
+        Some(color) if color.is_empty() => None,
+        Some(color) if color.chars().all(|c| c.is_ascii_alphanumeric() || c == '#') => Some(color),
+        Some(color) => {
+            eprintln!("[BRAND] Invalid key color {:?} for brand asset {}", color, asset_id);
+            return Err(StatusCode::BAD_REQUEST);
+        }
+        None => asset.key_color,
+    };
+    db.update_brand_asset(asset_id, req.licensed.unwrap_or(asset.licensed), key_color.as_deref())
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    find_asset(&db, project_id, asset_id).map(Json)
+}
+
+/// DELETE /projects/:id/brand/assets/:aid - Remove a logo or font; the kit stops using it
+async fn delete_asset(
+    State(db): State<Arc<Database>>,
+    Path((project_id, asset_id)): Path<(i64, i64)>,
+) -> Result<StatusCode, StatusCode> {
+    let asset = find_asset(&db, project_id, asset_id)?;
+    let mut kit = BrandKit::for_project(&db, project_id).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    kit.forget_asset(asset_id);
+    let json = serde_json::to_string(&kit).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    db.set_project_brand_kit(project_id, Some(&json))
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    db.delete_brand_asset(asset_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    // Another asset may be the same file
+    let shared = db
+        .list_brand_assets(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .iter()
+        .any(|a| a.path == asset.path);
+    if !shared {
+        let _ = std::fs::remove_file(&asset.path);
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+fn find_project(db: &Database, id: i64) -> Result<crate::db::Project, StatusCode> {
+    db.get_project(id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+fn find_asset(db: &Database, project_id: i64, asset_id: i64) -> Result<BrandAsset, StatusCode> {
+    db.get_brand_asset(asset_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .filter(|a| a.project_id == project_id)
+        .ok_or(StatusCode::NOT_FOUND)
+}
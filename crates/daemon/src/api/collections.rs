@@ -0,0 +1,225 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+    routing::{delete, get, patch, post},
+    Router,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::db::Database;
+
+#[derive(Deserialize)]
+pub struct CreateCollectionRequest {
+    name: String,
+    #[serde(default)]
+    asset_ids: Vec<i64>,
+    #[serde(default)]
+    segment_ids: Vec<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateCollectionRequest {
+    name: Option<String>,
+    #[serde(default)]
+    add_asset_ids: Vec<i64>,
+    #[serde(default)]
+    remove_asset_ids: Vec<i64>,
+    #[serde(default)]
+    add_segment_ids: Vec<i64>,
+    #[serde(default)]
+    remove_segment_ids: Vec<i64>,
+}
+
+#[derive(Serialize)]
+pub struct CollectionSummary {
+    id: i64,
+    name: String,
+    created_at: String,
+    asset_count: i64,
+    segment_count: i64,
+}
+
+#[derive(Serialize)]
+pub struct CollectionResponse {
+    id: i64,
+    name: String,
+    created_at: String,
+    /// Whole assets in the collection (all of their segments count as members)
+    asset_ids: Vec<i64>,
+    /// Individual segments added on their own
+    segment_ids: Vec<i64>,
+    /// How many analyzed segments the collection covers, which is what a scoped search sees
+    scoped_segment_count: usize,
+}
+
+pub fn router(db: Arc<Database>) -> Router {
+    Router::new()
+        .route("/:id/collections", get(list_collections))
+        .route("/:id/collections", post(create_collection))
+        .route("/:id/collections/:cid", get(get_collection))
+        .route("/:id/collections/:cid", patch(update_collection))
+        .route("/:id/collections/:cid", delete(delete_collection))
+        .with_state(db)
+}
+
+async fn list_collections(
+    State(db): State<Arc<Database>>,
+    Path(project_id): Path<i64>,
+) -> Result<Json<Vec<CollectionSummary>>, StatusCode> {
+    db.get_project(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let collections = db
+        .list_collections(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .into_iter()
+        .map(|(id, name, created_at, asset_count, segment_count)| CollectionSummary {
+            id,
+            name,
+            created_at,
+            asset_count,
+            segment_count,
+        })
+        .collect();
+
+    Ok(Json(collections))
+}
+
+/// Create a collection (bin), optionally with its first assets and segments
+async fn create_collection(
+    State(db): State<Arc<Database>>,
+    Path(project_id): Path<i64>,
+    Json(req): Json<CreateCollectionRequest>,
+) -> Result<Json<CollectionResponse>, StatusCode> {
+    db.get_project(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let name = req.name.trim();
+    if name.is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    check_members(&db, project_id, &req.asset_ids, &req.segment_ids)?;
+    if db
+        .find_collection_by_name(project_id, name)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .is_some()
+    {
+        return Err(StatusCode::CONFLICT);
+    }
+
+    let collection_id = db
+        .create_collection(project_id, name)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    db.add_collection_members(collection_id, &req.asset_ids, &req.segment_ids)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    collection_response(&db, project_id, collection_id).map(Json)
+}
+
+async fn get_collection(
+    State(db): State<Arc<Database>>,
+    Path((project_id, collection_id)): Path<(i64, i64)>,
+) -> Result<Json<CollectionResponse>, StatusCode> {
+    collection_response(&db, project_id, collection_id).map(Json)
+}
+
+/// Rename a collection and/or add and remove members
+async fn update_collection(
+    State(db): State<Arc<Database>>,
+    Path((project_id, collection_id)): Path<(i64, i64)>,
+    Json(req): Json<UpdateCollectionRequest>,
+) -> Result<Json<CollectionResponse>, StatusCode> {
+    let (_, current_name, _) = project_collection(&db, project_id, collection_id)?;
+
+    if let Some(name) = req.name.as_deref().map(str::trim) {
+        if name.is_empty() {
+            return Err(StatusCode::BAD_REQUEST);
+        }
+        let taken = db
+            .find_collection_by_name(project_id, name)
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+            .is_some_and(|id| id != collection_id);
+        if taken {
+            return Err(StatusCode::CONFLICT);
+        }
+        if name != current_name {
+            db.rename_collection(collection_id, name)
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        }
+    }
+
+    check_members(&db, project_id, &req.add_asset_ids, &req.add_segment_ids)?;
+    db.add_collection_members(collection_id, &req.add_asset_ids, &req.add_segment_ids)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    db.remove_collection_members(collection_id, &req.remove_asset_ids, &req.remove_segment_ids)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    collection_response(&db, project_id, collection_id).map(Json)
+}
+
+/// Delete a collection; its assets and segments stay in the project
+async fn delete_collection(
+    State(db): State<Arc<Database>>,
+    Path((project_id, collection_id)): Path<(i64, i64)>,
+) -> Result<StatusCode, StatusCode> {
+    project_collection(&db, project_id, collection_id)?;
+    db.delete_collection(collection_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// The collection as (project_id, name, created_at), if it belongs to the project
+pub(crate) fn project_collection(
+    db: &Database,
+    project_id: i64,
+    collection_id: i64,
+) -> Result<(i64, String, String), StatusCode> {
+    db.get_collection(collection_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .filter(|(collection_project_id, _, _)| *collection_project_id == project_id)
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+/// Segments a collection scopes a search or plan to; 404 when the collection isn't the project's
+pub(crate) fn collection_scope(db: &Database, project_id: i64, collection_id: i64) -> Result<Vec<i64>, StatusCode> {
+    project_collection(db, project_id, collection_id)?;
+    db.collection_segment_ids(collection_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// Reject asset or segment ids from other projects
+fn check_members(db: &Database, project_id: i64, asset_ids: &[i64], segment_ids: &[i64]) -> Result<(), StatusCode> {
+    let (assets, segments) = db
+        .ids_outside_project(project_id, asset_ids, segment_ids)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if !assets.is_empty() || !segments.is_empty() {
+        eprintln!(
+            "[COLLECTIONS] Rejected ids not in project {}: assets {:?}, segments {:?}",
+            project_id, assets, segments
+        );
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    Ok(())
+}
+
+fn collection_response(db: &Database, project_id: i64, collection_id: i64) -> Result<CollectionResponse, StatusCode> {
+    let (_, name, created_at) = project_collection(db, project_id, collection_id)?;
+    let (asset_ids, segment_ids) = db
+        .get_collection_members(collection_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let scoped_segment_count = collection_scope(db, project_id, collection_id)?.len();
+
+    Ok(CollectionResponse {
+        id: collection_id,
+        name,
+        created_at,
+        asset_ids,
+        segment_ids,
+        scoped_segment_count,
+    })
+}
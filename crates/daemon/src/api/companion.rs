@@ -0,0 +1,417 @@
+use axum::{
+    body::Body,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::Json,
+    routing::{delete, get, post, put},
+    Router,
+};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock};
+use tokio::io::AsyncWriteExt;
+
+use crate::api;
+use crate::db::{CompanionDevice, CompanionUpload, Database};
+use crate::jobs::{JobManager, JobStatus, JobType};
+use crate::media::companion;
+use crate::quotas::{self, Metric};
+use serde_json::json;
+
+#[derive(Serialize)]
+pub struct PairingResponse {
+    device_id: i64,
+    /// One-time token for the phone to claim; also encoded in `qr_payload`
+    pairing_token: String,
+    expires_at: String,
+    /// `vibecut://pair?server=...&token=...`, for the desktop app to show as a QR code
+    qr_payload: String,
+}
+
+#[derive(Deserialize)]
+pub struct ClaimPairingRequest {
+    pairing_token: String,
+    device_name: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct ClaimPairingResponse {
+    /// Sent as `Authorization: Bearer <token>` on every upload request
+    device_token: String,
+    device_id: i64,
+    project_id: i64,
+    project_name: String,
+}
+
+#[derive(Deserialize)]
+pub struct CreateUploadRequest {
+    filename: String,
+    size_bytes: i64,
+    /// When the photo or video was taken (RFC 3339 with the phone's offset); picks the capture session
+    captured_at: Option<String>,
+    /// Asset identifier shared by the photo and video halves of a Live Photo
+    live_photo_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ChunkQuery {
+    /// Byte offset of this chunk; must equal the bytes received so far
+    offset: i64,
+}
+
+/// Routes under /api/projects (desktop side: pairing and paired devices)
+pub fn router(db: Arc<Database>) -> Router {
+    Router::new()
+        .route("/:id/companion/pair", post(create_pairing))
+        .route("/:id/companion/devices", get(list_devices))
+        .route("/:id/companion/devices/:did", delete(delete_device))
+        .route("/:id/companion/uploads", get(list_uploads))
+        .with_state(db)
+}
+
+/// Routes under /api/companion (phone side, authenticated with the device token)
+pub fn device_router(db: Arc<Database>, job_manager: Arc<JobManager>) -> Router {
+    Router::new()
+        .route("/pair", post(claim_pairing))
+        .route("/uploads", post(create_upload))
+        .route("/uploads/:uid", get(get_upload))
+        .route("/uploads/:uid", put(upload_chunk))
+        .with_state((db, job_manager))
+}
+
+/// POST /projects/:id/companion/pair - Start pairing a phone: returns a one-time token (and the
+/// QR code payload carrying it) that the companion app claims within a few minutes
+async fn create_pairing(
+    State(db): State<Arc<Database>>,
+    Path(project_id): Path<i64>,
+    headers: HeaderMap,
+) -> Result<Json<PairingResponse>, StatusCode> {
+    db.get_project(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let pairing_token = companion::new_pairing_token();
+    let expires_at =
+        (chrono::Utc::now() + chrono::Duration::minutes(companion::PAIRING_TTL_MINUTES)).to_rfc3339();
+    let device_id = db
+        .create_companion_pairing(project_id, &companion::hash_token(&pairing_token), &expires_at)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let qr_payload = companion::qr_payload(&api_base_url(&headers), &pairing_token);
+    Ok(Json(PairingResponse { device_id, pairing_token, expires_at, qr_payload }))
+}
+
+async fn list_devices(
+    State(db): State<Arc<Database>>,
+    Path(project_id): Path<i64>,
+) -> Result<Json<Vec<CompanionDevice>>, StatusCode> {
+    db.list_companion_devices(project_id)
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// Unpair a phone (or cancel a pairing); what it uploaded stays in the project
+async fn delete_device(
+    State(db): State<Arc<Database>>,
+    Path((project_id, device_id)): Path<(i64, i64)>,
+) -> Result<StatusCode, StatusCode> {
+    let deleted = db
+        .delete_companion_device(project_id, device_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if !deleted {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn list_uploads(
+    State(db): State<Arc<Database>>,
+    Path(project_id): Path<i64>,
+) -> Result<Json<Vec<CompanionUpload>>, StatusCode> {
+    db.list_companion_uploads(project_id)
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// POST /companion/pair - Claim a pairing token scanned from the QR code for a device token
+async fn claim_pairing(
+    State((db, _job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Json(req): Json<ClaimPairingRequest>,
+) -> Result<Json<ClaimPairingResponse>, StatusCode> {
+    let device_token = companion::new_device_token();
+    let name = req.device_name.as_deref().map(str::trim).filter(|n| !n.is_empty());
+    let device = db
+        .claim_companion_pairing(
+            &companion::hash_token(req.pairing_token.trim()),
+            &companion::hash_token(&device_token),
+            name,
+        )
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or_else(|| {
+            eprintln!("[COMPANION] Pairing token is unknown, expired or already used");
+            StatusCode::UNAUTHORIZED
+        })?;
+    let project = db
+        .get_project(device.project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    eprintln!("[COMPANION] Paired device {} with project {}", device.id, device.project_id);
+    Ok(Json(ClaimPairingResponse {
+        device_token,
+        device_id: device.id,
+        project_id: device.project_id,
+        project_name: project.name,
+    }))
+}
+
+/// POST /companion/uploads - Announce a file; its bytes follow in one or more PUT chunks.
+/// 429 when the file wouldn't fit in the project's storage quota.
+async fn create_upload(
+    State((db, _job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    headers: HeaderMap,
+    Json(req): Json<CreateUploadRequest>,
+) -> Result<Json<CompanionUpload>, StatusCode> {
+    let device = authenticated_device(&db, &headers)?;
+    if !companion::is_supported_upload(&req.filename) {
+        eprintln!("[COMPANION] Unsupported file type: {}", req.filename);
+        return Err(StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
+    if req.size_bytes <= 0 {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    if let Some(captured_at) = &req.captured_at {
+        if chrono::DateTime::parse_from_rfc3339(captured_at).is_err() {
+            eprintln!("[COMPANION] captured_at isn't RFC 3339: {}", captured_at);
+            return Err(StatusCode::BAD_REQUEST);
+        }
+    }
+    let project = db
+        .get_project(device.project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    // Refuse up front rather than after the whole file has been sent
+    quotas::check(&db, device.project_id, Metric::StorageBytes, req.size_bytes as f64, None)
+        .map_err(|e| api::quotas::error_status(&e))?;
+
+    let upload_id = db
+        .create_companion_upload(
+            device.id,
+            device.project_id,
+            &req.filename,
+            req.size_bytes,
+            req.captured_at.as_deref(),
+            req.live_photo_id.as_deref(),
+        )
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let day = companion::session_day(req.captured_at.as_deref());
+    let path = companion::upload_path(std::path::Path::new(&project.cache_dir), day, upload_id, &req.filename);
+    db.set_companion_upload_path(upload_id, &path.to_string_lossy())
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await.map_err(|e| {
+            eprintln!("[COMPANION] Failed to create {}: {:?}", parent.display(), e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    }
+    tokio::fs::File::create(&path).await.map_err(|e| {
+        eprintln!("[COMPANION] Failed to create {}: {:?}", path.display(), e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    device_upload(&db, &device, upload_id).map(Json)
+}
+
+/// GET /companion/uploads/:uid - Upload progress; `received_bytes` is where to resume from
+async fn get_upload(
+    State((db, _job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path(upload_id): Path<i64>,
+    headers: HeaderMap,
+) -> Result<Json<CompanionUpload>, StatusCode> {
+    let device = authenticated_device(&db, &headers)?;
+    device_upload(&db, &device, upload_id).map(Json)
+}
+
+/// One lock per upload in progress, held by a chunk from its offset check until its bytes are
+/// recorded, so a retried chunk racing the original can't append at the same offset
+static UPLOAD_LOCKS: OnceLock<Mutex<HashMap<i64, Arc<tokio::sync::Mutex<()>>>>> = OnceLock::new();
+
+fn upload_locks() -> &'static Mutex<HashMap<i64, Arc<tokio::sync::Mutex<()>>>> {
+    UPLOAD_LOCKS.get_or_init(Default::default)
+}
+
+/// PUT /companion/uploads/:uid?offset=N - Append a chunk of the file. A chunk at the wrong
+/// offset gets 409 (read `received_bytes` and resume from there). The upload is imported once
+/// its last byte arrives; if the storage quota no longer has room, it's marked failed and the
+/// chunk gets 429.
+async fn upload_chunk(
+    State((db, job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path(upload_id): Path<i64>,
+    Query(query): Query<ChunkQuery>,
+    headers: HeaderMap,
+    body: Body,
+) -> Result<Json<CompanionUpload>, StatusCode> {
+    let device = authenticated_device(&db, &headers)?;
+    device_upload(&db, &device, upload_id)?;
+    let lock = upload_locks().lock().unwrap().entry(upload_id).or_default().clone();
+    let _guard = lock.lock().await;
+    // Read again under the lock: the chunk before may have finished the upload
+    let upload = device_upload(&db, &device, upload_id)?;
+    if upload.status != "uploading" {
+        return Err(StatusCode::CONFLICT);
+    }
+    let path = PathBuf::from(&upload.path);
+    let received = tokio::fs::metadata(&path).await.map(|m| m.len() as i64).unwrap_or(0);
+    if query.offset != received {
+        eprintln!(
+            "[COMPANION] Upload {} chunk at offset {}, but {} bytes were received",
+            upload_id, query.offset, received
+        );
+        db.set_companion_upload_received(upload_id, received)
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        return Err(StatusCode::CONFLICT);
+    }
+
+    let mut file = tokio::fs::OpenOptions::new()
+        .append(true)
+        .open(&path)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let mut written = received;
+    let mut stream = body.into_data_stream();
+    while let Some(chunk) = stream.next().await {
+        // A dropped connection keeps what arrived; the phone resumes from received_bytes
+        let Ok(chunk) = chunk else { break };
+        if written + chunk.len() as i64 > upload.size_bytes {
+            eprintln!("[COMPANION] Upload {} is larger than its announced {} bytes", upload_id, upload.size_bytes);
+            file.set_len(received as u64).await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+            return Err(StatusCode::PAYLOAD_TOO_LARGE);
+        }
+        file.write_all(&chunk).await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        written += chunk.len() as i64;
+    }
+    file.flush().await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    db.set_companion_upload_received(upload_id, written)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    if written == upload.size_bytes {
+        let claimed = db
+            .claim_companion_upload_ingest(upload_id)
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        upload_locks().lock().unwrap().remove(&upload_id);
+        if claimed {
+            start_ingest(db.clone(), job_manager, upload_id, upload.project_id, path)
+                .map_err(|e| api::quotas::error_status(&e))?;
+        }
+    }
+
+    device_upload(&db, &device, upload_id).map(Json)
+}
+
+/// Import a finished upload in the background as an ImportRaw job: convert it to an editing
+/// format, import it, and add it to the capture session for its day
+fn start_ingest(
+    db: Arc<Database>,
+    job_manager: Arc<JobManager>,
+    upload_id: i64,
+    project_id: i64,
+    original: PathBuf,
+) -> anyhow::Result<i64> {
+    let job_payload = json!({
+        "project_id": project_id,
+        "file_path": original.to_string_lossy(),
+        "companion_upload_id": upload_id,
+    });
+    let job_id = match job_manager.create_job(JobType::ImportRaw, Some(job_payload), None) {
+        Ok(job_id) => job_id,
+        Err(e) => {
+            // E.g. over the storage quota; the upload won't be imported, so don't leave it processing
+            eprintln!("[COMPANION] Couldn't queue the import of upload {}: {}", upload_id, e);
+            db.set_companion_upload_status(upload_id, "failed", None, Some(&e.to_string()))?;
+            return Err(e);
+        }
+    };
+    db.set_companion_upload_status(upload_id, "processing", Some(job_id), None)?;
+
+    tokio::spawn(async move {
+        if let Err(e) = ingest_upload(db.clone(), job_manager.clone(), job_id, upload_id, project_id, &original).await {
+            eprintln!("[COMPANION] Import of upload {} failed: {:?}", upload_id, e);
+            let _ = db.set_companion_upload_status(upload_id, "failed", None, Some(&e.to_string()));
+            let _ = job_manager.fail_job(job_id, &e.to_string());
+        }
+    });
+
+    Ok(job_id)
+}
+
+async fn ingest_upload(
+    db: Arc<Database>,
+    job_manager: Arc<JobManager>,
+    job_id: i64,
+    upload_id: i64,
+    project_id: i64,
+    original: &std::path::Path,
+) -> anyhow::Result<()> {
+    job_manager.update_job_status(job_id, JobStatus::Running, Some(0.0))?;
+    let upload = db
+        .get_companion_upload(upload_id)?
+        .ok_or_else(|| anyhow::anyhow!("Upload {} not found", upload_id))?;
+
+    let editing_path = companion::prepare_for_editing(original).await?;
+    let asset_id =
+        super::media::process_single_video(db.clone(), job_manager.clone(), job_id, project_id, &editing_path, 0, 1)
+            .await?;
+
+    let session = companion::session_name(companion::session_day(upload.captured_at.as_deref()));
+    let collection_id = match db.find_collection_by_name(project_id, &session)? {
+        Some(id) => id,
+        None => db.create_collection(project_id, &session)?,
+    };
+    db.add_collection_members(collection_id, &[asset_id], &[])?;
+    db.set_companion_upload_imported(upload_id, asset_id, collection_id)?;
+
+    eprintln!(
+        "[COMPANION] Upload {} imported as asset {} into '{}'",
+        upload_id, asset_id, session
+    );
+    job_manager.update_job_status(job_id, JobStatus::Completed, Some(1.0))?;
+    Ok(())
+}
+
+/// The paired device whose token is in the Authorization header
+fn authenticated_device(db: &Database, headers: &HeaderMap) -> Result<CompanionDevice, StatusCode> {
+    let token = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(str::trim)
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+    db.companion_device_for_token(&companion::hash_token(token))
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::UNAUTHORIZED)
+}
+
+/// An upload sent by this device (404 for anyone else's)
+fn device_upload(db: &Database, device: &CompanionDevice, upload_id: i64) -> Result<CompanionUpload, StatusCode> {
+    db.get_companion_upload(upload_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .filter(|upload| upload.device_id == device.id)
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+/// Base URL the phone reaches the API at: COMPANION_PUBLIC_URL when set (e.g. behind a proxy),
+/// otherwise the host the pairing request came in on
+fn api_base_url(headers: &HeaderMap) -> String {
+    if let Ok(url) = std::env::var("COMPANION_PUBLIC_URL") {
+        if !url.trim().is_empty() {
+            return url.trim().trim_end_matches('/').to_string();
+        }
+    }
+    let host = headers
+        .get(header::HOST)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("127.0.0.1:7777");
+    format!("http://{}/api", host)
+}
@@ -0,0 +1,182 @@
+use axum::{
+    body::Body,
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::{Json, Response},
+    routing::{get, post},
+    Router,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::api::library;
+use crate::crypto;
+use crate::db::Database;
+use crate::jobs::transcribe::transcription_audio_path;
+use crate::storage;
+
+#[derive(Deserialize)]
+pub struct PurgeRequest {
+    /// The project name, typed back to confirm
+    confirm: String,
+}
+
+#[derive(Serialize)]
+pub struct PurgeResponse {
+    /// Rows deleted per table
+    deleted: BTreeMap<String, usize>,
+    /// Proxies, thumbnails and extracted audio removed from the cache
+    files_deleted: usize,
+    /// Whether a re-check found no derived rows or files left and every original media file
+    /// still in place
+    verified: bool,
+    /// Derived rows still present per table (empty when verified)
+    remaining: BTreeMap<String, usize>,
+    /// Cache files that couldn't be removed
+    remaining_files: Vec<String>,
+}
+
+pub fn router(db: Arc<Database>) -> Router {
+    Router::new()
+        .route("/:id/data_export", get(data_export))
+        .route("/:id/purge", post(purge))
+        .with_state(db)
+}
+
+/// GET /projects/:id/data_export - Download everything stored about the project as one JSON
+/// document (423 while an encrypted project is locked, since transcripts are included decrypted)
+async fn data_export(
+    State(db): State<Arc<Database>>,
+    Path(project_id): Path<i64>,
+) -> Result<Response, StatusCode> {
+    let export = db
+        .project_data_export(project_id)
+        .map_err(|e| {
+            if crypto::is_locked_error(&e) {
+                return StatusCode::LOCKED;
+            }
+            eprintln!("[DATA] Failed to export project {}: {:?}", project_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let body = serde_json::to_vec_pretty(&export).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "application/json")
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"project_{}_data.json\"", project_id),
+        )
+        .body(Body::from(body))
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// POST /projects/:id/purge - Delete all data derived from the project's media (segments,
+/// transcripts, vision results, embeddings, redactions, feedback, LLM call logs, proxies,
+/// thumbnails, extracted audio and cached renders), then check that none of it is left. Original media, the
+/// asset records, timelines and conversations are kept, so the media can be analyzed again.
+async fn purge(
+    State(db): State<Arc<Database>>,
+    Path(project_id): Path<i64>,
+    Json(req): Json<PurgeRequest>,
+) -> Result<Json<PurgeResponse>, StatusCode> {
+    let project = db
+        .get_project(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    if req.confirm != project.name {
+        eprintln!("[DATA] Purge of project {} not confirmed with its name", project_id);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    // Projects using its assets from the library plan with their segments
+    library::check_not_shared(&db, project_id)?;
+
+    let assets = db
+        .get_media_assets_for_project(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let originals: Vec<PathBuf> = assets.iter().map(|a| PathBuf::from(&a.path)).collect();
+    let originals_present: Vec<bool> = originals.iter().map(|p| p.exists()).collect();
+
+    // Collect cache files before their DB records go away
+    let mut files = Vec::new();
+    let mut thumbnail_dirs = Vec::new();
+    for asset in &assets {
+        let proxies = db.list_proxies(asset.id).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        files.extend(proxies.into_iter().map(|(_, path, _, _)| PathBuf::from(path)));
+        if let Some(dir) = db.get_thumbnail_dir(asset.id).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)? {
+            let dir = PathBuf::from(dir);
+            if let Ok(entries) = std::fs::read_dir(&dir) {
+                files.extend(entries.filter_map(|entry| entry.ok().map(|e| e.path())));
+            }
+            thumbnail_dirs.push(dir);
+        }
+        files.push(transcription_audio_path(asset.id));
+    }
+    // Compiled render graphs and clip intermediates from exports
+    let render_cache = PathBuf::from(&project.cache_dir).join("render_cache");
+    if let Ok(entries) = std::fs::read_dir(&render_cache) {
+        files.extend(entries.filter_map(|entry| entry.ok().map(|e| e.path())));
+    }
+    // Never touch the original media, whatever a record points at
+    files.retain(|path| !originals.iter().any(|original| same_file(original, path)));
+    thumbnail_dirs.retain(|dir| !originals.iter().any(|original| original.starts_with(dir)));
+
+    let deleted = db.purge_project_derived_data(project_id).map_err(|e| {
+        eprintln!("[DATA] Failed to purge project {}: {:?}", project_id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let mut files_deleted = 0;
+    for path in &files {
+        if let Err(e) = storage::remove(&db, path).await {
+            eprintln!("[DATA] Failed to delete stored copy of {}: {:?}", path.display(), e);
+        }
+        if path.exists() && std::fs::remove_file(path).is_ok() {
+            files_deleted += 1;
+        }
+    }
+    for dir in &thumbnail_dirs {
+        let _ = std::fs::remove_dir(dir);
+    }
+
+    // Verify
+    let remaining: BTreeMap<String, usize> = db
+        .remaining_derived_data(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .into_iter()
+        .collect();
+    let remaining_files: Vec<String> = files
+        .iter()
+        .filter(|path| path.exists())
+        .map(|path| path.to_string_lossy().to_string())
+        .collect();
+    let originals_intact = originals
+        .iter()
+        .zip(&originals_present)
+        .all(|(path, present)| !present || path.exists());
+    let verified = remaining.is_empty() && remaining_files.is_empty() && originals_intact;
+    if !verified {
+        eprintln!(
+            "[DATA] Purge of project {} incomplete: {:?} rows, {} files left",
+            project_id, remaining, remaining_files.len()
+        );
+    }
+
+    eprintln!("[DATA] Purged project {} ({} files)", project_id, files_deleted);
+    Ok(Json(PurgeResponse {
+        deleted: deleted.into_iter().collect(),
+        files_deleted,
+        verified,
+        remaining,
+        remaining_files,
+    }))
+}
+
+fn same_file(a: &std::path::Path, b: &std::path::Path) -> bool {
+    match (std::fs::canonicalize(a), std::fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
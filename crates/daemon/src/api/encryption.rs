@@ -0,0 +1,171 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+    routing::{get, post},
+    Router,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::api::library;
+use crate::crypto;
+use crate::db::Database;
+
+/// Shortest passphrase accepted when enabling encryption
+const MIN_PASSPHRASE_LEN: usize = 8;
+
+#[derive(Deserialize)]
+pub struct PassphraseRequest {
+    passphrase: String,
+}
+
+#[derive(Serialize)]
+pub struct EncryptionStatus {
+    encrypted: bool,
+    /// Whether the key is in memory, so encrypted data can be read and written
+    unlocked: bool,
+}
+
+#[derive(Serialize)]
+pub struct EnableEncryptionResponse {
+    #[serde(flatten)]
+    status: EncryptionStatus,
+    /// Transcript and vision results encrypted in the database
+    encrypted_fields: usize,
+    /// Proxies and thumbnails encrypted on disk
+    encrypted_files: usize,
+}
+
+pub fn router(db: Arc<Database>) -> Router {
+    Router::new()
+        .route("/:id/encryption", get(get_encryption).post(enable_encryption))
+        .route("/:id/encryption/unlock", post(unlock))
+        .route("/:id/encryption/lock", post(lock))
+        .with_state(db)
+}
+
+async fn get_encryption(
+    State(db): State<Arc<Database>>,
+    Path(project_id): Path<i64>,
+) -> Result<Json<EncryptionStatus>, StatusCode> {
+    find_project(&db, project_id)?;
+    Ok(Json(status(&db, project_id)?))
+}
+
+/// POST /projects/:id/encryption - Encrypt the project's transcripts, vision results, proxies and
+/// thumbnails with a key derived from the passphrase. The passphrase can't be recovered or
+/// changed; losing it means regenerating that data from the source media.
+async fn enable_encryption(
+    State(db): State<Arc<Database>>,
+    Path(project_id): Path<i64>,
+    Json(req): Json<PassphraseRequest>,
+) -> Result<Json<EnableEncryptionResponse>, StatusCode> {
+    find_project(&db, project_id)?;
+    if db
+        .get_project_encryption(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .is_some()
+    {
+        return Err(StatusCode::CONFLICT);
+    }
+    // Projects using its assets from the library couldn't read them any more
+    library::check_not_shared(&db, project_id)?;
+    if req.passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+        eprintln!("[ENCRYPTION] Passphrase must have at least {} characters", MIN_PASSPHRASE_LEN);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let salt = crypto::generate_salt().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let key = derive(req.passphrase, salt.clone()).await?;
+    let check = crypto::key_check(&key).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    db.set_project_encryption(project_id, &hex::encode(&salt), &check)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    db.unlock_project(project_id, key);
+
+    let encrypted_fields = db
+        .encrypt_project_fields(project_id, &key)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let assets = db
+        .get_media_assets_for_project(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let task_db = db.clone();
+    let encrypted_files = tokio::task::spawn_blocking(move || {
+        assets.iter().try_fold(0, |total, asset| {
+            Ok::<_, anyhow::Error>(total + crypto::encrypt_asset_files(&task_db, asset.id, &key)?)
+        })
+    })
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+    .map_err(|e| {
+        eprintln!("[ENCRYPTION] Failed to encrypt files of project {}: {:?}", project_id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    eprintln!(
+        "[ENCRYPTION] Encrypted project {} ({} fields, {} files)",
+        project_id, encrypted_fields, encrypted_files
+    );
+    Ok(Json(EnableEncryptionResponse {
+        status: status(&db, project_id)?,
+        encrypted_fields,
+        encrypted_files,
+    }))
+}
+
+/// POST /projects/:id/encryption/unlock - Keep the project key in memory until the daemon stops
+/// (or the project is locked again)
+async fn unlock(
+    State(db): State<Arc<Database>>,
+    Path(project_id): Path<i64>,
+    Json(req): Json<PassphraseRequest>,
+) -> Result<Json<EncryptionStatus>, StatusCode> {
+    find_project(&db, project_id)?;
+    let (salt, check) = db
+        .get_project_encryption(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::CONFLICT)?;
+    let salt = hex::decode(salt).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let key = derive(req.passphrase, salt).await?;
+    if !crypto::verify_key(&key, &check) {
+        eprintln!("[ENCRYPTION] Wrong passphrase for project {}", project_id);
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+    db.unlock_project(project_id, key);
+    Ok(Json(status(&db, project_id)?))
+}
+
+async fn lock(
+    State(db): State<Arc<Database>>,
+    Path(project_id): Path<i64>,
+) -> Result<Json<EncryptionStatus>, StatusCode> {
+    find_project(&db, project_id)?;
+    db.lock_project(project_id);
+    Ok(Json(status(&db, project_id)?))
+}
+
+/// Key derivation is deliberately slow, so it runs off the async workers
+async fn derive(passphrase: String, salt: Vec<u8>) -> Result<crypto::Key, StatusCode> {
+    tokio::task::spawn_blocking(move || crypto::derive_key(&passphrase, &salt))
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+fn status(db: &Database, project_id: i64) -> Result<EncryptionStatus, StatusCode> {
+    let encrypted = db
+        .get_project_encryption(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .is_some();
+    Ok(EncryptionStatus {
+        encrypted,
+        unlocked: encrypted && db.is_project_unlocked(project_id),
+    })
+}
+
+fn find_project(db: &Database, project_id: i64) -> Result<(), StatusCode> {
+    db.get_project(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    Ok(())
+}
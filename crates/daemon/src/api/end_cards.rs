@@ -0,0 +1,176 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+    routing::get,
+    Router,
+};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::api::media::PathError;
+use crate::db::Database;
+use crate::end_cards::{self, ProjectEndCard};
+use engine::end_card::EndCard;
+
+#[derive(Serialize)]
+pub struct EndCardResponse {
+    id: i64,
+    /// Set for built-in end cards (which can't be deleted)
+    builtin_key: Option<String>,
+    created_at: String,
+    card: EndCard,
+}
+
+/// Routes for the end card library (mounted under /end_cards)
+pub fn router(db: Arc<Database>) -> Router {
+    Router::new()
+        .route("/", get(list_end_cards).post(create_end_card))
+        .route("/:id", get(get_end_card).delete(delete_end_card))
+        .with_state(db)
+}
+
+/// Routes for the end card a project's exports finish with (merged under /projects)
+pub fn project_router(db: Arc<Database>) -> Router {
+    Router::new()
+        .route(
+            "/:id/end_card",
+            get(get_project_end_card).put(set_project_end_card).delete(delete_project_end_card),
+        )
+        .with_state(db)
+}
+
+async fn list_end_cards(
+    State(db): State<Arc<Database>>,
+) -> Result<Json<Vec<EndCardResponse>>, StatusCode> {
+    let cards = db
+        .list_end_cards()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .into_iter()
+        .filter_map(|(id, builtin_key, json, created_at)| match serde_json::from_str(&json) {
+            Ok(card) => Some(EndCardResponse { id, builtin_key, created_at, card }),
+            Err(e) => {
+                eprintln!("[END_CARDS] Skipping unreadable end card {}: {:?}", id, e);
+                None
+            }
+        })
+        .collect();
+
+    Ok(Json(cards))
+}
+
+async fn get_end_card(
+    State(db): State<Arc<Database>>,
+    Path(id): Path<i64>,
+) -> Result<Json<EndCardResponse>, StatusCode> {
+    let (builtin_key, json, created_at) = db
+        .get_end_card(id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let card = serde_json::from_str(&json).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(EndCardResponse { id, builtin_key, created_at, card }))
+}
+
+/// Add an end card to the library (background color or clip, with text and images over it).
+/// Paths without placeholders must be inside the media roots; the rest are checked once a
+/// project fills them in.
+async fn create_end_card(
+    State(db): State<Arc<Database>>,
+    Json(card): Json<EndCard>,
+) -> Result<Json<EndCardResponse>, PathError> {
+    if let Err(message) = card.validate() {
+        eprintln!("[END_CARDS] Rejected end card: {}", message);
+        return Err(StatusCode::BAD_REQUEST.into());
+    }
+    end_cards::resolve_files(&mut card.fill(&HashMap::new()))?;
+
+    let json = serde_json::to_string(&card).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let id = db
+        .create_end_card(&card.name, &json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(EndCardResponse {
+        id,
+        builtin_key: None,
+        created_at: chrono::Utc::now().to_rfc3339(),
+        card,
+    }))
+}
+
+/// Delete a user-created end card
+async fn delete_end_card(
+    State(db): State<Arc<Database>>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, StatusCode> {
+    let (builtin_key, _, _) = db
+        .get_end_card(id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    if builtin_key.is_some() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    db.delete_end_card(id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn get_project_end_card(
+    State(db): State<Arc<Database>>,
+    Path(id): Path<i64>,
+) -> Result<Json<Option<ProjectEndCard>>, StatusCode> {
+    db.get_project(id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    ProjectEndCard::for_project(&db, id)
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// Finish the project's exports with an end card from the library, filling its placeholders
+/// with `values` (e.g. {"instagram": "@me", "logo": "/path/logo.png"}). Every path the filled
+/// card reads must be inside the media roots.
+async fn set_project_end_card(
+    State(db): State<Arc<Database>>,
+    Path(id): Path<i64>,
+    Json(settings): Json<ProjectEndCard>,
+) -> Result<Json<ProjectEndCard>, PathError> {
+    db.get_project(id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    if db
+        .get_end_card(settings.end_card_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .is_none()
+    {
+        eprintln!("[END_CARDS] Project {} asked for missing end card {}", id, settings.end_card_id);
+        return Err(StatusCode::BAD_REQUEST.into());
+    }
+    let mut card = end_cards::filled(&db, id, &settings).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    end_cards::resolve_files(&mut card)?;
+
+    let json = serde_json::to_string(&settings).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    db.set_project_end_card(id, Some(&json))
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(settings))
+}
+
+/// Stop appending an end card to the project's exports
+async fn delete_project_end_card(
+    State(db): State<Arc<Database>>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, StatusCode> {
+    db.get_project(id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    db.set_project_end_card(id, None)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
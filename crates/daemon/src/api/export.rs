@@ -1,8 +1,9 @@
 use axum::{
+    body::Body,
     extract::{Path, State},
-    http::StatusCode,
-    response::Json,
-    routing::post,
+    http::{header, StatusCode},
+    response::{IntoResponse, Json, Response},
+    routing::{get, post},
     Router,
 };
 use serde::{Deserialize, Serialize};
@@ -10,72 +11,922 @@ use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
 
+use crate::brand::Brand;
+use crate::crypto;
 use crate::db::Database;
-use crate::jobs::{JobManager, JobType};
-use engine::render::generate_render_commands;
-use engine::timeline::Timeline;
-use serde_json::json;
+use crate::end_cards;
+use crate::jobs::{redaction, JobManager, JobStatus, JobType};
+use crate::media::availability::{self, OriginalsOffline, RequiredOriginal};
+use crate::media::conform::{self, ConformFailed, ConformReport};
+use crate::media::process::{self, Failure, ProcessFailed, Tool};
+use crate::media::roots::{self, PathNotAllowed};
+use crate::music::{self, MusicCredit};
+use crate::quotas::QuotaExceeded;
+use crate::resolve::{self, ResolveBundle};
+use crate::storage;
+use crate::uploads;
+use engine::incremental::{plan_stitch, render_spans, span_timeline, ticks_to_seconds, RenderSpan, StitchPiece, StitchPlan};
+use engine::render::{
+    captions_srt, chapters_ffmetadata, generate_render_commands, generate_stitch_command, output_duration_seconds,
+    AudioOptions, BlurRegion, BrandingOptions, ClipRender, MuxOptions, RenderCommand, SourceMedia,
+};
+use engine::render_cache::{self, content_hash};
+use engine::frame_rate::{clip_frame_rates, ClipFrameRate};
+use engine::encode::{EncoderOverrides, EncoderSettings, SizeEstimate, EXPORT_PRESETS};
+use engine::timeline::{AudioEnhancement, Timeline};
+use serde_json::{json, Value};
 
 #[derive(Deserialize)]
 pub struct ExportRequest {
-    preset: Option<String>,
     out_path: String,
+    /// Noise reduction and voice enhancement for every clip that doesn't set its own
+    enhance_audio: Option<AudioEnhancement>,
+    #[serde(flatten)]
+    encoding: EncodingOptions,
+    #[serde(flatten)]
+    embed: EmbedOptions,
+}
+
+/// Export preset and per-export changes to its encoder settings
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct EncodingOptions {
+    /// Built-in preset (see `engine::encode::EXPORT_PRESETS`; default "mp4")
+    pub preset: Option<String>,
+    pub encoder: EncoderOverrides,
+    /// Re-render only what changed since the project's last export and copy the rest from it
+    pub incremental: bool,
+}
+
+impl EncodingOptions {
+    /// The preset's settings with the overrides applied, checked against the output container
+    pub fn settings(&self, out_path: Option<&str>) -> Result<EncoderSettings, String> {
+        let name = self.preset.as_deref().unwrap_or("mp4");
+        let preset = EncoderSettings::for_preset(name).ok_or_else(|| {
+            format!("Unknown export preset '{}' (expected one of {})", name, EXPORT_PRESETS.join(", "))
+        })?;
+        let settings = preset.with_overrides(&self.encoder);
+        let container = out_path
+            .and_then(|p| std::path::Path::new(p).extension())
+            .map(|e| e.to_string_lossy().to_lowercase());
+        settings.validate(container.as_deref())?;
+        Ok(settings)
+    }
+}
+
+#[derive(Deserialize)]
+pub struct EstimateRequest {
+    /// Where the export would go; its extension is checked against the codec
+    out_path: Option<String>,
+    #[serde(flatten)]
+    encoding: EncodingOptions,
+}
+
+#[derive(Serialize)]
+pub struct EstimateResponse {
+    preset: String,
+    encoder: EncoderSettings,
+    #[serde(flatten)]
+    estimate: SizeEstimate,
+}
+
+/// Chapters, caption track, languages, tags, watermark and end card added to an export
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct EmbedOptions {
+    /// Embed chapter atoms from labelled markers (or segment summaries), as for publishing
+    pub chapters: bool,
+    /// Mux the timeline's captions as a soft subtitle track
+    pub captions: bool,
+    /// ISO 639-2 language of the audio (e.g. "eng")
+    pub language: Option<String>,
+    /// ISO 639-2 language of the captions (default: `language`)
+    pub caption_language: Option<String>,
+    /// Extra container tags; they win over the generated project, version and edit-plan tags
+    pub metadata: HashMap<String, String>,
+    /// Append the project's end card, when it has one
+    pub end_card: bool,
+    /// Lay the brand kit's watermark over the program, when it has one
+    pub watermark: bool,
+}
+
+impl Default for EmbedOptions {
+    fn default() -> Self {
+        Self {
+            chapters: true,
+            captions: true,
+            language: None,
+            caption_language: None,
+            metadata: HashMap::new(),
+            end_card: true,
+            watermark: true,
+        }
+    }
+}
+
+impl EmbedOptions {
+    pub fn validate(&self) -> Result<(), String> {
+        for language in self.language.iter().chain(&self.caption_language) {
+            if language.len() != 3 || !language.chars().all(|c| c.is_ascii_lowercase()) {
+                return Err(format!("Language must be an ISO 639-2 code like \"eng\", not {:?}", language));
+            }
+        }
+        for key in self.metadata.keys() {
+            if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.')) {
+                return Err(format!("Metadata keys may only use letters, digits, '_', '-' and '.', not {:?}", key));
+            }
+        }
+        Ok(())
+    }
 }
 
 #[derive(Serialize)]
 pub struct ExportResponse {
     job_id: i64,
+    /// Expected size of the finished file (see `POST /projects/:id/export/estimate`)
+    estimated_size_bytes: Option<u64>,
+    /// Clips whose frame-rate conversion will look bad
+    frame_rate_warnings: Vec<ClipFrameRate>,
+    /// Music on the timeline with no license on record in the music catalog
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    unlicensed_music: Vec<String>,
+    /// For incremental exports, the export built on and the output ranges (in seconds) re-rendered
+    #[serde(skip_serializing_if = "Option::is_none")]
+    incremental: Option<Value>,
+}
+
+#[derive(Serialize)]
+pub struct PresetResponse {
+    name: String,
+    encoder: EncoderSettings,
+}
+
+/// Export failures: a plain status, originals that need relinking first (409 with the list), or
+/// originals that don't conform to what was edited (422 with the conform report)
+pub enum ExportError {
+    Status(StatusCode),
+    OriginalsOffline(Vec<RequiredOriginal>),
+    ConformFailed(ConformReport),
+    QuotaExceeded(QuotaExceeded),
+    PathNotAllowed(PathNotAllowed),
+}
+
+impl From<StatusCode> for ExportError {
+    fn from(status: StatusCode) -> Self {
+        ExportError::Status(status)
+    }
+}
+
+impl From<PathNotAllowed> for ExportError {
+    fn from(refused: PathNotAllowed) -> Self {
+        eprintln!("[ROOTS] {}", refused);
+        ExportError::PathNotAllowed(refused)
+    }
+}
+
+impl IntoResponse for ExportError {
+    fn into_response(self) -> Response {
+        match self {
+            ExportError::Status(status) => status.into_response(),
+            ExportError::OriginalsOffline(required) => (
+                StatusCode::CONFLICT,
+                Json(json!({
+                    "error": "originals_offline",
+                    "message": OriginalsOffline(required.clone()).to_string(),
+                    "required_originals": required,
+                    "relink": "POST /api/projects/:id/media/:asset_id/relink",
+                })),
+            )
+                .into_response(),
+            ExportError::ConformFailed(report) => (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Json(json!({
+                    "error": "conform_failed",
+                    "message": ConformFailed(report.clone()).to_string(),
+                    "report": report,
+                })),
+            )
+                .into_response(),
+            ExportError::QuotaExceeded(exceeded) => exceeded.into_response(),
+            ExportError::PathNotAllowed(refused) => refused.into_response(),
+        }
+    }
+}
+
+impl From<anyhow::Error> for ExportError {
+    fn from(e: anyhow::Error) -> Self {
+        match e.downcast::<OriginalsOffline>() {
+            Ok(OriginalsOffline(required)) => ExportError::OriginalsOffline(required),
+            Err(e) => match e.downcast::<ConformFailed>() {
+                Ok(ConformFailed(report)) => ExportError::ConformFailed(report),
+                Err(e) => match e.downcast::<QuotaExceeded>() {
+                    Ok(exceeded) => ExportError::QuotaExceeded(exceeded),
+                    // An output path (or another argument) the sandbox won't run
+                    Err(e) if e.downcast_ref::<ProcessFailed>().is_some_and(|f| matches!(f.failure, Failure::Rejected(_))) => {
+                        eprintln!("[EXPORT] {}", e);
+                        ExportError::Status(StatusCode::BAD_REQUEST)
+                    }
+                    Err(e) => {
+                        eprintln!("[EXPORT] {:?}", e);
+                        ExportError::Status(StatusCode::INTERNAL_SERVER_ERROR)
+                    }
+                },
+            },
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ResolveExportRequest {
+    /// Where to write the bundle (default: `<cache_dir>/resolve`)
+    out_dir: Option<String>,
+    /// Also run the push script against a running Resolve (default true)
+    push: Option<bool>,
+}
+
+#[derive(Serialize)]
+pub struct ResolveExportResponse {
+    #[serde(flatten)]
+    bundle: ResolveBundle,
+    pushed: bool,
+    /// What the push script printed, or why the push failed (the bundle can still be pushed by
+    /// running the script by hand)
+    push_output: Option<String>,
 }
 
 pub fn router(db: Arc<Database>, job_manager: Arc<JobManager>) -> Router {
     Router::new()
         .route("/:id/export", post(export))
+        .route("/:id/export/estimate", post(estimate_export))
+        .route("/:id/export/resolve", post(export_to_resolve))
+        .route("/:id/exports/:eid/file", get(download_export))
         .with_state((db, job_manager))
 }
 
+/// Routes for the built-in export presets (mounted under /export)
+pub fn presets_router() -> Router {
+    Router::new().route("/presets", get(list_presets))
+}
+
+/// GET /export/presets - Built-in export presets and their encoder settings
+async fn list_presets() -> Json<Vec<PresetResponse>> {
+    Json(
+        EXPORT_PRESETS
+            .iter()
+            .filter_map(|name| {
+                EncoderSettings::for_preset(name).map(|encoder| PresetResponse {
+                    name: name.to_string(),
+                    encoder,
+                })
+            })
+            .collect(),
+    )
+}
+
 async fn export(
     State((db, job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
     Path(project_id): Path<i64>,
     Json(req): Json<ExportRequest>,
-) -> Result<Json<ExportResponse>, StatusCode> {
-    // Load timeline
+) -> Result<Json<ExportResponse>, ExportError> {
+    if let Some(enhance_audio) = &req.enhance_audio {
+        if let Err(e) = enhance_audio.validate() {
+            eprintln!("[EXPORT] Invalid audio enhancement for project {}: {}", project_id, e);
+            return Err(StatusCode::BAD_REQUEST.into());
+        }
+    }
+    if let Err(e) = req.embed.validate() {
+        eprintln!("[EXPORT] Invalid embed options for project {}: {}", project_id, e);
+        return Err(StatusCode::BAD_REQUEST.into());
+    }
+    if let Err(e) = req.encoding.settings(Some(&req.out_path)) {
+        eprintln!("[EXPORT] Invalid encoder settings for project {}: {}", project_id, e);
+        return Err(StatusCode::BAD_REQUEST.into());
+    }
+    let out_path = roots::resolve(&req.out_path)?;
+    let job_id = create_export_job(&db, &job_manager, project_id, &out_path.to_string_lossy(), &req.encoding, req.enhance_audio, &req.embed)
+        .await?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    // TODO: Spawn async task to execute FFmpeg command
+    // For V1, just return job_id - execution can be added later
+
+    let payload = job_manager.get_job(job_id)?.and_then(|job| job.payload);
+    let estimated_size_bytes = payload
+        .as_ref()
+        .and_then(|payload| payload.get("estimated_size_bytes").and_then(|b| b.as_u64()));
+    let incremental = payload
+        .as_ref()
+        .map(|payload| payload["incremental"].clone())
+        .filter(|incremental| !incremental.is_null());
+    let unlicensed_music = payload
+        .as_ref()
+        .and_then(|payload| serde_json::from_value::<Vec<Value>>(payload["music_credits"].clone()).ok())
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|credit| credit["license"].is_null())
+        .filter_map(|credit| credit["track_path"].as_str().map(str::to_string))
+        .collect();
+    let frame_rate_warnings = payload
+        .and_then(|payload| serde_json::from_value::<Vec<ClipFrameRate>>(payload["frame_rate_conversions"].clone()).ok())
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|c| c.warning.is_some())
+        .collect();
+
+    Ok(Json(ExportResponse { job_id, estimated_size_bytes, frame_rate_warnings, unlicensed_music, incremental }))
+}
+
+/// POST /projects/:id/export/estimate - Resolve the preset and overrides into encoder settings
+/// and estimate the export's size, without rendering anything
+async fn estimate_export(
+    State((db, _job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path(project_id): Path<i64>,
+    Json(req): Json<EstimateRequest>,
+) -> Result<Json<EstimateResponse>, StatusCode> {
+    let encoder = req.encoding.settings(req.out_path.as_deref()).map_err(|e| {
+        eprintln!("[EXPORT] Invalid encoder settings for project {}: {}", project_id, e);
+        StatusCode::BAD_REQUEST
+    })?;
     let timeline_json = db
         .get_timeline(project_id)
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
         .ok_or(StatusCode::NOT_FOUND)?;
-    
-    let timeline: Timeline = serde_json::from_str(&timeline_json)
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-
-    // Get proxy paths for all asset IDs in timeline
-    let mut proxy_paths = HashMap::new();
-    for track in &timeline.tracks {
-        for clip in &track.clips {
-            if !proxy_paths.contains_key(&clip.asset_id) {
-                if let Ok(Some(path)) = db.get_proxy_path(clip.asset_id) {
-                    proxy_paths.insert(clip.asset_id, path);
-                }
+    let timeline: Timeline = serde_json::from_str(&timeline_json).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(EstimateResponse {
+        preset: req.encoding.preset.unwrap_or_else(|| "mp4".to_string()),
+        estimate: encoder.estimate(&timeline),
+        encoder,
+    }))
+}
+
+/// GET /projects/:id/exports/:eid/file - Download an export's video. With a storage backend the
+/// file is stored on first request and the client is redirected to a signed URL.
+async fn download_export(
+    State((db, job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path((project_id, export_job_id)): Path<(i64, i64)>,
+) -> Result<Response, StatusCode> {
+    let job = job_manager
+        .get_job(export_job_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .filter(|job| matches!(job.job_type, JobType::Export))
+        .ok_or(StatusCode::NOT_FOUND)?;
+    if matches!(job.status, JobStatus::Failed | JobStatus::Cancelled) {
+        return Err(StatusCode::CONFLICT);
+    }
+    let out_path = job
+        .payload
+        .as_ref()
+        .and_then(|p| p.get("out_path"))
+        .and_then(|p| p.as_str())
+        .map(PathBuf::from)
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let file_size = std::fs::metadata(&out_path).map(|m| m.len()).unwrap_or(0);
+    if file_size == 0 {
+        eprintln!("[EXPORT] Export file {:?} is missing or empty", out_path);
+        return Err(StatusCode::NOT_FOUND);
+    }
+    let file_name = out_path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| format!("export_{}.mp4", export_job_id));
+
+    if storage::backend().is_some() {
+        let stored = db
+            .get_stored_object_key(&out_path.to_string_lossy())
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+            .is_some();
+        if !stored {
+            let object_name = format!("exports/project_{}/job_{}/{}", project_id, export_job_id, file_name);
+            if let Err(e) = storage::store(&db, &out_path, &object_name, "video/mp4").await {
+                eprintln!("[STORAGE] Failed to store export {}: {:?}", export_job_id, e);
             }
         }
+        if let Some(redirect) = super::media::storage_redirect(&db, &out_path)? {
+            return Ok(redirect);
+        }
     }
 
-    // Generate render command
-    let output_path = PathBuf::from(&req.out_path);
-    let render_cmd = generate_render_commands(&timeline, output_path.clone(), &proxy_paths);
+    let file = tokio::fs::File::open(&out_path)
+        .await
+        .map_err(|_| StatusCode::NOT_FOUND)?;
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "video/mp4")
+        .header(header::CONTENT_LENGTH, file_size.to_string())
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}\"", file_name.replace('"', "")),
+        )
+        .body(Body::from_stream(tokio_util::io::ReaderStream::new(file)))
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// POST /projects/:id/export/resolve - Write the timeline (OTIO), media bin manifest and push
+/// script for DaVinci Resolve, then push it into a running Resolve unless `push` is false
+async fn export_to_resolve(
+    State((db, _job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path(project_id): Path<i64>,
+    Json(req): Json<ResolveExportRequest>,
+) -> Result<Json<ResolveExportResponse>, ExportError> {
+    let project = db
+        .get_project(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let timeline_json = db
+        .get_timeline(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    // Resolve conforms against the originals, so they have to be reachable
+    let timeline: Timeline = serde_json::from_str(&timeline_json).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let required = availability::required_originals(&db, project_id, &timeline)?;
+    if !required.is_empty() {
+        return Err(ExportError::OriginalsOffline(required));
+    }
+
+    let out_dir = match req.out_dir {
+        Some(out_dir) => roots::resolve(&out_dir)?,
+        None => resolve::default_out_dir(&project.cache_dir),
+    };
+    let bundle = resolve::write_bundle(&db, project_id, &out_dir).map_err(|e| {
+        eprintln!("[RESOLVE] Failed to write bundle: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let (pushed, push_output) = if req.push.unwrap_or(true) {
+        match resolve::push(&bundle).await {
+            Ok(output) => (true, Some(output)),
+            Err(e) => {
+                eprintln!("[RESOLVE] {:?}", e);
+                (false, Some(e.to_string()))
+            }
+        }
+    } else {
+        (false, None)
+    };
+
+    Ok(Json(ResolveExportResponse { bundle, pushed, push_output }))
+}
+
+/// Render the project's timeline into an Export job; None when the project has no timeline.
+/// Fails with OriginalsOffline when the timeline uses originals that are offline or archived,
+/// and with ConformFailed when an original doesn't match the proxy or import it was edited from.
+pub async fn create_export_job(
+    db: &Database,
+    job_manager: &JobManager,
+    project_id: i64,
+    out_path: &str,
+    encoding: &EncodingOptions,
+    enhance_audio: Option<AudioEnhancement>,
+    embed: &EmbedOptions,
+) -> anyhow::Result<Option<i64>> {
+    let encoder = encoding.settings(Some(out_path)).map_err(anyhow::Error::msg)?;
+
+    // Load timeline
+    let Some(timeline_json) = db.get_timeline(project_id)? else {
+        return Ok(None);
+    };
+    let timeline: Timeline = serde_json::from_str(&timeline_json)?;
+
+    // Exports are final renders; don't start one while originals it uses are unplugged
+    let required = availability::required_originals(db, project_id, &timeline)?;
+    if !required.is_empty() {
+        return Err(OriginalsOffline(required).into());
+    }
+
+    // Map the proxies the edit was made with back to the originals, checking they still match
+    let (sources, conform) = conform::conform_timeline(db, &timeline).await?;
+
+    // Footage at other frame rates is converted with the project's (or the clip's) policy
+    let source_fps: HashMap<i64, f64> = sources.iter().filter_map(|(id, s)| Some((*id, s.fps?))).collect();
+    let frame_rates = clip_frame_rates(&timeline, &source_fps);
+    for warning in frame_rates.iter().filter_map(|c| c.warning.as_ref().map(|w| (&c.clip_id, w))) {
+        eprintln!("[EXPORT] Clip {} of project {}: {}", warning.0, project_id, warning.1);
+    }
+
+    // Blur the faces and plates that are marked for redaction
+    let asset_ids: Vec<i64> = timeline.tracks.iter().flat_map(|t| t.clips.iter().map(|c| c.asset_id)).collect();
+    let blur_regions = redaction::blur_regions(db, project_id, &asset_ids)?;
+
+    // Clean up dialog where the export or a clip asks for it, with RNNoise when a model is configured
+    let audio = AudioOptions {
+        enhance: enhance_audio,
+        rnnoise_model: std::env::var("RNNOISE_MODEL").ok().filter(|m| !m.is_empty()),
+    };
+    let enhanced_clips = timeline
+        .tracks
+        .iter()
+        .flat_map(|t| t.clips.iter())
+        .filter(|c| c.audio_enhancement.as_ref().or(audio.enhance.as_ref()).is_some_and(|e| e.enabled))
+        .count();
+
+    // Music is credited in the file's tags; tracks the catalog has no license for are reported
+    let music_credits = music::credits(db, &timeline)?;
+    for credit in music_credits.iter().filter(|c| !c.licensed()) {
+        eprintln!("[EXPORT] Project {}: no license on record for music {}", project_id, credit.track_path);
+    }
+
+    let mux = mux_options(db, project_id, &timeline, out_path, embed, &music_credits)?;
+
+    // The brand kit's watermark is laid over the program, and the project's end card
+    // (subscribe call to action, handles) plays after it
+    let branding = BrandingOptions {
+        watermark: if embed.watermark { Brand::for_project(db, project_id)?.watermark() } else { None },
+        end_card: if embed.end_card { end_cards::for_export(db, project_id)? } else { None },
+    };
+
+    // Keyed caches of compiled graphs, clip intermediates and span renders
+    let inputs = RenderInputs {
+        timeline: &timeline,
+        sources: &sources,
+        blur_regions: &blur_regions,
+        audio: &audio,
+        encoder: &encoder,
+        cache_dir: PathBuf::from(&project_cache_dir(db, project_id)?).join("render_cache"),
+        clip_cache: clip_cache_enabled(db, project_id)?,
+    };
+    let spans = render_spans(&timeline, &sources, &blur_regions, &audio, &encoder);
+    // Incremental exports copy whatever the last export already rendered. Narration, music and
+    // titles play across span boundaries, and the spans don't carry the watermark or cover an end
+    // card, so exports with any of them are rendered whole.
+    let has_beds = !timeline.narration.is_empty() || !timeline.music.is_empty() || !timeline.titles.is_empty();
+    let branded = branding.watermark.is_some() || branding.end_card.is_some();
+    let previous = if encoding.incremental && !has_beds && !branded {
+        previous_render(job_manager, project_id, out_path)?
+    } else {
+        None
+    };
+    let (render_cmd, graph_key, graph_cached, stitch) = match &previous {
+        Some(previous) => {
+            let stitch = inputs.stitch(previous, &spans, out_path, &mux)?;
+            (stitch.command.clone(), None, false, Some(stitch))
+        }
+        None => {
+            let (render_cmd, graph_key, graph_cached) = inputs.compile(&timeline, out_path, &mux, &branding)?;
+            (render_cmd, Some(graph_key), graph_cached, None)
+        }
+    };
+    // Clip intermediates already in the cache are reused; only new or changed clips are encoded
+    let clip_renders: Vec<&ClipRender> = render_cmd.clip_renders.iter().filter(|r| !r.path.exists()).collect();
+    match &stitch {
+        Some(stitch) => eprintln!(
+            "[EXPORT] Project {}: stitching from export {}, {:.1}s reused, {:.1}s in {} span(s) to render",
+            project_id,
+            stitch.previous_job_id,
+            ticks_to_seconds(stitch.plan.reused_ticks),
+            ticks_to_seconds(stitch.plan.rendered_ticks),
+            stitch.span_renders.len()
+        ),
+        None => eprintln!(
+            "[EXPORT] Project {}: render graph {} ({}), {} of {} clip(s) to render",
+            project_id,
+            graph_key.as_deref().unwrap_or_default(),
+            if graph_cached { "cached" } else { "compiled" },
+            clip_renders.len(),
+            render_cmd.clip_renders.len()
+        ),
+    }
+    let estimate = encoder.estimate(&timeline);
+    // Workers run these passes as they are, so they're held to the same rules as local runs
+    check_render_passes(&render_cmd, &clip_renders, stitch.as_ref())?;
 
     // Create export job with render command
     let job_payload = json!({
-        "preset": req.preset,
-        "out_path": req.out_path,
+        "project_id": project_id,
+        "preset": encoding.preset.as_deref().unwrap_or("mp4"),
+        "encoder": encoder,
+        "estimated_size_bytes": estimate.size_bytes,
+        "out_path": out_path,
+        "clip_renders": clip_renders,
+        "span_renders": stitch.as_ref().map(|s| &s.span_renders),
+        "concat_list": stitch.as_ref().map(|s| &s.concat_list),
+        "concat_list_path": stitch.as_ref().map(|s| &s.command.concat_list_path),
+        "first_pass_args": render_cmd.first_pass_args,
         "ffmpeg_args": render_cmd.ffmpeg_args,
+        "render_spans": spans,
+        "span_keyframes": render_cmd.span_keyframes,
+        "render_cache": {
+            "graph_key": graph_key,
+            "graph_cached": graph_cached,
+            "clips_cached": render_cmd.clip_renders.len() - clip_renders.len(),
+            "clips_rendered": clip_renders.len(),
+        },
+        "incremental": stitch.as_ref().map(|s| s.summary()),
+        "conform": conform,
+        "frame_rate_conversions": frame_rates,
+        "redacted_regions": blur_regions.len(),
+        "enhance_audio": audio.enhance,
+        "audio_enhanced_clips": enhanced_clips,
+        "chapters_path": mux.chapters_path,
+        "captions_path": mux.captions_path,
+        "metadata": mux.metadata.iter().cloned().collect::<HashMap<_, _>>(),
+        "music_credits": music_credits,
+        "watermark": branding.watermark,
+        "end_card": branding.end_card,
     });
 
-    let job_id = job_manager
-        .create_job(JobType::Export, Some(job_payload), None)
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Some(job_manager.create_job(JobType::Export, Some(job_payload), None)?))
+}
 
-    // TODO: Spawn async task to execute FFmpeg command
-    // For V1, just return job_id - execution can be added later
+/// Everything an export's picture and sound are rendered from
+struct RenderInputs<'a> {
+    timeline: &'a Timeline,
+    sources: &'a HashMap<i64, SourceMedia>,
+    blur_regions: &'a [BlurRegion],
+    audio: &'a AudioOptions,
+    encoder: &'a EncoderSettings,
+    /// Where compiled graphs, clip intermediates and span renders are kept
+    cache_dir: PathBuf,
+    /// Whether primary clips go through cached intermediates
+    clip_cache: bool,
+}
+
+/// The last export of the project, which an incremental export copies unchanged spans from
+struct PreviousRender {
+    job_id: i64,
+    output: String,
+    spans: Vec<RenderSpan>,
+}
+
+/// Every ffmpeg pass of an export must pass the sandbox's argument checks
+fn check_render_passes(render_cmd: &RenderCommand, clip_renders: &[&ClipRender], stitch: Option<&Stitch>) -> anyhow::Result<()> {
+    let mut passes: Vec<Vec<String>> = vec![render_cmd.ffmpeg_args.clone()];
+    passes.extend(render_cmd.first_pass_args.clone());
+    passes.extend(clip_renders.iter().map(|r| r.ffmpeg_args.clone()));
+    for span in stitch.iter().flat_map(|s| s.span_renders.iter()) {
+        for key in ["first_pass_args", "ffmpeg_args"] {
+            if let Some(args) = span[key].as_array() {
+                passes.push(args.iter().filter_map(|a| a.as_str().map(String::from)).collect());
+            }
+        }
+    }
+    for args in &passes {
+        process::check_args(Tool::Ffmpeg, args)?;
+    }
+    Ok(())
+}
+
+/// An incremental export: the stitch command, and what has to be rendered before it
+struct Stitch {
+    command: RenderCommand,
+    plan: StitchPlan,
+    previous_job_id: i64,
+    /// Spans to render into the cache first (`path`, `first_pass_args`, `ffmpeg_args`)
+    span_renders: Vec<Value>,
+    /// Entries of the ffconcat file, in output order (`path`, with `inpoint`/`outpoint` for
+    /// stretches of the previous export)
+    concat_list: Vec<Value>,
+}
+
+impl Stitch {
+    fn summary(&self) -> Value {
+        let seconds = |ticks| (ticks_to_seconds(ticks) * 1000.0).round() / 1000.0;
+        json!({
+            "previous_job_id": self.previous_job_id,
+            "changed_ranges": self.plan.changed_ranges.iter().map(|(s, e)| [seconds(*s), seconds(*e)]).collect::<Vec<_>>(),
+            "reused_seconds": seconds(self.plan.reused_ticks),
+            "rendered_seconds": seconds(self.plan.rendered_ticks),
+            "spans_rendered": self.span_renders.len(),
+        })
+    }
+}
+
+impl RenderInputs<'_> {
+    /// Compile the render graph of `timeline` (with its watermark and end card), or reuse the one compiled
+    /// from identical inputs. Returns it with its key and whether it came from the cache.
+    fn compile(
+        &self,
+        timeline: &Timeline,
+        out_path: &str,
+        mux: &MuxOptions,
+        branding: &BrandingOptions,
+    ) -> anyhow::Result<(RenderCommand, String, bool)> {
+        let graph_key = content_hash(&json!({
+            "timeline": timeline,
+            "out_path": out_path,
+            "sources": self.sources,
+            "blur_regions": self.blur_regions,
+            "audio": self.audio,
+            "mux": mux,
+            "branding": branding,
+            "encoder": self.encoder,
+            "clip_cache": self.clip_cache,
+        }));
+        let graph_path = render_cache::graph_path(&self.cache_dir, &graph_key);
+        let cached_graph: Option<RenderCommand> = std::fs::read_to_string(&graph_path)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok());
+        if let Some(render_cmd) = cached_graph {
+            return Ok((render_cmd, graph_key, true));
+        }
+        // Generate render command
+        let render_cmd = generate_render_commands(
+            timeline,
+            PathBuf::from(out_path),
+            self.sources,
+            self.blur_regions,
+            self.audio,
+            mux,
+            branding,
+            self.encoder,
+            self.clip_cache.then_some(self.cache_dir.as_path()),
+        );
+        std::fs::create_dir_all(&self.cache_dir)?;
+        std::fs::write(&graph_path, serde_json::to_string(&render_cmd)?)?;
+        Ok((render_cmd, graph_key, false))
+    }
+
+    /// Build the export out of the previous one: spans it rendered are copied out of its
+    /// output, the others are rendered on their own (or taken from the span cache) and
+    /// everything is joined without re-encoding
+    fn stitch(&self, previous: &PreviousRender, spans: &[RenderSpan], out_path: &str, mux: &MuxOptions) -> anyhow::Result<Stitch> {
+        let plan = plan_stitch(&previous.spans, spans);
+        let extension = std::path::Path::new(out_path)
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_else(|| "mp4".to_string());
+        let mut clip_renders: Vec<ClipRender> = Vec::new();
+        let mut span_renders: Vec<Value> = Vec::new();
+        let mut concat_list = Vec::new();
+        for piece in &plan.pieces {
+            match piece {
+                StitchPiece::Previous { start_ticks, end_ticks } => concat_list.push(json!({
+                    "path": previous.output,
+                    "inpoint": ticks_to_seconds(*start_ticks),
+                    "outpoint": ticks_to_seconds(*end_ticks),
+                })),
+                StitchPiece::Render { span } => {
+                    let path = render_cache::span_render_path(&self.cache_dir, &span.key, &extension);
+                    let path_str = path.to_string_lossy().to_string();
+                    let queued = span_renders.iter().any(|r| r["path"] == path_str);
+                    if !path.exists() && !queued {
+                        let span_timeline = span_timeline(self.timeline, spans, span);
+                        let (render_cmd, _, _) = self.compile(&span_timeline, &path_str, &MuxOptions::default(), &BrandingOptions::default())?;
+                        for render in render_cmd.clip_renders {
+                            if !clip_renders.iter().any(|r| r.key == render.key) {
+                                clip_renders.push(render);
+                            }
+                        }
+                        span_renders.push(json!({
+                            "key": span.key,
+                            "path": path_str,
+                            "first_pass_args": render_cmd.first_pass_args,
+                            "ffmpeg_args": render_cmd.ffmpeg_args,
+                        }));
+                    }
+                    concat_list.push(json!({ "path": path_str }));
+                }
+            }
+        }
+
+        // The list is written with the daemon's paths; workers write their own copy
+        let list_path = self.cache_dir.join(format!("stitch_{}.ffconcat", content_hash(&concat_list)));
+        std::fs::create_dir_all(&self.cache_dir)?;
+        std::fs::write(&list_path, ffconcat(&concat_list))?;
+        let mut command = generate_stitch_command(list_path, PathBuf::from(out_path), mux);
+        command.clip_renders = clip_renders;
+        Ok(Stitch {
+            command,
+            plan,
+            previous_job_id: previous.job_id,
+            span_renders,
+            concat_list,
+        })
+    }
+}
+
+/// An ffconcat file listing `entries` (see `Stitch::concat_list`)
+fn ffconcat(entries: &[Value]) -> String {
+    let mut list = String::from("ffconcat version 1.0\n");
+    for entry in entries {
+        list.push_str(&format!("file '{}'\n", entry["path"].as_str().unwrap_or_default().replace('\'', "'\\''")));
+        for key in ["inpoint", "outpoint"] {
+            if let Some(seconds) = entry[key].as_f64() {
+                list.push_str(&format!("{} {:.6}\n", key, seconds));
+            }
+        }
+    }
+    list
+}
+
+/// The project's last completed export, when an incremental export can build on it: it
+/// recorded its spans, was rendered with a keyframe at each of their boundaries, wrote the same
+/// container, and its file is still there as rendered
+fn previous_render(job_manager: &JobManager, project_id: i64, out_path: &str) -> anyhow::Result<Option<PreviousRender>> {
+    let Some(job) = job_manager.latest_completed_job(&JobType::Export, project_id)? else {
+        return Ok(None);
+    };
+    let Some(payload) = job.payload else {
+        return Ok(None);
+    };
+    let Some(output) = payload["out_path"].as_str().map(String::from) else {
+        return Ok(None);
+    };
+    let Ok(spans) = serde_json::from_value::<Vec<RenderSpan>>(payload["render_spans"].clone()) else {
+        return Ok(None);
+    };
+    let extension = |path: &str| std::path::Path::new(path).extension().map(|e| e.to_string_lossy().to_lowercase());
+    let rendered_bytes = payload["result"]["size_bytes"].as_u64();
+    let current_bytes = std::fs::metadata(&output).ok().map(|m| m.len());
+    let span_keyframes = payload["span_keyframes"].as_bool() == Some(true);
+    if !span_keyframes
+        || extension(&output) != extension(out_path)
+        || rendered_bytes.is_none()
+        || rendered_bytes != current_bytes
+    {
+        eprintln!("[EXPORT] Export {} of project {} can't be stitched from; rendering in full", job.id, project_id);
+        return Ok(None);
+    }
+    Ok(Some(PreviousRender { job_id: job.id, output, spans }))
+}
+
+fn project_cache_dir(db: &Database, project_id: i64) -> anyhow::Result<String> {
+    Ok(db
+        .get_project(project_id)?
+        .ok_or_else(|| anyhow::anyhow!("Project {} not found", project_id))?
+        .cache_dir)
+}
+
+/// Whether primary clips go through cached intermediates: off with RENDER_CACHE=off, or when
+/// the project's renders mustn't be cached
+fn clip_cache_enabled(db: &Database, project_id: i64) -> anyhow::Result<bool> {
+    let disabled = std::env::var("RENDER_CACHE").is_ok_and(|v| v == "off");
+    Ok(!disabled && crypto::caching_allowed(db, project_id)?)
+}
+
+/// Write the export's chapter and caption files next to the project cache and gather its tags:
+/// the project, the timeline version and the edit plan it came from, credits for its music, then
+/// the caller's own
+fn mux_options(
+    db: &Database,
+    project_id: i64,
+    timeline: &Timeline,
+    out_path: &str,
+    embed: &EmbedOptions,
+    music_credits: &[MusicCredit],
+) -> anyhow::Result<MuxOptions> {
+    let project = db
+        .get_project(project_id)?
+        .ok_or_else(|| anyhow::anyhow!("Project {} not found", project_id))?;
+    let stem = std::path::Path::new(out_path)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "export".to_string());
+    let exports_dir = PathBuf::from(&project.cache_dir).join("exports");
+
+    let chapters: Vec<(f64, String)> = if embed.chapters {
+        uploads::chapters(db, project_id, timeline)?
+            .into_iter()
+            .map(|c| (c.start_seconds, c.title))
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let chapters_path = if chapters.is_empty() {
+        None
+    } else {
+        let path = exports_dir.join(format!("{}.chapters.txt", stem));
+        std::fs::create_dir_all(&exports_dir)?;
+        std::fs::write(&path, chapters_ffmetadata(&chapters, output_duration_seconds(timeline)))?;
+        Some(path.to_string_lossy().to_string())
+    };
+    let captions_path = if embed.captions && !timeline.captions.is_empty() {
+        let path = exports_dir.join(format!("{}.srt", stem));
+        std::fs::create_dir_all(&exports_dir)?;
+        std::fs::write(&path, captions_srt(&timeline.captions))?;
+        Some(path.to_string_lossy().to_string())
+    } else {
+        None
+    };
+
+    let mut metadata = vec![
+        ("title".to_string(), project.name.clone()),
+        ("vibecut_project".to_string(), project.name),
+        ("vibecut_project_id".to_string(), project_id.to_string()),
+        ("vibecut_version".to_string(), env!("CARGO_PKG_VERSION").to_string()),
+    ];
+    if let Some(version_id) = db.get_current_timeline_version_id(project_id)? {
+        metadata.push(("vibecut_timeline_version".to_string(), version_id));
+    }
+    if let Some(plan_id) = db.get_latest_edit_plan_id(project_id)? {
+        metadata.push(("vibecut_edit_plan_id".to_string(), plan_id.to_string()));
+    }
+    if !music_credits.is_empty() {
+        let lines: Vec<String> = music_credits.iter().map(MusicCredit::line).collect();
+        metadata.push(("vibecut_music_credits".to_string(), lines.join("; ")));
+    }
+    let mut custom: Vec<(&String, &String)> = embed.metadata.iter().collect();
+    custom.sort();
+    for (key, value) in custom {
+        metadata.retain(|(k, _)| k != key);
+        metadata.push((key.clone(), value.clone()));
+    }
 
-    Ok(Json(ExportResponse { job_id }))
+    Ok(MuxOptions {
+        chapters_path,
+        captions_path,
+        audio_language: embed.language.clone(),
+        caption_language: embed.caption_language.clone().or_else(|| embed.language.clone()),
+        metadata,
+    })
 }
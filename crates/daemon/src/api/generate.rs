@@ -1,17 +1,25 @@
 use axum::{
     extract::{Path, State},
     http::StatusCode,
-    response::Json,
+    response::{IntoResponse, Json, Response},
     routing::post,
     Router,
 };
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
-use crate::db::Database;
-use crate::planner::generate_edit_plan;
-use engine::compiler::{compile_edit_plan, EditConstraints};
-use engine::timeline::{ProjectSettings, Resolution, TICKS_PER_SECOND};
+use crate::brand;
+use crate::api::collections::collection_scope;
+use crate::db::{Database, MediaAssetInfo};
+use crate::lower_thirds;
+use crate::media::still;
+use crate::music::{self, FollowedMusic};
+use crate::planner::interview::generate_interview_plan;
+use crate::planner::{generate_edit_plan, PlanMode};
+use crate::style::presets::find_preset;
+use crate::templates;
+use engine::compiler::{compile_edit_plan, ConstraintConflict, EditConstraints, PlanSuggestion, SectionConstraints, SlowMotionMode};
+use engine::timeline::{FrameRateConversion, ProjectSettings, Resolution, TICKS_PER_SECOND};
 use serde_json;
 
 #[derive(Deserialize)]
@@ -20,11 +28,62 @@ pub struct GenerateRequest {
     vibe: Option<String>,
     captions_on: Option<bool>,
     music_on: Option<bool>,
+    /// Per-section pins, e.g. {"section_type": "intro", "max_duration": 240000}
+    #[serde(default)]
+    section_overrides: Vec<SectionConstraints>,
+    /// Pre/post-roll around planned clips, in ticks (default 200ms each)
+    handle_pre_ticks: Option<i64>,
+    handle_post_ticks: Option<i64>,
+    /// Slow down high-motion scenery shot at 120fps and up ("auto"), only suggest it
+    /// ("suggest", the default) or leave it alone ("off")
+    #[serde(default)]
+    slow_motion: SlowMotionMode,
+    /// Only plan with this collection's assets and segments
+    collection_id: Option<i64>,
+    /// Catalog track to cut the edit to (default: the best analyzed match for the vibe)
+    music_track_id: Option<i64>,
+    /// Cut section changes and impact cuts to a music track's structure (default: whether a
+    /// music_track_id is given)
+    align_to_music: Option<bool>,
+    /// "interview" cuts the answers to the project's detected interview questions together by
+    /// topic; "standard" (the default) plans an intro, body and outro
+    #[serde(default)]
+    mode: PlanMode,
 }
 
 #[derive(Serialize)]
 pub struct GenerateResponse {
     job_id: i64,
+    /// Changes the planner left to the user (e.g. clips that could play in slow motion)
+    suggestions: Vec<PlanSuggestion>,
+}
+
+/// Generate failures: a plain status, or section pins that can't be satisfied (422 with details)
+pub enum GenerateError {
+    Status(StatusCode),
+    Conflicts(Vec<ConstraintConflict>),
+}
+
+impl From<StatusCode> for GenerateError {
+    fn from(status: StatusCode) -> Self {
+        GenerateError::Status(status)
+    }
+}
+
+impl IntoResponse for GenerateError {
+    fn into_response(self) -> Response {
+        match self {
+            GenerateError::Status(status) => status.into_response(),
+            GenerateError::Conflicts(conflicts) => (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Json(serde_json::json!({
+                    "error": "constraint_conflict",
+                    "conflicts": conflicts,
+                })),
+            )
+                .into_response(),
+        }
+    }
 }
 
 pub fn router(db: Arc<Database>) -> Router {
@@ -33,59 +92,170 @@ pub fn router(db: Arc<Database>) -> Router {
         .with_state(db)
 }
 
+/// Style profile guiding the planner: a vibe naming a built-in preset ("cinematic",
+/// "tutorial", ...) selects that style; otherwise the project's own (reference-derived) profile
+pub(crate) fn load_style_profile(
+    db: &Database,
+    project_style_profile_id: Option<i64>,
+    vibe: Option<&str>,
+) -> Result<Option<serde_json::Value>, StatusCode> {
+    let preset_profile = match vibe.and_then(find_preset) {
+        Some(preset) => db
+            .get_style_preset(preset.key)
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+            .map(|(_, json)| json),
+        None => None,
+    };
+
+    Ok(match (preset_profile, project_style_profile_id) {
+        (Some(json), _) => serde_json::from_str(&json).ok(),
+        (None, Some(profile_id)) => db
+            .get_style_profile(profile_id)
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+            .and_then(|json| serde_json::from_str(&json).ok()),
+        (None, None) => None,
+    })
+}
+
+/// With `align`, the music the edit is cut to (see [`music::music_to_follow`]) and the style
+/// profile with its sections as the beat template. A requested track that isn't in the catalog
+/// or hasn't been analyzed is a bad request.
+pub(crate) fn follow_music(
+    db: &Database,
+    track_id: Option<i64>,
+    align: bool,
+    constraints: &mut EditConstraints,
+    style_profile: Option<serde_json::Value>,
+) -> Result<(Option<serde_json::Value>, Option<FollowedMusic>), StatusCode> {
+    if !align {
+        return Ok((style_profile, None));
+    }
+    let length_ticks = constraints.target_length.unwrap_or(60 * TICKS_PER_SECOND);
+    let followed = music::music_to_follow(db, track_id, constraints.vibe.as_deref(), style_profile.as_ref(), length_ticks)
+        .map_err(|e| {
+            eprintln!("[MUSIC] Can't cut to music: {:?}", e);
+            StatusCode::BAD_REQUEST
+        })?;
+    match followed {
+        Some(followed) => {
+            eprintln!(
+                "[MUSIC] Cutting to \"{}\" ({} section(s))",
+                followed.track.title,
+                followed.structure.sections.len()
+            );
+            Ok((Some(followed.apply(constraints, style_profile)), Some(followed)))
+        }
+        None => Ok((style_profile, None)),
+    }
+}
+
+/// Timeline settings matching an asset's frame rate and resolution
+pub(crate) fn project_settings(asset: &MediaAssetInfo) -> ProjectSettings {
+    ProjectSettings {
+        fps: asset.fps_num as f64 / asset.fps_den as f64,
+        resolution: Resolution {
+            width: asset.width,
+            height: asset.height,
+        },
+        sample_rate: 48000,
+        ticks_per_second: TICKS_PER_SECOND,
+        frame_rate_conversion: FrameRateConversion::default(),
+    }
+}
+
 async fn generate(
     State(db): State<Arc<Database>>,
     Path(project_id): Path<i64>,
     Json(req): Json<GenerateRequest>,
-) -> Result<Json<GenerateResponse>, StatusCode> {
+) -> Result<Json<GenerateResponse>, GenerateError> {
     // Verify project exists
-    let _project = db
+    let project = db
         .get_project(project_id)
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
         .ok_or(StatusCode::NOT_FOUND)?;
 
     // Load segments for project
-    let segments_with_assets = db
+    let mut segments_with_assets = db
         .get_segments_for_project(project_id)
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if let Some(collection_id) = req.collection_id {
+        let scope = collection_scope(&db, project_id, collection_id)?;
+        segments_with_assets.retain(|(segment, _)| scope.contains(&segment.id));
+    }
 
     if segments_with_assets.is_empty() {
-        return Err(StatusCode::BAD_REQUEST);
+        return Err(StatusCode::BAD_REQUEST.into());
     }
 
     // Create constraints
-    let constraints = EditConstraints {
+    let mut constraints = EditConstraints {
         target_length: req.target_length,
         vibe: req.vibe,
         captions_on: req.captions_on.unwrap_or(true),
         music_on: req.music_on.unwrap_or(true),
+        section_overrides: req.section_overrides,
+        handle_pre_ticks: req.handle_pre_ticks,
+        handle_post_ticks: req.handle_post_ticks,
+        slow_motion: req.slow_motion,
     };
 
+    // A project template fills in what the request left open and sets the edit's sections
+    let template = templates::project_template(&db, project_id).map_err(|e| {
+        eprintln!("[TEMPLATES] Failed to load template for project {}: {:?}", project_id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    if let Some(template) = &template {
+        template.apply_to_constraints(&mut constraints);
+    }
+    let mut style_profile = load_style_profile(&db, project.style_profile_id, constraints.vibe.as_deref())?;
+    if let Some(template) = &template {
+        style_profile = Some(template.apply_to_style_profile(style_profile));
+    }
+    let align_to_music = req.align_to_music.unwrap_or(req.music_track_id.is_some());
+    let (style_profile, followed) =
+        follow_music(&db, req.music_track_id, align_to_music, &mut constraints, style_profile)?;
+
     // Generate edit plan
-    let plan = generate_edit_plan(&segments_with_assets, constraints);
+    let mut plan = match req.mode {
+        PlanMode::Standard => generate_edit_plan(&segments_with_assets, constraints, style_profile.as_ref()),
+        PlanMode::Interview => {
+            let questions = db
+                .list_interview_questions(project_id)
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+            generate_interview_plan(&segments_with_assets, &questions, constraints)
+        }
+    }
+    .map_err(GenerateError::Conflicts)?;
+    if let Some(template) = &template {
+        template.decorate_plan(&mut plan, &segments_with_assets, style_profile.as_ref());
+    }
+    if let Err(e) = music::add_catalog_music(&db, &mut plan, style_profile.as_ref(), followed.as_ref()) {
+        eprintln!("[MUSIC] Failed to pick music for project {}: {:?}", project_id, e);
+    }
+    if let Err(e) = brand::style_captions(&db, project_id, &mut plan) {
+        eprintln!("[BRAND] Failed to style captions for project {}: {:?}", project_id, e);
+    }
 
     // Create project settings from first media asset
-    let first_asset = &segments_with_assets[0].1;
-    let fps = first_asset.fps_num as f64 / first_asset.fps_den as f64;
-    let settings = ProjectSettings {
-        fps,
-        resolution: Resolution {
-            width: first_asset.width,
-            height: first_asset.height,
-        },
-        sample_rate: 48000,
-        ticks_per_second: TICKS_PER_SECOND,
-    };
+    let settings = project_settings(&segments_with_assets[0].1);
 
     // Compile to timeline
-    let timeline = compile_edit_plan(plan, settings);
+    let suggestions = plan.suggestions.clone();
+    let mut timeline = compile_edit_plan(plan, settings);
+    if let Err(e) = still::apply_auto_ken_burns(&db, project_id, &mut timeline) {
+        eprintln!("[STILLS] Failed to add Ken Burns motion: {:?}", e);
+    }
 
     // Serialize and store timeline
     let timeline_json = serde_json::to_string(&timeline)
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     db.store_timeline(project_id, &timeline_json)
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    // Named people get lower thirds suggested where they first speak in the new cut
+    if let Err(e) = lower_thirds::suggest(&db, project_id) {
+        eprintln!("[LOWER_THIRDS] Failed to suggest lower thirds for project {}: {:?}", project_id, e);
+    }
 
     // Return success (for now, synchronous. Can make async with job later)
-    Ok(Json(GenerateResponse { job_id: 0 }))
+    Ok(Json(GenerateResponse { job_id: 0, suggestions }))
 }
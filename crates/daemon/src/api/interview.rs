@@ -0,0 +1,64 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+    routing::{get, post},
+    Router,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::db::{Database, InterviewQuestion};
+use crate::interview::{self, ListedQuestion};
+
+#[derive(Deserialize)]
+pub struct DetectQuestionsRequest {
+    /// Questions the interviews were meant to cover, in order, optionally under topics; the
+    /// questions asked on camera are matched to them
+    #[serde(default)]
+    questions: Vec<ListedQuestion>,
+}
+
+pub fn router(db: Arc<Database>) -> Router {
+    Router::new()
+        .route("/:id/interview", get(list_questions))
+        .route("/:id/interview/detect", post(detect_questions))
+        .with_state(db)
+}
+
+/// GET /projects/:id/interview - Questions asked in the project's interviews and the segments
+/// answering them
+async fn list_questions(
+    State(db): State<Arc<Database>>,
+    Path(project_id): Path<i64>,
+) -> Result<Json<Vec<InterviewQuestion>>, StatusCode> {
+    find_project(&db, project_id)?;
+    db.list_interview_questions(project_id)
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// POST /projects/:id/interview/detect - Find the questions and answers in the project's
+/// interviews, replacing what was found before
+async fn detect_questions(
+    State(db): State<Arc<Database>>,
+    Path(project_id): Path<i64>,
+    Json(req): Json<DetectQuestionsRequest>,
+) -> Result<Json<Vec<InterviewQuestion>>, StatusCode> {
+    find_project(&db, project_id)?;
+    if req.questions.iter().any(|q| q.text.trim().is_empty()) {
+        eprintln!("[INTERVIEW] Listed questions need text");
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    interview::detect(&db, project_id, &req.questions).map(Json).map_err(|e| {
+        eprintln!("[INTERVIEW] Failed to detect questions for project {}: {:?}", project_id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })
+}
+
+fn find_project(db: &Database, project_id: i64) -> Result<(), StatusCode> {
+    db.get_project(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)
+        .map(|_| ())
+}
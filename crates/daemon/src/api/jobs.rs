@@ -2,13 +2,15 @@ use axum::{
     extract::{Path, State},
     http::StatusCode,
     response::Json,
-    routing::{get, post},
+    routing::{get, post, put},
     Router,
 };
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
-use crate::jobs::JobManager;
+use crate::db::{Database, JobWorker};
+use crate::jobs::resources::{self, JobClass, ResourceLimits};
+use crate::jobs::{external, JobManager, JobStatus, JobType};
 
 #[derive(Serialize)]
 pub struct JobResponse {
@@ -17,19 +19,69 @@ pub struct JobResponse {
     status: String,
     progress: f64,
     payload: Option<serde_json::Value>,
+    attempts: i64,
+    error: Option<String>,
     created_at: String,
     updated_at: String,
 }
 
-pub fn router(job_manager: Arc<JobManager>) -> Router {
+#[derive(Deserialize)]
+pub struct CreateJobRequest {
+    /// Namespaced kind with a registered worker, e.g. "acme.jersey_numbers"
+    kind: String,
+    /// Handed to the worker as is; an `asset_id` gates the job on the worker's `requires`
+    payload: Option<serde_json::Value>,
+    dedupe_key: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct CreateJobResponse {
+    job_id: i64,
+}
+
+#[derive(Deserialize)]
+pub struct ProgressRequest {
+    progress: f64,
+}
+
+#[derive(Serialize)]
+pub struct ResourcesResponse {
+    performance_mode: bool,
+    classes: Vec<ClassLimitsResponse>,
+}
+
+#[derive(Serialize)]
+pub struct ClassLimitsResponse {
+    class: JobClass,
+    limits: ResourceLimits,
+    /// False while the class is on its defaults
+    customized: bool,
+    /// What the class's processes start with now, performance mode included
+    effective: ResourceLimits,
+}
+
+#[derive(Deserialize)]
+pub struct PerformanceModeRequest {
+    enabled: bool,
+}
+
+pub fn router(db: Arc<Database>, job_manager: Arc<JobManager>) -> Router {
     Router::new()
+        .route("/", post(create_job))
+        .route("/workers", get(list_workers))
+        .route("/workers/:kind", put(register_worker).delete(delete_worker))
+        .route("/resources", get(get_resources))
+        .route("/resources/:class", put(set_resource_limits).delete(reset_resource_limits))
+        .route("/performance_mode", put(set_performance_mode))
         .route("/:id", get(get_job))
         .route("/:id/cancel", post(cancel_job))
-        .with_state(job_manager)
+        .route("/:id/retry", post(retry_job))
+        .route("/:id/progress", post(report_progress))
+        .with_state((db, job_manager))
 }
 
 async fn get_job(
-    State(job_manager): State<Arc<JobManager>>,
+    State((_db, job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
     Path(id): Path<i64>,
 ) -> Result<Json<JobResponse>, StatusCode> {
     let job = job_manager
@@ -43,13 +95,15 @@ async fn get_job(
         status: serde_json::to_string(&job.status).unwrap_or_default(),
         progress: job.progress,
         payload: job.payload,
+        attempts: job.attempts,
+        error: job.error,
         created_at: job.created_at.to_rfc3339(),
         updated_at: job.updated_at.to_rfc3339(),
     }))
 }
 
 async fn cancel_job(
-    State(job_manager): State<Arc<JobManager>>,
+    State((_db, job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
     Path(id): Path<i64>,
 ) -> Result<Json<()>, StatusCode> {
     job_manager
@@ -58,3 +112,171 @@ async fn cancel_job(
 
     Ok(Json(()))
 }
+
+/// POST /jobs - Queue an extension job for the worker registered for its kind
+async fn create_job(
+    State((db, job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Json(req): Json<CreateJobRequest>,
+) -> Result<Json<CreateJobResponse>, StatusCode> {
+    if !external::is_valid_kind(&req.kind) {
+        eprintln!("[JOBS] Job kind {} isn't namespaced as <namespace>.<name>", req.kind);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    if external::find_worker(&db, &req.kind)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .is_none()
+    {
+        eprintln!("[JOBS] No worker registered for {}", req.kind);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    if req.payload.as_ref().is_some_and(|p| !p.is_object()) {
+        eprintln!("[JOBS] Job payload must be a JSON object");
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let job_id = job_manager
+        .create_job(JobType::External(req.kind), req.payload, req.dedupe_key)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(CreateJobResponse { job_id }))
+}
+
+/// POST /jobs/:id/retry - Queue a failed or cancelled job again
+async fn retry_job(
+    State((_db, job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path(id): Path<i64>,
+) -> Result<Json<()>, StatusCode> {
+    job_manager
+        .get_job(id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    if !job_manager
+        .retry_job(id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+    {
+        return Err(StatusCode::CONFLICT);
+    }
+    Ok(Json(()))
+}
+
+/// POST /jobs/:id/progress - Progress report from an HTTP worker while it runs the job
+async fn report_progress(
+    State((_db, job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path(id): Path<i64>,
+    Json(req): Json<ProgressRequest>,
+) -> Result<Json<()>, StatusCode> {
+    let job = job_manager
+        .get_job(id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    if !matches!(job.status, JobStatus::Running) {
+        return Err(StatusCode::CONFLICT);
+    }
+    if !req.progress.is_finite() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    job_manager
+        .report_progress(id, req.progress)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(()))
+}
+
+/// GET /jobs/workers - Workers for extension job kinds: the configured ones (JOB_WORKERS_PATH),
+/// then those registered through the API for other kinds
+async fn list_workers(
+    State((db, _job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+) -> Result<Json<Vec<JobWorker>>, StatusCode> {
+    let registered = db.list_job_workers().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let mut workers = external::configured_workers().to_vec();
+    workers.extend(registered.into_iter().filter(|w| !external::is_configured(&w.kind)));
+    Ok(Json(workers))
+}
+
+/// PUT /jobs/workers/:kind - Register (or replace) the HTTP worker that processes a job kind.
+/// Command workers are only read from JOB_WORKERS_PATH, and kinds configured there can't be
+/// taken over.
+async fn register_worker(
+    State((db, _job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path(kind): Path<String>,
+    Json(mut worker): Json<JobWorker>,
+) -> Result<Json<JobWorker>, StatusCode> {
+    worker.kind = kind;
+    if worker.command.is_some() {
+        eprintln!("[JOBS] Refusing command worker for {}: configure it in JOB_WORKERS_PATH", worker.kind);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    if let Some(problem) = external::validate_worker(&worker) {
+        eprintln!("[JOBS] Invalid worker for {}: {}", worker.kind, problem);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    if external::is_configured(&worker.kind) {
+        eprintln!("[JOBS] {} has a configured worker in JOB_WORKERS_PATH", worker.kind);
+        return Err(StatusCode::CONFLICT);
+    }
+    db.upsert_job_worker(&worker)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(worker))
+}
+
+async fn delete_worker(
+    State((db, _job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path(kind): Path<String>,
+) -> Result<StatusCode, StatusCode> {
+    if external::is_configured(&kind) {
+        return Err(StatusCode::CONFLICT);
+    }
+    if !db
+        .delete_job_worker(&kind)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+    {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+fn class_limits(class: JobClass) -> ClassLimitsResponse {
+    let (limits, customized) = resources::configured(class);
+    ClassLimitsResponse { class, limits, customized, effective: resources::effective(class) }
+}
+
+fn resources_response() -> ResourcesResponse {
+    ResourcesResponse {
+        performance_mode: resources::performance_mode(),
+        classes: JobClass::ALL.into_iter().map(class_limits).collect(),
+    }
+}
+
+/// GET /jobs/resources - Resource limits of each job class and whether performance mode is on
+async fn get_resources() -> Json<ResourcesResponse> {
+    Json(resources_response())
+}
+
+/// PUT /jobs/resources/:class - Replace a job class's limits (fields left out are unlimited)
+async fn set_resource_limits(
+    State((db, _job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path(class): Path<String>,
+    Json(limits): Json<ResourceLimits>,
+) -> Result<Json<ClassLimitsResponse>, StatusCode> {
+    let class = JobClass::parse(&class).ok_or(StatusCode::NOT_FOUND)?;
+    if let Err(problem) = limits.validate() {
+        eprintln!("[JOBS] Invalid resource limits for {}: {}", class.as_str(), problem);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    resources::set_limits(&db, class, limits).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(class_limits(class)))
+}
+
+/// DELETE /jobs/resources/:class - Put a job class back on its default limits
+async fn reset_resource_limits(
+    State((db, _job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path(class): Path<String>,
+) -> Result<Json<ClassLimitsResponse>, StatusCode> {
+    let class = JobClass::parse(&class).ok_or(StatusCode::NOT_FOUND)?;
+    resources::reset_limits(&db, class).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(class_limits(class)))
+}
+
+/// PUT /jobs/performance_mode - Hold background jobs back while the user is editing
+async fn set_performance_mode(Json(req): Json<PerformanceModeRequest>) -> Json<ResourcesResponse> {
+    resources::set_performance_mode(req.enabled);
+    Json(resources_response())
+}
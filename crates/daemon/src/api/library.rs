@@ -0,0 +1,222 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+    routing::{delete, get},
+    Router,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::db::{Database, LibraryAsset};
+use crate::library::{self, AssetUsage};
+
+#[derive(Serialize)]
+pub struct LibraryAssetResponse {
+    #[serde(flatten)]
+    asset: LibraryAsset,
+    usage: Vec<AssetUsage>,
+}
+
+#[derive(Deserialize)]
+pub struct PublishRequest {
+    asset_id: i64,
+    /// Name shown in the library (default: the file name)
+    name: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct LinkRequest {
+    asset_id: i64,
+}
+
+/// Routes under /library
+pub fn router(db: Arc<Database>) -> Router {
+    Router::new()
+        .route("/", get(list_library).post(publish_asset))
+        .route("/:asset_id", get(get_library_asset).delete(unpublish_asset))
+        .with_state(db)
+}
+
+/// Routes under /projects
+pub fn project_router(db: Arc<Database>) -> Router {
+    Router::new()
+        .route("/:id/library", get(list_linked_assets).post(link_asset))
+        .route("/:id/library/:asset_id", delete(unlink_asset))
+        .with_state(db)
+}
+
+/// GET /library - Shared assets with the projects using them
+async fn list_library(State(db): State<Arc<Database>>) -> Result<Json<Vec<LibraryAssetResponse>>, StatusCode> {
+    let assets = db.list_library_assets().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let mut response = Vec::with_capacity(assets.len());
+    for asset in assets {
+        response.push(with_usage(&db, asset)?);
+    }
+    Ok(Json(response))
+}
+
+async fn get_library_asset(
+    State(db): State<Arc<Database>>,
+    Path(asset_id): Path<i64>,
+) -> Result<Json<LibraryAssetResponse>, StatusCode> {
+    let asset = find_library_asset(&db, asset_id)?;
+    with_usage(&db, asset).map(Json)
+}
+
+/// POST /library - Share an analyzed asset with every project (or rename a shared one)
+async fn publish_asset(
+    State(db): State<Arc<Database>>,
+    Json(req): Json<PublishRequest>,
+) -> Result<Json<LibraryAssetResponse>, StatusCode> {
+    let asset = db
+        .get_media_asset(req.asset_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let owner = db
+        .get_asset_project_id(asset.id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    if !library::is_analyzed(&db, asset.id).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)? {
+        eprintln!("[LIBRARY] Asset {} hasn't been analyzed yet", asset.id);
+        return Err(StatusCode::CONFLICT);
+    }
+    // Other projects would read its transcripts without the key
+    if db.get_project_encryption(owner).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?.is_some() {
+        eprintln!("[LIBRARY] Asset {} belongs to an encrypted project", asset.id);
+        return Err(StatusCode::CONFLICT);
+    }
+
+    let name = match req.name.as_deref().map(str::trim) {
+        Some(name) if !name.is_empty() => name.to_string(),
+        _ => std::path::Path::new(&asset.path)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| asset.path.clone()),
+    };
+    db.publish_library_asset(asset.id, &name)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let asset = find_library_asset(&db, asset.id)?;
+    with_usage(&db, asset).map(Json)
+}
+
+/// DELETE /library/:asset_id - Stop sharing an asset; projects must unlink it first
+async fn unpublish_asset(
+    State(db): State<Arc<Database>>,
+    Path(asset_id): Path<i64>,
+) -> Result<StatusCode, StatusCode> {
+    find_library_asset(&db, asset_id)?;
+    let links = db.list_asset_links(asset_id).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if !links.is_empty() {
+        eprintln!("[LIBRARY] Asset {} is still used by {} project(s)", asset_id, links.len());
+        return Err(StatusCode::CONFLICT);
+    }
+    db.unpublish_library_asset(asset_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /projects/:id/library - Library assets the project uses
+async fn list_linked_assets(
+    State(db): State<Arc<Database>>,
+    Path(project_id): Path<i64>,
+) -> Result<Json<Vec<LibraryAsset>>, StatusCode> {
+    find_project(&db, project_id)?;
+    linked_assets(&db, project_id).map(Json)
+}
+
+/// POST /projects/:id/library - Use a library asset in the project, with its analysis
+async fn link_asset(
+    State(db): State<Arc<Database>>,
+    Path(project_id): Path<i64>,
+    Json(req): Json<LinkRequest>,
+) -> Result<Json<Vec<LibraryAsset>>, StatusCode> {
+    find_project(&db, project_id)?;
+    let asset = find_library_asset(&db, req.asset_id)?;
+    if asset.owner_project_id == project_id {
+        eprintln!("[LIBRARY] Asset {} already belongs to project {}", asset.asset_id, project_id);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    if db
+        .get_project_encryption(asset.owner_project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .is_some()
+    {
+        eprintln!("[LIBRARY] Asset {}'s project has been encrypted since it was shared", asset.asset_id);
+        return Err(StatusCode::CONFLICT);
+    }
+    db.link_library_asset(project_id, asset.asset_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    eprintln!("[LIBRARY] Project {} now uses asset {}", project_id, asset.asset_id);
+    linked_assets(&db, project_id).map(Json)
+}
+
+/// DELETE /projects/:id/library/:asset_id - Stop using a library asset; refused while the
+/// project's timeline has clips cut from it
+async fn unlink_asset(
+    State(db): State<Arc<Database>>,
+    Path((project_id, asset_id)): Path<(i64, i64)>,
+) -> Result<StatusCode, StatusCode> {
+    find_project(&db, project_id)?;
+    let linked = db
+        .get_linked_assets_for_project(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if !linked.iter().any(|a| a.id == asset_id) {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    check_unused(&db, project_id, asset_id)?;
+    db.unlink_library_asset(project_id, asset_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// 409 while the project's timeline has clips cut from the asset
+pub(crate) fn check_unused(db: &Database, project_id: i64, asset_id: i64) -> Result<(), StatusCode> {
+    let clips = library::timeline_clips(db, project_id, asset_id).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if clips > 0 {
+        eprintln!("[LIBRARY] Project {}'s timeline has {} clip(s) from asset {}", project_id, clips, asset_id);
+        return Err(StatusCode::CONFLICT);
+    }
+    Ok(())
+}
+
+/// 409 while other projects use any of the project's assets from the library
+pub(crate) fn check_not_shared(db: &Database, project_id: i64) -> Result<(), StatusCode> {
+    let shared = db
+        .get_shared_assets_of_project(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if !shared.is_empty() {
+        eprintln!("[LIBRARY] Other projects use assets {:?} of project {} from the library", shared, project_id);
+        return Err(StatusCode::CONFLICT);
+    }
+    Ok(())
+}
+
+fn linked_assets(db: &Database, project_id: i64) -> Result<Vec<LibraryAsset>, StatusCode> {
+    let linked = db
+        .get_linked_assets_for_project(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let mut assets = Vec::with_capacity(linked.len());
+    for asset in linked {
+        assets.push(find_library_asset(db, asset.id)?);
+    }
+    Ok(assets)
+}
+
+fn with_usage(db: &Database, asset: LibraryAsset) -> Result<LibraryAssetResponse, StatusCode> {
+    let usage = library::usage(db, &asset).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(LibraryAssetResponse { asset, usage })
+}
+
+fn find_library_asset(db: &Database, asset_id: i64) -> Result<LibraryAsset, StatusCode> {
+    db.get_library_asset(asset_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+fn find_project(db: &Database, project_id: i64) -> Result<(), StatusCode> {
+    db.get_project(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)
+        .map(|_| ())
+}
@@ -0,0 +1,84 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+    routing::{get, patch, post},
+    Router,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::db::{Database, LowerThird};
+use crate::lower_thirds::{self, STATUSES};
+
+#[derive(Deserialize)]
+pub struct UpdateLowerThirdRequest {
+    /// "accepted" puts it on the timeline, "dismissed" takes it off and stops suggesting it,
+    /// "suggested" undoes either
+    status: String,
+}
+
+pub fn router(db: Arc<Database>) -> Router {
+    Router::new()
+        .route("/:id/lower_thirds", get(list_lower_thirds))
+        .route("/:id/lower_thirds/suggest", post(suggest_lower_thirds))
+        .route("/:id/lower_thirds/:lid", patch(update_lower_third))
+        .with_state(db)
+}
+
+async fn list_lower_thirds(
+    State(db): State<Arc<Database>>,
+    Path(project_id): Path<i64>,
+) -> Result<Json<Vec<LowerThird>>, StatusCode> {
+    db.list_lower_thirds(project_id)
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// POST /projects/:id/lower_thirds/suggest - Suggest lower thirds again after the cut changed
+async fn suggest_lower_thirds(
+    State(db): State<Arc<Database>>,
+    Path(project_id): Path<i64>,
+) -> Result<Json<Vec<LowerThird>>, StatusCode> {
+    db.get_project(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    lower_thirds::suggest(&db, project_id).map(Json).map_err(|e| {
+        eprintln!("[LOWER_THIRDS] Failed to suggest lower thirds for project {}: {:?}", project_id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })
+}
+
+/// PATCH /projects/:id/lower_thirds/:lid - Accept or dismiss a suggested lower third
+async fn update_lower_third(
+    State(db): State<Arc<Database>>,
+    Path((project_id, lower_third_id)): Path<(i64, i64)>,
+    Json(req): Json<UpdateLowerThirdRequest>,
+) -> Result<Json<LowerThird>, StatusCode> {
+    let lower_third = find_lower_third(&db, project_id, lower_third_id)?;
+    if !STATUSES.contains(&req.status.as_str()) {
+        eprintln!("[LOWER_THIRDS] Status must be one of {:?}", STATUSES);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let result = match req.status.as_str() {
+        "accepted" => lower_thirds::accept(&db, project_id, &lower_third).map(|_| ()),
+        status => lower_thirds::withdraw(&db, project_id, &lower_third, status),
+    };
+    result.map_err(|e| {
+        eprintln!("[LOWER_THIRDS] Failed to update lower third {}: {:?}", lower_third_id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    // Back to a suggestion: placed where the person first speaks in the cut as it is now
+    if req.status == "suggested" {
+        lower_thirds::suggest(&db, project_id).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    }
+    find_lower_third(&db, project_id, lower_third_id).map(Json)
+}
+
+fn find_lower_third(db: &Database, project_id: i64, lower_third_id: i64) -> Result<LowerThird, StatusCode> {
+    db.get_lower_third(lower_third_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .filter(|l| l.project_id == project_id)
+        .ok_or(StatusCode::NOT_FOUND)
+}
@@ -1,22 +1,37 @@
 use axum::{
     extract::{Path, Query, State},
     http::{header, StatusCode, HeaderMap},
-    response::{Json, Response},
-    routing::{delete, get, post},
+    response::{IntoResponse, Json, Response},
+    routing::{delete, get, post, put},
     Router,
     body::Body,
 };
 use serde::{Deserialize, Serialize};
-use std::{path::PathBuf, sync::Arc};
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
 use tokio_util::codec::{BytesCodec, FramedRead};
-use futures::StreamExt;
+use futures::{StreamExt, TryStreamExt};
 use bytes::Bytes;
 use tokio::io::{AsyncSeekExt, AsyncReadExt, SeekFrom};
 
+use crate::api::quotas;
+use crate::crypto;
 use crate::db::Database;
+use crate::jobs::integrity::{self, Integrity};
+use crate::jobs::priority::ImportPolicy;
+use crate::jobs::reanalyze;
+use crate::jobs::sync_drift;
 use crate::jobs::{JobManager, JobType};
-use crate::media::ffmpeg::FFmpegWrapper;
+use crate::media::ffmpeg::{FFmpegWrapper, MediaInfo};
+use crate::media::availability::{self, Availability};
+use crate::media::av_sync::{SyncDrift, SyncStatus};
 use crate::media::compute_file_checksum;
+use crate::media::proxy;
+use crate::media::remote;
+use crate::media::roots::{self, PathNotAllowed, Refusal};
+use crate::media::slow_motion;
+use crate::media::still;
+use crate::media::thumbnails::{self, LevelSource};
+use crate::storage;
 use serde_json::json;
 
 #[derive(Deserialize, Clone, Debug)]
@@ -49,6 +64,32 @@ pub struct MediaAssetResponse {
     duration_ticks: i64,
     width: i32,
     height: i32,
+    /// Photo imported as a still
+    is_still: bool,
+    /// Detected as a screen recording (segmented at slide changes and cursor activity)
+    is_screen_recording: bool,
+    /// Frame rate of the footage
+    fps: f64,
+    /// Shot at 120fps or more, so it can play in slow motion without repeating frames
+    slow_motion_capable: bool,
+    /// Clockwise rotation the source is displayed with; width and height are as displayed
+    rotation: i32,
+    /// Whether the original can be read; offline and archived assets play from their proxies
+    availability: Availability,
+    /// Result of the last checksum verification (None until the asset has been verified)
+    integrity: Option<Integrity>,
+    integrity_checked_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    integrity_warning: Option<String>,
+    /// Last A/V sync measurement (None until the asset has been measured)
+    sync_drift: Option<SyncDrift>,
+    sync_checked_at: Option<String>,
+    /// Whether proxies resample the audio to cancel the measured drift
+    sync_correction: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sync_warning: Option<String>,
+    /// Used from the asset library: imported and analyzed by another project
+    linked: bool,
 }
 
 #[derive(Serialize)]
@@ -58,6 +99,64 @@ pub struct AudioAssetResponse {
     duration_ticks: i64,
 }
 
+impl IntoResponse for PathNotAllowed {
+    fn into_response(self) -> Response {
+        let status = match self.reason {
+            Refusal::Invalid => StatusCode::BAD_REQUEST,
+            Refusal::Traversal | Refusal::OutsideRoots => StatusCode::FORBIDDEN,
+        };
+        (
+            status,
+            Json(json!({
+                "error": "path_not_allowed",
+                "message": self.to_string(),
+                "path": self.path,
+                "reason": self.reason,
+                "allowed_roots": roots::allowed_roots(),
+            })),
+        )
+            .into_response()
+    }
+}
+
+/// Failures of endpoints that take filesystem paths: a plain status, or a path refused by the
+/// allowed media roots (403, or 400 when it isn't a usable path, with the details)
+pub enum PathError {
+    Status(StatusCode),
+    NotAllowed(PathNotAllowed),
+}
+
+impl From<StatusCode> for PathError {
+    fn from(status: StatusCode) -> Self {
+        PathError::Status(status)
+    }
+}
+
+impl From<PathNotAllowed> for PathError {
+    fn from(refused: PathNotAllowed) -> Self {
+        eprintln!("[ROOTS] {}", refused);
+        PathError::NotAllowed(refused)
+    }
+}
+
+impl IntoResponse for PathError {
+    fn into_response(self) -> Response {
+        match self {
+            PathError::Status(status) => status.into_response(),
+            PathError::NotAllowed(refused) => refused.into_response(),
+        }
+    }
+}
+
+/// A local path or s3:// / gs:// URL to import: local paths are resolved against the allowed
+/// media roots, URLs are passed through
+pub(crate) fn import_source(path: &str) -> Result<String, PathNotAllowed> {
+    if remote::is_remote_url(path) {
+        return Ok(path.to_string());
+    }
+    roots::resolve(path).map(|p| p.to_string_lossy().to_string())
+}
+
 pub fn router(db: Arc<Database>, job_manager: Arc<JobManager>) -> Router {
     Router::new()
         .route("/:id/import_raw", post(import_raw))
@@ -65,10 +164,20 @@ pub fn router(db: Arc<Database>, job_manager: Arc<JobManager>) -> Router {
         .route("/:id/references", get(list_references))
         .route("/:id/audio", get(list_audio))
         .route("/:id/media/:asset_id", delete(delete_media_asset))
-        .route("/:id/media/:asset_id/proxy", get(get_proxy_file))
+        .route("/:id/media/:asset_id/proxy", get(get_proxy_file).head(head_proxy_file))
         .route("/:id/media/:asset_id/thumbnail/:timestamp_ms", get(get_thumbnail))
         .route("/:id/media/:asset_id/generate_thumbnails", post(generate_thumbnails_for_asset))
-        .route("/proxy/:asset_id", get(get_proxy_file_legacy)) // Legacy route for compatibility
+        .route("/:id/media/:asset_id/thumbnails", get(get_thumbnail_manifest))
+        .route("/:id/media/:asset_id/thumbnails/:interval_ms/:index", get(get_pyramid_thumbnail))
+        .route("/:id/media/:asset_id/regenerate_proxies", post(regenerate_proxies))
+        .route("/:id/media/:asset_id/relink", post(relink_media_asset))
+        .route("/:id/media/:asset_id/refresh", post(refresh_media_asset))
+        .route("/:id/media/:asset_id/availability", put(set_media_availability))
+        .route("/:id/media/check_availability", post(check_media_availability))
+        .route("/:id/media/verify_checksums", post(verify_checksums))
+        .route("/:id/media/detect_sync_drift", post(detect_sync_drift))
+        .route("/:id/media/:asset_id/sync_correction", put(set_sync_correction))
+        .route("/proxy/:asset_id", get(get_proxy_file_legacy).head(head_proxy_file_legacy)) // Legacy route for compatibility
         .with_state((db, job_manager))
 }
 
@@ -76,14 +185,46 @@ async fn list_media(
     State((db, _job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
     Path(project_id): Path<i64>,
 ) -> Result<Json<Vec<MediaAssetResponse>>, StatusCode> {
-    // Get media assets for this specific project (excluding references)
-    let assets = db
+    // Get media assets for this specific project (excluding references), then the library
+    // assets it uses
+    let mut assets = db
         .get_media_assets_for_project(project_id)
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let linked_assets = db
+        .get_linked_assets_for_project(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let linked: Vec<i64> = linked_assets.iter().map(|asset| asset.id).collect();
+    assets.extend(linked_assets);
+    let stills = still_asset_ids(&db, project_id)?;
+    let screen_recordings = screen_recording_asset_ids(&db, project_id)?;
+    let rotations = asset_rotations(&db, project_id)?;
+    let availabilities = asset_availabilities(&db, project_id)?;
+    let integrity = asset_integrity(&db, project_id)?;
+    let sync = asset_sync_drift(&db, project_id)?;
     
     let response: Vec<MediaAssetResponse> = assets
         .into_iter()
         .map(|asset| MediaAssetResponse {
+            is_still: stills.contains(&asset.id),
+            is_screen_recording: screen_recordings.contains(&asset.id),
+            fps: slow_motion::frame_rate(asset.fps_num, asset.fps_den),
+            slow_motion_capable: !stills.contains(&asset.id)
+                && slow_motion::is_slow_motion_capable(slow_motion::frame_rate(asset.fps_num, asset.fps_den)),
+            rotation: rotations.get(&asset.id).copied().unwrap_or(0),
+            availability: availabilities.get(&asset.id).copied().unwrap_or(Availability::Online),
+            integrity: integrity.get(&asset.id).map(|(status, _)| *status),
+            integrity_checked_at: integrity.get(&asset.id).map(|(_, checked_at)| checked_at.clone()),
+            integrity_warning: integrity
+                .get(&asset.id)
+                .and_then(|(status, _)| status.warning())
+                .map(str::to_string),
+            sync_drift: sync.get(&asset.id).map(|(drift, _, _)| drift.clone()),
+            sync_checked_at: sync.get(&asset.id).map(|(_, checked_at, _)| checked_at.clone()),
+            sync_correction: sync.get(&asset.id).is_some_and(|(_, _, corrected)| *corrected),
+            sync_warning: sync
+                .get(&asset.id)
+                .and_then(|(drift, _, corrected)| drift.warning(*corrected)),
+            linked: linked.contains(&asset.id),
             id: asset.id,
             path: asset.path,
             duration_ticks: asset.duration_ticks,
@@ -103,10 +244,36 @@ async fn list_references(
     let assets = db
         .get_reference_assets_for_project(project_id)
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let stills = still_asset_ids(&db, project_id)?;
+    let screen_recordings = screen_recording_asset_ids(&db, project_id)?;
+    let rotations = asset_rotations(&db, project_id)?;
+    let availabilities = asset_availabilities(&db, project_id)?;
+    let integrity = asset_integrity(&db, project_id)?;
+    let sync = asset_sync_drift(&db, project_id)?;
     
     let response: Vec<MediaAssetResponse> = assets
         .into_iter()
         .map(|asset| MediaAssetResponse {
+            is_still: stills.contains(&asset.id),
+            is_screen_recording: screen_recordings.contains(&asset.id),
+            fps: slow_motion::frame_rate(asset.fps_num, asset.fps_den),
+            slow_motion_capable: !stills.contains(&asset.id)
+                && slow_motion::is_slow_motion_capable(slow_motion::frame_rate(asset.fps_num, asset.fps_den)),
+            rotation: rotations.get(&asset.id).copied().unwrap_or(0),
+            availability: availabilities.get(&asset.id).copied().unwrap_or(Availability::Online),
+            integrity: integrity.get(&asset.id).map(|(status, _)| *status),
+            integrity_checked_at: integrity.get(&asset.id).map(|(_, checked_at)| checked_at.clone()),
+            integrity_warning: integrity
+                .get(&asset.id)
+                .and_then(|(status, _)| status.warning())
+                .map(str::to_string),
+            sync_drift: sync.get(&asset.id).map(|(drift, _, _)| drift.clone()),
+            sync_checked_at: sync.get(&asset.id).map(|(_, checked_at, _)| checked_at.clone()),
+            sync_correction: sync.get(&asset.id).is_some_and(|(_, _, corrected)| *corrected),
+            sync_warning: sync
+                .get(&asset.id)
+                .and_then(|(drift, _, corrected)| drift.warning(*corrected)),
+            linked: false,
             id: asset.id,
             path: asset.path,
             duration_ticks: asset.duration_ticks,
@@ -118,6 +285,64 @@ async fn list_references(
     Ok(Json(response))
 }
 
+fn still_asset_ids(db: &Database, project_id: i64) -> Result<std::collections::HashSet<i64>, StatusCode> {
+    Ok(db
+        .get_still_assets(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .into_iter()
+        .map(|still| still.id)
+        .collect())
+}
+
+fn screen_recording_asset_ids(db: &Database, project_id: i64) -> Result<std::collections::HashSet<i64>, StatusCode> {
+    Ok(db
+        .get_screen_recording_asset_ids(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .into_iter()
+        .collect())
+}
+
+fn asset_rotations(db: &Database, project_id: i64) -> Result<std::collections::HashMap<i64, i32>, StatusCode> {
+    Ok(db
+        .get_media_asset_rotations(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .into_iter()
+        .collect())
+}
+
+/// Availability of the project's assets, re-checked against the disk
+fn asset_availabilities(db: &Database, project_id: i64) -> Result<std::collections::HashMap<i64, Availability>, StatusCode> {
+    availability::refresh_project(db, project_id).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(db
+        .get_media_asset_availabilities(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .into_iter()
+        .map(|(id, _, value)| (id, Availability::parse(&value).unwrap_or(Availability::Online)))
+        .collect())
+}
+
+/// Last A/V sync measurement of the project's assets as (drift, checked_at, corrected)
+fn asset_sync_drift(db: &Database, project_id: i64) -> Result<std::collections::HashMap<i64, (SyncDrift, String, bool)>, StatusCode> {
+    Ok(db
+        .get_project_sync_drift(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .into_iter()
+        .filter_map(|(id, json, checked_at, corrected)| {
+            Some((id, (serde_json::from_str(&json).ok()?, checked_at, corrected)))
+        })
+        .collect())
+}
+
+/// Last checksum verification of the project's assets as (result, checked_at)
+fn asset_integrity(db: &Database, project_id: i64) -> Result<std::collections::HashMap<i64, (Integrity, String)>, StatusCode> {
+    Ok(db
+        .get_media_asset_integrity(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .into_iter()
+        .filter_map(|(id, status, checked_at, _)| Some((id, (Integrity::parse(&status)?, checked_at))))
+        .collect())
+}
+
 async fn list_audio(
     State((db, _job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
     Path(project_id): Path<i64>,
@@ -133,6 +358,19 @@ async fn delete_media_asset(
     Path(params): Path<(i64, i64)>, // (project_id, asset_id)
 ) -> Result<StatusCode, StatusCode> {
     let (project_id, asset_id) = params;
+
+    // A library asset stays while other projects use it
+    if let Some(asset) = db.get_library_asset(asset_id).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)? {
+        if asset.owner_project_id == project_id {
+            let links = db.list_asset_links(asset_id).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+            if !links.is_empty() {
+                eprintln!("[LIBRARY] Asset {} is still used by {} project(s)", asset_id, links.len());
+                return Err(StatusCode::CONFLICT);
+            }
+            db.unpublish_library_asset(asset_id)
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        }
+    }
     
     db.delete_media_asset(project_id, asset_id)
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
@@ -143,137 +381,389 @@ async fn delete_media_asset(
 #[derive(Deserialize)]
 struct ProxyQuery {
     thumbnail: Option<bool>,
+    /// Proxy tier to serve (e.g. "scrub", "preview"); defaults to the highest tier
+    tier: Option<String>,
 }
 
 async fn get_proxy_file(
-    State((db, _job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    State((db, job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
     Path(params): Path<(i64, i64)>, // (project_id, asset_id) for /:id/media/:asset_id/proxy
-    Query(_query): Query<ProxyQuery>,
+    Query(query): Query<ProxyQuery>,
+    headers: HeaderMap,
+) -> Result<Response, StatusCode> {
+    let (_project_id, asset_id) = params;
+    serve_video_file(db, job_manager, asset_id, query.tier, headers, false).await
+}
+
+/// HEAD handler for /:id/media/:asset_id/proxy (headers only, no file read)
+async fn head_proxy_file(
+    State((db, job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path(params): Path<(i64, i64)>,
+    Query(query): Query<ProxyQuery>,
     headers: HeaderMap,
 ) -> Result<Response, StatusCode> {
     let (_project_id, asset_id) = params;
-    serve_video_file(db, asset_id, headers).await
+    serve_video_file(db, job_manager, asset_id, query.tier, headers, true).await
 }
 
 /// Legacy handler for /proxy/:asset_id route (without project_id)
 async fn get_proxy_file_legacy(
-    State((db, _job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    State((db, job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
     Path(asset_id): Path<i64>,
-    Query(_query): Query<ProxyQuery>,
+    Query(query): Query<ProxyQuery>,
     headers: HeaderMap,
 ) -> Result<Response, StatusCode> {
-    serve_video_file(db, asset_id, headers).await
+    serve_video_file(db, job_manager, asset_id, query.tier, headers, false).await
 }
 
-/// Common logic to serve video file with range request support
-async fn serve_video_file(
-    db: Arc<Database>,
-    asset_id: i64,
+/// Legacy HEAD handler for /proxy/:asset_id route
+async fn head_proxy_file_legacy(
+    State((db, job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path(asset_id): Path<i64>,
+    Query(query): Query<ProxyQuery>,
     headers: HeaderMap,
 ) -> Result<Response, StatusCode> {
-    
-    // Try to get proxy path, fallback to original file path
-    let file_path = match db
-        .get_proxy_path(asset_id)
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-    {
-        Some(proxy_path) => {
-            let path = PathBuf::from(&proxy_path);
-            if path.exists() {
-                path
-            } else {
-                // Proxy file doesn't exist, fallback to original
-                db.get_media_asset_path(asset_id)
-                    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-                    .ok_or(StatusCode::NOT_FOUND)
-                    .map(PathBuf::from)?
-            }
+    serve_video_file(db, job_manager, asset_id, query.tier, headers, true).await
+}
+
+/// Boundary used for multipart/byteranges responses
+const BYTERANGES_BOUNDARY: &str = "vibecut_byteranges_boundary";
+
+/// Resolve which file to serve for an asset.
+/// Serves the requested tier if it exists on disk; otherwise queues lazy generation of that
+/// tier and falls back to the best lower tier available, then to the original file. While the
+/// original is offline or archived, any existing proxy is served instead and nothing is queued.
+fn resolve_proxy_file(
+    db: &Database,
+    job_manager: &JobManager,
+    asset_id: i64,
+    tier: Option<&str>,
+) -> Result<PathBuf, StatusCode> {
+    let requested = match tier {
+        Some(name) => proxy::find_tier(name).ok_or(StatusCode::BAD_REQUEST)?,
+        None => proxy::default_playback_tier(),
+    };
+
+    let proxies = db
+        .list_proxies(asset_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    if let Some((_, path, _, _)) = proxies.iter().find(|(t, _, _, _)| *t == requested.name) {
+        let path = PathBuf::from(path);
+        if path.exists() {
+            return Ok(path);
         }
+    }
+
+    let online = availability::asset_availability(db, asset_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .is_none_or(|a| a == Availability::Online);
+    if !online {
+        // Prefer the closest tier below the requested one, then anything above it
+        let below = proxies
+            .iter()
+            .rev()
+            .filter(|(_, _, _, height)| *height <= requested.max_height);
+        let above = proxies
+            .iter()
+            .filter(|(_, _, _, height)| *height > requested.max_height);
+        return below
+            .chain(above)
+            .map(|(_, path, _, _)| PathBuf::from(path))
+            .find(|path| path.exists())
+            .ok_or_else(|| {
+                eprintln!("[PROXY] Asset {} is offline and has no proxy to play", asset_id);
+                StatusCode::CONFLICT
+            });
+    }
+
+    // Requested tier not generated yet (or cache was cleared) - generate it in the background
+    let payload = json!({
+        "media_asset_id": asset_id,
+        "tiers": [requested.name],
+    });
+    let dedupe_key = format!("GenerateProxy:{}:{}", asset_id, requested.name);
+    if let Err(e) = job_manager.create_job(JobType::GenerateProxy, Some(payload), Some(dedupe_key)) {
+        eprintln!("[PROXY] Failed to queue {} proxy for asset {}: {:?}", requested.name, asset_id, e);
+    }
+
+    // Best existing proxy no larger than the requested tier
+    let fallback = proxies
+        .iter()
+        .rev()
+        .filter(|(_, _, _, height)| *height <= requested.max_height)
+        .map(|(_, path, _, _)| PathBuf::from(path))
+        .find(|path| path.exists());
+
+    match fallback {
+        Some(path) => Ok(path),
         None => {
             // No proxy exists, use original file
             db.get_media_asset_path(asset_id)
                 .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
                 .ok_or(StatusCode::NOT_FOUND)
-                .map(PathBuf::from)?
+                .map(PathBuf::from)
         }
-    };
+    }
+}
+
+/// Common logic to serve video file with range request support
+/// Handles HEAD (headers only), single and multiple byte ranges, and If-Range validation
+async fn serve_video_file(
+    db: Arc<Database>,
+    job_manager: Arc<JobManager>,
+    asset_id: i64,
+    tier: Option<String>,
+    headers: HeaderMap,
+    head_only: bool,
+) -> Result<Response, StatusCode> {
+    let file_path = resolve_proxy_file(&db, &job_manager, asset_id, tier.as_deref())?;
 
     if !file_path.exists() {
         return Err(StatusCode::NOT_FOUND);
     }
 
+    if let Some(redirect) = storage_redirect(&db, &file_path)? {
+        return Ok(redirect);
+    }
+
     // Get file metadata
     let metadata = tokio::fs::metadata(&file_path)
         .await
         .map_err(|_| StatusCode::NOT_FOUND)?;
-    let file_size = metadata.len();
+    let source = FileSource::open(&db, asset_id, file_path)?;
+    let file_size = match &source {
+        FileSource::Plain(_) => metadata.len(),
+        FileSource::Encrypted(path, _) => crypto::encrypted_file_len(path)
+            .ok()
+            .flatten()
+            .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?,
+    };
+
+    // Validators used for If-Range (and exposed so clients can send them back)
+    let modified: Option<chrono::DateTime<chrono::Utc>> = metadata.modified().ok().map(|t| t.into());
+    let etag = format!(
+        "\"{:x}-{:x}\"",
+        file_size,
+        modified.map(|m| m.timestamp()).unwrap_or(0)
+    );
+    let last_modified = modified.map(|m| m.format("%a, %d %b %Y %H:%M:%S GMT").to_string());
+
+    let base_builder = || {
+        let mut builder = Response::builder()
+            .header(header::ACCEPT_RANGES, "bytes")
+            .header(header::ETAG, etag.as_str());
+        if let Some(ref lm) = last_modified {
+            builder = builder.header(header::LAST_MODIFIED, lm.as_str());
+        }
+        builder
+    };
 
     // Handle empty file
     if file_size == 0 {
-        return Ok(Response::builder()
+        return base_builder()
             .status(StatusCode::OK)
             .header(header::CONTENT_TYPE, "video/mp4")
-            .header(header::ACCEPT_RANGES, "bytes")
             .header(header::CONTENT_LENGTH, "0")
             .body(Body::empty())
-            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?);
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR);
     }
 
+    // Range only applies if If-Range (when present) still matches the current file
+    let range_applies = match headers.get(header::IF_RANGE).and_then(|v| v.to_str().ok()) {
+        Some(if_range) => if_range_matches(if_range, &etag, modified),
+        None => true,
+    };
+
     // Parse Range header if present
-    let range_header = headers.get(header::RANGE);
-    let (start, end, status_code) = if let Some(range_value) = range_header {
-        if let Ok(range_str) = range_value.to_str() {
-            if let Some(range) = parse_range(range_str, file_size) {
-                (range.0, range.1, StatusCode::PARTIAL_CONTENT)
+    let ranges = match headers.get(header::RANGE) {
+        Some(range_value) if range_applies => match range_value.to_str() {
+            Ok(range_str) => parse_ranges(range_str, file_size),
+            Err(_) => RangeSpec::Ignored,
+        },
+        _ => RangeSpec::Ignored,
+    };
+
+    match ranges {
+        RangeSpec::Unsatisfiable => {
+            // 416 with the current length so the client can retry with a valid range
+            base_builder()
+                .status(StatusCode::RANGE_NOT_SATISFIABLE)
+                .header(header::CONTENT_RANGE, format!("bytes */{}", file_size))
+                .header(header::CONTENT_LENGTH, "0")
+                .body(Body::empty())
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+        }
+        RangeSpec::Ignored => {
+            // Full file
+            let body = if head_only {
+                Body::empty()
+            } else {
+                Body::from_stream(source.range_stream(0, file_size))
+            };
+            base_builder()
+                .status(StatusCode::OK)
+                .header(header::CONTENT_TYPE, "video/mp4")
+                .header(header::CONTENT_LENGTH, file_size.to_string())
+                .body(body)
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+        }
+        RangeSpec::Ranges(ranges) if ranges.len() == 1 => {
+            let (start, end) = ranges[0];
+            let content_length = end - start + 1;
+            let body = if head_only {
+                Body::empty()
             } else {
-                // Invalid range, return full file
-                (0, file_size.saturating_sub(1), StatusCode::OK)
+                Body::from_stream(source.range_stream(start, content_length))
+            };
+            base_builder()
+                .status(StatusCode::PARTIAL_CONTENT)
+                .header(header::CONTENT_TYPE, "video/mp4")
+                .header(header::CONTENT_LENGTH, content_length.to_string())
+                .header(
+                    header::CONTENT_RANGE,
+                    format!("bytes {}-{}/{}", start, end, file_size),
+                )
+                .body(body)
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+        }
+        RangeSpec::Ranges(ranges) => {
+            // Multiple ranges: multipart/byteranges body, each part streamed from disk
+            let mut parts: Vec<futures::stream::BoxStream<'static, Result<Bytes, std::io::Error>>> = Vec::new();
+            let mut content_length = 0u64;
+            for (start, end) in &ranges {
+                let part_header = format!(
+                    "\r\n--{}\r\nContent-Type: video/mp4\r\nContent-Range: bytes {}-{}/{}\r\n\r\n",
+                    BYTERANGES_BOUNDARY, start, end, file_size
+                );
+                content_length += part_header.len() as u64 + (end - start + 1);
+                parts.push(futures::stream::once(async move { Ok(Bytes::from(part_header)) }).boxed());
+                parts.push(source.range_stream(*start, end - start + 1));
             }
-        } else {
-            (0, file_size.saturating_sub(1), StatusCode::OK)
+            let closing = format!("\r\n--{}--\r\n", BYTERANGES_BOUNDARY);
+            content_length += closing.len() as u64;
+            parts.push(futures::stream::once(async move { Ok(Bytes::from(closing)) }).boxed());
+
+            let body = if head_only {
+                Body::empty()
+            } else {
+                Body::from_stream(futures::stream::iter(parts).flatten())
+            };
+            base_builder()
+                .status(StatusCode::PARTIAL_CONTENT)
+                .header(
+                    header::CONTENT_TYPE,
+                    format!("multipart/byteranges; boundary={}", BYTERANGES_BOUNDARY),
+                )
+                .header(header::CONTENT_LENGTH, content_length.to_string())
+                .body(body)
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
         }
-    } else {
-        (0, file_size.saturating_sub(1), StatusCode::OK)
-    };
+    }
+}
 
-    let content_length = end - start + 1;
+/// Redirect to a signed storage URL when the file has a copy in the storage backend, so the
+/// client downloads it from the bucket instead of through the daemon
+pub(crate) fn storage_redirect(db: &Database, path: &std::path::Path) -> Result<Option<Response>, StatusCode> {
+    let Some(url) = storage::signed_url(db, path).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)? else {
+        return Ok(None);
+    };
+    Response::builder()
+        .status(StatusCode::TEMPORARY_REDIRECT)
+        .header(header::LOCATION, url)
+        // Signed URLs expire, so the redirect itself mustn't be cached
+        .header(header::CACHE_CONTROL, "no-store")
+        .body(Body::empty())
+        .map(Some)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
 
-    // Open file and seek to start position
-    let mut file = tokio::fs::File::open(&file_path)
-        .await
-        .map_err(|_| StatusCode::NOT_FOUND)?;
-    
-    file.seek(SeekFrom::Start(start))
-        .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+/// Where served media bytes come from: the file as is, or decrypted with its project's key
+enum FileSource {
+    Plain(PathBuf),
+    Encrypted(PathBuf, crypto::Key),
+}
 
-    // Create a limited reader for the range
-    let limited_file = file.take(content_length);
-    let stream = FramedRead::new(limited_file, BytesCodec::new());
-    let body_stream = stream.map(|result| {
-        result.map(|bytes| Bytes::from(bytes.freeze()))
-            .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))
-    });
-    let body = Body::from_stream(body_stream);
+/// Plaintext bytes decrypted per request of an encrypted file's range
+const DECRYPT_WINDOW: u64 = 1024 * 1024;
 
-    // Build response with appropriate headers
-    let mut response_builder = Response::builder()
-        .status(status_code)
-        .header(header::CONTENT_TYPE, "video/mp4")
-        .header(header::ACCEPT_RANGES, "bytes")
-        .header(header::CONTENT_LENGTH, content_length.to_string());
+impl FileSource {
+    /// Encrypted files need their project unlocked (423 Locked otherwise)
+    fn open(db: &Database, asset_id: i64, path: PathBuf) -> Result<Self, StatusCode> {
+        if !crypto::is_encrypted_file(&path) {
+            return Ok(FileSource::Plain(path));
+        }
+        match db.asset_key(asset_id) {
+            Ok(Some(key)) => Ok(FileSource::Encrypted(path, key)),
+            Ok(None) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+            Err(e) if crypto::is_locked_error(&e) => Err(StatusCode::LOCKED),
+            Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+        }
+    }
 
-    // Add Content-Range header for partial content
-    if status_code == StatusCode::PARTIAL_CONTENT {
-        response_builder = response_builder.header(
-            header::CONTENT_RANGE,
-            format!("bytes {}-{}/{}", start, end, file_size),
-        );
+    /// Stream `len` bytes starting at `start`
+    fn range_stream(&self, start: u64, len: u64) -> futures::stream::BoxStream<'static, Result<Bytes, std::io::Error>> {
+        match self {
+            FileSource::Plain(path) => file_range_stream(path.clone(), start, len).boxed(),
+            FileSource::Encrypted(path, key) => {
+                let (path, key) = (path.clone(), *key);
+                let end = start + len;
+                futures::stream::try_unfold(start, move |pos| {
+                    let path = path.clone();
+                    async move {
+                        if pos >= end {
+                            return Ok(None);
+                        }
+                        let len = (end - pos).min(DECRYPT_WINDOW);
+                        let bytes = tokio::task::spawn_blocking(move || crypto::decrypt_file_range(&key, &path, pos, len))
+                            .await
+                            .map_err(std::io::Error::other)?
+                            .map_err(std::io::Error::other)?;
+                        Ok(Some((Bytes::from(bytes), pos + len)))
+                    }
+                })
+                .boxed()
+            }
+        }
     }
+}
+
+/// Stream `len` bytes of a file starting at `start`
+fn file_range_stream(
+    path: PathBuf,
+    start: u64,
+    len: u64,
+) -> impl futures::Stream<Item = Result<Bytes, std::io::Error>> + Send + 'static {
+    futures::stream::once(async move {
+        let mut file = tokio::fs::File::open(&path).await?;
+        file.seek(SeekFrom::Start(start)).await?;
+        // Create a limited reader for the range
+        let limited_file = file.take(len);
+        let stream = FramedRead::new(limited_file, BytesCodec::new())
+            .map(|result| result.map(|bytes| bytes.freeze()));
+        Ok::<_, std::io::Error>(stream)
+    })
+    .try_flatten()
+}
 
-    Ok(response_builder
-        .body(body)
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?)
+/// Check an If-Range value against the current validators.
+/// Entity tags must match exactly (weak tags never match); dates must match Last-Modified.
+fn if_range_matches(
+    if_range: &str,
+    etag: &str,
+    modified: Option<chrono::DateTime<chrono::Utc>>,
+) -> bool {
+    let if_range = if_range.trim();
+    if if_range.starts_with('"') {
+        return if_range == etag;
+    }
+    if if_range.starts_with("W/") {
+        return false;
+    }
+    match (chrono::DateTime::parse_from_rfc2822(if_range), modified) {
+        (Ok(date), Some(modified)) => date.timestamp() == modified.timestamp(),
+        _ => false,
+    }
 }
 
 /// Get thumbnail image for a specific timestamp
@@ -295,33 +785,199 @@ async fn get_thumbnail(
     let thumbnail_filename = format!("t_{:04}.jpg", timestamp_sec);
     let thumbnail_path = PathBuf::from(&thumbnail_dir).join(&thumbnail_filename);
     
+    serve_thumbnail_file(&db, asset_id, thumbnail_path, true).await
+}
+
+/// Serve a thumbnail image (decrypted when the project is encrypted); stand-ins for thumbnails
+/// still being generated aren't cached
+async fn serve_thumbnail_file(
+    db: &Database,
+    asset_id: i64,
+    thumbnail_path: PathBuf,
+    cacheable: bool,
+) -> Result<Response, StatusCode> {
+    if let Some(redirect) = storage_redirect(db, &thumbnail_path)? {
+        return Ok(redirect);
+    }
+
     if !thumbnail_path.exists() {
         return Err(StatusCode::NOT_FOUND);
     }
     
-    // Read thumbnail file
-    let thumbnail_data = tokio::fs::read(&thumbnail_path)
-        .await
-        .map_err(|_| StatusCode::NOT_FOUND)?;
-    
-    // Get file metadata
-    let metadata = tokio::fs::metadata(&thumbnail_path)
-        .await
-        .map_err(|_| StatusCode::NOT_FOUND)?;
-    let file_size = metadata.len();
+    // Read thumbnail file (decrypted when the project is encrypted)
+    let (thumbnail_data, cache_control) = match FileSource::open(db, asset_id, thumbnail_path.clone())? {
+        FileSource::Plain(path) => (
+            tokio::fs::read(&path).await.map_err(|_| StatusCode::NOT_FOUND)?,
+            if cacheable { "public, max-age=31536000" } else { "no-cache" }, // Cache for 1 year
+        ),
+        FileSource::Encrypted(path, key) => (
+            crypto::decrypt_file(&key, &path).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?,
+            "private, no-store",
+        ),
+    };
+    let file_size = thumbnail_data.len();
     
     // Build response with image/jpeg content type
     let response = Response::builder()
         .status(StatusCode::OK)
         .header(header::CONTENT_TYPE, "image/jpeg")
         .header(header::CONTENT_LENGTH, file_size.to_string())
-        .header(header::CACHE_CONTROL, "public, max-age=31536000") // Cache for 1 year
+        .header(header::CACHE_CONTROL, cache_control)
         .body(Body::from(thumbnail_data))
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     
     Ok(response)
 }
 
+#[derive(Serialize)]
+struct ThumbnailLevel {
+    interval_ms: i64,
+    source: LevelSource,
+    /// "ready", "pending" (queued or being generated), "on_request" (generated when first
+    /// asked for) or "missing" (no base strip yet)
+    status: &'static str,
+    /// Thumbnails in the level, at 0, interval_ms, 2 * interval_ms, ... (estimated from the
+    /// asset's duration until the level is ready)
+    count: i64,
+    url_template: String,
+}
+
+#[derive(Serialize)]
+struct ThumbnailManifest {
+    asset_id: i64,
+    duration_ms: i64,
+    /// Densest first
+    levels: Vec<ThumbnailLevel>,
+}
+
+/// Which thumbnail densities an asset has, for zooming the timeline: the base strip, levels
+/// derived from it, and levels generated on demand
+async fn get_thumbnail_manifest(
+    State((db, job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path((project_id, asset_id)): Path<(i64, i64)>,
+) -> Result<Json<ThumbnailManifest>, StatusCode> {
+    let asset = db
+        .get_media_asset(asset_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let duration_ms = asset.duration_ticks * 1000 / engine::timeline::TICKS_PER_SECOND;
+    let ready = ready_thumbnail_levels(&db, asset_id)?;
+    let pending = pending_thumbnail_levels(&job_manager, asset_id);
+
+    let levels = thumbnails::pyramid_intervals()
+        .into_iter()
+        .map(|interval_ms| {
+            let source = thumbnails::level_source(interval_ms);
+            let (status, count) = match ready.get(&interval_ms) {
+                Some(count) => ("ready", *count),
+                None => {
+                    let status = match source {
+                        LevelSource::Generated if pending.contains(&interval_ms) => "pending",
+                        LevelSource::Generated => "on_request",
+                        _ => "missing",
+                    };
+                    (status, (duration_ms + interval_ms - 1) / interval_ms)
+                }
+            };
+            ThumbnailLevel {
+                interval_ms,
+                source,
+                status,
+                count,
+                url_template: format!(
+                    "/api/projects/{}/media/{}/thumbnails/{}/{{index}}",
+                    project_id, asset_id, interval_ms
+                ),
+            }
+        })
+        .collect();
+
+    Ok(Json(ThumbnailManifest { asset_id, duration_ms, levels }))
+}
+
+/// Thumbnail counts of the levels whose files are on disk (or mirrored to storage), derived
+/// levels included
+fn ready_thumbnail_levels(db: &Database, asset_id: i64) -> Result<HashMap<i64, i64>, StatusCode> {
+    let Some(dir) = db.get_thumbnail_dir(asset_id).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)? else {
+        return Ok(HashMap::new());
+    };
+    let dir = PathBuf::from(dir);
+    let recorded = db
+        .get_thumbnail_levels(asset_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let present = |interval_ms: i64| {
+        let first = thumbnails::thumbnail_file(&dir, interval_ms, 0);
+        first.exists() || db.get_stored_object_key(&first.to_string_lossy()).ok().flatten().is_some()
+    };
+
+    let mut ready: HashMap<i64, i64> = recorded
+        .into_iter()
+        .filter(|(interval_ms, _)| present(*interval_ms))
+        .collect();
+    if let Some(base_count) = ready.get(&thumbnails::BASE_INTERVAL_MS).copied() {
+        for interval_ms in thumbnails::pyramid_intervals() {
+            if thumbnails::level_source(interval_ms) == LevelSource::Derived {
+                ready.insert(interval_ms, thumbnails::derived_count(interval_ms, base_count));
+            }
+        }
+    }
+    Ok(ready)
+}
+
+/// Generated levels with a GenerateThumbnailLevel job queued or running
+fn pending_thumbnail_levels(job_manager: &JobManager, asset_id: i64) -> Vec<i64> {
+    thumbnails::pyramid_intervals()
+        .into_iter()
+        .filter(|interval_ms| {
+            job_manager
+                .has_active_job(&thumbnail_level_dedupe_key(asset_id, *interval_ms))
+                .unwrap_or(false)
+        })
+        .collect()
+}
+
+fn thumbnail_level_dedupe_key(asset_id: i64, interval_ms: i64) -> String {
+    format!("GenerateThumbnailLevel:{}:{}", asset_id, interval_ms)
+}
+
+/// The `index`th thumbnail of one pyramid level. A generated level that isn't ready yet is
+/// queued, and the nearest base thumbnail stands in until it is.
+async fn get_pyramid_thumbnail(
+    State((db, job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path((_project_id, asset_id, interval_ms, index)): Path<(i64, i64, i64, i64)>,
+) -> Result<Response, StatusCode> {
+    if index < 0 || !thumbnails::pyramid_intervals().contains(&interval_ms) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let dir = db
+        .get_thumbnail_dir(asset_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .map(PathBuf::from)
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let ready = ready_thumbnail_levels(&db, asset_id)?;
+    if ready.contains_key(&interval_ms) {
+        return serve_thumbnail_file(&db, asset_id, thumbnails::thumbnail_file(&dir, interval_ms, index), true).await;
+    }
+    if thumbnails::level_source(interval_ms) != LevelSource::Generated {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    let online = availability::asset_availability(&db, asset_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .is_none_or(|a| a == Availability::Online);
+    if online {
+        let payload = json!({ "media_asset_id": asset_id, "interval_ms": interval_ms });
+        let dedupe_key = thumbnail_level_dedupe_key(asset_id, interval_ms);
+        if let Err(e) = job_manager.create_job(JobType::GenerateThumbnailLevel, Some(payload), Some(dedupe_key)) {
+            eprintln!("[THUMBS] Failed to queue {}ms thumbnails for asset {}: {:?}", interval_ms, asset_id, e);
+        }
+    }
+
+    let base_index = (index * interval_ms + thumbnails::BASE_INTERVAL_MS / 2) / thumbnails::BASE_INTERVAL_MS;
+    let stand_in = thumbnails::thumbnail_file(&dir, thumbnails::BASE_INTERVAL_MS, base_index);
+    serve_thumbnail_file(&db, asset_id, stand_in, false).await
+}
+
 /// Generate thumbnails for an asset that doesn't have them yet
 async fn generate_thumbnails_for_asset(
     State((db, _job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
@@ -340,6 +996,11 @@ async fn generate_thumbnails_for_asset(
         return Ok(Json(json!({ "status": "already_exists" })));
     }
     
+    // Locked projects can't have plaintext thumbnails written for them
+    let key = db.asset_key(asset_id).map_err(|e| {
+        if crypto::is_locked_error(&e) { StatusCode::LOCKED } else { StatusCode::INTERNAL_SERVER_ERROR }
+    })?;
+
     // Generate thumbnails
     let cache_dir = PathBuf::from(".cache");
     let thumbnails_dir = cache_dir.join("thumbs").join(format!("asset_{}", asset_id));
@@ -356,72 +1017,442 @@ async fn generate_thumbnails_for_asset(
     // Store thumbnail directory in database
     db.set_thumbnail_dir(asset_id, &thumbnail_dir_path)
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let base_count = thumbnails::count_files(Path::new(&thumbnail_dir_path), "t_");
+    db.set_thumbnail_level(asset_id, thumbnails::BASE_INTERVAL_MS, base_count)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if let Some(key) = key {
+        crypto::encrypt_asset_files(&db, asset_id, &key)
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    }
+    if let Err(e) = storage::store_thumbnails(&db, asset_id, Path::new(&thumbnail_dir_path)).await {
+        eprintln!("[STORAGE] Failed to store thumbnails for asset {}: {:?}", asset_id, e);
+    }
     
     Ok(Json(json!({ "status": "success", "thumbnail_dir": thumbnail_dir_path })))
 }
 
-/// Parse Range header value (e.g., "bytes=0-1023")
-/// Returns (start, end) inclusive range, or None if invalid
-fn parse_range(range_str: &str, file_size: u64) -> Option<(u64, u64)> {
-    if !range_str.starts_with("bytes=") {
-        return None;
-    }
-
-    let range = &range_str[6..]; // Skip "bytes="
-    let parts: Vec<&str> = range.split('-').collect();
-    if parts.len() != 2 {
-        return None;
-    }
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct RegenerateProxiesRequest {
+    /// Tiers to regenerate; defaults to eager tiers plus any tier whose file is missing
+    tiers: Option<Vec<String>>,
+    /// Regenerate even if the proxy file still exists on disk
+    force: bool,
+}
 
-    let start_str = parts[0].trim();
-    let end_str = parts[1].trim();
+/// Regenerate proxies for an asset (e.g. after the cache directory was cleared)
+async fn regenerate_proxies(
+    State((db, job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path((_project_id, asset_id)): Path<(i64, i64)>,
+    body: Option<Json<RegenerateProxiesRequest>>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let request = body.map(|Json(r)| r).unwrap_or_default();
 
-    if start_str.is_empty() && end_str.is_empty() {
-        return None;
+    db.get_media_asset_path(asset_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    // Regenerating drops the current proxies, which are all there is while the original is offline
+    if availability::asset_availability(&db, asset_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .is_some_and(|a| a != Availability::Online)
+    {
+        eprintln!("[PROXY] Asset {} is offline; relink it before regenerating proxies", asset_id);
+        return Err(StatusCode::CONFLICT);
     }
 
-    let start = if start_str.is_empty() {
-        // Suffix range: "-500" means last 500 bytes
-        if file_size == 0 {
-            return None; // Can't have suffix range for empty file
+    let proxies = db
+        .list_proxies(asset_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let mut tiers: Vec<String> = match request.tiers {
+        Some(tiers) => {
+            if let Some(unknown) = tiers.iter().find(|t| proxy::find_tier(t).is_none()) {
+                eprintln!("[PROXY] Unknown proxy tier requested: {}", unknown);
+                return Err(StatusCode::BAD_REQUEST);
+            }
+            tiers
         }
-        if let Ok(suffix) = end_str.parse::<u64>() {
-            if suffix > file_size {
-                0
-            } else {
-                file_size - suffix
+        None => {
+            let mut tiers = proxy::eager_tier_names();
+            for (tier, path, _, _) in &proxies {
+                if !PathBuf::from(path).exists() && !tiers.contains(tier) {
+                    tiers.push(tier.clone());
+                }
             }
-        } else {
-            return None;
+            tiers
         }
-    } else {
-        start_str.parse::<u64>().ok()?
     };
 
-    let end = if end_str.is_empty() {
-        // Prefix range: "500-" means from byte 500 to end
-        if file_size > 0 {
-            file_size - 1
-        } else {
-            return None; // Can't have range for empty file
-        }
-    } else {
-        end_str.parse::<u64>().ok()?
-    };
+    // Skip tiers whose files are still on disk unless forced
+    if !request.force {
+        tiers.retain(|tier| {
+            !proxies
+                .iter()
+                .any(|(t, path, _, _)| t == tier && PathBuf::from(path).exists())
+        });
+    }
 
-    // Validate range
-    if file_size == 0 || start > end || end >= file_size {
-        return None;
+    if tiers.is_empty() {
+        return Ok(Json(json!({ "status": "up_to_date", "tiers": tiers })));
     }
 
-    Some((start, end))
-}
+    // Drop stale records so playback falls back to the original until regeneration finishes
+    for tier in &tiers {
+        db.delete_proxy(asset_id, tier)
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    }
+
+    let payload = json!({
+        "media_asset_id": asset_id,
+        "tiers": tiers,
+    });
+    let job_id = job_manager
+        .create_job(JobType::GenerateProxy, Some(payload), None)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(json!({ "status": "queued", "job_id": job_id, "tiers": tiers })))
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct RelinkRequest {
+    /// New local path for the original; when omitted, a remote asset is re-fetched from its origin
+    path: Option<String>,
+}
+
+/// Point an asset back at its original media: either a new local path, or a fresh download
+/// from the s3:// / gs:// URL it was imported from
+async fn relink_media_asset(
+    State((db, _job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path((project_id, asset_id)): Path<(i64, i64)>,
+    body: Option<Json<RelinkRequest>>,
+) -> Result<Json<serde_json::Value>, PathError> {
+    let request = body.map(|Json(r)| r).unwrap_or_default();
+
+    db.get_media_asset_path(asset_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let remote_url = db
+        .get_media_asset_remote_url(asset_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let new_path = if let Some(path) = request.path {
+        let path = roots::resolve(&path)?;
+        if !path.is_file() {
+            eprintln!("[RELINK] File not found: {}", path.display());
+            return Err(StatusCode::BAD_REQUEST.into());
+        }
+        path.to_string_lossy().to_string()
+    } else if let Some(object) = remote_url.as_deref().and_then(remote::RemoteObject::parse) {
+        let project = db
+            .get_project(project_id)
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+            .ok_or(StatusCode::NOT_FOUND)?;
+        let dest = object.cache_path(std::path::Path::new(&project.cache_dir));
+        if let Err(e) = remote::download(&object, &dest).await {
+            eprintln!("[RELINK] Failed to fetch {}: {:?}", object.url(), e);
+            return Err(StatusCode::BAD_GATEWAY.into());
+        }
+        dest.to_string_lossy().to_string()
+    } else {
+        eprintln!("[RELINK] Asset {} has no remote origin; a path is required", asset_id);
+        return Err(StatusCode::BAD_REQUEST.into());
+    };
+
+    db.set_media_asset_path(asset_id, &new_path)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    db.set_media_asset_availability(asset_id, Availability::Online.as_str())
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    // The new file hasn't been verified or measured yet
+    db.set_media_asset_integrity(asset_id, None, None)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    db.set_media_asset_sync_drift(asset_id, None)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(json!({ "status": "relinked", "path": new_path, "remote_url": remote_url })))
+}
+
+/// Check an asset's file for changes made in place (e.g. a re-export over the original); if it
+/// changed, the analysis of unchanged content is kept and only the rest is analyzed again
+async fn refresh_media_asset(
+    State((db, job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path((project_id, asset_id)): Path<(i64, i64)>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let in_project = db
+        .get_asset_project_id(asset_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        == Some(project_id);
+    if !in_project {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    if availability::asset_availability(&db, asset_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .is_some_and(|a| a != Availability::Online)
+    {
+        eprintln!("[REANALYZE] Asset {} is offline; relink it before refreshing", asset_id);
+        return Err(StatusCode::CONFLICT);
+    }
+
+    let job_id = reanalyze::queue_reanalysis(&job_manager, asset_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(json!({ "job_id": job_id })))
+}
+
+#[derive(Deserialize)]
+struct SetAvailabilityRequest {
+    availability: Availability,
+}
+
+/// Mark an asset's original offline or archived (editing continues against its proxies), or
+/// back online once the file is reachable at its path again
+async fn set_media_availability(
+    State((db, _job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path((project_id, asset_id)): Path<(i64, i64)>,
+    Json(req): Json<SetAvailabilityRequest>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let path = db
+        .get_media_asset_availabilities(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .into_iter()
+        .find(|(id, _, _)| *id == asset_id)
+        .map(|(_, path, _)| path)
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    if req.availability == Availability::Online && !PathBuf::from(&path).is_file() {
+        eprintln!("[MEDIA] Can't mark asset {} online; {} is missing (relink it instead)", asset_id, path);
+        return Err(StatusCode::CONFLICT);
+    }
+    db.set_media_asset_availability(asset_id, req.availability.as_str())
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(json!({ "asset_id": asset_id, "path": path, "availability": req.availability })))
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct VerifyChecksumsRequest {
+    /// Assets to verify (default: every online original in the project)
+    asset_ids: Option<Vec<i64>>,
+}
+
+/// Queue a re-hash of the project's originals against their stored checksums; mismatches are
+/// flagged on the assets and reported in the project's conversation
+async fn verify_checksums(
+    State((db, job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path(project_id): Path<i64>,
+    body: Option<Json<VerifyChecksumsRequest>>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let request = body.map(|Json(r)| r).unwrap_or_default();
+    db.get_project(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    if let Some(asset_ids) = &request.asset_ids {
+        let (outside, _) = db
+            .ids_outside_project(project_id, asset_ids, &[])
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        if !outside.is_empty() {
+            eprintln!("[INTEGRITY] Assets {:?} aren't in project {}", outside, project_id);
+            return Err(StatusCode::BAD_REQUEST);
+        }
+    }
+
+    let job_id = integrity::queue_verification(&job_manager, project_id, request.asset_ids)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(json!({ "job_id": job_id })))
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct DetectSyncDriftRequest {
+    /// Assets to measure (default: every online video in the project)
+    asset_ids: Option<Vec<i64>>,
+    /// Turn on sync correction for drifting assets and re-render their proxies
+    correct: bool,
+}
+
+/// Queue an A/V sync measurement of the project's videos; drifting assets are flagged in the
+/// media list
+async fn detect_sync_drift(
+    State((db, job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path(project_id): Path<i64>,
+    body: Option<Json<DetectSyncDriftRequest>>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let request = body.map(|Json(r)| r).unwrap_or_default();
+    db.get_project(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    if let Some(asset_ids) = &request.asset_ids {
+        let (outside, _) = db
+            .ids_outside_project(project_id, asset_ids, &[])
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        if !outside.is_empty() {
+            eprintln!("[SYNC] Assets {:?} aren't in project {}", outside, project_id);
+            return Err(StatusCode::BAD_REQUEST);
+        }
+    }
+
+    let job_id = sync_drift::queue_detection(&job_manager, project_id, request.asset_ids, request.correct)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(json!({ "job_id": job_id })))
+}
+
+#[derive(Deserialize)]
+struct SyncCorrectionRequest {
+    enabled: bool,
+}
+
+/// Turn resampling an asset's audio in its proxies on or off, and re-render its proxies
+async fn set_sync_correction(
+    State((db, job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path((project_id, asset_id)): Path<(i64, i64)>,
+    Json(req): Json<SyncCorrectionRequest>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let (outside, _) = db
+        .ids_outside_project(project_id, &[asset_id], &[])
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if !outside.is_empty() {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    let drift: Option<SyncDrift> = db
+        .get_media_asset_sync_drift(asset_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .and_then(|(json, _)| serde_json::from_str(&json).ok());
+    // Only a measured drift can be corrected
+    if req.enabled && drift.as_ref().is_none_or(|d| d.status != SyncStatus::Drifting) {
+        eprintln!("[SYNC] Asset {} has no measured drift to correct", asset_id);
+        return Err(StatusCode::CONFLICT);
+    }
+
+    db.set_media_asset_sync_correction(asset_id, req.enabled)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    // Waits in the queue while the original is offline; the current proxies keep playing
+    let job_id = sync_drift::queue_proxy_refresh(&db, &job_manager, asset_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(json!({ "asset_id": asset_id, "sync_correction": req.enabled, "job_id": job_id })))
+}
+
+/// Re-check every original in the project against the disk (e.g. after plugging a drive back
+/// in); returns the assets whose availability changed
+async fn check_media_availability(
+    State((db, _job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path(project_id): Path<i64>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    db.get_project(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let changed: Vec<serde_json::Value> = availability::refresh_project(&db, project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .into_iter()
+        .map(|(asset_id, availability)| json!({ "asset_id": asset_id, "availability": availability }))
+        .collect();
+
+    Ok(Json(json!({ "changed": changed })))
+}
+
+/// Result of parsing a Range header against a file of known size
+#[derive(Debug, PartialEq)]
+enum RangeSpec {
+    /// Header missing or malformed - serve the full file
+    Ignored,
+    /// Well-formed but no range overlaps the file - respond 416
+    Unsatisfiable,
+    /// Satisfiable ranges, sorted and coalesced, inclusive (start, end)
+    Ranges(Vec<(u64, u64)>),
+}
+
+/// Parse Range header value (e.g., "bytes=0-1023" or "bytes=0-99,200-299,-500")
+fn parse_ranges(range_str: &str, file_size: u64) -> RangeSpec {
+    let range_str = range_str.trim();
+    if !range_str.starts_with("bytes=") {
+        return RangeSpec::Ignored;
+    }
+
+    let mut ranges: Vec<(u64, u64)> = Vec::new();
+    for spec in range_str[6..].split(',') {
+        let spec = spec.trim();
+        if spec.is_empty() {
+            continue;
+        }
+        match parse_range(spec, file_size) {
+            Ok(Some(range)) => ranges.push(range),
+            Ok(None) => {} // Valid syntax but outside the file
+            Err(()) => return RangeSpec::Ignored,
+        }
+    }
+
+    if ranges.is_empty() {
+        return RangeSpec::Unsatisfiable;
+    }
+
+    // Coalesce overlapping/adjacent ranges so we never send the same bytes twice
+    ranges.sort_by_key(|r| r.0);
+    let mut merged: Vec<(u64, u64)> = Vec::with_capacity(ranges.len());
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 + 1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+
+    RangeSpec::Ranges(merged)
+}
+
+/// Parse a single range spec (without the "bytes=" prefix, e.g. "0-1023")
+/// Returns Err for malformed specs, Ok(None) for unsatisfiable ones, and
+/// Ok(Some((start, end))) with an inclusive end clamped to the file size
+fn parse_range(spec: &str, file_size: u64) -> Result<Option<(u64, u64)>, ()> {
+    let parts: Vec<&str> = spec.split('-').collect();
+    if parts.len() != 2 {
+        return Err(());
+    }
+
+    let start_str = parts[0].trim();
+    let end_str = parts[1].trim();
+
+    if start_str.is_empty() && end_str.is_empty() {
+        return Err(());
+    }
+
+    if start_str.is_empty() {
+        // Suffix range: "-500" means last 500 bytes
+        let suffix = end_str.parse::<u64>().map_err(|_| ())?;
+        if suffix == 0 || file_size == 0 {
+            return Ok(None);
+        }
+        return Ok(Some((file_size.saturating_sub(suffix), file_size - 1)));
+    }
+
+    let start = start_str.parse::<u64>().map_err(|_| ())?;
+    let end = if end_str.is_empty() {
+        // Prefix range: "500-" means from byte 500 to end
+        None
+    } else {
+        Some(end_str.parse::<u64>().map_err(|_| ())?)
+    };
+
+    if let Some(end) = end {
+        if start > end {
+            return Err(());
+        }
+    }
+
+    if start >= file_size {
+        return Ok(None);
+    }
+
+    let last = file_size - 1;
+    Ok(Some((start, end.map(|e| e.min(last)).unwrap_or(last))))
+}
 
 async fn import_raw(
     State((db, job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
     Path(project_id): Path<i64>,
     Json(req): Json<ImportRawRequest>,
-) -> Result<Json<ImportRawResponse>, StatusCode> {
+) -> Result<Json<ImportRawResponse>, PathError> {
     // Verify project exists
     let _project = db
         .get_project(project_id)
@@ -431,7 +1462,7 @@ async fn import_raw(
     // Validate that at least one field is provided
     if req.file_paths.is_none() && req.folder_path.is_none() {
         eprintln!("Import request missing both file_paths and folder_path");
-        return Err(StatusCode::BAD_REQUEST);
+        return Err(StatusCode::BAD_REQUEST.into());
     }
 
     // Debug logging
@@ -440,45 +1471,20 @@ async fn import_raw(
     // Handle individual file paths - create a separate job for each file
     if let Some(file_paths) = req.file_paths {
         if file_paths.is_empty() {
-            return Err(StatusCode::BAD_REQUEST);
+            return Err(StatusCode::BAD_REQUEST.into());
         }
+        // Every path is checked before any job is queued
+        let file_paths = file_paths
+            .iter()
+            .map(|path| import_source(path))
+            .collect::<Result<Vec<_>, _>>()?;
 
+        // Don't filter by existence here - let the job handle it
         let mut job_ids = Vec::new();
-        let db_clone = db.clone();
-        let job_manager_clone = job_manager.clone();
-
-        // Create a separate job for each file (don't filter by existence here - let the job handle it)
         for file_path_str in file_paths {
-            let video_path = PathBuf::from(&file_path_str);
-            let job_payload = json!({
-                "project_id": project_id,
-                "file_path": file_path_str,
-            });
-
-            let job_id = job_manager
-                .create_job(JobType::ImportRaw, Some(job_payload), None)
-                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-
+            let job_id = start_file_import(db.clone(), job_manager.clone(), project_id, &file_path_str)
+                .map_err(|e| quotas::error_status(&e))?;
             job_ids.push(job_id);
-
-            // Spawn async task to process this single file
-            let db_task = db_clone.clone();
-            let job_manager_task = job_manager_clone.clone();
-            let path_for_task = video_path.clone();
-
-            tokio::spawn(async move {
-                if let Err(e) = process_single_file_import(
-                    db_task,
-                    job_manager_task.clone(),
-                    job_id,
-                    path_for_task,
-                )
-                .await
-                {
-                    eprintln!("Import job {} failed: {:?}", job_id, e);
-                    let _ = job_manager_task.update_job_status(job_id, crate::jobs::JobStatus::Failed, Some(0.0));
-                }
-            });
         }
 
         // Return the first job_id for backward compatibility, and all job_ids
@@ -488,44 +1494,136 @@ async fn import_raw(
         }))
     } else if let Some(folder_path) = req.folder_path {
         // Folder scanning mode - single job for all files in folder
-        let job_payload = json!({
-            "project_id": project_id,
-            "folder_path": folder_path,
-        });
+        let folder_path = import_source(&folder_path)?;
+        let job_id = start_folder_import(db, job_manager, project_id, &folder_path)
+            .map_err(|e| quotas::error_status(&e))?;
 
-        let job_id = job_manager
-            .create_job(JobType::ImportRaw, Some(job_payload), None)
-            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        Ok(Json(ImportRawResponse {
+            job_id,
+            job_ids: None,
+        }))
+    } else {
+        Err(StatusCode::BAD_REQUEST.into())
+    }
+}
+
+/// Create an ImportRaw job for one file and process it in the background
+pub fn start_file_import(
+    db: Arc<Database>,
+    job_manager: Arc<JobManager>,
+    project_id: i64,
+    file_path: &str,
+) -> anyhow::Result<i64> {
+    let job_payload = json!({
+        "project_id": project_id,
+        "file_path": file_path,
+    });
+    let job_id = job_manager.create_job(JobType::ImportRaw, Some(job_payload), None)?;
 
-        // Spawn async task to process import
-        let db_clone = db.clone();
-        let job_manager_clone = job_manager.clone();
-        let folder = PathBuf::from(&folder_path);
-        
-        tokio::spawn(async move {
+    let file_path = file_path.to_string();
+    tokio::spawn(async move {
+        let result = if remote::is_remote_url(&file_path) {
+            process_remote_file_import(db, job_manager.clone(), job_id, project_id, &file_path).await
+        } else {
+            process_single_file_import(db, job_manager.clone(), job_id, PathBuf::from(file_path)).await
+        };
+        if let Err(e) = result {
+            eprintln!("Import job {} failed: {:?}", job_id, e);
+            let _ = job_manager.update_job_status(job_id, crate::jobs::JobStatus::Failed, Some(0.0));
+        }
+    });
+
+    Ok(job_id)
+}
+
+/// Create a single ImportRaw job for every video in a folder and process it in the background
+pub fn start_folder_import(
+    db: Arc<Database>,
+    job_manager: Arc<JobManager>,
+    project_id: i64,
+    folder_path: &str,
+) -> anyhow::Result<i64> {
+    let job_payload = json!({
+        "project_id": project_id,
+        "folder_path": folder_path,
+    });
+    let job_id = job_manager.create_job(JobType::ImportRaw, Some(job_payload), None)?;
+
+    let folder_path = folder_path.to_string();
+    tokio::spawn(async move {
+        let result = if remote::is_remote_url(&folder_path) {
+            process_remote_import(db, job_manager.clone(), job_id, project_id, &folder_path).await
+        } else {
+            let folder = PathBuf::from(folder_path);
             if !folder.exists() {
                 return;
             }
-            if let Err(e) = process_import(
-                db_clone,
-                job_manager_clone.clone(),
-                job_id,
-                folder,
-            )
-            .await
-            {
-                eprintln!("Import job {} failed: {:?}", job_id, e);
-                let _ = job_manager_clone.update_job_status(job_id, crate::jobs::JobStatus::Failed, Some(0.0));
-            }
-        });
+            process_import(db, job_manager.clone(), job_id, folder).await
+        };
+        if let Err(e) = result {
+            eprintln!("Import job {} failed: {:?}", job_id, e);
+            let _ = job_manager.update_job_status(job_id, crate::jobs::JobStatus::Failed, Some(0.0));
+        }
+    });
 
-        Ok(Json(ImportRawResponse {
+    Ok(job_id)
+}
+
+/// Import one s3:// or gs:// object: download it into the project cache, then import the local
+/// copy and remember where it came from
+async fn process_remote_file_import(
+    db: Arc<Database>,
+    job_manager: Arc<JobManager>,
+    job_id: i64,
+    project_id: i64,
+    url: &str,
+) -> anyhow::Result<()> {
+    job_manager.update_job_status(job_id, crate::jobs::JobStatus::Running, Some(0.0))?;
+    let cache_dir = project_cache_dir(&db, project_id)?;
+    let local_path = remote::fetch_to_cache(url, &cache_dir).await?;
+    let asset_id = process_single_video(db.clone(), job_manager.clone(), job_id, project_id, &local_path, 0, 1).await?;
+    db.set_media_asset_remote_url(asset_id, url)?;
+    job_manager.update_job_status(job_id, crate::jobs::JobStatus::Completed, Some(1.0))?;
+    Ok(())
+}
+
+/// Import every video under an s3:// or gs:// prefix, downloading each into the project cache
+async fn process_remote_import(
+    db: Arc<Database>,
+    job_manager: Arc<JobManager>,
+    job_id: i64,
+    project_id: i64,
+    prefix_url: &str,
+) -> anyhow::Result<()> {
+    job_manager.update_job_status(job_id, crate::jobs::JobStatus::Running, Some(0.0))?;
+    let cache_dir = project_cache_dir(&db, project_id)?;
+    let urls = remote::list_videos(prefix_url).await?;
+
+    let total_files = urls.len();
+    for (idx, url) in urls.iter().enumerate() {
+        let local_path = remote::fetch_to_cache(url, &cache_dir).await?;
+        let asset_id = process_single_video(
+            db.clone(),
+            job_manager.clone(),
             job_id,
-            job_ids: None,
-        }))
-    } else {
-        Err(StatusCode::BAD_REQUEST)
+            project_id,
+            &local_path,
+            idx,
+            total_files,
+        )
+        .await?;
+        db.set_media_asset_remote_url(asset_id, url)?;
     }
+
+    job_manager.update_job_status(job_id, crate::jobs::JobStatus::Completed, Some(1.0))?;
+    Ok(())
+}
+
+fn project_cache_dir(db: &Database, project_id: i64) -> anyhow::Result<PathBuf> {
+    let project = db
+        .get_project(project_id)?
+        .ok_or_else(|| anyhow::anyhow!("Project {} not found", project_id))?;
+    Ok(PathBuf::from(project.cache_dir))
 }
 
 /// Process a single file import (one file per job)
@@ -561,7 +1659,6 @@ async fn process_single_file_import(
         &video_path,
         0,
         1, // Only one file in this job
-        false, // Not a reference
     )
     .await?;
 
@@ -570,23 +1667,61 @@ async fn process_single_file_import(
 }
 
 /// Process a single video file
-async fn process_single_video(
+pub(crate) async fn process_single_video(
     db: Arc<Database>,
     job_manager: Arc<JobManager>,
     job_id: i64,
     project_id: i64,
-    video_path: &PathBuf,
+    video_path: &std::path::Path,
     idx: usize,
     total_files: usize,
+) -> anyhow::Result<i64> {
+    let media_info = FFmpegWrapper::probe(video_path).await?;
+    let asset_id = register_video(&db, &job_manager, project_id, video_path, media_info, false, None).await?;
+
+    // Update progress
+    let progress = (idx + 1) as f64 / total_files as f64;
+    job_manager.update_job_status(job_id, crate::jobs::JobStatus::Running, Some(progress))?;
+
+    Ok(asset_id)
+}
+
+/// Register a probed file as a media asset and queue its proxies and analysis. With an
+/// `import_rank`, its analysis is deferred behind other work (see `jobs::priority`).
+async fn register_video(
+    db: &Database,
+    job_manager: &JobManager,
+    project_id: i64,
+    video_path: &std::path::Path,
+    mut media_info: MediaInfo,
     is_reference: bool,
-) -> anyhow::Result<()> {
+    import_rank: Option<i64>,
+) -> anyhow::Result<i64> {
     // Compute checksum
     let checksum: Option<String> = compute_file_checksum(video_path)
         .await
         .ok();
 
-    // Probe media
-    let media_info = FFmpegWrapper::probe(video_path).await?;
+    // Importing a file that's already in the project: unchanged, it's left alone; replaced
+    // (e.g. re-exported after a trim), only what changed in it is analyzed again
+    if let Some((asset_id, stored)) = db.find_media_asset_by_path(project_id, video_path.to_str().unwrap())? {
+        if checksum.is_some() && checksum == stored {
+            eprintln!("[IMPORT] {} is already imported and unchanged", video_path.display());
+        } else {
+            eprintln!("[IMPORT] {} changed since it was imported; re-analyzing what changed", video_path.display());
+            reanalyze::queue_reanalysis(job_manager, asset_id)?;
+        }
+        return Ok(asset_id);
+    }
+
+    // Photos become stills of a fixed length, played at a fixed rate
+    let is_still = still::is_image_path(video_path);
+    if is_still {
+        media_info.duration_ticks = still::STILL_DURATION_TICKS;
+        media_info.fps_num = still::STILL_FPS;
+        media_info.fps_den = 1;
+        media_info.has_audio = false;
+    }
 
     // Register media asset with project_id
     let asset_id = db.create_media_asset_with_reference_flag(
@@ -601,11 +1736,21 @@ async fn process_single_video(
         media_info.has_audio,
         is_reference,
     )?;
+    if is_still {
+        db.set_media_asset_still(asset_id)?;
+    }
+    if media_info.rotation != 0 {
+        db.set_media_asset_rotation(asset_id, media_info.rotation)?;
+    }
+    if let Some(rank) = import_rank {
+        db.set_media_asset_import_rank(asset_id, rank)?;
+    }
 
     // Queue proxy generation job
     let proxy_job_payload = json!({
         "media_asset_id": asset_id,
         "input_path": video_path.to_str().unwrap(),
+        "tiers": crate::media::proxy::eager_tier_names(),
     });
     let _proxy_job_id = job_manager.create_job(JobType::GenerateProxy, Some(proxy_job_payload), None)?;
 
@@ -615,12 +1760,28 @@ async fn process_single_video(
     });
     let _build_segments_id = job_manager.create_job(JobType::BuildSegments, Some(build_segments_payload), None)?;
 
-    // Queue transcription job (runs in parallel)
-    let transcribe_job_payload = json!({
-        "asset_id": asset_id,
-        "media_path": video_path.to_str().unwrap(),
-    });
-    let _transcribe_job_id = job_manager.create_job(JobType::TranscribeAsset, Some(transcribe_job_payload), None)?;
+    if is_still {
+        // Nothing to transcribe: record an empty transcript so enrichment doesn't wait for one
+        db.store_asset_transcript(asset_id, &json!({ "segments": [] }).to_string())?;
+        db.update_asset_analysis_state(asset_id, "transcript_ready_at", None)?;
+        let enrich_payload = json!({
+            "asset_id": asset_id,
+        });
+        let _enrich_job_id = job_manager.create_job(JobType::EnrichSegmentsFromTranscript, Some(enrich_payload), None)?;
+
+        // Find what the still's Ken Burns motion should move towards
+        let subjects_payload = json!({
+            "asset_id": asset_id,
+        });
+        let _subjects_job_id = job_manager.create_job(JobType::DetectStillSubjects, Some(subjects_payload), None)?;
+    } else {
+        // Queue transcription job (runs in parallel)
+        let transcribe_job_payload = json!({
+            "asset_id": asset_id,
+            "media_path": video_path.to_str().unwrap(),
+        });
+        let _transcribe_job_id = job_manager.create_job(JobType::TranscribeAsset, Some(transcribe_job_payload), None)?;
+    }
 
     // Queue vision analysis job (runs in parallel)
     let vision_job_payload = json!({
@@ -629,19 +1790,27 @@ async fn process_single_video(
     });
     let _vision_job_id = job_manager.create_job(JobType::AnalyzeVisionAsset, Some(vision_job_payload), None)?;
 
-    // Queue TwelveLabs indexing job (will wait for embeddings to be ready via prerequisites)
-    let twelvelabs_index_payload = json!({
-        "asset_id": asset_id,
-        "project_id": project_id,
-    });
-    let dedupe_key = format!("IndexAssetWithTwelveLabs:{}", asset_id);
-    let _twelvelabs_index_job_id = job_manager.create_job(JobType::IndexAssetWithTwelveLabs, Some(twelvelabs_index_payload), Some(dedupe_key))?;
+    // Queue audio event detection (waits for segments via prerequisites)
+    if media_info.has_audio {
+        let audio_events_payload = json!({
+            "asset_id": asset_id,
+            "media_path": video_path.to_str().unwrap(),
+        });
+        let _audio_events_job_id = job_manager.create_job(JobType::AnalyzeAudioEvents, Some(audio_events_payload), None)?;
+    }
 
-    // Update progress
-    let progress = (idx + 1) as f64 / total_files as f64;
-    job_manager.update_job_status(job_id, crate::jobs::JobStatus::Running, Some(progress))?;
+    // Queue TwelveLabs indexing job (will wait for embeddings to be ready via prerequisites);
+    // TwelveLabs only indexes video
+    if !is_still {
+        let twelvelabs_index_payload = json!({
+            "asset_id": asset_id,
+            "project_id": project_id,
+        });
+        let dedupe_key = format!("IndexAssetWithTwelveLabs:{}", asset_id);
+        let _twelvelabs_index_job_id = job_manager.create_job(JobType::IndexAssetWithTwelveLabs, Some(twelvelabs_index_payload), Some(dedupe_key))?;
+    }
 
-    Ok(())
+    Ok(asset_id)
 }
 
 async fn process_import(
@@ -659,10 +1828,9 @@ async fn process_import(
         .and_then(|p| p.get("project_id").and_then(|v| v.as_i64()))
         .ok_or_else(|| anyhow::anyhow!("Missing project_id in job payload"))?;
 
-    // Video file extensions
-    let video_extensions: &[&str] = &["mp4", "mov", "avi", "mkv", "m4v", "webm"];
+    let video_extensions = remote::VIDEO_EXTENSIONS;
 
-    // Scan for video files
+    // Scan for video files and photos
     let mut video_files = Vec::new();
     if folder_path.is_dir() {
         let mut entries = tokio::fs::read_dir(&folder_path).await?;
@@ -671,8 +1839,12 @@ async fn process_import(
             if path.is_file() {
                 if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
                     let ext_lower = ext.to_lowercase();
-                    if video_extensions.contains(&ext_lower.as_str()) {
-                        video_files.push(path);
+                    if video_extensions.contains(&ext_lower.as_str()) || still::is_image_path(&path) {
+                        // A symlink in the folder may point outside the allowed roots
+                        match roots::resolve(&path) {
+                            Ok(path) => video_files.push(path),
+                            Err(e) => eprintln!("[ROOTS] Skipping {}", e),
+                        }
                     }
                 }
             }
@@ -680,99 +1852,46 @@ async fn process_import(
     }
 
     let total_files = video_files.len();
-    for (idx, video_path) in video_files.iter().enumerate() {
-        process_single_video(
-            db.clone(),
-            job_manager.clone(),
-            job_id,
-            project_id,
-            video_path,
-            idx,
-            total_files,
-            false, // Not a reference
-        )
-        .await?;
+    let policy = ImportPolicy::from_env();
+    if !policy.defers(total_files) {
+        for (idx, video_path) in video_files.iter().enumerate() {
+            process_single_video(
+                db.clone(),
+                job_manager.clone(),
+                job_id,
+                project_id,
+                video_path,
+                idx,
+                total_files,
+            )
+            .await?;
+        }
+        job_manager.update_job_status(job_id, crate::jobs::JobStatus::Completed, Some(1.0))?;
+        return Ok(());
     }
 
-    job_manager.update_job_status(job_id, crate::jobs::JobStatus::Completed, Some(1.0))?;
-    Ok(())
-}
+    // Bulk import: probe everything, then import in analysis order. The first few assets are
+    // analyzed right away; the rest wait behind them with their rank in the order.
+    let mut probed = Vec::with_capacity(total_files);
+    for (idx, video_path) in video_files.into_iter().enumerate() {
+        let media_info = FFmpegWrapper::probe(&video_path).await?;
+        probed.push((video_path, media_info));
+        let progress = 0.1 * (idx + 1) as f64 / total_files as f64;
+        job_manager.update_job_status(job_id, crate::jobs::JobStatus::Running, Some(progress))?;
+    }
+    policy.sort(&mut probed, |(path, info)| (path.as_path(), info.creation_time.as_deref()));
+    eprintln!(
+        "[IMPORT] Bulk import of {} files: analyzing {} first, then the rest by {:?} order",
+        total_files, policy.eager_assets, policy.order
+    );
+
+    for (idx, (video_path, media_info)) in probed.into_iter().enumerate() {
+        let import_rank = idx.checked_sub(policy.eager_assets).map(|rank| rank as i64);
+        register_video(&db, &job_manager, project_id, &video_path, media_info, false, import_rank).await?;
+        let progress = 0.1 + 0.9 * (idx + 1) as f64 / total_files as f64;
+        job_manager.update_job_status(job_id, crate::jobs::JobStatus::Running, Some(progress))?;
+    }
 
-/// Process proxy generation job with thumbnail extraction
-/// This function generates a proxy video and extracts thumbnails for a media asset
-pub async fn process_proxy_generation_with_thumbnails(
-    db: Arc<Database>,
-    job_manager: Arc<JobManager>,
-    job_id: i64,
-    media_asset_id: i64,
-    input_path: &str,
-) -> anyhow::Result<()> {
-    use std::path::Path;
-    
-    // Get media asset info to determine proxy dimensions
-    let asset_path = db.get_media_asset_path(media_asset_id)?
-        .ok_or_else(|| anyhow::anyhow!("Media asset not found"))?;
-    
-    // Probe to get dimensions
-    let media_info = FFmpegWrapper::probe(Path::new(&asset_path)).await?;
-    
-    // Calculate proxy dimensions (scale down if large)
-    let proxy_width = if media_info.width > 1920 { 1920 } else { media_info.width };
-    let proxy_height = if media_info.height > 1080 { 1080 } else { media_info.height };
-    
-    // Determine proxy output path
-    let cache_dir = PathBuf::from(".cache");
-    let proxies_dir = cache_dir.join("proxies");
-    tokio::fs::create_dir_all(&proxies_dir).await?;
-    
-    let proxy_filename = format!("proxy_{}.mp4", media_asset_id);
-    let proxy_path = proxies_dir.join(&proxy_filename);
-    
-    // Generate proxy
-    job_manager.update_job_status(
-        job_id,
-        crate::jobs::JobStatus::Running,
-        Some(0.3),
-    )?;
-    
-    FFmpegWrapper::generate_proxy(
-        Path::new(input_path),
-        &proxy_path,
-        proxy_width,
-        proxy_height,
-    ).await?;
-    
-    // Store proxy path in database
-    db.create_proxy(
-        media_asset_id,
-        proxy_path.to_str().unwrap(),
-        "libx264",
-        proxy_width,
-        proxy_height,
-    )?;
-    
-    // Generate thumbnails
-    job_manager.update_job_status(
-        job_id,
-        crate::jobs::JobStatus::Running,
-        Some(0.7),
-    )?;
-    
-    let thumbnails_dir = cache_dir.join("thumbs").join(format!("asset_{}", media_asset_id));
-    let thumbnail_dir_path = FFmpegWrapper::extract_thumbnails(
-        Path::new(input_path),
-        &thumbnails_dir,
-    ).await?;
-    
-    // Store thumbnail directory in database
-    db.set_thumbnail_dir(media_asset_id, &thumbnail_dir_path)?;
-    
-    // Mark job as completed
-    job_manager.update_job_status(
-        job_id,
-        crate::jobs::JobStatus::Completed,
-        Some(1.0),
-    )?;
-    
+    job_manager.update_job_status(job_id, crate::jobs::JobStatus::Completed, Some(1.0))?;
     Ok(())
 }
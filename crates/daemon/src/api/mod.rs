@@ -1,30 +1,84 @@
-use axum::Router;
+use axum::{middleware, Router};
 use std::sync::Arc;
 
 use crate::db::Database;
 use crate::jobs::JobManager;
 
+pub mod brand;
+pub mod collections;
+pub mod companion;
+pub mod data;
+pub mod encryption;
+pub mod end_cards;
 pub mod export;
 pub mod generate;
+pub mod interview;
 pub mod jobs;
+pub mod library;
+pub mod lower_thirds;
 pub mod media;
+pub mod multicam;
+pub mod music;
 pub mod orchestrator;
 pub mod orchestrator_helper;
+pub mod persons;
+pub mod plugins;
 pub mod projects;
+pub mod quotas;
+pub mod redactions;
+pub mod remote_workers;
+pub mod search;
+pub mod segments;
 pub mod style;
+pub mod templates;
 pub mod timeline;
+pub mod uploads;
+pub mod voiceovers;
+pub mod webhooks;
 
 pub fn router(db: Arc<Database>, job_manager: Arc<JobManager>) -> Router {
     Router::new()
         .nest("/projects", {
             Router::new()
                 .merge(projects::router(db.clone()))
+                .merge(segments::router(db.clone()))
+                .merge(collections::router(db.clone()))
+                .merge(companion::router(db.clone()))
                 .merge(media::router(db.clone(), job_manager.clone()))
                 .merge(style::router(db.clone(), job_manager.clone()))
                 .merge(generate::router(db.clone()))
-                .merge(timeline::router(db.clone()))
+                .merge(timeline::router(db.clone(), job_manager.clone()))
                 .merge(orchestrator::router(db.clone(), job_manager.clone()))
-                .merge(export::router(db, job_manager.clone()))
+                .merge(export::router(db.clone(), job_manager.clone()))
+                .merge(uploads::router(db.clone(), job_manager.clone()))
+                .merge(webhooks::router(db.clone()))
+                .merge(plugins::router(db.clone()))
+                .merge(encryption::router(db.clone()))
+                .merge(redactions::router(db.clone(), job_manager.clone()))
+                .merge(data::router(db.clone()))
+                .merge(multicam::router(db.clone(), job_manager.clone()))
+                .merge(voiceovers::router(db.clone(), job_manager.clone()))
+                .merge(end_cards::project_router(db.clone()))
+                .merge(brand::router(db.clone()))
+                .merge(persons::router(db.clone()))
+                .merge(lower_thirds::router(db.clone()))
+                .merge(interview::router(db.clone()))
+                .merge(library::project_router(db.clone()))
+                .merge(quotas::project_router(db.clone()))
+                .layer(middleware::from_fn_with_state(db.clone(), quotas::enforce))
         })
-        .nest("/jobs", jobs::router(job_manager))
+        .nest("/search", search::router(db.clone()))
+        .nest("/style", style::profiles_router(db.clone()))
+        .nest("/templates", templates::router(db.clone()))
+        .nest("/music", music::router(db.clone(), job_manager.clone()))
+        .nest("/library", library::router(db.clone()))
+        .nest("/tokens", quotas::router(db.clone()))
+        .nest("/end_cards", end_cards::router(db.clone()))
+        .nest("/plugins", plugins::plugins_router())
+        .nest("/export", export::presets_router())
+        .nest("/uploads", uploads::credentials_router(db.clone()))
+        .nest("/companion", companion::device_router(db.clone(), job_manager.clone()))
+        .nest("/remote_workers", remote_workers::router(db.clone(), job_manager.clone()))
+        .nest("/queue", remote_workers::queue_router(db.clone(), job_manager.clone()))
+        .nest("/jobs", jobs::router(db, job_manager))
 }
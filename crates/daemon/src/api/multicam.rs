@@ -0,0 +1,136 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+    routing::{delete, get, post},
+    Router,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Arc;
+
+use super::timeline::{ApplyError, ApplyOperationsRequest, TimelineResponse};
+use crate::db::{Database, MulticamGroup};
+use crate::jobs::{JobManager, JobType};
+use engine::ops::{MulticamAngle, TimelineOperation};
+
+#[derive(Deserialize)]
+pub struct SyncMulticamRequest {
+    /// Clips of the same event, from different cameras or phones
+    asset_ids: Vec<i64>,
+    name: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct SyncMulticamResponse {
+    job_id: i64,
+}
+
+#[derive(Deserialize)]
+pub struct SwitchAngleRequest {
+    position_ticks: i64,
+    /// Angle to cut to
+    asset_id: i64,
+}
+
+pub fn router(db: Arc<Database>, job_manager: Arc<JobManager>) -> Router {
+    Router::new()
+        .route("/:id/multicam", get(list_groups))
+        .route("/:id/multicam/sync", post(sync_multicam))
+        .route("/:id/multicam/:gid", delete(delete_group))
+        .route("/:id/multicam/:gid/switch", post(switch_angle))
+        .with_state((db, job_manager))
+}
+
+/// POST /projects/:id/multicam/sync - Queue an audio sync of the clips. The job result holds the
+/// new group's id and the clips whose audio didn't match.
+async fn sync_multicam(
+    State((db, job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path(project_id): Path<i64>,
+    Json(req): Json<SyncMulticamRequest>,
+) -> Result<Json<SyncMulticamResponse>, StatusCode> {
+    db.get_project(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let mut asset_ids = req.asset_ids.clone();
+    asset_ids.sort_unstable();
+    asset_ids.dedup();
+    if asset_ids.len() < 2 {
+        eprintln!("[MULTICAM] At least two clips are needed to sync");
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let project_assets = db
+        .get_media_assets_for_project(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if let Some(missing) = asset_ids.iter().find(|id| !project_assets.iter().any(|a| a.id == **id)) {
+        eprintln!("[MULTICAM] Asset {} not found in project {}", missing, project_id);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let payload = json!({
+        "project_id": project_id,
+        "asset_ids": asset_ids,
+        "name": req.name,
+    });
+    let job_id = job_manager
+        .create_job(JobType::SyncMulticam, Some(payload), None)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(SyncMulticamResponse { job_id }))
+}
+
+async fn list_groups(
+    State((db, _job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path(project_id): Path<i64>,
+) -> Result<Json<Vec<MulticamGroup>>, StatusCode> {
+    db.list_multicam_groups(project_id)
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+async fn delete_group(
+    State((db, _job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path((project_id, group_id)): Path<(i64, i64)>,
+) -> Result<StatusCode, StatusCode> {
+    find_group(&db, project_id, group_id)?;
+    db.delete_multicam_group(group_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /projects/:id/multicam/:gid/switch - Cut the primary storyline to another angle at a
+/// tick (applies a SwitchAngle operation built from the group's sync offsets)
+async fn switch_angle(
+    State((db, job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path((project_id, group_id)): Path<(i64, i64)>,
+    Json(req): Json<SwitchAngleRequest>,
+) -> Result<Json<TimelineResponse>, ApplyError> {
+    let group = find_group(&db, project_id, group_id)?;
+    let op = TimelineOperation::SwitchAngle {
+        position_ticks: req.position_ticks,
+        asset_id: req.asset_id,
+        angles: group
+            .angles
+            .iter()
+            .map(|a| MulticamAngle {
+                asset_id: a.asset_id,
+                offset_ticks: a.offset_ticks,
+                duration_ticks: a.duration_ticks,
+            })
+            .collect(),
+    };
+    let op = serde_json::to_value(op).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    super::timeline::apply_operations(
+        State((db, job_manager)),
+        Path(project_id),
+        Json(ApplyOperationsRequest { operations: vec![op] }),
+    )
+    .await
+}
+
+fn find_group(db: &Database, project_id: i64, group_id: i64) -> Result<MulticamGroup, StatusCode> {
+    db.get_multicam_group(group_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .filter(|g| g.project_id == project_id)
+        .ok_or(StatusCode::NOT_FOUND)
+}
@@ -0,0 +1,197 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Json,
+    routing::{get, post},
+    Router,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+use crate::api::generate::load_style_profile;
+use crate::api::media::PathError;
+use crate::db::{Database, MusicTrack, NewMusicTrack};
+use crate::jobs::music::queue_analysis;
+use crate::jobs::JobManager;
+use crate::media::ffmpeg::FFmpegWrapper;
+use crate::media::roots;
+use crate::music::{rank_tracks, MusicPick};
+use engine::timeline::TICKS_PER_SECOND;
+
+/// Edit length matched against when a match request doesn't give one
+const DEFAULT_MATCH_LENGTH_SEC: f64 = 60.0;
+
+#[derive(Deserialize)]
+pub struct RegisterTrackRequest {
+    /// Audio file of the track (downloaded first, for music-service tracks)
+    path: String,
+    /// Defaults to the file name
+    title: Option<String>,
+    artist: Option<String>,
+    /// "local" (the default) or the music service the track came from
+    source: Option<String>,
+    external_id: Option<String>,
+    bpm: Option<f64>,
+    #[serde(default)]
+    moods: Vec<String>,
+    license: Option<String>,
+    license_url: Option<String>,
+    attribution: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct RegisterTrackResponse {
+    track: MusicTrack,
+    /// Job that analyzes the track's structure
+    analysis_job_id: i64,
+}
+
+#[derive(Deserialize)]
+pub struct MatchQuery {
+    vibe: Option<String>,
+    length_sec: Option<f64>,
+}
+
+/// Routes for the music catalog (mounted under /music)
+pub fn router(db: Arc<Database>, job_manager: Arc<JobManager>) -> Router {
+    Router::new()
+        .route("/", get(list_tracks).post(register_track))
+        .route("/match", get(match_tracks))
+        .route("/:id", get(get_track).delete(delete_track))
+        .route("/:id/structure", get(get_structure))
+        .route("/:id/analyze", post(analyze_track))
+        .with_state((db, job_manager))
+}
+
+async fn list_tracks(
+    State((db, _job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+) -> Result<Json<Vec<MusicTrack>>, StatusCode> {
+    db.list_music_tracks()
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// POST /music - Add a track to the catalog with its tags and license (a track already at the
+/// same path is updated). Its length is probed from the file, and its structure is analyzed
+/// in the background.
+async fn register_track(
+    State((db, job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Json(req): Json<RegisterTrackRequest>,
+) -> Result<Json<RegisterTrackResponse>, PathError> {
+    if req.bpm.is_some_and(|bpm| !(bpm > 0.0 && bpm < 400.0)) {
+        eprintln!("[MUSIC] Implausible tempo {:?} for {}", req.bpm, req.path);
+        return Err(StatusCode::BAD_REQUEST.into());
+    }
+    let path = roots::resolve(&req.path)?;
+    if !path.is_file() {
+        eprintln!("[MUSIC] Track file not found: {}", req.path);
+        return Err(StatusCode::BAD_REQUEST.into());
+    }
+    let duration_ticks = FFmpegWrapper::probe(&path)
+        .await
+        .map_err(|e| {
+            eprintln!("[MUSIC] Can't read {} as audio: {:?}", path.display(), e);
+            StatusCode::BAD_REQUEST
+        })?
+        .duration_ticks;
+
+    let title = req.title.unwrap_or_else(|| {
+        path.file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default()
+    });
+    let mut moods: Vec<String> = req
+        .moods
+        .iter()
+        .map(|m| m.trim().to_lowercase())
+        .filter(|m| !m.is_empty())
+        .collect();
+    moods.sort();
+    moods.dedup();
+
+    let id = db
+        .upsert_music_track(&NewMusicTrack {
+            title,
+            artist: req.artist,
+            path: path.to_string_lossy().to_string(),
+            source: req.source.unwrap_or_else(|| "local".to_string()),
+            external_id: req.external_id,
+            bpm: req.bpm,
+            moods,
+            duration_ticks: Some(duration_ticks),
+            license: req.license,
+            license_url: req.license_url,
+            attribution: req.attribution,
+        })
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let analysis_job_id = queue_analysis(&job_manager, id).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let track = db
+        .get_music_track(id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(RegisterTrackResponse { track, analysis_job_id }))
+}
+
+/// GET /music/match?vibe=&length_sec= - Catalog tracks that suit a vibe, best first, scored the
+/// way generation picks music
+async fn match_tracks(
+    State((db, _job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Query(query): Query<MatchQuery>,
+) -> Result<Json<Vec<MusicPick>>, StatusCode> {
+    let tracks = db.list_music_tracks().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let style_profile = load_style_profile(&db, None, query.vibe.as_deref())?;
+    let length_ticks = (query.length_sec.unwrap_or(DEFAULT_MATCH_LENGTH_SEC) * TICKS_PER_SECOND as f64) as i64;
+    Ok(Json(rank_tracks(&tracks, query.vibe.as_deref(), style_profile.as_ref(), length_ticks)))
+}
+
+async fn get_track(
+    State((db, _job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path(id): Path<i64>,
+) -> Result<Json<MusicTrack>, StatusCode> {
+    find_track(&db, id).map(Json)
+}
+
+/// GET /music/:id/structure - The track's sections, drops, accents and energy curve (null
+/// until it's been analyzed)
+async fn get_structure(
+    State((db, _job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path(id): Path<i64>,
+) -> Result<Json<Value>, StatusCode> {
+    let track = find_track(&db, id)?;
+    let structure: Option<Value> = db
+        .get_music_structure(id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .map(|json| serde_json::from_str(&json))
+        .transpose()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(json!({ "track": track, "structure": structure })))
+}
+
+/// POST /music/:id/analyze - Analyze the track's structure again (e.g. after replacing the file)
+async fn analyze_track(
+    State((db, job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path(id): Path<i64>,
+) -> Result<Json<Value>, StatusCode> {
+    find_track(&db, id)?;
+    let job_id = queue_analysis(&job_manager, id).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(json!({ "job_id": job_id })))
+}
+
+/// DELETE /music/:id - Remove a track from the catalog; timelines using it keep the file
+async fn delete_track(
+    State((db, _job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, StatusCode> {
+    if db.delete_music_track(id).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)? {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(StatusCode::NOT_FOUND)
+    }
+}
+
+fn find_track(db: &Database, id: i64) -> Result<MusicTrack, StatusCode> {
+    db.get_music_track(id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)
+}
@@ -1,7 +1,8 @@
 use axum::{
+    body::Body,
     extract::{Path, Query, State},
-    http::StatusCode,
-    response::{sse::Event, Json, Sse},
+    http::{header, HeaderMap, StatusCode},
+    response::{sse::Event, Json, Response, Sse},
     routing::{get, post},
     Router,
 };
@@ -16,13 +17,26 @@ use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
 use anyhow::Result;
+use crate::brand;
 use crate::db::Database;
-use crate::embeddings;
-use crate::jobs::{JobEvent, JobManager, JobStatus, JobType};
+use crate::events::Event as PipelineEvent;
+use crate::jobs::{JobManager, JobStatus, JobType};
 use crate::llm;
+use crate::orchestrator::collections::{collection_named_in, CollectionScope};
 use crate::orchestrator::ensure::{ensure_ready, ReadinessGoal};
-use crate::api::orchestrator_helper::diversify_candidates;
+use crate::orchestrator::persona::AgentPersona;
+use crate::orchestrator::tools::{primary_clips, validate_tool_calls, ToolCall, ToolCallOutcome};
+use crate::orchestrator::working_set::{self, resolve_working_set, WorkingSet};
+use crate::api::collections::{collection_scope, project_collection};
+use crate::api::orchestrator_helper::{diversify_candidates, group_into_moments};
 use crate::api::timeline;
+use crate::api::generate::{follow_music, load_style_profile};
+use crate::planner::beats::{beats_from_plan, edit_beats, plan_from_beats, BeatEdit, PlanBeat};
+use crate::planner::compare::plan_stats;
+use crate::planner::script::{self, build_script};
+use crate::planner::{generate_edit_plan_with_strategy, PlanStrategy};
+use crate::music;
+use crate::templates;
 use serde_json;
 use rusqlite::params;
 
@@ -55,19 +69,30 @@ pub struct ProposeRequest {
     pub context: Option<TimelineContext>,
 }
 
-#[derive(Deserialize)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct RetrievalFilters {
     pub capture_time_range: Option<(String, String)>,
     pub quality_threshold: Option<f64>,
     pub unused_only: Option<bool>,
-    pub segment_kind: Option<String>,
+    pub segment_kind: Option<crate::media::segment_kind::SegmentKind>,
+    pub asset_ids: Option<Vec<i64>>,
+    /// Only search these segments
+    pub segment_ids: Option<Vec<i64>>,
+    /// Only search this collection's assets and segments
+    #[serde(default)]
+    pub collection_id: Option<i64>,
 }
 
 #[derive(Deserialize, Serialize)]
 pub struct TimelineContext {
     pub current_clips: Vec<ClipInfo>,
     pub selected_range: Option<TimeRange>,
+    /// Segment ids of the clips selected in the UI (they become the working set)
     pub user_selected_clips: Option<Vec<i64>>,
+    /// The clips the conversation is about, carried over from earlier turns (filled in by the
+    /// daemon)
+    #[serde(default)]
+    pub working_set: Option<WorkingSet>,
 }
 
 #[derive(Deserialize, Serialize)]
@@ -105,6 +130,15 @@ pub struct AgentResponse<T> {
 pub struct ProposeData {
     pub candidate_segments: Vec<SegmentCandidate>,
     pub narrative_structure: Option<String>,
+    /// Segments left out because of exclusions in the intent, with reasons
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub excluded_segments: Vec<crate::retrieval::exclusions::ExcludedSegment>,
+    /// The working set the search was limited to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub working_set: Option<WorkingSet>,
+    /// The collection the search was limited to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub collection: Option<CollectionScope>,
 }
 
 #[derive(Serialize)]
@@ -129,6 +163,9 @@ pub struct SegmentCandidate {
     pub capture_time: Option<String>,
     pub duration_sec: f64,
     pub similarity_score: f32,
+    /// Segments merged into this candidate as one moment, in time order (empty = single segment)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub moment_segment_ids: Vec<i64>,
 }
 
 #[derive(Deserialize)]
@@ -152,6 +189,51 @@ pub struct EditConstraints {
     pub vibe: Option<String>,
     pub captions_on: bool,
     pub music_on: bool,
+    /// Per-section pins (see engine::compiler::SectionConstraints)
+    #[serde(default)]
+    pub section_overrides: Vec<engine::compiler::SectionConstraints>,
+    /// Pre/post-roll around planned clips, in ticks
+    #[serde(default)]
+    pub handle_pre_ticks: Option<i64>,
+    #[serde(default)]
+    pub handle_post_ticks: Option<i64>,
+    /// Whether high-frame-rate action is slowed down, suggested or left alone
+    #[serde(default)]
+    pub slow_motion: engine::compiler::SlowMotionMode,
+    /// Catalog track to cut to (default: the best analyzed match for the vibe)
+    #[serde(default)]
+    pub music_track_id: Option<i64>,
+    /// Cut section changes and impact cuts to a music track's structure (default: whether a
+    /// music_track_id is given)
+    #[serde(default)]
+    pub align_to_music: Option<bool>,
+}
+
+#[derive(Deserialize)]
+pub struct CandidatesRequest {
+    pub user_intent: Option<String>,
+    pub constraints: EditConstraints,
+    /// Strategy/seed per candidate (default: one of each strategy)
+    pub variants: Option<Vec<PlanVariant>>,
+    /// Only plan with this collection's assets and segments
+    pub collection_id: Option<i64>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy)]
+pub struct PlanVariant {
+    #[serde(default)]
+    pub strategy: PlanStrategy,
+    #[serde(default)]
+    pub seed: u64,
+}
+
+#[derive(Deserialize)]
+pub struct BeatsPatchRequest {
+    /// Every beat id in the new order (left out keeps the order)
+    pub order: Option<Vec<String>>,
+    /// Changes to individual beats' segments and targets
+    #[serde(default)]
+    pub beats: Vec<BeatEdit>,
 }
 
 #[derive(Deserialize)]
@@ -200,7 +282,7 @@ async fn parse_intent_endpoint(
     };
     
     // Call LLM to parse intent
-    let parsed = llm::parse_intent(&req.user_message, Some(&history))
+    let parsed = llm::parse_intent(&llm::CallAudit::new(&db, project_id), &req.user_message, Some(&history))
         .await
         .map_err(|e| {
             eprintln!("Error parsing intent: {:?}", e);
@@ -210,14 +292,283 @@ async fn parse_intent_endpoint(
     Ok(Json(parsed))
 }
 
+#[derive(Deserialize)]
+struct EditRequest {
+    /// What to change, e.g. "cut the first clip to its last 3 seconds and put it at the end"
+    message: String,
+    /// Segment ids of the clips selected in the UI (they become the working set)
+    user_selected_clips: Option<Vec<i64>>,
+}
+
+#[derive(Serialize)]
+struct EditResponse {
+    /// Id of the stored tool call transcript
+    audit_id: i64,
+    message: String,
+    /// Whether the timeline was changed (false when any call was rejected or none were made)
+    applied: bool,
+    tool_calls: Vec<ToolCallOutcome>,
+    /// The updated timeline, when applied
+    timeline: Option<engine::timeline::Timeline>,
+    error: Option<String>,
+}
+
+/// POST /projects/:id/orchestrator/edit - Make a fine-grained change to the current timeline.
+/// The LLM answers with tool calls (trim_clip, reorder_clip, delete_clip, set_speed) rather than
+/// a whole EditPlan; each is validated and mapped to a TimelineOperation server-side and they are
+/// applied together, or not at all if any is rejected. The calls and their outcomes are stored
+/// for audit either way.
+async fn edit(
+    State((db, job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path(project_id): Path<i64>,
+    Json(req): Json<EditRequest>,
+) -> Result<Json<EditResponse>, StatusCode> {
+    let timeline: engine::timeline::Timeline = db
+        .get_timeline(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let working_set = resolve_working_set(&db, project_id, &req.message, req.user_selected_clips.as_deref())
+        .map_err(|e| {
+            eprintln!("[ORCHESTRATOR] Failed to resolve working set for project {}: {:?}", project_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    let in_working_set = |segment_id: Option<i64>| {
+        segment_id.is_some_and(|id| working_set.as_ref().is_some_and(|set| set.segment_ids.contains(&id)))
+    };
+
+    // Describe the primary track for the LLM
+    let mut source_durations = HashMap::new();
+    let mut asset_segments: HashMap<i64, Vec<crate::db::Segment>> = HashMap::new();
+    let mut clips = Vec::new();
+    for (index, clip) in primary_clips(&timeline).into_iter().enumerate() {
+        if let std::collections::hash_map::Entry::Vacant(entry) = source_durations.entry(clip.asset_id) {
+            if let Ok(Some(asset)) = db.get_media_asset(clip.asset_id) {
+                entry.insert(asset.duration_ticks);
+            }
+        }
+        let segments = asset_segments
+            .entry(clip.asset_id)
+            .or_insert_with(|| db.get_segments_by_asset(clip.asset_id).unwrap_or_default());
+        let summary = clip
+            .segment_id
+            .and_then(|id| segments.iter().find(|s| s.id == id))
+            .and_then(|s| s.summary_text.clone().or_else(|| s.transcript.clone()));
+        let to_sec = |ticks: i64| ticks as f64 / engine::timeline::TICKS_PER_SECOND as f64;
+        clips.push(serde_json::json!({
+            "clip_id": clip.id,
+            "index": index,
+            "asset_id": clip.asset_id,
+            "in_sec": to_sec(clip.in_ticks),
+            "out_sec": to_sec(clip.out_ticks),
+            "source_duration_sec": source_durations.get(&clip.asset_id).map(|d| to_sec(*d)),
+            "speed": clip.speed,
+            "locked": clip.locked,
+            "in_working_set": in_working_set(clip.segment_id),
+            "summary": summary,
+        }));
+    }
+
+    let history = db.get_orchestrator_messages(project_id, 10).unwrap_or_default();
+    let response = llm::plan_edit_tool_calls(&llm::CallAudit::new(&db, project_id), &req.message, &clips, &history)
+        .await
+        .map_err(|e| {
+            eprintln!("[ORCHESTRATOR] Edit tool planning failed for project {}: {:?}", project_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    let message = response["message"].as_str().unwrap_or("").to_string();
+    let calls: Vec<ToolCall> = serde_json::from_value(response["tool_calls"].clone()).unwrap_or_default();
+
+    let (outcomes, operations) = validate_tool_calls(&timeline, &calls, &source_durations);
+    let (updated, error) = match operations {
+        Some(operations) if !operations.is_empty() => {
+            match timeline::apply_ops_to_timeline(&db, job_manager.events(), project_id, operations, false) {
+                Ok(updated) => (Some(updated), None),
+                Err(e) => (None, Some(format!("Failed to apply: {}", e))),
+            }
+        }
+        Some(_) => (None, Some("No edits were made".to_string())),
+        None => (None, Some("A tool call was rejected; the timeline wasn't changed".to_string())),
+    };
+    let applied = updated.is_some();
+
+    let tool_calls_json = serde_json::to_value(&outcomes).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let audit_id = db
+        .store_orchestrator_tool_calls(project_id, &req.message, &message, &tool_calls_json, applied, error.as_deref())
+        .map_err(|e| {
+            eprintln!("[ORCHESTRATOR] Failed to store tool calls for project {}: {:?}", project_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    let _ = db.store_orchestrator_message(project_id, "user", &req.message, None);
+    let _ = db.store_orchestrator_message(
+        project_id,
+        "assistant",
+        &message,
+        Some(&serde_json::json!({ "tool_call_audit_id": audit_id, "applied": applied })),
+    );
+    eprintln!(
+        "[ORCHESTRATOR] Edit for project {}: {} tool calls, applied: {}",
+        project_id, outcomes.len(), applied
+    );
+
+    Ok(Json(EditResponse {
+        audit_id,
+        message,
+        applied,
+        tool_calls: outcomes,
+        timeline: updated,
+        error,
+    }))
+}
+
+#[derive(Deserialize)]
+struct WorkingSetRequest {
+    segment_ids: Vec<i64>,
+}
+
+/// GET /projects/:id/orchestrator/working_set - The clips the conversation is currently about
+async fn get_working_set(
+    State((db, _job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path(project_id): Path<i64>,
+) -> Result<Json<Option<WorkingSet>>, StatusCode> {
+    working_set::get_working_set(&db, project_id)
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// PUT /projects/:id/orchestrator/working_set - Set the working set from a selection in the UI
+async fn put_working_set(
+    State((db, _job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path(project_id): Path<i64>,
+    Json(req): Json<WorkingSetRequest>,
+) -> Result<Json<Option<WorkingSet>>, StatusCode> {
+    let segment_ids = (!req.segment_ids.is_empty()).then_some(req.segment_ids.as_slice());
+    db.set_working_set(project_id, segment_ids, segment_ids.map(|_| "selection"))
+        .map_err(|e| {
+            eprintln!("[ORCHESTRATOR] Failed to set working set for project {}: {:?}", project_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    working_set::get_working_set(&db, project_id)
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// DELETE /projects/:id/orchestrator/working_set - Go back to working with all of the footage
+async fn delete_working_set(
+    State((db, _job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path(project_id): Path<i64>,
+) -> Result<StatusCode, StatusCode> {
+    db.set_working_set(project_id, None, None)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /projects/:id/orchestrator/tool_calls - Audit transcript of edit tool calls (newest first)
+async fn get_tool_calls(
+    State((db, _job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path(project_id): Path<i64>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let transcripts = db.get_orchestrator_tool_calls(project_id, 50).map_err(|e| {
+        eprintln!("[ORCHESTRATOR] Failed to get tool calls for project {}: {:?}", project_id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    Ok(Json(serde_json::json!({ "tool_calls": transcripts })))
+}
+
+#[derive(Deserialize)]
+struct RetrievalDebugRequest {
+    query: String,
+    filters: Option<RetrievalFilters>,
+    context: Option<TimelineContext>,
+    /// "twelvelabs", "local" or "twelvelabs_then_local"; defaults to RETRIEVAL_BACKEND
+    backend: Option<String>,
+}
+
+#[derive(Serialize)]
+struct RetrievalDebugResponse {
+    query: String,
+    backend_requested: String,
+    backend_used: String,
+    filters: Option<RetrievalFilters>,
+    candidates: Vec<SegmentCandidate>,
+    /// Backend debug output: per-candidate raw scores (candidate_scores), snapped vs created
+    /// segments (mapping_stats), prefilter and timings_ms
+    debug: serde_json::Value,
+    warnings: Vec<String>,
+    excluded: Vec<crate::retrieval::exclusions::ExcludedSegment>,
+    elapsed_ms: u64,
+}
+
+/// POST /projects/:id/retrieval/debug - Run a query through retrieval and return score breakdowns.
+/// Same path as propose, so TwelveLabs matches may still create dynamic segments.
+async fn retrieval_debug(
+    State((db, _job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path(project_id): Path<i64>,
+    Json(req): Json<RetrievalDebugRequest>,
+) -> Result<Json<RetrievalDebugResponse>, StatusCode> {
+    if req.query.trim().is_empty() {
+        eprintln!("[RETRIEVAL_DEBUG] Empty query");
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let backend = req.backend.clone().unwrap_or_else(|| {
+        std::env::var("RETRIEVAL_BACKEND").unwrap_or_else(|_| "twelvelabs_then_local".to_string())
+    });
+    if !["twelvelabs", "local", "twelvelabs_then_local"].contains(&backend.as_str()) {
+        eprintln!("[RETRIEVAL_DEBUG] Unknown backend: {}", backend);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    
+    let started = std::time::Instant::now();
+    let result = crate::retrieval::retrieve_candidates_with_backend(
+        db.clone(),
+        project_id,
+        &req.query,
+        req.filters.as_ref(),
+        req.context.as_ref(),
+        &backend,
+    )
+    .await
+    .map_err(|e| {
+        eprintln!("[RETRIEVAL_DEBUG] Retrieval failed: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    
+    Ok(Json(RetrievalDebugResponse {
+        query: req.query,
+        backend_requested: backend,
+        backend_used: result.backend_used.as_str().to_string(),
+        filters: req.filters,
+        candidates: result.candidates,
+        debug: result.debug,
+        warnings: result.warnings,
+        excluded: result.excluded,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+    }))
+}
+
 pub fn router(db: Arc<Database>, job_manager: Arc<JobManager>) -> Router {
     Router::new()
         .route("/:id/orchestrator/propose", post(propose))
         .route("/:id/orchestrator/plan", post(plan))
+        .route("/:id/orchestrator/proposals/candidates", post(generate_candidates))
+        .route("/:id/orchestrator/proposals/compare", get(compare_proposals))
+        .route(
+            "/:id/orchestrator/proposals/:pid/beats",
+            get(get_proposal_beats).patch(patch_proposal_beats),
+        )
+        .route("/:id/orchestrator/proposals/:pid/script", get(get_proposal_script))
         .route("/:id/orchestrator/apply", post(apply))
         .route("/:id/orchestrator/events", get(events))
         .route("/:id/orchestrator/messages", get(get_messages))
         .route("/:id/orchestrator/parse_intent", post(parse_intent_endpoint))
+        .route("/:id/orchestrator/edit", post(edit))
+        .route("/:id/orchestrator/tool_calls", get(get_tool_calls))
+        .route(
+            "/:id/orchestrator/working_set",
+            get(get_working_set).put(put_working_set).delete(delete_working_set),
+        )
+        .route("/:id/retrieval/debug", post(retrieval_debug))
         .with_state((db, job_manager))
 }
 
@@ -332,7 +683,8 @@ pub fn check_project_preconditions(db: &Database, project_id: i64) -> Result<Pro
                         matches!(jt,
                             JobType::TranscribeAsset | JobType::AnalyzeVisionAsset | JobType::BuildSegments |
                             JobType::EnrichSegmentsFromTranscript | JobType::EnrichSegmentsFromVision |
-                            JobType::ComputeSegmentMetadata | JobType::EmbedSegments
+                            JobType::ComputeSegmentMetadata | JobType::ClassifySegments | JobType::EmbedSegments |
+                            JobType::AnalyzeAudioEvents
                         )
                     });
                     
@@ -587,8 +939,30 @@ async fn generate_agent_response_with_llm(
         });
     }
     
+    // Locked clips/regions, so the agent proposes edits around them instead of replacing them
+    if let Some(timeline_obj) = db
+        .get_timeline(project_id)
+        .ok()
+        .flatten()
+        .and_then(|json_str| serde_json::from_str::<engine::timeline::Timeline>(&json_str).ok())
+    {
+        let spans = timeline_obj.locked_spans();
+        if !spans.is_empty() {
+            let to_sec = |ticks: i64| ticks as f64 / engine::timeline::TICKS_PER_SECOND as f64;
+            context_json["locked_content"] = serde_json::json!({
+                "locked_spans_sec": spans.iter().map(|(start, end)| (to_sec(*start), to_sec(*end))).collect::<Vec<_>>(),
+                "safe_insert_sec": spans.last().map(|(_, end)| to_sec(*end)).unwrap_or(0.0),
+            });
+        }
+    }
+    
+    // Project tone and language, so generated copy matches the templated fallback
+    let persona = AgentPersona::for_project(db, project_id);
+    context_json["persona"] = persona.prompt_context();
+    
     // Call LLM to generate response
     let response = match llm::generate_agent_response(
+        &llm::CallAudit::new(db, project_id),
         &conversation_history,
         &project_state_json,
         &context_json,
@@ -599,7 +973,14 @@ async fn generate_agent_response_with_llm(
             eprintln!("[ERROR] LLM call failed: {:?}", e);
             eprintln!("[ERROR] This means the agent will use fallback messages instead of LLM-generated ones.");
             eprintln!("[ERROR] Check: 1) ML service is running, 2) OpenAI API key is set, 3) Network connectivity");
-            return Err(e);
+            let (message, suggestions, questions) = generate_response_for_mode(mode, state, candidate_count, &persona);
+            let _ = db.store_orchestrator_message(
+                project_id,
+                "assistant",
+                &message,
+                Some(&serde_json::json!({ "fallback": true, "error": e.to_string() })),
+            );
+            return Ok((message, suggestions, questions));
         }
     };
     
@@ -663,103 +1044,72 @@ async fn generate_agent_response_with_llm(
     Ok((message, suggestions, questions))
 }
 
-// Generate mode-specific friendly messages (fallback if LLM fails)
+// Generate mode-specific messages from the project's persona templates (fallback if LLM fails)
 fn generate_response_for_mode(
     mode: &AgentMode,
     state: &ProjectState,
-    user_intent: &str,
     candidate_count: usize,
+    persona: &AgentPersona,
 ) -> (String, Vec<Suggestion>, Vec<String>) {
+    let suggestion = |key: &str, action: &str, confirm_token: Option<&str>| Suggestion {
+        label: persona.render(key, &[]),
+        action: action.to_string(),
+        confirm_token: confirm_token.map(|t| t.to_string()),
+    };
     match mode {
         AgentMode::TalkImport => (
-            "Hey! Your library is empty right now. Click Import Video Clips to add footage — then I'll scan it and suggest a first cut.".to_string(),
-            vec![Suggestion {
-                label: "Import clips".to_string(),
-                action: "import_clips".to_string(),
-                confirm_token: None,
-            }],
+            persona.render("import.message", &[]),
+            vec![suggestion("import.action", "import_clips", None)],
             vec![],
         ),
         AgentMode::TalkAnalyze => (
-            "Nice — I see your clips. Next step is analyzing them into moments I can edit with. Want me to start the scan?".to_string(),
-            vec![Suggestion {
-                label: "Analyze clips".to_string(),
-                action: "analyze_clips".to_string(),
-                confirm_token: None,
-            }],
+            persona.render("analyze.message", &[]),
+            vec![suggestion("analyze.action", "analyze_clips", None)],
             vec![],
         ),
         AgentMode::Busy => {
-            let jobs_msg = if state.jobs_running_count > 0 {
-                format!("I'm scanning your footage now ({} jobs running).", state.jobs_running_count)
+            let status = if state.jobs_running_count > 0 {
+                persona.render("busy.jobs_running", &[("jobs", state.jobs_running_count.to_string())])
             } else {
-                format!("I'm still analyzing your footage ({}% complete).", (state.embedding_coverage * 100.0) as u32)
+                persona.render("busy.analyzing", &[("percent", ((state.embedding_coverage * 100.0) as u32).to_string())])
             };
             (
-                format!("{}. You can keep browsing — I'll tell you when I'm ready to propose an edit.", jobs_msg),
-                vec![Suggestion {
-                    label: "Show progress".to_string(),
-                    action: "show_progress".to_string(),
-                    confirm_token: None,
-                }],
+                persona.render("busy.message", &[("status", status)]),
+                vec![suggestion("busy.action", "show_progress", None)],
                 vec![],
             )
         },
         AgentMode::TalkClarify => (
-            "Got it — before I start, what kind of vibe are you going for? Casual vlog, cinematic montage, or something fast-paced?".to_string(),
+            persona.render("clarify.message", &[]),
             vec![],
             vec![
-                "What's the main story you want to tell?".to_string(),
-                "How long should the final video be?".to_string(),
+                persona.render("clarify.question_story", &[]),
+                persona.render("clarify.question_length", &[]),
             ],
         ),
         AgentMode::TalkConfirm => (
-            "This will replace your current timeline. Do you want to overwrite it, or create a new version?".to_string(),
+            persona.render("confirm.message", &[]),
             vec![
-                Suggestion {
-                    label: "Overwrite timeline".to_string(),
-                    action: "overwrite_timeline".to_string(),
-                    confirm_token: Some("overwrite".to_string()),
-                },
-                Suggestion {
-                    label: "Create new version".to_string(),
-                    action: "create_new_version".to_string(),
-                    confirm_token: Some("new_version".to_string()),
-                },
-                Suggestion {
-                    label: "Cancel".to_string(),
-                    action: "cancel".to_string(),
-                    confirm_token: None,
-                },
+                suggestion("confirm.overwrite", "overwrite_timeline", Some("overwrite")),
+                suggestion("confirm.new_version", "create_new_version", Some("new_version")),
+                suggestion("confirm.cancel", "cancel", None),
             ],
             vec![],
         ),
         AgentMode::Act => {
             if candidate_count == 0 {
                 (
-                    "I couldn't find moments that match that request yet. Want me to broaden the search, or are you aiming for a specific vibe (funny / cinematic / cozy)?".to_string(),
+                    persona.render("act.no_candidates", &[]),
                     vec![
-                        Suggestion {
-                            label: "Broaden search".to_string(),
-                            action: "broaden_search".to_string(),
-                            confirm_token: None,
-                        },
-                        Suggestion {
-                            label: "Show all moments".to_string(),
-                            action: "show_all_moments".to_string(),
-                            confirm_token: None,
-                        },
+                        suggestion("act.broaden", "broaden_search", None),
+                        suggestion("act.show_all", "show_all_moments", None),
                     ],
-                    vec!["What kind of moments are you looking for?".to_string()],
+                    vec![persona.render("act.question_moments", &[])],
                 )
             } else {
                 (
-                    format!("I found {} good moments based on speech and visual interest. I'll start with a short hook, then build the main section around these scenes.", candidate_count),
-                    vec![Suggestion {
-                        label: "Generate Plan".to_string(),
-                        action: "generate_plan".to_string(),
-                        confirm_token: None,
-                    }],
+                    persona.render("act.found", &[("count", candidate_count.to_string())]),
+                    vec![suggestion("act.generate_plan", "generate_plan", None)],
                     vec![],
                 )
             }
@@ -811,6 +1161,15 @@ async fn propose(
         }
     }
     
+    // Carry the conversation's working set into this turn (a new selection or "these clips"
+    // replaces it, "all clips" clears it)
+    let selected = req.context.as_ref().and_then(|c| c.user_selected_clips.as_deref());
+    let working_set = resolve_working_set(&db, project_id, &req.user_intent, selected)
+        .map_err(|e| {
+            eprintln!("Error resolving working set: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    
     // Auto-enqueue missing jobs for TalkAnalyze or Busy modes
     match mode {
         AgentMode::TalkAnalyze => {
@@ -923,20 +1282,61 @@ async fn propose(
             }
         },
         AgentMode::Act => {
+            // Follow-ups work on the working set: search only its segments and pass it on
+            let mut filters = req.filters.clone();
+            let mut context = req.context;
+            if let Some(ref set) = working_set {
+                filters.get_or_insert_with(RetrievalFilters::default).segment_ids = Some(set.segment_ids.clone());
+                context
+                    .get_or_insert_with(|| TimelineContext {
+                        current_clips: Vec::new(),
+                        selected_range: None,
+                        user_selected_clips: None,
+                        working_set: None,
+                    })
+                    .working_set = Some(set.clone());
+            }
+
+            // "Only use the drone footage bin" limits the search to that collection
+            let collection = match filters.as_ref().and_then(|f| f.collection_id) {
+                Some(collection_id) => {
+                    let (_, name, _) = project_collection(&db, project_id, collection_id)?;
+                    Some(CollectionScope { collection_id, name })
+                }
+                None => collection_named_in(&db, project_id, &req.user_intent).map_err(|e| {
+                    eprintln!("Error matching collections: {:?}", e);
+                    StatusCode::INTERNAL_SERVER_ERROR
+                })?,
+            };
+            if let Some(ref scope) = collection {
+                filters.get_or_insert_with(RetrievalFilters::default).collection_id = Some(scope.collection_id);
+            }
+            
             // Continue with retrieval + reasoning
-            // Use retrieval module (handles TwelveLabs + fallback to local embeddings)
+            // Use retrieval module (handles backend selection, TwelveLabs + fallback to local
+            // embeddings, prefilters and feedback) so propose ranks like every other entry point
             let retrieval_result = crate::retrieval::retrieve_candidates(
                 db.clone(),
                 project_id,
                 &req.user_intent,
-                req.filters.as_ref(),
-                req.context.as_ref(),
+                filters.as_ref(),
+                context.as_ref(),
             ).await.map_err(|e| {
                 eprintln!("Error in retrieval: {:?}", e);
                 StatusCode::INTERNAL_SERVER_ERROR
             })?;
             
             let mut candidate_segments = retrieval_result.candidates;
+            let excluded_segments = retrieval_result.excluded;
+            let retrieved_count = candidate_segments.len();
+            
+            // Merge runs of adjacent high-scoring segments from one asset into single moments
+            candidate_segments = group_into_moments(candidate_segments, &db)
+                .map_err(|e| {
+                    eprintln!("Error grouping candidates into moments: {:?}", e);
+                    StatusCode::INTERNAL_SERVER_ERROR
+                })?;
+            let moment_count = candidate_segments.len();
             
             // Apply diversity filtering (max 3 segments per asset, dedupe summaries)
             candidate_segments = diversify_candidates(candidate_segments, 3, &db)
@@ -945,12 +1345,21 @@ async fn propose(
                     StatusCode::INTERNAL_SERVER_ERROR
                 })?;
             
+            // Same debug shape as /retrieval/debug, plus what propose did on top of retrieval
+            let mut retrieval_debug = retrieval_result.debug;
+            if let Some(debug_obj) = retrieval_debug.as_object_mut() {
+                debug_obj.insert("warnings".to_string(), serde_json::json!(retrieval_result.warnings));
+                debug_obj.insert("retrieved_count".to_string(), serde_json::json!(retrieved_count));
+                debug_obj.insert("moment_count".to_string(), serde_json::json!(moment_count));
+                debug_obj.insert("diversified_count".to_string(), serde_json::json!(candidate_segments.len()));
+            }
+            
             // Build warning message if fallback was used
             let mut warning_message = None;
-            if let Some(debug_obj) = retrieval_result.debug.as_object() {
+            if let Some(debug_obj) = retrieval_debug.as_object() {
                 if let Some(fallback_reason) = debug_obj.get("fallback_reason") {
                     if !fallback_reason.is_null() {
-                        warning_message = Some("I'm still indexing your footage for better search; results will improve shortly.".to_string());
+                        warning_message = Some(AgentPersona::for_project(&db, project_id).render("retrieval.indexing_warning", &[]));
                     }
                 }
             }
@@ -975,7 +1384,7 @@ async fn propose(
                             suggestions,
                             questions,
                             data: None,
-                            debug: None,
+                            debug: Some(retrieval_debug),
                         }));
                     }
                     Err(e) => {
@@ -1035,6 +1444,7 @@ async fn propose(
                         "summary_text": c.summary_text,
                         "capture_time": c.capture_time,
                         "duration_sec": c.duration_sec,
+                        "moment_segment_ids": c.moment_segment_ids,
                     })
                 })
                 .collect();
@@ -1052,11 +1462,12 @@ async fn propose(
             };
             
             // Call LLM for structured reasoning (not user-facing copy)
-            let timeline_context_json = req.context.as_ref()
+            let timeline_context_json = context.as_ref()
                 .map(|c| serde_json::to_value(c).ok())
                 .flatten();
             
             let narrative_proposal = llm::reason_narrative(
+                &llm::CallAudit::new(&db, project_id),
                 &segment_metadata,
                 style_profile.as_ref(),
                 timeline_context_json.as_ref(),
@@ -1068,7 +1479,15 @@ async fn propose(
             // Store proposal in database
             let proposal_json = serde_json::to_string(&narrative_proposal)
                 .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-            let _ = db.store_orchestrator_proposal(project_id, &proposal_json);
+            if let Ok(proposal_id) = db.store_orchestrator_proposal(project_id, &proposal_json) {
+                job_manager.events().publish(PipelineEvent::ProposalReady { project_id, proposal_id });
+            }
+            
+            // Remember what was shown so the next message can say "these clips"
+            let shown: Vec<i64> = candidate_segments.iter().map(|c| c.segment_id).collect();
+            if let Err(e) = db.set_last_shown_segments(project_id, &shown) {
+                eprintln!("Error storing shown candidates: {:?}", e);
+            }
             
             // Update goal status to "proposed"
             if let Ok(Some((goal_id, _))) = db.get_orchestrator_goal_by_status(project_id, "ready_to_propose") {
@@ -1123,8 +1542,11 @@ async fn propose(
                     narrative_structure: narrative_proposal.get("narrative_structure")
                         .and_then(|v| v.as_str())
                         .map(|s| s.to_string()),
+                    excluded_segments,
+                    working_set,
+                    collection,
                 }),
-                debug: Some(retrieval_result.debug),
+                debug: Some(retrieval_debug),
             }))
         },
         AgentMode::TalkConfirm => {
@@ -1211,17 +1633,40 @@ async fn plan(
         }))
         .collect();
     
+    // Reject section pins that contradict each other before planning
+    let engine_constraints = engine::compiler::EditConstraints {
+        target_length: req.constraints.target_length,
+        vibe: req.constraints.vibe.clone(),
+        captions_on: req.constraints.captions_on,
+        music_on: req.constraints.music_on,
+        section_overrides: req.constraints.section_overrides.clone(),
+        handle_pre_ticks: req.constraints.handle_pre_ticks,
+        handle_post_ticks: req.constraints.handle_post_ticks,
+        slow_motion: req.constraints.slow_motion,
+    };
+    if let Err(conflicts) = engine_constraints.validate() {
+        for conflict in &conflicts {
+            eprintln!("[ORCHESTRATOR] Section constraint conflict: {}", conflict.message);
+        }
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
     // Convert constraints to JSON
     let constraints_json = serde_json::json!({
         "target_length": req.constraints.target_length,
         "vibe": req.constraints.vibe,
         "captions_on": req.constraints.captions_on,
         "music_on": req.constraints.music_on,
+        "section_overrides": req.constraints.section_overrides,
+        "handle_pre_ticks": req.constraints.handle_pre_ticks,
+        "handle_post_ticks": req.constraints.handle_post_ticks,
+        "slow_motion": req.constraints.slow_motion,
     });
     
     // Call LLM to generate EditPlan
     let beats_json_value = serde_json::json!(beats_json);
     let edit_plan = llm::generate_edit_plan(
+        &llm::CallAudit::new(&db, project_id),
         &req.narrative_structure,
         &beats_json_value,
         &constraints_json,
@@ -1279,6 +1724,322 @@ async fn plan(
     }))
 }
 
+/// POST /projects/:id/orchestrator/proposals/candidates - Generate alternative plans for the
+/// same intent (one per strategy/seed) and store each as a proposal for comparison
+async fn generate_candidates(
+    State((db, job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path(project_id): Path<i64>,
+    Json(req): Json<CandidatesRequest>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let variants = req.variants.unwrap_or_else(|| {
+        [PlanStrategy::Clarity, PlanStrategy::Energy, PlanStrategy::Coverage]
+            .into_iter()
+            .map(|strategy| PlanVariant { strategy, seed: 0 })
+            .collect()
+    });
+    if variants.len() < 2 {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let project = db
+        .get_project(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let mut segments_with_assets = db
+        .get_segments_for_project(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if let Some(collection_id) = req.collection_id {
+        let scope = collection_scope(&db, project_id, collection_id)?;
+        segments_with_assets.retain(|(segment, _)| scope.contains(&segment.id));
+    }
+    if segments_with_assets.is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let template = templates::project_template(&db, project_id).map_err(|e| {
+        eprintln!("[TEMPLATES] Failed to load template for project {}: {:?}", project_id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let mut base_constraints = engine::compiler::EditConstraints {
+        target_length: req.constraints.target_length,
+        vibe: req.constraints.vibe.clone(),
+        captions_on: req.constraints.captions_on,
+        music_on: req.constraints.music_on,
+        section_overrides: req.constraints.section_overrides.clone(),
+        handle_pre_ticks: req.constraints.handle_pre_ticks,
+        handle_post_ticks: req.constraints.handle_post_ticks,
+        slow_motion: req.constraints.slow_motion,
+    };
+    if let Some(template) = &template {
+        template.apply_to_constraints(&mut base_constraints);
+    }
+    let mut style_profile = load_style_profile(&db, project.style_profile_id, base_constraints.vibe.as_deref())?;
+    if let Some(template) = &template {
+        style_profile = Some(template.apply_to_style_profile(style_profile));
+    }
+    let align_to_music = req.constraints.align_to_music.unwrap_or(req.constraints.music_track_id.is_some());
+    let (style_profile, followed) = follow_music(
+        &db,
+        req.constraints.music_track_id,
+        align_to_music,
+        &mut base_constraints,
+        style_profile,
+    )?;
+
+    let mut candidates = Vec::new();
+    for variant in variants {
+        let constraints = base_constraints.clone();
+        let mut edit_plan = generate_edit_plan_with_strategy(
+            &segments_with_assets,
+            constraints,
+            style_profile.as_ref(),
+            variant.strategy,
+            variant.seed,
+        )
+        .map_err(|conflicts| {
+            for conflict in &conflicts {
+                eprintln!("[ORCHESTRATOR] Section constraint conflict: {}", conflict.message);
+            }
+            StatusCode::UNPROCESSABLE_ENTITY
+        })?;
+        if let Some(template) = &template {
+            template.decorate_plan(&mut edit_plan, &segments_with_assets, style_profile.as_ref());
+        }
+        if let Err(e) = music::add_catalog_music(&db, &mut edit_plan, style_profile.as_ref(), followed.as_ref()) {
+            eprintln!("[MUSIC] Failed to pick music for project {}: {:?}", project_id, e);
+        }
+        if let Err(e) = brand::style_captions(&db, project_id, &mut edit_plan) {
+            eprintln!("[BRAND] Failed to style captions for project {}: {:?}", project_id, e);
+        }
+        let stats = plan_stats(&edit_plan, &segments_with_assets, style_profile.as_ref());
+
+        let proposal_json = serde_json::to_string(&serde_json::json!({
+            "kind": "plan_candidate",
+            "user_intent": req.user_intent,
+            "strategy": variant.strategy,
+            "seed": variant.seed,
+            "edit_plan": edit_plan,
+        }))
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        let proposal_id = db
+            .store_orchestrator_proposal(project_id, &proposal_json)
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        job_manager.events().publish(PipelineEvent::ProposalReady { project_id, proposal_id });
+
+        candidates.push(serde_json::json!({
+            "proposal_id": proposal_id,
+            "strategy": variant.strategy,
+            "seed": variant.seed,
+            "stats": stats,
+            "suggestions": edit_plan.suggestions,
+        }));
+    }
+
+    Ok(Json(serde_json::json!({ "candidates": candidates })))
+}
+
+/// GET /projects/:id/orchestrator/proposals/compare?ids=1,2 - Side-by-side stats for
+/// candidate plans (duration, clip count, asset coverage, style match)
+async fn compare_proposals(
+    State((db, _job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path(project_id): Path<i64>,
+    Query(query_params): Query<HashMap<String, String>>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let ids: Vec<i64> = query_params
+        .get("ids")
+        .ok_or(StatusCode::BAD_REQUEST)?
+        .split(',')
+        .map(|id| id.trim().parse::<i64>())
+        .collect::<Result<_, _>>()
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
+    if ids.len() < 2 {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let project = db
+        .get_project(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let segments_with_assets = db
+        .get_segments_for_project(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let mut proposals = Vec::new();
+    for id in ids {
+        let (proposal_project_id, proposal_json, created_at) = db
+            .get_orchestrator_proposal(id)
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+            .ok_or(StatusCode::NOT_FOUND)?;
+        if proposal_project_id != project_id {
+            return Err(StatusCode::NOT_FOUND);
+        }
+        let proposal: serde_json::Value = serde_json::from_str(&proposal_json)
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        // Narrative proposals carry no plan, so they get no stats
+        let edit_plan = proposal
+            .get("edit_plan")
+            .and_then(|p| serde_json::from_value::<engine::compiler::EditPlan>(p.clone()).ok());
+        let stats = match edit_plan {
+            Some(edit_plan) => {
+                let style_profile =
+                    load_style_profile(&db, project.style_profile_id, edit_plan.constraints.vibe.as_deref())?;
+                Some(plan_stats(&edit_plan, &segments_with_assets, style_profile.as_ref()))
+            }
+            None => None,
+        };
+
+        proposals.push(serde_json::json!({
+            "proposal_id": id,
+            "created_at": created_at,
+            "user_intent": proposal.get("user_intent"),
+            "strategy": proposal.get("strategy"),
+            "seed": proposal.get("seed"),
+            "stats": stats,
+        }));
+    }
+
+    Ok(Json(serde_json::json!({ "proposals": proposals })))
+}
+
+/// A project's proposal and its plan. Narrative proposals carry no plan, so they're not found.
+fn load_proposal_plan(
+    db: &Database,
+    project_id: i64,
+    proposal_id: i64,
+) -> Result<(serde_json::Value, engine::compiler::EditPlan), StatusCode> {
+    let (proposal_project_id, proposal_json, _) = db
+        .get_orchestrator_proposal(proposal_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    if proposal_project_id != project_id {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    let proposal: serde_json::Value =
+        serde_json::from_str(&proposal_json).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let edit_plan = proposal
+        .get("edit_plan")
+        .and_then(|p| serde_json::from_value::<engine::compiler::EditPlan>(p.clone()).ok())
+        .ok_or(StatusCode::NOT_FOUND)?;
+    Ok((proposal, edit_plan))
+}
+
+/// A project's proposal, its plan and its beats: the beats it was last regenerated from, else
+/// one per section of its plan
+fn load_proposal_beats(
+    db: &Database,
+    project_id: i64,
+    proposal_id: i64,
+) -> Result<(serde_json::Value, engine::compiler::EditPlan, Vec<PlanBeat>), StatusCode> {
+    let (proposal, edit_plan) = load_proposal_plan(db, project_id, proposal_id)?;
+    let beats = match proposal.get("beats").and_then(|b| serde_json::from_value::<Vec<PlanBeat>>(b.clone()).ok()) {
+        Some(beats) => beats,
+        None => beats_from_plan(&edit_plan),
+    };
+    Ok((proposal, edit_plan, beats))
+}
+
+/// GET /projects/:id/orchestrator/proposals/:pid/beats - The beats of a candidate plan
+async fn get_proposal_beats(
+    State((db, _job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path((project_id, proposal_id)): Path<(i64, i64)>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let (_, _, beats) = load_proposal_beats(&db, project_id, proposal_id)?;
+    Ok(Json(serde_json::json!({ "proposal_id": proposal_id, "beats": beats })))
+}
+
+/// PATCH /projects/:id/orchestrator/proposals/:pid/beats - Reorder beats, change their target
+/// lengths or swap their segments, then regenerate the proposal's plan from the edited beats
+async fn patch_proposal_beats(
+    State((db, _job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path((project_id, proposal_id)): Path<(i64, i64)>,
+    Json(req): Json<BeatsPatchRequest>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let (mut proposal, edit_plan, beats) = load_proposal_beats(&db, project_id, proposal_id)?;
+    let project = db
+        .get_project(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let segments_with_assets = db
+        .get_segments_for_project(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let known_segment_ids: Vec<i64> = segments_with_assets.iter().map(|(segment, _)| segment.id).collect();
+
+    let beats = edit_beats(&beats, req.order.as_deref(), &req.beats, &known_segment_ids).map_err(|e| {
+        eprintln!("[ORCHESTRATOR] Rejected beat edit of proposal {}: {}", proposal_id, e);
+        StatusCode::BAD_REQUEST
+    })?;
+
+    let style_profile = load_style_profile(&db, project.style_profile_id, edit_plan.constraints.vibe.as_deref())?;
+    let mut edit_plan = plan_from_beats(&beats, edit_plan.constraints, &segments_with_assets);
+    let template = templates::project_template(&db, project_id).map_err(|e| {
+        eprintln!("[TEMPLATES] Failed to load template for project {}: {:?}", project_id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    if let Some(template) = &template {
+        template.decorate_plan(&mut edit_plan, &segments_with_assets, style_profile.as_ref());
+    }
+    if let Err(e) = music::add_catalog_music(&db, &mut edit_plan, style_profile.as_ref(), None) {
+        eprintln!("[MUSIC] Failed to pick music for project {}: {:?}", project_id, e);
+    }
+    if let Err(e) = brand::style_captions(&db, project_id, &mut edit_plan) {
+        eprintln!("[BRAND] Failed to style captions for project {}: {:?}", project_id, e);
+    }
+    let stats = plan_stats(&edit_plan, &segments_with_assets, style_profile.as_ref());
+
+    proposal["beats"] = serde_json::json!(beats);
+    proposal["edit_plan"] = serde_json::json!(edit_plan);
+    let proposal_json = serde_json::to_string(&proposal).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    db.update_orchestrator_proposal(proposal_id, &proposal_json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(serde_json::json!({
+        "proposal_id": proposal_id,
+        "beats": beats,
+        "edit_plan": edit_plan,
+        "stats": stats,
+    })))
+}
+
+/// GET /projects/:id/orchestrator/proposals/:pid/script?format=markdown|pdf - The proposal's
+/// plan written out as an edit script for review (sections, clips, pull quotes, durations)
+async fn get_proposal_script(
+    State((db, _job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path((project_id, proposal_id)): Path<(i64, i64)>,
+    Query(query_params): Query<HashMap<String, String>>,
+) -> Result<Response, StatusCode> {
+    let format = query_params.get("format").map(|f| f.as_str()).unwrap_or("markdown");
+    if !["markdown", "md", "pdf"].contains(&format) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let (_, edit_plan) = load_proposal_plan(&db, project_id, proposal_id)?;
+    let project = db
+        .get_project(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let segments_with_assets = db.get_segments_for_project(project_id).map_err(|e| {
+        // Transcripts of a locked encrypted project can't be quoted
+        if crate::crypto::is_locked_error(&e) {
+            return StatusCode::LOCKED;
+        }
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let script = build_script(&format!("{}: edit script", project.name), &edit_plan, &segments_with_assets);
+    let (content_type, extension, body) = match format {
+        "pdf" => ("application/pdf", "pdf", script::to_pdf(&script)),
+        _ => ("text/markdown; charset=utf-8", "md", script::to_markdown(&script).into_bytes()),
+    };
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, content_type)
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"proposal_{}_script.{}\"", proposal_id, extension),
+        )
+        .body(Body::from(body))
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
 /// POST /projects/:id/orchestrator/apply - Apply EditPlan to timeline
 async fn apply(
     State((db, _job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
@@ -1363,40 +2124,39 @@ async fn apply(
     return Err(StatusCode::NOT_IMPLEMENTED);
 }
 
-/// GET /projects/:id/orchestrator/events - SSE endpoint for orchestrator events
+#[derive(Deserialize)]
+struct EventsQuery {
+    /// Replay stored events after this id first
+    after: Option<i64>,
+}
+
+/// GET /projects/:id/orchestrator/events - SSE stream of the project's pipeline events (see
+/// `crate::events`). Each carries its outbox id; a client reconnecting with `?after=<id>` (or the
+/// Last-Event-ID header) gets what it missed first, otherwise the stream starts with new events.
 async fn events(
-    State((_db, job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    State((db, job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
     Path(project_id): Path<i64>,
+    Query(query): Query<EventsQuery>,
+    headers: HeaderMap,
 ) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
-    // Subscribe to job events
-    let mut rx = job_manager.subscribe();
-    
-    // Create a stream from the broadcast receiver using unfold
-    let event_stream = stream::unfold((rx, project_id), |(mut rx, project_id)| async move {
-        match rx.recv().await {
-            Ok(event) => {
-                // Filter events for this project
-                let should_include = match &event {
-                    JobEvent::AnalysisComplete { project_id: pid, .. } => *pid == project_id,
-                    JobEvent::JobCompleted { .. } | JobEvent::JobFailed { .. } => {
-                        // For now, accept all job events (we can improve filtering later)
-                        true
-                    }
-                };
-                
-                if should_include {
-                    let json = serde_json::to_string(&event).unwrap_or_else(|_| "{}".to_string());
-                    Some((Ok(Event::default().data(json)), (rx, project_id)))
-                } else {
-                    // Continue stream with next event
-                    Some((Ok(Event::default().comment("filtered")), (rx, project_id)))
-                }
-            }
-            Err(_) => {
-                // Receiver closed or lagged, end stream
-                None
-            }
+    let last_event_id = headers
+        .get("last-event-id")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<i64>().ok());
+    let after = match query.after.or(last_event_id) {
+        Some(after) => after,
+        None => db.latest_event_id().unwrap_or(0),
+    };
+    let subscription = job_manager.events().subscribe_after(after, Some(project_id));
+
+    let event_stream = stream::unfold(subscription, |mut subscription| async move {
+        let published = subscription.recv().await?;
+        let json = serde_json::to_string(&published.event).unwrap_or_else(|_| "{}".to_string());
+        let mut event = Event::default().data(json);
+        if let Some(id) = published.id {
+            event = event.id(id.to_string());
         }
+        Some((Ok(event), subscription))
     });
 
     // Combine with keep-alive stream
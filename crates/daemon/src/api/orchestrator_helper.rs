@@ -1,5 +1,6 @@
 use crate::api::orchestrator::SegmentCandidate;
 use crate::db::Database;
+use engine::timeline::TICKS_PER_SECOND;
 use std::collections::HashMap;
 
 /// Diversify candidate segments by:
@@ -66,3 +67,84 @@ pub fn diversify_candidates(
 
     Ok(diversified)
 }
+
+/// Candidates scoring at least this fraction of the best score count as high-scoring and may be
+/// merged into moments; weaker ones pass through unchanged
+const MOMENT_MIN_SCORE_RATIO: f32 = 0.75;
+/// Max gap between two segments of one moment
+const MOMENT_MAX_GAP_TICKS: i64 = TICKS_PER_SECOND;
+/// A moment stops growing once it would span longer than this
+const MOMENT_MAX_DURATION_TICKS: i64 = 30 * TICKS_PER_SECOND;
+
+/// Merge temporally adjacent high-scoring segments of the same asset into one "moment"
+/// candidate. A moment is represented by its best-scoring member (id, summary, score) with the
+/// duration spanning all members and `moment_segment_ids` listing them in time order.
+pub fn group_into_moments(
+    candidates: Vec<SegmentCandidate>,
+    db: &Database,
+) -> anyhow::Result<Vec<SegmentCandidate>> {
+    let best_score = candidates
+        .iter()
+        .map(|c| c.similarity_score)
+        .fold(f32::NEG_INFINITY, f32::max);
+    let min_score = best_score * MOMENT_MIN_SCORE_RATIO;
+
+    // (src_in, src_out, candidate) of mergeable candidates per asset
+    let mut by_asset: HashMap<i64, Vec<(i64, i64, SegmentCandidate)>> = HashMap::new();
+    let mut grouped = Vec::new();
+    for candidate in candidates {
+        if candidate.similarity_score < min_score {
+            grouped.push(candidate);
+            continue;
+        }
+        match db.get_segment_with_embeddings(candidate.segment_id)? {
+            Some((segment, _)) => {
+                let src_in = Database::get_coalesced_src_in(&segment);
+                let src_out = Database::get_coalesced_src_out(&segment);
+                by_asset.entry(segment.media_asset_id).or_default().push((src_in, src_out, candidate));
+            }
+            None => grouped.push(candidate),
+        }
+    }
+
+    for (_asset_id, mut segments) in by_asset {
+        segments.sort_by_key(|(src_in, _, _)| *src_in);
+        let mut runs: Vec<(i64, i64, Vec<SegmentCandidate>)> = Vec::new();
+        for (src_in, src_out, candidate) in segments {
+            match runs.last_mut() {
+                Some((run_in, run_out, members))
+                    if src_in - *run_out <= MOMENT_MAX_GAP_TICKS
+                        && src_out.max(*run_out) - *run_in <= MOMENT_MAX_DURATION_TICKS =>
+                {
+                    *run_out = src_out.max(*run_out);
+                    members.push(candidate);
+                }
+                _ => runs.push((src_in, src_out, vec![candidate])),
+            }
+        }
+
+        for (run_in, run_out, members) in runs {
+            if members.len() == 1 {
+                grouped.extend(members);
+                continue;
+            }
+            let moment_segment_ids: Vec<i64> = members.iter().map(|c| c.segment_id).collect();
+            let capture_time = members.iter().find_map(|c| c.capture_time.clone());
+            let lead = members
+                .into_iter()
+                .max_by(|a, b| a.similarity_score.partial_cmp(&b.similarity_score).unwrap_or(std::cmp::Ordering::Equal))
+                .expect("run has members");
+            grouped.push(SegmentCandidate {
+                capture_time,
+                duration_sec: (run_out - run_in) as f64 / TICKS_PER_SECOND as f64,
+                moment_segment_ids,
+                ..lead
+            });
+        }
+    }
+
+    grouped.sort_by(|a, b| {
+        b.similarity_score.partial_cmp(&a.similarity_score).unwrap_or(std::cmp::Ordering::Equal)
+    });
+    Ok(grouped)
+}
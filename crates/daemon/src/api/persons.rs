@@ -0,0 +1,173 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+    routing::{get, patch},
+    Router,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::db::{Database, Person, SpeakerRef};
+use crate::lower_thirds;
+
+#[derive(Serialize)]
+pub struct SpeakerSummary {
+    #[serde(flatten)]
+    speaker: SpeakerRef,
+    /// Segments the speaker speaks most of
+    segments: i64,
+    /// Person the speaker has been matched to
+    person_id: Option<i64>,
+}
+
+#[derive(Serialize)]
+pub struct PersonsResponse {
+    persons: Vec<Person>,
+    /// Every speaker diarization found, matched to a person or not
+    speakers: Vec<SpeakerSummary>,
+}
+
+#[derive(Deserialize)]
+pub struct CreatePersonRequest {
+    name: String,
+    role: Option<String>,
+    #[serde(default)]
+    speakers: Vec<SpeakerRef>,
+}
+
+#[derive(Deserialize)]
+pub struct UpdatePersonRequest {
+    name: Option<String>,
+    /// Empty string clears it
+    role: Option<String>,
+    speakers: Option<Vec<SpeakerRef>>,
+}
+
+pub fn router(db: Arc<Database>) -> Router {
+    Router::new()
+        .route("/:id/persons", get(list_persons).post(create_person))
+        .route("/:id/persons/:pid", patch(update_person).delete(delete_person))
+        .with_state(db)
+}
+
+/// GET /projects/:id/persons - People, and the diarized speakers they can be matched to
+async fn list_persons(
+    State(db): State<Arc<Database>>,
+    Path(project_id): Path<i64>,
+) -> Result<Json<PersonsResponse>, StatusCode> {
+    find_project(&db, project_id)?;
+    let persons = db.list_persons(project_id).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let speakers = db
+        .list_project_speakers(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .into_iter()
+        .map(|(speaker, segments)| SpeakerSummary {
+            person_id: persons.iter().find(|p| p.speakers.contains(&speaker)).map(|p| p.id),
+            speaker,
+            segments,
+        })
+        .collect();
+    Ok(Json(PersonsResponse { persons, speakers }))
+}
+
+/// POST /projects/:id/persons - Name a person and match them to speakers; a lower third is
+/// suggested where they first speak in the cut
+async fn create_person(
+    State(db): State<Arc<Database>>,
+    Path(project_id): Path<i64>,
+    Json(req): Json<CreatePersonRequest>,
+) -> Result<Json<Person>, StatusCode> {
+    find_project(&db, project_id)?;
+    if req.name.trim().is_empty() {
+        eprintln!("[PERSONS] A person needs a name");
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    check_speakers(&db, project_id, None, &req.speakers)?;
+
+    let role = req.role.as_deref().map(str::trim).filter(|r| !r.is_empty());
+    let person_id = db
+        .create_person(project_id, req.name.trim(), role, &req.speakers)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    refresh_lower_thirds(&db, project_id);
+    find_person(&db, project_id, person_id).map(Json)
+}
+
+/// PATCH /projects/:id/persons/:pid - Rename a person, change their role or their speakers
+async fn update_person(
+    State(db): State<Arc<Database>>,
+    Path((project_id, person_id)): Path<(i64, i64)>,
+    Json(req): Json<UpdatePersonRequest>,
+) -> Result<Json<Person>, StatusCode> {
+    let person = find_person(&db, project_id, person_id)?;
+    let name = req.name.map(|n| n.trim().to_string()).unwrap_or(person.name);
+    if name.is_empty() {
+        eprintln!("[PERSONS] A person needs a name");
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let role = match req.role {
+        Some(role) => Some(role.trim().to_string()).filter(|r| !r.is_empty()),
+        None => person.role,
+    };
+    let speakers = req.speakers.unwrap_or(person.speakers);
+    check_speakers(&db, project_id, Some(person_id), &speakers)?;
+
+    db.update_person(person_id, &name, role.as_deref(), &speakers)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    refresh_lower_thirds(&db, project_id);
+    find_person(&db, project_id, person_id).map(Json)
+}
+
+/// DELETE /projects/:id/persons/:pid - Forget a person; their lower third comes off the timeline
+async fn delete_person(
+    State(db): State<Arc<Database>>,
+    Path((project_id, person_id)): Path<(i64, i64)>,
+) -> Result<StatusCode, StatusCode> {
+    find_person(&db, project_id, person_id)?;
+    let lower_thirds = db.list_lower_thirds(project_id).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if let Some(lower_third) = lower_thirds.iter().find(|l| l.person_id == person_id) {
+        lower_thirds::withdraw(&db, project_id, lower_third, "dismissed").map_err(|e| {
+            eprintln!("[LOWER_THIRDS] Failed to take lower third {} off the timeline: {:?}", lower_third.id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    }
+    db.delete_person(person_id).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Speakers must have been found in the project, and belong to one person at most
+fn check_speakers(db: &Database, project_id: i64, person_id: Option<i64>, speakers: &[SpeakerRef]) -> Result<(), StatusCode> {
+    let found = db.list_project_speakers(project_id).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let persons = db.list_persons(project_id).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    for speaker in speakers {
+        if !found.iter().any(|(s, _)| s == speaker) {
+            eprintln!("[PERSONS] Speaker {} wasn't found in asset {}", speaker.speaker, speaker.asset_id);
+            return Err(StatusCode::BAD_REQUEST);
+        }
+        if let Some(other) = persons.iter().find(|p| Some(p.id) != person_id && p.speakers.contains(speaker)) {
+            eprintln!("[PERSONS] Speaker {} in asset {} is already {}", speaker.speaker, speaker.asset_id, other.name);
+            return Err(StatusCode::CONFLICT);
+        }
+    }
+    Ok(())
+}
+
+fn refresh_lower_thirds(db: &Database, project_id: i64) {
+    if let Err(e) = lower_thirds::suggest(db, project_id) {
+        eprintln!("[LOWER_THIRDS] Failed to suggest lower thirds for project {}: {:?}", project_id, e);
+    }
+}
+
+fn find_project(db: &Database, project_id: i64) -> Result<(), StatusCode> {
+    db.get_project(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)
+        .map(|_| ())
+}
+
+fn find_person(db: &Database, project_id: i64, person_id: i64) -> Result<Person, StatusCode> {
+    db.get_person(person_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .filter(|p| p.project_id == project_id)
+        .ok_or(StatusCode::NOT_FOUND)
+}
@@ -0,0 +1,55 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+    routing::{get, post},
+    Router,
+};
+use serde_json::json;
+use std::sync::Arc;
+
+use crate::db::Database;
+use crate::plugins::{self, AnalyzerPlugin};
+
+pub fn router(db: Arc<Database>) -> Router {
+    Router::new()
+        .route("/:id/plugins/run", post(run_plugins))
+        .with_state(db)
+}
+
+pub fn plugins_router() -> Router {
+    Router::new().route("/", get(list_plugins))
+}
+
+/// GET /plugins - Analyzer plugins registered in ANALYZER_PLUGINS_PATH
+async fn list_plugins() -> Json<Vec<AnalyzerPlugin>> {
+    Json(plugins::configured_plugins().to_vec())
+}
+
+/// POST /projects/:id/plugins/run - Re-run analyzer plugins over every asset already in the
+/// project (e.g. after registering a new plugin). Runs in the background.
+async fn run_plugins(
+    State(db): State<Arc<Database>>,
+    Path(project_id): Path<i64>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    db.get_project(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let asset_ids: Vec<i64> = db
+        .get_media_assets_for_project(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .into_iter()
+        .map(|asset| asset.id)
+        .collect();
+
+    let queued = asset_ids.len();
+    tokio::spawn(async move {
+        for asset_id in asset_ids {
+            if let Err(e) = plugins::run_analyzers(&db, asset_id).await {
+                eprintln!("[PLUGINS] Analyzer plugins failed for asset {}: {:?}", asset_id, e);
+            }
+        }
+    });
+
+    Ok(Json(json!({ "status": "running", "assets": queued })))
+}
@@ -1,19 +1,34 @@
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
     response::Json,
-    routing::{delete, get, post},
+    routing::{delete, get, post, put},
     Router,
 };
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
+use crate::api::generate::load_style_profile;
+use crate::api::media::PathError;
+use crate::api::{library, quotas};
 use crate::db::Database;
+use crate::jobs::vision::VisionConfig;
+use crate::media::roots;
+use crate::orchestrator::persona::AgentPersona;
+use crate::orchestrator::state::{get_asset_readiness, AssetReadiness};
+use crate::templates::ProjectTemplate;
+use engine::timeline::{Timeline, TrackKind, TICKS_PER_SECOND};
+
+/// Rough time an editor spends finding, trimming and placing one clip by hand, used to
+/// estimate what the agent's cuts saved
+const MANUAL_SECONDS_PER_CUT: f64 = 90.0;
 
 #[derive(Deserialize)]
 pub struct CreateProjectRequest {
     name: String,
     cache_dir: String,
+    /// Library template to start from; the project gets its own copy
+    template_id: Option<i64>,
 }
 
 #[derive(Serialize)]
@@ -30,12 +45,82 @@ pub struct ProjectResponse {
     style_profile_id: Option<i64>,
 }
 
+#[derive(Serialize)]
+pub struct ProjectTemplateResponse {
+    /// Library template the copy came from (None when it was set directly)
+    template_id: Option<i64>,
+    template: ProjectTemplate,
+}
+
+#[derive(Deserialize)]
+pub struct UsageQuery {
+    /// How many recent calls to list (default 50)
+    limit: Option<i64>,
+}
+
+#[derive(Serialize)]
+pub struct EndpointUsage {
+    endpoint: String,
+    calls: i64,
+    failed_calls: i64,
+    prompt_tokens: i64,
+    completion_tokens: i64,
+    avg_latency_ms: i64,
+    estimated_cost_usd: f64,
+}
+
+#[derive(Serialize)]
+pub struct UsageResponse {
+    project_id: i64,
+    total_calls: i64,
+    total_prompt_tokens: i64,
+    total_completion_tokens: i64,
+    total_estimated_cost_usd: f64,
+    by_endpoint: Vec<EndpointUsage>,
+    recent_calls: Vec<serde_json::Value>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GlobalSearchSetting {
+    /// Whether the project's footage shows up in `GET /search`
+    enabled: bool,
+}
+
+#[derive(Serialize)]
+pub struct ProjectStatsResponse {
+    project_id: i64,
+    asset_count: usize,
+    footage_hours: f64,
+    /// Share of the footage (by duration) with transcripts and vision analysis
+    analyzed_percent: f64,
+    /// Share of the footage (by duration) that appears in the current cut
+    used_percent: f64,
+    clip_count: usize,
+    avg_clip_seconds: Option<f64>,
+    /// Median clip length of the project's style profile, when it has one
+    target_clip_seconds: Option<f64>,
+    /// Clips in the current cut that the agent placed
+    automated_clip_count: usize,
+    estimated_minutes_saved: f64,
+}
+
 pub fn router(db: Arc<Database>) -> Router {
     Router::new()
         .route("/", get(list_projects))
         .route("/", post(create_project))
         .route("/:id", get(get_project))
         .route("/:id", delete(delete_project))
+        .route("/:id/vision_config", get(get_vision_config))
+        .route("/:id/vision_config", put(set_vision_config))
+        .route("/:id/usage", get(get_usage))
+        .route("/:id/stats", get(get_stats))
+        .route("/:id/global_search", get(get_global_search))
+        .route("/:id/global_search", put(set_global_search))
+        .route("/:id/agent_persona", get(get_agent_persona))
+        .route("/:id/agent_persona", put(set_agent_persona))
+        .route("/:id/template", get(get_project_template))
+        .route("/:id/template", put(set_project_template))
+        .route("/:id/template", delete(delete_project_template))
         .with_state(db.clone())
 }
 
@@ -60,13 +145,37 @@ async fn list_projects(
     Ok(Json(responses))
 }
 
+/// POST /projects - With `Authorization: Bearer <API token>`, the project counts against the
+/// token's quotas
 async fn create_project(
     State(db): State<Arc<Database>>,
+    headers: HeaderMap,
     Json(req): Json<CreateProjectRequest>,
-) -> Result<Json<CreateProjectResponse>, StatusCode> {
+) -> Result<Json<CreateProjectResponse>, PathError> {
+    let token = quotas::request_token(&db, &headers)?;
+    let template_json = match req.template_id {
+        Some(template_id) => {
+            let (_, json, _) = db
+                .get_project_template(template_id)
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+                .ok_or(StatusCode::BAD_REQUEST)?;
+            Some(json)
+        }
+        None => None,
+    };
+
+    let cache_dir = roots::resolve(&req.cache_dir)?;
     let id = db
-        .create_project(&req.name, &req.cache_dir)
+        .create_project(&req.name, &cache_dir.to_string_lossy())
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if let Some(token) = token {
+        db.set_project_api_token(id, Some(token.id))
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    }
+    if let Some(json) = template_json {
+        db.set_project_template_copy(id, req.template_id, Some(&json))
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    }
     
     Ok(Json(CreateProjectResponse { id }))
 }
@@ -93,8 +202,297 @@ async fn delete_project(
     State(db): State<Arc<Database>>,
     Path(id): Path<i64>,
 ) -> Result<StatusCode, StatusCode> {
+    library::check_not_shared(&db, id)?;
     db.delete_project(id)
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     
     Ok(StatusCode::NO_CONTENT)
 }
+
+/// Get the project's vision sampling config (defaults if never set)
+async fn get_vision_config(
+    State(db): State<Arc<Database>>,
+    Path(id): Path<i64>,
+) -> Result<Json<VisionConfig>, StatusCode> {
+    db.get_project(id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let config = match db
+        .get_project_vision_config(id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+    {
+        Some(json) => serde_json::from_str(&json).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?,
+        None => VisionConfig::default(),
+    };
+
+    Ok(Json(config))
+}
+
+/// Replace the project's vision sampling config; applies to vision jobs queued afterwards
+async fn set_vision_config(
+    State(db): State<Arc<Database>>,
+    Path(id): Path<i64>,
+    Json(config): Json<VisionConfig>,
+) -> Result<Json<VisionConfig>, StatusCode> {
+    db.get_project(id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    if config.sample_interval_sec.is_nan() || config.sample_interval_sec <= 0.0 {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    if !(0.0..=1.0).contains(&config.tag_confidence_threshold) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let json = serde_json::to_string(&config).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    db.set_project_vision_config(id, Some(&json))
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(config))
+}
+
+async fn get_agent_persona(
+    State(db): State<Arc<Database>>,
+    Path(id): Path<i64>,
+) -> Result<Json<AgentPersona>, StatusCode> {
+    db.get_project(id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(Json(AgentPersona::for_project(&db, id)))
+}
+
+/// Replace the project's agent persona (tone, locale and per-message copy overrides)
+async fn set_agent_persona(
+    State(db): State<Arc<Database>>,
+    Path(id): Path<i64>,
+    Json(persona): Json<AgentPersona>,
+) -> Result<Json<AgentPersona>, StatusCode> {
+    db.get_project(id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    if let Err(message) = persona.validate() {
+        eprintln!("[PERSONA] Rejected persona for project {}: {}", id, message);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let json = serde_json::to_string(&persona).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    db.set_project_agent_persona(id, Some(&json))
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(persona))
+}
+
+async fn get_project_template(
+    State(db): State<Arc<Database>>,
+    Path(id): Path<i64>,
+) -> Result<Json<ProjectTemplateResponse>, StatusCode> {
+    db.get_project(id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let (template_id, json) = db
+        .get_project_template_copy(id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let template = serde_json::from_str(&json).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(ProjectTemplateResponse { template_id, template }))
+}
+
+/// Replace the project's copy of its template (e.g. to fill the music slot with a track);
+/// the library template is left alone. The music slot's track must be inside the media roots.
+async fn set_project_template(
+    State(db): State<Arc<Database>>,
+    Path(id): Path<i64>,
+    Json(mut template): Json<ProjectTemplate>,
+) -> Result<Json<ProjectTemplateResponse>, PathError> {
+    db.get_project(id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    if let Err(message) = template.validate() {
+        eprintln!("[TEMPLATES] Rejected template for project {}: {}", id, message);
+        return Err(StatusCode::BAD_REQUEST.into());
+    }
+    template.resolve_music()?;
+
+    let template_id = db
+        .get_project_template_copy(id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .and_then(|(template_id, _)| template_id);
+    let json = serde_json::to_string(&template).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    db.set_project_template_copy(id, template_id, Some(&json))
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(ProjectTemplateResponse { template_id, template }))
+}
+
+/// Stop using a template; generated edits go back to the style profile's structure
+async fn delete_project_template(
+    State(db): State<Arc<Database>>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, StatusCode> {
+    db.get_project(id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    db.set_project_template_copy(id, None, None)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn get_global_search(
+    State(db): State<Arc<Database>>,
+    Path(id): Path<i64>,
+) -> Result<Json<GlobalSearchSetting>, StatusCode> {
+    db.get_project(id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let enabled = db
+        .project_in_global_search(id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(GlobalSearchSetting { enabled }))
+}
+
+/// Include the project in search across projects, or keep it out (e.g. client work)
+async fn set_global_search(
+    State(db): State<Arc<Database>>,
+    Path(id): Path<i64>,
+    Json(setting): Json<GlobalSearchSetting>,
+) -> Result<Json<GlobalSearchSetting>, StatusCode> {
+    db.get_project(id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    db.set_project_global_search(id, setting.enabled)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(setting))
+}
+
+/// What the agent's LLM calls for this project cost: totals, per-endpoint breakdown, and the
+/// most recent calls (prompt summary, tokens, latency, status) for debugging bad generations
+async fn get_usage(
+    State(db): State<Arc<Database>>,
+    Path(id): Path<i64>,
+    Query(query): Query<UsageQuery>,
+) -> Result<Json<UsageResponse>, StatusCode> {
+    db.get_project(id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let by_endpoint: Vec<EndpointUsage> = db
+        .get_llm_usage_by_endpoint(id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .into_iter()
+        .map(|(endpoint, calls, failed_calls, prompt_tokens, completion_tokens, total_latency_ms, cost)| EndpointUsage {
+            endpoint,
+            calls,
+            failed_calls,
+            prompt_tokens,
+            completion_tokens,
+            avg_latency_ms: if calls > 0 { total_latency_ms / calls } else { 0 },
+            estimated_cost_usd: cost,
+        })
+        .collect();
+    let recent_calls = db
+        .get_llm_calls(id, query.limit.unwrap_or(50).clamp(1, 500))
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(UsageResponse {
+        project_id: id,
+        total_calls: by_endpoint.iter().map(|e| e.calls).sum(),
+        total_prompt_tokens: by_endpoint.iter().map(|e| e.prompt_tokens).sum(),
+        total_completion_tokens: by_endpoint.iter().map(|e| e.completion_tokens).sum(),
+        total_estimated_cost_usd: by_endpoint.iter().map(|e| e.estimated_cost_usd).sum(),
+        by_endpoint,
+        recent_calls,
+    }))
+}
+
+/// Throughput summary: footage imported and analyzed, how much of it the current cut uses,
+/// its pacing against the style target, and an estimate of the editing time automated cuts saved
+async fn get_stats(
+    State(db): State<Arc<Database>>,
+    Path(id): Path<i64>,
+) -> Result<Json<ProjectStatsResponse>, StatusCode> {
+    let project = db
+        .get_project(id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let assets = db
+        .get_media_assets_for_project(id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let total_ticks: i64 = assets.iter().map(|a| a.duration_ticks.max(0)).sum();
+    let mut analyzed_ticks = 0;
+    for asset in &assets {
+        let readiness = get_asset_readiness(&db, asset.id).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        if !matches!(readiness, AssetReadiness::Imported | AssetReadiness::Segmented) {
+            analyzed_ticks += asset.duration_ticks.max(0);
+        }
+    }
+
+    let timeline: Option<Timeline> = db
+        .get_timeline(id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .and_then(|json| serde_json::from_str(&json).ok());
+    let clips: Vec<_> = timeline
+        .iter()
+        .flat_map(|t| t.tracks.iter())
+        .filter(|t| t.kind == TrackKind::Video)
+        .flat_map(|t| t.clips.iter())
+        .collect();
+
+    // Source ranges used per asset, merged so footage used by several clips counts once
+    let mut used_ticks = 0;
+    for asset in &assets {
+        let mut ranges: Vec<(i64, i64)> = clips
+            .iter()
+            .filter(|c| c.asset_id == asset.id && c.out_ticks > c.in_ticks)
+            .map(|c| (c.in_ticks.max(0), c.out_ticks.min(asset.duration_ticks)))
+            .collect();
+        ranges.sort();
+        let mut covered_to = 0;
+        for (start, end) in ranges {
+            let start = start.max(covered_to);
+            if end > start {
+                used_ticks += end - start;
+                covered_to = end;
+            }
+        }
+    }
+
+    let clip_seconds: Vec<f64> = clips
+        .iter()
+        .map(|c| (c.out_ticks - c.in_ticks) as f64 / TICKS_PER_SECOND as f64 / c.speed.max(f64::EPSILON))
+        .collect();
+    let target_clip_seconds = load_style_profile(&db, project.style_profile_id, None)?
+        .and_then(|profile| profile["pacing_stats"]["median_clip_length"].as_f64());
+    let automated_clip_count = clips.iter().filter(|c| c.agent_placed).count();
+
+    let percent_of_footage = |ticks: i64| {
+        if total_ticks > 0 { ticks as f64 * 100.0 / total_ticks as f64 } else { 0.0 }
+    };
+    Ok(Json(ProjectStatsResponse {
+        project_id: id,
+        asset_count: assets.len(),
+        footage_hours: total_ticks as f64 / TICKS_PER_SECOND as f64 / 3600.0,
+        analyzed_percent: percent_of_footage(analyzed_ticks),
+        used_percent: percent_of_footage(used_ticks),
+        clip_count: clips.len(),
+        avg_clip_seconds: if clip_seconds.is_empty() {
+            None
+        } else {
+            Some(clip_seconds.iter().sum::<f64>() / clip_seconds.len() as f64)
+        },
+        target_clip_seconds,
+        automated_clip_count,
+        estimated_minutes_saved: automated_clip_count as f64 * MANUAL_SECONDS_PER_CUT / 60.0,
+    }))
+}
@@ -0,0 +1,283 @@
+use axum::{
+    extract::{Path, Request, State},
+    http::{header, HeaderMap, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Json, Response},
+    routing::{delete, get, put},
+    Router,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::db::{ApiToken, Database};
+use crate::media::companion::hash_token;
+use crate::quotas::{self, Metric, QuotaExceeded, QuotaLimits, Usage};
+
+#[derive(Serialize)]
+pub struct QuotaStatus {
+    usage: Usage,
+    limits: QuotaLimits,
+    /// Quotas with nothing left
+    exhausted: Vec<Metric>,
+}
+
+#[derive(Serialize)]
+pub struct TokenUsageResponse {
+    token: ApiToken,
+    /// Projects created with the token, whose usage is summed
+    projects: Vec<i64>,
+    #[serde(flatten)]
+    status: QuotaStatus,
+}
+
+#[derive(Serialize)]
+pub struct ProjectUsageResponse {
+    project_id: i64,
+    #[serde(flatten)]
+    status: QuotaStatus,
+    /// The API token the project was created with, whose quotas apply too
+    token: Option<TokenUsageResponse>,
+}
+
+#[derive(Deserialize)]
+pub struct CreateTokenRequest {
+    name: String,
+}
+
+#[derive(Serialize)]
+pub struct CreateTokenResponse {
+    #[serde(flatten)]
+    token: ApiToken,
+    /// Sent as `Authorization: Bearer <token>` when creating projects; only shown once
+    secret: String,
+}
+
+impl IntoResponse for QuotaExceeded {
+    fn into_response(self) -> Response {
+        (
+            StatusCode::TOO_MANY_REQUESTS,
+            Json(json!({
+                "error": "quota_exceeded",
+                "message": self.to_string(),
+                "scope": self.scope,
+                "scope_id": self.scope_id,
+                "metric": self.metric,
+                "limit": self.limit,
+                "used": self.used,
+            })),
+        )
+            .into_response()
+    }
+}
+
+/// Routes under /projects
+pub fn project_router(db: Arc<Database>) -> Router {
+    Router::new()
+        .route("/:id/quotas", get(get_project_usage).put(set_project_quotas))
+        .with_state(db)
+}
+
+/// Routes under /tokens
+pub fn router(db: Arc<Database>) -> Router {
+    Router::new()
+        .route("/", get(list_tokens).post(create_token))
+        .route("/:tid", delete(delete_token))
+        .route("/:tid/usage", get(get_token_usage))
+        .route("/:tid/quotas", put(set_token_quotas))
+        .with_state(db)
+}
+
+/// Metered project routes and what they use up
+fn metered(route: &str) -> Option<Metric> {
+    match route {
+        "import_raw" | "import_reference" => Some(Metric::StorageBytes),
+        "export" => Some(Metric::ExportsPerDay),
+        "orchestrator/propose"
+        | "orchestrator/plan"
+        | "orchestrator/proposals/candidates"
+        | "orchestrator/parse_intent"
+        | "orchestrator/edit" => Some(Metric::LlmCalls),
+        _ => None,
+    }
+}
+
+/// Middleware on the /projects routes: a metered request for a project (or token) with nothing
+/// left of that quota is answered 429 before any work starts
+pub async fn enforce(State(db): State<Arc<Database>>, request: Request, next: Next) -> Response {
+    if request.method() == Method::POST {
+        let path = request.uri().path().trim_start_matches('/');
+        if let Some((project_id, route)) = path.split_once('/') {
+            if let (Ok(project_id), Some(metric)) = (project_id.parse::<i64>(), metered(route)) {
+                // Room for at least one more
+                if let Err(e) = quotas::check(&db, project_id, metric, 1.0, None) {
+                    return match quotas::quota_error(&e) {
+                        Some(exceeded) => {
+                            eprintln!("[QUOTAS] {}", exceeded);
+                            exceeded.clone().into_response()
+                        }
+                        None => {
+                            eprintln!("[QUOTAS] Failed to check project {}'s quotas: {:?}", project_id, e);
+                            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+                        }
+                    };
+                }
+            }
+        }
+    }
+    next.run(request).await
+}
+
+/// 429 for work refused over a quota, 500 for anything else
+pub(crate) fn error_status(e: &anyhow::Error) -> StatusCode {
+    match quotas::quota_error(e) {
+        Some(exceeded) => {
+            eprintln!("[QUOTAS] {}", exceeded);
+            StatusCode::TOO_MANY_REQUESTS
+        }
+        None => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+/// The API token in the Authorization header, if one was sent (401 when it isn't valid)
+pub(crate) fn request_token(db: &Database, headers: &HeaderMap) -> Result<Option<ApiToken>, StatusCode> {
+    let Some(value) = headers.get(header::AUTHORIZATION) else { return Ok(None) };
+    let token = value
+        .to_str()
+        .ok()
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(str::trim)
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+    db.api_token_for_hash(&hash_token(token))
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::UNAUTHORIZED)
+        .map(Some)
+}
+
+/// GET /projects/:id/quotas - What the project (and its token) has used of each quota
+async fn get_project_usage(
+    State(db): State<Arc<Database>>,
+    Path(project_id): Path<i64>,
+) -> Result<Json<ProjectUsageResponse>, StatusCode> {
+    db.get_project(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let usage = quotas::project_usage(&db, project_id).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let limits = db
+        .get_quota_limits("project", project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let token = match db.get_project_api_token(project_id).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)? {
+        Some(token_id) => Some(token_usage(&db, token_id)?),
+        None => None,
+    };
+    Ok(Json(ProjectUsageResponse {
+        project_id,
+        status: quota_status(usage, limits),
+        token,
+    }))
+}
+
+/// PUT /projects/:id/quotas - Replace the project's limits (omitted ones are unlimited)
+async fn set_project_quotas(
+    State(db): State<Arc<Database>>,
+    Path(project_id): Path<i64>,
+    Json(limits): Json<QuotaLimits>,
+) -> Result<Json<ProjectUsageResponse>, StatusCode> {
+    db.get_project(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    check_limits(&limits)?;
+    db.set_quota_limits("project", project_id, &limits)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    get_project_usage(State(db), Path(project_id)).await
+}
+
+async fn list_tokens(State(db): State<Arc<Database>>) -> Result<Json<Vec<ApiToken>>, StatusCode> {
+    db.list_api_tokens().map(Json).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// POST /tokens - Hand out a token; projects created with it count against its quotas
+async fn create_token(
+    State(db): State<Arc<Database>>,
+    Json(req): Json<CreateTokenRequest>,
+) -> Result<Json<CreateTokenResponse>, StatusCode> {
+    let name = req.name.trim();
+    if name.is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let secret = format!("vctok_{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+    let id = db
+        .create_api_token(name, &hash_token(&secret))
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let token = find_token(&db, id)?;
+    Ok(Json(CreateTokenResponse { token, secret }))
+}
+
+/// DELETE /tokens/:tid - Revoke a token; its projects keep only their own quotas
+async fn delete_token(State(db): State<Arc<Database>>, Path(token_id): Path<i64>) -> Result<StatusCode, StatusCode> {
+    if db.delete_api_token(token_id).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)? {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(StatusCode::NOT_FOUND)
+    }
+}
+
+/// GET /tokens/:tid/usage - What the token's projects have used of its quotas
+async fn get_token_usage(
+    State(db): State<Arc<Database>>,
+    Path(token_id): Path<i64>,
+) -> Result<Json<TokenUsageResponse>, StatusCode> {
+    token_usage(&db, token_id).map(Json)
+}
+
+/// PUT /tokens/:tid/quotas - Replace the token's limits (omitted ones are unlimited)
+async fn set_token_quotas(
+    State(db): State<Arc<Database>>,
+    Path(token_id): Path<i64>,
+    Json(limits): Json<QuotaLimits>,
+) -> Result<Json<TokenUsageResponse>, StatusCode> {
+    find_token(&db, token_id)?;
+    check_limits(&limits)?;
+    db.set_quota_limits("token", token_id, &limits)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    token_usage(&db, token_id).map(Json)
+}
+
+fn check_limits(limits: &QuotaLimits) -> Result<(), StatusCode> {
+    if limits.analysis_minutes.is_some_and(|m| !m.is_finite() || m < 0.0) {
+        eprintln!("[QUOTAS] analysis_minutes must be a non-negative number");
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    Ok(())
+}
+
+fn quota_status(usage: Usage, limits: QuotaLimits) -> QuotaStatus {
+    let exhausted = Metric::ALL
+        .into_iter()
+        .filter(|metric| limits.limit(*metric).is_some_and(|limit| usage.get(*metric) >= limit))
+        .collect();
+    QuotaStatus { usage, limits, exhausted }
+}
+
+fn token_usage(db: &Database, token_id: i64) -> Result<TokenUsageResponse, StatusCode> {
+    let token = find_token(db, token_id)?;
+    let projects = db
+        .list_api_token_projects(token_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let usage = quotas::token_usage(db, token_id).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let limits = db
+        .get_quota_limits("token", token_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(TokenUsageResponse {
+        token,
+        projects,
+        status: quota_status(usage, limits),
+    })
+}
+
+fn find_token(db: &Database, token_id: i64) -> Result<ApiToken, StatusCode> {
+    db.get_api_token(token_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)
+}
@@ -0,0 +1,128 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Json,
+    routing::{get, patch, post},
+    Router,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use crate::db::{Database, RedactionSubject};
+use crate::jobs::{redaction, JobManager, JobType};
+
+#[derive(Deserialize)]
+pub struct DetectRedactionsRequest {
+    /// Segments to scan
+    segment_ids: Vec<i64>,
+    /// "face" and/or "plate" (default: both)
+    kinds: Option<Vec<String>>,
+}
+
+#[derive(Serialize)]
+pub struct DetectRedactionsResponse {
+    /// One DetectRedactions job per asset
+    job_ids: Vec<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct ListRedactionsQuery {
+    asset_id: Option<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateRedactionRequest {
+    /// Whether the subject is blurred on export
+    enabled: bool,
+}
+
+pub fn router(db: Arc<Database>, job_manager: Arc<JobManager>) -> Router {
+    Router::new()
+        .route("/:id/redactions", get(list_redactions))
+        .route("/:id/redactions/detect", post(detect_redactions))
+        .route("/:id/redactions/:rid", patch(update_redaction))
+        .with_state((db, job_manager))
+}
+
+/// POST /projects/:id/redactions/detect - Queue face/plate detection for the selected segments.
+/// Re-detecting a segment replaces its subjects.
+async fn detect_redactions(
+    State((db, job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path(project_id): Path<i64>,
+    Json(req): Json<DetectRedactionsRequest>,
+) -> Result<Json<DetectRedactionsResponse>, StatusCode> {
+    db.get_project(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    if req.segment_ids.is_empty() {
+        eprintln!("[REDACTION] No segments selected");
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let kinds = req
+        .kinds
+        .unwrap_or_else(|| redaction::KINDS.iter().map(|k| k.to_string()).collect());
+    if kinds.is_empty() || kinds.iter().any(|k| !redaction::KINDS.contains(&k.as_str())) {
+        eprintln!("[REDACTION] Kinds must be among {:?}", redaction::KINDS);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    // Group the selected segments by asset
+    let segments = db
+        .get_segments_for_project(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let mut by_asset: BTreeMap<i64, Vec<i64>> = BTreeMap::new();
+    for segment_id in &req.segment_ids {
+        let Some((segment, _)) = segments.iter().find(|(s, _)| s.id == *segment_id) else {
+            eprintln!("[REDACTION] Segment {} not found in project {}", segment_id, project_id);
+            return Err(StatusCode::BAD_REQUEST);
+        };
+        by_asset.entry(segment.media_asset_id).or_default().push(segment.id);
+    }
+
+    let mut job_ids = Vec::new();
+    for (asset_id, segment_ids) in by_asset {
+        let payload = json!({
+            "project_id": project_id,
+            "asset_id": asset_id,
+            "segment_ids": segment_ids,
+            "kinds": kinds,
+        });
+        let job_id = job_manager
+            .create_job(JobType::DetectRedactions, Some(payload), None)
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        job_ids.push(job_id);
+    }
+    Ok(Json(DetectRedactionsResponse { job_ids }))
+}
+
+/// GET /projects/:id/redactions - Detected faces and plates with their tracked regions
+async fn list_redactions(
+    State((db, _job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path(project_id): Path<i64>,
+    Query(query): Query<ListRedactionsQuery>,
+) -> Result<Json<Vec<RedactionSubject>>, StatusCode> {
+    db.list_redaction_subjects(project_id, query.asset_id)
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// PATCH /projects/:id/redactions/:rid - Turn blurring of one subject on or off
+async fn update_redaction(
+    State((db, _job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path((project_id, subject_id)): Path<(i64, i64)>,
+    Json(req): Json<UpdateRedactionRequest>,
+) -> Result<Json<RedactionSubject>, StatusCode> {
+    db.get_redaction_subject(subject_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .filter(|s| s.project_id == project_id)
+        .ok_or(StatusCode::NOT_FOUND)?;
+    db.set_redaction_subject_enabled(subject_id, req.enabled)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    db.get_redaction_subject(subject_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .map(Json)
+        .ok_or(StatusCode::NOT_FOUND)
+}
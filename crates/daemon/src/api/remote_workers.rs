@@ -0,0 +1,349 @@
+use axum::{
+    body::Body,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
+    routing::{delete, get, post},
+    Router,
+};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::io::AsyncWriteExt;
+
+use crate::db::{Database, RemoteWorker};
+use crate::jobs::remote::{self, ClaimedJob};
+use crate::jobs::{Job, JobManager, JobStatus};
+use crate::media::companion::hash_token;
+
+#[derive(Deserialize)]
+pub struct CreateWorkerRequest {
+    name: String,
+    /// Job types it takes (default: every remote-capable type)
+    job_types: Option<Vec<String>>,
+}
+
+#[derive(Serialize)]
+pub struct CreateWorkerResponse {
+    #[serde(flatten)]
+    worker: RemoteWorker,
+    /// Sent as `Authorization: Bearer <token>` on every queue request; only shown once
+    token: String,
+}
+
+#[derive(Serialize)]
+pub struct WorkerResponse {
+    #[serde(flatten)]
+    worker: RemoteWorker,
+    /// Checked in within the last minute
+    online: bool,
+    /// Jobs it's running now
+    job_ids: Vec<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct ClaimRequest {
+    /// Only claim these types (default: every type the worker was registered for)
+    job_types: Option<Vec<String>>,
+}
+
+#[derive(Deserialize)]
+pub struct HeartbeatRequest {
+    progress: Option<f64>,
+}
+
+#[derive(Serialize)]
+pub struct HeartbeatResponse {
+    lease_expires_at: String,
+}
+
+#[derive(Deserialize)]
+pub struct ArtifactQuery {
+    /// Byte offset of this chunk; must equal the bytes received so far
+    offset: u64,
+}
+
+#[derive(Serialize)]
+pub struct ArtifactResponse {
+    name: String,
+    received_bytes: u64,
+}
+
+#[derive(Deserialize)]
+pub struct CompleteRequest {
+    /// An analysis job's ML service response, or anything worth keeping about a render
+    #[serde(default)]
+    result: serde_json::Value,
+}
+
+#[derive(Deserialize)]
+pub struct FailRequest {
+    error: String,
+}
+
+#[derive(Serialize)]
+pub struct FailResponse {
+    /// Queued for another attempt (false once it has failed for good)
+    requeued: bool,
+}
+
+/// Routes under /api/remote_workers (registering and removing workers)
+pub fn router(db: Arc<Database>, job_manager: Arc<JobManager>) -> Router {
+    Router::new()
+        .route("/", get(list_workers).post(create_worker))
+        .route("/:wid", delete(delete_worker))
+        .with_state((db, job_manager))
+}
+
+/// Routes under /api/queue (worker side, authenticated with the worker token)
+pub fn queue_router(db: Arc<Database>, job_manager: Arc<JobManager>) -> Router {
+    Router::new()
+        .route("/claim", post(claim_job))
+        .route("/jobs/:id/inputs/:index", get(download_input))
+        .route("/jobs/:id/artifacts/:name", get(get_artifact).put(upload_artifact))
+        .route("/jobs/:id/heartbeat", post(heartbeat))
+        .route("/jobs/:id/complete", post(complete_job))
+        .route("/jobs/:id/fail", post(fail_job))
+        .with_state((db, job_manager))
+}
+
+/// POST /remote_workers - Register a worker; returns its token once
+async fn create_worker(
+    State((db, _job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Json(req): Json<CreateWorkerRequest>,
+) -> Result<Json<CreateWorkerResponse>, StatusCode> {
+    let name = req.name.trim();
+    if name.is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let job_types = req
+        .job_types
+        .unwrap_or_else(|| remote::REMOTE_JOB_TYPES.iter().map(|t| t.to_string()).collect());
+    if let Some(problem) = remote::validate_job_types(&job_types) {
+        eprintln!("[REMOTE] Invalid worker {}: {}", name, problem);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let token = remote::new_worker_token();
+    let id = db
+        .create_remote_worker(name, &hash_token(&token), &job_types)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let worker = db
+        .list_remote_workers()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .into_iter()
+        .find(|w| w.id == id)
+        .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(CreateWorkerResponse { worker, token }))
+}
+
+async fn list_workers(
+    State((db, job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+) -> Result<Json<Vec<WorkerResponse>>, StatusCode> {
+    let workers = db.list_remote_workers().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let claimed = job_manager
+        .claimed_jobs(None, None)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(
+        workers
+            .into_iter()
+            .map(|worker| WorkerResponse {
+                online: remote::is_online(&worker),
+                job_ids: claimed.iter().filter(|(_, w)| *w == worker.id).map(|(j, _)| *j).collect(),
+                worker,
+            })
+            .collect(),
+    ))
+}
+
+/// DELETE /remote_workers/:wid - Revoke a worker; jobs it was running go back to the queue
+async fn delete_worker(
+    State((db, job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path(worker_id): Path<i64>,
+) -> Result<StatusCode, StatusCode> {
+    if !db
+        .delete_remote_worker(worker_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+    {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    remote::release_worker_jobs(&job_manager, worker_id).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /queue/claim - Take the oldest pending job the worker can run; 204 when there's none
+async fn claim_job(
+    State((db, job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    headers: HeaderMap,
+    Json(req): Json<ClaimRequest>,
+) -> Result<Response, StatusCode> {
+    let worker = authenticated_worker(&db, &headers)?;
+    let claimed: Option<ClaimedJob> = remote::claim_next(&db, &job_manager, &worker, req.job_types.as_deref())
+        .map_err(|e| {
+            eprintln!("[REMOTE] Claim by worker {} failed: {:?}", worker.id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    Ok(match claimed {
+        Some(job) => Json(job).into_response(),
+        None => StatusCode::NO_CONTENT.into_response(),
+    })
+}
+
+/// GET /queue/jobs/:id/inputs/:index - Download one of a claimed job's input files
+async fn download_input(
+    State((db, job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path((job_id, index)): Path<(i64, usize)>,
+    headers: HeaderMap,
+) -> Result<Response, StatusCode> {
+    let worker = authenticated_worker(&db, &headers)?;
+    let job = claimed_job(&job_manager, &worker, job_id)?;
+    let input = remote::inputs(&job)
+        .into_iter()
+        .find(|i| i.index == index)
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let file = tokio::fs::File::open(&input.path)
+        .await
+        .map_err(|_| StatusCode::NOT_FOUND)?;
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "application/octet-stream")
+        .header(header::CONTENT_LENGTH, input.size_bytes.to_string())
+        .body(Body::from_stream(tokio_util::io::ReaderStream::new(file)))
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// GET /queue/jobs/:id/artifacts/:name - Bytes of an artifact received so far (where to resume)
+async fn get_artifact(
+    State((db, job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path((job_id, name)): Path<(i64, String)>,
+    headers: HeaderMap,
+) -> Result<Json<ArtifactResponse>, StatusCode> {
+    let worker = authenticated_worker(&db, &headers)?;
+    claimed_job(&job_manager, &worker, job_id)?;
+    let received_bytes = tokio::fs::metadata(remote::artifact_path(job_id, &name))
+        .await
+        .map(|m| m.len())
+        .unwrap_or(0);
+    Ok(Json(ArtifactResponse { name, received_bytes }))
+}
+
+/// PUT /queue/jobs/:id/artifacts/:name?offset=N - Append a chunk of a rendered file. A chunk at
+/// the wrong offset gets 409 (read `received_bytes` and resume from there).
+async fn upload_artifact(
+    State((db, job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path((job_id, name)): Path<(i64, String)>,
+    Query(query): Query<ArtifactQuery>,
+    headers: HeaderMap,
+    body: Body,
+) -> Result<Json<ArtifactResponse>, StatusCode> {
+    let worker = authenticated_worker(&db, &headers)?;
+    claimed_job(&job_manager, &worker, job_id)?;
+    let path = remote::artifact_path(job_id, &name);
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    }
+    let received = tokio::fs::metadata(&path).await.map(|m| m.len()).unwrap_or(0);
+    if query.offset != received {
+        eprintln!(
+            "[REMOTE] Job {} artifact {} chunk at offset {}, but {} bytes were received",
+            job_id, name, query.offset, received
+        );
+        return Err(StatusCode::CONFLICT);
+    }
+
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let mut written = received;
+    let mut stream = body.into_data_stream();
+    while let Some(chunk) = stream.next().await {
+        // A dropped connection keeps what arrived; the worker resumes from received_bytes
+        let Ok(chunk) = chunk else { break };
+        file.write_all(&chunk).await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        written += chunk.len() as u64;
+    }
+    file.flush().await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(ArtifactResponse { name, received_bytes: written }))
+}
+
+/// POST /queue/jobs/:id/heartbeat - Report progress and keep the claim; 409 means stop (the job
+/// was cancelled or given to another worker)
+async fn heartbeat(
+    State((db, job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path(job_id): Path<i64>,
+    headers: HeaderMap,
+    Json(req): Json<HeartbeatRequest>,
+) -> Result<Json<HeartbeatResponse>, StatusCode> {
+    let worker = authenticated_worker(&db, &headers)?;
+    let job = claimed_job(&job_manager, &worker, job_id)?;
+    if req.progress.is_some_and(|p| !p.is_finite()) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let lease_expires_at = remote::heartbeat(&job_manager, &job, worker.id, req.progress)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::CONFLICT)?;
+    Ok(Json(HeartbeatResponse { lease_expires_at }))
+}
+
+/// POST /queue/jobs/:id/complete - Finish a claimed job with its result (and uploaded artifacts)
+async fn complete_job(
+    State((db, job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path(job_id): Path<i64>,
+    headers: HeaderMap,
+    Json(req): Json<CompleteRequest>,
+) -> Result<Json<()>, StatusCode> {
+    let worker = authenticated_worker(&db, &headers)?;
+    let job = claimed_job(&job_manager, &worker, job_id)?;
+    if let Err(e) = remote::complete(&db, &job_manager, &job, req.result).await {
+        // Nothing was applied; the worker can upload what's missing and complete again
+        eprintln!("[REMOTE] Job {} from worker {} couldn't be completed: {:?}", job_id, worker.id, e);
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+    eprintln!("[REMOTE] Worker {} completed job {}", worker.id, job_id);
+    Ok(Json(()))
+}
+
+/// POST /queue/jobs/:id/fail - Give up on a claimed job; it's retried until MAX_ATTEMPTS
+async fn fail_job(
+    State((db, job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path(job_id): Path<i64>,
+    headers: HeaderMap,
+    Json(req): Json<FailRequest>,
+) -> Result<Json<FailResponse>, StatusCode> {
+    let worker = authenticated_worker(&db, &headers)?;
+    let job = claimed_job(&job_manager, &worker, job_id)?;
+    eprintln!("[REMOTE] Worker {} failed job {}: {}", worker.id, job_id, req.error);
+    let requeued = remote::fail(&job_manager, &job, &req.error).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(FailResponse { requeued }))
+}
+
+/// The registered worker whose token is in the Authorization header
+fn authenticated_worker(db: &Database, headers: &HeaderMap) -> Result<RemoteWorker, StatusCode> {
+    let token = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(str::trim)
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+    db.remote_worker_for_token(&hash_token(token))
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::UNAUTHORIZED)
+}
+
+/// A job this worker is running (404 if it doesn't exist, 409 once it's no longer the worker's)
+fn claimed_job(job_manager: &JobManager, worker: &RemoteWorker, job_id: i64) -> Result<Job, StatusCode> {
+    let job = job_manager
+        .get_job(job_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    if job.worker_id != Some(worker.id) || !matches!(job.status, JobStatus::Running) {
+        return Err(StatusCode::CONFLICT);
+    }
+    Ok(job)
+}
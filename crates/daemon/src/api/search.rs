@@ -0,0 +1,84 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::Json,
+    routing::get,
+    Router,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::db::{Database, GlobalSearchHit};
+
+#[derive(Deserialize)]
+pub struct GlobalSearchQuery {
+    q: String,
+    /// Only footage captured on or after this date prefix ("2024-06", "2024-06-21")
+    from: Option<String>,
+    /// Only footage captured on or before this date prefix
+    to: Option<String>,
+    /// Most hits to return across all projects (default 100)
+    limit: Option<usize>,
+}
+
+#[derive(Serialize)]
+pub struct ProjectHits {
+    project_id: i64,
+    project_name: String,
+    hits: Vec<GlobalSearchHit>,
+}
+
+#[derive(Serialize)]
+pub struct GlobalSearchResponse {
+    query: String,
+    total: usize,
+    /// Projects ordered by their best hit; hits within a project best first
+    projects: Vec<ProjectHits>,
+}
+
+pub fn router(db: Arc<Database>) -> Router {
+    Router::new()
+        .route("/", get(search))
+        .with_state(db)
+}
+
+/// GET /search?q= - Search transcripts and on-screen text across every project that hasn't
+/// opted out, grouped by project
+async fn search(
+    State(db): State<Arc<Database>>,
+    Query(query): Query<GlobalSearchQuery>,
+) -> Result<Json<GlobalSearchResponse>, StatusCode> {
+    if query.q.trim().is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let hits = db
+        .search_segments_global(
+            &query.q,
+            query.from.as_deref(),
+            query.to.as_deref(),
+            query.limit.unwrap_or(100).clamp(1, 500),
+        )
+        .map_err(|e| {
+            eprintln!("[SEARCH] Global search failed: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let total = hits.len();
+    let mut projects: Vec<ProjectHits> = Vec::new();
+    for hit in hits {
+        match projects.iter_mut().find(|p| p.project_id == hit.project_id) {
+            Some(group) => group.hits.push(hit),
+            None => projects.push(ProjectHits {
+                project_id: hit.project_id,
+                project_name: hit.project_name.clone(),
+                hits: vec![hit],
+            }),
+        }
+    }
+
+    Ok(Json(GlobalSearchResponse {
+        query: query.q,
+        total,
+        projects,
+    }))
+}
@@ -0,0 +1,293 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Json,
+    routing::{get, post},
+    Router,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::db::{Database, Segment};
+use crate::embeddings;
+use crate::embeddings::registry::{TEXT_MODEL, VISION_MODEL};
+use crate::retrieval::feedback::segment_embedding;
+use crate::retrieval::prefilter::{used_segment_ids, SegmentPrefilter};
+use crate::jobs::vision::VisionConfig;
+
+const DEFAULT_PAGE_SIZE: usize = 100;
+const DEFAULT_SIMILAR_LIMIT: usize = 20;
+const MAX_SIMILAR_LIMIT: usize = 100;
+/// Unreferenced dynamic segments younger than this are kept: a retrieval that just created them
+/// may not have stored its proposal yet
+pub const DYNAMIC_SEGMENT_MIN_AGE_SECS: i64 = 3600;
+
+#[derive(Deserialize)]
+struct SegmentBrowserQuery {
+    /// Comma-separated tag labels; a segment must carry all of them
+    tags: Option<String>,
+    /// Restrict tags (filters and facets) to one kind: "scene", "object", "action" or "audio"
+    kind: Option<String>,
+    /// Overrides the project's tag confidence threshold
+    min_confidence: Option<f64>,
+    limit: Option<usize>,
+    offset: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct SegmentTag {
+    label: String,
+    kind: String,
+    confidence: f64,
+}
+
+#[derive(Serialize)]
+struct SegmentBrowserItem {
+    id: i64,
+    media_asset_id: i64,
+    src_in_ticks: i64,
+    src_out_ticks: i64,
+    summary_text: Option<String>,
+    transcript: Option<String>,
+    language: Option<String>,
+    shot_type: Option<String>,
+    tags: Vec<SegmentTag>,
+}
+
+#[derive(Serialize)]
+struct TagFacet {
+    label: String,
+    kind: String,
+    count: usize,
+}
+
+#[derive(Serialize)]
+struct SegmentBrowserResponse {
+    segments: Vec<SegmentBrowserItem>,
+    /// Tag counts across all matching segments (not just the current page)
+    facets: Vec<TagFacet>,
+    total: usize,
+    min_confidence: f64,
+}
+
+pub fn router(db: Arc<Database>) -> Router {
+    Router::new()
+        .route("/:id/segments", get(browse_segments))
+        .route("/:id/segments/dynamic/cleanup", post(cleanup_dynamic_segments))
+        .route("/:id/segments/:sid/similar", post(similar_segments))
+        .with_state(db)
+}
+
+#[derive(Deserialize, Default)]
+struct SimilarSegmentsRequest {
+    limit: Option<usize>,
+    /// Also return segments already on the timeline (default: unused only)
+    include_used: Option<bool>,
+}
+
+#[derive(Serialize)]
+struct SimilarSegment {
+    id: i64,
+    media_asset_id: i64,
+    src_in_ticks: i64,
+    src_out_ticks: i64,
+    summary_text: Option<String>,
+    similarity: f32,
+}
+
+#[derive(Serialize)]
+struct SimilarSegmentsResponse {
+    segment_id: i64,
+    embedding_type: String,
+    segments: Vec<SimilarSegment>,
+}
+
+/// "Find more like this": rank the project's other (by default unused) segments by similarity
+/// to this segment's own text embedding, or its vision embedding when it has no text one
+async fn similar_segments(
+    State(db): State<Arc<Database>>,
+    Path((project_id, segment_id)): Path<(i64, i64)>,
+    body: Option<Json<SimilarSegmentsRequest>>,
+) -> Result<Json<SimilarSegmentsResponse>, StatusCode> {
+    let req = body.map(|Json(req)| req).unwrap_or_default();
+    let (segment, _) = db
+        .get_segment_with_embeddings(segment_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    if segment.project_id != project_id {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    let Some((embedding_type, query)) = segment_embedding(&db, segment_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+    else {
+        eprintln!("[SEGMENTS] Segment {} has no embedding yet", segment_id);
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    };
+    let model = if embedding_type == VISION_MODEL.embedding_type { VISION_MODEL } else { TEXT_MODEL };
+
+    let mut exclude_segment_ids = if req.include_used.unwrap_or(false) {
+        Vec::new()
+    } else {
+        used_segment_ids(&db, project_id).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+    };
+    exclude_segment_ids.push(segment_id);
+    let candidate_ids = SegmentPrefilter {
+        project_id,
+        exclude_segment_ids,
+        ..Default::default()
+    }
+    .segment_ids(&db)
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let limit = req.limit.unwrap_or(DEFAULT_SIMILAR_LIMIT).clamp(1, MAX_SIMILAR_LIMIT);
+    let ranked = embeddings::similarity_search_among(
+        db.clone(),
+        &query,
+        model.embedding_type,
+        model.model_name,
+        limit,
+        &candidate_ids,
+    )
+    .map_err(|e| {
+        eprintln!("[SEGMENTS] Similar segment search failed: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let mut segments = Vec::with_capacity(ranked.len());
+    for (id, similarity) in ranked {
+        if let Some((similar, _)) = db
+            .get_segment_with_embeddings(id)
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        {
+            segments.push(SimilarSegment {
+                id,
+                media_asset_id: similar.media_asset_id,
+                src_in_ticks: Database::get_coalesced_src_in(&similar),
+                src_out_ticks: Database::get_coalesced_src_out(&similar),
+                summary_text: similar.summary_text,
+                similarity,
+            });
+        }
+    }
+
+    Ok(Json(SimilarSegmentsResponse {
+        segment_id,
+        embedding_type,
+        segments,
+    }))
+}
+
+#[derive(Serialize)]
+struct DynamicCleanupResponse {
+    removed: usize,
+}
+
+/// Remove the project's dynamic (search-created) segments that no proposal or timeline uses
+async fn cleanup_dynamic_segments(
+    State(db): State<Arc<Database>>,
+    Path(project_id): Path<i64>,
+) -> Result<Json<DynamicCleanupResponse>, StatusCode> {
+    let removed = db
+        .cleanup_dynamic_segments(Some(project_id), DYNAMIC_SEGMENT_MIN_AGE_SECS)
+        .map_err(|e| {
+            eprintln!("[SEGMENTS] Dynamic segment cleanup failed: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    Ok(Json(DynamicCleanupResponse { removed }))
+}
+
+/// Structured tags of a segment that clear the confidence threshold (and kind filter)
+fn visible_tags(segment: &Segment, min_confidence: f64, kind: Option<&str>) -> Vec<SegmentTag> {
+    let tags: Vec<SegmentTag> = segment
+        .tags_json
+        .as_deref()
+        .and_then(|json| serde_json::from_str(json).ok())
+        .unwrap_or_default();
+
+    tags.into_iter()
+        .filter(|tag| tag.confidence >= min_confidence)
+        .filter(|tag| kind.is_none_or(|k| tag.kind == k))
+        .collect()
+}
+
+/// List a project's segments filtered by vision tags, with tag facets for filter UIs
+async fn browse_segments(
+    State(db): State<Arc<Database>>,
+    Path(project_id): Path<i64>,
+    Query(query): Query<SegmentBrowserQuery>,
+) -> Result<Json<SegmentBrowserResponse>, StatusCode> {
+    db.get_project(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let min_confidence = match query.min_confidence {
+        Some(value) => value,
+        None => VisionConfig::resolve_for_project(&db, project_id)
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+            .tag_confidence_threshold,
+    };
+    let kind = query.kind.as_deref();
+    let required: Vec<String> = query
+        .tags
+        .as_deref()
+        .unwrap_or("")
+        .split(',')
+        .map(|t| t.trim().to_lowercase())
+        .filter(|t| !t.is_empty())
+        .collect();
+
+    let segments = db
+        .get_segments_for_project(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let mut matching = Vec::new();
+    let mut facets: Vec<TagFacet> = Vec::new();
+    for (segment, _) in &segments {
+        let tags = visible_tags(segment, min_confidence, kind);
+        let has_all = required
+            .iter()
+            .all(|r| tags.iter().any(|t| t.label.to_lowercase() == *r));
+        if !has_all {
+            continue;
+        }
+
+        for tag in &tags {
+            match facets.iter_mut().find(|f| f.label == tag.label && f.kind == tag.kind) {
+                Some(facet) => facet.count += 1,
+                None => facets.push(TagFacet {
+                    label: tag.label.clone(),
+                    kind: tag.kind.clone(),
+                    count: 1,
+                }),
+            }
+        }
+
+        matching.push(SegmentBrowserItem {
+            id: segment.id,
+            media_asset_id: segment.media_asset_id,
+            src_in_ticks: Database::get_coalesced_src_in(segment),
+            src_out_ticks: Database::get_coalesced_src_out(segment),
+            summary_text: segment.summary_text.clone(),
+            transcript: segment.transcript.clone(),
+            language: segment.language.clone(),
+            shot_type: segment.shot_type.clone(),
+            tags,
+        });
+    }
+
+    facets.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.label.cmp(&b.label)));
+
+    let total = matching.len();
+    let page: Vec<SegmentBrowserItem> = matching
+        .into_iter()
+        .skip(query.offset.unwrap_or(0))
+        .take(query.limit.unwrap_or(DEFAULT_PAGE_SIZE))
+        .collect();
+
+    Ok(Json(SegmentBrowserResponse {
+        segments: page,
+        facets,
+        total,
+        min_confidence,
+    }))
+}
@@ -2,7 +2,7 @@ use axum::{
     extract::{Path, State},
     http::StatusCode,
     response::Json,
-    routing::post,
+    routing::{get, post},
     Router,
 };
 use chrono::Utc;
@@ -10,10 +10,16 @@ use rusqlite;
 use serde::{Deserialize, Serialize};
 use std::{path::PathBuf, sync::Arc};
 
+use crate::api::media::PathError;
 use crate::db::Database;
 use crate::jobs::{JobManager, JobType};
+use crate::media::beat_template::{extract_beat_template, BeatTemplate};
 use crate::media::ffmpeg::FFmpegWrapper;
+use crate::media::segment_kind::SegmentKind;
 use crate::media::compute_file_checksum;
+use crate::media::roots;
+use crate::style::blend::blend_profiles;
+use crate::style::presets::builtin_presets;
 use serde_json::json;
 
 #[derive(Deserialize, Clone, Debug)]
@@ -53,6 +59,130 @@ pub struct StyleProfileResponse {
     structure: serde_json::Value,
 }
 
+#[derive(Serialize)]
+pub struct StylePresetResponse {
+    id: i64,
+    key: String,
+    name: String,
+    aliases: Vec<String>,
+}
+
+#[derive(Deserialize)]
+pub struct BlendComponent {
+    pub profile_id: i64,
+    pub weight: f64,
+}
+
+#[derive(Deserialize)]
+pub struct BlendProfilesRequest {
+    pub profiles: Vec<BlendComponent>,
+    pub name: Option<String>,
+    /// Return the blended profile without storing it
+    #[serde(default)]
+    pub preview: bool,
+    /// Make the stored blend this project's style profile (used by generate)
+    pub project_id: Option<i64>,
+}
+
+#[derive(Serialize)]
+pub struct BlendProfilesResponse {
+    /// None for previews
+    profile_id: Option<i64>,
+    profile: serde_json::Value,
+}
+
+/// Routes for style profiles that aren't project-scoped (presets, blends)
+pub fn profiles_router(db: Arc<Database>) -> Router {
+    Router::new()
+        .route("/presets", get(list_style_presets))
+        .route("/blend", post(blend_style_profiles))
+        .with_state(db)
+}
+
+/// Blend two or more style profiles by weight (e.g. 70% reference-derived + 30% "travel_vlog")
+/// Weights are normalized; the blend records its sources under "blended_from"
+async fn blend_style_profiles(
+    State(db): State<Arc<Database>>,
+    Json(req): Json<BlendProfilesRequest>,
+) -> Result<Json<BlendProfilesResponse>, StatusCode> {
+    if req.profiles.len() < 2 || req.profiles.iter().any(|c| c.weight.is_nan() || c.weight < 0.0) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let total_weight: f64 = req.profiles.iter().map(|c| c.weight).sum();
+    if total_weight <= 0.0 {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let mut components = Vec::with_capacity(req.profiles.len());
+    for component in &req.profiles {
+        let json = db
+            .get_style_profile(component.profile_id)
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+            .ok_or(StatusCode::NOT_FOUND)?;
+        let profile: serde_json::Value = serde_json::from_str(&json)
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        components.push((profile, component.weight / total_weight));
+    }
+
+    let mut profile = blend_profiles(&components);
+    profile["blended_from"] = json!(req.profiles
+        .iter()
+        .map(|c| json!({ "profile_id": c.profile_id, "weight": c.weight / total_weight }))
+        .collect::<Vec<_>>());
+
+    if req.preview {
+        return Ok(Json(BlendProfilesResponse { profile_id: None, profile }));
+    }
+
+    if let Some(project_id) = req.project_id {
+        db.get_project(project_id)
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+            .ok_or(StatusCode::NOT_FOUND)?;
+    }
+
+    let name = req.name.clone().unwrap_or_else(|| {
+        let parts: Vec<String> = req.profiles
+            .iter()
+            .map(|c| format!("{:.0}% #{}", 100.0 * c.weight / total_weight, c.profile_id))
+            .collect();
+        format!("Blend ({})", parts.join(" + "))
+    });
+    let profile_id = db
+        .create_style_profile(&name, &profile.to_string())
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    if let Some(project_id) = req.project_id {
+        db.set_project_style_profile(project_id, Some(profile_id))
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    }
+
+    Ok(Json(BlendProfilesResponse { profile_id: Some(profile_id), profile }))
+}
+
+/// List built-in style presets; pass a key, name or alias as `vibe` when generating
+async fn list_style_presets(
+    State(db): State<Arc<Database>>,
+) -> Result<Json<Vec<StylePresetResponse>>, StatusCode> {
+    let stored = db
+        .list_style_presets()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let presets = builtin_presets();
+    let responses = stored
+        .into_iter()
+        .map(|(id, key, name)| {
+            let aliases = presets
+                .iter()
+                .find(|p| p.key == key)
+                .map(|p| p.aliases.iter().map(|a| a.to_string()).collect())
+                .unwrap_or_default();
+            StylePresetResponse { id, key, name, aliases }
+        })
+        .collect();
+
+    Ok(Json(responses))
+}
+
 pub fn router(db: Arc<Database>, job_manager: Arc<JobManager>) -> Router {
     Router::new()
         .route("/:id/import_reference", post(import_reference))
@@ -64,7 +194,7 @@ async fn import_reference(
     State((db, job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
     Path(project_id): Path<i64>,
     Json(req): Json<ImportReferenceRequest>,
-) -> Result<Json<ImportReferenceResponse>, StatusCode> {
+) -> Result<Json<ImportReferenceResponse>, PathError> {
     // Verify project exists
     let _project = db
         .get_project(project_id)
@@ -74,7 +204,7 @@ async fn import_reference(
     // Validate that at least one field is provided
     if req.file_paths.is_none() && req.folder_path.is_none() {
         eprintln!("Import reference request missing both file_paths and folder_path");
-        return Err(StatusCode::BAD_REQUEST);
+        return Err(StatusCode::BAD_REQUEST.into());
     }
 
     // Debug logging
@@ -83,19 +213,20 @@ async fn import_reference(
     // Handle individual file paths - create a separate job for each file
     if let Some(file_paths) = req.file_paths {
         if file_paths.is_empty() {
-            return Err(StatusCode::BAD_REQUEST);
+            return Err(StatusCode::BAD_REQUEST.into());
         }
+        // Every path is checked before any job is queued
+        let file_paths = file_paths.iter().map(roots::resolve).collect::<Result<Vec<_>, _>>()?;
 
         let mut job_ids = Vec::new();
         let db_clone = db.clone();
         let job_manager_clone = job_manager.clone();
 
         // Create a separate job for each file
-        for file_path_str in file_paths {
-            let video_path = PathBuf::from(&file_path_str);
+        for video_path in file_paths {
             let job_payload = json!({
                 "project_id": project_id,
-                "file_path": file_path_str,
+                "file_path": video_path.to_string_lossy(),
                 "is_reference": true,
             });
 
@@ -133,9 +264,10 @@ async fn import_reference(
         }))
     } else if let Some(folder_path) = req.folder_path {
         // Folder scanning mode - single job for all files in folder
+        let folder = roots::resolve(&folder_path)?;
         let job_payload = json!({
             "project_id": project_id,
-            "folder_path": folder_path,
+            "folder_path": folder.to_string_lossy(),
             "is_reference": true,
         });
 
@@ -146,8 +278,7 @@ async fn import_reference(
         // Spawn async task to process import
         let db_clone = db.clone();
         let job_manager_clone = job_manager.clone();
-        let folder = PathBuf::from(&folder_path);
-        
+
         tokio::spawn(async move {
             if !folder.exists() {
                 return;
@@ -171,7 +302,7 @@ async fn import_reference(
             style_profile_id: None,
         }))
     } else {
-        Err(StatusCode::BAD_REQUEST)
+        Err(StatusCode::BAD_REQUEST.into())
     }
 }
 
@@ -246,11 +377,15 @@ async fn process_single_video_reference(
         media_info.has_audio,
         true, // This is a reference asset
     )?;
+    if media_info.rotation != 0 {
+        db.set_media_asset_rotation(asset_id, media_info.rotation)?;
+    }
 
     // Queue proxy generation job
     let proxy_job_payload = json!({
         "media_asset_id": asset_id,
         "input_path": video_path.to_str().unwrap(),
+        "tiers": crate::media::proxy::eager_tier_names(),
     });
     let _proxy_job_id = job_manager.create_job(JobType::GenerateProxy, Some(proxy_job_payload), None)?;
 
@@ -309,7 +444,11 @@ async fn process_import_reference(
                 if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
                     let ext_lower = ext.to_lowercase();
                     if video_extensions.contains(&ext_lower.as_str()) {
-                        video_files.push(path);
+                        // A symlink in the folder may point outside the allowed roots
+                        match roots::resolve(&path) {
+                            Ok(path) => video_files.push(path),
+                            Err(e) => eprintln!("[ROOTS] Skipping {}", e),
+                        }
                     }
                 }
             }
@@ -398,6 +537,81 @@ async fn profile_from_references(
         0.0
     };
     
+    // Shot type distribution and how often consecutive reference shots change framing
+    let mut shot_type_counts: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
+    let mut shot_changes = 0usize;
+    let mut shot_pairs = 0usize;
+    for asset_id in &req.reference_asset_ids {
+        let shot_types: Vec<&String> = all_segments.iter()
+            .filter(|s| s.media_asset_id == *asset_id)
+            .filter_map(|s| s.shot_type.as_ref())
+            .collect();
+        for shot_type in &shot_types {
+            *shot_type_counts.entry((*shot_type).clone()).or_insert(0) += 1;
+        }
+        for pair in shot_types.windows(2) {
+            shot_pairs += 1;
+            if pair[0] != pair[1] {
+                shot_changes += 1;
+            }
+        }
+    }
+    let total_typed: usize = shot_type_counts.values().sum();
+    let shot_type_distribution: serde_json::Map<String, serde_json::Value> = shot_type_counts.iter()
+        .map(|(k, v)| (k.clone(), serde_json::json!(*v as f64 / total_typed as f64)))
+        .collect();
+    let shot_alternation_rate = if shot_pairs > 0 {
+        Some(shot_changes as f64 / shot_pairs as f64)
+    } else {
+        None // No shot types classified yet
+    };
+
+    // Segment kind distribution (by duration) and how much of it is a-roll, over the
+    // classified segments
+    let mut kind_seconds: std::collections::BTreeMap<SegmentKind, f64> = std::collections::BTreeMap::new();
+    for (segment, duration) in all_segments.iter().zip(&durations) {
+        if let Some(kind) = SegmentKind::of(segment) {
+            *kind_seconds.entry(kind).or_insert(0.0) += duration;
+        }
+    }
+    let classified_seconds: f64 = kind_seconds.values().sum();
+    let segment_kind_distribution: serde_json::Map<String, serde_json::Value> = kind_seconds.iter()
+        .map(|(kind, seconds)| (kind.as_str().to_string(), serde_json::json!(seconds / classified_seconds)))
+        .collect();
+    let a_roll_ratio = if classified_seconds > 0.0 {
+        let a_roll: f64 = kind_seconds.iter().filter(|(kind, _)| kind.is_a_roll()).map(|(_, s)| s).sum();
+        Some(a_roll / classified_seconds)
+    } else {
+        None // Not classified yet
+    };
+
+    // Beat templates: per-reference section structure (hook, cut rate, music drops)
+    let mut beat_templates = Vec::new();
+    for asset_id in &req.reference_asset_ids {
+        match extract_reference_template(&db, *asset_id, &all_segments).await {
+            Ok(Some(template)) => beat_templates.push(template),
+            Ok(None) => {}
+            Err(e) => eprintln!("[STYLE] Beat template extraction failed for asset {}: {:?}", asset_id, e),
+        }
+    }
+    // The longest reference is the primary template the planner instantiates
+    let beat_template = beat_templates
+        .iter()
+        .max_by(|a, b| a.duration_sec.partial_cmp(&b.duration_sec).unwrap_or(std::cmp::Ordering::Equal))
+        .cloned();
+
+    // Captions keep clear of platform UI when the references are mostly vertical (shorts,
+    // reels); otherwise they keep to the title-safe area
+    let vertical_references = req.reference_asset_ids.iter()
+        .filter_map(|asset_id| db.get_media_asset(*asset_id).ok().flatten())
+        .filter(|asset| asset.height > asset.width)
+        .count();
+    let caption_safe_area = if vertical_references * 2 > req.reference_asset_ids.len() {
+        serde_json::json!("vertical_social")
+    } else {
+        serde_json::json!(true)
+    };
+
     // Build style profile
     let style_profile = serde_json::json!({
         "pacing_stats": {
@@ -409,6 +623,13 @@ async fn profile_from_references(
         "caption_frequency": caption_frequency,
         "music_presence_ratio": 0.0, // Would need audio track analysis
         "typical_overlay_usage": 0.0, // Would need timeline analysis
+        "shot_type_distribution": shot_type_distribution,
+        "shot_alternation_rate": shot_alternation_rate,
+        "segment_kind_distribution": segment_kind_distribution,
+        "a_roll_ratio": a_roll_ratio,
+        "beat_templates": beat_templates,
+        "beat_template": beat_template,
+        "caption_safe_area": caption_safe_area,
     });
     
     // Store style profile
@@ -430,7 +651,7 @@ async fn profile_from_references(
     Ok(Json(StyleProfileResponse {
         pacing: style_profile["pacing_stats"].clone(),
         caption_templates: vec![serde_json::json!({
-            "placement": {"x": 0.5, "y": 0.9, "safe_area": true},
+            "placement": {"x": 0.5, "y": 0.9, "safe_area": style_profile["caption_safe_area"].clone()},
             "font_family": "Arial",
             "font_weight": "bold",
             "font_size": 48,
@@ -443,9 +664,54 @@ async fn profile_from_references(
             "bpm_tendencies": [],
         }),
         structure: serde_json::json!({
-            "a_roll_b_roll_ratio": 0.6,
+            "a_roll_b_roll_ratio": a_roll_ratio.unwrap_or(0.6),
             "intro_duration_target": 10.0,
             "outro_duration_target": 5.0,
+            "shot_type_distribution": style_profile["shot_type_distribution"].clone(),
+            "shot_alternation_rate": style_profile["shot_alternation_rate"].clone(),
+            "segment_kind_distribution": style_profile["segment_kind_distribution"].clone(),
+            "beat_template": style_profile["beat_template"].clone(),
         }),
     }))
 }
+
+/// Extract the beat template for one reference asset
+/// Music-tagged segments (from audio event detection) limit where drops are looked for
+async fn extract_reference_template(
+    db: &Database,
+    asset_id: i64,
+    segments: &[crate::db::Segment],
+) -> anyhow::Result<Option<BeatTemplate>> {
+    use engine::timeline::TICKS_PER_SECOND;
+
+    let Some(media_path) = db.get_media_asset_path(asset_id)? else {
+        return Ok(None);
+    };
+    let media_info = FFmpegWrapper::probe(std::path::Path::new(&media_path)).await?;
+    let duration_sec = media_info.duration_ticks as f64 / TICKS_PER_SECOND as f64;
+    if duration_sec <= 0.0 {
+        return Ok(None);
+    }
+
+    let music_ranges: Vec<(f64, f64)> = segments
+        .iter()
+        .filter(|s| s.media_asset_id == asset_id)
+        .filter(|s| {
+            s.tags_json
+                .as_deref()
+                .and_then(|json| serde_json::from_str::<Vec<serde_json::Value>>(json).ok())
+                .map(|tags| tags.iter().any(|t| {
+                    t.get("kind").and_then(|k| k.as_str()) == Some(crate::jobs::audio_events::AUDIO_EVENT_KIND)
+                        && t.get("label").and_then(|l| l.as_str()) == Some("music")
+                }))
+                .unwrap_or(false)
+        })
+        .map(|s| (
+            crate::db::Database::get_coalesced_src_in(s) as f64 / TICKS_PER_SECOND as f64,
+            crate::db::Database::get_coalesced_src_out(s) as f64 / TICKS_PER_SECOND as f64,
+        ))
+        .collect();
+
+    let template = extract_beat_template(asset_id, std::path::Path::new(&media_path), duration_sec, &music_ranges).await?;
+    Ok(Some(template))
+}
@@ -0,0 +1,107 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+    routing::{delete, get, post},
+    Router,
+};
+use serde::Serialize;
+use std::sync::Arc;
+
+use crate::api::media::PathError;
+use crate::db::Database;
+use crate::templates::ProjectTemplate;
+
+#[derive(Serialize)]
+pub struct TemplateResponse {
+    id: i64,
+    /// Set for built-in templates (which can't be deleted)
+    builtin_key: Option<String>,
+    created_at: String,
+    template: ProjectTemplate,
+}
+
+pub fn router(db: Arc<Database>) -> Router {
+    Router::new()
+        .route("/", get(list_templates))
+        .route("/", post(create_template))
+        .route("/:id", get(get_template))
+        .route("/:id", delete(delete_template))
+        .with_state(db)
+}
+
+async fn list_templates(
+    State(db): State<Arc<Database>>,
+) -> Result<Json<Vec<TemplateResponse>>, StatusCode> {
+    let templates = db
+        .list_project_templates()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .into_iter()
+        .filter_map(|(id, builtin_key, json, created_at)| match serde_json::from_str(&json) {
+            Ok(template) => Some(TemplateResponse { id, builtin_key, created_at, template }),
+            Err(e) => {
+                eprintln!("[TEMPLATES] Skipping unreadable template {}: {:?}", id, e);
+                None
+            }
+        })
+        .collect();
+
+    Ok(Json(templates))
+}
+
+async fn get_template(
+    State(db): State<Arc<Database>>,
+    Path(id): Path<i64>,
+) -> Result<Json<TemplateResponse>, StatusCode> {
+    let (builtin_key, json, created_at) = db
+        .get_project_template(id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let template = serde_json::from_str(&json).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(TemplateResponse { id, builtin_key, created_at, template }))
+}
+
+/// Add a template to the library (sections with target lengths, caption style, music slot).
+/// The music slot's track must be inside the media roots.
+async fn create_template(
+    State(db): State<Arc<Database>>,
+    Json(mut template): Json<ProjectTemplate>,
+) -> Result<Json<TemplateResponse>, PathError> {
+    if let Err(message) = template.validate() {
+        eprintln!("[TEMPLATES] Rejected template: {}", message);
+        return Err(StatusCode::BAD_REQUEST.into());
+    }
+    template.resolve_music()?;
+
+    let json = serde_json::to_string(&template).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let id = db
+        .create_project_template(&template.name, &json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(TemplateResponse {
+        id,
+        builtin_key: None,
+        created_at: chrono::Utc::now().to_rfc3339(),
+        template,
+    }))
+}
+
+/// Delete a user-created template; projects created from it keep their copy
+async fn delete_template(
+    State(db): State<Arc<Database>>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, StatusCode> {
+    let (builtin_key, _, _) = db
+        .get_project_template(id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    if builtin_key.is_some() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    db.delete_project_template(id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
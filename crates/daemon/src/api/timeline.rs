@@ -1,16 +1,30 @@
 use axum::{
+    body::Body,
     extract::{Path, State},
-    http::StatusCode,
-    response::Json,
+    http::{header, StatusCode},
+    response::{IntoResponse, Json, Response},
     routing::{get, post},
     Router,
 };
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
-use crate::db::Database;
-use engine::timeline::{Timeline, ProjectSettings, Resolution, TICKS_PER_SECOND};
+use crate::autosave;
+use crate::db::{Database, TimelineSnapshot};
+use crate::events::{Event, EventBus};
+use crate::jobs::JobManager;
+use crate::jobs::preview;
+use crate::media::{conform, still};
+use crate::planner::segment_energy;
+use crate::retrieval::feedback::record_user_edit;
+use engine::timeline::{FrameRateConversion, Timeline, ProjectSettings, Resolution, TICKS_PER_SECOND};
+use engine::fit::fit_to_duration;
+use engine::frame_rate::clip_frame_rates;
+use engine::glitches::find_glitches;
+use engine::incremental::ticks_to_seconds;
+use engine::locks::LockWarning;
 use engine::ops::TimelineOperation;
+use engine::otio::{timeline_from_otio, timeline_to_otio};
 use serde_json::{json, Value};
 use rusqlite::params;
 
@@ -19,9 +33,44 @@ pub struct TimelineResponse {
     timeline: Value, // JSON representation of timeline
 }
 
+#[derive(Serialize)]
+pub struct RecoveryResponse {
+    /// The previous daemon run ended without shutting down cleanly
+    unclean_shutdown: bool,
+    /// When that run was last known to be alive
+    interrupted_at: Option<String>,
+    /// Autosaved states of the project's timeline, newest first
+    snapshots: Vec<SnapshotEntry>,
+}
+
+#[derive(Serialize)]
+pub struct SnapshotEntry {
+    #[serde(flatten)]
+    snapshot: TimelineSnapshot,
+    /// Identical to the current timeline, so there's nothing to recover from it
+    matches_current: bool,
+}
+
+#[derive(Serialize)]
+pub struct RestoreSnapshotResponse {
+    /// The new timeline version holding the restored state
+    version_id: Option<String>,
+    timeline: Value,
+}
+
 #[derive(Deserialize)]
 pub struct ApplyOperationsRequest {
-    operations: Vec<Value>, // Simplified - would be TimelineOperation enums
+    pub(crate) operations: Vec<Value>, // Simplified - would be TimelineOperation enums
+}
+
+#[derive(Deserialize)]
+pub struct FitRequest {
+    target_ticks: i64,
+    /// How far from the target counts as a fit (default 0.5s)
+    tolerance_ticks: Option<i64>,
+    /// Apply the fit instead of only previewing it
+    #[serde(default)]
+    apply: bool,
 }
 
 #[derive(Deserialize)]
@@ -30,15 +79,64 @@ pub struct DiffRequest {
     to: Value,
 }
 
-pub fn router(db: Arc<Database>) -> Router {
+/// Apply failures: a plain status, or an operation refused because it touches locked content
+/// (409 with the structured warning so the client can offer alternatives)
+pub enum ApplyError {
+    Status(StatusCode),
+    Locked {
+        operation_index: usize,
+        warning: LockWarning,
+    },
+}
+
+impl From<StatusCode> for ApplyError {
+    fn from(status: StatusCode) -> Self {
+        ApplyError::Status(status)
+    }
+}
+
+impl IntoResponse for ApplyError {
+    fn into_response(self) -> Response {
+        match self {
+            ApplyError::Status(status) => status.into_response(),
+            ApplyError::Locked {
+                operation_index,
+                warning,
+            } => (
+                StatusCode::CONFLICT,
+                Json(json!({
+                    "error": "locked_content",
+                    "operation_index": operation_index,
+                    "warning": warning,
+                })),
+            )
+                .into_response(),
+        }
+    }
+}
+
+pub fn router(db: Arc<Database>, job_manager: Arc<JobManager>) -> Router {
+    // Applying operations publishes TimelineApplied, so it needs the event bus as well
+    let apply = Router::new()
+        .route("/:id/timeline/apply", post(apply_operations))
+        .with_state((db.clone(), job_manager));
     Router::new()
         .route("/:id/timeline", get(get_timeline))
-        .route("/:id/timeline/apply", post(apply_operations))
         .route("/:id/timeline/consolidate", post(consolidate_timeline))
+        .route("/:id/timeline/fit", post(fit_timeline))
+        .route("/:id/timeline/frame_rates", get(get_frame_rates))
+        .route("/:id/timeline/glitches", get(get_glitches))
+        .route("/:id/timeline/previews", get(list_previews))
+        .route("/:id/timeline/previews/:key", get(get_preview_file))
         .route("/timeline/consolidate-all", post(consolidate_all_timelines))
         .route("/:id/timeline/diff", post(log_diff))
+        .route("/:id/timeline/otio", get(export_otio).post(import_otio))
         .route("/:id/timeline/test", post(test_timeline_serialization))
+        .route("/:id/timeline/recovery", get(get_recovery))
+        .route("/:id/timeline/snapshots/:sid", get(get_snapshot))
+        .route("/:id/timeline/snapshots/:sid/restore", post(restore_snapshot))
         .with_state(db)
+        .merge(apply)
 }
 
 async fn get_timeline(
@@ -59,7 +157,8 @@ async fn get_timeline(
                 "fps": 30.0,
                 "resolution": { "width": 1920, "height": 1080 },
                 "sample_rate": 48000,
-                "ticks_per_second": 48000
+                "ticks_per_second": 48000,
+                "frame_rate_conversion": "drop_dup"
             },
             "tracks": [],
             "captions": [],
@@ -71,11 +170,11 @@ async fn get_timeline(
     Ok(Json(TimelineResponse { timeline }))
 }
 
-async fn apply_operations(
-    State(db): State<Arc<Database>>,
+pub(crate) async fn apply_operations(
+    State((db, job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
     Path(project_id): Path<i64>,
     Json(req): Json<ApplyOperationsRequest>,
-) -> Result<Json<TimelineResponse>, StatusCode> {
+) -> Result<Json<TimelineResponse>, ApplyError> {
     // Load timeline from database
     let timeline_json = db
         .get_timeline(project_id)
@@ -111,6 +210,7 @@ async fn apply_operations(
                     },
                     sample_rate: 48000,
                     ticks_per_second: TICKS_PER_SECOND,
+                    frame_rate_conversion: FrameRateConversion::default(),
                 };
                 Timeline::new(settings)
             }
@@ -126,6 +226,7 @@ async fn apply_operations(
             },
             sample_rate: 48000,
             ticks_per_second: TICKS_PER_SECOND,
+            frame_rate_conversion: FrameRateConversion::default(),
         };
         Timeline::new(settings)
     };
@@ -133,6 +234,9 @@ async fn apply_operations(
     eprintln!("Timeline before operations - tracks: {}, settings: {:?}", 
         timeline.tracks.len(), timeline.settings);
 
+    // Snapshot for the edit log (and learning from corrections to agent-placed clips)
+    let timeline_before = timeline.clone();
+
     // Parse and apply each operation
     eprintln!("=== STARTING OPERATION APPLICATION ===");
     eprintln!("Number of operations to apply: {}", req.operations.len());
@@ -150,6 +254,14 @@ async fn apply_operations(
         
         eprintln!("Successfully deserialized operation {}: {:?}", i, op);
         eprintln!("Timeline before applying operation {} - tracks: {}", i, timeline.tracks.len());
+
+        if let Some(warning) = timeline.lock_conflict(&op) {
+            eprintln!("Operation {} refused: {}", i, warning.message);
+            return Err(ApplyError::Locked {
+                operation_index: i,
+                warning,
+            });
+        }
         
         timeline.apply_operation(op)
             .map_err(|e| {
@@ -169,6 +281,15 @@ async fn apply_operations(
     timeline.consolidate_timeline();
     eprintln!("Timeline after consolidation - tracks: {}", timeline.tracks.len());
 
+    // Stills placed by these operations get Ken Burns motion
+    if let Err(e) = still::apply_auto_ken_burns(&db, project_id, &mut timeline) {
+        eprintln!("[STILLS] Failed to add Ken Burns motion: {:?}", e);
+    }
+
+    if let Err(e) = record_user_edit(&db, project_id, &timeline_before, &timeline) {
+        eprintln!("Failed to record edit log: {:?}", e);
+    }
+
     // Serialize and save updated timeline
     eprintln!("Timeline after all operations - tracks: {}, captions: {}, music: {}, markers: {}", 
         timeline.tracks.len(), timeline.captions.len(), timeline.music.len(), timeline.markers.len());
@@ -201,6 +322,10 @@ async fn apply_operations(
             eprintln!("Failed to store timeline in database: {:?}", e);
             StatusCode::INTERNAL_SERVER_ERROR
         })?;
+    job_manager.events().publish(Event::TimelineApplied {
+        project_id,
+        operations: req.operations.len(),
+    });
 
     // Convert timeline back to JSON Value for response
     // Try direct conversion first (more reliable), fallback to string parsing
@@ -280,7 +405,7 @@ async fn apply_operations(
     } else {
         eprintln!("ERROR: Timeline value is not an object!");
         // Return error if timeline value is not properly structured
-        return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        return Err(StatusCode::INTERNAL_SERVER_ERROR.into());
     }
 
     // Final validation: ensure timeline has all required fields before returning
@@ -294,7 +419,7 @@ async fn apply_operations(
         if !has_settings || !has_tracks || !has_captions || !has_music || !has_markers {
             eprintln!("ERROR: Timeline value missing required fields - settings: {}, tracks: {}, captions: {}, music: {}, markers: {}", 
                 has_settings, has_tracks, has_captions, has_music, has_markers);
-            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR.into());
         }
         
         eprintln!("Timeline response validated - all required fields present");
@@ -308,10 +433,12 @@ async fn apply_operations(
 /// Internal helper: apply operations to timeline (used by orchestrator)
 pub fn apply_ops_to_timeline(
     db: &Database,
+    events: &EventBus,
     project_id: i64,
     operations: Vec<TimelineOperation>,
     is_new_version: bool,
 ) -> Result<Timeline, anyhow::Error> {
+    let operation_count = operations.len();
     // Load timeline from database
     let timeline_json = db.get_timeline(project_id)?;
 
@@ -328,6 +455,7 @@ pub fn apply_ops_to_timeline(
                     },
                     sample_rate: 48000,
                     ticks_per_second: TICKS_PER_SECOND,
+                    frame_rate_conversion: FrameRateConversion::default(),
                 };
                 Timeline::new(settings)
             })
@@ -341,18 +469,36 @@ pub fn apply_ops_to_timeline(
             },
             sample_rate: 48000,
             ticks_per_second: TICKS_PER_SECOND,
+            frame_rate_conversion: FrameRateConversion::default(),
         };
         Timeline::new(settings)
     };
 
-    // Apply each operation
+    let existing_clip_ids: std::collections::HashSet<String> = timeline
+        .tracks
+        .iter()
+        .flat_map(|t| t.clips.iter().map(|c| c.id.clone()))
+        .collect();
+
+    // Apply each operation (locked content surfaces as a LockWarning the caller can downcast)
     for op in operations {
+        if let Some(warning) = timeline.lock_conflict(&op) {
+            return Err(warning.into());
+        }
         timeline.apply_operation(op)
             .map_err(|e| anyhow::anyhow!("Failed to apply operation: {}", e))?;
     }
 
+    // Clips added here came from the agent
+    for clip in timeline.tracks.iter_mut().flat_map(|t| t.clips.iter_mut()) {
+        if !existing_clip_ids.contains(&clip.id) {
+            clip.agent_placed = true;
+        }
+    }
+
     // Consolidate timeline to ensure contiguity
     timeline.consolidate_timeline();
+    still::apply_auto_ken_burns(db, project_id, &mut timeline)?;
 
     // Serialize and save updated timeline
     let updated_timeline_json = serde_json::to_string(&timeline)?;
@@ -372,6 +518,10 @@ pub fn apply_ops_to_timeline(
     };
     
     db.store_timeline_version(project_id, &updated_timeline_json, parent_version_id.as_deref(), is_new_version)?;
+    events.publish(Event::TimelineApplied {
+        project_id,
+        operations: operation_count,
+    });
 
     Ok(timeline)
 }
@@ -402,6 +552,7 @@ async fn consolidate_timeline(
                     },
                     sample_rate: 48000,
                     ticks_per_second: TICKS_PER_SECOND,
+                    frame_rate_conversion: FrameRateConversion::default(),
                 };
                 Timeline::new(settings)
             }
@@ -416,6 +567,7 @@ async fn consolidate_timeline(
             },
             sample_rate: 48000,
             ticks_per_second: TICKS_PER_SECOND,
+            frame_rate_conversion: FrameRateConversion::default(),
         };
         Timeline::new(settings)
     };
@@ -436,6 +588,163 @@ async fn consolidate_timeline(
     Ok(Json(TimelineResponse { timeline: timeline_value }))
 }
 
+/// GET /projects/:id/timeline/frame_rates - The project's frame-rate conversion and every clip
+/// that export will convert to the timeline's rate, with warnings where it will look bad
+async fn get_frame_rates(
+    State(db): State<Arc<Database>>,
+    Path(project_id): Path<i64>,
+) -> Result<Json<Value>, StatusCode> {
+    let timeline: Timeline = db
+        .get_timeline(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .and_then(|json_str| serde_json::from_str(&json_str).ok())
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let source_fps = conform::recorded_frame_rates(&db, &timeline).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let clips = clip_frame_rates(&timeline, &source_fps);
+    let warnings = clips.iter().filter(|c| c.warning.is_some()).count();
+
+    Ok(Json(json!({
+        "fps": timeline.settings.fps,
+        "frame_rate_conversion": timeline.settings.frame_rate_conversion,
+        "clips": clips,
+        "warnings": warnings,
+    })))
+}
+
+/// GET /projects/:id/timeline/glitches - Flash frames, repeated frames at cuts and sub-frame
+/// gaps, each with the operations that fix it. Nothing is saved; the operations go to /apply.
+async fn get_glitches(
+    State(db): State<Arc<Database>>,
+    Path(project_id): Path<i64>,
+) -> Result<Json<Value>, StatusCode> {
+    let timeline: Timeline = db
+        .get_timeline(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .and_then(|json_str| serde_json::from_str(&json_str).ok())
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let report = find_glitches(&timeline);
+    if !report.glitches.is_empty() {
+        eprintln!("[TIMELINE] Project {}: {} glitch(es) found", project_id, report.glitches.len());
+    }
+
+    serde_json::to_value(&report)
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// GET /projects/:id/timeline/previews - The timeline's complex regions (overlays, retimed
+/// clips, Ken Burns moves, blended frame-rate conversions), whether each one's pre-rendered
+/// preview is ready, and where the player fetches it from
+async fn list_previews(
+    State(db): State<Arc<Database>>,
+    Path(project_id): Path<i64>,
+) -> Result<Json<Value>, StatusCode> {
+    let plan = preview::plan_previews(&db, project_id)
+        .map_err(|e| {
+            eprintln!("[TIMELINE] Failed to plan previews for project {}: {:?}", project_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let enabled = preview::enabled(&db, project_id).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let regions: Vec<Value> = plan
+        .regions
+        .iter()
+        .map(|region| {
+            let ready = plan.path(region).exists();
+            json!({
+                "key": region.span.key,
+                "clip_ids": region.span.clip_ids,
+                "start_seconds": ticks_to_seconds(region.span.start_ticks),
+                "end_seconds": ticks_to_seconds(region.span.end_ticks),
+                "reasons": region.reasons,
+                "ready": ready,
+                "url": ready.then(|| format!("/api/projects/{}/timeline/previews/{}", project_id, region.span.key)),
+            })
+        })
+        .collect();
+    Ok(Json(json!({
+        "enabled": enabled,
+        "resolution": plan.timeline.settings.resolution,
+        "regions": regions,
+    })))
+}
+
+/// GET /projects/:id/timeline/previews/:key - A pre-rendered preview of a complex region
+async fn get_preview_file(
+    State(db): State<Arc<Database>>,
+    Path((project_id, key)): Path<(i64, String)>,
+) -> Result<Response, StatusCode> {
+    let path = preview::preview_file(&db, project_id, &key)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let file_size = std::fs::metadata(&path).map(|m| m.len()).map_err(|_| StatusCode::NOT_FOUND)?;
+    let file = tokio::fs::File::open(&path)
+        .await
+        .map_err(|_| StatusCode::NOT_FOUND)?;
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "video/mp4")
+        .header(header::CONTENT_LENGTH, file_size.to_string())
+        // Keyed by content, so a preview never changes once rendered
+        .header(header::CACHE_CONTROL, "private, max-age=31536000, immutable")
+        .body(Body::from_stream(tokio_util::io::ReaderStream::new(file)))
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// POST /projects/:id/timeline/fit - Trim/drop clips to hit a target length.
+/// Returns the operations and a preview diff; only saves when `apply` is set.
+async fn fit_timeline(
+    State(db): State<Arc<Database>>,
+    Path(project_id): Path<i64>,
+    Json(req): Json<FitRequest>,
+) -> Result<Json<Value>, StatusCode> {
+    if req.target_ticks <= 0 {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let timeline: Timeline = db
+        .get_timeline(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .and_then(|json_str| serde_json::from_str(&json_str).ok())
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    // Score each clip by the energy of the segment it was cut from (most overlap wins)
+    let segments = db
+        .get_segments_for_project(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let mut clip_scores = std::collections::HashMap::new();
+    for clip in timeline.tracks.iter().flat_map(|t| t.clips.iter()) {
+        let best = segments
+            .iter()
+            .filter(|(segment, _)| segment.media_asset_id == clip.asset_id)
+            .map(|(segment, _)| {
+                let overlap = clip.out_ticks.min(segment.end_ticks) - clip.in_ticks.max(segment.start_ticks);
+                (segment, overlap)
+            })
+            .filter(|(_, overlap)| *overlap > 0)
+            .max_by_key(|(_, overlap)| *overlap);
+        if let Some(energy) = best.and_then(|(segment, _)| segment_energy(segment)) {
+            clip_scores.insert(clip.id.clone(), energy);
+        }
+    }
+
+    let tolerance_ticks = req.tolerance_ticks.unwrap_or(TICKS_PER_SECOND / 2).max(0);
+    let plan = fit_to_duration(&timeline, req.target_ticks, tolerance_ticks, &clip_scores);
+
+    if req.apply && !plan.operations.is_empty() {
+        let updated_timeline_json = serde_json::to_string(&plan.preview)
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        db.store_timeline(project_id, &updated_timeline_json)
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    }
+
+    Ok(Json(json!({
+        "fit": plan,
+        "applied": req.apply && !plan.operations.is_empty(),
+    })))
+}
+
 async fn consolidate_all_timelines(
     State(db): State<Arc<Database>>,
 ) -> Result<Json<Value>, StatusCode> {
@@ -474,14 +783,91 @@ async fn consolidate_all_timelines(
 }
 
 async fn log_diff(
-    State(_db): State<Arc<Database>>,
-    Path(_project_id): Path<i64>,
-    Json(_req): Json<DiffRequest>,
+    State(db): State<Arc<Database>>,
+    Path(project_id): Path<i64>,
+    Json(req): Json<DiffRequest>,
 ) -> Result<Json<()>, StatusCode> {
-    // Placeholder - would generate diff and log to edit_logs table
+    // Log the diff to edit_logs and learn from changes to agent-placed clips
+    let from: Timeline = serde_json::from_value(req.from).map_err(|_| StatusCode::BAD_REQUEST)?;
+    let to: Timeline = serde_json::from_value(req.to).map_err(|_| StatusCode::BAD_REQUEST)?;
+    record_user_edit(&db, project_id, &from, &to).map_err(|e| {
+        eprintln!("Failed to record edit log: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
     Ok(Json(()))
 }
 
+/// GET /projects/:id/timeline/otio - The timeline as OpenTimelineIO JSON, with clips pointing
+/// at the original media files
+async fn export_otio(
+    State(db): State<Arc<Database>>,
+    Path(project_id): Path<i64>,
+) -> Result<Json<Value>, StatusCode> {
+    let project = db
+        .get_project(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let timeline: Timeline = db
+        .get_timeline(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .and_then(|json_str| serde_json::from_str(&json_str).ok())
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let media_paths = db
+        .get_media_assets_for_project(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .into_iter()
+        .chain(db.get_linked_assets_for_project(project_id).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?)
+        .map(|asset| (asset.id, asset.path))
+        .collect();
+
+    Ok(Json(timeline_to_otio(&timeline, &project.name, &media_paths)))
+}
+
+/// POST /projects/:id/timeline/otio - Replace the timeline with an OpenTimelineIO timeline.
+/// Clips are matched to the project's media by path, then by the asset id a VibeCut export
+/// recorded, then by file name.
+async fn import_otio(
+    State(db): State<Arc<Database>>,
+    Path(project_id): Path<i64>,
+    Json(otio): Json<Value>,
+) -> Result<Json<TimelineResponse>, StatusCode> {
+    db.get_project(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let assets = db
+        .get_media_assets_for_project(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let file_name = |path: &str| std::path::Path::new(path).file_name().map(|n| n.to_os_string());
+    let mut timeline = timeline_from_otio(&otio, |path, asset_id_hint| {
+        let by_path = assets.iter().find(|a| !path.is_empty() && a.path == path);
+        let by_hint = || assets.iter().find(|a| Some(a.id) == asset_id_hint);
+        let by_name = || {
+            let name = file_name(path)?;
+            let mut matches = assets.iter().filter(|a| file_name(&a.path).as_ref() == Some(&name));
+            // Only an unambiguous file name counts as a match
+            matches.next().filter(|_| matches.next().is_none())
+        };
+        by_path.or_else(by_hint).or_else(by_name).map(|a| a.id)
+    })
+    .map_err(|e| {
+        eprintln!("[TIMELINE] OTIO import failed: {}", e);
+        StatusCode::BAD_REQUEST
+    })?;
+    if let Err(e) = still::apply_auto_ken_burns(&db, project_id, &mut timeline) {
+        eprintln!("[STILLS] Failed to add Ken Burns motion: {:?}", e);
+    }
+
+    let timeline_json = serde_json::to_string(&timeline).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    db.store_timeline(project_id, &timeline_json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(TimelineResponse {
+        timeline: serde_json::to_value(&timeline).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?,
+    }))
+}
+
 // Test endpoint to verify timeline serialization works
 async fn test_timeline_serialization() -> Result<Json<Value>, StatusCode> {
     eprintln!("=== TEST: Creating test timeline ===");
@@ -493,6 +879,7 @@ async fn test_timeline_serialization() -> Result<Json<Value>, StatusCode> {
         },
         sample_rate: 48000,
         ticks_per_second: TICKS_PER_SECOND,
+        frame_rate_conversion: FrameRateConversion::default(),
     };
     let timeline = Timeline::new(settings);
     
@@ -519,3 +906,71 @@ async fn test_timeline_serialization() -> Result<Json<Value>, StatusCode> {
     
     Ok(Json(value))
 }
+
+/// GET /projects/:id/timeline/recovery - Autosave snapshots to recover from, and whether the
+/// daemon crashed last time
+async fn get_recovery(
+    State(db): State<Arc<Database>>,
+    Path(project_id): Path<i64>,
+) -> Result<Json<RecoveryResponse>, StatusCode> {
+    let previous = db.previous_daemon_session().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let unclean = previous.filter(|session| session.ended_at.is_none());
+    let current_hash = db
+        .get_timeline(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .map(|json| autosave::content_hash(&json));
+    let snapshots = db
+        .list_timeline_snapshots(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .into_iter()
+        .map(|snapshot| SnapshotEntry {
+            matches_current: current_hash.as_deref() == Some(snapshot.content_hash.as_str()),
+            snapshot,
+        })
+        .collect();
+    Ok(Json(RecoveryResponse {
+        unclean_shutdown: unclean.is_some(),
+        interrupted_at: unclean.map(|session| session.last_seen_at),
+        snapshots,
+    }))
+}
+
+/// GET /projects/:id/timeline/snapshots/:sid - A snapshot's timeline, to preview before restoring
+async fn get_snapshot(
+    State(db): State<Arc<Database>>,
+    Path((project_id, snapshot_id)): Path<(i64, i64)>,
+) -> Result<Json<TimelineResponse>, StatusCode> {
+    let (_, timeline_json) = db
+        .get_timeline_snapshot(project_id, snapshot_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let timeline = serde_json::from_str(&timeline_json).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(TimelineResponse { timeline }))
+}
+
+/// POST /projects/:id/timeline/snapshots/:sid/restore - Make a snapshot the current timeline, as
+/// a new version. The timeline it replaces is snapshotted first, so a restore can be undone.
+async fn restore_snapshot(
+    State(db): State<Arc<Database>>,
+    Path((project_id, snapshot_id)): Path<(i64, i64)>,
+) -> Result<Json<RestoreSnapshotResponse>, StatusCode> {
+    let (_, timeline_json) = db
+        .get_timeline_snapshot(project_id, snapshot_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let timeline: Value = serde_json::from_str(&timeline_json).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    autosave::snapshot_project(&db, project_id).map_err(|e| {
+        eprintln!("[AUTOSAVE] Failed to snapshot project {} before restoring: {:?}", project_id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let parent_version_id = db
+        .get_current_timeline_version_id(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    db.store_timeline_version(project_id, &timeline_json, parent_version_id.as_deref(), true)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let version_id = db
+        .get_current_timeline_version_id(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(RestoreSnapshotResponse { version_id, timeline }))
+}
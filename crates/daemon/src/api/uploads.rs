@@ -0,0 +1,190 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+    routing::{get, post},
+    Router,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::db::{Database, Publication};
+use crate::jobs::{JobManager, JobStatus, JobType};
+use crate::uploads::{self, MetadataOverrides, PublishMetadata, PROVIDERS};
+
+#[derive(Deserialize)]
+pub struct CredentialsRequest {
+    client_id: String,
+    client_secret: String,
+    refresh_token: String,
+}
+
+#[derive(Serialize)]
+pub struct CredentialsResponse {
+    provider: String,
+    configured: bool,
+}
+
+#[derive(Deserialize)]
+pub struct PublishRequest {
+    /// Upload provider (default "youtube")
+    provider: Option<String>,
+    #[serde(flatten)]
+    overrides: MetadataOverrides,
+}
+
+#[derive(Serialize)]
+pub struct PublishResponse {
+    publication_id: i64,
+    metadata: PublishMetadata,
+}
+
+#[derive(Serialize)]
+pub struct PublicationResponse {
+    #[serde(flatten)]
+    publication: Publication,
+    /// 0-1
+    progress: f64,
+}
+
+pub fn router(db: Arc<Database>, job_manager: Arc<JobManager>) -> Router {
+    Router::new()
+        .route("/:id/exports/:eid/publish", post(publish_export))
+        .route("/:id/publications/:pid", get(get_publication))
+        .route("/:id/publications/:pid/retry", post(retry_publication))
+        .with_state((db, job_manager))
+}
+
+/// Routes under /api/uploads (provider accounts)
+pub fn credentials_router(db: Arc<Database>) -> Router {
+    Router::new()
+        .route("/credentials/:provider", get(get_credentials).put(set_credentials))
+        .with_state(db)
+}
+
+/// GET /uploads/credentials/:provider - Whether an account is connected (secrets are never returned)
+async fn get_credentials(
+    State(db): State<Arc<Database>>,
+    Path(provider): Path<String>,
+) -> Result<Json<CredentialsResponse>, StatusCode> {
+    if !PROVIDERS.contains(&provider.as_str()) {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    let configured = db
+        .get_oauth_credentials(&provider)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .is_some();
+    Ok(Json(CredentialsResponse { provider, configured }))
+}
+
+/// PUT /uploads/credentials/:provider - Store the OAuth client and refresh token for a provider
+async fn set_credentials(
+    State(db): State<Arc<Database>>,
+    Path(provider): Path<String>,
+    Json(req): Json<CredentialsRequest>,
+) -> Result<Json<CredentialsResponse>, StatusCode> {
+    if !PROVIDERS.contains(&provider.as_str()) {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    if [&req.client_id, &req.client_secret, &req.refresh_token].iter().any(|v| v.trim().is_empty()) {
+        eprintln!("[UPLOADS] Credentials for {} are missing a field", provider);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    db.upsert_oauth_credentials(&provider, &req.client_id, &req.client_secret, &req.refresh_token)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(CredentialsResponse { provider, configured: true }))
+}
+
+/// POST /projects/:id/exports/:eid/publish - Upload an export's file (export job `eid`) with
+/// title, description and chapters generated from the timeline. Runs in the background; poll
+/// GET /projects/:id/publications/:pid for progress.
+async fn publish_export(
+    State((db, job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path((project_id, export_job_id)): Path<(i64, i64)>,
+    Json(req): Json<PublishRequest>,
+) -> Result<Json<PublishResponse>, StatusCode> {
+    let provider = req.provider.unwrap_or_else(|| "youtube".to_string());
+    if !PROVIDERS.contains(&provider.as_str()) {
+        eprintln!("[UPLOADS] Unknown provider: {}", provider);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    if db.get_oauth_credentials(&provider).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?.is_none() {
+        eprintln!("[UPLOADS] No {} credentials configured", provider);
+        return Err(StatusCode::PRECONDITION_FAILED);
+    }
+
+    let job = job_manager
+        .get_job(export_job_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .filter(|job| matches!(job.job_type, JobType::Export))
+        .ok_or(StatusCode::NOT_FOUND)?;
+    if matches!(job.status, JobStatus::Failed | JobStatus::Cancelled) {
+        eprintln!("[UPLOADS] Export {} didn't finish", export_job_id);
+        return Err(StatusCode::CONFLICT);
+    }
+    let file_path = job
+        .payload
+        .as_ref()
+        .and_then(|p| p.get("out_path"))
+        .and_then(|p| p.as_str())
+        .map(String::from)
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let total_bytes = std::fs::metadata(&file_path).map(|m| m.len()).unwrap_or(0);
+    if total_bytes == 0 {
+        eprintln!("[UPLOADS] Export file {} is missing or empty", file_path);
+        return Err(StatusCode::CONFLICT);
+    }
+
+    let metadata = uploads::generate_metadata(&db, project_id, req.overrides).map_err(|e| {
+        eprintln!("[UPLOADS] {:?}", e);
+        StatusCode::BAD_REQUEST
+    })?;
+    let metadata_json = serde_json::to_value(&metadata).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let publication_id = db
+        .create_publication(project_id, export_job_id, &provider, &file_path, &metadata_json, total_bytes as i64)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    tokio::spawn(uploads::run_publication(db.clone(), publication_id));
+
+    Ok(Json(PublishResponse { publication_id, metadata }))
+}
+
+/// GET /projects/:id/publications/:pid - Upload status and progress
+async fn get_publication(
+    State((db, _job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path((project_id, publication_id)): Path<(i64, i64)>,
+) -> Result<Json<PublicationResponse>, StatusCode> {
+    let publication = db
+        .get_publication(publication_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .filter(|p| p.project_id == project_id)
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let progress = if publication.total_bytes > 0 {
+        publication.bytes_sent as f64 / publication.total_bytes as f64
+    } else {
+        0.0
+    };
+    Ok(Json(PublicationResponse { publication, progress }))
+}
+
+/// POST /projects/:id/publications/:pid/retry - Resume a failed upload where it stopped
+async fn retry_publication(
+    State((db, job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path((project_id, publication_id)): Path<(i64, i64)>,
+) -> Result<Json<PublicationResponse>, StatusCode> {
+    let publication = db
+        .get_publication(publication_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .filter(|p| p.project_id == project_id)
+        .ok_or(StatusCode::NOT_FOUND)?;
+    if publication.status != "failed" {
+        eprintln!("[UPLOADS] Publication {} is {}, not failed", publication_id, publication.status);
+        return Err(StatusCode::CONFLICT);
+    }
+    db.update_publication_progress(publication_id, "pending", publication.bytes_sent, None)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    tokio::spawn(uploads::run_publication(db.clone(), publication_id));
+
+    get_publication(State((db, job_manager)), Path((project_id, publication_id))).await
+}
@@ -0,0 +1,158 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+    routing::{get, post},
+    Router,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+use crate::api::media::PathError;
+use crate::db::{Database, Voiceover};
+use crate::jobs::voiceover::{queue_alignment, queue_synthesis, voiceover_beats, NarrationRequest};
+use crate::jobs::JobManager;
+use crate::media::roots;
+
+/// Where a voiceover came from
+const VOICEOVER_SOURCES: &[&str] = &["recording", "tts"];
+
+#[derive(Deserialize)]
+pub struct AttachVoiceoverRequest {
+    /// Audio file of the narration
+    path: String,
+    /// "recording" (the default) or "tts"
+    source: Option<String>,
+    /// Cut the timeline to the narration once it's transcribed (default true)
+    align: Option<bool>,
+}
+
+#[derive(Deserialize)]
+pub struct AlignVoiceoverRequest {
+    align: Option<bool>,
+}
+
+#[derive(Serialize)]
+pub struct SynthesisJobResponse {
+    job_id: i64,
+}
+
+#[derive(Serialize)]
+pub struct VoiceoverJobResponse {
+    voiceover_id: i64,
+    job_id: i64,
+}
+
+pub fn router(db: Arc<Database>, job_manager: Arc<JobManager>) -> Router {
+    Router::new()
+        .route("/:id/voiceovers", get(list_voiceovers).post(attach_voiceover))
+        .route("/:id/voiceovers/tts", post(synthesize_voiceover))
+        .route("/:id/voiceovers/:vid", get(get_voiceover))
+        .route("/:id/voiceovers/:vid/align", post(align_voiceover))
+        .with_state((db, job_manager))
+}
+
+/// POST /projects/:id/voiceovers - Attach a narration file and queue its transcription and
+/// alignment. The job result lists the beats and how the timeline was cut to them.
+async fn attach_voiceover(
+    State((db, job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path(project_id): Path<i64>,
+    Json(req): Json<AttachVoiceoverRequest>,
+) -> Result<Json<VoiceoverJobResponse>, PathError> {
+    db.get_project(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let source = req.source.as_deref().unwrap_or("recording");
+    if !VOICEOVER_SOURCES.contains(&source) {
+        eprintln!("[VOICEOVER] Unknown source '{}'", source);
+        return Err(StatusCode::BAD_REQUEST.into());
+    }
+    // The ML service is handed the path, so it has to be absolute
+    let path = roots::resolve(&req.path)?;
+    if !path.is_file() {
+        eprintln!("[VOICEOVER] Narration file not found: {}", req.path);
+        return Err(StatusCode::BAD_REQUEST.into());
+    }
+
+    let voiceover_id = db
+        .create_voiceover(project_id, &path.to_string_lossy(), source)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let job_id = queue_alignment(&job_manager, project_id, voiceover_id, req.align.unwrap_or(true))
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(VoiceoverJobResponse { voiceover_id, job_id }))
+}
+
+/// POST /projects/:id/voiceovers/tts - Queue narration spoken from a script (or one written from
+/// a brief). The job result names the voiceover and its sentences' timing; alignment is queued
+/// after it like for an attached file.
+async fn synthesize_voiceover(
+    State((db, job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path(project_id): Path<i64>,
+    Json(req): Json<NarrationRequest>,
+) -> Result<Json<SynthesisJobResponse>, StatusCode> {
+    db.get_project(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let has_text = |text: &Option<String>| text.as_deref().is_some_and(|t| !t.trim().is_empty());
+    if !has_text(&req.script) && !has_text(&req.brief) {
+        eprintln!("[VOICEOVER] Narration needs a script or a brief");
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    // Catch an unknown provider (or a missing API key) now rather than in the job
+    if let Err(e) = crate::tts::provider(req.provider.as_deref()) {
+        eprintln!("[VOICEOVER] {}", e);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let job_id = queue_synthesis(&job_manager, project_id, &req).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(SynthesisJobResponse { job_id }))
+}
+
+async fn list_voiceovers(
+    State((db, _job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path(project_id): Path<i64>,
+) -> Result<Json<Vec<Voiceover>>, StatusCode> {
+    db.list_voiceovers(project_id)
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// GET /projects/:id/voiceovers/:vid - The voiceover and its beats (null until it's transcribed)
+async fn get_voiceover(
+    State((db, _job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path((project_id, voiceover_id)): Path<(i64, i64)>,
+) -> Result<Json<Value>, StatusCode> {
+    let voiceover = find_voiceover(&db, project_id, voiceover_id)?;
+    let beats = voiceover_beats(&db, voiceover_id).map_err(|e| {
+        if crate::crypto::is_locked_error(&e) {
+            StatusCode::LOCKED
+        } else {
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    })?;
+    Ok(Json(json!({ "voiceover": voiceover, "beats": beats })))
+}
+
+/// POST /projects/:id/voiceovers/:vid/align - Cut the timeline to the voiceover again (e.g.
+/// after editing it)
+async fn align_voiceover(
+    State((db, job_manager)): State<(Arc<Database>, Arc<JobManager>)>,
+    Path((project_id, voiceover_id)): Path<(i64, i64)>,
+    body: Option<Json<AlignVoiceoverRequest>>,
+) -> Result<Json<VoiceoverJobResponse>, StatusCode> {
+    find_voiceover(&db, project_id, voiceover_id)?;
+    let align = body.and_then(|Json(req)| req.align).unwrap_or(true);
+    let job_id = queue_alignment(&job_manager, project_id, voiceover_id, align)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(VoiceoverJobResponse { voiceover_id, job_id }))
+}
+
+fn find_voiceover(db: &Database, project_id: i64, voiceover_id: i64) -> Result<Voiceover, StatusCode> {
+    db.get_voiceover(voiceover_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .filter(|v| v.project_id == project_id)
+        .ok_or(StatusCode::NOT_FOUND)
+}
@@ -0,0 +1,120 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+    routing::{delete, get, post},
+    Router,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Arc;
+
+use crate::db::{Database, Webhook};
+use crate::webhooks::{self, Delivery, EVENTS};
+
+#[derive(Deserialize)]
+pub struct CreateWebhookRequest {
+    url: String,
+    /// Events to deliver (default: all of them)
+    events: Option<Vec<String>>,
+    /// Signing secret (default: generated)
+    secret: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct CreateWebhookResponse {
+    #[serde(flatten)]
+    webhook: Webhook,
+    /// Only returned here; keep it to verify X-Vibecut-Signature
+    secret: String,
+}
+
+pub fn router(db: Arc<Database>) -> Router {
+    Router::new()
+        .route("/:id/webhooks", get(list_webhooks).post(create_webhook))
+        .route("/:id/webhooks/:wid", delete(delete_webhook))
+        .route("/:id/webhooks/:wid/test", post(test_webhook))
+        .with_state(db)
+}
+
+/// GET /projects/:id/webhooks - Webhooks with their last delivery outcome (secrets omitted)
+async fn list_webhooks(
+    State(db): State<Arc<Database>>,
+    Path(project_id): Path<i64>,
+) -> Result<Json<Vec<Webhook>>, StatusCode> {
+    db.list_webhooks(project_id)
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// POST /projects/:id/webhooks - Register a URL to receive signed event payloads
+async fn create_webhook(
+    State(db): State<Arc<Database>>,
+    Path(project_id): Path<i64>,
+    Json(req): Json<CreateWebhookRequest>,
+) -> Result<Json<CreateWebhookResponse>, StatusCode> {
+    db.get_project(project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let url = req.url.trim();
+    if !(url.starts_with("http://") || url.starts_with("https://")) || reqwest::Url::parse(url).is_err() {
+        eprintln!("[WEBHOOKS] Invalid webhook URL: {}", url);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let events = req
+        .events
+        .unwrap_or_else(|| EVENTS.iter().map(|e| e.to_string()).collect());
+    if events.is_empty() {
+        eprintln!("[WEBHOOKS] Webhook subscribes to no events");
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    if let Some(unknown) = events.iter().find(|e| !EVENTS.contains(&e.as_str())) {
+        eprintln!("[WEBHOOKS] Unknown webhook event: {}", unknown);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let secret = match req.secret.filter(|s| !s.trim().is_empty()) {
+        Some(secret) => secret,
+        None => webhooks::generate_secret(),
+    };
+
+    let webhook_id = db
+        .create_webhook(project_id, url, &secret, &events)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let webhook = db
+        .get_webhook(webhook_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(CreateWebhookResponse { webhook, secret }))
+}
+
+async fn delete_webhook(
+    State(db): State<Arc<Database>>,
+    Path((project_id, webhook_id)): Path<(i64, i64)>,
+) -> Result<StatusCode, StatusCode> {
+    find_webhook(&db, project_id, webhook_id)?;
+    db.delete_webhook(webhook_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /projects/:id/webhooks/:wid/test - Send a "ping" event now and report what the receiver
+/// answered
+async fn test_webhook(
+    State(db): State<Arc<Database>>,
+    Path((project_id, webhook_id)): Path<(i64, i64)>,
+) -> Result<Json<Delivery>, StatusCode> {
+    let webhook = find_webhook(&db, project_id, webhook_id)?;
+    let payload = webhooks::build_payload(project_id, "ping", json!({ "webhook_id": webhook_id }));
+    let delivery = webhooks::deliver(&webhook, &payload).await;
+    db.record_webhook_delivery(webhook_id, delivery.status.map(i64::from), delivery.error.as_deref())
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(delivery))
+}
+
+fn find_webhook(db: &Database, project_id: i64, webhook_id: i64) -> Result<Webhook, StatusCode> {
+    db.get_webhook(webhook_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .filter(|w| w.project_id == project_id)
+        .ok_or(StatusCode::NOT_FOUND)
+}
@@ -0,0 +1,78 @@
+//! Timeline autosave: a snapshot of each timeline that changed, once a minute, kept in a small
+//! ring per project apart from explicit versions. Together with a record of whether the last
+//! daemon run shut down cleanly, it lets the editor offer recovery after a crash.
+
+use anyhow::Result;
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::db::Database;
+use engine::render::output_duration_seconds;
+use engine::timeline::Timeline;
+
+/// How often changed timelines are snapshotted, so at most this much editing is lost
+pub const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(60);
+/// Snapshots kept per project; older ones are dropped as new ones come in
+pub const SNAPSHOTS_KEPT: usize = 30;
+
+/// Snapshot the project's current timeline unless it's unchanged since the last snapshot.
+/// Returns the new snapshot's id.
+pub fn snapshot_project(db: &Database, project_id: i64) -> Result<Option<i64>> {
+    let Some(timeline_json) = db.get_timeline(project_id)? else {
+        return Ok(None);
+    };
+    let content_hash = content_hash(&timeline_json);
+    if db
+        .latest_timeline_snapshot(project_id)?
+        .is_some_and(|latest| latest.content_hash == content_hash)
+    {
+        return Ok(None);
+    }
+
+    // Summary for the recovery list; a timeline that doesn't parse is still worth keeping
+    let (clip_count, duration_seconds) = match serde_json::from_str::<Timeline>(&timeline_json) {
+        Ok(timeline) => (
+            timeline.tracks.iter().map(|t| t.clips.len() as i64).sum(),
+            output_duration_seconds(&timeline),
+        ),
+        Err(_) => (0, 0.0),
+    };
+    let snapshot_id = db.insert_timeline_snapshot(
+        project_id,
+        &content_hash,
+        &timeline_json,
+        clip_count,
+        duration_seconds,
+        SNAPSHOTS_KEPT,
+    )?;
+    Ok(Some(snapshot_id))
+}
+
+/// Identifies a timeline state, to skip snapshots of unchanged timelines
+pub fn content_hash(timeline_json: &str) -> String {
+    hex::encode(Sha256::digest(timeline_json.as_bytes()))
+}
+
+pub fn snapshot_all(db: &Database) -> Result<()> {
+    for project in db.get_all_projects()? {
+        if let Err(e) = snapshot_project(db, project.id) {
+            eprintln!("[AUTOSAVE] Snapshot of project {} failed: {:?}", project.id, e);
+        }
+    }
+    Ok(())
+}
+
+/// Snapshots changed timelines every interval and marks the session alive, for as long as the
+/// daemon runs
+pub async fn autosave_loop(db: Arc<Database>, session_id: i64) {
+    loop {
+        tokio::time::sleep(SNAPSHOT_INTERVAL).await;
+        if let Err(e) = db.touch_daemon_session(session_id) {
+            eprintln!("[AUTOSAVE] Failed to update session {}: {:?}", session_id, e);
+        }
+        if let Err(e) = snapshot_all(&db) {
+            eprintln!("[AUTOSAVE] Failed to list projects: {:?}", e);
+        }
+    }
+}
@@ -0,0 +1,105 @@
+//! Just enough of the OpenType format to check a font file and read its family name and the
+//! embedding rights it declares.
+
+use anyhow::Result;
+
+/// What a font file says about itself
+#[derive(Debug, Clone)]
+pub struct FontInfo {
+    pub family: Option<String>,
+    /// "installable", "editable", "preview_print" or "restricted", from the OS/2 table's fsType
+    pub embedding: Option<String>,
+}
+
+/// Read a TrueType, OpenType or collection font. WOFF files are rejected: drawtext can't load them.
+pub fn inspect_font(bytes: &[u8]) -> Result<FontInfo> {
+    let tag = bytes.get(0..4).ok_or_else(|| anyhow::anyhow!("File is too short to be a font"))?;
+    let font_offset = match tag {
+        [0, 1, 0, 0] | b"OTTO" | b"true" => 0,
+        // A collection: the first font is described
+        b"ttcf" => read_u32(bytes, 12)? as usize,
+        b"wOFF" | b"wOF2" => return Err(anyhow::anyhow!("WOFF fonts aren't supported; use the TTF or OTF file")),
+        _ => return Err(anyhow::anyhow!("Not a TrueType or OpenType font")),
+    };
+
+    let num_tables = read_u16(bytes, font_offset + 4)? as usize;
+    let mut name_table = None;
+    let mut os2_table = None;
+    for i in 0..num_tables {
+        let record = font_offset + 12 + i * 16;
+        let offset = read_u32(bytes, record + 8)? as usize;
+        match bytes.get(record..record + 4) {
+            Some(b"name") => name_table = Some(offset),
+            Some(b"OS/2") => os2_table = Some(offset),
+            _ => {}
+        }
+    }
+    Ok(FontInfo {
+        family: name_table.and_then(|offset| family_name(bytes, offset).ok().flatten()),
+        embedding: os2_table.and_then(|offset| read_u16(bytes, offset + 8).ok()).map(embedding),
+    })
+}
+
+/// Embedding rights from fsType: the most permissive bit set wins, as the spec says
+fn embedding(fs_type: u16) -> String {
+    let rights = if fs_type & 0x000F == 0 {
+        "installable"
+    } else if fs_type & 0x0008 != 0 {
+        "editable"
+    } else if fs_type & 0x0004 != 0 {
+        "preview_print"
+    } else {
+        "restricted"
+    };
+    rights.to_string()
+}
+
+/// The typographic family (name ID 16), or the family (name ID 1), preferring Windows Unicode
+/// names to Mac Roman ones
+fn family_name(bytes: &[u8], table: usize) -> Result<Option<String>> {
+    let count = read_u16(bytes, table + 2)? as usize;
+    let strings = table + read_u16(bytes, table + 4)? as usize;
+    let mut best: Option<(u8, String)> = None;
+    for i in 0..count {
+        let record = table + 6 + i * 12;
+        let platform = read_u16(bytes, record)?;
+        let name_id = read_u16(bytes, record + 6)?;
+        let length = read_u16(bytes, record + 8)? as usize;
+        let offset = strings + read_u16(bytes, record + 10)? as usize;
+        let Some(raw) = bytes.get(offset..offset + length) else {
+            continue;
+        };
+        let name = match platform {
+            0 | 3 => String::from_utf16_lossy(
+                &raw.chunks_exact(2).map(|pair| u16::from_be_bytes([pair[0], pair[1]])).collect::<Vec<_>>(),
+            ),
+            1 => raw.iter().map(|b| *b as char).collect(),
+            _ => continue,
+        };
+        let rank = match (name_id, platform) {
+            (16, 0 | 3) => 0,
+            (16, _) => 1,
+            (1, 0 | 3) => 2,
+            (1, _) => 3,
+            _ => continue,
+        };
+        if !name.trim().is_empty() && best.as_ref().is_none_or(|(r, _)| rank < *r) {
+            best = Some((rank, name.trim().to_string()));
+        }
+    }
+    Ok(best.map(|(_, name)| name))
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> Result<u16> {
+    bytes
+        .get(offset..offset + 2)
+        .map(|b| u16::from_be_bytes([b[0], b[1]]))
+        .ok_or_else(|| anyhow::anyhow!("Font file is truncated"))
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Result<u32> {
+    bytes
+        .get(offset..offset + 4)
+        .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| anyhow::anyhow!("Font file is truncated"))
+}
@@ -0,0 +1,347 @@
+//! A project's brand kit: its palette, logo and fonts, which captions, lower thirds, end cards
+//! and the export watermark are drawn with.
+
+pub mod fonts;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::db::{BrandAsset, Database, NewBrandAsset};
+use crate::media::compute_file_checksum;
+use crate::media::ffmpeg::FFmpegWrapper;
+use engine::compiler::{EditEvent, EditPlan};
+use engine::end_card::{EndCard, EndCardElement};
+use engine::title::TitleStyle;
+use engine::watermark::Watermark;
+
+/// Kinds of files a brand kit holds
+pub const ASSET_KINDS: &[&str] = &["logo", "font"];
+/// Image files a logo can be
+const LOGO_EXTENSIONS: &[&str] = &["png", "webp", "jpg", "jpeg", "tif", "tiff"];
+/// Font files drawtext can load
+const FONT_EXTENSIONS: &[&str] = &["ttf", "otf", "ttc"];
+
+/// Palette and choices of a brand kit; logo and fonts refer to the project's brand assets
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BrandKit {
+    pub colors: BrandColors,
+    pub logo_id: Option<i64>,
+    /// Font for end card text
+    pub heading_font_id: Option<i64>,
+    /// Font for captions
+    pub body_font_id: Option<i64>,
+    /// The logo in a corner of every export (None = no watermark)
+    pub watermark: Option<WatermarkSettings>,
+}
+
+/// Brand colors as #RRGGBB
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BrandColors {
+    pub primary: Option<String>,
+    pub secondary: Option<String>,
+    pub accent: Option<String>,
+    /// Caption and end card text
+    pub text: Option<String>,
+    pub background: Option<String>,
+}
+
+impl BrandColors {
+    fn named(&self) -> [(&'static str, &Option<String>); 5] {
+        [
+            ("primary", &self.primary),
+            ("secondary", &self.secondary),
+            ("accent", &self.accent),
+            ("text", &self.text),
+            ("background", &self.background),
+        ]
+    }
+}
+
+/// Where and how strongly the logo is laid over exports
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WatermarkSettings {
+    pub position: String,
+    /// Width as a share of the frame width
+    pub width: f64,
+    pub opacity: f64,
+    /// Distance from the frame edges as a share of the frame width
+    pub margin: f64,
+}
+
+impl Default for WatermarkSettings {
+    fn default() -> Self {
+        WatermarkSettings {
+            position: "bottom_right".to_string(),
+            width: 0.12,
+            opacity: 0.8,
+            margin: 0.03,
+        }
+    }
+}
+
+impl BrandKit {
+    pub fn for_project(db: &Database, project_id: i64) -> Result<Self> {
+        match db.get_project_brand_kit(project_id)? {
+            Some(json) => Ok(serde_json::from_str(&json)?),
+            None => Ok(BrandKit::default()),
+        }
+    }
+
+    /// Check the palette and the watermark, and that the logo and fonts are the project's
+    /// assets of the right kind
+    pub fn validate(&self, assets: &[BrandAsset]) -> Result<(), String> {
+        for (name, color) in self.colors.named() {
+            if let Some(color) = color {
+                let hex = color.strip_prefix('#').unwrap_or_default();
+                if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                    return Err(format!("{} color must be #RRGGBB, not {:?}", name, color));
+                }
+            }
+        }
+        for (field, id, kind) in [
+            ("logo_id", self.logo_id, "logo"),
+            ("heading_font_id", self.heading_font_id, "font"),
+            ("body_font_id", self.body_font_id, "font"),
+        ] {
+            if let Some(id) = id {
+                if !assets.iter().any(|a| a.id == id && a.kind == kind) {
+                    return Err(format!("{} {} isn't a {} in this project's brand kit", field, id, kind));
+                }
+            }
+        }
+        if let Some(watermark) = &self.watermark {
+            if self.logo_id.is_none() {
+                return Err("A watermark needs a logo".to_string());
+            }
+            self.watermark_with("logo", watermark, None).validate()?;
+        }
+        Ok(())
+    }
+
+    fn watermark_with(&self, path: &str, settings: &WatermarkSettings, key_color: Option<String>) -> Watermark {
+        Watermark {
+            path: path.to_string(),
+            position: settings.position.clone(),
+            width: settings.width,
+            opacity: settings.opacity,
+            margin: settings.margin,
+            key_color,
+        }
+    }
+
+    /// Drop references to an asset that's been removed
+    pub fn forget_asset(&mut self, asset_id: i64) {
+        if self.logo_id == Some(asset_id) {
+            self.logo_id = None;
+            self.watermark = None;
+        }
+        for font in [&mut self.heading_font_id, &mut self.body_font_id] {
+            if *font == Some(asset_id) {
+                *font = None;
+            }
+        }
+    }
+}
+
+/// A project's brand kit with its assets loaded, for drawing with
+pub struct Brand {
+    pub kit: BrandKit,
+    pub logo: Option<BrandAsset>,
+    pub heading_font: Option<BrandAsset>,
+    pub body_font: Option<BrandAsset>,
+}
+
+impl Brand {
+    pub fn for_project(db: &Database, project_id: i64) -> Result<Self> {
+        let kit = BrandKit::for_project(db, project_id)?;
+        let load = |id: Option<i64>| -> Result<Option<BrandAsset>> {
+            Ok(match id {
+                Some(id) => db.get_brand_asset(id)?.filter(|a| a.project_id == project_id),
+                None => None,
+            })
+        };
+        // Fonts nobody has marked licensed aren't drawn with
+        let licensed = |asset: Option<BrandAsset>| {
+            asset.filter(|font| {
+                if !font.licensed {
+                    eprintln!("[BRAND] Project {}: font \"{}\" isn't marked licensed; using the default", project_id, font.name);
+                }
+                font.licensed
+            })
+        };
+        Ok(Brand {
+            logo: load(kit.logo_id)?,
+            heading_font: licensed(load(kit.heading_font_id)?),
+            body_font: licensed(load(kit.body_font_id)?),
+            kit,
+        })
+    }
+
+    /// Color keyed out of the logo: only for logos without transparency
+    fn logo_key_color(&self) -> Option<String> {
+        self.logo.as_ref().filter(|l| !l.has_alpha).and_then(|l| l.key_color.clone())
+    }
+
+    /// Values for end card placeholders: `{logo}` and the palette colors by name
+    pub fn values(&self) -> HashMap<String, String> {
+        let mut values: HashMap<String, String> = self
+            .kit
+            .colors
+            .named()
+            .into_iter()
+            .filter_map(|(name, color)| Some((name.to_string(), color.clone()?)))
+            .collect();
+        if let Some(logo) = &self.logo {
+            values.insert("logo".to_string(), logo.path.clone());
+        }
+        values
+    }
+
+    /// Key the logo's background out where it's drawn, and draw text without a font of its own
+    /// in the heading font
+    pub fn style_end_card(&self, card: &mut EndCard) {
+        for element in &mut card.elements {
+            match element {
+                EndCardElement::Text { font_file, .. } if font_file.is_none() => {
+                    *font_file = self.heading_font.as_ref().map(|f| f.path.clone());
+                }
+                EndCardElement::Image { path, key_color, .. }
+                    if key_color.is_none() && self.logo.as_ref().is_some_and(|l| &l.path == path) =>
+                {
+                    *key_color = self.logo_key_color();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Lower thirds in the heading font, with text in the text color on bars of the primary color
+    pub fn title_style(&self) -> TitleStyle {
+        TitleStyle {
+            font_file: self.heading_font.as_ref().map(|f| f.path.clone()),
+            color: self.kit.colors.text.clone(),
+            background: self.kit.colors.primary.as_ref().map(|c| format!("{}@0.85", c)),
+        }
+    }
+
+    /// The watermark exports get, when the kit has one
+    pub fn watermark(&self) -> Option<Watermark> {
+        let settings = self.kit.watermark.as_ref()?;
+        let logo = self.logo.as_ref()?;
+        Some(self.kit.watermark_with(&logo.path, settings, self.logo_key_color()))
+    }
+}
+
+/// Draw the plan's captions in the brand's body font and text color
+pub fn style_captions(db: &Database, project_id: i64, plan: &mut EditPlan) -> Result<()> {
+    let brand = Brand::for_project(db, project_id)?;
+    let family = brand.body_font.as_ref().and_then(|f| f.font_family.clone());
+    let color = brand.kit.colors.text.clone();
+    if family.is_none() && color.is_none() {
+        return Ok(());
+    }
+    for event in plan.sections.iter_mut().flat_map(|s| s.events.iter_mut()) {
+        if let EditEvent::Caption { style: Some(style), .. } = event {
+            if let Some(family) = &family {
+                style.font_family = family.clone();
+            }
+            if let Some(color) = &color {
+                style.color = color.to_uppercase();
+            }
+        }
+    }
+    Ok(())
+}
+
+/// What checking a new brand asset found
+pub struct ImportedAsset {
+    pub asset: NewBrandAsset,
+    /// Things to fix before the asset looks right in exports
+    pub warnings: Vec<String>,
+}
+
+/// Check a logo or font file and copy it into the project's `brand/` cache directory under its
+/// checksum. Logos are probed for their size and whether they have an alpha channel; fonts are
+/// read for their family name and the embedding rights they declare.
+pub async fn import_asset(
+    project_id: i64,
+    cache_dir: &str,
+    kind: &str,
+    source: &Path,
+    name: Option<String>,
+    licensed: bool,
+    key_color: Option<String>,
+) -> Result<ImportedAsset> {
+    let extension = source
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let mut asset = NewBrandAsset {
+        project_id,
+        kind: kind.to_string(),
+        name: name.unwrap_or_else(|| {
+            source.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default()
+        }),
+        licensed,
+        key_color,
+        ..Default::default()
+    };
+    let mut warnings = Vec::new();
+    match kind {
+        "logo" => {
+            if !LOGO_EXTENSIONS.contains(&extension.as_str()) {
+                return Err(anyhow::anyhow!("Logos must be one of {} (export SVGs to PNG)", LOGO_EXTENSIONS.join(", ")));
+            }
+            let info = FFmpegWrapper::probe(source).await?;
+            if info.width <= 0 || info.height <= 0 {
+                return Err(anyhow::anyhow!("{} isn't a readable image", source.display()));
+            }
+            asset.width = Some(info.width);
+            asset.height = Some(info.height);
+            asset.has_alpha = info.color.pix_fmt.as_deref().is_some_and(has_alpha);
+            if !asset.has_alpha && asset.key_color.is_none() {
+                warnings.push(
+                    "The logo has no transparency, so it's drawn on its own background; set key_color to key that out"
+                        .to_string(),
+                );
+            }
+        }
+        "font" => {
+            if !FONT_EXTENSIONS.contains(&extension.as_str()) {
+                return Err(anyhow::anyhow!("Fonts must be one of {}", FONT_EXTENSIONS.join(", ")));
+            }
+            let info = fonts::inspect_font(&tokio::fs::read(source).await?)?;
+            if info.embedding.as_deref() == Some("restricted") {
+                warnings.push("The font's embedding rights are restricted; check its license allows video use".to_string());
+            }
+            if !licensed {
+                warnings.push("The font isn't marked licensed, so exports use the default font until it is".to_string());
+            }
+            asset.font_family = info.family;
+            asset.embedding = info.embedding;
+        }
+        other => return Err(anyhow::anyhow!("Unknown brand asset kind '{}' (expected one of {})", other, ASSET_KINDS.join(", "))),
+    }
+
+    asset.checksum = compute_file_checksum(source).await?;
+    let brand_dir = PathBuf::from(cache_dir).join("brand");
+    tokio::fs::create_dir_all(&brand_dir).await?;
+    let path = brand_dir.join(format!("{}.{}", &asset.checksum[..16], extension));
+    tokio::fs::copy(source, &path).await?;
+    asset.path = path.to_string_lossy().to_string();
+    Ok(ImportedAsset { asset, warnings })
+}
+
+/// Whether an ffmpeg pixel format carries alpha (palette images may, through their palette)
+fn has_alpha(pix_fmt: &str) -> bool {
+    pix_fmt.starts_with("yuva")
+        || pix_fmt.starts_with("ya")
+        || pix_fmt.starts_with("gbrap")
+        || pix_fmt == "pal8"
+        || ["rgba", "bgra", "argb", "abgr"].iter().any(|f| pix_fmt.starts_with(f))
+}
@@ -0,0 +1,276 @@
+use anyhow::{anyhow, Result};
+use axum::Router;
+use engine::compiler::{compile_edit_plan, EditConstraints, EditPlan};
+use engine::encode::{EncoderSettings, SizeEstimate};
+use engine::timeline::{AudioEnhancement, Timeline};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+use crate::api::export::{EmbedOptions, EncodingOptions};
+use crate::api::orchestrator::{RetrievalFilters, TimelineContext};
+use crate::db::{Database, Project};
+use crate::jobs::processor::JobProcessor;
+use crate::jobs::{Job, JobManager, JobStatus};
+use crate::planner::PlanStrategy;
+use crate::retrieval::RetrievalResult;
+
+/// How often `wait_for_job` checks job status
+const JOB_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// In-process handle to the VibeCut pipeline: the same database, job queue and planner the
+/// daemon serves over HTTP, as typed Rust calls.
+///
+/// Analysis (proxies, transcription, vision, embeddings) runs on the job processor, so call
+/// [`Vibecut::start`] once before importing media.
+#[derive(Clone)]
+pub struct Vibecut {
+    db: Arc<Database>,
+    job_manager: Arc<JobManager>,
+}
+
+impl Vibecut {
+    /// Open (or create) the SQLite database at `db_path`
+    pub fn open(db_path: impl AsRef<Path>) -> Result<Self> {
+        let db_path = db_path.as_ref();
+        if let Some(parent) = db_path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        Ok(Self::from_database(Arc::new(Database::new(db_path)?)))
+    }
+
+    pub fn from_database(db: Arc<Database>) -> Self {
+        let job_manager = Arc::new(JobManager::new(db.clone()));
+        Vibecut { db, job_manager }
+    }
+
+    pub fn db(&self) -> &Arc<Database> {
+        &self.db
+    }
+
+    pub fn job_manager(&self) -> &Arc<JobManager> {
+        &self.job_manager
+    }
+
+    /// Spawn the job processor, the agent event loop, webhook delivery, events outbox pruning and
+    /// timeline autosave on the current Tokio runtime, plus scheduled checksum verification when
+    /// CHECKSUM_VERIFY_INTERVAL_HOURS is set (and first, in simulation mode, the fake ML service
+    /// and TwelveLabs API). Call [`Vibecut::shutdown`] when stopping, or the next start reports
+    /// this run as crashed.
+    pub fn start(&self) -> Vec<JoinHandle<()>> {
+        if crate::simulation::enabled() {
+            if let Err(e) = crate::simulation::serve() {
+                eprintln!("[SIMULATION] Failed to serve the fake services: {:?}", e);
+            }
+        }
+        let job_processor = JobProcessor::new(self.db.clone(), self.job_manager.clone());
+        let agent_db = self.db.clone();
+        let agent_job_manager = self.job_manager.clone();
+        let mut handles = vec![
+            tokio::spawn(async move {
+                job_processor.run().await;
+            }),
+            tokio::spawn(async move {
+                crate::orchestrator::events::agent_event_loop(agent_db, agent_job_manager).await;
+            }),
+            tokio::spawn(crate::webhooks::webhook_event_loop(self.db.clone(), self.job_manager.clone())),
+            tokio::spawn(crate::events::outbox::retention_loop(self.db.clone())),
+        ];
+        match self.db.begin_daemon_session() {
+            Ok(session_id) => handles.push(tokio::spawn(crate::autosave::autosave_loop(self.db.clone(), session_id))),
+            Err(e) => eprintln!("[AUTOSAVE] Failed to start a session, autosave is off: {:?}", e),
+        }
+        handles.push(tokio::spawn(crate::jobs::preview::preview_watch_loop(
+            self.db.clone(),
+            self.job_manager.clone(),
+        )));
+        if let Some(interval) = crate::jobs::integrity::schedule_interval() {
+            handles.push(tokio::spawn(crate::jobs::integrity::verification_schedule_loop(
+                self.db.clone(),
+                self.job_manager.clone(),
+                interval,
+            )));
+        }
+        handles
+    }
+
+    /// Snapshot changed timelines one last time and record a clean shutdown
+    pub fn shutdown(&self) -> Result<()> {
+        crate::autosave::snapshot_all(&self.db)?;
+        self.db.end_daemon_session()
+    }
+
+    /// The daemon's `/api` routes over this pipeline, for serving it over HTTP as well
+    pub fn router(&self) -> Router {
+        crate::api::router(self.db.clone(), self.job_manager.clone())
+    }
+
+    pub fn projects(&self) -> Result<Vec<Project>> {
+        self.db.get_all_projects()
+    }
+
+    pub fn project(&self, project_id: i64) -> Result<Option<Project>> {
+        self.db.get_project(project_id)
+    }
+
+    pub fn create_project(&self, name: &str, cache_dir: &str) -> Result<i64> {
+        self.db.create_project(name, cache_dir)
+    }
+
+    /// Create a project with its own copy of a library template
+    pub fn create_project_from_template(&self, name: &str, cache_dir: &str, template_id: i64) -> Result<i64> {
+        let (_, template_json, _) = self
+            .db
+            .get_project_template(template_id)?
+            .ok_or_else(|| anyhow!("Template {} not found", template_id))?;
+        let project_id = self.db.create_project(name, cache_dir)?;
+        self.db.set_project_template_copy(project_id, Some(template_id), Some(&template_json))?;
+        Ok(project_id)
+    }
+
+    /// Import every video in a folder as one job; returns its id
+    pub fn import_folder(&self, project_id: i64, folder_path: &str) -> Result<i64> {
+        self.require_project(project_id)?;
+        crate::api::media::start_folder_import(self.db.clone(), self.job_manager.clone(), project_id, folder_path)
+    }
+
+    /// Import files, one job per file; returns the job ids in order
+    pub fn import_files(&self, project_id: i64, file_paths: &[String]) -> Result<Vec<i64>> {
+        self.require_project(project_id)?;
+        file_paths
+            .iter()
+            .map(|path| {
+                crate::api::media::start_file_import(self.db.clone(), self.job_manager.clone(), project_id, path)
+            })
+            .collect()
+    }
+
+    pub fn job(&self, job_id: i64) -> Result<Option<Job>> {
+        self.job_manager.get_job(job_id)
+    }
+
+    pub fn cancel_job(&self, job_id: i64) -> Result<()> {
+        self.job_manager.cancel_job(job_id)
+    }
+
+    /// Wait until a job completes; errors if it fails, is cancelled or doesn't exist
+    pub async fn wait_for_job(&self, job_id: i64) -> Result<Job> {
+        loop {
+            let job = self.job(job_id)?.ok_or_else(|| anyhow!("Job {} not found", job_id))?;
+            match job.status {
+                JobStatus::Completed => return Ok(job),
+                JobStatus::Failed | JobStatus::Cancelled => {
+                    return Err(anyhow!("Job {} ended as {}", job_id, job.status.to_string()))
+                }
+                JobStatus::Pending | JobStatus::Running => tokio::time::sleep(JOB_POLL_INTERVAL).await,
+            }
+        }
+    }
+
+    /// Find segments matching a natural-language intent, with the backend chosen by
+    /// RETRIEVAL_BACKEND (as propose does)
+    pub async fn search(
+        &self,
+        project_id: i64,
+        intent: &str,
+        filters: Option<&RetrievalFilters>,
+        context: Option<&TimelineContext>,
+    ) -> Result<RetrievalResult> {
+        crate::retrieval::retrieve_candidates(self.db.clone(), project_id, intent, filters, context).await
+    }
+
+    /// Plan an edit from the project's analyzed segments without touching its timeline; a
+    /// project created from a template is planned section by section from it
+    pub fn plan_edit(
+        &self,
+        project_id: i64,
+        mut constraints: EditConstraints,
+        strategy: PlanStrategy,
+        seed: u64,
+    ) -> Result<EditPlan> {
+        let project = self.require_project(project_id)?;
+        let segments_with_assets = self.db.get_segments_for_project(project_id)?;
+        if segments_with_assets.is_empty() {
+            return Err(anyhow!("Project {} has no segments yet", project_id));
+        }
+        let template = crate::templates::project_template(&self.db, project_id)?;
+        if let Some(template) = &template {
+            template.apply_to_constraints(&mut constraints);
+        }
+        let mut style_profile =
+            crate::api::generate::load_style_profile(&self.db, project.style_profile_id, constraints.vibe.as_deref())
+                .map_err(|status| anyhow!("Couldn't load style profile ({})", status))?;
+        if let Some(template) = &template {
+            style_profile = Some(template.apply_to_style_profile(style_profile));
+        }
+
+        let mut plan = crate::planner::generate_edit_plan_with_strategy(
+            &segments_with_assets,
+            constraints,
+            style_profile.as_ref(),
+            strategy,
+            seed,
+        )
+        .map_err(|conflicts| {
+            let messages: Vec<_> = conflicts.into_iter().map(|c| c.message).collect();
+            anyhow!("Constraint conflicts: {}", messages.join("; "))
+        })?;
+        if let Some(template) = &template {
+            template.decorate_plan(&mut plan, &segments_with_assets, style_profile.as_ref());
+        }
+        Ok(plan)
+    }
+
+    /// Plan an edit, compile it and store it as the project's timeline (what
+    /// `POST /projects/:id/generate` does)
+    pub fn generate_timeline(&self, project_id: i64, constraints: EditConstraints) -> Result<Timeline> {
+        let plan = self.plan_edit(project_id, constraints, PlanStrategy::default(), 0)?;
+        let segments_with_assets = self.db.get_segments_for_project(project_id)?;
+        let settings = crate::api::generate::project_settings(&segments_with_assets[0].1);
+        let mut timeline = compile_edit_plan(plan, settings);
+        crate::media::still::apply_auto_ken_burns(&self.db, project_id, &mut timeline)?;
+        self.db.store_timeline(project_id, &serde_json::to_string(&timeline)?)?;
+        Ok(timeline)
+    }
+
+    /// Queue an export of the project's timeline with the preset and encoder overrides in
+    /// `encoding`, optionally enhancing dialog on every clip that doesn't set its own
+    /// enhancement, with the chapters, captions and tags in `embed`; returns the job id. Fails
+    /// with `media::availability::OriginalsOffline` while originals the timeline uses are offline,
+    /// and with `media::conform::ConformFailed` when they don't match what was edited.
+    pub async fn export(
+        &self,
+        project_id: i64,
+        out_path: &str,
+        encoding: &EncodingOptions,
+        enhance_audio: Option<AudioEnhancement>,
+        embed: &EmbedOptions,
+    ) -> Result<i64> {
+        embed.validate().map_err(|e| anyhow!(e))?;
+        crate::api::export::create_export_job(&self.db, &self.job_manager, project_id, out_path, encoding, enhance_audio, embed)
+            .await?
+            .ok_or_else(|| anyhow!("Project {} has no timeline to export", project_id))
+    }
+
+    /// Resolve `encoding` into encoder settings and estimate how big the export would be
+    pub fn estimate_export(
+        &self,
+        project_id: i64,
+        out_path: Option<&str>,
+        encoding: &EncodingOptions,
+    ) -> Result<(EncoderSettings, SizeEstimate)> {
+        let encoder = encoding.settings(out_path).map_err(|e| anyhow!(e))?;
+        let timeline_json = self
+            .db
+            .get_timeline(project_id)?
+            .ok_or_else(|| anyhow!("Project {} has no timeline to export", project_id))?;
+        let timeline: Timeline = serde_json::from_str(&timeline_json)?;
+        let estimate = encoder.estimate(&timeline);
+        Ok((encoder, estimate))
+    }
+
+    fn require_project(&self, project_id: i64) -> Result<Project> {
+        self.project(project_id)?.ok_or_else(|| anyhow!("Project {} not found", project_id))
+    }
+}
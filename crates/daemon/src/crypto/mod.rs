@@ -0,0 +1,249 @@
+use anyhow::{anyhow, Result};
+use openssl::hash::MessageDigest;
+use openssl::symm::{decrypt_aead, encrypt_aead, Cipher};
+use std::fmt;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use crate::db::Database;
+
+/// AES-256 key derived from a project passphrase
+pub type Key = [u8; 32];
+
+const PBKDF2_ITERATIONS: usize = 310_000;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const TAG_LEN: usize = 16;
+
+/// Prefix of encrypted DB fields: `enc:v1:` + hex(nonce | ciphertext | tag)
+const FIELD_PREFIX: &str = "enc:v1:";
+/// Known plaintext stored encrypted with the project key, to check a passphrase on unlock
+const CHECK_PLAINTEXT: &str = "vibecut-project-key";
+
+/// Encrypted files start with the magic, the plaintext length (u64 LE) and an 8-byte nonce prefix,
+/// followed by CHUNK_SIZE plaintext chunks sealed one by one so a byte range can be decrypted
+/// without reading the whole file
+const FILE_MAGIC: &[u8; 8] = b"VCENC1\0\0";
+const HEADER_LEN: usize = 24;
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Raised when a project's data is encrypted and its passphrase hasn't been entered since the
+/// daemon started. The API answers 423 Locked.
+#[derive(Debug)]
+pub struct ProjectLocked(pub i64);
+
+impl fmt::Display for ProjectLocked {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Project {} is encrypted and locked; unlock it with its passphrase", self.0)
+    }
+}
+
+impl std::error::Error for ProjectLocked {}
+
+pub fn is_locked_error(e: &anyhow::Error) -> bool {
+    e.downcast_ref::<ProjectLocked>().is_some()
+}
+
+pub fn generate_salt() -> Result<Vec<u8>> {
+    random_bytes(SALT_LEN)
+}
+
+/// PBKDF2-HMAC-SHA256 of the passphrase
+pub fn derive_key(passphrase: &str, salt: &[u8]) -> Result<Key> {
+    let mut key = [0u8; 32];
+    openssl::pkcs5::pbkdf2_hmac(passphrase.as_bytes(), salt, PBKDF2_ITERATIONS, MessageDigest::sha256(), &mut key)?;
+    Ok(key)
+}
+
+/// Value stored next to the salt so a wrong passphrase is rejected instead of producing a key
+/// that can't decrypt anything
+pub fn key_check(key: &Key) -> Result<String> {
+    encrypt_field(key, CHECK_PLAINTEXT)
+}
+
+pub fn verify_key(key: &Key, check: &str) -> bool {
+    matches!(decrypt_field(key, check), Ok(plaintext) if plaintext == CHECK_PLAINTEXT)
+}
+
+pub fn is_encrypted_field(value: &str) -> bool {
+    value.starts_with(FIELD_PREFIX)
+}
+
+pub fn encrypt_field(key: &Key, plaintext: &str) -> Result<String> {
+    let nonce = random_bytes(NONCE_LEN)?;
+    let sealed = seal(key, &nonce, &[], plaintext.as_bytes())?;
+    Ok(format!("{}{}{}", FIELD_PREFIX, hex::encode(&nonce), hex::encode(sealed)))
+}
+
+pub fn decrypt_field(key: &Key, value: &str) -> Result<String> {
+    let encoded = value
+        .strip_prefix(FIELD_PREFIX)
+        .ok_or_else(|| anyhow!("Field isn't encrypted"))?;
+    let bytes = hex::decode(encoded)?;
+    if bytes.len() < NONCE_LEN + TAG_LEN {
+        return Err(anyhow!("Encrypted field is truncated"));
+    }
+    let (nonce, sealed) = bytes.split_at(NONCE_LEN);
+    Ok(String::from_utf8(open(key, nonce, &[], sealed)?)?)
+}
+
+/// Plaintext length of an encrypted file, or None when the file isn't encrypted
+pub fn encrypted_file_len(path: &Path) -> Result<Option<u64>> {
+    let mut file = std::fs::File::open(path)?;
+    let mut header = [0u8; HEADER_LEN];
+    if file.read_exact(&mut header).is_err() || &header[..8] != FILE_MAGIC {
+        return Ok(None);
+    }
+    Ok(Some(u64::from_le_bytes(header[8..16].try_into()?)))
+}
+
+/// Whether a file on disk is in the encrypted format (unreadable files count as plain)
+pub fn is_encrypted_file(path: &Path) -> bool {
+    matches!(encrypted_file_len(path), Ok(Some(_)))
+}
+
+/// Whether renders of the project's footage may be cached (intermediates for exports,
+/// pre-rendered previews). Not for encrypted projects: the cache isn't encrypted.
+pub fn caching_allowed(db: &Database, project_id: i64) -> Result<bool> {
+    Ok(db.get_project_encryption(project_id)?.is_none())
+}
+
+/// Encrypt an asset's proxies and thumbnails in place. Copies mirrored to the storage backend
+/// are forgotten so clients are never redirected to plaintext, and the cached transcription
+/// audio is deleted (it's extracted again when needed).
+pub fn encrypt_asset_files(db: &Database, asset_id: i64, key: &Key) -> Result<usize> {
+    let _ = std::fs::remove_file(crate::jobs::transcribe::transcription_audio_path(asset_id));
+    let mut paths: Vec<std::path::PathBuf> = db
+        .list_proxies(asset_id)?
+        .into_iter()
+        .map(|(_, path, _, _)| path.into())
+        .collect();
+    if let Some(dir) = db.get_thumbnail_dir(asset_id)? {
+        if let Ok(entries) = std::fs::read_dir(&dir) {
+            paths.extend(
+                entries
+                    .filter_map(|entry| entry.ok().map(|e| e.path()))
+                    .filter(|path| path.extension().and_then(|e| e.to_str()) == Some("jpg")),
+            );
+        }
+    }
+
+    let mut encrypted = 0;
+    for path in paths.iter().filter(|p| p.exists() && !is_encrypted_file(p)) {
+        encrypt_file(key, path)?;
+        db.delete_stored_object(&path.to_string_lossy())?;
+        encrypted += 1;
+    }
+    Ok(encrypted)
+}
+
+/// Encrypt a file in place (through a temporary file, so a crash never leaves it half written).
+/// Already encrypted files are left alone.
+pub fn encrypt_file(key: &Key, path: &Path) -> Result<()> {
+    if encrypted_file_len(path)?.is_some() {
+        return Ok(());
+    }
+    let mut input = std::fs::File::open(path)?;
+    let len = input.metadata()?.len();
+    let mut header = Vec::with_capacity(HEADER_LEN);
+    header.extend_from_slice(FILE_MAGIC);
+    header.extend_from_slice(&len.to_le_bytes());
+    header.extend_from_slice(&random_bytes(8)?);
+
+    let tmp_path = path.with_extension("enc.tmp");
+    let mut output = std::io::BufWriter::new(std::fs::File::create(&tmp_path)?);
+    output.write_all(&header)?;
+    let mut chunk = vec![0u8; CHUNK_SIZE];
+    let mut index = 0u32;
+    loop {
+        let read = read_full(&mut input, &mut chunk)?;
+        if read == 0 {
+            break;
+        }
+        output.write_all(&seal(key, &chunk_nonce(&header, index), &header, &chunk[..read])?)?;
+        index += 1;
+    }
+    output.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+    std::fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+/// Decrypt a whole encrypted file into memory (thumbnails and other small files)
+pub fn decrypt_file(key: &Key, path: &Path) -> Result<Vec<u8>> {
+    let len = encrypted_file_len(path)?.ok_or_else(|| anyhow!("{} isn't encrypted", path.display()))?;
+    decrypt_file_range(key, path, 0, len)
+}
+
+/// Decrypt `len` plaintext bytes starting at `start`, reading only the chunks that cover them
+pub fn decrypt_file_range(key: &Key, path: &Path, start: u64, len: u64) -> Result<Vec<u8>> {
+    let mut file = std::fs::File::open(path)?;
+    let mut header = [0u8; HEADER_LEN];
+    file.read_exact(&mut header)?;
+    if &header[..8] != FILE_MAGIC {
+        return Err(anyhow!("{} isn't encrypted", path.display()));
+    }
+    let total = u64::from_le_bytes(header[8..16].try_into()?);
+    let end = start.saturating_add(len).min(total);
+    if start >= end {
+        return Ok(Vec::new());
+    }
+
+    let chunk_size = CHUNK_SIZE as u64;
+    let sealed_chunk_size = chunk_size + TAG_LEN as u64;
+    let first = start / chunk_size;
+    let last = (end - 1) / chunk_size;
+    let mut plaintext = Vec::with_capacity((end - start) as usize);
+    let mut sealed = vec![0u8; sealed_chunk_size as usize];
+    for index in first..=last {
+        let chunk_start = index * chunk_size;
+        let chunk_len = (total - chunk_start).min(chunk_size) as usize;
+        file.seek(SeekFrom::Start(HEADER_LEN as u64 + index * sealed_chunk_size))?;
+        let sealed = &mut sealed[..chunk_len + TAG_LEN];
+        file.read_exact(sealed)?;
+        let chunk = open(key, &chunk_nonce(&header, index as u32), &header, sealed)?;
+        let from = start.saturating_sub(chunk_start) as usize;
+        let to = ((end - chunk_start) as usize).min(chunk.len());
+        plaintext.extend_from_slice(&chunk[from..to]);
+    }
+    Ok(plaintext)
+}
+
+/// Nonce prefix from the file header followed by the chunk index
+fn chunk_nonce(header: &[u8], index: u32) -> [u8; NONCE_LEN] {
+    let mut nonce = [0u8; NONCE_LEN];
+    nonce[..8].copy_from_slice(&header[16..24]);
+    nonce[8..].copy_from_slice(&index.to_be_bytes());
+    nonce
+}
+
+/// AES-256-GCM; the tag is appended to the ciphertext
+fn seal(key: &Key, nonce: &[u8], aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>> {
+    let mut tag = [0u8; TAG_LEN];
+    let mut sealed = encrypt_aead(Cipher::aes_256_gcm(), key, Some(nonce), aad, plaintext, &mut tag)?;
+    sealed.extend_from_slice(&tag);
+    Ok(sealed)
+}
+
+fn open(key: &Key, nonce: &[u8], aad: &[u8], sealed: &[u8]) -> Result<Vec<u8>> {
+    let (ciphertext, tag) = sealed.split_at(sealed.len() - TAG_LEN);
+    decrypt_aead(Cipher::aes_256_gcm(), key, Some(nonce), aad, ciphertext, tag)
+        .map_err(|_| anyhow!("Decryption failed (wrong key or corrupted data)"))
+}
+
+fn random_bytes(len: usize) -> Result<Vec<u8>> {
+    let mut bytes = vec![0u8; len];
+    openssl::rand::rand_bytes(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Fill `buf` as far as the reader allows; returns how much was read
+fn read_full(reader: &mut impl Read, buf: &mut [u8]) -> Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    Ok(filled)
+}
@@ -1,12 +1,21 @@
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use rusqlite::{params, Connection, Row};
+use std::collections::HashMap;
 use std::path::Path;
 use std::sync::Mutex;
 use uuid::Uuid;
 
+use crate::crypto::{self, Key, ProjectLocked};
+use crate::embeddings::registry::EmbeddingError;
+use crate::jobs::resources::ResourceLimits;
+use crate::quotas::QuotaLimits;
+use engine::ken_burns::StillSubject;
+
 pub struct Database {
     pub(crate) conn: Mutex<Connection>,
+    /// Keys of encrypted projects unlocked since startup; never written to disk
+    project_keys: Mutex<HashMap<i64, Key>>,
 }
 
 impl Database {
@@ -14,8 +23,13 @@ impl Database {
         let conn = Connection::open(db_path)?;
         let db = Database {
             conn: Mutex::new(conn),
+            project_keys: Mutex::new(HashMap::new()),
         };
         db.init_schema()?;
+        db.seed_builtin_style_presets()?;
+        db.seed_builtin_project_templates()?;
+        db.seed_builtin_end_cards()?;
+        db.seed_embedding_models()?;
         Ok(db)
     }
 
@@ -125,6 +139,18 @@ impl Database {
             );
         }
 
+        // Migration: Add remote_url column (s3:// or gs:// origin of a downloaded asset)
+        let has_remote_url = conn
+            .prepare("SELECT remote_url FROM media_assets LIMIT 1")
+            .is_ok();
+
+        if !has_remote_url {
+            let _ = conn.execute(
+                "ALTER TABLE media_assets ADD COLUMN remote_url TEXT",
+                [],
+            );
+        }
+
         // Migration: Add analysis state tracking columns to media_assets
         let has_segments_built_at = conn
             .prepare("SELECT segments_built_at FROM media_assets LIMIT 1")
@@ -169,6 +195,82 @@ impl Database {
             );
         }
 
+        // Migration: Add per-project vision sampling config
+        let has_vision_config = conn
+            .prepare("SELECT vision_config_json FROM projects LIMIT 1")
+            .is_ok();
+
+        if !has_vision_config {
+            let _ = conn.execute(
+                "ALTER TABLE projects ADD COLUMN vision_config_json TEXT NULL",
+                [],
+            );
+        }
+
+        // Migration: Add per-project agent persona (tone, locale, message overrides)
+        let has_agent_persona = conn
+            .prepare("SELECT agent_persona_json FROM projects LIMIT 1")
+            .is_ok();
+
+        if !has_agent_persona {
+            let _ = conn.execute(
+                "ALTER TABLE projects ADD COLUMN agent_persona_json TEXT NULL",
+                [],
+            );
+        }
+
+        // Migration: Add the template a project was created from, and its copy of that template
+        let has_project_template = conn
+            .prepare("SELECT template_json FROM projects LIMIT 1")
+            .is_ok();
+
+        if !has_project_template {
+            let _ = conn.execute(
+                "ALTER TABLE projects ADD COLUMN template_id INTEGER NULL",
+                [],
+            );
+            let _ = conn.execute(
+                "ALTER TABLE projects ADD COLUMN template_json TEXT NULL",
+                [],
+            );
+        }
+
+        // Migration: Let projects opt out of search across all projects
+        let has_global_search = conn
+            .prepare("SELECT exclude_from_global_search FROM projects LIMIT 1")
+            .is_ok();
+
+        if !has_global_search {
+            let _ = conn.execute(
+                "ALTER TABLE projects ADD COLUMN exclude_from_global_search INTEGER NOT NULL DEFAULT 0",
+                [],
+            );
+        }
+
+        // Migration: Add the end card a project's exports finish with
+        let has_end_card = conn
+            .prepare("SELECT end_card_json FROM projects LIMIT 1")
+            .is_ok();
+
+        if !has_end_card {
+            let _ = conn.execute(
+                "ALTER TABLE projects ADD COLUMN end_card_json TEXT NULL",
+                [],
+            );
+        }
+
+        // Migration: Add the project's brand kit (palette, logo, fonts, watermark)
+        let has_brand_kit = conn
+            .prepare("SELECT brand_kit_json FROM projects LIMIT 1")
+            .is_ok();
+
+        if !has_brand_kit {
+            let _ = conn.execute(
+                "ALTER TABLE projects ADD COLUMN brand_kit_json TEXT NULL",
+                [],
+            );
+        }
+
         // Migration: Add TwelveLabs columns to media_assets table
         let has_twelvelabs_video_id = conn
             .prepare("SELECT twelvelabs_video_id FROM media_assets LIMIT 1")
@@ -193,32 +295,109 @@ impl Database {
             );
         }
 
-        // Migration: Add dedupe_key and external columns to segments table
-        let has_dedupe_key = conn
-            .prepare("SELECT dedupe_key FROM segments LIMIT 1")
+        // Migration: Add still image columns to media_assets (photos are imported as timed stills)
+        let has_is_still = conn
+            .prepare("SELECT is_still FROM media_assets LIMIT 1")
             .is_ok();
-        
-        if !has_dedupe_key {
+
+        if !has_is_still {
             let _ = conn.execute(
-                "ALTER TABLE segments ADD COLUMN dedupe_key TEXT NULL",
+                "ALTER TABLE media_assets ADD COLUMN is_still INTEGER NOT NULL DEFAULT 0",
                 [],
             );
             let _ = conn.execute(
-                "ALTER TABLE segments ADD COLUMN external_source TEXT NULL",
+                "ALTER TABLE media_assets ADD COLUMN still_subjects_json TEXT NULL",
                 [],
             );
+        }
+
+        // Migration: Track whether an asset's original is reachable ('online', 'offline' or
+        // 'archived'); offline and archived assets are edited against their proxies only
+        let has_availability = conn
+            .prepare("SELECT availability FROM media_assets LIMIT 1")
+            .is_ok();
+
+        if !has_availability {
             let _ = conn.execute(
-                "ALTER TABLE segments ADD COLUMN external_ref TEXT NULL",
+                "ALTER TABLE media_assets ADD COLUMN availability TEXT NOT NULL DEFAULT 'online'",
                 [],
             );
-            
-            // Create unique index on dedupe_key (only for non-null values)
             let _ = conn.execute(
-                "CREATE UNIQUE INDEX IF NOT EXISTS segments_dedupe_key_unique ON segments(dedupe_key) WHERE dedupe_key IS NOT NULL",
+                "ALTER TABLE media_assets ADD COLUMN availability_changed_at TEXT NULL",
+                [],
+            );
+        }
+
+        // Migration: Result of the last checksum verification ('ok', 'mismatch' or 'unreadable')
+        let has_integrity = conn
+            .prepare("SELECT integrity FROM media_assets LIMIT 1")
+            .is_ok();
+
+        if !has_integrity {
+            let _ = conn.execute("ALTER TABLE media_assets ADD COLUMN integrity TEXT NULL", []);
+            let _ = conn.execute("ALTER TABLE media_assets ADD COLUMN integrity_checked_at TEXT NULL", []);
+            let _ = conn.execute("ALTER TABLE media_assets ADD COLUMN integrity_error TEXT NULL", []);
+        }
+
+        // Migration: Display rotation of phone video (width/height are stored as displayed)
+        let has_rotation = conn
+            .prepare("SELECT rotation FROM media_assets LIMIT 1")
+            .is_ok();
+
+        if !has_rotation {
+            let _ = conn.execute(
+                "ALTER TABLE media_assets ADD COLUMN rotation INTEGER NOT NULL DEFAULT 0",
+                [],
+            );
+        }
+
+        // Migration: Position of a bulk import's deferred assets in the analysis order
+        let has_import_rank = conn
+            .prepare("SELECT import_rank FROM media_assets LIMIT 1")
+            .is_ok();
+
+        if !has_import_rank {
+            let _ = conn.execute(
+                "ALTER TABLE media_assets ADD COLUMN import_rank INTEGER NULL",
                 [],
             );
         }
 
+        // Migration: Flag screen recordings (segmented at slide changes and cursor activity)
+        let has_screen_recording = conn
+            .prepare("SELECT is_screen_recording FROM media_assets LIMIT 1")
+            .is_ok();
+
+        if !has_screen_recording {
+            let _ = conn.execute(
+                "ALTER TABLE media_assets ADD COLUMN is_screen_recording INTEGER NOT NULL DEFAULT 0",
+                [],
+            );
+        }
+
+        // Migration: Measured A/V sync drift, and whether proxies resample the audio to correct it
+        let has_sync_drift = conn
+            .prepare("SELECT sync_drift_json FROM media_assets LIMIT 1")
+            .is_ok();
+
+        if !has_sync_drift {
+            let _ = conn.execute("ALTER TABLE media_assets ADD COLUMN sync_drift_json TEXT NULL", []);
+            let _ = conn.execute("ALTER TABLE media_assets ADD COLUMN sync_checked_at TEXT NULL", []);
+            let _ = conn.execute(
+                "ALTER TABLE media_assets ADD COLUMN sync_correction INTEGER NOT NULL DEFAULT 0",
+                [],
+            );
+        }
+
+        // Migration: Thumbnail pyramid manifest (thumbnail count of each generated density)
+        let has_thumbnail_levels = conn
+            .prepare("SELECT thumbnail_levels_json FROM media_assets LIMIT 1")
+            .is_ok();
+
+        if !has_thumbnail_levels {
+            let _ = conn.execute("ALTER TABLE media_assets ADD COLUMN thumbnail_levels_json TEXT NULL", []);
+        }
+
         conn.execute(
             "CREATE TABLE IF NOT EXISTS proxies (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -232,6 +411,18 @@ impl Database {
             [],
         )?;
 
+        // Migration: Add tier column to proxies (existing proxies were the single 1080p preview)
+        let has_proxy_tier = conn
+            .prepare("SELECT tier FROM proxies LIMIT 1")
+            .is_ok();
+
+        if !has_proxy_tier {
+            let _ = conn.execute(
+                "ALTER TABLE proxies ADD COLUMN tier TEXT NOT NULL DEFAULT 'preview'",
+                [],
+            );
+        }
+
         conn.execute(
             "CREATE TABLE IF NOT EXISTS segments (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -258,6 +449,27 @@ impl Database {
             [],
         )?;
 
+        // Migration: Dynamic segment columns (segments created from external search hits, e.g.
+        // TwelveLabs). Each column is probed on its own so a partially applied migration, or a
+        // database created before the segments table existed, still ends up complete.
+        for (column, ddl) in [
+            ("dedupe_key", "ALTER TABLE segments ADD COLUMN dedupe_key TEXT NULL"),
+            ("external_source", "ALTER TABLE segments ADD COLUMN external_source TEXT NULL"),
+            ("external_ref", "ALTER TABLE segments ADD COLUMN external_ref TEXT NULL"),
+            ("created_at", "ALTER TABLE segments ADD COLUMN created_at TEXT NULL"),
+        ] {
+            let has_column = conn
+                .prepare(&format!("SELECT {} FROM segments LIMIT 1", column))
+                .is_ok();
+            if !has_column {
+                conn.execute(ddl, [])?;
+            }
+        }
+        conn.execute(
+            "CREATE UNIQUE INDEX IF NOT EXISTS segments_dedupe_key_unique ON segments(dedupe_key) WHERE dedupe_key IS NOT NULL",
+            [],
+        )?;
+
         // Migration: Add new segment columns if they don't exist
         let has_project_id = conn
             .prepare("SELECT project_id FROM segments LIMIT 1")
@@ -305,6 +517,16 @@ impl Database {
                 "ALTER TABLE segments ADD COLUMN capture_time TEXT",
                 [],
             );
+            // Segment kinds stored before the taxonomy; dynamic search segments are told apart
+            // by their dedupe key, not their kind
+            let _ = conn.execute(
+                "UPDATE segments SET segment_kind = CASE segment_kind
+                    WHEN 'broll' THEN 'b_roll'
+                    WHEN 'screen_recording' THEN 'screen'
+                    ELSE NULL END
+                 WHERE segment_kind IN ('broll', 'screen_recording', 'twelvelabs_dynamic')",
+                [],
+            );
             
             // Backfill src_in_ticks and src_out_ticks from start_ticks and end_ticks
             let _ = conn.execute(
@@ -317,6 +539,53 @@ impl Database {
             );
         }
 
+        // Migration: Add shot_type column to segments (wide / medium / close_up)
+        let has_shot_type = conn
+            .prepare("SELECT shot_type FROM segments LIMIT 1")
+            .is_ok();
+
+        if !has_shot_type {
+            let _ = conn.execute(
+                "ALTER TABLE segments ADD COLUMN shot_type TEXT NULL",
+                [],
+            );
+        }
+
+        // Migration: Add language column to segments (spoken language detected during transcription)
+        let has_segment_language = conn
+            .prepare("SELECT language FROM segments LIMIT 1")
+            .is_ok();
+
+        if !has_segment_language {
+            let _ = conn.execute(
+                "ALTER TABLE segments ADD COLUMN language TEXT NULL",
+                [],
+            );
+        }
+
+        // Migration: Add text_in_video column to segments (OCR of on-screen text)
+        let has_text_in_video = conn
+            .prepare("SELECT text_in_video FROM segments LIMIT 1")
+            .is_ok();
+
+        if !has_text_in_video {
+            let _ = conn.execute(
+                "ALTER TABLE segments ADD COLUMN text_in_video TEXT NULL",
+                [],
+            );
+        }
+
+        // Full-text index over on-screen text and transcript for lexical search
+        conn.execute(
+            "CREATE VIRTUAL TABLE IF NOT EXISTS segment_text_fts USING fts5(
+                segment_id UNINDEXED,
+                project_id UNINDEXED,
+                text_in_video,
+                transcript
+            )",
+            [],
+        )?;
+
         conn.execute(
             "CREATE TABLE IF NOT EXISTS embeddings (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -332,6 +601,19 @@ impl Database {
             [],
         )?;
 
+        // Registry of embedding models: vectors of a (type, model) pair all share its dimension
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS embedding_models (
+                embedding_type TEXT NOT NULL,
+                model_name TEXT NOT NULL,
+                model_version TEXT NOT NULL,
+                dimension INTEGER NOT NULL,
+                created_at TEXT NOT NULL,
+                PRIMARY KEY (embedding_type, model_name)
+            )",
+            [],
+        )?;
+
         // Migration: Update embeddings table if it has old schema
         let has_embedding_type = conn
             .prepare("SELECT embedding_type FROM embeddings LIMIT 1")
@@ -370,6 +652,17 @@ impl Database {
             );
         }
 
+        // Migration: Drop fusion vectors. They summed MiniLM text vectors with truncated CLIP
+        // image vectors, which live in different spaces; retrieval now fuses text and vision
+        // similarity scores at query time instead (see embeddings::fused_search)
+        let has_fusion_model: bool = conn
+            .query_row("SELECT COUNT(*) > 0 FROM embedding_models WHERE embedding_type = 'fusion'", [], |row| row.get(0))
+            .unwrap_or(false);
+        if has_fusion_model {
+            conn.execute("DELETE FROM embeddings WHERE embedding_type = 'fusion'", [])?;
+            conn.execute("DELETE FROM embedding_models WHERE embedding_type = 'fusion'", [])?;
+        }
+
         conn.execute(
             "CREATE TABLE IF NOT EXISTS style_profiles (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -399,6 +692,18 @@ impl Database {
             );
         }
 
+        // Migration: Add preset_key column (set for built-in presets)
+        let has_preset_key = conn
+            .prepare("SELECT preset_key FROM style_profiles LIMIT 1")
+            .is_ok();
+
+        if !has_preset_key {
+            let _ = conn.execute(
+                "ALTER TABLE style_profiles ADD COLUMN preset_key TEXT",
+                [],
+            );
+        }
+
         conn.execute(
             "CREATE TABLE IF NOT EXISTS timeline_projects (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -447,6 +752,56 @@ impl Database {
             );
         }
 
+        // Migration: Add attempts and error columns (retries and why the last attempt failed)
+        let has_job_attempts = conn
+            .prepare("SELECT attempts FROM jobs LIMIT 1")
+            .is_ok();
+        if !has_job_attempts {
+            let _ = conn.execute(
+                "ALTER TABLE jobs ADD COLUMN attempts INTEGER NOT NULL DEFAULT 1",
+                [],
+            );
+            let _ = conn.execute(
+                "ALTER TABLE jobs ADD COLUMN error TEXT",
+                [],
+            );
+        }
+
+        // Migration: Add remote worker claims (which worker runs a job, and until when)
+        let has_job_worker = conn
+            .prepare("SELECT worker_id FROM jobs LIMIT 1")
+            .is_ok();
+        if !has_job_worker {
+            let _ = conn.execute(
+                "ALTER TABLE jobs ADD COLUMN worker_id INTEGER",
+                [],
+            );
+            let _ = conn.execute(
+                "ALTER TABLE jobs ADD COLUMN lease_expires_at TEXT",
+                [],
+            );
+        }
+
+        // Workers that process extension job kinds (JobType::External)
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS job_workers (
+                kind TEXT PRIMARY KEY,
+                command_json TEXT,
+                url TEXT,
+                requires_json TEXT NOT NULL,
+                max_attempts INTEGER NOT NULL,
+                timeout_secs INTEGER NOT NULL,
+                registered_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+        // Command workers used to be registrable through the API; they now only come from
+        // JOB_WORKERS_PATH, so stored ones are dropped rather than run
+        let dropped = conn.execute("DELETE FROM job_workers WHERE command_json IS NOT NULL", [])?;
+        if dropped > 0 {
+            eprintln!("[JOBS] Removed {} command worker(s) registered through the API; configure them in JOB_WORKERS_PATH", dropped);
+        }
+
         conn.execute(
             "CREATE TABLE IF NOT EXISTS edit_logs (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -458,6 +813,41 @@ impl Database {
             [],
         )?;
 
+        // Per-segment feedback learned from user edits to agent-placed clips
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS segment_feedback (
+                project_id INTEGER NOT NULL,
+                segment_id INTEGER NOT NULL,
+                kept INTEGER NOT NULL DEFAULT 0,
+                removed_count INTEGER NOT NULL DEFAULT 0,
+                updated_at TEXT NOT NULL,
+                PRIMARY KEY (project_id, segment_id),
+                FOREIGN KEY (project_id) REFERENCES projects(id),
+                FOREIGN KEY (segment_id) REFERENCES segments(id)
+            )",
+            [],
+        )?;
+
+        // Audit log of LLM calls (token counts, latency and estimated cost per call)
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS llm_calls (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                project_id INTEGER NOT NULL,
+                endpoint TEXT NOT NULL,
+                prompt_summary TEXT NOT NULL,
+                model TEXT,
+                prompt_tokens INTEGER,
+                completion_tokens INTEGER,
+                latency_ms INTEGER NOT NULL,
+                estimated_cost_usd REAL NOT NULL DEFAULT 0,
+                status TEXT NOT NULL,
+                error TEXT,
+                created_at TEXT NOT NULL,
+                FOREIGN KEY (project_id) REFERENCES projects(id)
+            )",
+            [],
+        )?;
+
         // New tables for raw analysis results
         conn.execute(
             "CREATE TABLE IF NOT EXISTS asset_transcripts (
@@ -526,12 +916,26 @@ impl Database {
         )?;
 
         conn.execute(
-            "CREATE TABLE IF NOT EXISTS orchestrator_goals (
+            "CREATE TABLE IF NOT EXISTS orchestrator_tool_calls (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
                 project_id INTEGER NOT NULL,
-                user_intent TEXT NOT NULL,
-                status TEXT NOT NULL,
+                instruction TEXT NOT NULL,
+                response_message TEXT NOT NULL,
+                tool_calls_json TEXT NOT NULL,
+                applied INTEGER NOT NULL DEFAULT 0,
+                error TEXT NULL,
                 created_at TEXT NOT NULL,
+                FOREIGN KEY (project_id) REFERENCES projects(id)
+            )",
+            [],
+        )?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS orchestrator_working_sets (
+                project_id INTEGER PRIMARY KEY,
+                segment_ids_json TEXT NULL,
+                source TEXT NULL,
+                last_shown_json TEXT NULL,
                 updated_at TEXT NOT NULL,
                 FOREIGN KEY (project_id) REFERENCES projects(id)
             )",
@@ -539,1110 +943,6112 @@ impl Database {
         )?;
 
         conn.execute(
-            "CREATE TABLE IF NOT EXISTS timeline_versions (
+            "CREATE TABLE IF NOT EXISTS collections (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
                 project_id INTEGER NOT NULL,
-                version_id TEXT NOT NULL,
-                parent_version_id TEXT,
-                is_current INTEGER NOT NULL DEFAULT 0,
-                json_blob TEXT NOT NULL,
+                name TEXT NOT NULL COLLATE NOCASE,
                 created_at TEXT NOT NULL,
+                UNIQUE(project_id, name),
                 FOREIGN KEY (project_id) REFERENCES projects(id)
             )",
             [],
         )?;
 
-        // Create unique index for current version (only one current version per project)
         conn.execute(
-            "CREATE UNIQUE INDEX IF NOT EXISTS timeline_versions_current ON timeline_versions(project_id, is_current) WHERE is_current = 1",
+            "CREATE TABLE IF NOT EXISTS collection_assets (
+                collection_id INTEGER NOT NULL,
+                asset_id INTEGER NOT NULL,
+                added_at TEXT NOT NULL,
+                PRIMARY KEY (collection_id, asset_id),
+                FOREIGN KEY (collection_id) REFERENCES collections(id),
+                FOREIGN KEY (asset_id) REFERENCES media_assets(id)
+            )",
             [],
         )?;
 
-        Ok(())
-    }
-}
-
-#[derive(Debug, Clone)]
-pub struct Project {
-    pub id: i64,
-    pub name: String,
-    pub created_at: DateTime<Utc>,
-    pub cache_dir: String,
-    pub style_profile_id: Option<i64>,
-}
-
-impl Project {
-    pub fn from_row(row: &Row) -> rusqlite::Result<Self> {
-        let created_at_str: String = row.get(2)?;
-        let created_at = DateTime::parse_from_rfc3339(&created_at_str)
-            .map_err(|_| rusqlite::Error::InvalidColumnType(2, "TEXT".to_string(), rusqlite::types::Type::Text))?
-            .with_timezone(&Utc);
-        
-        Ok(Project {
-            id: row.get(0)?,
-            name: row.get(1)?,
-            created_at,
-            cache_dir: row.get(3)?,
-            style_profile_id: row.get(4)?,
-        })
-    }
-}
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS collection_segments (
+                collection_id INTEGER NOT NULL,
+                segment_id INTEGER NOT NULL,
+                added_at TEXT NOT NULL,
+                PRIMARY KEY (collection_id, segment_id),
+                FOREIGN KEY (collection_id) REFERENCES collections(id),
+                FOREIGN KEY (segment_id) REFERENCES segments(id)
+            )",
+            [],
+        )?;
 
-impl Database {
-    pub fn create_project(&self, name: &str, cache_dir: &str) -> Result<i64> {
-        let now = Utc::now().to_rfc3339();
-        let conn = self.conn.lock().unwrap();
+        // Phones paired with a project through the companion endpoint. The pairing token (shown
+        // as a QR code) is exchanged once for a long-lived device token; only hashes are stored.
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS companion_devices (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                project_id INTEGER NOT NULL,
+                name TEXT,
+                pairing_token_hash TEXT NOT NULL UNIQUE,
+                pairing_expires_at TEXT NOT NULL,
+                device_token_hash TEXT UNIQUE,
+                paired_at TEXT,
+                last_seen_at TEXT,
+                created_at TEXT NOT NULL,
+                FOREIGN KEY (project_id) REFERENCES projects(id)
+            )",
+            [],
+        )?;
+
+        // Files sent from a paired phone, uploaded in chunks and imported once complete
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS companion_uploads (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                device_id INTEGER NOT NULL,
+                project_id INTEGER NOT NULL,
+                filename TEXT NOT NULL,
+                path TEXT NOT NULL,
+                size_bytes INTEGER NOT NULL,
+                received_bytes INTEGER NOT NULL DEFAULT 0,
+                captured_at TEXT,
+                live_photo_id TEXT,
+                status TEXT NOT NULL,
+                job_id INTEGER,
+                asset_id INTEGER,
+                collection_id INTEGER,
+                error TEXT,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL,
+                FOREIGN KEY (device_id) REFERENCES companion_devices(id),
+                FOREIGN KEY (project_id) REFERENCES projects(id)
+            )",
+            [],
+        )?;
+
+        // Worker processes on other machines that claim export and analysis jobs over the
+        // queue API (see jobs::remote); only a hash of each worker's token is stored
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS remote_workers (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL,
+                token_hash TEXT NOT NULL UNIQUE,
+                job_types_json TEXT NOT NULL,
+                last_seen_at TEXT,
+                created_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+
+        // Per job class overrides of the default resource limits (see jobs::resources)
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS job_resource_limits (
+                class TEXT PRIMARY KEY,
+                limits_json TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS project_templates (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL,
+                template_json TEXT NOT NULL,
+                builtin_key TEXT NULL UNIQUE,
+                created_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS end_cards (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL,
+                card_json TEXT NOT NULL,
+                builtin_key TEXT NULL UNIQUE,
+                created_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS orchestrator_goals (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                project_id INTEGER NOT NULL,
+                user_intent TEXT NOT NULL,
+                status TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL,
+                FOREIGN KEY (project_id) REFERENCES projects(id)
+            )",
+            [],
+        )?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS timeline_versions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                project_id INTEGER NOT NULL,
+                version_id TEXT NOT NULL,
+                parent_version_id TEXT,
+                is_current INTEGER NOT NULL DEFAULT 0,
+                json_blob TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                FOREIGN KEY (project_id) REFERENCES projects(id)
+            )",
+            [],
+        )?;
+
+        // Create unique index for current version (only one current version per project)
+        conn.execute(
+            "CREATE UNIQUE INDEX IF NOT EXISTS timeline_versions_current ON timeline_versions(project_id, is_current) WHERE is_current = 1",
+            [],
+        )?;
+
+        // Autosave ring of recent timeline states per project, apart from explicit versions
+        // (see autosave)
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS timeline_snapshots (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                project_id INTEGER NOT NULL,
+                version_id TEXT,
+                content_hash TEXT NOT NULL,
+                json_blob TEXT NOT NULL,
+                clip_count INTEGER NOT NULL,
+                duration_seconds REAL NOT NULL,
+                created_at TEXT NOT NULL,
+                FOREIGN KEY (project_id) REFERENCES projects(id)
+            )",
+            [],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS timeline_snapshots_project ON timeline_snapshots(project_id, id)",
+            [],
+        )?;
+
+        // One row per daemon run; a run that never recorded ended_at crashed or was killed
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS daemon_sessions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                started_at TEXT NOT NULL,
+                last_seen_at TEXT NOT NULL,
+                ended_at TEXT
+            )",
+            [],
+        )?;
+
+        // OAuth credentials for upload providers (one account per provider)
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS oauth_credentials (
+                provider TEXT PRIMARY KEY,
+                client_id TEXT NOT NULL,
+                client_secret TEXT NOT NULL,
+                refresh_token TEXT NOT NULL,
+                access_token TEXT,
+                expires_at TEXT,
+                updated_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+
+        // Uploads of exports to external platforms; upload_url keeps the resumable session
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS publications (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                project_id INTEGER NOT NULL,
+                export_job_id INTEGER NOT NULL,
+                provider TEXT NOT NULL,
+                status TEXT NOT NULL,
+                file_path TEXT NOT NULL,
+                metadata_json TEXT NOT NULL,
+                upload_url TEXT,
+                bytes_sent INTEGER NOT NULL DEFAULT 0,
+                total_bytes INTEGER NOT NULL DEFAULT 0,
+                remote_id TEXT,
+                remote_url TEXT,
+                error TEXT,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL,
+                FOREIGN KEY (project_id) REFERENCES projects(id),
+                FOREIGN KEY (export_job_id) REFERENCES jobs(id)
+            )",
+            [],
+        )?;
+
+        // Per-project webhooks; events_json is the list of subscribed event names
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS webhooks (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                project_id INTEGER NOT NULL,
+                url TEXT NOT NULL,
+                secret TEXT NOT NULL,
+                events_json TEXT NOT NULL,
+                last_status INTEGER,
+                last_error TEXT,
+                last_delivery_at TEXT,
+                created_at TEXT NOT NULL,
+                FOREIGN KEY (project_id) REFERENCES projects(id)
+            )",
+            [],
+        )?;
+
+        // Outbox of pipeline events (see crate::events), kept for the retention window so
+        // consumers can pick up what they missed
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS events (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                event_type TEXT NOT NULL,
+                project_id INTEGER,
+                payload_json TEXT NOT NULL,
+                created_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS events_created_at ON events(created_at)",
+            [],
+        )?;
+
+        // Last event each named consumer (agent loop, webhook delivery) has handled
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS event_deliveries (
+                consumer TEXT PRIMARY KEY,
+                last_event_id INTEGER NOT NULL,
+                updated_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+
+        // Generated files mirrored to the storage backend, keyed by their local path
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS stored_objects (
+                local_path TEXT PRIMARY KEY,
+                storage_key TEXT NOT NULL,
+                size_bytes INTEGER NOT NULL,
+                uploaded_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+
+        // Clips of the same event from several cameras, aligned by audio. offset_ticks places
+        // each angle's source start on the group's shared clock.
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS multicam_groups (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                project_id INTEGER NOT NULL,
+                name TEXT NOT NULL,
+                reference_asset_id INTEGER NOT NULL,
+                created_at TEXT NOT NULL,
+                FOREIGN KEY (project_id) REFERENCES projects(id),
+                FOREIGN KEY (reference_asset_id) REFERENCES media_assets(id)
+            )",
+            [],
+        )?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS multicam_angles (
+                group_id INTEGER NOT NULL,
+                asset_id INTEGER NOT NULL,
+                offset_ticks INTEGER NOT NULL,
+                confidence REAL NOT NULL,
+                PRIMARY KEY (group_id, asset_id),
+                FOREIGN KEY (group_id) REFERENCES multicam_groups(id),
+                FOREIGN KEY (asset_id) REFERENCES media_assets(id)
+            )",
+            [],
+        )?;
+
+        // Faces and license plates detected for redaction; regions_json holds the tracked boxes
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS redaction_subjects (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                project_id INTEGER NOT NULL,
+                asset_id INTEGER NOT NULL,
+                segment_id INTEGER NOT NULL,
+                kind TEXT NOT NULL,
+                enabled INTEGER NOT NULL DEFAULT 1,
+                start_sec REAL NOT NULL,
+                end_sec REAL NOT NULL,
+                regions_json TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                FOREIGN KEY (project_id) REFERENCES projects(id),
+                FOREIGN KEY (asset_id) REFERENCES media_assets(id)
+            )",
+            [],
+        )?;
+
+        // Narration recorded (or synthesized) for a project; transcript_json is filled in once
+        // it's been transcribed
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS voiceovers (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                project_id INTEGER NOT NULL,
+                path TEXT NOT NULL,
+                source TEXT NOT NULL,
+                duration_ticks INTEGER,
+                transcript_json TEXT,
+                created_at TEXT NOT NULL,
+                FOREIGN KEY (project_id) REFERENCES projects(id)
+            )",
+            [],
+        )?;
+
+        // Logos and fonts in a project's brand kit, copied into its cache; fonts record the
+        // embedding rights they declare and whether the user has marked them licensed for export
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS brand_assets (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                project_id INTEGER NOT NULL,
+                kind TEXT NOT NULL,
+                name TEXT NOT NULL,
+                path TEXT NOT NULL,
+                checksum TEXT NOT NULL,
+                width INTEGER,
+                height INTEGER,
+                has_alpha INTEGER NOT NULL DEFAULT 0,
+                key_color TEXT,
+                font_family TEXT,
+                embedding TEXT,
+                licensed INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL,
+                FOREIGN KEY (project_id) REFERENCES projects(id)
+            )",
+            [],
+        )?;
+
+        // People in a project's footage: a name and role for the diarized speakers they are
+        // (speakers_json lists {asset_id, speaker} labels, which are only consistent within an asset)
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS persons (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                project_id INTEGER NOT NULL,
+                name TEXT NOT NULL,
+                role TEXT,
+                speakers_json TEXT NOT NULL DEFAULT '[]',
+                created_at TEXT NOT NULL,
+                FOREIGN KEY (project_id) REFERENCES projects(id)
+            )",
+            [],
+        )?;
+
+        // Lower thirds suggested where each named person first speaks in the cut; status is
+        // suggested, accepted (title_id is its title on the timeline) or dismissed
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS lower_thirds (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                project_id INTEGER NOT NULL,
+                person_id INTEGER NOT NULL,
+                status TEXT NOT NULL DEFAULT 'suggested',
+                start_ticks INTEGER NOT NULL,
+                end_ticks INTEGER NOT NULL,
+                title_id TEXT,
+                created_at TEXT NOT NULL,
+                UNIQUE(project_id, person_id),
+                FOREIGN KEY (project_id) REFERENCES projects(id),
+                FOREIGN KEY (person_id) REFERENCES persons(id)
+            )",
+            [],
+        )?;
+
+        // Questions asked in a project's interviews (found in transcripts, or from a question
+        // list, source says which) and the segments that ask (role 'question') or answer them
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS interview_questions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                project_id INTEGER NOT NULL,
+                text TEXT NOT NULL,
+                topic TEXT,
+                position INTEGER NOT NULL,
+                source TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                FOREIGN KEY (project_id) REFERENCES projects(id)
+            )",
+            [],
+        )?;
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS interview_segments (
+                segment_id INTEGER PRIMARY KEY,
+                project_id INTEGER NOT NULL,
+                question_id INTEGER NOT NULL,
+                role TEXT NOT NULL,
+                FOREIGN KEY (segment_id) REFERENCES segments(id),
+                FOREIGN KEY (question_id) REFERENCES interview_questions(id)
+            )",
+            [],
+        )?;
+
+        // Asset library: analyzed assets their project has shared, and the other projects using
+        // them by reference (their segments, transcripts and embeddings stay with the owner)
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS library_assets (
+                asset_id INTEGER PRIMARY KEY,
+                name TEXT NOT NULL,
+                published_at TEXT NOT NULL,
+                FOREIGN KEY (asset_id) REFERENCES media_assets(id)
+            )",
+            [],
+        )?;
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS project_asset_links (
+                project_id INTEGER NOT NULL,
+                asset_id INTEGER NOT NULL,
+                linked_at TEXT NOT NULL,
+                PRIMARY KEY (project_id, asset_id),
+                FOREIGN KEY (project_id) REFERENCES projects(id),
+                FOREIGN KEY (asset_id) REFERENCES library_assets(asset_id)
+            )",
+            [],
+        )?;
+
+        // Tokens a hosted deployment hands out to API clients; projects created with one count
+        // against its quotas (see quotas). Only a hash of each token is stored
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS api_tokens (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL,
+                token_hash TEXT NOT NULL UNIQUE,
+                created_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+
+        // Quota limits of a project or an API token (scope "project" or "token")
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS quotas (
+                scope TEXT NOT NULL,
+                scope_id INTEGER NOT NULL,
+                limits_json TEXT NOT NULL,
+                updated_at TEXT NOT NULL,
+                PRIMARY KEY (scope, scope_id)
+            )",
+            [],
+        )?;
+
+        // Music catalog shared by all projects: local files and tracks downloaded from music
+        // services, with tempo and mood tags for picking one and the license to credit it under
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS music_tracks (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                title TEXT NOT NULL,
+                artist TEXT,
+                path TEXT NOT NULL UNIQUE,
+                source TEXT NOT NULL,
+                external_id TEXT,
+                bpm REAL,
+                moods_json TEXT NOT NULL,
+                duration_ticks INTEGER,
+                license TEXT,
+                license_url TEXT,
+                attribution TEXT,
+                created_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+
+        // Migration: Add the analyzed structure (sections, drops, energy) of music tracks
+        let has_music_structure = conn
+            .prepare("SELECT structure_json FROM music_tracks LIMIT 1")
+            .is_ok();
+        if !has_music_structure {
+            let _ = conn.execute(
+                "ALTER TABLE music_tracks ADD COLUMN structure_json TEXT",
+                [],
+            );
+        }
+
+        // Migration: Add encryption columns (passphrase salt and key check for encrypted projects)
+        let has_encryption = conn
+            .prepare("SELECT encryption_salt FROM projects LIMIT 1")
+            .is_ok();
+        if !has_encryption {
+            let _ = conn.execute(
+                "ALTER TABLE projects ADD COLUMN encryption_salt TEXT",
+                [],
+            );
+            let _ = conn.execute(
+                "ALTER TABLE projects ADD COLUMN encryption_check TEXT",
+                [],
+            );
+        }
+
+        // Migration: Add the API token a project was created with (its quotas apply too)
+        let has_api_token = conn
+            .prepare("SELECT api_token_id FROM projects LIMIT 1")
+            .is_ok();
+        if !has_api_token {
+            let _ = conn.execute(
+                "ALTER TABLE projects ADD COLUMN api_token_id INTEGER NULL",
+                [],
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// Usage totals for one endpoint as
+/// (endpoint, calls, failed_calls, prompt_tokens, completion_tokens, total_latency_ms, estimated_cost_usd)
+pub type LlmUsageRow = (String, i64, i64, i64, i64, i64, f64);
+
+/// A project template as (id, builtin_key, template_json, created_at)
+pub type ProjectTemplateRow = (i64, Option<String>, String, String);
+
+/// An end card as (id, builtin_key, card_json, created_at)
+pub type EndCardRow = (i64, Option<String>, String, String);
+
+/// A collection as (id, name, created_at, asset_count, segment_count)
+pub type CollectionRow = (i64, String, String, i64, i64);
+
+/// (asset id, integrity, checked_at, error) from the last checksum verification
+pub type AssetIntegrityRow = (i64, String, String, Option<String>);
+
+/// (asset id, sync_drift_json, checked_at, sync_correction) from the last A/V sync measurement
+pub type AssetSyncDriftRow = (i64, String, String, bool);
+
+/// One LLM call for the audit log
+#[derive(Debug, Clone)]
+pub struct LlmCallRecord {
+    pub project_id: i64,
+    pub endpoint: String,
+    pub prompt_summary: String,
+    pub model: Option<String>,
+    pub prompt_tokens: Option<i64>,
+    pub completion_tokens: Option<i64>,
+    pub latency_ms: i64,
+    pub estimated_cost_usd: f64,
+    /// "ok" or "error"
+    pub status: String,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Project {
+    pub id: i64,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub cache_dir: String,
+    pub style_profile_id: Option<i64>,
+}
+
+impl Project {
+    pub fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        let created_at_str: String = row.get(2)?;
+        let created_at = DateTime::parse_from_rfc3339(&created_at_str)
+            .map_err(|_| rusqlite::Error::InvalidColumnType(2, "TEXT".to_string(), rusqlite::types::Type::Text))?
+            .with_timezone(&Utc);
+        
+        Ok(Project {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            created_at,
+            cache_dir: row.get(3)?,
+            style_profile_id: row.get(4)?,
+        })
+    }
+}
+
+impl Database {
+    pub fn create_project(&self, name: &str, cache_dir: &str) -> Result<i64> {
+        let now = Utc::now().to_rfc3339();
+        let conn = self.conn.lock().unwrap();
         conn.execute(
             "INSERT INTO projects (name, created_at, cache_dir) VALUES (?1, ?2, ?3)",
             params![name, now, cache_dir],
         )?;
-        Ok(conn.last_insert_rowid())
+        Ok(conn.last_insert_rowid())
+    }
+
+    pub fn get_project(&self, id: i64) -> Result<Option<Project>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, name, created_at, cache_dir, style_profile_id FROM projects WHERE id = ?1"
+        )?;
+        let mut rows = stmt.query_map(params![id], |row| Project::from_row(row))?;
+        
+        match rows.next() {
+            Some(Ok(project)) => Ok(Some(project)),
+            Some(Err(e)) => Err(e.into()),
+            None => Ok(None),
+        }
+    }
+
+    pub fn get_all_projects(&self) -> Result<Vec<Project>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, name, created_at, cache_dir, style_profile_id FROM projects ORDER BY created_at DESC"
+        )?;
+        let rows = stmt.query_map([], |row| Project::from_row(row))?;
+        
+        let mut projects = Vec::new();
+        for row in rows {
+            projects.push(row?);
+        }
+        Ok(projects)
+    }
+
+    pub fn delete_project(&self, id: i64) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        // Library assets it used, and its own assets in the library (which nothing else uses)
+        conn.execute("DELETE FROM project_asset_links WHERE project_id = ?1", params![id])?;
+        conn.execute(
+            "DELETE FROM library_assets WHERE asset_id IN (SELECT id FROM media_assets WHERE project_id = ?1)",
+            params![id],
+        )?;
+        conn.execute("DELETE FROM quotas WHERE scope = 'project' AND scope_id = ?1", params![id])?;
+        conn.execute("DELETE FROM projects WHERE id = ?1", params![id])?;
+        Ok(())
+    }
+
+    pub fn create_media_asset(
+        &self,
+        project_id: i64,
+        path: &str,
+        checksum: Option<&str>,
+        duration_ticks: i64,
+        fps_num: i32,
+        fps_den: i32,
+        width: i32,
+        height: i32,
+        has_audio: bool,
+    ) -> Result<i64> {
+        self.create_media_asset_with_reference_flag(
+            project_id, path, checksum, duration_ticks, fps_num, fps_den, width, height, has_audio, false,
+        )
+    }
+    
+    pub fn create_media_asset_with_reference_flag(
+        &self,
+        project_id: i64,
+        path: &str,
+        checksum: Option<&str>,
+        duration_ticks: i64,
+        fps_num: i32,
+        fps_den: i32,
+        width: i32,
+        height: i32,
+        has_audio: bool,
+        is_reference: bool,
+    ) -> Result<i64> {
+        let conn = self.conn.lock().unwrap();
+        
+        // Check if asset already exists for this project
+        let existing_id: Result<i64, rusqlite::Error> = conn.query_row(
+            "SELECT id FROM media_assets WHERE project_id = ?1 AND path = ?2",
+            params![project_id, path],
+            |row| row.get::<_, i64>(0),
+        );
+        
+        match existing_id {
+            Ok(id) => {
+                // Update existing asset
+                conn.execute(
+                    "UPDATE media_assets SET checksum = ?1, duration_ticks = ?2, fps_num = ?3, fps_den = ?4, width = ?5, height = ?6, has_audio = ?7, is_reference = ?8 WHERE id = ?9",
+                    params![checksum, duration_ticks, fps_num, fps_den, width, height, if has_audio { 1 } else { 0 }, if is_reference { 1 } else { 0 }, id],
+                )?;
+                Ok(id)
+            }
+            Err(rusqlite::Error::QueryReturnedNoRows) => {
+                // Insert new asset
+                conn.execute(
+                    "INSERT INTO media_assets (project_id, path, checksum, duration_ticks, fps_num, fps_den, width, height, has_audio, is_reference) 
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                    params![project_id, path, checksum, duration_ticks, fps_num, fps_den, width, height, if has_audio { 1 } else { 0 }, if is_reference { 1 } else { 0 }],
+                )?;
+                Ok(conn.last_insert_rowid())
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn create_proxy(
+        &self,
+        media_asset_id: i64,
+        path: &str,
+        codec: &str,
+        width: i32,
+        height: i32,
+    ) -> Result<i64> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO proxies (media_asset_id, path, codec, width, height) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![media_asset_id, path, codec, width, height],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    pub fn create_style_profile(&self, name: &str, json_blob: &str) -> Result<i64> {
+        let now = Utc::now().to_rfc3339();
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO style_profiles (name, json_blob, created_at) VALUES (?1, ?2, ?3)",
+            params![name, json_blob, now],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    pub fn get_style_profile(&self, id: i64) -> Result<Option<String>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT json_blob FROM style_profiles WHERE id = ?1")?;
+        let mut rows = stmt.query_map(params![id], |row| {
+            Ok(row.get::<_, String>(0)?)
+        })?;
+        
+        match rows.next() {
+            Some(Ok(blob)) => Ok(Some(blob)),
+            Some(Err(e)) => Err(e.into()),
+            None => Ok(None),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Segment {
+    pub id: i64,
+    pub media_asset_id: i64,
+    pub project_id: i64,
+    pub start_ticks: i64,
+    pub end_ticks: i64,
+    pub src_in_ticks: Option<i64>,
+    pub src_out_ticks: Option<i64>,
+    pub segment_kind: Option<String>,
+    pub summary_text: Option<String>,
+    pub keywords_json: Option<String>,
+    pub quality_json: Option<String>,
+    pub subject_json: Option<String>,
+    pub scene_json: Option<String>,
+    pub capture_time: Option<String>,
+    pub transcript: Option<String>,
+    pub speaker: Option<String>,
+    pub shot_type: Option<String>,
+    pub text_in_video: Option<String>,
+    pub tags_json: Option<String>,
+    pub scores_json: Option<String>,
+    /// ISO 639-1 code of the segment's speech, when it has any
+    pub language: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MediaAssetInfo {
+    pub id: i64,
+    pub path: String,
+    pub duration_ticks: i64,
+    pub fps_num: i32,
+    pub fps_den: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Database {
+    /// Get all segments with their media asset info for a project, library assets it uses included
+    pub fn get_segments_for_project(&self, project_id: i64) -> Result<Vec<(Segment, MediaAssetInfo)>> {
+        let conn = self.conn.lock().unwrap();
+        
+        // Join segments with media_assets to get full info, filter by project_id
+        let mut stmt = conn.prepare(
+            "SELECT s.id, s.media_asset_id, s.project_id, s.start_ticks, s.end_ticks, 
+                    s.src_in_ticks, s.src_out_ticks, s.segment_kind, s.summary_text, 
+                    s.keywords_json, s.quality_json, s.subject_json, s.scene_json, 
+                    s.capture_time, s.transcript, s.speaker, s.shot_type, s.text_in_video, s.tags_json, s.scores_json, s.language,
+                    ma.id, ma.path, ma.duration_ticks, ma.fps_num, ma.fps_den, ma.width, ma.height
+             FROM segments s
+             INNER JOIN media_assets ma ON s.media_asset_id = ma.id
+             WHERE (s.project_id = ?1 OR s.media_asset_id IN (SELECT asset_id FROM project_asset_links WHERE project_id = ?1))
+             ORDER BY ma.id, s.start_ticks"
+        )?;
+        
+        let rows = stmt.query_map(params![project_id], |row| {
+            let segment = Segment {
+                id: row.get(0)?,
+                media_asset_id: row.get(1)?,
+                project_id: row.get(2)?,
+                start_ticks: row.get(3)?,
+                end_ticks: row.get(4)?,
+                src_in_ticks: row.get(5)?,
+                src_out_ticks: row.get(6)?,
+                segment_kind: row.get(7)?,
+                summary_text: row.get(8)?,
+                keywords_json: row.get(9)?,
+                quality_json: row.get(10)?,
+                subject_json: row.get(11)?,
+                scene_json: row.get(12)?,
+                capture_time: row.get(13)?,
+                transcript: row.get(14)?,
+                speaker: row.get(15)?,
+                shot_type: row.get(16)?,
+                text_in_video: row.get(17)?,
+                tags_json: row.get(18)?,
+                scores_json: row.get(19)?,
+                language: row.get(20)?,
+            };
+            
+            let media_asset = MediaAssetInfo {
+                id: row.get(21)?,
+                path: row.get(22)?,
+                duration_ticks: row.get(23)?,
+                fps_num: row.get(24)?,
+                fps_den: row.get(25)?,
+                width: row.get(26)?,
+                height: row.get(27)?,
+            };
+            
+            Ok((segment, media_asset))
+        })?;
+        
+        let mut result = Vec::new();
+        for row in rows {
+            result.push(row?);
+        }
+        
+        Ok(result)
+    }
+
+    /// Get coalesced src_in_ticks (single source of truth for reading)
+    pub fn get_coalesced_src_in(segment: &Segment) -> i64 {
+        segment.src_in_ticks.unwrap_or(segment.start_ticks)
+    }
+
+    /// Get coalesced src_out_ticks (single source of truth for reading)
+    pub fn get_coalesced_src_out(segment: &Segment) -> i64 {
+        segment.src_out_ticks.unwrap_or(segment.end_ticks)
+    }
+
+    /// Create a new segment with stable identity fields
+    pub fn create_segment(
+        &self,
+        project_id: i64,
+        media_asset_id: i64,
+        src_in_ticks: i64,
+        src_out_ticks: i64,
+    ) -> Result<i64> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO segments (project_id, media_asset_id, src_in_ticks, src_out_ticks, start_ticks, end_ticks) 
+             VALUES (?1, ?2, ?3, ?4, ?3, ?4)",
+            params![project_id, media_asset_id, src_in_ticks, src_out_ticks],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Get or create a dynamic segment (idempotent via dedupe_key). The asset must belong to the
+    /// project and the range must be non-empty; a dedupe_key already used by another project's
+    /// segment is an error rather than a silent cross-project match.
+    pub fn get_or_create_dynamic_segment(
+        &self,
+        asset_id: i64,
+        project_id: i64,
+        start_ticks: i64,
+        end_ticks: i64,
+        dedupe_key: &str,
+        external_source: &str,
+        external_ref: &str,
+    ) -> Result<i64> {
+        if end_ticks <= start_ticks {
+            return Err(anyhow::anyhow!(
+                "Dynamic segment range is empty: {}..{}",
+                start_ticks, end_ticks
+            ));
+        }
+        
+        let conn = self.conn.lock().unwrap();
+        
+        let asset_in_project: bool = conn.query_row(
+            "SELECT COUNT(*) FROM media_assets WHERE id = ?1 AND project_id = ?2",
+            params![asset_id, project_id],
+            |row| row.get::<_, i64>(0),
+        )? > 0;
+        if !asset_in_project {
+            return Err(anyhow::anyhow!(
+                "Media asset {} doesn't belong to project {}",
+                asset_id, project_id
+            ));
+        }
+        
+        // INSERT OR IGNORE against the unique dedupe_key index, then read back whichever row won
+        conn.execute(
+            "INSERT OR IGNORE INTO segments (
+                project_id, media_asset_id, 
+                src_in_ticks, src_out_ticks, start_ticks, end_ticks,
+                segment_kind, dedupe_key, external_source, external_ref, created_at
+            ) VALUES (?1, ?2, ?3, ?4, ?3, ?4, NULL, ?5, ?6, ?7, ?8)",
+            params![
+                project_id, asset_id,
+                start_ticks, end_ticks,
+                dedupe_key, external_source, external_ref,
+                Utc::now().to_rfc3339()
+            ],
+        )?;
+        
+        let (segment_id, segment_project_id): (i64, i64) = conn.query_row(
+            "SELECT id, project_id FROM segments WHERE dedupe_key = ?1",
+            params![dedupe_key],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        if segment_project_id != project_id {
+            return Err(anyhow::anyhow!(
+                "Dedupe key {} already belongs to a segment in project {}",
+                dedupe_key, segment_project_id
+            ));
+        }
+        Ok(segment_id)
+    }
+
+    /// Delete dynamic segments (those with a dedupe_key) older than `min_age_secs` that no stored
+    /// proposal or timeline version references, along with their embeddings, feedback and
+    /// full-text rows. Returns the number of segments removed.
+    pub fn cleanup_dynamic_segments(&self, project_id: Option<i64>, min_age_secs: i64) -> Result<usize> {
+        let cutoff = (Utc::now() - chrono::Duration::seconds(min_age_secs)).to_rfc3339();
+        let conn = self.conn.lock().unwrap();
+        
+        // Segments referenced anywhere in proposals or timelines (any version or autosave
+        // snapshot, so restoring one still resolves its clips)
+        let mut referenced: std::collections::HashSet<i64> = std::collections::HashSet::new();
+        for query in [
+            "SELECT proposal_json FROM orchestrator_proposals WHERE ?1 IS NULL OR project_id = ?1",
+            "SELECT json_blob FROM timeline_versions WHERE ?1 IS NULL OR project_id = ?1",
+            "SELECT json_blob FROM timeline_projects WHERE ?1 IS NULL OR project_id = ?1",
+            "SELECT json_blob FROM timeline_snapshots WHERE ?1 IS NULL OR project_id = ?1",
+        ] {
+            let mut stmt = conn.prepare(query)?;
+            let rows = stmt.query_map(params![project_id], |row| row.get::<_, String>(0))?;
+            for row in rows {
+                if let Ok(value) = serde_json::from_str::<serde_json::Value>(&row?) {
+                    collect_segment_ids(&value, &mut referenced);
+                }
+            }
+        }
+        
+        let candidates: Vec<i64> = {
+            let mut stmt = conn.prepare(
+                "SELECT id FROM segments
+                 WHERE dedupe_key IS NOT NULL
+                   AND (?1 IS NULL OR project_id = ?1)
+                   AND (created_at IS NULL OR created_at < ?2)",
+            )?;
+            let rows = stmt.query_map(params![project_id, cutoff], |row| row.get(0))?;
+            rows.collect::<Result<Vec<_>, _>>()?
+        };
+        
+        let mut removed = 0;
+        for segment_id in candidates.into_iter().filter(|id| !referenced.contains(id)) {
+            conn.execute("DELETE FROM embeddings WHERE segment_id = ?1", params![segment_id])?;
+            conn.execute("DELETE FROM segment_feedback WHERE segment_id = ?1", params![segment_id])?;
+            conn.execute("DELETE FROM segment_text_fts WHERE segment_id = ?1", params![segment_id])?;
+            removed += conn.execute("DELETE FROM segments WHERE id = ?1", params![segment_id])?;
+        }
+        Ok(removed)
+    }
+
+    /// Update segment metadata fields (enrichable fields)
+    pub fn update_segment_metadata(
+        &self,
+        segment_id: i64,
+        summary_text: Option<&str>,
+        keywords_json: Option<&str>,
+        quality_json: Option<&str>,
+        subject_json: Option<&str>,
+        scene_json: Option<&str>,
+        transcript: Option<&str>,
+        segment_kind: Option<&str>,
+    ) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE segments SET 
+                summary_text = COALESCE(?1, summary_text),
+                keywords_json = COALESCE(?2, keywords_json),
+                quality_json = COALESCE(?3, quality_json),
+                subject_json = COALESCE(?4, subject_json),
+                scene_json = COALESCE(?5, scene_json),
+                transcript = COALESCE(?6, transcript),
+                segment_kind = COALESCE(?7, segment_kind)
+             WHERE id = ?8",
+            params![summary_text, keywords_json, quality_json, subject_json, scene_json, transcript, segment_kind, segment_id],
+        )?;
+        Ok(())
+    }
+
+    /// Set the detected spoken language (ISO 639-1) for a segment
+    pub fn set_segment_language(&self, segment_id: i64, language: Option<&str>) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE segments SET language = ?1 WHERE id = ?2",
+            params![language, segment_id],
+        )?;
+        Ok(())
+    }
+
+    /// Set the diarized speaker label for a segment
+    pub fn set_segment_speaker(&self, segment_id: i64, speaker: Option<&str>) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE segments SET speaker = ?1 WHERE id = ?2",
+            params![speaker, segment_id],
+        )?;
+        Ok(())
+    }
+
+    /// Spoken languages in a project's raw footage, most common first
+    pub fn get_project_languages(&self, project_id: i64) -> Result<Vec<String>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT s.language FROM segments s
+             INNER JOIN media_assets ma ON s.media_asset_id = ma.id
+             WHERE (s.project_id = ?1 OR s.media_asset_id IN (SELECT asset_id FROM project_asset_links WHERE project_id = ?1)) AND s.language IS NOT NULL AND COALESCE(ma.is_reference, 0) = 0
+             GROUP BY s.language ORDER BY COUNT(*) DESC"
+        )?;
+        let rows = stmt.query_map(params![project_id], |row| row.get(0))?;
+        Ok(rows.collect::<Result<Vec<_>, _>>()?)
+    }
+
+    /// Set the classified shot type for a segment
+    pub fn set_segment_shot_type(&self, segment_id: i64, shot_type: Option<&str>) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE segments SET shot_type = ?1 WHERE id = ?2",
+            params![shot_type, segment_id],
+        )?;
+        Ok(())
+    }
+
+    /// Replace a segment's structured tags of the given kinds, keeping tags of other kinds
+    /// (vision and audio tagging write to the same tags_json array independently)
+    pub fn replace_segment_tags(&self, segment_id: i64, kinds: &[&str], tags: &[serde_json::Value]) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        let existing: Option<String> = conn.query_row(
+            "SELECT tags_json FROM segments WHERE id = ?1",
+            params![segment_id],
+            |row| row.get(0),
+        )?;
+
+        let mut merged: Vec<serde_json::Value> = existing
+            .and_then(|json| serde_json::from_str::<Vec<serde_json::Value>>(&json).ok())
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|tag| {
+                let kind = tag.get("kind").and_then(|k| k.as_str()).unwrap_or("");
+                !kinds.contains(&kind)
+            })
+            .collect();
+        merged.extend(tags.iter().cloned());
+
+        let tags_json = if merged.is_empty() {
+            None
+        } else {
+            Some(serde_json::Value::Array(merged).to_string())
+        };
+        conn.execute(
+            "UPDATE segments SET tags_json = ?1 WHERE id = ?2",
+            params![tags_json, segment_id],
+        )?;
+        Ok(())
+    }
+
+    /// Overlay keys onto a segment's scores_json object (other scores are kept)
+    pub fn merge_segment_scores(&self, segment_id: i64, scores: &serde_json::Value) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        let existing: Option<String> = conn.query_row(
+            "SELECT scores_json FROM segments WHERE id = ?1",
+            params![segment_id],
+            |row| row.get(0),
+        )?;
+
+        let mut merged = existing
+            .and_then(|json| serde_json::from_str::<serde_json::Value>(&json).ok())
+            .filter(|v| v.is_object())
+            .unwrap_or_else(|| serde_json::json!({}));
+        if let (Some(target), Some(patch)) = (merged.as_object_mut(), scores.as_object()) {
+            for (key, value) in patch {
+                target.insert(key.clone(), value.clone());
+            }
+        }
+
+        conn.execute(
+            "UPDATE segments SET scores_json = ?1 WHERE id = ?2",
+            params![merged.to_string(), segment_id],
+        )?;
+        Ok(())
+    }
+
+    /// Raw segments in a project carrying a structured tag, with the tag's confidence
+    pub fn get_segments_with_tag(&self, project_id: i64, label: &str, kind: &str, min_confidence: f64) -> Result<Vec<(i64, f64)>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT s.id, MAX(json_extract(t.value, '$.confidence')) AS confidence
+             FROM segments s
+             JOIN media_assets m ON s.media_asset_id = m.id,
+                  json_each(s.tags_json) t
+             WHERE (s.project_id = ?1 OR s.media_asset_id IN (SELECT asset_id FROM project_asset_links WHERE project_id = ?1))
+               AND s.tags_json IS NOT NULL
+               AND (m.is_reference IS NULL OR m.is_reference = 0)
+               AND json_extract(t.value, '$.label') = ?2
+               AND json_extract(t.value, '$.kind') = ?3
+               AND json_extract(t.value, '$.confidence') >= ?4
+             GROUP BY s.id
+             ORDER BY confidence DESC"
+        )?;
+        let rows = stmt.query_map(params![project_id, label, kind, min_confidence], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, f64>(1)?))
+        })?;
+
+        let mut results = Vec::new();
+        for row in rows {
+            results.push(row?);
+        }
+        Ok(results)
+    }
+
+    /// Set OCR'd on-screen text for a segment and refresh its lexical index entry
+    pub fn set_segment_text_in_video(&self, segment_id: i64, text_in_video: Option<&str>) -> Result<()> {
+        {
+            let conn = self.conn.lock().unwrap();
+            conn.execute(
+                "UPDATE segments SET text_in_video = ?1 WHERE id = ?2",
+                params![text_in_video, segment_id],
+            )?;
+        }
+        self.reindex_segment_text(segment_id)
+    }
+
+    /// Rebuild the full-text index row for a segment from its current text columns
+    pub fn reindex_segment_text(&self, segment_id: i64) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "DELETE FROM segment_text_fts WHERE segment_id = ?1",
+            params![segment_id],
+        )?;
+        conn.execute(
+            "INSERT INTO segment_text_fts (segment_id, project_id, text_in_video, transcript)
+             SELECT id, project_id, COALESCE(text_in_video, ''), COALESCE(transcript, '')
+             FROM segments
+             WHERE id = ?1 AND (text_in_video IS NOT NULL OR transcript IS NOT NULL)",
+            params![segment_id],
+        )?;
+        Ok(())
+    }
+
+    /// Lexical (BM25) search over on-screen text and transcripts of raw (non-reference) segments
+    /// Returns (segment_id, score) with score normalized to 0..1, best first
+    pub fn search_segments_lexical(&self, project_id: i64, query: &str, limit: usize) -> Result<Vec<(i64, f32)>> {
+        let fts_query = fts_match_query(query);
+        if fts_query.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let conn = self.conn.lock().unwrap();
+        // Weight on-screen text above transcript matches
+        let mut stmt = conn.prepare(
+            "SELECT segment_id, bm25(segment_text_fts, 0.0, 0.0, 2.0, 1.0) AS rank
+             FROM segment_text_fts
+             WHERE segment_text_fts MATCH ?1
+               AND (project_id = ?2 OR segment_id IN (
+                   SELECT s.id FROM segments s
+                   INNER JOIN project_asset_links l ON l.asset_id = s.media_asset_id
+                   WHERE l.project_id = ?2
+               ))
+               AND segment_id IN (
+                   SELECT s.id FROM segments s
+                   JOIN media_assets m ON s.media_asset_id = m.id
+                   WHERE m.is_reference IS NULL OR m.is_reference = 0
+               )
+             ORDER BY rank
+             LIMIT ?3"
+        )?;
+        let rows = stmt.query_map(params![fts_query, project_id, limit as i64], |row| {
+            let segment_id: i64 = row.get(0)?;
+            let rank: f64 = row.get(1)?;
+            // bm25 is negative (more negative = better); squash to 0..1
+            let strength = -rank;
+            Ok((segment_id, (strength / (1.0 + strength)).max(0.0) as f32))
+        })?;
+
+        let mut results = Vec::new();
+        for row in rows {
+            results.push(row?);
+        }
+        Ok(results)
+    }
+
+    /// Lexical search over every project that hasn't opted out, for finding footage without
+    /// knowing which project it's in. `captured_from`/`captured_to` are date prefixes
+    /// ("2024-06", "2024-08-31") bounding capture time, both inclusive.
+    pub fn search_segments_global(
+        &self,
+        query: &str,
+        captured_from: Option<&str>,
+        captured_to: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<GlobalSearchHit>> {
+        let fts_query = fts_match_query(query);
+        if fts_query.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT f.segment_id, s.project_id, p.name, s.media_asset_id, m.path,
+                    s.src_in_ticks, s.src_out_ticks, s.capture_time, s.summary_text,
+                    snippet(segment_text_fts, -1, '[', ']', '...', 12),
+                    bm25(segment_text_fts, 0.0, 0.0, 2.0, 1.0) AS rank
+             FROM segment_text_fts f
+             JOIN segments s ON s.id = f.segment_id
+             JOIN media_assets m ON m.id = s.media_asset_id
+             JOIN projects p ON p.id = s.project_id
+             WHERE segment_text_fts MATCH ?1
+               AND p.exclude_from_global_search = 0
+               AND (m.is_reference IS NULL OR m.is_reference = 0)
+               AND (?2 IS NULL OR substr(s.capture_time, 1, length(?2)) >= ?2)
+               AND (?3 IS NULL OR substr(s.capture_time, 1, length(?3)) <= ?3)
+             ORDER BY rank
+             LIMIT ?4"
+        )?;
+        let rows = stmt.query_map(params![fts_query, captured_from, captured_to, limit as i64], |row| {
+            let rank: f64 = row.get(10)?;
+            let strength = -rank;
+            Ok(GlobalSearchHit {
+                segment_id: row.get(0)?,
+                project_id: row.get(1)?,
+                project_name: row.get(2)?,
+                asset_id: row.get(3)?,
+                asset_path: row.get(4)?,
+                src_in_ticks: row.get(5)?,
+                src_out_ticks: row.get(6)?,
+                capture_time: row.get(7)?,
+                summary_text: row.get(8)?,
+                snippet: row.get(9)?,
+                score: (strength / (1.0 + strength)).max(0.0),
+            })
+        })?;
+
+        let mut results = Vec::new();
+        for row in rows {
+            results.push(row?);
+        }
+        Ok(results)
+    }
+
+    /// Get segments for a specific asset
+    pub fn get_segments_by_asset(&self, asset_id: i64) -> Result<Vec<Segment>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, media_asset_id, project_id, start_ticks, end_ticks, 
+                    src_in_ticks, src_out_ticks, segment_kind, summary_text, 
+                    keywords_json, quality_json, subject_json, scene_json, 
+                    capture_time, transcript, speaker, shot_type, text_in_video, tags_json, scores_json, language
+             FROM segments
+             WHERE media_asset_id = ?1
+             ORDER BY start_ticks"
+        )?;
+        
+        let rows = stmt.query_map(params![asset_id], |row| {
+            Ok(Segment {
+                id: row.get(0)?,
+                media_asset_id: row.get(1)?,
+                project_id: row.get(2)?,
+                start_ticks: row.get(3)?,
+                end_ticks: row.get(4)?,
+                src_in_ticks: row.get(5)?,
+                src_out_ticks: row.get(6)?,
+                segment_kind: row.get(7)?,
+                summary_text: row.get(8)?,
+                keywords_json: row.get(9)?,
+                quality_json: row.get(10)?,
+                subject_json: row.get(11)?,
+                scene_json: row.get(12)?,
+                capture_time: row.get(13)?,
+                transcript: row.get(14)?,
+                speaker: row.get(15)?,
+                shot_type: row.get(16)?,
+                text_in_video: row.get(17)?,
+                tags_json: row.get(18)?,
+                scores_json: row.get(19)?,
+                language: row.get(20)?,
+            })
+        })?;
+        
+        let mut segments = Vec::new();
+        for row in rows {
+            segments.push(row?);
+        }
+        Ok(segments)
+    }
+
+    /// Get segment with its embeddings
+    pub fn get_segment_with_embeddings(&self, segment_id: i64) -> Result<Option<(Segment, Vec<(String, String, Vec<u8>)>)>> {
+        let conn = self.conn.lock().unwrap();
+        
+        // Get segment
+        let mut stmt = conn.prepare(
+            "SELECT id, media_asset_id, project_id, start_ticks, end_ticks, 
+                    src_in_ticks, src_out_ticks, segment_kind, summary_text, 
+                    keywords_json, quality_json, subject_json, scene_json, 
+                    capture_time, transcript, speaker, shot_type, text_in_video, tags_json, scores_json, language
+             FROM segments
+             WHERE id = ?1"
+        )?;
+        
+        let segment_opt: Option<Segment> = stmt.query_row(params![segment_id], |row| {
+            Ok(Segment {
+                id: row.get(0)?,
+                media_asset_id: row.get(1)?,
+                project_id: row.get(2)?,
+                start_ticks: row.get(3)?,
+                end_ticks: row.get(4)?,
+                src_in_ticks: row.get(5)?,
+                src_out_ticks: row.get(6)?,
+                segment_kind: row.get(7)?,
+                summary_text: row.get(8)?,
+                keywords_json: row.get(9)?,
+                quality_json: row.get(10)?,
+                subject_json: row.get(11)?,
+                scene_json: row.get(12)?,
+                capture_time: row.get(13)?,
+                transcript: row.get(14)?,
+                speaker: row.get(15)?,
+                shot_type: row.get(16)?,
+                text_in_video: row.get(17)?,
+                tags_json: row.get(18)?,
+                scores_json: row.get(19)?,
+                language: row.get(20)?,
+            })
+        }).ok();
+        
+        if let Some(segment) = segment_opt {
+            // Get embeddings
+            let mut emb_stmt = conn.prepare(
+                "SELECT embedding_type, model_name, vector_blob
+                 FROM embeddings
+                 WHERE segment_id = ?1"
+            )?;
+            
+            let emb_rows = emb_stmt.query_map(params![segment_id], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, Vec<u8>>(2)?,
+                ))
+            })?;
+            
+            let mut embeddings = Vec::new();
+            for row in emb_rows {
+                embeddings.push(row?);
+            }
+            
+            Ok(Some((segment, embeddings)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Update asset analysis state timestamp
+    pub fn update_asset_analysis_state(
+        &self,
+        asset_id: i64,
+        field: &str,
+        timestamp: Option<&str>,
+    ) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        let timestamp_str = timestamp.map(|s| s.to_string()).unwrap_or_else(|| {
+            Utc::now().to_rfc3339()
+        });
+        
+        match field {
+            "segments_built_at" => {
+                conn.execute(
+                    "UPDATE media_assets SET segments_built_at = ?1 WHERE id = ?2",
+                    params![timestamp_str, asset_id],
+                )?;
+            }
+            "transcript_ready_at" => {
+                conn.execute(
+                    "UPDATE media_assets SET transcript_ready_at = ?1 WHERE id = ?2",
+                    params![timestamp_str, asset_id],
+                )?;
+            }
+            "vision_ready_at" => {
+                conn.execute(
+                    "UPDATE media_assets SET vision_ready_at = ?1 WHERE id = ?2",
+                    params![timestamp_str, asset_id],
+                )?;
+            }
+            "metadata_ready_at" => {
+                conn.execute(
+                    "UPDATE media_assets SET metadata_ready_at = ?1 WHERE id = ?2",
+                    params![timestamp_str, asset_id],
+                )?;
+            }
+            "embeddings_ready_at" => {
+                conn.execute(
+                    "UPDATE media_assets SET embeddings_ready_at = ?1 WHERE id = ?2",
+                    params![timestamp_str, asset_id],
+                )?;
+            }
+            _ => return Err(anyhow::anyhow!("Unknown analysis state field: {}", field)),
+        }
+        Ok(())
+    }
+
+    /// Check if asset prerequisites are ready for job gating
+    pub fn check_asset_prerequisites(
+        &self,
+        asset_id: i64,
+        required_states: &[&str],
+    ) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        
+        for state in required_states {
+            let column = match *state {
+                "segments_built" => "segments_built_at",
+                "transcript_ready" => "transcript_ready_at",
+                "vision_ready" => "vision_ready_at",
+                "metadata_ready" => "metadata_ready_at",
+                "embeddings_ready" => "embeddings_ready_at",
+                _ => return Err(anyhow::anyhow!("Unknown state: {}", state)),
+            };
+            
+            let is_ready: bool = conn.query_row(
+                &format!("SELECT {} IS NOT NULL FROM media_assets WHERE id = ?1", column),
+                params![asset_id],
+                |row| row.get(0),
+            )?;
+            
+            if !is_ready {
+                return Ok(false);
+            }
+        }
+        
+        Ok(true)
+    }
+
+    pub fn get_media_asset(&self, asset_id: i64) -> Result<Option<MediaAssetInfo>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, path, duration_ticks, fps_num, fps_den, width, height
+             FROM media_assets
+             WHERE id = ?1"
+        )?;
+        
+        let mut rows = stmt.query_map(params![asset_id], |row| {
+            Ok(MediaAssetInfo {
+                id: row.get(0)?,
+                path: row.get(1)?,
+                duration_ticks: row.get(2)?,
+                fps_num: row.get(3)?,
+                fps_den: row.get(4)?,
+                width: row.get(5)?,
+                height: row.get(6)?,
+            })
+        })?;
+        
+        match rows.next() {
+            Some(Ok(asset)) => Ok(Some(asset)),
+            Some(Err(e)) => Err(e.into()),
+            None => Ok(None),
+        }
+    }
+
+    pub fn get_media_assets_for_project(&self, project_id: i64) -> Result<Vec<MediaAssetInfo>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, path, duration_ticks, fps_num, fps_den, width, height
+             FROM media_assets
+             WHERE project_id = ?1 AND project_id IS NOT NULL AND (is_reference IS NULL OR is_reference = 0)
+             ORDER BY id DESC"
+        )?;
+        
+        let rows = stmt.query_map(params![project_id], |row| {
+            Ok(MediaAssetInfo {
+                id: row.get(0)?,
+                path: row.get(1)?,
+                duration_ticks: row.get(2)?,
+                fps_num: row.get(3)?,
+                fps_den: row.get(4)?,
+                width: row.get(5)?,
+                height: row.get(6)?,
+            })
+        })?;
+        
+        let mut assets = Vec::new();
+        for row in rows {
+            assets.push(row?);
+        }
+        Ok(assets)
+    }
+
+    pub fn get_reference_assets_for_project(&self, project_id: i64) -> Result<Vec<MediaAssetInfo>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, path, duration_ticks, fps_num, fps_den, width, height
+             FROM media_assets
+             WHERE project_id = ?1 AND project_id IS NOT NULL AND is_reference = 1
+             ORDER BY id DESC"
+        )?;
+        
+        let rows = stmt.query_map(params![project_id], |row| {
+            Ok(MediaAssetInfo {
+                id: row.get(0)?,
+                path: row.get(1)?,
+                duration_ticks: row.get(2)?,
+                fps_num: row.get(3)?,
+                fps_den: row.get(4)?,
+                width: row.get(5)?,
+                height: row.get(6)?,
+            })
+        })?;
+        
+        let mut assets = Vec::new();
+        for row in rows {
+            assets.push(row?);
+        }
+        Ok(assets)
+    }
+
+    pub fn delete_media_asset(&self, project_id: i64, asset_id: i64) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        // Verify the asset belongs to the project before deleting
+        let asset_exists: Result<i64, rusqlite::Error> = conn.query_row(
+            "SELECT id FROM media_assets WHERE id = ?1 AND project_id = ?2",
+            params![asset_id, project_id],
+            |row| row.get::<_, i64>(0),
+        );
+        
+        match asset_exists {
+            Ok(_) => {
+                // Delete the media asset (cascade will handle related records if foreign keys are set up)
+                conn.execute(
+                    "DELETE FROM media_assets WHERE id = ?1 AND project_id = ?2",
+                    params![asset_id, project_id],
+                )?;
+                Ok(())
+            }
+            Err(rusqlite::Error::QueryReturnedNoRows) => {
+                // Asset doesn't exist or doesn't belong to this project
+                Err(anyhow::anyhow!("Media asset not found or doesn't belong to this project"))
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Store timeline for a project (backward compatible - defaults to overwrite)
+    pub fn store_timeline(&self, project_id: i64, timeline_json: &str) -> Result<()> {
+        self.store_timeline_version(project_id, timeline_json, None, false)
+    }
+
+    /// Store timeline version (new version or overwrite)
+    pub fn store_timeline_version(
+        &self,
+        project_id: i64,
+        timeline_json: &str,
+        parent_version_id: Option<&str>,
+        is_new_version: bool,
+    ) -> Result<()> {
+        let now = Utc::now().to_rfc3339();
+        let conn = self.conn.lock().unwrap();
+        
+        if is_new_version {
+            // Create new version
+            let version_id = Uuid::new_v4().to_string();
+            
+            // Set previous current version to not current
+            conn.execute(
+                "UPDATE timeline_versions SET is_current = 0 WHERE project_id = ?1 AND is_current = 1",
+                params![project_id],
+            )?;
+            
+            // Insert new version as current
+            conn.execute(
+                "INSERT INTO timeline_versions (project_id, version_id, parent_version_id, is_current, json_blob, created_at) VALUES (?1, ?2, ?3, 1, ?4, ?5)",
+                params![project_id, version_id, parent_version_id, timeline_json, now],
+            )?;
+        } else {
+            // Update existing current version (overwrite)
+            let existing = conn.query_row(
+                "SELECT id FROM timeline_versions WHERE project_id = ?1 AND is_current = 1",
+                params![project_id],
+                |row| row.get::<_, i64>(0),
+            );
+            
+            match existing {
+                Ok(_id) => {
+                    // Update existing current version
+                    conn.execute(
+                        "UPDATE timeline_versions SET json_blob = ?1 WHERE project_id = ?2 AND is_current = 1",
+                        params![timeline_json, project_id],
+                    )?;
+                }
+                Err(rusqlite::Error::QueryReturnedNoRows) => {
+                    // No current version exists, create one
+                    let version_id = Uuid::new_v4().to_string();
+                    conn.execute(
+                        "INSERT INTO timeline_versions (project_id, version_id, parent_version_id, is_current, json_blob, created_at) VALUES (?1, ?2, ?3, 1, ?4, ?5)",
+                        params![project_id, version_id, parent_version_id, timeline_json, now],
+                    )?;
+                }
+                Err(e) => return Err(e.into()),
+            }
+            
+            // Also update timeline_projects for backward compatibility
+            let existing_legacy = conn.query_row(
+                "SELECT id FROM timeline_projects WHERE project_id = ?1",
+                params![project_id],
+                |row| row.get::<_, i64>(0),
+            );
+            
+            match existing_legacy {
+                Ok(_id) => {
+                    conn.execute(
+                        "UPDATE timeline_projects SET json_blob = ?1, updated_at = ?2 WHERE project_id = ?3",
+                        params![timeline_json, now, project_id],
+                    )?;
+                }
+                Err(rusqlite::Error::QueryReturnedNoRows) => {
+                    conn.execute(
+                        "INSERT INTO timeline_projects (project_id, json_blob, created_at, updated_at) VALUES (?1, ?2, ?3, ?4)",
+                        params![project_id, timeline_json, now, now],
+                    )?;
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+        
+        Ok(())
+    }
+
+    /// Get timeline for a project (prefers timeline_versions, falls back to timeline_projects for backward compatibility)
+    pub fn get_timeline(&self, project_id: i64) -> Result<Option<String>> {
+        let conn = self.conn.lock().unwrap();
+        
+        // Try timeline_versions first
+        let result: Result<String, rusqlite::Error> = conn.query_row(
+            "SELECT json_blob FROM timeline_versions WHERE project_id = ?1 AND is_current = 1",
+            params![project_id],
+            |row| row.get(0),
+        );
+        
+        match result {
+            Ok(blob) => Ok(Some(blob)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => {
+                // Fallback to timeline_projects for backward compatibility
+                let mut stmt = conn.prepare("SELECT json_blob FROM timeline_projects WHERE project_id = ?1")?;
+                let mut rows = stmt.query_map(params![project_id], |row| {
+                    Ok(row.get::<_, String>(0)?)
+                })?;
+                
+                match rows.next() {
+                    Some(Ok(blob)) => Ok(Some(blob)),
+                    Some(Err(e)) => Err(e.into()),
+                    None => Ok(None),
+                }
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Get proxy path for a media asset (highest resolution tier available)
+    pub fn get_proxy_path(&self, media_asset_id: i64) -> Result<Option<String>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT path FROM proxies WHERE media_asset_id = ?1 ORDER BY height DESC LIMIT 1")?;
+        let mut rows = stmt.query_map(params![media_asset_id], |row| {
+            Ok(row.get::<_, String>(0)?)
+        })?;
+        
+        match rows.next() {
+            Some(Ok(path)) => Ok(Some(path)),
+            Some(Err(e)) => Err(e.into()),
+            None => Ok(None),
+        }
+    }
+
+    /// List all proxies for a media asset as (tier, path, width, height), lowest resolution first
+    pub fn list_proxies(&self, media_asset_id: i64) -> Result<Vec<(String, String, i32, i32)>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT tier, path, width, height FROM proxies WHERE media_asset_id = ?1 ORDER BY height ASC"
+        )?;
+        let rows = stmt.query_map(params![media_asset_id], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+        })?;
+        let proxies = rows.collect::<Result<Vec<_>, _>>()?;
+        Ok(proxies)
+    }
+
+    /// Insert or replace the proxy record for a tier of a media asset
+    pub fn upsert_proxy(
+        &self,
+        media_asset_id: i64,
+        tier: &str,
+        path: &str,
+        codec: &str,
+        width: i32,
+        height: i32,
+    ) -> Result<i64> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "DELETE FROM proxies WHERE media_asset_id = ?1 AND tier = ?2",
+            params![media_asset_id, tier],
+        )?;
+        conn.execute(
+            "INSERT INTO proxies (media_asset_id, path, codec, width, height, tier) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![media_asset_id, path, codec, width, height, tier],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Remove a proxy record for a tier (e.g. when its file was deleted from the cache)
+    pub fn delete_proxy(&self, media_asset_id: i64, tier: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "DELETE FROM proxies WHERE media_asset_id = ?1 AND tier = ?2",
+            params![media_asset_id, tier],
+        )?;
+        Ok(())
+    }
+
+    /// Get original media asset path by ID
+    pub fn get_media_asset_path(&self, media_asset_id: i64) -> Result<Option<String>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT path FROM media_assets WHERE id = ?1 LIMIT 1")?;
+        let mut rows = stmt.query_map(params![media_asset_id], |row| {
+            Ok(row.get::<_, String>(0)?)
+        })?;
+        
+        match rows.next() {
+            Some(Ok(path)) => Ok(Some(path)),
+            Some(Err(e)) => Err(e.into()),
+            None => Ok(None),
+        }
+    }
+
+    /// Record the remote object a media asset was downloaded from
+    pub fn set_media_asset_remote_url(&self, media_asset_id: i64, remote_url: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE media_assets SET remote_url = ?1 WHERE id = ?2",
+            params![remote_url, media_asset_id],
+        )?;
+        Ok(())
+    }
+
+    /// Remote origin (s3:// or gs://) of a media asset, if it was imported from one
+    pub fn get_media_asset_remote_url(&self, media_asset_id: i64) -> Result<Option<String>> {
+        let conn = self.conn.lock().unwrap();
+        let result = conn.query_row(
+            "SELECT remote_url FROM media_assets WHERE id = ?1",
+            params![media_asset_id],
+            |row| row.get::<_, Option<String>>(0),
+        );
+        match result {
+            Ok(remote_url) => Ok(remote_url),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Point a media asset at a different file (after relinking)
+    pub fn set_media_asset_path(&self, media_asset_id: i64, path: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE media_assets SET path = ?1 WHERE id = ?2",
+            params![path, media_asset_id],
+        )?;
+        Ok(())
+    }
+
+    /// Set thumbnail directory path for a media asset
+    pub fn set_thumbnail_dir(&self, media_asset_id: i64, thumbnail_dir: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE media_assets SET thumbnail_dir = ?1 WHERE id = ?2",
+            params![thumbnail_dir, media_asset_id],
+        )?;
+        Ok(())
+    }
+
+    /// Get thumbnail directory path for a media asset
+    pub fn get_thumbnail_dir(&self, media_asset_id: i64) -> Result<Option<String>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT thumbnail_dir FROM media_assets WHERE id = ?1 LIMIT 1")?;
+        let mut rows = stmt.query_map(params![media_asset_id], |row| {
+            Ok(row.get::<_, Option<String>>(0)?)
+        })?;
+        
+        match rows.next() {
+            Some(Ok(dir)) => Ok(dir),
+            Some(Err(e)) => Err(e.into()),
+            None => Ok(None),
+        }
+    }
+
+    /// Store raw transcript results for an asset
+    /// (encrypted when the asset's project is)
+    pub fn store_asset_transcript(&self, asset_id: i64, transcript_json: &str) -> Result<()> {
+        let transcript_json = self.seal_asset_field(asset_id, transcript_json)?;
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO asset_transcripts (asset_id, transcript_json) VALUES (?1, ?2)",
+            params![asset_id, transcript_json],
+        )?;
+        Ok(())
+    }
+
+    /// Get raw transcript results for an asset (decrypted)
+    pub fn get_asset_transcript(&self, asset_id: i64) -> Result<Option<String>> {
+        let json = {
+            let conn = self.conn.lock().unwrap();
+            let mut stmt = conn.prepare("SELECT transcript_json FROM asset_transcripts WHERE asset_id = ?1")?;
+            let mut rows = stmt.query_map(params![asset_id], |row| {
+                row.get::<_, String>(0)
+            })?;
+
+            match rows.next() {
+                Some(Ok(json)) => json,
+                Some(Err(e)) => return Err(e.into()),
+                None => return Ok(None),
+            }
+        };
+        self.unseal_asset_field(asset_id, json).map(Some)
+    }
+
+    /// Store raw vision analysis results for an asset (encrypted when the asset's project is)
+    pub fn store_asset_vision(&self, asset_id: i64, vision_json: &str) -> Result<()> {
+        let vision_json = self.seal_asset_field(asset_id, vision_json)?;
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO asset_vision (asset_id, vision_json) VALUES (?1, ?2)",
+            params![asset_id, vision_json],
+        )?;
+        Ok(())
+    }
+
+    /// Get raw vision analysis results for an asset (decrypted)
+    pub fn get_asset_vision(&self, asset_id: i64) -> Result<Option<String>> {
+        let json = {
+            let conn = self.conn.lock().unwrap();
+            let mut stmt = conn.prepare("SELECT vision_json FROM asset_vision WHERE asset_id = ?1")?;
+            let mut rows = stmt.query_map(params![asset_id], |row| {
+                row.get::<_, String>(0)
+            })?;
+
+            match rows.next() {
+                Some(Ok(json)) => json,
+                Some(Err(e)) => return Err(e.into()),
+                None => return Ok(None),
+            }
+        };
+        self.unseal_asset_field(asset_id, json).map(Some)
+    }
+
+    /// Store orchestrator message
+    pub fn store_orchestrator_message(
+        &self,
+        project_id: i64,
+        role: &str,
+        content: &str,
+        metadata: Option<&serde_json::Value>,
+    ) -> Result<i64> {
+        let now = Utc::now().to_rfc3339();
+        let metadata_json = metadata.and_then(|m| serde_json::to_string(m).ok());
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO orchestrator_messages (project_id, role, content, metadata_json, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![project_id, role, content, metadata_json, now],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Get orchestrator messages for a project (most recent first)
+    pub fn get_orchestrator_messages(&self, project_id: i64, limit: usize) -> Result<Vec<serde_json::Value>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT role, content, metadata_json, created_at FROM orchestrator_messages WHERE project_id = ?1 ORDER BY created_at DESC LIMIT ?2"
+        )?;
+        let rows = stmt.query_map(params![project_id, limit as i64], |row| {
+            let role: String = row.get(0)?;
+            let content: String = row.get(1)?;
+            let metadata_json: Option<String> = row.get(2)?;
+            let created_at: String = row.get(3)?;
+            
+            let mut msg = serde_json::json!({
+                "role": role,
+                "content": content,
+                "created_at": created_at,
+            });
+            
+            if let Some(meta_str) = metadata_json {
+                if let Ok(meta) = serde_json::from_str::<serde_json::Value>(&meta_str) {
+                    msg["metadata"] = meta;
+                }
+            }
+            
+            Ok(msg)
+        })?;
+        
+        let mut messages = Vec::new();
+        for row in rows {
+            messages.push(row?);
+        }
+        
+        // Reverse to get chronological order (oldest first)
+        messages.reverse();
+        Ok(messages)
+    }
+
+    /// Store orchestrator proposal
+    pub fn store_orchestrator_proposal(
+        &self,
+        project_id: i64,
+        proposal_json: &str,
+    ) -> Result<i64> {
+        let now = Utc::now().to_rfc3339();
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO orchestrator_proposals (project_id, proposal_json, created_at) VALUES (?1, ?2, ?3)",
+            params![project_id, proposal_json, now],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Store a timeline diff in edit_logs
+    pub fn store_edit_log(&self, project_id: i64, diff_json: &str) -> Result<i64> {
+        let now = Utc::now().to_rfc3339();
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO edit_logs (project_id, diff_json, created_at) VALUES (?1, ?2, ?3)",
+            params![project_id, diff_json, now],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Record that the user removed (or swapped out) an agent-placed clip from this segment
+    pub fn record_segment_removed(&self, project_id: i64, segment_id: i64) -> Result<()> {
+        let now = Utc::now().to_rfc3339();
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO segment_feedback (project_id, segment_id, kept, removed_count, updated_at)
+             VALUES (?1, ?2, 0, 1, ?3)
+             ON CONFLICT(project_id, segment_id) DO UPDATE SET
+                kept = 0, removed_count = removed_count + 1, updated_at = ?3",
+            params![project_id, segment_id, now],
+        )?;
+        Ok(())
+    }
+
+    /// Record that the user kept an agent-placed clip from this segment through an edit
+    pub fn record_segment_kept(&self, project_id: i64, segment_id: i64) -> Result<()> {
+        let now = Utc::now().to_rfc3339();
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO segment_feedback (project_id, segment_id, kept, removed_count, updated_at)
+             VALUES (?1, ?2, 1, 0, ?3)
+             ON CONFLICT(project_id, segment_id) DO UPDATE SET kept = 1, updated_at = ?3",
+            params![project_id, segment_id, now],
+        )?;
+        Ok(())
+    }
+
+    /// Feedback for a project as (segment_id, kept, removed_count)
+    pub fn get_segment_feedback(&self, project_id: i64) -> Result<Vec<(i64, bool, i64)>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT segment_id, kept, removed_count FROM segment_feedback WHERE project_id = ?1"
+        )?;
+        let rows = stmt.query_map(params![project_id], |row| {
+            Ok((row.get(0)?, row.get::<_, i64>(1)? != 0, row.get(2)?))
+        })?;
+        Ok(rows.collect::<Result<Vec<_>, _>>()?)
+    }
+
+    /// Record one LLM call in the audit log
+    pub fn store_llm_call(&self, call: &LlmCallRecord) -> Result<i64> {
+        let now = Utc::now().to_rfc3339();
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO llm_calls (project_id, endpoint, prompt_summary, model, prompt_tokens, completion_tokens,
+                                    latency_ms, estimated_cost_usd, status, error, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            params![
+                call.project_id,
+                call.endpoint,
+                call.prompt_summary,
+                call.model,
+                call.prompt_tokens,
+                call.completion_tokens,
+                call.latency_ms,
+                call.estimated_cost_usd,
+                call.status,
+                call.error,
+                now,
+            ],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Most recent LLM calls for a project, newest first
+    pub fn get_llm_calls(&self, project_id: i64, limit: i64) -> Result<Vec<serde_json::Value>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, endpoint, prompt_summary, model, prompt_tokens, completion_tokens, latency_ms,
+                    estimated_cost_usd, status, error, created_at
+             FROM llm_calls WHERE project_id = ?1 ORDER BY id DESC LIMIT ?2"
+        )?;
+        let rows = stmt.query_map(params![project_id, limit], |row| {
+            Ok(serde_json::json!({
+                "id": row.get::<_, i64>(0)?,
+                "endpoint": row.get::<_, String>(1)?,
+                "prompt_summary": row.get::<_, String>(2)?,
+                "model": row.get::<_, Option<String>>(3)?,
+                "prompt_tokens": row.get::<_, Option<i64>>(4)?,
+                "completion_tokens": row.get::<_, Option<i64>>(5)?,
+                "latency_ms": row.get::<_, i64>(6)?,
+                "estimated_cost_usd": row.get::<_, f64>(7)?,
+                "status": row.get::<_, String>(8)?,
+                "error": row.get::<_, Option<String>>(9)?,
+                "created_at": row.get::<_, String>(10)?,
+            }))
+        })?;
+        Ok(rows.collect::<Result<Vec<_>, _>>()?)
+    }
+
+    /// Per-endpoint usage totals for a project
+    pub fn get_llm_usage_by_endpoint(&self, project_id: i64) -> Result<Vec<LlmUsageRow>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT endpoint, COUNT(*), SUM(CASE WHEN status = 'ok' THEN 0 ELSE 1 END),
+                    COALESCE(SUM(prompt_tokens), 0), COALESCE(SUM(completion_tokens), 0),
+                    SUM(latency_ms), SUM(estimated_cost_usd)
+             FROM llm_calls WHERE project_id = ?1 GROUP BY endpoint ORDER BY endpoint"
+        )?;
+        let rows = stmt.query_map(params![project_id], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?, row.get(5)?, row.get(6)?))
+        })?;
+        Ok(rows.collect::<Result<Vec<_>, _>>()?)
+    }
+
+    /// Get a stored proposal as (project_id, proposal_json, created_at)
+    pub fn get_orchestrator_proposal(&self, proposal_id: i64) -> Result<Option<(i64, String, String)>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT project_id, proposal_json, created_at FROM orchestrator_proposals WHERE id = ?1"
+        )?;
+        let mut rows = stmt.query_map(params![proposal_id], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+        })?;
+        match rows.next() {
+            Some(row) => Ok(Some(row?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Replace a stored proposal's JSON
+    pub fn update_orchestrator_proposal(&self, proposal_id: i64, proposal_json: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE orchestrator_proposals SET proposal_json = ?1 WHERE id = ?2",
+            params![proposal_json, proposal_id],
+        )?;
+        Ok(())
+    }
+
+    /// Store orchestrator applied plan
+    pub fn store_orchestrator_apply(
+        &self,
+        project_id: i64,
+        edit_plan_json: &str,
+    ) -> Result<i64> {
+        let now = Utc::now().to_rfc3339();
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO orchestrator_applies (project_id, edit_plan_json, created_at) VALUES (?1, ?2, ?3)",
+            params![project_id, edit_plan_json, now],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+    
+    /// Get the most recent edit plan for a project (from proposals or applies)
+    pub fn get_latest_edit_plan(&self, project_id: i64) -> Result<Option<serde_json::Value>> {
+        let conn = self.conn.lock().unwrap();
+        
+        // First try to get from applies (most recent)
+        let mut stmt = conn.prepare(
+            "SELECT edit_plan_json FROM orchestrator_applies WHERE project_id = ?1 ORDER BY created_at DESC LIMIT 1"
+        )?;
+        
+        let mut rows = stmt.query_map(params![project_id], |row| {
+            let plan_json: String = row.get(0)?;
+            Ok(plan_json)
+        })?;
+        
+        if let Some(row) = rows.next() {
+            let plan_json = row?;
+            if let Ok(mut plan) = serde_json::from_str::<serde_json::Value>(&plan_json) {
+                // Enrich plan with segment semantic data
+                if let Some(primary_segments) = plan.get_mut("primary_segments").and_then(|v| v.as_array_mut()) {
+                    for segment_op in primary_segments {
+                        if let Some(segment_id) = segment_op.get("segment_id").and_then(|v| v.as_i64()) {
+                            // Get segment using get_segment_with_embeddings (we only need the segment part)
+                            if let Ok(Some((segment, _))) = self.get_segment_with_embeddings(segment_id) {
+                                // Add semantic description to the segment operation
+                                let description = segment.summary_text
+                                    .or_else(|| segment.transcript.clone())
+                                    .unwrap_or_else(|| {
+                                        // Fallback to scene tags or keywords
+                                        if let Some(scene_json) = &segment.scene_json {
+                                            if let Ok(scene) = serde_json::from_str::<serde_json::Value>(scene_json) {
+                                                if let Some(tags) = scene.get("tags").and_then(|t| t.as_array()) {
+                                                    let tag_str: Vec<String> = tags.iter()
+                                                        .filter_map(|t| t.as_str().map(|s| s.to_string()))
+                                                        .collect();
+                                                    if !tag_str.is_empty() {
+                                                        return tag_str.join(", ");
+                                                    }
+                                                }
+                                            }
+                                        }
+                                        "video segment".to_string()
+                                    });
+                                
+                                segment_op["description"] = serde_json::json!(description);
+                                segment_op["duration_sec"] = serde_json::json!(
+                                    (segment.end_ticks - segment.start_ticks) as f64 / 48000.0
+                                );
+                            }
+                        }
+                    }
+                }
+                return Ok(Some(plan));
+            }
+        }
+        
+        // If no applied plan, check proposals (they might have plan data)
+        // For now, return None if no applied plan exists
+        // TODO: Store plan when generated, not just when applied
+        Ok(None)
+    }
+
+    /// Create orchestrator goal
+    pub fn create_orchestrator_goal(
+        &self,
+        project_id: i64,
+        user_intent: &str,
+        status: &str,
+    ) -> Result<i64> {
+        let now = Utc::now().to_rfc3339();
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO orchestrator_goals (project_id, user_intent, status, created_at, updated_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![project_id, user_intent, status, now, now],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Update orchestrator goal status
+    pub fn update_orchestrator_goal_status(
+        &self,
+        goal_id: i64,
+        status: &str,
+    ) -> Result<()> {
+        let now = Utc::now().to_rfc3339();
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE orchestrator_goals SET status = ?1, updated_at = ?2 WHERE id = ?3",
+            params![status, now, goal_id],
+        )?;
+        Ok(())
+    }
+
+    /// Get active goals for a project
+    pub fn get_active_orchestrator_goals(&self, project_id: i64) -> Result<Vec<(i64, String, String)>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, user_intent, status FROM orchestrator_goals WHERE project_id = ?1 AND status NOT IN ('completed', 'cancelled') ORDER BY created_at DESC"
+        )?;
+        let rows = stmt.query_map(params![project_id], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+        })?;
+        let mut goals = Vec::new();
+        for row in rows {
+            goals.push(row?);
+        }
+        Ok(goals)
+    }
+
+    /// Get goal by status
+    pub fn get_orchestrator_goal_by_status(
+        &self,
+        project_id: i64,
+        status: &str,
+    ) -> Result<Option<(i64, String)>> {
+        let conn = self.conn.lock().unwrap();
+        let result = conn.query_row(
+            "SELECT id, user_intent FROM orchestrator_goals WHERE project_id = ?1 AND status = ?2 ORDER BY created_at DESC LIMIT 1",
+            params![project_id, status],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        );
+        match result {
+            Ok(goal) => Ok(Some(goal)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+impl Database {
+    /// Get the project that owns a media asset
+    pub fn get_asset_project_id(&self, asset_id: i64) -> Result<Option<i64>> {
+        let conn = self.conn.lock().unwrap();
+        let result = conn.query_row(
+            "SELECT project_id FROM media_assets WHERE id = ?1",
+            params![asset_id],
+            |row| row.get(0),
+        );
+        match result {
+            Ok(project_id) => Ok(Some(project_id)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Get the vision sampling config JSON for a project (None = defaults)
+    pub fn get_project_vision_config(&self, project_id: i64) -> Result<Option<String>> {
+        let conn = self.conn.lock().unwrap();
+        let result = conn.query_row(
+            "SELECT vision_config_json FROM projects WHERE id = ?1",
+            params![project_id],
+            |row| row.get::<_, Option<String>>(0),
+        );
+        match result {
+            Ok(config) => Ok(config),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Set (or clear with None) the vision sampling config JSON for a project
+    pub fn set_project_vision_config(&self, project_id: i64, config_json: Option<&str>) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE projects SET vision_config_json = ?1 WHERE id = ?2",
+            params![config_json, project_id],
+        )?;
+        Ok(())
+    }
+
+    /// Get the agent persona JSON for a project (None = defaults)
+    pub fn get_project_agent_persona(&self, project_id: i64) -> Result<Option<String>> {
+        let conn = self.conn.lock().unwrap();
+        let result = conn.query_row(
+            "SELECT agent_persona_json FROM projects WHERE id = ?1",
+            params![project_id],
+            |row| row.get::<_, Option<String>>(0),
+        );
+        match result {
+            Ok(persona) => Ok(persona),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Set (or clear with None) the agent persona JSON for a project
+    pub fn set_project_agent_persona(&self, project_id: i64, persona_json: Option<&str>) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE projects SET agent_persona_json = ?1 WHERE id = ?2",
+            params![persona_json, project_id],
+        )?;
+        Ok(())
+    }
+}
+
+impl Database {
+    /// Insert built-in style presets that aren't in the DB yet (existing rows are left alone)
+    fn seed_builtin_style_presets(&self) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        let now = Utc::now().to_rfc3339();
+        for preset in crate::style::presets::builtin_presets() {
+            let exists = conn
+                .query_row(
+                    "SELECT 1 FROM style_profiles WHERE preset_key = ?1",
+                    params![preset.key],
+                    |_| Ok(()),
+                )
+                .is_ok();
+            if !exists {
+                conn.execute(
+                    "INSERT INTO style_profiles (name, json_blob, created_at, preset_key) VALUES (?1, ?2, ?3, ?4)",
+                    params![preset.name, preset.profile.to_string(), now, preset.key],
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Insert built-in project templates that aren't in the DB yet (existing rows are left alone)
+    fn seed_builtin_project_templates(&self) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        let now = Utc::now().to_rfc3339();
+        for builtin in crate::templates::builtin::builtin_templates() {
+            let exists = conn
+                .query_row(
+                    "SELECT 1 FROM project_templates WHERE builtin_key = ?1",
+                    params![builtin.key],
+                    |_| Ok(()),
+                )
+                .is_ok();
+            if !exists {
+                conn.execute(
+                    "INSERT INTO project_templates (name, template_json, builtin_key, created_at) VALUES (?1, ?2, ?3, ?4)",
+                    params![builtin.template.name, serde_json::to_string(&builtin.template)?, builtin.key, now],
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Insert built-in end cards that aren't in the DB yet (existing rows are left alone)
+    fn seed_builtin_end_cards(&self) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        let now = Utc::now().to_rfc3339();
+        for builtin in crate::end_cards::builtin_end_cards() {
+            let exists = conn
+                .query_row(
+                    "SELECT 1 FROM end_cards WHERE builtin_key = ?1",
+                    params![builtin.key],
+                    |_| Ok(()),
+                )
+                .is_ok();
+            if !exists {
+                conn.execute(
+                    "INSERT INTO end_cards (name, card_json, builtin_key, created_at) VALUES (?1, ?2, ?3, ?4)",
+                    params![builtin.card.name, serde_json::to_string(&builtin.card)?, builtin.key, now],
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Register built-in embedding models that aren't in the DB yet (existing rows are left alone)
+    fn seed_embedding_models(&self) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        let now = Utc::now().to_rfc3339();
+        for model in crate::embeddings::registry::builtin_models() {
+            conn.execute(
+                "INSERT OR IGNORE INTO embedding_models (embedding_type, model_name, model_version, dimension, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![model.embedding_type, model.model_name, model.model_version, model.dimension as i64, now],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Registered (model_version, dimension) for an embedding model
+    pub fn get_embedding_model(&self, embedding_type: &str, model_name: &str) -> Result<Option<(String, usize)>> {
+        let conn = self.conn.lock().unwrap();
+        match conn.query_row(
+            "SELECT model_version, dimension FROM embedding_models WHERE embedding_type = ?1 AND model_name = ?2",
+            params![embedding_type, model_name],
+            |row| Ok((row.get(0)?, row.get::<_, i64>(1)? as usize)),
+        ) {
+            Ok(model) => Ok(Some(model)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Registered dimension for an embedding model, or an error naming the unknown model
+    pub fn embedding_dimension(&self, embedding_type: &str, model_name: &str) -> Result<usize> {
+        self.get_embedding_model(embedding_type, model_name)?
+            .map(|(_, dimension)| dimension)
+            .ok_or_else(|| {
+                EmbeddingError::UnknownModel {
+                    embedding_type: embedding_type.to_string(),
+                    model_name: model_name.to_string(),
+                }
+                .into()
+            })
+    }
+
+    /// Store a segment embedding after checking it against the registered model's dimension.
+    /// Returns false (and stores nothing) if the segment already has an embedding of this model.
+    pub fn store_embedding(&self, segment_id: i64, embedding_type: &str, model_name: &str, vector: &[f32]) -> Result<bool> {
+        let (model_version, dimension) = self.get_embedding_model(embedding_type, model_name)?.ok_or_else(|| {
+            EmbeddingError::UnknownModel {
+                embedding_type: embedding_type.to_string(),
+                model_name: model_name.to_string(),
+            }
+        })?;
+        if vector.len() != dimension {
+            return Err(EmbeddingError::DimensionMismatch {
+                embedding_type: embedding_type.to_string(),
+                model_name: model_name.to_string(),
+                expected: dimension,
+                actual: vector.len(),
+            }
+            .into());
+        }
+
+        let vector_blob: Vec<u8> = vector.iter().flat_map(|f| f.to_le_bytes()).collect();
+        let conn = self.conn.lock().unwrap();
+        let inserted = conn.execute(
+            "INSERT OR IGNORE INTO embeddings (segment_id, embedding_type, model_name, model_version, vector_blob)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![segment_id, embedding_type, model_name, model_version, vector_blob],
+        )?;
+        Ok(inserted > 0)
+    }
+
+    /// Built-in style preset by key: (profile_id, json_blob)
+    pub fn get_style_preset(&self, preset_key: &str) -> Result<Option<(i64, String)>> {
+        let conn = self.conn.lock().unwrap();
+        match conn.query_row(
+            "SELECT id, json_blob FROM style_profiles WHERE preset_key = ?1",
+            params![preset_key],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        ) {
+            Ok(preset) => Ok(Some(preset)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// All built-in style presets: (profile_id, preset_key, name)
+    pub fn list_style_presets(&self) -> Result<Vec<(i64, String, String)>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, preset_key, name FROM style_profiles WHERE preset_key IS NOT NULL ORDER BY id"
+        )?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+
+        let mut presets = Vec::new();
+        for row in rows {
+            presets.push(row?);
+        }
+        Ok(presets)
+    }
+
+    /// Point a project at a style profile (None clears it)
+    pub fn set_project_style_profile(&self, project_id: i64, style_profile_id: Option<i64>) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE projects SET style_profile_id = ?1 WHERE id = ?2",
+            params![style_profile_id, project_id],
+        )?;
+        Ok(())
+    }
+
+    /// Set a segment's wall-clock capture time (RFC 3339)
+    pub fn update_segment_capture_time(&self, segment_id: i64, capture_time: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE segments SET capture_time = ?1 WHERE id = ?2",
+            params![capture_time, segment_id],
+        )?;
+        Ok(())
+    }
+}
+
+/// Stored OAuth client and tokens for an upload provider
+#[derive(Debug, Clone)]
+pub struct OAuthCredentials {
+    pub provider: String,
+    pub client_id: String,
+    pub client_secret: String,
+    pub refresh_token: String,
+    pub access_token: Option<String>,
+    /// RFC 3339 expiry of `access_token`
+    pub expires_at: Option<String>,
+}
+
+/// An upload of an export to an external platform
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Publication {
+    pub id: i64,
+    pub project_id: i64,
+    pub export_job_id: i64,
+    pub provider: String,
+    /// "pending" | "uploading" | "completed" | "failed"
+    pub status: String,
+    pub file_path: String,
+    pub metadata: serde_json::Value,
+    #[serde(skip_serializing)]
+    pub upload_url: Option<String>,
+    pub bytes_sent: i64,
+    pub total_bytes: i64,
+    pub remote_id: Option<String>,
+    pub remote_url: Option<String>,
+    pub error: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl Database {
+    /// Store (or replace) the OAuth client and refresh token for a provider
+    pub fn upsert_oauth_credentials(
+        &self,
+        provider: &str,
+        client_id: &str,
+        client_secret: &str,
+        refresh_token: &str,
+    ) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO oauth_credentials (provider, client_id, client_secret, refresh_token, access_token, expires_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, NULL, NULL, ?5)
+             ON CONFLICT(provider) DO UPDATE SET
+                client_id = excluded.client_id, client_secret = excluded.client_secret,
+                refresh_token = excluded.refresh_token, access_token = NULL, expires_at = NULL,
+                updated_at = excluded.updated_at",
+            params![provider, client_id, client_secret, refresh_token, Utc::now().to_rfc3339()],
+        )?;
+        Ok(())
+    }
+
+    pub fn get_oauth_credentials(&self, provider: &str) -> Result<Option<OAuthCredentials>> {
+        let conn = self.conn.lock().unwrap();
+        let result = conn.query_row(
+            "SELECT provider, client_id, client_secret, refresh_token, access_token, expires_at
+             FROM oauth_credentials WHERE provider = ?1",
+            params![provider],
+            |row| {
+                Ok(OAuthCredentials {
+                    provider: row.get(0)?,
+                    client_id: row.get(1)?,
+                    client_secret: row.get(2)?,
+                    refresh_token: row.get(3)?,
+                    access_token: row.get(4)?,
+                    expires_at: row.get(5)?,
+                })
+            },
+        );
+        match result {
+            Ok(credentials) => Ok(Some(credentials)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Cache a refreshed access token
+    pub fn update_oauth_access_token(&self, provider: &str, access_token: &str, expires_at: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE oauth_credentials SET access_token = ?1, expires_at = ?2, updated_at = ?3 WHERE provider = ?4",
+            params![access_token, expires_at, Utc::now().to_rfc3339(), provider],
+        )?;
+        Ok(())
+    }
+
+    pub fn create_publication(
+        &self,
+        project_id: i64,
+        export_job_id: i64,
+        provider: &str,
+        file_path: &str,
+        metadata: &serde_json::Value,
+        total_bytes: i64,
+    ) -> Result<i64> {
+        let conn = self.conn.lock().unwrap();
+        let now = Utc::now().to_rfc3339();
+        conn.execute(
+            "INSERT INTO publications (project_id, export_job_id, provider, status, file_path, metadata_json, total_bytes, created_at, updated_at)
+             VALUES (?1, ?2, ?3, 'pending', ?4, ?5, ?6, ?7, ?7)",
+            params![project_id, export_job_id, provider, file_path, serde_json::to_string(metadata)?, total_bytes, now],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    pub fn get_publication(&self, publication_id: i64) -> Result<Option<Publication>> {
+        let conn = self.conn.lock().unwrap();
+        let result = conn.query_row(
+            "SELECT id, project_id, export_job_id, provider, status, file_path, metadata_json, upload_url,
+                    bytes_sent, total_bytes, remote_id, remote_url, error, created_at, updated_at
+             FROM publications WHERE id = ?1",
+            params![publication_id],
+            |row| {
+                let metadata_json: String = row.get(6)?;
+                Ok(Publication {
+                    id: row.get(0)?,
+                    project_id: row.get(1)?,
+                    export_job_id: row.get(2)?,
+                    provider: row.get(3)?,
+                    status: row.get(4)?,
+                    file_path: row.get(5)?,
+                    metadata: serde_json::from_str(&metadata_json).unwrap_or(serde_json::Value::Null),
+                    upload_url: row.get(7)?,
+                    bytes_sent: row.get(8)?,
+                    total_bytes: row.get(9)?,
+                    remote_id: row.get(10)?,
+                    remote_url: row.get(11)?,
+                    error: row.get(12)?,
+                    created_at: row.get(13)?,
+                    updated_at: row.get(14)?,
+                })
+            },
+        );
+        match result {
+            Ok(publication) => Ok(Some(publication)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Record upload progress (and the resumable session URL once there is one)
+    pub fn update_publication_progress(
+        &self,
+        publication_id: i64,
+        status: &str,
+        bytes_sent: i64,
+        upload_url: Option<&str>,
+    ) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE publications SET status = ?1, bytes_sent = ?2, upload_url = COALESCE(?3, upload_url),
+                error = NULL, updated_at = ?4 WHERE id = ?5",
+            params![status, bytes_sent, upload_url, Utc::now().to_rfc3339(), publication_id],
+        )?;
+        Ok(())
+    }
+
+    pub fn complete_publication(&self, publication_id: i64, remote_id: &str, remote_url: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE publications SET status = 'completed', bytes_sent = total_bytes, remote_id = ?1, remote_url = ?2,
+                error = NULL, updated_at = ?3 WHERE id = ?4",
+            params![remote_id, remote_url, Utc::now().to_rfc3339(), publication_id],
+        )?;
+        Ok(())
+    }
+
+    /// Mark a publication failed; the upload session is kept so a retry can resume it
+    pub fn fail_publication(&self, publication_id: i64, error: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE publications SET status = 'failed', error = ?1, updated_at = ?2 WHERE id = ?3",
+            params![error, Utc::now().to_rfc3339(), publication_id],
+        )?;
+        Ok(())
+    }
+
+    /// Remember that a local file was copied to the storage backend under `storage_key`
+    pub fn record_stored_object(&self, local_path: &str, storage_key: &str, size_bytes: i64) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO stored_objects (local_path, storage_key, size_bytes, uploaded_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![local_path, storage_key, size_bytes, Utc::now().to_rfc3339()],
+        )?;
+        Ok(())
+    }
+
+    /// Storage key of a local file's stored copy, if it has one
+    pub fn get_stored_object_key(&self, local_path: &str) -> Result<Option<String>> {
+        let conn = self.conn.lock().unwrap();
+        match conn.query_row(
+            "SELECT storage_key FROM stored_objects WHERE local_path = ?1",
+            params![local_path],
+            |row| row.get(0),
+        ) {
+            Ok(key) => Ok(Some(key)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn delete_stored_object(&self, local_path: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("DELETE FROM stored_objects WHERE local_path = ?1", params![local_path])?;
+        Ok(())
+    }
+}
+
+/// A project webhook: JSON event payloads are POSTed to `url`, signed with `secret`
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Webhook {
+    pub id: i64,
+    pub project_id: i64,
+    pub url: String,
+    #[serde(skip_serializing)]
+    pub secret: String,
+    pub events: Vec<String>,
+    /// HTTP status of the last delivery (None if it never got a response)
+    pub last_status: Option<i64>,
+    pub last_error: Option<String>,
+    pub last_delivery_at: Option<String>,
+    pub created_at: String,
+}
+
+impl Database {
+    pub fn create_webhook(&self, project_id: i64, url: &str, secret: &str, events: &[String]) -> Result<i64> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO webhooks (project_id, url, secret, events_json, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![project_id, url, secret, serde_json::to_string(events)?, Utc::now().to_rfc3339()],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    pub fn get_webhook(&self, webhook_id: i64) -> Result<Option<Webhook>> {
+        let conn = self.conn.lock().unwrap();
+        let result = conn.query_row(
+            "SELECT id, project_id, url, secret, events_json, last_status, last_error, last_delivery_at, created_at
+             FROM webhooks WHERE id = ?1",
+            params![webhook_id],
+            webhook_from_row,
+        );
+        match result {
+            Ok(webhook) => Ok(Some(webhook)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Webhooks for a project, oldest first
+    pub fn list_webhooks(&self, project_id: i64) -> Result<Vec<Webhook>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, project_id, url, secret, events_json, last_status, last_error, last_delivery_at, created_at
+             FROM webhooks WHERE project_id = ?1 ORDER BY id",
+        )?;
+        let webhooks = stmt
+            .query_map(params![project_id], webhook_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(webhooks)
+    }
+
+    pub fn delete_webhook(&self, webhook_id: i64) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("DELETE FROM webhooks WHERE id = ?1", params![webhook_id])?;
+        Ok(())
+    }
+
+    /// Record the outcome of the latest delivery attempt
+    pub fn record_webhook_delivery(&self, webhook_id: i64, status: Option<i64>, error: Option<&str>) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE webhooks SET last_status = ?1, last_error = ?2, last_delivery_at = ?3 WHERE id = ?4",
+            params![status, error, Utc::now().to_rfc3339(), webhook_id],
+        )?;
+        Ok(())
+    }
+}
+
+impl Database {
+    /// Append an event to the outbox; returns its id
+    pub fn insert_event(&self, event_type: &str, project_id: Option<i64>, payload_json: &str) -> Result<i64> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO events (event_type, project_id, payload_json, created_at) VALUES (?1, ?2, ?3, ?4)",
+            params![event_type, project_id, payload_json, Utc::now().to_rfc3339()],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Up to `limit` stored events after `after_id` as (id, payload_json), oldest first, only a
+    /// project's when `project_id` is set
+    pub fn get_events_after(&self, after_id: i64, project_id: Option<i64>, limit: usize) -> Result<Vec<(i64, String)>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, payload_json FROM events
+             WHERE id > ?1 AND (?2 IS NULL OR project_id = ?2)
+             ORDER BY id LIMIT ?3",
+        )?;
+        let events = stmt
+            .query_map(params![after_id, project_id, limit as i64], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(events)
+    }
+
+    /// Id of the newest stored event (0 if there are none)
+    pub fn latest_event_id(&self) -> Result<i64> {
+        let conn = self.conn.lock().unwrap();
+        let id = conn.query_row("SELECT COALESCE(MAX(id), 0) FROM events", [], |row| row.get(0))?;
+        Ok(id)
+    }
+
+    /// Last event a consumer has handled, None for a consumer that has never run
+    pub fn get_event_cursor(&self, consumer: &str) -> Result<Option<i64>> {
+        let conn = self.conn.lock().unwrap();
+        let result = conn.query_row(
+            "SELECT last_event_id FROM event_deliveries WHERE consumer = ?1",
+            params![consumer],
+            |row| row.get(0),
+        );
+        match result {
+            Ok(id) => Ok(Some(id)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn set_event_cursor(&self, consumer: &str, event_id: i64) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO event_deliveries (consumer, last_event_id, updated_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT(consumer) DO UPDATE SET last_event_id = excluded.last_event_id, updated_at = excluded.updated_at",
+            params![consumer, event_id, Utc::now().to_rfc3339()],
+        )?;
+        Ok(())
+    }
+
+    /// Delete events stored before `cutoff`, delivered or not; returns how many went
+    pub fn prune_events(&self, cutoff: DateTime<Utc>) -> Result<usize> {
+        let conn = self.conn.lock().unwrap();
+        let removed = conn.execute("DELETE FROM events WHERE created_at < ?1", params![cutoff.to_rfc3339()])?;
+        Ok(removed)
+    }
+}
+
+/// A worker registered for an extension job kind (`<namespace>.<name>`). Jobs are handed to it as
+/// JSON, either on the stdin of `command` (only from JOB_WORKERS_PATH) or POSTed to `url`.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct JobWorker {
+    #[serde(default)]
+    pub kind: String,
+    #[serde(default)]
+    pub command: Option<Vec<String>>,
+    #[serde(default)]
+    pub url: Option<String>,
+    /// Asset states (e.g. "segments_built") a job's asset must reach before the job runs
+    #[serde(default)]
+    pub requires: Vec<String>,
+    /// Attempts before a failing job is marked failed
+    #[serde(default = "default_worker_max_attempts")]
+    pub max_attempts: i64,
+    #[serde(default = "default_worker_timeout_secs")]
+    pub timeout_secs: i64,
+}
+
+fn default_worker_max_attempts() -> i64 {
+    3
+}
+
+fn default_worker_timeout_secs() -> i64 {
+    3600
+}
+
+impl Database {
+    /// Register (or replace) the worker for a job kind
+    pub fn upsert_job_worker(&self, worker: &JobWorker) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO job_workers (kind, command_json, url, requires_json, max_attempts, timeout_secs, registered_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                worker.kind,
+                worker.command.as_ref().map(serde_json::to_string).transpose()?,
+                worker.url,
+                serde_json::to_string(&worker.requires)?,
+                worker.max_attempts,
+                worker.timeout_secs,
+                Utc::now().to_rfc3339(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn get_job_worker(&self, kind: &str) -> Result<Option<JobWorker>> {
+        let conn = self.conn.lock().unwrap();
+        let result = conn.query_row(
+            "SELECT kind, command_json, url, requires_json, max_attempts, timeout_secs FROM job_workers WHERE kind = ?1",
+            params![kind],
+            job_worker_from_row,
+        );
+        match result {
+            Ok(worker) => Ok(Some(worker)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn list_job_workers(&self) -> Result<Vec<JobWorker>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT kind, command_json, url, requires_json, max_attempts, timeout_secs FROM job_workers ORDER BY kind",
+        )?;
+        let workers = stmt
+            .query_map([], job_worker_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(workers)
+    }
+
+    pub fn delete_job_worker(&self, kind: &str) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        Ok(conn.execute("DELETE FROM job_workers WHERE kind = ?1", params![kind])? > 0)
+    }
+}
+
+fn job_worker_from_row(row: &rusqlite::Row) -> rusqlite::Result<JobWorker> {
+    let command_json: Option<String> = row.get(1)?;
+    let requires_json: String = row.get(3)?;
+    Ok(JobWorker {
+        kind: row.get(0)?,
+        command: command_json.and_then(|json| serde_json::from_str(&json).ok()),
+        url: row.get(2)?,
+        requires: serde_json::from_str(&requires_json).unwrap_or_default(),
+        max_attempts: row.get(4)?,
+        timeout_secs: row.get(5)?,
+    })
+}
+
+fn webhook_from_row(row: &rusqlite::Row) -> rusqlite::Result<Webhook> {
+    let events_json: String = row.get(4)?;
+    Ok(Webhook {
+        id: row.get(0)?,
+        project_id: row.get(1)?,
+        url: row.get(2)?,
+        secret: row.get(3)?,
+        events: serde_json::from_str(&events_json).unwrap_or_default(),
+        last_status: row.get(5)?,
+        last_error: row.get(6)?,
+        last_delivery_at: row.get(7)?,
+        created_at: row.get(8)?,
+    })
+}
+
+/// Collect segment ids from any `segment_id` / `*_segment_ids` field in a stored JSON document
+/// (proposals, timelines), at any depth
+fn collect_segment_ids(value: &serde_json::Value, ids: &mut std::collections::HashSet<i64>) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, value) in map {
+                if key == "segment_id" || key.ends_with("segment_ids") {
+                    match value {
+                        serde_json::Value::Array(items) => ids.extend(items.iter().filter_map(|v| v.as_i64())),
+                        other => ids.extend(other.as_i64()),
+                    }
+                }
+                collect_segment_ids(value, ids);
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for item in items {
+                collect_segment_ids(item, ids);
+            }
+        }
+        _ => {}
+    }
+}
+
+impl Database {
+    /// Passphrase salt (hex) and key check of an encrypted project; None when it isn't encrypted
+    pub fn get_project_encryption(&self, project_id: i64) -> Result<Option<(String, String)>> {
+        let conn = self.conn.lock().unwrap();
+        let result = conn.query_row(
+            "SELECT encryption_salt, encryption_check FROM projects WHERE id = ?1",
+            params![project_id],
+            |row| Ok((row.get::<_, Option<String>>(0)?, row.get::<_, Option<String>>(1)?)),
+        );
+        match result {
+            Ok((Some(salt), Some(check))) => Ok(Some((salt, check))),
+            Ok(_) | Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn set_project_encryption(&self, project_id: i64, salt: &str, check: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE projects SET encryption_salt = ?1, encryption_check = ?2 WHERE id = ?3",
+            params![salt, check, project_id],
+        )?;
+        Ok(())
+    }
+
+    /// Keep a project's key in memory so its encrypted data can be read and written
+    pub fn unlock_project(&self, project_id: i64, key: Key) {
+        self.project_keys.lock().unwrap().insert(project_id, key);
+    }
+
+    /// Forget a project's key; its encrypted data is unreadable until it's unlocked again
+    pub fn lock_project(&self, project_id: i64) {
+        self.project_keys.lock().unwrap().remove(&project_id);
+    }
+
+    pub fn is_project_unlocked(&self, project_id: i64) -> bool {
+        self.project_keys.lock().unwrap().contains_key(&project_id)
+    }
+
+    /// Key for a project's data: None when the project isn't encrypted, a ProjectLocked error
+    /// when it is but hasn't been unlocked
+    pub fn project_key(&self, project_id: i64) -> Result<Option<Key>> {
+        if let Some(key) = self.project_keys.lock().unwrap().get(&project_id) {
+            return Ok(Some(*key));
+        }
+        match self.get_project_encryption(project_id)? {
+            Some(_) => Err(ProjectLocked(project_id).into()),
+            None => Ok(None),
+        }
+    }
+
+    /// Key for the project an asset belongs to (see [`Database::project_key`])
+    pub fn asset_key(&self, asset_id: i64) -> Result<Option<Key>> {
+        match self.get_asset_project_id(asset_id)? {
+            Some(project_id) => self.project_key(project_id),
+            None => Ok(None),
+        }
+    }
+
+    /// Encrypt a value about to be stored for an asset if its project is encrypted
+    fn seal_asset_field(&self, asset_id: i64, value: &str) -> Result<String> {
+        match self.asset_key(asset_id)? {
+            Some(key) => crypto::encrypt_field(&key, value),
+            None => Ok(value.to_string()),
+        }
+    }
+
+    /// Decrypt a stored value for an asset if it was encrypted
+    fn unseal_asset_field(&self, asset_id: i64, value: String) -> Result<String> {
+        if !crypto::is_encrypted_field(&value) {
+            return Ok(value);
+        }
+        let key = self
+            .asset_key(asset_id)?
+            .ok_or_else(|| anyhow::anyhow!("Asset {} has encrypted data but its project has no key", asset_id))?;
+        crypto::decrypt_field(&key, &value)
+    }
+
+    /// Encrypt the raw transcript and vision results already stored for a project's assets, and
+    /// its voiceovers' transcripts. Returns how many were encrypted.
+    pub fn encrypt_project_fields(&self, project_id: i64, key: &Key) -> Result<usize> {
+        let conn = self.conn.lock().unwrap();
+        let mut encrypted = 0;
+        for (table, column) in [("asset_transcripts", "transcript_json"), ("asset_vision", "vision_json")] {
+            let rows: Vec<(i64, String)> = {
+                let mut stmt = conn.prepare(&format!(
+                    "SELECT t.asset_id, t.{column} FROM {table} t
+                     JOIN media_assets m ON m.id = t.asset_id WHERE m.project_id = ?1",
+                ))?;
+                let rows = stmt
+                    .query_map(params![project_id], |row| Ok((row.get(0)?, row.get(1)?)))?
+                    .collect::<Result<Vec<_>, _>>()?;
+                rows
+            };
+            for (asset_id, value) in rows.into_iter().filter(|(_, v)| !crypto::is_encrypted_field(v)) {
+                conn.execute(
+                    &format!("UPDATE {table} SET {column} = ?1 WHERE asset_id = ?2"),
+                    params![crypto::encrypt_field(key, &value)?, asset_id],
+                )?;
+                encrypted += 1;
+            }
+        }
+        let voiceovers: Vec<(i64, String)> = {
+            let mut stmt = conn.prepare(
+                "SELECT id, transcript_json FROM voiceovers WHERE project_id = ?1 AND transcript_json IS NOT NULL",
+            )?;
+            let rows = stmt
+                .query_map(params![project_id], |row| Ok((row.get(0)?, row.get(1)?)))?
+                .collect::<Result<Vec<_>, _>>()?;
+            rows
+        };
+        for (voiceover_id, value) in voiceovers.into_iter().filter(|(_, v)| !crypto::is_encrypted_field(v)) {
+            conn.execute(
+                "UPDATE voiceovers SET transcript_json = ?1 WHERE id = ?2",
+                params![crypto::encrypt_field(key, &value)?, voiceover_id],
+            )?;
+            encrypted += 1;
+        }
+        Ok(encrypted)
+    }
+}
+
+/// Where a redaction subject was seen in one sampled frame: source seconds and a box normalized
+/// to the frame (0-1)
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct RedactionBox {
+    pub t: f64,
+    pub x: f64,
+    pub y: f64,
+    pub w: f64,
+    pub h: f64,
+}
+
+/// A face or license plate tracked through a segment. Enabled subjects are blurred on export.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct RedactionSubject {
+    pub id: i64,
+    pub project_id: i64,
+    pub asset_id: i64,
+    pub segment_id: i64,
+    /// "face" or "plate"
+    pub kind: String,
+    pub enabled: bool,
+    pub start_sec: f64,
+    pub end_sec: f64,
+    pub regions: Vec<RedactionBox>,
+    pub created_at: String,
+}
+
+const REDACTION_SUBJECT_COLUMNS: &str =
+    "id, project_id, asset_id, segment_id, kind, enabled, start_sec, end_sec, regions_json, created_at";
+
+impl Database {
+    /// Replace the redaction subjects detected in a segment with a new detection pass, as
+    /// (kind, boxes) pairs. Returns the new subject ids.
+    pub fn replace_redaction_subjects(
+        &self,
+        project_id: i64,
+        asset_id: i64,
+        segment_id: i64,
+        subjects: &[(String, Vec<RedactionBox>)],
+    ) -> Result<Vec<i64>> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM redaction_subjects WHERE segment_id = ?1", params![segment_id])?;
+        let now = Utc::now().to_rfc3339();
+        let mut ids = Vec::new();
+        for (kind, boxes) in subjects.iter().filter(|(_, boxes)| !boxes.is_empty()) {
+            let start_sec = boxes.iter().map(|b| b.t).fold(f64::MAX, f64::min);
+            let end_sec = boxes.iter().map(|b| b.t).fold(f64::MIN, f64::max);
+            tx.execute(
+                "INSERT INTO redaction_subjects
+                 (project_id, asset_id, segment_id, kind, enabled, start_sec, end_sec, regions_json, created_at)
+                 VALUES (?1, ?2, ?3, ?4, 1, ?5, ?6, ?7, ?8)",
+                params![project_id, asset_id, segment_id, kind, start_sec, end_sec, serde_json::to_string(boxes)?, now],
+            )?;
+            ids.push(tx.last_insert_rowid());
+        }
+        tx.commit()?;
+        Ok(ids)
+    }
+
+    pub fn get_redaction_subject(&self, subject_id: i64) -> Result<Option<RedactionSubject>> {
+        let conn = self.conn.lock().unwrap();
+        let result = conn.query_row(
+            &format!("SELECT {} FROM redaction_subjects WHERE id = ?1", REDACTION_SUBJECT_COLUMNS),
+            params![subject_id],
+            redaction_subject_from_row,
+        );
+        match result {
+            Ok(subject) => Ok(Some(subject)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Redaction subjects of a project (optionally one asset), in source order
+    pub fn list_redaction_subjects(&self, project_id: i64, asset_id: Option<i64>) -> Result<Vec<RedactionSubject>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM redaction_subjects
+             WHERE project_id = ?1 AND (?2 IS NULL OR asset_id = ?2)
+             ORDER BY asset_id, start_sec, id",
+            REDACTION_SUBJECT_COLUMNS
+        ))?;
+        let subjects = stmt
+            .query_map(params![project_id, asset_id], redaction_subject_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(subjects)
+    }
+
+    pub fn set_redaction_subject_enabled(&self, subject_id: i64, enabled: bool) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE redaction_subjects SET enabled = ?1 WHERE id = ?2",
+            params![enabled as i64, subject_id],
+        )?;
+        Ok(())
+    }
+}
+
+fn redaction_subject_from_row(row: &rusqlite::Row) -> rusqlite::Result<RedactionSubject> {
+    let regions_json: String = row.get(8)?;
+    Ok(RedactionSubject {
+        id: row.get(0)?,
+        project_id: row.get(1)?,
+        asset_id: row.get(2)?,
+        segment_id: row.get(3)?,
+        kind: row.get(4)?,
+        enabled: row.get::<_, i64>(5)? != 0,
+        start_sec: row.get(6)?,
+        end_sec: row.get(7)?,
+        regions: serde_json::from_str(&regions_json).unwrap_or_default(),
+        created_at: row.get(9)?,
+    })
+}
+
+/// Data derived from a project's media by analysis and generation, with the predicate selecting
+/// the project's rows (bound to ?1). Listed in deletion order: rows referencing segments first.
+/// Original media, timelines, conversations and settings aren't derived and are kept.
+const DERIVED_DATA_TABLES: &[(&str, &str)] = &[
+    ("embeddings", "segment_id IN (SELECT id FROM segments WHERE project_id = ?1)"),
+    ("segment_feedback", "project_id = ?1"),
+    ("segment_text_fts", "project_id = ?1"),
+    ("redaction_subjects", "project_id = ?1"),
+    ("collection_segments", "collection_id IN (SELECT id FROM collections WHERE project_id = ?1)"),
+    ("segments", "project_id = ?1"),
+    ("asset_transcripts", "asset_id IN (SELECT id FROM media_assets WHERE project_id = ?1)"),
+    ("asset_vision", "asset_id IN (SELECT id FROM media_assets WHERE project_id = ?1)"),
+    ("proxies", "media_asset_id IN (SELECT id FROM media_assets WHERE project_id = ?1)"),
+    ("llm_calls", "project_id = ?1"),
+    ("orchestrator_working_sets", "project_id = ?1"),
+    ("multicam_angles", "group_id IN (SELECT id FROM multicam_groups WHERE project_id = ?1)"),
+    ("multicam_groups", "project_id = ?1"),
+];
+
+/// Analysis state kept on media_assets rows, cleared by a purge
+const DERIVED_ASSET_COLUMNS: &[&str] = &[
+    "thumbnail_dir",
+    "thumbnail_levels_json",
+    "segments_built_at",
+    "transcript_ready_at",
+    "vision_ready_at",
+    "metadata_ready_at",
+    "embeddings_ready_at",
+    "twelvelabs_video_id",
+    "twelvelabs_task_id",
+    "twelvelabs_indexed_at",
+    "twelvelabs_last_error",
+    "still_subjects_json",
+];
+
+impl Database {
+    /// Everything stored about a project as one JSON document: the project and its media records,
+    /// derived analysis (segments, decrypted transcripts and vision results, embedding metadata
+    /// without the vectors, redactions, feedback), voiceovers, LLM calls, conversations and timelines.
+    /// Fails with ProjectLocked when the project is encrypted and locked.
+    pub fn project_data_export(&self, project_id: i64) -> Result<Option<serde_json::Value>> {
+        let Some(project) = self.get_project(project_id)? else {
+            return Ok(None);
+        };
+        let assets = self.get_media_assets_for_project(project_id)?;
+        let mut transcripts = Vec::new();
+        let mut vision = Vec::new();
+        for asset in &assets {
+            if let Some(json) = self.get_asset_transcript(asset.id)? {
+                transcripts.push(serde_json::json!({ "asset_id": asset.id, "transcript": parse_json_text(json) }));
+            }
+            if let Some(json) = self.get_asset_vision(asset.id)? {
+                vision.push(serde_json::json!({ "asset_id": asset.id, "vision": parse_json_text(json) }));
+            }
+        }
+        let mut voiceovers = Vec::new();
+        for voiceover in self.list_voiceovers(project_id)? {
+            let transcript = self.get_voiceover_transcript(voiceover.id)?.map(parse_json_text);
+            voiceovers.push(serde_json::json!({ "voiceover": voiceover, "transcript": transcript }));
+        }
+
+        let conn = self.conn.lock().unwrap();
+        let rows = |sql: &str| json_rows(&conn, sql, project_id);
+        Ok(Some(serde_json::json!({
+            "exported_at": Utc::now().to_rfc3339(),
+            "project": {
+                "id": project.id,
+                "name": project.name,
+                "created_at": project.created_at,
+                "cache_dir": project.cache_dir,
+            },
+            "media_assets": rows("SELECT * FROM media_assets WHERE project_id = ?1 ORDER BY id")?,
+            "segments": rows("SELECT * FROM segments WHERE project_id = ?1 ORDER BY id")?,
+            "transcripts": transcripts,
+            "vision": vision,
+            "embeddings": rows(
+                "SELECT e.id, e.segment_id, e.embedding_type, e.model_name, e.model_version,
+                        length(e.vector_blob) / 4 AS dimension, e.semantic_text
+                 FROM embeddings e JOIN segments s ON s.id = e.segment_id
+                 WHERE s.project_id = ?1 ORDER BY e.id",
+            )?,
+            "redaction_subjects": rows("SELECT * FROM redaction_subjects WHERE project_id = ?1 ORDER BY id")?,
+            "multicam_groups": multicam_groups_for_project(&conn, project_id)?,
+            "voiceovers": voiceovers,
+            "segment_feedback": rows("SELECT * FROM segment_feedback WHERE project_id = ?1 ORDER BY segment_id")?,
+            "collections": {
+                "collections": rows("SELECT * FROM collections WHERE project_id = ?1 ORDER BY id")?,
+                "assets": rows(
+                    "SELECT ca.* FROM collection_assets ca JOIN collections c ON c.id = ca.collection_id
+                     WHERE c.project_id = ?1 ORDER BY ca.collection_id, ca.asset_id",
+                )?,
+                "segments": rows(
+                    "SELECT cs.* FROM collection_segments cs JOIN collections c ON c.id = cs.collection_id
+                     WHERE c.project_id = ?1 ORDER BY cs.collection_id, cs.segment_id",
+                )?,
+            },
+            "llm_calls": rows("SELECT * FROM llm_calls WHERE project_id = ?1 ORDER BY id")?,
+            "orchestrator": {
+                "messages": rows("SELECT * FROM orchestrator_messages WHERE project_id = ?1 ORDER BY id")?,
+                "goals": rows("SELECT * FROM orchestrator_goals WHERE project_id = ?1 ORDER BY id")?,
+                "proposals": rows("SELECT * FROM orchestrator_proposals WHERE project_id = ?1 ORDER BY id")?,
+                "applies": rows("SELECT * FROM orchestrator_applies WHERE project_id = ?1 ORDER BY id")?,
+                "tool_calls": rows("SELECT * FROM orchestrator_tool_calls WHERE project_id = ?1 ORDER BY id")?,
+                "working_set": rows("SELECT * FROM orchestrator_working_sets WHERE project_id = ?1")?,
+            },
+            "edit_logs": rows("SELECT * FROM edit_logs WHERE project_id = ?1 ORDER BY id")?,
+            "timelines": rows("SELECT * FROM timeline_projects WHERE project_id = ?1 ORDER BY id")?,
+            "timeline_versions": rows("SELECT * FROM timeline_versions WHERE project_id = ?1 ORDER BY id")?,
+            "timeline_snapshots": rows("SELECT * FROM timeline_snapshots WHERE project_id = ?1 ORDER BY id")?,
+        })))
+    }
+
+    /// Delete a project's derived data (see DERIVED_DATA_TABLES) and reset the analysis state of
+    /// its assets, in one transaction. Pending jobs for the project are cancelled so they don't
+    /// regenerate it. Returns the number of rows deleted per table.
+    pub fn purge_project_derived_data(&self, project_id: i64) -> Result<Vec<(String, usize)>> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let mut deleted = Vec::new();
+        for (table, predicate) in DERIVED_DATA_TABLES {
+            let count = tx.execute(&format!("DELETE FROM {} WHERE {}", table, predicate), params![project_id])?;
+            deleted.push((table.to_string(), count));
+        }
+        let reset = DERIVED_ASSET_COLUMNS
+            .iter()
+            .map(|column| format!("{} = NULL", column))
+            .collect::<Vec<_>>()
+            .join(", ");
+        tx.execute(&format!("UPDATE media_assets SET {} WHERE project_id = ?1", reset), params![project_id])?;
+        tx.execute(
+            "UPDATE jobs SET status = 'Cancelled', is_active = 0, updated_at = ?2
+             WHERE status = 'Pending'
+               AND (json_extract(payload_json, '$.project_id') = ?1
+                    OR json_extract(payload_json, '$.asset_id') IN (SELECT id FROM media_assets WHERE project_id = ?1))",
+            params![project_id, Utc::now().to_rfc3339()],
+        )?;
+        tx.commit()?;
+        Ok(deleted)
+    }
+
+    /// Derived rows still present for a project, per table (assets with analysis state count
+    /// under "media_assets"). Empty after a complete purge.
+    pub fn remaining_derived_data(&self, project_id: i64) -> Result<Vec<(String, usize)>> {
+        let conn = self.conn.lock().unwrap();
+        let mut remaining = Vec::new();
+        for (table, predicate) in DERIVED_DATA_TABLES {
+            let count: i64 = conn.query_row(
+                &format!("SELECT COUNT(*) FROM {} WHERE {}", table, predicate),
+                params![project_id],
+                |row| row.get(0),
+            )?;
+            if count > 0 {
+                remaining.push((table.to_string(), count as usize));
+            }
+        }
+        let any_set = DERIVED_ASSET_COLUMNS
+            .iter()
+            .map(|column| format!("{} IS NOT NULL", column))
+            .collect::<Vec<_>>()
+            .join(" OR ");
+        let assets: i64 = conn.query_row(
+            &format!("SELECT COUNT(*) FROM media_assets WHERE project_id = ?1 AND ({})", any_set),
+            params![project_id],
+            |row| row.get(0),
+        )?;
+        if assets > 0 {
+            remaining.push(("media_assets".to_string(), assets as usize));
+        }
+        Ok(remaining)
+    }
+}
+
+/// Rows of a query bound to a project id as JSON objects. Columns holding JSON (`*_json`,
+/// `json_blob`) are embedded as values; blobs are left out.
+fn json_rows(conn: &Connection, sql: &str, project_id: i64) -> Result<Vec<serde_json::Value>> {
+    use rusqlite::types::ValueRef;
+
+    let mut stmt = conn.prepare(sql)?;
+    let columns: Vec<String> = stmt.column_names().iter().map(|c| c.to_string()).collect();
+    let mut rows = stmt.query(params![project_id])?;
+    let mut out = Vec::new();
+    while let Some(row) = rows.next()? {
+        let mut object = serde_json::Map::new();
+        for (i, column) in columns.iter().enumerate() {
+            let value = match row.get_ref(i)? {
+                ValueRef::Null => serde_json::Value::Null,
+                ValueRef::Integer(n) => n.into(),
+                ValueRef::Real(f) => f.into(),
+                ValueRef::Text(text) => {
+                    let text = String::from_utf8_lossy(text).to_string();
+                    if column.ends_with("_json") || column == "json_blob" {
+                        parse_json_text(text)
+                    } else {
+                        text.into()
+                    }
+                }
+                ValueRef::Blob(_) => continue,
+            };
+            object.insert(column.clone(), value);
+        }
+        out.push(serde_json::Value::Object(object));
+    }
+    Ok(out)
+}
+
+/// Stored JSON text as a value (kept as a string if it doesn't parse)
+fn parse_json_text(text: String) -> serde_json::Value {
+    serde_json::from_str(&text).unwrap_or(serde_json::Value::String(text))
+}
+
+/// Clips of one event recorded by several cameras, synced by audio
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct MulticamGroup {
+    pub id: i64,
+    pub project_id: i64,
+    pub name: String,
+    /// Angle the others were correlated against
+    pub reference_asset_id: i64,
+    pub angles: Vec<MulticamGroupAngle>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct MulticamGroupAngle {
+    pub asset_id: i64,
+    /// Where the asset's source starts on the group's shared clock (the earliest angle is at 0)
+    pub offset_ticks: i64,
+    pub duration_ticks: i64,
+    /// Strength of the audio match (the reference is 1)
+    pub confidence: f64,
+}
+
+impl Database {
+    /// Store a synced group; `angles` are (asset_id, offset_ticks, confidence)
+    pub fn create_multicam_group(
+        &self,
+        project_id: i64,
+        name: &str,
+        reference_asset_id: i64,
+        angles: &[(i64, i64, f64)],
+    ) -> Result<i64> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        tx.execute(
+            "INSERT INTO multicam_groups (project_id, name, reference_asset_id, created_at) VALUES (?1, ?2, ?3, ?4)",
+            params![project_id, name, reference_asset_id, Utc::now().to_rfc3339()],
+        )?;
+        let group_id = tx.last_insert_rowid();
+        for (asset_id, offset_ticks, confidence) in angles {
+            tx.execute(
+                "INSERT INTO multicam_angles (group_id, asset_id, offset_ticks, confidence) VALUES (?1, ?2, ?3, ?4)",
+                params![group_id, asset_id, offset_ticks, confidence],
+            )?;
+        }
+        tx.commit()?;
+        Ok(group_id)
+    }
+
+    pub fn get_multicam_group(&self, group_id: i64) -> Result<Option<MulticamGroup>> {
+        let conn = self.conn.lock().unwrap();
+        let result = conn.query_row(
+            "SELECT id, project_id, name, reference_asset_id, created_at FROM multicam_groups WHERE id = ?1",
+            params![group_id],
+            multicam_group_from_row,
+        );
+        match result {
+            Ok(mut group) => {
+                group.angles = multicam_angles(&conn, group.id)?;
+                Ok(Some(group))
+            }
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn list_multicam_groups(&self, project_id: i64) -> Result<Vec<MulticamGroup>> {
+        let conn = self.conn.lock().unwrap();
+        multicam_groups_for_project(&conn, project_id)
+    }
+
+    pub fn delete_multicam_group(&self, group_id: i64) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM multicam_angles WHERE group_id = ?1", params![group_id])?;
+        tx.execute("DELETE FROM multicam_groups WHERE id = ?1", params![group_id])?;
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+fn multicam_group_from_row(row: &rusqlite::Row) -> rusqlite::Result<MulticamGroup> {
+    Ok(MulticamGroup {
+        id: row.get(0)?,
+        project_id: row.get(1)?,
+        name: row.get(2)?,
+        reference_asset_id: row.get(3)?,
+        angles: Vec::new(),
+        created_at: row.get(4)?,
+    })
+}
+
+fn multicam_groups_for_project(conn: &Connection, project_id: i64) -> Result<Vec<MulticamGroup>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, project_id, name, reference_asset_id, created_at FROM multicam_groups
+         WHERE project_id = ?1 ORDER BY id",
+    )?;
+    let mut groups = stmt
+        .query_map(params![project_id], multicam_group_from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+    for group in &mut groups {
+        group.angles = multicam_angles(conn, group.id)?;
+    }
+    Ok(groups)
+}
+
+/// Angles of a group, earliest first
+fn multicam_angles(conn: &Connection, group_id: i64) -> Result<Vec<MulticamGroupAngle>> {
+    let mut stmt = conn.prepare(
+        "SELECT a.asset_id, a.offset_ticks, m.duration_ticks, a.confidence
+         FROM multicam_angles a JOIN media_assets m ON m.id = a.asset_id
+         WHERE a.group_id = ?1 ORDER BY a.offset_ticks, a.asset_id",
+    )?;
+    let angles = stmt
+        .query_map(params![group_id], |row| {
+            Ok(MulticamGroupAngle {
+                asset_id: row.get(0)?,
+                offset_ticks: row.get(1)?,
+                duration_ticks: row.get(2)?,
+                confidence: row.get(3)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(angles)
+}
+
+/// A photo imported as a still, with what its Ken Burns motion can move towards
+#[derive(Debug, Clone)]
+pub struct StillAsset {
+    pub id: i64,
+    pub width: i32,
+    pub height: i32,
+    /// None until subject detection has run
+    pub subjects: Option<Vec<StillSubject>>,
+}
+
+impl Database {
+    /// Mark a media asset as a still image
+    pub fn set_media_asset_still(&self, media_asset_id: i64) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE media_assets SET is_still = 1 WHERE id = ?1",
+            params![media_asset_id],
+        )?;
+        Ok(())
+    }
+
+    pub fn is_still_asset(&self, media_asset_id: i64) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        let result = conn.query_row(
+            "SELECT is_still FROM media_assets WHERE id = ?1",
+            params![media_asset_id],
+            |row| row.get::<_, i64>(0),
+        );
+        match result {
+            Ok(is_still) => Ok(is_still != 0),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Record the subjects detected in a still
+    pub fn set_still_subjects(&self, media_asset_id: i64, subjects: &[StillSubject]) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE media_assets SET still_subjects_json = ?1 WHERE id = ?2",
+            params![serde_json::to_string(subjects)?, media_asset_id],
+        )?;
+        Ok(())
+    }
+
+    /// The project's still images
+    pub fn get_still_assets(&self, project_id: i64) -> Result<Vec<StillAsset>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, width, height, still_subjects_json FROM media_assets
+             WHERE (project_id = ?1 OR id IN (SELECT asset_id FROM project_asset_links WHERE project_id = ?1)) AND is_still = 1 ORDER BY id",
+        )?;
+        let assets = stmt
+            .query_map(params![project_id], |row| {
+                let subjects: Option<String> = row.get(3)?;
+                Ok(StillAsset {
+                    id: row.get(0)?,
+                    width: row.get(1)?,
+                    height: row.get(2)?,
+                    subjects: subjects.and_then(|json| serde_json::from_str(&json).ok()),
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(assets)
+    }
+
+    /// Record the tool calls an edit instruction produced, with each call's outcome, whether the
+    /// edit was applied and why not
+    pub fn store_orchestrator_tool_calls(
+        &self,
+        project_id: i64,
+        instruction: &str,
+        response_message: &str,
+        tool_calls: &serde_json::Value,
+        applied: bool,
+        error: Option<&str>,
+    ) -> Result<i64> {
+        let now = Utc::now().to_rfc3339();
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO orchestrator_tool_calls
+                (project_id, instruction, response_message, tool_calls_json, applied, error, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                project_id,
+                instruction,
+                response_message,
+                serde_json::to_string(tool_calls)?,
+                applied as i64,
+                error,
+                now
+            ],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// The project's recorded tool call transcripts (most recent first)
+    pub fn get_orchestrator_tool_calls(&self, project_id: i64, limit: i64) -> Result<Vec<serde_json::Value>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, instruction, response_message, tool_calls_json, applied, error, created_at
+             FROM orchestrator_tool_calls WHERE project_id = ?1 ORDER BY id DESC LIMIT ?2",
+        )?;
+        let transcripts = stmt
+            .query_map(params![project_id, limit], |row| {
+                let tool_calls: String = row.get(3)?;
+                Ok(serde_json::json!({
+                    "id": row.get::<_, i64>(0)?,
+                    "instruction": row.get::<_, String>(1)?,
+                    "message": row.get::<_, String>(2)?,
+                    "tool_calls": serde_json::from_str::<serde_json::Value>(&tool_calls).unwrap_or_default(),
+                    "applied": row.get::<_, i64>(4)? != 0,
+                    "error": row.get::<_, Option<String>>(5)?,
+                    "created_at": row.get::<_, String>(6)?,
+                }))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(transcripts)
+    }
+
+    /// The conversation's working set: (segment ids, where it came from, when it was set)
+    pub fn get_working_set(&self, project_id: i64) -> Result<Option<(Vec<i64>, String, String)>> {
+        let conn = self.conn.lock().unwrap();
+        let result = conn.query_row(
+            "SELECT segment_ids_json, source, updated_at FROM orchestrator_working_sets
+             WHERE project_id = ?1 AND segment_ids_json IS NOT NULL",
+            params![project_id],
+            |row| Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?, row.get::<_, String>(2)?)),
+        );
+        match result {
+            Ok((ids, source, updated_at)) => Ok(Some((
+                serde_json::from_str(&ids).unwrap_or_default(),
+                source.unwrap_or_default(),
+                updated_at,
+            ))),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Replace the conversation's working set (None clears it)
+    pub fn set_working_set(&self, project_id: i64, segment_ids: Option<&[i64]>, source: Option<&str>) -> Result<()> {
+        let now = Utc::now().to_rfc3339();
+        let ids_json = segment_ids.map(serde_json::to_string).transpose()?;
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO orchestrator_working_sets (project_id, segment_ids_json, source, updated_at)
+             VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(project_id) DO UPDATE SET
+                segment_ids_json = excluded.segment_ids_json,
+                source = excluded.source,
+                updated_at = excluded.updated_at",
+            params![project_id, ids_json, source, now],
+        )?;
+        Ok(())
+    }
+
+    /// Segments the assistant last showed the user, in the order shown
+    pub fn get_last_shown_segments(&self, project_id: i64) -> Result<Vec<i64>> {
+        let conn = self.conn.lock().unwrap();
+        let result = conn.query_row(
+            "SELECT last_shown_json FROM orchestrator_working_sets WHERE project_id = ?1",
+            params![project_id],
+            |row| row.get::<_, Option<String>>(0),
+        );
+        match result {
+            Ok(json) => Ok(json.and_then(|j| serde_json::from_str(&j).ok()).unwrap_or_default()),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(Vec::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Remember the segments just shown to the user, so "these clips" can refer to them
+    pub fn set_last_shown_segments(&self, project_id: i64, segment_ids: &[i64]) -> Result<()> {
+        let now = Utc::now().to_rfc3339();
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO orchestrator_working_sets (project_id, last_shown_json, updated_at)
+             VALUES (?1, ?2, ?3)
+             ON CONFLICT(project_id) DO UPDATE SET last_shown_json = excluded.last_shown_json",
+            params![project_id, serde_json::to_string(segment_ids)?, now],
+        )?;
+        Ok(())
+    }
+
+    /// All project templates, built-ins first
+    pub fn list_project_templates(&self) -> Result<Vec<ProjectTemplateRow>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, builtin_key, template_json, created_at FROM project_templates
+             ORDER BY builtin_key IS NULL, id"
+        )?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))?;
+
+        let mut templates = Vec::new();
+        for row in rows {
+            templates.push(row?);
+        }
+        Ok(templates)
+    }
+
+    /// One project template as (builtin_key, template_json, created_at)
+    pub fn get_project_template(&self, template_id: i64) -> Result<Option<(Option<String>, String, String)>> {
+        let conn = self.conn.lock().unwrap();
+        match conn.query_row(
+            "SELECT builtin_key, template_json, created_at FROM project_templates WHERE id = ?1",
+            params![template_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        ) {
+            Ok(template) => Ok(Some(template)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn create_project_template(&self, name: &str, template_json: &str) -> Result<i64> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO project_templates (name, template_json, created_at) VALUES (?1, ?2, ?3)",
+            params![name, template_json, Utc::now().to_rfc3339()],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Delete a user-created template (built-ins can't be deleted); returns whether one was
+    pub fn delete_project_template(&self, template_id: i64) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        let deleted = conn.execute(
+            "DELETE FROM project_templates WHERE id = ?1 AND builtin_key IS NULL",
+            params![template_id],
+        )?;
+        Ok(deleted > 0)
+    }
+
+    /// The template a project was created from and the project's copy of it, as
+    /// (template_id, template_json); None when the project has no template
+    pub fn get_project_template_copy(&self, project_id: i64) -> Result<Option<(Option<i64>, String)>> {
+        let conn = self.conn.lock().unwrap();
+        let result = conn.query_row(
+            "SELECT template_id, template_json FROM projects WHERE id = ?1",
+            params![project_id],
+            |row| Ok((row.get::<_, Option<i64>>(0)?, row.get::<_, Option<String>>(1)?)),
+        );
+        match result {
+            Ok((template_id, json)) => Ok(json.map(|json| (template_id, json))),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Set (or clear with None) a project's copy of a template, keeping which template it came from
+    pub fn set_project_template_copy(
+        &self,
+        project_id: i64,
+        template_id: Option<i64>,
+        template_json: Option<&str>,
+    ) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE projects SET template_id = ?1, template_json = ?2 WHERE id = ?3",
+            params![template_id, template_json, project_id],
+        )?;
+        Ok(())
+    }
+
+    /// Create an empty collection; names are unique per project, ignoring case
+    pub fn create_collection(&self, project_id: i64, name: &str) -> Result<i64> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO collections (project_id, name, created_at) VALUES (?1, ?2, ?3)",
+            params![project_id, name, Utc::now().to_rfc3339()],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// A project's collections with their member counts, by name
+    pub fn list_collections(&self, project_id: i64) -> Result<Vec<CollectionRow>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT c.id, c.name, c.created_at,
+                    (SELECT COUNT(*) FROM collection_assets ca WHERE ca.collection_id = c.id),
+                    (SELECT COUNT(*) FROM collection_segments cs WHERE cs.collection_id = c.id)
+             FROM collections c WHERE c.project_id = ?1 ORDER BY c.name"
+        )?;
+        let rows = stmt.query_map(params![project_id], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?))
+        })?;
+
+        let mut collections = Vec::new();
+        for row in rows {
+            collections.push(row?);
+        }
+        Ok(collections)
+    }
+
+    /// One collection as (project_id, name, created_at)
+    pub fn get_collection(&self, collection_id: i64) -> Result<Option<(i64, String, String)>> {
+        let conn = self.conn.lock().unwrap();
+        match conn.query_row(
+            "SELECT project_id, name, created_at FROM collections WHERE id = ?1",
+            params![collection_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        ) {
+            Ok(collection) => Ok(Some(collection)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Id of the project's collection with this name (ignoring case)
+    pub fn find_collection_by_name(&self, project_id: i64, name: &str) -> Result<Option<i64>> {
+        let conn = self.conn.lock().unwrap();
+        match conn.query_row(
+            "SELECT id FROM collections WHERE project_id = ?1 AND name = ?2",
+            params![project_id, name],
+            |row| row.get(0),
+        ) {
+            Ok(id) => Ok(Some(id)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn rename_collection(&self, collection_id: i64, name: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE collections SET name = ?1 WHERE id = ?2",
+            params![name, collection_id],
+        )?;
+        Ok(())
+    }
+
+    /// A collection's members as (asset_ids, segment_ids)
+    pub fn get_collection_members(&self, collection_id: i64) -> Result<(Vec<i64>, Vec<i64>)> {
+        let conn = self.conn.lock().unwrap();
+        let ids = |sql: &str| -> Result<Vec<i64>> {
+            let mut stmt = conn.prepare(sql)?;
+            let rows = stmt.query_map(params![collection_id], |row| row.get(0))?;
+            Ok(rows.collect::<Result<Vec<i64>, _>>()?)
+        };
+        Ok((
+            ids("SELECT asset_id FROM collection_assets WHERE collection_id = ?1 ORDER BY asset_id")?,
+            ids("SELECT segment_id FROM collection_segments WHERE collection_id = ?1 ORDER BY segment_id")?,
+        ))
+    }
+
+    /// Add assets and segments to a collection (ones already in it are left alone)
+    pub fn add_collection_members(&self, collection_id: i64, asset_ids: &[i64], segment_ids: &[i64]) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let now = Utc::now().to_rfc3339();
+        for asset_id in asset_ids {
+            tx.execute(
+                "INSERT OR IGNORE INTO collection_assets (collection_id, asset_id, added_at) VALUES (?1, ?2, ?3)",
+                params![collection_id, asset_id, now],
+            )?;
+        }
+        for segment_id in segment_ids {
+            tx.execute(
+                "INSERT OR IGNORE INTO collection_segments (collection_id, segment_id, added_at) VALUES (?1, ?2, ?3)",
+                params![collection_id, segment_id, now],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    pub fn remove_collection_members(&self, collection_id: i64, asset_ids: &[i64], segment_ids: &[i64]) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        for asset_id in asset_ids {
+            tx.execute(
+                "DELETE FROM collection_assets WHERE collection_id = ?1 AND asset_id = ?2",
+                params![collection_id, asset_id],
+            )?;
+        }
+        for segment_id in segment_ids {
+            tx.execute(
+                "DELETE FROM collection_segments WHERE collection_id = ?1 AND segment_id = ?2",
+                params![collection_id, segment_id],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Delete a collection and its memberships (the assets and segments themselves stay)
+    pub fn delete_collection(&self, collection_id: i64) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM collection_assets WHERE collection_id = ?1", params![collection_id])?;
+        tx.execute("DELETE FROM collection_segments WHERE collection_id = ?1", params![collection_id])?;
+        tx.execute("DELETE FROM collections WHERE id = ?1", params![collection_id])?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Segments a collection covers: every segment of its assets plus its individual segments
+    pub fn collection_segment_ids(&self, collection_id: i64) -> Result<Vec<i64>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT s.id FROM segments s
+             JOIN collections c ON c.project_id = s.project_id
+             WHERE c.id = ?1
+               AND (s.media_asset_id IN (SELECT asset_id FROM collection_assets WHERE collection_id = ?1)
+                    OR s.id IN (SELECT segment_id FROM collection_segments WHERE collection_id = ?1))
+             ORDER BY s.id"
+        )?;
+        let rows = stmt.query_map(params![collection_id], |row| row.get(0))?;
+        Ok(rows.collect::<Result<Vec<i64>, _>>()?)
+    }
+
+    /// Which of these asset and segment ids don't belong to the project
+    pub fn ids_outside_project(
+        &self,
+        project_id: i64,
+        asset_ids: &[i64],
+        segment_ids: &[i64],
+    ) -> Result<(Vec<i64>, Vec<i64>)> {
+        let conn = self.conn.lock().unwrap();
+        let outside = |sql: &str, ids: &[i64]| -> Result<Vec<i64>> {
+            let mut result = Vec::new();
+            for id in ids {
+                let found = conn
+                    .query_row(sql, params![id, project_id], |_| Ok(()))
+                    .is_ok();
+                if !found {
+                    result.push(*id);
+                }
+            }
+            Ok(result)
+        };
+        Ok((
+            outside("SELECT 1 FROM media_assets WHERE id = ?1 AND project_id = ?2", asset_ids)?,
+            outside("SELECT 1 FROM segments WHERE id = ?1 AND project_id = ?2", segment_ids)?,
+        ))
+    }
+
+    /// Whether an asset's original is 'online', 'offline' or 'archived'
+    pub fn get_media_asset_availability(&self, media_asset_id: i64) -> Result<Option<String>> {
+        let conn = self.conn.lock().unwrap();
+        let result = conn.query_row(
+            "SELECT availability FROM media_assets WHERE id = ?1",
+            params![media_asset_id],
+            |row| row.get::<_, String>(0),
+        );
+        match result {
+            Ok(availability) => Ok(Some(availability)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Mark an asset's original online, offline or archived
+    pub fn set_media_asset_availability(&self, media_asset_id: i64, availability: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE media_assets SET availability = ?1, availability_changed_at = ?2
+             WHERE id = ?3 AND availability != ?1",
+            params![availability, Utc::now().to_rfc3339(), media_asset_id],
+        )?;
+        Ok(())
+    }
+
+    /// Every asset in the project (references and library assets it uses included) as
+    /// (id, path, availability)
+    pub fn get_media_asset_availabilities(&self, project_id: i64) -> Result<Vec<(i64, String, String)>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, path, availability FROM media_assets
+             WHERE (project_id = ?1 OR id IN (SELECT asset_id FROM project_asset_links WHERE project_id = ?1))
+             ORDER BY id"
+        )?;
+        let rows = stmt.query_map(params![project_id], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+        Ok(rows.collect::<Result<Vec<_>, _>>()?)
+    }
+
+    /// Online originals of the project as (id, path, stored checksum)
+    pub fn get_media_asset_checksums(&self, project_id: i64) -> Result<Vec<(i64, String, Option<String>)>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, path, checksum FROM media_assets
+             WHERE project_id = ?1 AND availability = 'online'
+             ORDER BY id"
+        )?;
+        let rows = stmt.query_map(params![project_id], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+        Ok(rows.collect::<Result<Vec<_>, _>>()?)
+    }
+
+    /// Stored checksum of an asset's original (None when it was never hashed)
+    pub fn get_media_asset_checksum(&self, media_asset_id: i64) -> Result<Option<String>> {
+        use rusqlite::OptionalExtension;
+        let conn = self.conn.lock().unwrap();
+        let checksum = conn
+            .query_row(
+                "SELECT checksum FROM media_assets WHERE id = ?1",
+                params![media_asset_id],
+                |row| row.get::<_, Option<String>>(0),
+            )
+            .optional()?;
+        Ok(checksum.flatten())
+    }
+
+    /// Store the checksum of an asset that was imported without one
+    pub fn set_media_asset_checksum(&self, media_asset_id: i64, checksum: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE media_assets SET checksum = ?1 WHERE id = ?2",
+            params![checksum, media_asset_id],
+        )?;
+        Ok(())
+    }
+
+    /// Record the outcome of verifying an asset's checksum (None clears it, e.g. after a relink)
+    pub fn set_media_asset_integrity(
+        &self,
+        media_asset_id: i64,
+        integrity: Option<&str>,
+        error: Option<&str>,
+    ) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        let checked_at = integrity.map(|_| Utc::now().to_rfc3339());
+        conn.execute(
+            "UPDATE media_assets SET integrity = ?1, integrity_checked_at = ?2, integrity_error = ?3 WHERE id = ?4",
+            params![integrity, checked_at, error, media_asset_id],
+        )?;
+        Ok(())
+    }
+
+    /// Last verification result of every checked asset in the project
+    pub fn get_media_asset_integrity(&self, project_id: i64) -> Result<Vec<AssetIntegrityRow>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, integrity, integrity_checked_at, integrity_error FROM media_assets
+             WHERE (project_id = ?1 OR id IN (SELECT asset_id FROM project_asset_links WHERE project_id = ?1)) AND integrity IS NOT NULL
+             ORDER BY id"
+        )?;
+        let rows = stmt.query_map(params![project_id], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+        })?;
+        Ok(rows.collect::<Result<Vec<_>, _>>()?)
+    }
+}
+
+/// A phone paired (or waiting to be paired) with a project
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct CompanionDevice {
+    pub id: i64,
+    pub project_id: i64,
+    pub name: Option<String>,
+    /// None until the pairing token has been claimed
+    pub paired_at: Option<String>,
+    pub pairing_expires_at: String,
+    pub last_seen_at: Option<String>,
+    pub created_at: String,
+}
+
+/// A file sent from a paired phone
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct CompanionUpload {
+    pub id: i64,
+    pub device_id: i64,
+    pub project_id: i64,
+    pub filename: String,
+    /// Where the phone's original file is written
+    pub path: String,
+    pub size_bytes: i64,
+    pub received_bytes: i64,
+    pub captured_at: Option<String>,
+    /// Shared by the photo and the video of a Live Photo
+    pub live_photo_id: Option<String>,
+    /// "uploading", "processing", "imported" or "failed"
+    pub status: String,
+    pub job_id: Option<i64>,
+    pub asset_id: Option<i64>,
+    /// The capture session (a collection per day) the import was added to
+    pub collection_id: Option<i64>,
+    pub error: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+const COMPANION_DEVICE_COLUMNS: &str =
+    "id, project_id, name, paired_at, pairing_expires_at, last_seen_at, created_at";
+
+const COMPANION_UPLOAD_COLUMNS: &str = "id, device_id, project_id, filename, path, size_bytes, received_bytes, \
+     captured_at, live_photo_id, status, job_id, asset_id, collection_id, error, created_at, updated_at";
+
+fn companion_device_from_row(row: &rusqlite::Row) -> rusqlite::Result<CompanionDevice> {
+    Ok(CompanionDevice {
+        id: row.get(0)?,
+        project_id: row.get(1)?,
+        name: row.get(2)?,
+        paired_at: row.get(3)?,
+        pairing_expires_at: row.get(4)?,
+        last_seen_at: row.get(5)?,
+        created_at: row.get(6)?,
+    })
+}
+
+fn companion_upload_from_row(row: &rusqlite::Row) -> rusqlite::Result<CompanionUpload> {
+    Ok(CompanionUpload {
+        id: row.get(0)?,
+        device_id: row.get(1)?,
+        project_id: row.get(2)?,
+        filename: row.get(3)?,
+        path: row.get(4)?,
+        size_bytes: row.get(5)?,
+        received_bytes: row.get(6)?,
+        captured_at: row.get(7)?,
+        live_photo_id: row.get(8)?,
+        status: row.get(9)?,
+        job_id: row.get(10)?,
+        asset_id: row.get(11)?,
+        collection_id: row.get(12)?,
+        error: row.get(13)?,
+        created_at: row.get(14)?,
+        updated_at: row.get(15)?,
+    })
+}
+
+impl Database {
+    /// Start pairing a phone with a project; the device is unusable until the token is claimed
+    pub fn create_companion_pairing(&self, project_id: i64, pairing_token_hash: &str, expires_at: &str) -> Result<i64> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO companion_devices (project_id, pairing_token_hash, pairing_expires_at, created_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![project_id, pairing_token_hash, expires_at, Utc::now().to_rfc3339()],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Exchange an unexpired, unclaimed pairing token for a device token. Returns the device,
+    /// or None when the token is unknown, expired or already used.
+    pub fn claim_companion_pairing(
+        &self,
+        pairing_token_hash: &str,
+        device_token_hash: &str,
+        name: Option<&str>,
+    ) -> Result<Option<CompanionDevice>> {
+        let conn = self.conn.lock().unwrap();
+        let now = Utc::now().to_rfc3339();
+        let claimed = conn.execute(
+            "UPDATE companion_devices SET device_token_hash = ?1, name = ?2, paired_at = ?3, last_seen_at = ?3
+             WHERE pairing_token_hash = ?4 AND device_token_hash IS NULL AND pairing_expires_at > ?3",
+            params![device_token_hash, name, now, pairing_token_hash],
+        )?;
+        if claimed == 0 {
+            return Ok(None);
+        }
+        let device = conn.query_row(
+            &format!("SELECT {} FROM companion_devices WHERE pairing_token_hash = ?1", COMPANION_DEVICE_COLUMNS),
+            params![pairing_token_hash],
+            companion_device_from_row,
+        )?;
+        Ok(Some(device))
+    }
+
+    /// The paired device a device token belongs to, marking it as just seen
+    pub fn companion_device_for_token(&self, device_token_hash: &str) -> Result<Option<CompanionDevice>> {
+        let conn = self.conn.lock().unwrap();
+        let result = conn.query_row(
+            &format!("SELECT {} FROM companion_devices WHERE device_token_hash = ?1", COMPANION_DEVICE_COLUMNS),
+            params![device_token_hash],
+            companion_device_from_row,
+        );
+        match result {
+            Ok(device) => {
+                conn.execute(
+                    "UPDATE companion_devices SET last_seen_at = ?1 WHERE id = ?2",
+                    params![Utc::now().to_rfc3339(), device.id],
+                )?;
+                Ok(Some(device))
+            }
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Paired devices and pending pairings of a project
+    pub fn list_companion_devices(&self, project_id: i64) -> Result<Vec<CompanionDevice>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM companion_devices WHERE project_id = ?1 ORDER BY id",
+            COMPANION_DEVICE_COLUMNS
+        ))?;
+        let rows = stmt.query_map(params![project_id], companion_device_from_row)?;
+        Ok(rows.collect::<Result<Vec<_>, _>>()?)
+    }
+
+    /// Unpair a device; its uploads (and the assets imported from them) stay
+    pub fn delete_companion_device(&self, project_id: i64, device_id: i64) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        let deleted = conn.execute(
+            "DELETE FROM companion_devices WHERE id = ?1 AND project_id = ?2",
+            params![device_id, project_id],
+        )?;
+        Ok(deleted > 0)
+    }
+
+    /// Record an announced upload; its path is set once the id is known
+    pub fn create_companion_upload(
+        &self,
+        device_id: i64,
+        project_id: i64,
+        filename: &str,
+        size_bytes: i64,
+        captured_at: Option<&str>,
+        live_photo_id: Option<&str>,
+    ) -> Result<i64> {
+        let conn = self.conn.lock().unwrap();
+        let now = Utc::now().to_rfc3339();
+        conn.execute(
+            "INSERT INTO companion_uploads
+                (device_id, project_id, filename, path, size_bytes, captured_at, live_photo_id, status, created_at, updated_at)
+             VALUES (?1, ?2, ?3, '', ?4, ?5, ?6, 'uploading', ?7, ?7)",
+            params![device_id, project_id, filename, size_bytes, captured_at, live_photo_id, now],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    pub fn get_companion_upload(&self, upload_id: i64) -> Result<Option<CompanionUpload>> {
+        let conn = self.conn.lock().unwrap();
+        let result = conn.query_row(
+            &format!("SELECT {} FROM companion_uploads WHERE id = ?1", COMPANION_UPLOAD_COLUMNS),
+            params![upload_id],
+            companion_upload_from_row,
+        );
+        match result {
+            Ok(upload) => Ok(Some(upload)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// A project's uploads, newest first
+    pub fn list_companion_uploads(&self, project_id: i64) -> Result<Vec<CompanionUpload>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM companion_uploads WHERE project_id = ?1 ORDER BY id DESC",
+            COMPANION_UPLOAD_COLUMNS
+        ))?;
+        let rows = stmt.query_map(params![project_id], companion_upload_from_row)?;
+        Ok(rows.collect::<Result<Vec<_>, _>>()?)
+    }
+
+    pub fn set_companion_upload_path(&self, upload_id: i64, path: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE companion_uploads SET path = ?1 WHERE id = ?2",
+            params![path, upload_id],
+        )?;
+        Ok(())
+    }
+
+    pub fn set_companion_upload_received(&self, upload_id: i64, received_bytes: i64) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE companion_uploads SET received_bytes = ?1, updated_at = ?2 WHERE id = ?3",
+            params![received_bytes, Utc::now().to_rfc3339(), upload_id],
+        )?;
+        Ok(())
+    }
+
+    /// Move a fully received upload from uploading to processing. False when it had already
+    /// left uploading, so only one request starts its import.
+    pub fn claim_companion_upload_ingest(&self, upload_id: i64) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        let changed = conn.execute(
+            "UPDATE companion_uploads SET status = 'processing', updated_at = ?1
+             WHERE id = ?2 AND status = 'uploading'",
+            params![Utc::now().to_rfc3339(), upload_id],
+        )?;
+        Ok(changed == 1)
+    }
+
+    /// Move an upload to another status, recording its import job or the error that stopped it
+    pub fn set_companion_upload_status(
+        &self,
+        upload_id: i64,
+        status: &str,
+        job_id: Option<i64>,
+        error: Option<&str>,
+    ) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE companion_uploads SET status = ?1, job_id = COALESCE(?2, job_id), error = ?3, updated_at = ?4
+             WHERE id = ?5",
+            params![status, job_id, error, Utc::now().to_rfc3339(), upload_id],
+        )?;
+        Ok(())
+    }
+
+    /// Record what an upload was imported as and the capture session it joined
+    pub fn set_companion_upload_imported(&self, upload_id: i64, asset_id: i64, collection_id: i64) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE companion_uploads SET status = 'imported', asset_id = ?1, collection_id = ?2, error = NULL,
+                updated_at = ?3
+             WHERE id = ?4",
+            params![asset_id, collection_id, Utc::now().to_rfc3339(), upload_id],
+        )?;
+        Ok(())
+    }
+}
+
+impl Database {
+    /// Record whether a media asset was detected as a screen recording
+    pub fn set_media_asset_screen_recording(&self, media_asset_id: i64, is_screen_recording: bool) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE media_assets SET is_screen_recording = ?1 WHERE id = ?2",
+            params![is_screen_recording as i64, media_asset_id],
+        )?;
+        Ok(())
+    }
+
+    /// The project's assets detected as screen recordings
+    pub fn get_screen_recording_asset_ids(&self, project_id: i64) -> Result<Vec<i64>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id FROM media_assets WHERE (project_id = ?1 OR id IN (SELECT asset_id FROM project_asset_links WHERE project_id = ?1)) AND is_screen_recording = 1 ORDER BY id",
+        )?;
+        let rows = stmt.query_map(params![project_id], |row| row.get(0))?;
+        Ok(rows.collect::<Result<Vec<_>, _>>()?)
+    }
+
+    /// Record the clockwise display rotation probed from a video
+    pub fn set_media_asset_rotation(&self, media_asset_id: i64, rotation: i32) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE media_assets SET rotation = ?1 WHERE id = ?2",
+            params![rotation, media_asset_id],
+        )?;
+        Ok(())
+    }
+
+    /// The project's rotated assets with their rotation
+    pub fn get_media_asset_rotations(&self, project_id: i64) -> Result<Vec<(i64, i32)>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, rotation FROM media_assets WHERE (project_id = ?1 OR id IN (SELECT asset_id FROM project_asset_links WHERE project_id = ?1)) AND rotation != 0 ORDER BY id",
+        )?;
+        let rows = stmt.query_map(params![project_id], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        Ok(rows.collect::<Result<Vec<_>, _>>()?)
+    }
+}
+
+impl Database {
+    /// Record an asset's measured A/V sync (None clears it, along with any correction)
+    pub fn set_media_asset_sync_drift(&self, media_asset_id: i64, sync_drift_json: Option<&str>) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        let checked_at = sync_drift_json.map(|_| Utc::now().to_rfc3339());
+        conn.execute(
+            "UPDATE media_assets SET sync_drift_json = ?1, sync_checked_at = ?2,
+                sync_correction = CASE WHEN ?1 IS NULL THEN 0 ELSE sync_correction END
+             WHERE id = ?3",
+            params![sync_drift_json, checked_at, media_asset_id],
+        )?;
+        Ok(())
+    }
+
+    /// Turn resampling an asset's audio in its proxies on or off
+    pub fn set_media_asset_sync_correction(&self, media_asset_id: i64, enabled: bool) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE media_assets SET sync_correction = ?1 WHERE id = ?2",
+            params![enabled as i64, media_asset_id],
+        )?;
+        Ok(())
+    }
+
+    /// An asset's measured A/V sync and whether it's corrected (None until it's been measured)
+    pub fn get_media_asset_sync_drift(&self, media_asset_id: i64) -> Result<Option<(String, bool)>> {
+        let conn = self.conn.lock().unwrap();
+        let result = conn.query_row(
+            "SELECT sync_drift_json, sync_correction FROM media_assets WHERE id = ?1",
+            params![media_asset_id],
+            |row| Ok((row.get::<_, Option<String>>(0)?, row.get::<_, i64>(1)?)),
+        );
+        match result {
+            Ok((json, correction)) => Ok(json.map(|json| (json, correction != 0))),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Last A/V sync measurement of every measured asset in the project
+    pub fn get_project_sync_drift(&self, project_id: i64) -> Result<Vec<AssetSyncDriftRow>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, sync_drift_json, sync_checked_at, sync_correction FROM media_assets
+             WHERE (project_id = ?1 OR id IN (SELECT asset_id FROM project_asset_links WHERE project_id = ?1)) AND sync_drift_json IS NOT NULL
+             ORDER BY id"
+        )?;
+        let rows = stmt.query_map(params![project_id], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get::<_, i64>(3)? != 0))
+        })?;
+        Ok(rows.collect::<Result<Vec<_>, _>>()?)
+    }
+}
+
+impl Database {
+    /// Id of the project's current timeline version, stamped on exports
+    pub fn get_current_timeline_version_id(&self, project_id: i64) -> Result<Option<String>> {
+        let conn = self.conn.lock().unwrap();
+        let result = conn.query_row(
+            "SELECT version_id FROM timeline_versions WHERE project_id = ?1 AND is_current = 1",
+            params![project_id],
+            |row| row.get(0),
+        );
+        match result {
+            Ok(version_id) => Ok(Some(version_id)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Id of the edit plan most recently applied to the project
+    pub fn get_latest_edit_plan_id(&self, project_id: i64) -> Result<Option<i64>> {
+        let conn = self.conn.lock().unwrap();
+        let result = conn.query_row(
+            "SELECT id FROM orchestrator_applies WHERE project_id = ?1 ORDER BY created_at DESC, id DESC LIMIT 1",
+            params![project_id],
+            |row| row.get(0),
+        );
+        match result {
+            Ok(id) => Ok(Some(id)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/// A machine registered to run jobs for this daemon
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct RemoteWorker {
+    pub id: i64,
+    pub name: String,
+    /// Job types it takes (see jobs::remote::REMOTE_JOB_TYPES)
+    pub job_types: Vec<String>,
+    pub last_seen_at: Option<String>,
+    pub created_at: String,
+}
+
+const REMOTE_WORKER_COLUMNS: &str = "id, name, job_types_json, last_seen_at, created_at";
+
+fn remote_worker_from_row(row: &rusqlite::Row) -> rusqlite::Result<RemoteWorker> {
+    let job_types_json: String = row.get(2)?;
+    Ok(RemoteWorker {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        job_types: serde_json::from_str(&job_types_json).unwrap_or_default(),
+        last_seen_at: row.get(3)?,
+        created_at: row.get(4)?,
+    })
+}
+
+impl Database {
+    pub fn create_remote_worker(&self, name: &str, token_hash: &str, job_types: &[String]) -> Result<i64> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO remote_workers (name, token_hash, job_types_json, created_at) VALUES (?1, ?2, ?3, ?4)",
+            params![name, token_hash, serde_json::to_string(job_types)?, Utc::now().to_rfc3339()],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// The worker a token belongs to, marking it as just seen
+    pub fn remote_worker_for_token(&self, token_hash: &str) -> Result<Option<RemoteWorker>> {
+        let conn = self.conn.lock().unwrap();
+        let result = conn.query_row(
+            &format!("SELECT {} FROM remote_workers WHERE token_hash = ?1", REMOTE_WORKER_COLUMNS),
+            params![token_hash],
+            remote_worker_from_row,
+        );
+        match result {
+            Ok(mut worker) => {
+                let now = Utc::now().to_rfc3339();
+                conn.execute(
+                    "UPDATE remote_workers SET last_seen_at = ?1 WHERE id = ?2",
+                    params![now, worker.id],
+                )?;
+                worker.last_seen_at = Some(now);
+                Ok(Some(worker))
+            }
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn list_remote_workers(&self) -> Result<Vec<RemoteWorker>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!("SELECT {} FROM remote_workers ORDER BY id", REMOTE_WORKER_COLUMNS))?;
+        let rows = stmt.query_map([], remote_worker_from_row)?;
+        Ok(rows.collect::<Result<Vec<_>, _>>()?)
+    }
+
+    /// Revoke a worker's token; returns false when there was no such worker
+    pub fn delete_remote_worker(&self, worker_id: i64) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        let deleted = conn.execute("DELETE FROM remote_workers WHERE id = ?1", params![worker_id])?;
+        Ok(deleted > 0)
+    }
+}
+
+impl Database {
+    pub fn set_job_resource_limits(&self, class: &str, limits: &ResourceLimits) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO job_resource_limits (class, limits_json, updated_at) VALUES (?1, ?2, ?3)",
+            params![class, serde_json::to_string(limits)?, Utc::now().to_rfc3339()],
+        )?;
+        Ok(())
     }
 
-    pub fn get_project(&self, id: i64) -> Result<Option<Project>> {
+    /// Stored overrides as (class, limits); rows that no longer parse are skipped
+    pub fn list_job_resource_limits(&self) -> Result<Vec<(String, ResourceLimits)>> {
         let conn = self.conn.lock().unwrap();
-        let mut stmt = conn.prepare(
-            "SELECT id, name, created_at, cache_dir, style_profile_id FROM projects WHERE id = ?1"
+        let mut stmt = conn.prepare("SELECT class, limits_json FROM job_resource_limits ORDER BY class")?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows
+            .into_iter()
+            .filter_map(|(class, json)| Some((class, serde_json::from_str(&json).ok()?)))
+            .collect())
+    }
+
+    /// Drop a class's override so it's back on the defaults; false when it had none
+    pub fn delete_job_resource_limits(&self, class: &str) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        Ok(conn.execute("DELETE FROM job_resource_limits WHERE class = ?1", params![class])? > 0)
+    }
+}
+
+/// An autosaved timeline state (the timeline JSON itself is loaded separately)
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TimelineSnapshot {
+    pub id: i64,
+    pub project_id: i64,
+    /// Timeline version that was current when the snapshot was taken
+    pub version_id: Option<String>,
+    #[serde(skip)]
+    pub content_hash: String,
+    pub clip_count: i64,
+    pub duration_seconds: f64,
+    pub created_at: String,
+}
+
+const TIMELINE_SNAPSHOT_COLUMNS: &str = "id, project_id, version_id, content_hash, clip_count, duration_seconds, created_at";
+
+fn timeline_snapshot_from_row(row: &rusqlite::Row) -> rusqlite::Result<TimelineSnapshot> {
+    Ok(TimelineSnapshot {
+        id: row.get(0)?,
+        project_id: row.get(1)?,
+        version_id: row.get(2)?,
+        content_hash: row.get(3)?,
+        clip_count: row.get(4)?,
+        duration_seconds: row.get(5)?,
+        created_at: row.get(6)?,
+    })
+}
+
+/// A run of the daemon
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct DaemonSession {
+    pub id: i64,
+    pub started_at: String,
+    /// Last autosave tick; roughly when a crashed run stopped
+    pub last_seen_at: String,
+    pub ended_at: Option<String>,
+}
+
+impl Database {
+    /// Add a snapshot (tagged with the current version) and drop the project's oldest ones
+    /// beyond `keep`
+    pub fn insert_timeline_snapshot(
+        &self,
+        project_id: i64,
+        content_hash: &str,
+        timeline_json: &str,
+        clip_count: i64,
+        duration_seconds: f64,
+        keep: usize,
+    ) -> Result<i64> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        tx.execute(
+            "INSERT INTO timeline_snapshots (project_id, version_id, content_hash, json_blob, clip_count, duration_seconds, created_at)
+             VALUES (?1, (SELECT version_id FROM timeline_versions WHERE project_id = ?1 AND is_current = 1), ?2, ?3, ?4, ?5, ?6)",
+            params![project_id, content_hash, timeline_json, clip_count, duration_seconds, Utc::now().to_rfc3339()],
         )?;
-        let mut rows = stmt.query_map(params![id], |row| Project::from_row(row))?;
-        
-        match rows.next() {
-            Some(Ok(project)) => Ok(Some(project)),
-            Some(Err(e)) => Err(e.into()),
-            None => Ok(None),
+        let snapshot_id = tx.last_insert_rowid();
+        tx.execute(
+            "DELETE FROM timeline_snapshots WHERE project_id = ?1 AND id NOT IN
+                (SELECT id FROM timeline_snapshots WHERE project_id = ?1 ORDER BY id DESC LIMIT ?2)",
+            params![project_id, keep as i64],
+        )?;
+        tx.commit()?;
+        Ok(snapshot_id)
+    }
+
+    /// Newest first
+    pub fn list_timeline_snapshots(&self, project_id: i64) -> Result<Vec<TimelineSnapshot>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM timeline_snapshots WHERE project_id = ?1 ORDER BY id DESC",
+            TIMELINE_SNAPSHOT_COLUMNS
+        ))?;
+        let rows = stmt.query_map(params![project_id], timeline_snapshot_from_row)?;
+        Ok(rows.collect::<Result<Vec<_>, _>>()?)
+    }
+
+    pub fn latest_timeline_snapshot(&self, project_id: i64) -> Result<Option<TimelineSnapshot>> {
+        let conn = self.conn.lock().unwrap();
+        let result = conn.query_row(
+            &format!(
+                "SELECT {} FROM timeline_snapshots WHERE project_id = ?1 ORDER BY id DESC LIMIT 1",
+                TIMELINE_SNAPSHOT_COLUMNS
+            ),
+            params![project_id],
+            timeline_snapshot_from_row,
+        );
+        match result {
+            Ok(snapshot) => Ok(Some(snapshot)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
         }
     }
 
-    pub fn get_all_projects(&self) -> Result<Vec<Project>> {
+    /// A snapshot with its timeline JSON
+    pub fn get_timeline_snapshot(&self, project_id: i64, snapshot_id: i64) -> Result<Option<(TimelineSnapshot, String)>> {
         let conn = self.conn.lock().unwrap();
-        let mut stmt = conn.prepare(
-            "SELECT id, name, created_at, cache_dir, style_profile_id FROM projects ORDER BY created_at DESC"
+        let result = conn.query_row(
+            &format!(
+                "SELECT {}, json_blob FROM timeline_snapshots WHERE project_id = ?1 AND id = ?2",
+                TIMELINE_SNAPSHOT_COLUMNS
+            ),
+            params![project_id, snapshot_id],
+            |row| Ok((timeline_snapshot_from_row(row)?, row.get(7)?)),
+        );
+        match result {
+            Ok(snapshot) => Ok(Some(snapshot)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn begin_daemon_session(&self) -> Result<i64> {
+        let conn = self.conn.lock().unwrap();
+        let now = Utc::now().to_rfc3339();
+        conn.execute(
+            "INSERT INTO daemon_sessions (started_at, last_seen_at) VALUES (?1, ?1)",
+            params![now],
         )?;
-        let rows = stmt.query_map([], |row| Project::from_row(row))?;
-        
-        let mut projects = Vec::new();
-        for row in rows {
-            projects.push(row?);
+        Ok(conn.last_insert_rowid())
+    }
+
+    pub fn touch_daemon_session(&self, session_id: i64) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE daemon_sessions SET last_seen_at = ?1 WHERE id = ?2",
+            params![Utc::now().to_rfc3339(), session_id],
+        )?;
+        Ok(())
+    }
+
+    /// Record that the running session (the newest) shut down cleanly
+    pub fn end_daemon_session(&self) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE daemon_sessions SET ended_at = ?1 WHERE id = (SELECT MAX(id) FROM daemon_sessions)",
+            params![Utc::now().to_rfc3339()],
+        )?;
+        Ok(())
+    }
+
+    /// The run before the current one
+    pub fn previous_daemon_session(&self) -> Result<Option<DaemonSession>> {
+        let conn = self.conn.lock().unwrap();
+        let result = conn.query_row(
+            "SELECT id, started_at, last_seen_at, ended_at FROM daemon_sessions ORDER BY id DESC LIMIT 1 OFFSET 1",
+            [],
+            |row| {
+                Ok(DaemonSession {
+                    id: row.get(0)?,
+                    started_at: row.get(1)?,
+                    last_seen_at: row.get(2)?,
+                    ended_at: row.get(3)?,
+                })
+            },
+        );
+        match result {
+            Ok(session) => Ok(Some(session)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
         }
-        Ok(projects)
     }
+}
 
-    pub fn delete_project(&self, id: i64) -> Result<()> {
+/// A segment found by search across projects
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct GlobalSearchHit {
+    pub segment_id: i64,
+    pub project_id: i64,
+    pub project_name: String,
+    pub asset_id: i64,
+    pub asset_path: String,
+    pub src_in_ticks: Option<i64>,
+    pub src_out_ticks: Option<i64>,
+    pub capture_time: Option<String>,
+    pub summary_text: Option<String>,
+    /// Matching words in [brackets] with some context from the transcript or on-screen text
+    pub snippet: String,
+    /// 0..1, higher is better
+    pub score: f64,
+}
+
+/// FTS5 query matching any of the words in `query`. Each token is quoted so user input can't
+/// inject FTS syntax; empty when there's nothing to search for.
+fn fts_match_query(query: &str) -> String {
+    query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|t| t.len() > 1)
+        .map(|t| format!("\"{}\"", t.to_lowercase()))
+        .collect::<Vec<_>>()
+        .join(" OR ")
+}
+
+impl Database {
+    pub fn set_project_global_search(&self, project_id: i64, enabled: bool) -> Result<()> {
         let conn = self.conn.lock().unwrap();
-        conn.execute("DELETE FROM projects WHERE id = ?1", params![id])?;
+        conn.execute(
+            "UPDATE projects SET exclude_from_global_search = ?1 WHERE id = ?2",
+            params![!enabled, project_id],
+        )?;
         Ok(())
     }
 
-    pub fn create_media_asset(
-        &self,
-        project_id: i64,
-        path: &str,
-        checksum: Option<&str>,
-        duration_ticks: i64,
-        fps_num: i32,
-        fps_den: i32,
-        width: i32,
-        height: i32,
-        has_audio: bool,
-    ) -> Result<i64> {
-        self.create_media_asset_with_reference_flag(
-            project_id, path, checksum, duration_ticks, fps_num, fps_den, width, height, has_audio, false,
-        )
+    /// Whether the project's footage shows up in search across projects
+    pub fn project_in_global_search(&self, project_id: i64) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        let excluded: bool = conn.query_row(
+            "SELECT exclude_from_global_search FROM projects WHERE id = ?1",
+            params![project_id],
+            |row| row.get(0),
+        )?;
+        Ok(!excluded)
     }
-    
-    pub fn create_media_asset_with_reference_flag(
-        &self,
-        project_id: i64,
-        path: &str,
-        checksum: Option<&str>,
-        duration_ticks: i64,
-        fps_num: i32,
-        fps_den: i32,
-        width: i32,
-        height: i32,
-        has_audio: bool,
-        is_reference: bool,
-    ) -> Result<i64> {
+}
+
+impl Database {
+    /// Defer an asset's analysis behind other work: `rank` is its place in the bulk import's
+    /// analysis order (0 = first)
+    pub fn set_media_asset_import_rank(&self, media_asset_id: i64, rank: i64) -> Result<()> {
         let conn = self.conn.lock().unwrap();
-        
-        // Check if asset already exists for this project
-        let existing_id: Result<i64, rusqlite::Error> = conn.query_row(
-            "SELECT id FROM media_assets WHERE project_id = ?1 AND path = ?2",
+        conn.execute(
+            "UPDATE media_assets SET import_rank = ?1 WHERE id = ?2",
+            params![rank, media_asset_id],
+        )?;
+        Ok(())
+    }
+
+    /// Import rank of each deferred asset whose analysis hasn't finished (embedded or not)
+    pub fn get_media_asset_import_ranks(&self) -> Result<HashMap<i64, i64>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, import_rank FROM media_assets WHERE import_rank IS NOT NULL AND embeddings_ready_at IS NULL",
+        )?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        Ok(rows.collect::<Result<HashMap<_, _>, _>>()?)
+    }
+}
+
+impl Database {
+    /// Existing asset at `path` in the project, as (id, stored checksum)
+    pub fn find_media_asset_by_path(&self, project_id: i64, path: &str) -> Result<Option<(i64, Option<String>)>> {
+        let conn = self.conn.lock().unwrap();
+        match conn.query_row(
+            "SELECT id, checksum FROM media_assets WHERE project_id = ?1 AND path = ?2",
             params![project_id, path],
-            |row| row.get::<_, i64>(0),
-        );
-        
-        match existing_id {
-            Ok(id) => {
-                // Update existing asset
-                conn.execute(
-                    "UPDATE media_assets SET checksum = ?1, duration_ticks = ?2, fps_num = ?3, fps_den = ?4, width = ?5, height = ?6, has_audio = ?7, is_reference = ?8 WHERE id = ?9",
-                    params![checksum, duration_ticks, fps_num, fps_den, width, height, if has_audio { 1 } else { 0 }, if is_reference { 1 } else { 0 }, id],
-                )?;
-                Ok(id)
-            }
-            Err(rusqlite::Error::QueryReturnedNoRows) => {
-                // Insert new asset
-                conn.execute(
-                    "INSERT INTO media_assets (project_id, path, checksum, duration_ticks, fps_num, fps_den, width, height, has_audio, is_reference) 
-                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
-                    params![project_id, path, checksum, duration_ticks, fps_num, fps_den, width, height, if has_audio { 1 } else { 0 }, if is_reference { 1 } else { 0 }],
-                )?;
-                Ok(conn.last_insert_rowid())
-            }
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        ) {
+            Ok(asset) => Ok(Some(asset)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
             Err(e) => Err(e.into()),
         }
     }
 
-    pub fn create_proxy(
-        &self,
-        media_asset_id: i64,
-        path: &str,
-        codec: &str,
-        width: i32,
-        height: i32,
-    ) -> Result<i64> {
+    /// Point an asset at the new contents of its file: checksum and probed properties
+    pub fn update_media_asset_file(
+        &self,
+        media_asset_id: i64,
+        checksum: &str,
+        info: &crate::media::ffmpeg::MediaInfo,
+    ) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE media_assets SET checksum = ?1, duration_ticks = ?2, fps_num = ?3, fps_den = ?4,
+                    width = ?5, height = ?6, has_audio = ?7
+             WHERE id = ?8",
+            params![
+                checksum,
+                info.duration_ticks,
+                info.fps_num,
+                info.fps_den,
+                info.width,
+                info.height,
+                info.has_audio,
+                media_asset_id
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Move a segment's source range by `offset_ticks` (its content moved within the file),
+    /// clamped to the file's length
+    pub fn shift_segment(&self, segment_id: i64, offset_ticks: i64, duration_ticks: i64) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE segments SET
+                src_in_ticks = MAX(0, COALESCE(src_in_ticks, start_ticks) + ?1),
+                src_out_ticks = MIN(?2, COALESCE(src_out_ticks, end_ticks) + ?1),
+                start_ticks = MAX(0, start_ticks + ?1),
+                end_ticks = MIN(?2, end_ticks + ?1)
+             WHERE id = ?3",
+            params![offset_ticks, duration_ticks, segment_id],
+        )?;
+        Ok(())
+    }
+
+    /// Forget what was seen in a segment whose picture changed (vision fields, the summary and
+    /// keywords built on them, its embeddings, and redaction subjects found in it)
+    pub fn clear_segment_picture_analysis(&self, segment_id: i64) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE segments SET quality_json = NULL, subject_json = NULL, scene_json = NULL,
+                    shot_type = NULL, text_in_video = NULL, summary_text = NULL, keywords_json = NULL
+             WHERE id = ?1",
+            params![segment_id],
+        )?;
+        conn.execute("DELETE FROM embeddings WHERE segment_id = ?1", params![segment_id])?;
+        conn.execute("DELETE FROM redaction_subjects WHERE segment_id = ?1", params![segment_id])?;
+        Ok(())
+    }
+
+    /// Forget what was heard in a segment whose audio changed (transcript, speaker and language,
+    /// the summary and keywords built on them, and its embeddings)
+    pub fn clear_segment_audio_analysis(&self, segment_id: i64) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE segments SET transcript = NULL, speaker = NULL, language = NULL,
+                    summary_text = NULL, keywords_json = NULL
+             WHERE id = ?1",
+            params![segment_id],
+        )?;
+        conn.execute("DELETE FROM embeddings WHERE segment_id = ?1", params![segment_id])?;
+        Ok(())
+    }
+
+    /// Delete segments and everything hanging off them (embeddings, feedback, the text index,
+    /// collection membership, redaction subjects)
+    pub fn delete_segments(&self, segment_ids: &[i64]) -> Result<usize> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let mut removed = 0;
+        for segment_id in segment_ids {
+            tx.execute("DELETE FROM embeddings WHERE segment_id = ?1", params![segment_id])?;
+            tx.execute("DELETE FROM segment_feedback WHERE segment_id = ?1", params![segment_id])?;
+            tx.execute("DELETE FROM segment_text_fts WHERE segment_id = ?1", params![segment_id])?;
+            tx.execute("DELETE FROM collection_segments WHERE segment_id = ?1", params![segment_id])?;
+            tx.execute("DELETE FROM redaction_subjects WHERE segment_id = ?1", params![segment_id])?;
+            removed += tx.execute("DELETE FROM segments WHERE id = ?1", params![segment_id])?;
+        }
+        tx.commit()?;
+        Ok(removed)
+    }
+}
+
+impl Database {
+    /// Set a segment's source range (e.g. after moving its boundaries between words)
+    pub fn set_segment_source_range(&self, segment_id: i64, src_in_ticks: i64, src_out_ticks: i64) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE segments SET src_in_ticks = ?1, src_out_ticks = ?2, start_ticks = ?1, end_ticks = ?2 WHERE id = ?3",
+            params![src_in_ticks, src_out_ticks, segment_id],
+        )?;
+        Ok(())
+    }
+}
+
+impl Database {
+    /// Thumbnail counts of an asset's extracted pyramid levels, keyed by interval in ms
+    pub fn get_thumbnail_levels(&self, media_asset_id: i64) -> Result<std::collections::BTreeMap<i64, i64>> {
+        let conn = self.conn.lock().unwrap();
+        let json = match conn.query_row(
+            "SELECT thumbnail_levels_json FROM media_assets WHERE id = ?1",
+            params![media_asset_id],
+            |row| row.get::<_, Option<String>>(0),
+        ) {
+            Ok(json) => json,
+            Err(rusqlite::Error::QueryReturnedNoRows) => None,
+            Err(e) => return Err(e.into()),
+        };
+        Ok(json
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default())
+    }
+
+    /// Record how many thumbnails a pyramid level has
+    pub fn set_thumbnail_level(&self, media_asset_id: i64, interval_ms: i64, count: i64) -> Result<()> {
+        let mut levels = self.get_thumbnail_levels(media_asset_id)?;
+        levels.insert(interval_ms, count);
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE media_assets SET thumbnail_levels_json = ?1 WHERE id = ?2",
+            params![serde_json::to_string(&levels)?, media_asset_id],
+        )?;
+        Ok(())
+    }
+
+    /// Forget an asset's pyramid levels (its thumbnails were removed)
+    pub fn clear_thumbnail_levels(&self, media_asset_id: i64) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE media_assets SET thumbnail_levels_json = NULL WHERE id = ?1",
+            params![media_asset_id],
+        )?;
+        Ok(())
+    }
+}
+
+/// Narration attached to a project: a recording, or speech synthesized for it
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Voiceover {
+    pub id: i64,
+    pub project_id: i64,
+    pub path: String,
+    /// "recording" or "tts"
+    pub source: String,
+    /// Known once it's been transcribed
+    pub duration_ticks: Option<i64>,
+    pub transcribed: bool,
+    pub created_at: String,
+}
+
+const VOICEOVER_COLUMNS: &str =
+    "id, project_id, path, source, duration_ticks, transcript_json IS NOT NULL, created_at";
+
+fn voiceover_from_row(row: &rusqlite::Row) -> rusqlite::Result<Voiceover> {
+    Ok(Voiceover {
+        id: row.get(0)?,
+        project_id: row.get(1)?,
+        path: row.get(2)?,
+        source: row.get(3)?,
+        duration_ticks: row.get(4)?,
+        transcribed: row.get(5)?,
+        created_at: row.get(6)?,
+    })
+}
+
+impl Database {
+    pub fn create_voiceover(&self, project_id: i64, path: &str, source: &str) -> Result<i64> {
         let conn = self.conn.lock().unwrap();
         conn.execute(
-            "INSERT INTO proxies (media_asset_id, path, codec, width, height) VALUES (?1, ?2, ?3, ?4, ?5)",
-            params![media_asset_id, path, codec, width, height],
+            "INSERT INTO voiceovers (project_id, path, source, created_at) VALUES (?1, ?2, ?3, ?4)",
+            params![project_id, path, source, Utc::now().to_rfc3339()],
         )?;
         Ok(conn.last_insert_rowid())
     }
 
-    pub fn create_style_profile(&self, name: &str, json_blob: &str) -> Result<i64> {
-        let now = Utc::now().to_rfc3339();
+    pub fn get_voiceover(&self, voiceover_id: i64) -> Result<Option<Voiceover>> {
+        let conn = self.conn.lock().unwrap();
+        let result = conn.query_row(
+            &format!("SELECT {} FROM voiceovers WHERE id = ?1", VOICEOVER_COLUMNS),
+            params![voiceover_id],
+            voiceover_from_row,
+        );
+        match result {
+            Ok(voiceover) => Ok(Some(voiceover)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn list_voiceovers(&self, project_id: i64) -> Result<Vec<Voiceover>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM voiceovers WHERE project_id = ?1 ORDER BY id",
+            VOICEOVER_COLUMNS
+        ))?;
+        let voiceovers = stmt
+            .query_map(params![project_id], voiceover_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(voiceovers)
+    }
+
+    /// Store a voiceover's transcript (encrypted if its project is) and its length
+    pub fn set_voiceover_transcript(&self, voiceover_id: i64, duration_ticks: i64, transcript_json: &str) -> Result<()> {
+        let project_id = self
+            .get_voiceover(voiceover_id)?
+            .ok_or_else(|| anyhow::anyhow!("Voiceover {} not found", voiceover_id))?
+            .project_id;
+        let transcript_json = match self.project_key(project_id)? {
+            Some(key) => crypto::encrypt_field(&key, transcript_json)?,
+            None => transcript_json.to_string(),
+        };
         let conn = self.conn.lock().unwrap();
         conn.execute(
-            "INSERT INTO style_profiles (name, json_blob, created_at) VALUES (?1, ?2, ?3)",
-            params![name, json_blob, now],
+            "UPDATE voiceovers SET duration_ticks = ?1, transcript_json = ?2 WHERE id = ?3",
+            params![duration_ticks, transcript_json, voiceover_id],
         )?;
-        Ok(conn.last_insert_rowid())
+        Ok(())
     }
 
-    pub fn get_style_profile(&self, id: i64) -> Result<Option<String>> {
-        let conn = self.conn.lock().unwrap();
-        let mut stmt = conn.prepare("SELECT json_blob FROM style_profiles WHERE id = ?1")?;
-        let mut rows = stmt.query_map(params![id], |row| {
-            Ok(row.get::<_, String>(0)?)
-        })?;
-        
-        match rows.next() {
-            Some(Ok(blob)) => Ok(Some(blob)),
-            Some(Err(e)) => Err(e.into()),
-            None => Ok(None),
+    /// A voiceover's transcript (decrypted), once it's been transcribed
+    pub fn get_voiceover_transcript(&self, voiceover_id: i64) -> Result<Option<String>> {
+        let row = {
+            let conn = self.conn.lock().unwrap();
+            conn.query_row(
+                "SELECT project_id, transcript_json FROM voiceovers WHERE id = ?1",
+                params![voiceover_id],
+                |row| Ok((row.get::<_, i64>(0)?, row.get::<_, Option<String>>(1)?)),
+            )
+        };
+        let (project_id, json) = match row {
+            Ok((project_id, Some(json))) => (project_id, json),
+            Ok((_, None)) | Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        if !crypto::is_encrypted_field(&json) {
+            return Ok(Some(json));
         }
+        let key = self
+            .project_key(project_id)?
+            .ok_or_else(|| anyhow::anyhow!("Voiceover {} has encrypted data but its project has no key", voiceover_id))?;
+        crypto::decrypt_field(&key, &json).map(Some)
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct Segment {
+/// A track in the music catalog
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct MusicTrack {
     pub id: i64,
-    pub media_asset_id: i64,
-    pub project_id: i64,
-    pub start_ticks: i64,
-    pub end_ticks: i64,
-    pub src_in_ticks: Option<i64>,
-    pub src_out_ticks: Option<i64>,
-    pub segment_kind: Option<String>,
-    pub summary_text: Option<String>,
-    pub keywords_json: Option<String>,
-    pub quality_json: Option<String>,
-    pub subject_json: Option<String>,
-    pub scene_json: Option<String>,
-    pub capture_time: Option<String>,
-    pub transcript: Option<String>,
-    pub speaker: Option<String>,
+    pub title: String,
+    pub artist: Option<String>,
+    pub path: String,
+    /// "local", or the music service it came from
+    pub source: String,
+    /// The track's id in its music service
+    pub external_id: Option<String>,
+    pub bpm: Option<f64>,
+    pub moods: Vec<String>,
+    pub duration_ticks: Option<i64>,
+    /// License name (e.g. "CC BY 4.0" or a service's standard license)
+    pub license: Option<String>,
+    pub license_url: Option<String>,
+    /// Credit line the license asks for, when it asks for one
+    pub attribution: Option<String>,
+    /// Whether its structure has been analyzed
+    pub analyzed: bool,
+    pub created_at: String,
 }
 
-#[derive(Debug, Clone)]
-pub struct MediaAssetInfo {
-    pub id: i64,
+/// A track to add to the music catalog
+#[derive(Debug, Clone, Default)]
+pub struct NewMusicTrack {
+    pub title: String,
+    pub artist: Option<String>,
     pub path: String,
-    pub duration_ticks: i64,
-    pub fps_num: i32,
-    pub fps_den: i32,
-    pub width: i32,
-    pub height: i32,
+    pub source: String,
+    pub external_id: Option<String>,
+    pub bpm: Option<f64>,
+    pub moods: Vec<String>,
+    pub duration_ticks: Option<i64>,
+    pub license: Option<String>,
+    pub license_url: Option<String>,
+    pub attribution: Option<String>,
+}
+
+const MUSIC_TRACK_COLUMNS: &str = "id, title, artist, path, source, external_id, bpm, moods_json, duration_ticks, \
+     license, license_url, attribution, created_at, structure_json IS NOT NULL";
+
+fn music_track_from_row(row: &rusqlite::Row) -> rusqlite::Result<MusicTrack> {
+    let moods_json: String = row.get(7)?;
+    Ok(MusicTrack {
+        id: row.get(0)?,
+        title: row.get(1)?,
+        artist: row.get(2)?,
+        path: row.get(3)?,
+        source: row.get(4)?,
+        external_id: row.get(5)?,
+        bpm: row.get(6)?,
+        moods: serde_json::from_str(&moods_json).unwrap_or_default(),
+        duration_ticks: row.get(8)?,
+        license: row.get(9)?,
+        license_url: row.get(10)?,
+        attribution: row.get(11)?,
+        created_at: row.get(12)?,
+        analyzed: row.get(13)?,
+    })
 }
 
 impl Database {
-    /// Get all segments with their media asset info for a project
-    pub fn get_segments_for_project(&self, project_id: i64) -> Result<Vec<(Segment, MediaAssetInfo)>> {
+    /// Add a track to the music catalog; a track already registered at the same path is updated
+    pub fn upsert_music_track(&self, track: &NewMusicTrack) -> Result<i64> {
         let conn = self.conn.lock().unwrap();
-        
-        // Join segments with media_assets to get full info, filter by project_id
-        let mut stmt = conn.prepare(
-            "SELECT s.id, s.media_asset_id, s.project_id, s.start_ticks, s.end_ticks, 
-                    s.src_in_ticks, s.src_out_ticks, s.segment_kind, s.summary_text, 
-                    s.keywords_json, s.quality_json, s.subject_json, s.scene_json, 
-                    s.capture_time, s.transcript, s.speaker,
-                    ma.id, ma.path, ma.duration_ticks, ma.fps_num, ma.fps_den, ma.width, ma.height
-             FROM segments s
-             INNER JOIN media_assets ma ON s.media_asset_id = ma.id
-             WHERE s.project_id = ?1
-             ORDER BY ma.id, s.start_ticks"
+        conn.execute(
+            "INSERT INTO music_tracks (title, artist, path, source, external_id, bpm, moods_json, duration_ticks,
+                                       license, license_url, attribution, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
+             ON CONFLICT(path) DO UPDATE SET
+                title = excluded.title, artist = excluded.artist, source = excluded.source,
+                external_id = excluded.external_id, bpm = excluded.bpm, moods_json = excluded.moods_json,
+                duration_ticks = excluded.duration_ticks, license = excluded.license,
+                license_url = excluded.license_url, attribution = excluded.attribution",
+            params![
+                track.title,
+                track.artist,
+                track.path,
+                track.source,
+                track.external_id,
+                track.bpm,
+                serde_json::to_string(&track.moods)?,
+                track.duration_ticks,
+                track.license,
+                track.license_url,
+                track.attribution,
+                Utc::now().to_rfc3339(),
+            ],
         )?;
-        
-        let rows = stmt.query_map(params![project_id], |row| {
-            let segment = Segment {
-                id: row.get(0)?,
-                media_asset_id: row.get(1)?,
-                project_id: row.get(2)?,
-                start_ticks: row.get(3)?,
-                end_ticks: row.get(4)?,
-                src_in_ticks: row.get(5)?,
-                src_out_ticks: row.get(6)?,
-                segment_kind: row.get(7)?,
-                summary_text: row.get(8)?,
-                keywords_json: row.get(9)?,
-                quality_json: row.get(10)?,
-                subject_json: row.get(11)?,
-                scene_json: row.get(12)?,
-                capture_time: row.get(13)?,
-                transcript: row.get(14)?,
-                speaker: row.get(15)?,
-            };
-            
-            let media_asset = MediaAssetInfo {
-                id: row.get(16)?,
-                path: row.get(17)?,
-                duration_ticks: row.get(18)?,
-                fps_num: row.get(19)?,
-                fps_den: row.get(20)?,
-                width: row.get(21)?,
-                height: row.get(22)?,
-            };
-            
-            Ok((segment, media_asset))
-        })?;
-        
-        let mut result = Vec::new();
-        for row in rows {
-            result.push(row?);
-        }
-        
-        Ok(result)
+        Ok(conn.query_row("SELECT id FROM music_tracks WHERE path = ?1", params![track.path], |row| row.get(0))?)
     }
 
-    /// Get coalesced src_in_ticks (single source of truth for reading)
-    pub fn get_coalesced_src_in(segment: &Segment) -> i64 {
-        segment.src_in_ticks.unwrap_or(segment.start_ticks)
+    pub fn get_music_track(&self, track_id: i64) -> Result<Option<MusicTrack>> {
+        self.find_music_track("id = ?1", params![track_id])
     }
 
-    /// Get coalesced src_out_ticks (single source of truth for reading)
-    pub fn get_coalesced_src_out(segment: &Segment) -> i64 {
-        segment.src_out_ticks.unwrap_or(segment.end_ticks)
+    /// The catalog track registered at a path, e.g. one a timeline's music plays
+    pub fn get_music_track_by_path(&self, path: &str) -> Result<Option<MusicTrack>> {
+        self.find_music_track("path = ?1", params![path])
     }
 
-    /// Create a new segment with stable identity fields
-    pub fn create_segment(
-        &self,
-        project_id: i64,
-        media_asset_id: i64,
-        src_in_ticks: i64,
-        src_out_ticks: i64,
-    ) -> Result<i64> {
+    fn find_music_track(&self, condition: &str, params: &[&dyn rusqlite::ToSql]) -> Result<Option<MusicTrack>> {
+        let conn = self.conn.lock().unwrap();
+        let result = conn.query_row(
+            &format!("SELECT {} FROM music_tracks WHERE {}", MUSIC_TRACK_COLUMNS, condition),
+            params,
+            music_track_from_row,
+        );
+        match result {
+            Ok(track) => Ok(Some(track)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn list_music_tracks(&self) -> Result<Vec<MusicTrack>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!("SELECT {} FROM music_tracks ORDER BY title, id", MUSIC_TRACK_COLUMNS))?;
+        let tracks = stmt
+            .query_map([], music_track_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(tracks)
+    }
+
+    /// Store a track's analyzed structure
+    pub fn set_music_structure(&self, track_id: i64, structure_json: &str) -> Result<()> {
         let conn = self.conn.lock().unwrap();
         conn.execute(
-            "INSERT INTO segments (project_id, media_asset_id, src_in_ticks, src_out_ticks, start_ticks, end_ticks) 
-             VALUES (?1, ?2, ?3, ?4, ?3, ?4)",
-            params![project_id, media_asset_id, src_in_ticks, src_out_ticks],
+            "UPDATE music_tracks SET structure_json = ?1 WHERE id = ?2",
+            params![structure_json, track_id],
         )?;
-        Ok(conn.last_insert_rowid())
+        Ok(())
     }
 
-    /// Get or create a dynamic segment (idempotent via dedupe_key)
-    pub fn get_or_create_dynamic_segment(
-        &self,
-        asset_id: i64,
-        project_id: i64,
-        start_ticks: i64,
-        end_ticks: i64,
-        dedupe_key: &str,
-        external_source: &str,
-        external_ref: &str,
-    ) -> Result<i64> {
+    /// A track's analyzed structure (None until it's been analyzed)
+    pub fn get_music_structure(&self, track_id: i64) -> Result<Option<String>> {
         let conn = self.conn.lock().unwrap();
-        
-        // Check if segment with dedupe_key exists
-        let existing_id: Result<i64, rusqlite::Error> = conn.query_row(
-            "SELECT id FROM segments WHERE dedupe_key = ?1",
-            params![dedupe_key],
-            |row| row.get(0),
+        let result = conn.query_row(
+            "SELECT structure_json FROM music_tracks WHERE id = ?1",
+            params![track_id],
+            |row| row.get::<_, Option<String>>(0),
         );
-        
-        match existing_id {
-            Ok(segment_id) => Ok(segment_id),
-            Err(rusqlite::Error::QueryReturnedNoRows) => {
-                // Create new segment
-                conn.execute(
-                    "INSERT INTO segments (
-                        project_id, media_asset_id, 
-                        src_in_ticks, src_out_ticks, start_ticks, end_ticks,
-                        segment_kind, dedupe_key, external_source, external_ref
-                    ) VALUES (?1, ?2, ?3, ?4, ?3, ?4, ?5, ?6, ?7, ?8)",
-                    params![
-                        project_id, asset_id,
-                        start_ticks, end_ticks,
-                        "twelvelabs_dynamic", dedupe_key, external_source, external_ref
-                    ],
-                )?;
-                Ok(conn.last_insert_rowid())
-            }
+        match result {
+            Ok(json) => Ok(json),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
             Err(e) => Err(e.into()),
         }
     }
 
-    /// Update segment metadata fields (enrichable fields)
-    pub fn update_segment_metadata(
-        &self,
-        segment_id: i64,
-        summary_text: Option<&str>,
-        keywords_json: Option<&str>,
-        quality_json: Option<&str>,
-        subject_json: Option<&str>,
-        scene_json: Option<&str>,
-        transcript: Option<&str>,
-        segment_kind: Option<&str>,
-    ) -> Result<()> {
+    /// Remove a track from the catalog; returns whether it was there. Timelines that use it keep
+    /// playing the file but lose its credit.
+    pub fn delete_music_track(&self, track_id: i64) -> Result<bool> {
         let conn = self.conn.lock().unwrap();
-        conn.execute(
-            "UPDATE segments SET 
-                summary_text = COALESCE(?1, summary_text),
-                keywords_json = COALESCE(?2, keywords_json),
-                quality_json = COALESCE(?3, quality_json),
-                subject_json = COALESCE(?4, subject_json),
-                scene_json = COALESCE(?5, scene_json),
-                transcript = COALESCE(?6, transcript),
-                segment_kind = COALESCE(?7, segment_kind)
-             WHERE id = ?8",
-            params![summary_text, keywords_json, quality_json, subject_json, scene_json, transcript, segment_kind, segment_id],
-        )?;
-        Ok(())
+        let deleted = conn.execute("DELETE FROM music_tracks WHERE id = ?1", params![track_id])?;
+        Ok(deleted > 0)
     }
+}
 
-    /// Get segments for a specific asset
-    pub fn get_segments_by_asset(&self, asset_id: i64) -> Result<Vec<Segment>> {
+impl Database {
+    /// All end cards, built-ins first
+    pub fn list_end_cards(&self) -> Result<Vec<EndCardRow>> {
         let conn = self.conn.lock().unwrap();
         let mut stmt = conn.prepare(
-            "SELECT id, media_asset_id, project_id, start_ticks, end_ticks, 
-                    src_in_ticks, src_out_ticks, segment_kind, summary_text, 
-                    keywords_json, quality_json, subject_json, scene_json, 
-                    capture_time, transcript, speaker
-             FROM segments
-             WHERE media_asset_id = ?1
-             ORDER BY start_ticks"
+            "SELECT id, builtin_key, card_json, created_at FROM end_cards
+             ORDER BY builtin_key IS NULL, id"
         )?;
-        
-        let rows = stmt.query_map(params![asset_id], |row| {
-            Ok(Segment {
-                id: row.get(0)?,
-                media_asset_id: row.get(1)?,
-                project_id: row.get(2)?,
-                start_ticks: row.get(3)?,
-                end_ticks: row.get(4)?,
-                src_in_ticks: row.get(5)?,
-                src_out_ticks: row.get(6)?,
-                segment_kind: row.get(7)?,
-                summary_text: row.get(8)?,
-                keywords_json: row.get(9)?,
-                quality_json: row.get(10)?,
-                subject_json: row.get(11)?,
-                scene_json: row.get(12)?,
-                capture_time: row.get(13)?,
-                transcript: row.get(14)?,
-                speaker: row.get(15)?,
-            })
-        })?;
-        
-        let mut segments = Vec::new();
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))?;
+
+        let mut cards = Vec::new();
         for row in rows {
-            segments.push(row?);
+            cards.push(row?);
+        }
+        Ok(cards)
+    }
+
+    /// One end card as (builtin_key, card_json, created_at)
+    pub fn get_end_card(&self, end_card_id: i64) -> Result<Option<(Option<String>, String, String)>> {
+        let conn = self.conn.lock().unwrap();
+        match conn.query_row(
+            "SELECT builtin_key, card_json, created_at FROM end_cards WHERE id = ?1",
+            params![end_card_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        ) {
+            Ok(card) => Ok(Some(card)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn create_end_card(&self, name: &str, card_json: &str) -> Result<i64> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO end_cards (name, card_json, created_at) VALUES (?1, ?2, ?3)",
+            params![name, card_json, Utc::now().to_rfc3339()],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Delete a user-created end card (built-ins can't be deleted); returns whether one was
+    pub fn delete_end_card(&self, end_card_id: i64) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        let deleted = conn.execute(
+            "DELETE FROM end_cards WHERE id = ?1 AND builtin_key IS NULL",
+            params![end_card_id],
+        )?;
+        Ok(deleted > 0)
+    }
+
+    /// The project's end card settings JSON (None = exports don't get an end card)
+    pub fn get_project_end_card(&self, project_id: i64) -> Result<Option<String>> {
+        let conn = self.conn.lock().unwrap();
+        let result = conn.query_row(
+            "SELECT end_card_json FROM projects WHERE id = ?1",
+            params![project_id],
+            |row| row.get::<_, Option<String>>(0),
+        );
+        match result {
+            Ok(end_card) => Ok(end_card),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
         }
-        Ok(segments)
     }
 
-    /// Get segment with its embeddings
-    pub fn get_segment_with_embeddings(&self, segment_id: i64) -> Result<Option<(Segment, Vec<(String, String, Vec<u8>)>)>> {
+    /// Set (or clear with None) the project's end card settings JSON
+    pub fn set_project_end_card(&self, project_id: i64, end_card_json: Option<&str>) -> Result<()> {
         let conn = self.conn.lock().unwrap();
-        
-        // Get segment
-        let mut stmt = conn.prepare(
-            "SELECT id, media_asset_id, project_id, start_ticks, end_ticks, 
-                    src_in_ticks, src_out_ticks, segment_kind, summary_text, 
-                    keywords_json, quality_json, subject_json, scene_json, 
-                    capture_time, transcript, speaker
-             FROM segments
-             WHERE id = ?1"
+        conn.execute(
+            "UPDATE projects SET end_card_json = ?1 WHERE id = ?2",
+            params![end_card_json, project_id],
         )?;
-        
-        let segment_opt: Option<Segment> = stmt.query_row(params![segment_id], |row| {
-            Ok(Segment {
-                id: row.get(0)?,
-                media_asset_id: row.get(1)?,
-                project_id: row.get(2)?,
-                start_ticks: row.get(3)?,
-                end_ticks: row.get(4)?,
-                src_in_ticks: row.get(5)?,
-                src_out_ticks: row.get(6)?,
-                segment_kind: row.get(7)?,
-                summary_text: row.get(8)?,
-                keywords_json: row.get(9)?,
-                quality_json: row.get(10)?,
-                subject_json: row.get(11)?,
-                scene_json: row.get(12)?,
-                capture_time: row.get(13)?,
-                transcript: row.get(14)?,
-                speaker: row.get(15)?,
-            })
-        }).ok();
-        
-        if let Some(segment) = segment_opt {
-            // Get embeddings
-            let mut emb_stmt = conn.prepare(
-                "SELECT embedding_type, model_name, vector_blob
-                 FROM embeddings
-                 WHERE segment_id = ?1"
-            )?;
-            
-            let emb_rows = emb_stmt.query_map(params![segment_id], |row| {
-                Ok((
-                    row.get::<_, String>(0)?,
-                    row.get::<_, String>(1)?,
-                    row.get::<_, Vec<u8>>(2)?,
-                ))
-            })?;
-            
-            let mut embeddings = Vec::new();
-            for row in emb_rows {
-                embeddings.push(row?);
-            }
-            
-            Ok(Some((segment, embeddings)))
-        } else {
-            Ok(None)
-        }
+        Ok(())
     }
+}
 
-    /// Update asset analysis state timestamp
-    pub fn update_asset_analysis_state(
-        &self,
-        asset_id: i64,
-        field: &str,
-        timestamp: Option<&str>,
-    ) -> Result<()> {
+/// A logo or font in a project's brand kit
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct BrandAsset {
+    pub id: i64,
+    pub project_id: i64,
+    /// "logo" or "font"
+    pub kind: String,
+    pub name: String,
+    /// The copy in the project cache
+    pub path: String,
+    pub checksum: String,
+    /// Logo size in pixels
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    /// Whether the logo has an alpha channel
+    pub has_alpha: bool,
+    /// Color keyed out of a logo without transparency
+    pub key_color: Option<String>,
+    /// Family name the font declares
+    pub font_family: Option<String>,
+    /// Embedding rights the font declares ("installable", "editable", "preview_print" or
+    /// "restricted")
+    pub embedding: Option<String>,
+    /// Marked by the user as licensed for use in exports
+    pub licensed: bool,
+    pub created_at: String,
+}
+
+/// A logo or font to add to a project's brand kit
+#[derive(Debug, Clone, Default)]
+pub struct NewBrandAsset {
+    pub project_id: i64,
+    pub kind: String,
+    pub name: String,
+    pub path: String,
+    pub checksum: String,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    pub has_alpha: bool,
+    pub key_color: Option<String>,
+    pub font_family: Option<String>,
+    pub embedding: Option<String>,
+    pub licensed: bool,
+}
+
+const BRAND_ASSET_COLUMNS: &str = "id, project_id, kind, name, path, checksum, width, height, has_alpha, key_color, \
+     font_family, embedding, licensed, created_at";
+
+fn brand_asset_from_row(row: &rusqlite::Row) -> rusqlite::Result<BrandAsset> {
+    Ok(BrandAsset {
+        id: row.get(0)?,
+        project_id: row.get(1)?,
+        kind: row.get(2)?,
+        name: row.get(3)?,
+        path: row.get(4)?,
+        checksum: row.get(5)?,
+        width: row.get(6)?,
+        height: row.get(7)?,
+        has_alpha: row.get(8)?,
+        key_color: row.get(9)?,
+        font_family: row.get(10)?,
+        embedding: row.get(11)?,
+        licensed: row.get(12)?,
+        created_at: row.get(13)?,
+    })
+}
+
+impl Database {
+    pub fn create_brand_asset(&self, asset: &NewBrandAsset) -> Result<i64> {
         let conn = self.conn.lock().unwrap();
-        let timestamp_str = timestamp.map(|s| s.to_string()).unwrap_or_else(|| {
-            Utc::now().to_rfc3339()
-        });
-        
-        match field {
-            "segments_built_at" => {
-                conn.execute(
-                    "UPDATE media_assets SET segments_built_at = ?1 WHERE id = ?2",
-                    params![timestamp_str, asset_id],
-                )?;
-            }
-            "transcript_ready_at" => {
-                conn.execute(
-                    "UPDATE media_assets SET transcript_ready_at = ?1 WHERE id = ?2",
-                    params![timestamp_str, asset_id],
-                )?;
-            }
-            "vision_ready_at" => {
-                conn.execute(
-                    "UPDATE media_assets SET vision_ready_at = ?1 WHERE id = ?2",
-                    params![timestamp_str, asset_id],
-                )?;
-            }
-            "metadata_ready_at" => {
-                conn.execute(
-                    "UPDATE media_assets SET metadata_ready_at = ?1 WHERE id = ?2",
-                    params![timestamp_str, asset_id],
-                )?;
-            }
-            "embeddings_ready_at" => {
-                conn.execute(
-                    "UPDATE media_assets SET embeddings_ready_at = ?1 WHERE id = ?2",
-                    params![timestamp_str, asset_id],
-                )?;
-            }
-            _ => return Err(anyhow::anyhow!("Unknown analysis state field: {}", field)),
+        conn.execute(
+            "INSERT INTO brand_assets (project_id, kind, name, path, checksum, width, height, has_alpha, key_color,
+                                       font_family, embedding, licensed, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+            params![
+                asset.project_id,
+                asset.kind,
+                asset.name,
+                asset.path,
+                asset.checksum,
+                asset.width,
+                asset.height,
+                asset.has_alpha,
+                asset.key_color,
+                asset.font_family,
+                asset.embedding,
+                asset.licensed,
+                Utc::now().to_rfc3339(),
+            ],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    pub fn get_brand_asset(&self, asset_id: i64) -> Result<Option<BrandAsset>> {
+        let conn = self.conn.lock().unwrap();
+        let result = conn.query_row(
+            &format!("SELECT {} FROM brand_assets WHERE id = ?1", BRAND_ASSET_COLUMNS),
+            params![asset_id],
+            brand_asset_from_row,
+        );
+        match result {
+            Ok(asset) => Ok(Some(asset)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
         }
+    }
+
+    /// A project's logos and fonts, oldest first
+    pub fn list_brand_assets(&self, project_id: i64) -> Result<Vec<BrandAsset>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM brand_assets WHERE project_id = ?1 ORDER BY id",
+            BRAND_ASSET_COLUMNS
+        ))?;
+        let assets = stmt
+            .query_map(params![project_id], brand_asset_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(assets)
+    }
+
+    /// Change whether an asset is licensed for export and the color keyed out of it
+    pub fn update_brand_asset(&self, asset_id: i64, licensed: bool, key_color: Option<&str>) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE brand_assets SET licensed = ?1, key_color = ?2 WHERE id = ?3",
+            params![licensed, key_color, asset_id],
+        )?;
         Ok(())
     }
 
-    /// Check if asset prerequisites are ready for job gating
-    pub fn check_asset_prerequisites(
-        &self,
-        asset_id: i64,
-        required_states: &[&str],
-    ) -> Result<bool> {
+    /// Remove an asset from its brand kit; returns whether it was there
+    pub fn delete_brand_asset(&self, asset_id: i64) -> Result<bool> {
         let conn = self.conn.lock().unwrap();
-        
-        for state in required_states {
-            let column = match *state {
-                "segments_built" => "segments_built_at",
-                "transcript_ready" => "transcript_ready_at",
-                "vision_ready" => "vision_ready_at",
-                "metadata_ready" => "metadata_ready_at",
-                "embeddings_ready" => "embeddings_ready_at",
-                _ => return Err(anyhow::anyhow!("Unknown state: {}", state)),
-            };
-            
-            let is_ready: bool = conn.query_row(
-                &format!("SELECT {} IS NOT NULL FROM media_assets WHERE id = ?1", column),
-                params![asset_id],
-                |row| row.get(0),
-            )?;
-            
-            if !is_ready {
-                return Ok(false);
-            }
+        let deleted = conn.execute("DELETE FROM brand_assets WHERE id = ?1", params![asset_id])?;
+        Ok(deleted > 0)
+    }
+
+    /// The project's brand kit JSON (None = no brand kit)
+    pub fn get_project_brand_kit(&self, project_id: i64) -> Result<Option<String>> {
+        let conn = self.conn.lock().unwrap();
+        let result = conn.query_row(
+            "SELECT brand_kit_json FROM projects WHERE id = ?1",
+            params![project_id],
+            |row| row.get::<_, Option<String>>(0),
+        );
+        match result {
+            Ok(kit) => Ok(kit),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
         }
-        
-        Ok(true)
     }
 
-    pub fn get_media_asset(&self, asset_id: i64) -> Result<Option<MediaAssetInfo>> {
+    /// Set (or clear with None) the project's brand kit JSON
+    pub fn set_project_brand_kit(&self, project_id: i64, brand_kit_json: Option<&str>) -> Result<()> {
         let conn = self.conn.lock().unwrap();
-        let mut stmt = conn.prepare(
-            "SELECT id, path, duration_ticks, fps_num, fps_den, width, height
-             FROM media_assets
-             WHERE id = ?1"
+        conn.execute(
+            "UPDATE projects SET brand_kit_json = ?1 WHERE id = ?2",
+            params![brand_kit_json, project_id],
         )?;
-        
-        let mut rows = stmt.query_map(params![asset_id], |row| {
-            Ok(MediaAssetInfo {
-                id: row.get(0)?,
-                path: row.get(1)?,
-                duration_ticks: row.get(2)?,
-                fps_num: row.get(3)?,
-                fps_den: row.get(4)?,
-                width: row.get(5)?,
-                height: row.get(6)?,
-            })
-        })?;
-        
-        match rows.next() {
-            Some(Ok(asset)) => Ok(Some(asset)),
-            Some(Err(e)) => Err(e.into()),
-            None => Ok(None),
-        }
+        Ok(())
     }
+}
 
-    pub fn get_media_assets_for_project(&self, project_id: i64) -> Result<Vec<MediaAssetInfo>> {
+/// A diarized speaker label in one asset
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct SpeakerRef {
+    pub asset_id: i64,
+    pub speaker: String,
+}
+
+/// Someone in a project's footage, and the speakers diarization heard them as
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Person {
+    pub id: i64,
+    pub project_id: i64,
+    pub name: String,
+    pub role: Option<String>,
+    pub speakers: Vec<SpeakerRef>,
+    pub created_at: String,
+}
+
+const PERSON_COLUMNS: &str = "id, project_id, name, role, speakers_json, created_at";
+
+fn person_from_row(row: &rusqlite::Row) -> rusqlite::Result<Person> {
+    let speakers_json: String = row.get(4)?;
+    Ok(Person {
+        id: row.get(0)?,
+        project_id: row.get(1)?,
+        name: row.get(2)?,
+        role: row.get(3)?,
+        speakers: serde_json::from_str(&speakers_json).unwrap_or_default(),
+        created_at: row.get(5)?,
+    })
+}
+
+/// A lower third naming a person where they first speak
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct LowerThird {
+    pub id: i64,
+    pub project_id: i64,
+    pub person_id: i64,
+    /// "suggested", "accepted" or "dismissed"
+    pub status: String,
+    pub start_ticks: i64,
+    pub end_ticks: i64,
+    /// The title on the timeline, once accepted
+    pub title_id: Option<String>,
+    pub created_at: String,
+}
+
+const LOWER_THIRD_COLUMNS: &str = "id, project_id, person_id, status, start_ticks, end_ticks, title_id, created_at";
+
+fn lower_third_from_row(row: &rusqlite::Row) -> rusqlite::Result<LowerThird> {
+    Ok(LowerThird {
+        id: row.get(0)?,
+        project_id: row.get(1)?,
+        person_id: row.get(2)?,
+        status: row.get(3)?,
+        start_ticks: row.get(4)?,
+        end_ticks: row.get(5)?,
+        title_id: row.get(6)?,
+        created_at: row.get(7)?,
+    })
+}
+
+impl Database {
+    pub fn create_person(&self, project_id: i64, name: &str, role: Option<&str>, speakers: &[SpeakerRef]) -> Result<i64> {
         let conn = self.conn.lock().unwrap();
-        let mut stmt = conn.prepare(
-            "SELECT id, path, duration_ticks, fps_num, fps_den, width, height
-             FROM media_assets
-             WHERE project_id = ?1 AND project_id IS NOT NULL AND (is_reference IS NULL OR is_reference = 0)
-             ORDER BY id DESC"
+        conn.execute(
+            "INSERT INTO persons (project_id, name, role, speakers_json, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![project_id, name, role, serde_json::to_string(speakers)?, Utc::now().to_rfc3339()],
         )?;
-        
-        let rows = stmt.query_map(params![project_id], |row| {
-            Ok(MediaAssetInfo {
-                id: row.get(0)?,
-                path: row.get(1)?,
-                duration_ticks: row.get(2)?,
-                fps_num: row.get(3)?,
-                fps_den: row.get(4)?,
-                width: row.get(5)?,
-                height: row.get(6)?,
-            })
-        })?;
-        
-        let mut assets = Vec::new();
-        for row in rows {
-            assets.push(row?);
+        Ok(conn.last_insert_rowid())
+    }
+
+    pub fn get_person(&self, person_id: i64) -> Result<Option<Person>> {
+        let conn = self.conn.lock().unwrap();
+        let result = conn.query_row(
+            &format!("SELECT {} FROM persons WHERE id = ?1", PERSON_COLUMNS),
+            params![person_id],
+            person_from_row,
+        );
+        match result {
+            Ok(person) => Ok(Some(person)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
         }
-        Ok(assets)
     }
 
-    pub fn get_reference_assets_for_project(&self, project_id: i64) -> Result<Vec<MediaAssetInfo>> {
+    /// A project's people, oldest first
+    pub fn list_persons(&self, project_id: i64) -> Result<Vec<Person>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM persons WHERE project_id = ?1 ORDER BY id",
+            PERSON_COLUMNS
+        ))?;
+        let persons = stmt
+            .query_map(params![project_id], person_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(persons)
+    }
+
+    pub fn update_person(&self, person_id: i64, name: &str, role: Option<&str>, speakers: &[SpeakerRef]) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE persons SET name = ?1, role = ?2, speakers_json = ?3 WHERE id = ?4",
+            params![name, role, serde_json::to_string(speakers)?, person_id],
+        )?;
+        Ok(())
+    }
+
+    /// Remove a person and their lower third; returns whether they were there
+    pub fn delete_person(&self, person_id: i64) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("DELETE FROM lower_thirds WHERE person_id = ?1", params![person_id])?;
+        let deleted = conn.execute("DELETE FROM persons WHERE id = ?1", params![person_id])?;
+        Ok(deleted > 0)
+    }
+
+    /// Speaker labels diarization found in a project's footage, with how many segments each
+    /// speaks most of, by asset
+    pub fn list_project_speakers(&self, project_id: i64) -> Result<Vec<(SpeakerRef, i64)>> {
         let conn = self.conn.lock().unwrap();
         let mut stmt = conn.prepare(
-            "SELECT id, path, duration_ticks, fps_num, fps_den, width, height
-             FROM media_assets
-             WHERE project_id = ?1 AND project_id IS NOT NULL AND is_reference = 1
-             ORDER BY id DESC"
+            "SELECT media_asset_id, speaker, COUNT(*) FROM segments
+             WHERE project_id = ?1 AND speaker IS NOT NULL AND speaker != ''
+             GROUP BY media_asset_id, speaker ORDER BY media_asset_id, speaker",
         )?;
-        
-        let rows = stmt.query_map(params![project_id], |row| {
-            Ok(MediaAssetInfo {
-                id: row.get(0)?,
-                path: row.get(1)?,
-                duration_ticks: row.get(2)?,
-                fps_num: row.get(3)?,
-                fps_den: row.get(4)?,
-                width: row.get(5)?,
-                height: row.get(6)?,
-            })
-        })?;
-        
-        let mut assets = Vec::new();
-        for row in rows {
-            assets.push(row?);
-        }
-        Ok(assets)
+        let speakers = stmt
+            .query_map(params![project_id], |row| {
+                Ok((SpeakerRef { asset_id: row.get(0)?, speaker: row.get(1)? }, row.get(2)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(speakers)
     }
 
-    pub fn delete_media_asset(&self, project_id: i64, asset_id: i64) -> Result<()> {
+    pub fn get_lower_third(&self, lower_third_id: i64) -> Result<Option<LowerThird>> {
         let conn = self.conn.lock().unwrap();
-        // Verify the asset belongs to the project before deleting
-        let asset_exists: Result<i64, rusqlite::Error> = conn.query_row(
-            "SELECT id FROM media_assets WHERE id = ?1 AND project_id = ?2",
-            params![asset_id, project_id],
-            |row| row.get::<_, i64>(0),
+        let result = conn.query_row(
+            &format!("SELECT {} FROM lower_thirds WHERE id = ?1", LOWER_THIRD_COLUMNS),
+            params![lower_third_id],
+            lower_third_from_row,
         );
-        
-        match asset_exists {
-            Ok(_) => {
-                // Delete the media asset (cascade will handle related records if foreign keys are set up)
-                conn.execute(
-                    "DELETE FROM media_assets WHERE id = ?1 AND project_id = ?2",
-                    params![asset_id, project_id],
-                )?;
-                Ok(())
-            }
-            Err(rusqlite::Error::QueryReturnedNoRows) => {
-                // Asset doesn't exist or doesn't belong to this project
-                Err(anyhow::anyhow!("Media asset not found or doesn't belong to this project"))
-            }
+        match result {
+            Ok(lower_third) => Ok(Some(lower_third)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
             Err(e) => Err(e.into()),
         }
     }
 
-    /// Store timeline for a project (backward compatible - defaults to overwrite)
-    pub fn store_timeline(&self, project_id: i64, timeline_json: &str) -> Result<()> {
-        self.store_timeline_version(project_id, timeline_json, None, false)
+    /// A project's lower thirds, in timeline order
+    pub fn list_lower_thirds(&self, project_id: i64) -> Result<Vec<LowerThird>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM lower_thirds WHERE project_id = ?1 ORDER BY start_ticks, id",
+            LOWER_THIRD_COLUMNS
+        ))?;
+        let lower_thirds = stmt
+            .query_map(params![project_id], lower_third_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(lower_thirds)
     }
 
-    /// Store timeline version (new version or overwrite)
-    pub fn store_timeline_version(
-        &self,
-        project_id: i64,
-        timeline_json: &str,
-        parent_version_id: Option<&str>,
-        is_new_version: bool,
-    ) -> Result<()> {
-        let now = Utc::now().to_rfc3339();
+    /// Suggest a lower third for a person at a new time; a person has at most one
+    pub fn suggest_lower_third(&self, project_id: i64, person_id: i64, start_ticks: i64, end_ticks: i64) -> Result<()> {
         let conn = self.conn.lock().unwrap();
-        
-        if is_new_version {
-            // Create new version
-            let version_id = Uuid::new_v4().to_string();
-            
-            // Set previous current version to not current
-            conn.execute(
-                "UPDATE timeline_versions SET is_current = 0 WHERE project_id = ?1 AND is_current = 1",
-                params![project_id],
-            )?;
-            
-            // Insert new version as current
-            conn.execute(
-                "INSERT INTO timeline_versions (project_id, version_id, parent_version_id, is_current, json_blob, created_at) VALUES (?1, ?2, ?3, 1, ?4, ?5)",
-                params![project_id, version_id, parent_version_id, timeline_json, now],
-            )?;
-        } else {
-            // Update existing current version (overwrite)
-            let existing = conn.query_row(
-                "SELECT id FROM timeline_versions WHERE project_id = ?1 AND is_current = 1",
-                params![project_id],
-                |row| row.get::<_, i64>(0),
-            );
-            
-            match existing {
-                Ok(_id) => {
-                    // Update existing current version
-                    conn.execute(
-                        "UPDATE timeline_versions SET json_blob = ?1 WHERE project_id = ?2 AND is_current = 1",
-                        params![timeline_json, project_id],
-                    )?;
-                }
-                Err(rusqlite::Error::QueryReturnedNoRows) => {
-                    // No current version exists, create one
-                    let version_id = Uuid::new_v4().to_string();
-                    conn.execute(
-                        "INSERT INTO timeline_versions (project_id, version_id, parent_version_id, is_current, json_blob, created_at) VALUES (?1, ?2, ?3, 1, ?4, ?5)",
-                        params![project_id, version_id, parent_version_id, timeline_json, now],
-                    )?;
-                }
-                Err(e) => return Err(e.into()),
-            }
-            
-            // Also update timeline_projects for backward compatibility
-            let existing_legacy = conn.query_row(
-                "SELECT id FROM timeline_projects WHERE project_id = ?1",
-                params![project_id],
-                |row| row.get::<_, i64>(0),
-            );
-            
-            match existing_legacy {
-                Ok(_id) => {
-                    conn.execute(
-                        "UPDATE timeline_projects SET json_blob = ?1, updated_at = ?2 WHERE project_id = ?3",
-                        params![timeline_json, now, project_id],
-                    )?;
-                }
-                Err(rusqlite::Error::QueryReturnedNoRows) => {
-                    conn.execute(
-                        "INSERT INTO timeline_projects (project_id, json_blob, created_at, updated_at) VALUES (?1, ?2, ?3, ?4)",
-                        params![project_id, timeline_json, now, now],
-                    )?;
-                }
-                Err(e) => return Err(e.into()),
-            }
-        }
-        
+        conn.execute(
+            "INSERT INTO lower_thirds (project_id, person_id, status, start_ticks, end_ticks, created_at)
+             VALUES (?1, ?2, 'suggested', ?3, ?4, ?5)
+             ON CONFLICT(project_id, person_id) DO UPDATE SET
+                status = 'suggested', start_ticks = excluded.start_ticks, end_ticks = excluded.end_ticks, title_id = NULL",
+            params![project_id, person_id, start_ticks, end_ticks, Utc::now().to_rfc3339()],
+        )?;
         Ok(())
     }
 
-    /// Get timeline for a project (prefers timeline_versions, falls back to timeline_projects for backward compatibility)
-    pub fn get_timeline(&self, project_id: i64) -> Result<Option<String>> {
+    /// Accept or dismiss a lower third; `title_id` is its title once it's on the timeline
+    pub fn set_lower_third_status(&self, lower_third_id: i64, status: &str, title_id: Option<&str>) -> Result<()> {
         let conn = self.conn.lock().unwrap();
-        
-        // Try timeline_versions first
-        let result: Result<String, rusqlite::Error> = conn.query_row(
-            "SELECT json_blob FROM timeline_versions WHERE project_id = ?1 AND is_current = 1",
-            params![project_id],
-            |row| row.get(0),
-        );
-        
-        match result {
-            Ok(blob) => Ok(Some(blob)),
-            Err(rusqlite::Error::QueryReturnedNoRows) => {
-                // Fallback to timeline_projects for backward compatibility
-                let mut stmt = conn.prepare("SELECT json_blob FROM timeline_projects WHERE project_id = ?1")?;
-                let mut rows = stmt.query_map(params![project_id], |row| {
-                    Ok(row.get::<_, String>(0)?)
-                })?;
-                
-                match rows.next() {
-                    Some(Ok(blob)) => Ok(Some(blob)),
-                    Some(Err(e)) => Err(e.into()),
-                    None => Ok(None),
-                }
-            }
-            Err(e) => Err(e.into()),
-        }
+        conn.execute(
+            "UPDATE lower_thirds SET status = ?1, title_id = ?2 WHERE id = ?3",
+            params![status, title_id, lower_third_id],
+        )?;
+        Ok(())
     }
 
-    /// Get proxy path for a media asset
-    pub fn get_proxy_path(&self, media_asset_id: i64) -> Result<Option<String>> {
+    pub fn delete_lower_third(&self, lower_third_id: i64) -> Result<()> {
         let conn = self.conn.lock().unwrap();
-        let mut stmt = conn.prepare("SELECT path FROM proxies WHERE media_asset_id = ?1 LIMIT 1")?;
-        let mut rows = stmt.query_map(params![media_asset_id], |row| {
-            Ok(row.get::<_, String>(0)?)
-        })?;
-        
-        match rows.next() {
-            Some(Ok(path)) => Ok(Some(path)),
-            Some(Err(e)) => Err(e.into()),
-            None => Ok(None),
+        conn.execute("DELETE FROM lower_thirds WHERE id = ?1", params![lower_third_id])?;
+        Ok(())
+    }
+}
+
+/// A question asked in a project's interviews, with the segments that ask and answer it
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct InterviewQuestion {
+    pub id: i64,
+    pub project_id: i64,
+    pub text: String,
+    /// Topic the interview cut groups the question under (None = its own topic)
+    pub topic: Option<String>,
+    /// Order in the question list, then in order of first asking
+    pub position: i64,
+    /// "list" or "transcript"
+    pub source: String,
+    pub question_segment_ids: Vec<i64>,
+    pub answer_segment_ids: Vec<i64>,
+}
+
+/// A question to record, with the segments that ask and answer it
+#[derive(Debug, Clone, Default)]
+pub struct NewInterviewQuestion {
+    pub text: String,
+    pub topic: Option<String>,
+    pub source: String,
+    pub question_segment_ids: Vec<i64>,
+    pub answer_segment_ids: Vec<i64>,
+}
+
+impl Database {
+    /// Replace a project's interview questions and the segments tagged with them; questions are
+    /// positioned in the order given
+    pub fn replace_interview_questions(&self, project_id: i64, questions: &[NewInterviewQuestion]) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM interview_segments WHERE project_id = ?1", params![project_id])?;
+        tx.execute("DELETE FROM interview_questions WHERE project_id = ?1", params![project_id])?;
+        let now = Utc::now().to_rfc3339();
+        for (position, question) in questions.iter().enumerate() {
+            tx.execute(
+                "INSERT INTO interview_questions (project_id, text, topic, position, source, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![project_id, question.text, question.topic, position as i64, question.source, now],
+            )?;
+            let question_id = tx.last_insert_rowid();
+            let roles = question
+                .question_segment_ids
+                .iter()
+                .map(|id| (id, "question"))
+                .chain(question.answer_segment_ids.iter().map(|id| (id, "answer")));
+            for (segment_id, role) in roles {
+                tx.execute(
+                    "INSERT OR REPLACE INTO interview_segments (segment_id, project_id, question_id, role)
+                     VALUES (?1, ?2, ?3, ?4)",
+                    params![segment_id, project_id, question_id, role],
+                )?;
+            }
         }
+        tx.commit()?;
+        Ok(())
     }
 
-    /// Get original media asset path by ID
-    pub fn get_media_asset_path(&self, media_asset_id: i64) -> Result<Option<String>> {
+    /// A project's interview questions in order, with their segments in source order
+    pub fn list_interview_questions(&self, project_id: i64) -> Result<Vec<InterviewQuestion>> {
         let conn = self.conn.lock().unwrap();
-        let mut stmt = conn.prepare("SELECT path FROM media_assets WHERE id = ?1 LIMIT 1")?;
-        let mut rows = stmt.query_map(params![media_asset_id], |row| {
-            Ok(row.get::<_, String>(0)?)
-        })?;
-        
-        match rows.next() {
-            Some(Ok(path)) => Ok(Some(path)),
-            Some(Err(e)) => Err(e.into()),
-            None => Ok(None),
+        let mut stmt = conn.prepare(
+            "SELECT id, project_id, text, topic, position, source FROM interview_questions
+             WHERE project_id = ?1 ORDER BY position",
+        )?;
+        let mut questions = stmt
+            .query_map(params![project_id], |row| {
+                Ok(InterviewQuestion {
+                    id: row.get(0)?,
+                    project_id: row.get(1)?,
+                    text: row.get(2)?,
+                    topic: row.get(3)?,
+                    position: row.get(4)?,
+                    source: row.get(5)?,
+                    question_segment_ids: Vec::new(),
+                    answer_segment_ids: Vec::new(),
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut stmt = conn.prepare(
+            "SELECT i.question_id, i.segment_id, i.role FROM interview_segments i
+             INNER JOIN segments s ON s.id = i.segment_id
+             WHERE i.project_id = ?1 ORDER BY s.media_asset_id, s.start_ticks",
+        )?;
+        let tagged = stmt
+            .query_map(params![project_id], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?, row.get::<_, String>(2)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        for (question_id, segment_id, role) in tagged {
+            if let Some(question) = questions.iter_mut().find(|q| q.id == question_id) {
+                match role.as_str() {
+                    "question" => question.question_segment_ids.push(segment_id),
+                    _ => question.answer_segment_ids.push(segment_id),
+                }
+            }
         }
+        Ok(questions)
     }
+}
 
-    /// Set thumbnail directory path for a media asset
-    pub fn set_thumbnail_dir(&self, media_asset_id: i64, thumbnail_dir: &str) -> Result<()> {
+/// An asset shared in the library by the project that imported it
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct LibraryAsset {
+    pub asset_id: i64,
+    pub name: String,
+    /// Project that imported and analyzed the asset
+    pub owner_project_id: i64,
+    pub path: String,
+    pub duration_ticks: i64,
+    pub width: i32,
+    pub height: i32,
+    /// Segments analysis found in it
+    pub segments: i64,
+    pub published_at: String,
+}
+
+const LIBRARY_ASSET_COLUMNS: &str = "l.asset_id, l.name, ma.project_id, ma.path, ma.duration_ticks, ma.width, ma.height,
+     (SELECT COUNT(*) FROM segments s WHERE s.media_asset_id = l.asset_id), l.published_at";
+
+fn library_asset_from_row(row: &rusqlite::Row) -> rusqlite::Result<LibraryAsset> {
+    Ok(LibraryAsset {
+        asset_id: row.get(0)?,
+        name: row.get(1)?,
+        owner_project_id: row.get(2)?,
+        path: row.get(3)?,
+        duration_ticks: row.get(4)?,
+        width: row.get(5)?,
+        height: row.get(6)?,
+        segments: row.get(7)?,
+        published_at: row.get(8)?,
+    })
+}
+
+impl Database {
+    /// Share an asset in the library (or rename it there)
+    pub fn publish_library_asset(&self, asset_id: i64, name: &str) -> Result<()> {
         let conn = self.conn.lock().unwrap();
         conn.execute(
-            "UPDATE media_assets SET thumbnail_dir = ?1 WHERE id = ?2",
-            params![thumbnail_dir, media_asset_id],
+            "INSERT INTO library_assets (asset_id, name, published_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT(asset_id) DO UPDATE SET name = excluded.name",
+            params![asset_id, name, Utc::now().to_rfc3339()],
         )?;
         Ok(())
     }
 
-    /// Get thumbnail directory path for a media asset
-    pub fn get_thumbnail_dir(&self, media_asset_id: i64) -> Result<Option<String>> {
+    /// Take an asset out of the library; projects must have stopped using it first
+    pub fn unpublish_library_asset(&self, asset_id: i64) -> Result<()> {
         let conn = self.conn.lock().unwrap();
-        let mut stmt = conn.prepare("SELECT thumbnail_dir FROM media_assets WHERE id = ?1 LIMIT 1")?;
-        let mut rows = stmt.query_map(params![media_asset_id], |row| {
-            Ok(row.get::<_, Option<String>>(0)?)
-        })?;
-        
-        match rows.next() {
-            Some(Ok(dir)) => Ok(dir),
-            Some(Err(e)) => Err(e.into()),
-            None => Ok(None),
+        conn.execute("DELETE FROM library_assets WHERE asset_id = ?1", params![asset_id])?;
+        Ok(())
+    }
+
+    pub fn get_library_asset(&self, asset_id: i64) -> Result<Option<LibraryAsset>> {
+        let conn = self.conn.lock().unwrap();
+        let result = conn.query_row(
+            &format!(
+                "SELECT {} FROM library_assets l INNER JOIN media_assets ma ON ma.id = l.asset_id
+                 WHERE l.asset_id = ?1",
+                LIBRARY_ASSET_COLUMNS
+            ),
+            params![asset_id],
+            library_asset_from_row,
+        );
+        match result {
+            Ok(asset) => Ok(Some(asset)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
         }
     }
 
-    /// Store raw transcript results for an asset
-    pub fn store_asset_transcript(&self, asset_id: i64, transcript_json: &str) -> Result<()> {
+    /// Every asset in the library, by name
+    pub fn list_library_assets(&self) -> Result<Vec<LibraryAsset>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM library_assets l INNER JOIN media_assets ma ON ma.id = l.asset_id
+             ORDER BY l.name COLLATE NOCASE, l.asset_id",
+            LIBRARY_ASSET_COLUMNS
+        ))?;
+        let assets = stmt
+            .query_map([], library_asset_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(assets)
+    }
+
+    /// Let a project use a library asset; its segments are planned and searched with the
+    /// project's own
+    pub fn link_library_asset(&self, project_id: i64, asset_id: i64) -> Result<()> {
         let conn = self.conn.lock().unwrap();
         conn.execute(
-            "INSERT OR REPLACE INTO asset_transcripts (asset_id, transcript_json) VALUES (?1, ?2)",
-            params![asset_id, transcript_json],
+            "INSERT OR IGNORE INTO project_asset_links (project_id, asset_id, linked_at) VALUES (?1, ?2, ?3)",
+            params![project_id, asset_id, Utc::now().to_rfc3339()],
         )?;
         Ok(())
     }
 
-    /// Get raw transcript results for an asset
-    pub fn get_asset_transcript(&self, asset_id: i64) -> Result<Option<String>> {
-        let conn = self.conn.lock().unwrap();
-        let mut stmt = conn.prepare("SELECT transcript_json FROM asset_transcripts WHERE asset_id = ?1")?;
-        let mut rows = stmt.query_map(params![asset_id], |row| {
-            Ok(row.get::<_, String>(0)?)
-        })?;
-        
-        match rows.next() {
-            Some(Ok(json)) => Ok(Some(json)),
-            Some(Err(e)) => Err(e.into()),
-            None => Ok(None),
-        }
-    }
-
-    /// Store raw vision analysis results for an asset
-    pub fn store_asset_vision(&self, asset_id: i64, vision_json: &str) -> Result<()> {
+    pub fn unlink_library_asset(&self, project_id: i64, asset_id: i64) -> Result<()> {
         let conn = self.conn.lock().unwrap();
         conn.execute(
-            "INSERT OR REPLACE INTO asset_vision (asset_id, vision_json) VALUES (?1, ?2)",
-            params![asset_id, vision_json],
+            "DELETE FROM project_asset_links WHERE project_id = ?1 AND asset_id = ?2",
+            params![project_id, asset_id],
         )?;
         Ok(())
     }
 
-    /// Get raw vision analysis results for an asset
-    pub fn get_asset_vision(&self, asset_id: i64) -> Result<Option<String>> {
+    /// Projects using a library asset by reference, as (project id, linked at), oldest first
+    pub fn list_asset_links(&self, asset_id: i64) -> Result<Vec<(i64, String)>> {
         let conn = self.conn.lock().unwrap();
-        let mut stmt = conn.prepare("SELECT vision_json FROM asset_vision WHERE asset_id = ?1")?;
-        let mut rows = stmt.query_map(params![asset_id], |row| {
-            Ok(row.get::<_, String>(0)?)
-        })?;
-        
-        match rows.next() {
-            Some(Ok(json)) => Ok(Some(json)),
-            Some(Err(e)) => Err(e.into()),
-            None => Ok(None),
-        }
+        let mut stmt = conn.prepare(
+            "SELECT project_id, linked_at FROM project_asset_links WHERE asset_id = ?1 ORDER BY linked_at, project_id",
+        )?;
+        let links = stmt
+            .query_map(params![asset_id], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(links)
     }
 
-    /// Store orchestrator message
-    pub fn store_orchestrator_message(
-        &self,
-        project_id: i64,
-        role: &str,
-        content: &str,
-        metadata: Option<&serde_json::Value>,
-    ) -> Result<i64> {
-        let now = Utc::now().to_rfc3339();
-        let metadata_json = metadata.and_then(|m| serde_json::to_string(m).ok());
+    /// Library assets a project uses by reference
+    pub fn get_linked_assets_for_project(&self, project_id: i64) -> Result<Vec<MediaAssetInfo>> {
         let conn = self.conn.lock().unwrap();
-        conn.execute(
-            "INSERT INTO orchestrator_messages (project_id, role, content, metadata_json, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
-            params![project_id, role, content, metadata_json, now],
+        let mut stmt = conn.prepare(
+            "SELECT ma.id, ma.path, ma.duration_ticks, ma.fps_num, ma.fps_den, ma.width, ma.height
+             FROM project_asset_links l
+             INNER JOIN media_assets ma ON ma.id = l.asset_id
+             WHERE l.project_id = ?1
+             ORDER BY l.linked_at DESC, ma.id DESC",
         )?;
-        Ok(conn.last_insert_rowid())
+        let assets = stmt
+            .query_map(params![project_id], |row| {
+                Ok(MediaAssetInfo {
+                    id: row.get(0)?,
+                    path: row.get(1)?,
+                    duration_ticks: row.get(2)?,
+                    fps_num: row.get(3)?,
+                    fps_den: row.get(4)?,
+                    width: row.get(5)?,
+                    height: row.get(6)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(assets)
     }
 
-    /// Get orchestrator messages for a project (most recent first)
-    pub fn get_orchestrator_messages(&self, project_id: i64, limit: usize) -> Result<Vec<serde_json::Value>> {
+    /// A project's own assets that other projects use from the library
+    pub fn get_shared_assets_of_project(&self, project_id: i64) -> Result<Vec<i64>> {
         let conn = self.conn.lock().unwrap();
         let mut stmt = conn.prepare(
-            "SELECT role, content, metadata_json, created_at FROM orchestrator_messages WHERE project_id = ?1 ORDER BY created_at DESC LIMIT ?2"
+            "SELECT DISTINCT l.asset_id FROM project_asset_links l
+             INNER JOIN media_assets ma ON ma.id = l.asset_id
+             WHERE ma.project_id = ?1 AND l.project_id != ?1
+             ORDER BY l.asset_id",
         )?;
-        let rows = stmt.query_map(params![project_id, limit as i64], |row| {
-            let role: String = row.get(0)?;
-            let content: String = row.get(1)?;
-            let metadata_json: Option<String> = row.get(2)?;
-            let created_at: String = row.get(3)?;
-            
-            let mut msg = serde_json::json!({
-                "role": role,
-                "content": content,
-                "created_at": created_at,
-            });
-            
-            if let Some(meta_str) = metadata_json {
-                if let Ok(meta) = serde_json::from_str::<serde_json::Value>(&meta_str) {
-                    msg["metadata"] = meta;
-                }
-            }
-            
-            Ok(msg)
-        })?;
-        
-        let mut messages = Vec::new();
-        for row in rows {
-            messages.push(row?);
-        }
-        
-        // Reverse to get chronological order (oldest first)
-        messages.reverse();
-        Ok(messages)
+        let ids = stmt
+            .query_map(params![project_id], |row| row.get(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(ids)
     }
+}
 
-    /// Store orchestrator proposal
-    pub fn store_orchestrator_proposal(
-        &self,
-        project_id: i64,
-        proposal_json: &str,
-    ) -> Result<i64> {
-        let now = Utc::now().to_rfc3339();
+/// A token API clients of a hosted deployment authenticate with
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ApiToken {
+    pub id: i64,
+    pub name: String,
+    pub created_at: String,
+}
+
+const API_TOKEN_COLUMNS: &str = "id, name, created_at";
+
+fn api_token_from_row(row: &rusqlite::Row) -> rusqlite::Result<ApiToken> {
+    Ok(ApiToken {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        created_at: row.get(2)?,
+    })
+}
+
+impl Database {
+    pub fn create_api_token(&self, name: &str, token_hash: &str) -> Result<i64> {
         let conn = self.conn.lock().unwrap();
         conn.execute(
-            "INSERT INTO orchestrator_proposals (project_id, proposal_json, created_at) VALUES (?1, ?2, ?3)",
-            params![project_id, proposal_json, now],
+            "INSERT INTO api_tokens (name, token_hash, created_at) VALUES (?1, ?2, ?3)",
+            params![name, token_hash, Utc::now().to_rfc3339()],
         )?;
         Ok(conn.last_insert_rowid())
     }
 
-    /// Store orchestrator applied plan
-    pub fn store_orchestrator_apply(
-        &self,
-        project_id: i64,
-        edit_plan_json: &str,
-    ) -> Result<i64> {
-        let now = Utc::now().to_rfc3339();
+    pub fn get_api_token(&self, token_id: i64) -> Result<Option<ApiToken>> {
         let conn = self.conn.lock().unwrap();
-        conn.execute(
-            "INSERT INTO orchestrator_applies (project_id, edit_plan_json, created_at) VALUES (?1, ?2, ?3)",
-            params![project_id, edit_plan_json, now],
-        )?;
-        Ok(conn.last_insert_rowid())
+        let result = conn.query_row(
+            &format!("SELECT {} FROM api_tokens WHERE id = ?1", API_TOKEN_COLUMNS),
+            params![token_id],
+            api_token_from_row,
+        );
+        match result {
+            Ok(token) => Ok(Some(token)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
     }
-    
-    /// Get the most recent edit plan for a project (from proposals or applies)
-    pub fn get_latest_edit_plan(&self, project_id: i64) -> Result<Option<serde_json::Value>> {
+
+    pub fn api_token_for_hash(&self, token_hash: &str) -> Result<Option<ApiToken>> {
         let conn = self.conn.lock().unwrap();
-        
-        // First try to get from applies (most recent)
-        let mut stmt = conn.prepare(
-            "SELECT edit_plan_json FROM orchestrator_applies WHERE project_id = ?1 ORDER BY created_at DESC LIMIT 1"
-        )?;
-        
-        let mut rows = stmt.query_map(params![project_id], |row| {
-            let plan_json: String = row.get(0)?;
-            Ok(plan_json)
-        })?;
-        
-        if let Some(row) = rows.next() {
-            let plan_json = row?;
-            if let Ok(mut plan) = serde_json::from_str::<serde_json::Value>(&plan_json) {
-                // Enrich plan with segment semantic data
-                if let Some(primary_segments) = plan.get_mut("primary_segments").and_then(|v| v.as_array_mut()) {
-                    for segment_op in primary_segments {
-                        if let Some(segment_id) = segment_op.get("segment_id").and_then(|v| v.as_i64()) {
-                            // Get segment using get_segment_with_embeddings (we only need the segment part)
-                            if let Ok(Some((segment, _))) = self.get_segment_with_embeddings(segment_id) {
-                                // Add semantic description to the segment operation
-                                let description = segment.summary_text
-                                    .or_else(|| segment.transcript.clone())
-                                    .unwrap_or_else(|| {
-                                        // Fallback to scene tags or keywords
-                                        if let Some(scene_json) = &segment.scene_json {
-                                            if let Ok(scene) = serde_json::from_str::<serde_json::Value>(scene_json) {
-                                                if let Some(tags) = scene.get("tags").and_then(|t| t.as_array()) {
-                                                    let tag_str: Vec<String> = tags.iter()
-                                                        .filter_map(|t| t.as_str().map(|s| s.to_string()))
-                                                        .collect();
-                                                    if !tag_str.is_empty() {
-                                                        return tag_str.join(", ");
-                                                    }
-                                                }
-                                            }
-                                        }
-                                        "video segment".to_string()
-                                    });
-                                
-                                segment_op["description"] = serde_json::json!(description);
-                                segment_op["duration_sec"] = serde_json::json!(
-                                    (segment.end_ticks - segment.start_ticks) as f64 / 48000.0
-                                );
-                            }
-                        }
-                    }
-                }
-                return Ok(Some(plan));
-            }
+        let result = conn.query_row(
+            &format!("SELECT {} FROM api_tokens WHERE token_hash = ?1", API_TOKEN_COLUMNS),
+            params![token_hash],
+            api_token_from_row,
+        );
+        match result {
+            Ok(token) => Ok(Some(token)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
         }
-        
-        // If no applied plan, check proposals (they might have plan data)
-        // For now, return None if no applied plan exists
-        // TODO: Store plan when generated, not just when applied
-        Ok(None)
     }
 
-    /// Create orchestrator goal
-    pub fn create_orchestrator_goal(
-        &self,
-        project_id: i64,
-        user_intent: &str,
-        status: &str,
-    ) -> Result<i64> {
-        let now = Utc::now().to_rfc3339();
+    pub fn list_api_tokens(&self) -> Result<Vec<ApiToken>> {
         let conn = self.conn.lock().unwrap();
-        conn.execute(
-            "INSERT INTO orchestrator_goals (project_id, user_intent, status, created_at, updated_at) VALUES (?1, ?2, ?3, ?4, ?5)",
-            params![project_id, user_intent, status, now, now],
-        )?;
-        Ok(conn.last_insert_rowid())
+        let mut stmt = conn.prepare(&format!("SELECT {} FROM api_tokens ORDER BY id", API_TOKEN_COLUMNS))?;
+        let rows = stmt.query_map([], api_token_from_row)?;
+        Ok(rows.collect::<Result<Vec<_>, _>>()?)
     }
 
-    /// Update orchestrator goal status
-    pub fn update_orchestrator_goal_status(
-        &self,
-        goal_id: i64,
-        status: &str,
-    ) -> Result<()> {
-        let now = Utc::now().to_rfc3339();
+    /// Revoke a token along with its quotas; its projects are left with only their own.
+    /// Returns false when there was no such token
+    pub fn delete_api_token(&self, token_id: i64) -> Result<bool> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        tx.execute("UPDATE projects SET api_token_id = NULL WHERE api_token_id = ?1", params![token_id])?;
+        tx.execute("DELETE FROM quotas WHERE scope = 'token' AND scope_id = ?1", params![token_id])?;
+        let deleted = tx.execute("DELETE FROM api_tokens WHERE id = ?1", params![token_id])?;
+        tx.commit()?;
+        Ok(deleted > 0)
+    }
+
+    pub fn set_project_api_token(&self, project_id: i64, token_id: Option<i64>) -> Result<()> {
         let conn = self.conn.lock().unwrap();
         conn.execute(
-            "UPDATE orchestrator_goals SET status = ?1, updated_at = ?2 WHERE id = ?3",
-            params![status, now, goal_id],
+            "UPDATE projects SET api_token_id = ?1 WHERE id = ?2",
+            params![token_id, project_id],
         )?;
         Ok(())
     }
 
-    /// Get active goals for a project
-    pub fn get_active_orchestrator_goals(&self, project_id: i64) -> Result<Vec<(i64, String, String)>> {
+    pub fn get_project_api_token(&self, project_id: i64) -> Result<Option<i64>> {
         let conn = self.conn.lock().unwrap();
-        let mut stmt = conn.prepare(
-            "SELECT id, user_intent, status FROM orchestrator_goals WHERE project_id = ?1 AND status NOT IN ('completed', 'cancelled') ORDER BY created_at DESC"
-        )?;
-        let rows = stmt.query_map(params![project_id], |row| {
-            Ok((row.get(0)?, row.get(1)?, row.get(2)?))
-        })?;
-        let mut goals = Vec::new();
-        for row in rows {
-            goals.push(row?);
+        let result = conn.query_row(
+            "SELECT api_token_id FROM projects WHERE id = ?1",
+            params![project_id],
+            |row| row.get(0),
+        );
+        match result {
+            Ok(token_id) => Ok(token_id),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
         }
-        Ok(goals)
     }
 
-    /// Get goal by status
-    pub fn get_orchestrator_goal_by_status(
-        &self,
-        project_id: i64,
-        status: &str,
-    ) -> Result<Option<(i64, String)>> {
+    /// Projects created with a token
+    pub fn list_api_token_projects(&self, token_id: i64) -> Result<Vec<i64>> {
         let conn = self.conn.lock().unwrap();
-        let result = conn.query_row(
-            "SELECT id, user_intent FROM orchestrator_goals WHERE project_id = ?1 AND status = ?2 ORDER BY created_at DESC LIMIT 1",
-            params![project_id, status],
-            |row| Ok((row.get(0)?, row.get(1)?)),
+        let mut stmt = conn.prepare("SELECT id FROM projects WHERE api_token_id = ?1 ORDER BY id")?;
+        let rows = stmt.query_map(params![token_id], |row| row.get(0))?;
+        Ok(rows.collect::<Result<Vec<_>, _>>()?)
+    }
+
+    pub fn set_quota_limits(&self, scope: &str, scope_id: i64, limits: &QuotaLimits) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO quotas (scope, scope_id, limits_json, updated_at) VALUES (?1, ?2, ?3, ?4)",
+            params![scope, scope_id, serde_json::to_string(limits)?, Utc::now().to_rfc3339()],
+        )?;
+        Ok(())
+    }
+
+    /// A project's or token's limits; unlimited when none were set
+    pub fn get_quota_limits(&self, scope: &str, scope_id: i64) -> Result<QuotaLimits> {
+        let conn = self.conn.lock().unwrap();
+        let result: rusqlite::Result<String> = conn.query_row(
+            "SELECT limits_json FROM quotas WHERE scope = ?1 AND scope_id = ?2",
+            params![scope, scope_id],
+            |row| row.get(0),
         );
         match result {
-            Ok(goal) => Ok(Some(goal)),
-            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Ok(json) => Ok(serde_json::from_str(&json).unwrap_or_default()),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(QuotaLimits::default()),
             Err(e) => Err(e.into()),
         }
     }
+
+    /// LLM calls logged against a project since an RFC 3339 time
+    pub fn count_llm_calls_since(&self, project_id: i64, since: &str) -> Result<i64> {
+        let conn = self.conn.lock().unwrap();
+        Ok(conn.query_row(
+            "SELECT COUNT(*) FROM llm_calls WHERE project_id = ?1 AND created_at >= ?2",
+            params![project_id, since],
+            |row| row.get(0),
+        )?)
+    }
+
+    /// Export jobs queued for a project since an RFC 3339 time, leaving out failed and
+    /// cancelled ones
+    pub fn count_exports_since(&self, project_id: i64, since: &str) -> Result<i64> {
+        let conn = self.conn.lock().unwrap();
+        Ok(conn.query_row(
+            "SELECT COUNT(*) FROM jobs
+             WHERE type = 'Export' AND json_extract(payload_json, '$.project_id') = ?1
+               AND created_at >= ?2 AND status NOT IN ('Failed', 'Cancelled')",
+            params![project_id, since],
+            |row| row.get(0),
+        )?)
+    }
+
+    /// Length in ticks of the media transcription and vision analysis ran (or are running) on
+    /// for a project's assets since an RFC 3339 time; each pass over an asset counts
+    pub fn analysis_ticks_since(&self, project_id: i64, since: &str, exclude_job_id: Option<i64>) -> Result<i64> {
+        let conn = self.conn.lock().unwrap();
+        Ok(conn.query_row(
+            "SELECT COALESCE(SUM(m.duration_ticks), 0) FROM jobs j
+             JOIN media_assets m ON m.id = json_extract(j.payload_json, '$.asset_id')
+             WHERE j.type IN ('TranscribeAsset', 'AnalyzeVisionAsset') AND m.project_id = ?1
+               AND j.created_at >= ?2 AND j.status IN ('Running', 'Completed')
+               AND (?3 IS NULL OR j.id != ?3)",
+            params![project_id, since, exclude_job_id],
+            |row| row.get(0),
+        )?)
+    }
 }
@@ -1,20 +1,70 @@
 use anyhow::Result;
 use rusqlite::params;
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use crate::db::Database;
 
+pub mod registry;
+
+/// Segment ids bound per statement in id-restricted searches
+const SEGMENT_ID_CHUNK: usize = 500;
+
+use registry::{EmbeddingError, EmbeddingModel, TEXT_MODEL, TEXT_WEIGHT, VISION_MODEL, VISION_WEIGHT};
+
+/// Fail unless the query vector has the registered dimension of the model it's compared against
+fn check_query_dimension(db: &Database, query_embedding: &[f32], embedding_type: &str, model_name: &str) -> Result<()> {
+    let expected = db.embedding_dimension(embedding_type, model_name)?;
+    if query_embedding.len() != expected {
+        return Err(EmbeddingError::DimensionMismatch {
+            embedding_type: embedding_type.to_string(),
+            model_name: model_name.to_string(),
+            expected,
+            actual: query_embedding.len(),
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// Score stored vectors against the query, skipping (and reporting) rows whose length doesn't
+/// match: they predate dimension validation and can't be compared meaningfully
+fn score_rows(query_embedding: &[f32], rows: Vec<(i64, Vec<u8>)>, model_name: &str) -> Vec<(i64, f32)> {
+    let mut skipped = 0;
+    let mut results = Vec::new();
+    for (segment_id, vector_blob) in rows {
+        // Deserialize embedding vector (f32 array stored as little-endian bytes)
+        let embedding: Vec<f32> = vector_blob.chunks_exact(4)
+            .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+            .collect();
+        if embedding.len() != query_embedding.len() {
+            skipped += 1;
+            continue;
+        }
+        results.push((segment_id, cosine_similarity(query_embedding, &embedding)));
+    }
+    if skipped > 0 {
+        eprintln!(
+            "[EMBEDDING] Skipped {} stored '{}' vectors with the wrong dimension (expected {}); re-embed those segments",
+            skipped, model_name, query_embedding.len()
+        );
+    }
+    results
+}
+
 /// Perform similarity search using cosine similarity
-/// Supports multiple embedding types (text, vision, fusion) and filters by raw vs reference segments
+/// Supports multiple embedding types (text, vision) and filters by raw vs reference segments
 pub fn similarity_search(
     db: Arc<Database>,
     query_embedding: &[f32],
-    embedding_type: &str, // 'text', 'vision', or 'fusion'
+    embedding_type: &str, // 'text' or 'vision'
     model_name: &str,
     limit: usize,
     project_id: Option<i64>,
     raw_segments_only: bool, // If true, only search raw segments (not references)
 ) -> Result<Vec<(i64, f32)>> {
+    check_query_dimension(&db, query_embedding, embedding_type, model_name)?;
+    
     // Build query with optional filtering
     let query = if raw_segments_only {
         // Only search segments from non-reference assets
@@ -24,14 +74,16 @@ pub fn similarity_search(
          JOIN media_assets m ON s.media_asset_id = m.id
          WHERE e.embedding_type = ?1 AND e.model_name = ?2
            AND (m.is_reference IS NULL OR m.is_reference = 0)
-           AND (?3 IS NULL OR s.project_id = ?3)"
+           AND (?3 IS NULL OR s.project_id = ?3
+                OR s.media_asset_id IN (SELECT asset_id FROM project_asset_links WHERE project_id = ?3))"
     } else {
         // Search all segments (raw + reference)
         "SELECT e.segment_id, e.vector_blob 
          FROM embeddings e
          JOIN segments s ON e.segment_id = s.id
          WHERE e.embedding_type = ?1 AND e.model_name = ?2
-           AND (?3 IS NULL OR s.project_id = ?3)"
+           AND (?3 IS NULL OR s.project_id = ?3
+                OR s.media_asset_id IN (SELECT asset_id FROM project_asset_links WHERE project_id = ?3))"
     };
     
     // Load all embeddings of the specified type
@@ -46,31 +98,53 @@ pub fn similarity_search(
     drop(stmt);
     drop(conn);
     
-    let mut results = Vec::new();
-    for (segment_id, vector_blob) in rows {
-        // Deserialize embedding vector (assuming f32 array stored as bytes)
-        let embedding: Vec<f32> = vector_blob.chunks(4)
-            .map(|chunk| {
-                let bytes: [u8; 4] = [chunk[0], chunk[1], chunk[2], chunk[3]];
-                f32::from_le_bytes(bytes)
-            })
-            .collect();
-        
-        // Handle dimension mismatch gracefully
-        let min_dim = query_embedding.len().min(embedding.len());
-        if min_dim == 0 {
-            continue;
+    let mut results = score_rows(query_embedding, rows, model_name);
+    
+    // Sort by similarity (descending) and take top N
+    results.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+    results.truncate(limit);
+    
+    Ok(results)
+}
+
+/// Similarity search restricted to a prefiltered set of segment ids (see retrieval::prefilter),
+/// so only vectors of segments that passed the metadata filters are loaded and scored
+pub fn similarity_search_among(
+    db: Arc<Database>,
+    query_embedding: &[f32],
+    embedding_type: &str,
+    model_name: &str,
+    limit: usize,
+    segment_ids: &[i64],
+) -> Result<Vec<(i64, f32)>> {
+    check_query_dimension(&db, query_embedding, embedding_type, model_name)?;
+    
+    // Chunked IN lists keep each statement under SQLite's bound-parameter limit
+    let mut rows = Vec::new();
+    {
+        let conn = db.conn.lock().unwrap();
+        for chunk in segment_ids.chunks(SEGMENT_ID_CHUNK) {
+            let query = format!(
+                "SELECT segment_id, vector_blob FROM embeddings
+                 WHERE embedding_type = ? AND model_name = ? AND segment_id IN ({})",
+                vec!["?"; chunk.len()].join(", ")
+            );
+            let mut values: Vec<rusqlite::types::Value> = vec![
+                embedding_type.to_string().into(),
+                model_name.to_string().into(),
+            ];
+            values.extend(chunk.iter().map(|id| rusqlite::types::Value::Integer(*id)));
+            let mut stmt = conn.prepare(&query)?;
+            let chunk_rows = stmt.query_map(rusqlite::params_from_iter(values), |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, Vec<u8>>(1)?))
+            })?;
+            for row in chunk_rows {
+                rows.push(row?);
+            }
         }
-        
-        let query_trimmed: Vec<f32> = query_embedding.iter().take(min_dim).copied().collect();
-        let emb_trimmed: Vec<f32> = embedding.iter().take(min_dim).copied().collect();
-        
-        // Compute cosine similarity
-        let similarity = cosine_similarity(&query_trimmed, &emb_trimmed);
-        results.push((segment_id, similarity));
     }
     
-    // Sort by similarity (descending) and take top N
+    let mut results = score_rows(query_embedding, rows, model_name);
     results.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
     results.truncate(limit);
     
@@ -86,6 +160,8 @@ pub fn similarity_search_references(
     limit: usize,
     project_id: Option<i64>,
 ) -> Result<Vec<(i64, f32)>> {
+    check_query_dimension(&db, query_embedding, embedding_type, model_name)?;
+    
     let query = "SELECT e.segment_id, e.vector_blob 
                  FROM embeddings e
                  JOIN segments s ON e.segment_id = s.id
@@ -105,26 +181,7 @@ pub fn similarity_search_references(
     drop(stmt);
     drop(conn);
     
-    let mut results = Vec::new();
-    for (segment_id, vector_blob) in rows {
-        let embedding: Vec<f32> = vector_blob.chunks(4)
-            .map(|chunk| {
-                let bytes: [u8; 4] = [chunk[0], chunk[1], chunk[2], chunk[3]];
-                f32::from_le_bytes(bytes)
-            })
-            .collect();
-        
-        let min_dim = query_embedding.len().min(embedding.len());
-        if min_dim == 0 {
-            continue;
-        }
-        
-        let query_trimmed: Vec<f32> = query_embedding.iter().take(min_dim).copied().collect();
-        let emb_trimmed: Vec<f32> = embedding.iter().take(min_dim).copied().collect();
-        
-        let similarity = cosine_similarity(&query_trimmed, &emb_trimmed);
-        results.push((segment_id, similarity));
-    }
+    let mut results = score_rows(query_embedding, rows, model_name);
     
     results.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
     results.truncate(limit);
@@ -132,8 +189,77 @@ pub fn similarity_search_references(
     Ok(results)
 }
 
+/// Segments a fused search scores
+#[derive(Debug, Clone, Copy)]
+pub enum SearchScope<'a> {
+    /// A project's raw segments (not reference footage)
+    Project(i64),
+    /// A prefiltered set of segment ids (see retrieval::prefilter)
+    Segments(&'a [i64]),
+}
+
+/// Blend a segment's text and vision similarity; a segment (or search) with only one of them is
+/// scored by that one alone
+pub fn fuse_scores(text: Option<f32>, vision: Option<f32>) -> f32 {
+    match (text, vision) {
+        (Some(text), Some(vision)) => (TEXT_WEIGHT * text + VISION_WEIGHT * vision) / (TEXT_WEIGHT + VISION_WEIGHT),
+        (Some(score), None) | (None, Some(score)) => score,
+        (None, None) => 0.0,
+    }
+}
+
+/// Rank segments by text similarity to `text_query` (a [`TEXT_MODEL`] vector) blended with
+/// vision similarity to `vision_query` (the same query from CLIP's text encoder, comparable with
+/// [`VISION_MODEL`] vectors), weighted by [`TEXT_WEIGHT`] and [`VISION_WEIGHT`]. Each similarity
+/// is taken within its own model's space; vectors of the two models are never mixed.
+pub fn fused_search(
+    db: Arc<Database>,
+    text_query: &[f32],
+    vision_query: Option<&[f32]>,
+    limit: usize,
+    scope: SearchScope,
+) -> Result<Vec<(i64, f32)>> {
+    let search = |query: &[f32], model: &EmbeddingModel| match scope {
+        SearchScope::Project(project_id) => similarity_search(
+            db.clone(),
+            query,
+            model.embedding_type,
+            model.model_name,
+            usize::MAX,
+            Some(project_id),
+            true,
+        ),
+        SearchScope::Segments(segment_ids) => similarity_search_among(
+            db.clone(),
+            query,
+            model.embedding_type,
+            model.model_name,
+            usize::MAX,
+            segment_ids,
+        ),
+    };
+
+    let mut scores: HashMap<i64, (Option<f32>, Option<f32>)> = HashMap::new();
+    for (segment_id, score) in search(text_query, &TEXT_MODEL)? {
+        scores.entry(segment_id).or_default().0 = Some(score);
+    }
+    if let Some(vision_query) = vision_query {
+        for (segment_id, score) in search(vision_query, &VISION_MODEL)? {
+            scores.entry(segment_id).or_default().1 = Some(score);
+        }
+    }
+
+    let mut results: Vec<(i64, f32)> = scores
+        .into_iter()
+        .map(|(segment_id, (text, vision))| (segment_id, fuse_scores(text, vision)))
+        .collect();
+    results.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal).then(a.0.cmp(&b.0)));
+    results.truncate(limit);
+    Ok(results)
+}
+
 /// Compute cosine similarity between two vectors
-fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+pub(crate) fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
     if a.len() != b.len() {
         return 0.0;
     }
@@ -0,0 +1,70 @@
+/// An embedding model vectors are stored under: every vector of this (type, model) pair has
+/// exactly `dimension` components and is only comparable with vectors of the same pair
+#[derive(Debug, Clone)]
+pub struct EmbeddingModel {
+    pub embedding_type: &'static str,
+    pub model_name: &'static str,
+    pub model_version: &'static str,
+    pub dimension: usize,
+}
+
+/// sentence-transformers all-MiniLM-L6-v2 (ML service /embeddings/text)
+pub const TEXT_MODEL: EmbeddingModel = EmbeddingModel {
+    embedding_type: "text",
+    model_name: "all-MiniLM-L6-v2",
+    model_version: "1",
+    dimension: 384,
+};
+
+/// CLIP ViT-B/32 image embeddings (ML service /embeddings/vision)
+pub const VISION_MODEL: EmbeddingModel = EmbeddingModel {
+    embedding_type: "vision",
+    model_name: "clip-vit-b-32",
+    model_version: "1",
+    dimension: 512,
+};
+
+/// Share of text and vision similarity in a fused score (see [`super::fused_search`]). Text and
+/// vision vectors come from different models and are never combined or compared with each
+/// other: a query is embedded once per model and only the scores are blended.
+pub const TEXT_WEIGHT: f32 = 0.6;
+pub const VISION_WEIGHT: f32 = 0.4;
+
+/// Models seeded into embedding_models on startup
+pub fn builtin_models() -> Vec<EmbeddingModel> {
+    vec![TEXT_MODEL, VISION_MODEL]
+}
+
+/// Vectors that can't be stored or compared under the registered model
+#[derive(Debug, Clone)]
+pub enum EmbeddingError {
+    UnknownModel {
+        embedding_type: String,
+        model_name: String,
+    },
+    DimensionMismatch {
+        embedding_type: String,
+        model_name: String,
+        expected: usize,
+        actual: usize,
+    },
+}
+
+impl std::fmt::Display for EmbeddingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EmbeddingError::UnknownModel { embedding_type, model_name } => write!(
+                f,
+                "No registered {} embedding model named '{}'",
+                embedding_type, model_name
+            ),
+            EmbeddingError::DimensionMismatch { embedding_type, model_name, expected, actual } => write!(
+                f,
+                "{} embedding for model '{}' has {} dimensions, expected {}; vectors from different models can't be mixed",
+                embedding_type, model_name, actual, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for EmbeddingError {}
@@ -0,0 +1,137 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::brand::Brand;
+use crate::db::Database;
+use crate::media::roots::{self, PathNotAllowed};
+use engine::end_card::{EndCard, EndCardBackground, EndCardElement};
+
+/// A built-in end card, seeded into end_cards on startup
+pub struct BuiltinEndCard {
+    /// Stable identifier stored in end_cards.builtin_key
+    pub key: &'static str,
+    pub card: EndCard,
+}
+
+/// Built-in end cards, so a project can finish on a call to action without designing one.
+/// They fill `{project}` from the project name, `{logo}` and `{background}` from the brand kit,
+/// and `{channel}` and the handles from the project's end card values; lines whose value isn't
+/// set are left off.
+pub fn builtin_end_cards() -> Vec<BuiltinEndCard> {
+    vec![
+        BuiltinEndCard {
+            key: "subscribe",
+            card: EndCard {
+                name: "Subscribe".to_string(),
+                duration_sec: 5.0,
+                background: EndCardBackground::Color { color: "{background}".to_string() },
+                elements: vec![
+                    image("{logo}", 0.5, 0.22, 0.15),
+                    text("Thanks for watching!", 0.5, 0.45, 0.08, "white"),
+                    text("Subscribe for more", 0.5, 0.58, 0.05, "#ff3b3b"),
+                    text("{channel}", 0.5, 0.68, 0.04, "#cccccc"),
+                ],
+            },
+        },
+        BuiltinEndCard {
+            key: "social_handles",
+            card: EndCard {
+                name: "Social handles".to_string(),
+                duration_sec: 6.0,
+                background: EndCardBackground::Color { color: "#111111".to_string() },
+                elements: vec![
+                    text("Follow {project}", 0.5, 0.2, 0.07, "white"),
+                    text("YouTube  {youtube}", 0.5, 0.38, 0.045, "white"),
+                    text("Instagram  {instagram}", 0.5, 0.48, 0.045, "white"),
+                    text("TikTok  {tiktok}", 0.5, 0.58, 0.045, "white"),
+                    text("X  {x}", 0.5, 0.68, 0.045, "white"),
+                    image("{logo}", 0.5, 0.85, 0.1),
+                ],
+            },
+        },
+    ]
+}
+
+fn text(text: &str, x: f64, y: f64, size: f64, color: &str) -> EndCardElement {
+    EndCardElement::Text {
+        text: text.to_string(),
+        x,
+        y,
+        size,
+        color: color.to_string(),
+        font_file: None,
+    }
+}
+
+fn image(path: &str, x: f64, y: f64, width: f64) -> EndCardElement {
+    EndCardElement::Image {
+        path: path.to_string(),
+        x,
+        y,
+        width,
+        key_color: None,
+    }
+}
+
+/// The end card a project's exports finish with, and the values its placeholders are filled
+/// with (handles, channel name, logo file)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectEndCard {
+    pub end_card_id: i64,
+    #[serde(default)]
+    pub values: HashMap<String, String>,
+}
+
+impl ProjectEndCard {
+    pub fn for_project(db: &Database, project_id: i64) -> Result<Option<Self>> {
+        db.get_project_end_card(project_id)?
+            .map(|json| Ok(serde_json::from_str(&json)?))
+            .transpose()
+    }
+}
+
+/// The project's end card with its placeholders filled in (the project's values first, then
+/// its brand kit's logo and colors) and drawn in its brand fonts, ready to render; None when
+/// the project doesn't have one. Fails if a file the card shows is missing or outside the media
+/// roots.
+pub fn for_export(db: &Database, project_id: i64) -> Result<Option<EndCard>> {
+    let Some(settings) = ProjectEndCard::for_project(db, project_id)? else {
+        return Ok(None);
+    };
+    let mut card = filled(db, project_id, &settings)?;
+    resolve_files(&mut card)?;
+    if let Some(missing) = card.files().into_iter().find(|path| !std::path::Path::new(path).exists()) {
+        return Err(anyhow::anyhow!("End card \"{}\" uses {}, which doesn't exist", card.name, missing));
+    }
+    Ok(Some(card))
+}
+
+/// The end card `settings` picks, filled from the brand kit, the settings' values and the
+/// project name, and styled by the brand kit
+pub fn filled(db: &Database, project_id: i64, settings: &ProjectEndCard) -> Result<EndCard> {
+    let (_, json, _) = db
+        .get_end_card(settings.end_card_id)?
+        .ok_or_else(|| anyhow::anyhow!("End card {} not found", settings.end_card_id))?;
+    let card: EndCard = serde_json::from_str(&json)?;
+    let project = db
+        .get_project(project_id)?
+        .ok_or_else(|| anyhow::anyhow!("Project {} not found", project_id))?;
+
+    let brand = Brand::for_project(db, project_id)?;
+    let mut values = brand.values();
+    values.extend(settings.values.clone());
+    values.entry("project".to_string()).or_insert(project.name);
+    let mut card = card.fill(&values);
+    brand.style_end_card(&mut card);
+    Ok(card)
+}
+
+/// Resolve every file a filled card reads against the media roots, replacing each path with
+/// its canonical form. Placeholders have to be filled first: a value can expand into a path.
+pub fn resolve_files(card: &mut EndCard) -> Result<(), PathNotAllowed> {
+    for path in card.paths_mut() {
+        *path = roots::resolve(path.as_str())?.to_string_lossy().to_string();
+    }
+    Ok(())
+}
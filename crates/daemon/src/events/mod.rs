@@ -0,0 +1,145 @@
+//! Event bus: what happened to jobs, analysis, proposals and timelines, stored in the events
+//! outbox and broadcast to every subscriber. The job manager owns the bus
+//! ([`crate::jobs::JobManager::events`]); the agent loop, the orchestrator's SSE stream and
+//! webhook delivery subscribe to it.
+//!
+//! Named consumers resume from their stored position (see [`outbox`]), so events published while
+//! the daemon was down, or while a consumer was busy, are delivered when it gets to them. A plain
+//! [`EventBus::subscribe`] only sees events published after it subscribed, and skips the oldest
+//! (`RecvError::Lagged`) when it falls more than [`CAPACITY`] behind.
+
+pub mod outbox;
+
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+
+use crate::db::Database;
+use crate::jobs::JobStatus;
+
+pub use outbox::Subscription;
+
+/// Events buffered for each subscriber
+pub const CAPACITY: usize = 1000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum Event {
+    /// A job moved to a new status: queued again, started (locally or by a remote worker),
+    /// completed, failed or cancelled
+    JobStateChanged {
+        job_id: i64,
+        job_type: String,
+        status: JobStatus,
+        /// From the job's payload, when it names one
+        project_id: Option<i64>,
+        asset_id: Option<i64>,
+        /// Why the job failed (Failed only)
+        error: Option<String>,
+    },
+    /// Every analysis stage of an asset has finished
+    AnalysisComplete {
+        project_id: i64,
+        asset_id: i64,
+        readiness: String,
+    },
+    /// The orchestrator stored a proposal
+    ProposalReady {
+        project_id: i64,
+        proposal_id: i64,
+    },
+    /// Timeline operations were applied and the result stored
+    TimelineApplied {
+        project_id: i64,
+        operations: usize,
+    },
+}
+
+impl Event {
+    /// Project the event belongs to (None for jobs whose payload doesn't name one)
+    pub fn project_id(&self) -> Option<i64> {
+        match self {
+            Event::JobStateChanged { project_id, .. } => *project_id,
+            Event::AnalysisComplete { project_id, .. }
+            | Event::ProposalReady { project_id, .. }
+            | Event::TimelineApplied { project_id, .. } => Some(*project_id),
+        }
+    }
+
+    /// The `type` tag it's serialized with
+    pub fn name(&self) -> &'static str {
+        match self {
+            Event::JobStateChanged { .. } => "JobStateChanged",
+            Event::AnalysisComplete { .. } => "AnalysisComplete",
+            Event::ProposalReady { .. } => "ProposalReady",
+            Event::TimelineApplied { .. } => "TimelineApplied",
+        }
+    }
+}
+
+/// An event as subscribers receive it
+#[derive(Debug, Clone)]
+pub struct PublishedEvent {
+    /// Outbox id, increasing in publish order (None if storing it failed)
+    pub id: Option<i64>,
+    pub event: Event,
+}
+
+#[derive(Clone)]
+pub struct EventBus {
+    db: Arc<Database>,
+    sender: broadcast::Sender<PublishedEvent>,
+    /// Held from storing an event until it's broadcast, so subscribers get ids in order
+    publish_lock: Arc<Mutex<()>>,
+}
+
+impl EventBus {
+    pub fn new(db: Arc<Database>) -> Self {
+        let (sender, _) = broadcast::channel(CAPACITY);
+        EventBus {
+            db,
+            sender,
+            publish_lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// Store an event in the outbox and send it to current subscribers (none listening is
+    /// fine). Not storing it only costs replay, so that's logged rather than returned.
+    pub fn publish(&self, event: Event) {
+        let _guard = self.publish_lock.lock().unwrap();
+        let id = match outbox::store(&self.db, &event) {
+            Ok(id) => Some(id),
+            Err(e) => {
+                eprintln!("[EVENTS] Failed to store {} event: {:?}", event.name(), e);
+                None
+            }
+        };
+        let _ = self.sender.send(PublishedEvent { id, event });
+    }
+
+    /// Events published from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<PublishedEvent> {
+        self.sender.subscribe()
+    }
+
+    /// Stored events after `after_id` (only a project's when `project_id` is set), then live
+    /// ones, for clients that keep their own position such as SSE streams
+    pub fn subscribe_after(&self, after_id: i64, project_id: Option<i64>) -> Subscription {
+        Subscription::new(self.db.clone(), self.subscribe(), after_id, project_id, None)
+    }
+
+    /// Every event after the last one `consumer` acknowledged, then live ones. A consumer that
+    /// has never run starts from the newest stored event rather than the whole retention window.
+    pub fn subscribe_as(&self, consumer: &'static str) -> anyhow::Result<Subscription> {
+        let rx = self.subscribe();
+        let after_id = match self.db.get_event_cursor(consumer)? {
+            Some(id) => id,
+            None => {
+                let id = self.db.latest_event_id()?;
+                self.db.set_event_cursor(consumer, id)?;
+                id
+            }
+        };
+        Ok(Subscription::new(self.db.clone(), rx, after_id, None, Some(consumer)))
+    }
+}
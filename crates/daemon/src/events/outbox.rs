@@ -0,0 +1,154 @@
+//! The events outbox: every published event is stored in the `events` table before it's
+//! broadcast, and named consumers record the last one they handled in `event_deliveries`.
+//!
+//! A [`Subscription`] first replays stored events past its position, then follows the live bus,
+//! skipping what it already replayed. Falling behind the bus re-reads the missed events from
+//! the table instead of dropping them. Events older than EVENT_RETENTION_DAYS (default 7) are
+//! pruned whether or not every consumer got to them, so a consumer gone for longer than that
+//! resumes from the oldest event left.
+
+use anyhow::Result;
+use chrono::Utc;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use super::{Event, PublishedEvent};
+use crate::db::Database;
+
+/// Consumer names in `event_deliveries`
+pub const AGENT_CONSUMER: &str = "agent";
+pub const WEBHOOKS_CONSUMER: &str = "webhooks";
+
+/// Stored events read per query while catching up
+const REPLAY_BATCH: usize = 500;
+const DEFAULT_RETENTION_DAYS: i64 = 7;
+const PRUNE_INTERVAL: Duration = Duration::from_secs(3600);
+
+pub(super) fn store(db: &Database, event: &Event) -> Result<i64> {
+    db.insert_event(event.name(), event.project_id(), &serde_json::to_string(event)?)
+}
+
+/// Stored events and then live ones, in id order, each at most once
+pub struct Subscription {
+    db: Arc<Database>,
+    rx: broadcast::Receiver<PublishedEvent>,
+    /// Last event returned
+    position: i64,
+    project_id: Option<i64>,
+    /// Whose position [`Subscription::ack`] stores (None for positions kept by the client)
+    consumer: Option<&'static str>,
+    backlog: VecDeque<PublishedEvent>,
+    /// Still reading stored events rather than the bus
+    catching_up: bool,
+}
+
+impl Subscription {
+    pub(super) fn new(
+        db: Arc<Database>,
+        rx: broadcast::Receiver<PublishedEvent>,
+        after_id: i64,
+        project_id: Option<i64>,
+        consumer: Option<&'static str>,
+    ) -> Self {
+        Subscription {
+            db,
+            rx,
+            position: after_id,
+            project_id,
+            consumer,
+            backlog: VecDeque::new(),
+            catching_up: true,
+        }
+    }
+
+    /// The next event; None once the bus is gone
+    pub async fn recv(&mut self) -> Option<PublishedEvent> {
+        loop {
+            if let Some(published) = self.backlog.pop_front() {
+                self.position = published.id.unwrap_or(self.position);
+                return Some(published);
+            }
+            if self.catching_up {
+                match self.load_backlog() {
+                    Ok(0) => self.catching_up = false,
+                    Ok(_) => {}
+                    Err(e) => {
+                        eprintln!("[EVENTS] Failed to read stored events after {}: {:?}", self.position, e);
+                        self.catching_up = false;
+                    }
+                }
+                continue;
+            }
+            match self.rx.recv().await {
+                Ok(published) => {
+                    if self.project_id.is_some() && published.event.project_id() != self.project_id {
+                        continue;
+                    }
+                    match published.id {
+                        Some(id) if id <= self.position => continue,
+                        Some(id) => self.position = id,
+                        None => {}
+                    }
+                    return Some(published);
+                }
+                Err(RecvError::Lagged(missed)) => {
+                    eprintln!("[EVENTS] Fell {} events behind, reading them from the outbox", missed);
+                    self.catching_up = true;
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+
+    fn load_backlog(&mut self) -> Result<usize> {
+        let rows = self.db.get_events_after(self.position, self.project_id, REPLAY_BATCH)?;
+        let count = rows.len();
+        let last_id = rows.last().map(|(id, _)| *id);
+        for (id, payload_json) in rows {
+            match serde_json::from_str::<Event>(&payload_json) {
+                Ok(event) => self.backlog.push_back(PublishedEvent { id: Some(id), event }),
+                Err(e) => eprintln!("[EVENTS] Skipping unreadable stored event {}: {}", id, e),
+            }
+        }
+        // A batch of nothing but unreadable rows still moves past them
+        if self.backlog.is_empty() {
+            self.position = last_id.unwrap_or(self.position);
+        }
+        Ok(count)
+    }
+
+    /// Record that the consumer has handled `published`, so a restart resumes after it
+    pub fn ack(&self, published: &PublishedEvent) {
+        let (Some(consumer), Some(id)) = (self.consumer, published.id) else {
+            return;
+        };
+        if let Err(e) = self.db.set_event_cursor(consumer, id) {
+            eprintln!("[EVENTS] Failed to record {} handling event {}: {:?}", consumer, id, e);
+        }
+    }
+}
+
+/// How long stored events are kept, from EVENT_RETENTION_DAYS (default 7)
+pub fn retention() -> chrono::Duration {
+    let days = std::env::var("EVENT_RETENTION_DAYS")
+        .ok()
+        .and_then(|days| days.trim().parse::<i64>().ok())
+        .filter(|days| *days > 0)
+        .unwrap_or(DEFAULT_RETENTION_DAYS);
+    chrono::Duration::days(days)
+}
+
+/// Prunes events past the retention window at startup and hourly after that
+pub async fn retention_loop(db: Arc<Database>) {
+    let retention = retention();
+    loop {
+        match db.prune_events(Utc::now() - retention) {
+            Ok(removed) if removed > 0 => eprintln!("[EVENTS] Pruned {} events past retention", removed),
+            Ok(_) => {}
+            Err(e) => eprintln!("[EVENTS] Failed to prune events: {:?}", e),
+        }
+        tokio::time::sleep(PRUNE_INTERVAL).await;
+    }
+}
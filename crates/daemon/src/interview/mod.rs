@@ -0,0 +1,221 @@
+//! Interview structure: the questions asked in a project's interviews, found in their
+//! transcripts or given as a list, and the segments that answer them. The interview planner
+//! mode assembles a cut from them, topic by topic.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+use crate::db::{Database, InterviewQuestion, NewInterviewQuestion, Segment};
+use crate::media::segment_kind;
+
+/// How alike a question asked on camera and one in the question list must be to match
+const LIST_MATCH_SIMILARITY: f64 = 0.3;
+/// How alike two questions asked on camera must be to count as the same question (e.g. put to
+/// two interviewees)
+const SAME_QUESTION_SIMILARITY: f64 = 0.5;
+
+/// Words too common to tell questions apart
+const STOP_WORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "but", "by", "can", "could", "did", "do", "does", "for", "from",
+    "had", "has", "have", "how", "i", "if", "in", "is", "it", "its", "me", "my", "of", "on", "or", "so", "that",
+    "the", "their", "them", "there", "they", "this", "to", "was", "we", "were", "what", "when", "where", "which",
+    "who", "why", "will", "with", "would", "you", "your",
+];
+
+/// A question in a question list, optionally under a topic
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListedQuestion {
+    pub text: String,
+    #[serde(default)]
+    pub topic: Option<String>,
+}
+
+/// A question put to an interviewee, and the segments of their answer
+#[derive(Debug, Clone)]
+pub struct AskedQuestion {
+    pub asset_id: i64,
+    pub text: String,
+    pub question_segment_ids: Vec<i64>,
+    pub answer_segment_ids: Vec<i64>,
+}
+
+/// Questions asked in one asset's segments (in source order) and the answers that follow them.
+/// When the transcript was diarized, the interviewer is the speaker who asks the most questions:
+/// whatever they say opens a question (prompts like "Tell me about..." included), and only the
+/// other speakers answer. Otherwise a segment ending in a question opens one, and the segments
+/// up to the next question answer it.
+pub fn asked_questions(segments: &[&Segment]) -> Vec<AskedQuestion> {
+    let spoken: Vec<&Segment> = segments
+        .iter()
+        .copied()
+        .filter(|s| s.transcript.as_deref().is_some_and(|t| !t.trim().is_empty()))
+        .collect();
+    let transcript = |s: &Segment| s.transcript.clone().unwrap_or_default();
+
+    let mut asks: BTreeMap<&str, usize> = BTreeMap::new();
+    for segment in &spoken {
+        if let Some(speaker) = segment.speaker.as_deref().filter(|s| !s.is_empty()) {
+            let count = asks.entry(speaker).or_insert(0);
+            if transcript(segment).contains('?') {
+                *count += 1;
+            }
+        }
+    }
+    let interviewer = (asks.len() > 1)
+        .then(|| asks.iter().max_by_key(|(_, count)| **count).filter(|(_, count)| **count > 0))
+        .flatten()
+        .map(|(speaker, _)| speaker.to_string());
+
+    let mut questions: Vec<AskedQuestion> = Vec::new();
+    let mut asking = false;
+    for segment in spoken {
+        let text = transcript(segment);
+        let opens_question = match &interviewer {
+            Some(interviewer) => segment.speaker.as_deref() == Some(interviewer.as_str()),
+            None => last_sentence(&text).ends_with('?'),
+        };
+        if opens_question {
+            let sentence = question_sentence(&text).unwrap_or_else(|| text.trim().to_string());
+            match questions.last_mut() {
+                // The interviewer kept talking: one question, phrased by its last part
+                Some(question) if asking => {
+                    question.text = sentence;
+                    question.question_segment_ids.push(segment.id);
+                }
+                _ => questions.push(AskedQuestion {
+                    asset_id: segment.media_asset_id,
+                    text: sentence,
+                    question_segment_ids: vec![segment.id],
+                    answer_segment_ids: Vec::new(),
+                }),
+            }
+            asking = true;
+        } else if let Some(question) = questions.last_mut() {
+            question.answer_segment_ids.push(segment.id);
+            asking = false;
+        }
+    }
+    questions.retain(|q| !q.answer_segment_ids.is_empty());
+    questions
+}
+
+/// The last sentence of a transcript
+fn last_sentence(text: &str) -> &str {
+    let trimmed = text.trim().trim_end_matches(['"', '\'', ')']);
+    let body = trimmed.trim_end_matches(['?', '.', '!']);
+    match body.rfind(['.', '?', '!']) {
+        Some(end) => trimmed[end + 1..].trim(),
+        None => trimmed,
+    }
+}
+
+/// The last sentence of a transcript that asks something
+fn question_sentence(text: &str) -> Option<String> {
+    let mut sentence_start = 0;
+    let mut question = None;
+    for (i, c) in text.char_indices() {
+        if matches!(c, '.' | '!' | '?') {
+            if c == '?' {
+                question = Some(text[sentence_start..=i].trim().to_string());
+            }
+            sentence_start = i + 1;
+        }
+    }
+    question.filter(|q| q.len() > 1)
+}
+
+fn content_words(text: &str) -> HashSet<String> {
+    text.split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .map(|w| w.trim_matches('\'').to_lowercase())
+        .filter(|w| w.len() > 1 && !STOP_WORDS.contains(&w.as_str()))
+        .collect()
+}
+
+/// Share of content words two questions have in common (Jaccard)
+fn similarity(a: &str, b: &str) -> f64 {
+    let (a, b) = (content_words(a), content_words(b));
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    a.intersection(&b).count() as f64 / a.union(&b).count() as f64
+}
+
+/// Find the questions asked in the project's interviews and tag the segments answering them,
+/// replacing what an earlier run found. Questions asked on camera are matched to `listed` ones
+/// (which come first, in order, with their topics); the same question put to two interviewees
+/// is recorded once; a follow-up that matches nothing falls under the topic of the question
+/// before it.
+pub fn detect(db: &Database, project_id: i64, listed: &[ListedQuestion]) -> Result<Vec<InterviewQuestion>> {
+    let segments = db.get_segments_for_project(project_id)?;
+    let mut by_asset: BTreeMap<i64, Vec<&Segment>> = BTreeMap::new();
+    for (segment, _) in &segments {
+        by_asset.entry(segment.media_asset_id).or_default().push(segment);
+    }
+
+    let mut questions: Vec<NewInterviewQuestion> = listed
+        .iter()
+        .map(|q| NewInterviewQuestion {
+            text: q.text.trim().to_string(),
+            topic: q.topic.clone().filter(|t| !t.trim().is_empty()),
+            source: "list".to_string(),
+            ..Default::default()
+        })
+        .collect();
+    for (asset_id, mut asset_segments) in by_asset {
+        asset_segments.sort_by_key(|s| s.start_ticks);
+        let owned: Vec<Segment> = asset_segments.iter().map(|s| (*s).clone()).collect();
+        if !segment_kind::is_interview(&owned) {
+            continue;
+        }
+        let asked = asked_questions(&asset_segments);
+        eprintln!("[INTERVIEW] Asset {}: {} question(s) asked", asset_id, asked.len());
+
+        let mut previous_topic: Option<String> = None;
+        for question in asked {
+            let best = |source: &str, threshold: f64| {
+                questions
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, q)| q.source == source)
+                    .map(|(i, q)| (i, similarity(&q.text, &question.text)))
+                    .filter(|(_, score)| *score >= threshold)
+                    .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+                    .map(|(i, _)| i)
+            };
+            let index = match best("list", LIST_MATCH_SIMILARITY).or_else(|| best("transcript", SAME_QUESTION_SIMILARITY)) {
+                Some(index) => index,
+                None => {
+                    questions.push(NewInterviewQuestion {
+                        text: question.text.clone(),
+                        topic: previous_topic.clone(),
+                        source: "transcript".to_string(),
+                        ..Default::default()
+                    });
+                    questions.len() - 1
+                }
+            };
+            let entry = &mut questions[index];
+            entry.question_segment_ids.extend(&question.question_segment_ids);
+            entry.answer_segment_ids.extend(&question.answer_segment_ids);
+            previous_topic = entry.topic.clone();
+        }
+    }
+
+    db.replace_interview_questions(project_id, &questions)?;
+    db.list_interview_questions(project_id)
+}
+
+/// Topics of an interview cut in order, each with its questions; a question without a topic is
+/// a topic of its own, named after it
+pub fn topics(questions: &[InterviewQuestion]) -> Vec<(String, Vec<&InterviewQuestion>)> {
+    let mut topics: Vec<(String, Vec<&InterviewQuestion>)> = Vec::new();
+    for question in questions.iter().filter(|q| !q.answer_segment_ids.is_empty()) {
+        let name = question.topic.clone().unwrap_or_else(|| question.text.clone());
+        match topics.iter_mut().find(|(topic, _)| *topic == name) {
+            Some((_, questions)) => questions.push(question),
+            None => topics.push((name, vec![question])),
+        }
+    }
+    topics
+}
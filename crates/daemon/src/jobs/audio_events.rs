@@ -0,0 +1,159 @@
+use anyhow::Result;
+use reqwest;
+use serde_json;
+use std::sync::Arc;
+
+use crate::db::Database;
+use crate::jobs::JobManager;
+use engine::timeline::TICKS_PER_SECOND;
+
+use crate::llm::ml_service_url;
+
+/// Tag kind used for audio events in segments.tags_json
+pub const AUDIO_EVENT_KIND: &str = "audio";
+
+/// Event labels and the query words that ask for them ("find the moments where people laugh")
+const AUDIO_EVENT_QUERY_TERMS: &[(&str, &[&str])] = &[
+    ("laughter", &["laugh", "laughs", "laughing", "laughter", "giggle", "giggling", "funny"]),
+    ("applause", &["applause", "applaud", "applauding", "clap", "clapping"]),
+    ("music", &["music", "musical", "song", "singing", "soundtrack"]),
+    ("crowd", &["crowd", "cheer", "cheering", "audience", "chatter"]),
+];
+
+/// Audio event labels referenced by a free-text query
+pub fn audio_events_in_query(query: &str) -> Vec<&'static str> {
+    let words: Vec<String> = query
+        .split(|c: char| !c.is_alphanumeric())
+        .map(|w| w.to_lowercase())
+        .collect();
+
+    AUDIO_EVENT_QUERY_TERMS
+        .iter()
+        .filter(|(_, terms)| words.iter().any(|w| terms.contains(&w.as_str())))
+        .map(|(label, _)| *label)
+        .collect()
+}
+
+/// Process AnalyzeAudioEvents job - detects non-speech events and tags intersecting segments
+pub async fn process_analyze_audio_events(
+    db: Arc<Database>,
+    job_manager: Arc<JobManager>,
+    job_id: i64,
+    asset_id: i64,
+    media_path: &str,
+) -> Result<()> {
+    // Reuse the cached mono 16kHz audio extracted for transcription
+    let audio_path = crate::jobs::transcribe::ensure_transcription_audio(asset_id, media_path).await?;
+    job_manager.update_job_status(job_id, crate::jobs::JobStatus::Running, Some(0.1))?;
+
+    let client = reqwest::Client::new();
+    let response = client
+        .post(format!("{}/audio/events", ml_service_url()))
+        .json(&serde_json::json!({
+            "mediaPath": audio_path.to_string_lossy(),
+        }))
+        .send()
+        .await;
+    crate::jobs::transcribe::release_transcription_audio(&db, asset_id).await;
+    let response = response?;
+
+    if !response.status().is_success() {
+        return Err(anyhow::anyhow!("ML service audio events failed: {}", response.status()));
+    }
+
+    let events_response: serde_json::Value = response.json().await?;
+    let events = events_response
+        .get("events")
+        .and_then(|e| e.as_array())
+        .cloned()
+        .unwrap_or_default();
+    let loudness_windows: Vec<(i64, i64, f64)> = events_response
+        .get("loudness")
+        .and_then(|l| l.as_array())
+        .map(|windows| {
+            windows
+                .iter()
+                .filter_map(|w| Some((
+                    (w.get("start")?.as_f64()? * TICKS_PER_SECOND as f64) as i64,
+                    (w.get("end")?.as_f64()? * TICKS_PER_SECOND as f64) as i64,
+                    w.get("rms_db")?.as_f64()?,
+                )))
+                .collect()
+        })
+        .unwrap_or_default();
+    job_manager.update_job_status(job_id, crate::jobs::JobStatus::Running, Some(0.7))?;
+
+    let segments = db.get_segments_by_asset(asset_id)?;
+    let mut tagged_count = 0;
+    for segment in &segments {
+        let segment_start_ticks = Database::get_coalesced_src_in(segment);
+        let segment_end_ticks = Database::get_coalesced_src_out(segment);
+
+        // (label, max confidence) across intersecting events
+        let mut segment_events: Vec<(String, f64)> = Vec::new();
+        for event in &events {
+            if let (Some(start_sec), Some(end_sec), Some(label), Some(confidence)) = (
+                event.get("start").and_then(|v| v.as_f64()),
+                event.get("end").and_then(|v| v.as_f64()),
+                event.get("label").and_then(|v| v.as_str()),
+                event.get("confidence").and_then(|v| v.as_f64()),
+            ) {
+                let event_start_ticks = (start_sec * TICKS_PER_SECOND as f64) as i64;
+                let event_end_ticks = (end_sec * TICKS_PER_SECOND as f64) as i64;
+                if event_start_ticks < segment_end_ticks && event_end_ticks > segment_start_ticks {
+                    match segment_events.iter_mut().find(|(l, _)| l == label) {
+                        Some(existing) => existing.1 = existing.1.max(confidence),
+                        None => segment_events.push((label.to_string(), confidence)),
+                    }
+                }
+            }
+        }
+
+        let tags: Vec<serde_json::Value> = segment_events
+            .iter()
+            .map(|(label, confidence)| serde_json::json!({
+                "label": label,
+                "kind": AUDIO_EVENT_KIND,
+                "confidence": confidence,
+            }))
+            .collect();
+        if !tags.is_empty() {
+            tagged_count += 1;
+        }
+        // Always replace, so re-running clears events that are no longer detected
+        db.replace_segment_tags(segment.id, &[AUDIO_EVENT_KIND], &tags)?;
+
+        // Loudness level and dynamics (swing between quietest and loudest window)
+        let levels: Vec<f64> = loudness_windows
+            .iter()
+            .filter(|(start, end, _)| *start < segment_end_ticks && *end > segment_start_ticks)
+            .map(|(_, _, db_level)| *db_level)
+            .collect();
+        if !levels.is_empty() {
+            let mean_db = levels.iter().sum::<f64>() / levels.len() as f64;
+            let max_db = levels.iter().cloned().fold(f64::MIN, f64::max);
+            let min_db = levels.iter().cloned().fold(f64::MAX, f64::min);
+            db.merge_segment_scores(segment.id, &serde_json::json!({
+                "loudness": {
+                    "mean_db": mean_db,
+                    "max_db": max_db,
+                    "dynamics_db": max_db - min_db,
+                },
+            }))?;
+        }
+    }
+
+    // Energy depends on loudness and laughter, so recompute it with the fresh data
+    for segment in db.get_segments_by_asset(asset_id)? {
+        crate::jobs::energy::refresh_segment_energy(&db, &segment)?;
+    }
+
+    eprintln!(
+        "[AUDIO_EVENTS] Asset {}: {} events, {} of {} segments tagged",
+        asset_id, events.len(), tagged_count, segments.len()
+    );
+
+    job_manager.update_job_status(job_id, crate::jobs::JobStatus::Completed, Some(1.0))?;
+
+    Ok(())
+}
@@ -5,12 +5,16 @@ use std::sync::Arc;
 use crate::db::Database;
 use crate::jobs::JobManager;
 use crate::media::ffmpeg::FFmpegWrapper;
+use crate::media::screen_recording::{self, ScreenSegment, SCREEN_TAG_KIND};
+use crate::media::segment_kind::SegmentKind;
+use crate::media::slow_motion::{self, CAPTURE_TAG_KIND, SLOW_MOTION_LABEL};
 
 use engine::timeline::TICKS_PER_SECOND;
 
-const SEGMENT_DURATION_SECONDS: f64 = 5.0; // Fixed 5 second segments for v1
+pub(crate) const SEGMENT_DURATION_SECONDS: f64 = 5.0; // Fixed 5 second segments for v1
 
-/// Process BuildSegments job - creates segments from fixed time windows
+/// Process BuildSegments job - creates segments from fixed time windows, or for screen
+/// recordings from slide changes and bursts of cursor activity
 pub async fn process_build_segments(
     db: Arc<Database>,
     job_manager: Arc<JobManager>,
@@ -31,34 +35,101 @@ pub async fn process_build_segments(
         )?
     };
     
-    // Probe media to get duration
+    // Probe media to get duration (a still has none of its own; it lasts as long as it was given)
     let media_info = FFmpegWrapper::probe(&std::path::PathBuf::from(&asset_path)).await?;
-    let duration_ticks = media_info.duration_ticks;
+    let is_still = db.is_still_asset(asset_id)?;
+    let duration_ticks = if is_still {
+        db.get_media_asset(asset_id)?.map(|a| a.duration_ticks).unwrap_or(0)
+    } else {
+        media_info.duration_ticks
+    };
     let duration_seconds = duration_ticks as f64 / TICKS_PER_SECOND as f64;
+    let recording_start = media_info
+        .creation_time
+        .as_deref()
+        .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok());
     
-    // Create segments with fixed 5s windows (deterministic chunking)
+    // Screen recordings are cut at slide changes and cursor activity; everything else gets
+    // fixed 5s windows (deterministic chunking)
+    let screen_segments = if is_still {
+        None
+    } else {
+        screen_recording::analyze(std::path::Path::new(&asset_path), duration_seconds)
+            .await
+            .unwrap_or_else(|e| {
+                eprintln!("[SEGMENTS] Screen recording detection failed for asset {}: {:?}", asset_id, e);
+                None
+            })
+    };
+    db.set_media_asset_screen_recording(asset_id, screen_segments.is_some())?;
+
+    let to_ticks = |seconds: f64| ((seconds * TICKS_PER_SECOND as f64) as i64).min(duration_ticks);
+    let ranges: Vec<(i64, i64)> = match &screen_segments {
+        Some(planned) => planned.iter().map(|s| (to_ticks(s.start), to_ticks(s.end))).collect(),
+        None => {
+            let segment_duration_ticks = (SEGMENT_DURATION_SECONDS * TICKS_PER_SECOND as f64) as i64;
+            (0..duration_ticks)
+                .step_by(segment_duration_ticks.max(1) as usize)
+                .map(|start| (start, (start + segment_duration_ticks).min(duration_ticks)))
+                .collect()
+        }
+    };
+
     let mut segments_created = 0;
-    let mut current_time_ticks = 0i64;
-    let segment_duration_ticks = (SEGMENT_DURATION_SECONDS * TICKS_PER_SECOND as f64) as i64;
-    
-    while current_time_ticks < duration_ticks {
-        let segment_end_ticks = (current_time_ticks + segment_duration_ticks).min(duration_ticks);
-        
+    let mut created_ids = Vec::with_capacity(ranges.len());
+    for (src_in_ticks, src_out_ticks) in ranges {
         // Create segment with stable identity (write only to src_in_ticks/src_out_ticks)
-        let _segment_id = db.create_segment(
+        let segment_id = db.create_segment(
             project_id,
             asset_id,
-            current_time_ticks,
-            segment_end_ticks,
+            src_in_ticks,
+            src_out_ticks,
         )?;
+        created_ids.push(segment_id);
+
+        // Wall-clock capture time of the segment start (used for "day 2"-style section pins)
+        if let Some(start) = recording_start {
+            let offset_ms = src_in_ticks * 1000 / TICKS_PER_SECOND;
+            let capture_time = start + chrono::Duration::milliseconds(offset_ms);
+            db.update_segment_capture_time(segment_id, &capture_time.to_rfc3339())?;
+        }
         
         segments_created += 1;
-        current_time_ticks = segment_end_ticks;
         
         // Update progress
-        let progress = current_time_ticks as f64 / duration_ticks as f64;
+        let progress = src_out_ticks as f64 / duration_ticks as f64;
         job_manager.update_job_status(job_id, crate::jobs::JobStatus::Running, Some(progress))?;
     }
+
+    // Footage shot at 120fps and up can play in slow motion; tag it so planning and search see it
+    let fps = slow_motion::frame_rate(media_info.fps_num, media_info.fps_den);
+    if !is_still && slow_motion::is_slow_motion_capable(fps) {
+        for segment_id in &created_ids {
+            db.replace_segment_tags(*segment_id, &[CAPTURE_TAG_KIND], &[serde_json::json!({
+                "label": SLOW_MOTION_LABEL,
+                "kind": CAPTURE_TAG_KIND,
+                "confidence": 1.0,
+            })])?;
+        }
+        eprintln!("[SEGMENTS] Asset {} was shot at {:.0} fps; tagged as slow-motion capable", asset_id, fps);
+    }
+
+    if let Some(planned) = screen_segments {
+        let screen_segments: Vec<(i64, ScreenSegment)> = created_ids.into_iter().zip(planned).collect();
+        for (segment_id, segment) in &screen_segments {
+            db.update_segment_metadata(*segment_id, None, None, None, None, None, None, Some(SegmentKind::Screen.as_str()))?;
+            db.replace_segment_tags(*segment_id, &[SCREEN_TAG_KIND], &[serde_json::json!({
+                "label": segment.activity.as_str(),
+                "kind": SCREEN_TAG_KIND,
+                "confidence": 1.0,
+            })])?;
+        }
+        // Window titles need the ML service's OCR; without it the segments just go untitled
+        match screen_recording::tag_window_titles(&db, std::path::Path::new(&asset_path), &screen_segments).await {
+            Ok(tagged) => eprintln!("[SEGMENTS] Asset {}: window titles read for {} of {} segments", asset_id, tagged, screen_segments.len()),
+            Err(e) => eprintln!("[SEGMENTS] Window title OCR skipped for asset {}: {:?}", asset_id, e),
+        }
+    }
     
     // Update asset analysis state
     db.update_asset_analysis_state(asset_id, "segments_built_at", None)?;
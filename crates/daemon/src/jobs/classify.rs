@@ -0,0 +1,46 @@
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use crate::db::Database;
+use crate::jobs::{JobManager, JobType};
+use crate::media::segment_kind::{self, SegmentKind};
+
+/// Process ClassifySegments job - assigns every segment of the asset a kind from its
+/// transcript and vision signals (re-running replaces earlier kinds)
+pub async fn process_classify_segments(
+    db: Arc<Database>,
+    job_manager: Arc<JobManager>,
+    job_id: i64,
+    asset_id: i64,
+) -> Result<()> {
+    let segments = db.get_segments_by_asset(asset_id)?;
+    let interview = segment_kind::is_interview(&segments);
+
+    let mut counts: BTreeMap<SegmentKind, usize> = BTreeMap::new();
+    for segment in &segments {
+        let kind = segment_kind::classify(segment, interview);
+        if segment.segment_kind.as_deref() != Some(kind.as_str()) {
+            db.update_segment_metadata(segment.id, None, None, None, None, None, None, Some(kind.as_str()))?;
+        }
+        *counts.entry(kind).or_insert(0) += 1;
+    }
+
+    let summary: Vec<String> = counts.iter().map(|(kind, count)| format!("{} {}", count, kind.as_str())).collect();
+    eprintln!("[CLASSIFY] Asset {}: {}", asset_id, if summary.is_empty() { "no segments".to_string() } else { summary.join(", ") });
+    job_manager.set_job_result(job_id, serde_json::json!({
+        "interview": interview,
+        "kinds": counts.iter().map(|(kind, count)| (kind.as_str(), *count)).collect::<BTreeMap<_, _>>(),
+    }))?;
+    job_manager.update_job_status(job_id, crate::jobs::JobStatus::Completed, Some(1.0))?;
+    Ok(())
+}
+
+/// Queue classification of an asset's segments; a run already queued for it is reused
+pub fn queue_classification(job_manager: &JobManager, asset_id: i64) -> Result<i64> {
+    job_manager.create_job(
+        JobType::ClassifySegments,
+        Some(serde_json::json!({ "asset_id": asset_id })),
+        Some(format!("ClassifySegments:{}", asset_id)),
+    )
+}
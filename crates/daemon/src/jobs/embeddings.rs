@@ -5,9 +5,11 @@ use serde_json;
 use std::sync::Arc;
 
 use crate::db::Database;
+use crate::embeddings::registry::{TEXT_MODEL, VISION_MODEL};
+use crate::events::Event;
 use crate::jobs::JobManager;
 
-const ML_SERVICE_URL: &str = "http://127.0.0.1:8001";
+use crate::llm::ml_service_url;
 const TICKS_PER_SECOND: i64 = 48000;
 
 /// Convert ticks to seconds
@@ -15,53 +17,8 @@ fn ticks_to_seconds(ticks: i64) -> f64 {
     ticks as f64 / TICKS_PER_SECOND as f64
 }
 
-/// Compute fusion embedding by combining text and vision embeddings with weighted combination
-/// fusion = normalize(Wt * text_emb + Wv * vision_emb)
-/// Default weights: Wt=0.6, Wv=0.4
-fn compute_fusion_embedding(
-    text_emb: &[f32],
-    vision_emb: &[f32],
-    weight_text: f32,
-    weight_vision: f32,
-) -> Vec<f32> {
-    // Normalize text embedding
-    let text_norm: f32 = text_emb.iter().map(|x| x * x).sum::<f32>().sqrt();
-    let text_emb_norm: Vec<f32> = if text_norm > 0.0 {
-        text_emb.iter().map(|x| x / text_norm).collect()
-    } else {
-        text_emb.to_vec()
-    };
-    
-    // Normalize vision embedding
-    let vision_norm: f32 = vision_emb.iter().map(|x| x * x).sum::<f32>().sqrt();
-    let vision_emb_norm: Vec<f32> = if vision_norm > 0.0 {
-        vision_emb.iter().map(|x| x / vision_norm).collect()
-    } else {
-        vision_emb.to_vec()
-    };
-    
-    // Handle dimension mismatch: use the smaller dimension or pad/truncate
-    let min_dim = text_emb_norm.len().min(vision_emb_norm.len());
-    let text_trimmed: Vec<f32> = text_emb_norm.iter().take(min_dim).copied().collect();
-    let vision_trimmed: Vec<f32> = vision_emb_norm.iter().take(min_dim).copied().collect();
-    
-    // Weighted combination
-    let fusion: Vec<f32> = text_trimmed.iter()
-        .zip(vision_trimmed.iter())
-        .map(|(t, v)| weight_text * t + weight_vision * v)
-        .collect();
-    
-    // Renormalize result
-    let fusion_norm: f32 = fusion.iter().map(|x| x * x).sum::<f32>().sqrt();
-    if fusion_norm > 0.0 {
-        fusion.iter().map(|x| x / fusion_norm).collect()
-    } else {
-        fusion
-    }
-}
-
-/// Construct structured text for embedding from segment metadata
-fn construct_semantic_text(segment: &crate::db::Segment) -> String {
+/// Construct structured text for embedding from segment metadata (also what re-ranking scores)
+pub(crate) fn construct_semantic_text(segment: &crate::db::Segment) -> String {
     let mut parts = Vec::new();
     
     // Format as structured text: spoken, summary, keywords
@@ -74,6 +31,29 @@ fn construct_semantic_text(segment: &crate::db::Segment) -> String {
         parts.push(format!("summary: {}", summary));
     }
     
+    if let Some(ref text_in_video) = segment.text_in_video {
+        parts.push(format!("on-screen text: {}", text_in_video.replace('\n', " | ")));
+    }
+    
+    if let Some(ref tags_json) = segment.tags_json {
+        if let Ok(tags) = serde_json::from_str::<Vec<serde_json::Value>>(tags_json) {
+            let sounds: Vec<&str> = tags.iter()
+                .filter(|t| t.get("kind").and_then(|k| k.as_str()) == Some(crate::jobs::audio_events::AUDIO_EVENT_KIND))
+                .filter_map(|t| t.get("label").and_then(|l| l.as_str()))
+                .collect();
+            if !sounds.is_empty() {
+                parts.push(format!("sounds: {}", sounds.join(", ")));
+            }
+            let windows: Vec<&str> = tags.iter()
+                .filter(|t| t.get("kind").and_then(|k| k.as_str()) == Some(crate::media::screen_recording::WINDOW_TAG_KIND))
+                .filter_map(|t| t.get("label").and_then(|l| l.as_str()))
+                .collect();
+            if !windows.is_empty() {
+                parts.push(format!("window: {}", windows.join(", ")));
+            }
+        }
+    }
+    
     if let Some(ref keywords_json) = segment.keywords_json {
         if let Ok(keywords) = serde_json::from_str::<serde_json::Value>(keywords_json) {
             if let Some(kw_array) = keywords.get("keywords").and_then(|k| k.as_array()) {
@@ -94,7 +74,8 @@ fn construct_semantic_text(segment: &crate::db::Segment) -> String {
     }
 }
 
-/// Process EmbedSegments job - generates text, vision, and fusion embeddings (idempotent)
+/// Process EmbedSegments job - generates text and vision embeddings, stored separately and
+/// fused only as scores at query time (idempotent)
 pub async fn process_embed_segments(
     db: Arc<Database>,
     job_manager: Arc<JobManager>,
@@ -121,6 +102,9 @@ pub async fn process_embed_segments(
         let start_time = ticks_to_seconds(src_in);
         let end_time = ticks_to_seconds(src_out);
         
+        // 0. Refresh lexical index (transcript + on-screen text are final by now)
+        db.reindex_segment_text(segment.id)?;
+        
         // 1. Generate text embedding
         let has_text_emb: bool = {
             let conn = db.conn.lock().unwrap();
@@ -137,7 +121,7 @@ pub async fn process_embed_segments(
             
             // Call ML service /embeddings/text endpoint
             let response = client
-                .post(&format!("{}/embeddings/text", ML_SERVICE_URL))
+                .post(format!("{}/embeddings/text", ml_service_url()))
                 .json(&serde_json::json!({
                     "text": semantic_text
                 }))
@@ -156,36 +140,11 @@ pub async fn process_embed_segments(
                     
                     eprintln!("[EMBEDDING] Segment {}: Generated text embedding ({} dims)", segment.id, embedding.len());
                     
-                    let embedding_bytes: Vec<u8> = embedding.iter()
-                        .flat_map(|f| f.to_le_bytes().to_vec())
-                        .collect();
-                    
-                    // Store in database
-                    {
-                        let conn = db.conn.lock().unwrap();
-                        // Check if embedding already exists
-                        let exists: bool = conn.query_row(
-                            "SELECT COUNT(*) > 0 FROM embeddings WHERE segment_id = ?1 AND embedding_type = 'text' AND model_name = 'all-MiniLM-L6-v2'",
-                            params![segment.id],
-                            |row| row.get(0),
-                        ).unwrap_or(false);
-                        
-                        if exists {
-                            eprintln!("[EMBEDDING] Text embedding for segment {} already exists, skipping insert", segment.id);
-                        } else {
-                            let result = conn.execute(
-                                "INSERT INTO embeddings (segment_id, embedding_type, model_name, model_version, vector_blob) VALUES (?1, ?2, ?3, ?4, ?5)",
-                                params![segment.id, "text", "all-MiniLM-L6-v2", "1", embedding_bytes],
-                            );
-                            match result {
-                                Ok(rows_affected) => {
-                                    eprintln!("[EMBEDDING] Successfully stored text embedding for segment {} ({} rows affected)", segment.id, rows_affected);
-                                }
-                                Err(e) => {
-                                    eprintln!("[EMBEDDING] Error storing text embedding for segment {}: {:?}", segment.id, e);
-                                }
-                            }
-                        }
+                    // Store in database (validated against the registered model's dimension)
+                    match db.store_embedding(segment.id, TEXT_MODEL.embedding_type, TEXT_MODEL.model_name, &embedding) {
+                        Ok(true) => eprintln!("[EMBEDDING] Successfully stored text embedding for segment {}", segment.id),
+                        Ok(false) => eprintln!("[EMBEDDING] Text embedding for segment {} already exists, skipping insert", segment.id),
+                        Err(e) => eprintln!("[EMBEDDING] Error storing text embedding for segment {}: {:?}", segment.id, e),
                     }
                 }
             }
@@ -205,7 +164,7 @@ pub async fn process_embed_segments(
         if !has_vision_emb {
             // Call ML service /embeddings/vision endpoint
             let response = client
-                .post(&format!("{}/embeddings/vision", ML_SERVICE_URL))
+                .post(format!("{}/embeddings/vision", ml_service_url()))
                 .json(&serde_json::json!({
                     "media_path": media_path,
                     "start_time": start_time,
@@ -226,125 +185,13 @@ pub async fn process_embed_segments(
                     
                     eprintln!("[EMBEDDING] Segment {}: Generated vision embedding ({} dims)", segment.id, embedding.len());
                     
-                    let embedding_bytes: Vec<u8> = embedding.iter()
-                        .flat_map(|f| f.to_le_bytes().to_vec())
-                        .collect();
-                    
-                    // Store in database
-                    {
-                        let conn = db.conn.lock().unwrap();
-                        // Check if embedding already exists
-                        let exists: bool = conn.query_row(
-                            "SELECT COUNT(*) > 0 FROM embeddings WHERE segment_id = ?1 AND embedding_type = 'vision' AND model_name = 'clip-vit-b-32'",
-                            params![segment.id],
-                            |row| row.get(0),
-                        ).unwrap_or(false);
-                        
-                        if exists {
-                            eprintln!("[EMBEDDING] Vision embedding for segment {} already exists, skipping insert", segment.id);
-                        } else {
-                            let result = conn.execute(
-                                "INSERT INTO embeddings (segment_id, embedding_type, model_name, model_version, vector_blob) VALUES (?1, ?2, ?3, ?4, ?5)",
-                                params![segment.id, "vision", "clip-vit-b-32", "1", embedding_bytes],
-                            );
-                            match result {
-                                Ok(rows_affected) => {
-                                    eprintln!("[EMBEDDING] Successfully stored vision embedding for segment {} ({} rows affected)", segment.id, rows_affected);
-                                }
-                                Err(e) => {
-                                    eprintln!("[EMBEDDING] Error storing vision embedding for segment {}: {:?}", segment.id, e);
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
-        
-        // 3. Generate fusion embedding (requires both text and vision)
-        // Note: We need to retrieve embeddings after they're stored, so use a fresh connection
-        let has_fusion_emb: bool = {
-            let conn = db.conn.lock().unwrap();
-            let result = conn.query_row(
-                "SELECT COUNT(*) > 0 FROM embeddings WHERE segment_id = ?1 AND embedding_type = 'fusion' AND model_name = 'fusion-0.6-0.4'",
-                params![segment.id],
-                |row| row.get(0),
-            ).unwrap_or(false);
-            result
-        };
-        
-        if !has_fusion_emb {
-            // Retrieve text and vision embeddings (use fresh connection to ensure we see the just-stored embeddings)
-            let (text_emb, vision_emb) = {
-                let conn = db.conn.lock().unwrap();
-                
-                // Get text embedding
-                let text_emb_blob: Option<Vec<u8>> = conn.query_row(
-                    "SELECT vector_blob FROM embeddings WHERE segment_id = ?1 AND embedding_type = 'text' AND model_name = 'all-MiniLM-L6-v2'",
-                    params![segment.id],
-                    |row| row.get(0),
-                ).ok();
-                
-                // Get vision embedding
-                let vision_emb_blob: Option<Vec<u8>> = conn.query_row(
-                    "SELECT vector_blob FROM embeddings WHERE segment_id = ?1 AND embedding_type = 'vision' AND model_name = 'clip-vit-b-32'",
-                    params![segment.id],
-                    |row| row.get(0),
-                ).ok();
-                
-                eprintln!("[EMBEDDING] Segment {}: Retrieving embeddings for fusion - text: {}, vision: {}", 
-                    segment.id, text_emb_blob.is_some(), vision_emb_blob.is_some());
-                
-                // Convert blobs back to f32 vectors
-                let text_emb = text_emb_blob.map(|blob| {
-                    blob.chunks(4)
-                        .map(|chunk| {
-                            let bytes: [u8; 4] = [chunk[0], chunk[1], chunk[2], chunk[3]];
-                            f32::from_le_bytes(bytes)
-                        })
-                        .collect::<Vec<f32>>()
-                });
-                
-                let vision_emb = vision_emb_blob.map(|blob| {
-                    blob.chunks(4)
-                        .map(|chunk| {
-                            let bytes: [u8; 4] = [chunk[0], chunk[1], chunk[2], chunk[3]];
-                            f32::from_le_bytes(bytes)
-                        })
-                        .collect::<Vec<f32>>()
-                });
-                
-                (text_emb, vision_emb)
-            };
-            
-            // Compute fusion if both embeddings exist
-            if let (Some(text_vec), Some(vision_vec)) = (text_emb, vision_emb) {
-                let fusion_vec = compute_fusion_embedding(&text_vec, &vision_vec, 0.6, 0.4);
-                
-                eprintln!("[EMBEDDING] Segment {}: Generated fusion embedding ({} dims)", segment.id, fusion_vec.len());
-                
-                let embedding_bytes: Vec<u8> = fusion_vec.iter()
-                    .flat_map(|f| f.to_le_bytes().to_vec())
-                    .collect();
-                
-                // Store in database
-                {
-                    let conn = db.conn.lock().unwrap();
-                    let result = conn.execute(
-                        "INSERT INTO embeddings (segment_id, embedding_type, model_name, model_version, vector_blob) VALUES (?1, ?2, ?3, ?4, ?5)",
-                        params![segment.id, "fusion", "fusion-0.6-0.4", "1", embedding_bytes],
-                    );
-                    match result {
-                        Ok(rows_affected) => {
-                            eprintln!("[EMBEDDING] Successfully stored fusion embedding for segment {} ({} rows affected)", segment.id, rows_affected);
-                        }
-                        Err(e) => {
-                            eprintln!("[EMBEDDING] Error storing fusion embedding for segment {}: {:?}", segment.id, e);
-                        }
+                    // Store in database (validated against the registered model's dimension)
+                    match db.store_embedding(segment.id, VISION_MODEL.embedding_type, VISION_MODEL.model_name, &embedding) {
+                        Ok(true) => eprintln!("[EMBEDDING] Successfully stored vision embedding for segment {}", segment.id),
+                        Ok(false) => eprintln!("[EMBEDDING] Vision embedding for segment {} already exists, skipping insert", segment.id),
+                        Err(e) => eprintln!("[EMBEDDING] Error storing vision embedding for segment {}: {:?}", segment.id, e),
                     }
                 }
-            } else {
-                eprintln!("[EMBEDDING] Segment {}: Skipping fusion embedding (missing text or vision embedding)", segment.id);
             }
         }
         
@@ -370,7 +217,11 @@ pub async fn process_embed_segments(
     
     if project_id > 0 {
         // Emit AnalysisComplete event for orchestrator
-        job_manager.emit_analysis_complete(asset_id, project_id, "Embedded".to_string());
+        job_manager.events().publish(Event::AnalysisComplete {
+            project_id,
+            asset_id,
+            readiness: "Embedded".to_string(),
+        });
     }
     
     eprintln!("[EMBEDDING] Completed EmbedSegments job {} for asset_id: {} (processed {} segments)", job_id, asset_id, processed_count);
@@ -0,0 +1,118 @@
+use anyhow::Result;
+use serde_json;
+
+use crate::db::{Database, Segment};
+use crate::jobs::audio_events::AUDIO_EVENT_KIND;
+use engine::timeline::TICKS_PER_SECOND;
+
+/// Words per second treated as maximally energetic speech (conversational is ~2.5)
+const FAST_SPEECH_WPS: f64 = 4.0;
+/// Mean frame difference treated as maximal motion (matches the action cutoff in segment
+/// classification)
+const HIGH_MOTION_SCORE: f64 = 50.0;
+/// Loudness (dBFS) mapped to 0 and 1 respectively
+const QUIET_DB: f64 = -45.0;
+const LOUD_DB: f64 = -15.0;
+/// Level swing (dB) across a segment treated as maximally dynamic
+const FULL_DYNAMICS_DB: f64 = 20.0;
+
+/// Component weights; missing components are dropped and the rest renormalized
+const SPEECH_RATE_WEIGHT: f64 = 0.3;
+const LOUDNESS_WEIGHT: f64 = 0.25;
+const MOTION_WEIGHT: f64 = 0.25;
+const REACTION_WEIGHT: f64 = 0.2;
+
+/// Audio events that signal an excited moment
+const REACTION_EVENTS: &[&str] = &["laughter", "applause"];
+
+/// Compute a 0..1 energy score for a segment from speech rate, loudness dynamics, motion and
+/// laughter/applause events. Returns None when no signal is available yet.
+pub fn compute_segment_energy(segment: &Segment) -> Option<serde_json::Value> {
+    let duration_sec = (Database::get_coalesced_src_out(segment) - Database::get_coalesced_src_in(segment)) as f64
+        / TICKS_PER_SECOND as f64;
+    let mut components = serde_json::Map::new();
+
+    if let Some(ref transcript) = segment.transcript {
+        if duration_sec > 0.0 {
+            let words_per_sec = transcript.split_whitespace().count() as f64 / duration_sec;
+            components.insert("speech_rate".to_string(), (words_per_sec / FAST_SPEECH_WPS).min(1.0).into());
+        }
+    }
+
+    let scores: Option<serde_json::Value> = segment
+        .scores_json
+        .as_deref()
+        .and_then(|json| serde_json::from_str(json).ok());
+    if let Some(loudness) = scores.as_ref().and_then(|s| s.get("loudness")) {
+        if let (Some(mean_db), Some(dynamics_db)) = (
+            loudness.get("mean_db").and_then(|v| v.as_f64()),
+            loudness.get("dynamics_db").and_then(|v| v.as_f64()),
+        ) {
+            let level = ((mean_db - QUIET_DB) / (LOUD_DB - QUIET_DB)).clamp(0.0, 1.0);
+            let dynamics = (dynamics_db / FULL_DYNAMICS_DB).clamp(0.0, 1.0);
+            components.insert("loudness".to_string(), (0.5 * level + 0.5 * dynamics).into());
+        }
+    }
+
+    let motion = segment
+        .quality_json
+        .as_deref()
+        .and_then(|json| serde_json::from_str::<serde_json::Value>(json).ok())
+        .and_then(|q| q.get("motion_score").and_then(|v| v.as_f64()));
+    if let Some(motion) = motion {
+        components.insert("motion".to_string(), (motion / HIGH_MOTION_SCORE).clamp(0.0, 1.0).into());
+    }
+
+    // Reaction = strongest laughter/applause confidence; only counts once audio tagging has run
+    let tags: Option<Vec<serde_json::Value>> = segment
+        .tags_json
+        .as_deref()
+        .and_then(|json| serde_json::from_str(json).ok());
+    let audio_tagged = scores.as_ref().map(|s| s.get("loudness").is_some()).unwrap_or(false);
+    if audio_tagged {
+        let reaction = tags
+            .unwrap_or_default()
+            .iter()
+            .filter(|t| t.get("kind").and_then(|k| k.as_str()) == Some(AUDIO_EVENT_KIND))
+            .filter(|t| {
+                t.get("label")
+                    .and_then(|l| l.as_str())
+                    .map(|l| REACTION_EVENTS.contains(&l))
+                    .unwrap_or(false)
+            })
+            .filter_map(|t| t.get("confidence").and_then(|c| c.as_f64()))
+            .fold(0.0, f64::max);
+        components.insert("reaction".to_string(), reaction.into());
+    }
+
+    let weights = [
+        ("speech_rate", SPEECH_RATE_WEIGHT),
+        ("loudness", LOUDNESS_WEIGHT),
+        ("motion", MOTION_WEIGHT),
+        ("reaction", REACTION_WEIGHT),
+    ];
+    let mut weighted_sum = 0.0;
+    let mut total_weight = 0.0;
+    for (name, weight) in weights {
+        if let Some(value) = components.get(name).and_then(|v| v.as_f64()) {
+            weighted_sum += weight * value;
+            total_weight += weight;
+        }
+    }
+    if total_weight == 0.0 {
+        return None;
+    }
+
+    Some(serde_json::json!({
+        "energy": weighted_sum / total_weight,
+        "energy_components": components,
+    }))
+}
+
+/// Recompute and store energy for a segment (called whenever one of its inputs changes)
+pub fn refresh_segment_energy(db: &Database, segment: &Segment) -> Result<()> {
+    if let Some(energy) = compute_segment_energy(segment) {
+        db.merge_segment_scores(segment.id, &energy)?;
+    }
+    Ok(())
+}
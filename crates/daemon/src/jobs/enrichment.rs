@@ -4,8 +4,15 @@ use std::sync::Arc;
 
 use crate::db::Database;
 use crate::jobs::JobManager;
+use crate::jobs::transcribe::dominant_language;
 use engine::timeline::TICKS_PER_SECOND;
 
+/// Structured tag kinds produced by vision analysis (audio events use their own kind)
+pub(crate) const VISION_TAG_KINDS: &[&str] = &["scene", "object", "action"];
+
+/// How far a segment boundary may move to get out of a word (SEGMENT_WORD_SNAP_SECONDS)
+const DEFAULT_WORD_SNAP_SECONDS: f64 = 0.5;
+
 /// Helper: Convert seconds to ticks
 fn secs_to_ticks(seconds: f64) -> i64 {
     (seconds * TICKS_PER_SECOND as f64) as i64
@@ -27,6 +34,12 @@ pub async fn process_enrich_segments_from_transcript(
         .and_then(|s| s.as_array())
         .ok_or_else(|| anyhow::anyhow!("Invalid transcript format"))?;
     
+    // Cut between words rather than through them, before transcript text is attached by time
+    let snapped = snap_boundaries_to_words(&db, &db.get_segments_by_asset(asset_id)?, segments_data)?;
+    if snapped > 0 {
+        eprintln!("[ENRICH] Asset {}: moved {} segment boundaries out of words", asset_id, snapped);
+    }
+
     // Get all segments for this asset
     let segments = db.get_segments_by_asset(asset_id)?;
     
@@ -37,6 +50,7 @@ pub async fn process_enrich_segments_from_transcript(
         
         // Find intersecting transcript segments
         let mut transcript_texts = Vec::new();
+        let mut intersecting = Vec::new();
         for transcript_seg in segments_data {
             if let (Some(start_sec), Some(end_sec)) = (
                 transcript_seg.get("start").and_then(|v| v.as_f64()),
@@ -50,6 +64,7 @@ pub async fn process_enrich_segments_from_transcript(
                     if let Some(text) = transcript_seg.get("text").and_then(|v| v.as_str()) {
                         transcript_texts.push(text);
                     }
+                    intersecting.push(transcript_seg.clone());
                 }
             }
         }
@@ -67,6 +82,13 @@ pub async fn process_enrich_segments_from_transcript(
                 Some(&combined_text), // transcript
                 None, // segment_kind
             )?;
+            // Language of the speech in this segment, else the asset's overall language
+            let language = dominant_language(&intersecting)
+                .or_else(|| transcript_data.get("language").and_then(|l| l.as_str()).map(String::from));
+            db.set_segment_language(segment.id, language.as_deref())?;
+            // Who speaks most of the segment, when the transcript was diarized
+            let speaker = dominant_speaker(&intersecting, segment_start_ticks, segment_end_ticks);
+            db.set_segment_speaker(segment.id, speaker.as_deref())?;
             enriched_count += 1;
         }
         
@@ -90,6 +112,105 @@ pub async fn process_enrich_segments_from_transcript(
     Ok(())
 }
 
+/// The diarized speaker heard longest between `start_ticks` and `end_ticks`
+fn dominant_speaker(transcript_segments: &[serde_json::Value], start_ticks: i64, end_ticks: i64) -> Option<String> {
+    let mut heard: Vec<(&str, i64)> = Vec::new();
+    for seg in transcript_segments {
+        let (Some(speaker), Some(start), Some(end)) = (
+            seg.get("speaker").and_then(|s| s.as_str()),
+            seg.get("start").and_then(|v| v.as_f64()),
+            seg.get("end").and_then(|v| v.as_f64()),
+        ) else {
+            continue;
+        };
+        let overlap = secs_to_ticks(end).min(end_ticks) - secs_to_ticks(start).max(start_ticks);
+        match heard.iter_mut().find(|(s, _)| *s == speaker) {
+            Some(entry) => entry.1 += overlap.max(0),
+            None => heard.push((speaker, overlap.max(0))),
+        }
+    }
+    heard.into_iter().max_by_key(|(_, ticks)| *ticks).map(|(speaker, _)| speaker.to_string())
+}
+
+/// Tolerance for snapping segment boundaries to words, from SEGMENT_WORD_SNAP_SECONDS (0 = off)
+fn word_snap_tolerance_ticks() -> i64 {
+    let seconds = match std::env::var("SEGMENT_WORD_SNAP_SECONDS") {
+        Ok(value) => value
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|s| s.is_finite() && *s >= 0.0)
+            .unwrap_or_else(|| {
+                eprintln!("[ENRICH] Invalid SEGMENT_WORD_SNAP_SECONDS '{}', using {}", value, DEFAULT_WORD_SNAP_SECONDS);
+                DEFAULT_WORD_SNAP_SECONDS
+            }),
+        Err(_) => DEFAULT_WORD_SNAP_SECONDS,
+    };
+    secs_to_ticks(seconds)
+}
+
+/// Move each boundary shared by two adjacent segments that falls inside a spoken word to the
+/// nearer edge of that word, if it's within the snap tolerance and both segments keep some
+/// length. Word times come from the transcript's word-level timestamps; boundaries at the
+/// asset's ends, and segments that don't touch (e.g. dynamic ones), are left alone.
+/// Returns how many boundaries moved.
+fn snap_boundaries_to_words(
+    db: &Database,
+    segments: &[crate::db::Segment],
+    transcript_segments: &[serde_json::Value],
+) -> Result<usize> {
+    let tolerance = word_snap_tolerance_ticks();
+    if tolerance == 0 {
+        return Ok(0);
+    }
+    let words: Vec<(i64, i64)> = transcript_segments
+        .iter()
+        .filter_map(|seg| seg.get("words").and_then(|w| w.as_array()))
+        .flatten()
+        .filter_map(|word| Some((
+            secs_to_ticks(word.get("start")?.as_f64()?),
+            secs_to_ticks(word.get("end")?.as_f64()?),
+        )))
+        .collect();
+    if words.is_empty() {
+        return Ok(0);
+    }
+
+    // (id, src in, src out, moved) in source order
+    let mut ranges: Vec<(i64, i64, i64, bool)> = segments
+        .iter()
+        .map(|s| (s.id, Database::get_coalesced_src_in(s), Database::get_coalesced_src_out(s), false))
+        .collect();
+    ranges.sort_by_key(|(_, src_in, _, _)| *src_in);
+
+    let mut snapped = 0;
+    for i in 1..ranges.len() {
+        let (prev_in, boundary) = (ranges[i - 1].1, ranges[i - 1].2);
+        let (next_in, next_out) = (ranges[i].1, ranges[i].2);
+        if boundary != next_in {
+            continue;
+        }
+        let Some((word_start, word_end)) = words.iter().find(|(start, end)| *start < boundary && boundary < *end) else {
+            continue;
+        };
+        let target = if boundary - word_start <= word_end - boundary { *word_start } else { *word_end };
+        if (target - boundary).abs() <= tolerance && target > prev_in && target < next_out {
+            ranges[i - 1].2 = target;
+            ranges[i - 1].3 = true;
+            ranges[i].1 = target;
+            ranges[i].3 = true;
+            snapped += 1;
+        }
+    }
+
+    for (segment_id, src_in, src_out, moved) in ranges {
+        if moved {
+            db.set_segment_source_range(segment_id, src_in, src_out)?;
+        }
+    }
+    Ok(snapped)
+}
+
 /// Process EnrichSegmentsFromVision job - attaches vision data to segments by time intersection
 pub async fn process_enrich_segments_from_vision(
     db: Arc<Database>,
@@ -106,6 +227,10 @@ pub async fn process_enrich_segments_from_vision(
         .and_then(|s| s.as_array())
         .ok_or_else(|| anyhow::anyhow!("Invalid vision format"))?;
     
+    // Project-level threshold for which structured tags count as segment tags
+    let tag_threshold = crate::jobs::vision::VisionConfig::resolve(&db, asset_id, None)?
+        .tag_confidence_threshold;
+    
     // Get all segments for this asset
     let segments = db.get_segments_by_asset(asset_id)?;
     
@@ -120,6 +245,10 @@ pub async fn process_enrich_segments_from_vision(
         let mut tags = Vec::new();
         let mut has_face = false;
         let mut face_bbox = None;
+        let mut shot_type_votes: Vec<String> = Vec::new();
+        let mut ocr_lines: Vec<String> = Vec::new();
+        // (label, kind, max confidence) across intersecting frames
+        let mut structured_tags: Vec<(String, String, f64)> = Vec::new();
         
         for vision_seg in vision_segments {
             if let (Some(start_sec), Some(end_sec)) = (
@@ -146,6 +275,30 @@ pub async fn process_enrich_segments_from_vision(
                             }
                         }
                     }
+                    if let Some(vision_tags) = vision_seg.get("structured_tags").and_then(|v| v.as_array()) {
+                        for tag in vision_tags {
+                            if let (Some(label), Some(kind), Some(confidence)) = (
+                                tag.get("label").and_then(|v| v.as_str()),
+                                tag.get("kind").and_then(|v| v.as_str()),
+                                tag.get("confidence").and_then(|v| v.as_f64()),
+                            ) {
+                                match structured_tags.iter_mut().find(|(l, k, _)| l == label && k == kind) {
+                                    Some(existing) => existing.2 = existing.2.max(confidence),
+                                    None => structured_tags.push((label.to_string(), kind.to_string(), confidence)),
+                                }
+                            }
+                        }
+                    }
+                    if let Some(ocr_text) = vision_seg.get("ocr_text").and_then(|v| v.as_str()) {
+                        for line in ocr_text.lines().map(str::trim).filter(|l| !l.is_empty()) {
+                            if !ocr_lines.iter().any(|existing| existing.eq_ignore_ascii_case(line)) {
+                                ocr_lines.push(line.to_string());
+                            }
+                        }
+                    }
+                    if let Some(shot_type) = vision_seg.get("shot_type").and_then(|v| v.as_str()) {
+                        shot_type_votes.push(shot_type.to_string());
+                    }
                     if let Some(has_face_val) = vision_seg.get("has_face").and_then(|v| v.as_bool()) {
                         if has_face_val {
                             has_face = true;
@@ -175,6 +328,18 @@ pub async fn process_enrich_segments_from_vision(
             "motion_score": avg_motion,
         });
         
+        // Structured tags replace the flat list when present: only labels that clear
+        // the project's confidence threshold are kept as plain scene tags
+        if !structured_tags.is_empty() {
+            structured_tags.sort_by(|a, b| b.2.partial_cmp(&a.2).unwrap_or(std::cmp::Ordering::Equal));
+            tags = Vec::new();
+            for (label, _, confidence) in &structured_tags {
+                if *confidence >= tag_threshold && !tags.contains(label) {
+                    tags.push(label.clone());
+                }
+            }
+        }
+        
         let scene_json = serde_json::json!({
             "tags": tags,
             "has_face": has_face,
@@ -192,6 +357,27 @@ pub async fn process_enrich_segments_from_vision(
             None, // transcript
             None, // segment_kind
         )?;
+
+        // Shot type = most frequent per-frame classification within the segment
+        if let Some(shot_type) = majority_vote(&shot_type_votes) {
+            db.set_segment_shot_type(segment.id, Some(&shot_type))?;
+        }
+
+        // Structured tags (all confidences kept; thresholds are applied at query time)
+        let tags_json: Vec<serde_json::Value> = structured_tags
+            .iter()
+            .map(|(label, kind, confidence)| serde_json::json!({
+                "label": label,
+                "kind": kind,
+                "confidence": confidence,
+            }))
+            .collect();
+        db.replace_segment_tags(segment.id, VISION_TAG_KINDS, &tags_json)?;
+
+        // On-screen text (deduplicated OCR lines across sampled frames)
+        if !ocr_lines.is_empty() {
+            db.set_segment_text_in_video(segment.id, Some(&ocr_lines.join("\n")))?;
+        }
         enriched_count += 1;
         
         // Update progress
@@ -214,3 +400,14 @@ pub async fn process_enrich_segments_from_vision(
     Ok(())
 }
 
+/// Most frequent value (ties broken by first occurrence)
+fn majority_vote(values: &[String]) -> Option<String> {
+    let mut best: Option<(&String, usize)> = None;
+    for value in values {
+        let count = values.iter().filter(|v| *v == value).count();
+        if best.map(|(_, c)| count > c).unwrap_or(true) {
+            best = Some((value, count));
+        }
+    }
+    best.map(|(v, _)| v.clone())
+}
@@ -0,0 +1,232 @@
+use anyhow::{anyhow, Context, Result};
+use serde_json::{json, Value};
+use std::process::Stdio;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+
+use crate::db::{Database, JobWorker};
+use crate::jobs::{resources, JobManager, JobStatus};
+
+/// Version of the request/response shape workers speak
+const PROTOCOL_VERSION: u32 = 1;
+
+/// Asset states a worker can gate its jobs on (see Database::check_asset_prerequisites)
+pub const ASSET_STATES: &[&str] = &[
+    "segments_built",
+    "transcript_ready",
+    "vision_ready",
+    "metadata_ready",
+    "embeddings_ready",
+];
+
+/// Extension kinds are namespaced, `<namespace>.<name>` (e.g. "acme.jersey_numbers"): lowercase
+/// letters, digits, `_` and `-`, separated by dots
+pub fn is_valid_kind(kind: &str) -> bool {
+    let valid_part = |part: &str| {
+        !part.is_empty()
+            && part
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-')
+    };
+    kind.contains('.') && kind.split('.').all(valid_part)
+}
+
+/// Problems with a worker registration, or None when it can be stored
+pub fn validate_worker(worker: &JobWorker) -> Option<String> {
+    if !is_valid_kind(&worker.kind) {
+        return Some(format!("Job kind {} isn't namespaced as <namespace>.<name>", worker.kind));
+    }
+    match (&worker.command, &worker.url) {
+        (Some(command), None) if !command.is_empty() => {}
+        (None, Some(url)) if reqwest::Url::parse(url).is_ok() => {}
+        _ => return Some("Set exactly one of command or url".to_string()),
+    }
+    if let Some(state) = worker.requires.iter().find(|s| !ASSET_STATES.contains(&s.as_str())) {
+        return Some(format!("Unknown asset state {}", state));
+    }
+    if worker.max_attempts < 1 || worker.timeout_secs < 1 {
+        return Some("max_attempts and timeout_secs must be at least 1".to_string());
+    }
+    None
+}
+
+/// Workers from the file at JOB_WORKERS_PATH (a JSON array of registrations with their `kind`).
+/// Command workers can only be set up here: the API registers `url` workers alone, so nothing
+/// reachable over HTTP picks what runs on this machine. Read once; a missing or invalid file,
+/// and invalid entries in it, are logged and ignored.
+pub fn configured_workers() -> &'static [JobWorker] {
+    static WORKERS: OnceLock<Vec<JobWorker>> = OnceLock::new();
+    WORKERS.get_or_init(|| {
+        let Ok(path) = std::env::var("JOB_WORKERS_PATH") else {
+            return Vec::new();
+        };
+        match std::fs::read_to_string(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|json| Ok(serde_json::from_str::<Vec<JobWorker>>(&json)?))
+        {
+            Ok(workers) => workers
+                .into_iter()
+                .filter(|worker| match validate_worker(worker) {
+                    Some(problem) => {
+                        eprintln!("[JOBS] Ignoring configured worker for {}: {}", worker.kind, problem);
+                        false
+                    }
+                    None => true,
+                })
+                .collect(),
+            Err(e) => {
+                eprintln!("[JOBS] Ignoring job workers at {}: {:?}", path, e);
+                Vec::new()
+            }
+        }
+    })
+}
+
+/// Whether a kind's worker comes from JOB_WORKERS_PATH (and so can't be changed through the API)
+pub fn is_configured(kind: &str) -> bool {
+    configured_workers().iter().any(|w| w.kind == kind)
+}
+
+/// The worker for a job kind: the configured one, or else the one registered through the API
+pub fn find_worker(db: &Database, kind: &str) -> Result<Option<JobWorker>> {
+    if let Some(worker) = configured_workers().iter().find(|w| w.kind == kind) {
+        return Ok(Some(worker.clone()));
+    }
+    db.get_job_worker(kind)
+}
+
+/// Whether an extension job for `asset_id` may run: its worker's required asset states are
+/// reached. Jobs without a registered worker are let through so they fail with a clear error.
+pub fn prerequisites_met(db: &Database, kind: &str, asset_id: i64) -> Result<bool> {
+    match find_worker(db, kind)? {
+        Some(worker) => {
+            let requires: Vec<&str> = worker.requires.iter().map(String::as_str).collect();
+            db.check_asset_prerequisites(asset_id, &requires)
+        }
+        None => Ok(true),
+    }
+}
+
+/// Process an extension job with its registered worker. A failed attempt goes back to the queue
+/// until the worker's max_attempts is used up; the worker's result is stored in the payload.
+pub async fn process_external_job(
+    db: Arc<Database>,
+    job_manager: Arc<JobManager>,
+    job_id: i64,
+    kind: &str,
+) -> Result<()> {
+    let job = job_manager
+        .get_job(job_id)?
+        .ok_or_else(|| anyhow!("Job {} not found", job_id))?;
+    let Some(worker) = find_worker(&db, kind)? else {
+        job_manager.fail_job(job_id, &format!("No worker registered for {}", kind))?;
+        return Ok(());
+    };
+
+    let request = json!({
+        "protocol": PROTOCOL_VERSION,
+        "job_id": job_id,
+        "kind": kind,
+        "attempt": job.attempts,
+        "payload": job.payload,
+    });
+    let timeout = Duration::from_secs(worker.timeout_secs as u64);
+    let outcome = match tokio::time::timeout(timeout, run_worker(&worker, &job_manager, job_id, &request)).await {
+        Ok(outcome) => outcome,
+        Err(_) => Err(anyhow!("Timed out after {}s", worker.timeout_secs)),
+    };
+
+    match outcome {
+        Ok(result) => {
+            job_manager.set_job_result(job_id, result)?;
+            job_manager.update_job_status(job_id, JobStatus::Completed, Some(1.0))?;
+        }
+        Err(e) if job.attempts < worker.max_attempts => {
+            eprintln!(
+                "[JOBS] {} job {} failed (attempt {}/{}), retrying: {:?}",
+                kind, job_id, job.attempts, worker.max_attempts, e
+            );
+            job_manager.requeue_job(job_id, &e.to_string())?;
+        }
+        Err(e) => {
+            eprintln!("[JOBS] {} job {} failed: {:?}", kind, job_id, e);
+            job_manager.fail_job(job_id, &e.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+async fn run_worker(worker: &JobWorker, job_manager: &JobManager, job_id: i64, request: &Value) -> Result<Value> {
+    match (&worker.command, &worker.url) {
+        (Some(command), _) if !command.is_empty() => run_stdio_worker(command, job_manager, job_id, request).await,
+        (_, Some(url)) => run_http_worker(url, request).await,
+        _ => Err(anyhow!("Worker for {} has no command or url", worker.kind)),
+    }
+}
+
+/// Stdio workers read the request on stdin and write JSON lines to stdout: `{"progress": 0.4}`
+/// as they go, then `{"result": ...}` or `{"error": "..."}`
+async fn run_stdio_worker(command: &[String], job_manager: &JobManager, job_id: i64, request: &Value) -> Result<Value> {
+    let mut child = resources::command(&command[0])
+        .args(&command[1..])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .with_context(|| format!("Failed to start {}", command[0]))?;
+
+    let mut stdin = child.stdin.take().ok_or_else(|| anyhow!("No stdin"))?;
+    stdin.write_all(request.to_string().as_bytes()).await?;
+    // Close stdin so the worker sees the end of the request
+    drop(stdin);
+
+    let stdout = child.stdout.take().ok_or_else(|| anyhow!("No stdout"))?;
+    let mut lines = BufReader::new(stdout).lines();
+    let mut result = Value::Null;
+    let mut error = None;
+    while let Some(line) = lines.next_line().await? {
+        let Ok(message) = serde_json::from_str::<Value>(line.trim()) else {
+            continue; // Worker logging on stdout
+        };
+        if let Some(progress) = message.get("progress").and_then(|p| p.as_f64()) {
+            job_manager.report_progress(job_id, progress)?;
+        }
+        if let Some(value) = message.get("result") {
+            result = value.clone();
+        }
+        if let Some(message) = message.get("error").and_then(|e| e.as_str()) {
+            error = Some(message.to_string());
+        }
+    }
+
+    let mut stderr = String::new();
+    if let Some(mut pipe) = child.stderr.take() {
+        pipe.read_to_string(&mut stderr).await?;
+    }
+    let status = child.wait().await?;
+    if let Some(error) = error {
+        return Err(anyhow!(error));
+    }
+    if !status.success() {
+        return Err(anyhow!("Exited with {}: {}", status, stderr.trim()));
+    }
+    Ok(result)
+}
+
+/// HTTP workers get the request as a POST and answer with `{"result": ...}` once done; they can
+/// report progress meanwhile through POST /api/jobs/:id/progress
+async fn run_http_worker(url: &str, request: &Value) -> Result<Value> {
+    let response = reqwest::Client::new().post(url).json(request).send().await?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(anyhow!("{} answered {}: {}", url, status, body));
+    }
+    let body: Value = response.json().await.unwrap_or(Value::Null);
+    if let Some(error) = body.get("error").and_then(|e| e.as_str()) {
+        return Err(anyhow!(error.to_string()));
+    }
+    Ok(body.get("result").cloned().unwrap_or(body))
+}
@@ -0,0 +1,188 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::db::Database;
+use crate::jobs::{JobManager, JobType};
+use crate::media::availability;
+use crate::media::compute_file_checksum;
+
+/// Outcome of re-hashing an original against the checksum stored at import
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Integrity {
+    Ok,
+    /// The file reads, but its contents changed since import (bit rot, or overwritten)
+    Mismatch,
+    /// The file is there but couldn't be read to the end
+    Unreadable,
+}
+
+impl Integrity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Integrity::Ok => "ok",
+            Integrity::Mismatch => "mismatch",
+            Integrity::Unreadable => "unreadable",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "ok" => Some(Integrity::Ok),
+            "mismatch" => Some(Integrity::Mismatch),
+            "unreadable" => Some(Integrity::Unreadable),
+            _ => None,
+        }
+    }
+
+    /// What to tell the user about an asset in this state (None when it's fine)
+    pub fn warning(&self) -> Option<&'static str> {
+        match self {
+            Integrity::Ok => None,
+            Integrity::Mismatch => Some("Original no longer matches its checksum; it may be corrupted"),
+            Integrity::Unreadable => Some("Original couldn't be read during verification; it may be corrupted"),
+        }
+    }
+}
+
+/// Process VerifyChecksums job - re-hashes the project's online originals (or just `asset_ids`)
+/// and records the result on each asset. Assets imported without a checksum get one, so later
+/// runs have something to compare against. Offline and archived originals are skipped.
+pub async fn process_verify_checksums(
+    db: Arc<Database>,
+    job_manager: Arc<JobManager>,
+    job_id: i64,
+    project_id: i64,
+    asset_ids: Option<&[i64]>,
+) -> Result<()> {
+    availability::refresh_project(&db, project_id)?;
+    let previous = db.get_media_asset_integrity(project_id)?;
+    let assets: Vec<_> = db
+        .get_media_asset_checksums(project_id)?
+        .into_iter()
+        .filter(|(id, _, _)| asset_ids.is_none_or(|ids| ids.contains(id)))
+        .collect();
+
+    let mut verified = 0;
+    let mut baselined = 0;
+    let mut corrupted = Vec::new();
+    for (i, (asset_id, path, stored)) in assets.iter().enumerate() {
+        let (integrity, error) = match compute_file_checksum(Path::new(path)).await {
+            Ok(checksum) => match stored {
+                Some(stored) if *stored != checksum => (
+                    Integrity::Mismatch,
+                    Some(format!("expected {}, found {}", stored, checksum)),
+                ),
+                Some(_) => (Integrity::Ok, None),
+                None => {
+                    db.set_media_asset_checksum(*asset_id, &checksum)?;
+                    baselined += 1;
+                    (Integrity::Ok, None)
+                }
+            },
+            Err(e) => (Integrity::Unreadable, Some(e.to_string())),
+        };
+        db.set_media_asset_integrity(*asset_id, Some(integrity.as_str()), error.as_deref())?;
+
+        if integrity == Integrity::Ok {
+            verified += 1;
+        } else {
+            // Only warn about assets that weren't already flagged by an earlier run
+            let already_flagged = previous
+                .iter()
+                .any(|(id, status, _, _)| id == asset_id && Integrity::parse(status) == Some(integrity));
+            corrupted.push((*asset_id, path.clone(), integrity, !already_flagged));
+        }
+        job_manager.report_progress(job_id, (i + 1) as f64 / assets.len() as f64)?;
+    }
+
+    let newly_flagged: Vec<_> = corrupted.iter().filter(|(_, _, _, new)| *new).collect();
+    if !newly_flagged.is_empty() {
+        let names: Vec<String> = newly_flagged
+            .iter()
+            .map(|(_, path, _, _)| {
+                Path::new(path)
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_else(|| path.clone())
+            })
+            .collect();
+        let message = format!(
+            "Checksum verification found {} original(s) that may be corrupted: {}. Their proxies still \
+             play, but restore the originals from a backup (then relink them) before exporting.",
+            names.len(),
+            names.join(", ")
+        );
+        let asset_ids: Vec<i64> = newly_flagged.iter().map(|(id, _, _, _)| *id).collect();
+        db.store_orchestrator_message(
+            project_id,
+            "assistant",
+            &message,
+            Some(&serde_json::json!({ "integrity_warning": { "asset_ids": asset_ids, "job_id": job_id } })),
+        )?;
+    }
+
+    job_manager.set_job_result(job_id, serde_json::json!({
+        "checked": assets.len(),
+        "verified": verified,
+        "baselined": baselined,
+        "corrupted": corrupted
+            .iter()
+            .map(|(asset_id, path, integrity, _)| serde_json::json!({
+                "asset_id": asset_id,
+                "path": path,
+                "integrity": integrity,
+            }))
+            .collect::<Vec<_>>(),
+    }))?;
+
+    eprintln!(
+        "[INTEGRITY] Project {}: {} originals checked, {} corrupted ({} new), {} baselined",
+        project_id, assets.len(), corrupted.len(), newly_flagged.len(), baselined
+    );
+    job_manager.update_job_status(job_id, crate::jobs::JobStatus::Completed, Some(1.0))?;
+    Ok(())
+}
+
+/// Queue a verification of one project's originals; a run already queued for the whole project
+/// is reused
+pub fn queue_verification(job_manager: &JobManager, project_id: i64, asset_ids: Option<Vec<i64>>) -> Result<i64> {
+    let dedupe_key = asset_ids.is_none().then(|| format!("VerifyChecksums:{}", project_id));
+    let payload = serde_json::json!({
+        "project_id": project_id,
+        "asset_ids": asset_ids,
+    });
+    job_manager.create_job(JobType::VerifyChecksums, Some(payload), dedupe_key)
+}
+
+/// Interval between scheduled verifications of every project, from CHECKSUM_VERIFY_INTERVAL_HOURS
+/// (unset or 0 = only on demand)
+pub fn schedule_interval() -> Option<Duration> {
+    std::env::var("CHECKSUM_VERIFY_INTERVAL_HOURS")
+        .ok()
+        .and_then(|hours| hours.trim().parse::<f64>().ok())
+        .filter(|hours| hours.is_finite() && *hours > 0.0)
+        .map(|hours| Duration::from_secs_f64(hours * 3600.0))
+}
+
+/// Queues a verification of every project once per interval, for as long as the daemon runs
+pub async fn verification_schedule_loop(db: Arc<Database>, job_manager: Arc<JobManager>, interval: Duration) {
+    loop {
+        tokio::time::sleep(interval).await;
+        let projects = match db.get_all_projects() {
+            Ok(projects) => projects,
+            Err(e) => {
+                eprintln!("[INTEGRITY] Failed to list projects for scheduled verification: {:?}", e);
+                continue;
+            }
+        };
+        for project in projects {
+            if let Err(e) = queue_verification(&job_manager, project.id, None) {
+                eprintln!("[INTEGRITY] Failed to queue verification for project {}: {:?}", project.id, e);
+            }
+        }
+    }
+}
@@ -86,38 +86,6 @@ pub async fn process_compute_segment_metadata(
             }).to_string()
         };
         
-        // Determine segment_kind using heuristics
-        let segment_kind = {
-            let has_transcript = segment.transcript.is_some();
-            let has_face = if let Some(ref scene_json) = segment.scene_json {
-                serde_json::from_str::<serde_json::Value>(scene_json)
-                    .ok()
-                    .and_then(|s| s.get("has_face").and_then(|v| v.as_bool()))
-                    .unwrap_or(false)
-            } else {
-                false
-            };
-            let motion_high = if let Some(ref quality_json) = segment.quality_json {
-                serde_json::from_str::<serde_json::Value>(quality_json)
-                    .ok()
-                    .and_then(|q| q.get("motion_score").and_then(|v| v.as_f64()))
-                    .map(|m| m > 50.0)
-                    .unwrap_or(false)
-            } else {
-                false
-            };
-            
-            if has_transcript && has_face {
-                Some("talking_head".to_string())
-            } else if !has_transcript && motion_high {
-                Some("action".to_string())
-            } else if !has_transcript && !motion_high {
-                Some("broll".to_string())
-            } else {
-                None
-            }
-        };
-        
         // Update segment metadata
         db.update_segment_metadata(
             segment.id,
@@ -127,8 +95,11 @@ pub async fn process_compute_segment_metadata(
             Some(&subject_json),
             None, // scene_json (already set)
             None, // transcript (already set)
-            segment_kind.as_deref(),
+            None, // segment_kind (classified once metadata is ready)
         )?;
+
+        // Energy score for hook selection (refreshed again when audio events land)
+        crate::jobs::energy::refresh_segment_energy(&db, segment)?;
         
         processed_count += 1;
         
@@ -137,9 +108,18 @@ pub async fn process_compute_segment_metadata(
         job_manager.update_job_status(job_id, crate::jobs::JobStatus::Running, Some(progress))?;
     }
     
+    // Custom analyzer plugins add their tags and scores before segments are embedded
+    if let Err(e) = crate::plugins::run_analyzers(&db, asset_id).await {
+        eprintln!("[PLUGINS] Analyzer plugins failed for asset {}: {:?}", asset_id, e);
+    }
+
     // Update asset analysis state
     db.update_asset_analysis_state(asset_id, "metadata_ready_at", None)?;
     
+    // Classify segments now that transcript, vision and motion signals are all in
+    let classify_id = crate::jobs::classify::queue_classification(&job_manager, asset_id)?;
+    eprintln!("[METADATA] Queued ClassifySegments job {} for asset_id: {}", classify_id, asset_id);
+
     // Queue embedding job
     let embed_payload = serde_json::json!({
         "asset_id": asset_id,
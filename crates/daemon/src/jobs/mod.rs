@@ -4,9 +4,10 @@ use rusqlite::params;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::sync::Arc;
-use tokio::sync::broadcast;
 
 use crate::db::Database;
+use crate::events::{Event, EventBus};
+use crate::quotas;
 
 pub mod processor;
 pub mod build_segments;
@@ -14,8 +15,26 @@ pub mod transcribe;
 pub mod vision;
 pub mod enrichment;
 pub mod metadata;
+pub mod classify;
 pub mod embeddings;
 pub mod twelvelabs_index;
+pub mod proxy;
+pub mod audio_events;
+pub mod energy;
+pub mod redaction;
+pub mod multicam;
+pub mod still_subjects;
+pub mod integrity;
+pub mod sync_drift;
+pub mod external;
+pub mod remote;
+pub mod resources;
+pub mod priority;
+pub mod reanalyze;
+pub mod thumbnails;
+pub mod preview;
+pub mod voiceover;
+pub mod music;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum JobType {
@@ -31,13 +50,38 @@ pub enum JobType {
     EnrichSegmentsFromTranscript,
     EnrichSegmentsFromVision,
     ComputeSegmentMetadata,
+    /// Assign each segment of an asset a kind (talking head, b-roll, action, ...)
+    ClassifySegments,
     EmbedSegments,
     IndexAssetWithTwelveLabs,
+    AnalyzeAudioEvents,
+    DetectRedactions,
+    SyncMulticam,
+    DetectStillSubjects,
+    /// Re-hash a project's originals against their stored checksums
+    VerifyChecksums,
+    /// Measure A/V sync drift on a project's videos
+    DetectSyncDrift,
+    /// Work out what changed in an asset whose file was replaced, and re-run only that analysis
+    ReanalyzeChangedAsset,
+    /// Extract one level of an asset's thumbnail pyramid that isn't derived from the base strip
+    GenerateThumbnailLevel,
+    /// Render the complex regions of a project's timeline at preview size for scrubbing
+    PreRenderPreview,
+    /// Transcribe a voiceover and cut the project's timeline to its beats
+    AlignVoiceover,
+    /// Speak a narration script with a TTS provider and attach it as a voiceover
+    SynthesizeNarration,
+    /// Find the sections, drops and accents of a music catalog track
+    AnalyzeMusic,
+    /// Extension job `<namespace>.<name>`, processed by the worker registered for that kind
+    #[serde(untagged)]
+    External(String),
 }
 
 impl JobType {
-    /// Convert to plain string (variant name)
-    pub fn to_string(&self) -> &'static str {
+    /// Convert to plain string (variant name, or the namespaced kind for extension jobs)
+    pub fn to_string(&self) -> &str {
         match self {
             JobType::ImportRaw => "ImportRaw",
             JobType::GenerateProxy => "GenerateProxy",
@@ -51,8 +95,22 @@ impl JobType {
             JobType::EnrichSegmentsFromTranscript => "EnrichSegmentsFromTranscript",
             JobType::EnrichSegmentsFromVision => "EnrichSegmentsFromVision",
             JobType::ComputeSegmentMetadata => "ComputeSegmentMetadata",
+            JobType::ClassifySegments => "ClassifySegments",
             JobType::EmbedSegments => "EmbedSegments",
             JobType::IndexAssetWithTwelveLabs => "IndexAssetWithTwelveLabs",
+            JobType::AnalyzeAudioEvents => "AnalyzeAudioEvents",
+            JobType::DetectRedactions => "DetectRedactions",
+            JobType::SyncMulticam => "SyncMulticam",
+            JobType::DetectStillSubjects => "DetectStillSubjects",
+            JobType::VerifyChecksums => "VerifyChecksums",
+            JobType::DetectSyncDrift => "DetectSyncDrift",
+            JobType::ReanalyzeChangedAsset => "ReanalyzeChangedAsset",
+            JobType::GenerateThumbnailLevel => "GenerateThumbnailLevel",
+            JobType::PreRenderPreview => "PreRenderPreview",
+            JobType::AlignVoiceover => "AlignVoiceover",
+            JobType::SynthesizeNarration => "SynthesizeNarration",
+            JobType::AnalyzeMusic => "AnalyzeMusic",
+            JobType::External(kind) => kind,
         }
     }
     
@@ -71,8 +129,23 @@ impl JobType {
             "EnrichSegmentsFromTranscript" => Ok(JobType::EnrichSegmentsFromTranscript),
             "EnrichSegmentsFromVision" => Ok(JobType::EnrichSegmentsFromVision),
             "ComputeSegmentMetadata" => Ok(JobType::ComputeSegmentMetadata),
+            "ClassifySegments" => Ok(JobType::ClassifySegments),
             "EmbedSegments" => Ok(JobType::EmbedSegments),
             "IndexAssetWithTwelveLabs" => Ok(JobType::IndexAssetWithTwelveLabs),
+            "AnalyzeAudioEvents" => Ok(JobType::AnalyzeAudioEvents),
+            "DetectRedactions" => Ok(JobType::DetectRedactions),
+            "SyncMulticam" => Ok(JobType::SyncMulticam),
+            "DetectStillSubjects" => Ok(JobType::DetectStillSubjects),
+            "VerifyChecksums" => Ok(JobType::VerifyChecksums),
+            "DetectSyncDrift" => Ok(JobType::DetectSyncDrift),
+            "ReanalyzeChangedAsset" => Ok(JobType::ReanalyzeChangedAsset),
+            "GenerateThumbnailLevel" => Ok(JobType::GenerateThumbnailLevel),
+            "PreRenderPreview" => Ok(JobType::PreRenderPreview),
+            "AlignVoiceover" => Ok(JobType::AlignVoiceover),
+            "SynthesizeNarration" => Ok(JobType::SynthesizeNarration),
+            "AnalyzeMusic" => Ok(JobType::AnalyzeMusic),
+            // Built-in names never contain a dot, so namespaced kinds can't collide with them
+            kind if external::is_valid_kind(kind) => Ok(JobType::External(kind.to_string())),
             _ => Err(format!("Unknown job type: {}", s)),
         }
     }
@@ -119,65 +192,65 @@ pub struct Job {
     pub status: JobStatus,
     pub progress: f64,
     pub payload: Option<Value>,
+    /// 1 for the first run, incremented on every retry
+    pub attempts: i64,
+    /// Why the last attempt failed, when the processor recorded it
+    pub error: Option<String>,
+    /// Remote worker running the job, and when its claim runs out unless it checks in
+    pub worker_id: Option<i64>,
+    pub lease_expires_at: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Serialize)]
-#[serde(tag = "type")]
-pub enum JobEvent {
-    JobCompleted {
-        job_id: i64,
-        job_type: String,
-        asset_id: Option<i64>,
-    },
-    JobFailed {
-        job_id: i64,
-        job_type: String,
-        asset_id: Option<i64>,
-        error: String,
-    },
-    AnalysisComplete {
-        asset_id: i64,
-        readiness: String, // AssetReadiness as string
-        project_id: i64,
-    },
-}
-
 pub struct JobManager {
     db: Arc<Database>,
-    event_sender: broadcast::Sender<JobEvent>,
+    events: EventBus,
 }
 
 impl JobManager {
     pub fn new(db: Arc<Database>) -> Self {
-        let (event_sender, _) = broadcast::channel(1000); // Buffer up to 1000 events
         JobManager {
+            events: EventBus::new(db.clone()),
             db,
-            event_sender,
         }
     }
 
-    /// Get a receiver for job events
-    pub fn subscribe(&self) -> broadcast::Receiver<JobEvent> {
-        self.event_sender.subscribe()
-    }
-
-    /// Emit a job event (internal use)
-    fn emit_event(&self, event: JobEvent) {
-        // Ignore errors - receivers may not be listening
-        let _ = self.event_sender.send(event);
+    /// The bus job state changes are published on, which the rest of the pipeline publishes to
+    /// and subscribes through as well
+    pub fn events(&self) -> &EventBus {
+        &self.events
     }
 
-    /// Emit AnalysisComplete event (public, called from job processors)
-    pub fn emit_analysis_complete(&self, asset_id: i64, project_id: i64, readiness: String) {
-        self.emit_event(JobEvent::AnalysisComplete {
-            asset_id,
-            readiness,
+    /// Publish JobStateChanged for a job that just moved to `status`
+    fn publish_state_change(&self, job: &Job, status: JobStatus) {
+        let payload_id = |key: &str| job.payload.as_ref().and_then(|p| p.get(key)).and_then(|v| v.as_i64());
+        let error = match status {
+            JobStatus::Failed => Some(job.error.clone().unwrap_or_else(|| "Job failed".to_string())),
+            _ => None,
+        };
+        // Analysis jobs only name their asset
+        let asset_id = payload_id("asset_id");
+        let project_id = payload_id("project_id")
+            .or_else(|| asset_id.and_then(|id| self.db.get_asset_project_id(id).ok().flatten()));
+        self.events.publish(Event::JobStateChanged {
+            job_id: job.id,
+            job_type: job.job_type.to_string().to_owned(),
+            status,
             project_id,
+            asset_id,
+            error,
         });
     }
 
+    /// Publish JobStateChanged after a status change made outside `update_job_status`
+    fn publish_current_state(&self, id: i64) {
+        if let Ok(Some(job)) = self.get_job(id) {
+            let status = job.status.clone();
+            self.publish_state_change(&job, status);
+        }
+    }
+
     pub fn create_job(
         &self,
         job_type: JobType,
@@ -189,6 +262,8 @@ impl JobManager {
         let job_type_str = job_type.to_string(); // Plain string, not JSON
         let status_str = status.to_string(); // Plain string, not JSON
         let payload_str = payload.as_ref().map(|v| serde_json::to_string(v)).transpose()?;
+        // Imports and exports over a quota aren't queued at all
+        quotas::check_new_job(&self.db, &job_type, payload.as_ref())?;
 
         let conn = self.db.conn.lock().unwrap();
         
@@ -214,10 +289,22 @@ impl JobManager {
         Ok(conn.last_insert_rowid())
     }
 
+    /// Whether a job with this dedupe key is queued or running
+    pub fn has_active_job(&self, dedupe_key: &str) -> Result<bool> {
+        let conn = self.db.conn.lock().unwrap();
+        let count: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM jobs WHERE dedupe_key = ?1 AND is_active = 1",
+            params![dedupe_key],
+            |row| row.get(0),
+        )?;
+        Ok(count > 0)
+    }
+
     pub fn get_job(&self, id: i64) -> Result<Option<Job>> {
         let conn = self.db.conn.lock().unwrap();
         let mut stmt = conn.prepare(
-            "SELECT id, type, status, progress, payload_json, created_at, updated_at FROM jobs WHERE id = ?1"
+            "SELECT id, type, status, progress, payload_json, created_at, updated_at, attempts, error, worker_id, lease_expires_at
+             FROM jobs WHERE id = ?1"
         )?;
 
         let mut rows = stmt.query_map(params![id], |row| {
@@ -250,6 +337,10 @@ impl JobManager {
                 status,
                 progress: row.get(3)?,
                 payload,
+                attempts: row.get(7)?,
+                error: row.get(8)?,
+                worker_id: row.get(9)?,
+                lease_expires_at: row.get(10)?,
                 created_at,
                 updated_at,
             })
@@ -271,12 +362,8 @@ impl JobManager {
         let now = Utc::now().to_rfc3339();
         let status_str = status.to_string(); // Plain string, not JSON
 
-        // Get job info for event emission (need to get job_type and asset_id)
+        // Job info for the state change event (type, project, asset, failure reason)
         let job_opt = self.get_job(id)?;
-        let job_type_str = job_opt.as_ref().map(|j| j.job_type.to_string());
-        let asset_id = job_opt.as_ref()
-            .and_then(|j| j.payload.as_ref())
-            .and_then(|p| p.get("asset_id").and_then(|v| v.as_i64()));
 
         let conn = self.db.conn.lock().unwrap();
         
@@ -298,26 +385,11 @@ impl JobManager {
             )?;
         }
 
-        // Emit events for completed/failed jobs
-        if let Some(job_type) = job_type_str {
-            match status {
-                JobStatus::Completed => {
-                    self.emit_event(JobEvent::JobCompleted {
-                        job_id: id,
-                        job_type: job_type.to_string(),
-                        asset_id,
-                    });
-                },
-                JobStatus::Failed => {
-                    self.emit_event(JobEvent::JobFailed {
-                        job_id: id,
-                        job_type: job_type.to_string(),
-                        asset_id,
-                        error: "Job failed".to_string(), // Could extract from job if we store error messages
-                    });
-                },
-                _ => {}
-            }
+        drop(conn);
+
+        // Progress updates re-send the current status; only transitions are events
+        if let Some(job) = job_opt.filter(|j| j.status.to_string() != status_str) {
+            self.publish_state_change(&job, status);
         }
 
         Ok(())
@@ -326,4 +398,182 @@ impl JobManager {
     pub fn cancel_job(&self, id: i64) -> Result<()> {
         self.update_job_status(id, JobStatus::Cancelled, None)
     }
+
+    /// Mark a job failed and record why (carried in its JobStateChanged event)
+    pub fn fail_job(&self, id: i64, error: &str) -> Result<()> {
+        {
+            let conn = self.db.conn.lock().unwrap();
+            conn.execute("UPDATE jobs SET error = ?1 WHERE id = ?2", params![error, id])?;
+        }
+        self.update_job_status(id, JobStatus::Failed, None)
+    }
+
+    /// Put a failed or cancelled job back in the queue as a new attempt. Returns false when the
+    /// job isn't in a retryable state.
+    pub fn retry_job(&self, id: i64) -> Result<bool> {
+        let conn = self.db.conn.lock().unwrap();
+        let updated = conn.execute(
+            "UPDATE jobs SET status = ?1, progress = 0.0, is_active = 1, attempts = attempts + 1, error = NULL, updated_at = ?2
+             WHERE id = ?3 AND status IN (?4, ?5)",
+            params![
+                JobStatus::Pending.to_string(),
+                Utc::now().to_rfc3339(),
+                id,
+                JobStatus::Failed.to_string(),
+                JobStatus::Cancelled.to_string(),
+            ],
+        )?;
+        drop(conn);
+        if updated > 0 {
+            self.publish_current_state(id);
+        }
+        Ok(updated > 0)
+    }
+
+    /// Send a running job back to the queue for another attempt after a failure; it goes back to
+    /// Pending rather than Failed (the final failure publishes that)
+    pub fn requeue_job(&self, id: i64, error: &str) -> Result<()> {
+        let conn = self.db.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE jobs SET status = ?1, progress = 0.0, attempts = attempts + 1, error = ?2, worker_id = NULL,
+             lease_expires_at = NULL, updated_at = ?3 WHERE id = ?4",
+            params![JobStatus::Pending.to_string(), error, Utc::now().to_rfc3339(), id],
+        )?;
+        drop(conn);
+        self.publish_current_state(id);
+        Ok(())
+    }
+
+    /// Record progress reported by a job's worker without changing its status
+    pub fn report_progress(&self, id: i64, progress: f64) -> Result<()> {
+        let conn = self.db.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE jobs SET progress = ?1, updated_at = ?2 WHERE id = ?3",
+            params![progress.clamp(0.0, 1.0), Utc::now().to_rfc3339(), id],
+        )?;
+        Ok(())
+    }
+
+    /// Newest completed job of a type for a project (the `project_id` in its payload)
+    pub fn latest_completed_job(&self, job_type: &JobType, project_id: i64) -> Result<Option<Job>> {
+        use rusqlite::OptionalExtension;
+        let id: Option<i64> = {
+            let conn = self.db.conn.lock().unwrap();
+            conn.query_row(
+                "SELECT id FROM jobs
+                 WHERE type = ?1 AND status = ?2 AND json_extract(payload_json, '$.project_id') = ?3
+                 ORDER BY id DESC LIMIT 1",
+                params![job_type.to_string(), JobStatus::Completed.to_string(), project_id],
+                |row| row.get(0),
+            )
+            .optional()?
+        };
+        match id {
+            Some(id) => self.get_job(id),
+            None => Ok(None),
+        }
+    }
+
+    /// Store a job's output under `result` in its payload
+    pub fn set_job_result(&self, id: i64, result: Value) -> Result<()> {
+        let conn = self.db.conn.lock().unwrap();
+        let payload: Option<String> = conn.query_row(
+            "SELECT payload_json FROM jobs WHERE id = ?1",
+            params![id],
+            |row| row.get(0),
+        )?;
+        let mut payload = payload
+            .and_then(|p| serde_json::from_str::<Value>(&p).ok())
+            .filter(|p| p.is_object())
+            .unwrap_or_else(|| serde_json::json!({}));
+        payload["result"] = result;
+        conn.execute(
+            "UPDATE jobs SET payload_json = ?1 WHERE id = ?2",
+            params![payload.to_string(), id],
+        )?;
+        Ok(())
+    }
+
+    /// Hand a pending job to a remote worker until `lease_expires_at`. Returns false when
+    /// another worker (or the local processor) got to it first.
+    pub fn claim_job(&self, id: i64, worker_id: i64, lease_expires_at: &str) -> Result<bool> {
+        let conn = self.db.conn.lock().unwrap();
+        let claimed = conn.execute(
+            "UPDATE jobs SET status = ?1, progress = 0.0, worker_id = ?2, lease_expires_at = ?3, updated_at = ?4
+             WHERE id = ?5 AND status = ?6",
+            params![
+                JobStatus::Running.to_string(),
+                worker_id,
+                lease_expires_at,
+                Utc::now().to_rfc3339(),
+                id,
+                JobStatus::Pending.to_string(),
+            ],
+        )?;
+        drop(conn);
+        if claimed > 0 {
+            self.publish_current_state(id);
+        }
+        Ok(claimed > 0)
+    }
+
+    /// Extend a remote worker's claim on a running job. Returns false when the worker no longer
+    /// holds it (it was cancelled, timed out or handed to someone else).
+    pub fn renew_claim(&self, id: i64, worker_id: i64, lease_expires_at: &str) -> Result<bool> {
+        let conn = self.db.conn.lock().unwrap();
+        let renewed = conn.execute(
+            "UPDATE jobs SET lease_expires_at = ?1, updated_at = ?2 WHERE id = ?3 AND worker_id = ?4 AND status = ?5",
+            params![lease_expires_at, Utc::now().to_rfc3339(), id, worker_id, JobStatus::Running.to_string()],
+        )?;
+        Ok(renewed > 0)
+    }
+
+    /// Drop a finished job's claim so it no longer counts as the worker's
+    pub fn release_claim(&self, id: i64) -> Result<()> {
+        let conn = self.db.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE jobs SET worker_id = NULL, lease_expires_at = NULL WHERE id = ?1",
+            params![id],
+        )?;
+        Ok(())
+    }
+
+    /// Running jobs held by remote workers: all of them, or one worker's. With `expired_before`,
+    /// only claims that ran out before then.
+    pub fn claimed_jobs(&self, worker_id: Option<i64>, expired_before: Option<&str>) -> Result<Vec<(i64, i64)>> {
+        let conn = self.db.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, worker_id FROM jobs
+             WHERE status = ?1 AND worker_id IS NOT NULL AND (?2 IS NULL OR worker_id = ?2)
+               AND (?3 IS NULL OR lease_expires_at < ?3)
+             ORDER BY id",
+        )?;
+        let rows = stmt.query_map(params![JobStatus::Running.to_string(), worker_id, expired_before], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })?;
+        Ok(rows.collect::<Result<Vec<_>, _>>()?)
+    }
+
+    /// Pending jobs of the given types, oldest first (deferred assets of bulk imports last)
+    pub fn pending_jobs(&self, job_types: &[String]) -> Result<Vec<i64>> {
+        let mut jobs = Vec::new();
+        {
+            let conn = self.db.conn.lock().unwrap();
+            let mut stmt = conn.prepare("SELECT id, type, payload_json FROM jobs WHERE status = ?1 ORDER BY created_at ASC, id ASC")?;
+            let rows = stmt.query_map(params![JobStatus::Pending.to_string()], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, Option<String>>(2)?))
+            })?;
+            for row in rows {
+                let (id, job_type, payload) = row?;
+                if !job_types.contains(&job_type) {
+                    continue;
+                }
+                if let Ok(job_type) = JobType::from_str(&job_type) {
+                    jobs.push((id, job_type, processor::JobProcessor::extract_asset_id(&payload)));
+                }
+            }
+        }
+        priority::order_ready_jobs(&self.db, &mut jobs)?;
+        Ok(jobs.into_iter().map(|(id, _, _)| id).collect())
+    }
 }
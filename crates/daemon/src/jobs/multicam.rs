@@ -0,0 +1,111 @@
+use anyhow::Result;
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::db::Database;
+use crate::jobs::transcribe::{ensure_transcription_audio, release_transcription_audio};
+use crate::jobs::JobManager;
+use engine::timeline::TICKS_PER_SECOND;
+
+use crate::llm::ml_service_url;
+
+/// Angles whose audio matches the reference less clearly than this are left out of the group
+const MIN_SYNC_CONFIDENCE: f64 = 0.2;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SyncOffset {
+    offset_sec: f64,
+    confidence: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct SyncResponse {
+    offsets: Vec<SyncOffset>,
+}
+
+/// Process SyncMulticam job - aligns the assets by audio cross-correlation against the longest
+/// one and stores the ones that matched as a multicam group
+pub async fn process_sync_multicam(
+    db: Arc<Database>,
+    job_manager: Arc<JobManager>,
+    job_id: i64,
+    project_id: i64,
+    asset_ids: &[i64],
+    name: Option<String>,
+) -> Result<()> {
+    let mut assets = Vec::new();
+    for asset_id in asset_ids {
+        let asset = db
+            .get_media_asset(*asset_id)?
+            .ok_or_else(|| anyhow::anyhow!("Media asset {} not found", asset_id))?;
+        assets.push(asset);
+    }
+    let reference = assets
+        .iter()
+        .enumerate()
+        .max_by_key(|(_, a)| a.duration_ticks)
+        .map(|(i, _)| i)
+        .ok_or_else(|| anyhow::anyhow!("No assets to sync"))?;
+
+    let mut audio_paths = Vec::new();
+    for (i, asset) in assets.iter().enumerate() {
+        let path = ensure_transcription_audio(asset.id, &asset.path).await?;
+        audio_paths.push(path.to_string_lossy().to_string());
+        job_manager.report_progress(job_id, 0.6 * (i + 1) as f64 / assets.len() as f64)?;
+    }
+
+    let response = reqwest::Client::new()
+        .post(format!("{}/multicam/sync", ml_service_url()))
+        .json(&serde_json::json!({
+            "audioPaths": audio_paths,
+            "referenceIndex": reference,
+        }))
+        .send()
+        .await;
+    for asset in &assets {
+        release_transcription_audio(&db, asset.id).await;
+    }
+    let response = response?;
+    if !response.status().is_success() {
+        return Err(anyhow::anyhow!("ML service multicam sync failed: {}", response.status()));
+    }
+    let synced: SyncResponse = response.json().await?;
+    if synced.offsets.len() != assets.len() {
+        return Err(anyhow::anyhow!("ML service returned {} offsets for {} clips", synced.offsets.len(), assets.len()));
+    }
+    job_manager.report_progress(job_id, 0.9)?;
+
+    let mut angles: Vec<(i64, i64, f64)> = Vec::new();
+    let mut unsynced = Vec::new();
+    for (asset, offset) in assets.iter().zip(&synced.offsets) {
+        if offset.confidence < MIN_SYNC_CONFIDENCE {
+            unsynced.push(asset.id);
+            continue;
+        }
+        let offset_ticks = (offset.offset_sec * TICKS_PER_SECOND as f64).round() as i64;
+        angles.push((asset.id, offset_ticks, offset.confidence));
+    }
+    if angles.len() < 2 {
+        return Err(anyhow::anyhow!("No clip's audio matched the reference (asset {})", assets[reference].id));
+    }
+    // Put the earliest angle at 0 on the group clock
+    let earliest = angles.iter().map(|(_, offset, _)| *offset).min().unwrap_or(0);
+    for angle in &mut angles {
+        angle.1 -= earliest;
+    }
+
+    let name = name.unwrap_or_else(|| format!("Multicam {}", assets[reference].id));
+    let group_id = db.create_multicam_group(project_id, &name, assets[reference].id, &angles)?;
+    job_manager.set_job_result(job_id, serde_json::json!({
+        "group_id": group_id,
+        "unsynced_asset_ids": unsynced,
+    }))?;
+
+    eprintln!(
+        "[MULTICAM] Group {} in project {}: {} angles synced, {} unmatched",
+        group_id, project_id, angles.len(), unsynced.len()
+    );
+    job_manager.update_job_status(job_id, crate::jobs::JobStatus::Completed, Some(1.0))?;
+    Ok(())
+}
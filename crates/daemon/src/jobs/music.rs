@@ -0,0 +1,55 @@
+use anyhow::Result;
+use serde_json::json;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::db::Database;
+use crate::jobs::{JobManager, JobType};
+use crate::media::music_structure::analyze_music;
+use engine::timeline::TICKS_PER_SECOND;
+
+/// Process AnalyzeMusic job - measures a catalog track's loudness and stores its sections,
+/// drops and energy curve
+pub async fn process_analyze_music(
+    db: Arc<Database>,
+    job_manager: Arc<JobManager>,
+    job_id: i64,
+    track_id: i64,
+) -> Result<()> {
+    let track = db
+        .get_music_track(track_id)?
+        .ok_or_else(|| anyhow::anyhow!("Music track {} not found", track_id))?;
+    job_manager.update_job_status(job_id, crate::jobs::JobStatus::Running, Some(0.1))?;
+
+    let duration_sec = track.duration_ticks.unwrap_or(0) as f64 / TICKS_PER_SECOND as f64;
+    let structure = analyze_music(Path::new(&track.path), duration_sec).await?;
+    db.set_music_structure(track_id, &serde_json::to_string(&structure)?)?;
+    eprintln!(
+        "[MUSIC] \"{}\": {} section(s), {} drop(s), {} accent(s)",
+        track.title,
+        structure.sections.len(),
+        structure.drops.len(),
+        structure.accents.len()
+    );
+
+    job_manager.set_job_result(
+        job_id,
+        json!({
+            "music_track_id": track_id,
+            "sections": structure.sections,
+            "drops": structure.drops,
+            "accents": structure.accents,
+        }),
+    )?;
+    job_manager.update_job_status(job_id, crate::jobs::JobStatus::Completed, Some(1.0))?;
+    Ok(())
+}
+
+/// Queue structure analysis of a catalog track; a run already queued for it is reused
+pub fn queue_analysis(job_manager: &JobManager, track_id: i64) -> Result<i64> {
+    job_manager.create_job(
+        JobType::AnalyzeMusic,
+        Some(json!({ "music_track_id": track_id })),
+        Some(format!("AnalyzeMusic:{}", track_id)),
+    )
+}
@@ -0,0 +1,230 @@
+//! Scrubbing previews: spans of the timeline the player can't composite in real time
+//! (overlays, retimed clips, Ken Burns moves, blended frame-rate conversions) are rendered from
+//! the proxies at preview size as soon as they're edited, so scrubbing over them stays smooth.
+
+use anyhow::Result;
+use serde_json::json;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::autosave;
+use crate::crypto;
+use crate::db::Database;
+use crate::jobs::{redaction, JobManager, JobType};
+use crate::media::conform;
+use crate::media::ffmpeg::FFmpegWrapper;
+use engine::encode::EncoderSettings;
+use engine::incremental::{render_spans, span_timeline, RenderSpan};
+use engine::preview::{complex_regions, preview_encoder, preview_timeline, PreviewRegion, PREVIEW_HEIGHT};
+use engine::render::{generate_render_commands, AudioOptions, BlurRegion, BrandingOptions, MuxOptions, SourceMedia};
+use engine::render_cache;
+use engine::timeline::{Timeline, TrackKind};
+
+/// How often timelines are checked for edits
+pub const WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// What a project's previews are rendered from, and the regions that get one
+pub struct PreviewPlan {
+    /// The timeline at preview size
+    pub timeline: Timeline,
+    pub sources: HashMap<i64, SourceMedia>,
+    pub blur_regions: Vec<BlurRegion>,
+    pub encoder: EncoderSettings,
+    pub spans: Vec<RenderSpan>,
+    pub regions: Vec<PreviewRegion>,
+    /// Where the previews are kept (next to the export render cache, so purging clears both)
+    pub cache_dir: PathBuf,
+}
+
+impl PreviewPlan {
+    pub fn path(&self, region: &PreviewRegion) -> PathBuf {
+        render_cache::preview_path(&self.cache_dir, &region.span.key)
+    }
+}
+
+/// Whether previews are pre-rendered for the project: off with PREVIEW_PRERENDER=off, or when
+/// the project's renders mustn't be cached
+pub fn enabled(db: &Database, project_id: i64) -> Result<bool> {
+    let disabled = std::env::var("PREVIEW_PRERENDER").is_ok_and(|v| v == "off");
+    Ok(!disabled && crypto::caching_allowed(db, project_id)?)
+}
+
+/// Plan the previews of the project's current timeline; None when it has no timeline
+pub fn plan_previews(db: &Database, project_id: i64) -> Result<Option<PreviewPlan>> {
+    let Some(timeline_json) = db.get_timeline(project_id)? else {
+        return Ok(None);
+    };
+    let timeline = preview_timeline(&serde_json::from_str(&timeline_json)?);
+    let project = db
+        .get_project(project_id)?
+        .ok_or_else(|| anyhow::anyhow!("Project {} not found", project_id))?;
+
+    let sources = preview_sources(db, &timeline)?;
+    let asset_ids: Vec<i64> = sources.keys().copied().collect();
+    // Redacted faces and plates stay blurred in previews too
+    let blur_regions = redaction::blur_regions(db, project_id, &asset_ids)?;
+    let encoder = preview_encoder();
+    let spans = render_spans(&timeline, &sources, &blur_regions, &AudioOptions::default(), &encoder);
+    let regions = complex_regions(&timeline, &spans, &sources);
+    Ok(Some(PreviewPlan {
+        timeline,
+        sources,
+        blur_regions,
+        encoder,
+        spans,
+        regions,
+        cache_dir: PathBuf::from(project.cache_dir).join("render_cache"),
+    }))
+}
+
+/// File of the project's preview with `key`, when it's been rendered
+pub fn preview_file(db: &Database, project_id: i64, key: &str) -> Result<Option<PathBuf>> {
+    // Keys are content hashes; anything else isn't a preview (and mustn't reach the path)
+    if key.len() != 16 || !key.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Ok(None);
+    }
+    let project = db
+        .get_project(project_id)?
+        .ok_or_else(|| anyhow::anyhow!("Project {} not found", project_id))?;
+    let path = render_cache::preview_path(&PathBuf::from(project.cache_dir).join("render_cache"), key);
+    Ok(Some(path).filter(|p| p.exists()))
+}
+
+/// The smallest proxy of each video asset at least `PREVIEW_HEIGHT` tall (or the largest there
+/// is), falling back to the original. Assets with neither on disk are left out.
+fn preview_sources(db: &Database, timeline: &Timeline) -> Result<HashMap<i64, SourceMedia>> {
+    let frame_rates = conform::recorded_frame_rates(db, timeline)?;
+    let mut sources = HashMap::new();
+    for clip in timeline.tracks.iter().filter(|t| t.kind == TrackKind::Video).flat_map(|t| t.clips.iter()) {
+        if sources.contains_key(&clip.asset_id) {
+            continue;
+        }
+        let proxies = db.list_proxies(clip.asset_id)?;
+        let proxy = proxies
+            .iter()
+            .find(|(_, _, _, height)| *height >= PREVIEW_HEIGHT)
+            .or(proxies.last())
+            .map(|(_, path, _, _)| path.clone());
+        let path = proxy
+            .filter(|p| Path::new(p).exists())
+            .or(db.get_media_asset_path(clip.asset_id)?.filter(|p| Path::new(p).exists()));
+        if let Some(path) = path {
+            sources.insert(
+                clip.asset_id,
+                SourceMedia {
+                    path,
+                    color_filter: None,
+                    fps: frame_rates.get(&clip.asset_id).copied(),
+                    checksum: db.get_media_asset_checksum(clip.asset_id)?,
+                },
+            );
+        }
+    }
+    Ok(sources)
+}
+
+/// Process PreRenderPreview job - renders the project's complex regions that don't have a
+/// preview yet. A region that fails to render is skipped; the rest still get theirs.
+pub async fn process_prerender_preview(
+    db: Arc<Database>,
+    job_manager: Arc<JobManager>,
+    job_id: i64,
+    project_id: i64,
+) -> Result<()> {
+    let Some(plan) = plan_previews(&db, project_id)? else {
+        job_manager.update_job_status(job_id, crate::jobs::JobStatus::Completed, Some(1.0))?;
+        return Ok(());
+    };
+    let missing: Vec<&PreviewRegion> = plan.regions.iter().filter(|r| !plan.path(r).exists()).collect();
+    std::fs::create_dir_all(&plan.cache_dir)?;
+
+    let (mut rendered, mut failed) = (0, 0);
+    for (idx, region) in missing.iter().enumerate() {
+        let path = plan.path(region);
+        // Rendered under another name first, so a half-written preview is never served
+        let partial = path.with_extension("partial.mp4");
+        let span_timeline = span_timeline(&plan.timeline, &plan.spans, &region.span);
+        let render_cmd = generate_render_commands(
+            &span_timeline,
+            partial.clone(),
+            &plan.sources,
+            &plan.blur_regions,
+            &AudioOptions::default(),
+            &MuxOptions::default(),
+            &BrandingOptions::default(),
+            &plan.encoder,
+            None,
+        );
+        match FFmpegWrapper::render(&render_cmd.ffmpeg_args).await {
+            Ok(()) => {
+                std::fs::rename(&partial, &path)?;
+                rendered += 1;
+            }
+            Err(e) => {
+                eprintln!("[PREVIEW] Project {}: preview of span {} failed: {:?}", project_id, region.span.key, e);
+                let _ = std::fs::remove_file(&partial);
+                failed += 1;
+            }
+        }
+        job_manager.report_progress(job_id, (idx + 1) as f64 / missing.len() as f64)?;
+    }
+
+    job_manager.set_job_result(job_id, json!({
+        "regions": plan.regions.len(),
+        "cached": plan.regions.len() - missing.len(),
+        "rendered": rendered,
+        "failed": failed,
+    }))?;
+    if !missing.is_empty() {
+        eprintln!(
+            "[PREVIEW] Project {}: {} of {} complex region(s) pre-rendered, {} failed",
+            project_id, rendered, plan.regions.len(), failed
+        );
+    }
+    job_manager.update_job_status(job_id, crate::jobs::JobStatus::Completed, Some(1.0))?;
+    Ok(())
+}
+
+/// Queue a pre-render of the project's previews when its timeline changed since the last one
+/// was queued. `queued` holds the timeline hash each project was last queued for.
+fn queue_if_edited(db: &Database, job_manager: &JobManager, project_id: i64, queued: &mut HashMap<i64, String>) -> Result<()> {
+    let Some(timeline_json) = db.get_timeline(project_id)? else {
+        return Ok(());
+    };
+    let hash = autosave::content_hash(&timeline_json);
+    if queued.get(&project_id) == Some(&hash) || !enabled(db, project_id)? {
+        return Ok(());
+    }
+    // A run already queued or underway may have read the timeline before this edit, so the
+    // edit is picked up again once it's done
+    let dedupe_key = format!("PreRenderPreview:{}", project_id);
+    if job_manager.has_active_job(&dedupe_key)? {
+        return Ok(());
+    }
+    job_manager.create_job(JobType::PreRenderPreview, Some(json!({ "project_id": project_id })), Some(dedupe_key))?;
+    queued.insert(project_id, hash);
+    Ok(())
+}
+
+/// Queues a pre-render of a project's previews whenever its timeline is edited, for as long as
+/// the daemon runs
+pub async fn preview_watch_loop(db: Arc<Database>, job_manager: Arc<JobManager>) {
+    let mut queued = HashMap::new();
+    loop {
+        tokio::time::sleep(WATCH_INTERVAL).await;
+        let projects = match db.get_all_projects() {
+            Ok(projects) => projects,
+            Err(e) => {
+                eprintln!("[PREVIEW] Failed to list projects: {:?}", e);
+                continue;
+            }
+        };
+        for project in projects {
+            if let Err(e) = queue_if_edited(&db, &job_manager, project.id, &mut queued) {
+                eprintln!("[PREVIEW] Failed to queue previews for project {}: {:?}", project.id, e);
+            }
+        }
+    }
+}
@@ -0,0 +1,124 @@
+//! Cold-start ordering for bulk imports. The first few assets of a large import are imported and
+//! analyzed in full right away, so there's something to work with within minutes. The rest are
+//! registered and get proxies and segments next, and their deep analysis (transcription, vision,
+//! embeddings, ...) follows in order of likely relevance.
+
+use anyhow::Result;
+use std::path::Path;
+
+use crate::db::Database;
+use crate::jobs::JobType;
+
+const DEFAULT_EAGER_ASSETS: usize = 10;
+
+/// Order in which a bulk import's deferred assets are analyzed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportOrder {
+    /// Most recently captured first
+    Recent,
+    /// Oldest capture first
+    Oldest,
+    /// By file name, as the folder lists them
+    Name,
+}
+
+impl ImportOrder {
+    pub fn parse(s: &str) -> Option<ImportOrder> {
+        match s.trim().to_lowercase().as_str() {
+            "recent" => Some(ImportOrder::Recent),
+            "oldest" => Some(ImportOrder::Oldest),
+            "name" => Some(ImportOrder::Name),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImportPolicy {
+    /// Assets of an import that are analyzed fully before the rest (0 = no deferral)
+    pub eager_assets: usize,
+    pub order: ImportOrder,
+}
+
+impl ImportPolicy {
+    /// From IMPORT_EAGER_ASSETS (default 10) and IMPORT_ANALYSIS_ORDER (`recent`, `oldest` or
+    /// `name`; default `recent`)
+    pub fn from_env() -> ImportPolicy {
+        let eager_assets = match std::env::var("IMPORT_EAGER_ASSETS") {
+            Ok(value) => value.trim().parse().unwrap_or_else(|_| {
+                eprintln!("[IMPORT] Invalid IMPORT_EAGER_ASSETS '{}', using {}", value, DEFAULT_EAGER_ASSETS);
+                DEFAULT_EAGER_ASSETS
+            }),
+            Err(_) => DEFAULT_EAGER_ASSETS,
+        };
+        let order = match std::env::var("IMPORT_ANALYSIS_ORDER") {
+            Ok(value) => ImportOrder::parse(&value).unwrap_or_else(|| {
+                eprintln!("[IMPORT] Invalid IMPORT_ANALYSIS_ORDER '{}', using recent", value);
+                ImportOrder::Recent
+            }),
+            Err(_) => ImportOrder::Recent,
+        };
+        ImportPolicy { eager_assets, order }
+    }
+
+    /// Whether an import of this many files has assets to defer
+    pub fn defers(&self, file_count: usize) -> bool {
+        self.eager_assets > 0 && file_count > self.eager_assets
+    }
+
+    /// Sort files into analysis order. `file` gives each item's path and recording start
+    /// (RFC 3339) when known; files without one fall back to their modification time.
+    pub fn sort<T>(&self, files: &mut [T], file: impl Fn(&T) -> (&Path, Option<&str>)) {
+        match self.order {
+            ImportOrder::Name => files.sort_by(|a, b| file(a).0.file_name().cmp(&file(b).0.file_name())),
+            ImportOrder::Recent => files.sort_by_cached_key(|f| {
+                let (path, capture_time) = file(f);
+                std::cmp::Reverse(capture_timestamp(path, capture_time))
+            }),
+            ImportOrder::Oldest => files.sort_by_cached_key(|f| {
+                let (path, capture_time) = file(f);
+                capture_timestamp(path, capture_time)
+            }),
+        }
+    }
+}
+
+/// Seconds since the epoch the file was recorded, best guess (0 when unknown)
+fn capture_timestamp(path: &Path, capture_time: Option<&str>) -> i64 {
+    if let Some(time) = capture_time.and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok()) {
+        return time.timestamp();
+    }
+    std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|m| m.duration_since(std::time::UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_secs() as i64)
+}
+
+/// Work that makes an asset browsable (scrubbing, the segment list) rather than searchable
+fn is_browse_work(job_type: &JobType) -> bool {
+    matches!(job_type, JobType::GenerateProxy | JobType::GenerateThumbnailLevel | JobType::BuildSegments)
+}
+
+/// Put ready jobs in the order to run them: jobs for assets that aren't deferred (and jobs that
+/// aren't about an asset) keep their queue order and go first, then proxies and segments of
+/// deferred assets, then the deferred assets' analysis by import rank. Each job comes with
+/// its type and asset.
+pub fn order_ready_jobs(db: &Database, jobs: &mut [(i64, JobType, Option<i64>)]) -> Result<()> {
+    let ranks = db.get_media_asset_import_ranks()?;
+    if ranks.is_empty() {
+        return Ok(());
+    }
+    // Stable, so equal keys keep the queue's oldest-first order
+    jobs.sort_by_key(|(_, job_type, asset_id)| match asset_id.and_then(|id| ranks.get(&id)) {
+        None => (0, 0),
+        Some(_) if is_browse_work(job_type) => (1, 0),
+        Some(rank) => (2, *rank),
+    });
+    Ok(())
+}
+
+/// Whether a bulk import still has deferred assets to analyze
+pub fn has_deferred_assets(db: &Database) -> Result<bool> {
+    Ok(!db.get_media_asset_import_ranks()?.is_empty())
+}
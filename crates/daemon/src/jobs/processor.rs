@@ -6,7 +6,11 @@ use std::time::Duration;
 use tokio::time::sleep;
 
 use crate::db::Database;
-use crate::jobs::{JobManager, JobStatus, JobType};
+use crate::jobs::priority;
+use crate::jobs::resources::{self, JobClass};
+use crate::jobs::{Job, JobManager, JobStatus, JobType};
+use crate::media::availability::{self, Availability};
+use crate::quotas;
 
 pub struct JobProcessor {
     db: Arc<Database>,
@@ -24,7 +28,7 @@ impl JobProcessor {
         let rows: Vec<_> = {
             let conn = self.db.conn.lock().unwrap();
             let mut stmt = conn.prepare(
-                "SELECT id, type, payload_json FROM jobs WHERE status = ?1 ORDER BY created_at ASC"
+                "SELECT id, type, payload_json FROM jobs WHERE status = ?1 ORDER BY created_at ASC, id ASC"
             )?;
             
             let rows: Vec<_> = stmt.query_map(params![status_str], |row| {
@@ -37,8 +41,14 @@ impl JobProcessor {
             rows
         };
         
+        // Job types an online remote worker takes are left for it to claim
+        let remote_job_types = crate::jobs::remote::online_job_types(&self.db)?;
+
         let mut ready_jobs = Vec::new();
         for (job_id, job_type_str, payload_str) in rows {
+            if remote_job_types.contains(&job_type_str) {
+                continue;
+            }
             // Parse job type from plain string
             let job_type = JobType::from_str(&job_type_str)
                 .map_err(|e| anyhow::anyhow!("Failed to parse job type: {}", e))?;
@@ -46,13 +56,18 @@ impl JobProcessor {
             // Check prerequisites based on job type
             if let Some(asset_id) = Self::extract_asset_id(&payload_str) {
                 if Self::check_job_prerequisites(&self.db, &job_type, asset_id)? {
-                    ready_jobs.push(job_id);
+                    ready_jobs.push((job_id, job_type, Some(asset_id)));
                 }
             } else {
                 // Jobs without asset_id requirements can run immediately
                 match job_type {
-                    JobType::ImportRaw | JobType::GenerateEdit | JobType::Export => {
-                        ready_jobs.push(job_id);
+                    // Export jobs aren't rendered here; they stay queued for a remote worker (in
+                    // simulation they're rendered in-process)
+                    JobType::Export if crate::simulation::enabled() => {
+                        ready_jobs.push((job_id, job_type, None));
+                    }
+                    JobType::ImportRaw | JobType::GenerateEdit | JobType::SyncMulticam | JobType::VerifyChecksums | JobType::DetectSyncDrift | JobType::PreRenderPreview | JobType::AlignVoiceover | JobType::SynthesizeNarration | JobType::AnalyzeMusic | JobType::External(_) => {
+                        ready_jobs.push((job_id, job_type, None));
                     }
                     _ => {
                         // Jobs that require asset_id but don't have it in payload - skip for now
@@ -60,12 +75,15 @@ impl JobProcessor {
                 }
             }
         }
+
+        // Deferred assets of bulk imports wait behind everything else
+        priority::order_ready_jobs(&self.db, &mut ready_jobs)?;
         
-        Ok(ready_jobs)
+        Ok(ready_jobs.into_iter().map(|(job_id, _, _)| job_id).collect())
     }
 
     /// Extract asset_id from job payload (string version)
-    fn extract_asset_id(payload_str: &Option<String>) -> Option<i64> {
+    pub(crate) fn extract_asset_id(payload_str: &Option<String>) -> Option<i64> {
         if let Some(ref payload) = payload_str {
             if let Ok(payload_json) = serde_json::from_str::<serde_json::Value>(payload) {
                 if let Some(asset_id) = payload_json.get("asset_id").and_then(|v| v.as_i64()) {
@@ -92,12 +110,27 @@ impl JobProcessor {
         None
     }
 
+    /// Extract the (start, end) second ranges a job is limited to, when it's limited
+    fn extract_windows(payload: &Option<serde_json::Value>) -> Option<Vec<(f64, f64)>> {
+        payload
+            .as_ref()
+            .and_then(|p| p.get("windows"))
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+    }
+
     /// Check if prerequisites are met for a job type
     fn check_job_prerequisites(
         db: &Database,
         job_type: &JobType,
         asset_id: i64,
     ) -> Result<bool> {
+        // Jobs that read the original wait while it's offline or archived, until it's relinked
+        if availability::reads_original(job_type)
+            && availability::asset_availability(db, asset_id)?.is_some_and(|a| a != Availability::Online)
+        {
+            return Ok(false);
+        }
+
         match job_type {
             JobType::BuildSegments | JobType::TranscribeAsset | JobType::AnalyzeVisionAsset => {
                 // These can run immediately (no prerequisites)
@@ -115,6 +148,10 @@ impl JobProcessor {
                 // Requires segments_built_at
                 db.check_asset_prerequisites(asset_id, &["segments_built"])
             }
+            JobType::ClassifySegments => {
+                // Reads the transcript, vision and motion signals gathered by then
+                db.check_asset_prerequisites(asset_id, &["metadata_ready"])
+            }
             JobType::EmbedSegments => {
                 // Requires metadata_ready_at
                 db.check_asset_prerequisites(asset_id, &["metadata_ready"])
@@ -127,6 +164,18 @@ impl JobProcessor {
                 // Can run immediately (no prerequisites)
                 Ok(true)
             }
+            JobType::AnalyzeAudioEvents => {
+                // Requires segments_built_at (events are attached to segments)
+                db.check_asset_prerequisites(asset_id, &["segments_built"])
+            }
+            JobType::DetectRedactions => {
+                // Scans the time ranges of built segments
+                db.check_asset_prerequisites(asset_id, &["segments_built"])
+            }
+            JobType::External(kind) => {
+                // Gated on whatever asset states its worker registered as required
+                crate::jobs::external::prerequisites_met(db, kind, asset_id)
+            }
             _ => {
                 // Other job types - allow them to run (they'll handle their own prerequisites)
                 Ok(true)
@@ -138,10 +187,22 @@ impl JobProcessor {
     pub async fn process_job(&self, job_id: i64) -> Result<()> {
         let job = self.job_manager.get_job(job_id)?
             .ok_or_else(|| anyhow::anyhow!("Job {} not found", job_id))?;
+
+        // Analysis over a quota fails with the reason; a retry runs it once there's room
+        if let Err(e) = quotas::check_analysis_job(&self.db, &job) {
+            eprintln!("[QUOTAS] Job {} not run: {}", job_id, e);
+            self.job_manager.fail_job(job_id, &e.to_string())?;
+            return Ok(());
+        }
         
         // Update status to Running
         self.job_manager.update_job_status(job_id, JobStatus::Running, Some(0.0))?;
-        
+
+        // Processes the job starts run under its class's resource limits
+        resources::run_as(JobClass::for_job(&job.job_type), self.run_job(job_id, job)).await
+    }
+
+    async fn run_job(&self, job_id: i64, job: Job) -> Result<()> {
         // Process based on job type
         // Note: Actual processing logic will be implemented in separate modules
         // This is just the processor framework with gating logic
@@ -170,6 +231,7 @@ impl JobProcessor {
                         .and_then(|p| p.get("media_path"))
                         .and_then(|v| v.as_str())
                         .ok_or_else(|| anyhow::anyhow!("Missing media_path"))?;
+                    let windows = Self::extract_windows(&job.payload);
                     
                     if let Err(e) = crate::jobs::transcribe::process_transcribe_asset(
                         self.db.clone(),
@@ -177,6 +239,7 @@ impl JobProcessor {
                         job_id,
                         asset_id,
                         media_path,
+                        windows.as_deref(),
                     ).await {
                         eprintln!("Error processing TranscribeAsset job {}: {:?}", job_id, e);
                         let _ = self.job_manager.update_job_status(job_id, JobStatus::Failed, None);
@@ -194,12 +257,27 @@ impl JobProcessor {
                         .and_then(|v| v.as_str())
                         .ok_or_else(|| anyhow::anyhow!("Missing media_path"))?;
                     
+                    let vision_config = match crate::jobs::vision::VisionConfig::resolve(
+                        &self.db,
+                        asset_id,
+                        job.payload.as_ref().and_then(|p| p.get("vision_config")),
+                    ) {
+                        Ok(config) => config,
+                        Err(e) => {
+                            eprintln!("AnalyzeVisionAsset job {} has invalid vision config: {:?}", job_id, e);
+                            let _ = self.job_manager.update_job_status(job_id, JobStatus::Failed, None);
+                            return Err(e);
+                        }
+                    };
+
                     if let Err(e) = crate::jobs::vision::process_analyze_vision_asset(
                         self.db.clone(),
                         self.job_manager.clone(),
                         job_id,
                         asset_id,
                         media_path,
+                        vision_config,
+                        Self::extract_windows(&job.payload).as_deref(),
                     ).await {
                         eprintln!("Error processing AnalyzeVisionAsset job {}: {:?}", job_id, e);
                         let _ = self.job_manager.update_job_status(job_id, JobStatus::Failed, None);
@@ -261,6 +339,23 @@ impl JobProcessor {
                     let _ = self.job_manager.update_job_status(job_id, JobStatus::Failed, None);
                 }
             }
+            JobType::ClassifySegments => {
+                if let Some(asset_id) = Self::extract_asset_id_from_payload(&job.payload) {
+                    if let Err(e) = crate::jobs::classify::process_classify_segments(
+                        self.db.clone(),
+                        self.job_manager.clone(),
+                        job_id,
+                        asset_id,
+                    ).await {
+                        eprintln!("Error processing ClassifySegments job {}: {:?}", job_id, e);
+                        let _ = self.job_manager.update_job_status(job_id, JobStatus::Failed, None);
+                        return Err(e);
+                    }
+                } else {
+                    eprintln!("ClassifySegments job {} missing asset_id", job_id);
+                    let _ = self.job_manager.update_job_status(job_id, JobStatus::Failed, None);
+                }
+            }
             JobType::EmbedSegments => {
                 if let Some(asset_id) = Self::extract_asset_id_from_payload(&job.payload) {
                     if let Err(e) = crate::jobs::embeddings::process_embed_segments(
@@ -301,6 +396,323 @@ impl JobProcessor {
                     let _ = self.job_manager.update_job_status(job_id, JobStatus::Failed, None);
                 }
             }
+            JobType::GenerateProxy => {
+                if let Some(asset_id) = Self::extract_asset_id_from_payload(&job.payload) {
+                    // Tiers to render; defaults to the eager tiers of the proxy ladder
+                    let tiers: Vec<String> = job.payload.as_ref()
+                        .and_then(|p| p.get("tiers"))
+                        .and_then(|v| v.as_array())
+                        .map(|arr| arr.iter().filter_map(|t| t.as_str().map(String::from)).collect())
+                        .unwrap_or_else(crate::media::proxy::eager_tier_names);
+
+                    if let Err(e) = crate::jobs::proxy::process_generate_proxy(
+                        self.db.clone(),
+                        self.job_manager.clone(),
+                        job_id,
+                        asset_id,
+                        tiers,
+                    ).await {
+                        eprintln!("Error processing GenerateProxy job {}: {:?}", job_id, e);
+                        let _ = self.job_manager.update_job_status(job_id, JobStatus::Failed, None);
+                        return Err(e);
+                    }
+                } else {
+                    eprintln!("GenerateProxy job {} missing asset_id", job_id);
+                    let _ = self.job_manager.update_job_status(job_id, JobStatus::Failed, None);
+                }
+            }
+            JobType::AnalyzeAudioEvents => {
+                if let Some(asset_id) = Self::extract_asset_id_from_payload(&job.payload) {
+                    let media_path = job.payload.as_ref()
+                        .and_then(|p| p.get("media_path"))
+                        .and_then(|v| v.as_str())
+                        .ok_or_else(|| anyhow::anyhow!("Missing media_path"))?;
+
+                    if let Err(e) = crate::jobs::audio_events::process_analyze_audio_events(
+                        self.db.clone(),
+                        self.job_manager.clone(),
+                        job_id,
+                        asset_id,
+                        media_path,
+                    ).await {
+                        eprintln!("Error processing AnalyzeAudioEvents job {}: {:?}", job_id, e);
+                        let _ = self.job_manager.update_job_status(job_id, JobStatus::Failed, None);
+                        return Err(e);
+                    }
+                } else {
+                    eprintln!("AnalyzeAudioEvents job {} missing asset_id", job_id);
+                    let _ = self.job_manager.update_job_status(job_id, JobStatus::Failed, None);
+                }
+            }
+            JobType::DetectRedactions => {
+                if let Some(asset_id) = Self::extract_asset_id_from_payload(&job.payload) {
+                    let segment_ids: Vec<i64> = job.payload.as_ref()
+                        .and_then(|p| p.get("segment_ids"))
+                        .and_then(|v| serde_json::from_value(v.clone()).ok())
+                        .unwrap_or_default();
+                    let kinds: Vec<String> = job.payload.as_ref()
+                        .and_then(|p| p.get("kinds"))
+                        .and_then(|v| serde_json::from_value(v.clone()).ok())
+                        .unwrap_or_else(|| crate::jobs::redaction::KINDS.iter().map(|k| k.to_string()).collect());
+
+                    if let Err(e) = crate::jobs::redaction::process_detect_redactions(
+                        self.db.clone(),
+                        self.job_manager.clone(),
+                        job_id,
+                        asset_id,
+                        &segment_ids,
+                        &kinds,
+                    ).await {
+                        eprintln!("Error processing DetectRedactions job {}: {:?}", job_id, e);
+                        let _ = self.job_manager.update_job_status(job_id, JobStatus::Failed, None);
+                        return Err(e);
+                    }
+                } else {
+                    eprintln!("DetectRedactions job {} missing asset_id", job_id);
+                    let _ = self.job_manager.update_job_status(job_id, JobStatus::Failed, None);
+                }
+            }
+            JobType::DetectStillSubjects => {
+                if let Some(asset_id) = Self::extract_asset_id_from_payload(&job.payload) {
+                    if let Err(e) = crate::jobs::still_subjects::process_detect_still_subjects(
+                        self.db.clone(),
+                        self.job_manager.clone(),
+                        job_id,
+                        asset_id,
+                    ).await {
+                        eprintln!("Error processing DetectStillSubjects job {}: {:?}", job_id, e);
+                        let _ = self.job_manager.update_job_status(job_id, JobStatus::Failed, None);
+                        return Err(e);
+                    }
+                } else {
+                    eprintln!("DetectStillSubjects job {} missing asset_id", job_id);
+                    let _ = self.job_manager.update_job_status(job_id, JobStatus::Failed, None);
+                }
+            }
+            JobType::SyncMulticam => {
+                let payload = job.payload.as_ref();
+                let project_id = payload.and_then(|p| p.get("project_id")).and_then(|v| v.as_i64());
+                let asset_ids: Vec<i64> = payload
+                    .and_then(|p| p.get("asset_ids"))
+                    .and_then(|v| serde_json::from_value(v.clone()).ok())
+                    .unwrap_or_default();
+                let name = payload
+                    .and_then(|p| p.get("name"))
+                    .and_then(|v| v.as_str())
+                    .map(|s| s.to_string());
+
+                if let Some(project_id) = project_id {
+                    if let Err(e) = crate::jobs::multicam::process_sync_multicam(
+                        self.db.clone(),
+                        self.job_manager.clone(),
+                        job_id,
+                        project_id,
+                        &asset_ids,
+                        name,
+                    ).await {
+                        eprintln!("Error processing SyncMulticam job {}: {:?}", job_id, e);
+                        let _ = self.job_manager.fail_job(job_id, &e.to_string());
+                        return Err(e);
+                    }
+                } else {
+                    eprintln!("SyncMulticam job {} missing project_id", job_id);
+                    let _ = self.job_manager.update_job_status(job_id, JobStatus::Failed, None);
+                }
+            }
+            JobType::ReanalyzeChangedAsset => {
+                if let Some(asset_id) = Self::extract_asset_id_from_payload(&job.payload) {
+                    if let Err(e) = crate::jobs::reanalyze::process_reanalyze_changed_asset(
+                        self.db.clone(),
+                        self.job_manager.clone(),
+                        job_id,
+                        asset_id,
+                    ).await {
+                        eprintln!("Error processing ReanalyzeChangedAsset job {}: {:?}", job_id, e);
+                        let _ = self.job_manager.fail_job(job_id, &e.to_string());
+                        return Err(e);
+                    }
+                } else {
+                    eprintln!("ReanalyzeChangedAsset job {} missing asset_id", job_id);
+                    let _ = self.job_manager.update_job_status(job_id, JobStatus::Failed, None);
+                }
+            }
+            JobType::GenerateThumbnailLevel => {
+                let asset_id = Self::extract_asset_id_from_payload(&job.payload);
+                let interval_ms = job.payload.as_ref()
+                    .and_then(|p| p.get("interval_ms"))
+                    .and_then(|v| v.as_i64());
+                if let (Some(asset_id), Some(interval_ms)) = (asset_id, interval_ms) {
+                    if let Err(e) = crate::jobs::thumbnails::process_generate_thumbnail_level(
+                        self.db.clone(),
+                        self.job_manager.clone(),
+                        job_id,
+                        asset_id,
+                        interval_ms,
+                    ).await {
+                        eprintln!("Error processing GenerateThumbnailLevel job {}: {:?}", job_id, e);
+                        let _ = self.job_manager.fail_job(job_id, &e.to_string());
+                        return Err(e);
+                    }
+                } else {
+                    eprintln!("GenerateThumbnailLevel job {} missing asset_id or interval_ms", job_id);
+                    let _ = self.job_manager.update_job_status(job_id, JobStatus::Failed, None);
+                }
+            }
+            JobType::VerifyChecksums => {
+                let payload = job.payload.as_ref();
+                let project_id = payload.and_then(|p| p.get("project_id")).and_then(|v| v.as_i64());
+                let asset_ids: Option<Vec<i64>> = payload
+                    .and_then(|p| p.get("asset_ids"))
+                    .and_then(|v| serde_json::from_value(v.clone()).ok());
+
+                if let Some(project_id) = project_id {
+                    if let Err(e) = crate::jobs::integrity::process_verify_checksums(
+                        self.db.clone(),
+                        self.job_manager.clone(),
+                        job_id,
+                        project_id,
+                        asset_ids.as_deref(),
+                    ).await {
+                        eprintln!("Error processing VerifyChecksums job {}: {:?}", job_id, e);
+                        let _ = self.job_manager.fail_job(job_id, &e.to_string());
+                        return Err(e);
+                    }
+                } else {
+                    eprintln!("VerifyChecksums job {} missing project_id", job_id);
+                    let _ = self.job_manager.update_job_status(job_id, JobStatus::Failed, None);
+                }
+            }
+            JobType::DetectSyncDrift => {
+                let payload = job.payload.as_ref();
+                let project_id = payload.and_then(|p| p.get("project_id")).and_then(|v| v.as_i64());
+                let asset_ids: Option<Vec<i64>> = payload
+                    .and_then(|p| p.get("asset_ids"))
+                    .and_then(|v| serde_json::from_value(v.clone()).ok());
+                let correct = payload
+                    .and_then(|p| p.get("correct"))
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
+
+                if let Some(project_id) = project_id {
+                    if let Err(e) = crate::jobs::sync_drift::process_detect_sync_drift(
+                        self.db.clone(),
+                        self.job_manager.clone(),
+                        job_id,
+                        project_id,
+                        asset_ids.as_deref(),
+                        correct,
+                    ).await {
+                        eprintln!("Error processing DetectSyncDrift job {}: {:?}", job_id, e);
+                        let _ = self.job_manager.fail_job(job_id, &e.to_string());
+                        return Err(e);
+                    }
+                } else {
+                    eprintln!("DetectSyncDrift job {} missing project_id", job_id);
+                    let _ = self.job_manager.update_job_status(job_id, JobStatus::Failed, None);
+                }
+            }
+            JobType::PreRenderPreview => {
+                let project_id = job.payload.as_ref().and_then(|p| p.get("project_id")).and_then(|v| v.as_i64());
+                if let Some(project_id) = project_id {
+                    if let Err(e) = crate::jobs::preview::process_prerender_preview(
+                        self.db.clone(),
+                        self.job_manager.clone(),
+                        job_id,
+                        project_id,
+                    ).await {
+                        eprintln!("Error processing PreRenderPreview job {}: {:?}", job_id, e);
+                        let _ = self.job_manager.fail_job(job_id, &e.to_string());
+                        return Err(e);
+                    }
+                } else {
+                    eprintln!("PreRenderPreview job {} missing project_id", job_id);
+                    let _ = self.job_manager.update_job_status(job_id, JobStatus::Failed, None);
+                }
+            }
+            JobType::AlignVoiceover => {
+                let payload = job.payload.as_ref();
+                let voiceover_id = payload.and_then(|p| p.get("voiceover_id")).and_then(|v| v.as_i64());
+                let align = payload.and_then(|p| p.get("align")).and_then(|v| v.as_bool()).unwrap_or(true);
+                if let Some(voiceover_id) = voiceover_id {
+                    if let Err(e) = crate::jobs::voiceover::process_align_voiceover(
+                        self.db.clone(),
+                        self.job_manager.clone(),
+                        job_id,
+                        voiceover_id,
+                        align,
+                    ).await {
+                        eprintln!("Error processing AlignVoiceover job {}: {:?}", job_id, e);
+                        let _ = self.job_manager.fail_job(job_id, &e.to_string());
+                        return Err(e);
+                    }
+                } else {
+                    eprintln!("AlignVoiceover job {} missing voiceover_id", job_id);
+                    let _ = self.job_manager.update_job_status(job_id, JobStatus::Failed, None);
+                }
+            }
+            JobType::SynthesizeNarration => {
+                let payload = job.payload.clone().unwrap_or_default();
+                let project_id = payload.get("project_id").and_then(|v| v.as_i64());
+                let request: crate::jobs::voiceover::NarrationRequest =
+                    serde_json::from_value(payload).unwrap_or_default();
+                if let Some(project_id) = project_id {
+                    if let Err(e) = crate::jobs::voiceover::process_synthesize_narration(
+                        self.db.clone(),
+                        self.job_manager.clone(),
+                        job_id,
+                        project_id,
+                        request,
+                    ).await {
+                        eprintln!("Error processing SynthesizeNarration job {}: {:?}", job_id, e);
+                        let _ = self.job_manager.fail_job(job_id, &e.to_string());
+                        return Err(e);
+                    }
+                } else {
+                    eprintln!("SynthesizeNarration job {} missing project_id", job_id);
+                    let _ = self.job_manager.update_job_status(job_id, JobStatus::Failed, None);
+                }
+            }
+            JobType::AnalyzeMusic => {
+                let track_id = job.payload.as_ref().and_then(|p| p.get("music_track_id")).and_then(|v| v.as_i64());
+                if let Some(track_id) = track_id {
+                    if let Err(e) = crate::jobs::music::process_analyze_music(
+                        self.db.clone(),
+                        self.job_manager.clone(),
+                        job_id,
+                        track_id,
+                    ).await {
+                        eprintln!("Error processing AnalyzeMusic job {}: {:?}", job_id, e);
+                        let _ = self.job_manager.fail_job(job_id, &e.to_string());
+                        return Err(e);
+                    }
+                } else {
+                    eprintln!("AnalyzeMusic job {} missing music_track_id", job_id);
+                    let _ = self.job_manager.update_job_status(job_id, JobStatus::Failed, None);
+                }
+            }
+            JobType::Export if crate::simulation::enabled() => {
+                if let Err(e) = crate::simulation::export::process_export(
+                    self.job_manager.clone(),
+                    job_id,
+                    job.payload,
+                ).await {
+                    eprintln!("Error processing Export job {}: {:?}", job_id, e);
+                    let _ = self.job_manager.fail_job(job_id, &e.to_string());
+                    return Err(e);
+                }
+            }
+            JobType::External(ref kind) => {
+                if let Err(e) = crate::jobs::external::process_external_job(
+                    self.db.clone(),
+                    self.job_manager.clone(),
+                    job_id,
+                    kind,
+                ).await {
+                    eprintln!("Error processing {} job {}: {:?}", kind, job_id, e);
+                    let _ = self.job_manager.fail_job(job_id, &e.to_string());
+                    return Err(e);
+                }
+            }
             _ => {
                 // Other job types handled elsewhere
                 // Don't mark as completed here - let the actual handlers do it
@@ -315,7 +727,16 @@ impl JobProcessor {
 
     /// Main processing loop
     pub async fn run(&self) {
+        if let Err(e) = resources::load(&self.db) {
+            eprintln!("Error loading job resource limits, using defaults: {:?}", e);
+        }
+
         loop {
+            // Jobs whose remote worker went quiet go back to the queue
+            if let Err(e) = crate::jobs::remote::release_expired_claims(&self.job_manager) {
+                eprintln!("Error releasing expired remote claims: {:?}", e);
+            }
+
             // Get ready jobs (this locks the DB, but releases before await)
             let ready_jobs = match self.get_ready_jobs() {
                 Ok(jobs) => jobs,
@@ -326,8 +747,14 @@ impl JobProcessor {
                 }
             };
             
+            // While a bulk import has deferred assets, the order is re-checked after every job, so
+            // follow-up work of the assets that come first isn't stuck behind deferred analysis
+            let one_at_a_time = priority::has_deferred_assets(&self.db).unwrap_or(false);
+            let more_ready = one_at_a_time && ready_jobs.len() > 1;
+            let batch = if one_at_a_time { &ready_jobs[..ready_jobs.len().min(1)] } else { &ready_jobs[..] };
+
             // Process jobs (no DB locks held during await)
-            for job_id in ready_jobs {
+            for &job_id in batch {
                 if let Err(e) = self.process_job(job_id).await {
                     eprintln!("Error processing job {}: {:?}", job_id, e);
                     let _ = self.job_manager.update_job_status(
@@ -339,7 +766,9 @@ impl JobProcessor {
             }
             
             // Poll every 1-2 seconds
-            sleep(Duration::from_secs(1)).await;
+            if !more_ready {
+                sleep(Duration::from_secs(1)).await;
+            }
         }
     }
 }
@@ -0,0 +1,118 @@
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::crypto;
+use crate::db::Database;
+use crate::jobs::JobManager;
+use crate::media::av_sync::SyncDrift;
+use crate::media::ffmpeg::FFmpegWrapper;
+use crate::media::proxy::{find_tier, proxy_dimensions, proxy_path};
+use crate::media::thumbnails::{count_files, BASE_INTERVAL_MS};
+use crate::media::still::{STILL_DURATION_TICKS, STILL_FPS};
+use crate::storage;
+use engine::timeline::TICKS_PER_SECOND;
+
+/// Process GenerateProxy job - renders the requested proxy tiers and thumbnails
+pub async fn process_generate_proxy(
+    db: Arc<Database>,
+    job_manager: Arc<JobManager>,
+    job_id: i64,
+    asset_id: i64,
+    tiers: Vec<String>,
+) -> Result<()> {
+    let asset_path = db.get_media_asset_path(asset_id)?
+        .ok_or_else(|| anyhow::anyhow!("Media asset {} not found", asset_id))?;
+
+    // Fails while the project is locked, before any plaintext proxy is rendered
+    let key = db.asset_key(asset_id)?;
+
+    // Probe to get source dimensions
+    let media_info = FFmpegWrapper::probe(Path::new(&asset_path)).await?;
+    let still = db.is_still_asset(asset_id)?;
+    // Resample drifting audio when the asset's sync correction is on
+    let sync_correction = db
+        .get_media_asset_sync_drift(asset_id)?
+        .filter(|(_, corrected)| *corrected)
+        .and_then(|(json, _)| serde_json::from_str::<SyncDrift>(&json).ok())
+        .and_then(|drift| drift.correction_filter());
+
+    let cache_dir = PathBuf::from(".cache");
+    let total_steps = tiers.len() as f64 + 1.0; // +1 for thumbnails
+
+    for (idx, tier_name) in tiers.iter().enumerate() {
+        let tier = find_tier(tier_name)
+            .ok_or_else(|| anyhow::anyhow!("Unknown proxy tier '{}'", tier_name))?;
+
+        let (width, height) = proxy_dimensions(media_info.width, media_info.height, tier.max_height);
+        let output_path = proxy_path(&cache_dir, asset_id, &tier.name);
+
+        if still {
+            FFmpegWrapper::generate_still_proxy(
+                Path::new(&asset_path),
+                &output_path,
+                width,
+                height,
+                tier.crf,
+                STILL_FPS,
+                STILL_DURATION_TICKS as f64 / TICKS_PER_SECOND as f64,
+            ).await?;
+        } else {
+            FFmpegWrapper::generate_proxy_with_crf(
+                Path::new(&asset_path),
+                &output_path,
+                width,
+                height,
+                tier.crf,
+                sync_correction.as_deref(),
+            ).await?;
+        }
+
+        db.upsert_proxy(
+            asset_id,
+            &tier.name,
+            output_path.to_str().unwrap(),
+            "libx264",
+            width,
+            height,
+        )?;
+
+        eprintln!("[PROXY] Generated {} proxy ({}x{}) for asset {}", tier.name, width, height, asset_id);
+        if let Some(key) = key {
+            let path = output_path.clone();
+            tokio::task::spawn_blocking(move || crypto::encrypt_file(&key, &path)).await??;
+        }
+        let object_name = format!("proxies/asset_{}/{}.mp4", asset_id, tier.name);
+        if let Err(e) = storage::store(&db, &output_path, &object_name, "video/mp4").await {
+            eprintln!("[STORAGE] Failed to store {} proxy for asset {}: {:?}", tier.name, asset_id, e);
+        }
+
+        let progress = (idx + 1) as f64 / total_steps;
+        job_manager.update_job_status(job_id, crate::jobs::JobStatus::Running, Some(progress))?;
+    }
+
+    // Generate thumbnails if missing (first proxy job for an asset, or cache was cleared)
+    let has_thumbnails = db.get_thumbnail_dir(asset_id)?
+        .map(|dir| Path::new(&dir).exists())
+        .unwrap_or(false);
+    if !has_thumbnails {
+        let thumbnails_dir = cache_dir.join("thumbs").join(format!("asset_{}", asset_id));
+        let thumbnail_dir_path = FFmpegWrapper::extract_thumbnails(
+            Path::new(&asset_path),
+            &thumbnails_dir,
+        ).await?;
+        db.set_thumbnail_dir(asset_id, &thumbnail_dir_path)?;
+        db.set_thumbnail_level(asset_id, BASE_INTERVAL_MS, count_files(Path::new(&thumbnail_dir_path), "t_"))?;
+        if let Some(key) = key {
+            crypto::encrypt_asset_files(&db, asset_id, &key)?;
+        }
+        if let Err(e) = storage::store_thumbnails(&db, asset_id, Path::new(&thumbnail_dir_path)).await {
+            eprintln!("[STORAGE] Failed to store thumbnails for asset {}: {:?}", asset_id, e);
+        }
+    }
+
+    // Mark job as completed
+    job_manager.update_job_status(job_id, crate::jobs::JobStatus::Completed, Some(1.0))?;
+
+    Ok(())
+}
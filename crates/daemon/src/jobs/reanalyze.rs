@@ -0,0 +1,556 @@
+//! Re-analysis of an asset whose file was replaced in place, e.g. re-exported after a trim or a
+//! grade. The old contents (through the asset's proxy) and the new file are fingerprinted and
+//! lined up, so segments whose content is still there keep their analysis, moved to where the
+//! content now sits. Only what changed or is new gets transcribed, analyzed and embedded again.
+
+use anyhow::Result;
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::db::Database;
+use crate::jobs::build_segments::SEGMENT_DURATION_SECONDS;
+use crate::jobs::enrichment::VISION_TAG_KINDS;
+use crate::jobs::transcribe::{offset_segment, transcription_audio_path};
+use crate::jobs::{JobManager, JobType};
+use crate::media::compute_file_checksum;
+use crate::media::ffmpeg::{FFmpegWrapper, MediaInfo};
+
+use engine::timeline::{Timeline, TICKS_PER_SECOND};
+
+/// Fingerprint samples per second, which is also how finely moved content is placed
+const SAMPLES_PER_SECOND: f64 = 4.0;
+/// Frames whose hashes differ in at most this many of their 64 bits show the same picture
+const MAX_HASH_DISTANCE: u32 = 10;
+/// Share of a segment's frames that must be found for its picture to count as unchanged
+const MIN_PICTURE_MATCH: f64 = 0.8;
+/// Share of a segment's audio levels that must match for its sound to count as unchanged
+const MIN_AUDIO_MATCH: f64 = 0.9;
+const AUDIO_LEVEL_TOLERANCE_DB: f64 = 3.0;
+/// Levels below this are silence, which matches silence whatever the exact level
+const SILENCE_DB: f64 = -60.0;
+/// Stretches of the new file that no old segment covers get segments once they're this long
+const MIN_NEW_CONTENT_SECONDS: f64 = 1.0;
+
+struct Fingerprint {
+    /// Picture hash per sample
+    picture: Vec<u64>,
+    /// Audio level (dBFS) per sample; None without audio, or when it couldn't be read
+    audio: Option<Vec<f64>>,
+}
+
+/// Where an old segment's content is in the new file
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Placement {
+    /// Still there, `offset` samples later (negative = earlier)
+    Kept { offset: i64, picture_changed: bool, audio_changed: bool },
+    /// Cut from the new file
+    Removed,
+}
+
+/// Process ReanalyzeChangedAsset job - compares an asset's file against its stored checksum and,
+/// if it changed, carries over the analysis of unchanged content and queues analysis of the rest
+pub async fn process_reanalyze_changed_asset(
+    db: Arc<Database>,
+    job_manager: Arc<JobManager>,
+    job_id: i64,
+    asset_id: i64,
+) -> Result<()> {
+    let asset = db.get_media_asset(asset_id)?
+        .ok_or_else(|| anyhow::anyhow!("Media asset {} not found", asset_id))?;
+    let project_id = db.get_asset_project_id(asset_id)?
+        .ok_or_else(|| anyhow::anyhow!("Media asset {} has no project", asset_id))?;
+    let path = PathBuf::from(&asset.path);
+
+    let checksum = compute_file_checksum(&path).await?;
+    let stored = db.find_media_asset_by_path(project_id, &asset.path)?.and_then(|(_, checksum)| checksum);
+    if stored.as_deref() == Some(checksum.as_str()) {
+        eprintln!("[REANALYZE] Asset {} is unchanged", asset_id);
+        job_manager.set_job_result(job_id, json!({ "changed": false }))?;
+        job_manager.update_job_status(job_id, crate::jobs::JobStatus::Completed, Some(1.0))?;
+        return Ok(());
+    }
+    job_manager.report_progress(job_id, 0.1)?;
+
+    // A still keeps the length and rate it was given
+    let is_still = db.is_still_asset(asset_id)?;
+    let mut info = FFmpegWrapper::probe(&path).await?;
+    if is_still {
+        info.duration_ticks = asset.duration_ticks;
+        info.fps_num = asset.fps_num;
+        info.fps_den = asset.fps_den;
+        info.has_audio = false;
+    }
+
+    // Without both fingerprints nothing can be matched, and everything is analyzed afresh
+    let fingerprints = if is_still {
+        None
+    } else {
+        let old_seconds = asset.duration_ticks as f64 / TICKS_PER_SECOND as f64;
+        match (old_fingerprint(&db, asset_id, old_seconds).await, new_fingerprint(&path, &info).await) {
+            (Ok(old), Ok(new)) => Some((old, new)),
+            (Err(e), _) | (_, Err(e)) => {
+                eprintln!("[REANALYZE] Can't line up asset {}'s old and new contents, re-analyzing all of it: {:?}", asset_id, e);
+                None
+            }
+        }
+    };
+    job_manager.report_progress(job_id, 0.5)?;
+
+    let segments = db.get_segments_by_asset(asset_id)?;
+    let ranges: Vec<(usize, usize)> = segments
+        .iter()
+        .map(|s| (to_sample(Database::get_coalesced_src_in(s)), to_sample(Database::get_coalesced_src_out(s))))
+        .collect();
+    let placements = match &fingerprints {
+        Some((old, new)) => place_segments(old, new, &ranges),
+        None => vec![Placement::Removed; segments.len()],
+    };
+    // (old src in, old src out, placement) of every segment, for remapping anything timed in the old file
+    let moves: Vec<(i64, i64, Placement)> = segments
+        .iter()
+        .zip(&placements)
+        .map(|(s, p)| (Database::get_coalesced_src_in(s), Database::get_coalesced_src_out(s), *p))
+        .collect();
+
+    // Carry over what's still there and forget what changed
+    let duration_ticks = info.duration_ticks;
+    let mut kept: Vec<(i64, i64)> = Vec::new();
+    let mut picture_windows: Vec<(i64, i64)> = Vec::new();
+    let mut audio_windows: Vec<(i64, i64)> = Vec::new();
+    let mut removed = Vec::new();
+    let (mut moved, mut picture_changes, mut audio_changes) = (0, 0, 0);
+    for (segment, (src_in, src_out, placement)) in segments.iter().zip(&moves) {
+        let Placement::Kept { offset, picture_changed, audio_changed } = *placement else {
+            removed.push(segment.id);
+            continue;
+        };
+        let shift = offset_ticks(offset);
+        if shift != 0 {
+            db.shift_segment(segment.id, shift, duration_ticks)?;
+            moved += 1;
+        }
+        let range = ((src_in + shift).max(0), (src_out + shift).min(duration_ticks));
+        kept.push(range);
+        if picture_changed {
+            db.clear_segment_picture_analysis(segment.id)?;
+            db.replace_segment_tags(segment.id, VISION_TAG_KINDS, &[])?;
+            picture_windows.push(range);
+            picture_changes += 1;
+        }
+        if audio_changed {
+            db.clear_segment_audio_analysis(segment.id)?;
+            audio_windows.push(range);
+            audio_changes += 1;
+        }
+        if picture_changed || audio_changed {
+            db.reindex_segment_text(segment.id)?;
+        }
+    }
+    db.delete_segments(&removed)?;
+
+    // New content gets segments of its own, analyzed in full
+    let added = add_segments(&db, project_id, asset_id, &info, &kept)?;
+    picture_windows.extend(&added);
+    audio_windows.extend(&added);
+
+    remap_asset_analysis(&db, asset_id, &moves)?;
+    let (clips_moved, affected_clips) = remap_timeline(&db, project_id, asset_id, &moves)?;
+    job_manager.report_progress(job_id, 0.8)?;
+
+    db.update_media_asset_file(asset_id, &checksum, &info)?;
+    // The new file hasn't been verified or measured yet
+    db.set_media_asset_integrity(asset_id, None, None)?;
+    db.set_media_asset_sync_drift(asset_id, None)?;
+    let _ = tokio::fs::remove_file(transcription_audio_path(asset_id)).await;
+    if let Some(dir) = db.get_thumbnail_dir(asset_id)? {
+        let _ = tokio::fs::remove_dir_all(dir).await;
+    }
+    db.clear_thumbnail_levels(asset_id)?;
+    for (tier, proxy_path, _, _) in db.list_proxies(asset_id)? {
+        let _ = tokio::fs::remove_file(&proxy_path).await;
+        db.delete_proxy(asset_id, &tier)?;
+    }
+    job_manager.create_job(JobType::GenerateProxy, Some(json!({
+        "media_asset_id": asset_id,
+        "input_path": asset.path,
+        "tiers": crate::media::proxy::eager_tier_names(),
+    })), None)?;
+
+    // Nothing carried over means the whole file is new: analyze it like a fresh import
+    let whole = kept.is_empty();
+    let audio_windows = if info.has_audio { merge_windows(audio_windows) } else { Vec::new() };
+    let picture_windows = merge_windows(picture_windows);
+    let mut queued = Vec::new();
+    if info.has_audio && (whole || !audio_windows.is_empty()) {
+        let mut payload = json!({ "asset_id": asset_id, "media_path": asset.path });
+        if !whole {
+            payload["windows"] = json!(audio_windows);
+        }
+        job_manager.create_job(JobType::TranscribeAsset, Some(payload), None)?;
+        job_manager.create_job(JobType::AnalyzeAudioEvents, Some(json!({
+            "asset_id": asset_id,
+            "media_path": asset.path,
+        })), None)?;
+        queued.extend(["TranscribeAsset", "AnalyzeAudioEvents"]);
+    }
+    if whole || !picture_windows.is_empty() {
+        let mut payload = json!({ "asset_id": asset_id, "media_path": asset.path });
+        if !whole {
+            payload["windows"] = json!(picture_windows);
+        }
+        job_manager.create_job(JobType::AnalyzeVisionAsset, Some(payload), None)?;
+        queued.push("AnalyzeVisionAsset");
+    }
+    if is_still {
+        job_manager.create_job(JobType::DetectStillSubjects, Some(json!({ "asset_id": asset_id })), None)?;
+        queued.push("DetectStillSubjects");
+    }
+    // Analysis was dropped (e.g. the sound was removed) with nothing queued to redo it
+    if queued.is_empty() && (picture_changes + audio_changes > 0 || !added.is_empty()) {
+        job_manager.create_job(JobType::ComputeSegmentMetadata, Some(json!({ "asset_id": asset_id })), None)?;
+        queued.push("ComputeSegmentMetadata");
+    }
+
+    eprintln!(
+        "[REANALYZE] Asset {}: {} segments kept ({} moved, {} picture changed, {} sound changed), {} removed, {} added",
+        asset_id, kept.len(), moved, picture_changes, audio_changes, removed.len(), added.len()
+    );
+    job_manager.set_job_result(job_id, json!({
+        "changed": true,
+        "aligned": fingerprints.is_some(),
+        "segments": {
+            "kept": kept.len(),
+            "moved": moved,
+            "picture_changed": picture_changes,
+            "audio_changed": audio_changes,
+            "removed": removed.len(),
+            "added": added.len(),
+        },
+        "transcribe_windows": if whole { None } else { Some(&audio_windows) },
+        "vision_windows": if whole { None } else { Some(&picture_windows) },
+        "timeline_clips_moved": clips_moved,
+        "affected_clips": affected_clips,
+        "queued": queued,
+    }))?;
+    job_manager.update_job_status(job_id, crate::jobs::JobStatus::Completed, Some(1.0))?;
+    Ok(())
+}
+
+/// Queue a check of an asset's file for changes; one already queued for the asset is reused
+pub fn queue_reanalysis(job_manager: &JobManager, asset_id: i64) -> Result<i64> {
+    job_manager.create_job(
+        JobType::ReanalyzeChangedAsset,
+        Some(json!({ "asset_id": asset_id })),
+        Some(format!("ReanalyzeChangedAsset:{}", asset_id)),
+    )
+}
+
+/// Fingerprint of the asset's old contents, from its smallest proxy (and the cached
+/// transcription audio, when there is some)
+async fn old_fingerprint(db: &Database, asset_id: i64, duration_seconds: f64) -> Result<Fingerprint> {
+    let proxy = db
+        .list_proxies(asset_id)?
+        .into_iter()
+        .map(|(_, path, _, _)| PathBuf::from(path))
+        .find(|path| path.exists())
+        .ok_or_else(|| anyhow::anyhow!("no proxy of the old contents"))?;
+    let picture = FFmpegWrapper::picture_fingerprints(&proxy, SAMPLES_PER_SECOND).await?;
+    if picture.is_empty() {
+        anyhow::bail!("no frames in the old contents' proxy");
+    }
+    let cached_audio = transcription_audio_path(asset_id);
+    let audio_source = if cached_audio.exists() { cached_audio } else { proxy };
+    let audio = audio_levels(&audio_source, duration_seconds).await;
+    Ok(Fingerprint { picture, audio })
+}
+
+async fn new_fingerprint(path: &Path, info: &MediaInfo) -> Result<Fingerprint> {
+    let picture = FFmpegWrapper::picture_fingerprints(path, SAMPLES_PER_SECOND).await?;
+    if picture.is_empty() {
+        anyhow::bail!("no frames in the new file");
+    }
+    let audio = if info.has_audio {
+        audio_levels(path, info.duration_ticks as f64 / TICKS_PER_SECOND as f64).await
+    } else {
+        None
+    };
+    Ok(Fingerprint { picture, audio })
+}
+
+async fn audio_levels(path: &Path, duration_seconds: f64) -> Option<Vec<f64>> {
+    FFmpegWrapper::audio_levels_between(path, 1.0 / SAMPLES_PER_SECOND, 0.0, duration_seconds)
+        .await
+        .ok()
+        .map(|levels| levels.into_iter().map(|(_, level)| level).collect::<Vec<_>>())
+        .filter(|levels| !levels.is_empty())
+}
+
+fn to_sample(ticks: i64) -> usize {
+    (ticks as f64 / TICKS_PER_SECOND as f64 * SAMPLES_PER_SECOND).round().max(0.0) as usize
+}
+
+fn offset_ticks(offset: i64) -> i64 {
+    (offset as f64 * TICKS_PER_SECOND as f64 / SAMPLES_PER_SECOND).round() as i64
+}
+
+/// Find each old segment (a range of samples) in the new contents. A segment is kept where its
+/// frames are found; one whose frames aren't found but whose sound is still where its neighbours
+/// moved to was changed in place (re-graded, re-titled). Anything else was cut.
+fn place_segments(old: &Fingerprint, new: &Fingerprint, ranges: &[(usize, usize)]) -> Vec<Placement> {
+    let mut placements = Vec::with_capacity(ranges.len());
+    let mut previous_offset = 0;
+    for &(start, end) in ranges {
+        let end = end.min(old.picture.len());
+        let placement = match best_offset(old, new, start, end, previous_offset) {
+            Some((offset, matched)) if matched as f64 >= MIN_PICTURE_MATCH * (end - start) as f64 => {
+                previous_offset = offset;
+                Placement::Kept {
+                    offset,
+                    picture_changed: false,
+                    audio_changed: audio_changed(old, new, start, end, offset),
+                }
+            }
+            _ => Placement::Removed,
+        };
+        placements.push(placement);
+    }
+
+    for idx in 0..placements.len() {
+        if placements[idx] != Placement::Removed {
+            continue;
+        }
+        // Nearest kept segment before this one, else after it
+        let neighbour_offset = placements[..idx]
+            .iter()
+            .rev()
+            .chain(&placements[idx + 1..])
+            .find_map(|p| match p {
+                Placement::Kept { offset, .. } => Some(*offset),
+                Placement::Removed => None,
+            });
+        let Some(offset) = neighbour_offset else { continue };
+        let (start, end) = ranges[idx];
+        let fits = start as i64 + offset >= 0 && end as i64 + offset <= new.picture.len() as i64;
+        if fits && !is_silent(old, start, end) && audio_matches(old, new, start, end, offset) == Some(true) {
+            placements[idx] = Placement::Kept { offset, picture_changed: true, audio_changed: false };
+        }
+    }
+    placements
+}
+
+/// Offset (in samples) at which the most of the segment's frames match, with how many matched.
+/// Ties go to `preferred` (where the previous segment went), then to the smallest move, so
+/// static shots that look alike everywhere stay put.
+fn best_offset(old: &Fingerprint, new: &Fingerprint, start: usize, end: usize, preferred: i64) -> Option<(i64, usize)> {
+    if end <= start || new.picture.len() < end - start {
+        return None;
+    }
+    let segment = &old.picture[start..end];
+    let matches_at = |position: usize| {
+        segment
+            .iter()
+            .zip(&new.picture[position..position + segment.len()])
+            .filter(|(a, b)| (**a ^ **b).count_ones() <= MAX_HASH_DISTANCE)
+            .count()
+    };
+
+    // Usually the content is where the previous segment's went
+    let preferred_position = start as i64 + preferred;
+    if preferred_position >= 0 && preferred_position as usize + segment.len() <= new.picture.len() {
+        let matched = matches_at(preferred_position as usize);
+        if matched == segment.len() {
+            return Some((preferred, matched));
+        }
+    }
+
+    let mut best: Option<(i64, usize)> = None;
+    for position in 0..=(new.picture.len() - segment.len()) {
+        let matched = matches_at(position);
+        let offset = position as i64 - start as i64;
+        let rank = |offset: i64, matched: usize| (matched, offset == preferred, std::cmp::Reverse(offset.abs()));
+        if best.is_none_or(|(best_offset, best_matched)| rank(offset, matched) > rank(best_offset, best_matched)) {
+            best = Some((offset, matched));
+        }
+    }
+    best
+}
+
+/// Whether the segment's sound is the same at `offset` (None when there's nothing to compare)
+fn audio_matches(old: &Fingerprint, new: &Fingerprint, start: usize, end: usize, offset: i64) -> Option<bool> {
+    let (old_levels, new_levels) = (old.audio.as_ref()?, new.audio.as_ref()?);
+    let (mut compared, mut matched) = (0, 0);
+    for i in start..end {
+        let new_level = usize::try_from(i as i64 + offset).ok().and_then(|j| new_levels.get(j));
+        let (Some(a), Some(b)) = (old_levels.get(i), new_level) else { continue };
+        compared += 1;
+        if (a - b).abs() <= AUDIO_LEVEL_TOLERANCE_DB || (*a < SILENCE_DB && *b < SILENCE_DB) {
+            matched += 1;
+        }
+    }
+    (compared > 0).then_some(matched as f64 >= MIN_AUDIO_MATCH * compared as f64)
+}
+
+fn audio_changed(old: &Fingerprint, new: &Fingerprint, start: usize, end: usize, offset: i64) -> bool {
+    match (&old.audio, &new.audio) {
+        (None, None) => false,
+        (Some(_), Some(_)) => audio_matches(old, new, start, end, offset) != Some(true),
+        // Sound added or removed (or the old sound couldn't be read)
+        _ => true,
+    }
+}
+
+fn is_silent(fingerprint: &Fingerprint, start: usize, end: usize) -> bool {
+    fingerprint.audio.as_ref().is_none_or(|levels| {
+        levels.get(start..end.min(levels.len())).unwrap_or_default().iter().all(|level| *level < SILENCE_DB)
+    })
+}
+
+/// Cover the parts of the new file no kept segment covers with fresh fixed-length segments.
+/// Returns their (src in, src out) ranges.
+fn add_segments(
+    db: &Database,
+    project_id: i64,
+    asset_id: i64,
+    info: &MediaInfo,
+    kept: &[(i64, i64)],
+) -> Result<Vec<(i64, i64)>> {
+    let min_ticks = (MIN_NEW_CONTENT_SECONDS * TICKS_PER_SECOND as f64) as i64;
+    let segment_ticks = (SEGMENT_DURATION_SECONDS * TICKS_PER_SECOND as f64) as i64;
+    let mut covered = kept.to_vec();
+    covered.sort();
+
+    let mut gaps = Vec::new();
+    let mut cursor = 0;
+    for (start, end) in covered {
+        if start - cursor >= min_ticks {
+            gaps.push((cursor, start));
+        }
+        cursor = cursor.max(end);
+    }
+    if info.duration_ticks - cursor >= min_ticks {
+        gaps.push((cursor, info.duration_ticks));
+    }
+
+    let recording_start = info
+        .creation_time
+        .as_deref()
+        .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok());
+    let mut added = Vec::new();
+    for (gap_start, gap_end) in gaps {
+        for start in (gap_start..gap_end).step_by(segment_ticks.max(1) as usize) {
+            let end = (start + segment_ticks).min(gap_end);
+            let segment_id = db.create_segment(project_id, asset_id, start, end)?;
+            if let Some(recorded) = recording_start {
+                let capture_time = recorded + chrono::Duration::milliseconds(start * 1000 / TICKS_PER_SECOND);
+                db.update_segment_capture_time(segment_id, &capture_time.to_rfc3339())?;
+            }
+            added.push((start, end));
+        }
+    }
+    Ok(added)
+}
+
+/// Offset (in ticks) to apply to something at `ticks` in the old file, when it's in content that
+/// was kept and whose picture (or sound) didn't change
+fn carried_offset(moves: &[(i64, i64, Placement)], ticks: i64, picture: bool) -> Option<i64> {
+    moves.iter().find_map(|(start, end, placement)| match *placement {
+        Placement::Kept { offset, picture_changed, audio_changed }
+            if ticks >= *start && ticks < *end && !(if picture { picture_changed } else { audio_changed }) =>
+        {
+            Some(offset_ticks(offset))
+        }
+        _ => None,
+    })
+}
+
+/// Move the asset's stored transcript and vision results along with the content they describe,
+/// dropping what no longer applies; the windowed jobs fill in the rest
+fn remap_asset_analysis(db: &Database, asset_id: i64, moves: &[(i64, i64, Placement)]) -> Result<()> {
+    let remap = |json: &str, picture: bool| -> Result<String> {
+        let mut analysis: serde_json::Value = serde_json::from_str(json)?;
+        let entries: Vec<serde_json::Value> = analysis
+            .get("segments")
+            .and_then(|s| s.as_array())
+            .cloned()
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|entry| {
+                let start = entry.get("start").and_then(|v| v.as_f64())?;
+                let end = entry.get("end").and_then(|v| v.as_f64())?;
+                let mid_ticks = ((start + end) / 2.0 * TICKS_PER_SECOND as f64) as i64;
+                let offset = carried_offset(moves, mid_ticks, picture)?;
+                Some(offset_segment(entry, offset as f64 / TICKS_PER_SECOND as f64))
+            })
+            .collect();
+        analysis["segments"] = json!(entries);
+        Ok(analysis.to_string())
+    };
+
+    if let Some(transcript) = db.get_asset_transcript(asset_id)? {
+        db.store_asset_transcript(asset_id, &remap(&transcript, false)?)?;
+    }
+    if let Some(vision) = db.get_asset_vision(asset_id)? {
+        db.store_asset_vision(asset_id, &remap(&vision, true)?)?;
+    }
+    Ok(())
+}
+
+/// Move the current timeline's clips of the asset along with their content. Returns how many
+/// moved, and the clips over content that was cut or changed.
+fn remap_timeline(
+    db: &Database,
+    project_id: i64,
+    asset_id: i64,
+    moves: &[(i64, i64, Placement)],
+) -> Result<(usize, Vec<serde_json::Value>)> {
+    let Some(mut timeline) = db
+        .get_timeline(project_id)?
+        .and_then(|json| serde_json::from_str::<Timeline>(&json).ok())
+    else {
+        return Ok((0, Vec::new()));
+    };
+
+    let mut moved = 0;
+    let mut affected = Vec::new();
+    for clip in timeline.tracks.iter_mut().flat_map(|t| t.clips.iter_mut()) {
+        if clip.asset_id != asset_id {
+            continue;
+        }
+        let mid = (clip.in_ticks + clip.out_ticks) / 2;
+        match moves.iter().find(|(start, end, _)| mid >= *start && mid < *end).map(|(_, _, p)| *p) {
+            Some(Placement::Kept { offset, picture_changed, audio_changed }) => {
+                let shift = offset_ticks(offset);
+                if shift != 0 {
+                    clip.in_ticks += shift;
+                    clip.out_ticks += shift;
+                    moved += 1;
+                }
+                if picture_changed || audio_changed {
+                    affected.push(json!({ "clip_id": clip.id, "reason": "changed" }));
+                }
+            }
+            _ => affected.push(json!({ "clip_id": clip.id, "reason": "removed" })),
+        }
+    }
+    if moved > 0 {
+        db.store_timeline(project_id, &serde_json::to_string(&timeline)?)?;
+    }
+    Ok((moved, affected))
+}
+
+/// Merge overlapping or touching tick ranges into (start, end) second windows
+fn merge_windows(mut ranges: Vec<(i64, i64)>) -> Vec<(f64, f64)> {
+    ranges.sort();
+    let mut merged: Vec<(i64, i64)> = Vec::new();
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+        .into_iter()
+        .map(|(start, end)| (start as f64 / TICKS_PER_SECOND as f64, end as f64 / TICKS_PER_SECOND as f64))
+        .collect()
+}
@@ -0,0 +1,137 @@
+use anyhow::Result;
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::db::{Database, RedactionBox};
+use crate::jobs::JobManager;
+use engine::render::BlurRegion;
+use engine::timeline::TICKS_PER_SECOND;
+
+use crate::llm::ml_service_url;
+
+/// What the detector can find
+pub const KINDS: &[&str] = &["face", "plate"];
+
+/// Seconds between scanned frames; each detected box is blurred until the next one
+const SAMPLE_INTERVAL_SEC: f64 = 0.5;
+/// Margin added around detected boxes (fraction of the box on each side), since detectors
+/// crop tightly and subjects move between samples
+const BOX_PADDING: f64 = 0.2;
+
+#[derive(Debug, Deserialize)]
+struct DetectedSubject {
+    kind: String,
+    window: usize,
+    boxes: Vec<RedactionBox>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DetectResponse {
+    subjects: Vec<DetectedSubject>,
+}
+
+/// Process DetectRedactions job - finds faces/plates in the selected segments of an asset and
+/// replaces each segment's redaction subjects
+pub async fn process_detect_redactions(
+    db: Arc<Database>,
+    job_manager: Arc<JobManager>,
+    job_id: i64,
+    asset_id: i64,
+    segment_ids: &[i64],
+    kinds: &[String],
+) -> Result<()> {
+    let asset = db
+        .get_media_asset(asset_id)?
+        .ok_or_else(|| anyhow::anyhow!("Media asset {} not found", asset_id))?;
+    let media_path = std::fs::canonicalize(&asset.path)?;
+
+    // Selected segments of this asset (all of them when none were selected)
+    let segments: Vec<_> = db
+        .get_segments_by_asset(asset_id)?
+        .into_iter()
+        .filter(|s| segment_ids.is_empty() || segment_ids.contains(&s.id))
+        .collect();
+    let windows: Vec<serde_json::Value> = segments
+        .iter()
+        .map(|segment| serde_json::json!({
+            "start": Database::get_coalesced_src_in(segment) as f64 / TICKS_PER_SECOND as f64,
+            "end": Database::get_coalesced_src_out(segment) as f64 / TICKS_PER_SECOND as f64,
+        }))
+        .collect();
+    job_manager.update_job_status(job_id, crate::jobs::JobStatus::Running, Some(0.1))?;
+
+    let response = reqwest::Client::new()
+        .post(format!("{}/redaction/detect", ml_service_url()))
+        .json(&serde_json::json!({
+            "mediaPath": media_path.to_string_lossy(),
+            "windows": windows,
+            "kinds": kinds,
+            "sampleIntervalSec": SAMPLE_INTERVAL_SEC,
+        }))
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Err(anyhow::anyhow!("ML service redaction detection failed: {}", response.status()));
+    }
+    let detected: DetectResponse = response.json().await?;
+    job_manager.update_job_status(job_id, crate::jobs::JobStatus::Running, Some(0.8))?;
+
+    let mut subject_count = 0;
+    for (window, segment) in segments.iter().enumerate() {
+        let subjects: Vec<(String, Vec<RedactionBox>)> = detected
+            .subjects
+            .iter()
+            .filter(|s| s.window == window)
+            .map(|s| (s.kind.clone(), s.boxes.clone()))
+            .collect();
+        subject_count += db
+            .replace_redaction_subjects(segment.project_id, asset_id, segment.id, &subjects)?
+            .len();
+    }
+
+    eprintln!(
+        "[REDACTION] Asset {}: {} subjects in {} segments",
+        asset_id, subject_count, segments.len()
+    );
+    job_manager.update_job_status(job_id, crate::jobs::JobStatus::Completed, Some(1.0))?;
+    Ok(())
+}
+
+/// Regions to blur on export for the enabled subjects of the given assets. Each sampled box is
+/// held until the subject's next sample (at most two sample intervals) and padded.
+pub fn blur_regions(db: &Database, project_id: i64, asset_ids: &[i64]) -> Result<Vec<BlurRegion>> {
+    let mut regions = Vec::new();
+    for subject in db
+        .list_redaction_subjects(project_id, None)?
+        .into_iter()
+        .filter(|s| s.enabled && asset_ids.contains(&s.asset_id))
+    {
+        let mut boxes = subject.regions.clone();
+        boxes.sort_by(|a, b| a.t.total_cmp(&b.t));
+        for (i, b) in boxes.iter().enumerate() {
+            let hold_until = boxes
+                .get(i + 1)
+                .map(|next| next.t.min(b.t + 2.0 * SAMPLE_INTERVAL_SEC))
+                .unwrap_or(b.t + SAMPLE_INTERVAL_SEC);
+            let (x, w) = pad(b.x, b.w);
+            let (y, h) = pad(b.y, b.h);
+            regions.push(BlurRegion {
+                asset_id: subject.asset_id,
+                start_ticks: (b.t * TICKS_PER_SECOND as f64) as i64,
+                end_ticks: (hold_until * TICKS_PER_SECOND as f64) as i64,
+                x,
+                y,
+                w,
+                h,
+            });
+        }
+    }
+    Ok(regions)
+}
+
+/// Grow a normalized (offset, size) span by BOX_PADDING on each side, kept inside the frame
+fn pad(offset: f64, size: f64) -> (f64, f64) {
+    let start = (offset - size * BOX_PADDING).clamp(0.0, 0.99);
+    let end = (offset + size * (1.0 + BOX_PADDING)).clamp(start + 0.01, 1.0);
+    (start, end - start)
+}
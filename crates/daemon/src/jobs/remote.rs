@@ -0,0 +1,351 @@
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use uuid::Uuid;
+
+use crate::db::{Database, RemoteWorker};
+use crate::jobs::{transcribe, vision, Job, JobManager, JobStatus, JobType};
+use crate::media::availability::{self, Availability};
+use crate::media::companion::sanitize_filename;
+use crate::quotas;
+
+/// Job types remote workers can take: renders, and the analysis passes heavy enough to be worth
+/// sending the media over for
+pub const REMOTE_JOB_TYPES: &[&str] = &["Export", "TranscribeAsset", "AnalyzeVisionAsset"];
+/// How long a claim lasts without a heartbeat before the job goes back to the queue
+pub const LEASE_SECONDS: i64 = 120;
+/// A worker that checked in this recently counts as online, and its job types are left for it
+pub const ONLINE_SECONDS: i64 = 60;
+/// Attempts (including ones a worker dropped) before a remotely run job is marked failed
+pub const MAX_ATTEMPTS: i64 = 3;
+
+pub fn new_worker_token() -> String {
+    format!("vcwrk_{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+/// Problems with the job types a worker asks for, or None when they're all remote-capable
+pub fn validate_job_types(job_types: &[String]) -> Option<String> {
+    if job_types.is_empty() {
+        return Some("A worker needs at least one job type".to_string());
+    }
+    job_types
+        .iter()
+        .find(|t| !REMOTE_JOB_TYPES.contains(&t.as_str()))
+        .map(|t| format!("{} can't run remotely (expected one of {})", t, REMOTE_JOB_TYPES.join(", ")))
+}
+
+pub fn is_online(worker: &RemoteWorker) -> bool {
+    let cutoff = chrono::Utc::now() - chrono::Duration::seconds(ONLINE_SECONDS);
+    worker
+        .last_seen_at
+        .as_deref()
+        .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+        .is_some_and(|t| t > cutoff)
+}
+
+/// Job types an online worker takes; the local processor leaves pending jobs of these types
+/// for the workers so a laptop doesn't start a render a desktop is waiting to pick up
+pub fn online_job_types(db: &Database) -> Result<Vec<String>> {
+    let mut job_types = Vec::new();
+    for worker in db.list_remote_workers()?.iter().filter(|w| is_online(w)) {
+        for job_type in &worker.job_types {
+            if !job_types.contains(job_type) {
+                job_types.push(job_type.clone());
+            }
+        }
+    }
+    Ok(job_types)
+}
+
+/// A file the worker needs: fetched from GET /queue/jobs/:id/inputs/:index, then used wherever
+/// `path` appears in the job's ffmpeg arguments or ML request
+#[derive(Debug, Clone, Serialize)]
+pub struct RemoteInput {
+    pub index: usize,
+    pub path: String,
+    pub size_bytes: u64,
+}
+
+/// What a worker gets when it claims a job
+#[derive(Debug, Clone, Serialize)]
+pub struct ClaimedJob {
+    pub job_id: i64,
+    pub job_type: String,
+    pub attempt: i64,
+    pub payload: Option<Value>,
+    /// Send a heartbeat before this, or the job goes back to the queue
+    pub lease_expires_at: String,
+    pub inputs: Vec<RemoteInput>,
+    /// For analysis jobs: the ML service `endpoint` to call and the `body` to send it
+    pub ml_request: Option<Value>,
+    /// Files to upload before completing (PUT /queue/jobs/:id/artifacts/:name)
+    pub artifacts: Vec<&'static str>,
+}
+
+fn lease_deadline() -> String {
+    (chrono::Utc::now() + chrono::Duration::seconds(LEASE_SECONDS)).to_rfc3339()
+}
+
+/// Claim the oldest pending job the worker can run (of `job_types`, when it narrows them)
+pub fn claim_next(
+    db: &Database,
+    job_manager: &JobManager,
+    worker: &RemoteWorker,
+    job_types: Option<&[String]>,
+) -> Result<Option<ClaimedJob>> {
+    let job_types: Vec<String> = worker
+        .job_types
+        .iter()
+        .filter(|t| job_types.is_none_or(|wanted| wanted.contains(t)))
+        .cloned()
+        .collect();
+    for job_id in job_manager.pending_jobs(&job_types)? {
+        let Some(job) = job_manager.get_job(job_id)? else { continue };
+        if !is_ready(db, &job)? {
+            continue;
+        }
+        if let Err(e) = quotas::check_analysis_job(db, &job) {
+            eprintln!("[QUOTAS] Job {} not run: {}", job_id, e);
+            job_manager.fail_job(job_id, &e.to_string())?;
+            continue;
+        }
+        let lease_expires_at = lease_deadline();
+        if !job_manager.claim_job(job_id, worker.id, &lease_expires_at)? {
+            continue; // Taken since it was listed
+        }
+        eprintln!("[REMOTE] Worker {} ({}) claimed {} job {}", worker.id, worker.name, job.job_type.to_string(), job_id);
+        return Ok(Some(ClaimedJob {
+            job_id,
+            job_type: job.job_type.to_string().to_string(),
+            attempt: job.attempts,
+            lease_expires_at,
+            inputs: inputs(&job),
+            ml_request: ml_request(db, &job)?,
+            artifacts: match job.job_type {
+                JobType::Export => vec!["output"],
+                _ => Vec::new(),
+            },
+            payload: job.payload,
+        }));
+    }
+    Ok(None)
+}
+
+/// Analysis jobs wait while the original they read is offline, as they do locally
+fn is_ready(db: &Database, job: &Job) -> Result<bool> {
+    match asset_id(job) {
+        Some(asset_id) if availability::reads_original(&job.job_type) => Ok(availability::asset_availability(db, asset_id)?
+            .is_none_or(|a| a == Availability::Online)),
+        _ => Ok(true),
+    }
+}
+
+fn asset_id(job: &Job) -> Option<i64> {
+    let payload = job.payload.as_ref()?;
+    payload
+        .get("asset_id")
+        .or_else(|| payload.get("media_asset_id"))
+        .and_then(|v| v.as_i64())
+}
+
+fn payload_str<'a>(job: &'a Job, key: &str) -> Option<&'a str> {
+    job.payload.as_ref()?.get(key)?.as_str()
+}
+
+/// Local files a job reads: an export's ffmpeg inputs (media, chapters, captions), or the media
+/// an analysis job looks at
+pub fn inputs(job: &Job) -> Vec<RemoteInput> {
+    let mut paths: Vec<String> = Vec::new();
+    match job.job_type {
+        JobType::Export => {
+            let payload = job.payload.as_ref();
+            // Clip intermediates and spans to render read the sources; the passes read cached
+            // intermediates, and a stitched export the previous output and cached spans
+            let renders: Vec<&Value> = ["clip_renders", "span_renders"]
+                .into_iter()
+                .filter_map(|key| payload.and_then(|p| p.get(key)).and_then(|r| r.as_array()))
+                .flatten()
+                .collect();
+            let render_args = renders
+                .iter()
+                .flat_map(|r| ["first_pass_args", "ffmpeg_args"].into_iter().filter_map(|key| r.get(key)));
+            let passes = ["first_pass_args", "ffmpeg_args"].into_iter().filter_map(|key| payload.and_then(|p| p.get(key)));
+            let concat_list = payload
+                .and_then(|p| p.get("concat_list"))
+                .and_then(|l| l.as_array())
+                .into_iter()
+                .flatten()
+                .filter_map(|entry| entry.get("path").and_then(|p| p.as_str()));
+            for path in concat_list {
+                if !paths.iter().any(|p| p == path) {
+                    paths.push(path.to_string());
+                }
+            }
+            for args in render_args.chain(passes) {
+                let args: Vec<&str> = args
+                    .as_array()
+                    .map(|a| a.iter().filter_map(|v| v.as_str()).collect())
+                    .unwrap_or_default();
+                for pair in args.windows(2) {
+                    // lavfi sources after -i aren't files and are skipped by the existence check
+                    if pair[0] == "-i" && !paths.iter().any(|p| p == pair[1]) {
+                        paths.push(pair[1].to_string());
+                    }
+                }
+            }
+        }
+        _ => paths.extend(payload_str(job, "media_path").map(String::from)),
+    }
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            let size_bytes = std::fs::metadata(&path).ok().filter(|m| m.is_file())?.len();
+            Some((path, size_bytes))
+        })
+        .enumerate()
+        .map(|(index, (path, size_bytes))| RemoteInput { index, path, size_bytes })
+        .collect()
+}
+
+/// The request the daemon would send its own ML service for an analysis job
+fn ml_request(db: &Database, job: &Job) -> Result<Option<Value>> {
+    let Some(media_path) = payload_str(job, "media_path") else {
+        return Ok(None);
+    };
+    Ok(match job.job_type {
+        JobType::TranscribeAsset => Some(json!({
+            "endpoint": "/transcribe",
+            "body": { "mediaPath": media_path },
+        })),
+        JobType::AnalyzeVisionAsset => {
+            let asset_id = asset_id(job).ok_or_else(|| anyhow!("Job {} has no asset_id", job.id))?;
+            let config = vision::VisionConfig::resolve(
+                db,
+                asset_id,
+                job.payload.as_ref().and_then(|p| p.get("vision_config")),
+            )?;
+            Some(json!({
+                "endpoint": "/vision/analyze",
+                "body": vision::analyze_request(media_path, &config),
+            }))
+        }
+        _ => None,
+    })
+}
+
+/// Where a worker's uploaded artifact for a job is kept until the job completes
+pub fn artifact_path(job_id: i64, name: &str) -> PathBuf {
+    artifact_dir(job_id).join(sanitize_filename(name))
+}
+
+fn artifact_dir(job_id: i64) -> PathBuf {
+    PathBuf::from(".cache").join("remote").join(format!("job_{}", job_id))
+}
+
+/// Keep the worker's claim on a job alive and record its progress. Returns the new deadline,
+/// or None when the worker no longer holds the job (cancelled, or handed to someone else).
+pub fn heartbeat(job_manager: &JobManager, job: &Job, worker_id: i64, progress: Option<f64>) -> Result<Option<String>> {
+    let lease_expires_at = lease_deadline();
+    if !job_manager.renew_claim(job.id, worker_id, &lease_expires_at)? {
+        return Ok(None);
+    }
+    if let Some(progress) = progress {
+        job_manager.report_progress(job.id, progress)?;
+    }
+    Ok(Some(lease_expires_at))
+}
+
+/// Apply a worker's finished job the way the local processor would: move an export's output
+/// into place, or store an analysis result and queue what follows it
+pub async fn complete(db: &Database, job_manager: &JobManager, job: &Job, result: Value) -> Result<()> {
+    match job.job_type {
+        JobType::Export => {
+            let out_path = payload_str(job, "out_path").ok_or_else(|| anyhow!("Export job {} has no out_path", job.id))?;
+            let output = artifact_path(job.id, "output");
+            let size_bytes = tokio::fs::metadata(&output).await.map(|m| m.len()).unwrap_or(0);
+            if size_bytes == 0 {
+                return Err(anyhow!("The rendered output hasn't been uploaded"));
+            }
+            if let Some(parent) = std::path::Path::new(out_path).parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            // Rename fails across filesystems; copy then
+            if tokio::fs::rename(&output, out_path).await.is_err() {
+                tokio::fs::copy(&output, out_path).await?;
+            }
+            // Clip intermediates and spans the worker rendered go into the render cache for the
+            // next export
+            let render_paths: Vec<PathBuf> = ["clip_renders", "span_renders"]
+                .into_iter()
+                .filter_map(|key| job.payload.as_ref().and_then(|p| p.get(key)).and_then(|r| r.as_array()))
+                .flatten()
+                .filter_map(|r| r.get("path").and_then(|p| p.as_str()).map(PathBuf::from))
+                .collect();
+            for path in render_paths {
+                let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+                let artifact = artifact_path(job.id, &name);
+                if !artifact.exists() {
+                    continue;
+                }
+                if let Some(parent) = path.parent() {
+                    tokio::fs::create_dir_all(parent).await?;
+                }
+                if tokio::fs::rename(&artifact, &path).await.is_err() {
+                    tokio::fs::copy(&artifact, &path).await?;
+                }
+            }
+            let mut result = if result.is_object() { result } else { json!({}) };
+            result["size_bytes"] = json!(size_bytes);
+            job_manager.set_job_result(job.id, result)?;
+        }
+        JobType::TranscribeAsset => {
+            let asset_id = asset_id(job).ok_or_else(|| anyhow!("Job {} has no asset_id", job.id))?;
+            transcribe::store_transcript(db, job_manager, asset_id, &result)?;
+        }
+        JobType::AnalyzeVisionAsset => {
+            let asset_id = asset_id(job).ok_or_else(|| anyhow!("Job {} has no asset_id", job.id))?;
+            vision::store_vision(db, job_manager, asset_id, &result)?;
+        }
+        _ => return Err(anyhow!("{} jobs can't run remotely", job.job_type.to_string())),
+    }
+    job_manager.update_job_status(job.id, JobStatus::Completed, Some(1.0))?;
+    job_manager.release_claim(job.id)?;
+    let _ = tokio::fs::remove_dir_all(artifact_dir(job.id)).await;
+    Ok(())
+}
+
+/// A remote attempt failed: queue the job for another attempt, or fail it once MAX_ATTEMPTS
+/// are used up. Returns whether it was requeued.
+pub fn fail(job_manager: &JobManager, job: &Job, error: &str) -> Result<bool> {
+    let _ = std::fs::remove_dir_all(artifact_dir(job.id));
+    if job.attempts < MAX_ATTEMPTS {
+        job_manager.requeue_job(job.id, error)?;
+        return Ok(true);
+    }
+    job_manager.fail_job(job.id, error)?;
+    job_manager.release_claim(job.id)?;
+    Ok(false)
+}
+
+/// Send the jobs of workers that stopped checking in back to the queue
+pub fn release_expired_claims(job_manager: &JobManager) -> Result<()> {
+    let now = chrono::Utc::now().to_rfc3339();
+    for (job_id, worker_id) in job_manager.claimed_jobs(None, Some(&now))? {
+        if let Some(job) = job_manager.get_job(job_id)? {
+            eprintln!("[REMOTE] Worker {} stopped checking in on job {}", worker_id, job_id);
+            fail(job_manager, &job, &format!("Remote worker {} stopped responding", worker_id))?;
+        }
+    }
+    Ok(())
+}
+
+/// Send every job a worker holds back to the queue (when it's removed)
+pub fn release_worker_jobs(job_manager: &JobManager, worker_id: i64) -> Result<()> {
+    for (job_id, _) in job_manager.claimed_jobs(Some(worker_id), None)? {
+        if let Some(job) = job_manager.get_job(job_id)? {
+            fail(job_manager, &job, &format!("Remote worker {} was removed", worker_id))?;
+        }
+    }
+    Ok(())
+}
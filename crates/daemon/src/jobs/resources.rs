@@ -0,0 +1,249 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{OnceLock, RwLock};
+use tokio::process::Command;
+
+use crate::db::Database;
+use crate::jobs::JobType;
+
+/// In performance mode, background processes get at most this many threads...
+const PERFORMANCE_THREADS: u32 = 2;
+/// ...and at least this niceness, with idle IO priority
+const PERFORMANCE_NICE: i32 = 15;
+
+const MAX_THREADS: u32 = 64;
+const MIN_MEMORY_MB: u64 = 256;
+
+/// Groups of jobs that share resource limits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobClass {
+    /// Importing and checksumming originals
+    Import,
+    /// Transcoding editing proxies
+    Proxy,
+    /// Segmenting, transcription, vision and the rest of the analysis pipeline
+    Analysis,
+    /// Extension jobs and analyzer plugins
+    Extension,
+}
+
+impl JobClass {
+    pub const ALL: [JobClass; 4] = [JobClass::Import, JobClass::Proxy, JobClass::Analysis, JobClass::Extension];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobClass::Import => "import",
+            JobClass::Proxy => "proxy",
+            JobClass::Analysis => "analysis",
+            JobClass::Extension => "extension",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<JobClass> {
+        JobClass::ALL.into_iter().find(|class| class.as_str() == s)
+    }
+
+    pub fn for_job(job_type: &JobType) -> JobClass {
+        match job_type {
+            JobType::ImportRaw | JobType::VerifyChecksums => JobClass::Import,
+            JobType::GenerateProxy | JobType::GenerateThumbnailLevel | JobType::PreRenderPreview => JobClass::Proxy,
+            JobType::External(_) => JobClass::Extension,
+            _ => JobClass::Analysis,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IoPriority {
+    #[default]
+    Normal,
+    /// Lowest best-effort level
+    Low,
+    /// Only touches the disk when nothing else wants it
+    Idle,
+}
+
+/// Limits applied to the processes (ffmpeg, ffprobe, plugins, extension workers) a job starts.
+/// IO priority only takes effect on Linux.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResourceLimits {
+    /// ffmpeg decoder, filter and encoder threads (None = ffmpeg picks, usually every core)
+    pub threads: Option<u32>,
+    /// Scheduling niceness, 0 (normal) to 19 (only spare CPU)
+    pub nice: i32,
+    pub io_priority: IoPriority,
+    /// Address space cap per process; a process that needs more fails instead of swapping
+    pub memory_mb: Option<u64>,
+}
+
+impl ResourceLimits {
+    /// What a class gets until it's configured: analysis and extensions yield to the editor
+    pub fn default_for(class: JobClass) -> ResourceLimits {
+        match class {
+            JobClass::Import => ResourceLimits::default(),
+            JobClass::Proxy => ResourceLimits { nice: 5, ..ResourceLimits::default() },
+            JobClass::Analysis | JobClass::Extension => ResourceLimits {
+                nice: 10,
+                io_priority: IoPriority::Low,
+                ..ResourceLimits::default()
+            },
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.threads.is_some_and(|t| !(1..=MAX_THREADS).contains(&t)) {
+            return Err(format!("threads must be 1-{}", MAX_THREADS));
+        }
+        if !(0..=19).contains(&self.nice) {
+            return Err("nice must be 0-19".to_string());
+        }
+        if self.memory_mb.is_some_and(|m| m < MIN_MEMORY_MB) {
+            return Err(format!("memory_mb must be at least {}", MIN_MEMORY_MB));
+        }
+        Ok(())
+    }
+
+    /// Tightened for performance mode; configured limits that are already stricter stay
+    fn for_performance_mode(&self) -> ResourceLimits {
+        ResourceLimits {
+            threads: Some(self.threads.map_or(PERFORMANCE_THREADS, |t| t.min(PERFORMANCE_THREADS))),
+            nice: self.nice.max(PERFORMANCE_NICE),
+            io_priority: IoPriority::Idle,
+            memory_mb: self.memory_mb,
+        }
+    }
+}
+
+static CONFIGURED: OnceLock<RwLock<HashMap<JobClass, ResourceLimits>>> = OnceLock::new();
+static PERFORMANCE_MODE: AtomicBool = AtomicBool::new(false);
+
+tokio::task_local! {
+    /// Class of the job the current task is running (unset for API requests)
+    static CURRENT_CLASS: JobClass;
+}
+
+fn configured_limits() -> &'static RwLock<HashMap<JobClass, ResourceLimits>> {
+    CONFIGURED.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+/// Load the stored per-class limits; called once when the job processor starts
+pub fn load(db: &Database) -> Result<()> {
+    let mut configured = configured_limits().write().unwrap();
+    configured.clear();
+    for (class, limits) in db.list_job_resource_limits()? {
+        if let Some(class) = JobClass::parse(&class) {
+            configured.insert(class, limits);
+        }
+    }
+    Ok(())
+}
+
+/// Limits set for a class (its defaults when none are stored), and whether they were set
+pub fn configured(class: JobClass) -> (ResourceLimits, bool) {
+    match configured_limits().read().unwrap().get(&class) {
+        Some(limits) => (limits.clone(), true),
+        None => (ResourceLimits::default_for(class), false),
+    }
+}
+
+/// Limits a class's processes start with right now, performance mode included
+pub fn effective(class: JobClass) -> ResourceLimits {
+    let (limits, _) = configured(class);
+    if performance_mode() {
+        limits.for_performance_mode()
+    } else {
+        limits
+    }
+}
+
+pub fn set_limits(db: &Database, class: JobClass, limits: ResourceLimits) -> Result<()> {
+    db.set_job_resource_limits(class.as_str(), &limits)?;
+    configured_limits().write().unwrap().insert(class, limits);
+    Ok(())
+}
+
+/// Put a class back on its defaults
+pub fn reset_limits(db: &Database, class: JobClass) -> Result<()> {
+    db.delete_job_resource_limits(class.as_str())?;
+    configured_limits().write().unwrap().remove(&class);
+    Ok(())
+}
+
+pub fn performance_mode() -> bool {
+    PERFORMANCE_MODE.load(Ordering::SeqCst)
+}
+
+/// Performance mode holds background jobs to a couple of low-priority threads while the user
+/// edits. Processes already running keep the limits they started with.
+pub fn set_performance_mode(enabled: bool) {
+    PERFORMANCE_MODE.store(enabled, Ordering::SeqCst);
+}
+
+/// Run a job's work under its class's limits
+pub async fn run_as<F: Future>(class: JobClass, work: F) -> F::Output {
+    CURRENT_CLASS.scope(class, work).await
+}
+
+fn current_limits() -> Option<ResourceLimits> {
+    CURRENT_CLASS.try_with(|class| effective(*class)).ok()
+}
+
+/// Thread cap for ffmpeg in the current job, if any
+pub fn ffmpeg_threads() -> Option<u32> {
+    current_limits().and_then(|limits| limits.threads)
+}
+
+/// A command for `program` that starts with the current job's niceness, IO priority and memory
+/// cap. Outside a job (e.g. an API request the user is waiting on) it's a plain command.
+pub fn command(program: impl AsRef<std::ffi::OsStr>) -> Command {
+    let mut command = Command::new(program);
+    if let Some(limits) = current_limits() {
+        apply(&mut command, limits);
+    }
+    command
+}
+
+#[cfg(unix)]
+fn apply(command: &mut Command, limits: ResourceLimits) {
+    // SAFETY: the closure runs in the forked child before exec and only makes
+    // async-signal-safe system calls. Failures (e.g. no permission) leave the default.
+    unsafe {
+        command.pre_exec(move || {
+            if limits.nice > 0 {
+                libc::setpriority(libc::PRIO_PROCESS as _, 0, limits.nice);
+            }
+            if let Some(memory_mb) = limits.memory_mb {
+                let bytes = (memory_mb * 1024 * 1024) as libc::rlim_t;
+                let limit = libc::rlimit { rlim_cur: bytes, rlim_max: bytes };
+                libc::setrlimit(libc::RLIMIT_AS, &limit);
+            }
+            #[cfg(target_os = "linux")]
+            set_io_priority(limits.io_priority);
+            Ok(())
+        });
+    }
+}
+
+#[cfg(not(unix))]
+fn apply(_command: &mut Command, _limits: ResourceLimits) {}
+
+#[cfg(target_os = "linux")]
+fn set_io_priority(priority: IoPriority) {
+    const IOPRIO_WHO_PROCESS: libc::c_int = 1;
+    const IOPRIO_CLASS_SHIFT: libc::c_int = 13;
+    let value = match priority {
+        IoPriority::Normal => return,
+        // Best-effort class, lowest level
+        IoPriority::Low => (2 << IOPRIO_CLASS_SHIFT) | 7,
+        IoPriority::Idle => 3 << IOPRIO_CLASS_SHIFT,
+    };
+    unsafe {
+        libc::syscall(libc::SYS_ioprio_set, IOPRIO_WHO_PROCESS, 0, value);
+    }
+}
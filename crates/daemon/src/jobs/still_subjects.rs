@@ -0,0 +1,46 @@
+use anyhow::Result;
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::db::Database;
+use crate::jobs::JobManager;
+use engine::ken_burns::StillSubject;
+
+use crate::llm::ml_service_url;
+
+#[derive(Debug, Deserialize)]
+struct SubjectsResponse {
+    subjects: Vec<StillSubject>,
+}
+
+/// Process DetectStillSubjects job - finds the faces (or the most detailed area) of a photo for
+/// its automatic Ken Burns motion to move towards
+pub async fn process_detect_still_subjects(
+    db: Arc<Database>,
+    job_manager: Arc<JobManager>,
+    job_id: i64,
+    asset_id: i64,
+) -> Result<()> {
+    let asset = db
+        .get_media_asset(asset_id)?
+        .ok_or_else(|| anyhow::anyhow!("Media asset {} not found", asset_id))?;
+    let image_path = std::fs::canonicalize(&asset.path)?;
+    job_manager.update_job_status(job_id, crate::jobs::JobStatus::Running, Some(0.1))?;
+
+    let response = reqwest::Client::new()
+        .post(format!("{}/stills/subjects", ml_service_url()))
+        .json(&serde_json::json!({
+            "imagePath": image_path.to_string_lossy(),
+        }))
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Err(anyhow::anyhow!("ML service still subject detection failed: {}", response.status()));
+    }
+    let detected: SubjectsResponse = response.json().await?;
+    db.set_still_subjects(asset_id, &detected.subjects)?;
+
+    eprintln!("[STILLS] Asset {}: {} subjects", asset_id, detected.subjects.len());
+    job_manager.update_job_status(job_id, crate::jobs::JobStatus::Completed, Some(1.0))?;
+    Ok(())
+}
@@ -0,0 +1,120 @@
+use anyhow::Result;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::db::Database;
+use crate::jobs::{JobManager, JobType};
+use crate::media::availability::{self, Availability};
+use crate::media::av_sync::{self, SyncDrift, SyncStatus};
+use crate::media::proxy;
+use engine::timeline::TICKS_PER_SECOND;
+
+/// Process DetectSyncDrift job - measures A/V sync on the project's online videos (or just
+/// `asset_ids`) and records it on each asset. With `correct`, drifting assets get sync
+/// correction turned on and their proxies re-rendered with resampled audio.
+pub async fn process_detect_sync_drift(
+    db: Arc<Database>,
+    job_manager: Arc<JobManager>,
+    job_id: i64,
+    project_id: i64,
+    asset_ids: Option<&[i64]>,
+    correct: bool,
+) -> Result<()> {
+    availability::refresh_project(&db, project_id)?;
+    let mut assets = Vec::new();
+    for asset in db.get_media_assets_for_project(project_id)? {
+        if asset_ids.is_some_and(|ids| !ids.contains(&asset.id))
+            || db.is_still_asset(asset.id)?
+            || availability::asset_availability(&db, asset.id)? != Some(Availability::Online)
+        {
+            continue;
+        }
+        assets.push(asset);
+    }
+
+    let mut measured = Vec::new();
+    let mut skipped = 0;
+    let mut failed = 0;
+    for (i, asset) in assets.iter().enumerate() {
+        let duration = asset.duration_ticks as f64 / TICKS_PER_SECOND as f64;
+        if duration < av_sync::MIN_DURATION_SECONDS {
+            skipped += 1;
+        } else {
+            match av_sync::measure(Path::new(&asset.path), duration).await {
+                Ok(drift) => {
+                    db.set_media_asset_sync_drift(asset.id, Some(&serde_json::to_string(&drift)?))?;
+                    if correct && drift.status == SyncStatus::Drifting {
+                        db.set_media_asset_sync_correction(asset.id, true)?;
+                        queue_proxy_refresh(&db, &job_manager, asset.id)?;
+                    }
+                    measured.push((asset.id, drift));
+                }
+                Err(e) => {
+                    // No audio stream, or a file ffmpeg can't decode; the rest still get measured
+                    eprintln!("[SYNC] Failed to measure asset {}: {:?}", asset.id, e);
+                    failed += 1;
+                }
+            }
+        }
+        job_manager.report_progress(job_id, (i + 1) as f64 / assets.len() as f64)?;
+    }
+
+    let drifting: Vec<&(i64, SyncDrift)> = measured
+        .iter()
+        .filter(|(_, drift)| drift.status == SyncStatus::Drifting)
+        .collect();
+    job_manager.set_job_result(job_id, serde_json::json!({
+        "measured": measured.len(),
+        "skipped": skipped,
+        "failed": failed,
+        "corrected": correct,
+        "drifting": drifting
+            .iter()
+            .map(|(asset_id, drift)| serde_json::json!({
+                "asset_id": asset_id,
+                "drift_ms": drift.drift_ms,
+                "drift_ppm": drift.drift_ppm,
+            }))
+            .collect::<Vec<_>>(),
+    }))?;
+
+    eprintln!(
+        "[SYNC] Project {}: {} videos measured, {} drifting, {} too short, {} failed",
+        project_id, measured.len(), drifting.len(), skipped, failed
+    );
+    job_manager.update_job_status(job_id, crate::jobs::JobStatus::Completed, Some(1.0))?;
+    Ok(())
+}
+
+/// Queue a sync measurement of one project's videos; a run already queued for the whole
+/// project is reused
+pub fn queue_detection(
+    job_manager: &JobManager,
+    project_id: i64,
+    asset_ids: Option<Vec<i64>>,
+    correct: bool,
+) -> Result<i64> {
+    let dedupe_key = (asset_ids.is_none() && !correct).then(|| format!("DetectSyncDrift:{}", project_id));
+    let payload = serde_json::json!({
+        "project_id": project_id,
+        "asset_ids": asset_ids,
+        "correct": correct,
+    });
+    job_manager.create_job(JobType::DetectSyncDrift, Some(payload), dedupe_key)
+}
+
+/// Re-render every proxy tier the asset has (and the eager tiers) so they pick up a change to
+/// its sync correction. The old proxies keep playing until the new ones replace them.
+pub fn queue_proxy_refresh(db: &Database, job_manager: &JobManager, asset_id: i64) -> Result<i64> {
+    let mut tiers = proxy::eager_tier_names();
+    for (tier, _, _, _) in db.list_proxies(asset_id)? {
+        if !tiers.contains(&tier) {
+            tiers.push(tier);
+        }
+    }
+    let payload = serde_json::json!({
+        "media_asset_id": asset_id,
+        "tiers": tiers,
+    });
+    job_manager.create_job(JobType::GenerateProxy, Some(payload), None)
+}
@@ -0,0 +1,58 @@
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::crypto;
+use crate::db::Database;
+use crate::jobs::JobManager;
+use crate::media::ffmpeg::FFmpegWrapper;
+use crate::media::thumbnails::{count_files, level_file_pattern, level_prefix, level_source, LevelSource};
+use crate::storage;
+
+/// Process GenerateThumbnailLevel job - extracts one generated density of an asset's thumbnail
+/// pyramid into its thumbnail directory and records it in the manifest
+pub async fn process_generate_thumbnail_level(
+    db: Arc<Database>,
+    job_manager: Arc<JobManager>,
+    job_id: i64,
+    asset_id: i64,
+    interval_ms: i64,
+) -> Result<()> {
+    if level_source(interval_ms) != LevelSource::Generated {
+        anyhow::bail!("{}ms thumbnails come from the base strip and aren't generated", interval_ms);
+    }
+    let asset_path = db.get_media_asset_path(asset_id)?
+        .ok_or_else(|| anyhow::anyhow!("Media asset {} not found", asset_id))?;
+
+    // Fails while the project is locked, before any plaintext thumbnail is written
+    let key = db.asset_key(asset_id)?;
+
+    let thumbnail_dir = match db.get_thumbnail_dir(asset_id)? {
+        Some(dir) => PathBuf::from(dir),
+        None => {
+            let dir = PathBuf::from(".cache").join("thumbs").join(format!("asset_{}", asset_id));
+            db.set_thumbnail_dir(asset_id, &dir.to_string_lossy())?;
+            dir
+        }
+    };
+
+    FFmpegWrapper::extract_thumbnails_at_interval(
+        Path::new(&asset_path),
+        &thumbnail_dir,
+        interval_ms,
+        &level_file_pattern(interval_ms),
+    ).await?;
+    let count = count_files(&thumbnail_dir, &level_prefix(interval_ms));
+    db.set_thumbnail_level(asset_id, interval_ms, count)?;
+    eprintln!("[THUMBS] Generated {} thumbnails every {}ms for asset {}", count, interval_ms, asset_id);
+
+    if let Some(key) = key {
+        crypto::encrypt_asset_files(&db, asset_id, &key)?;
+    }
+    if let Err(e) = storage::store_thumbnails(&db, asset_id, &thumbnail_dir).await {
+        eprintln!("[STORAGE] Failed to store thumbnails for asset {}: {:?}", asset_id, e);
+    }
+
+    job_manager.update_job_status(job_id, crate::jobs::JobStatus::Completed, Some(1.0))?;
+    Ok(())
+}
@@ -1,39 +1,192 @@
 use anyhow::Result;
 use reqwest;
 use serde_json;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use crate::db::Database;
 use crate::jobs::JobManager;
+use crate::media::ffmpeg::FFmpegWrapper;
 
-const ML_SERVICE_URL: &str = "http://127.0.0.1:8001";
+use engine::timeline::TICKS_PER_SECOND;
 
-/// Process TranscribeAsset job - calls ML service and stores raw transcript
+use crate::llm::ml_service_url;
+
+/// Assets longer than this are transcribed in windows to avoid timeouts and memory blowups
+const CHUNK_DURATION_SECONDS: f64 = 10.0 * 60.0;
+/// Overlap between consecutive windows so words at a boundary are heard whole by one chunk
+const CHUNK_OVERLAP_SECONDS: f64 = 5.0;
+
+/// Process TranscribeAsset job - calls ML service and stores raw transcript. With `windows`
+/// (start, end seconds), only those parts are transcribed and merged into the stored transcript.
 pub async fn process_transcribe_asset(
     db: Arc<Database>,
     job_manager: Arc<JobManager>,
     job_id: i64,
     asset_id: i64,
     media_path: &str,
+    windows: Option<&[(f64, f64)]>,
 ) -> Result<()> {
-    // Call ML service /transcribe endpoint
+    // Extract (or reuse cached) mono 16kHz audio so the ML service doesn't decode the whole video
+    let audio_path = match ensure_transcription_audio(asset_id, media_path).await {
+        Ok(path) => path.to_string_lossy().to_string(),
+        Err(e) => {
+            eprintln!("[TRANSCRIBE] Audio extraction failed for asset {}, using original media: {:?}", asset_id, e);
+            media_path.to_string()
+        }
+    };
+    job_manager.update_job_status(job_id, crate::jobs::JobStatus::Running, Some(0.1))?;
+
+    let transcript_response = transcribe_audio(&db, &job_manager, job_id, asset_id, &audio_path, windows).await;
+    release_transcription_audio(&db, asset_id).await;
+    store_transcript(&db, &job_manager, asset_id, &transcript_response?)?;
+    job_manager.update_job_status(job_id, crate::jobs::JobStatus::Completed, Some(1.0))?;
+
+    Ok(())
+}
+
+/// Transcribe the extracted audio: only `windows` merged into the stored transcript when given,
+/// otherwise all of it, in chunks when it's long
+async fn transcribe_audio(
+    db: &Database,
+    job_manager: &JobManager,
+    job_id: i64,
+    asset_id: i64,
+    audio_path: &str,
+    windows: Option<&[(f64, f64)]>,
+) -> Result<serde_json::Value> {
     let client = reqwest::Client::new();
-    let response = client
-        .post(&format!("{}/transcribe", ML_SERVICE_URL))
-        .json(&serde_json::json!({
-            "mediaPath": media_path
-        }))
-        .send()
-        .await?;
-    
-    if !response.status().is_success() {
-        return Err(anyhow::anyhow!("ML service transcribe failed: {}", response.status()));
+    if let Some(windows) = windows {
+        return transcribe_windows(db, job_manager, job_id, asset_id, &client, audio_path, windows).await;
     }
-    
-    let transcript_response: serde_json::Value = response.json().await?;
-    
+
+    // Probe duration to decide whether to chunk (unknown duration = single call)
+    let duration_seconds = FFmpegWrapper::probe(Path::new(audio_path))
+        .await
+        .map(|info| info.duration_ticks as f64 / TICKS_PER_SECOND as f64)
+        .unwrap_or(0.0);
+    let windows = plan_chunks(duration_seconds, CHUNK_DURATION_SECONDS, CHUNK_OVERLAP_SECONDS);
+
+    if windows.len() <= 1 {
+        return request_transcription(&client, audio_path).await;
+    }
+    eprintln!(
+        "[TRANSCRIBE] Asset {} is {:.0}s, transcribing in {} chunks",
+        asset_id, duration_seconds, windows.len()
+    );
+
+    let mut chunk_segments: Vec<Vec<serde_json::Value>> = Vec::with_capacity(windows.len());
+    for (idx, (start, end)) in windows.iter().enumerate() {
+        chunk_segments.push(transcribe_chunk(&client, asset_id, idx, audio_path, *start, *end).await?);
+
+        let progress = 0.1 + 0.8 * (idx + 1) as f64 / windows.len() as f64;
+        job_manager.update_job_status(job_id, crate::jobs::JobStatus::Running, Some(progress))?;
+    }
+
+    let segments = stitch_chunks(&windows, chunk_segments);
+    Ok(serde_json::json!({
+        "language": dominant_language(&segments),
+        "segments": segments,
+        "chunks": windows.len(),
+    }))
+}
+
+/// Transcribe a window of the asset's audio; segments come back in asset time
+async fn transcribe_chunk(
+    client: &reqwest::Client,
+    asset_id: i64,
+    idx: usize,
+    audio_path: &str,
+    start: f64,
+    end: f64,
+) -> Result<Vec<serde_json::Value>> {
+    let chunk_path = PathBuf::from(".cache")
+        .join("audio")
+        .join(format!("asset_{}_chunk_{}.wav", asset_id, idx));
+    FFmpegWrapper::extract_audio_chunk(Path::new(audio_path), &chunk_path, start, end - start).await?;
+    let chunk_abs = tokio::fs::canonicalize(&chunk_path).await?;
+
+    let result = request_transcription(client, &chunk_abs.to_string_lossy()).await;
+    let _ = tokio::fs::remove_file(&chunk_path).await;
+    let response = result?;
+
+    // Whisper detects language per call, so each chunk's segments carry their own. Speakers are
+    // diarized per call too, so their labels are qualified by where the chunk starts: the same
+    // label in two chunks isn't necessarily the same person.
+    let chunk_language = response.get("language").cloned().unwrap_or(serde_json::Value::Null);
+    Ok(response
+        .get("segments")
+        .and_then(|s| s.as_array())
+        .cloned()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|mut seg| {
+            if seg.get("language").is_none_or(|l| l.is_null()) {
+                seg["language"] = chunk_language.clone();
+            }
+            if let Some(speaker) = seg.get("speaker").and_then(|s| s.as_str()).map(String::from) {
+                seg["speaker"] = serde_json::json!(format!("{}@{:.0}s", speaker, start));
+            }
+            offset_segment(seg, start)
+        })
+        .collect())
+}
+
+/// Re-transcribe only `windows` of an asset: the stored transcript's segments centered in a
+/// window are replaced by what the window transcribes to, the rest are kept
+async fn transcribe_windows(
+    db: &Database,
+    job_manager: &JobManager,
+    job_id: i64,
+    asset_id: i64,
+    client: &reqwest::Client,
+    audio_path: &str,
+    windows: &[(f64, f64)],
+) -> Result<serde_json::Value> {
+    let in_windows = |seg: &serde_json::Value| {
+        let time = |key: &str| seg.get(key).and_then(|v| v.as_f64()).unwrap_or(0.0);
+        let mid = (time("start") + time("end")) / 2.0;
+        windows.iter().any(|(start, end)| mid >= *start && mid < *end)
+    };
+
+    let mut segments: Vec<serde_json::Value> = db
+        .get_asset_transcript(asset_id)?
+        .and_then(|json| serde_json::from_str::<serde_json::Value>(&json).ok())
+        .and_then(|t| t.get("segments").and_then(|s| s.as_array()).cloned())
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|seg| !in_windows(seg))
+        .collect();
+
+    for (idx, (start, end)) in windows.iter().enumerate() {
+        let transcribed = transcribe_chunk(client, asset_id, idx, audio_path, *start, *end).await?;
+        segments.extend(transcribed.into_iter().filter(|seg| in_windows(seg)));
+
+        let progress = 0.1 + 0.8 * (idx + 1) as f64 / windows.len() as f64;
+        job_manager.update_job_status(job_id, crate::jobs::JobStatus::Running, Some(progress))?;
+    }
+
+    segments.sort_by(|a, b| {
+        let start = |seg: &serde_json::Value| seg.get("start").and_then(|v| v.as_f64()).unwrap_or(0.0);
+        start(a).partial_cmp(&start(b)).unwrap_or(std::cmp::Ordering::Equal)
+    });
+    eprintln!("[TRANSCRIBE] Asset {}: re-transcribed {} window(s)", asset_id, windows.len());
+    Ok(serde_json::json!({
+        "language": dominant_language(&segments),
+        "segments": segments,
+    }))
+}
+
+/// Store an asset's raw transcript (as the ML service returns it) and queue enrichment from it.
+/// Shared with remote workers, which send back the same shape.
+pub fn store_transcript(
+    db: &Database,
+    job_manager: &JobManager,
+    asset_id: i64,
+    transcript_response: &serde_json::Value,
+) -> Result<()> {
     // Store raw transcript results in asset_transcripts table
-    let transcript_json = serde_json::to_string(&transcript_response)?;
+    let transcript_json = serde_json::to_string(transcript_response)?;
     db.store_asset_transcript(asset_id, &transcript_json)?;
     
     // Update asset analysis state
@@ -48,9 +201,170 @@ pub async fn process_transcribe_asset(
         Some(enrich_payload),
         None,
     )?;
-    
-    job_manager.update_job_status(job_id, crate::jobs::JobStatus::Completed, Some(1.0))?;
-    
     Ok(())
 }
 
+/// Call ML service /transcribe endpoint for a single file
+pub(crate) async fn request_transcription(client: &reqwest::Client, media_path: &str) -> Result<serde_json::Value> {
+    let response = client
+        .post(format!("{}/transcribe", ml_service_url()))
+        .json(&serde_json::json!({
+            "mediaPath": media_path
+        }))
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        return Err(anyhow::anyhow!("ML service transcribe failed: {}", response.status()));
+    }
+
+    Ok(response.json().await?)
+}
+
+/// Split a duration into overlapping (start, end) windows in seconds
+/// Returns a single window when the duration fits in one chunk (or is unknown)
+fn plan_chunks(duration_seconds: f64, chunk_seconds: f64, overlap_seconds: f64) -> Vec<(f64, f64)> {
+    if duration_seconds <= chunk_seconds {
+        return vec![(0.0, duration_seconds)];
+    }
+
+    let step = chunk_seconds - overlap_seconds;
+    let mut windows = Vec::new();
+    let mut start = 0.0;
+    loop {
+        let end = (start + chunk_seconds).min(duration_seconds);
+        windows.push((start, end));
+        if end >= duration_seconds {
+            break;
+        }
+        start += step;
+    }
+    windows
+}
+
+/// Shift a chunk-relative transcript segment (and its words) to asset time
+pub(crate) fn offset_segment(mut segment: serde_json::Value, offset_seconds: f64) -> serde_json::Value {
+    for key in ["start", "end"] {
+        if let Some(t) = segment.get(key).and_then(|v| v.as_f64()) {
+            segment[key] = serde_json::json!(t + offset_seconds);
+        }
+    }
+    if let Some(words) = segment.get_mut("words").and_then(|w| w.as_array_mut()) {
+        for word in words {
+            for key in ["start", "end"] {
+                if let Some(t) = word.get(key).and_then(|v| v.as_f64()) {
+                    word[key] = serde_json::json!(t + offset_seconds);
+                }
+            }
+        }
+    }
+    segment
+}
+
+/// Merge per-chunk segments into one transcript
+/// Each overlap is cut at its midpoint: a segment belongs to the chunk whose territory
+/// contains its midpoint. Remaining duplicates (same text, overlapping time) are dropped.
+fn stitch_chunks(windows: &[(f64, f64)], chunks: Vec<Vec<serde_json::Value>>) -> Vec<serde_json::Value> {
+    let seg_time = |seg: &serde_json::Value, key: &str| seg.get(key).and_then(|v| v.as_f64()).unwrap_or(0.0);
+
+    let mut stitched: Vec<serde_json::Value> = Vec::new();
+    for (idx, segments) in chunks.into_iter().enumerate() {
+        let lower = if idx == 0 {
+            f64::NEG_INFINITY
+        } else {
+            (windows[idx - 1].1 + windows[idx].0) / 2.0
+        };
+        let upper = if idx + 1 < windows.len() {
+            (windows[idx].1 + windows[idx + 1].0) / 2.0
+        } else {
+            f64::INFINITY
+        };
+
+        for seg in segments {
+            let mid = (seg_time(&seg, "start") + seg_time(&seg, "end")) / 2.0;
+            if mid >= lower && mid < upper {
+                stitched.push(seg);
+            }
+        }
+    }
+
+    stitched.sort_by(|a, b| {
+        seg_time(a, "start")
+            .partial_cmp(&seg_time(b, "start"))
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    let normalize = |seg: &serde_json::Value| {
+        seg.get("text")
+            .and_then(|t| t.as_str())
+            .unwrap_or("")
+            .trim()
+            .to_lowercase()
+    };
+
+    let mut deduped: Vec<serde_json::Value> = Vec::with_capacity(stitched.len());
+    for seg in stitched {
+        if let Some(prev) = deduped.last() {
+            let overlaps = seg_time(&seg, "start") < seg_time(prev, "end");
+            if overlaps && normalize(&seg) == normalize(prev) {
+                continue;
+            }
+        }
+        deduped.push(seg);
+    }
+    deduped
+}
+
+/// Language covering the most transcript text, weighted by characters
+pub fn dominant_language(segments: &[serde_json::Value]) -> Option<String> {
+    let mut weights: std::collections::HashMap<&str, usize> = std::collections::HashMap::new();
+    for seg in segments {
+        if let Some(language) = seg.get("language").and_then(|l| l.as_str()) {
+            let chars = seg.get("text").and_then(|t| t.as_str()).map(|t| t.trim().len()).unwrap_or(0);
+            *weights.entry(language).or_insert(0) += chars.max(1);
+        }
+    }
+    weights
+        .into_iter()
+        .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0)))
+        .map(|(language, _)| language.to_string())
+}
+
+/// Path of the cached transcription audio for an asset
+pub fn transcription_audio_path(asset_id: i64) -> PathBuf {
+    PathBuf::from(".cache")
+        .join("audio")
+        .join(format!("asset_{}_16k.wav", asset_id))
+}
+
+/// Extract mono 16kHz WAV for an asset, reusing the cached file if it's already there
+/// Returns an absolute path (the ML service rejects relative paths)
+pub async fn ensure_transcription_audio(asset_id: i64, media_path: &str) -> Result<PathBuf> {
+    let audio_path = transcription_audio_path(asset_id);
+
+    let cached = tokio::fs::metadata(&audio_path)
+        .await
+        .map(|m| m.len() > 0)
+        .unwrap_or(false);
+    if !cached {
+        FFmpegWrapper::extract_audio_for_transcription(Path::new(media_path), &audio_path).await?;
+    }
+
+    Ok(tokio::fs::canonicalize(&audio_path).await?)
+}
+
+/// Delete an asset's cached transcription audio when its project is encrypted. The cache is a
+/// plain WAV, so it's only kept between jobs for projects that aren't.
+pub async fn release_transcription_audio(db: &Database, asset_id: i64) {
+    let encrypted = db.get_asset_project_id(asset_id).and_then(|project_id| match project_id {
+        Some(project_id) => db.get_project_encryption(project_id).map(|e| e.is_some()),
+        None => Ok(false),
+    });
+    if let Err(e) = &encrypted {
+        eprintln!("[TRANSCRIBE] Couldn't check encryption of asset {}, removing its audio: {:?}", asset_id, e);
+    }
+    // Not knowing counts as encrypted: the audio can always be extracted again
+    if encrypted.unwrap_or(true) {
+        let _ = tokio::fs::remove_file(transcription_audio_path(asset_id)).await;
+    }
+}
@@ -1,28 +1,148 @@
 use anyhow::Result;
 use reqwest;
+use serde::{Deserialize, Serialize};
 use serde_json;
 use std::sync::Arc;
 
 use crate::db::Database;
 use crate::jobs::JobManager;
 
-const ML_SERVICE_URL: &str = "http://127.0.0.1:8001";
+use crate::llm::ml_service_url;
 
-/// Process AnalyzeVisionAsset job - calls ML service and stores raw vision data
+/// Frame sampling and analyzer selection for vision analysis
+/// Resolved as defaults <- project config <- job payload "vision_config"
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VisionConfig {
+    /// Seconds between analyzed frames (larger = faster, coarser)
+    pub sample_interval_sec: f64,
+    /// Downscale frames to this width before analysis (None = native resolution)
+    pub max_width: Option<u32>,
+    /// Seconds between OCR passes (OCR is much slower than the other analyzers)
+    pub ocr_interval_sec: f64,
+    /// Seconds between object/action tagging passes
+    pub tag_interval_sec: f64,
+    /// Minimum confidence for a structured tag to count (flat tags, facets, tag filters)
+    pub tag_confidence_threshold: f64,
+    pub analyzers: VisionAnalyzers,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VisionAnalyzers {
+    pub blur: bool,
+    pub motion: bool,
+    pub faces: bool,
+    pub tags: bool,
+    /// Wide / medium / close-up classification (derived from face size, needs `faces`)
+    pub shot_type: bool,
+    /// On-screen text extraction (signs, slides, screen recordings)
+    pub ocr: bool,
+    /// Zero-shot object/action tags with confidences
+    pub objects: bool,
+}
+
+impl Default for VisionConfig {
+    fn default() -> Self {
+        VisionConfig {
+            sample_interval_sec: 0.5,
+            max_width: None,
+            ocr_interval_sec: 2.0,
+            tag_interval_sec: 2.0,
+            tag_confidence_threshold: 0.3,
+            analyzers: VisionAnalyzers::default(),
+        }
+    }
+}
+
+impl Default for VisionAnalyzers {
+    fn default() -> Self {
+        VisionAnalyzers {
+            blur: true,
+            motion: true,
+            faces: true,
+            tags: true,
+            shot_type: true,
+            ocr: true,
+            objects: true,
+        }
+    }
+}
+
+impl VisionConfig {
+    /// Resolve the effective config for an asset: project settings overlaid by the job payload
+    pub fn resolve(db: &Database, asset_id: i64, payload_override: Option<&serde_json::Value>) -> Result<Self> {
+        let project_id = db.get_asset_project_id(asset_id)?;
+        Self::resolve_merged(db, project_id, payload_override)
+    }
+
+    /// Resolve the effective config for a project (defaults overlaid by project settings)
+    pub fn resolve_for_project(db: &Database, project_id: i64) -> Result<Self> {
+        Self::resolve_merged(db, Some(project_id), None)
+    }
+
+    fn resolve_merged(db: &Database, project_id: Option<i64>, payload_override: Option<&serde_json::Value>) -> Result<Self> {
+        let mut merged = serde_json::to_value(VisionConfig::default())?;
+
+        if let Some(project_id) = project_id {
+            if let Some(project_json) = db.get_project_vision_config(project_id)? {
+                let project_config: serde_json::Value = serde_json::from_str(&project_json)?;
+                merge_json(&mut merged, &project_config);
+            }
+        }
+
+        if let Some(override_config) = payload_override {
+            merge_json(&mut merged, override_config);
+        }
+
+        let mut config: VisionConfig = serde_json::from_value(merged)?;
+        // Guard against zero/negative intervals, which would analyze every frame forever
+        if config.sample_interval_sec.is_nan() || config.sample_interval_sec <= 0.0 {
+            config.sample_interval_sec = VisionConfig::default().sample_interval_sec;
+        }
+        Ok(config)
+    }
+}
+
+/// Recursively overlay `overlay` onto `base` (objects merge, other values replace)
+fn merge_json(base: &mut serde_json::Value, overlay: &serde_json::Value) {
+    match (base, overlay) {
+        (serde_json::Value::Object(base_map), serde_json::Value::Object(overlay_map)) => {
+            for (key, value) in overlay_map {
+                merge_json(base_map.entry(key.clone()).or_insert(serde_json::Value::Null), value);
+            }
+        }
+        (base, overlay) => *base = overlay.clone(),
+    }
+}
+
+/// Process AnalyzeVisionAsset job - calls ML service and stores raw vision data. With `windows`
+/// (start, end seconds), only those parts are analyzed and merged into the stored analysis.
 pub async fn process_analyze_vision_asset(
     db: Arc<Database>,
     job_manager: Arc<JobManager>,
     job_id: i64,
     asset_id: i64,
     media_path: &str,
+    config: VisionConfig,
+    windows: Option<&[(f64, f64)]>,
 ) -> Result<()> {
-    // Call ML service /vision/analyze endpoint
-    let client = reqwest::Client::new();
+    let vision_response = match windows {
+        Some(windows) => analyze_windows(&db, &job_manager, job_id, asset_id, media_path, &config, windows).await?,
+        None => request_analysis(&reqwest::Client::new(), media_path, &config).await?,
+    };
+    
+    store_vision(&db, &job_manager, asset_id, &vision_response)?;
+    job_manager.update_job_status(job_id, crate::jobs::JobStatus::Completed, Some(1.0))?;
+    
+    Ok(())
+}
+
+/// Call ML service /vision/analyze endpoint for a single file
+async fn request_analysis(client: &reqwest::Client, media_path: &str, config: &VisionConfig) -> Result<serde_json::Value> {
     let response = client
-        .post(&format!("{}/vision/analyze", ML_SERVICE_URL))
-        .json(&serde_json::json!({
-            "mediaPath": media_path
-        }))
+        .post(format!("{}/vision/analyze", ml_service_url()))
+        .json(&analyze_request(media_path, config))
         .send()
         .await?;
     
@@ -30,10 +150,96 @@ pub async fn process_analyze_vision_asset(
         return Err(anyhow::anyhow!("ML service vision analyze failed: {}", response.status()));
     }
     
-    let vision_response: serde_json::Value = response.json().await?;
-    
+    Ok(response.json().await?)
+}
+
+/// Re-analyze only `windows` of an asset: each window is cut to a temporary file and analyzed,
+/// and its results replace the stored analysis whose midpoint falls in the window
+async fn analyze_windows(
+    db: &Database,
+    job_manager: &JobManager,
+    job_id: i64,
+    asset_id: i64,
+    media_path: &str,
+    config: &VisionConfig,
+    windows: &[(f64, f64)],
+) -> Result<serde_json::Value> {
+    let time = |entry: &serde_json::Value, key: &str| entry.get(key).and_then(|v| v.as_f64()).unwrap_or(0.0);
+    let in_windows = |entry: &serde_json::Value| {
+        let mid = (time(entry, "start") + time(entry, "end")) / 2.0;
+        windows.iter().any(|(start, end)| mid >= *start && mid < *end)
+    };
+
+    let mut vision: serde_json::Value = db
+        .get_asset_vision(asset_id)?
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .filter(|v: &serde_json::Value| v.is_object())
+        .unwrap_or_else(|| serde_json::json!({}));
+    let mut segments: Vec<serde_json::Value> = vision
+        .get("segments")
+        .and_then(|s| s.as_array())
+        .cloned()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|entry| !in_windows(entry))
+        .collect();
+
+    let client = reqwest::Client::new();
+    for (idx, (start, end)) in windows.iter().enumerate() {
+        let clip_path = std::path::PathBuf::from(".cache")
+            .join("vision")
+            .join(format!("asset_{}_window_{}.mp4", asset_id, idx));
+        crate::media::ffmpeg::FFmpegWrapper::extract_video_window(
+            std::path::Path::new(media_path),
+            &clip_path,
+            *start,
+            end - start,
+        ).await?;
+        let clip_abs = tokio::fs::canonicalize(&clip_path).await?;
+        let result = request_analysis(&client, &clip_abs.to_string_lossy(), config).await;
+        let _ = tokio::fs::remove_file(&clip_path).await;
+
+        for mut entry in result?.get("segments").and_then(|s| s.as_array()).cloned().unwrap_or_default() {
+            for key in ["start", "end"] {
+                if let Some(t) = entry.get(key).and_then(|v| v.as_f64()) {
+                    entry[key] = serde_json::json!(t + start);
+                }
+            }
+            if in_windows(&entry) {
+                segments.push(entry);
+            }
+        }
+        job_manager.report_progress(job_id, (idx + 1) as f64 / windows.len() as f64)?;
+    }
+
+    segments.sort_by(|a, b| time(a, "start").partial_cmp(&time(b, "start")).unwrap_or(std::cmp::Ordering::Equal));
+    eprintln!("[VISION] Asset {}: re-analyzed {} window(s)", asset_id, windows.len());
+    vision["segments"] = serde_json::json!(segments);
+    Ok(vision)
+}
+
+/// Body of the ML service's /vision/analyze request for a file
+pub fn analyze_request(media_path: &str, config: &VisionConfig) -> serde_json::Value {
+    serde_json::json!({
+        "mediaPath": media_path,
+        "sampleIntervalSec": config.sample_interval_sec,
+        "maxWidth": config.max_width,
+        "ocrIntervalSec": config.ocr_interval_sec,
+        "tagIntervalSec": config.tag_interval_sec,
+        "analyzers": config.analyzers,
+    })
+}
+
+/// Store an asset's raw vision analysis (as the ML service returns it) and queue enrichment
+/// from it. Shared with remote workers, which send back the same shape.
+pub fn store_vision(
+    db: &Database,
+    job_manager: &JobManager,
+    asset_id: i64,
+    vision_response: &serde_json::Value,
+) -> Result<()> {
     // Store raw vision results in asset_vision table
-    let vision_json = serde_json::to_string(&vision_response)?;
+    let vision_json = serde_json::to_string(vision_response)?;
     db.store_asset_vision(asset_id, &vision_json)?;
     
     // Update asset analysis state
@@ -48,9 +254,6 @@ pub async fn process_analyze_vision_asset(
         Some(enrich_payload),
         None,
     )?;
-    
-    job_manager.update_job_status(job_id, crate::jobs::JobStatus::Completed, Some(1.0))?;
-    
     Ok(())
 }
 
@@ -0,0 +1,340 @@
+//! Voiceovers: narration recorded (or synthesized) for a project is transcribed, cut into beats
+//! at the pauses between its sentences, and the timeline's b-roll is trimmed or extended so its
+//! cuts land on those beats. Narration can also be synthesized from a script (written by the
+//! LLM from a brief when there isn't one), a sentence at a time, so its timing is known without
+//! transcribing it.
+
+use anyhow::Result;
+use serde_json::json;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::db::{Database, Segment};
+use crate::llm;
+use crate::jobs::transcribe::request_transcription;
+use crate::jobs::{JobManager, JobType};
+use crate::media::ffmpeg::FFmpegWrapper;
+use crate::media::segment_kind::{self, SegmentKind};
+use crate::tts;
+use engine::narration::{align_to_narration, narration_beats, NarrationBeat};
+use engine::timeline::{NarrationEvent, Timeline, TICKS_PER_SECOND};
+
+/// Process AlignVoiceover job - transcribes the voiceover if it hasn't been yet, then (with
+/// `align`) cuts the project's timeline to its beats and lays it under the picture
+pub async fn process_align_voiceover(
+    db: Arc<Database>,
+    job_manager: Arc<JobManager>,
+    job_id: i64,
+    voiceover_id: i64,
+    align: bool,
+) -> Result<()> {
+    let voiceover = db
+        .get_voiceover(voiceover_id)?
+        .ok_or_else(|| anyhow::anyhow!("Voiceover {} not found", voiceover_id))?;
+    job_manager.update_job_status(job_id, crate::jobs::JobStatus::Running, Some(0.1))?;
+
+    if !voiceover.transcribed {
+        let duration_ticks = FFmpegWrapper::probe(Path::new(&voiceover.path)).await?.duration_ticks;
+        let client = reqwest::Client::new();
+        let transcript = request_transcription(&client, &voiceover.path).await?;
+        db.set_voiceover_transcript(voiceover_id, duration_ticks, &serde_json::to_string(&transcript)?)?;
+    }
+    job_manager.update_job_status(job_id, crate::jobs::JobStatus::Running, Some(0.7))?;
+
+    let duration_ticks = db
+        .get_voiceover(voiceover_id)?
+        .and_then(|v| v.duration_ticks)
+        .unwrap_or(0);
+    let beats = voiceover_beats(&db, voiceover_id)?.unwrap_or_default();
+    let timeline: Option<Timeline> = match db.get_timeline(voiceover.project_id)? {
+        Some(json) if align => Some(serde_json::from_str(&json)?),
+        _ => None,
+    };
+
+    let result = match timeline {
+        Some(timeline) => {
+            let b_roll = b_roll_clips(&db, voiceover.project_id, &timeline)?;
+            let narration = NarrationEvent {
+                start_ticks: 0,
+                end_ticks: duration_ticks,
+                track_path: voiceover.path.clone(),
+                voiceover_id: Some(voiceover_id),
+            };
+            let plan = align_to_narration(&timeline, &beats, &b_roll, narration);
+            db.store_timeline(voiceover.project_id, &serde_json::to_string(&plan.preview)?)?;
+            eprintln!(
+                "[VOICEOVER] Project {}: timeline cut to {} beat(s) of voiceover {} ({:.1}s -> {:.1}s)",
+                voiceover.project_id,
+                plan.beats.len(),
+                voiceover_id,
+                plan.before_ticks as f64 / TICKS_PER_SECOND as f64,
+                plan.after_ticks as f64 / TICKS_PER_SECOND as f64,
+            );
+            json!({
+                "voiceover_id": voiceover_id,
+                "aligned": true,
+                "narration_ticks": plan.narration_ticks,
+                "before_ticks": plan.before_ticks,
+                "after_ticks": plan.after_ticks,
+                "beats": plan.beats,
+                "message": plan.message,
+                "diff": plan.diff,
+            })
+        }
+        None => json!({
+            "voiceover_id": voiceover_id,
+            "aligned": false,
+            "narration_ticks": duration_ticks,
+            "beats": beats,
+        }),
+    };
+    job_manager.set_job_result(job_id, result)?;
+    job_manager.update_job_status(job_id, crate::jobs::JobStatus::Completed, Some(1.0))?;
+    Ok(())
+}
+
+/// Queue transcription and (with `align`) alignment of a voiceover; a run already queued for it
+/// is reused
+pub fn queue_alignment(job_manager: &JobManager, project_id: i64, voiceover_id: i64, align: bool) -> Result<i64> {
+    job_manager.create_job(
+        JobType::AlignVoiceover,
+        Some(json!({ "project_id": project_id, "voiceover_id": voiceover_id, "align": align })),
+        Some(format!("AlignVoiceover:{}", voiceover_id)),
+    )
+}
+
+/// Silence left between synthesized sentences
+const SENTENCE_PAUSE_SECONDS: f64 = 0.4;
+
+/// What to synthesize narration from
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+pub struct NarrationRequest {
+    /// Script to speak; written from `brief` when absent
+    pub script: Option<String>,
+    pub brief: Option<String>,
+    pub voice: Option<String>,
+    /// TTS provider; TTS_PROVIDER's when absent
+    pub provider: Option<String>,
+    /// Cut the timeline to the narration once it's attached (default true)
+    pub align: Option<bool>,
+}
+
+/// Process SynthesizeNarration job - writes a script from the brief if there isn't one, speaks it
+/// sentence by sentence, joins the sentences with short pauses into one narration file, attaches
+/// it as a "tts" voiceover whose transcript is the sentences' timing, and queues its alignment
+pub async fn process_synthesize_narration(
+    db: Arc<Database>,
+    job_manager: Arc<JobManager>,
+    job_id: i64,
+    project_id: i64,
+    request: NarrationRequest,
+) -> Result<()> {
+    let project = db
+        .get_project(project_id)?
+        .ok_or_else(|| anyhow::anyhow!("Project {} not found", project_id))?;
+    let provider = tts::provider(request.provider.as_deref())?;
+    job_manager.update_job_status(job_id, crate::jobs::JobStatus::Running, Some(0.05))?;
+
+    let script = match request.script.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+        Some(script) => script.to_string(),
+        None => {
+            let brief = request
+                .brief
+                .as_deref()
+                .ok_or_else(|| anyhow::anyhow!("Narration needs a script or a brief"))?;
+            let (descriptions, target_seconds) = timeline_descriptions(&db, project_id)?;
+            llm::write_narration_script(&llm::CallAudit::new(&db, project_id), brief, &descriptions, target_seconds)
+                .await?
+        }
+    };
+    let sentences = tts::split_sentences(&script);
+    if sentences.is_empty() {
+        return Err(anyhow::anyhow!("Narration script is empty"));
+    }
+    job_manager.update_job_status(job_id, crate::jobs::JobStatus::Running, Some(0.2))?;
+
+    // The ML service is handed these paths, so they have to be absolute
+    let dir = PathBuf::from(&project.cache_dir).join("voiceovers");
+    tokio::fs::create_dir_all(&dir).await?;
+    let dir = std::fs::canonicalize(&dir)?;
+    let parts_dir = dir.join(format!("tts_{}", job_id));
+    tokio::fs::create_dir_all(&parts_dir).await?;
+
+    let mut parts = Vec::new();
+    let mut segments = Vec::new();
+    let mut cursor = 0.0;
+    for (idx, sentence) in sentences.iter().enumerate() {
+        let part = parts_dir.join(format!("{:03}.{}", idx, provider.extension()));
+        provider.synthesize(sentence, request.voice.as_deref(), &part).await?;
+        let seconds = FFmpegWrapper::probe(&part).await?.duration_ticks as f64 / TICKS_PER_SECOND as f64;
+        segments.push(json!({ "start": cursor, "end": cursor + seconds, "text": sentence }));
+        cursor += seconds + SENTENCE_PAUSE_SECONDS;
+        parts.push(part);
+        let progress = 0.2 + 0.6 * (idx + 1) as f64 / sentences.len() as f64;
+        job_manager.update_job_status(job_id, crate::jobs::JobStatus::Running, Some(progress))?;
+    }
+
+    let output = dir.join(format!("narration_{}.wav", job_id));
+    FFmpegWrapper::render(&join_args(&parts, &output)).await?;
+    let _ = tokio::fs::remove_dir_all(&parts_dir).await;
+    let duration_ticks = FFmpegWrapper::probe(&output).await?.duration_ticks;
+
+    let output_path = output.to_string_lossy().to_string();
+    let transcript = json!({ "segments": segments, "provider": provider.name(), "script": script });
+    let voiceover_id = db.create_voiceover(project_id, &output_path, "tts")?;
+    db.set_voiceover_transcript(voiceover_id, duration_ticks, &serde_json::to_string(&transcript)?)?;
+    let align_job_id = queue_alignment(&job_manager, project_id, voiceover_id, request.align.unwrap_or(true))?;
+    eprintln!(
+        "[VOICEOVER] Project {}: synthesized {} sentence(s) with {} into voiceover {} ({:.1}s)",
+        project_id,
+        sentences.len(),
+        provider.name(),
+        voiceover_id,
+        duration_ticks as f64 / TICKS_PER_SECOND as f64,
+    );
+
+    job_manager.set_job_result(
+        job_id,
+        json!({
+            "voiceover_id": voiceover_id,
+            "path": output_path,
+            "provider": provider.name(),
+            "script": script,
+            "sentences": segments,
+            "duration_ticks": duration_ticks,
+            "align_job_id": align_job_id,
+        }),
+    )?;
+    job_manager.update_job_status(job_id, crate::jobs::JobStatus::Completed, Some(1.0))?;
+    Ok(())
+}
+
+/// Queue narration synthesis for a project
+pub fn queue_synthesis(job_manager: &JobManager, project_id: i64, request: &NarrationRequest) -> Result<i64> {
+    job_manager.create_job(
+        JobType::SynthesizeNarration,
+        Some(json!({
+            "project_id": project_id,
+            "script": request.script,
+            "brief": request.brief,
+            "voice": request.voice,
+            "provider": request.provider,
+            "align": request.align,
+        })),
+        None,
+    )
+}
+
+/// ffmpeg arguments that join sentence files, each followed by a pause but the last, into one
+/// mono 48kHz wav
+fn join_args(parts: &[PathBuf], output: &Path) -> Vec<String> {
+    let mut args = vec!["-y".to_string()];
+    for part in parts {
+        args.push("-i".to_string());
+        args.push(part.to_string_lossy().to_string());
+    }
+    let mut filters: Vec<String> = parts
+        .iter()
+        .enumerate()
+        .map(|(idx, _)| {
+            let pad = if idx + 1 < parts.len() {
+                format!(",apad=pad_dur={}", SENTENCE_PAUSE_SECONDS)
+            } else {
+                String::new()
+            };
+            format!("[{}:a]aresample=48000,aformat=channel_layouts=mono{}[s{}]", idx, pad, idx)
+        })
+        .collect();
+    let labels: String = (0..parts.len()).map(|idx| format!("[s{}]", idx)).collect();
+    filters.push(format!("{}concat=n={}:v=0:a=1[outa]", labels, parts.len()));
+    args.extend([
+        "-filter_complex".to_string(),
+        filters.join(";"),
+        "-map".to_string(),
+        "[outa]".to_string(),
+        "-c:a".to_string(),
+        "pcm_s16le".to_string(),
+        output.to_string_lossy().to_string(),
+    ]);
+    args
+}
+
+/// Summaries of the segments the timeline's primary clips were cut from, in order, and how long
+/// the primary track runs (None without a timeline)
+fn timeline_descriptions(db: &Database, project_id: i64) -> Result<(Vec<String>, Option<f64>)> {
+    let Some(json) = db.get_timeline(project_id)? else {
+        return Ok((Vec::new(), None));
+    };
+    let timeline: Timeline = serde_json::from_str(&json)?;
+    let segments = db.get_segments_for_project(project_id)?;
+    let primary: Vec<_> = timeline.tracks.iter().filter(|t| t.id == 1).flat_map(|t| t.clips.iter()).collect();
+    let descriptions = primary
+        .iter()
+        .filter_map(|clip| {
+            let id = clip.segment_id?;
+            segments.iter().find(|(segment, _)| segment.id == id)?.0.summary_text.clone()
+        })
+        .collect();
+    let ticks: i64 = primary.iter().map(|c| c.out_ticks - c.in_ticks).sum();
+    Ok((descriptions, (ticks > 0).then(|| ticks as f64 / TICKS_PER_SECOND as f64)))
+}
+
+/// Spoken stretches of a transcript as (start, end, text) in ticks
+fn spoken_stretches(transcript: &serde_json::Value) -> Vec<(i64, i64, String)> {
+    let ticks = |seconds: Option<f64>| (seconds.unwrap_or(0.0) * TICKS_PER_SECOND as f64) as i64;
+    transcript["segments"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|segment| {
+            let text = segment["text"].as_str()?.trim();
+            (!text.is_empty()).then(|| (ticks(segment["start"].as_f64()), ticks(segment["end"].as_f64()), text.to_string()))
+        })
+        .collect()
+}
+
+/// Primary clips cut from b-roll (anything that isn't a-roll), mapped to the end of their
+/// source. A clip's segment is the one it was placed from, or the one it overlaps most.
+fn b_roll_clips(db: &Database, project_id: i64, timeline: &Timeline) -> Result<HashMap<String, i64>> {
+    let segments = db.get_segments_for_project(project_id)?;
+    let mut b_roll = HashMap::new();
+    for clip in timeline.tracks.iter().filter(|t| t.id == 1).flat_map(|t| t.clips.iter()) {
+        let placed_from = clip
+            .segment_id
+            .and_then(|id| segments.iter().find(|(segment, _)| segment.id == id));
+        let overlapping = || {
+            segments
+                .iter()
+                .filter(|(segment, _)| segment.media_asset_id == clip.asset_id)
+                .map(|entry| (entry, clip.out_ticks.min(entry.0.end_ticks) - clip.in_ticks.max(entry.0.start_ticks)))
+                .filter(|(_, overlap)| *overlap > 0)
+                .max_by_key(|(_, overlap)| *overlap)
+                .map(|(entry, _)| entry)
+        };
+        let Some((segment, asset)) = placed_from.or_else(overlapping) else {
+            continue;
+        };
+        if !kind_of(segment).is_a_roll() {
+            b_roll.insert(clip.id.clone(), asset.duration_ticks);
+        }
+    }
+    Ok(b_roll)
+}
+
+fn kind_of(segment: &Segment) -> SegmentKind {
+    SegmentKind::of(segment).unwrap_or_else(|| segment_kind::classify(segment, false))
+}
+
+/// Beats of a voiceover that's been transcribed
+pub fn voiceover_beats(db: &Database, voiceover_id: i64) -> Result<Option<Vec<NarrationBeat>>> {
+    let Some(json) = db.get_voiceover_transcript(voiceover_id)? else {
+        return Ok(None);
+    };
+    let transcript: serde_json::Value = serde_json::from_str(&json)?;
+    let duration_ticks = db
+        .get_voiceover(voiceover_id)?
+        .and_then(|v| v.duration_ticks)
+        .unwrap_or(0);
+    Ok(Some(narration_beats(&spoken_stretches(&transcript), duration_ticks)))
+}
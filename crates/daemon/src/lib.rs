@@ -0,0 +1,36 @@
+//! VibeCut's analysis and edit pipeline as a library: the database, job system, retrieval,
+//! planner and orchestrator behind the daemon's HTTP API. Embed it through [`Vibecut`]
+//! instead of talking to a running daemon.
+
+pub mod api;
+pub mod autosave;
+pub mod brand;
+pub mod client;
+pub mod crypto;
+pub mod db;
+pub mod embeddings;
+pub mod end_cards;
+pub mod events;
+pub mod interview;
+pub mod jobs;
+pub mod library;
+pub mod llm;
+pub mod lower_thirds;
+pub mod media;
+pub mod music;
+pub mod orchestrator;
+pub mod planner;
+pub mod plugins;
+pub mod quotas;
+pub mod resolve;
+pub mod retrieval;
+pub mod simulation;
+pub mod storage;
+pub mod style;
+pub mod templates;
+pub mod tts;
+pub mod twelvelabs;
+pub mod uploads;
+pub mod webhooks;
+
+pub use client::Vibecut;
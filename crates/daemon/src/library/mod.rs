@@ -0,0 +1,65 @@
+//! Asset library: analyzed assets shared across projects by reference. A library asset keeps
+//! its segments, transcripts and embeddings in the project that imported it; projects linking
+//! it plan and search with them as with their own, so nothing is imported or analyzed twice.
+//! An asset can't be deleted while other projects use it.
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::db::{Database, LibraryAsset};
+use engine::timeline::Timeline;
+
+/// A project using a library asset
+#[derive(Debug, Clone, Serialize)]
+pub struct AssetUsage {
+    pub project_id: i64,
+    pub project_name: String,
+    /// The project imported the asset (the others link it)
+    pub owner: bool,
+    pub linked_at: Option<String>,
+    /// Clips cut from the asset on the project's current timeline
+    pub timeline_clips: usize,
+}
+
+/// Clips on a project's current timeline cut from an asset
+pub fn timeline_clips(db: &Database, project_id: i64, asset_id: i64) -> Result<usize> {
+    let timeline: Option<Timeline> = db
+        .get_timeline(project_id)?
+        .map(|json| serde_json::from_str(&json))
+        .transpose()?;
+    Ok(timeline.map_or(0, |timeline| {
+        timeline
+            .tracks
+            .iter()
+            .flat_map(|track| track.clips.iter())
+            .filter(|clip| clip.asset_id == asset_id)
+            .count()
+    }))
+}
+
+/// Every project using a library asset: its owner first, then the projects linking it
+pub fn usage(db: &Database, asset: &LibraryAsset) -> Result<Vec<AssetUsage>> {
+    let links = db.list_asset_links(asset.asset_id)?;
+    let projects = std::iter::once((asset.owner_project_id, None))
+        .chain(links.into_iter().map(|(project_id, linked_at)| (project_id, Some(linked_at))));
+
+    let mut usage = Vec::new();
+    for (project_id, linked_at) in projects {
+        let Some(project) = db.get_project(project_id)? else {
+            continue;
+        };
+        usage.push(AssetUsage {
+            project_id,
+            project_name: project.name,
+            owner: linked_at.is_none(),
+            linked_at,
+            timeline_clips: timeline_clips(db, project_id, asset.asset_id)?,
+        });
+    }
+    Ok(usage)
+}
+
+/// Whether an asset has been analyzed far enough to share: its segments are built
+pub fn is_analyzed(db: &Database, asset_id: i64) -> Result<bool> {
+    db.check_asset_prerequisites(asset_id, &["segments_built"])
+}
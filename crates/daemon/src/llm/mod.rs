@@ -1,15 +1,29 @@
 use anyhow::Result;
 use reqwest;
 use serde_json;
+use std::time::Instant;
+
+pub mod usage;
+
+pub use usage::CallAudit;
 
 const ML_SERVICE_URL: &str = "http://127.0.0.1:8001";
 
+/// Base URL of the ML service: the in-process fake in simulation mode, else ML_SERVICE_URL
+/// from the environment, else the local default
+pub fn ml_service_url() -> String {
+    if let Some(url) = crate::simulation::ml_service_url() {
+        return url.to_string();
+    }
+    std::env::var("ML_SERVICE_URL").unwrap_or_else(|_| ML_SERVICE_URL.to_string())
+}
+
 /// Embed text using the ML service /embeddings/text endpoint
 /// Returns a 384-dimensional vector (all-MiniLM-L6-v2)
 pub async fn embed_text(text: &str) -> Result<Vec<f32>> {
     let client = reqwest::Client::new();
     let response = client
-        .post(&format!("{}/embeddings/text", ML_SERVICE_URL))
+        .post(format!("{}/embeddings/text", ml_service_url()))
         .json(&serde_json::json!({
             "text": text
         }))
@@ -33,12 +47,70 @@ pub async fn embed_text(text: &str) -> Result<Vec<f32>> {
     }
 }
 
+/// Embed a text query into the vision space using the ML service /embeddings/vision_text
+/// endpoint (CLIP ViT-B/32's text encoder), comparable with vision embeddings
+/// Returns a 512-dimensional vector
+pub async fn embed_vision_text(text: &str) -> Result<Vec<f32>> {
+    let client = reqwest::Client::new();
+    let response = client
+        .post(format!("{}/embeddings/vision_text", ml_service_url()))
+        .json(&serde_json::json!({
+            "text": text
+        }))
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        return Err(anyhow::anyhow!("ML service returned error: {}", response.status()));
+    }
+    let embedding_response: serde_json::Value = response.json().await?;
+    let embedding_vec = embedding_response
+        .get("embedding")
+        .and_then(|e| e.as_array())
+        .ok_or_else(|| anyhow::anyhow!("Invalid response format from ML service"))?;
+    Ok(embedding_vec.iter().filter_map(|v| v.as_f64().map(|f| f as f32)).collect())
+}
+
+/// Score (query, text) pairs with the ML service cross-encoder (/rerank)
+/// Returns (id, relevance in [0, 1]) per document
+pub async fn rerank(query: &str, documents: &[(i64, String)]) -> Result<Vec<(i64, f32)>> {
+    let client = reqwest::Client::new();
+    let request_body = serde_json::json!({
+        "query": query,
+        "documents": documents
+            .iter()
+            .map(|(id, text)| serde_json::json!({ "id": id, "text": text }))
+            .collect::<Vec<_>>(),
+    });
+    let response = client
+        .post(format!("{}/rerank", ml_service_url()))
+        .json(&request_body)
+        .send()
+        .await?;
+    
+    if response.status().is_success() {
+        let rerank_response: serde_json::Value = response.json().await?;
+        let scores = rerank_response
+            .get("scores")
+            .and_then(|s| s.as_array())
+            .ok_or_else(|| anyhow::anyhow!("Invalid response format from ML service"))?;
+        Ok(scores
+            .iter()
+            .filter_map(|s| Some((s.get("id")?.as_i64()?, s.get("score")?.as_f64()? as f32)))
+            .collect())
+    } else {
+        Err(anyhow::anyhow!("ML service returned error: {}", response.status()))
+    }
+}
+
 /// Call the orchestrator reason endpoint (placeholder for now)
 pub async fn reason_narrative(
+    audit: &CallAudit<'_>,
     segments: &[serde_json::Value],
     style_profile: Option<&serde_json::Value>,
     timeline_context: Option<&serde_json::Value>,
 ) -> Result<serde_json::Value> {
+    audit.check_quota()?;
     let client = reqwest::Client::new();
     let mut request_body = serde_json::json!({
         "segments": segments,
@@ -51,26 +123,33 @@ pub async fn reason_narrative(
         request_body["timeline_context"] = context.clone();
     }
     
-    let response = client
-        .post(&format!("{}/orchestrator/reason", ML_SERVICE_URL))
-        .json(&request_body)
-        .send()
-        .await?;
-    
-    if response.status().is_success() {
-        Ok(response.json().await?)
-    } else {
-        Err(anyhow::anyhow!("ML service returned error: {}", response.status()))
-    }
+    let started = Instant::now();
+    let result = async {
+        let response = client
+            .post(format!("{}/orchestrator/reason", ml_service_url()))
+            .json(&request_body)
+            .send()
+            .await?;
+        
+        if response.status().is_success() {
+            Ok(response.json().await?)
+        } else {
+            Err(anyhow::anyhow!("ML service returned error: {}", response.status()))
+        }
+    }.await;
+    usage::record_call(audit, "reason", &format!("{} segments", segments.len()), started, &result);
+    result
 }
 
 /// Generate EditPlan from beats and constraints
 pub async fn generate_edit_plan(
+    audit: &CallAudit<'_>,
     narrative_structure: &str,
     beats: &serde_json::Value, // JSON array of beats
     constraints: &serde_json::Value,
     style_profile_id: Option<i64>,
 ) -> Result<serde_json::Value> {
+    audit.check_quota()?;
     let client = reqwest::Client::new();
     let mut request_body = serde_json::json!({
         "beats": beats,
@@ -82,26 +161,38 @@ pub async fn generate_edit_plan(
         request_body["style_profile_id"] = serde_json::json!(profile_id);
     }
     
-    let response = client
-        .post(&format!("{}/orchestrator/generate_plan", ML_SERVICE_URL))
-        .json(&request_body)
-        .send()
-        .await?;
-    
-    if response.status().is_success() {
-        Ok(response.json().await?)
-    } else {
-        Err(anyhow::anyhow!("ML service returned error: {}", response.status()))
-    }
+    let started = Instant::now();
+    let result = async {
+        let response = client
+            .post(format!("{}/orchestrator/generate_plan", ml_service_url()))
+            .json(&request_body)
+            .send()
+            .await?;
+        
+        if response.status().is_success() {
+            Ok(response.json().await?)
+        } else {
+            Err(anyhow::anyhow!("ML service returned error: {}", response.status()))
+        }
+    }.await;
+    let summary = format!(
+        "{} structure, {} beats",
+        narrative_structure,
+        beats.as_array().map(|b| b.len()).unwrap_or(0),
+    );
+    usage::record_call(audit, "generate_plan", &summary, started, &result);
+    result
 }
 
 /// Generate agent response using LLM
 pub async fn generate_agent_response(
+    audit: &CallAudit<'_>,
     conversation_history: &[serde_json::Value],
     project_state: &serde_json::Value,
     context: &serde_json::Value,
     event_type: &str,
 ) -> Result<serde_json::Value> {
+    audit.check_quota()?;
     let client = reqwest::Client::new();
     let request_body = serde_json::json!({
         "conversation_history": conversation_history,
@@ -110,27 +201,43 @@ pub async fn generate_agent_response(
         "event_type": event_type,
     });
     
-    let response = client
-        .post(&format!("{}/orchestrator/generate_response", ML_SERVICE_URL))
-        .json(&request_body)
-        .send()
-        .await?;
-    
-    let status = response.status();
-    if status.is_success() {
-        Ok(response.json().await?)
-    } else {
-        let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-        eprintln!("[ERROR] ML service returned error {}: {}", status, error_text);
-        Err(anyhow::anyhow!("ML service returned error {}: {}", status, error_text))
-    }
+    let started = Instant::now();
+    let result = async {
+        let response = client
+            .post(format!("{}/orchestrator/generate_response", ml_service_url()))
+            .json(&request_body)
+            .send()
+            .await?;
+        
+        let status = response.status();
+        if status.is_success() {
+            Ok(response.json().await?)
+        } else {
+            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+            eprintln!("[ERROR] ML service returned error {}: {}", status, error_text);
+            Err(anyhow::anyhow!("ML service returned error {}: {}", status, error_text))
+        }
+    }.await;
+    // Summarize as the event plus the latest user message it responds to
+    let last_user_message = conversation_history
+        .iter()
+        .rev()
+        .find(|msg| msg.get("role").and_then(|r| r.as_str()) == Some("user"))
+        .and_then(|msg| msg.get("content"))
+        .and_then(|c| c.as_str())
+        .unwrap_or("");
+    let summary = format!("{}: {}", event_type, last_user_message);
+    usage::record_call(audit, "generate_response", &summary, started, &result);
+    result
 }
 
 /// Parse user intent from natural language using LLM
 pub async fn parse_intent(
+    audit: &CallAudit<'_>,
     user_message: &str,
     conversation_history: Option<&[serde_json::Value]>,
 ) -> Result<serde_json::Value> {
+    audit.check_quota()?;
     let client = reqwest::Client::new();
     let mut request_body = serde_json::json!({
         "user_message": user_message,
@@ -140,15 +247,161 @@ pub async fn parse_intent(
         request_body["conversation_history"] = serde_json::json!(history);
     }
     
-    let response = client
-        .post(&format!("{}/orchestrator/parse_intent", ML_SERVICE_URL))
-        .json(&request_body)
-        .send()
-        .await?;
+    let started = Instant::now();
+    let result = async {
+        let response = client
+            .post(format!("{}/orchestrator/parse_intent", ml_service_url()))
+            .json(&request_body)
+            .send()
+            .await?;
+        
+        if response.status().is_success() {
+            Ok(response.json().await?)
+        } else {
+            Err(anyhow::anyhow!("ML service returned error: {}", response.status()))
+        }
+    }.await;
+    usage::record_call(audit, "parse_intent", user_message, started, &result);
+    result
+}
+
+/// Ask the LLM for fine-grained edit tool calls (trim, reorder, delete, set speed) that carry out
+/// `instruction` on the given primary-track clips. The calls are unvalidated.
+pub async fn plan_edit_tool_calls(
+    audit: &CallAudit<'_>,
+    instruction: &str,
+    clips: &[serde_json::Value],
+    conversation_history: &[serde_json::Value],
+) -> Result<serde_json::Value> {
+    audit.check_quota()?;
+    let client = reqwest::Client::new();
+    let request_body = serde_json::json!({
+        "instruction": instruction,
+        "clips": clips,
+        "conversation_history": conversation_history,
+    });
+
+    let started = Instant::now();
+    let result = async {
+        let response = client
+            .post(format!("{}/orchestrator/edit_tools", ml_service_url()))
+            .json(&request_body)
+            .send()
+            .await?;
+
+        if response.status().is_success() {
+            Ok(response.json().await?)
+        } else {
+            Err(anyhow::anyhow!("ML service returned error: {}", response.status()))
+        }
+    }.await;
+    usage::record_call(audit, "edit_tools", instruction, started, &result);
+    result
+}
+
+/// Expand a search intent into up to `max_queries` paraphrases / aspect queries (original excluded)
+pub async fn expand_query(
+    audit: &CallAudit<'_>,
+    text: &str,
+    max_queries: usize,
+) -> Result<Vec<String>> {
+    audit.check_quota()?;
+    let client = reqwest::Client::new();
+    let request_body = serde_json::json!({
+        "text": text,
+        "max_queries": max_queries,
+    });
     
-    if response.status().is_success() {
-        Ok(response.json().await?)
-    } else {
-        Err(anyhow::anyhow!("ML service returned error: {}", response.status()))
-    }
+    let started = Instant::now();
+    let result: Result<serde_json::Value> = async {
+        let response = client
+            .post(format!("{}/expand_query", ml_service_url()))
+            .json(&request_body)
+            .send()
+            .await?;
+        
+        if response.status().is_success() {
+            Ok(response.json().await?)
+        } else {
+            Err(anyhow::anyhow!("ML service returned error: {}", response.status()))
+        }
+    }.await;
+    usage::record_call(audit, "expand_query", text, started, &result);
+    
+    Ok(result?
+        .get("queries")
+        .and_then(|q| q.as_array())
+        .map(|queries| queries.iter().filter_map(|q| q.as_str()).map(String::from).collect())
+        .unwrap_or_default())
+}
+
+/// Detect a query's language and translate it into the given languages (ISO 639-1 codes)
+/// Returns { source_language, translations: { lang: text } } with the source language omitted
+pub async fn translate_query(
+    audit: &CallAudit<'_>,
+    text: &str,
+    target_languages: &[String],
+) -> Result<serde_json::Value> {
+    audit.check_quota()?;
+    let client = reqwest::Client::new();
+    let request_body = serde_json::json!({
+        "text": text,
+        "target_languages": target_languages,
+    });
+    
+    let started = Instant::now();
+    let result = async {
+        let response = client
+            .post(format!("{}/translate", ml_service_url()))
+            .json(&request_body)
+            .send()
+            .await?;
+        
+        if response.status().is_success() {
+            Ok(response.json().await?)
+        } else {
+            Err(anyhow::anyhow!("ML service returned error: {}", response.status()))
+        }
+    }.await;
+    usage::record_call(audit, "translate", text, started, &result);
+    result
+}
+
+/// Write a voiceover script from a brief and what the cut's clips show (in timeline order),
+/// sized to `target_seconds` when given
+pub async fn write_narration_script(
+    audit: &CallAudit<'_>,
+    brief: &str,
+    clip_descriptions: &[String],
+    target_seconds: Option<f64>,
+) -> Result<String> {
+    audit.check_quota()?;
+    let client = reqwest::Client::new();
+    let request_body = serde_json::json!({
+        "brief": brief,
+        "clip_descriptions": clip_descriptions,
+        "target_seconds": target_seconds,
+    });
+
+    let started = Instant::now();
+    let result: Result<serde_json::Value> = async {
+        let response = client
+            .post(format!("{}/narration/script", ml_service_url()))
+            .json(&request_body)
+            .send()
+            .await?;
+
+        if response.status().is_success() {
+            Ok(response.json().await?)
+        } else {
+            Err(anyhow::anyhow!("ML service returned error: {}", response.status()))
+        }
+    }.await;
+    usage::record_call(audit, "narration_script", brief, started, &result);
+
+    result?
+        .get("script")
+        .and_then(|s| s.as_str())
+        .map(String::from)
+        .ok_or_else(|| anyhow::anyhow!("Invalid response format from ML service"))
 }
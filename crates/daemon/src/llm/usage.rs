@@ -0,0 +1,81 @@
+use anyhow::Result;
+use std::time::Instant;
+
+use crate::db::{Database, LlmCallRecord};
+use crate::quotas::{self, Metric};
+
+/// USD per million (prompt, completion) tokens, matched by model-name prefix (first match wins,
+/// so more specific names come first)
+const MODEL_PRICES: &[(&str, f64, f64)] = &[
+    ("gpt-4o-mini", 0.15, 0.60),
+    ("gpt-4o", 2.50, 10.00),
+    ("gpt-4.1-mini", 0.40, 1.60),
+    ("gpt-4.1", 2.00, 8.00),
+];
+
+/// Prompt summaries are cut to this many characters
+const PROMPT_SUMMARY_CHARS: usize = 200;
+
+/// The project an LLM call is logged against
+pub struct CallAudit<'a> {
+    pub db: &'a Database,
+    pub project_id: i64,
+}
+
+impl<'a> CallAudit<'a> {
+    pub fn new(db: &'a Database, project_id: i64) -> Self {
+        CallAudit { db, project_id }
+    }
+
+    /// Fails with [`quotas::QuotaExceeded`] when the project has no LLM calls left this month
+    pub fn check_quota(&self) -> Result<()> {
+        quotas::check(self.db, self.project_id, Metric::LlmCalls, 1.0, None)
+    }
+}
+
+/// Estimated cost of a call in USD (0 for unknown models)
+pub fn estimate_cost_usd(model: &str, prompt_tokens: i64, completion_tokens: i64) -> f64 {
+    MODEL_PRICES
+        .iter()
+        .find(|(prefix, _, _)| model.starts_with(prefix))
+        .map(|(_, prompt_price, completion_price)| {
+            (prompt_tokens as f64 * prompt_price + completion_tokens as f64 * completion_price) / 1_000_000.0
+        })
+        .unwrap_or(0.0)
+}
+
+/// Log a finished call. Token counts come from the `usage` object the ML service attaches to
+/// responses that went through a hosted model; calls without one are logged at zero cost.
+/// Audit failures are logged and never fail the call itself.
+pub(crate) fn record_call(
+    audit: &CallAudit<'_>,
+    endpoint: &str,
+    prompt_summary: &str,
+    started: Instant,
+    result: &Result<serde_json::Value>,
+) {
+    let usage = result.as_ref().ok().and_then(|response| response.get("usage"));
+    let model = usage.and_then(|u| u.get("model")).and_then(|v| v.as_str()).map(String::from);
+    let prompt_tokens = usage.and_then(|u| u.get("prompt_tokens")).and_then(|v| v.as_i64());
+    let completion_tokens = usage.and_then(|u| u.get("completion_tokens")).and_then(|v| v.as_i64());
+    let estimated_cost_usd = model
+        .as_deref()
+        .map(|m| estimate_cost_usd(m, prompt_tokens.unwrap_or(0), completion_tokens.unwrap_or(0)))
+        .unwrap_or(0.0);
+
+    let record = LlmCallRecord {
+        project_id: audit.project_id,
+        endpoint: endpoint.to_string(),
+        prompt_summary: prompt_summary.chars().take(PROMPT_SUMMARY_CHARS).collect(),
+        model,
+        prompt_tokens,
+        completion_tokens,
+        latency_ms: started.elapsed().as_millis() as i64,
+        estimated_cost_usd,
+        status: if result.is_ok() { "ok" } else { "error" }.to_string(),
+        error: result.as_ref().err().map(|e| e.to_string()),
+    };
+    if let Err(e) = audit.db.store_llm_call(&record) {
+        eprintln!("[LLM] Failed to record {} call for project {}: {:?}", endpoint, audit.project_id, e);
+    }
+}
@@ -0,0 +1,147 @@
+//! Lower thirds naming each person where they first speak in the cut. They're suggested from
+//! the diarized speakers a person has been matched to, and only go on the timeline once accepted.
+
+use anyhow::Result;
+
+use crate::brand::Brand;
+use crate::db::{Database, LowerThird, Person};
+use engine::render::output_duration_seconds;
+use engine::timeline::{Timeline, TrackKind, TICKS_PER_SECOND};
+use engine::title::TitleEvent;
+
+/// Statuses a lower third can be given
+pub const STATUSES: &[&str] = &["suggested", "accepted", "dismissed"];
+/// How long after the person starts speaking the lower third comes up
+const DELAY_SECONDS: f64 = 0.5;
+/// How long it stays up
+const DURATION_SECONDS: f64 = 4.0;
+
+/// Where on the timeline a person is first heard: the earliest point of the primary storyline
+/// cut from a segment one of their speakers speaks
+pub fn first_appearance(db: &Database, project_id: i64, timeline: &Timeline, person: &Person) -> Result<Option<i64>> {
+    if person.speakers.is_empty() {
+        return Ok(None);
+    }
+    let segments = db.get_segments_for_project(project_id)?;
+    let spoken: Vec<_> = segments
+        .iter()
+        .map(|(segment, _)| segment)
+        .filter(|segment| {
+            person.speakers.iter().any(|s| {
+                s.asset_id == segment.media_asset_id && segment.speaker.as_deref() == Some(s.speaker.as_str())
+            })
+        })
+        .collect();
+
+    let primary = timeline.tracks.iter().filter(|t| t.kind == TrackKind::Video && t.id == 1);
+    let mut first: Option<i64> = None;
+    for clip in primary.flat_map(|t| t.clips.iter()) {
+        for segment in spoken.iter().filter(|s| s.media_asset_id == clip.asset_id) {
+            let src_in = Database::get_coalesced_src_in(segment).max(clip.in_ticks);
+            let src_out = Database::get_coalesced_src_out(segment).min(clip.out_ticks);
+            if src_in >= src_out {
+                continue;
+            }
+            let at = clip.timeline_start_ticks + ((src_in - clip.in_ticks) as f64 / clip.speed.max(0.01)) as i64;
+            first = Some(first.map_or(at, |earlier| earlier.min(at)));
+        }
+    }
+    Ok(first)
+}
+
+/// Bring the project's lower thirds up to date with its timeline and people: each named person
+/// heard in the cut gets a suggestion at their first appearance. Dismissed ones stay dismissed,
+/// and accepted ones are left alone while their title is still on the timeline.
+pub fn suggest(db: &Database, project_id: i64) -> Result<Vec<LowerThird>> {
+    let timeline: Option<Timeline> = db
+        .get_timeline(project_id)?
+        .map(|json| serde_json::from_str(&json))
+        .transpose()?;
+    let Some(timeline) = timeline else {
+        return db.list_lower_thirds(project_id);
+    };
+    let program_end = (output_duration_seconds(&timeline) * TICKS_PER_SECOND as f64) as i64;
+    let existing = db.list_lower_thirds(project_id)?;
+
+    for person in db.list_persons(project_id)? {
+        let current = existing.iter().find(|l| l.person_id == person.id);
+        let on_timeline = |l: &LowerThird| {
+            l.title_id.as_ref().is_some_and(|id| timeline.titles.iter().any(|t| &t.id == id))
+        };
+        match current {
+            Some(l) if l.status == "dismissed" => continue,
+            Some(l) if l.status == "accepted" && on_timeline(l) => continue,
+            _ => {}
+        }
+
+        let appearance = if person.name.trim().is_empty() {
+            None
+        } else {
+            first_appearance(db, project_id, &timeline, &person)?
+        };
+        match appearance {
+            Some(at) => {
+                let start = (at + (DELAY_SECONDS * TICKS_PER_SECOND as f64) as i64).min(program_end);
+                let end = (start + (DURATION_SECONDS * TICKS_PER_SECOND as f64) as i64).min(program_end);
+                if end > start {
+                    db.suggest_lower_third(project_id, person.id, start, end)?;
+                }
+            }
+            // Not in the cut (any more)
+            None => {
+                if let Some(l) = current {
+                    db.delete_lower_third(l.id)?;
+                }
+            }
+        }
+    }
+    db.list_lower_thirds(project_id)
+}
+
+/// Put a lower third on the timeline as a title naming its person, drawn in the brand kit's
+/// heading font and colors
+pub fn accept(db: &Database, project_id: i64, lower_third: &LowerThird) -> Result<TitleEvent> {
+    let person = db
+        .get_person(lower_third.person_id)?
+        .ok_or_else(|| anyhow::anyhow!("Person {} not found", lower_third.person_id))?;
+    let mut title = TitleEvent {
+        id: uuid::Uuid::new_v4().to_string(),
+        start_ticks: lower_third.start_ticks,
+        end_ticks: lower_third.end_ticks,
+        text: person.name,
+        subtitle: person.role.filter(|r| !r.trim().is_empty()),
+        person_id: Some(person.id),
+        style: Brand::for_project(db, project_id)?.title_style(),
+    };
+    if let Some(id) = &lower_third.title_id {
+        title.id = id.clone();
+    }
+
+    let mut timeline = load_timeline(db, project_id)?;
+    timeline.titles.retain(|t| t.id != title.id);
+    timeline.titles.push(title.clone());
+    timeline.titles.sort_by_key(|t| t.start_ticks);
+    db.store_timeline(project_id, &serde_json::to_string(&timeline)?)?;
+    db.set_lower_third_status(lower_third.id, "accepted", Some(&title.id))?;
+    Ok(title)
+}
+
+/// Take a lower third off the timeline (if it was accepted) and give it a new status
+pub fn withdraw(db: &Database, project_id: i64, lower_third: &LowerThird, status: &str) -> Result<()> {
+    if let Some(title_id) = &lower_third.title_id {
+        let mut timeline = load_timeline(db, project_id)?;
+        let before = timeline.titles.len();
+        timeline.titles.retain(|t| &t.id != title_id);
+        if timeline.titles.len() != before {
+            db.store_timeline(project_id, &serde_json::to_string(&timeline)?)?;
+        }
+    }
+    db.set_lower_third_status(lower_third.id, status, None)
+}
+
+fn load_timeline(db: &Database, project_id: i64) -> Result<Timeline> {
+    let json = db
+        .get_timeline(project_id)?
+        .ok_or_else(|| anyhow::anyhow!("Project {} has no timeline", project_id))?;
+    Ok(serde_json::from_str(&json)?)
+}
@@ -1,20 +1,10 @@
 use axum::{response::Json, routing::get, Router};
 use serde::Serialize;
-use std::{net::SocketAddr, path::PathBuf, sync::Arc};
+use std::{net::SocketAddr, path::PathBuf};
 use tracing::{info, level_filters::LevelFilter};
 use tracing_subscriber;
 use tower_http::cors::{CorsLayer, Any};
-
-mod api;
-mod db;
-mod embeddings;
-mod jobs;
-mod llm;
-mod media;
-mod planner;
-mod orchestrator;
-mod retrieval;
-mod twelvelabs;
+use vibecut::{api, Vibecut};
 
 #[derive(Serialize)]
 struct HealthResponse {
@@ -39,27 +29,19 @@ async fn main() -> anyhow::Result<()> {
     // Initialize database
     // For now, use a local SQLite file. In production, this should be configurable
     let db_path = PathBuf::from(".cache/vibecut.db");
-    if let Some(parent) = db_path.parent() {
-        std::fs::create_dir_all(parent)?;
-    }
-    let db = Arc::new(db::Database::new(&db_path)?);
+    let vibecut = Vibecut::open(&db_path)?;
     info!("Database initialized at {:?}", db_path);
 
-    // Initialize job manager
-    let job_manager = Arc::new(jobs::JobManager::new(db.clone()));
-
-    // Initialize and spawn job processor
-    let job_processor = jobs::processor::JobProcessor::new(db.clone(), job_manager.clone());
-    let _processor_handle = tokio::spawn(async move {
-        job_processor.run().await;
-    });
+    // Drop dynamic segments left behind by old searches that nothing references anymore
+    match vibecut.db().cleanup_dynamic_segments(None, api::segments::DYNAMIC_SEGMENT_MIN_AGE_SECS) {
+        Ok(removed) if removed > 0 => info!("Removed {} unreferenced dynamic segments", removed),
+        Ok(_) => {}
+        Err(e) => eprintln!("[STARTUP] Dynamic segment cleanup failed: {:?}", e),
+    }
 
-    // Initialize and spawn agent event loop
-    let agent_db = db.clone();
-    let agent_job_manager = job_manager.clone();
-    let _agent_handle = tokio::spawn(async move {
-        orchestrator::events::agent_event_loop(agent_db, agent_job_manager).await;
-    });
+    // Spawn the job processor, agent event loop, webhook delivery (both resuming from the events
+    // outbox), outbox pruning and checksum schedule
+    let _background_handles = vibecut.start();
 
     // Build the router with CORS support
     let cors = CorsLayer::new()
@@ -70,15 +52,47 @@ async fn main() -> anyhow::Result<()> {
     
     let app = Router::new()
         .route("/health", get(health))
-        .nest("/api", api::router(db.clone(), job_manager))
+        .nest("/api", vibecut.router())
         .layer(cors);
 
-    // Start the server
-    let addr = SocketAddr::from(([127, 0, 0, 1], 7777));
+    // Start the server (DAEMON_ADDR=0.0.0.0:7777 to serve thin clients from another machine)
+    let addr = match std::env::var("DAEMON_ADDR") {
+        Ok(addr) => addr
+            .parse::<SocketAddr>()
+            .map_err(|e| anyhow::anyhow!("Invalid DAEMON_ADDR {}: {}", addr, e))?,
+        Err(_) => SocketAddr::from(([127, 0, 0, 1], 7777)),
+    };
     info!("Starting daemon server on {}", addr);
 
     let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
+    axum::serve(listener, app).with_graceful_shutdown(shutdown_signal()).await?;
+
+    // Without this the next start offers crash recovery
+    vibecut.shutdown()?;
+    info!("Daemon stopped");
 
     Ok(())
+}
+
+/// Ctrl-C, or SIGTERM (how the desktop app stops the daemon)
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        let _ = tokio::signal::ctrl_c().await;
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(_) => std::future::pending::<()>().await,
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
 }
\ No newline at end of file
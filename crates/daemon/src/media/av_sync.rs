@@ -0,0 +1,232 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::media::ffmpeg::FFmpegWrapper;
+
+/// Resolution of the audio and motion envelopes (one video frame at 25 fps)
+const STEP_SECONDS: f64 = 0.04;
+/// Length of each stretch of the file that's measured
+const WINDOW_SECONDS: f64 = 45.0;
+/// Files shorter than this can't drift enough to matter and aren't measured
+pub const MIN_DURATION_SECONDS: f64 = 120.0;
+/// Largest audio/video offset searched for within a window
+const MAX_OFFSET_SECONDS: f64 = 1.0;
+/// Below this peak correlation a window has too few matching sound and motion events to trust
+const MIN_CONFIDENCE: f64 = 0.25;
+/// Drift across the whole file at which it's flagged (lip sync is noticeable from ~45ms)
+const DRIFT_THRESHOLD_SECONDS: f64 = 0.04;
+/// Rate the audio is resampled at when correcting, fine enough that rounding leaves <3ppm
+const CORRECTION_SAMPLE_RATE: f64 = 96000.0;
+const OUTPUT_SAMPLE_RATE: u32 = 48000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncStatus {
+    InSync,
+    /// Audio slides away from the picture over the file
+    Drifting,
+    /// Too few windows had sound lining up with motion to tell
+    Undetermined,
+}
+
+/// Offset measured in one window: how late the audio runs behind the picture around `time`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WindowOffset {
+    pub time: f64,
+    pub offset_ms: f64,
+    pub confidence: f64,
+}
+
+/// Result of measuring an asset's A/V sync
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncDrift {
+    pub status: SyncStatus,
+    /// Audio delay behind the picture at the start of the file (positive = audio late)
+    pub offset_ms: f64,
+    /// How much further the audio falls behind by the end of the file
+    pub drift_ms: f64,
+    /// Drift rate in parts per million of playback time
+    pub drift_ppm: f64,
+    pub windows: Vec<WindowOffset>,
+}
+
+impl SyncDrift {
+    /// What to tell the user about an asset in this state (None when it's fine)
+    pub fn warning(&self, corrected: bool) -> Option<String> {
+        (self.status == SyncStatus::Drifting && !corrected).then(|| {
+            format!(
+                "Audio drifts {:+.0}ms out of sync over the clip; enable sync correction to resample it in proxies",
+                self.drift_ms
+            )
+        })
+    }
+
+    /// Audio filter that resamples the audio to cancel the measured drift, keeping the start
+    /// where it is. None when there's nothing to correct.
+    pub fn correction_filter(&self) -> Option<String> {
+        if self.status != SyncStatus::Drifting {
+            return None;
+        }
+        // Audio that falls behind by r per second has to play (1 + r) times faster
+        let rate = (CORRECTION_SAMPLE_RATE * (1.0 + self.drift_ppm / 1_000_000.0)).round() as u32;
+        if rate == CORRECTION_SAMPLE_RATE as u32 {
+            return None;
+        }
+        Some(format!(
+            "aresample={},asetrate={},aresample={}",
+            CORRECTION_SAMPLE_RATE as u32, rate, OUTPUT_SAMPLE_RATE
+        ))
+    }
+}
+
+/// Measure A/V sync at the start, middle and end of a file by lining up audio onsets with
+/// visual motion, and fit the drift between them
+pub async fn measure(path: &Path, duration_seconds: f64) -> Result<SyncDrift> {
+    let length = WINDOW_SECONDS.min(duration_seconds);
+    let starts = [0.0, (duration_seconds - length) / 2.0, duration_seconds - length];
+
+    let mut windows = Vec::new();
+    for start in starts {
+        let levels = FFmpegWrapper::audio_levels_between(path, STEP_SECONDS, start, length).await?;
+        let motion = FFmpegWrapper::frame_differences_between(path, 1.0 / STEP_SECONDS, start, length).await?;
+        if let Some((offset, confidence)) = estimate_offset(&levels, &motion, start, length) {
+            windows.push(WindowOffset {
+                time: start + length / 2.0,
+                offset_ms: offset * 1000.0,
+                confidence,
+            });
+        }
+    }
+    Ok(fit_drift(windows, duration_seconds))
+}
+
+/// Offset (seconds, positive = audio late) and confidence that best line up the onsets in an
+/// audio level envelope with the onsets of motion, within one window
+pub fn estimate_offset(levels: &[(f64, f64)], motion: &[(f64, f64)], start: f64, length: f64) -> Option<(f64, f64)> {
+    let samples = (length / STEP_SECONDS).ceil() as usize;
+    let amplitude: Vec<(f64, f64)> = levels
+        .iter()
+        .map(|(t, db)| (*t, 10f64.powf(db / 20.0)))
+        .collect();
+    let audio = onsets(&resample(&amplitude, start, samples));
+    let video = onsets(&resample(motion, start, samples));
+
+    let max_lag = (MAX_OFFSET_SECONDS / STEP_SECONDS).round() as isize;
+    let correlations: Vec<f64> = (-max_lag..=max_lag)
+        .map(|lag| correlation(&audio, &video, lag))
+        .collect();
+    let (peak, confidence) = correlations
+        .iter()
+        .copied()
+        .enumerate()
+        .max_by(|a, b| a.1.total_cmp(&b.1))?;
+    if confidence < MIN_CONFIDENCE {
+        return None;
+    }
+
+    // Parabolic interpolation between the neighbouring lags for sub-frame precision
+    let refinement = match (peak.checked_sub(1).and_then(|i| correlations.get(i)), correlations.get(peak + 1)) {
+        (Some(before), Some(after)) => {
+            let curvature = before - 2.0 * confidence + after;
+            if curvature < 0.0 { 0.5 * (before - after) / curvature } else { 0.0 }
+        }
+        _ => 0.0,
+    };
+    let lag = peak as f64 - max_lag as f64 + refinement;
+    Some((lag * STEP_SECONDS, confidence))
+}
+
+/// Fit offset = start + rate * time through the windows' offsets, weighted by confidence
+pub fn fit_drift(windows: Vec<WindowOffset>, duration_seconds: f64) -> SyncDrift {
+    let total_weight: f64 = windows.iter().map(|w| w.confidence).sum();
+    if windows.len() < 2 || total_weight <= 0.0 {
+        return SyncDrift {
+            status: SyncStatus::Undetermined,
+            offset_ms: windows.first().map_or(0.0, |w| w.offset_ms),
+            drift_ms: 0.0,
+            drift_ppm: 0.0,
+            windows,
+        };
+    }
+
+    let mean_time = windows.iter().map(|w| w.confidence * w.time).sum::<f64>() / total_weight;
+    let mean_offset = windows.iter().map(|w| w.confidence * w.offset_ms).sum::<f64>() / total_weight;
+    let spread: f64 = windows.iter().map(|w| w.confidence * (w.time - mean_time).powi(2)).sum();
+    let slope = if spread > 0.0 {
+        windows
+            .iter()
+            .map(|w| w.confidence * (w.time - mean_time) * (w.offset_ms - mean_offset))
+            .sum::<f64>()
+            / spread
+    } else {
+        0.0
+    };
+
+    // slope is milliseconds of drift per second, i.e. thousandths of the playback rate
+    let drift_ms = slope * duration_seconds;
+    SyncDrift {
+        status: if drift_ms.abs() >= DRIFT_THRESHOLD_SECONDS * 1000.0 {
+            SyncStatus::Drifting
+        } else {
+            SyncStatus::InSync
+        },
+        offset_ms: mean_offset - slope * mean_time,
+        drift_ms,
+        drift_ppm: slope * 1000.0,
+        windows,
+    }
+}
+
+/// Place (seconds, value) samples on the window's regular grid, holding the last value over gaps
+fn resample(series: &[(f64, f64)], start: f64, samples: usize) -> Vec<f64> {
+    let mut grid = vec![None; samples];
+    for (t, value) in series {
+        let index = ((t - start) / STEP_SECONDS).round();
+        if index >= 0.0 && (index as usize) < samples {
+            grid[index as usize] = Some(*value);
+        }
+    }
+    let mut last = 0.0;
+    grid.into_iter()
+        .map(|value| {
+            last = value.unwrap_or(last);
+            last
+        })
+        .collect()
+}
+
+/// How sharply each sample rises over the one before it
+fn onsets(envelope: &[f64]) -> Vec<f64> {
+    std::iter::once(0.0)
+        .chain(envelope.windows(2).map(|pair| (pair[1] - pair[0]).max(0.0)))
+        .collect()
+}
+
+/// Normalized correlation of audio shifted `lag` samples later against video
+fn correlation(audio: &[f64], video: &[f64], lag: isize) -> f64 {
+    let pairs: Vec<(f64, f64)> = video
+        .iter()
+        .enumerate()
+        .filter_map(|(i, v)| {
+            let j = i as isize + lag;
+            (j >= 0).then(|| audio.get(j as usize).map(|a| (*a, *v))).flatten()
+        })
+        .collect();
+    if pairs.len() < 2 {
+        return 0.0;
+    }
+    let n = pairs.len() as f64;
+    let mean_a = pairs.iter().map(|(a, _)| a).sum::<f64>() / n;
+    let mean_v = pairs.iter().map(|(_, v)| v).sum::<f64>() / n;
+    let (mut covariance, mut var_a, mut var_v) = (0.0, 0.0, 0.0);
+    for (a, v) in &pairs {
+        covariance += (a - mean_a) * (v - mean_v);
+        var_a += (a - mean_a).powi(2);
+        var_v += (v - mean_v).powi(2);
+    }
+    if var_a <= 0.0 || var_v <= 0.0 {
+        return 0.0;
+    }
+    covariance / (var_a * var_v).sqrt()
+}
@@ -0,0 +1,137 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
+
+use crate::db::Database;
+use crate::jobs::JobType;
+use engine::timeline::Timeline;
+
+/// Whether an asset's original media can be read. Offline and archived assets (e.g. on an
+/// unplugged drive, or moved to cold storage) keep being edited and previewed against their
+/// proxies; anything that needs the original waits until the asset is relinked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Availability {
+    Online,
+    /// The original went missing (drive unplugged, file moved); comes back online by itself
+    /// once the file reappears at its path
+    Offline,
+    /// Deliberately taken offline; only relinking or marking it online brings it back
+    Archived,
+}
+
+impl Availability {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Availability::Online => "online",
+            Availability::Offline => "offline",
+            Availability::Archived => "archived",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "online" => Some(Availability::Online),
+            "offline" => Some(Availability::Offline),
+            "archived" => Some(Availability::Archived),
+            _ => None,
+        }
+    }
+}
+
+/// An original the timeline uses that has to be relinked before export
+#[derive(Debug, Clone, Serialize)]
+pub struct RequiredOriginal {
+    pub asset_id: i64,
+    /// Where the original was last seen
+    pub path: String,
+    pub availability: Availability,
+    /// s3:// or gs:// origin; relinking without a path re-fetches from here
+    pub remote_url: Option<String>,
+}
+
+/// Raised when an export needs originals that are offline or archived. The API answers 409
+/// with the list, so the client can prompt for a relink.
+#[derive(Debug)]
+pub struct OriginalsOffline(pub Vec<RequiredOriginal>);
+
+impl fmt::Display for OriginalsOffline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} original(s) are offline; relink them before exporting", self.0.len())
+    }
+}
+
+impl std::error::Error for OriginalsOffline {}
+
+/// Availability of one asset (None when the asset doesn't exist)
+pub fn asset_availability(db: &Database, asset_id: i64) -> Result<Option<Availability>> {
+    Ok(db
+        .get_media_asset_availability(asset_id)?
+        .map(|value| Availability::parse(&value).unwrap_or(Availability::Online)))
+}
+
+/// Re-check the project's originals on disk: online assets whose file is gone go offline,
+/// offline assets whose file is back come online. Archived assets are left alone. Returns the
+/// assets that changed, with their new state.
+pub fn refresh_project(db: &Database, project_id: i64) -> Result<Vec<(i64, Availability)>> {
+    let mut changed = Vec::new();
+    for (asset_id, path, value) in db.get_media_asset_availabilities(project_id)? {
+        let current = Availability::parse(&value).unwrap_or(Availability::Online);
+        let exists = Path::new(&path).is_file();
+        let next = match current {
+            Availability::Online if !exists => Availability::Offline,
+            Availability::Offline if exists => Availability::Online,
+            _ => continue,
+        };
+        db.set_media_asset_availability(asset_id, next.as_str())?;
+        changed.push((asset_id, next));
+    }
+    Ok(changed)
+}
+
+/// Originals the timeline uses that aren't online, after re-checking the disk
+pub fn required_originals(db: &Database, project_id: i64, timeline: &Timeline) -> Result<Vec<RequiredOriginal>> {
+    refresh_project(db, project_id)?;
+    let used: HashSet<i64> = timeline
+        .tracks
+        .iter()
+        .flat_map(|t| t.clips.iter().map(|c| c.asset_id))
+        .collect();
+
+    let mut required = Vec::new();
+    for (asset_id, path, value) in db.get_media_asset_availabilities(project_id)? {
+        if !used.contains(&asset_id) {
+            continue;
+        }
+        let availability = Availability::parse(&value).unwrap_or(Availability::Online);
+        if availability == Availability::Online {
+            continue;
+        }
+        required.push(RequiredOriginal {
+            asset_id,
+            path,
+            availability,
+            remote_url: db.get_media_asset_remote_url(asset_id)?,
+        });
+    }
+    Ok(required)
+}
+
+/// Jobs that read the original media, and so wait while it's offline
+pub fn reads_original(job_type: &JobType) -> bool {
+    matches!(
+        job_type,
+        JobType::GenerateProxy
+            | JobType::BuildSegments
+            | JobType::TranscribeAsset
+            | JobType::AnalyzeVisionAsset
+            | JobType::AnalyzeAudioEvents
+            | JobType::DetectRedactions
+            | JobType::DetectStillSubjects
+            | JobType::IndexAssetWithTwelveLabs
+            | JobType::ReanalyzeChangedAsset
+            | JobType::GenerateThumbnailLevel
+    )
+}
@@ -0,0 +1,148 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::media::ffmpeg::FFmpegWrapper;
+
+/// ffmpeg scene score above which a frame counts as a cut
+const SCENE_CUT_THRESHOLD: f64 = 0.3;
+/// A music drop is a short-term loudness rise of at least this many LU...
+const DROP_RISE_LU: f64 = 6.0;
+/// ...relative to the quietest point in this lookback window...
+const DROP_LOOKBACK_SECONDS: f64 = 2.0;
+/// ...landing above this level (quiet passages getting slightly louder aren't drops)
+const DROP_MIN_LUFS: f64 = -30.0;
+/// Minimum spacing between detected drops
+const DROP_MIN_SPACING_SECONDS: f64 = 4.0;
+/// A drop this early ends the hook; otherwise the hook ends at the first cut after HOOK_MIN_SECONDS
+const HOOK_MAX_SECONDS: f64 = 20.0;
+const HOOK_MIN_SECONDS: f64 = 3.0;
+const HOOK_DEFAULT_SECONDS: f64 = 10.0;
+
+/// Section-by-section structure of a reference edit, instantiated by the planner
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BeatTemplate {
+    /// Reference asset the template was extracted from (None for built-in presets)
+    #[serde(default)]
+    pub source_asset_id: Option<i64>,
+    pub duration_sec: f64,
+    pub hook_length_sec: f64,
+    pub cuts_per_10s: f64,
+    /// Music drop positions in seconds from the start
+    pub music_drops: Vec<f64>,
+    pub sections: Vec<TemplateSection>,
+    /// Points (seconds) inside sections where a cut should land
+    #[serde(default)]
+    pub impacts: Vec<f64>,
+    /// Sections follow a music track in real time: they keep their own start and length
+    /// instead of being scaled to the edit's length
+    #[serde(default)]
+    pub follows_music: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateSection {
+    /// "hook", "body" or "drop" (a section that starts on a music drop)
+    pub section_type: String,
+    pub start_sec: f64,
+    pub end_sec: f64,
+    pub cuts_per_10s: f64,
+}
+
+/// Extract a beat template from a reference video: cut positions (scene detection) and music
+/// drops (loudness jumps) split the edit into hook / body / drop sections with their cut rates.
+/// `music_ranges` (seconds) restricts drops to stretches tagged as music, when known.
+pub async fn extract_beat_template(
+    asset_id: i64,
+    media_path: &Path,
+    duration_sec: f64,
+    music_ranges: &[(f64, f64)],
+) -> Result<BeatTemplate> {
+    let cuts = FFmpegWrapper::detect_scene_cuts(media_path, SCENE_CUT_THRESHOLD).await?;
+    let loudness = FFmpegWrapper::loudness_timeline(media_path).await.unwrap_or_else(|e| {
+        eprintln!("[STYLE] Loudness analysis failed for asset {}: {:?}", asset_id, e);
+        Vec::new()
+    });
+
+    let music_drops: Vec<f64> = detect_music_drops(&loudness)
+        .into_iter()
+        .filter(|t| music_ranges.is_empty() || music_ranges.iter().any(|(s, e)| t >= s && t <= e))
+        .collect();
+
+    let hook_length_sec = match music_drops.first() {
+        Some(drop) if *drop <= HOOK_MAX_SECONDS => *drop,
+        _ => cuts
+            .iter()
+            .copied()
+            .find(|c| *c >= HOOK_MIN_SECONDS)
+            .unwrap_or(HOOK_DEFAULT_SECONDS)
+            .min(HOOK_DEFAULT_SECONDS),
+    }
+    .min(duration_sec);
+
+    // Section boundaries: hook end, then every later drop
+    let mut boundaries = vec![(0.0, "hook"), (hook_length_sec, "body")];
+    for drop in &music_drops {
+        if *drop > hook_length_sec + 1.0 && *drop < duration_sec - 1.0 {
+            boundaries.push((*drop, "drop"));
+        }
+    }
+
+    let sections = boundaries
+        .iter()
+        .enumerate()
+        .map(|(idx, (start, section_type))| {
+            let end = boundaries.get(idx + 1).map(|(b, _)| *b).unwrap_or(duration_sec);
+            TemplateSection {
+                section_type: section_type.to_string(),
+                start_sec: *start,
+                end_sec: end,
+                cuts_per_10s: cut_rate(&cuts, *start, end),
+            }
+        })
+        .filter(|s| s.end_sec > s.start_sec)
+        .collect();
+
+    Ok(BeatTemplate {
+        source_asset_id: Some(asset_id),
+        duration_sec,
+        hook_length_sec,
+        cuts_per_10s: cut_rate(&cuts, 0.0, duration_sec),
+        music_drops,
+        sections,
+        impacts: Vec::new(),
+        follows_music: false,
+    })
+}
+
+/// Cuts per 10 seconds within [start, end)
+fn cut_rate(cuts: &[f64], start: f64, end: f64) -> f64 {
+    if end <= start {
+        return 0.0;
+    }
+    let count = cuts.iter().filter(|c| **c >= start && **c < end).count();
+    count as f64 * 10.0 / (end - start)
+}
+
+/// Points where short-term loudness jumps sharply after a quieter stretch
+pub(crate) fn detect_music_drops(loudness: &[(f64, f64)]) -> Vec<f64> {
+    let mut drops: Vec<f64> = Vec::new();
+    for (idx, (t, level)) in loudness.iter().enumerate() {
+        if *level < DROP_MIN_LUFS {
+            continue;
+        }
+        if drops.last().is_some_and(|last| t - last < DROP_MIN_SPACING_SECONDS) {
+            continue;
+        }
+        let lookback_min = loudness[..idx]
+            .iter()
+            .rev()
+            .take_while(|(prev_t, _)| t - prev_t <= DROP_LOOKBACK_SECONDS)
+            .map(|(_, prev_level)| *prev_level)
+            .fold(f64::INFINITY, f64::min);
+        if lookback_min.is_finite() && level - lookback_min >= DROP_RISE_LU {
+            drops.push(*t);
+        }
+    }
+    drops
+}
@@ -0,0 +1,115 @@
+use anyhow::Result;
+use chrono::{DateTime, NaiveDate, Utc};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+use crate::media::ffmpeg::FFmpegWrapper;
+
+/// How long a pairing QR code can be scanned before a new one is needed
+pub const PAIRING_TTL_MINUTES: i64 = 10;
+
+/// What a phone can send: iPhone HEVC video and HEIC photos (including both halves of a Live
+/// Photo), Android MP4/3GP, and plain photos
+pub const UPLOAD_EXTENSIONS: &[&str] = &[
+    "mov", "mp4", "m4v", "3gp", "heic", "heif", "jpg", "jpeg", "png", "webp",
+];
+
+/// Photo formats converted to JPEG on ingest
+const HEIF_EXTENSIONS: &[&str] = &["heic", "heif"];
+
+/// Video codecs kept as-is; anything else (HEVC, VP9, ...) is transcoded to H.264 on ingest
+const EDITING_VIDEO_CODECS: &[&str] = &["h264"];
+
+pub fn new_pairing_token() -> String {
+    format!("vcpair_{}", Uuid::new_v4().simple())
+}
+
+pub fn new_device_token() -> String {
+    format!("vcdev_{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+/// Tokens are only stored hashed
+pub fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+/// What the pairing QR code encodes: the daemon's API base URL and the one-time pairing token
+pub fn qr_payload(api_base_url: &str, pairing_token: &str) -> String {
+    let mut url = reqwest::Url::parse("vibecut://pair").expect("static URL parses");
+    url.query_pairs_mut()
+        .append_pair("server", api_base_url)
+        .append_pair("token", pairing_token);
+    url.to_string()
+}
+
+pub fn is_supported_upload(filename: &str) -> bool {
+    extension(Path::new(filename)).is_some_and(|ext| UPLOAD_EXTENSIONS.contains(&ext.as_str()))
+}
+
+/// A phone's file name reduced to a safe base name
+pub fn sanitize_filename(filename: &str) -> String {
+    let base = filename.rsplit(['/', '\\']).next().unwrap_or(filename);
+    let cleaned: String = base
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') { c } else { '_' })
+        .collect();
+    match cleaned.trim_start_matches('.') {
+        "" => "upload".to_string(),
+        name => name.to_string(),
+    }
+}
+
+/// The day an upload's capture session covers: the capture date as the phone saw it (in its
+/// own time zone), or today when the phone didn't say
+pub fn session_day(captured_at: Option<&str>) -> NaiveDate {
+    captured_at
+        .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+        .map(|t| t.date_naive())
+        .unwrap_or_else(|| Utc::now().date_naive())
+}
+
+/// Name of the collection holding a day's captures
+pub fn session_name(day: NaiveDate) -> String {
+    format!("Capture {}", day.format("%Y-%m-%d"))
+}
+
+/// Where an upload's original is written: `<cache_dir>/companion/<day>/<upload_id>_<name>`
+pub fn upload_path(cache_dir: &Path, day: NaiveDate, upload_id: i64, filename: &str) -> PathBuf {
+    cache_dir
+        .join("companion")
+        .join(day.format("%Y-%m-%d").to_string())
+        .join(format!("{}_{}", upload_id, sanitize_filename(filename)))
+}
+
+/// The file to import for an uploaded original: HEIC photos become JPEGs and video that isn't
+/// H.264 is transcoded, next to the original under `editing/`. Other files are imported as-is.
+pub async fn prepare_for_editing(original: &Path) -> Result<PathBuf> {
+    let ext = extension(original).unwrap_or_default();
+    let editing_dir = original
+        .parent()
+        .map(|dir| dir.join("editing"))
+        .unwrap_or_else(|| PathBuf::from("editing"));
+    let stem = original.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+
+    if HEIF_EXTENSIONS.contains(&ext.as_str()) {
+        let output = editing_dir.join(format!("{}.jpg", stem));
+        FFmpegWrapper::convert_image_to_jpeg(original, &output).await?;
+        return Ok(output);
+    }
+    if crate::media::still::is_image_path(original) {
+        return Ok(original.to_path_buf());
+    }
+
+    let codec = FFmpegWrapper::video_codec(original).await?;
+    if codec.as_deref().is_some_and(|c| EDITING_VIDEO_CODECS.contains(&c)) {
+        return Ok(original.to_path_buf());
+    }
+    let output = editing_dir.join(format!("{}.mp4", stem));
+    FFmpegWrapper::transcode_for_editing(original, &output).await?;
+    Ok(output)
+}
+
+fn extension(path: &Path) -> Option<String> {
+    path.extension().and_then(|e| e.to_str()).map(|e| e.to_lowercase())
+}
@@ -0,0 +1,248 @@
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::Path;
+
+use crate::crypto;
+use crate::db::Database;
+use crate::media::ffmpeg::{FFmpegWrapper, MediaInfo, VideoColor};
+use engine::render::SourceMedia;
+use engine::timeline::{Timeline, TrackKind, TICKS_PER_SECOND};
+
+/// Frames an original may differ from the edit's view of it, for rounding in container durations
+const FRAME_TOLERANCE: i64 = 1;
+
+/// Transfer functions of HDR footage, which can't be brought into an SDR export without tone
+/// mapping
+const HDR_TRANSFERS: &[&str] = &["smpte2084", "arib-std-b67"];
+
+/// One asset as the export will read it
+#[derive(Debug, Clone, Serialize)]
+pub struct ConformedAsset {
+    pub asset_id: i64,
+    pub original_path: String,
+    /// Proxy the edit was made against, when there is one
+    pub proxy_path: Option<String>,
+    /// Frame rate of the original ("30000/1001")
+    pub fps: String,
+    pub frames: i64,
+    pub color: VideoColor,
+    /// Filter bringing the original to BT.709 4:2:0 (None when it already is)
+    pub color_filter: Option<String>,
+}
+
+/// Something about an original that doesn't match the edit
+#[derive(Debug, Clone, Serialize)]
+pub struct ConformIssue {
+    pub asset_id: i64,
+    pub path: String,
+    /// "probe_failed", "fps_mismatch", "frame_count_mismatch", "clip_out_of_range" or "hdr"
+    pub kind: &'static str,
+    pub message: String,
+}
+
+/// What the conform step found for each asset the timeline uses
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ConformReport {
+    pub assets: Vec<ConformedAsset>,
+    pub issues: Vec<ConformIssue>,
+}
+
+/// Raised when an export's originals don't match what was edited. The API answers 422 with
+/// the report.
+#[derive(Debug)]
+pub struct ConformFailed(pub ConformReport);
+
+impl fmt::Display for ConformFailed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} problem(s) conforming the timeline to its originals; the export wasn't started",
+            self.0.issues.len()
+        )
+    }
+}
+
+impl std::error::Error for ConformFailed {}
+
+/// Map every clip's asset to its original and check it. Returns the sources to render from
+/// and the report, or fails with ConformFailed when anything doesn't match.
+pub async fn conform_timeline(db: &Database, timeline: &Timeline) -> Result<(HashMap<i64, SourceMedia>, ConformReport)> {
+    // Source ranges each asset is used for, in timeline order
+    let mut clip_ranges: BTreeMap<i64, Vec<(i64, i64)>> = BTreeMap::new();
+    for track in timeline.tracks.iter().filter(|t| t.kind == TrackKind::Video) {
+        for clip in &track.clips {
+            clip_ranges.entry(clip.asset_id).or_default().push((clip.in_ticks, clip.out_ticks));
+        }
+    }
+
+    let mut report = ConformReport::default();
+    let mut sources = HashMap::new();
+    for (asset_id, ranges) in clip_ranges {
+        let Some(asset) = db.get_media_asset(asset_id)? else {
+            anyhow::bail!("Media asset {} not found", asset_id);
+        };
+        let issue = |kind, message: String| ConformIssue {
+            asset_id,
+            path: asset.path.clone(),
+            kind,
+            message,
+        };
+        let original = match FFmpegWrapper::probe(Path::new(&asset.path)).await {
+            Ok(info) => info,
+            Err(e) => {
+                report.issues.push(issue("probe_failed", format!("the original can't be read: {}", e)));
+                continue;
+            }
+        };
+        let proxy_path = db.get_proxy_path(asset_id)?;
+        let still = db.is_still_asset(asset_id)?;
+
+        if !still {
+            let recorded_fps = (asset.fps_num, asset.fps_den);
+            if !same_fps((original.fps_num, original.fps_den), recorded_fps) {
+                report.issues.push(issue(
+                    "fps_mismatch",
+                    format!(
+                        "the original runs at {}/{} fps but was edited at {}/{} fps",
+                        original.fps_num, original.fps_den, asset.fps_num, asset.fps_den
+                    ),
+                ));
+            }
+            let frames = frame_count(original.duration_ticks, original.fps_num, original.fps_den);
+            let recorded_frames = frame_count(asset.duration_ticks, asset.fps_num, asset.fps_den);
+            if (frames - recorded_frames).abs() > FRAME_TOLERANCE {
+                report.issues.push(issue(
+                    "frame_count_mismatch",
+                    format!("the original has {} frames but had {} when it was imported", frames, recorded_frames),
+                ));
+            }
+            // Encrypted proxies can't be probed; the import-time record stands in for them
+            let readable_proxy = proxy_path
+                .as_deref()
+                .filter(|p| Path::new(p).exists() && !crypto::is_encrypted_file(Path::new(p)));
+            if let Some(proxy) = readable_proxy {
+                if let Ok(proxy_info) = FFmpegWrapper::probe(Path::new(proxy)).await {
+                    let proxy_frames = frame_count(proxy_info.duration_ticks, proxy_info.fps_num, proxy_info.fps_den);
+                    if !same_fps((original.fps_num, original.fps_den), (proxy_info.fps_num, proxy_info.fps_den)) {
+                        report.issues.push(issue(
+                            "fps_mismatch",
+                            format!(
+                                "the proxy runs at {}/{} fps but the original at {}/{} fps",
+                                proxy_info.fps_num, proxy_info.fps_den, original.fps_num, original.fps_den
+                            ),
+                        ));
+                    } else if (frames - proxy_frames).abs() > FRAME_TOLERANCE {
+                        report.issues.push(issue(
+                            "frame_count_mismatch",
+                            format!("the proxy has {} frames but the original has {}", proxy_frames, frames),
+                        ));
+                    }
+                }
+            }
+            let frame_ticks = frame_ticks(original.fps_num, original.fps_den);
+            if let Some((_, out_ticks)) = ranges.iter().find(|(_, out)| *out > original.duration_ticks + frame_ticks) {
+                report.issues.push(issue(
+                    "clip_out_of_range",
+                    format!(
+                        "a clip runs to {:.2}s but the original is only {:.2}s long",
+                        *out_ticks as f64 / TICKS_PER_SECOND as f64,
+                        original.duration_ticks as f64 / TICKS_PER_SECOND as f64
+                    ),
+                ));
+            }
+        }
+
+        if let Some(transfer) = original.color.transfer.as_deref().filter(|t| HDR_TRANSFERS.contains(t)) {
+            report.issues.push(issue(
+                "hdr",
+                format!("the original is HDR ({}) and would need tone mapping for this export", transfer),
+            ));
+        }
+
+        let color_filter = color_filter(&original);
+        sources.insert(
+            asset_id,
+            SourceMedia {
+                path: asset.path.clone(),
+                color_filter: color_filter.clone(),
+                fps: (!still && original.fps_den > 0).then(|| original.fps_num as f64 / original.fps_den as f64),
+                checksum: db.get_media_asset_checksum(asset_id)?,
+            },
+        );
+        report.assets.push(ConformedAsset {
+            asset_id,
+            original_path: asset.path.clone(),
+            proxy_path,
+            fps: format!("{}/{}", original.fps_num, original.fps_den),
+            frames: frame_count(original.duration_ticks, original.fps_num, original.fps_den),
+            color: original.color,
+            color_filter,
+        });
+    }
+
+    if !report.issues.is_empty() {
+        for issue in &report.issues {
+            eprintln!("[CONFORM] Asset {} ({}): {}", issue.asset_id, issue.path, issue.message);
+        }
+        return Err(ConformFailed(report).into());
+    }
+    Ok((sources, report))
+}
+
+/// Frame rates of the timeline's video assets as recorded at import, leaving out stills, for
+/// previewing frame-rate conversions without probing the originals
+pub fn recorded_frame_rates(db: &Database, timeline: &Timeline) -> Result<HashMap<i64, f64>> {
+    let mut frame_rates = HashMap::new();
+    for clip in timeline.tracks.iter().filter(|t| t.kind == TrackKind::Video).flat_map(|t| t.clips.iter()) {
+        if frame_rates.contains_key(&clip.asset_id) || db.is_still_asset(clip.asset_id)? {
+            continue;
+        }
+        if let Some(asset) = db.get_media_asset(clip.asset_id)?.filter(|a| a.fps_den > 0) {
+            frame_rates.insert(clip.asset_id, asset.fps_num as f64 / asset.fps_den as f64);
+        }
+    }
+    Ok(frame_rates)
+}
+
+fn same_fps(a: (i32, i32), b: (i32, i32)) -> bool {
+    a.0 as i64 * b.1 as i64 == b.0 as i64 * a.1 as i64
+}
+
+fn frame_count(duration_ticks: i64, fps_num: i32, fps_den: i32) -> i64 {
+    if fps_den <= 0 {
+        return 0;
+    }
+    (duration_ticks as f64 / TICKS_PER_SECOND as f64 * fps_num as f64 / fps_den as f64).round() as i64
+}
+
+fn frame_ticks(fps_num: i32, fps_den: i32) -> i64 {
+    if fps_num <= 0 {
+        return 0;
+    }
+    TICKS_PER_SECOND * fps_den as i64 / fps_num as i64
+}
+
+/// Filter bringing an original to BT.709 limited-range 4:2:0: full-range (JPEG) pixels are
+/// squeezed to video range, BT.601 and BT.2020 matrices are converted, and other pixel formats
+/// (10-bit, 4:2:2, RGB) are converted down. None when the original already matches.
+fn color_filter(info: &MediaInfo) -> Option<String> {
+    let pix_fmt = info.color.pix_fmt.as_deref().unwrap_or("yuv420p");
+    let source_space = match info.color.space.as_deref() {
+        Some("bt470bg") => Some("bt601-6-625"),
+        Some("smpte170m") => Some("bt601-6-525"),
+        Some("bt2020nc") | Some("bt2020c") => Some("bt2020"),
+        _ => None,
+    };
+    let mut filters = Vec::new();
+    if pix_fmt.starts_with("yuvj") {
+        filters.push("scale=in_range=full:out_range=tv".to_string());
+    }
+    if let Some(space) = source_space {
+        filters.push(format!("colorspace=all=bt709:iall={}:format=yuv420p", space));
+    } else if pix_fmt != "yuv420p" {
+        filters.push("format=yuv420p".to_string());
+    }
+    (!filters.is_empty()).then(|| filters.join(","))
+}
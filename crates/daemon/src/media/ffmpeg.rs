@@ -1,16 +1,36 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::path::Path;
-use tokio::process::Command;
+
+use crate::jobs::resources;
+use crate::media::process::{Invocation, ProcessFailed, Tool};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MediaInfo {
     pub duration_ticks: i64,
     pub fps_num: i32,
     pub fps_den: i32,
+    /// Displayed size: already swapped for portrait video stored sideways with a rotation
     pub width: i32,
     pub height: i32,
+    /// Clockwise rotation players apply on display (0, 90, 180 or 270), as phones record it
+    pub rotation: i32,
     pub has_audio: bool,
+    /// Recording start from the container's creation_time tag (RFC 3339), when the camera set one
+    pub creation_time: Option<String>,
+    /// Pixel format and color tags of the video stream
+    #[serde(default)]
+    pub color: VideoColor,
+}
+
+/// How a video stream's pixels are stored and which color space they're in, as ffprobe names
+/// them (None when the stream doesn't say)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct VideoColor {
+    pub pix_fmt: Option<String>,
+    pub space: Option<String>,
+    pub transfer: Option<String>,
+    pub primaries: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,6 +42,12 @@ pub struct ProbeOutput {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct FormatInfo {
     duration: Option<String>,
+    tags: Option<FormatTags>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct FormatTags {
+    creation_time: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -31,34 +57,104 @@ struct StreamInfo {
     height: Option<i32>,
     r_frame_rate: Option<String>,
     avg_frame_rate: Option<String>,
+    pix_fmt: Option<String>,
+    color_space: Option<String>,
+    color_transfer: Option<String>,
+    color_primaries: Option<String>,
+    tags: Option<StreamTags>,
+    side_data_list: Option<Vec<SideData>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StreamTags {
+    rotate: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SideData {
+    rotation: Option<f64>,
+}
+
+/// Clockwise display rotation of a video stream, snapped to a quarter turn: the display
+/// matrix's rotation (counter-clockwise, so negated) or the older `rotate` tag
+fn display_rotation(stream: &StreamInfo) -> i32 {
+    let degrees = stream
+        .side_data_list
+        .iter()
+        .flatten()
+        .find_map(|side_data| side_data.rotation)
+        .map(|rotation| -rotation)
+        .or_else(|| {
+            stream
+                .tags
+                .as_ref()
+                .and_then(|t| t.rotate.as_deref())
+                .and_then(|r| r.trim().parse::<f64>().ok())
+        })
+        .unwrap_or(0.0);
+    ((degrees / 90.0).round() as i32 * 90).rem_euclid(360)
+}
+
+/// Input options that seek to `start` and read `duration` seconds (nothing for the whole file)
+fn window_args(window: Option<(f64, f64)>) -> Vec<String> {
+    match window {
+        Some((start, duration)) => vec![
+            "-ss".to_string(),
+            format!("{:.3}", start),
+            "-t".to_string(),
+            format!("{:.3}", duration),
+        ],
+        None => Vec::new(),
+    }
+}
+
+/// ffmpeg under the running job's resource limits, with its decoding and filtering threads capped
+fn ffmpeg() -> Invocation {
+    let invocation = Invocation::new(Tool::Ffmpeg);
+    match resources::ffmpeg_threads() {
+        Some(threads) => invocation.args(["-filter_threads", &threads.to_string(), "-threads", &threads.to_string()]),
+        None => invocation,
+    }
+}
+
+/// Output options capping encoder threads the same way (goes before the output path)
+fn encoder_thread_args() -> Vec<String> {
+    resources::ffmpeg_threads()
+        .map(|threads| vec!["-threads".to_string(), threads.to_string()])
+        .unwrap_or_default()
 }
 
 pub struct FFmpegWrapper;
 
 impl FFmpegWrapper {
     pub async fn probe(media_path: &Path) -> Result<MediaInfo> {
-        let output = Command::new("ffprobe")
+        let output = Invocation::new(Tool::Ffprobe)
             .args(&[
                 "-v",
                 "error",
                 "-show_entries",
-                "format=duration:stream=codec_type,width,height,r_frame_rate,avg_frame_rate",
+                "format=duration:format_tags=creation_time:stream=codec_type,width,height,r_frame_rate,avg_frame_rate,\
+                 pix_fmt,color_space,color_transfer,color_primaries:stream_tags=rotate:stream_side_data=rotation",
                 "-of",
                 "json",
                 media_path.to_str().unwrap(),
             ])
             .output()
-            .await
-            .context("Failed to execute ffprobe. Make sure FFmpeg is installed.")?;
+            .await?;
 
         if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            anyhow::bail!("ffprobe failed: {}", stderr);
+            return Err(ProcessFailed::exited(Tool::Ffprobe, &output).into());
         }
 
         let probe_output: ProbeOutput = serde_json::from_slice(&output.stdout)
             .context("Failed to parse ffprobe JSON output")?;
 
+        let creation_time = probe_output
+            .format
+            .as_ref()
+            .and_then(|f| f.tags.as_ref())
+            .and_then(|t| t.creation_time.clone());
+
         // Extract duration from format
         let duration_seconds = probe_output
             .format
@@ -72,6 +168,17 @@ impl FFmpegWrapper {
             .iter()
             .find(|s| s.codec_type.as_deref() == Some("video"));
 
+        let rotation = video_stream.map(display_rotation).unwrap_or(0);
+        // "unknown" and "reserved" say nothing more than a missing tag
+        let tag = |value: &Option<String>| value.clone().filter(|v| v != "unknown" && v != "reserved");
+        let color = video_stream
+            .map(|vs| VideoColor {
+                pix_fmt: tag(&vs.pix_fmt),
+                space: tag(&vs.color_space),
+                transfer: tag(&vs.color_transfer),
+                primaries: tag(&vs.color_primaries),
+            })
+            .unwrap_or_default();
         let (width, height, fps_num, fps_den) = if let Some(vs) = video_stream {
             let w = vs.width.unwrap_or(0);
             let h = vs.height.unwrap_or(0);
@@ -89,7 +196,12 @@ impl FFmpegWrapper {
                 })
                 .unwrap_or((30, 1));
 
-            (w, h, num, den)
+            // Decoding applies the rotation, so everything downstream sees the displayed size
+            if rotation % 180 == 90 {
+                (h, w, num, den)
+            } else {
+                (w, h, num, den)
+            }
         } else {
             (0, 0, 30, 1)
         };
@@ -110,7 +222,10 @@ impl FFmpegWrapper {
             fps_den,
             width,
             height,
+            rotation,
             has_audio,
+            creation_time,
+            color,
         })
     }
 
@@ -119,34 +234,55 @@ impl FFmpegWrapper {
         output_path: &Path,
         width: i32,
         height: i32,
+    ) -> Result<()> {
+        Self::generate_proxy_with_crf(input_path, output_path, width, height, 23, None).await
+    }
+
+    /// Generate an H.264 proxy at the given size and quality (lower crf = higher quality),
+    /// optionally passing the audio through `audio_filter` (e.g. a sync correction)
+    pub async fn generate_proxy_with_crf(
+        input_path: &Path,
+        output_path: &Path,
+        width: i32,
+        height: i32,
+        crf: u8,
+        audio_filter: Option<&str>,
     ) -> Result<()> {
         // Create parent directory if needed
         if let Some(parent) = output_path.parent() {
             tokio::fs::create_dir_all(parent).await?;
         }
 
-        let status = Command::new("ffmpeg")
-            .args(&[
+        let audio_filter_args = audio_filter
+            .map(|filter| vec!["-af".to_string(), filter.to_string()])
+            .unwrap_or_default();
+        let status = ffmpeg()
+            .args([
                 "-i",
                 input_path.to_str().unwrap(),
                 "-vf",
                 &format!("scale={}:{}", width, height),
+            ])
+            .args(audio_filter_args)
+            .args([
                 "-c:v",
                 "libx264",
                 "-preset",
                 "medium",
                 "-crf",
-                "23",
+                &crf.to_string(),
                 "-c:a",
                 "aac",
                 "-b:a",
                 "128k",
+            ])
+            .args(encoder_thread_args())
+            .args([
                 "-y", // Overwrite output file
                 output_path.to_str().unwrap(),
             ])
             .output()
-            .await
-            .context("Failed to execute ffmpeg. Make sure FFmpeg is installed.")?
+            .await?
             .status;
 
         if !status.success() {
@@ -156,13 +292,192 @@ impl FFmpegWrapper {
         Ok(())
     }
 
+    /// Run a render command built by the engine (inputs, filter graph, encoding and output)
+    pub async fn render(args: &[String]) -> Result<()> {
+        let output = ffmpeg()
+            .args(args)
+            .output()
+            .await?;
+        if !output.status.success() {
+            return Err(ProcessFailed::exited(Tool::Ffmpeg, &output).into());
+        }
+        Ok(())
+    }
+
+    /// Encode a still image as an H.264 proxy that holds it for `duration_sec`, with a silent
+    /// audio track so it cuts and mixes like footage
+    pub async fn generate_still_proxy(
+        input_path: &Path,
+        output_path: &Path,
+        width: i32,
+        height: i32,
+        crf: u8,
+        fps: i32,
+        duration_sec: f64,
+    ) -> Result<()> {
+        if let Some(parent) = output_path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        let duration = format!("{:.3}", duration_sec);
+        let status = ffmpeg()
+            .args([
+                "-loop",
+                "1",
+                "-framerate",
+                &fps.to_string(),
+                "-t",
+                &duration,
+                "-i",
+                input_path.to_str().unwrap(),
+                "-f",
+                "lavfi",
+                "-t",
+                &duration,
+                "-i",
+                "anullsrc=r=48000:cl=stereo",
+                "-vf",
+                &format!("scale={}:{},format=yuv420p", width, height),
+                "-c:v",
+                "libx264",
+                "-tune",
+                "stillimage",
+                "-crf",
+                &crf.to_string(),
+                "-c:a",
+                "aac",
+                "-b:a",
+                "128k",
+                "-shortest",
+            ])
+            .args(encoder_thread_args())
+            .args([
+                "-y",
+                output_path.to_str().unwrap(),
+            ])
+            .output()
+            .await?
+            .status;
+
+        if !status.success() {
+            anyhow::bail!("ffmpeg failed to generate still proxy");
+        }
+
+        Ok(())
+    }
+
+    /// Codec of the first video stream (e.g. "h264", "hevc"), None when there's no video
+    pub async fn video_codec(media_path: &Path) -> Result<Option<String>> {
+        let output = Invocation::new(Tool::Ffprobe)
+            .args([
+                "-v",
+                "error",
+                "-select_streams",
+                "v:0",
+                "-show_entries",
+                "stream=codec_name",
+                "-of",
+                "csv=p=0",
+                media_path.to_str().unwrap(),
+            ])
+            .output()
+            .await?;
+
+        if !output.status.success() {
+            return Err(ProcessFailed::exited(Tool::Ffprobe, &output).into());
+        }
+        let codec = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        Ok(Some(codec).filter(|c| !c.is_empty()))
+    }
+
+    /// Re-encode a clip as 8-bit H.264/AAC, which every decoder seeks quickly in. Keeps the
+    /// container metadata (creation time, location) of the source.
+    pub async fn transcode_for_editing(input_path: &Path, output_path: &Path) -> Result<()> {
+        if let Some(parent) = output_path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        let status = ffmpeg()
+            .args([
+                "-i",
+                input_path.to_str().unwrap(),
+                "-map_metadata",
+                "0",
+                "-vf",
+                "format=yuv420p",
+                "-c:v",
+                "libx264",
+                "-preset",
+                "fast",
+                "-crf",
+                "18",
+                "-c:a",
+                "aac",
+                "-b:a",
+                "192k",
+                "-movflags",
+                "+faststart",
+            ])
+            .args(encoder_thread_args())
+            .args([
+                "-y",
+                output_path.to_str().unwrap(),
+            ])
+            .output()
+            .await?
+            .status;
+
+        if !status.success() {
+            anyhow::bail!("ffmpeg failed to transcode {}", input_path.display());
+        }
+
+        Ok(())
+    }
+
+    /// Convert a HEIC/HEIF photo to JPEG, with libheif's `heif-convert` when it's installed
+    /// (it handles the tiled images phones write) and ffmpeg otherwise
+    pub async fn convert_image_to_jpeg(input_path: &Path, output_path: &Path) -> Result<()> {
+        if let Some(parent) = output_path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        let heif_convert = Invocation::new(Tool::HeifConvert)
+            .args(["-q", "95", input_path.to_str().unwrap(), output_path.to_str().unwrap()])
+            .output()
+            .await;
+        if heif_convert.is_ok_and(|output| output.status.success()) {
+            return Ok(());
+        }
+
+        let status = ffmpeg()
+            .args([
+                "-i",
+                input_path.to_str().unwrap(),
+                "-frames:v",
+                "1",
+                "-q:v",
+                "2",
+                "-y",
+                output_path.to_str().unwrap(),
+            ])
+            .output()
+            .await?
+            .status;
+
+        if !status.success() {
+            anyhow::bail!("Failed to convert {} to JPEG", input_path.display());
+        }
+
+        Ok(())
+    }
+
     pub async fn extract_audio(input_path: &Path, output_path: &Path) -> Result<()> {
         // Create parent directory if needed
         if let Some(parent) = output_path.parent() {
             tokio::fs::create_dir_all(parent).await?;
         }
 
-        let status = Command::new("ffmpeg")
+        let status = ffmpeg()
             .args(&[
                 "-i",
                 input_path.to_str().unwrap(),
@@ -177,8 +492,7 @@ impl FFmpegWrapper {
                 output_path.to_str().unwrap(),
             ])
             .output()
-            .await
-            .context("Failed to execute ffmpeg for audio extraction")?
+            .await?
             .status;
 
         if !status.success() {
@@ -188,6 +502,338 @@ impl FFmpegWrapper {
         Ok(())
     }
 
+    /// Extract mono 16kHz 16-bit PCM WAV audio, the native input format for speech models
+    /// Much smaller than the source video, so transcription uploads/decodes far less data
+    pub async fn extract_audio_for_transcription(input_path: &Path, output_path: &Path) -> Result<()> {
+        // Create parent directory if needed
+        if let Some(parent) = output_path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        let status = ffmpeg()
+            .args([
+                "-i",
+                input_path.to_str().unwrap(),
+                "-vn", // No video
+                "-acodec",
+                "pcm_s16le",
+                "-ar",
+                "16000",
+                "-ac",
+                "1",
+                "-y",
+                output_path.to_str().unwrap(),
+            ])
+            .output()
+            .await?
+            .status;
+
+        if !status.success() {
+            anyhow::bail!("ffmpeg failed to extract transcription audio");
+        }
+
+        Ok(())
+    }
+
+    /// Extract a time window of audio as mono 16kHz WAV (used for chunked transcription)
+    pub async fn extract_audio_chunk(
+        input_path: &Path,
+        output_path: &Path,
+        start_seconds: f64,
+        duration_seconds: f64,
+    ) -> Result<()> {
+        // Create parent directory if needed
+        if let Some(parent) = output_path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        let status = ffmpeg()
+            .args([
+                "-ss",
+                &format!("{:.3}", start_seconds),
+                "-t",
+                &format!("{:.3}", duration_seconds),
+                "-i",
+                input_path.to_str().unwrap(),
+                "-vn", // No video
+                "-acodec",
+                "pcm_s16le",
+                "-ar",
+                "16000",
+                "-ac",
+                "1",
+                "-y",
+                output_path.to_str().unwrap(),
+            ])
+            .output()
+            .await?
+            .status;
+
+        if !status.success() {
+            anyhow::bail!("ffmpeg failed to extract audio chunk");
+        }
+
+        Ok(())
+    }
+
+    /// Detect hard cuts with ffmpeg's scene change score
+    /// Returns cut timestamps in seconds (threshold 0..1, ~0.3 catches most edits)
+    pub async fn detect_scene_cuts(input_path: &Path, threshold: f64) -> Result<Vec<f64>> {
+        let output = ffmpeg()
+            .args([
+                "-i",
+                input_path.to_str().unwrap(),
+                "-vf",
+                &format!("select='gt(scene,{})',showinfo", threshold),
+                "-an",
+                "-f",
+                "null",
+                "-",
+            ])
+            .output()
+            .await?;
+
+        if !output.status.success() {
+            anyhow::bail!("ffmpeg failed to detect scene cuts");
+        }
+
+        // showinfo logs one line per selected frame: "... pts_time:12.345 ..."
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let cuts = stderr
+            .lines()
+            .filter(|line| line.contains("showinfo"))
+            .filter_map(|line| {
+                let value = line.split("pts_time:").nth(1)?.split_whitespace().next()?;
+                value.parse::<f64>().ok()
+            })
+            .collect();
+        Ok(cuts)
+    }
+
+    /// How much the picture changes between frames sampled `fps` times a second, as the mean
+    /// absolute luma difference from the previous sample (0 = pixel-identical, 255 = inverted)
+    /// Returns (seconds, difference) pairs
+    pub async fn frame_differences(input_path: &Path, fps: f64) -> Result<Vec<(f64, f64)>> {
+        Self::sampled_frame_differences(input_path, fps, None).await
+    }
+
+    /// frame_differences over `duration` seconds from `start`; times stay relative to the file
+    pub async fn frame_differences_between(
+        input_path: &Path,
+        fps: f64,
+        start: f64,
+        duration: f64,
+    ) -> Result<Vec<(f64, f64)>> {
+        Self::sampled_frame_differences(input_path, fps, Some((start, duration))).await
+    }
+
+    async fn sampled_frame_differences(
+        input_path: &Path,
+        fps: f64,
+        window: Option<(f64, f64)>,
+    ) -> Result<Vec<(f64, f64)>> {
+        let output = ffmpeg()
+            .args(window_args(window))
+            .args([
+                "-i",
+                input_path.to_str().unwrap(),
+                "-vf",
+                &format!(
+                    "fps={},scale=320:-2,format=gray,tblend=all_mode=difference,signalstats,\
+                     metadata=mode=print:key=lavfi.signalstats.YAVG",
+                    fps
+                ),
+                "-an",
+                "-f",
+                "null",
+                "-",
+            ])
+            .output()
+            .await?;
+
+        if !output.status.success() {
+            anyhow::bail!("ffmpeg failed to measure frame differences");
+        }
+
+        // The metadata filter logs "frame:3 pts:6 pts_time:1.5" followed by "lavfi.signalstats.YAVG=0.42"
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let mut differences = Vec::new();
+        let mut current_time = None;
+        for line in stderr.lines().filter(|line| line.contains("Parsed_metadata")) {
+            if let Some(value) = line.split("pts_time:").nth(1) {
+                current_time = value.split_whitespace().next().and_then(|t| t.parse::<f64>().ok());
+            } else if let Some(value) = line.split("lavfi.signalstats.YAVG=").nth(1) {
+                if let (Some(time), Ok(difference)) = (current_time.take(), value.trim().parse::<f64>()) {
+                    differences.push((time + window.map_or(0.0, |(start, _)| start), difference));
+                }
+            }
+        }
+        Ok(differences)
+    }
+
+    /// RMS level of the audio in consecutive `step`-second windows over `duration` seconds from
+    /// `start`, for lining sound up against picture. Silence is reported as -120 dB.
+    /// Returns (seconds, dBFS) pairs, times relative to the file
+    pub async fn audio_levels_between(
+        input_path: &Path,
+        step: f64,
+        start: f64,
+        duration: f64,
+    ) -> Result<Vec<(f64, f64)>> {
+        const ANALYSIS_SAMPLE_RATE: f64 = 8000.0;
+        let samples_per_step = ((step * ANALYSIS_SAMPLE_RATE).round() as i64).max(1);
+        let output = ffmpeg()
+            .args(window_args(Some((start, duration))))
+            .args([
+                "-i",
+                input_path.to_str().unwrap(),
+                "-vn",
+                "-af",
+                &format!(
+                    "aresample={},aformat=channel_layouts=mono,asetnsamples=n={}:p=0,astats=metadata=1:reset=1,\
+                     ametadata=mode=print:key=lavfi.astats.Overall.RMS_level",
+                    ANALYSIS_SAMPLE_RATE, samples_per_step
+                ),
+                "-f",
+                "null",
+                "-",
+            ])
+            .output()
+            .await?;
+
+        if !output.status.success() {
+            anyhow::bail!("ffmpeg failed to measure audio levels");
+        }
+
+        // Same layout as frame_differences: a "pts_time:" line, then "lavfi.astats.Overall.RMS_level=-31.2"
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let mut levels = Vec::new();
+        let mut current_time = None;
+        for line in stderr.lines().filter(|line| line.contains("Parsed_ametadata")) {
+            if let Some(value) = line.split("pts_time:").nth(1) {
+                current_time = value.split_whitespace().next().and_then(|t| t.parse::<f64>().ok());
+            } else if let Some(value) = line.split("lavfi.astats.Overall.RMS_level=").nth(1) {
+                if let Some(time) = current_time.take() {
+                    let level = value.trim().parse::<f64>().ok().filter(|l| l.is_finite()).unwrap_or(-120.0);
+                    levels.push((time + start, level.max(-120.0)));
+                }
+            }
+        }
+        Ok(levels)
+    }
+
+    /// Short-term (3s) loudness over time via the EBU R128 filter, sampled every 100ms
+    /// Returns (seconds, LUFS) pairs
+    pub async fn loudness_timeline(input_path: &Path) -> Result<Vec<(f64, f64)>> {
+        let output = ffmpeg()
+            .args([
+                "-i",
+                input_path.to_str().unwrap(),
+                "-vn",
+                "-af",
+                "ebur128=framelog=verbose",
+                "-f",
+                "null",
+                "-",
+            ])
+            .output()
+            .await?;
+
+        if !output.status.success() {
+            anyhow::bail!("ffmpeg failed to measure loudness");
+        }
+
+        // Frame lines look like: "[Parsed_ebur128_0 @ ...] t: 1.2  TARGET:-23 LUFS  M: -20.1 S: -21.4  I: ..."
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let timeline = stderr
+            .lines()
+            .filter(|line| line.contains("Parsed_ebur128") && line.contains(" t: "))
+            .filter_map(|line| {
+                let t = line.split(" t: ").nth(1)?.split_whitespace().next()?.parse::<f64>().ok()?;
+                let s = line.split(" S: ").nth(1)?.split_whitespace().next()?.parse::<f64>().ok()?;
+                Some((t, s))
+            })
+            .collect();
+        Ok(timeline)
+    }
+
+    /// 64-bit difference hash of frames sampled `fps` times a second: each bit says whether a
+    /// pixel of the frame shrunk to 9x8 gray is brighter than its right neighbour. Survives
+    /// re-encoding, scaling and mild grading, so the same shot hashes alike in an original and
+    /// its proxy. Frame i is at i / fps seconds.
+    pub async fn picture_fingerprints(input_path: &Path, fps: f64) -> Result<Vec<u64>> {
+        let output = ffmpeg()
+            .args([
+                "-i",
+                input_path.to_str().unwrap(),
+                "-vf",
+                &format!("fps={},scale=9:8,format=gray", fps),
+                "-an",
+                "-f",
+                "rawvideo",
+                "-",
+            ])
+            .output()
+            .await?;
+
+        if !output.status.success() {
+            anyhow::bail!("ffmpeg failed to fingerprint frames");
+        }
+
+        let hashes = output
+            .stdout
+            .chunks_exact(72)
+            .map(|frame| {
+                let mut hash = 0u64;
+                for row in frame.chunks_exact(9) {
+                    for pair in row.windows(2) {
+                        hash = (hash << 1) | (pair[0] > pair[1]) as u64;
+                    }
+                }
+                hash
+            })
+            .collect();
+        Ok(hashes)
+    }
+
+    /// Cut `duration` seconds from `start` into a small video-only file (for analyzing part of
+    /// an asset)
+    pub async fn extract_video_window(
+        input_path: &Path,
+        output_path: &Path,
+        start_seconds: f64,
+        duration_seconds: f64,
+    ) -> Result<()> {
+        if let Some(parent) = output_path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        let status = ffmpeg()
+            .args(window_args(Some((start_seconds, duration_seconds))))
+            .args([
+                "-i",
+                input_path.to_str().unwrap(),
+                "-an",
+                "-c:v",
+                "libx264",
+                "-preset",
+                "veryfast",
+                "-crf",
+                "18",
+            ])
+            .args(encoder_thread_args())
+            .args(["-y", output_path.to_str().unwrap()])
+            .output()
+            .await?
+            .status;
+
+        if !status.success() {
+            anyhow::bail!("ffmpeg failed to extract video window");
+        }
+
+        Ok(())
+    }
+
     /// Extract thumbnail frames from video at 1 second intervals
     /// Saves thumbnails as JPEG 160x90 to the specified output directory
     /// Returns the directory path where thumbnails were saved
@@ -204,7 +850,7 @@ impl FFmpegWrapper {
         let output_pattern_str = output_pattern.to_str()
             .ok_or_else(|| anyhow::anyhow!("Invalid output path"))?;
 
-        let status = Command::new("ffmpeg")
+        let status = ffmpeg()
             .args(&[
                 "-i",
                 input_path.to_str().unwrap(),
@@ -216,8 +862,7 @@ impl FFmpegWrapper {
                 output_pattern_str,
             ])
             .output()
-            .await
-            .context("Failed to execute ffmpeg for thumbnail extraction")?
+            .await?
             .status;
 
         if !status.success() {
@@ -229,4 +874,41 @@ impl FFmpegWrapper {
             .ok_or_else(|| anyhow::anyhow!("Invalid output directory path"))?
             .to_string())
     }
+
+    /// Extract one thumbnail every `interval_ms`, scaled to 160x90 like the base strip, into
+    /// `output_dir` as `pattern` (numbered from 0)
+    pub async fn extract_thumbnails_at_interval(
+        input_path: &Path,
+        output_dir: &Path,
+        interval_ms: i64,
+        pattern: &str,
+    ) -> Result<()> {
+        tokio::fs::create_dir_all(output_dir).await?;
+
+        let output_pattern = output_dir.join(pattern);
+        let output_pattern_str = output_pattern.to_str()
+            .ok_or_else(|| anyhow::anyhow!("Invalid output path"))?;
+
+        let status = ffmpeg()
+            .args([
+                "-i",
+                input_path.to_str().unwrap(),
+                "-vf",
+                &format!("fps=1000/{},scale=160:90", interval_ms),
+                "-q:v",
+                "2",
+                "-start_number",
+                "0",
+                "-y",
+                output_pattern_str,
+            ])
+            .output()
+            .await?
+            .status;
+
+        if !status.success() {
+            anyhow::bail!("ffmpeg failed to extract {}ms thumbnails", interval_ms);
+        }
+        Ok(())
+    }
 }
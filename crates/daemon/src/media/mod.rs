@@ -1,4 +1,19 @@
+pub mod availability;
+pub mod av_sync;
+pub mod beat_template;
+pub mod companion;
+pub mod conform;
 pub mod ffmpeg;
+pub mod music_structure;
+pub mod process;
+pub mod proxy;
+pub mod remote;
+pub mod roots;
+pub mod screen_recording;
+pub mod segment_kind;
+pub mod slow_motion;
+pub mod still;
+pub mod thumbnails;
 
 use anyhow::Result;
 use sha2::{Digest, Sha256};
@@ -0,0 +1,247 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::media::beat_template::{detect_music_drops, BeatTemplate, TemplateSection};
+use crate::media::ffmpeg::FFmpegWrapper;
+
+/// Energy (0-1, relative to the track's own range) at or above which a stretch is loud...
+const HIGH_ENERGY: f64 = 0.6;
+/// ...and below which it's a break
+const LOW_ENERGY: f64 = 0.4;
+/// Stretches shorter than this join the section before them
+const MIN_SECTION_SECONDS: f64 = 4.0;
+/// The intro and outro never take more than this share of the track each
+const MAX_INTRO_SHARE: f64 = 0.3;
+/// An accent is a rise of at least this many LU within the lookback window (smaller than a drop)
+const ACCENT_RISE_LU: f64 = 3.0;
+const ACCENT_LOOKBACK_SECONDS: f64 = 1.0;
+/// Minimum spacing between accents, and between an accent and a section boundary
+const ACCENT_MIN_SPACING_SECONDS: f64 = 2.0;
+/// Cut rate (per 10s) of a section at zero and full energy when the tempo isn't known
+const QUIET_CUTS_PER_10S: f64 = 1.0;
+const LOUD_CUTS_PER_10S: f64 = 5.0;
+
+/// How a music track is built: its energy over time, the drops and accents a cut should land
+/// on, and the sections those split it into
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MusicStructure {
+    pub duration_sec: f64,
+    /// (second, energy 0-1), one per second
+    pub energy: Vec<(f64, f64)>,
+    /// Sharp loudness jumps, in seconds
+    pub drops: Vec<f64>,
+    /// Smaller accents inside sections where an impact cut lands, in seconds
+    pub accents: Vec<f64>,
+    pub sections: Vec<MusicSection>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MusicSection {
+    /// "intro", "body", "drop" (starts on a drop), "break" (a quiet stretch) or "outro"
+    pub kind: String,
+    pub start_sec: f64,
+    pub end_sec: f64,
+    /// Mean energy over the section
+    pub energy: f64,
+}
+
+/// Analyze a track's loudness into its structure
+pub async fn analyze_music(path: &Path, duration_sec: f64) -> Result<MusicStructure> {
+    let loudness = FFmpegWrapper::loudness_timeline(path).await?;
+    Ok(structure_from_loudness(&loudness, duration_sec))
+}
+
+/// Structure of a track from its short-term loudness, as (second, LUFS). The intro runs until
+/// the energy first gets high (or the first drop), the outro from where it last drops off;
+/// in between, drops and sustained loud or quiet stretches start new sections.
+pub fn structure_from_loudness(loudness: &[(f64, f64)], duration_sec: f64) -> MusicStructure {
+    let energy = energy_curve(loudness, duration_sec);
+    let drops = detect_music_drops(loudness);
+    let level = |second: f64| {
+        energy
+            .iter()
+            .find(|(t, _)| *t >= second)
+            .map(|(_, e)| *e)
+            .unwrap_or(0.0)
+    };
+
+    let first_loud = energy.iter().find(|(_, e)| *e >= HIGH_ENERGY).map(|(t, _)| *t);
+    let intro_end = first_loud
+        .into_iter()
+        .chain(drops.first().copied())
+        .fold(duration_sec, f64::min)
+        .min(duration_sec * MAX_INTRO_SHARE);
+    let last_loud = energy.iter().rev().find(|(_, e)| *e >= HIGH_ENERGY).map(|(t, _)| *t + 1.0);
+    let outro_start = last_loud
+        .unwrap_or(duration_sec)
+        .max(duration_sec * (1.0 - MAX_INTRO_SHARE))
+        .max(intro_end);
+
+    // Section starts in the middle of the track: drops, and each change between loud, quiet
+    // and in-between that lasts
+    let class = |e: f64| {
+        if e >= HIGH_ENERGY {
+            2
+        } else if e < LOW_ENERGY {
+            0
+        } else {
+            1
+        }
+    };
+    let mut starts: Vec<(f64, &str)> = vec![(0.0, "intro"), (intro_end, "body")];
+    let mut current = class(level(intro_end));
+    for (t, e) in energy.iter().filter(|(t, _)| *t > intro_end && *t < outro_start) {
+        let next = class(*e);
+        let lasts = energy
+            .iter()
+            .filter(|(u, _)| *u >= *t && *u < t + MIN_SECTION_SECONDS)
+            .all(|(_, e)| class(*e) == next);
+        if next != current && lasts {
+            starts.push((*t, if next == 0 { "break" } else { "body" }));
+            current = next;
+        }
+    }
+    for drop in drops.iter().filter(|d| **d > intro_end && **d < outro_start) {
+        starts.retain(|(t, _)| *t <= intro_end || (t - drop).abs() >= MIN_SECTION_SECONDS / 2.0);
+        starts.push((*drop, "drop"));
+    }
+    starts.push((outro_start, "outro"));
+    starts.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    // Short sections fold into the one before them
+    let mut sections: Vec<MusicSection> = Vec::new();
+    for (idx, (start, kind)) in starts.iter().enumerate() {
+        let end = starts.get(idx + 1).map(|(s, _)| *s).unwrap_or(duration_sec);
+        if end <= *start {
+            continue;
+        }
+        match sections.last_mut() {
+            Some(last) if end - start < MIN_SECTION_SECONDS && *kind != "outro" => last.end_sec = end,
+            _ => sections.push(MusicSection {
+                kind: kind.to_string(),
+                start_sec: *start,
+                end_sec: end,
+                energy: 0.0,
+            }),
+        }
+    }
+    for section in &mut sections {
+        let inside: Vec<f64> = energy
+            .iter()
+            .filter(|(t, _)| *t >= section.start_sec && *t < section.end_sec)
+            .map(|(_, e)| *e)
+            .collect();
+        section.energy = if inside.is_empty() { 0.0 } else { inside.iter().sum::<f64>() / inside.len() as f64 };
+    }
+
+    let boundaries: Vec<f64> = sections.iter().map(|s| s.start_sec).collect();
+    let accents = detect_accents(loudness)
+        .into_iter()
+        .filter(|a| boundaries.iter().all(|b| (a - b).abs() >= ACCENT_MIN_SPACING_SECONDS))
+        .collect();
+
+    MusicStructure {
+        duration_sec,
+        energy,
+        drops,
+        accents,
+        sections,
+    }
+}
+
+impl MusicStructure {
+    /// The track's sections as a beat template the planner cuts to in real time. Louder
+    /// sections cut faster: with a known tempo, every 8 beats when quiet, 4 in between and 2
+    /// when loud; otherwise between 1 and 5 cuts per 10s by energy.
+    pub fn beat_template(&self, bpm: Option<f64>) -> BeatTemplate {
+        let cut_rate = |energy: f64| match bpm.filter(|b| *b > 0.0) {
+            Some(bpm) => {
+                let beats_per_cut = if energy >= HIGH_ENERGY {
+                    2.0
+                } else if energy >= LOW_ENERGY {
+                    4.0
+                } else {
+                    8.0
+                };
+                10.0 / (beats_per_cut * 60.0 / bpm)
+            }
+            None => QUIET_CUTS_PER_10S + (LOUD_CUTS_PER_10S - QUIET_CUTS_PER_10S) * energy,
+        };
+        let sections: Vec<TemplateSection> = self
+            .sections
+            .iter()
+            .map(|s| TemplateSection {
+                section_type: s.kind.clone(),
+                start_sec: s.start_sec,
+                end_sec: s.end_sec,
+                cuts_per_10s: cut_rate(s.energy),
+            })
+            .collect();
+        let cuts: f64 = sections.iter().map(|s| s.cuts_per_10s * (s.end_sec - s.start_sec)).sum();
+
+        BeatTemplate {
+            source_asset_id: None,
+            duration_sec: self.duration_sec,
+            hook_length_sec: self
+                .sections
+                .first()
+                .filter(|s| s.kind == "intro")
+                .map(|s| s.end_sec)
+                .unwrap_or(0.0),
+            cuts_per_10s: if self.duration_sec > 0.0 { cuts / self.duration_sec } else { 0.0 },
+            music_drops: self.drops.clone(),
+            sections,
+            impacts: self.accents.clone(),
+            follows_music: true,
+        }
+    }
+}
+
+/// Mean energy per second, scaled so the track's quiet floor (10th percentile) is 0 and its
+/// loud ceiling (95th percentile) is 1
+fn energy_curve(loudness: &[(f64, f64)], duration_sec: f64) -> Vec<(f64, f64)> {
+    let seconds = duration_sec.ceil().max(0.0) as usize;
+    let mut levels: Vec<(f64, f64)> = (0..seconds)
+        .filter_map(|second| {
+            let second = second as f64;
+            let inside: Vec<f64> = loudness
+                .iter()
+                .filter(|(t, l)| *t >= second && *t < second + 1.0 && l.is_finite())
+                .map(|(_, l)| *l)
+                .collect();
+            (!inside.is_empty()).then(|| (second, inside.iter().sum::<f64>() / inside.len() as f64))
+        })
+        .collect();
+    let mut sorted: Vec<f64> = levels.iter().map(|(_, l)| *l).collect();
+    sorted.sort_by(f64::total_cmp);
+    let percentile = |p: f64| sorted.get(((sorted.len() as f64 - 1.0) * p).round() as usize).copied();
+    let (Some(floor), Some(ceiling)) = (percentile(0.1), percentile(0.95)) else {
+        return Vec::new();
+    };
+    let range = (ceiling - floor).max(1.0);
+    for (_, level) in &mut levels {
+        *level = ((*level - floor) / range).clamp(0.0, 1.0);
+    }
+    levels
+}
+
+/// Rises in loudness too small to be drops but sharp enough to cut on
+fn detect_accents(loudness: &[(f64, f64)]) -> Vec<f64> {
+    let mut accents: Vec<f64> = Vec::new();
+    for (idx, (t, level)) in loudness.iter().enumerate() {
+        if accents.last().is_some_and(|last| t - last < ACCENT_MIN_SPACING_SECONDS) {
+            continue;
+        }
+        let lookback_min = loudness[..idx]
+            .iter()
+            .rev()
+            .take_while(|(prev_t, _)| t - prev_t <= ACCENT_LOOKBACK_SECONDS)
+            .map(|(_, prev_level)| *prev_level)
+            .fold(f64::INFINITY, f64::min);
+        if lookback_min.is_finite() && level - lookback_min >= ACCENT_RISE_LU {
+            accents.push(*t);
+        }
+    }
+    accents
+}
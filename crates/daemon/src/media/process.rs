@@ -0,0 +1,72 @@
+//! The media tool runner (see the media-process crate) with the daemon's additions: each run
+//! goes under the current job's resource limits, and simulation mode answers instead of the
+//! real tools.
+
+use anyhow::Result;
+use std::process::Output;
+use std::time::{Duration, Instant};
+
+pub use media_process::{check_args, jail_dir, stderr_tail, Failure, ProcessFailed, Tool};
+
+use crate::jobs::resources;
+
+/// One run of a media tool. Build it like a `Command`, then call [`Invocation::output`].
+pub struct Invocation {
+    tool: Tool,
+    args: Vec<String>,
+    timeout: Duration,
+}
+
+impl Invocation {
+    pub fn new(tool: Tool) -> Self {
+        Invocation {
+            tool,
+            args: Vec::new(),
+            timeout: tool.default_timeout(),
+        }
+    }
+
+    pub fn arg(mut self, arg: impl AsRef<str>) -> Self {
+        self.args.push(arg.as_ref().to_string());
+        self
+    }
+
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.args.extend(args.into_iter().map(|a| a.as_ref().to_string()));
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Check the arguments, run the tool (under the current job's resource limits) and collect
+    /// its output. Fails with [`ProcessFailed`] when the arguments are refused, the tool can't
+    /// start, runs out of time or writes too much; an unsuccessful exit is returned as is, and
+    /// logged with the end of stderr.
+    pub async fn output(self) -> Result<Output> {
+        let program = self.tool.program();
+        let args = media_process::checked_args(self.tool, self.args)?;
+        if crate::simulation::enabled() {
+            return crate::simulation::media::run(self.tool, &args);
+        }
+
+        let started = Instant::now();
+        let output = media_process::run(self.tool, resources::command(program), &args, self.timeout).await?;
+        if !output.status.success() {
+            eprintln!(
+                "[PROCESS] {} exited with {:?} after {:.1}s: {}",
+                program,
+                output.status.code(),
+                started.elapsed().as_secs_f64(),
+                stderr_tail(&output.stderr).join(" | ")
+            );
+        }
+        Ok(output)
+    }
+}
@@ -0,0 +1,98 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Default ladder: small scrub proxy generated at import, full preview generated on demand
+const DEFAULT_PROXY_LADDER: &str = "scrub:360:28,preview:1080:23";
+
+/// One rung of the proxy ladder
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ProxyTier {
+    pub name: String,
+    pub max_height: i32,
+    pub crf: u8,
+    /// Eager tiers are generated at import; the rest are generated lazily on first request
+    pub eager: bool,
+}
+
+/// Parse the proxy ladder from PROXY_LADDER (e.g. "scrub:360:28,preview:1080:23").
+/// Tiers are ordered from lowest to highest resolution; the first tier is always eager.
+pub fn proxy_ladder() -> Vec<ProxyTier> {
+    let ladder_str = std::env::var("PROXY_LADDER")
+        .unwrap_or_else(|_| DEFAULT_PROXY_LADDER.to_string());
+
+    let mut tiers = parse_ladder(&ladder_str);
+    if tiers.is_empty() {
+        eprintln!("[PROXY] Invalid PROXY_LADDER '{}', using default", ladder_str);
+        tiers = parse_ladder(DEFAULT_PROXY_LADDER);
+    }
+    tiers
+}
+
+fn parse_ladder(ladder_str: &str) -> Vec<ProxyTier> {
+    let mut tiers: Vec<ProxyTier> = ladder_str
+        .split(',')
+        .filter_map(|entry| {
+            let parts: Vec<&str> = entry.trim().split(':').collect();
+            let name = parts.first()?.trim();
+            if name.is_empty() {
+                return None;
+            }
+            let max_height = parts.get(1)?.trim().parse::<i32>().ok().filter(|h| *h > 0)?;
+            let crf = parts
+                .get(2)
+                .and_then(|c| c.trim().parse::<u8>().ok())
+                .unwrap_or(23);
+            Some(ProxyTier {
+                name: name.to_string(),
+                max_height,
+                crf,
+                eager: false,
+            })
+        })
+        .collect();
+
+    tiers.sort_by_key(|t| t.max_height);
+    if let Some(first) = tiers.first_mut() {
+        first.eager = true;
+    }
+    tiers
+}
+
+/// Look up a tier by name in the configured ladder
+pub fn find_tier(name: &str) -> Option<ProxyTier> {
+    proxy_ladder().into_iter().find(|t| t.name == name)
+}
+
+/// Names of tiers that should be generated at import time
+pub fn eager_tier_names() -> Vec<String> {
+    proxy_ladder()
+        .into_iter()
+        .filter(|t| t.eager)
+        .map(|t| t.name)
+        .collect()
+}
+
+/// Highest tier in the ladder, used for playback when no tier is requested
+pub fn default_playback_tier() -> ProxyTier {
+    proxy_ladder()
+        .pop()
+        .expect("proxy ladder always has at least one tier")
+}
+
+/// Scale source dimensions down to fit max_height, preserving aspect ratio.
+/// Never upscales; dimensions are rounded to even numbers for libx264.
+pub fn proxy_dimensions(src_width: i32, src_height: i32, max_height: i32) -> (i32, i32) {
+    if src_width <= 0 || src_height <= 0 {
+        return (src_width.max(2), src_height.max(2));
+    }
+    let height = src_height.min(max_height);
+    let width = (src_width as f64 * height as f64 / src_height as f64).round() as i32;
+    ((width / 2 * 2).max(2), (height / 2 * 2).max(2))
+}
+
+/// Proxy file path for an asset/tier inside the cache directory
+pub fn proxy_path(cache_dir: &Path, asset_id: i64, tier: &str) -> PathBuf {
+    cache_dir
+        .join("proxies")
+        .join(format!("proxy_{}_{}.mp4", asset_id, tier))
+}
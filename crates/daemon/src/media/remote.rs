@@ -0,0 +1,170 @@
+use anyhow::{anyhow, Context, Result};
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+
+/// Video file extensions picked up when importing a folder or bucket prefix
+pub const VIDEO_EXTENSIONS: &[&str] = &["mp4", "mov", "avi", "mkv", "m4v", "webm"];
+
+/// Object storage the importer can download from. Downloads go through the provider's CLI
+/// (`aws`, `gcloud`), so they use whatever credentials that CLI is configured with.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RemoteScheme {
+    S3,
+    Gcs,
+}
+
+/// A parsed `s3://bucket/key` or `gs://bucket/key` URL
+#[derive(Debug, Clone)]
+pub struct RemoteObject {
+    pub scheme: RemoteScheme,
+    pub bucket: String,
+    pub key: String,
+}
+
+impl RemoteObject {
+    pub fn parse(url: &str) -> Option<Self> {
+        let (scheme, rest) = if let Some(rest) = url.strip_prefix("s3://") {
+            (RemoteScheme::S3, rest)
+        } else if let Some(rest) = url.strip_prefix("gs://") {
+            (RemoteScheme::Gcs, rest)
+        } else {
+            return None;
+        };
+        let (bucket, key) = rest.split_once('/').unwrap_or((rest, ""));
+        if bucket.is_empty() {
+            return None;
+        }
+        Some(RemoteObject {
+            scheme,
+            bucket: bucket.to_string(),
+            key: key.to_string(),
+        })
+    }
+
+    pub fn url(&self) -> String {
+        let prefix = match self.scheme {
+            RemoteScheme::S3 => "s3",
+            RemoteScheme::Gcs => "gs",
+        };
+        format!("{}://{}/{}", prefix, self.bucket, self.key)
+    }
+
+    /// Where the object is cached locally: `<cache_dir>/remote/<scheme>/<bucket>/<key>`
+    pub fn cache_path(&self, cache_dir: &Path) -> PathBuf {
+        let scheme = match self.scheme {
+            RemoteScheme::S3 => "s3",
+            RemoteScheme::Gcs => "gs",
+        };
+        let mut path = cache_dir.join("remote").join(scheme).join(&self.bucket);
+        // Keys are untrusted; keep only normal path components
+        for part in self.key.split('/').filter(|p| !p.is_empty() && *p != "." && *p != "..") {
+            path.push(part);
+        }
+        path
+    }
+}
+
+pub fn is_remote_url(path: &str) -> bool {
+    RemoteObject::parse(path).is_some()
+}
+
+/// Download an object into the cache (skipped when it's already there) and return the local
+/// path. Streams to a `.partial` file first so an interrupted download is never mistaken for
+/// a complete one.
+pub async fn fetch_to_cache(url: &str, cache_dir: &Path) -> Result<PathBuf> {
+    let object = RemoteObject::parse(url).ok_or_else(|| anyhow!("Not an s3:// or gs:// URL: {}", url))?;
+    let dest = object.cache_path(cache_dir);
+    if dest.is_file() {
+        return Ok(dest);
+    }
+    download(&object, &dest).await?;
+    Ok(dest)
+}
+
+/// Download an object to `dest`, replacing whatever is there
+pub async fn download(object: &RemoteObject, dest: &Path) -> Result<()> {
+    if let Some(parent) = dest.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    let partial = dest.with_extension(match dest.extension().and_then(|e| e.to_str()) {
+        Some(ext) => format!("{}.partial", ext),
+        None => "partial".to_string(),
+    });
+    let url = object.url();
+    let partial_str = partial.to_string_lossy().to_string();
+    let args: Vec<&str> = match object.scheme {
+        RemoteScheme::S3 => vec!["s3", "cp", &url, &partial_str, "--only-show-errors"],
+        RemoteScheme::Gcs => vec!["storage", "cp", &url, &partial_str],
+    };
+    run_cli(object.scheme, &args).await?;
+    tokio::fs::rename(&partial, dest).await?;
+    Ok(())
+}
+
+/// Video objects under a prefix (recursively), as URLs
+pub async fn list_videos(prefix_url: &str) -> Result<Vec<String>> {
+    let mut prefix = RemoteObject::parse(prefix_url).ok_or_else(|| anyhow!("Not an s3:// or gs:// URL: {}", prefix_url))?;
+    // A folder, not every key that starts with the same characters
+    if !prefix.key.is_empty() && !prefix.key.ends_with('/') {
+        prefix.key.push('/');
+    }
+    let urls: Vec<String> = match prefix.scheme {
+        RemoteScheme::S3 => run_cli(prefix.scheme, &["s3", "ls", "--recursive", &prefix.url()])
+            .await?
+            .lines()
+            .filter_map(s3_listing_key)
+            .map(|key| format!("s3://{}/{}", prefix.bucket, key))
+            .collect(),
+        RemoteScheme::Gcs => {
+            let pattern = format!("{}/**", prefix.url().trim_end_matches('/'));
+            run_cli(prefix.scheme, &["storage", "ls", &pattern])
+                .await?
+                .lines()
+                .map(str::trim)
+                .filter(|line| line.starts_with("gs://") && !line.ends_with('/'))
+                .map(String::from)
+                .collect()
+        }
+    };
+    Ok(urls
+        .into_iter()
+        .filter(|url| {
+            Path::new(url)
+                .extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| VIDEO_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+        })
+        .collect())
+}
+
+/// Key from an `aws s3 ls --recursive` line ("2024-01-01 12:00:00       1234 path/to/key.mp4");
+/// keys may contain spaces, so everything after the third column is the key
+fn s3_listing_key(line: &str) -> Option<&str> {
+    let mut rest = line.trim_start();
+    for _ in 0..3 {
+        let end = rest.find(char::is_whitespace)?;
+        rest = rest[end..].trim_start();
+    }
+    Some(rest.trim_end()).filter(|key| !key.is_empty())
+}
+
+async fn run_cli(scheme: RemoteScheme, args: &[&str]) -> Result<String> {
+    let program = match scheme {
+        RemoteScheme::S3 => "aws",
+        RemoteScheme::Gcs => "gcloud",
+    };
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .await
+        .with_context(|| format!("Failed to run {}. Make sure it's installed and configured.", program))?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "{} {} failed: {}",
+            program,
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
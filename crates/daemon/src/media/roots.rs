@@ -0,0 +1,123 @@
+//! Allowed media roots: the directories paths sent to the API may point into. MEDIA_ROOTS is a
+//! list of directories separated like PATH (`:` on Unix); when it's set, every path an endpoint
+//! accepts (media to import, relink targets, music, voiceovers, brand assets, end card files,
+//! template music, project cache directories, export destinations) must resolve inside one of
+//! them. Paths are canonicalized first, so symlinks can't lead out of a root, and `..`
+//! components are refused outright.
+//! Without MEDIA_ROOTS any path is accepted, as on a single-user desktop.
+
+use serde::Serialize;
+use std::ffi::OsString;
+use std::fmt;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::sync::OnceLock;
+
+static ROOTS: OnceLock<Option<Vec<PathBuf>>> = OnceLock::new();
+
+/// Canonical allowed roots, or None when any path is allowed. A MEDIA_ROOTS whose entries all
+/// fail to resolve allows nothing rather than everything.
+pub fn allowed_roots() -> Option<&'static [PathBuf]> {
+    ROOTS
+        .get_or_init(|| {
+            let value = std::env::var_os("MEDIA_ROOTS").filter(|v| !v.is_empty())?;
+            let roots: Vec<PathBuf> = std::env::split_paths(&value)
+                .filter(|root| !root.as_os_str().is_empty())
+                .filter_map(|root| match canonical(&absolute(&root)) {
+                    Ok(root) => Some(root),
+                    Err(e) => {
+                        eprintln!("[ROOTS] Ignoring media root {}: {}", root.display(), e);
+                        None
+                    }
+                })
+                .collect();
+            eprintln!("[ROOTS] Paths are limited to {:?}", roots);
+            Some(roots)
+        })
+        .as_deref()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Refusal {
+    /// Empty, or containing a NUL byte
+    Invalid,
+    /// Has a `..` component
+    Traversal,
+    /// Resolves outside every allowed root
+    OutsideRoots,
+}
+
+/// A path an endpoint won't touch. The API answers 400 for an unusable path and 403 for one
+/// that's refused, with the path and the allowed roots.
+#[derive(Debug, Clone, Serialize)]
+pub struct PathNotAllowed {
+    pub path: String,
+    pub reason: Refusal,
+}
+
+impl fmt::Display for PathNotAllowed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.reason {
+            Refusal::Invalid => write!(f, "Path not allowed: {:?} isn't a usable path", self.path),
+            Refusal::Traversal => write!(f, "Path not allowed: {} contains '..'", self.path),
+            Refusal::OutsideRoots => write!(f, "Path not allowed: {} is outside the allowed media roots", self.path),
+        }
+    }
+}
+
+impl std::error::Error for PathNotAllowed {}
+
+/// Relative paths are taken from the daemon's working directory
+fn absolute(path: &Path) -> PathBuf {
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir().map(|cwd| cwd.join(path)).unwrap_or_else(|_| path.to_path_buf())
+    }
+}
+
+/// Canonical form of a path that may not exist yet (an export destination, a new cache
+/// directory): its deepest existing ancestor is canonicalized and the rest appended
+fn canonical(path: &Path) -> io::Result<PathBuf> {
+    let mut existing = path;
+    let mut missing: Vec<OsString> = Vec::new();
+    loop {
+        match std::fs::canonicalize(existing) {
+            Ok(mut resolved) => {
+                resolved.extend(missing.iter().rev());
+                return Ok(resolved);
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => match (existing.parent(), existing.file_name()) {
+                (Some(parent), Some(name)) => {
+                    missing.push(name.to_os_string());
+                    existing = parent;
+                }
+                _ => return Err(e),
+            },
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Resolve a path sent to the API: refuses `..`, canonicalizes (through symlinks) and, when
+/// MEDIA_ROOTS is set, checks the result is inside an allowed root. The canonical path is what
+/// should be used and stored from then on.
+pub fn resolve(path: impl AsRef<Path>) -> Result<PathBuf, PathNotAllowed> {
+    let path = path.as_ref();
+    let refused = |reason| PathNotAllowed {
+        path: path.to_string_lossy().to_string(),
+        reason,
+    };
+    if path.as_os_str().is_empty() || path.as_os_str().as_encoded_bytes().contains(&0) {
+        return Err(refused(Refusal::Invalid));
+    }
+    if path.components().any(|c| c == Component::ParentDir) {
+        return Err(refused(Refusal::Traversal));
+    }
+    let resolved = canonical(&absolute(path)).map_err(|_| refused(Refusal::Invalid))?;
+    match allowed_roots() {
+        Some(roots) if !roots.iter().any(|root| resolved.starts_with(root)) => Err(refused(Refusal::OutsideRoots)),
+        _ => Ok(resolved),
+    }
+}
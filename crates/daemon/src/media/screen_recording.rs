@@ -0,0 +1,218 @@
+use anyhow::Result;
+use serde::Deserialize;
+use std::path::Path;
+
+use crate::db::Database;
+use crate::media::ffmpeg::FFmpegWrapper;
+
+use crate::llm::ml_service_url;
+
+/// Tag kind for a screen segment's activity ("slide" or "activity")
+pub const SCREEN_TAG_KIND: &str = "screen";
+/// Tag kind for the application window title read from a screen segment
+pub const WINDOW_TAG_KIND: &str = "window";
+
+/// Frames sampled per second when measuring how the picture changes
+const SAMPLE_FPS: f64 = 2.0;
+/// Below this mean luma difference two samples are the same picture (only compression noise).
+/// Rendered screens hold pixel-identical frames; even a locked-off camera has sensor noise.
+const STILL_DIFFERENCE: f64 = 0.25;
+/// At or above this most of the screen changed: a new slide, window or page
+const SLIDE_CHANGE_DIFFERENCE: f64 = 6.0;
+/// Share of pixel-identical samples that marks a recording as a screen capture
+const SCREEN_STILL_SHARE: f64 = 0.5;
+/// Fewer samples than this say too little about the footage; only the file name decides
+const MIN_SAMPLES: usize = 10;
+/// A pause this long ends a burst of cursor or typing activity; the next burst starts a segment
+const IDLE_GAP_SECONDS: f64 = 3.0;
+const MIN_SEGMENT_SECONDS: f64 = 2.0;
+/// Long unbroken stretches (a slide talked over for minutes) are split evenly
+const MAX_SEGMENT_SECONDS: f64 = 60.0;
+/// Share of changing samples above which a segment is activity rather than a static slide
+const ACTIVITY_SHARE: f64 = 0.2;
+
+/// File names screen recorders give their output (macOS, iOS, Android, GNOME)
+const FILENAME_HINTS: &[&str] = &["screen recording", "screenrecording", "screencast", "screen capture"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScreenActivity {
+    /// The screen mostly holds still (a slide or page being talked over)
+    Slide,
+    /// Cursor movement, typing or scrolling
+    Activity,
+}
+
+impl ScreenActivity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScreenActivity::Slide => "slide",
+            ScreenActivity::Activity => "activity",
+        }
+    }
+}
+
+/// A stretch of a screen recording, in source seconds
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScreenSegment {
+    pub start: f64,
+    pub end: f64,
+    pub activity: ScreenActivity,
+}
+
+/// Detect whether a video is a screen recording and, if it is, cut it at slide changes and
+/// bursts of cursor activity. Returns None for camera footage.
+pub async fn analyze(path: &Path, duration_seconds: f64) -> Result<Option<Vec<ScreenSegment>>> {
+    let differences = FFmpegWrapper::frame_differences(path, SAMPLE_FPS).await?;
+    if !is_screen_recording(path, &differences) {
+        return Ok(None);
+    }
+    Ok(Some(plan_segments(&differences, duration_seconds)))
+}
+
+/// A screen recorder's file name, or footage that mostly holds pixel-identical frames
+pub fn is_screen_recording(path: &Path, differences: &[(f64, f64)]) -> bool {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_lowercase().replace(['_', '-'], " "))
+        .unwrap_or_default();
+    if FILENAME_HINTS.iter().any(|hint| name.contains(hint)) {
+        return true;
+    }
+    if differences.len() < MIN_SAMPLES {
+        return false;
+    }
+    let still = differences.iter().filter(|(_, d)| *d < STILL_DIFFERENCE).count();
+    still as f64 / differences.len() as f64 >= SCREEN_STILL_SHARE
+}
+
+/// Cut a screen recording at slide changes and at the start of each burst of activity that
+/// follows a pause, then merge slivers and split overlong stretches
+pub fn plan_segments(differences: &[(f64, f64)], duration_seconds: f64) -> Vec<ScreenSegment> {
+    if duration_seconds <= 0.0 {
+        return Vec::new();
+    }
+
+    let sample_interval = 1.0 / SAMPLE_FPS;
+    let mut boundaries = vec![0.0];
+    let mut in_change = false;
+    let mut last_active: Option<f64> = None;
+    for &(t, difference) in differences {
+        if difference >= SLIDE_CHANGE_DIFFERENCE {
+            // A transition spanning several samples (scrolling, animation) is one change
+            if !in_change {
+                boundaries.push(t);
+            }
+            in_change = true;
+            last_active = Some(t);
+            continue;
+        }
+        in_change = false;
+        if difference >= STILL_DIFFERENCE {
+            // The change happened since the previous sample; start just before it
+            if last_active.is_none_or(|last| t - last >= IDLE_GAP_SECONDS) {
+                boundaries.push((t - sample_interval).max(0.0));
+            }
+            last_active = Some(t);
+        }
+    }
+    boundaries.retain(|b| *b < duration_seconds);
+    boundaries.sort_by(|a, b| a.total_cmp(b));
+    boundaries.dedup_by(|a, b| (*a - *b).abs() < f64::EPSILON);
+
+    // Ranges between boundaries, slivers folded into the range before them
+    let mut ranges: Vec<(f64, f64)> = Vec::new();
+    for (i, start) in boundaries.iter().enumerate() {
+        let end = boundaries.get(i + 1).copied().unwrap_or(duration_seconds);
+        match ranges.last_mut() {
+            Some(previous) if end - start < MIN_SEGMENT_SECONDS => previous.1 = end,
+            _ => ranges.push((*start, end)),
+        }
+    }
+    // A sliver at the very start has nothing before it; fold it into the next range
+    if ranges.len() > 1 && ranges[0].1 - ranges[0].0 < MIN_SEGMENT_SECONDS {
+        let first = ranges.remove(0);
+        ranges[0].0 = first.0;
+    }
+
+    ranges
+        .into_iter()
+        .flat_map(|(start, end)| {
+            let parts = ((end - start) / MAX_SEGMENT_SECONDS).ceil().max(1.0) as usize;
+            let length = (end - start) / parts as f64;
+            (0..parts).map(move |i| {
+                let part_end = if i + 1 == parts { end } else { start + length * (i + 1) as f64 };
+                (start + length * i as f64, part_end)
+            })
+        })
+        .map(|(start, end)| ScreenSegment {
+            start,
+            end,
+            activity: classify(differences, start, end),
+        })
+        .collect()
+}
+
+/// Slide when the picture mostly holds still between its changes, activity otherwise
+fn classify(differences: &[(f64, f64)], start: f64, end: f64) -> ScreenActivity {
+    let samples: Vec<f64> = differences
+        .iter()
+        .filter(|(t, _)| *t > start && *t <= end)
+        .map(|(_, d)| *d)
+        .collect();
+    let changing = samples
+        .iter()
+        .filter(|d| **d >= STILL_DIFFERENCE && **d < SLIDE_CHANGE_DIFFERENCE)
+        .count();
+    if !samples.is_empty() && changing as f64 / samples.len() as f64 >= ACTIVITY_SHARE {
+        ScreenActivity::Activity
+    } else {
+        ScreenActivity::Slide
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct WindowTitlesResponse {
+    titles: Vec<WindowTitle>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WindowTitle {
+    title: Option<String>,
+}
+
+/// OCR the window title at the middle of each segment through the ML service and tag the
+/// segment with it. Segments whose title can't be read keep no window tag.
+pub async fn tag_window_titles(db: &Database, path: &Path, segments: &[(i64, ScreenSegment)]) -> Result<usize> {
+    let media_path = std::fs::canonicalize(path)?;
+    let times: Vec<f64> = segments.iter().map(|(_, s)| (s.start + s.end) / 2.0).collect();
+    let response = reqwest::Client::new()
+        .post(format!("{}/screen/window_titles", ml_service_url()))
+        .json(&serde_json::json!({
+            "mediaPath": media_path.to_string_lossy(),
+            "times": times,
+        }))
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Err(anyhow::anyhow!("ML service window title OCR failed: {}", response.status()));
+    }
+    let read: WindowTitlesResponse = response.json().await?;
+
+    let mut tagged = 0;
+    for ((segment_id, _), title) in segments.iter().zip(read.titles) {
+        let tags: Vec<serde_json::Value> = title
+            .title
+            .into_iter()
+            .map(|label| serde_json::json!({
+                "label": label,
+                "kind": WINDOW_TAG_KIND,
+                "confidence": 1.0,
+            }))
+            .collect();
+        if !tags.is_empty() {
+            tagged += 1;
+        }
+        db.replace_segment_tags(*segment_id, &[WINDOW_TAG_KIND], &tags)?;
+    }
+    Ok(tagged)
+}
@@ -0,0 +1,154 @@
+use serde::{Deserialize, Serialize};
+
+use crate::db::Segment;
+
+/// Segments without speech shorter than this are the gaps between shots: whip pans, passing
+/// blur, the camera being raised
+const TRANSITION_MAX_SECONDS: f64 = 1.0;
+/// Mean frame difference of footage in motion (matches the cutoff energy and slow motion use)
+const HIGH_MOTION_SCORE: f64 = 50.0;
+/// Share of an asset's spoken segments ending in a question that marks it as an interview
+const INTERVIEW_QUESTION_SHARE: f64 = 0.2;
+/// Fewer spoken segments than this say too little about whether an asset is an interview
+const MIN_INTERVIEW_SEGMENTS: usize = 3;
+
+/// Vision action tags of footage in motion
+const ACTION_TAGS: &[&str] = &["running", "dancing", "driving", "playing sports", "walking"];
+/// Vision object tags of landscapes and places rather than people
+const SCENERY_TAGS: &[&str] = &["tree", "water", "beach", "mountain", "sky", "building"];
+
+/// What a segment shows, as the planner, search filters and style profiles treat it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SegmentKind {
+    /// Someone talking to the camera
+    TalkingHead,
+    /// Cutaway footage without anyone talking on screen (also what voiceover plays over)
+    #[serde(alias = "broll")]
+    BRoll,
+    /// Fast movement: sports, dancing, driving
+    Action,
+    /// Landscapes and places, held or slowly moving
+    Scenery,
+    /// Captured from a computer or phone screen
+    #[serde(alias = "screen_recording")]
+    Screen,
+    /// Someone answering questions on camera
+    Interview,
+    /// A moment between shots (whip pans, the camera moving into place)
+    Transition,
+}
+
+impl SegmentKind {
+    pub const ALL: [SegmentKind; 7] = [
+        SegmentKind::TalkingHead,
+        SegmentKind::BRoll,
+        SegmentKind::Action,
+        SegmentKind::Scenery,
+        SegmentKind::Screen,
+        SegmentKind::Interview,
+        SegmentKind::Transition,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SegmentKind::TalkingHead => "talking_head",
+            SegmentKind::BRoll => "b_roll",
+            SegmentKind::Action => "action",
+            SegmentKind::Scenery => "scenery",
+            SegmentKind::Screen => "screen",
+            SegmentKind::Interview => "interview",
+            SegmentKind::Transition => "transition",
+        }
+    }
+
+    /// Parse a stored or requested kind, including the names earlier versions stored
+    pub fn parse(s: &str) -> Option<SegmentKind> {
+        match s {
+            "broll" => Some(SegmentKind::BRoll),
+            "screen_recording" => Some(SegmentKind::Screen),
+            _ => SegmentKind::ALL.into_iter().find(|kind| kind.as_str() == s),
+        }
+    }
+
+    /// The kind stored on a segment, if it's been classified
+    pub fn of(segment: &Segment) -> Option<SegmentKind> {
+        segment.segment_kind.as_deref().and_then(SegmentKind::parse)
+    }
+
+    /// A-roll carries the story (people speaking, the screen being demonstrated); everything
+    /// else is b-roll cut in around it
+    pub fn is_a_roll(&self) -> bool {
+        matches!(self, SegmentKind::TalkingHead | SegmentKind::Interview | SegmentKind::Screen)
+    }
+}
+
+/// Whether an asset's spoken segments read as an interview: more than one speaker, or
+/// questions asked throughout
+pub fn is_interview(segments: &[Segment]) -> bool {
+    let spoken: Vec<&Segment> = segments.iter().filter(|s| has_speech(s)).collect();
+    if spoken.len() < MIN_INTERVIEW_SEGMENTS {
+        return false;
+    }
+    let mut speakers: Vec<&str> = spoken.iter().filter_map(|s| s.speaker.as_deref()).filter(|s| !s.is_empty()).collect();
+    speakers.sort();
+    speakers.dedup();
+    let questions = spoken
+        .iter()
+        .filter(|s| s.transcript.as_deref().is_some_and(|t| t.contains('?')))
+        .count();
+    speakers.len() > 1 || questions as f64 >= spoken.len() as f64 * INTERVIEW_QUESTION_SHARE
+}
+
+/// Classify a segment from its transcript and vision analysis. `interview` says whether its
+/// asset is an interview (see `is_interview`).
+pub fn classify(segment: &Segment, interview: bool) -> SegmentKind {
+    // Screen recordings are recognized when they're cut into segments
+    if SegmentKind::of(segment) == Some(SegmentKind::Screen) {
+        return SegmentKind::Screen;
+    }
+    let speech = has_speech(segment);
+    let duration_seconds = (segment.end_ticks - segment.start_ticks) as f64 / engine::timeline::TICKS_PER_SECOND as f64;
+    let scene = json_field(&segment.scene_json);
+    let has_face = scene.as_ref().and_then(|s| s.get("has_face")?.as_bool()).unwrap_or(false);
+    let motion = json_field(&segment.quality_json).and_then(|q| q.get("motion_score")?.as_f64());
+    let tags = vision_tags(segment);
+    let tagged = |labels: &[&str]| tags.iter().any(|t| labels.contains(&t.as_str()));
+
+    if speech && has_face {
+        return if interview { SegmentKind::Interview } else { SegmentKind::TalkingHead };
+    }
+    if !speech && duration_seconds < TRANSITION_MAX_SECONDS {
+        return SegmentKind::Transition;
+    }
+    if motion.is_some_and(|m| m > HIGH_MOTION_SCORE) || tagged(ACTION_TAGS) {
+        return SegmentKind::Action;
+    }
+    if !has_face && !tagged(&["person", "crowd"]) && tagged(SCENERY_TAGS) {
+        return SegmentKind::Scenery;
+    }
+    SegmentKind::BRoll
+}
+
+fn has_speech(segment: &Segment) -> bool {
+    segment.transcript.as_deref().is_some_and(|t| !t.trim().is_empty())
+}
+
+fn json_field(field: &Option<String>) -> Option<serde_json::Value> {
+    field.as_deref().and_then(|json| serde_json::from_str(json).ok())
+}
+
+/// Labels of the segment's vision tags (scene, object and action kinds), lowercased
+pub fn vision_tags(segment: &Segment) -> Vec<String> {
+    json_field(&segment.tags_json)
+        .and_then(|tags| tags.as_array().cloned())
+        .unwrap_or_default()
+        .iter()
+        .filter(|t| {
+            t.get("kind")
+                .and_then(|k| k.as_str())
+                .is_some_and(|k| crate::jobs::enrichment::VISION_TAG_KINDS.contains(&k))
+        })
+        .filter_map(|t| t.get("label").and_then(|l| l.as_str()).map(|l| l.to_lowercase()))
+        .collect()
+}
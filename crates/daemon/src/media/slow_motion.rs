@@ -0,0 +1,48 @@
+use crate::db::Segment;
+use crate::media::segment_kind::SegmentKind;
+use engine::ops::MIN_CLIP_SPEED;
+
+/// Footage at or above this frame rate was shot for slow motion (120 and 240fps phone modes)
+pub const HIGH_FRAME_RATE_FPS: f64 = 100.0;
+/// Tag kind and label marking segments of slow-motion capable footage
+pub const CAPTURE_TAG_KIND: &str = "capture";
+pub const SLOW_MOTION_LABEL: &str = "slow_motion";
+
+/// Frame rate slow motion is planned to play back at: 120fps footage plays at quarter speed
+/// with every frame a real one
+const PLAYBACK_FPS: f64 = 30.0;
+/// Mean frame difference of a segment worth slowing down (matches the action cutoff in
+/// segment classification)
+const HIGH_MOTION_SCORE: f64 = 50.0;
+
+pub fn frame_rate(fps_num: i32, fps_den: i32) -> f64 {
+    if fps_den > 0 {
+        fps_num as f64 / fps_den as f64
+    } else {
+        0.0
+    }
+}
+
+pub fn is_slow_motion_capable(fps: f64) -> bool {
+    fps >= HIGH_FRAME_RATE_FPS
+}
+
+/// Slowest speed footage at `fps` plays at without repeating frames
+pub fn slow_motion_speed(fps: f64) -> f64 {
+    (PLAYBACK_FPS / fps).clamp(MIN_CLIP_SPEED, 1.0)
+}
+
+/// Whether a segment is scenery in motion rather than someone talking: slowing speech down
+/// ruins it, slowing action down shows it off
+pub fn is_scenic_action(segment: &Segment) -> bool {
+    let has_speech = segment.transcript.as_deref().is_some_and(|t| !t.trim().is_empty());
+    if has_speech {
+        return false;
+    }
+    let motion = segment
+        .quality_json
+        .as_deref()
+        .and_then(|q| serde_json::from_str::<serde_json::Value>(q).ok())
+        .and_then(|q| q.get("motion_score").and_then(|v| v.as_f64()));
+    SegmentKind::of(segment) == Some(SegmentKind::Action) || motion.is_some_and(|m| m > HIGH_MOTION_SCORE)
+}
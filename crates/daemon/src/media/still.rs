@@ -0,0 +1,53 @@
+use anyhow::Result;
+use std::path::Path;
+
+use crate::db::Database;
+use engine::ken_burns::auto_ken_burns;
+use engine::timeline::{Timeline, TICKS_PER_SECOND};
+
+/// Photo formats imported as stills
+pub const IMAGE_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "webp", "bmp", "tif", "tiff"];
+
+/// How long a still lasts as source media (clips of it can be at most this long)
+pub const STILL_DURATION_TICKS: i64 = 5 * TICKS_PER_SECOND;
+/// Frame rate stills are played back and proxied at
+pub const STILL_FPS: i32 = 30;
+
+pub fn is_image_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| IMAGE_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+}
+
+/// Give still clips that don't have Ken Burns motion yet an automatic one, moving towards the
+/// subjects detected in the photo. Consecutive stills alternate between pushing in and pulling
+/// back. Returns how many clips were given motion.
+pub fn apply_auto_ken_burns(db: &Database, project_id: i64, timeline: &mut Timeline) -> Result<usize> {
+    let stills = db.get_still_assets(project_id)?;
+    if stills.is_empty() {
+        return Ok(0);
+    }
+    let frame = (timeline.settings.resolution.width, timeline.settings.resolution.height);
+
+    let mut still_clips: Vec<_> = timeline
+        .tracks
+        .iter_mut()
+        .flat_map(|t| t.clips.iter_mut())
+        .filter_map(|clip| {
+            let still = stills.iter().find(|s| s.id == clip.asset_id)?;
+            Some((clip, still))
+        })
+        .collect();
+    still_clips.sort_by_key(|(clip, _)| (clip.timeline_start_ticks, clip.track_id));
+
+    let mut applied = 0;
+    for (index, (clip, still)) in still_clips.into_iter().enumerate() {
+        if clip.ken_burns.is_some() {
+            continue;
+        }
+        let subjects = still.subjects.as_deref().unwrap_or(&[]);
+        clip.ken_burns = Some(auto_ken_burns(subjects, (still.width, still.height), frame, index % 2 == 0));
+        applied += 1;
+    }
+    Ok(applied)
+}
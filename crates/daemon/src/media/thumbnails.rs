@@ -0,0 +1,113 @@
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Default pyramid: two dense levels for zoomed-in scrubbing, the 1s base strip, and sparse
+/// levels for zoomed-out views
+const DEFAULT_THUMBNAIL_PYRAMID: &str = "250,500,1000,5000,30000";
+
+/// Spacing of the base thumbnails extracted alongside the first proxy (`t_NNNN.jpg`)
+pub const BASE_INTERVAL_MS: i64 = 1000;
+
+/// Densities a zoomable timeline can ask for, as milliseconds between thumbnails, densest first.
+/// Read from THUMBNAIL_PYRAMID (e.g. "250,1000,10000"); the base interval is always included.
+pub fn pyramid_intervals() -> Vec<i64> {
+    let pyramid_str = std::env::var("THUMBNAIL_PYRAMID")
+        .unwrap_or_else(|_| DEFAULT_THUMBNAIL_PYRAMID.to_string());
+
+    let mut intervals = parse_pyramid(&pyramid_str);
+    if intervals.is_empty() {
+        eprintln!("[THUMBS] Invalid THUMBNAIL_PYRAMID '{}', using default", pyramid_str);
+        intervals = parse_pyramid(DEFAULT_THUMBNAIL_PYRAMID);
+    }
+    intervals
+}
+
+/// Intervals of at least 100ms, sorted, with the base interval added
+fn parse_pyramid(pyramid_str: &str) -> Vec<i64> {
+    let mut intervals: Vec<i64> = pyramid_str
+        .split(',')
+        .filter_map(|entry| entry.trim().parse::<i64>().ok())
+        .filter(|ms| *ms >= 100)
+        .collect();
+    if intervals.is_empty() {
+        return intervals;
+    }
+    intervals.push(BASE_INTERVAL_MS);
+    intervals.sort_unstable();
+    intervals.dedup();
+    intervals
+}
+
+/// How a level's thumbnails come into being
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LevelSource {
+    /// The 1s strip extracted with the first proxy
+    Base,
+    /// Every Nth base thumbnail (whole multiples of the base interval), so never generated
+    Derived,
+    /// Extracted from the original on its own, lazily on first request
+    Generated,
+}
+
+/// Where a level's thumbnails come from
+pub fn level_source(interval_ms: i64) -> LevelSource {
+    if interval_ms == BASE_INTERVAL_MS {
+        LevelSource::Base
+    } else if interval_ms % BASE_INTERVAL_MS == 0 {
+        LevelSource::Derived
+    } else {
+        LevelSource::Generated
+    }
+}
+
+/// File of the `index`th thumbnail of a level (at `index * interval_ms`)
+pub fn thumbnail_file(thumbnail_dir: &Path, interval_ms: i64, index: i64) -> PathBuf {
+    match level_source(interval_ms) {
+        // ffmpeg numbers the base strip from 1
+        LevelSource::Base | LevelSource::Derived => {
+            thumbnail_dir.join(format!("t_{:04}.jpg", index * (interval_ms / BASE_INTERVAL_MS) + 1))
+        }
+        LevelSource::Generated => thumbnail_dir.join(level_file_name(interval_ms, index)),
+    }
+}
+
+/// Name of a generated level's thumbnail; they share the asset's thumbnail directory so
+/// encryption, storage mirroring and cache purges treat them like the base strip
+pub fn level_file_name(interval_ms: i64, index: i64) -> String {
+    format!("d{:05}_{:05}.jpg", interval_ms, index)
+}
+
+/// ffmpeg output pattern for a generated level (numbered from 0)
+pub fn level_file_pattern(interval_ms: i64) -> String {
+    format!("d{:05}_%05d.jpg", interval_ms)
+}
+
+/// Number of thumbnails of a level derived from `base_count` base thumbnails
+pub fn derived_count(interval_ms: i64, base_count: i64) -> i64 {
+    let step = interval_ms / BASE_INTERVAL_MS;
+    (base_count + step - 1) / step
+}
+
+/// Count the thumbnails ffmpeg wrote for a level (`prefix` is "t_" or the level's "dNNNNN_")
+pub fn count_files(thumbnail_dir: &Path, prefix: &str) -> i64 {
+    std::fs::read_dir(thumbnail_dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| {
+                    let name = entry.file_name().to_string_lossy().to_string();
+                    name.starts_with(prefix) && name.ends_with(".jpg")
+                })
+                .count() as i64
+        })
+        .unwrap_or(0)
+}
+
+/// Prefix of a level's file names
+pub fn level_prefix(interval_ms: i64) -> String {
+    match level_source(interval_ms) {
+        LevelSource::Base | LevelSource::Derived => "t_".to_string(),
+        LevelSource::Generated => format!("d{:05}_", interval_ms),
+    }
+}
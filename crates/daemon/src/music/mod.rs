@@ -0,0 +1,300 @@
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::db::{Database, MusicTrack};
+use crate::media::music_structure::MusicStructure;
+use crate::style::presets::find_preset;
+use crate::templates::clip_bounds;
+use engine::compiler::{EditConstraints, EditEvent, EditPlan};
+use engine::timeline::{Timeline, TICKS_PER_SECOND};
+
+/// Tempo picked for an edit cutting `cuts_per_10s` times every ten seconds is this plus
+/// `BPM_PER_CUT` per cut (a 1.2-cut cinematic edit wants ~85 BPM, a 4.5-cut vlog ~125)
+const BASE_BPM: f64 = 70.0;
+const BPM_PER_CUT: f64 = 12.0;
+/// Tracks this far off the target tempo get no credit for it
+const BPM_TOLERANCE: f64 = 40.0;
+
+/// A catalog track chosen for an edit and why
+#[derive(Debug, Clone, Serialize)]
+pub struct MusicPick {
+    pub track: MusicTrack,
+    pub score: f64,
+    /// The track's moods that the vibe asked for
+    pub matched_moods: Vec<String>,
+    pub target_bpm: Option<f64>,
+    /// Whether the track runs at least as long as the edit
+    pub covers_edit: bool,
+}
+
+/// A catalog track an edit is cut to, with its analyzed structure
+#[derive(Debug, Clone)]
+pub struct FollowedMusic {
+    pub track: MusicTrack,
+    pub structure: MusicStructure,
+}
+
+impl FollowedMusic {
+    /// Plan the edit to the track: its sections become the style profile's beat template (so
+    /// section changes land on the music's and impact cuts on its accents), and an edit without
+    /// a target length runs as long as the track
+    pub fn apply(&self, constraints: &mut EditConstraints, style_profile: Option<serde_json::Value>) -> serde_json::Value {
+        if constraints.target_length.is_none() {
+            constraints.target_length = Some((self.structure.duration_sec * TICKS_PER_SECOND as f64) as i64);
+        }
+        let mut profile = match style_profile {
+            Some(serde_json::Value::Object(map)) => map,
+            _ => serde_json::Map::new(),
+        };
+        profile.insert(
+            "beat_template".to_string(),
+            serde_json::to_value(self.structure.beat_template(self.track.bpm)).unwrap_or(serde_json::Value::Null),
+        );
+        serde_json::Value::Object(profile)
+    }
+}
+
+/// Music on a timeline and the license it plays under, from the catalog
+#[derive(Debug, Clone, Serialize)]
+pub struct MusicCredit {
+    pub track_path: String,
+    /// None when the file isn't in the catalog
+    pub track_id: Option<i64>,
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub license: Option<String>,
+    pub license_url: Option<String>,
+    pub attribution: Option<String>,
+}
+
+impl MusicCredit {
+    /// Whether the catalog knows what license the track plays under
+    pub fn licensed(&self) -> bool {
+        self.license.is_some()
+    }
+
+    /// One line crediting the track: its attribution if the license asks for one, otherwise
+    /// "Title by Artist (License, url)"
+    pub fn line(&self) -> String {
+        if let Some(attribution) = &self.attribution {
+            return attribution.clone();
+        }
+        let title = self.title.clone().unwrap_or_else(|| {
+            std::path::Path::new(&self.track_path)
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| self.track_path.clone())
+        });
+        let mut line = match &self.artist {
+            Some(artist) => format!("{} by {}", title, artist),
+            None => title,
+        };
+        match (&self.license, &self.license_url) {
+            (Some(license), Some(url)) => line.push_str(&format!(" ({}, {})", license, url)),
+            (Some(license), None) => line.push_str(&format!(" ({})", license)),
+            (None, _) => line.push_str(" (license unknown)"),
+        }
+        line
+    }
+}
+
+/// Words a vibe asks for in a track's moods: its own words, and the aliases of the style preset
+/// it selects ("travel" also matches tracks tagged "energetic")
+fn vibe_words(vibe: &str) -> Vec<String> {
+    let mut words: Vec<String> = vibe
+        .to_lowercase()
+        .split(|c: char| c.is_whitespace() || c == ',' || c == '/')
+        .filter(|w| !w.is_empty())
+        .map(str::to_string)
+        .collect();
+    if let Some(preset) = find_preset(vibe) {
+        words.extend(preset.aliases.iter().map(|a| a.to_string()));
+        words.push(preset.key.to_string());
+    }
+    words.sort();
+    words.dedup();
+    words
+}
+
+/// Tempo that suits the style profile's cut rate, when it has one
+fn target_bpm(style_profile: Option<&serde_json::Value>) -> Option<f64> {
+    let cuts_per_10s = style_profile?
+        .get("beat_template")?
+        .get("cuts_per_10s")?
+        .as_f64()
+        .filter(|c| *c > 0.0)?;
+    Some((BASE_BPM + BPM_PER_CUT * cuts_per_10s).clamp(60.0, 160.0))
+}
+
+/// Best catalog track for an edit of `length_ticks` with this vibe and style: two points per
+/// mood the vibe asks for, up to one for tempo close to what the cut rate suits, and half a
+/// point for running the whole edit. Tracks that match neither mood nor tempo aren't picked.
+pub fn pick_track(
+    tracks: &[MusicTrack],
+    vibe: Option<&str>,
+    style_profile: Option<&serde_json::Value>,
+    length_ticks: i64,
+) -> Option<MusicPick> {
+    rank_tracks(tracks, vibe, style_profile, length_ticks).into_iter().next()
+}
+
+/// Catalog tracks that suit an edit, best first (see [`pick_track`])
+pub fn rank_tracks(
+    tracks: &[MusicTrack],
+    vibe: Option<&str>,
+    style_profile: Option<&serde_json::Value>,
+    length_ticks: i64,
+) -> Vec<MusicPick> {
+    let words = vibe.map(vibe_words).unwrap_or_default();
+    let target_bpm = target_bpm(style_profile);
+    let mut picks: Vec<MusicPick> = tracks
+        .iter()
+        .filter_map(|track| {
+            let matched_moods: Vec<String> = track
+                .moods
+                .iter()
+                .filter(|mood| words.contains(&mood.to_lowercase()))
+                .cloned()
+                .collect();
+            let tempo = match (track.bpm, target_bpm) {
+                (Some(bpm), Some(target)) => (1.0 - (bpm - target).abs() / BPM_TOLERANCE).max(0.0),
+                _ => 0.0,
+            };
+            if matched_moods.is_empty() && tempo == 0.0 {
+                return None;
+            }
+            let covers_edit = track.duration_ticks.is_some_and(|d| d >= length_ticks);
+            let score = 2.0 * matched_moods.len() as f64 + tempo + if covers_edit { 0.5 } else { 0.0 };
+            Some(MusicPick {
+                track: track.clone(),
+                score,
+                matched_moods,
+                target_bpm,
+                covers_edit,
+            })
+        })
+        .collect();
+    picks.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.track.id.cmp(&b.track.id)));
+    picks
+}
+
+/// The track an edit follows: the one requested (an error if it isn't in the catalog or hasn't
+/// been analyzed yet), otherwise the best match for the vibe if it has been analyzed
+pub fn music_to_follow(
+    db: &Database,
+    track_id: Option<i64>,
+    vibe: Option<&str>,
+    style_profile: Option<&serde_json::Value>,
+    length_ticks: i64,
+) -> Result<Option<FollowedMusic>> {
+    let track = match track_id {
+        Some(id) => db
+            .get_music_track(id)?
+            .ok_or_else(|| anyhow::anyhow!("Music track {} not found", id))?,
+        None => match pick_track(&db.list_music_tracks()?, vibe, style_profile, length_ticks) {
+            Some(pick) => pick.track,
+            None => return Ok(None),
+        },
+    };
+    match db.get_music_structure(track.id)? {
+        Some(json) => Ok(Some(FollowedMusic {
+            track,
+            structure: serde_json::from_str(&json)?,
+        })),
+        None if track_id.is_some() => Err(anyhow::anyhow!("Music track {} hasn't been analyzed yet", track.id)),
+        None => Ok(None),
+    }
+}
+
+/// Score the whole plan with a catalog track, when music is on: the track the plan `followed`
+/// (replacing any other music), otherwise one matching its vibe if nothing (e.g. the template's
+/// music slot) has put music in it already. The music is placed in the first section with clips
+/// and runs to the end of the last.
+pub fn add_catalog_music(
+    db: &Database,
+    plan: &mut EditPlan,
+    style_profile: Option<&serde_json::Value>,
+    followed: Option<&FollowedMusic>,
+) -> Result<Option<MusicPick>> {
+    if followed.is_some() {
+        for section in &mut plan.sections {
+            section.events.retain(|e| !matches!(e, EditEvent::Music { .. }));
+        }
+    }
+    let has_music = plan
+        .sections
+        .iter()
+        .flat_map(|s| s.events.iter())
+        .any(|e| matches!(e, EditEvent::Music { .. }));
+    if !plan.constraints.music_on || has_music {
+        return Ok(None);
+    }
+    let bounds: Vec<(usize, (i64, i64))> = plan
+        .sections
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, section)| Some((idx, clip_bounds(&section.events)?)))
+        .collect();
+    let (Some(&(first, (start_ticks, _))), Some(end_ticks)) =
+        (bounds.first(), bounds.iter().map(|(_, (_, end))| *end).max())
+    else {
+        return Ok(None);
+    };
+
+    let pick = match followed {
+        Some(followed) => Some(MusicPick {
+            track: followed.track.clone(),
+            score: 0.0,
+            matched_moods: Vec::new(),
+            target_bpm: None,
+            covers_edit: followed.track.duration_ticks.is_some_and(|d| d >= end_ticks - start_ticks),
+        }),
+        None => pick_track(&db.list_music_tracks()?, plan.constraints.vibe.as_deref(), style_profile, end_ticks - start_ticks),
+    };
+    let Some(pick) = pick else {
+        return Ok(None);
+    };
+    // Music shorter than the edit stops when it runs out
+    let end_ticks = match pick.track.duration_ticks {
+        Some(duration) => end_ticks.min(start_ticks + duration),
+        None => end_ticks,
+    };
+    plan.sections[first].events.push(EditEvent::Music {
+        track_path: pick.track.path.clone(),
+        ducking_profile_id: None,
+        start_ticks,
+        end_ticks,
+    });
+    eprintln!(
+        "[MUSIC] Scored the edit with \"{}\" ({:.1}s, score {:.1})",
+        pick.track.title,
+        (end_ticks - start_ticks) as f64 / TICKS_PER_SECOND as f64,
+        pick.score
+    );
+    Ok(Some(pick))
+}
+
+/// Credits for the music a timeline plays, one per track file, in order of first use
+pub fn credits(db: &Database, timeline: &Timeline) -> Result<Vec<MusicCredit>> {
+    let mut paths: Vec<&str> = Vec::new();
+    for music in &timeline.music {
+        if !paths.contains(&music.track_path.as_str()) {
+            paths.push(&music.track_path);
+        }
+    }
+    paths
+        .into_iter()
+        .map(|path| {
+            let track = db.get_music_track_by_path(path)?;
+            Ok(MusicCredit {
+                track_path: path.to_string(),
+                track_id: track.as_ref().map(|t| t.id),
+                title: track.as_ref().map(|t| t.title.clone()),
+                artist: track.as_ref().and_then(|t| t.artist.clone()),
+                license: track.as_ref().and_then(|t| t.license.clone()),
+                license_url: track.as_ref().and_then(|t| t.license_url.clone()),
+                attribution: track.and_then(|t| t.attribution),
+            })
+        })
+        .collect()
+}
@@ -73,6 +73,7 @@ impl AgentContext {
         
         // Generate LLM response (message only, no control decisions)
         let response = llm::generate_agent_response(
+            &llm::CallAudit::new(&self.db, self.project_id),
             &history,
             &project_state_json,
             &context,
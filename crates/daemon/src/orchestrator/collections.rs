@@ -0,0 +1,45 @@
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::db::Database;
+
+/// Words that mark a collection being named ("only use the drone footage bin")
+const COLLECTION_WORDS: &[&str] = &["bin", "bins", "collection", "collections", "folder"];
+
+/// A collection a search was limited to
+#[derive(Debug, Clone, Serialize)]
+pub struct CollectionScope {
+    pub collection_id: i64,
+    pub name: String,
+}
+
+/// The project's collection a message names, when it also says "bin" or "collection" (so a
+/// collection called "Beach" doesn't scope every message about beaches). The longest matching
+/// name wins, so "drone footage" beats "drone".
+pub fn collection_named_in(db: &Database, project_id: i64, message: &str) -> Result<Option<CollectionScope>> {
+    let message = normalize(message);
+    if !message.split(' ').any(|word| COLLECTION_WORDS.contains(&word)) {
+        return Ok(None);
+    }
+
+    Ok(db
+        .list_collections(project_id)?
+        .into_iter()
+        .filter(|(_, name, _, _, _)| {
+            let name = normalize(name);
+            !name.trim().is_empty() && message.contains(&name)
+        })
+        .max_by_key(|(_, name, _, _, _)| name.len())
+        .map(|(collection_id, name, _, _, _)| CollectionScope { collection_id, name }))
+}
+
+/// Lowercase words separated by single spaces, padded with a space on each side so `contains`
+/// only matches whole words
+fn normalize(text: &str) -> String {
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_lowercase())
+        .collect();
+    format!(" {} ", words.join(" "))
+}
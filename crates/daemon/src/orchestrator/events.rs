@@ -1,59 +1,51 @@
-use anyhow::Result;
-use serde_json;
 use std::sync::Arc;
-use tokio::time::{sleep, Duration};
-use tracing::{info, warn};
+use tracing::info;
 
 use crate::db::Database;
-use crate::jobs::{JobEvent, JobManager};
+use crate::events::outbox::AGENT_CONSUMER;
+use crate::events::Event;
+use crate::jobs::JobManager;
 use crate::orchestrator::agent::AgentContext;
 
-/// Agent event loop that handles events and takes autonomous actions
+/// Agent event loop that handles events and takes autonomous actions. It resumes from the last
+/// event it handled, so analysis that finished while the daemon was down still gets its message.
 pub async fn agent_event_loop(db: Arc<Database>, job_manager: Arc<JobManager>) {
-    let mut rx = job_manager.subscribe();
+    let mut subscription = match job_manager.events().subscribe_as(AGENT_CONSUMER) {
+        Ok(subscription) => subscription,
+        Err(e) => {
+            eprintln!("[Agent] Failed to resume from the events outbox, event loop not started: {:?}", e);
+            return;
+        }
+    };
     
     info!("[Agent] Event loop started");
     
-    loop {
-        match rx.recv().await {
-            Ok(event) => {
-                match event {
-                    JobEvent::AnalysisComplete { project_id, .. } => {
-                        // Generate proactive LLM message when analysis completes
-                        let context = AgentContext::new(project_id, db.clone(), job_manager.clone());
-                        
-                        // Check if there's a goal waiting for analysis
-                        if let Ok(Some((goal_id, user_intent))) = db.get_orchestrator_goal_by_status(project_id, "ready_to_propose") {
-                            // Generate proactive message using LLM
-                            if let Err(e) = context.handle_event("analysis_complete", &serde_json::json!({
-                                "goal_id": goal_id,
-                                "user_intent": user_intent,
-                            })).await {
-                                eprintln!("[Agent] Error generating proactive message: {:?}", e);
-                            }
-                        } else {
-                            // No goal waiting, but still generate a general message
-                            if let Err(e) = context.handle_event("analysis_complete", &serde_json::json!({})).await {
-                                eprintln!("[Agent] Error generating proactive message: {:?}", e);
-                            }
-                        }
-                    }
-                    JobEvent::JobCompleted { job_id, job_type, asset_id: _, .. } => {
-                        // Job completion events - could generate messages for specific job types
-                        // For now, focus on AnalysisComplete which is more useful
+    while let Some(published) = subscription.recv().await {
+        match published.event {
+            Event::AnalysisComplete { project_id, .. } => {
+                // Generate proactive LLM message when analysis completes
+                let context = AgentContext::new(project_id, db.clone(), job_manager.clone());
+                
+                // Check if there's a goal waiting for analysis
+                if let Ok(Some((goal_id, user_intent))) = db.get_orchestrator_goal_by_status(project_id, "ready_to_propose") {
+                    // Generate proactive message using LLM
+                    if let Err(e) = context.handle_event("analysis_complete", &serde_json::json!({
+                        "goal_id": goal_id,
+                        "user_intent": user_intent,
+                    })).await {
+                        eprintln!("[Agent] Error generating proactive message: {:?}", e);
                     }
-                    _ => {
-                        // Ignore other events for now
+                } else {
+                    // No goal waiting, but still generate a general message
+                    if let Err(e) = context.handle_event("analysis_complete", &serde_json::json!({})).await {
+                        eprintln!("[Agent] Error generating proactive message: {:?}", e);
                     }
                 }
             }
-            Err(_) => {
-                // Receiver closed or lagged - reconnect
-                warn!("[Agent] Event receiver closed, reconnecting...");
-                sleep(Duration::from_secs(1)).await;
-                rx = job_manager.subscribe();
+            _ => {
+                // Job state, proposal and timeline events don't prompt the agent (yet)
             }
         }
+        subscription.ack(&published);
     }
 }
-
@@ -3,3 +3,7 @@ pub mod ensure;
 pub mod events;
 pub mod agent;
 
+pub mod collections;
+pub mod persona;
+pub mod tools;
+pub mod working_set;
@@ -0,0 +1,236 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// Locale every lookup falls back to
+const DEFAULT_LOCALE: &str = "en";
+
+/// How the agent phrases its messages
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Tone {
+    Concise,
+    #[default]
+    Friendly,
+    Professional,
+}
+
+impl Tone {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Tone::Concise => "concise",
+            Tone::Friendly => "friendly",
+            Tone::Professional => "professional",
+        }
+    }
+
+    /// Style guidance handed to the LLM alongside the tone name
+    fn guidance(&self) -> &'static str {
+        match self {
+            Tone::Concise => "one or two short sentences, no small talk",
+            Tone::Friendly => "warm and conversational, a little enthusiastic",
+            Tone::Professional => "clear and neutral, complete sentences, no slang or exclamations",
+        }
+    }
+}
+
+/// Per-project agent settings (projects.agent_persona_json)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AgentPersona {
+    pub tone: Tone,
+    /// BCP 47-style language tag ("en", "es", "pt-BR"); unknown locales fall back to the base
+    /// language, then English
+    pub locale: String,
+    /// Project-specific copy by message key, taking precedence over every built-in template
+    pub overrides: HashMap<String, String>,
+}
+
+impl Default for AgentPersona {
+    fn default() -> Self {
+        AgentPersona {
+            tone: Tone::default(),
+            locale: DEFAULT_LOCALE.to_string(),
+            overrides: HashMap::new(),
+        }
+    }
+}
+
+/// Built-in templates as (locale, tone, key, template). `{name}` placeholders are filled in by
+/// `AgentPersona::render`. Every key has an English friendly template, the final fallback.
+const BUILTIN_TEMPLATES: &[(&str, Tone, &str, &str)] = &[
+    // English, friendly
+    ("en", Tone::Friendly, "import.message", "Hey! Your library is empty right now. Click Import Video Clips to add footage — then I'll scan it and suggest a first cut."),
+    ("en", Tone::Friendly, "import.action", "Import clips"),
+    ("en", Tone::Friendly, "analyze.message", "Nice — I see your clips. Next step is analyzing them into moments I can edit with. Want me to start the scan?"),
+    ("en", Tone::Friendly, "analyze.action", "Analyze clips"),
+    ("en", Tone::Friendly, "busy.jobs_running", "I'm scanning your footage now ({jobs} jobs running)"),
+    ("en", Tone::Friendly, "busy.analyzing", "I'm still analyzing your footage ({percent}% complete)"),
+    ("en", Tone::Friendly, "busy.message", "{status}. You can keep browsing — I'll tell you when I'm ready to propose an edit."),
+    ("en", Tone::Friendly, "busy.action", "Show progress"),
+    ("en", Tone::Friendly, "clarify.message", "Got it — before I start, what kind of vibe are you going for? Casual vlog, cinematic montage, or something fast-paced?"),
+    ("en", Tone::Friendly, "clarify.question_story", "What's the main story you want to tell?"),
+    ("en", Tone::Friendly, "clarify.question_length", "How long should the final video be?"),
+    ("en", Tone::Friendly, "confirm.message", "This will replace your current timeline. Do you want to overwrite it, or create a new version?"),
+    ("en", Tone::Friendly, "confirm.overwrite", "Overwrite timeline"),
+    ("en", Tone::Friendly, "confirm.new_version", "Create new version"),
+    ("en", Tone::Friendly, "confirm.cancel", "Cancel"),
+    ("en", Tone::Friendly, "act.no_candidates", "I couldn't find moments that match that request yet. Want me to broaden the search, or are you aiming for a specific vibe (funny / cinematic / cozy)?"),
+    ("en", Tone::Friendly, "act.broaden", "Broaden search"),
+    ("en", Tone::Friendly, "act.show_all", "Show all moments"),
+    ("en", Tone::Friendly, "act.question_moments", "What kind of moments are you looking for?"),
+    ("en", Tone::Friendly, "act.found", "I found {count} good moments based on speech and visual interest. I'll start with a short hook, then build the main section around these scenes."),
+    ("en", Tone::Friendly, "act.generate_plan", "Generate Plan"),
+    ("en", Tone::Friendly, "retrieval.indexing_warning", "I'm still indexing your footage for better search; results will improve shortly."),
+    // English, concise
+    ("en", Tone::Concise, "import.message", "No footage yet. Import clips to get started."),
+    ("en", Tone::Concise, "analyze.message", "Clips imported. Analyze them to continue?"),
+    ("en", Tone::Concise, "busy.jobs_running", "Scanning footage ({jobs} jobs running)"),
+    ("en", Tone::Concise, "busy.analyzing", "Analyzing footage ({percent}% complete)"),
+    ("en", Tone::Concise, "busy.message", "{status}."),
+    ("en", Tone::Concise, "clarify.message", "What vibe: casual vlog, cinematic montage, or fast-paced?"),
+    ("en", Tone::Concise, "clarify.question_story", "Main story?"),
+    ("en", Tone::Concise, "clarify.question_length", "Target length?"),
+    ("en", Tone::Concise, "confirm.message", "This replaces the current timeline. Overwrite or create a new version?"),
+    ("en", Tone::Concise, "act.no_candidates", "No matching moments. Broaden the search or pick a vibe?"),
+    ("en", Tone::Concise, "act.question_moments", "Which moments?"),
+    ("en", Tone::Concise, "act.found", "Found {count} moments. Ready to plan."),
+    ("en", Tone::Concise, "retrieval.indexing_warning", "Indexing in progress; results will improve."),
+    // English, professional
+    ("en", Tone::Professional, "import.message", "Your media library is currently empty. Please use Import Video Clips to add footage, after which I will analyze it and propose an initial cut."),
+    ("en", Tone::Professional, "analyze.message", "Your clips have been imported. The next step is to analyze them into editable moments. Would you like me to begin the analysis?"),
+    ("en", Tone::Professional, "busy.jobs_running", "Footage analysis is in progress ({jobs} jobs running)"),
+    ("en", Tone::Professional, "busy.analyzing", "Footage analysis is {percent}% complete"),
+    ("en", Tone::Professional, "busy.message", "{status}. You may continue working; I will notify you when an edit can be proposed."),
+    ("en", Tone::Professional, "clarify.message", "Before I begin, which style would you like: a casual vlog, a cinematic montage, or a fast-paced edit?"),
+    ("en", Tone::Professional, "clarify.question_story", "What is the main story you would like to tell?"),
+    ("en", Tone::Professional, "clarify.question_length", "What should the final running time be?"),
+    ("en", Tone::Professional, "confirm.message", "Applying this plan will replace the current timeline. Would you like to overwrite it or create a new version?"),
+    ("en", Tone::Professional, "act.no_candidates", "I could not find moments matching this request. I can broaden the search, or you can specify a style (for example humorous, cinematic or relaxed)."),
+    ("en", Tone::Professional, "act.question_moments", "Which kinds of moments should I look for?"),
+    ("en", Tone::Professional, "act.found", "I identified {count} strong moments based on speech and visual interest. The edit will open with a short hook, followed by a main section built around these scenes."),
+    ("en", Tone::Professional, "retrieval.indexing_warning", "Footage indexing is still in progress; search results will improve once it completes."),
+    // Spanish, friendly
+    ("es", Tone::Friendly, "import.message", "¡Hola! Tu biblioteca está vacía. Haz clic en Importar clips de video para añadir material; después lo analizaré y te propondré un primer montaje."),
+    ("es", Tone::Friendly, "import.action", "Importar clips"),
+    ("es", Tone::Friendly, "analyze.message", "Genial, ya veo tus clips. El siguiente paso es analizarlos en momentos con los que pueda editar. ¿Empiezo el análisis?"),
+    ("es", Tone::Friendly, "analyze.action", "Analizar clips"),
+    ("es", Tone::Friendly, "busy.jobs_running", "Estoy analizando tu material ({jobs} tareas en curso)"),
+    ("es", Tone::Friendly, "busy.analyzing", "Sigo analizando tu material ({percent}% completado)"),
+    ("es", Tone::Friendly, "busy.message", "{status}. Puedes seguir navegando; te avisaré cuando pueda proponer un montaje."),
+    ("es", Tone::Friendly, "busy.action", "Ver progreso"),
+    ("es", Tone::Friendly, "clarify.message", "Entendido. Antes de empezar, ¿qué estilo buscas? ¿Vlog casual, montaje cinematográfico o algo con ritmo rápido?"),
+    ("es", Tone::Friendly, "clarify.question_story", "¿Cuál es la historia principal que quieres contar?"),
+    ("es", Tone::Friendly, "clarify.question_length", "¿Cuánto debería durar el video final?"),
+    ("es", Tone::Friendly, "confirm.message", "Esto reemplazará tu línea de tiempo actual. ¿Quieres sobrescribirla o crear una nueva versión?"),
+    ("es", Tone::Friendly, "confirm.overwrite", "Sobrescribir línea de tiempo"),
+    ("es", Tone::Friendly, "confirm.new_version", "Crear nueva versión"),
+    ("es", Tone::Friendly, "confirm.cancel", "Cancelar"),
+    ("es", Tone::Friendly, "act.no_candidates", "Todavía no encuentro momentos que encajen con eso. ¿Amplío la búsqueda o buscas un estilo concreto (divertido / cinematográfico / acogedor)?"),
+    ("es", Tone::Friendly, "act.broaden", "Ampliar búsqueda"),
+    ("es", Tone::Friendly, "act.show_all", "Ver todos los momentos"),
+    ("es", Tone::Friendly, "act.question_moments", "¿Qué tipo de momentos buscas?"),
+    ("es", Tone::Friendly, "act.found", "Encontré {count} buenos momentos por el habla y el interés visual. Empezaré con un gancho corto y construiré la parte principal con estas escenas."),
+    ("es", Tone::Friendly, "act.generate_plan", "Generar plan"),
+    ("es", Tone::Friendly, "retrieval.indexing_warning", "Sigo indexando tu material para mejorar la búsqueda; los resultados mejorarán en breve."),
+];
+
+/// Templates by locale, then tone name, then message key
+type TemplateTable = HashMap<String, HashMap<String, HashMap<String, String>>>;
+
+/// Templates from the file at AGENT_TEMPLATES_PATH, shaped `{locale: {tone: {key: template}}}`,
+/// layered over the built-ins. Read once; a missing or invalid file is logged and ignored.
+fn config_templates() -> &'static TemplateTable {
+    static TEMPLATES: OnceLock<TemplateTable> = OnceLock::new();
+    TEMPLATES.get_or_init(|| {
+        let Ok(path) = std::env::var("AGENT_TEMPLATES_PATH") else {
+            return HashMap::new();
+        };
+        match std::fs::read_to_string(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|json| Ok(serde_json::from_str(&json)?))
+        {
+            Ok(templates) => templates,
+            Err(e) => {
+                eprintln!("[PERSONA] Ignoring agent templates at {}: {:?}", path, e);
+                HashMap::new()
+            }
+        }
+    })
+}
+
+/// Every message key the agent uses (the keys of the English friendly templates)
+pub fn message_keys() -> Vec<&'static str> {
+    BUILTIN_TEMPLATES
+        .iter()
+        .filter(|(locale, tone, _, _)| *locale == DEFAULT_LOCALE && *tone == Tone::Friendly)
+        .map(|(_, _, key, _)| *key)
+        .collect()
+}
+
+fn lookup(locale: &str, tone: Tone, key: &str) -> Option<String> {
+    let configured = config_templates()
+        .get(locale)
+        .and_then(|tones| tones.get(tone.as_str()))
+        .and_then(|keys| keys.get(key));
+    if let Some(template) = configured {
+        return Some(template.clone());
+    }
+    BUILTIN_TEMPLATES
+        .iter()
+        .find(|(l, t, k, _)| *l == locale && *t == tone && *k == key)
+        .map(|(_, _, _, template)| template.to_string())
+}
+
+impl AgentPersona {
+    /// Load the project's persona, falling back to defaults when unset or unreadable
+    pub fn for_project(db: &crate::db::Database, project_id: i64) -> AgentPersona {
+        db.get_project_agent_persona(project_id)
+            .ok()
+            .flatten()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    /// Validate settings before they're stored
+    pub fn validate(&self) -> Result<(), String> {
+        if self.locale.trim().is_empty() {
+            return Err("locale must not be empty".to_string());
+        }
+        let keys = message_keys();
+        if let Some(unknown) = self.overrides.keys().find(|k| !keys.contains(&k.as_str())) {
+            return Err(format!("unknown message key '{}'", unknown));
+        }
+        Ok(())
+    }
+
+    /// Render a message: project override, then the locale (full tag, then base language) in
+    /// this tone, then in the friendly tone, then English. Placeholders `{name}` are replaced
+    /// from `vars`.
+    pub fn render(&self, key: &str, vars: &[(&str, String)]) -> String {
+        let base_language = self.locale.split(['-', '_']).next().unwrap_or(DEFAULT_LOCALE);
+        let template = self.overrides.get(key).cloned().or_else(|| {
+            [self.locale.as_str(), base_language, DEFAULT_LOCALE]
+                .iter()
+                .flat_map(|locale| [(*locale, self.tone), (*locale, Tone::Friendly)])
+                .find_map(|(locale, tone)| lookup(locale, tone, key))
+        });
+        let Some(mut message) = template else {
+            eprintln!("[PERSONA] No template for message key '{}'", key);
+            return key.to_string();
+        };
+        for (name, value) in vars {
+            message = message.replace(&format!("{{{}}}", name), value);
+        }
+        message
+    }
+
+    /// Tone and language for the LLM response prompt
+    pub fn prompt_context(&self) -> serde_json::Value {
+        serde_json::json!({
+            "tone": self.tone.as_str(),
+            "tone_guidance": self.tone.guidance(),
+            "locale": self.locale,
+        })
+    }
+}
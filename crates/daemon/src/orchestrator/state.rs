@@ -108,10 +108,10 @@ pub fn get_asset_readiness(db: &Database, asset_id: i64) -> Result<AssetReadines
 
 /// Get asset states for all raw assets in a project
 pub fn get_asset_states(db: &Database, project_id: i64) -> Result<Vec<AssetState>> {
-    let conn = db.conn.lock().unwrap();
-    
-    // Get all raw (non-reference) assets for this project
+    // Get all raw (non-reference) assets for this project; the connection is released before
+    // get_asset_readiness locks it again
     let asset_ids: Vec<i64> = {
+        let conn = db.conn.lock().unwrap();
         let mut stmt = conn.prepare(
             "SELECT id FROM media_assets WHERE project_id = ?1 AND (is_reference IS NULL OR is_reference = 0)"
         )?;
@@ -0,0 +1,163 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use engine::ops::{TimelineOperation, MAX_CLIP_SPEED, MIN_CLIP_SPEED};
+use engine::timeline::{ClipInstance, Timeline, TICKS_PER_SECOND};
+
+/// The fine-grained edits the LLM may make, by tool name
+pub const EDIT_TOOLS: &[&str] = &["trim_clip", "reorder_clip", "delete_clip", "set_speed"];
+
+/// A tool call as the LLM made it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolCall {
+    pub name: String,
+    #[serde(default)]
+    pub arguments: serde_json::Value,
+}
+
+/// A tool call and what became of it, as kept in the audit transcript
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolCallOutcome {
+    pub name: String,
+    pub arguments: serde_json::Value,
+    /// The timeline operation the call mapped to, when it passed validation
+    pub operation: Option<TimelineOperation>,
+    /// Why the call was rejected (calls after a rejected one are skipped)
+    pub error: Option<String>,
+}
+
+/// Validate `calls` in order against a copy of `timeline`, each seeing the timeline as the calls
+/// before it left it. Returns every call's outcome, and the operations to apply when all of them
+/// passed (a single bad call rejects the whole batch, so an edit is never half done).
+pub fn validate_tool_calls(
+    timeline: &Timeline,
+    calls: &[ToolCall],
+    source_durations: &HashMap<i64, i64>,
+) -> (Vec<ToolCallOutcome>, Option<Vec<TimelineOperation>>) {
+    let mut preview = timeline.clone();
+    preview.consolidate_timeline();
+    let mut outcomes = Vec::new();
+    let mut operations = Vec::new();
+    let mut failed = false;
+
+    for call in calls {
+        let result = if failed {
+            Err("Skipped: an earlier call was rejected".to_string())
+        } else {
+            to_operation(call, &preview, source_durations).and_then(|op| {
+                preview.apply_operation(op.clone())?;
+                preview.consolidate_timeline();
+                Ok(op)
+            })
+        };
+        failed |= result.is_err();
+        outcomes.push(ToolCallOutcome {
+            name: call.name.clone(),
+            arguments: call.arguments.clone(),
+            operation: result.as_ref().ok().cloned(),
+            error: result.as_ref().err().cloned(),
+        });
+        if let Ok(op) = result {
+            operations.push(op);
+        }
+    }
+
+    (outcomes, if failed { None } else { Some(operations) })
+}
+
+/// The timeline operation a tool call maps to, checked against `timeline`
+fn to_operation(
+    call: &ToolCall,
+    timeline: &Timeline,
+    source_durations: &HashMap<i64, i64>,
+) -> Result<TimelineOperation, String> {
+    if !EDIT_TOOLS.contains(&call.name.as_str()) {
+        return Err(format!("Unknown tool: {}", call.name));
+    }
+    let clip_id = call.arguments["clip_id"]
+        .as_str()
+        .ok_or_else(|| "clip_id is required".to_string())?;
+    let clips = primary_clips(timeline);
+    let clip = clips
+        .iter()
+        .find(|c| c.id == clip_id)
+        .ok_or_else(|| format!("Clip {} is not on the timeline", clip_id))?;
+    if clip.locked {
+        return Err(format!("Clip {} is locked", clip_id));
+    }
+
+    match call.name.as_str() {
+        "trim_clip" => {
+            let in_sec = number_arg(call, "in_sec")?;
+            let out_sec = number_arg(call, "out_sec")?;
+            let new_in_ticks = (in_sec * TICKS_PER_SECOND as f64).round() as i64;
+            let new_out_ticks = (out_sec * TICKS_PER_SECOND as f64).round() as i64;
+            if new_in_ticks < 0 || new_out_ticks <= new_in_ticks {
+                return Err("in_sec must be at least 0 and before out_sec".to_string());
+            }
+            if let Some(duration) = source_durations.get(&clip.asset_id) {
+                if new_out_ticks > *duration {
+                    return Err(format!(
+                        "out_sec is past the end of the source ({:.2}s)",
+                        *duration as f64 / TICKS_PER_SECOND as f64
+                    ));
+                }
+            }
+            Ok(TimelineOperation::TrimClip {
+                clip_id: clip_id.to_string(),
+                new_in_ticks,
+                new_out_ticks,
+            })
+        }
+        "reorder_clip" => {
+            let index = call.arguments["index"]
+                .as_u64()
+                .ok_or_else(|| "index must be a non-negative integer".to_string())? as usize;
+            if index >= clips.len() {
+                return Err(format!("index must be below {}", clips.len()));
+            }
+            // Where the clip starts once it's taken out and the others close up
+            let new_position_ticks = clips
+                .iter()
+                .filter(|c| c.id != clip_id)
+                .take(index)
+                .map(|c| c.out_ticks - c.in_ticks)
+                .sum();
+            Ok(TimelineOperation::ReorderClip {
+                clip_id: clip_id.to_string(),
+                new_position_ticks,
+            })
+        }
+        "delete_clip" => Ok(TimelineOperation::DeleteClip { clip_id: clip_id.to_string() }),
+        "set_speed" => {
+            let speed = number_arg(call, "speed")?;
+            if !(MIN_CLIP_SPEED..=MAX_CLIP_SPEED).contains(&speed) {
+                return Err(format!("speed must be between {} and {}", MIN_CLIP_SPEED, MAX_CLIP_SPEED));
+            }
+            Ok(TimelineOperation::SetClipSpeed {
+                clip_id: clip_id.to_string(),
+                speed,
+            })
+        }
+        other => Err(format!("Unknown tool: {}", other)),
+    }
+}
+
+/// The primary track's clips in timeline order
+pub fn primary_clips(timeline: &Timeline) -> Vec<&ClipInstance> {
+    let mut clips: Vec<&ClipInstance> = timeline
+        .tracks
+        .iter()
+        .filter(|t| t.id == 1)
+        .flat_map(|t| t.clips.iter())
+        .collect();
+    clips.sort_by_key(|c| c.timeline_start_ticks);
+    clips
+}
+
+fn number_arg(call: &ToolCall, name: &str) -> Result<f64, String> {
+    call.arguments[name]
+        .as_f64()
+        .filter(|v| v.is_finite())
+        .ok_or_else(|| format!("{} must be a number", name))
+}
@@ -0,0 +1,101 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::db::Database;
+
+/// The clips the conversation is currently about ("these clips"). It's kept across turns, so a
+/// follow-up like "make it snappier" works on the same selection.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkingSet {
+    pub segment_ids: Vec<i64>,
+    /// "selection" (picked in the UI) or "message" (named in a message, e.g. "use these three
+    /// clips" after candidates were shown)
+    pub source: String,
+    pub updated_at: String,
+}
+
+/// Phrases that widen the conversation back to all of the footage
+const CLEAR_PHRASES: &[&str] = &[
+    "all clips",
+    "all the clips",
+    "all my clips",
+    "all footage",
+    "all the footage",
+    "all my footage",
+    "everything",
+    "any clip",
+    "start over",
+    "from scratch",
+];
+
+/// Words that pick out clips just shown ("these", "those") and the nouns they can refer to
+const DEICTIC_WORDS: &[&str] = &["these", "those"];
+const CLIP_NOUNS: &[&str] = &["clip", "clips", "shot", "shots", "segment", "segments", "ones", "moments"];
+
+const NUMBER_WORDS: &[&str] = &["one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten"];
+
+/// Work out the working set for a new turn and persist it:
+/// - clips selected in the UI replace it
+/// - "all clips", "everything", "start over" and the like clear it
+/// - "these (three) clips" takes the first clips the assistant last showed
+/// - otherwise the previous turn's working set carries over
+pub fn resolve_working_set(
+    db: &Database,
+    project_id: i64,
+    message: &str,
+    selected_segment_ids: Option<&[i64]>,
+) -> Result<Option<WorkingSet>> {
+    if let Some(selected) = selected_segment_ids.filter(|ids| !ids.is_empty()) {
+        db.set_working_set(project_id, Some(selected), Some("selection"))?;
+        return get_working_set(db, project_id);
+    }
+
+    let message = message.to_lowercase();
+    if CLEAR_PHRASES.iter().any(|phrase| message.contains(phrase)) {
+        db.set_working_set(project_id, None, None)?;
+        return Ok(None);
+    }
+
+    if let Some(count) = referenced_clip_count(&message) {
+        let shown = db.get_last_shown_segments(project_id)?;
+        if !shown.is_empty() {
+            let take = count.unwrap_or(shown.len()).min(shown.len());
+            db.set_working_set(project_id, Some(&shown[..take]), Some("message"))?;
+        }
+    }
+    get_working_set(db, project_id)
+}
+
+pub fn get_working_set(db: &Database, project_id: i64) -> Result<Option<WorkingSet>> {
+    Ok(db
+        .get_working_set(project_id)?
+        .filter(|(segment_ids, _, _)| !segment_ids.is_empty())
+        .map(|(segment_ids, source, updated_at)| WorkingSet {
+            segment_ids,
+            source,
+            updated_at,
+        }))
+}
+
+/// Whether the message refers to clips just shown ("these clips", "those 3 shots"), with the
+/// count when it gives one
+fn referenced_clip_count(message: &str) -> Option<Option<usize>> {
+    let words: Vec<&str> = message
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect();
+    words.iter().enumerate().find_map(|(i, word)| {
+        if !DEICTIC_WORDS.contains(word) {
+            return None;
+        }
+        let next = words.get(i + 1)?;
+        if CLIP_NOUNS.contains(next) {
+            return Some(None);
+        }
+        let count = next
+            .parse::<usize>()
+            .ok()
+            .or_else(|| NUMBER_WORDS.iter().position(|n| n == next).map(|p| p + 1))?;
+        CLIP_NOUNS.contains(words.get(i + 2)?).then_some(Some(count))
+    })
+}
@@ -0,0 +1,166 @@
+use engine::compiler::{EditConstraints, EditEvent, EditPlan, EditSection};
+use serde::{Deserialize, Serialize};
+
+use crate::db::{MediaAssetInfo, Segment};
+
+use super::{apply_handles, apply_slow_motion, TICKS_PER_SECOND};
+
+/// One beat of a proposal: a section of the plan and the segments it plays, in order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanBeat {
+    pub beat_id: String,
+    /// Section the beat becomes ("intro", "body", "hook", ...)
+    pub section_type: String,
+    pub segment_ids: Vec<i64>,
+    /// How long the beat should run (None plays its segments in full)
+    pub target_sec: Option<f64>,
+}
+
+/// A change to one beat; fields left out keep their value
+#[derive(Debug, Clone, Deserialize)]
+pub struct BeatEdit {
+    pub beat_id: String,
+    pub segment_ids: Option<Vec<i64>>,
+    pub target_sec: Option<f64>,
+    /// Drop the target and play the beat's segments in full
+    #[serde(default)]
+    pub full_length: bool,
+}
+
+/// Beats of a plan: one per section, with the segments of its clips (captions and music
+/// aren't beats; the plan regenerated from them adds its own)
+pub fn beats_from_plan(plan: &EditPlan) -> Vec<PlanBeat> {
+    plan.sections
+        .iter()
+        .enumerate()
+        .map(|(idx, section)| {
+            let mut segment_ids: Vec<i64> = Vec::new();
+            for event in &section.events {
+                if let EditEvent::Clip { segment_id: Some(segment_id), .. } = event {
+                    if segment_ids.last() != Some(segment_id) {
+                        segment_ids.push(*segment_id);
+                    }
+                }
+            }
+            PlanBeat {
+                beat_id: format!("{}-{}", idx + 1, section.section_type),
+                section_type: section.section_type.clone(),
+                segment_ids,
+                target_sec: Some(section.target_duration as f64 / TICKS_PER_SECOND as f64),
+            }
+        })
+        .collect()
+}
+
+/// Apply edits to beats: `order` (every beat id, once) reorders them, then each edit changes
+/// its beat's segments or target. Segments must be among `known_segment_ids`.
+pub fn edit_beats(
+    beats: &[PlanBeat],
+    order: Option<&[String]>,
+    edits: &[BeatEdit],
+    known_segment_ids: &[i64],
+) -> Result<Vec<PlanBeat>, String> {
+    let mut edited: Vec<PlanBeat> = match order {
+        Some(order) => {
+            if order.len() != beats.len() {
+                return Err(format!("order lists {} beats, the proposal has {}", order.len(), beats.len()));
+            }
+            order
+                .iter()
+                .enumerate()
+                .map(|(idx, beat_id)| {
+                    if order[..idx].contains(beat_id) {
+                        return Err(format!("beat {} is listed twice in order", beat_id));
+                    }
+                    beats
+                        .iter()
+                        .find(|b| &b.beat_id == beat_id)
+                        .cloned()
+                        .ok_or_else(|| format!("no beat {}", beat_id))
+                })
+                .collect::<Result<_, _>>()?
+        }
+        None => beats.to_vec(),
+    };
+
+    for edit in edits {
+        let beat = edited
+            .iter_mut()
+            .find(|b| b.beat_id == edit.beat_id)
+            .ok_or_else(|| format!("no beat {}", edit.beat_id))?;
+        if let Some(segment_ids) = &edit.segment_ids {
+            if let Some(unknown) = segment_ids.iter().find(|id| !known_segment_ids.contains(id)) {
+                return Err(format!("segment {} isn't in the project", unknown));
+            }
+            beat.segment_ids = segment_ids.clone();
+        }
+        if let Some(target_sec) = edit.target_sec {
+            if !target_sec.is_finite() || target_sec <= 0.0 {
+                return Err(format!("target_sec of beat {} must be positive", edit.beat_id));
+            }
+            beat.target_sec = Some(target_sec);
+        }
+        if edit.full_length {
+            beat.target_sec = None;
+        }
+    }
+    Ok(edited)
+}
+
+/// Lay the beats out back to back, one section each. A beat whose segments run longer than
+/// its target has every clip shortened by the same share; shorter ones play in full. Handles
+/// and slow motion are applied as for any plan.
+pub fn plan_from_beats(
+    beats: &[PlanBeat],
+    constraints: EditConstraints,
+    segments_with_assets: &[(Segment, MediaAssetInfo)],
+) -> EditPlan {
+    let mut sections = Vec::new();
+    let mut position = 0i64;
+    for beat in beats {
+        let segments: Vec<&Segment> = beat
+            .segment_ids
+            .iter()
+            .filter_map(|id| segments_with_assets.iter().find(|(s, _)| s.id == *id).map(|(s, _)| s))
+            .collect();
+        let total: i64 = segments.iter().map(|s| s.end_ticks - s.start_ticks).sum();
+        let target = beat.target_sec.map(|t| (t * TICKS_PER_SECOND as f64) as i64);
+        let scale = match target {
+            Some(target) if total > target && total > 0 => target as f64 / total as f64,
+            _ => 1.0,
+        };
+
+        let mut events = Vec::new();
+        let start = position;
+        for segment in segments {
+            let length = ((segment.end_ticks - segment.start_ticks) as f64 * scale) as i64;
+            if length <= 0 {
+                continue;
+            }
+            events.push(EditEvent::Clip {
+                asset_id: segment.media_asset_id,
+                segment_id: Some(segment.id),
+                in_ticks: segment.start_ticks,
+                out_ticks: segment.start_ticks + length,
+                timeline_start_ticks: position,
+                track_id: 1,
+                speed: None,
+            });
+            position += length;
+        }
+        sections.push(EditSection {
+            section_type: beat.section_type.clone(),
+            target_duration: target.unwrap_or(position - start),
+            events,
+            constraints: None,
+        });
+    }
+
+    apply_handles(&mut sections, &constraints, segments_with_assets);
+    let suggestions = apply_slow_motion(&mut sections, constraints.slow_motion, segments_with_assets);
+    EditPlan {
+        sections,
+        constraints,
+        suggestions,
+    }
+}
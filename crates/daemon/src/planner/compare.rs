@@ -0,0 +1,148 @@
+use engine::compiler::{EditEvent, EditPlan};
+use serde::Serialize;
+use std::collections::HashSet;
+
+use crate::db::{MediaAssetInfo, Segment};
+use crate::media::segment_kind::SegmentKind;
+
+use super::TICKS_PER_SECOND;
+
+/// Side-by-side numbers for one candidate plan
+#[derive(Debug, Clone, Serialize)]
+pub struct PlanStats {
+    pub duration_sec: f64,
+    pub clip_count: usize,
+    /// Distinct assets the plan uses
+    pub asset_ids: Vec<i64>,
+    /// Share of the project's assets (with segments) the plan uses, 0-1
+    pub asset_coverage: f64,
+    pub median_clip_sec: f64,
+    /// How closely pacing, shot alternation and the a-roll share match the style profile, 0-1
+    /// (None without a profile)
+    pub style_match: Option<f64>,
+}
+
+/// Summarize a plan for comparison against other candidates
+pub fn plan_stats(
+    plan: &EditPlan,
+    segments_with_assets: &[(Segment, MediaAssetInfo)],
+    style_profile: Option<&serde_json::Value>,
+) -> PlanStats {
+    let clips: Vec<(i64, i64, i64, i64, i64)> = plan
+        .sections
+        .iter()
+        .flat_map(|s| s.events.iter())
+        .filter_map(|event| match event {
+            EditEvent::Clip {
+                asset_id,
+                in_ticks,
+                out_ticks,
+                timeline_start_ticks,
+                ..
+            } => Some((*asset_id, *in_ticks, *out_ticks, *timeline_start_ticks, event.clip_timeline_ticks()?)),
+            _ => None,
+        })
+        .collect();
+
+    let duration_ticks = clips
+        .iter()
+        .map(|(_, _, _, start, length)| start + length)
+        .max()
+        .unwrap_or(0);
+
+    let mut asset_ids: Vec<i64> = clips.iter().map(|c| c.0).collect::<HashSet<_>>().into_iter().collect();
+    asset_ids.sort();
+    let project_assets: HashSet<i64> = segments_with_assets.iter().map(|(_, asset)| asset.id).collect();
+    let asset_coverage = if project_assets.is_empty() {
+        0.0
+    } else {
+        asset_ids.iter().filter(|id| project_assets.contains(id)).count() as f64 / project_assets.len() as f64
+    };
+
+    let mut lengths: Vec<f64> = clips
+        .iter()
+        .map(|(_, _, _, _, length)| *length as f64 / TICKS_PER_SECOND as f64)
+        .collect();
+    lengths.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let median_clip_sec = lengths.get(lengths.len() / 2).copied().unwrap_or(0.0);
+
+    // The segment each clip overlaps most, in timeline order
+    let mut ordered = clips.clone();
+    ordered.sort_by_key(|c| c.3);
+    let clip_segments: Vec<(Option<&Segment>, i64)> = ordered
+        .iter()
+        .map(|(asset_id, in_ticks, out_ticks, _, length)| {
+            let segment = segments_with_assets
+                .iter()
+                .filter(|(segment, _)| segment.media_asset_id == *asset_id)
+                .map(|(segment, _)| (segment, out_ticks.min(&segment.end_ticks) - in_ticks.max(&segment.start_ticks)))
+                .filter(|(_, overlap)| *overlap > 0)
+                .max_by_key(|(_, overlap)| *overlap)
+                .map(|(segment, _)| segment);
+            (segment, *length)
+        })
+        .collect();
+    let shot_types: Vec<Option<&str>> = clip_segments
+        .iter()
+        .map(|(segment, _)| segment.and_then(|s| s.shot_type.as_deref()))
+        .collect();
+    // Share of the classified clip time that's a-roll
+    let (a_roll_ticks, classified_ticks) = clip_segments
+        .iter()
+        .filter_map(|(segment, length)| Some((SegmentKind::of((*segment)?)?, *length)))
+        .fold((0, 0), |(a_roll, total), (kind, length)| {
+            (a_roll + if kind.is_a_roll() { length } else { 0 }, total + length)
+        });
+    let a_roll_share = (classified_ticks > 0).then(|| a_roll_ticks as f64 / classified_ticks as f64);
+
+    PlanStats {
+        duration_sec: duration_ticks as f64 / TICKS_PER_SECOND as f64,
+        clip_count: clips.len(),
+        asset_ids,
+        asset_coverage,
+        median_clip_sec,
+        style_match: style_profile.and_then(|profile| style_match(profile, median_clip_sec, &shot_types, a_roll_share)),
+    }
+}
+
+/// Average of the pacing match (median clip length, on a log scale), the shot alternation match
+/// and the a-roll share match, over whichever of them the profile has
+fn style_match(
+    profile: &serde_json::Value,
+    median_clip_sec: f64,
+    shot_types: &[Option<&str>],
+    a_roll_share: Option<f64>,
+) -> Option<f64> {
+    let mut scores = Vec::new();
+
+    let target_median = profile
+        .get("pacing_stats")
+        .and_then(|p| p.get("median_clip_length"))
+        .and_then(|v| v.as_f64());
+    if let Some(target) = target_median.filter(|t| *t > 0.0) {
+        if median_clip_sec > 0.0 {
+            scores.push(1.0 / (1.0 + (median_clip_sec / target).ln().abs()));
+        }
+    }
+
+    let target_alternation = profile.get("shot_alternation_rate").and_then(|v| v.as_f64());
+    let typed: Vec<&str> = shot_types.iter().flatten().copied().collect();
+    if let Some(target) = target_alternation {
+        if typed.len() >= 2 {
+            let changes = typed.windows(2).filter(|w| w[0] != w[1]).count();
+            let rate = changes as f64 / (typed.len() - 1) as f64;
+            scores.push(1.0 - (rate - target).abs().min(1.0));
+        }
+    }
+
+    let target_a_roll = profile.get("a_roll_ratio").and_then(|v| v.as_f64());
+    if let (Some(target), Some(share)) = (target_a_roll, a_roll_share) {
+        scores.push(1.0 - (share - target).abs().min(1.0));
+    }
+
+    if scores.is_empty() {
+        None
+    } else {
+        Some(scores.iter().sum::<f64>() / scores.len() as f64)
+    }
+}
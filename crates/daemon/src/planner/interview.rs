@@ -0,0 +1,116 @@
+//! Interview cuts: answers to the questions asked, grouped into one section per topic. The
+//! questions themselves are left out, so the interviewees tell the story.
+
+use engine::compiler::{ConstraintConflict, EditConstraints, EditPlan, EditSection};
+
+use crate::db::{InterviewQuestion, MediaAssetInfo, Segment};
+use crate::interview;
+
+use super::{apply_handles, apply_slow_motion, calculate_clarity_score, clip_from_segment, sequential_events, ClipInfo, TICKS_PER_SECOND};
+
+/// An unbroken stretch of one interviewee's answer: segments of the same asset answering the
+/// same question, in source order
+struct AnswerRun<'a> {
+    question_position: i64,
+    segments: Vec<&'a (Segment, MediaAssetInfo)>,
+}
+
+impl AnswerRun<'_> {
+    fn duration(&self) -> i64 {
+        self.segments.iter().map(|(s, _)| s.end_ticks - s.start_ticks).sum()
+    }
+
+    fn score(&self) -> f64 {
+        self.segments.iter().map(|entry| calculate_clarity_score(entry)).sum()
+    }
+}
+
+fn answer_runs<'a>(question: &InterviewQuestion, segments_with_assets: &'a [(Segment, MediaAssetInfo)]) -> Vec<AnswerRun<'a>> {
+    let mut answers: Vec<&(Segment, MediaAssetInfo)> = question
+        .answer_segment_ids
+        .iter()
+        .filter_map(|id| segments_with_assets.iter().find(|(s, _)| s.id == *id))
+        .collect();
+    answers.sort_by_key(|(s, _)| (s.media_asset_id, s.start_ticks));
+
+    let mut runs: Vec<AnswerRun> = Vec::new();
+    for entry in answers {
+        match runs.last_mut() {
+            Some(run) if run.segments.last().is_some_and(|(s, _)| s.media_asset_id == entry.0.media_asset_id) => {
+                run.segments.push(entry)
+            }
+            _ => runs.push(AnswerRun {
+                question_position: question.position,
+                segments: vec![entry],
+            }),
+        }
+    }
+    runs
+}
+
+/// Plan an interview cut from the project's questions (see [`interview::detect`]): a section
+/// per topic in the order the topics were first asked about, each given an even share of the
+/// target length. A topic gets its clearest answer, then as many of the next clearest as fit
+/// its share, played in the order their questions were asked.
+pub fn generate_interview_plan(
+    segments_with_assets: &[(Segment, MediaAssetInfo)],
+    questions: &[InterviewQuestion],
+    constraints: EditConstraints,
+) -> Result<EditPlan, Vec<ConstraintConflict>> {
+    constraints.validate()?;
+
+    let topics: Vec<(String, Vec<AnswerRun>)> = interview::topics(questions)
+        .into_iter()
+        .map(|(topic, questions)| {
+            let runs = questions.iter().flat_map(|q| answer_runs(q, segments_with_assets)).collect();
+            (topic, runs)
+        })
+        .filter(|(_, runs): &(String, Vec<AnswerRun>)| !runs.is_empty())
+        .collect();
+    if topics.is_empty() {
+        return Err(vec![ConstraintConflict::new(
+            None,
+            "No answers to interview questions to cut from; detect the interview's questions first",
+        )]);
+    }
+
+    let target_length_ticks = constraints.target_length.unwrap_or(60 * TICKS_PER_SECOND); // Default 1 minute
+    let topic_budget = target_length_ticks / topics.len() as i64;
+
+    let mut sections = Vec::new();
+    let mut position = 0;
+    for (topic, mut runs) in topics {
+        runs.sort_by(|a, b| b.score().partial_cmp(&a.score()).unwrap_or(std::cmp::Ordering::Equal));
+        let mut used = 0;
+        let mut chosen: Vec<AnswerRun> = Vec::new();
+        for run in runs {
+            if chosen.is_empty() || used + run.duration() <= topic_budget {
+                used += run.duration();
+                chosen.push(run);
+            }
+        }
+        chosen.sort_by_key(|run| run.question_position);
+
+        let clips: Vec<ClipInfo> = chosen
+            .iter()
+            .flat_map(|run| run.segments.iter())
+            .map(|(segment, asset)| clip_from_segment(segment, asset))
+            .collect();
+        let (events, next_position) = sequential_events(&clips, position, None);
+        sections.push(EditSection {
+            section_type: topic,
+            target_duration: next_position - position,
+            events,
+            constraints: None,
+        });
+        position = next_position;
+    }
+
+    apply_handles(&mut sections, &constraints, segments_with_assets);
+    let suggestions = apply_slow_motion(&mut sections, constraints.slow_motion, segments_with_assets);
+    Ok(EditPlan {
+        sections,
+        constraints,
+        suggestions,
+    })
+}
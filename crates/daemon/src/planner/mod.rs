@@ -1,13 +1,118 @@
-use engine::compiler::{EditConstraints, EditEvent, EditPlan, EditSection};
+use engine::compiler::{
+    ConstraintConflict, EditConstraints, EditEvent, EditPlan, EditSection, PlanSuggestion, SectionConstraints,
+    SlowMotionMode,
+};
 use crate::db::{MediaAssetInfo, Segment};
+use crate::media::beat_template::BeatTemplate;
+use crate::media::segment_kind::{self, SegmentKind};
+use crate::media::slow_motion;
+use serde::{Deserialize, Serialize};
+
+pub mod beats;
+pub mod compare;
+pub mod interview;
+pub mod script;
 
 const TICKS_PER_SECOND: i64 = 48000;
 
+/// Shot alternation rate (from a style profile) at or above which every repeated shot type
+/// is avoided; below it only back-to-back close-ups are broken up
+const STRICT_ALTERNATION_THRESHOLD: f64 = 0.5;
+
+/// Minimum energy score for a segment to be used as the intro hook; below this the
+/// intro falls back to the best-scoring clip
+const HOOK_MIN_ENERGY: f64 = 0.5;
+
+/// How much a hook candidate's energy counts by what it shows: people speaking to camera and
+/// striking visuals open an edit best, other cutaways and screens less so
+const HOOK_FACE_WEIGHT: f64 = 1.0;
+const HOOK_VISUAL_WEIGHT: f64 = 0.9;
+const HOOK_OTHER_WEIGHT: f64 = 0.6;
+
+/// Outro bonuses on top of how calm a segment is: a goodbye (said or waved) ends an edit best,
+/// then wide scenery, then any scenery
+const OUTRO_GOODBYE_BONUS: f64 = 2.0;
+const OUTRO_WIDE_SCENERY_BONUS: f64 = 1.0;
+const OUTRO_SCENERY_BONUS: f64 = 0.5;
+
+/// Said when signing off (matched in lowercased transcripts; "bye" alone is matched as a word)
+const GOODBYE_PHRASES: &[&str] = &[
+    "goodbye",
+    "see you",
+    "see ya",
+    "thanks for watching",
+    "thank you for watching",
+    "until next time",
+    "signing off",
+];
+
+/// Clip length bounds when deriving clip length from a template's cut rate
+const TEMPLATE_MIN_CLIP_SECONDS: f64 = 0.5;
+const TEMPLATE_MAX_CLIP_SECONDS: f64 = 10.0;
+
+/// Default section lengths when nothing is pinned
+const DEFAULT_INTRO_SECONDS: i64 = 10;
+const DEFAULT_OUTRO_SECONDS: i64 = 5;
+
+/// B-roll shorter than this reads as a flash rather than a cutaway
+const B_ROLL_MIN_SECONDS: i64 = 1;
+/// Longest a single cutaway holds before cutting back to the a-roll
+const B_ROLL_MAX_SECONDS: i64 = 4;
+
+/// Default pre/post-roll around planned clips so cuts don't land exactly on segment boundaries
+const DEFAULT_HANDLE_MS: i64 = 200;
+
+/// How far a seed can move a segment's ranking score (±15%)
+const SEED_JITTER: f64 = 0.15;
+
+/// What kind of cut the planner assembles
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlanMode {
+    /// Intro, body and outro (or the style's beats) from the best clips (the default)
+    #[default]
+    Standard,
+    /// Answers to the interview's questions, a section per topic (see [`interview`])
+    Interview,
+}
+
+/// How candidate segments are ranked before the greedy fill; A/B candidates for the same
+/// intent use different strategies (and/or seeds)
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlanStrategy {
+    /// Longest, clearest transcripts first (the default)
+    #[default]
+    Clarity,
+    /// Most energetic moments first
+    Energy,
+    /// Clarity order, but rotating through assets so every clip source gets used
+    Coverage,
+}
+
 /// Generate an edit plan from segments
+/// The project's style profile (if any) tunes how strictly shot types alternate; section pins
+/// in the constraints (durations, asset/day filters, required segments) are honored, and pins
+/// that can't be satisfied are returned as conflicts instead of a plan
 pub fn generate_edit_plan(
     segments_with_assets: &[(Segment, MediaAssetInfo)],
     constraints: EditConstraints,
-) -> EditPlan {
+    style_profile: Option<&serde_json::Value>,
+) -> Result<EditPlan, Vec<ConstraintConflict>> {
+    generate_edit_plan_with_strategy(segments_with_assets, constraints, style_profile, PlanStrategy::Clarity, 0)
+}
+
+/// Generate an edit plan, ranking candidates by `strategy`; a non-zero `seed` perturbs the
+/// ranking so the same strategy can produce different (but reproducible) candidates
+pub fn generate_edit_plan_with_strategy(
+    segments_with_assets: &[(Segment, MediaAssetInfo)],
+    constraints: EditConstraints,
+    style_profile: Option<&serde_json::Value>,
+    strategy: PlanStrategy,
+    seed: u64,
+) -> Result<EditPlan, Vec<ConstraintConflict>> {
+    constraints.validate()?;
+
     // V1: Simple greedy selection based on transcript quality
     
     // Filter segments that have transcripts and reasonable length
@@ -26,115 +131,937 @@ pub fn generate_edit_plan(
         .collect();
 
     // Score segments: longer transcripts with reasonable length score higher
+    // (or higher energy, for the energy strategy)
+    let rank_score = |entry: &(Segment, MediaAssetInfo)| {
+        let score = match strategy {
+            PlanStrategy::Energy => segment_energy(&entry.0).unwrap_or(0.0),
+            PlanStrategy::Clarity | PlanStrategy::Coverage => calculate_clarity_score(entry),
+        };
+        score * seed_jitter(seed, entry.0.id)
+    };
     candidate_segments.sort_by(|a, b| {
-        let score_a = calculate_clarity_score(&**a);
-        let score_b = calculate_clarity_score(&**b);
+        let score_a = rank_score(a);
+        let score_b = rank_score(b);
         score_b.partial_cmp(&score_a).unwrap_or(std::cmp::Ordering::Equal)
     });
+    if strategy == PlanStrategy::Coverage {
+        candidate_segments = interleave_assets(candidate_segments);
+    }
 
     // Determine target length
     let target_length_ticks = constraints.target_length.unwrap_or(60 * TICKS_PER_SECOND); // Default 1 minute
-    
-    // Structure: intro, body, outro
-    let intro_duration = 10 * TICKS_PER_SECOND; // 10 seconds
-    let outro_duration = 5 * TICKS_PER_SECOND; // 5 seconds
-    let body_duration = target_length_ticks - intro_duration - outro_duration;
 
-    let mut timeline_position = 0i64;
-    let mut selected_clips = Vec::new();
+    // Hook: open on the highest-energy moment (laughter, fast speech, motion) if one stands out
+    let hook = select_hook(segments_with_assets);
+
+    // Alternate shot types so e.g. two talking-head close-ups don't cut back-to-back
+    let strict_alternation = style_profile
+        .and_then(|p| p.get("shot_alternation_rate"))
+        .and_then(|v| v.as_f64())
+        .map(|rate| rate >= STRICT_ALTERNATION_THRESHOLD)
+        .unwrap_or(false);
+
+    // Share of a-roll the references keep; the rest is b-roll cut in around it
+    let a_roll_ratio = style_profile
+        .and_then(|p| p.get("a_roll_ratio"))
+        .and_then(|v| v.as_f64())
+        .filter(|ratio| *ratio > 0.0 && *ratio < 1.0);
+    let hook_id = hook.map(|(segment, _)| segment.id);
+
+    let template = beat_template(style_profile);
+    let section_types: Vec<String> = match template {
+        Some(ref template) => template.sections.iter().map(|s| s.section_type.clone()).collect(),
+        None => ["intro", "body", "outro"].iter().map(|s| s.to_string()).collect(),
+    };
+    let pins = SectionPins::new(&constraints.section_overrides, segments_with_assets);
+    pins.validate(&section_types)?;
+
+    // A reference beat template replaces the fixed intro/body/outro structure
+    if let Some(template) = template {
+        let pool: Vec<ClipInfo> = candidate_segments
+            .iter()
+            .filter(|(segment, _)| hook.is_none_or(|(hook_segment, _)| hook_segment.id != segment.id))
+            .filter(|(segment, _)| !pins.is_required(segment.id))
+            .map(|(segment, asset)| clip_from_segment(segment, asset))
+            .collect();
+        let pool = alternate_shot_types(pool, strict_alternation);
+        let pool = match a_roll_ratio {
+            Some(ratio) => {
+                let taken: Vec<i64> = pool.iter().map(|c| c.segment_id).chain(hook_id).collect();
+                weave_b_roll(pool, b_roll_pool(segments_with_assets, &taken, &pins), ratio)
+            }
+            None => pool,
+        };
+        let hook_clip = hook
+            .filter(|(segment, _)| !pins.is_required(segment.id))
+            .map(|(segment, asset)| clip_from_segment(segment, asset));
+        let mut sections = instantiate_beat_template(&template, hook_clip, pool, target_length_ticks, &pins);
+        // Handles would push every later cut off the music
+        if !template.follows_music {
+            apply_handles(&mut sections, &constraints, segments_with_assets);
+        }
+        let suggestions = apply_slow_motion(&mut sections, constraints.slow_motion, segments_with_assets);
+        return Ok(EditPlan {
+            sections,
+            constraints,
+            suggestions,
+        });
+    }
+
+    // Structure: intro, body, outro (stretched to fit required clips, clamped to pinned durations)
+    let intro_required = pins.required_clips("intro");
+    let body_required = pins.required_clips("body");
+    let outro_required = pins.required_clips("outro");
+    let intro_duration = pins.section_duration("intro", DEFAULT_INTRO_SECONDS * TICKS_PER_SECOND, &intro_required);
+    let outro_duration = pins.section_duration("outro", DEFAULT_OUTRO_SECONDS * TICKS_PER_SECOND, &outro_required);
+    let body_duration = pins.section_duration(
+        "body",
+        (target_length_ticks - intro_duration - outro_duration).max(0),
+        &body_required,
+    );
+
+    // Clips that a section may take from the shared pool (required clips stay in their own section)
+    let unpinned_clip = |section_type: &str, segment: &Segment, asset: &MediaAssetInfo| {
+        let clip = clip_from_segment(segment, asset);
+        (!pins.is_required(segment.id) && pins.allows(section_type, &clip)).then_some(clip)
+    };
+
+    // Intro: required clips, else the hook, else the best clip the intro allows (someone
+    // speaking to camera if there is one)
+    let intro_clips: Vec<ClipInfo> = if !intro_required.is_empty() {
+        intro_required
+    } else {
+        hook.and_then(|(segment, asset)| unpinned_clip("intro", segment, asset))
+            .or_else(|| {
+                candidate_segments
+                    .iter()
+                    .filter(|(segment, _)| SegmentKind::of(segment).is_some_and(|k| k.is_a_roll()))
+                    .find_map(|(segment, asset)| unpinned_clip("intro", segment, asset))
+            })
+            .or_else(|| {
+                candidate_segments
+                    .iter()
+                    .filter(|(segment, _)| SegmentKind::of(segment) != Some(SegmentKind::Transition))
+                    .find_map(|(segment, asset)| unpinned_clip("intro", segment, asset))
+            })
+            .into_iter()
+            .collect()
+    };
+
+    // B-roll the body can cut away to, when the style keeps an a-roll share
+    let b_roll = a_roll_ratio
+        .map(|_| {
+            let taken: Vec<i64> = intro_clips.iter().map(|c| c.segment_id).chain(hook_id).collect();
+            b_roll_pool(segments_with_assets, &taken, &pins)
+                .into_iter()
+                .filter(|clip| pins.allows("body", clip))
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    // With b-roll to weave in, the a-roll only fills its share of the body
+    let a_roll_duration = match a_roll_ratio {
+        Some(ratio) if !b_roll.is_empty() => (body_duration as f64 * ratio) as i64,
+        _ => body_duration,
+    };
 
-    // Select segments for body (most of the content)
+    // Body: required clips first, then the best remaining clips until the body is filled
+    let mut body_clips = body_required;
     for (segment, asset) in candidate_segments.iter() {
-        let clip_duration = segment.end_ticks - segment.start_ticks;
-        
-        // Check if we've filled the body
-        let current_body_duration: i64 = selected_clips.iter().map(|c: &ClipInfo| c.duration).sum();
-        if current_body_duration >= body_duration {
+        let current_body_duration: i64 = body_clips.iter().map(|c| c.duration).sum();
+        if current_body_duration >= a_roll_duration {
             break;
         }
-
-        // Add clip for body
-        selected_clips.push(ClipInfo {
-            asset_id: asset.id,
-            in_ticks: segment.start_ticks,
-            out_ticks: segment.end_ticks,
-            timeline_start: timeline_position,
-            duration: clip_duration,
-        });
-        
-        timeline_position += clip_duration;
+        if intro_clips.iter().any(|c| c.segment_id == segment.id) {
+            continue;
+        }
+        if let Some(clip) = unpinned_clip("body", segment, asset) {
+            body_clips.push(clip);
+        }
     }
+    let body_clips = alternate_shot_types(body_clips, strict_alternation);
+    let body_clips = match a_roll_ratio {
+        Some(ratio) => {
+            let b_roll = b_roll.into_iter().filter(|b| body_clips.iter().all(|c| c.segment_id != b.segment_id)).collect();
+            weave_b_roll(body_clips, b_roll, ratio)
+        }
+        None => body_clips,
+    };
 
     // Build sections
     let mut sections = Vec::new();
-    
-    // Intro: first clip (if available)
-    if let Some(first_clip) = selected_clips.first() {
-        let intro_events = vec![EditEvent::Clip {
-            asset_id: first_clip.asset_id,
-            in_ticks: first_clip.in_ticks,
-            out_ticks: first_clip.out_ticks.min(first_clip.in_ticks + intro_duration),
-            timeline_start_ticks: 0,
-            track_id: 1,
-        }];
+
+    // Intro: placed from the start, trimmed to the intro length
+    if !intro_clips.is_empty() {
+        let (intro_events, _) = sequential_events(&intro_clips, 0, Some(intro_duration));
         sections.push(EditSection {
             section_type: "intro".to_string(),
             target_duration: intro_duration,
             events: intro_events,
+            constraints: pins.pin_for("intro").cloned(),
         });
     }
 
     // Body: remaining clips
-    let body_start = if !selected_clips.is_empty() { intro_duration } else { 0 };
-    let mut body_position = body_start;
-    let mut body_events = Vec::new();
-    
-    // Skip first clip for body (already in intro)
-    for clip in selected_clips.iter().skip(1) {
-        body_events.push(EditEvent::Clip {
-            asset_id: clip.asset_id,
-            in_ticks: clip.in_ticks,
-            out_ticks: clip.out_ticks,
-            timeline_start_ticks: body_position,
-            track_id: 1,
-        });
-        body_position += clip.duration;
-    }
+    let body_start = if !intro_clips.is_empty() { intro_duration } else { 0 };
+    let (body_events, body_position) = sequential_events(&body_clips, body_start, None);
 
     sections.push(EditSection {
         section_type: "body".to_string(),
         target_duration: body_duration,
         events: body_events,
+        constraints: pins.pin_for("body").cloned(),
     });
 
-    // Outro: last clip (if available)
-    if let Some(last_clip) = selected_clips.last() {
-        let outro_events = vec![EditEvent::Clip {
-            asset_id: last_clip.asset_id,
-            in_ticks: last_clip.out_ticks.saturating_sub(outro_duration),
-            out_ticks: last_clip.out_ticks,
-            timeline_start_ticks: body_position,
-            track_id: 1,
-        }];
+    // Outro: required clips, else the unused clip that winds down best (a goodbye, wide
+    // scenery, something calm), else the tail of the last clip (if the outro allows it)
+    let outro_events = if !outro_required.is_empty() {
+        sequential_events(&outro_required, body_position, Some(outro_duration)).0
+    } else {
+        let used: Vec<i64> = intro_clips.iter().chain(body_clips.iter()).map(|c| c.segment_id).collect();
+        let outro_clip = select_outro(segments_with_assets, |segment, asset| {
+            !used.contains(&segment.id) && unpinned_clip("outro", segment, asset).is_some()
+        })
+        .and_then(|(segment, asset)| unpinned_clip("outro", segment, asset))
+            .or_else(|| {
+                body_clips
+                    .last()
+                    .or(intro_clips.last())
+                    .filter(|clip| pins.allows("outro", clip))
+                    .cloned()
+            })
+            .or_else(|| {
+                candidate_segments
+                    .iter()
+                    .find_map(|(segment, asset)| unpinned_clip("outro", segment, asset))
+            });
+        outro_clip
+            .map(|clip| vec![EditEvent::Clip {
+                asset_id: clip.asset_id,
+                segment_id: Some(clip.segment_id),
+                in_ticks: clip.out_ticks.saturating_sub(outro_duration).max(clip.in_ticks),
+                out_ticks: clip.out_ticks,
+                timeline_start_ticks: body_position,
+                track_id: 1,
+                speed: None,
+            }])
+            .unwrap_or_default()
+    };
+    if !outro_events.is_empty() {
         sections.push(EditSection {
             section_type: "outro".to_string(),
             target_duration: outro_duration,
             events: outro_events,
+            constraints: pins.pin_for("outro").cloned(),
         });
     }
 
-    EditPlan {
+    apply_handles(&mut sections, &constraints, segments_with_assets);
+    let suggestions = apply_slow_motion(&mut sections, constraints.slow_motion, segments_with_assets);
+    Ok(EditPlan {
         sections,
         constraints,
+        suggestions,
+    })
+}
+
+/// Widen every planned clip by the pre/post-roll handles, clamped to the asset's bounds and
+/// never running into another selected range of the same asset (a gap between two selected
+/// ranges is split between them). Later events shift right by the length added before them,
+/// so gaps and section boundaries keep their relative spacing.
+fn apply_handles(
+    sections: &mut [EditSection],
+    constraints: &EditConstraints,
+    segments_with_assets: &[(Segment, MediaAssetInfo)],
+) {
+    let default_ticks = DEFAULT_HANDLE_MS * TICKS_PER_SECOND / 1000;
+    let pre = constraints.handle_pre_ticks.unwrap_or(default_ticks).max(0);
+    let post = constraints.handle_post_ticks.unwrap_or(default_ticks).max(0);
+    if pre == 0 && post == 0 {
+        return;
+    }
+
+    // Source ranges of every selected clip, before any handles
+    let selected: Vec<(i64, i64, i64)> = sections
+        .iter()
+        .flat_map(|section| section.events.iter())
+        .filter_map(|event| match event {
+            EditEvent::Clip { asset_id, in_ticks, out_ticks, .. } => Some((*asset_id, *in_ticks, *out_ticks)),
+            _ => None,
+        })
+        .collect();
+    let asset_duration = |asset_id: i64| {
+        segments_with_assets
+            .iter()
+            .find(|(_, asset)| asset.id == asset_id)
+            .map(|(_, asset)| asset.duration_ticks)
+            .filter(|&d| d > 0)
+    };
+
+    let mut shift = 0i64;
+    for section in sections.iter_mut() {
+        for event in section.events.iter_mut() {
+            let EditEvent::Clip { asset_id, in_ticks, out_ticks, timeline_start_ticks, .. } = event else {
+                continue;
+            };
+            let (asset_id, start, end) = (*asset_id, *in_ticks, *out_ticks);
+            let same_asset = selected.iter().filter(|(id, _, _)| *id == asset_id);
+            let floor = same_asset
+                .clone()
+                .filter(|(_, _, other_end)| *other_end <= start)
+                .map(|(_, _, other_end)| start - (start - other_end) / 2)
+                .max()
+                .unwrap_or(0);
+            let ceiling = same_asset
+                .filter(|(_, other_start, _)| *other_start >= end)
+                .map(|(_, other_start, _)| end + (other_start - end) / 2)
+                .min()
+                .unwrap_or(i64::MAX)
+                .min(asset_duration(asset_id).unwrap_or(i64::MAX));
+
+            let new_in = (start - pre).max(floor).min(start);
+            let new_out = (end + post).min(ceiling).max(end);
+            *timeline_start_ticks += shift;
+            *in_ticks = new_in;
+            *out_ticks = new_out;
+            shift += (start - new_in) + (new_out - end);
+        }
     }
 }
 
+/// Slow down planned clips of high-motion scenery shot at a high frame rate, or in suggest mode
+/// list them instead. A slowed clip keeps its length on the timeline by covering less of its
+/// source, so nothing after it moves.
+fn apply_slow_motion(
+    sections: &mut [EditSection],
+    mode: SlowMotionMode,
+    segments_with_assets: &[(Segment, MediaAssetInfo)],
+) -> Vec<PlanSuggestion> {
+    let mut suggestions = Vec::new();
+    if mode == SlowMotionMode::Off {
+        return suggestions;
+    }
+    for section in sections.iter_mut() {
+        for event in section.events.iter_mut() {
+            let EditEvent::Clip { asset_id, segment_id: Some(segment_id), in_ticks, out_ticks, speed, .. } = event else {
+                continue;
+            };
+            let Some((segment, asset)) = segments_with_assets.iter().find(|(s, _)| s.id == *segment_id) else {
+                continue;
+            };
+            let fps = slow_motion::frame_rate(asset.fps_num, asset.fps_den);
+            if !slow_motion::is_slow_motion_capable(fps) || !slow_motion::is_scenic_action(segment) {
+                continue;
+            }
+            let slow = slow_motion::slow_motion_speed(fps);
+            if mode == SlowMotionMode::Auto {
+                *out_ticks = *in_ticks + ((*out_ticks - *in_ticks) as f64 * slow).round() as i64;
+                *speed = Some(slow);
+            } else {
+                suggestions.push(PlanSuggestion {
+                    kind: slow_motion::SLOW_MOTION_LABEL.to_string(),
+                    section_type: section.section_type.clone(),
+                    asset_id: *asset_id,
+                    segment_id: Some(*segment_id),
+                    speed: slow,
+                    message: format!(
+                        "Shot at {:.0} fps, this action could play at {:.0}% speed as slow motion",
+                        fps,
+                        slow * 100.0
+                    ),
+                });
+            }
+        }
+    }
+    suggestions
+}
+
+#[derive(Clone)]
 struct ClipInfo {
+    segment_id: i64,
     asset_id: i64,
     in_ticks: i64,
     out_ticks: i64,
     timeline_start: i64,
     duration: i64,
+    shot_type: Option<String>,
+    energy: Option<f64>,
+    /// Capture date (YYYY-MM-DD), when the footage carries a creation time
+    capture_date: Option<String>,
+    kind: Option<SegmentKind>,
+}
+
+fn clip_from_segment(segment: &Segment, asset: &MediaAssetInfo) -> ClipInfo {
+    ClipInfo {
+        segment_id: segment.id,
+        asset_id: asset.id,
+        in_ticks: segment.start_ticks,
+        out_ticks: segment.end_ticks,
+        timeline_start: 0,
+        duration: segment.end_ticks - segment.start_ticks,
+        shot_type: segment.shot_type.clone(),
+        energy: segment_energy(segment),
+        capture_date: capture_date(segment),
+        kind: SegmentKind::of(segment),
+    }
+}
+
+fn capture_date(segment: &Segment) -> Option<String> {
+    segment
+        .capture_time
+        .as_deref()
+        .and_then(|t| t.get(..10))
+        .map(|d| d.to_string())
+}
+
+/// "intro" and "hook" both name the opening section (fixed plans call it intro, templates hook)
+fn section_matches(pin_section: &str, section_type: &str) -> bool {
+    let is_opening = |s: &str| s == "intro" || s == "hook";
+    pin_section == section_type || (is_opening(pin_section) && is_opening(section_type))
+}
+
+/// The user's section pins, resolved against the project's footage
+struct SectionPins<'a> {
+    pins: &'a [SectionConstraints],
+    segments_with_assets: &'a [(Segment, MediaAssetInfo)],
+    /// Distinct capture dates in order; capture_day N is capture_days[N - 1]
+    capture_days: Vec<String>,
+}
+
+impl<'a> SectionPins<'a> {
+    fn new(pins: &'a [SectionConstraints], segments_with_assets: &'a [(Segment, MediaAssetInfo)]) -> Self {
+        let mut capture_days: Vec<String> = segments_with_assets
+            .iter()
+            .filter_map(|(segment, _)| capture_date(segment))
+            .collect();
+        capture_days.sort();
+        capture_days.dedup();
+        SectionPins {
+            pins,
+            segments_with_assets,
+            capture_days,
+        }
+    }
+
+    fn pin_for(&self, section_type: &str) -> Option<&'a SectionConstraints> {
+        self.pins.iter().find(|p| section_matches(&p.section_type, section_type))
+    }
+
+    /// Whether a section's asset/day filters admit the clip
+    fn allows(&self, section_type: &str, clip: &ClipInfo) -> bool {
+        let Some(pin) = self.pin_for(section_type) else {
+            return true;
+        };
+        if pin.allowed_asset_ids.as_ref().is_some_and(|ids| !ids.contains(&clip.asset_id)) {
+            return false;
+        }
+        if let Some(day) = pin.capture_day {
+            let date = self.capture_days.get((day as usize).saturating_sub(1));
+            if date.is_none() || clip.capture_date.as_ref() != date {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Whether some section requires this segment (it then appears only there)
+    fn is_required(&self, segment_id: i64) -> bool {
+        self.pins.iter().any(|p| p.required_segment_ids.contains(&segment_id))
+    }
+
+    fn required_clips(&self, section_type: &str) -> Vec<ClipInfo> {
+        let Some(pin) = self.pin_for(section_type) else {
+            return Vec::new();
+        };
+        pin.required_segment_ids
+            .iter()
+            .filter_map(|id| self.segments_with_assets.iter().find(|(segment, _)| segment.id == *id))
+            .map(|(segment, asset)| clip_from_segment(segment, asset))
+            .collect()
+    }
+
+    /// Section length: the default, stretched to fit required clips, clamped to the pinned range
+    fn section_duration(&self, section_type: &str, default: i64, required: &[ClipInfo]) -> i64 {
+        let required_total: i64 = required.iter().map(|c| c.duration).sum();
+        let mut duration = default.max(required_total);
+        if let Some(pin) = self.pin_for(section_type) {
+            if let Some(max) = pin.max_duration {
+                duration = duration.min(max);
+            }
+            if let Some(min) = pin.min_duration {
+                duration = duration.max(min);
+            }
+        }
+        duration
+    }
+
+    /// Conflicts between the pins and this project's footage and plan structure
+    fn validate(&self, section_types: &[String]) -> Result<(), Vec<ConstraintConflict>> {
+        let mut conflicts = Vec::new();
+
+        for pin in self.pins {
+            let section = Some(pin.section_type.as_str());
+            if !section_types.iter().any(|s| section_matches(&pin.section_type, s)) {
+                conflicts.push(ConstraintConflict::new(
+                    section,
+                    format!(
+                        "this plan has no '{}' section (sections: {})",
+                        pin.section_type,
+                        section_types.join(", ")
+                    ),
+                ));
+                continue;
+            }
+
+            if let Some(day) = pin.capture_day {
+                if self.capture_days.is_empty() {
+                    conflicts.push(ConstraintConflict::new(
+                        section,
+                        format!("'{}' is pinned to day {}, but the footage has no capture dates", pin.section_type, day),
+                    ));
+                } else if day as usize > self.capture_days.len() {
+                    conflicts.push(ConstraintConflict::new(
+                        section,
+                        format!(
+                            "'{}' is pinned to day {}, but the footage only covers {} day(s)",
+                            pin.section_type,
+                            day,
+                            self.capture_days.len()
+                        ),
+                    ));
+                }
+            }
+
+            let mut required_total = 0i64;
+            for segment_id in &pin.required_segment_ids {
+                let Some((segment, asset)) = self.segments_with_assets.iter().find(|(s, _)| s.id == *segment_id) else {
+                    conflicts.push(ConstraintConflict::new(
+                        section,
+                        format!("segment {} required in '{}' isn't in this project", segment_id, pin.section_type),
+                    ));
+                    continue;
+                };
+                let clip = clip_from_segment(segment, asset);
+                required_total += clip.duration;
+                if !self.allows(&pin.section_type, &clip) {
+                    conflicts.push(ConstraintConflict::new(
+                        section,
+                        format!(
+                            "segment {} is required in '{}' but doesn't match its asset/day filter",
+                            segment_id, pin.section_type
+                        ),
+                    ));
+                }
+            }
+            if let Some(max) = pin.max_duration {
+                if required_total > max {
+                    conflicts.push(ConstraintConflict::new(
+                        section,
+                        format!(
+                            "clips required in '{}' run {:.1}s, longer than its {:.1}s max",
+                            pin.section_type,
+                            required_total as f64 / TICKS_PER_SECOND as f64,
+                            max as f64 / TICKS_PER_SECOND as f64,
+                        ),
+                    ));
+                }
+            }
+
+            let filtered = pin.allowed_asset_ids.is_some() || pin.capture_day.is_some();
+            if filtered
+                && pin.required_segment_ids.is_empty()
+                && !self
+                    .segments_with_assets
+                    .iter()
+                    .any(|(segment, asset)| self.allows(&pin.section_type, &clip_from_segment(segment, asset)))
+            {
+                conflicts.push(ConstraintConflict::new(
+                    section,
+                    format!("no clips match the asset/day filter on '{}'", pin.section_type),
+                ));
+            }
+        }
+
+        if conflicts.is_empty() {
+            Ok(())
+        } else {
+            Err(conflicts)
+        }
+    }
+}
+
+/// Place clips back to back from `start`, trimming the last one to `max_length` if given.
+/// Returns the events and the timeline position after them.
+fn sequential_events(clips: &[ClipInfo], start: i64, max_length: Option<i64>) -> (Vec<EditEvent>, i64) {
+    let end = max_length.map(|length| start + length);
+    let mut position = start;
+    let mut events = Vec::new();
+    for clip in clips {
+        let length = match end {
+            Some(end) => clip.duration.min(end - position),
+            None => clip.duration,
+        };
+        if length <= 0 {
+            break;
+        }
+        events.push(EditEvent::Clip {
+            asset_id: clip.asset_id,
+            segment_id: Some(clip.segment_id),
+            in_ticks: clip.in_ticks,
+            out_ticks: clip.in_ticks + length,
+            timeline_start_ticks: position,
+            track_id: 1,
+            speed: None,
+        });
+        position += length;
+    }
+    (events, position)
+}
+
+/// Primary beat template from the style profile, if one was extracted
+fn beat_template(style_profile: Option<&serde_json::Value>) -> Option<BeatTemplate> {
+    style_profile
+        .and_then(|p| p.get("beat_template"))
+        .filter(|t| !t.is_null())
+        .and_then(|t| serde_json::from_value::<BeatTemplate>(t.clone()).ok())
+        .filter(|t| !t.sections.is_empty() && t.duration_sec > 0.0)
+}
+
+/// Lay the user's clips onto a reference template: the hook keeps its absolute length (up to a
+/// third of the edit), later sections are scaled to fill the target length, each section cuts
+/// at the reference's rate, and sections that start on a music drop open on the most energetic
+/// remaining clip. Pinned sections open with their required clips and only draw pool clips
+/// their filters allow. A template that follows a music track keeps its sections' own times
+/// (the edit ends where the target length cuts the track off), and a cut lands on each of its
+/// impacts a clip would otherwise play across.
+fn instantiate_beat_template(
+    template: &BeatTemplate,
+    hook_clip: Option<ClipInfo>,
+    pool: Vec<ClipInfo>,
+    target_length_ticks: i64,
+    pins: &SectionPins,
+) -> Vec<EditSection> {
+    let target_sec = target_length_ticks as f64 / TICKS_PER_SECOND as f64;
+    let hook_sec = template.hook_length_sec.min(target_sec / 3.0);
+    let template_rest_sec = (template.duration_sec - template.hook_length_sec).max(0.0);
+    let rest_scale = if template_rest_sec > 0.0 {
+        (target_sec - hook_sec) / template_rest_sec
+    } else {
+        0.0
+    };
+
+    let impacts: Vec<i64> = template
+        .impacts
+        .iter()
+        .filter(|_| template.follows_music)
+        .map(|t| (t * TICKS_PER_SECOND as f64) as i64)
+        .collect();
+
+    let mut pool: std::collections::VecDeque<ClipInfo> = hook_clip.into_iter().chain(pool).collect();
+    let mut sections = Vec::new();
+    let mut position = 0i64;
+    // A pin on a repeated section type (e.g. "drop") places its required clips in the first one
+    let mut placed_required: Vec<String> = Vec::new();
+
+    for section in &template.sections {
+        let section_sec = if template.follows_music {
+            (section.end_sec.min(target_sec) - section.start_sec).max(0.0)
+        } else if section.section_type == "hook" {
+            hook_sec
+        } else {
+            (section.end_sec - section.start_sec) * rest_scale
+        };
+        let required = if placed_required.contains(&section.section_type) {
+            Vec::new()
+        } else {
+            pins.required_clips(&section.section_type)
+        };
+        let section_ticks = pins.section_duration(
+            &section.section_type,
+            (section_sec * TICKS_PER_SECOND as f64) as i64,
+            &required,
+        );
+        if section_ticks <= 0 {
+            continue;
+        }
+        placed_required.push(section.section_type.clone());
+
+        let clip_sec = if section.cuts_per_10s > 0.0 {
+            (10.0 / section.cuts_per_10s).clamp(TEMPLATE_MIN_CLIP_SECONDS, TEMPLATE_MAX_CLIP_SECONDS)
+        } else {
+            TEMPLATE_MAX_CLIP_SECONDS
+        };
+        let clip_ticks = (clip_sec * TICKS_PER_SECOND as f64) as i64;
+
+        if section.section_type == "drop" {
+            let best = pool
+                .iter()
+                .enumerate()
+                .filter(|(_, c)| pins.allows(&section.section_type, c))
+                .filter_map(|(idx, c)| c.energy.map(|e| (idx, e)))
+                .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+                .map(|(idx, _)| idx);
+            if let Some(clip) = best.and_then(|idx| pool.remove(idx)) {
+                pool.push_front(clip);
+            }
+        }
+
+        let section_end = position + section_ticks;
+        let (mut events, required_end) = sequential_events(&required, position, Some(section_ticks));
+        position = required_end;
+        while position < section_end {
+            let Some(clip) = pool
+                .iter()
+                .position(|c| pins.allows(&section.section_type, c))
+                .and_then(|idx| pool.remove(idx))
+            else {
+                break;
+            };
+            let mut length = clip_ticks.min(clip.duration).min(section_end - position);
+            let shortest = (TEMPLATE_MIN_CLIP_SECONDS * TICKS_PER_SECOND as f64) as i64;
+            if let Some(impact) = impacts.iter().copied().find(|t| *t >= position + shortest && *t < position + length) {
+                length = impact - position;
+            }
+            if length <= 0 {
+                continue;
+            }
+            events.push(EditEvent::Clip {
+                asset_id: clip.asset_id,
+                segment_id: Some(clip.segment_id),
+                in_ticks: clip.in_ticks,
+                out_ticks: clip.in_ticks + length,
+                timeline_start_ticks: position,
+                track_id: 1,
+                speed: None,
+            });
+            position += length;
+        }
+
+        sections.push(EditSection {
+            section_type: section.section_type.clone(),
+            target_duration: section_ticks,
+            events,
+            constraints: pins.pin_for(&section.section_type).cloned(),
+        });
+    }
+
+    sections
+}
+
+/// Segments to cut away to: classified kinds other than a-roll and transitions, long enough to
+/// read, most energetic first. `taken` segments (already planned) are left out, as are segments
+/// pinned to a section.
+fn b_roll_pool(segments_with_assets: &[(Segment, MediaAssetInfo)], taken: &[i64], pins: &SectionPins) -> Vec<ClipInfo> {
+    let mut pool: Vec<ClipInfo> = segments_with_assets
+        .iter()
+        .filter(|(segment, _)| !taken.contains(&segment.id) && !pins.is_required(segment.id))
+        .map(|(segment, asset)| clip_from_segment(segment, asset))
+        .filter(|clip| clip.kind.is_some_and(|k| !k.is_a_roll() && k != SegmentKind::Transition))
+        .filter(|clip| clip.duration >= B_ROLL_MIN_SECONDS * TICKS_PER_SECOND)
+        .collect();
+    pool.sort_by(|a, b| {
+        b.energy
+            .unwrap_or(0.0)
+            .partial_cmp(&a.energy.unwrap_or(0.0))
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    pool
+}
+
+/// Cut b-roll in after a-roll clips (keeping their order) whenever the b-roll placed so far
+/// falls behind its share of the edit, `1 - a_roll_ratio`. Clips that aren't a-roll count
+/// toward the b-roll share wherever they already sit; cutaways are trimmed to
+/// `B_ROLL_MAX_SECONDS`. Timeline positions are recomputed afterwards.
+fn weave_b_roll(clips: Vec<ClipInfo>, b_roll: Vec<ClipInfo>, a_roll_ratio: f64) -> Vec<ClipInfo> {
+    let mut b_roll: std::collections::VecDeque<ClipInfo> = b_roll.into();
+    let mut woven: Vec<ClipInfo> = Vec::with_capacity(clips.len() + b_roll.len());
+    let (mut a_roll_ticks, mut b_roll_ticks) = (0i64, 0i64);
+    for clip in clips {
+        let is_a_roll = clip.kind.is_none_or(|k| k.is_a_roll());
+        if is_a_roll {
+            a_roll_ticks += clip.duration;
+        } else {
+            b_roll_ticks += clip.duration;
+        }
+        woven.push(clip);
+        if !is_a_roll {
+            continue;
+        }
+        let b_roll_target = a_roll_ticks as f64 * (1.0 - a_roll_ratio) / a_roll_ratio;
+        while (b_roll_ticks as f64) < b_roll_target {
+            let Some(mut cutaway) = b_roll.pop_front() else {
+                break;
+            };
+            cutaway.duration = cutaway.duration.min(B_ROLL_MAX_SECONDS * TICKS_PER_SECOND);
+            cutaway.out_ticks = cutaway.in_ticks + cutaway.duration;
+            b_roll_ticks += cutaway.duration;
+            woven.push(cutaway);
+        }
+    }
+
+    let mut position = 0i64;
+    for clip in &mut woven {
+        clip.timeline_start = position;
+        position += clip.duration;
+    }
+    woven
+}
+
+/// Whether cutting from `prev` to `next` repeats framing in a way that should be avoided
+fn is_repeated_shot(prev: &ClipInfo, next: &ClipInfo, strict: bool) -> bool {
+    match (&prev.shot_type, &next.shot_type) {
+        (Some(a), Some(b)) if a == b => strict || a == "close_up",
+        _ => false,
+    }
+}
+
+/// Reorder clips (keeping score order as much as possible) so repeated shot types are not
+/// adjacent: when the next clip repeats the previous framing, pull forward the next clip
+/// that doesn't. Timeline positions are recomputed afterwards.
+fn alternate_shot_types(clips: Vec<ClipInfo>, strict: bool) -> Vec<ClipInfo> {
+    let mut remaining: std::collections::VecDeque<ClipInfo> = clips.into();
+    let mut ordered: Vec<ClipInfo> = Vec::with_capacity(remaining.len());
+
+    while let Some(next) = remaining.pop_front() {
+        let repeats = ordered
+            .last()
+            .map(|prev| is_repeated_shot(prev, &next, strict))
+            .unwrap_or(false);
+
+        if repeats {
+            let prev = ordered.last().unwrap();
+            if let Some(idx) = remaining.iter().position(|c| !is_repeated_shot(prev, c, strict)) {
+                let alternative = remaining.remove(idx).unwrap();
+                remaining.push_front(next);
+                ordered.push(alternative);
+                continue;
+            }
+        }
+        ordered.push(next);
+    }
+
+    let mut position = 0i64;
+    for clip in &mut ordered {
+        clip.timeline_start = position;
+        position += clip.duration;
+    }
+    ordered
+}
+
+/// Energy score computed during segment metadata (scores_json.energy)
+/// Deterministic multiplier in [1 - SEED_JITTER, 1 + SEED_JITTER] for a segment (1.0 for seed 0)
+fn seed_jitter(seed: u64, segment_id: i64) -> f64 {
+    if seed == 0 {
+        return 1.0;
+    }
+    // splitmix64 over (seed, segment id)
+    let mut z = seed ^ (segment_id as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^= z >> 31;
+    let unit = (z >> 11) as f64 / (1u64 << 53) as f64;
+    1.0 + SEED_JITTER * (2.0 * unit - 1.0)
+}
+
+/// Round-robin across assets, keeping each asset's own ranking order
+fn interleave_assets<'a>(
+    ranked: Vec<&'a (Segment, MediaAssetInfo)>,
+) -> Vec<&'a (Segment, MediaAssetInfo)> {
+    let mut by_asset: Vec<(i64, std::collections::VecDeque<&'a (Segment, MediaAssetInfo)>)> = Vec::new();
+    for entry in ranked {
+        match by_asset.iter_mut().find(|(asset_id, _)| *asset_id == entry.1.id) {
+            Some((_, queue)) => queue.push_back(entry),
+            None => by_asset.push((entry.1.id, std::collections::VecDeque::from([entry]))),
+        }
+    }
+    let mut interleaved = Vec::new();
+    while by_asset.iter().any(|(_, queue)| !queue.is_empty()) {
+        for (_, queue) in by_asset.iter_mut() {
+            if let Some(entry) = queue.pop_front() {
+                interleaved.push(entry);
+            }
+        }
+    }
+    interleaved
+}
+
+pub(crate) fn segment_energy(segment: &Segment) -> Option<f64> {
+    segment
+        .scores_json
+        .as_deref()
+        .and_then(|json| serde_json::from_str::<serde_json::Value>(json).ok())
+        .and_then(|scores| scores.get("energy").and_then(|e| e.as_f64()))
+}
+
+fn usable_length(segment: &Segment) -> bool {
+    let duration_sec = (segment.end_ticks - segment.start_ticks) as f64 / TICKS_PER_SECOND as f64;
+    (1.0..=30.0).contains(&duration_sec)
+}
+
+/// How much a hook candidate's energy counts for what it shows (unclassified segments count
+/// fully); None for transitions, which never open an edit
+fn hook_weight(kind: Option<SegmentKind>) -> Option<f64> {
+    match kind {
+        None | Some(SegmentKind::TalkingHead | SegmentKind::Interview) => Some(HOOK_FACE_WEIGHT),
+        Some(SegmentKind::Action | SegmentKind::Scenery) => Some(HOOK_VISUAL_WEIGHT),
+        Some(SegmentKind::BRoll | SegmentKind::Screen) => Some(HOOK_OTHER_WEIGHT),
+        Some(SegmentKind::Transition) => None,
+    }
+}
+
+/// Segment of usable length clearing HOOK_MIN_ENERGY with the best energy, weighted by what it
+/// shows (see `hook_weight`)
+fn select_hook(segments_with_assets: &[(Segment, MediaAssetInfo)]) -> Option<&(Segment, MediaAssetInfo)> {
+    segments_with_assets
+        .iter()
+        .filter(|(segment, _)| usable_length(segment))
+        .filter_map(|entry| segment_energy(&entry.0).map(|energy| (entry, energy)))
+        .filter(|(_, energy)| *energy >= HOOK_MIN_ENERGY)
+        .filter_map(|(entry, energy)| Some((entry, energy * hook_weight(SegmentKind::of(&entry.0))?)))
+        .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+        .map(|(entry, _)| entry)
+}
+
+/// Whether the segment signs off: a goodbye in its transcript, or someone waving
+fn says_goodbye(segment: &Segment) -> bool {
+    let said = segment.transcript.as_deref().is_some_and(|transcript| {
+        let transcript = transcript.to_lowercase();
+        GOODBYE_PHRASES.iter().any(|phrase| transcript.contains(phrase))
+            || transcript.split(|c: char| !c.is_alphanumeric()).any(|word| word == "bye")
+    });
+    said || segment_kind::vision_tags(segment).iter().any(|tag| tag == "waving")
+}
+
+/// Segment of usable length that winds the edit down best, among those `allowed`: calm (low
+/// energy) with a bonus for a goodbye, wide scenery or any scenery. Transitions and action
+/// never close it.
+fn select_outro(
+    segments_with_assets: &[(Segment, MediaAssetInfo)],
+    allowed: impl Fn(&Segment, &MediaAssetInfo) -> bool,
+) -> Option<&(Segment, MediaAssetInfo)> {
+    segments_with_assets
+        .iter()
+        .filter(|(segment, asset)| usable_length(segment) && allowed(segment, asset))
+        .filter_map(|entry| {
+            let segment = &entry.0;
+            let kind = SegmentKind::of(segment);
+            let goodbye = says_goodbye(segment);
+            if !goodbye && matches!(kind, Some(SegmentKind::Transition | SegmentKind::Action)) {
+                return None;
+            }
+            let bonus = if goodbye {
+                OUTRO_GOODBYE_BONUS
+            } else if kind == Some(SegmentKind::Scenery) && segment.shot_type.as_deref() == Some("wide") {
+                OUTRO_WIDE_SCENERY_BONUS
+            } else if kind == Some(SegmentKind::Scenery) {
+                OUTRO_SCENERY_BONUS
+            } else {
+                0.0
+            };
+            let calm = 1.0 - segment_energy(segment).unwrap_or(0.5).clamp(0.0, 1.0);
+            Some((entry, bonus + calm))
+        })
+        .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+        .map(|(entry, _)| entry)
 }
 
 fn calculate_clarity_score((segment, _asset): &(Segment, MediaAssetInfo)) -> f64 {
@@ -0,0 +1,378 @@
+//! Edit scripts: a plan written out for people to read (a client signing off on a cut, a crew
+//! planning pickups) as Markdown or PDF.
+
+use engine::compiler::{EditEvent, EditPlan};
+use serde::Serialize;
+
+use crate::db::{MediaAssetInfo, Segment};
+use crate::uploads::format_timestamp;
+
+use super::TICKS_PER_SECOND;
+
+/// Longest pull quote taken from a clip's transcript
+const MAX_QUOTE_CHARS: usize = 200;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Script {
+    pub title: String,
+    pub duration_sec: f64,
+    pub sections: Vec<ScriptSection>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScriptSection {
+    pub heading: String,
+    pub start_sec: f64,
+    pub duration_sec: f64,
+    pub clips: Vec<ScriptClip>,
+    /// Music tracks starting in the section, as (file name, start, end) in seconds
+    pub music: Vec<(String, f64, f64)>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScriptClip {
+    pub start_sec: f64,
+    pub duration_sec: f64,
+    /// Source file name and the range used from it, in seconds
+    pub source: String,
+    pub source_in_sec: f64,
+    pub source_out_sec: f64,
+    /// The segment's summary, else what kind of shot it is
+    pub description: String,
+    /// What's said during the clip
+    pub quote: Option<String>,
+    /// Playback speed when it isn't real time
+    pub speed: Option<f64>,
+}
+
+fn seconds(ticks: i64) -> f64 {
+    ticks as f64 / TICKS_PER_SECOND as f64
+}
+
+fn file_name(path: &str) -> String {
+    std::path::Path::new(path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string())
+}
+
+/// Section types as headings ("intro" -> "Intro", "b_roll" -> "B roll")
+fn heading(section_type: &str) -> String {
+    let words = section_type.replace(['_', '-'], " ");
+    let mut chars = words.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => "Section".to_string(),
+    }
+}
+
+/// The words of the segment's transcript spoken during `in_ticks..out_ticks`, assuming an
+/// even pace through the segment
+fn quote(segment: &Segment, in_ticks: i64, out_ticks: i64) -> Option<String> {
+    let words: Vec<&str> = segment.transcript.as_deref()?.split_whitespace().collect();
+    let segment_ticks = segment.end_ticks - segment.start_ticks;
+    if words.is_empty() || segment_ticks <= 0 {
+        return None;
+    }
+    let share = |ticks: i64| ((ticks - segment.start_ticks) as f64 / segment_ticks as f64).clamp(0.0, 1.0);
+    let first = ((words.len() as f64 * share(in_ticks)).floor() as usize).min(words.len() - 1);
+    let last = ((words.len() as f64 * share(out_ticks)).ceil() as usize).clamp(first + 1, words.len());
+    let text = words[first..last].join(" ");
+    Some(match text.char_indices().nth(MAX_QUOTE_CHARS) {
+        Some((cut, _)) => format!("{}…", text[..cut].trim_end()),
+        None => text,
+    })
+}
+
+fn describe(segment: Option<&Segment>) -> String {
+    let Some(segment) = segment else {
+        return "Clip".to_string();
+    };
+    if let Some(summary) = segment.summary_text.as_deref().filter(|s| !s.trim().is_empty()) {
+        return summary.trim().to_string();
+    }
+    let kind = segment.segment_kind.as_deref().map(heading);
+    let shot = segment.shot_type.as_deref().map(|s| s.replace('_', "-"));
+    match (kind, shot) {
+        (Some(kind), Some(shot)) => format!("{}, {} shot", kind, shot),
+        (Some(kind), None) => kind,
+        (None, Some(shot)) => format!("{} shot", heading(&shot)),
+        (None, None) => "Clip".to_string(),
+    }
+}
+
+/// Write out a plan: each section with its clips in timeline order, what they show, what's
+/// said in them and where they come from
+pub fn build_script(title: &str, plan: &EditPlan, segments_with_assets: &[(Segment, MediaAssetInfo)]) -> Script {
+    let mut sections = Vec::new();
+    let mut duration_ticks = 0i64;
+    for section in &plan.sections {
+        let mut clips = Vec::new();
+        let mut music = Vec::new();
+        let (mut start, mut end) = (i64::MAX, i64::MIN);
+        for event in &section.events {
+            match event {
+                EditEvent::Clip {
+                    asset_id,
+                    segment_id,
+                    in_ticks,
+                    out_ticks,
+                    timeline_start_ticks,
+                    speed,
+                    ..
+                } => {
+                    let length = event.clip_timeline_ticks().unwrap_or(out_ticks - in_ticks);
+                    start = start.min(*timeline_start_ticks);
+                    end = end.max(timeline_start_ticks + length);
+                    let segment = segment_id.and_then(|id| {
+                        segments_with_assets.iter().find(|(s, _)| s.id == id).map(|(s, _)| s)
+                    });
+                    let asset = segments_with_assets.iter().find(|(_, a)| a.id == *asset_id).map(|(_, a)| a);
+                    clips.push(ScriptClip {
+                        start_sec: seconds(*timeline_start_ticks),
+                        duration_sec: seconds(length),
+                        source: asset.map(|a| file_name(&a.path)).unwrap_or_else(|| format!("Asset {}", asset_id)),
+                        source_in_sec: seconds(*in_ticks),
+                        source_out_sec: seconds(*out_ticks),
+                        description: describe(segment),
+                        quote: segment.and_then(|s| quote(s, *in_ticks, *out_ticks)),
+                        speed: speed.filter(|s| (s - 1.0).abs() > f64::EPSILON),
+                    });
+                }
+                EditEvent::Music { track_path, start_ticks, end_ticks, .. } => {
+                    music.push((file_name(track_path), seconds(*start_ticks), seconds(*end_ticks)));
+                }
+                _ => {}
+            }
+        }
+        clips.sort_by(|a, b| a.start_sec.partial_cmp(&b.start_sec).unwrap_or(std::cmp::Ordering::Equal));
+        let (start, end) = if clips.is_empty() { (duration_ticks, duration_ticks) } else { (start, end) };
+        duration_ticks = duration_ticks.max(end);
+        sections.push(ScriptSection {
+            heading: heading(&section.section_type),
+            start_sec: seconds(start),
+            duration_sec: seconds(end - start),
+            clips,
+            music,
+        });
+    }
+    Script {
+        title: title.to_string(),
+        duration_sec: seconds(duration_ticks),
+        sections,
+    }
+}
+
+/// How a line of the script is set
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum LineStyle {
+    Title,
+    Heading,
+    Body,
+    Quote,
+    Blank,
+}
+
+/// Lines of the script as (style, text), for the Markdown and PDF writers alike
+fn script_lines(script: &Script) -> Vec<(LineStyle, String)> {
+    let clip_count: usize = script.sections.iter().map(|s| s.clips.len()).sum();
+    let mut lines = vec![
+        (LineStyle::Title, script.title.clone()),
+        (
+            LineStyle::Body,
+            format!(
+                "Running time {} · {} clip{} in {} section{}",
+                format_timestamp(script.duration_sec),
+                clip_count,
+                if clip_count == 1 { "" } else { "s" },
+                script.sections.len(),
+                if script.sections.len() == 1 { "" } else { "s" },
+            ),
+        ),
+    ];
+    for (idx, section) in script.sections.iter().enumerate() {
+        lines.push((LineStyle::Blank, String::new()));
+        lines.push((
+            LineStyle::Heading,
+            format!(
+                "{}. {} ({}–{}, {:.1}s)",
+                idx + 1,
+                section.heading,
+                format_timestamp(section.start_sec),
+                format_timestamp(section.start_sec + section.duration_sec),
+                section.duration_sec,
+            ),
+        ));
+        for clip in &section.clips {
+            let speed = clip.speed.map(|s| format!(", played at {:.2}x", s)).unwrap_or_default();
+            lines.push((
+                LineStyle::Body,
+                format!(
+                    "{} ({:.1}s) {}. From {}, {:.1}s–{:.1}s{}",
+                    format_timestamp(clip.start_sec),
+                    clip.duration_sec,
+                    clip.description.trim_end_matches('.'),
+                    clip.source,
+                    clip.source_in_sec,
+                    clip.source_out_sec,
+                    speed,
+                ),
+            ));
+            if let Some(quote) = &clip.quote {
+                lines.push((LineStyle::Quote, format!("\"{}\"", quote)));
+            }
+        }
+        for (track, start, end) in &section.music {
+            lines.push((
+                LineStyle::Body,
+                format!("Music: {} ({}–{})", track, format_timestamp(*start), format_timestamp(*end)),
+            ));
+        }
+    }
+    lines
+}
+
+/// The script as Markdown
+pub fn to_markdown(script: &Script) -> String {
+    let mut markdown = String::new();
+    for (style, text) in script_lines(script) {
+        match style {
+            LineStyle::Title => markdown.push_str(&format!("# {}\n\n", text)),
+            LineStyle::Heading => markdown.push_str(&format!("## {}\n\n", text)),
+            LineStyle::Body => markdown.push_str(&format!("- {}\n", text)),
+            LineStyle::Quote => markdown.push_str(&format!("  > {}\n", text)),
+            LineStyle::Blank => {
+                if !markdown.ends_with("\n\n") {
+                    markdown.push('\n');
+                }
+            }
+        }
+    }
+    markdown
+}
+
+/// US Letter, in points
+const PAGE_WIDTH: f64 = 612.0;
+const PAGE_HEIGHT: f64 = 792.0;
+const MARGIN: f64 = 54.0;
+/// Characters per wrapped line of 10pt body text (Helvetica averages about half its size wide)
+const BODY_CHARS_PER_LINE: usize = 96;
+
+/// Text as a PDF string literal in WinAnsi (characters it lacks become '?')
+fn pdf_string(text: &str) -> String {
+    let mut literal = String::from("(");
+    for c in text.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                literal.push('\\');
+                literal.push(c);
+            }
+            '–' => literal.push_str("\\226"),
+            '—' => literal.push_str("\\227"),
+            '…' => literal.push_str("\\205"),
+            '·' => literal.push_str("\\267"),
+            ' '..='~' => literal.push(c),
+            _ => literal.push('?'),
+        }
+    }
+    literal.push(')');
+    literal
+}
+
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > width {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// The script as a PDF: plain text in Helvetica, paginated onto Letter pages
+pub fn to_pdf(script: &Script) -> Vec<u8> {
+    // Content stream of each page
+    let mut pages: Vec<String> = Vec::new();
+    let mut content = String::new();
+    let mut y = PAGE_HEIGHT - MARGIN;
+    for (style, text) in script_lines(script) {
+        let (font, size, indent, width) = match style {
+            LineStyle::Title => ("F2", 18.0, 0.0, BODY_CHARS_PER_LINE / 2),
+            LineStyle::Heading => ("F2", 13.0, 0.0, BODY_CHARS_PER_LINE * 3 / 4),
+            LineStyle::Body => ("F1", 10.0, 0.0, BODY_CHARS_PER_LINE),
+            LineStyle::Quote => ("F1", 10.0, 18.0, BODY_CHARS_PER_LINE - 4),
+            LineStyle::Blank => {
+                y -= 8.0;
+                continue;
+            }
+        };
+        let leading = size * 1.4;
+        for line in wrap(&text, width) {
+            if y - leading < MARGIN {
+                pages.push(std::mem::take(&mut content));
+                y = PAGE_HEIGHT - MARGIN;
+            }
+            y -= leading;
+            content.push_str(&format!(
+                "BT /{} {} Tf {:.1} {:.1} Td {} Tj ET\n",
+                font,
+                size,
+                MARGIN + indent,
+                y,
+                pdf_string(&line)
+            ));
+        }
+    }
+    pages.push(content);
+
+    // Objects: 1 catalog, 2 page tree, 3-4 fonts, then a page and its content per page
+    let page_ids: Vec<usize> = (0..pages.len()).map(|i| 5 + i * 2).collect();
+    let mut objects: Vec<String> = vec![
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            page_ids.iter().map(|id| format!("{} 0 R", id)).collect::<Vec<_>>().join(" "),
+            pages.len()
+        ),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>".to_string(),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold /Encoding /WinAnsiEncoding >>".to_string(),
+    ];
+    for (page, id) in pages.iter().zip(&page_ids) {
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /Font << /F1 3 0 R /F2 4 0 R >> >> /Contents {} 0 R >>",
+            PAGE_WIDTH,
+            PAGE_HEIGHT,
+            id + 1
+        ));
+        objects.push(format!("<< /Length {} >>\nstream\n{}endstream", page.len(), page));
+    }
+
+    let mut pdf = b"%PDF-1.4\n".to_vec();
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (idx, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.extend_from_slice(format!("{} 0 obj\n{}\nendobj\n", idx + 1, object).as_bytes());
+    }
+    let xref_offset = pdf.len();
+    pdf.extend_from_slice(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).as_bytes());
+    for offset in offsets {
+        pdf.extend_from_slice(format!("{:010} 00000 n \n", offset).as_bytes());
+    }
+    pdf.extend_from_slice(
+        format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            xref_offset
+        )
+        .as_bytes(),
+    );
+    pdf
+}
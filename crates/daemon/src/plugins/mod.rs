@@ -0,0 +1,241 @@
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::sync::OnceLock;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+
+use crate::db::{Database, Segment};
+use crate::jobs::resources;
+use engine::timeline::TICKS_PER_SECOND;
+
+/// Version of the request/response shape plugins speak
+const PROTOCOL_VERSION: u32 = 1;
+const DEFAULT_TIMEOUT_SECS: u64 = 300;
+
+/// A custom analyzer registered in the file at ANALYZER_PLUGINS_PATH. It gets every segment of an
+/// asset once the built-in enrichment is done and answers with tags and scores per segment,
+/// either as an external process (JSON on stdin, JSON on stdout) or an HTTP endpoint (JSON POST).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnalyzerPlugin {
+    /// Becomes the `kind` of the plugin's tags and the prefix of its scores ("<name>.<score>")
+    pub name: String,
+    /// Program and arguments for a stdio plugin
+    #[serde(default)]
+    pub command: Option<Vec<String>>,
+    /// Endpoint for an HTTP plugin
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// What a plugin returns for one segment
+#[derive(Debug, Deserialize)]
+struct SegmentResult {
+    id: i64,
+    #[serde(default)]
+    tags: Vec<PluginTag>,
+    #[serde(default)]
+    scores: HashMap<String, f64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PluginTag {
+    label: String,
+    #[serde(default = "default_confidence")]
+    confidence: f64,
+}
+
+fn default_confidence() -> f64 {
+    1.0
+}
+
+#[derive(Debug, Deserialize)]
+struct PluginResponse {
+    segments: Vec<SegmentResult>,
+}
+
+/// Plugins from the file at ANALYZER_PLUGINS_PATH (a JSON array). Read once; a missing or
+/// invalid file is logged and ignored.
+pub fn configured_plugins() -> &'static [AnalyzerPlugin] {
+    static PLUGINS: OnceLock<Vec<AnalyzerPlugin>> = OnceLock::new();
+    PLUGINS.get_or_init(|| {
+        let Ok(path) = std::env::var("ANALYZER_PLUGINS_PATH") else {
+            return Vec::new();
+        };
+        match std::fs::read_to_string(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|json| Ok(serde_json::from_str::<Vec<AnalyzerPlugin>>(&json)?))
+        {
+            Ok(plugins) => plugins
+                .into_iter()
+                .filter(|plugin| match (&plugin.command, &plugin.url) {
+                    (Some(command), None) if !command.is_empty() => true,
+                    (None, Some(_)) => true,
+                    _ => {
+                        eprintln!("[PLUGINS] Ignoring {}: set exactly one of command or url", plugin.name);
+                        false
+                    }
+                })
+                .collect(),
+            Err(e) => {
+                eprintln!("[PLUGINS] Ignoring analyzer plugins at {}: {:?}", path, e);
+                Vec::new()
+            }
+        }
+    })
+}
+
+/// Run every enabled plugin over an asset's segments and store what they return. A failing
+/// plugin is logged and skipped so it never blocks the rest of the analysis.
+pub async fn run_analyzers(db: &Database, asset_id: i64) -> Result<()> {
+    let plugins: Vec<&AnalyzerPlugin> = configured_plugins().iter().filter(|p| p.enabled).collect();
+    if plugins.is_empty() {
+        return Ok(());
+    }
+
+    let asset = db
+        .get_media_asset(asset_id)?
+        .ok_or_else(|| anyhow!("Media asset {} not found", asset_id))?;
+    let segments = db.get_segments_by_asset(asset_id)?;
+    if segments.is_empty() {
+        return Ok(());
+    }
+    let request = json!({
+        "protocol": PROTOCOL_VERSION,
+        "asset": {
+            "id": asset.id,
+            "path": asset.path,
+            "duration_sec": asset.duration_ticks as f64 / TICKS_PER_SECOND as f64,
+            "width": asset.width,
+            "height": asset.height,
+        },
+        "segments": segments.iter().map(segment_request).collect::<Vec<_>>(),
+    });
+
+    for plugin in plugins {
+        match call_plugin(plugin, &request).await {
+            Ok(response) => {
+                let stored = store_results(db, plugin, &segments, response)?;
+                eprintln!("[PLUGINS] {} annotated {} segments of asset {}", plugin.name, stored, asset_id);
+            }
+            Err(e) => eprintln!("[PLUGINS] {} failed on asset {}: {:?}", plugin.name, asset_id, e),
+        }
+    }
+    Ok(())
+}
+
+fn segment_request(segment: &Segment) -> Value {
+    let parse = |json: &Option<String>| {
+        json.as_deref()
+            .and_then(|j| serde_json::from_str::<Value>(j).ok())
+            .unwrap_or(Value::Null)
+    };
+    json!({
+        "id": segment.id,
+        "start_sec": Database::get_coalesced_src_in(segment) as f64 / TICKS_PER_SECOND as f64,
+        "end_sec": Database::get_coalesced_src_out(segment) as f64 / TICKS_PER_SECOND as f64,
+        "transcript": segment.transcript,
+        "summary_text": segment.summary_text,
+        "segment_kind": segment.segment_kind,
+        "tags": parse(&segment.tags_json),
+        "scores": parse(&segment.scores_json),
+    })
+}
+
+async fn call_plugin(plugin: &AnalyzerPlugin, request: &Value) -> Result<PluginResponse> {
+    let timeout = Duration::from_secs(plugin.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS));
+    let body = match (&plugin.command, &plugin.url) {
+        (Some(command), _) => tokio::time::timeout(timeout, call_stdio(command, request))
+            .await
+            .map_err(|_| anyhow!("Timed out after {}s", timeout.as_secs()))??,
+        (None, Some(url)) => {
+            let response = reqwest::Client::builder()
+                .timeout(timeout)
+                .build()?
+                .post(url)
+                .json(request)
+                .send()
+                .await?;
+            if !response.status().is_success() {
+                let status = response.status();
+                let text = response.text().await.unwrap_or_default();
+                return Err(anyhow!("{} answered {}: {}", url, status, text));
+            }
+            response.text().await?
+        }
+        (None, None) => return Err(anyhow!("No command or url")),
+    };
+    serde_json::from_str(&body).context("Plugin response isn't {\"segments\": [...]}")
+}
+
+async fn call_stdio(command: &[String], request: &Value) -> Result<String> {
+    let mut child = resources::command(&command[0])
+        .args(&command[1..])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .with_context(|| format!("Failed to start {}", command[0]))?;
+
+    let mut stdin = child.stdin.take().ok_or_else(|| anyhow!("No stdin"))?;
+    stdin.write_all(request.to_string().as_bytes()).await?;
+    // Close stdin so the plugin sees the end of the request
+    drop(stdin);
+
+    let output = child.wait_with_output().await?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "Exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Write a plugin's tags (kind = plugin name, replacing its earlier tags) and scores
+/// ("<plugin>.<score>"); results for segments that weren't in the request are dropped
+fn store_results(db: &Database, plugin: &AnalyzerPlugin, segments: &[Segment], response: PluginResponse) -> Result<usize> {
+    let mut stored = 0;
+    for result in response.segments {
+        if !segments.iter().any(|s| s.id == result.id) {
+            continue;
+        }
+        let tags: Vec<Value> = result
+            .tags
+            .iter()
+            .filter(|tag| !tag.label.trim().is_empty())
+            .map(|tag| {
+                json!({
+                    "label": tag.label.trim(),
+                    "kind": plugin.name,
+                    "confidence": tag.confidence.clamp(0.0, 1.0),
+                })
+            })
+            .collect();
+        db.replace_segment_tags(result.id, &[plugin.name.as_str()], &tags)?;
+
+        if !result.scores.is_empty() {
+            let scores: serde_json::Map<String, Value> = result
+                .scores
+                .iter()
+                .filter(|(_, score)| score.is_finite())
+                .map(|(key, score)| (format!("{}.{}", plugin.name, key), json!(score)))
+                .collect();
+            db.merge_segment_scores(result.id, &Value::Object(scores))?;
+        }
+        stored += 1;
+    }
+    Ok(stored)
+}
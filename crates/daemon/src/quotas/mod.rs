@@ -0,0 +1,272 @@
+//! Quotas for hosted deployments: limits on a project's storage, analysis minutes, LLM calls
+//! and exports, set per project and per API token (a token's limits cover every project created
+//! with it). Imports and exports over a quota aren't queued, analysis jobs over it fail with
+//! the reason instead of running, and LLM calls over it aren't made. Without limits nothing is
+//! counted against anything.
+
+use anyhow::Result;
+use chrono::{Datelike, TimeZone, Utc};
+use engine::timeline::TICKS_PER_SECOND;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::db::Database;
+use crate::jobs::{Job, JobType};
+
+/// Limits of a project or a token; None is unlimited
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QuotaLimits {
+    /// Originals, proxies, thumbnails and everything else under the project's cache directory
+    pub storage_bytes: Option<u64>,
+    /// Length of the media transcribed or run through vision analysis this calendar month (UTC)
+    pub analysis_minutes: Option<f64>,
+    /// LLM calls this calendar month (UTC)
+    pub llm_calls: Option<u64>,
+    /// Exports started this day (UTC)
+    pub exports_per_day: Option<u64>,
+}
+
+impl QuotaLimits {
+    pub fn limit(&self, metric: Metric) -> Option<f64> {
+        match metric {
+            Metric::StorageBytes => self.storage_bytes.map(|v| v as f64),
+            Metric::AnalysisMinutes => self.analysis_minutes,
+            Metric::LlmCalls => self.llm_calls.map(|v| v as f64),
+            Metric::ExportsPerDay => self.exports_per_day.map(|v| v as f64),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Metric {
+    StorageBytes,
+    AnalysisMinutes,
+    LlmCalls,
+    ExportsPerDay,
+}
+
+impl Metric {
+    pub const ALL: [Metric; 4] = [Metric::StorageBytes, Metric::AnalysisMinutes, Metric::LlmCalls, Metric::ExportsPerDay];
+
+    fn label(&self) -> &'static str {
+        match self {
+            Metric::StorageBytes => "storage",
+            Metric::AnalysisMinutes => "analysis minutes this month",
+            Metric::LlmCalls => "LLM calls this month",
+            Metric::ExportsPerDay => "exports today",
+        }
+    }
+
+    fn amount(&self, value: f64) -> String {
+        match self {
+            Metric::StorageBytes => format!("{:.0} bytes", value),
+            Metric::AnalysisMinutes => format!("{:.1} min", value),
+            Metric::LlmCalls | Metric::ExportsPerDay => format!("{:.0}", value),
+        }
+    }
+}
+
+/// What a project (or all of a token's projects) has used of each quota
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Usage {
+    pub storage_bytes: u64,
+    pub analysis_minutes: f64,
+    pub llm_calls: u64,
+    pub exports_today: u64,
+}
+
+impl Usage {
+    pub fn get(&self, metric: Metric) -> f64 {
+        match metric {
+            Metric::StorageBytes => self.storage_bytes as f64,
+            Metric::AnalysisMinutes => self.analysis_minutes,
+            Metric::LlmCalls => self.llm_calls as f64,
+            Metric::ExportsPerDay => self.exports_today as f64,
+        }
+    }
+
+    fn add(&mut self, other: &Usage) {
+        self.storage_bytes += other.storage_bytes;
+        self.analysis_minutes += other.analysis_minutes;
+        self.llm_calls += other.llm_calls;
+        self.exports_today += other.exports_today;
+    }
+}
+
+/// Raised when an import, export, analysis job or LLM call would go over a quota. The API
+/// answers 429 Too Many Requests with the details.
+#[derive(Debug, Clone, Serialize)]
+pub struct QuotaExceeded {
+    /// "project" or "token"
+    pub scope: &'static str,
+    pub scope_id: i64,
+    pub metric: Metric,
+    pub limit: f64,
+    pub used: f64,
+}
+
+impl fmt::Display for QuotaExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Quota exceeded: {} {} has used {} of its {} limit of {}",
+            if self.scope == "token" { "API token" } else { "project" },
+            self.scope_id,
+            self.metric.amount(self.used),
+            self.metric.label(),
+            self.metric.amount(self.limit),
+        )
+    }
+}
+
+impl std::error::Error for QuotaExceeded {}
+
+pub fn quota_error(e: &anyhow::Error) -> Option<&QuotaExceeded> {
+    e.downcast_ref::<QuotaExceeded>()
+}
+
+/// Start of the current UTC day, as stored timestamps are written
+fn day_start() -> String {
+    let now = Utc::now();
+    Utc.with_ymd_and_hms(now.year(), now.month(), now.day(), 0, 0, 0)
+        .unwrap()
+        .to_rfc3339()
+}
+
+/// Start of the current UTC month, as stored timestamps are written
+fn month_start() -> String {
+    let now = Utc::now();
+    Utc.with_ymd_and_hms(now.year(), now.month(), 1, 0, 0, 0)
+        .unwrap()
+        .to_rfc3339()
+}
+
+fn file_size(path: &Path) -> u64 {
+    std::fs::symlink_metadata(path).map(|m| m.len()).unwrap_or(0)
+}
+
+fn walk(dir: &Path, files: &mut HashSet<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else { return };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        match entry.file_type() {
+            Ok(t) if t.is_dir() => walk(&path, files),
+            Ok(t) if t.is_file() => {
+                files.insert(path);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Bytes on disk a project's media takes, each file counted once
+pub fn storage_bytes(db: &Database, project_id: i64) -> Result<u64> {
+    let Some(project) = db.get_project(project_id)? else { return Ok(0) };
+    let mut assets = db.get_media_assets_for_project(project_id)?;
+    assets.extend(db.get_reference_assets_for_project(project_id)?);
+
+    let mut files: HashSet<PathBuf> = HashSet::new();
+    for asset in &assets {
+        files.insert(PathBuf::from(&asset.path));
+        files.extend(db.list_proxies(asset.id)?.into_iter().map(|(_, path, _, _)| PathBuf::from(path)));
+        if let Some(dir) = db.get_thumbnail_dir(asset.id)? {
+            walk(Path::new(&dir), &mut files);
+        }
+    }
+    walk(Path::new(&project.cache_dir), &mut files);
+    Ok(files.iter().map(|path| file_size(path)).sum())
+}
+
+fn metric_used(db: &Database, project_id: i64, metric: Metric, exclude_job_id: Option<i64>) -> Result<f64> {
+    Ok(match metric {
+        Metric::StorageBytes => storage_bytes(db, project_id)? as f64,
+        Metric::AnalysisMinutes => {
+            db.analysis_ticks_since(project_id, &month_start(), exclude_job_id)? as f64 / TICKS_PER_SECOND as f64 / 60.0
+        }
+        Metric::LlmCalls => db.count_llm_calls_since(project_id, &month_start())? as f64,
+        Metric::ExportsPerDay => db.count_exports_since(project_id, &day_start())? as f64,
+    })
+}
+
+/// A project's usage of every quota
+pub fn project_usage(db: &Database, project_id: i64) -> Result<Usage> {
+    Ok(Usage {
+        storage_bytes: storage_bytes(db, project_id)?,
+        analysis_minutes: metric_used(db, project_id, Metric::AnalysisMinutes, None)?,
+        llm_calls: db.count_llm_calls_since(project_id, &month_start())? as u64,
+        exports_today: db.count_exports_since(project_id, &day_start())? as u64,
+    })
+}
+
+/// Usage of every quota summed over the projects created with a token
+pub fn token_usage(db: &Database, token_id: i64) -> Result<Usage> {
+    let mut usage = Usage::default();
+    for project_id in db.list_api_token_projects(token_id)? {
+        usage.add(&project_usage(db, project_id)?);
+    }
+    Ok(usage)
+}
+
+/// Fails with [`QuotaExceeded`] when `additional` more of a metric would take the project, or
+/// the token it was created with, over a limit. `exclude_job_id` leaves an analysis job that's
+/// about to run out of what's been used.
+pub fn check(db: &Database, project_id: i64, metric: Metric, additional: f64, exclude_job_id: Option<i64>) -> Result<()> {
+    if let Some(limit) = db.get_quota_limits("project", project_id)?.limit(metric) {
+        let used = metric_used(db, project_id, metric, exclude_job_id)?;
+        if used + additional > limit {
+            return Err(QuotaExceeded { scope: "project", scope_id: project_id, metric, limit, used }.into());
+        }
+    }
+    if let Some(token_id) = db.get_project_api_token(project_id)? {
+        if let Some(limit) = db.get_quota_limits("token", token_id)?.limit(metric) {
+            let mut used = 0.0;
+            for project_id in db.list_api_token_projects(token_id)? {
+                used += metric_used(db, project_id, metric, exclude_job_id)?;
+            }
+            if used + additional > limit {
+                return Err(QuotaExceeded { scope: "token", scope_id: token_id, metric, limit, used }.into());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Room for a job that's about to be queued: an import needs room for the file, an export
+/// counts against the day's exports
+pub fn check_new_job(db: &Database, job_type: &JobType, payload: Option<&Value>) -> Result<()> {
+    let Some(project_id) = payload.and_then(|p| p.get("project_id")).and_then(|v| v.as_i64()) else {
+        return Ok(());
+    };
+    match job_type {
+        JobType::ImportRaw => {
+            let size = payload
+                .and_then(|p| p.get("file_path"))
+                .and_then(|v| v.as_str())
+                .map(|path| file_size(Path::new(path)))
+                .unwrap_or(0);
+            check(db, project_id, Metric::StorageBytes, size as f64, None)
+        }
+        JobType::Export => check(db, project_id, Metric::ExportsPerDay, 1.0, None),
+        _ => Ok(()),
+    }
+}
+
+/// Room for an analysis job that's about to run: the length of the asset it reads
+pub fn check_analysis_job(db: &Database, job: &Job) -> Result<()> {
+    if !matches!(job.job_type, JobType::TranscribeAsset | JobType::AnalyzeVisionAsset) {
+        return Ok(());
+    }
+    let Some(asset_id) = job.payload.as_ref().and_then(|p| p.get("asset_id")).and_then(|v| v.as_i64()) else {
+        return Ok(());
+    };
+    let (Some(asset), Some(project_id)) = (db.get_media_asset(asset_id)?, db.get_asset_project_id(asset_id)?) else {
+        return Ok(());
+    };
+    let minutes = asset.duration_ticks as f64 / TICKS_PER_SECOND as f64 / 60.0;
+    check(db, project_id, Metric::AnalysisMinutes, minutes, Some(job.id))
+}
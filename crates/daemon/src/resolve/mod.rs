@@ -0,0 +1,133 @@
+use anyhow::{anyhow, Context, Result};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+
+use crate::crypto;
+use crate::db::Database;
+use engine::otio::timeline_to_otio;
+use engine::timeline::Timeline;
+
+/// Resolve-side script that builds the bins and imports the timeline
+const PUSH_SCRIPT: &str = include_str!("push_to_resolve.py");
+
+/// Everything needed to rebuild a project's edit in DaVinci Resolve: the timeline as
+/// OpenTimelineIO, a manifest of media bins, and the script that imports both
+#[derive(Debug, Clone, Serialize)]
+pub struct ResolveBundle {
+    pub otio_path: String,
+    pub manifest_path: String,
+    pub script_path: String,
+    /// Media that couldn't be relinked to its original file (and uses the proxy instead)
+    pub warnings: Vec<String>,
+}
+
+/// Write the Resolve bundle for a project's timeline into `out_dir`. Clips are relinked to the
+/// original camera files (the proxy only when the original is gone); timeline footage, unused
+/// project footage and music go into separate bins under "VibeCut/<project>".
+pub fn write_bundle(db: &Database, project_id: i64, out_dir: &Path) -> Result<ResolveBundle> {
+    let project = db
+        .get_project(project_id)?
+        .ok_or_else(|| anyhow!("Project {} not found", project_id))?;
+    let timeline: Timeline = db
+        .get_timeline(project_id)?
+        .map(|json| serde_json::from_str(&json))
+        .transpose()?
+        .ok_or_else(|| anyhow!("Project {} has no timeline", project_id))?;
+
+    let mut warnings = Vec::new();
+    let mut media_paths = HashMap::new();
+    for asset in db.get_media_assets_for_project(project_id)? {
+        // Resolve can't read encrypted proxies
+        let proxy = db
+            .get_proxy_path(asset.id)?
+            .filter(|p| Path::new(p).exists() && !crypto::is_encrypted_file(Path::new(p)));
+        let path = if Path::new(&asset.path).exists() {
+            asset.path
+        } else if let Some(proxy) = proxy {
+            warnings.push(format!("{} is missing; linked to its proxy {}", asset.path, proxy));
+            proxy
+        } else {
+            warnings.push(format!("{} is missing and has no proxy", asset.path));
+            asset.path
+        };
+        media_paths.insert(asset.id, path);
+    }
+
+    let used: Vec<i64> = timeline.tracks.iter().flat_map(|t| t.clips.iter().map(|c| c.asset_id)).collect();
+    let bin_root = format!("VibeCut/{}", project.name);
+    let mut bins: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for (asset_id, path) in &media_paths {
+        let bin = if used.contains(asset_id) { "Footage" } else { "Unused Footage" };
+        bins.entry(format!("{}/{}", bin_root, bin)).or_default().push(path.clone());
+    }
+    if !timeline.music.is_empty() {
+        bins.insert(
+            format!("{}/Music", bin_root),
+            timeline.music.iter().map(|m| m.track_path.clone()).collect(),
+        );
+    }
+    for files in bins.values_mut() {
+        files.sort();
+        files.dedup();
+    }
+
+    std::fs::create_dir_all(out_dir)?;
+    let out_dir = out_dir.canonicalize()?;
+    let file_stem = sanitize_file_name(&project.name);
+    let otio_path = out_dir.join(format!("{}.otio", file_stem));
+    let manifest_path = out_dir.join(format!("{}.resolve.json", file_stem));
+    let script_path = out_dir.join("push_to_resolve.py");
+
+    let otio = timeline_to_otio(&timeline, &project.name, &media_paths);
+    std::fs::write(&otio_path, serde_json::to_string_pretty(&otio)?)?;
+    let manifest = serde_json::json!({
+        "project_name": project.name,
+        "timeline_name": format!("{} (VibeCut)", project.name),
+        "timeline_bin": bin_root,
+        "otio_path": otio_path,
+        "bins": bins,
+    });
+    std::fs::write(&manifest_path, serde_json::to_string_pretty(&manifest)?)?;
+    std::fs::write(&script_path, PUSH_SCRIPT)?;
+
+    Ok(ResolveBundle {
+        otio_path: otio_path.to_string_lossy().to_string(),
+        manifest_path: manifest_path.to_string_lossy().to_string(),
+        script_path: script_path.to_string_lossy().to_string(),
+        warnings,
+    })
+}
+
+/// Run the bundle's script against a running Resolve; returns what the script printed.
+/// RESOLVE_PYTHON picks the interpreter (default python3).
+pub async fn push(bundle: &ResolveBundle) -> Result<String> {
+    let python = std::env::var("RESOLVE_PYTHON").unwrap_or_else(|_| "python3".to_string());
+    let output = Command::new(&python)
+        .arg(&bundle.script_path)
+        .arg(&bundle.manifest_path)
+        .output()
+        .await
+        .with_context(|| format!("Failed to run {}", python))?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(anyhow!("Resolve push failed: {}", if stderr.is_empty() { stdout } else { stderr }));
+    }
+    Ok(stdout)
+}
+
+/// Default bundle folder: `<project cache dir>/resolve`
+pub fn default_out_dir(cache_dir: &str) -> PathBuf {
+    PathBuf::from(cache_dir).join("resolve")
+}
+
+fn sanitize_file_name(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' || c == ' ' { c } else { '_' })
+        .collect();
+    if cleaned.trim().is_empty() { "timeline".to_string() } else { cleaned.trim().to_string() }
+}
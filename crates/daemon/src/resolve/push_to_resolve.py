@@ -0,0 +1,82 @@
+"""Push a VibeCut timeline into DaVinci Resolve.
+
+Usage: python3 push_to_resolve.py manifest.json
+
+Needs Resolve running with external scripting enabled (Preferences > System > General >
+External scripting using: Local). The manifest lists the media bins to create and the
+OpenTimelineIO file to import; VibeCut writes both next to this script.
+"""
+
+import json
+import os
+import sys
+
+DEFAULT_MODULE_DIRS = {
+    "darwin": "/Library/Application Support/Blackmagic Design/DaVinci Resolve/Developer/Scripting/Modules",
+    "win32": os.path.join(
+        os.environ.get("PROGRAMDATA", "C:\\ProgramData"),
+        "Blackmagic Design", "DaVinci Resolve", "Support", "Developer", "Scripting", "Modules",
+    ),
+    "linux": "/opt/resolve/Developer/Scripting/Modules",
+}
+
+
+def connect():
+    module_dir = os.environ.get("RESOLVE_SCRIPT_API")
+    module_dir = os.path.join(module_dir, "Modules") if module_dir else DEFAULT_MODULE_DIRS.get(sys.platform)
+    if module_dir and module_dir not in sys.path:
+        sys.path.append(module_dir)
+    try:
+        import DaVinciResolveScript as dvr
+    except ImportError:
+        sys.exit("Couldn't load DaVinciResolveScript; set RESOLVE_SCRIPT_API to Resolve's Developer/Scripting folder")
+    resolve = dvr.scriptapp("Resolve")
+    if resolve is None:
+        sys.exit("Couldn't connect to Resolve; is it running with external scripting enabled?")
+    return resolve
+
+
+def open_project(resolve, name):
+    manager = resolve.GetProjectManager()
+    project = manager.LoadProject(name) or manager.CreateProject(name)
+    if project is None:
+        sys.exit(f"Couldn't open or create Resolve project '{name}'")
+    return project
+
+
+def folder_path(media_pool, path):
+    """Find or create a bin for a "A/B/C" path under the root bin"""
+    folder = media_pool.GetRootFolder()
+    for name in path.split("/"):
+        existing = next((f for f in folder.GetSubFolderList() if f.GetName() == name), None)
+        folder = existing or media_pool.AddSubFolder(folder, name)
+    return folder
+
+
+def main():
+    with open(sys.argv[1]) as f:
+        manifest = json.load(f)
+
+    resolve = connect()
+    project = open_project(resolve, manifest["project_name"])
+    media_pool = project.GetMediaPool()
+
+    for bin_path, files in manifest["bins"].items():
+        files = [p for p in files if os.path.exists(p)]
+        if files:
+            media_pool.SetCurrentFolder(folder_path(media_pool, bin_path))
+            media_pool.ImportMedia(files)
+
+    media_pool.SetCurrentFolder(folder_path(media_pool, manifest["timeline_bin"]))
+    timeline = media_pool.ImportTimelineFromFile(
+        manifest["otio_path"],
+        {"timelineName": manifest["timeline_name"], "importSourceClips": False},
+    )
+    if timeline is None:
+        sys.exit("Resolve couldn't import the timeline")
+    project.SetCurrentTimeline(timeline)
+    print(f"Imported '{timeline.GetName()}' into Resolve project '{manifest['project_name']}'")
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,242 @@
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::api::orchestrator::SegmentCandidate;
+use crate::db::{Database, Segment};
+use crate::embeddings::cosine_similarity;
+use crate::llm;
+use crate::retrieval::feedback::segment_embedding;
+
+/// Phrases that start an exclusion, longest first so "nothing from" wins over "no"
+const NEGATION_CUES: &[&str] = &[
+    "do not include", "don't include", "do not show", "don't show", "nothing from",
+    "nothing with", "nothing of", "leave out", "excluding", "exclude", "without", "avoid",
+    "skip", "except", "no",
+];
+/// Words trimmed from the front of an excluded phrase ("from the airport" -> "airport")
+const LEADING_FILLER: &[&str] = &["the", "a", "an", "any", "from", "of", "at", "in", "with", "on"];
+/// Generic nouns trimmed from the end ("shaky footage" -> "shaky")
+const TRAILING_FILLER: &[&str] = &[
+    "footage", "shots", "shot", "clips", "clip", "videos", "video", "scenes", "scene", "parts",
+    "moments", "stuff", "bits", "ones",
+];
+/// "no more than 30 seconds" is a length constraint, not an exclusion
+const NOT_AN_EXCLUSION: &[&str] = &["more", "less", "longer", "shorter", "than", "later", "earlier"];
+
+const SHAKY_WORDS: &[&str] = &["shaky", "shake", "shaking", "unstable", "jittery", "wobbly"];
+const BLURRY_WORDS: &[&str] = &["blurry", "blur", "blurred", "unfocused", "out of focus"];
+
+/// Motion score (mean frame difference) at which footage counts as shaky; same scale as the
+/// energy pass's high-motion cutoff
+const SHAKY_MOTION_SCORE: f64 = 50.0;
+/// Blur score (Laplacian variance) below which footage counts as blurry
+const BLURRY_BLUR_SCORE: f64 = 50.0;
+/// Segments at least this similar to an excluded phrase are dropped
+const EXCLUSION_SIMILARITY: f32 = 0.45;
+/// Below that, similarity to an excluded phrase is subtracted from the score at this weight
+const EXCLUSION_PENALTY_WEIGHT: f32 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExclusionKind {
+    /// Metadata: high motion score
+    Shaky,
+    /// Metadata: low blur score
+    Blurry,
+    /// Content: literal mention or embedding similarity
+    Semantic,
+}
+
+/// One negative constraint parsed from an intent
+#[derive(Debug, Clone, Serialize)]
+pub struct Exclusion {
+    pub phrase: String,
+    pub kind: ExclusionKind,
+}
+
+/// A candidate removed by an exclusion
+#[derive(Debug, Clone, Serialize)]
+pub struct ExcludedSegment {
+    pub segment_id: i64,
+    pub exclusion: String,
+    pub reason: String,
+}
+
+/// An intent split into what to search for and what to leave out
+#[derive(Debug, Clone)]
+pub struct ParsedIntent {
+    pub positive: String,
+    pub exclusions: Vec<Exclusion>,
+}
+
+/// Split "the fun parts at the beach, no shaky footage, nothing from the airport" into the
+/// positive query ("the fun parts at the beach") and its exclusions. The positive query is what
+/// gets embedded, so excluded words don't pull matching footage in.
+pub fn parse_exclusions(intent: &str) -> ParsedIntent {
+    let mut positive_parts = Vec::new();
+    let mut exclusions: Vec<Exclusion> = Vec::new();
+
+    for clause in intent.split([',', ';', '.', '!', '?']) {
+        // ASCII lowercasing keeps byte offsets valid for slicing the original clause
+        let lower = clause.to_ascii_lowercase();
+        let Some((cue_start, cue_end)) = find_cue(&lower) else {
+            positive_parts.push(clause.trim().to_string());
+            continue;
+        };
+        let phrase = trim_filler(&lower[cue_end..]);
+        let first_word = phrase.split_whitespace().next().unwrap_or("");
+        if phrase.is_empty()
+            || first_word.chars().next().is_some_and(|c| c.is_ascii_digit())
+            || NOT_AN_EXCLUSION.contains(&first_word)
+        {
+            positive_parts.push(clause.trim().to_string());
+            continue;
+        }
+
+        positive_parts.push(clause[..cue_start].trim().to_string());
+        let kind = if contains_any(&phrase, SHAKY_WORDS) {
+            ExclusionKind::Shaky
+        } else if contains_any(&phrase, BLURRY_WORDS) {
+            ExclusionKind::Blurry
+        } else {
+            ExclusionKind::Semantic
+        };
+        if !exclusions.iter().any(|e| e.phrase == phrase) {
+            exclusions.push(Exclusion { phrase, kind });
+        }
+    }
+
+    let positive = positive_parts
+        .into_iter()
+        .map(|p| p.trim_end_matches(|c: char| c.is_whitespace() || c == '-').to_string())
+        .filter(|p| !p.is_empty())
+        .collect::<Vec<_>>()
+        .join(", ");
+    ParsedIntent { positive, exclusions }
+}
+
+/// Earliest negation cue in a lowercased clause, as (start, end) byte offsets
+fn find_cue(lower: &str) -> Option<(usize, usize)> {
+    NEGATION_CUES
+        .iter()
+        .filter_map(|cue| find_word(lower, cue).map(|start| (start, start + cue.len())))
+        .min_by_key(|(start, end)| (*start, usize::MAX - end))
+}
+
+/// Byte offset of `needle` in `haystack` at word boundaries
+fn find_word(haystack: &str, needle: &str) -> Option<usize> {
+    haystack.match_indices(needle).map(|(i, _)| i).find(|&i| {
+        let before = haystack[..i].chars().next_back();
+        let after = haystack[i + needle.len()..].chars().next();
+        !before.is_some_and(|c| c.is_alphanumeric()) && !after.is_some_and(|c| c.is_alphanumeric())
+    })
+}
+
+fn contains_any(phrase: &str, words: &[&str]) -> bool {
+    words.iter().any(|w| find_word(phrase, w).is_some())
+}
+
+fn trim_filler(phrase: &str) -> String {
+    let mut words: Vec<&str> = phrase.split_whitespace().collect();
+    while words.first().is_some_and(|w| LEADING_FILLER.contains(w)) {
+        words.remove(0);
+    }
+    while words.len() > 1 && words.last().is_some_and(|w| TRAILING_FILLER.contains(w)) {
+        words.pop();
+    }
+    words.join(" ")
+}
+
+/// Drop candidates that violate an exclusion and penalize ones that resemble an excluded phrase.
+/// Returns what was dropped and why.
+pub async fn apply_exclusions(
+    db: &Database,
+    exclusions: &[Exclusion],
+    candidates: &mut Vec<SegmentCandidate>,
+) -> Result<Vec<ExcludedSegment>> {
+    let mut phrase_embeddings = Vec::new();
+    for exclusion in exclusions.iter().filter(|e| e.kind == ExclusionKind::Semantic) {
+        match llm::embed_text(&exclusion.phrase).await {
+            Ok(embedding) => phrase_embeddings.push((exclusion.phrase.as_str(), embedding)),
+            Err(e) => eprintln!("[RETRIEVAL] Couldn't embed exclusion '{}': {:?}", exclusion.phrase, e),
+        }
+    }
+
+    let mut excluded = Vec::new();
+    let mut kept = Vec::with_capacity(candidates.len());
+    for mut candidate in candidates.drain(..) {
+        let Some((segment, _)) = db.get_segment_with_embeddings(candidate.segment_id)? else {
+            kept.push(candidate);
+            continue;
+        };
+
+        let violation = exclusions.iter().find_map(|exclusion| {
+            metadata_violation(&segment, exclusion).map(|reason| (exclusion.phrase.clone(), reason))
+        });
+        if let Some((exclusion, reason)) = violation {
+            excluded.push(ExcludedSegment { segment_id: candidate.segment_id, exclusion, reason });
+            continue;
+        }
+
+        if !phrase_embeddings.is_empty() {
+            if let Some((_, vector)) = segment_embedding(db, candidate.segment_id)? {
+                let closest = phrase_embeddings
+                    .iter()
+                    .filter(|(_, embedding)| embedding.len() == vector.len())
+                    .map(|(phrase, embedding)| (*phrase, cosine_similarity(embedding, &vector)))
+                    .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+                if let Some((phrase, similarity)) = closest {
+                    if similarity >= EXCLUSION_SIMILARITY {
+                        excluded.push(ExcludedSegment {
+                            segment_id: candidate.segment_id,
+                            exclusion: phrase.to_string(),
+                            reason: format!("similar to '{}' ({:.2})", phrase, similarity),
+                        });
+                        continue;
+                    }
+                    candidate.similarity_score -= EXCLUSION_PENALTY_WEIGHT * similarity.max(0.0);
+                }
+            }
+        }
+        kept.push(candidate);
+    }
+
+    kept.sort_by(|a, b| b.similarity_score.partial_cmp(&a.similarity_score).unwrap_or(std::cmp::Ordering::Equal));
+    *candidates = kept;
+    Ok(excluded)
+}
+
+/// Why a segment's metadata rules it out under an exclusion, if it does
+fn metadata_violation(segment: &Segment, exclusion: &Exclusion) -> Option<String> {
+    let quality = segment
+        .quality_json
+        .as_ref()
+        .and_then(|q| serde_json::from_str::<serde_json::Value>(q).ok());
+    let quality_score = |key: &str| quality.as_ref().and_then(|q| q.get(key)).and_then(|v| v.as_f64());
+
+    match exclusion.kind {
+        ExclusionKind::Shaky => quality_score("motion_score")
+            .filter(|motion| *motion >= SHAKY_MOTION_SCORE)
+            .map(|motion| format!("motion score {:.0} (shaky at {:.0}+)", motion, SHAKY_MOTION_SCORE)),
+        // Enrichment writes 0 when no frame was measured, so 0 means unknown rather than blurry
+        ExclusionKind::Blurry => quality_score("blur_score")
+            .filter(|blur| *blur > 0.0 && *blur < BLURRY_BLUR_SCORE)
+            .map(|blur| format!("blur score {:.0} (blurry below {:.0})", blur, BLURRY_BLUR_SCORE)),
+        ExclusionKind::Semantic => {
+            // Literal mentions in descriptive metadata (not transcript: saying a word isn't
+            // being there)
+            [
+                ("summary", segment.summary_text.as_deref()),
+                ("on-screen text", segment.text_in_video.as_deref()),
+                ("keywords", segment.keywords_json.as_deref()),
+                ("scene tags", segment.scene_json.as_deref()),
+                ("tags", segment.tags_json.as_deref()),
+            ]
+            .into_iter()
+            .find_map(|(field, text)| {
+                let text = text?.to_lowercase();
+                find_word(&text, &exclusion.phrase).map(|_| format!("{} mentions '{}'", field, exclusion.phrase))
+            })
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use anyhow::Result;
+use std::collections::HashMap;
+
+use crate::db::{Database, Segment};
+use crate::embeddings::cosine_similarity;
+use crate::embeddings::registry::{TEXT_MODEL, VISION_MODEL};
+use engine::diff::generate_diff;
+use engine::timeline::{ClipInstance, Timeline};
+
+/// Score added to a segment the user kept
+const KEPT_REWARD: f32 = 0.1;
+/// Score removed per time the user deleted or swapped out a segment (capped)
+const REMOVED_PENALTY: f32 = 0.15;
+const MAX_REMOVED_COUNT: i64 = 3;
+/// Segments at least this similar to a corrected one share its adjustment (scaled by similarity)
+const SIMILAR_THRESHOLD: f32 = 0.85;
+
+/// Log a user edit to edit_logs and learn from it: agent-placed clips the user removed
+/// penalize their segment, agent-placed clips that survived the edit reward theirs
+pub fn record_user_edit(db: &Database, project_id: i64, before: &Timeline, after: &Timeline) -> Result<()> {
+    db.store_edit_log(project_id, &generate_diff(before, after).to_string())?;
+
+    let agent_clips: Vec<&ClipInstance> = before
+        .tracks
+        .iter()
+        .flat_map(|t| t.clips.iter())
+        .filter(|c| c.agent_placed)
+        .collect();
+    if agent_clips.is_empty() {
+        return Ok(());
+    }
+
+    let segments: Vec<Segment> = db
+        .get_segments_for_project(project_id)?
+        .into_iter()
+        .map(|(segment, _)| segment)
+        .collect();
+    let after_ids: Vec<&str> = after
+        .tracks
+        .iter()
+        .flat_map(|t| t.clips.iter())
+        .map(|c| c.id.as_str())
+        .collect();
+
+    for clip in agent_clips {
+        let Some(segment_id) = clip.segment_id.or_else(|| source_segment(clip, &segments)) else {
+            continue;
+        };
+        if after_ids.contains(&clip.id.as_str()) {
+            db.record_segment_kept(project_id, segment_id)?;
+        } else {
+            db.record_segment_removed(project_id, segment_id)?;
+        }
+    }
+    Ok(())
+}
+
+/// Segment on the clip's asset that overlaps its source range the most
+fn source_segment(clip: &ClipInstance, segments: &[Segment]) -> Option<i64> {
+    segments
+        .iter()
+        .filter(|s| s.media_asset_id == clip.asset_id)
+        .map(|s| (s.id, clip.out_ticks.min(s.end_ticks) - clip.in_ticks.max(s.start_ticks)))
+        .filter(|(_, overlap)| *overlap > 0)
+        .max_by_key(|(_, overlap)| *overlap)
+        .map(|(id, _)| id)
+}
+
+/// Score adjustments for candidate segments from the project's feedback: the corrected segment
+/// itself gets the full adjustment, similar segments (by embedding) a share of it
+pub fn feedback_adjustments(db: &Database, project_id: i64, candidate_ids: &[i64]) -> Result<HashMap<i64, f32>> {
+    let feedback = db.get_segment_feedback(project_id)?;
+    let mut adjustments = HashMap::new();
+    if feedback.is_empty() {
+        return Ok(adjustments);
+    }
+
+    let weights: Vec<(i64, f32)> = feedback
+        .iter()
+        .map(|(segment_id, kept, removed_count)| {
+            let reward = if *kept { KEPT_REWARD } else { 0.0 };
+            let penalty = REMOVED_PENALTY * (*removed_count).min(MAX_REMOVED_COUNT) as f32;
+            (*segment_id, reward - penalty)
+        })
+        .filter(|(_, weight)| *weight != 0.0)
+        .collect();
+
+    let mut embeddings: HashMap<i64, Option<(String, Vec<f32>)>> = HashMap::new();
+    let mut embedding_for = |segment_id: i64| -> Result<Option<(String, Vec<f32>)>> {
+        if let Some(cached) = embeddings.get(&segment_id) {
+            return Ok(cached.clone());
+        }
+        let embedding = segment_embedding(db, segment_id)?;
+        embeddings.insert(segment_id, embedding.clone());
+        Ok(embedding)
+    };
+
+    for candidate_id in candidate_ids {
+        let mut adjustment = 0.0;
+        for (segment_id, weight) in &weights {
+            if segment_id == candidate_id {
+                adjustment += weight;
+                continue;
+            }
+            let (Some((type_a, a)), Some((type_b, b))) = (embedding_for(*candidate_id)?, embedding_for(*segment_id)?) else {
+                continue;
+            };
+            // Text and vision vectors live in different spaces; only compare like with like
+            if type_a != type_b {
+                continue;
+            }
+            let similarity = cosine_similarity(&a, &b);
+            if similarity >= SIMILAR_THRESHOLD {
+                adjustment += weight * similarity;
+            }
+        }
+        if adjustment != 0.0 {
+            adjustments.insert(*candidate_id, adjustment);
+        }
+    }
+    Ok(adjustments)
+}
+
+/// A segment's text embedding, or its vision embedding when it has no text one, with the
+/// embedding type it came from
+pub(crate) fn segment_embedding(db: &Database, segment_id: i64) -> Result<Option<(String, Vec<f32>)>> {
+    let Some((_, embeddings)) = db.get_segment_with_embeddings(segment_id)? else {
+        return Ok(None);
+    };
+    let found = [TEXT_MODEL, VISION_MODEL].iter().find_map(|model| {
+        embeddings
+            .iter()
+            .find(|(t, name, _)| t == model.embedding_type && name == model.model_name)
+            .map(|(t, _, blob)| (t.clone(), blob))
+    });
+    Ok(found.map(|(embedding_type, blob)| {
+        let vector = blob
+            .chunks_exact(4)
+            .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+            .collect();
+        (embedding_type, vector)
+    }))
+}
@@ -1,13 +1,41 @@
 use anyhow::Result;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
+use std::time::Instant;
 
 use crate::api::orchestrator::{RetrievalFilters, SegmentCandidate, TimelineContext};
 use crate::db::Database;
 use crate::embeddings;
+use crate::embeddings::SearchScope;
+use crate::jobs::audio_events::{audio_events_in_query, AUDIO_EVENT_KIND};
 use crate::llm;
+use crate::retrieval::prefilter::SegmentPrefilter;
 use crate::retrieval::{RetrievalBackend, RetrievalBackendKind, RetrievalResult};
 use engine::timeline::TICKS_PER_SECOND;
 
+/// Max lexical (full-text) matches blended into embedding results
+const LEXICAL_LIMIT: usize = 50;
+/// Weight of the normalized lexical score added to cosine similarity
+const LEXICAL_WEIGHT: f32 = 0.3;
+/// Weight of audio event confidence added when the query asks for that event
+const AUDIO_EVENT_WEIGHT: f32 = 0.3;
+/// Audio events below this confidence don't count as a match
+const AUDIO_EVENT_MIN_CONFIDENCE: f64 = 0.3;
+/// Paraphrase / aspect queries generated per intent (QUERY_EXPANSION=off disables expansion)
+const QUERY_EXPANSION_COUNT: usize = 4;
+/// Similarity from an expanded query is scaled by this, so a segment matching the intent as
+/// written outranks one that only matches a paraphrase
+const EXPANSION_WEIGHT: f32 = 0.9;
+
+/// Raw signals behind a candidate's blended score, reported in debug output
+#[derive(Debug, Clone, Default, Serialize)]
+struct ScoreParts {
+    semantic: Option<f32>,
+    lexical: Option<f32>,
+    audio_event: Option<f32>,
+}
+
 pub struct LocalEmbeddingsBackend {
     db: Arc<Database>,
 }
@@ -16,6 +44,68 @@ impl LocalEmbeddingsBackend {
     pub fn new(db: Arc<Database>) -> Self {
         LocalEmbeddingsBackend { db }
     }
+
+    /// The query plus its translation into each language spoken in the project's footage, with
+    /// the query's detected language. Translation failures fall back to the query alone.
+    async fn query_variants(&self, project_id: i64, user_intent: &str) -> (Option<String>, Vec<String>) {
+        let mut queries = vec![user_intent.to_string()];
+        let languages = self.db.get_project_languages(project_id).unwrap_or_default();
+        if languages.is_empty() {
+            return (None, queries);
+        }
+        match llm::translate_query(&llm::CallAudit::new(&self.db, project_id), user_intent, &languages).await {
+            Ok(response) => {
+                if let Some(translations) = response.get("translations").and_then(|t| t.as_object()) {
+                    queries.extend(
+                        translations
+                            .values()
+                            .filter_map(|t| t.as_str())
+                            .filter(|t| !queries.iter().any(|q| q.eq_ignore_ascii_case(t)))
+                            .map(String::from)
+                            .collect::<Vec<_>>(),
+                    );
+                }
+                let source_language = response.get("source_language").and_then(|l| l.as_str()).map(String::from);
+                (source_language, queries)
+            }
+            Err(e) => {
+                eprintln!("[RETRIEVAL] Query translation failed, searching untranslated: {:?}", e);
+                (None, queries)
+            }
+        }
+    }
+
+    /// LLM paraphrases / aspect queries for the intent, improving recall for vague prompts.
+    /// Empty when disabled or when expansion fails.
+    async fn expanded_queries(&self, project_id: i64, user_intent: &str) -> Vec<String> {
+        if std::env::var("QUERY_EXPANSION").map(|v| v == "off").unwrap_or(false) {
+            return Vec::new();
+        }
+        llm::expand_query(&llm::CallAudit::new(&self.db, project_id), user_intent, QUERY_EXPANSION_COUNT)
+            .await
+            .unwrap_or_else(|e| {
+                eprintln!("[RETRIEVAL] Query expansion failed, searching the intent only: {:?}", e);
+                Vec::new()
+            })
+    }
+
+    /// Embed a query for both text and vision (CLIP's text encoder) and rank segments by their
+    /// fused similarity; without a vision query embedding, by text similarity alone. Searches raw
+    /// segments only (not reference segments), limited to `allowed_ids` when a prefilter
+    /// produced them.
+    async fn semantic_search(&self, project_id: i64, query: &str, allowed_ids: Option<&[i64]>) -> Result<Vec<(i64, f32)>> {
+        let (text_query, vision_query) = tokio::join!(llm::embed_text(query), llm::embed_vision_text(query));
+        let text_query = text_query?;
+        let vision_query = vision_query
+            .map_err(|e| eprintln!("[RETRIEVAL] Vision query embedding failed, ranking by text only: {:?}", e))
+            .ok();
+        let scope = match allowed_ids {
+            Some(ids) => SearchScope::Segments(ids),
+            None => SearchScope::Project(project_id),
+        };
+        // Oversample: get top 200 candidates
+        embeddings::fused_search(self.db.clone(), &text_query, vision_query.as_deref(), 200, scope)
+    }
 }
 
 #[async_trait::async_trait]
@@ -25,51 +115,128 @@ impl RetrievalBackend for LocalEmbeddingsBackend {
         project_id: i64,
         user_intent: &str,
         filters: Option<&RetrievalFilters>,
-        context: Option<&TimelineContext>,
+        _context: Option<&TimelineContext>,
     ) -> Result<RetrievalResult> {
-        // Embed user intent using text embedding
-        let query_embedding = llm::embed_text(user_intent).await?;
+        // Search with the query as written plus its translations into the footage's languages,
+        // so a prompt in one language finds speech and on-screen text in another
+        // Expansion adds paraphrases of the intent for recall; they only feed the vector search
+        // (lexical matching stays on the literal query and its translations)
+        let started = Instant::now();
+        let ((query_language, queries), expansions) = tokio::join!(
+            self.query_variants(project_id, user_intent),
+            self.expanded_queries(project_id, user_intent),
+        );
+        let expansions: Vec<String> = expansions
+            .into_iter()
+            .filter(|e| !queries.iter().any(|q| q.eq_ignore_ascii_case(e)))
+            .collect();
+        let rewrite_ms = started.elapsed().as_millis() as u64;
+        
+        // Narrow the candidate set in SQL first (kind, assets, quality, unused, capture window)
+        // so the vector search only scores segments that can be returned
+        let stage = Instant::now();
+        let prefilter = SegmentPrefilter::from_filters(&self.db, project_id, filters)?;
+        let allowed_ids: Option<Vec<i64>> = if prefilter.is_project_only() {
+            None
+        } else {
+            Some(prefilter.segment_ids(&self.db)?)
+        };
+        let prefilter_ms = stage.elapsed().as_millis() as u64;
+        
+        // Oversample: retrieve 200 candidates per query first, then apply filters and diversity.
+        // A segment matched by several queries keeps its best (weighted) similarity.
+        let stage = Instant::now();
+        let weighted_queries = queries
+            .iter()
+            .map(|q| (q, 1.0))
+            .chain(expansions.iter().map(|q| (q, EXPANSION_WEIGHT)));
+        let mut search_results: Vec<(i64, f32)> = Vec::new();
+        for (query, weight) in weighted_queries {
+            let results = match self.semantic_search(project_id, query, allowed_ids.as_deref()).await {
+                Ok(results) => results,
+                // The intent itself must search; a failed expansion only costs recall
+                Err(e) if weight < 1.0 => {
+                    eprintln!("[RETRIEVAL] Expanded query '{}' failed: {:?}", query, e);
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+            for (segment_id, score) in results {
+                let score = score * weight;
+                match search_results.iter_mut().find(|(id, _)| *id == segment_id) {
+                    Some((_, best)) => *best = best.max(score),
+                    None => search_results.push((segment_id, score)),
+                }
+            }
+        }
+        
+        let mut score_parts: HashMap<i64, ScoreParts> = search_results
+            .iter()
+            .map(|(id, score)| (*id, ScoreParts { semantic: Some(*score), ..Default::default() }))
+            .collect();
+        let semantic_ms = stage.elapsed().as_millis() as u64;
         
-        // Oversample: retrieve 200 candidates first, then apply filters and diversity
-        // Try to use fusion embeddings first, fallback to text embeddings if fusion not available
-        // Search raw segments only (not reference segments for content)
-        let mut search_results = embeddings::similarity_search(
-            self.db.clone(),
-            &query_embedding,
-            "fusion",
-            "fusion-0.6-0.4",
-            200, // Oversample: get top 200 candidates
-            Some(project_id),
-            true, // raw_segments_only = true
-        ).or_else(|_| {
-            // Fallback to text embeddings if fusion not available
-            embeddings::similarity_search(
-                self.db.clone(),
-                &query_embedding,
-                "text",
-                "all-MiniLM-L6-v2",
-                200, // Oversample: get top 200 candidates
-                Some(project_id),
-                true, // raw_segments_only = true
-            )
-        })?;
+        // Hybrid: blend in lexical matches on on-screen text / transcript so literal queries
+        // ("the pricing slide") find segments even when embeddings are fuzzy
+        let stage = Instant::now();
+        let mut lexical_results: Vec<(i64, f32)> = Vec::new();
+        for query in &queries {
+            let results = self.db
+                .search_segments_lexical(project_id, query, LEXICAL_LIMIT)
+                .unwrap_or_else(|e| {
+                    eprintln!("[RETRIEVAL] Lexical search failed: {:?}", e);
+                    Vec::new()
+                });
+            for (segment_id, lexical_score) in results {
+                match lexical_results.iter_mut().find(|(id, _)| *id == segment_id) {
+                    Some((_, best)) => *best = best.max(lexical_score),
+                    None => lexical_results.push((segment_id, lexical_score)),
+                }
+            }
+        }
+        let lexical_hits = lexical_results.len();
+        for (segment_id, lexical_score) in lexical_results {
+            score_parts.entry(segment_id).or_default().lexical = Some(lexical_score);
+            match search_results.iter_mut().find(|(id, _)| *id == segment_id) {
+                Some((_, score)) => *score += LEXICAL_WEIGHT * lexical_score,
+                None => search_results.push((segment_id, LEXICAL_WEIGHT * lexical_score)),
+            }
+        }
         
-        // Get segments and apply filters
+        // Audio events: "moments where people laugh" should surface segments tagged with laughter
+        // even when nothing about the laugh made it into transcripts or embeddings
+        let mut audio_event_hits = 0;
+        for label in audio_events_in_query(user_intent) {
+            let tagged = self.db
+                .get_segments_with_tag(project_id, label, AUDIO_EVENT_KIND, AUDIO_EVENT_MIN_CONFIDENCE)
+                .unwrap_or_else(|e| {
+                    eprintln!("[RETRIEVAL] Audio event lookup failed: {:?}", e);
+                    Vec::new()
+                });
+            audio_event_hits += tagged.len();
+            for (segment_id, confidence) in tagged {
+                let boost = AUDIO_EVENT_WEIGHT * confidence as f32;
+                score_parts.entry(segment_id).or_default().audio_event = Some(confidence as f32);
+                match search_results.iter_mut().find(|(id, _)| *id == segment_id) {
+                    Some((_, score)) => *score += boost,
+                    None => search_results.push((segment_id, boost)),
+                }
+            }
+        }
+        // Lexical and audio-event matches aren't prefiltered in SQL; hold them to the same set
+        if let Some(ref allowed) = allowed_ids {
+            let allowed: HashSet<i64> = allowed.iter().copied().collect();
+            search_results.retain(|(id, _)| allowed.contains(id));
+        }
+        search_results.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        let lexical_ms = stage.elapsed().as_millis() as u64;
+        
+        // Get segments (already filtered)
         let mut candidate_segments = Vec::new();
         for (segment_id, similarity_score) in search_results {
             let segment_opt = self.db.get_segment_with_embeddings(segment_id)?;
             
             if let Some((segment, _embeddings)) = segment_opt {
-                // Apply filters
-                if let Some(ref filters) = filters {
-                    if let Some(ref kind) = filters.segment_kind {
-                        if segment.segment_kind.as_ref() != Some(kind) {
-                            continue;
-                        }
-                    }
-                    // Additional filters can be applied here
-                }
-                
                 let duration_sec = {
                     let start = Database::get_coalesced_src_in(&segment);
                     let end = Database::get_coalesced_src_out(&segment);
@@ -82,20 +249,51 @@ impl RetrievalBackend for LocalEmbeddingsBackend {
                     capture_time: segment.capture_time.clone(),
                     duration_sec,
                     similarity_score,
+                    moment_segment_ids: Vec::new(),
                 });
             }
         }
         
+        let candidate_scores: Vec<serde_json::Value> = candidate_segments
+            .iter()
+            .map(|c| {
+                let parts = score_parts.remove(&c.segment_id).unwrap_or_default();
+                serde_json::json!({
+                    "segment_id": c.segment_id,
+                    "score": c.similarity_score,
+                    "semantic": parts.semantic,
+                    "lexical": parts.lexical,
+                    "audio_event": parts.audio_event,
+                    "mapping": "existing",
+                })
+            })
+            .collect();
+        
         // Build debug info
         let debug = serde_json::json!({
             "backend_used": "local_embeddings",
+            "lexical_hits": lexical_hits,
+            "query_language": query_language,
+            "query_variants": queries,
+            "expanded_queries": expansions,
+            "prefilter": prefilter,
+            "prefiltered_count": allowed_ids.as_ref().map(|ids| ids.len()),
+            "audio_event_hits": audio_event_hits,
             "tl_index_ready": false,
             "tl_results_count": 0,
             "mapping_stats": {
                 "snapped_count": 0,
                 "created_count": 0
             },
-            "fallback_reason": null
+            "fallback_reason": null,
+            "candidate_scores": candidate_scores,
+            "timings_ms": {
+                "translate_and_expand": rewrite_ms,
+                "prefilter": prefilter_ms,
+                "semantic": semantic_ms,
+                "lexical_and_audio": lexical_ms,
+                "total": started.elapsed().as_millis() as u64
+            }
         });
         
         Ok(RetrievalResult {
@@ -103,6 +301,7 @@ impl RetrievalBackend for LocalEmbeddingsBackend {
             backend_used: RetrievalBackendKind::LocalEmbeddings,
             debug,
             warnings: Vec::new(),
+            excluded: Vec::new(),
         })
     }
 }
@@ -1,6 +1,7 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use std::time::Instant;
 
 use crate::api::orchestrator::{RetrievalFilters, SegmentCandidate, TimelineContext};
 use crate::db::Database;
@@ -30,6 +31,8 @@ pub struct RetrievalResult {
     pub backend_used: RetrievalBackendKind,
     pub debug: serde_json::Value,
     pub warnings: Vec<String>,
+    /// Candidates dropped by exclusions in the intent ("no shaky footage"), with reasons
+    pub excluded: Vec<exclusions::ExcludedSegment>,
 }
 
 /// Trait for retrieval backends
@@ -56,7 +59,42 @@ pub async fn retrieve_candidates(
     let backend_str = std::env::var("RETRIEVAL_BACKEND")
         .unwrap_or_else(|_| "twelvelabs_then_local".to_string());
     
-    match backend_str.as_str() {
+    retrieve_candidates_with_backend(db, project_id, user_intent, filters, context, &backend_str).await
+}
+
+/// Retrieve with an explicit backend selection ("twelvelabs", "local" or
+/// "twelvelabs_then_local"), as RETRIEVAL_BACKEND would choose it
+pub async fn retrieve_candidates_with_backend(
+    db: Arc<Database>,
+    project_id: i64,
+    user_intent: &str,
+    filters: Option<&RetrievalFilters>,
+    context: Option<&TimelineContext>,
+    backend_str: &str,
+) -> Result<RetrievalResult> {
+    let started = Instant::now();
+
+    // A collection scope becomes a segment scope here, so every backend honors it
+    let scoped_filters = collection_scoped_filters(&db, project_id, filters)?;
+    if let Some(scoped) = &scoped_filters {
+        if scoped.segment_ids.as_ref().is_none_or(|ids| ids.is_empty()) {
+            return Ok(RetrievalResult {
+                candidates: Vec::new(),
+                backend_used: RetrievalBackendKind::LocalEmbeddings,
+                debug: serde_json::json!({ "collection_id": scoped.collection_id, "collection_segments": 0 }),
+                warnings: vec!["The collection has no analyzed segments to search".to_string()],
+                excluded: Vec::new(),
+            });
+        }
+    }
+    let filters = scoped_filters.as_ref().or(filters);
+    
+    // Negative constraints ("nothing from the airport") are applied after retrieval; only the
+    // positive part of the intent is searched for
+    let parsed = exclusions::parse_exclusions(user_intent);
+    let user_intent = if parsed.positive.is_empty() { user_intent } else { parsed.positive.as_str() };
+    
+    let mut result = match backend_str {
         "twelvelabs" => {
             // Try TwelveLabs only
             match crate::retrieval::twelvelabs_backend::TwelveLabsBackend::new(db.clone()).retrieve_candidates(
@@ -74,7 +112,7 @@ pub async fn retrieve_candidates(
         }
         "local" => {
             // Use local embeddings only
-            crate::retrieval::local_backend::LocalEmbeddingsBackend::new(db).retrieve_candidates(
+            crate::retrieval::local_backend::LocalEmbeddingsBackend::new(db.clone()).retrieve_candidates(
                 project_id,
                 user_intent,
                 filters,
@@ -92,8 +130,9 @@ pub async fn retrieve_candidates(
                 Ok(result) => Ok(result),
                 Err(e) => {
                     // Fallback to local embeddings
+                    let twelvelabs_ms = started.elapsed().as_millis() as u64;
                     eprintln!("[RETRIEVAL] TwelveLabs failed, falling back to local embeddings: {:?}", e);
-                    let mut local_result = crate::retrieval::local_backend::LocalEmbeddingsBackend::new(db).retrieve_candidates(
+                    let mut local_result = crate::retrieval::local_backend::LocalEmbeddingsBackend::new(db.clone()).retrieve_candidates(
                         project_id,
                         user_intent,
                         filters,
@@ -103,6 +142,7 @@ pub async fn retrieve_candidates(
                     // Update debug to indicate fallback
                     if let Some(debug_obj) = local_result.debug.as_object_mut() {
                         debug_obj.insert("fallback_reason".to_string(), serde_json::json!(e.to_string()));
+                        debug_obj.insert("failed_backend_ms".to_string(), serde_json::json!(twelvelabs_ms));
                     }
                     local_result.warnings.push(format!("TwelveLabs unavailable, using local embeddings: {}", e));
                     
@@ -110,9 +150,92 @@ pub async fn retrieve_candidates(
                 }
             }
         }
+    }?;
+
+    // Learned feedback: segments like the ones the user removed rank lower, kept ones higher
+    let candidate_ids: Vec<i64> = result.candidates.iter().map(|c| c.segment_id).collect();
+    match feedback::feedback_adjustments(&db, project_id, &candidate_ids) {
+        Ok(adjustments) if !adjustments.is_empty() => {
+            for candidate in result.candidates.iter_mut() {
+                if let Some(adjustment) = adjustments.get(&candidate.segment_id) {
+                    candidate.similarity_score += adjustment;
+                }
+            }
+            result.candidates.sort_by(|a, b| {
+                b.similarity_score.partial_cmp(&a.similarity_score).unwrap_or(std::cmp::Ordering::Equal)
+            });
+            if let Some(debug_obj) = result.debug.as_object_mut() {
+                debug_obj.insert("feedback_adjusted".to_string(), serde_json::json!(adjustments.len()));
+                debug_obj.insert("feedback_adjustments".to_string(), serde_json::json!(adjustments));
+            }
+        }
+        Ok(_) => {}
+        Err(e) => eprintln!("[RETRIEVAL] Feedback adjustment failed: {:?}", e),
+    }
+
+    if !parsed.exclusions.is_empty() {
+        match exclusions::apply_exclusions(&db, &parsed.exclusions, &mut result.candidates).await {
+            Ok(excluded) => result.excluded = excluded,
+            Err(e) => eprintln!("[RETRIEVAL] Applying exclusions failed: {:?}", e),
+        }
+        if let Some(debug_obj) = result.debug.as_object_mut() {
+            debug_obj.insert("positive_query".to_string(), serde_json::json!(user_intent));
+            debug_obj.insert("exclusions".to_string(), serde_json::json!(parsed.exclusions));
+            debug_obj.insert("excluded".to_string(), serde_json::json!(result.excluded));
+        }
+    }
+
+    // Optional precision pass: cross-encoder scores for the top candidates
+    if rerank::rerank_enabled() && !result.candidates.is_empty() {
+        match rerank::rerank_candidates(&db, user_intent, &mut result.candidates).await {
+            Ok(rerank_debug) => {
+                if let Some(debug_obj) = result.debug.as_object_mut() {
+                    debug_obj.insert("rerank".to_string(), rerank_debug);
+                }
+            }
+            Err(e) => {
+                eprintln!("[RETRIEVAL] Re-ranking failed, keeping retrieval order: {:?}", e);
+                result.warnings.push(format!("Re-ranking unavailable: {}", e));
+            }
+        }
+    }
+    if let Some(debug_obj) = result.debug.as_object_mut() {
+        debug_obj.insert("retrieval_ms".to_string(), serde_json::json!(started.elapsed().as_millis() as u64));
     }
+
+    Ok(result)
+}
+
+/// `filters` with its collection resolved to segment ids (intersected with any segment scope
+/// already set); None when no collection is set
+fn collection_scoped_filters(
+    db: &Database,
+    project_id: i64,
+    filters: Option<&RetrievalFilters>,
+) -> Result<Option<RetrievalFilters>> {
+    let Some((filters, collection_id)) = filters.and_then(|f| f.collection_id.map(|id| (f, id))) else {
+        return Ok(None);
+    };
+    match db.get_collection(collection_id)? {
+        Some((collection_project_id, _, _)) if collection_project_id == project_id => {}
+        _ => return Err(anyhow::anyhow!("Collection {} not found in project {}", collection_id, project_id)),
+    }
+
+    let in_collection = db.collection_segment_ids(collection_id)?;
+    let segment_ids = match filters.segment_ids.as_ref().filter(|ids| !ids.is_empty()) {
+        Some(ids) => ids.iter().copied().filter(|id| in_collection.contains(id)).collect(),
+        None => in_collection,
+    };
+    Ok(Some(RetrievalFilters {
+        segment_ids: Some(segment_ids),
+        ..filters.clone()
+    }))
 }
 
+pub mod exclusions;
+pub mod feedback;
 pub mod local_backend;
+pub mod prefilter;
+pub mod rerank;
 pub mod twelvelabs_backend;
 
@@ -0,0 +1,136 @@
+use anyhow::Result;
+use rusqlite::types::Value;
+use serde::Serialize;
+use std::collections::HashSet;
+
+use crate::api::orchestrator::RetrievalFilters;
+use crate::db::Database;
+
+/// Laplacian variance at which a frame counts as fully sharp; `quality_threshold` (0-1) is a
+/// fraction of this
+const SHARP_BLUR_SCORE: f64 = 100.0;
+
+/// Metadata constraints applied in SQL before any vector is scored, so the vector search only
+/// ranks segments that can actually be returned
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SegmentPrefilter {
+    pub project_id: i64,
+    pub asset_ids: Option<Vec<i64>>,
+    pub segment_kind: Option<String>,
+    /// Minimum blur_score (Laplacian variance); segments not analyzed yet pass
+    pub min_blur_score: Option<f64>,
+    /// Segments already on the timeline, excluded for `unused_only`
+    pub exclude_segment_ids: Vec<i64>,
+    /// Inclusive RFC 3339 capture-time window; segments without a capture time are excluded
+    pub capture_window: Option<(String, String)>,
+    /// Only these segments (the conversation's working set)
+    pub segment_ids: Option<Vec<i64>>,
+}
+
+impl SegmentPrefilter {
+    /// Translate request filters into SQL prefilters for a project
+    pub fn from_filters(db: &Database, project_id: i64, filters: Option<&RetrievalFilters>) -> Result<Self> {
+        let mut prefilter = SegmentPrefilter {
+            project_id,
+            ..Default::default()
+        };
+        let Some(filters) = filters else {
+            return Ok(prefilter);
+        };
+        prefilter.asset_ids = filters.asset_ids.clone().filter(|ids| !ids.is_empty());
+        prefilter.segment_kind = filters.segment_kind.map(|kind| kind.as_str().to_string());
+        prefilter.min_blur_score = filters.quality_threshold.map(|t| t.clamp(0.0, 1.0) * SHARP_BLUR_SCORE);
+        prefilter.capture_window = filters.capture_time_range.clone();
+        prefilter.segment_ids = filters.segment_ids.clone().filter(|ids| !ids.is_empty());
+        if filters.unused_only.unwrap_or(false) {
+            prefilter.exclude_segment_ids = used_segment_ids(db, project_id)?;
+        }
+        Ok(prefilter)
+    }
+
+    /// True when only the project scope applies (nothing to narrow beyond it)
+    pub fn is_project_only(&self) -> bool {
+        self.asset_ids.is_none()
+            && self.segment_kind.is_none()
+            && self.min_blur_score.is_none()
+            && self.exclude_segment_ids.is_empty()
+            && self.capture_window.is_none()
+            && self.segment_ids.is_none()
+    }
+
+    /// Ids of the project's raw (non-reference) segments that pass every constraint
+    pub fn segment_ids(&self, db: &Database) -> Result<Vec<i64>> {
+        let mut sql = String::from(
+            "SELECT s.id FROM segments s
+             JOIN media_assets m ON s.media_asset_id = m.id
+             WHERE s.project_id = ? AND (m.is_reference IS NULL OR m.is_reference = 0)",
+        );
+        let mut values: Vec<Value> = vec![Value::Integer(self.project_id)];
+
+        if let Some(ref asset_ids) = self.asset_ids {
+            sql.push_str(&format!(" AND s.media_asset_id IN ({})", placeholders(asset_ids.len())));
+            values.extend(asset_ids.iter().map(|id| Value::Integer(*id)));
+        }
+        if let Some(ref segment_ids) = self.segment_ids {
+            sql.push_str(&format!(" AND s.id IN ({})", placeholders(segment_ids.len())));
+            values.extend(segment_ids.iter().map(|id| Value::Integer(*id)));
+        }
+        if let Some(ref kind) = self.segment_kind {
+            sql.push_str(" AND s.segment_kind = ?");
+            values.push(Value::Text(kind.clone()));
+        }
+        if let Some(min_blur_score) = self.min_blur_score {
+            sql.push_str(
+                " AND (json_extract(s.quality_json, '$.blur_score') IS NULL
+                       OR json_extract(s.quality_json, '$.blur_score') >= ?)",
+            );
+            values.push(Value::Real(min_blur_score));
+        }
+        if !self.exclude_segment_ids.is_empty() {
+            sql.push_str(&format!(" AND s.id NOT IN ({})", placeholders(self.exclude_segment_ids.len())));
+            values.extend(self.exclude_segment_ids.iter().map(|id| Value::Integer(*id)));
+        }
+        if let Some((ref start, ref end)) = self.capture_window {
+            sql.push_str(" AND s.capture_time IS NOT NULL AND s.capture_time >= ? AND s.capture_time <= ?");
+            values.push(Value::Text(start.clone()));
+            values.push(Value::Text(end.clone()));
+        }
+
+        let conn = db.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(rusqlite::params_from_iter(values), |row| row.get(0))?;
+        Ok(rows.collect::<Result<Vec<_>, _>>()?)
+    }
+}
+
+fn placeholders(count: usize) -> String {
+    vec!["?"; count].join(", ")
+}
+
+/// Segments on the project's current timeline: the segment each clip was placed from, or for
+/// clips without one, every segment its source range overlaps
+pub(crate) fn used_segment_ids(db: &Database, project_id: i64) -> Result<Vec<i64>> {
+    let Some(timeline) = db
+        .get_timeline(project_id)?
+        .and_then(|json| serde_json::from_str::<engine::timeline::Timeline>(&json).ok())
+    else {
+        return Ok(Vec::new());
+    };
+
+    let clips: Vec<_> = timeline.tracks.iter().flat_map(|t| t.clips.iter()).collect();
+    let mut used: HashSet<i64> = clips.iter().filter_map(|c| c.segment_id).collect();
+    if clips.iter().any(|c| c.segment_id.is_none()) {
+        for (segment, _) in db.get_segments_for_project(project_id)? {
+            let overlaps = clips.iter().any(|c| {
+                c.segment_id.is_none()
+                    && c.asset_id == segment.media_asset_id
+                    && c.in_ticks < segment.end_ticks
+                    && c.out_ticks > segment.start_ticks
+            });
+            if overlaps {
+                used.insert(segment.id);
+            }
+        }
+    }
+    Ok(used.into_iter().collect())
+}
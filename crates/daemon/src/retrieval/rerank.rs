@@ -0,0 +1,54 @@
+use anyhow::Result;
+use std::collections::HashMap;
+use std::time::Instant;
+
+use crate::api::orchestrator::SegmentCandidate;
+use crate::db::Database;
+use crate::jobs::embeddings::construct_semantic_text;
+use crate::llm;
+
+/// Candidates re-scored by the cross-encoder; comfortably more than the 20 passed to the LLM
+const RERANK_TOP_N: usize = 50;
+/// Share of a re-ranked candidate's score that comes from the cross-encoder (rest: retrieval)
+const RERANK_WEIGHT: f32 = 0.7;
+
+/// Re-ranking is optional: RETRIEVAL_RERANK=cross_encoder turns it on
+pub fn rerank_enabled() -> bool {
+    std::env::var("RETRIEVAL_RERANK").map(|v| v == "cross_encoder").unwrap_or(false)
+}
+
+/// Re-score the top candidates with a cross-encoder over (intent, segment semantic text) and
+/// re-sort. Candidates past RERANK_TOP_N keep their retrieval score. Returns debug info.
+pub async fn rerank_candidates(
+    db: &Database,
+    user_intent: &str,
+    candidates: &mut [SegmentCandidate],
+) -> Result<serde_json::Value> {
+    let started = Instant::now();
+    let top_n = candidates.len().min(RERANK_TOP_N);
+    let mut documents = Vec::with_capacity(top_n);
+    for candidate in candidates.iter().take(top_n) {
+        let text = match db.get_segment_with_embeddings(candidate.segment_id)? {
+            Some((segment, _)) => construct_semantic_text(&segment),
+            None => candidate.summary_text.clone().unwrap_or_default(),
+        };
+        documents.push((candidate.segment_id, text));
+    }
+
+    let scores: HashMap<i64, f32> = llm::rerank(user_intent, &documents).await?.into_iter().collect();
+    for candidate in candidates.iter_mut().take(top_n) {
+        if let Some(score) = scores.get(&candidate.segment_id) {
+            candidate.similarity_score =
+                (1.0 - RERANK_WEIGHT) * candidate.similarity_score + RERANK_WEIGHT * score;
+        }
+    }
+    candidates.sort_by(|a, b| {
+        b.similarity_score.partial_cmp(&a.similarity_score).unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    Ok(serde_json::json!({
+        "reranked_count": scores.len(),
+        "cross_encoder_scores": scores,
+        "elapsed_ms": started.elapsed().as_millis() as u64,
+    }))
+}
@@ -1,5 +1,6 @@
 use anyhow::Result;
 use std::sync::Arc;
+use std::time::Instant;
 
 use crate::api::orchestrator::{RetrievalFilters, SegmentCandidate, TimelineContext};
 use crate::db::Database;
@@ -63,6 +64,7 @@ impl RetrievalBackend for TwelveLabsBackend {
                     backend_used: RetrievalBackendKind::TwelveLabs,
                     debug,
                     warnings: vec!["TwelveLabs index not ready for this project. Indexing in progress.".to_string()],
+                    excluded: Vec::new(),
                 });
             }
         };
@@ -95,10 +97,12 @@ impl RetrievalBackend for TwelveLabsBackend {
                 backend_used: RetrievalBackendKind::TwelveLabs,
                 debug,
                 warnings: vec!["No assets indexed with TwelveLabs yet. Indexing in progress.".to_string()],
+                excluded: Vec::new(),
             });
         }
         
         // Search TwelveLabs
+        let started = Instant::now();
         let search_results = match twelvelabs::search(&index_id, user_intent, 200).await {
             Ok(results) => results,
             Err(e) => {
@@ -108,11 +112,14 @@ impl RetrievalBackend for TwelveLabsBackend {
         };
         
         let results_count = search_results.len();
+        let search_ms = started.elapsed().as_millis() as u64;
         
         // Map search results to segments
         let mut candidates = Vec::new();
         let mut snapped_count = 0;
         let mut created_count = 0;
+        let mut filtered_out_count = 0;
+        let mut candidate_scores = Vec::new();
         
         for search_result in search_results {
             // Convert seconds to ticks
@@ -140,6 +147,7 @@ impl RetrievalBackend for TwelveLabsBackend {
             };
             
             // Try to snap to existing segment
+            let mut mapping = "snapped";
             let segment_id = {
                 let segments = self.db.get_segments_by_asset(asset_id)?;
                 
@@ -196,6 +204,7 @@ impl RetrievalBackend for TwelveLabsBackend {
                     )?;
                     
                     created_count += 1;
+                    mapping = "created";
                     seg_id
                 }
             };
@@ -206,8 +215,15 @@ impl RetrievalBackend for TwelveLabsBackend {
             if let Some((segment, _embeddings)) = segment_opt {
                 // Apply filters
                 if let Some(ref filters) = filters {
-                    if let Some(ref kind) = filters.segment_kind {
-                        if segment.segment_kind.as_ref() != Some(kind) {
+                    if let Some(kind) = filters.segment_kind {
+                        if crate::media::segment_kind::SegmentKind::of(&segment) != Some(kind) {
+                            filtered_out_count += 1;
+                            continue;
+                        }
+                    }
+                    if let Some(ref segment_ids) = filters.segment_ids {
+                        if !segment_ids.is_empty() && !segment_ids.contains(&segment.id) {
+                            filtered_out_count += 1;
                             continue;
                         }
                     }
@@ -220,12 +236,20 @@ impl RetrievalBackend for TwelveLabsBackend {
                     (end - start) as f64 / TICKS_PER_SECOND as f64
                 };
                 
+                candidate_scores.push(serde_json::json!({
+                    "segment_id": segment.id,
+                    "score": search_result.score,
+                    "tl_start": search_result.start,
+                    "tl_end": search_result.end,
+                    "mapping": mapping,
+                }));
                 candidates.push(SegmentCandidate {
                     segment_id: segment.id,
                     summary_text: segment.summary_text.clone(),
                     capture_time: segment.capture_time.clone(),
                     duration_sec,
                     similarity_score: search_result.score as f32,
+                    moment_segment_ids: Vec::new(),
                 });
             }
         }
@@ -239,7 +263,14 @@ impl RetrievalBackend for TwelveLabsBackend {
                 "snapped_count": snapped_count,
                 "created_count": created_count
             },
-            "fallback_reason": null
+            "filtered_out_count": filtered_out_count,
+            "fallback_reason": null,
+            "candidate_scores": candidate_scores,
+            "timings_ms": {
+                "search": search_ms,
+                "mapping": started.elapsed().as_millis() as u64 - search_ms,
+                "total": started.elapsed().as_millis() as u64
+            }
         });
         
         Ok(RetrievalResult {
@@ -247,6 +278,7 @@ impl RetrievalBackend for TwelveLabsBackend {
             backend_used: RetrievalBackendKind::TwelveLabs,
             debug,
             warnings: Vec::new(),
+            excluded: Vec::new(),
         })
     }
 }
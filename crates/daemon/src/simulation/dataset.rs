@@ -0,0 +1,156 @@
+//! Synthetic projects at retrieval scale (10k-100k segments) for benchmarks: assets cut into
+//! 5-second segments with transcripts, occasional on-screen text, full-text index rows and
+//! text and vision embeddings, all seeded so every run searches the same data.
+//!
+//! Text vectors are the fake ML service's (see [`super::ml`]), so [`query_embedding`] ranks
+//! them the way a simulated project would. Vision vectors are random unit vectors; a 100k-segment
+//! database comes to about 500 MB.
+
+use anyhow::Result;
+use chrono::Utc;
+use rusqlite::{params, OptionalExtension};
+
+use super::ml::{text_embedding, vision_text_embedding, SENTENCES};
+use super::Rng;
+use crate::db::Database;
+use crate::embeddings::registry::{TEXT_MODEL, VISION_MODEL};
+use engine::timeline::TICKS_PER_SECOND;
+
+/// Segments cut from each generated asset
+const SEGMENTS_PER_ASSET: usize = 24;
+const SEGMENT_SECONDS: i64 = 5;
+/// One segment in this many has on-screen text
+const TEXT_IN_VIDEO_EVERY: usize = 8;
+const SIGNS: &[&str] = &[
+    "Harbor Cafe", "Lighthouse Trail 2.4 km", "Welcome to Seaside", "Parking", "Gate 12",
+    "Fresh Fish Market", "Summit 1,024 m", "Closed on Mondays",
+];
+
+/// A generated project and what went into it
+#[derive(Debug, Clone)]
+pub struct SyntheticProject {
+    pub project_id: i64,
+    pub asset_count: usize,
+    pub segment_ids: Vec<i64>,
+}
+
+/// Query vector comparable with the generated text embeddings
+pub fn query_embedding(query: &str) -> Vec<f32> {
+    text_embedding(query).into_iter().map(|v| v as f32).collect()
+}
+
+/// Query vector comparable with the generated vision embeddings
+pub fn vision_query_embedding(query: &str) -> Vec<f32> {
+    vision_text_embedding(query).into_iter().map(|v| v as f32).collect()
+}
+
+fn random_unit_vector(rng: &mut Rng, dimension: usize) -> Vec<f32> {
+    let vector: Vec<f32> = (0..dimension).map(|_| rng.unit() as f32 - 0.5).collect();
+    let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+    vector.into_iter().map(|v| v / norm).collect()
+}
+
+fn blob(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|f| f.to_le_bytes()).collect()
+}
+
+fn project_name(segments: usize, seed: u64) -> String {
+    format!("Synthetic {} segments (seed {})", segments, seed)
+}
+
+/// A project [`generate`] made earlier with the same size and seed, if the database has one
+pub fn find(db: &Database, segments: usize, seed: u64) -> Result<Option<SyntheticProject>> {
+    let conn = db.conn.lock().unwrap();
+    let project_id: Option<i64> = conn
+        .query_row(
+            "SELECT id FROM projects WHERE name = ?1 ORDER BY id LIMIT 1",
+            params![project_name(segments, seed)],
+            |row| row.get(0),
+        )
+        .optional()?;
+    let Some(project_id) = project_id else {
+        return Ok(None);
+    };
+    let asset_count: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM media_assets WHERE project_id = ?1",
+        params![project_id],
+        |row| row.get(0),
+    )?;
+    let mut stmt = conn.prepare("SELECT id FROM segments WHERE project_id = ?1 ORDER BY id")?;
+    let segment_ids = stmt
+        .query_map(params![project_id], |row| row.get(0))?
+        .collect::<Result<Vec<i64>, _>>()?;
+    Ok(Some(SyntheticProject {
+        project_id,
+        asset_count: asset_count as usize,
+        segment_ids,
+    }))
+}
+
+/// Create a project with `segments` analyzed segments, written in one transaction rather than
+/// a commit per row
+pub fn generate(db: &Database, segments: usize, seed: u64) -> Result<SyntheticProject> {
+    let mut rng = Rng::new(seed);
+    let mut segment_ids = Vec::with_capacity(segments);
+    let asset_count = segments.div_ceil(SEGMENTS_PER_ASSET);
+    let now = Utc::now().to_rfc3339();
+
+    let mut conn = db.conn.lock().unwrap();
+    let tx = conn.transaction()?;
+    tx.execute(
+        "INSERT INTO projects (name, created_at, cache_dir) VALUES (?1, ?2, ?3)",
+        params![project_name(segments, seed), now, "/tmp/vibecut-synthetic"],
+    )?;
+    let project_id = tx.last_insert_rowid();
+    for asset in 0..asset_count {
+        let count = SEGMENTS_PER_ASSET.min(segments - asset * SEGMENTS_PER_ASSET);
+        let duration_ticks = count as i64 * SEGMENT_SECONDS * TICKS_PER_SECOND;
+        tx.execute(
+            "INSERT INTO media_assets (project_id, path, duration_ticks, fps_num, fps_den, width, height, has_audio)
+             VALUES (?1, ?2, ?3, 30, 1, 1920, 1080, 1)",
+            params![project_id, format!("/synthetic/clip_{:05}.mp4", asset), duration_ticks],
+        )?;
+        let asset_id = tx.last_insert_rowid();
+
+        for i in 0..count {
+            let start = i as i64 * SEGMENT_SECONDS * TICKS_PER_SECOND;
+            let end = start + SEGMENT_SECONDS * TICKS_PER_SECOND;
+            let transcript = format!(
+                "{}. {}.",
+                SENTENCES[rng.below(SENTENCES.len())],
+                SENTENCES[rng.below(SENTENCES.len())]
+            );
+            let text_in_video = (rng.below(TEXT_IN_VIDEO_EVERY) == 0).then(|| SIGNS[rng.below(SIGNS.len())]);
+            tx.execute(
+                "INSERT INTO segments (media_asset_id, project_id, start_ticks, end_ticks, src_in_ticks, src_out_ticks,
+                                       segment_kind, transcript, text_in_video, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?3, ?4, 'b_roll', ?5, ?6, ?7)",
+                params![asset_id, project_id, start, end, transcript, text_in_video, now],
+            )?;
+            let segment_id = tx.last_insert_rowid();
+            tx.execute(
+                "INSERT INTO segment_text_fts (segment_id, project_id, text_in_video, transcript)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![segment_id, project_id, text_in_video.unwrap_or(""), transcript],
+            )?;
+
+            let text = query_embedding(&format!("{} {}", transcript, text_in_video.unwrap_or("")));
+            let vision = random_unit_vector(&mut rng, VISION_MODEL.dimension);
+            for (model, vector) in [(&TEXT_MODEL, &text), (&VISION_MODEL, &vision)] {
+                tx.execute(
+                    "INSERT INTO embeddings (segment_id, embedding_type, model_name, model_version, vector_blob)
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                    params![segment_id, model.embedding_type, model.model_name, model.model_version, blob(vector)],
+                )?;
+            }
+            segment_ids.push(segment_id);
+        }
+    }
+    tx.commit()?;
+
+    Ok(SyntheticProject {
+        project_id,
+        asset_count,
+        segment_ids,
+    })
+}
@@ -0,0 +1,63 @@
+//! Exports rendered in-process. Outside simulation an export waits for a remote worker; in
+//! simulation the job processor takes it and runs its passes here, through the fake ffmpeg, the
+//! way `vibecut worker` does: clip intermediates and spans first, then the first pass, then the
+//! output.
+
+use anyhow::{anyhow, Result};
+use serde_json::{json, Value};
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::jobs::{JobManager, JobStatus};
+use crate::media::process::{Invocation, Tool};
+
+fn arguments(args: &Value) -> Vec<String> {
+    args.as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|a| a.as_str())
+        .map(String::from)
+        .collect()
+}
+
+async fn run_pass(args: &Value) -> Result<()> {
+    let args = arguments(args);
+    if let Some(parent) = args.last().and_then(|out| Path::new(out).parent()) {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    let output = Invocation::new(Tool::Ffmpeg).args(&args).output().await?;
+    if !output.status.success() {
+        return Err(anyhow!("ffmpeg failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(())
+}
+
+pub async fn process_export(job_manager: Arc<JobManager>, job_id: i64, payload: Option<Value>) -> Result<()> {
+    let payload = payload.ok_or_else(|| anyhow!("Export job {} has no payload", job_id))?;
+    let out_path = payload["out_path"]
+        .as_str()
+        .ok_or_else(|| anyhow!("Export job {} has no out_path", job_id))?;
+
+    let mut renders: Vec<&Value> = Vec::new();
+    for key in ["clip_renders", "span_renders"] {
+        renders.extend(payload[key].as_array().into_iter().flatten());
+    }
+    for render in renders {
+        if !render["first_pass_args"].is_null() {
+            run_pass(&render["first_pass_args"]).await?;
+        }
+        run_pass(&render["ffmpeg_args"]).await?;
+    }
+    job_manager.report_progress(job_id, 0.3)?;
+    if !payload["first_pass_args"].is_null() {
+        run_pass(&payload["first_pass_args"]).await?;
+        job_manager.report_progress(job_id, 0.5)?;
+    }
+    run_pass(&payload["ffmpeg_args"]).await?;
+
+    let size_bytes = tokio::fs::metadata(out_path).await.map(|m| m.len()).unwrap_or(0);
+    job_manager.set_job_result(job_id, json!({ "size_bytes": size_bytes }))?;
+    job_manager.update_job_status(job_id, JobStatus::Completed, Some(1.0))?;
+    eprintln!("[SIMULATION] Export job {} rendered {}", job_id, out_path);
+    Ok(())
+}
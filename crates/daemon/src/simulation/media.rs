@@ -0,0 +1,417 @@
+//! Fake ffmpeg, ffprobe and heif-convert. Every file the fake ffmpeg writes is a small text file
+//! describing the media it stands for (length, frame size, streams), and the fake ffprobe reads
+//! that description back, so proxies, extracted audio and exports probe like the real thing.
+//! Files that weren't written by the fakes (footage a test drops in place, or real media) probe
+//! as a clip whose length is derived from the file name, or as a photo for image extensions.
+//!
+//! Analysis filters (scene cuts, frame differences, audio levels, loudness) report nothing, as
+//! for static footage; fingerprinting returns frames that change every five seconds.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::path::Path;
+use std::process::{ExitStatus, Output};
+
+use super::{seed, Rng};
+use crate::media::process::Tool;
+use crate::media::still;
+
+/// First line of every file the fakes write
+const MAGIC: &str = "VIBECUT-SIMULATED-MEDIA";
+/// Seconds between the picture changes fingerprinting reports
+const SHOT_SECONDS: f64 = 5.0;
+
+/// The media a simulated file stands for
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SimulatedMedia {
+    /// Seconds (0 for a photo)
+    pub duration: f64,
+    pub width: i32,
+    pub height: i32,
+    /// As ffprobe reports it ("30/1", "30000/1001")
+    pub frame_rate: String,
+    pub has_video: bool,
+    pub has_audio: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub creation_time: Option<String>,
+}
+
+impl SimulatedMedia {
+    /// 1080p30 footage with sound
+    pub fn clip(duration: f64) -> Self {
+        SimulatedMedia {
+            duration,
+            width: 1920,
+            height: 1080,
+            frame_rate: "30/1".to_string(),
+            has_video: true,
+            has_audio: true,
+            creation_time: None,
+        }
+    }
+
+    /// Sound only (music, narration, extracted audio)
+    pub fn audio(duration: f64) -> Self {
+        SimulatedMedia {
+            duration,
+            width: 0,
+            height: 0,
+            frame_rate: "0/0".to_string(),
+            has_video: false,
+            has_audio: true,
+            creation_time: None,
+        }
+    }
+
+    /// A photo
+    pub fn image(width: i32, height: i32) -> Self {
+        SimulatedMedia {
+            duration: 0.0,
+            width,
+            height,
+            frame_rate: "25/1".to_string(),
+            has_video: true,
+            has_audio: false,
+            creation_time: None,
+        }
+    }
+
+    pub fn with_creation_time(mut self, creation_time: &str) -> Self {
+        self.creation_time = Some(creation_time.to_string());
+        self
+    }
+
+    /// Write the description to `path` (creating its directory), where the fakes and the
+    /// importer will find it
+    pub fn write(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, format!("{}\n{}\n", MAGIC, serde_json::to_string(self)?))?;
+        Ok(())
+    }
+
+    /// The description in a file the fakes wrote
+    pub fn read(path: &Path) -> Option<Self> {
+        let contents = std::fs::read(path).ok()?;
+        let contents = String::from_utf8_lossy(&contents);
+        let rest = contents.strip_prefix(MAGIC)?;
+        serde_json::from_str(rest.trim()).ok()
+    }
+
+    /// What a probe of an existing file finds: its description, or one derived from its name
+    pub fn of(path: &Path) -> Self {
+        if let Some(media) = Self::read(path) {
+            return media;
+        }
+        let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        let mut rng = Rng::new(seed(&["media", &name]));
+        if is_image(path) {
+            SimulatedMedia::image(4032, 3024)
+        } else {
+            SimulatedMedia::clip(20.0 + rng.below(41) as f64)
+        }
+    }
+
+    fn fps(&self) -> f64 {
+        let (num, den) = self.frame_rate.split_once('/').unwrap_or((&self.frame_rate, "1"));
+        match (num.parse::<f64>(), den.parse::<f64>()) {
+            (Ok(num), Ok(den)) if den > 0.0 => num / den,
+            _ => 0.0,
+        }
+    }
+}
+
+fn is_image(path: &Path) -> bool {
+    still::is_image_path(path)
+        || path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| matches!(e.to_lowercase().as_str(), "heic" | "heif"))
+}
+
+#[cfg(unix)]
+fn exit_status(code: i32) -> ExitStatus {
+    std::os::unix::process::ExitStatusExt::from_raw(code << 8)
+}
+
+#[cfg(windows)]
+fn exit_status(code: i32) -> ExitStatus {
+    std::os::windows::process::ExitStatusExt::from_raw(code as u32)
+}
+
+fn succeeded(stdout: Vec<u8>) -> Output {
+    Output {
+        status: exit_status(0),
+        stdout,
+        stderr: Vec::new(),
+    }
+}
+
+fn failed(message: String) -> Output {
+    Output {
+        status: exit_status(1),
+        stdout: Vec::new(),
+        stderr: format!("{}\n", message).into_bytes(),
+    }
+}
+
+/// Answer a media tool invocation the way the real tool would (arguments already checked and
+/// made absolute by the process runner)
+pub fn run(tool: Tool, args: &[String]) -> Result<Output> {
+    match tool {
+        Tool::Ffprobe => Ok(probe(args)),
+        Tool::HeifConvert => heif_convert(args),
+        Tool::Ffmpeg => ffmpeg(args),
+    }
+}
+
+fn missing(path: &str) -> Output {
+    failed(format!("{}: No such file or directory", path))
+}
+
+fn probe(args: &[String]) -> Output {
+    let Some(input) = args.last() else { return failed("No input specified".to_string()) };
+    let path = Path::new(input);
+    if !path.exists() {
+        return missing(input);
+    }
+    let media = SimulatedMedia::of(path);
+
+    // `-of csv=p=0` asks for the video codec
+    if args.iter().any(|a| a.starts_with("csv")) {
+        return succeeded(if media.has_video { b"h264\n".to_vec() } else { Vec::new() });
+    }
+    let mut streams = Vec::new();
+    if media.has_video {
+        streams.push(json!({
+            "codec_type": "video",
+            "width": media.width,
+            "height": media.height,
+            "r_frame_rate": media.frame_rate,
+            "avg_frame_rate": media.frame_rate,
+            "pix_fmt": "yuv420p",
+        }));
+    }
+    if media.has_audio {
+        streams.push(json!({ "codec_type": "audio" }));
+    }
+    let mut format = json!({ "duration": format!("{:.6}", media.duration) });
+    if let Some(creation_time) = &media.creation_time {
+        format["tags"] = json!({ "creation_time": creation_time });
+    }
+    succeeded(json!({ "format": format, "streams": streams }).to_string().into_bytes())
+}
+
+fn heif_convert(args: &[String]) -> Result<Output> {
+    let [.., input, output] = args else { return Ok(failed("Usage: heif-convert [-q quality] in out".to_string())) };
+    let input = Path::new(input);
+    if !input.exists() {
+        return Ok(missing(&input.to_string_lossy()));
+    }
+    let media = SimulatedMedia::of(input);
+    SimulatedMedia::image(media.width, media.height).write(Path::new(output))?;
+    Ok(succeeded(Vec::new()))
+}
+
+/// An input of an ffmpeg command with the options given before it
+struct Input<'a> {
+    path: &'a str,
+    options: &'a [String],
+}
+
+fn option<'a>(options: &'a [String], name: &str) -> Option<&'a str> {
+    options
+        .iter()
+        .position(|a| a == name || a.starts_with(&format!("{}:", name)))
+        .and_then(|i| options.get(i + 1))
+        .map(String::as_str)
+}
+
+fn seconds(options: &[String], name: &str) -> Option<f64> {
+    option(options, name).and_then(|v| v.parse::<f64>().ok())
+}
+
+/// Frames per second asked for with an `fps=` filter ("fps=1", "fps=1000/500")
+fn filter_rate(filter: &str) -> Option<f64> {
+    let value = filter.split("fps=").nth(1)?.split([',', ':']).next()?;
+    match value.split_once('/') {
+        Some((num, den)) => Some(num.parse::<f64>().ok()? / den.parse::<f64>().ok()?),
+        None => value.parse().ok(),
+    }
+}
+
+/// Frame size asked for with a `scale=W:H` filter (None for computed sizes like -2)
+fn filter_size(filter: &str) -> Option<(i32, i32)> {
+    let value = filter.split("scale=").nth(1)?.split(',').next()?;
+    let (width, height) = value.split_once(':')?;
+    match (width.parse::<i32>(), height.parse::<i32>()) {
+        (Ok(w), Ok(h)) if w > 0 && h > 0 => Some((w, h)),
+        _ => None,
+    }
+}
+
+/// Length of the first `trim`/`atrim` in a filter graph that sets a duration
+fn filter_trim(graph: &str) -> Option<f64> {
+    graph
+        .split("trim=")
+        .skip(1)
+        .find_map(|args| args.split([',', ';', '[']).next()?.split(':').find_map(|a| a.strip_prefix("duration=")?.parse().ok()))
+}
+
+/// Entries of an ffconcat list, each trimmed by its inpoint/outpoint and run together
+fn concat_media(list: &Path) -> Option<SimulatedMedia> {
+    let contents = std::fs::read_to_string(list).ok()?;
+    let mut media: Option<SimulatedMedia> = None;
+    let mut entry: Option<(SimulatedMedia, f64)> = None;
+    let add = |media: &mut Option<SimulatedMedia>, (entry, start): (SimulatedMedia, f64), end: Option<f64>| {
+        let length = end.unwrap_or(entry.duration).min(entry.duration) - start;
+        match media {
+            Some(media) => media.duration += length.max(0.0),
+            None => *media = Some(SimulatedMedia { duration: length.max(0.0), ..entry }),
+        }
+    };
+    for line in contents.lines().map(str::trim) {
+        if let Some(file) = line.strip_prefix("file ") {
+            if let Some(previous) = entry.take() {
+                add(&mut media, previous, None);
+            }
+            let file = file.trim_matches('\'').replace("'\\''", "'");
+            entry = Some((SimulatedMedia::of(Path::new(&file)), 0.0));
+        } else if let Some(inpoint) = line.strip_prefix("inpoint ").and_then(|v| v.parse::<f64>().ok()) {
+            if let Some((_, start)) = entry.as_mut() {
+                *start = inpoint;
+            }
+        } else if let Some(outpoint) = line.strip_prefix("outpoint ").and_then(|v| v.parse::<f64>().ok()) {
+            if let Some(previous) = entry.take() {
+                add(&mut media, previous, Some(outpoint));
+            }
+        }
+    }
+    if let Some(previous) = entry.take() {
+        add(&mut media, previous, None);
+    }
+    media
+}
+
+/// What one input contributes: the file (lavfi source, or concat list) trimmed by its -ss/-t
+fn input_media(input: &Input) -> Option<SimulatedMedia> {
+    let mut media = if option(input.options, "-f") == Some("concat") {
+        concat_media(Path::new(input.path))?
+    } else if option(input.options, "-f") == Some("lavfi") {
+        let duration = seconds(input.options, "-t").unwrap_or(SHOT_SECONDS);
+        if input.path.starts_with("anullsrc") || input.path.starts_with("sine") || input.path.starts_with("aevalsrc") {
+            SimulatedMedia::audio(duration)
+        } else {
+            SimulatedMedia { has_audio: false, ..SimulatedMedia::clip(duration) }
+        }
+    } else {
+        let path = Path::new(input.path);
+        if !path.exists() {
+            return None;
+        }
+        SimulatedMedia::of(path)
+    };
+    let start = seconds(input.options, "-ss").unwrap_or(0.0).max(0.0);
+    match seconds(input.options, "-t") {
+        // A looped photo becomes footage of that length
+        Some(length) if media.duration == 0.0 => media.duration = length,
+        Some(length) => media.duration = length.min(media.duration - start).max(0.0),
+        None => media.duration = (media.duration - start).max(0.0),
+    }
+    Some(media)
+}
+
+fn ffmpeg(args: &[String]) -> Result<Output> {
+    let Some(output) = args.last() else { return Ok(failed("No output specified".to_string())) };
+    let mut inputs = Vec::new();
+    let mut options_start = 0;
+    let mut i = 0;
+    while i < args.len() {
+        if args[i] == "-i" && i + 1 < args.len() {
+            inputs.push(Input { path: &args[i + 1], options: &args[options_start..i] });
+            options_start = i + 2;
+            i += 2;
+        } else {
+            i += 1;
+        }
+    }
+    let output_options = &args[options_start..args.len() - 1];
+
+    let mut sources = Vec::with_capacity(inputs.len());
+    for input in &inputs {
+        match input_media(input) {
+            Some(media) => sources.push(media),
+            None => return Ok(missing(input.path)),
+        }
+    }
+    let Some(first) = sources.first() else { return Ok(failed("No input specified".to_string())) };
+
+    let mut media = first.clone();
+    media.has_audio = sources.iter().any(|s| s.has_audio);
+    media.has_video = sources.iter().any(|s| s.has_video);
+    let graph = option(output_options, "-filter_complex").unwrap_or_default();
+    if graph.contains("concat=") {
+        media.duration = sources.iter().map(|s| s.duration).sum();
+    } else if let Some(length) = filter_trim(graph) {
+        media.duration = media.duration.min(length);
+    }
+    if let Some(length) = seconds(output_options, "-t") {
+        media.duration = media.duration.min(length);
+    }
+    if output_options.iter().any(|a| a == "-vn") {
+        media = SimulatedMedia { creation_time: media.creation_time, ..SimulatedMedia::audio(media.duration) };
+    }
+    if output_options.iter().any(|a| a == "-an") {
+        media.has_audio = false;
+    }
+    let filter = option(output_options, "-vf").unwrap_or_default();
+    if let Some((width, height)) = filter_size(filter) {
+        media.width = width;
+        media.height = height;
+    }
+
+    // Raw frames on stdout (fingerprinting): 9x8 gray frames that change with each shot
+    if output == "-" && option(output_options, "-f") == Some("rawvideo") {
+        let rate = filter_rate(filter).unwrap_or(media.fps());
+        let frames = (media.duration * rate).floor() as usize;
+        let name = inputs.first().map(|input| input.path).unwrap_or_default();
+        let mut stdout = Vec::with_capacity(frames * 72);
+        for frame in 0..frames {
+            let shot = (frame as f64 / rate / SHOT_SECONDS).floor() as u64;
+            let mut rng = Rng::new(seed(&["frame", name, &shot.to_string()]));
+            stdout.extend((0..72).map(|_| rng.below(256) as u8));
+        }
+        return Ok(succeeded(stdout));
+    }
+    // Analysis to the null muxer, or the first pass of a two-pass encode
+    if output == "-" || output == "/dev/null" || output == "NUL" {
+        return Ok(succeeded(Vec::new()));
+    }
+
+    let path = Path::new(output);
+    if output.contains('%') {
+        // Numbered thumbnails: one per frame of the fps filter
+        let rate = filter_rate(filter).unwrap_or(1.0);
+        let frames = ((media.duration * rate).ceil() as i64).max(1);
+        let start_number = option(output_options, "-start_number").and_then(|v| v.parse::<i64>().ok()).unwrap_or(1);
+        let image = SimulatedMedia::image(media.width, media.height);
+        for n in start_number..start_number + frames {
+            image.write(Path::new(&numbered(output, n)))?;
+        }
+    } else if is_image(path) || option(output_options, "-frames:v") == Some("1") {
+        SimulatedMedia::image(media.width, media.height).write(path)?;
+    } else {
+        media.write(path)?;
+    }
+    Ok(succeeded(Vec::new()))
+}
+
+/// Fill in an image2 pattern's `%0Nd` (or `%d`) with `n`
+fn numbered(pattern: &str, n: i64) -> String {
+    let Some(start) = pattern.rfind('%') else { return pattern.to_string() };
+    let Some(len) = pattern[start..].find('d') else { return pattern.to_string() };
+    let width = pattern[start + 1..start + len].trim_start_matches('0').parse::<usize>().unwrap_or(0);
+    format!("{}{:0width$}{}", &pattern[..start], n, &pattern[start + len + 1..], width = width)
+}
@@ -0,0 +1,462 @@
+//! Fake ML service: the Python service's endpoints with the same request and response shapes,
+//! answered from file descriptions (see [`super::media`]) and seeded choices instead of models.
+//! Transcripts are stock sentences about a day out, embeddings are hashed bags of words (so
+//! texts sharing words come out similar), and the LLM endpoints echo what they're asked without
+//! proposing changes.
+
+use axum::{
+    http::StatusCode,
+    response::Json,
+    routing::{get, post},
+    Router,
+};
+use serde_json::{json, Value};
+use std::path::Path;
+
+use super::media::SimulatedMedia;
+use super::{seed, Rng};
+
+/// all-MiniLM-L6-v2's size, as the real /embeddings/text returns
+const TEXT_EMBEDDING_DIM: usize = 384;
+/// CLIP ViT-B/32's size, as the real /embeddings/vision returns
+const VISION_EMBEDDING_DIM: usize = 512;
+/// Seconds of speech per transcript segment
+const TRANSCRIPT_SEGMENT_SECONDS: f64 = 5.0;
+/// Model reported in LLM usage
+const MODEL: &str = "simulated";
+
+pub(crate) const SENTENCES: &[&str] = &[
+    "We got to the beach just as the sun came up",
+    "This is the best coffee I have had all week",
+    "Look at how clear the water is today",
+    "Okay everyone wave at the camera",
+    "The trail goes all the way up to the lighthouse",
+    "I can't believe how windy it is up here",
+    "Let's grab lunch at the place by the harbor",
+    "That was the funniest thing that happened all day",
+    "Here is the view from the top of the hill",
+    "We are packing up and heading home now",
+];
+const SCENE_TAGS: &[&str] = &["beach", "water", "sky", "person", "food", "building", "tree", "mountain"];
+const ACTION_TAGS: &[&str] = &["talking", "walking", "eating", "laughing", "waving", "sitting"];
+const SHOT_TYPES: &[&str] = &["wide", "medium", "close_up"];
+
+type Response = Result<Json<Value>, (StatusCode, Json<Value>)>;
+
+/// A FastAPI-style error
+fn error(status: StatusCode, detail: impl Into<String>) -> (StatusCode, Json<Value>) {
+    (status, Json(json!({ "detail": detail.into() })))
+}
+
+fn text<'a>(req: &'a Value, key: &str) -> &'a str {
+    req.get(key).and_then(|v| v.as_str()).unwrap_or_default()
+}
+
+fn number(req: &Value, key: &str, default: f64) -> f64 {
+    req.get(key).and_then(|v| v.as_f64()).unwrap_or(default)
+}
+
+/// The described media at a path the request names, or 404 as the real service answers
+fn media_at(path: &str) -> Result<SimulatedMedia, (StatusCode, Json<Value>)> {
+    let path = Path::new(path);
+    if !path.exists() {
+        return Err(error(StatusCode::NOT_FOUND, format!("File not found: {}", path.display())));
+    }
+    Ok(SimulatedMedia::of(path))
+}
+
+fn file_name(path: &str) -> String {
+    Path::new(path).file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default()
+}
+
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Token counts the daemon's cost tracking records: a word is a token
+fn usage(prompt: &Value, completion: &str) -> Value {
+    json!({
+        "model": MODEL,
+        "prompt_tokens": words(&prompt.to_string()).len(),
+        "completion_tokens": words(completion).len(),
+    })
+}
+
+fn normalized(mut vector: Vec<f64>) -> Vec<f64> {
+    let norm = vector.iter().map(|v| v * v).sum::<f64>().sqrt();
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|v| *v /= norm);
+    } else if let Some(first) = vector.first_mut() {
+        *first = 1.0;
+    }
+    vector
+}
+
+/// Hashed bag of words: each word adds ±1 to a few dimensions picked by its hash
+fn hashed_embedding(text: &str, dimension: usize, space: &str) -> Vec<f64> {
+    let mut vector = vec![0.0; dimension];
+    for word in words(text) {
+        let mut rng = Rng::new(seed(&[space, &word]));
+        for _ in 0..4 {
+            let sign = if rng.next_u64() & 1 == 0 { 1.0 } else { -1.0 };
+            vector[rng.below(dimension)] += sign;
+        }
+    }
+    normalized(vector)
+}
+
+pub(crate) fn text_embedding(text: &str) -> Vec<f64> {
+    hashed_embedding(text, TEXT_EMBEDDING_DIM, "word")
+}
+
+/// A query in the vision space (CLIP's text encoder in the real service)
+pub(crate) fn vision_text_embedding(text: &str) -> Vec<f64> {
+    hashed_embedding(text, VISION_EMBEDDING_DIM, "vision-word")
+}
+
+pub fn router() -> Router {
+    Router::new()
+        .route("/health", get(health))
+        .route("/transcribe", post(transcribe))
+        .route("/audio/events", post(audio_events))
+        .route("/vision/analyze", post(vision_analyze))
+        .route("/redaction/detect", post(redaction_detect))
+        .route("/multicam/sync", post(multicam_sync))
+        .route("/stills/subjects", post(still_subjects))
+        .route("/screen/window_titles", post(window_titles))
+        .route("/embeddings/text", post(embeddings_text))
+        .route("/rerank", post(rerank))
+        .route("/embeddings/vision", post(embeddings_vision))
+        .route("/embeddings/vision_text", post(embeddings_vision_text))
+        .route("/orchestrator/reason", post(reason))
+        .route("/orchestrator/generate_plan", post(generate_plan))
+        .route("/orchestrator/generate_response", post(generate_response))
+        .route("/orchestrator/parse_intent", post(parse_intent))
+        .route("/orchestrator/edit_tools", post(edit_tools))
+        .route("/translate", post(translate))
+        .route("/expand_query", post(expand_query))
+        .route("/narration/script", post(narration_script))
+        .route("/tts", post(tts))
+}
+
+async fn health() -> Json<Value> {
+    Json(json!({ "ok": true, "version": MODEL }))
+}
+
+/// One stock sentence every five seconds of sound, its words spread evenly, two speakers taking
+/// turns every few sentences
+async fn transcribe(Json(req): Json<Value>) -> Response {
+    let path = text(&req, "mediaPath");
+    let media = media_at(path)?;
+    let base = seed(&["transcript", &file_name(path)]);
+    let mut segments = Vec::new();
+    let mut index = 0;
+    while media.has_audio && (index as f64) * TRANSCRIPT_SEGMENT_SECONDS + 1.0 <= media.duration {
+        let start = index as f64 * TRANSCRIPT_SEGMENT_SECONDS;
+        let end = (start + TRANSCRIPT_SEGMENT_SECONDS - 0.5).min(media.duration);
+        let sentence = SENTENCES[(base as usize + index) % SENTENCES.len()];
+        let sentence_words: Vec<&str> = sentence.split_whitespace().collect();
+        let step = (end - start) / sentence_words.len() as f64;
+        let words: Vec<Value> = sentence_words
+            .iter()
+            .enumerate()
+            .map(|(i, word)| json!({ "start": start + i as f64 * step, "end": start + (i + 1) as f64 * step, "word": word }))
+            .collect();
+        segments.push(json!({
+            "start": start,
+            "end": end,
+            "text": sentence,
+            "words": words,
+            "language": "en",
+            "speaker": format!("SPEAKER_{:02}", ((base >> 8) as usize + index / 3) % 2),
+        }));
+        index += 1;
+    }
+    Ok(Json(json!({ "segments": segments, "language": "en", "language_probability": 0.99 })))
+}
+
+/// No laughter or applause; a steady level per hop
+async fn audio_events(Json(req): Json<Value>) -> Response {
+    let path = text(&req, "mediaPath");
+    let media = media_at(path)?;
+    let window = number(&req, "windowSec", 2.0);
+    let hop = number(&req, "hopSec", 1.0).max(0.1);
+    let mut rng = Rng::new(seed(&["loudness", &file_name(path)]));
+    let mut loudness = Vec::new();
+    let mut start = 0.0;
+    while start < media.duration {
+        loudness.push(json!({ "start": start, "end": (start + window).min(media.duration), "rms_db": -30.0 + rng.unit() * 15.0 }));
+        start += hop;
+    }
+    Ok(Json(json!({ "events": [], "loudness": loudness })))
+}
+
+/// What a shot of a file shows
+struct Shot {
+    has_face: bool,
+    shot_type: &'static str,
+    scene: &'static str,
+    action: &'static str,
+}
+
+/// A shot's content, and the generator its other per-shot choices continue from
+fn shot_at(file_name: &str, index: u64) -> (Rng, Shot) {
+    let mut rng = Rng::new(seed(&["shot", file_name, &index.to_string()]));
+    let shot = Shot {
+        has_face: rng.unit() < 0.6,
+        shot_type: SHOT_TYPES[rng.below(SHOT_TYPES.len())],
+        scene: SCENE_TAGS[rng.below(SCENE_TAGS.len())],
+        action: ACTION_TAGS[rng.below(ACTION_TAGS.len())],
+    };
+    (rng, shot)
+}
+
+/// One entry per sample interval; a shot (faces, framing, tags) lasts five seconds
+async fn vision_analyze(Json(req): Json<Value>) -> Response {
+    let path = text(&req, "mediaPath");
+    let media = media_at(path)?;
+    let interval = number(&req, "sampleIntervalSec", 0.5);
+    if interval <= 0.0 {
+        return Err(error(StatusCode::BAD_REQUEST, "sampleIntervalSec must be positive"));
+    }
+    let analyzers = req.get("analyzers").cloned().unwrap_or_else(|| json!({}));
+    let on = |name: &str| analyzers.get(name).and_then(|v| v.as_bool()).unwrap_or(true);
+    let name = file_name(path);
+    // A photo is one frame
+    let duration = if media.duration > 0.0 { media.duration } else { interval };
+
+    let mut segments = Vec::new();
+    let mut start = 0.0;
+    while start < duration {
+        let index = (start / TRANSCRIPT_SEGMENT_SECONDS).floor() as u64;
+        let mut frame_rng = Rng::new(seed(&["frame", &name, &format!("{:.3}", start)]));
+        let (mut shot_rng, Shot { has_face, shot_type, scene, action }) = shot_at(&name, index);
+
+        let mut tags = Vec::new();
+        let mut structured_tags = Vec::new();
+        if on("tags") {
+            tags.push("day");
+            structured_tags.push(json!({ "label": "day", "kind": "scene", "confidence": 0.9 }));
+        }
+        if on("objects") {
+            tags.extend([scene, action]);
+            structured_tags.push(json!({ "label": scene, "kind": "object", "confidence": 0.5 + shot_rng.unit() * 0.4 }));
+            structured_tags.push(json!({ "label": action, "kind": "action", "confidence": 0.5 + shot_rng.unit() * 0.4 }));
+        }
+        let face_bbox = (on("faces") && has_face).then(|| {
+            json!({ "x": media.width as f64 * 0.4, "y": media.height as f64 * 0.25, "width": media.width as f64 * 0.2, "height": media.height as f64 * 0.3 })
+        });
+        segments.push(json!({
+            "start": start,
+            "end": (start + interval).min(duration),
+            "has_face": on("faces").then_some(has_face),
+            "face_bbox": face_bbox,
+            "blur_score": on("blur").then(|| 150.0 + frame_rng.unit() * 350.0),
+            "motion_score": on("motion").then(|| frame_rng.unit() * 20.0),
+            "shot_type": (on("shot_type") && on("faces")).then_some(shot_type),
+            "ocr_text": Value::Null,
+            "tags": tags,
+            "structured_tags": structured_tags,
+        }));
+        start += interval;
+    }
+    Ok(Json(json!({ "segments": segments })))
+}
+
+/// Nothing to blur
+async fn redaction_detect(Json(req): Json<Value>) -> Response {
+    media_at(text(&req, "mediaPath"))?;
+    Ok(Json(json!({ "subjects": [] })))
+}
+
+/// Every angle started together
+async fn multicam_sync(Json(req): Json<Value>) -> Response {
+    let paths = req.get("audioPaths").and_then(|v| v.as_array()).cloned().unwrap_or_default();
+    let reference = number(&req, "referenceIndex", 0.0) as usize;
+    let mut offsets = Vec::with_capacity(paths.len());
+    for (i, path) in paths.iter().enumerate() {
+        media_at(path.as_str().unwrap_or_default())?;
+        offsets.push(json!({ "offsetSec": 0.0, "confidence": if i == reference { 1.0 } else { 0.9 } }));
+    }
+    Ok(Json(json!({ "offsets": offsets })))
+}
+
+/// The detail in the middle of the photo
+async fn still_subjects(Json(req): Json<Value>) -> Response {
+    let media = media_at(text(&req, "imagePath"))?;
+    Ok(Json(json!({
+        "width": media.width,
+        "height": media.height,
+        "subjects": [{ "kind": "detail", "x": 0.35, "y": 0.3, "w": 0.3, "h": 0.4, "score": 1.0 }],
+    })))
+}
+
+/// No title bars to read
+async fn window_titles(Json(req): Json<Value>) -> Response {
+    media_at(text(&req, "mediaPath"))?;
+    let times = req.get("times").and_then(|v| v.as_array()).cloned().unwrap_or_default();
+    let titles: Vec<Value> = times.iter().map(|t| json!({ "t": t, "title": Value::Null })).collect();
+    Ok(Json(json!({ "titles": titles })))
+}
+
+async fn embeddings_text(Json(req): Json<Value>) -> Response {
+    Ok(Json(json!({ "embedding": text_embedding(text(&req, "text")) })))
+}
+
+/// Share of the query's words in each document
+async fn rerank(Json(req): Json<Value>) -> Response {
+    let query = words(text(&req, "query"));
+    let documents = req.get("documents").and_then(|v| v.as_array()).cloned().unwrap_or_default();
+    let scores: Vec<Value> = documents
+        .iter()
+        .map(|doc| {
+            let doc_words = words(text(doc, "text"));
+            let matched = query.iter().filter(|w| doc_words.contains(w)).count();
+            let score = if query.is_empty() { 0.0 } else { matched as f64 / query.len() as f64 };
+            json!({ "id": doc.get("id").cloned().unwrap_or(Value::Null), "score": score })
+        })
+        .collect();
+    Ok(Json(json!({ "model": MODEL, "scores": scores })))
+}
+
+/// The same vector for every moment of a shot: its shot type, scene and action in the vision
+/// space, so vision-text queries naming them rank the shot higher
+async fn embeddings_vision(Json(req): Json<Value>) -> Response {
+    let path = text(&req, "media_path");
+    media_at(path)?;
+    let index = (number(&req, "start_time", 0.0) / TRANSCRIPT_SEGMENT_SECONDS).floor() as u64;
+    let (_, shot) = shot_at(&file_name(path), index);
+    let description = format!("{} {} {}", shot.shot_type, shot.scene, shot.action);
+    Ok(Json(json!({ "embedding": vision_text_embedding(&description) })))
+}
+
+async fn embeddings_vision_text(Json(req): Json<Value>) -> Response {
+    Ok(Json(json!({ "embedding": vision_text_embedding(text(&req, "text")) })))
+}
+
+async fn reason(Json(req): Json<Value>) -> Response {
+    let has_segments = req.get("segments").and_then(|v| v.as_array()).is_some_and(|s| !s.is_empty());
+    Ok(Json(json!({
+        "explanation": "",
+        "questions": [],
+        "narrative_structure": has_segments.then_some("linear"),
+    })))
+}
+
+/// Each beat's segments inserted in order, as the real service's v1 planner does
+async fn generate_plan(Json(req): Json<Value>) -> Response {
+    let beats = req.get("beats").and_then(|v| v.as_array()).cloned().unwrap_or_default();
+    let mut primary_segments = Vec::new();
+    for beat in &beats {
+        let target_sec = beat.get("target_sec").cloned().unwrap_or(Value::Null);
+        for segment_id in beat.get("segment_ids").and_then(|v| v.as_array()).into_iter().flatten() {
+            primary_segments.push(json!({
+                "operation": "insert",
+                "segment_id": segment_id,
+                "timeline_start_ticks": Value::Null,
+                "trim_in_offset_ticks": 0,
+                "trim_out_offset_ticks": 0,
+                "target_duration_sec": target_sec,
+            }));
+        }
+    }
+    let captions_on = req.pointer("/constraints/captions_on").and_then(|v| v.as_bool()).unwrap_or(false);
+    let overlays: Vec<Value> = if captions_on {
+        primary_segments
+            .iter()
+            .map(|s| json!({ "type": "caption", "segment_id": s["segment_id"], "text": "", "start_ticks": Value::Null, "duration_ticks": Value::Null }))
+            .collect()
+    } else {
+        Vec::new()
+    };
+    Ok(Json(json!({
+        "primary_segments": primary_segments,
+        "overlays": overlays,
+        "trims": [],
+        "titles": [],
+        "audio_events": [],
+    })))
+}
+
+async fn generate_response(Json(req): Json<Value>) -> Response {
+    let message = format!("Simulated reply to {}.", text(&req, "event_type"));
+    Ok(Json(json!({
+        "message": message,
+        "suggestions": [],
+        "questions": [],
+        "usage": usage(&req, &message),
+    })))
+}
+
+/// The message is the intent; "30 seconds" or "2 minutes" in it is the target length
+async fn parse_intent(Json(req): Json<Value>) -> Response {
+    let message = text(&req, "user_message");
+    let tokens = words(message);
+    let target_length_sec = tokens.windows(2).find_map(|pair| {
+        let amount = pair[0].parse::<i64>().ok()?;
+        match pair[1].as_str() {
+            "s" | "sec" | "secs" | "second" | "seconds" => Some(amount),
+            "min" | "mins" | "minute" | "minutes" => Some(amount * 60),
+            _ => None,
+        }
+    });
+    Ok(Json(json!({
+        "user_intent": message,
+        "language": "en",
+        "target_length_sec": target_length_sec,
+        "vibe": Value::Null,
+        "constraints": Value::Null,
+        "clarifying_questions": [],
+        "usage": usage(&req, message),
+    })))
+}
+
+/// Never calls a tool, so the timeline is left as it is
+async fn edit_tools(Json(req): Json<Value>) -> Response {
+    let message = "No changes made in simulation.";
+    Ok(Json(json!({ "message": message, "tool_calls": [], "usage": usage(&req, message) })))
+}
+
+async fn translate(Json(req): Json<Value>) -> Response {
+    let source = text(&req, "text");
+    let mut translations = serde_json::Map::new();
+    for language in req.get("target_languages").and_then(|v| v.as_array()).into_iter().flatten() {
+        let Some(language) = language.as_str().filter(|l| *l != "en") else { continue };
+        translations.insert(language.to_string(), json!(format!("[{}] {}", language, source)));
+    }
+    Ok(Json(json!({ "source_language": "en", "translations": translations, "usage": usage(&req, source) })))
+}
+
+async fn expand_query(Json(req): Json<Value>) -> Response {
+    Ok(Json(json!({ "queries": [], "usage": usage(&req, "") })))
+}
+
+/// The clips' descriptions read out in order (or the brief when there are none)
+async fn narration_script(Json(req): Json<Value>) -> Response {
+    let descriptions: Vec<&str> = req
+        .get("clip_descriptions")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|d| d.as_str())
+        .collect();
+    let script = if descriptions.is_empty() {
+        text(&req, "brief").to_string()
+    } else {
+        descriptions.join(". ")
+    };
+    Ok(Json(json!({ "script": script, "usage": usage(&req, &script) })))
+}
+
+/// Narration at two and a half words a second
+async fn tts(Json(req): Json<Value>) -> Response {
+    let output_path = text(&req, "output_path");
+    let duration = (words(text(&req, "text")).len() as f64 / 2.5).max(1.0);
+    SimulatedMedia::audio(duration)
+        .write(Path::new(output_path))
+        .map_err(|e| error(StatusCode::INTERNAL_SERVER_ERROR, format!("TTS failed: {}", e)))?;
+    Ok(Json(json!({ "output_path": output_path })))
+}
@@ -0,0 +1,124 @@
+//! Simulation mode: deterministic fakes for everything outside the daemon, so the whole pipeline
+//! (import, analysis, propose, apply, export) runs without ffmpeg, the Python ML service or
+//! TwelveLabs. Turned on with SIMULATION=on, or [`enable`] before [`crate::Vibecut::start`].
+//!
+//! - ffmpeg, ffprobe and heif-convert are answered by [`media`] in place of the process runner,
+//!   after the sandbox has checked their arguments. Files they write describe the media they
+//!   stand for, which later probes read back.
+//! - The ML service and the TwelveLabs API are served in-process on a free local port by
+//!   [`ml`] and [`twelvelabs`]; `llm::ml_service_url` and the TwelveLabs client point at them.
+//! - Exports, which otherwise wait for a remote worker, are rendered by the job processor with
+//!   [`export`].
+//! - [`dataset`] generates projects of tens of thousands of analyzed segments directly in the
+//!   database, for benchmarking retrieval at scale.
+//!
+//! Transcripts, vision analysis, embeddings and search results are derived from file names and
+//! request text, so the same inputs always give the same project.
+
+pub mod dataset;
+pub mod export;
+pub mod media;
+pub mod ml;
+pub mod twelvelabs;
+
+use anyhow::Result;
+use axum::Router;
+use sha2::{Digest, Sha256};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+
+static FORCED: AtomicBool = AtomicBool::new(false);
+static FROM_ENV: OnceLock<bool> = OnceLock::new();
+/// Where the fake services listen once started
+static SERVICE_URL: OnceLock<String> = OnceLock::new();
+
+pub fn enabled() -> bool {
+    FORCED.load(Ordering::Relaxed) || *FROM_ENV.get_or_init(|| std::env::var("SIMULATION").is_ok_and(|v| v == "on"))
+}
+
+/// Turn simulation on for this process (as SIMULATION=on does)
+pub fn enable() {
+    FORCED.store(true, Ordering::Relaxed);
+}
+
+/// Base URL of the fake ML service, once it's serving
+pub fn ml_service_url() -> Option<&'static str> {
+    SERVICE_URL.get().map(String::as_str)
+}
+
+/// Base URL of the fake TwelveLabs API, once it's serving
+pub fn twelvelabs_api_base() -> Option<String> {
+    SERVICE_URL.get().map(|url| format!("{}/twelvelabs", url))
+}
+
+/// Serve the fake ML service and TwelveLabs API on a free port of 127.0.0.1. They get a thread
+/// and runtime of their own, as a separate service would: the daemon's handlers can block
+/// their runtime's workers while they wait on the ML service. Served once per process.
+pub fn serve() -> Result<()> {
+    if SERVICE_URL.get().is_some() {
+        return Ok(());
+    }
+    let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+    listener.set_nonblocking(true)?;
+    let url = format!("http://{}", listener.local_addr()?);
+    if SERVICE_URL.set(url.clone()).is_err() {
+        return Ok(());
+    }
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .thread_name("simulation")
+        .enable_all()
+        .build()?;
+    std::thread::Builder::new().name("simulation".to_string()).spawn(move || {
+        runtime.block_on(async move {
+            let app = Router::new()
+                .merge(ml::router())
+                .nest("/twelvelabs", twelvelabs::router());
+            let result = match tokio::net::TcpListener::from_std(listener) {
+                Ok(listener) => axum::serve(listener, app).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                eprintln!("[SIMULATION] Fake services stopped: {:?}", e);
+            }
+        })
+    })?;
+    eprintln!("[SIMULATION] Fake ML service and TwelveLabs API on {}", url);
+    Ok(())
+}
+
+/// Stable 64-bit seed for the fakes' choices (the same across runs and platforms)
+pub(crate) fn seed(parts: &[&str]) -> u64 {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update(part.as_bytes());
+        hasher.update([0u8]);
+    }
+    let digest = hasher.finalize();
+    u64::from_le_bytes(digest[..8].try_into().expect("digest is 32 bytes"))
+}
+
+/// Small deterministic generator for the fakes' numbers (xorshift64*)
+pub(crate) struct Rng(u64);
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Self {
+        Rng(seed.max(1))
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Uniform in [0, 1)
+    pub(crate) fn unit(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    pub(crate) fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n.max(1) as u64) as usize
+    }
+}
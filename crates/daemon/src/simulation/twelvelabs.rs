@@ -0,0 +1,101 @@
+//! Fake TwelveLabs API: indexes, upload tasks that are ready as soon as they're created, and
+//! search over the videos of an index with seeded scores.
+
+use axum::{
+    extract::Path,
+    http::StatusCode,
+    response::Json,
+    routing::{get, post},
+    Router,
+};
+use serde_json::{json, Value};
+use std::sync::Mutex;
+
+use super::seed;
+
+/// Moment each search match covers, in seconds from the start of the video
+const MATCH_SECONDS: f64 = 5.0;
+
+#[derive(Default)]
+struct State {
+    /// Videos of each index, by index number
+    indexes: Vec<Vec<String>>,
+    /// (index, video) of each task
+    tasks: Vec<(usize, String)>,
+}
+
+static STATE: Mutex<State> = Mutex::new(State {
+    indexes: Vec::new(),
+    tasks: Vec::new(),
+});
+
+type Response = Result<Json<Value>, (StatusCode, Json<Value>)>;
+
+fn not_found(what: &str, id: &str) -> (StatusCode, Json<Value>) {
+    (StatusCode::NOT_FOUND, Json(json!({ "code": "resource_not_found", "message": format!("{} {} not found", what, id) })))
+}
+
+/// "sim-index-3" -> 3
+fn number(id: &str, prefix: &str) -> Option<usize> {
+    id.strip_prefix(prefix)?.parse().ok()
+}
+
+pub fn router() -> Router {
+    Router::new()
+        .route("/indexes", post(create_index))
+        .route("/tasks", post(create_task))
+        .route("/tasks/:task_id", get(get_task))
+        .route("/search", post(search))
+}
+
+async fn create_index() -> Response {
+    let mut state = STATE.lock().unwrap();
+    state.indexes.push(Vec::new());
+    Ok(Json(json!({ "_id": format!("sim-index-{}", state.indexes.len() - 1) })))
+}
+
+async fn create_task(Json(req): Json<Value>) -> Response {
+    let index_id = req.get("index_id").and_then(|v| v.as_str()).unwrap_or_default();
+    let mut state = STATE.lock().unwrap();
+    let index = number(index_id, "sim-index-")
+        .filter(|i| *i < state.indexes.len())
+        .ok_or_else(|| not_found("Index", index_id))?;
+    let video_id = format!("sim-video-{}", state.tasks.len());
+    state.indexes[index].push(video_id.clone());
+    state.tasks.push((index, video_id));
+    Ok(Json(json!({ "_id": format!("sim-task-{}", state.tasks.len() - 1) })))
+}
+
+async fn get_task(Path(task_id): Path<String>) -> Response {
+    let state = STATE.lock().unwrap();
+    let (_, video_id) = number(&task_id, "sim-task-")
+        .and_then(|i| state.tasks.get(i))
+        .ok_or_else(|| not_found("Task", &task_id))?;
+    Ok(Json(json!({ "_id": task_id, "status": "ready", "video_id": video_id })))
+}
+
+/// The first moment of every video in the index, best score first
+async fn search(Json(req): Json<Value>) -> Response {
+    let index_id = req.get("index_id").and_then(|v| v.as_str()).unwrap_or_default();
+    let query = req.get("query").and_then(|v| v.as_str()).unwrap_or_default();
+    let limit = req.get("limit").and_then(|v| v.as_u64()).unwrap_or(10) as usize;
+    let state = STATE.lock().unwrap();
+    let videos = number(index_id, "sim-index-")
+        .and_then(|i| state.indexes.get(i))
+        .ok_or_else(|| not_found("Index", index_id))?;
+
+    let mut data: Vec<(f64, Value)> = videos
+        .iter()
+        .map(|video_id| {
+            let score = 0.5 + (seed(&["search", query, video_id]) % 50) as f64 / 100.0;
+            let item = json!({
+                "video_id": video_id,
+                "matches": [{ "start": 0.0, "end": MATCH_SECONDS, "score": score }],
+            });
+            (score, item)
+        })
+        .collect();
+    data.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+    let data: Vec<Value> = data.into_iter().take(limit).map(|(_, item)| item).collect();
+    Ok(Json(json!({ "data": data })))
+}
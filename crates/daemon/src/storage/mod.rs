@@ -0,0 +1,90 @@
+pub mod s3;
+
+use anyhow::Result;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use crate::db::Database;
+pub use s3::S3Storage;
+
+static BACKEND: OnceLock<Option<S3Storage>> = OnceLock::new();
+
+/// Where generated proxies, thumbnails and exports are mirrored. STORAGE_BACKEND=s3 copies them
+/// to an S3-compatible bucket and the media API redirects clients to signed URLs, so the daemon
+/// can run on a server without streaming every byte itself. The default ("local") keeps
+/// everything on local disk.
+pub fn backend() -> Option<&'static S3Storage> {
+    BACKEND
+        .get_or_init(|| match std::env::var("STORAGE_BACKEND").as_deref() {
+            Ok("s3") => match S3Storage::from_env() {
+                Ok(storage) => Some(storage),
+                Err(e) => {
+                    eprintln!("[STORAGE] S3 storage is disabled: {:?}", e);
+                    None
+                }
+            },
+            _ => None,
+        })
+        .as_ref()
+}
+
+/// Copy a generated file to the storage backend as `name` (under S3_PREFIX) and remember where
+/// it went. No-op when storage is local.
+pub async fn store(db: &Database, local_path: &Path, name: &str, content_type: &str) -> Result<()> {
+    let Some(storage) = backend() else {
+        return Ok(());
+    };
+    // Files of encrypted projects stay on local disk, where the daemon can decrypt them
+    if crate::crypto::is_encrypted_file(local_path) {
+        return Ok(());
+    }
+    let local = local_path.to_string_lossy();
+    // The local file was just (re)generated, so an older stored copy is stale even if this
+    // upload fails
+    db.delete_stored_object(&local)?;
+    let key = storage.object_key(name);
+    storage.put_file(&key, local_path, content_type).await?;
+    let size_bytes = std::fs::metadata(local_path)?.len() as i64;
+    db.record_stored_object(&local, &key, size_bytes)?;
+    Ok(())
+}
+
+/// Store every thumbnail in an asset's thumbnail directory
+pub async fn store_thumbnails(db: &Database, asset_id: i64, thumbnail_dir: &Path) -> Result<()> {
+    if backend().is_none() {
+        return Ok(());
+    }
+    let mut entries = tokio::fs::read_dir(thumbnail_dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("jpg") {
+            continue;
+        }
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        store(db, &path, &format!("thumbs/asset_{}/{}", asset_id, file_name), "image/jpeg").await?;
+    }
+    Ok(())
+}
+
+/// Delete the stored copy of `local_path`, if any, and forget it
+pub async fn remove(db: &Database, local_path: &Path) -> Result<()> {
+    let local = local_path.to_string_lossy();
+    let Some(key) = db.get_stored_object_key(&local)? else {
+        return Ok(());
+    };
+    if let Some(storage) = backend() {
+        storage.delete_object(&key).await?;
+    }
+    db.delete_stored_object(&local)?;
+    Ok(())
+}
+
+/// Signed URL for a stored copy of `local_path`, or None when it should be served from disk
+pub fn signed_url(db: &Database, local_path: &Path) -> Result<Option<String>> {
+    let Some(storage) = backend() else {
+        return Ok(None);
+    };
+    Ok(db
+        .get_stored_object_key(&local_path.to_string_lossy())?
+        .map(|key| storage.presigned_get_url(&key)))
+}
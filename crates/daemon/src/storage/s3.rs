@@ -0,0 +1,339 @@
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use reqwest::Method;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use tokio::io::AsyncReadExt;
+
+/// Files up to this size go up in one PUT; larger ones as a multipart upload with parts this
+/// big (S3 requires at least 5 MiB for every part but the last)
+const PART_SIZE: u64 = 16 * 1024 * 1024;
+
+/// An S3-compatible bucket (AWS S3, MinIO, R2, ...), addressed path-style and signed with
+/// Signature Version 4
+#[derive(Debug, Clone)]
+pub struct S3Storage {
+    endpoint: reqwest::Url,
+    region: String,
+    bucket: String,
+    prefix: String,
+    access_key: String,
+    secret_key: String,
+    session_token: Option<String>,
+    url_expiry_secs: u64,
+    client: reqwest::Client,
+}
+
+impl S3Storage {
+    /// S3_BUCKET (required), S3_REGION (default AWS_REGION, then us-east-1), S3_ENDPOINT
+    /// (default AWS for the region), S3_PREFIX (default "vibecut"), S3_URL_EXPIRY_SECS
+    /// (default 3600) and the usual AWS_ACCESS_KEY_ID / AWS_SECRET_ACCESS_KEY / AWS_SESSION_TOKEN
+    pub fn from_env() -> Result<Self> {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+
+        let bucket = var("S3_BUCKET").ok_or_else(|| anyhow!("S3_BUCKET is not set"))?;
+        let region = var("S3_REGION")
+            .or_else(|| var("AWS_REGION"))
+            .unwrap_or_else(|| "us-east-1".to_string());
+        let endpoint = var("S3_ENDPOINT").unwrap_or_else(|| format!("https://s3.{}.amazonaws.com", region));
+        let endpoint = reqwest::Url::parse(&endpoint).with_context(|| format!("Invalid S3_ENDPOINT {}", endpoint))?;
+        if endpoint.host_str().is_none() {
+            return Err(anyhow!("S3_ENDPOINT {} has no host", endpoint));
+        }
+        let url_expiry_secs = match var("S3_URL_EXPIRY_SECS") {
+            Some(secs) => secs.parse().with_context(|| format!("Invalid S3_URL_EXPIRY_SECS {}", secs))?,
+            None => 3600,
+        };
+
+        Ok(S3Storage {
+            endpoint,
+            region,
+            bucket,
+            prefix: var("S3_PREFIX").unwrap_or_else(|| "vibecut".to_string()),
+            access_key: var("AWS_ACCESS_KEY_ID").ok_or_else(|| anyhow!("AWS_ACCESS_KEY_ID is not set"))?,
+            secret_key: var("AWS_SECRET_ACCESS_KEY").ok_or_else(|| anyhow!("AWS_SECRET_ACCESS_KEY is not set"))?,
+            session_token: var("AWS_SESSION_TOKEN"),
+            url_expiry_secs,
+            client: reqwest::Client::new(),
+        })
+    }
+
+    /// Object key for a name relative to the configured prefix
+    pub fn object_key(&self, name: &str) -> String {
+        let prefix = self.prefix.trim_matches('/');
+        if prefix.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}/{}", prefix, name)
+        }
+    }
+
+    /// Time-limited GET URL for an object, for handing to clients that can't reach the bucket
+    /// with credentials of their own
+    pub fn presigned_get_url(&self, key: &str) -> String {
+        let now = Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let scope = self.scope(&now);
+
+        let mut query = vec![
+            ("X-Amz-Algorithm", "AWS4-HMAC-SHA256".to_string()),
+            ("X-Amz-Credential", format!("{}/{}", self.access_key, scope)),
+            ("X-Amz-Date", amz_date.clone()),
+            ("X-Amz-Expires", self.url_expiry_secs.to_string()),
+            ("X-Amz-SignedHeaders", "host".to_string()),
+        ];
+        if let Some(token) = &self.session_token {
+            query.push(("X-Amz-Security-Token", token.clone()));
+        }
+        let query = canonical_query(&query);
+        let path = self.object_path(key);
+
+        let canonical_request = format!("GET\n{}\n{}\nhost:{}\n\nhost\nUNSIGNED-PAYLOAD", path, query, self.host());
+        let signature = self.signature(&now, &amz_date, &scope, &canonical_request);
+        format!("{}{}?{}&X-Amz-Signature={}", self.origin(), path, query, signature)
+    }
+
+    /// Upload a local file, replacing any object already at `key`
+    pub async fn put_file(&self, key: &str, path: &Path, content_type: &str) -> Result<()> {
+        let size = tokio::fs::metadata(path).await?.len();
+        let mut file = tokio::fs::File::open(path).await?;
+
+        if size <= PART_SIZE {
+            let mut body = Vec::with_capacity(size as usize);
+            file.read_to_end(&mut body).await?;
+            self.send(Method::PUT, key, &[], body, Some(content_type)).await?;
+            return Ok(());
+        }
+
+        let response = self
+            .send(Method::POST, key, &[("uploads", String::new())], Vec::new(), Some(content_type))
+            .await?;
+        let body = response.text().await?;
+        let upload_id = xml_value(&body, "UploadId")
+            .ok_or_else(|| anyhow!("CreateMultipartUpload returned no UploadId: {}", body))?;
+
+        match self.upload_parts(key, &upload_id, &mut file).await {
+            Ok(etags) => self.complete_multipart(key, &upload_id, &etags).await,
+            Err(e) => {
+                // Don't leave orphaned parts behind (they're billed until aborted)
+                let abort = self
+                    .send(Method::DELETE, key, &[("uploadId", upload_id.clone())], Vec::new(), None)
+                    .await;
+                if let Err(abort_err) = abort {
+                    eprintln!("[STORAGE] Failed to abort upload of {}: {:?}", key, abort_err);
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Delete the object at `key` (succeeds if it doesn't exist)
+    pub async fn delete_object(&self, key: &str) -> Result<()> {
+        self.send(Method::DELETE, key, &[], Vec::new(), None).await?;
+        Ok(())
+    }
+
+    async fn upload_parts(&self, key: &str, upload_id: &str, file: &mut tokio::fs::File) -> Result<Vec<String>> {
+        let mut etags = Vec::new();
+        loop {
+            let mut part = Vec::with_capacity(PART_SIZE as usize);
+            (&mut *file).take(PART_SIZE).read_to_end(&mut part).await?;
+            if part.is_empty() {
+                break;
+            }
+            let part_number = etags.len() + 1;
+            let response = self
+                .send(
+                    Method::PUT,
+                    key,
+                    &[("partNumber", part_number.to_string()), ("uploadId", upload_id.to_string())],
+                    part,
+                    None,
+                )
+                .await?;
+            let etag = response
+                .headers()
+                .get(reqwest::header::ETAG)
+                .and_then(|v| v.to_str().ok())
+                .ok_or_else(|| anyhow!("Part {} of {} returned no ETag", part_number, key))?;
+            etags.push(etag.to_string());
+        }
+        Ok(etags)
+    }
+
+    async fn complete_multipart(&self, key: &str, upload_id: &str, etags: &[String]) -> Result<()> {
+        let parts: String = etags
+            .iter()
+            .enumerate()
+            .map(|(i, etag)| format!("<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>", i + 1, etag))
+            .collect();
+        let body = format!("<CompleteMultipartUpload>{}</CompleteMultipartUpload>", parts);
+        let response = self
+            .send(Method::POST, key, &[("uploadId", upload_id.to_string())], body.into_bytes(), None)
+            .await?;
+        // CompleteMultipartUpload can fail after sending 200, with the error in the body
+        let body = response.text().await?;
+        if body.contains("<Error>") {
+            return Err(anyhow!("CompleteMultipartUpload for {} failed: {}", key, body));
+        }
+        Ok(())
+    }
+
+    /// Send a header-signed request for an object
+    async fn send(
+        &self,
+        method: Method,
+        key: &str,
+        query: &[(&str, String)],
+        body: Vec<u8>,
+        content_type: Option<&str>,
+    ) -> Result<reqwest::Response> {
+        let now = Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let scope = self.scope(&now);
+        let payload_hash = hex::encode(Sha256::digest(&body));
+
+        // Sorted by name, as the canonical request requires
+        let mut headers = vec![
+            ("host", self.host()),
+            ("x-amz-content-sha256", payload_hash.clone()),
+            ("x-amz-date", amz_date.clone()),
+        ];
+        if let Some(token) = &self.session_token {
+            headers.push(("x-amz-security-token", token.clone()));
+        }
+        let canonical_headers: String = headers.iter().map(|(name, value)| format!("{}:{}\n", name, value)).collect();
+        let signed_headers = headers.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(";");
+
+        let path = self.object_path(key);
+        let query = canonical_query(query);
+        let canonical_request = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}",
+            method, path, query, canonical_headers, signed_headers, payload_hash
+        );
+        let signature = self.signature(&now, &amz_date, &scope, &canonical_request);
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            self.access_key, scope, signed_headers, signature
+        );
+
+        let mut url = format!("{}{}", self.origin(), path);
+        if !query.is_empty() {
+            url.push('?');
+            url.push_str(&query);
+        }
+        let mut request = self
+            .client
+            .request(method.clone(), url)
+            .header("authorization", authorization)
+            .header("x-amz-content-sha256", payload_hash)
+            .header("x-amz-date", amz_date)
+            .body(body);
+        if let Some(token) = &self.session_token {
+            request = request.header("x-amz-security-token", token);
+        }
+        if let Some(content_type) = content_type {
+            request = request.header(reqwest::header::CONTENT_TYPE, content_type);
+        }
+
+        let response = request.send().await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(anyhow!("S3 {} {} failed ({}): {}", method, key, status, body));
+        }
+        Ok(response)
+    }
+
+    fn scope(&self, now: &DateTime<Utc>) -> String {
+        format!("{}/{}/s3/aws4_request", now.format("%Y%m%d"), self.region)
+    }
+
+    fn signature(&self, now: &DateTime<Utc>, amz_date: &str, scope: &str, canonical_request: &str) -> String {
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex::encode(Sha256::digest(canonical_request.as_bytes()))
+        );
+        let date_key = hmac_sha256(
+            format!("AWS4{}", self.secret_key).as_bytes(),
+            now.format("%Y%m%d").to_string().as_bytes(),
+        );
+        let region_key = hmac_sha256(&date_key, self.region.as_bytes());
+        let service_key = hmac_sha256(&region_key, b"s3");
+        let signing_key = hmac_sha256(&service_key, b"aws4_request");
+        hex::encode(hmac_sha256(&signing_key, string_to_sign.as_bytes()))
+    }
+
+    /// Host header value; the port is only included when it isn't the scheme's default
+    fn host(&self) -> String {
+        let host = self.endpoint.host_str().unwrap_or_default();
+        match self.endpoint.port() {
+            Some(port) => format!("{}:{}", host, port),
+            None => host.to_string(),
+        }
+    }
+
+    fn origin(&self) -> String {
+        format!("{}://{}", self.endpoint.scheme(), self.host())
+    }
+
+    fn object_path(&self, key: &str) -> String {
+        format!("/{}/{}", uri_encode(&self.bucket, true), uri_encode(key, false))
+    }
+}
+
+/// Query string in canonical form: names and values encoded, sorted by name
+fn canonical_query(params: &[(&str, String)]) -> String {
+    let mut encoded: Vec<(String, String)> = params
+        .iter()
+        .map(|(name, value)| (uri_encode(name, true), uri_encode(value, true)))
+        .collect();
+    encoded.sort();
+    encoded
+        .iter()
+        .map(|(name, value)| format!("{}={}", name, value))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// Percent-encode everything but unreserved characters (and `/`, unless `encode_slash`)
+fn uri_encode(value: &str, encode_slash: bool) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => encoded.push(byte as char),
+            b'/' if !encode_slash => encoded.push('/'),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+pub(crate) fn hmac_sha256(key: &[u8], data: &[u8]) -> [u8; 32] {
+    const BLOCK_SIZE: usize = 64;
+    let mut block = [0u8; BLOCK_SIZE];
+    if key.len() > BLOCK_SIZE {
+        block[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+
+    let mut inner = Sha256::new();
+    inner.update(block.map(|b| b ^ 0x36));
+    inner.update(data);
+    let mut outer = Sha256::new();
+    outer.update(block.map(|b| b ^ 0x5c));
+    outer.update(inner.finalize());
+    outer.finalize().into()
+}
+
+/// Text of the first `<tag>` element in an S3 XML response
+fn xml_value(xml: &str, tag: &str) -> Option<String> {
+    let open = format!("<{}>", tag);
+    let start = xml.find(&open)? + open.len();
+    let end = start + xml[start..].find(&format!("</{}>", tag))?;
+    Some(xml[start..end].to_string())
+}
+
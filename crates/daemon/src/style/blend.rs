@@ -0,0 +1,125 @@
+use serde_json::{json, Map, Value};
+
+use crate::media::beat_template::BeatTemplate;
+
+/// Blend weighted style profiles into one derived profile.
+/// Numbers are weighted averages (a field missing from some profiles is averaged over the
+/// ones that have it), objects blend key by key, and anything else (arrays, strings) comes
+/// from the heaviest profile. The beat template keeps the heaviest profile's structure with
+/// its cut rates and hook length pulled towards the others.
+pub fn blend_profiles(profiles: &[(Value, f64)]) -> Value {
+    let weighted: Vec<(&Value, f64)> = profiles
+        .iter()
+        .filter(|(_, weight)| *weight > 0.0)
+        .map(|(profile, weight)| (profile, *weight))
+        .collect();
+    if weighted.is_empty() {
+        return json!({});
+    }
+
+    let mut blended = blend_values(&weighted);
+    if let Some(template) = blend_beat_templates(&weighted) {
+        blended["beat_template"] = serde_json::to_value(&template).unwrap_or(Value::Null);
+        blended["beat_templates"] = json!([template]);
+    }
+    blended
+}
+
+fn blend_values(values: &[(&Value, f64)]) -> Value {
+    let present: Vec<(&Value, f64)> = values
+        .iter()
+        .filter(|(v, _)| !v.is_null())
+        .copied()
+        .collect();
+    let Some(dominant) = heaviest(&present) else {
+        return Value::Null;
+    };
+
+    match dominant {
+        Value::Number(_) => {
+            let numbers: Vec<(f64, f64)> = present
+                .iter()
+                .filter_map(|(v, w)| v.as_f64().map(|n| (n, *w)))
+                .collect();
+            let total_weight: f64 = numbers.iter().map(|(_, w)| w).sum();
+            let sum: f64 = numbers.iter().map(|(n, w)| n * w).sum();
+            json!(sum / total_weight)
+        }
+        Value::Object(_) => {
+            let mut keys: Vec<&String> = Vec::new();
+            for (value, _) in &present {
+                if let Some(map) = value.as_object() {
+                    for key in map.keys() {
+                        if !keys.contains(&key) {
+                            keys.push(key);
+                        }
+                    }
+                }
+            }
+            let mut blended = Map::new();
+            for key in keys {
+                let children: Vec<(&Value, f64)> = present
+                    .iter()
+                    .filter_map(|(v, w)| v.get(key.as_str()).map(|child| (child, *w)))
+                    .collect();
+                blended.insert(key.clone(), blend_values(&children));
+            }
+            Value::Object(blended)
+        }
+        other => other.clone(),
+    }
+}
+
+/// Heaviest profile's template, with hook length and per-section cut rates scaled to the
+/// weighted average across all profiles that carry a template
+fn blend_beat_templates(profiles: &[(&Value, f64)]) -> Option<BeatTemplate> {
+    let templates: Vec<(BeatTemplate, f64)> = profiles
+        .iter()
+        .filter_map(|(profile, weight)| {
+            profile
+                .get("beat_template")
+                .filter(|t| !t.is_null())
+                .and_then(|t| serde_json::from_value::<BeatTemplate>(t.clone()).ok())
+                .map(|t| (t, *weight))
+        })
+        .collect();
+
+    let (mut base, _) = templates
+        .iter()
+        .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+        .cloned()?;
+
+    let total_weight: f64 = templates.iter().map(|(_, w)| w).sum();
+    let hook_length_sec = templates.iter().map(|(t, w)| t.hook_length_sec * w).sum::<f64>() / total_weight;
+    let cuts_per_10s = templates.iter().map(|(t, w)| t.cuts_per_10s * w).sum::<f64>() / total_weight;
+
+    let rate_scale = if base.cuts_per_10s > 0.0 { cuts_per_10s / base.cuts_per_10s } else { 1.0 };
+    for section in base.sections.iter_mut() {
+        section.cuts_per_10s *= rate_scale;
+    }
+
+    // Move the hook/body boundary, keeping the section after the hook at least half a second long
+    let max_hook_end = base
+        .sections
+        .get(1)
+        .map(|next| next.end_sec - 0.5)
+        .unwrap_or(base.duration_sec);
+    let hook_end = hook_length_sec.min(max_hook_end).max(0.5);
+    if base.sections.first().is_some_and(|s| s.section_type == "hook") {
+        base.sections[0].end_sec = hook_end;
+        if let Some(next) = base.sections.get_mut(1) {
+            next.start_sec = hook_end;
+        }
+        base.hook_length_sec = hook_end;
+    }
+    base.cuts_per_10s = cuts_per_10s;
+    base.source_asset_id = None;
+    Some(base)
+}
+
+fn heaviest<'a>(values: &[(&'a Value, f64)]) -> Option<&'a Value> {
+    values
+        .iter()
+        .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+        .map(|(v, _)| *v)
+}
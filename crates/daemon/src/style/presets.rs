@@ -0,0 +1,137 @@
+use serde_json::json;
+
+/// A built-in style profile, seeded into style_profiles on startup
+pub struct StylePreset {
+    /// Stable identifier stored in style_profiles.preset_key
+    pub key: &'static str,
+    pub name: &'static str,
+    /// Words in EditConstraints.vibe that select this preset
+    pub aliases: &'static [&'static str],
+    pub profile: serde_json::Value,
+}
+
+/// Built-in presets, shaped like reference-derived profiles (pacing, shot alternation and a
+/// beat template the planner instantiates) so new projects cut well without reference videos
+pub fn builtin_presets() -> Vec<StylePreset> {
+    vec![
+        StylePreset {
+            key: "travel_vlog",
+            name: "Fast-cut travel vlog",
+            aliases: &["travel", "vlog", "fast", "fast-cut", "fast-paced", "energetic"],
+            profile: preset_profile(
+                1.5, 30.0, 0.8, 0.3, 0.9,
+                json!({
+                    "duration_sec": 60.0,
+                    "hook_length_sec": 3.0,
+                    "cuts_per_10s": 4.5,
+                    "music_drops": [30.0],
+                    "sections": [
+                        { "section_type": "hook", "start_sec": 0.0, "end_sec": 3.0, "cuts_per_10s": 6.0 },
+                        { "section_type": "body", "start_sec": 3.0, "end_sec": 30.0, "cuts_per_10s": 4.0 },
+                        { "section_type": "drop", "start_sec": 30.0, "end_sec": 60.0, "cuts_per_10s": 5.0 },
+                    ],
+                }),
+            ),
+        },
+        StylePreset {
+            key: "cinematic",
+            name: "Slow cinematic",
+            aliases: &["cinematic", "slow", "film", "moody", "montage"],
+            profile: preset_profile(
+                6.0, 8.0, 0.6, 0.1, 1.0,
+                json!({
+                    "duration_sec": 60.0,
+                    "hook_length_sec": 8.0,
+                    "cuts_per_10s": 1.2,
+                    "music_drops": [],
+                    "sections": [
+                        { "section_type": "hook", "start_sec": 0.0, "end_sec": 8.0, "cuts_per_10s": 1.5 },
+                        { "section_type": "body", "start_sec": 8.0, "end_sec": 60.0, "cuts_per_10s": 1.2 },
+                    ],
+                }),
+            ),
+        },
+        StylePreset {
+            key: "tutorial",
+            name: "Tutorial",
+            aliases: &["tutorial", "how-to", "howto", "explainer", "lesson", "demo"],
+            profile: preset_profile(
+                8.0, 6.0, 0.2, 0.9, 0.2,
+                json!({
+                    "duration_sec": 60.0,
+                    "hook_length_sec": 5.0,
+                    "cuts_per_10s": 1.0,
+                    "music_drops": [],
+                    "sections": [
+                        { "section_type": "hook", "start_sec": 0.0, "end_sec": 5.0, "cuts_per_10s": 2.0 },
+                        { "section_type": "body", "start_sec": 5.0, "end_sec": 60.0, "cuts_per_10s": 1.0 },
+                    ],
+                }),
+            ),
+        },
+        StylePreset {
+            key: "qa",
+            name: "Q&A",
+            aliases: &["q&a", "qa", "interview", "questions", "podcast"],
+            profile: preset_profile(
+                10.0, 5.0, 0.5, 0.8, 0.0,
+                json!({
+                    "duration_sec": 60.0,
+                    "hook_length_sec": 6.0,
+                    "cuts_per_10s": 0.8,
+                    "music_drops": [],
+                    "sections": [
+                        { "section_type": "hook", "start_sec": 0.0, "end_sec": 6.0, "cuts_per_10s": 2.0 },
+                        { "section_type": "body", "start_sec": 6.0, "end_sec": 60.0, "cuts_per_10s": 0.8 },
+                    ],
+                }),
+            ),
+        },
+    ]
+}
+
+fn preset_profile(
+    median_clip_length: f64,
+    montage_density: f64,
+    shot_alternation_rate: f64,
+    caption_frequency: f64,
+    music_presence_ratio: f64,
+    beat_template: serde_json::Value,
+) -> serde_json::Value {
+    json!({
+        "pacing_stats": {
+            "median_clip_length": median_clip_length,
+            "variance": 0.0,
+        },
+        "montage_density": montage_density,
+        "silence_cut_aggressiveness": 0.5,
+        "caption_frequency": caption_frequency,
+        "music_presence_ratio": music_presence_ratio,
+        "typical_overlay_usage": 0.0,
+        "shot_type_distribution": {},
+        "shot_alternation_rate": shot_alternation_rate,
+        "beat_templates": [beat_template.clone()],
+        "beat_template": beat_template,
+    })
+}
+
+/// Preset selected by a free-text vibe ("fast-cut travel vlog", "cinematic", "Q&A"):
+/// exact key/name match first, then the first preset with an alias among the vibe's words
+pub fn find_preset(vibe: &str) -> Option<StylePreset> {
+    let vibe = vibe.trim().to_lowercase();
+    if vibe.is_empty() {
+        return None;
+    }
+    let words: Vec<&str> = vibe
+        .split(|c: char| c.is_whitespace() || c == ',' || c == '/')
+        .filter(|w| !w.is_empty())
+        .collect();
+
+    let presets = builtin_presets();
+    if let Some(idx) = presets.iter().position(|p| p.key == vibe || p.name.to_lowercase() == vibe) {
+        return presets.into_iter().nth(idx);
+    }
+    presets
+        .into_iter()
+        .find(|p| p.aliases.iter().any(|alias| words.contains(alias)))
+}
@@ -0,0 +1,112 @@
+use engine::timeline::CaptionStyle;
+
+use super::{MusicSlot, ProjectTemplate, TemplateBeat};
+
+/// A built-in project template, seeded into project_templates on startup
+pub struct BuiltinTemplate {
+    /// Stable identifier stored in project_templates.builtin_key
+    pub key: &'static str,
+    pub template: ProjectTemplate,
+}
+
+/// Built-in templates for common formats, so a project starts from a structure without the
+/// user writing one
+pub fn builtin_templates() -> Vec<BuiltinTemplate> {
+    vec![
+        BuiltinTemplate {
+            key: "vlog",
+            template: ProjectTemplate {
+                name: "Vlog".to_string(),
+                description: Some("Cold-open hook, the day's story, and a short sign-off".to_string()),
+                target_length_sec: 90.0,
+                vibe: Some("travel_vlog".to_string()),
+                sections: vec![
+                    beat("hook", 4.0, 5.0, None, None, "Best moment of the day, before any setup"),
+                    beat("story", 76.0, 3.0, None, None, "What happened, in order"),
+                    beat("outro", 10.0, 1.5, Some(5.0), Some(15.0), "Sign-off and what's next"),
+                ],
+                caption_style: Some(CaptionStyle::default()),
+                music: Some(MusicSlot::default()),
+            },
+        },
+        BuiltinTemplate {
+            key: "product_demo",
+            template: ProjectTemplate {
+                name: "Product demo".to_string(),
+                description: Some("Problem, demo and call to action".to_string()),
+                target_length_sec: 60.0,
+                vibe: Some("tutorial".to_string()),
+                sections: vec![
+                    beat("hook", 3.0, 4.0, None, None, "The result, up front"),
+                    beat("problem", 12.0, 2.0, Some(5.0), Some(20.0), "What's hard without the product"),
+                    beat("demo", 37.0, 1.5, None, None, "The product solving it"),
+                    beat("cta", 8.0, 1.0, Some(4.0), Some(12.0), "Where to get it"),
+                ],
+                caption_style: Some(CaptionStyle {
+                    font_size: 56,
+                    uppercase: true,
+                    ..CaptionStyle::default()
+                }),
+                music: Some(MusicSlot {
+                    sections: vec!["hook".to_string(), "cta".to_string()],
+                    ..MusicSlot::default()
+                }),
+            },
+        },
+        BuiltinTemplate {
+            key: "interview",
+            template: ProjectTemplate {
+                name: "Interview".to_string(),
+                description: Some("Teaser answer, then the conversation".to_string()),
+                target_length_sec: 180.0,
+                vibe: Some("qa".to_string()),
+                sections: vec![
+                    beat("hook", 8.0, 1.5, None, None, "The most quotable answer"),
+                    beat("intro", 12.0, 1.0, Some(5.0), Some(20.0), "Who's talking and why"),
+                    beat("conversation", 150.0, 0.8, None, None, "Questions and answers"),
+                    beat("outro", 10.0, 1.0, None, None, "Closing thought"),
+                ],
+                caption_style: Some(CaptionStyle {
+                    background: None,
+                    ..CaptionStyle::default()
+                }),
+                music: None,
+            },
+        },
+        BuiltinTemplate {
+            key: "highlight_reel",
+            template: ProjectTemplate {
+                name: "Highlight reel".to_string(),
+                description: Some("Build-up to a music drop, fast cuts after".to_string()),
+                target_length_sec: 45.0,
+                vibe: Some("travel_vlog".to_string()),
+                sections: vec![
+                    beat("hook", 3.0, 6.0, None, None, "One striking shot"),
+                    beat("build", 17.0, 3.0, None, None, "Rising energy into the drop"),
+                    beat("drop", 20.0, 6.0, None, None, "Fastest cuts, most energetic moments"),
+                    beat("outro", 5.0, 2.0, None, None, "Let the last shot breathe"),
+                ],
+                caption_style: None,
+                music: Some(MusicSlot::default()),
+            },
+        },
+    ]
+}
+
+fn beat(
+    section_type: &str,
+    duration_sec: f64,
+    cuts_per_10s: f64,
+    min_sec: Option<f64>,
+    max_sec: Option<f64>,
+    description: &str,
+) -> TemplateBeat {
+    TemplateBeat {
+        section_type: section_type.to_string(),
+        duration_sec,
+        cuts_per_10s: Some(cuts_per_10s),
+        min_sec,
+        max_sec,
+        description: Some(description.to_string()),
+    }
+}
@@ -0,0 +1,324 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::db::{Database, MediaAssetInfo, Segment};
+use crate::media::beat_template::{BeatTemplate, TemplateSection};
+use crate::media::roots::{self, PathNotAllowed};
+use engine::compiler::{EditConstraints, EditEvent, EditPlan, SectionConstraints};
+use engine::timeline::{CaptionStyle, SafeArea, TICKS_PER_SECOND};
+
+pub mod builtin;
+
+/// Default cut rate for a template section that doesn't set one
+const DEFAULT_CUTS_PER_10S: f64 = 2.0;
+
+/// Reusable project structure: the sections an edit is built from (with target lengths), how
+/// captions look and where music plays. A project created from a template keeps its own copy
+/// (projects.template_json), so editing the library doesn't change existing projects.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectTemplate {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Default edit length when a generate request doesn't give one
+    pub target_length_sec: f64,
+    /// Default vibe (e.g. a style preset name) when a generate request doesn't give one
+    #[serde(default)]
+    pub vibe: Option<String>,
+    /// Beats in order; their lengths are scaled to the edit's target length
+    pub sections: Vec<TemplateBeat>,
+    /// Captions drawn over every clip with speech (None = no captions)
+    #[serde(default)]
+    pub caption_style: Option<CaptionStyle>,
+    #[serde(default)]
+    pub music: Option<MusicSlot>,
+}
+
+/// One beat of a template ("hook", "problem", "demo", "cta", ...)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateBeat {
+    /// "hook" keeps its length regardless of the target (up to a third of the edit) and "drop"
+    /// opens on the most energetic clip, as in reference beat templates
+    pub section_type: String,
+    pub duration_sec: f64,
+    #[serde(default)]
+    pub cuts_per_10s: Option<f64>,
+    /// Bounds on the section's length once scaled, passed to the planner as section pins
+    #[serde(default)]
+    pub min_sec: Option<f64>,
+    #[serde(default)]
+    pub max_sec: Option<f64>,
+    /// What the beat is for, shown to the user
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// Where a template's music goes. The track is usually picked per project, after the project
+/// is created; until then the slot stays empty and the edit has no music.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MusicSlot {
+    pub track_path: Option<String>,
+    /// Sections the music plays under (empty = the whole edit)
+    pub sections: Vec<String>,
+    pub ducking_profile_id: Option<i64>,
+}
+
+impl ProjectTemplate {
+    /// Resolve the music slot's track against the media roots, keeping the canonical path
+    pub fn resolve_music(&mut self) -> Result<(), PathNotAllowed> {
+        if let Some(track_path) = self.music.as_mut().and_then(|slot| slot.track_path.as_mut()) {
+            *track_path = roots::resolve(track_path.as_str())?.to_string_lossy().to_string();
+        }
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("name is required".to_string());
+        }
+        if !self.target_length_sec.is_finite() || self.target_length_sec <= 0.0 {
+            return Err("target_length_sec must be positive".to_string());
+        }
+        if self.sections.is_empty() {
+            return Err("a template needs at least one section".to_string());
+        }
+        for (idx, beat) in self.sections.iter().enumerate() {
+            if beat.section_type.trim().is_empty() {
+                return Err(format!("section {} is missing section_type", idx + 1));
+            }
+            if !beat.duration_sec.is_finite() || beat.duration_sec <= 0.0 {
+                return Err(format!("'{}' duration_sec must be positive", beat.section_type));
+            }
+            if beat.cuts_per_10s.is_some_and(|rate| !rate.is_finite() || rate <= 0.0) {
+                return Err(format!("'{}' cuts_per_10s must be positive", beat.section_type));
+            }
+            let min = beat.min_sec.unwrap_or(0.0);
+            let max = beat.max_sec.unwrap_or(f64::MAX);
+            if min < 0.0 || min.is_nan() || max.is_nan() || min > max {
+                return Err(format!("'{}' min_sec must be between 0 and max_sec", beat.section_type));
+            }
+            if self.sections[..idx].iter().any(|b| b.section_type == beat.section_type)
+                && (beat.min_sec.is_some() || beat.max_sec.is_some())
+            {
+                return Err(format!(
+                    "'{}' appears more than once, so it can't have length bounds",
+                    beat.section_type
+                ));
+            }
+        }
+        if let Some(style) = &self.caption_style {
+            style.validate().map_err(|e| format!("caption_style: {}", e))?;
+        }
+        if let Some(music) = &self.music {
+            if let Some(unknown) = music
+                .sections
+                .iter()
+                .find(|s| !self.sections.iter().any(|b| &b.section_type == *s))
+            {
+                return Err(format!("music slot names unknown section '{}'", unknown));
+            }
+        }
+        Ok(())
+    }
+
+    /// The template's sections as a beat template for the planner
+    pub fn beat_template(&self) -> BeatTemplate {
+        let mut start_sec = 0.0;
+        let mut sections = Vec::new();
+        for beat in &self.sections {
+            sections.push(TemplateSection {
+                section_type: beat.section_type.clone(),
+                start_sec,
+                end_sec: start_sec + beat.duration_sec,
+                cuts_per_10s: beat.cuts_per_10s.unwrap_or(DEFAULT_CUTS_PER_10S),
+            });
+            start_sec += beat.duration_sec;
+        }
+        let hook_length_sec = self
+            .sections
+            .first()
+            .filter(|b| b.section_type == "hook")
+            .map(|b| b.duration_sec)
+            .unwrap_or(0.0);
+        let cuts: f64 = sections.iter().map(|s| s.cuts_per_10s * (s.end_sec - s.start_sec)).sum();
+
+        BeatTemplate {
+            source_asset_id: None,
+            duration_sec: start_sec,
+            hook_length_sec,
+            cuts_per_10s: cuts / start_sec,
+            music_drops: sections
+                .iter()
+                .filter(|s| s.section_type == "drop")
+                .map(|s| s.start_sec)
+                .collect(),
+            sections,
+            impacts: Vec::new(),
+            follows_music: false,
+        }
+    }
+
+    /// Fill in what a generate request left open: target length, vibe, and the template's
+    /// section length bounds (a request's own pin for a section wins)
+    pub fn apply_to_constraints(&self, constraints: &mut EditConstraints) {
+        if constraints.target_length.is_none() {
+            constraints.target_length = Some((self.target_length_sec * TICKS_PER_SECOND as f64) as i64);
+        }
+        if constraints.vibe.is_none() {
+            constraints.vibe = self.vibe.clone();
+        }
+        for beat in &self.sections {
+            if beat.min_sec.is_none() && beat.max_sec.is_none() {
+                continue;
+            }
+            if constraints.section_overrides.iter().any(|p| p.section_type == beat.section_type) {
+                continue;
+            }
+            constraints.section_overrides.push(SectionConstraints {
+                section_type: beat.section_type.clone(),
+                min_duration: beat.min_sec.map(|s| (s * TICKS_PER_SECOND as f64) as i64),
+                max_duration: beat.max_sec.map(|s| (s * TICKS_PER_SECOND as f64) as i64),
+                ..Default::default()
+            });
+        }
+    }
+
+    /// The style profile guiding the planner, with this template's sections as its beat
+    /// template (pacing and shot alternation still come from the profile)
+    pub fn apply_to_style_profile(&self, style_profile: Option<serde_json::Value>) -> serde_json::Value {
+        let mut profile = match style_profile {
+            Some(serde_json::Value::Object(map)) => map,
+            _ => serde_json::Map::new(),
+        };
+        profile.insert(
+            "beat_template".to_string(),
+            serde_json::to_value(self.beat_template()).unwrap_or(serde_json::Value::Null),
+        );
+        serde_json::Value::Object(profile)
+    }
+
+    /// Add the template's captions (each clip's share of its segment's transcript) and its
+    /// music slot to a plan the planner filled from this template. Honors the plan's
+    /// captions_on / music_on. Captions keep to the style profile's platform safe area unless
+    /// the template's caption style sets its own.
+    pub fn decorate_plan(
+        &self,
+        plan: &mut EditPlan,
+        segments_with_assets: &[(Segment, MediaAssetInfo)],
+        style_profile: Option<&serde_json::Value>,
+    ) {
+        if plan.constraints.captions_on {
+            if let Some(style) = &self.caption_style {
+                let mut style = style.clone();
+                if style.safe_area.is_none() {
+                    style.safe_area = style_profile.and_then(SafeArea::from_style_profile);
+                }
+                for section in &mut plan.sections {
+                    let captions: Vec<EditEvent> = section
+                        .events
+                        .iter()
+                        .filter_map(|event| caption_for_clip(event, segments_with_assets, &style))
+                        .collect();
+                    section.events.extend(captions);
+                }
+            }
+        }
+
+        let Some(slot) = self.music.as_ref().filter(|_| plan.constraints.music_on) else {
+            return;
+        };
+        let Some(track_path) = slot.track_path.as_deref() else {
+            return;
+        };
+        // Checked when the template was saved too, but MEDIA_ROOTS may have changed since
+        let track_path = match roots::resolve(track_path) {
+            Ok(path) => path.to_string_lossy().to_string(),
+            Err(e) => {
+                eprintln!("[TEMPLATES] Leaving out the music of template \"{}\": {}", self.name, e);
+                return;
+            }
+        };
+        // One music event per run of consecutive slot sections, placed in the run's first section
+        let mut runs: Vec<(usize, i64, i64)> = Vec::new();
+        let mut in_run = false;
+        for (idx, section) in plan.sections.iter().enumerate() {
+            if !slot.sections.is_empty() && !slot.sections.contains(&section.section_type) {
+                in_run = false;
+                continue;
+            }
+            let Some((start_ticks, end_ticks)) = clip_bounds(&section.events) else {
+                continue;
+            };
+            match runs.last_mut().filter(|_| in_run) {
+                Some(run) => run.2 = run.2.max(end_ticks),
+                None => runs.push((idx, start_ticks, end_ticks)),
+            }
+            in_run = true;
+        }
+        for (first, start_ticks, end_ticks) in runs {
+            plan.sections[first].events.push(EditEvent::Music {
+                track_path: track_path.clone(),
+                ducking_profile_id: slot.ducking_profile_id,
+                start_ticks,
+                end_ticks,
+            });
+        }
+    }
+}
+
+/// The project's copy of the template it was created from, if any
+pub fn project_template(db: &Database, project_id: i64) -> Result<Option<ProjectTemplate>> {
+    match db.get_project_template_copy(project_id)? {
+        Some((_, json)) => Ok(Some(serde_json::from_str(&json)?)),
+        None => Ok(None),
+    }
+}
+
+/// Caption for a clip event: the share of its segment's transcript that the clip covers
+fn caption_for_clip(
+    event: &EditEvent,
+    segments_with_assets: &[(Segment, MediaAssetInfo)],
+    style: &CaptionStyle,
+) -> Option<EditEvent> {
+    let EditEvent::Clip {
+        segment_id: Some(segment_id),
+        in_ticks,
+        out_ticks,
+        timeline_start_ticks,
+        ..
+    } = event
+    else {
+        return None;
+    };
+    let (segment, _) = segments_with_assets.iter().find(|(s, _)| s.id == *segment_id)?;
+    let words: Vec<&str> = segment.transcript.as_deref()?.split_whitespace().collect();
+    let segment_ticks = segment.end_ticks - segment.start_ticks;
+    if words.is_empty() || segment_ticks <= 0 {
+        return None;
+    }
+    let share = ((out_ticks - in_ticks) as f64 / segment_ticks as f64).clamp(0.0, 1.0);
+    let count = ((words.len() as f64 * share).ceil() as usize).clamp(1, words.len());
+    let text = words[..count].join(" ");
+
+    Some(EditEvent::Caption {
+        text: if style.uppercase { text.to_uppercase() } else { text },
+        template_id: None,
+        style: Some(style.clone()),
+        start_ticks: *timeline_start_ticks,
+        end_ticks: timeline_start_ticks + event.clip_timeline_ticks()?,
+    })
+}
+
+/// Timeline span covered by a section's clips (None when it has none)
+pub(crate) fn clip_bounds(events: &[EditEvent]) -> Option<(i64, i64)> {
+    events
+        .iter()
+        .filter_map(|event| match event {
+            EditEvent::Clip { timeline_start_ticks, .. } => {
+                Some((*timeline_start_ticks, timeline_start_ticks + event.clip_timeline_ticks()?))
+            }
+            _ => None,
+        })
+        .reduce(|(s, e), (cs, ce)| (s.min(cs), e.max(ce)))
+}
@@ -0,0 +1,138 @@
+//! Text-to-speech for narration. TTS_PROVIDER picks who speaks: "ml" (the default) has the ML
+//! service synthesize speech with OpenAI's TTS; "elevenlabs" calls ElevenLabs with
+//! ELEVENLABS_API_KEY, in the voice ELEVENLABS_VOICE_ID unless a request names one.
+
+use anyhow::Result;
+use std::path::Path;
+
+use crate::llm::ml_service_url;
+const ELEVENLABS_API_BASE: &str = "https://api.elevenlabs.io/v1";
+/// ElevenLabs' "Rachel", used when neither the request nor ELEVENLABS_VOICE_ID names a voice
+const ELEVENLABS_DEFAULT_VOICE: &str = "21m00Tcm4TlvDq8ikWAM";
+
+/// Names TTS_PROVIDER (or a request) can pick
+pub const PROVIDERS: &[&str] = &["ml", "elevenlabs"];
+
+/// Something that turns text into speech
+#[async_trait::async_trait]
+pub trait TtsProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Extension of the audio files the provider writes
+    fn extension(&self) -> &'static str;
+
+    /// Speak `text` into `output`, in `voice` (the provider's default when None)
+    async fn synthesize(&self, text: &str, voice: Option<&str>, output: &Path) -> Result<()>;
+}
+
+/// The provider named by the request, or by TTS_PROVIDER when it names none
+pub fn provider(name: Option<&str>) -> Result<Box<dyn TtsProvider>> {
+    let configured = std::env::var("TTS_PROVIDER").ok();
+    match name.or(configured.as_deref()).unwrap_or("ml") {
+        "ml" => Ok(Box::new(MlServiceTts)),
+        "elevenlabs" => Ok(Box::new(ElevenLabsTts::from_env()?)),
+        other => Err(anyhow::anyhow!("Unknown TTS provider '{}' (expected one of {})", other, PROVIDERS.join(", "))),
+    }
+}
+
+/// OpenAI text-to-speech through the ML service's /tts endpoint
+pub struct MlServiceTts;
+
+#[async_trait::async_trait]
+impl TtsProvider for MlServiceTts {
+    fn name(&self) -> &'static str {
+        "ml"
+    }
+
+    fn extension(&self) -> &'static str {
+        "wav"
+    }
+
+    async fn synthesize(&self, text: &str, voice: Option<&str>, output: &Path) -> Result<()> {
+        let client = reqwest::Client::new();
+        let response = client
+            .post(format!("{}/tts", ml_service_url()))
+            .json(&serde_json::json!({
+                "text": text,
+                "voice": voice,
+                "output_path": output.to_string_lossy(),
+            }))
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!("ML service tts failed: {}", response.status()));
+        }
+        Ok(())
+    }
+}
+
+/// ElevenLabs text-to-speech
+pub struct ElevenLabsTts {
+    api_key: String,
+    default_voice: String,
+}
+
+impl ElevenLabsTts {
+    pub fn from_env() -> Result<Self> {
+        let api_key = std::env::var("ELEVENLABS_API_KEY")
+            .map_err(|_| anyhow::anyhow!("ELEVENLABS_API_KEY environment variable not set"))?;
+        let default_voice = std::env::var("ELEVENLABS_VOICE_ID").unwrap_or_else(|_| ELEVENLABS_DEFAULT_VOICE.to_string());
+        Ok(ElevenLabsTts { api_key, default_voice })
+    }
+}
+
+#[async_trait::async_trait]
+impl TtsProvider for ElevenLabsTts {
+    fn name(&self) -> &'static str {
+        "elevenlabs"
+    }
+
+    fn extension(&self) -> &'static str {
+        "mp3"
+    }
+
+    async fn synthesize(&self, text: &str, voice: Option<&str>, output: &Path) -> Result<()> {
+        let client = reqwest::Client::new();
+        let response = client
+            .post(format!(
+                "{}/text-to-speech/{}?output_format=mp3_44100_128",
+                ELEVENLABS_API_BASE,
+                voice.unwrap_or(&self.default_voice)
+            ))
+            .header("xi-api-key", &self.api_key)
+            .json(&serde_json::json!({ "text": text }))
+            .send()
+            .await?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(anyhow::anyhow!("ElevenLabs text-to-speech failed ({}): {}", status, body));
+        }
+        if let Some(parent) = output.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(output, response.bytes().await?).await?;
+        Ok(())
+    }
+}
+
+/// Split a script into the sentences it's spoken in: at sentence-ending punctuation followed by
+/// a space, and at line breaks
+pub fn split_sentences(script: &str) -> Vec<String> {
+    let mut sentences = Vec::new();
+    for line in script.lines() {
+        let mut current = String::new();
+        let mut chars = line.chars().peekable();
+        while let Some(c) = chars.next() {
+            current.push(c);
+            let ends = matches!(c, '.' | '!' | '?' | '。' | '！' | '？');
+            if ends && chars.peek().is_none_or(|next| next.is_whitespace()) {
+                sentences.push(current.trim().to_string());
+                current.clear();
+            }
+        }
+        sentences.push(current.trim().to_string());
+    }
+    sentences.retain(|s| !s.is_empty());
+    sentences
+}
@@ -20,8 +20,16 @@ pub struct SearchResult {
     pub transcript: Option<String>,
 }
 
-/// Get API key from environment
+/// The in-process fake in simulation mode, else the real API
+fn api_base() -> String {
+    crate::simulation::twelvelabs_api_base().unwrap_or_else(|| TWELVELABS_API_BASE.to_string())
+}
+
+/// Get API key from environment (the fake accepts any key)
 fn get_api_key() -> Result<String> {
+    if crate::simulation::enabled() {
+        return Ok(std::env::var("TWELVELABS_API_KEY").unwrap_or_else(|_| "simulated".to_string()));
+    }
     std::env::var("TWELVELABS_API_KEY")
         .map_err(|_| anyhow::anyhow!("TWELVELABS_API_KEY environment variable not set"))
 }
@@ -33,7 +41,7 @@ pub async fn create_index(project_id: i64, index_name: Option<String>) -> Result
     
     let client = reqwest::Client::new();
     let response = client
-        .post(&format!("{}/indexes", TWELVELABS_API_BASE))
+        .post(format!("{}/indexes", api_base()))
         .header("x-api-key", &api_key)
         .json(&serde_json::json!({
             "index_name": name,
@@ -77,7 +85,7 @@ pub async fn create_task_upload(index_id: &str, video_path: &str) -> Result<Stri
     
     let client = reqwest::Client::new();
     let response = client
-        .post(&format!("{}/tasks", TWELVELABS_API_BASE))
+        .post(format!("{}/tasks", api_base()))
         .header("x-api-key", &api_key)
         .json(&serde_json::json!({
             "index_id": index_id,
@@ -106,7 +114,7 @@ pub async fn get_task_status(task_id: &str) -> Result<TaskStatus> {
     
     let client = reqwest::Client::new();
     let response = client
-        .get(&format!("{}/tasks/{}", TWELVELABS_API_BASE, task_id))
+        .get(format!("{}/tasks/{}", api_base(), task_id))
         .header("x-api-key", &api_key)
         .send()
         .await?;
@@ -149,7 +157,7 @@ pub async fn search(
     
     let client = reqwest::Client::new();
     let response = client
-        .post(&format!("{}/search", TWELVELABS_API_BASE))
+        .post(format!("{}/search", api_base()))
         .header("x-api-key", &api_key)
         .timeout(Duration::from_secs(10))
         .json(&serde_json::json!({
@@ -0,0 +1,198 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::db::Database;
+use engine::timeline::{Timeline, TrackKind};
+
+pub mod youtube;
+
+/// Upload providers exports can be published to
+pub const PROVIDERS: &[&str] = &["youtube"];
+
+/// YouTube only shows chapters when there are at least this many, each this long or longer
+const MIN_CHAPTERS: usize = 3;
+const MIN_CHAPTER_SECONDS: f64 = 10.0;
+/// Longest chapter title taken from a segment summary
+const MAX_CHAPTER_TITLE_CHARS: usize = 60;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Chapter {
+    pub start_seconds: f64,
+    pub title: String,
+}
+
+/// Title, description and chapters for a published export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublishMetadata {
+    pub title: String,
+    /// Includes the chapter list, which is how YouTube picks chapters up
+    pub description: String,
+    pub tags: Vec<String>,
+    /// "private" | "unlisted" | "public"
+    pub privacy: String,
+    pub chapters: Vec<Chapter>,
+}
+
+/// Caller-supplied values that take precedence over generated metadata
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct MetadataOverrides {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub tags: Option<Vec<String>>,
+    pub privacy: Option<String>,
+}
+
+/// Build publish metadata from the project's timeline: chapters from labelled markers (or,
+/// without enough of them, from the summaries of the segments on the main track), a
+/// description from those chapters, and the project name as the title
+pub fn generate_metadata(db: &Database, project_id: i64, overrides: MetadataOverrides) -> Result<PublishMetadata> {
+    let project = db
+        .get_project(project_id)?
+        .ok_or_else(|| anyhow!("Project {} not found", project_id))?;
+    let timeline: Option<Timeline> = db
+        .get_timeline(project_id)?
+        .and_then(|json| serde_json::from_str(&json).ok());
+    let chapters = match timeline {
+        Some(ref timeline) => chapters(db, project_id, timeline)?,
+        None => Vec::new(),
+    };
+
+    let privacy = overrides.privacy.unwrap_or_else(|| "private".to_string());
+    if !["private", "unlisted", "public"].contains(&privacy.as_str()) {
+        return Err(anyhow!("Unknown privacy setting: {}", privacy));
+    }
+
+    let mut description = overrides.description.unwrap_or_else(|| {
+        let highlights: Vec<&str> = chapters.iter().skip(1).take(5).map(|c| c.title.as_str()).collect();
+        if highlights.is_empty() {
+            String::new()
+        } else {
+            format!("Featuring {}.", highlights.join(", "))
+        }
+    });
+    if !chapters.is_empty() {
+        if !description.is_empty() {
+            description.push_str("\n\n");
+        }
+        description.push_str("Chapters:\n");
+        for chapter in &chapters {
+            description.push_str(&format!("{} {}\n", format_timestamp(chapter.start_seconds), chapter.title));
+        }
+    }
+
+    Ok(PublishMetadata {
+        title: overrides.title.unwrap_or(project.name),
+        description: description.trim_end().to_string(),
+        tags: overrides.tags.unwrap_or_default(),
+        privacy,
+        chapters,
+    })
+}
+
+/// Chapters for the timeline: labelled markers, or without enough of them one per run of
+/// clips on the main track named after its segment. Empty when there aren't at least three.
+pub fn chapters(db: &Database, project_id: i64, timeline: &Timeline) -> Result<Vec<Chapter>> {
+    let tps = timeline.settings.ticks_per_second as f64;
+
+    // Labelled markers are the editor's own chapter points
+    let mut markers: Vec<(f64, String)> = timeline
+        .markers
+        .iter()
+        .filter_map(|m| Some((m.position_ticks as f64 / tps, m.label.clone()?)))
+        .filter(|(_, label)| !label.trim().is_empty())
+        .collect();
+    markers.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+    if !markers.is_empty() && markers[0].0 > 0.0 {
+        markers.insert(0, (0.0, "Intro".to_string()));
+    }
+    let from_markers = merge_short_chapters(markers);
+    if from_markers.len() >= MIN_CHAPTERS {
+        return Ok(from_markers);
+    }
+
+    // Otherwise one chapter per run of clips on the main video track, named after the first
+    // clip's segment summary
+    let Some(track) = timeline.tracks.iter().filter(|t| t.kind == TrackKind::Video).min_by_key(|t| t.id) else {
+        return Ok(Vec::new());
+    };
+    let summaries: HashMap<i64, String> = db
+        .get_segments_for_project(project_id)?
+        .into_iter()
+        .filter_map(|(segment, _)| Some((segment.id, segment.summary_text?)))
+        .collect();
+    let mut clips: Vec<_> = track.clips.iter().collect();
+    clips.sort_by_key(|c| c.timeline_start_ticks);
+    let mut points: Vec<(f64, String)> = clips
+        .iter()
+        .filter_map(|clip| {
+            let summary = summaries.get(&clip.segment_id?)?;
+            Some((clip.timeline_start_ticks as f64 / tps, chapter_title(summary)))
+        })
+        .collect();
+    if let Some(first) = points.first_mut() {
+        first.0 = 0.0;
+    }
+    let from_clips = merge_short_chapters(points);
+    Ok(if from_clips.len() >= MIN_CHAPTERS { from_clips } else { Vec::new() })
+}
+
+/// Drop chapter points that start less than MIN_CHAPTER_SECONDS after the previous one
+fn merge_short_chapters(points: Vec<(f64, String)>) -> Vec<Chapter> {
+    let mut chapters: Vec<Chapter> = Vec::new();
+    for (start_seconds, title) in points {
+        if chapters.last().is_some_and(|last| start_seconds - last.start_seconds < MIN_CHAPTER_SECONDS) {
+            continue;
+        }
+        chapters.push(Chapter { start_seconds, title });
+    }
+    chapters
+}
+
+/// First sentence of a summary, cut at a word boundary
+fn chapter_title(summary: &str) -> String {
+    let sentence = summary.split(['.', '!', '?']).next().unwrap_or(summary).trim();
+    if sentence.chars().count() <= MAX_CHAPTER_TITLE_CHARS {
+        return sentence.to_string();
+    }
+    let mut title = String::new();
+    for word in sentence.split_whitespace() {
+        if title.chars().count() + word.chars().count() + 1 > MAX_CHAPTER_TITLE_CHARS {
+            break;
+        }
+        if !title.is_empty() {
+            title.push(' ');
+        }
+        title.push_str(word);
+    }
+    format!("{}…", title)
+}
+
+/// "m:ss" or "h:mm:ss", as YouTube expects in descriptions
+pub(crate) fn format_timestamp(seconds: f64) -> String {
+    let total = seconds.floor() as i64;
+    let (hours, minutes, secs) = (total / 3600, (total % 3600) / 60, total % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, secs)
+    } else {
+        format!("{}:{:02}", minutes, secs)
+    }
+}
+
+/// Upload a publication to its provider, resuming its upload session if it has one.
+/// Progress and the outcome are written back to the publication row.
+pub async fn run_publication(db: Arc<Database>, publication_id: i64) {
+    let result = match db.get_publication(publication_id) {
+        Ok(Some(publication)) => match publication.provider.as_str() {
+            "youtube" => youtube::upload(&db, &publication).await,
+            other => Err(anyhow!("Unknown upload provider: {}", other)),
+        },
+        Ok(None) => Err(anyhow!("Publication {} not found", publication_id)),
+        Err(e) => Err(e),
+    };
+    if let Err(e) = result {
+        eprintln!("[UPLOADS] Publication {} failed: {:?}", publication_id, e);
+        let _ = db.fail_publication(publication_id, &e.to_string());
+    }
+}
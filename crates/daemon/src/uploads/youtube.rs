@@ -0,0 +1,203 @@
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use reqwest::StatusCode;
+use tokio::io::{AsyncReadExt, AsyncSeekExt, SeekFrom};
+
+use super::PublishMetadata;
+use crate::db::{Database, Publication};
+
+const TOKEN_URL: &str = "https://oauth2.googleapis.com/token";
+const UPLOAD_URL: &str = "https://www.googleapis.com/upload/youtube/v3/videos?uploadType=resumable&part=snippet,status";
+/// Bytes per PUT; YouTube requires chunks in multiples of 256 KiB
+const CHUNK_SIZE: u64 = 32 * 256 * 1024;
+/// Refresh access tokens this long before they expire
+const TOKEN_REFRESH_MARGIN_SECS: i64 = 60;
+/// Title and description limits enforced by the YouTube API
+const MAX_TITLE_CHARS: usize = 100;
+const MAX_DESCRIPTION_BYTES: usize = 5000;
+
+/// A valid access token, refreshed with the stored refresh token when needed
+async fn access_token(db: &Database) -> Result<String> {
+    let credentials = db
+        .get_oauth_credentials("youtube")?
+        .ok_or_else(|| anyhow!("No YouTube credentials; PUT /api/uploads/credentials/youtube first"))?;
+
+    let still_valid = credentials
+        .expires_at
+        .as_deref()
+        .and_then(|e| DateTime::parse_from_rfc3339(e).ok())
+        .is_some_and(|e| e.with_timezone(&Utc) > Utc::now() + Duration::seconds(TOKEN_REFRESH_MARGIN_SECS));
+    if let (Some(token), true) = (credentials.access_token.clone(), still_valid) {
+        return Ok(token);
+    }
+
+    let response = reqwest::Client::new()
+        .post(TOKEN_URL)
+        .form(&[
+            ("client_id", credentials.client_id.as_str()),
+            ("client_secret", credentials.client_secret.as_str()),
+            ("refresh_token", credentials.refresh_token.as_str()),
+            ("grant_type", "refresh_token"),
+        ])
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(anyhow!("Token refresh failed ({}): {}", status, body));
+    }
+    let body: serde_json::Value = response.json().await?;
+    let token = body["access_token"]
+        .as_str()
+        .ok_or_else(|| anyhow!("Token response had no access_token"))?
+        .to_string();
+    let expires_at = Utc::now() + Duration::seconds(body["expires_in"].as_i64().unwrap_or(3600));
+    db.update_oauth_access_token("youtube", &token, &expires_at.to_rfc3339())?;
+    Ok(token)
+}
+
+/// Open a resumable upload session; returns the session URL
+async fn start_session(token: &str, metadata: &PublishMetadata, total_bytes: u64) -> Result<String> {
+    let title: String = metadata.title.chars().take(MAX_TITLE_CHARS).collect();
+    let mut description = metadata.description.clone();
+    while description.len() > MAX_DESCRIPTION_BYTES {
+        description.pop();
+    }
+    let response = reqwest::Client::new()
+        .post(UPLOAD_URL)
+        .bearer_auth(token)
+        .header("X-Upload-Content-Type", "video/*")
+        .header("X-Upload-Content-Length", total_bytes.to_string())
+        .json(&serde_json::json!({
+            "snippet": {
+                "title": title,
+                "description": description,
+                "tags": metadata.tags,
+            },
+            "status": { "privacyStatus": metadata.privacy },
+        }))
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(anyhow!("Couldn't start YouTube upload ({}): {}", status, body));
+    }
+    response
+        .headers()
+        .get(reqwest::header::LOCATION)
+        .and_then(|l| l.to_str().ok())
+        .map(String::from)
+        .ok_or_else(|| anyhow!("YouTube didn't return an upload session URL"))
+}
+
+/// Where an upload session stands
+enum SessionState {
+    /// Bytes the server already has
+    Incomplete(u64),
+    /// The upload already finished; holds the video id
+    Complete(String),
+    /// The session expired or was never valid; start a new one
+    Gone,
+}
+
+/// Ask the server how much of the file it has (used to resume after an interruption)
+async fn session_state(token: &str, upload_url: &str, total_bytes: u64) -> Result<SessionState> {
+    let response = reqwest::Client::new()
+        .put(upload_url)
+        .bearer_auth(token)
+        .header(reqwest::header::CONTENT_RANGE, format!("bytes */{}", total_bytes))
+        .header(reqwest::header::CONTENT_LENGTH, "0")
+        .send()
+        .await?;
+    Ok(match response.status() {
+        StatusCode::PERMANENT_REDIRECT => SessionState::Incomplete(received_bytes(&response)),
+        StatusCode::OK | StatusCode::CREATED => SessionState::Complete(video_id(response).await?),
+        StatusCode::NOT_FOUND | StatusCode::GONE => SessionState::Gone,
+        status => return Err(anyhow!("Couldn't query YouTube upload session ({})", status)),
+    })
+}
+
+/// Bytes received so far, from a 308's `Range: bytes=0-N` header
+fn received_bytes(response: &reqwest::Response) -> u64 {
+    response
+        .headers()
+        .get(reqwest::header::RANGE)
+        .and_then(|r| r.to_str().ok())
+        .and_then(|r| r.rsplit('-').next())
+        .and_then(|end| end.parse::<u64>().ok())
+        .map(|end| end + 1)
+        .unwrap_or(0)
+}
+
+async fn video_id(response: reqwest::Response) -> Result<String> {
+    let body: serde_json::Value = response.json().await?;
+    body["id"]
+        .as_str()
+        .map(String::from)
+        .ok_or_else(|| anyhow!("YouTube's upload response had no video id"))
+}
+
+/// Upload (or resume uploading) a publication's file with the resumable upload protocol
+pub async fn upload(db: &Database, publication: &Publication) -> Result<()> {
+    let metadata: PublishMetadata = serde_json::from_value(publication.metadata.clone())?;
+    let mut file = tokio::fs::File::open(&publication.file_path)
+        .await
+        .with_context(|| format!("Couldn't open {}", publication.file_path))?;
+    let total_bytes = file.metadata().await?.len();
+    let token = access_token(db).await?;
+
+    let resumed = match publication.upload_url.as_deref() {
+        Some(url) => match session_state(&token, url, total_bytes).await? {
+            SessionState::Incomplete(offset) => Some((url.to_string(), offset)),
+            SessionState::Complete(id) => {
+                db.complete_publication(publication.id, &id, &format!("https://youtu.be/{}", id))?;
+                return Ok(());
+            }
+            SessionState::Gone => None,
+        },
+        None => None,
+    };
+    let (upload_url, mut offset) = match resumed {
+        Some(session) => session,
+        None => (start_session(&token, &metadata, total_bytes).await?, 0),
+    };
+    db.update_publication_progress(publication.id, "uploading", offset as i64, Some(&upload_url))?;
+
+    let client = reqwest::Client::new();
+    loop {
+        let chunk_len = CHUNK_SIZE.min(total_bytes - offset);
+        let mut chunk = vec![0u8; chunk_len as usize];
+        file.seek(SeekFrom::Start(offset)).await?;
+        file.read_exact(&mut chunk).await?;
+
+        // Tokens last an hour; long uploads outlive them
+        let token = access_token(db).await?;
+        let response = client
+            .put(&upload_url)
+            .bearer_auth(&token)
+            .header(
+                reqwest::header::CONTENT_RANGE,
+                format!("bytes {}-{}/{}", offset, offset + chunk_len.max(1) - 1, total_bytes),
+            )
+            .body(chunk)
+            .send()
+            .await?;
+
+        match response.status() {
+            StatusCode::PERMANENT_REDIRECT => {
+                offset = received_bytes(&response);
+                db.update_publication_progress(publication.id, "uploading", offset as i64, None)?;
+            }
+            StatusCode::OK | StatusCode::CREATED => {
+                let id = video_id(response).await?;
+                db.complete_publication(publication.id, &id, &format!("https://youtu.be/{}", id))?;
+                return Ok(());
+            }
+            status => {
+                let body = response.text().await.unwrap_or_default();
+                return Err(anyhow!("YouTube upload failed at byte {} ({}): {}", offset, status, body));
+            }
+        }
+    }
+}
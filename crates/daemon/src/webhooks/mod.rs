@@ -0,0 +1,190 @@
+use chrono::Utc;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::db::{Database, Webhook};
+use crate::events::outbox::WEBHOOKS_CONSUMER;
+use crate::events::Event;
+use crate::jobs::{JobManager, JobStatus};
+use crate::storage::s3::hmac_sha256;
+
+/// Events a webhook can subscribe to
+pub const EVENTS: &[&str] = &[
+    "import.completed",
+    "import.failed",
+    "analysis.completed",
+    "proposal.ready",
+    "export.succeeded",
+    "export.failed",
+];
+
+/// Waits before each retry of a failed delivery
+const RETRY_DELAYS_SECS: &[u64] = &[5, 30, 120];
+const DELIVERY_TIMEOUT_SECS: u64 = 10;
+
+/// Outcome of one delivery attempt
+#[derive(Debug, Clone, Serialize)]
+pub struct Delivery {
+    /// HTTP status, when the receiver answered at all
+    pub status: Option<u16>,
+    pub error: Option<String>,
+}
+
+impl Delivery {
+    pub fn succeeded(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// POSTs webhook payloads for pipeline events until the event bus goes away, starting with any
+/// stored while the daemon was down. An event counts as handled once its deliveries are
+/// dispatched; their retries don't survive a restart.
+pub async fn webhook_event_loop(db: Arc<Database>, job_manager: Arc<JobManager>) {
+    let mut subscription = match job_manager.events().subscribe_as(WEBHOOKS_CONSUMER) {
+        Ok(subscription) => subscription,
+        Err(e) => {
+            eprintln!("[WEBHOOKS] Failed to resume from the events outbox, delivery not started: {:?}", e);
+            return;
+        }
+    };
+    while let Some(published) = subscription.recv().await {
+        if let Some((project_id, name, data)) = webhook_event(&job_manager, &published.event) {
+            dispatch(&db, project_id, name, data);
+        }
+        subscription.ack(&published);
+    }
+}
+
+/// Webhook event name, project and data for a bus event; None for events webhooks don't cover
+fn webhook_event(job_manager: &JobManager, event: &Event) -> Option<(i64, &'static str, Value)> {
+    match event {
+        Event::AnalysisComplete { asset_id, readiness, project_id } => Some((
+            *project_id,
+            "analysis.completed",
+            json!({ "asset_id": asset_id, "readiness": readiness }),
+        )),
+        Event::ProposalReady { project_id, proposal_id } => {
+            Some((*project_id, "proposal.ready", json!({ "proposal_id": proposal_id })))
+        }
+        Event::JobStateChanged { job_id, job_type, status, project_id, .. } => {
+            let name = match (job_type.as_str(), status) {
+                ("ImportRaw", JobStatus::Completed) => "import.completed",
+                ("ImportRaw", JobStatus::Failed) => "import.failed",
+                ("Export", JobStatus::Completed) => "export.succeeded",
+                ("Export", JobStatus::Failed) => "export.failed",
+                _ => return None,
+            };
+            let project_id = (*project_id)?;
+            let payload = job_manager.get_job(*job_id).ok()??.payload?;
+
+            let mut data = json!({ "job_id": job_id });
+            for field in ["file_path", "folder_path", "out_path", "preset"] {
+                if let Some(value) = payload.get(field).filter(|v| !v.is_null()) {
+                    data[field] = value.clone();
+                }
+            }
+            Some((project_id, name, data))
+        }
+        Event::TimelineApplied { .. } => None,
+    }
+}
+
+/// Send an event to every project webhook subscribed to it, each in the background
+pub fn dispatch(db: &Arc<Database>, project_id: i64, event: &str, data: Value) {
+    let webhooks = match db.list_webhooks(project_id) {
+        Ok(webhooks) => webhooks,
+        Err(e) => {
+            eprintln!("[WEBHOOKS] Failed to load webhooks for project {}: {:?}", project_id, e);
+            return;
+        }
+    };
+    let payload = build_payload(project_id, event, data);
+    for webhook in webhooks.into_iter().filter(|w| w.events.iter().any(|e| e == event)) {
+        tokio::spawn(deliver_with_retries(db.clone(), webhook, payload.clone()));
+    }
+}
+
+/// The JSON body every delivery carries
+pub fn build_payload(project_id: i64, event: &str, data: Value) -> Value {
+    json!({
+        "id": Uuid::new_v4().to_string(),
+        "event": event,
+        "project_id": project_id,
+        "created_at": Utc::now().to_rfc3339(),
+        "data": data,
+    })
+}
+
+async fn deliver_with_retries(db: Arc<Database>, webhook: Webhook, payload: Value) {
+    let attempts = std::iter::once(0).chain(RETRY_DELAYS_SECS.iter().copied());
+    for (attempt, delay) in attempts.enumerate() {
+        if delay > 0 {
+            tokio::time::sleep(Duration::from_secs(delay)).await;
+        }
+        let delivery = deliver(&webhook, &payload).await;
+        let _ = db.record_webhook_delivery(webhook.id, delivery.status.map(i64::from), delivery.error.as_deref());
+        if delivery.succeeded() {
+            return;
+        }
+        eprintln!(
+            "[WEBHOOKS] Delivery {} of {} to {} failed (attempt {}): {}",
+            payload["id"].as_str().unwrap_or_default(),
+            payload["event"].as_str().unwrap_or_default(),
+            webhook.url,
+            attempt + 1,
+            delivery.error.as_deref().unwrap_or_default()
+        );
+    }
+}
+
+/// POST a payload to a webhook once. Receivers verify X-Vibecut-Signature against the raw body
+/// and X-Vibecut-Timestamp (see [`signature`]); any 2xx counts as delivered.
+pub async fn deliver(webhook: &Webhook, payload: &Value) -> Delivery {
+    let body = payload.to_string();
+    let timestamp = Utc::now().timestamp();
+    let client = match reqwest::Client::builder()
+        .timeout(Duration::from_secs(DELIVERY_TIMEOUT_SECS))
+        .build()
+    {
+        Ok(client) => client,
+        Err(e) => return Delivery { status: None, error: Some(e.to_string()) },
+    };
+
+    let response = client
+        .post(&webhook.url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header("X-Vibecut-Event", payload["event"].as_str().unwrap_or_default())
+        .header("X-Vibecut-Delivery", payload["id"].as_str().unwrap_or_default())
+        .header("X-Vibecut-Timestamp", timestamp.to_string())
+        .header("X-Vibecut-Signature", signature(&webhook.secret, timestamp, &body))
+        .body(body)
+        .send()
+        .await;
+
+    match response {
+        Ok(response) if response.status().is_success() => Delivery {
+            status: Some(response.status().as_u16()),
+            error: None,
+        },
+        Ok(response) => Delivery {
+            status: Some(response.status().as_u16()),
+            error: Some(format!("Receiver answered {}", response.status())),
+        },
+        Err(e) => Delivery { status: None, error: Some(e.to_string()) },
+    }
+}
+
+/// `sha256=` + hex HMAC-SHA256 of `"<timestamp>.<body>"`, keyed with the webhook secret. Signing
+/// the timestamp lets receivers reject replayed deliveries.
+pub fn signature(secret: &str, timestamp: i64, body: &str) -> String {
+    let message = format!("{}.{}", timestamp, body);
+    format!("sha256={}", hex::encode(hmac_sha256(secret.as_bytes(), message.as_bytes())))
+}
+
+/// Random signing secret for webhooks created without one
+pub fn generate_secret() -> String {
+    format!("whsec_{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
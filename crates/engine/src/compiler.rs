@@ -1,29 +1,60 @@
 use crate::timeline::*;
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 // EditPlan structure (simplified - full definition would match edit_plan.json schema)
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EditPlan {
     pub sections: Vec<EditSection>,
     pub constraints: EditConstraints,
+    /// Changes the planner would make but left to the user
+    #[serde(default)]
+    pub suggestions: Vec<PlanSuggestion>,
 }
 
+/// A change to one planned clip that the user can choose to make
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanSuggestion {
+    /// "slow_motion"
+    pub kind: String,
+    pub section_type: String,
+    pub asset_id: i64,
+    pub segment_id: Option<i64>,
+    /// Playback speed suggested for the clip
+    pub speed: f64,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EditSection {
     pub section_type: String,
     pub target_duration: i64,
     pub events: Vec<EditEvent>,
+    /// User pins the planner applied to this section
+    pub constraints: Option<SectionConstraints>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
 pub enum EditEvent {
     Clip {
         asset_id: i64,
+        /// Segment the clip was cut from, when the planner knows it
+        #[serde(default)]
+        segment_id: Option<i64>,
         in_ticks: i64,
         out_ticks: i64,
         timeline_start_ticks: i64,
         track_id: i64,
+        /// Playback speed, below 1 for slow motion (None plays at normal speed)
+        #[serde(default)]
+        speed: Option<f64>,
     },
     Caption {
         text: String,
         template_id: Option<i64>,
+        #[serde(default)]
+        style: Option<CaptionStyle>,
         start_ticks: i64,
         end_ticks: i64,
     },
@@ -35,11 +66,177 @@ pub enum EditEvent {
     },
 }
 
+impl EditEvent {
+    /// Length a clip event takes on the timeline: its source range played at its speed
+    pub fn clip_timeline_ticks(&self) -> Option<i64> {
+        match self {
+            EditEvent::Clip { in_ticks, out_ticks, speed, .. } => {
+                let speed = speed.filter(|s| *s > 0.0).unwrap_or(1.0);
+                Some(((out_ticks - in_ticks) as f64 / speed).round() as i64)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EditConstraints {
     pub target_length: Option<i64>,
     pub vibe: Option<String>,
     pub captions_on: bool,
     pub music_on: bool,
+    /// Per-section pins ("intro max 5s", "body only day 2", "outro must include clip X")
+    #[serde(default)]
+    pub section_overrides: Vec<SectionConstraints>,
+    /// Pre-roll added before each planned clip, in ticks (planner default when unset)
+    #[serde(default)]
+    pub handle_pre_ticks: Option<i64>,
+    /// Post-roll added after each planned clip, in ticks (planner default when unset)
+    #[serde(default)]
+    pub handle_post_ticks: Option<i64>,
+    /// Whether high-motion scenic clips shot at a high frame rate play in slow motion
+    #[serde(default)]
+    pub slow_motion: SlowMotionMode,
+}
+
+/// What the planner does with clips that could play in slow motion
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SlowMotionMode {
+    Off,
+    /// Leave the clips at normal speed and list them in the plan's suggestions
+    #[default]
+    Suggest,
+    /// Slow the clips down
+    Auto,
+}
+
+/// Constraints pinned to one plan section
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SectionConstraints {
+    /// Section the pin applies to ("intro", "body", "outro", or a template section like "drop")
+    pub section_type: String,
+    pub min_duration: Option<i64>,
+    pub max_duration: Option<i64>,
+    /// Only use clips from these assets
+    pub allowed_asset_ids: Option<Vec<i64>>,
+    /// Only use clips from the Nth shooting day (1-based, by capture date)
+    pub capture_day: Option<u32>,
+    /// Segments that must appear in this section
+    pub required_segment_ids: Vec<i64>,
+}
+
+/// A pin that can't be satisfied, with a message suitable for showing to the user
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConstraintConflict {
+    pub section_type: Option<String>,
+    pub message: String,
+}
+
+impl ConstraintConflict {
+    pub fn new(section_type: Option<&str>, message: impl Into<String>) -> Self {
+        ConstraintConflict {
+            section_type: section_type.map(|s| s.to_string()),
+            message: message.into(),
+        }
+    }
+}
+
+impl EditConstraints {
+    /// Check section pins for conflicts that don't depend on the footage
+    /// (the planner checks the rest, e.g. whether required segments exist)
+    pub fn validate(&self) -> Result<(), Vec<ConstraintConflict>> {
+        let mut conflicts = Vec::new();
+
+        if self.handle_pre_ticks.is_some_and(|t| t < 0) || self.handle_post_ticks.is_some_and(|t| t < 0) {
+            conflicts.push(ConstraintConflict::new(None, "clip handles can't be negative"));
+        }
+
+        for (idx, pin) in self.section_overrides.iter().enumerate() {
+            let section = Some(pin.section_type.as_str());
+            if pin.section_type.trim().is_empty() {
+                conflicts.push(ConstraintConflict::new(None, "section override is missing section_type"));
+                continue;
+            }
+            if self.section_overrides[..idx].iter().any(|p| p.section_type == pin.section_type) {
+                conflicts.push(ConstraintConflict::new(
+                    section,
+                    format!("section '{}' is pinned more than once", pin.section_type),
+                ));
+            }
+            if let (Some(min), Some(max)) = (pin.min_duration, pin.max_duration) {
+                if min > max {
+                    conflicts.push(ConstraintConflict::new(
+                        section,
+                        format!(
+                            "'{}' min duration ({:.1}s) is longer than its max duration ({:.1}s)",
+                            pin.section_type,
+                            min as f64 / TICKS_PER_SECOND as f64,
+                            max as f64 / TICKS_PER_SECOND as f64,
+                        ),
+                    ));
+                }
+            }
+            if pin.min_duration.is_some_and(|d| d < 0) || pin.max_duration.is_some_and(|d| d <= 0) {
+                conflicts.push(ConstraintConflict::new(
+                    section,
+                    format!("'{}' durations must be positive", pin.section_type),
+                ));
+            }
+            if pin.allowed_asset_ids.as_ref().is_some_and(|ids| ids.is_empty()) {
+                conflicts.push(ConstraintConflict::new(
+                    section,
+                    format!("'{}' allows no assets, so it can't contain any clips", pin.section_type),
+                ));
+            }
+            if pin.capture_day == Some(0) {
+                conflicts.push(ConstraintConflict::new(
+                    section,
+                    format!("'{}' capture_day is 1-based (day 1 is the first shooting day)", pin.section_type),
+                ));
+            }
+            for segment_id in &pin.required_segment_ids {
+                if let Some(other) = self.section_overrides[..idx]
+                    .iter()
+                    .find(|p| p.required_segment_ids.contains(segment_id))
+                {
+                    conflicts.push(ConstraintConflict::new(
+                        section,
+                        format!(
+                            "segment {} is required in both '{}' and '{}'",
+                            segment_id, other.section_type, pin.section_type
+                        ),
+                    ));
+                }
+            }
+        }
+
+        if let Some(target) = self.target_length {
+            let min_total: i64 = self.section_overrides.iter().filter_map(|p| p.min_duration).sum();
+            if min_total > target {
+                conflicts.push(ConstraintConflict::new(
+                    None,
+                    format!(
+                        "section minimums add up to {:.1}s, more than the {:.1}s target length",
+                        min_total as f64 / TICKS_PER_SECOND as f64,
+                        target as f64 / TICKS_PER_SECOND as f64,
+                    ),
+                ));
+            }
+        }
+
+        if conflicts.is_empty() {
+            Ok(())
+        } else {
+            Err(conflicts)
+        }
+    }
+
+    /// Pin for a section, if any
+    pub fn section_override(&self, section_type: &str) -> Option<&SectionConstraints> {
+        self.section_overrides.iter().find(|p| p.section_type == section_type)
+    }
 }
 
 pub fn compile_edit_plan(plan: EditPlan, settings: ProjectSettings) -> Timeline {
@@ -70,12 +267,15 @@ pub fn compile_edit_plan(plan: EditPlan, settings: ProjectSettings) -> Timeline
             match event {
                 EditEvent::Clip {
                     asset_id,
+                    segment_id,
                     in_ticks,
                     out_ticks,
                     timeline_start_ticks,
                     track_id,
+                    speed,
                 } => {
                     let track = tracks.iter_mut().find(|t| t.id == track_id);
+                    let speed = speed.unwrap_or(1.0);
                     if let Some(track) = track {
                         track.clips.push(ClipInstance {
                             id: Uuid::new_v4().to_string(),
@@ -83,14 +283,27 @@ pub fn compile_edit_plan(plan: EditPlan, settings: ProjectSettings) -> Timeline
                             in_ticks,
                             out_ticks,
                             timeline_start_ticks,
-                            speed: 1.0,
+                            speed,
                             track_id,
+                            locked: false,
+                            segment_id,
+                            // Compiled plans come from the planner, not the user
+                            agent_placed: true,
+                            transform: None,
+                            ken_burns: None,
+                            audio_enhancement: None,
+                            fit: None,
+                            split_audio: None,
+                            frame_rate_conversion: None,
+                            // Slowed-down sound drones; slow motion plays under the music
+                            audio_muted: speed < 1.0,
                         });
                     }
                 }
                 EditEvent::Caption {
                     text,
                     template_id,
+                    style,
                     start_ticks,
                     end_ticks,
                 } => {
@@ -99,6 +312,7 @@ pub fn compile_edit_plan(plan: EditPlan, settings: ProjectSettings) -> Timeline
                         end_ticks,
                         text,
                         template_id,
+                        style,
                     });
                 }
                 EditEvent::Music {
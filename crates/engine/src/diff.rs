@@ -1,14 +1,80 @@
-use crate::timeline::Timeline;
+use crate::timeline::{ClipInstance, Timeline};
 use serde_json::Value;
+use std::collections::HashMap;
+
+fn clip_duration(clip: &ClipInstance) -> i64 {
+    clip.out_ticks - clip.in_ticks
+}
+
+fn primary_duration(timeline: &Timeline) -> i64 {
+    timeline
+        .tracks
+        .iter()
+        .find(|t| t.id == 1)
+        .map(|t| t.clips.iter().map(clip_duration).sum())
+        .unwrap_or(0)
+}
 
 pub fn generate_diff(from: &Timeline, to: &Timeline) -> Value {
-    // Simplified diff generation - in production, would generate a detailed diff JSON
-    // For now, return a placeholder structure
+    // Clip-level diff keyed by clip id: added, removed, and changed (trimmed/moved) clips
+    let from_clips: HashMap<&str, &ClipInstance> = from
+        .tracks
+        .iter()
+        .flat_map(|t| t.clips.iter())
+        .map(|c| (c.id.as_str(), c))
+        .collect();
+    let to_clips: HashMap<&str, &ClipInstance> = to
+        .tracks
+        .iter()
+        .flat_map(|t| t.clips.iter())
+        .map(|c| (c.id.as_str(), c))
+        .collect();
+
+    let removed: Vec<&str> = from_clips.keys().filter(|id| !to_clips.contains_key(*id)).copied().collect();
+    let added: Vec<&str> = to_clips.keys().filter(|id| !from_clips.contains_key(*id)).copied().collect();
+    let changed: Vec<Value> = to_clips
+        .iter()
+        .filter_map(|(id, after)| {
+            let before = from_clips.get(id)?;
+            let same = before.in_ticks == after.in_ticks
+                && before.out_ticks == after.out_ticks
+                && before.timeline_start_ticks == after.timeline_start_ticks
+                && before.track_id == after.track_id;
+            (!same).then(|| {
+                serde_json::json!({
+                    "clip_id": id,
+                    "before": {
+                        "in_ticks": before.in_ticks,
+                        "out_ticks": before.out_ticks,
+                        "timeline_start_ticks": before.timeline_start_ticks,
+                        "track_id": before.track_id,
+                    },
+                    "after": {
+                        "in_ticks": after.in_ticks,
+                        "out_ticks": after.out_ticks,
+                        "timeline_start_ticks": after.timeline_start_ticks,
+                        "track_id": after.track_id,
+                    },
+                })
+            })
+        })
+        .collect();
+
     serde_json::json!({
         "type": "timeline_diff",
         "tracks_changed": to.tracks.len() != from.tracks.len(),
-        "clips_changed": true, // Placeholder
+        "clips_changed": !removed.is_empty() || !added.is_empty() || !changed.is_empty(),
         "captions_changed": to.captions.len() != from.captions.len(),
         "music_changed": to.music.len() != from.music.len(),
+        "narration_changed": to
+            .narration
+            .iter()
+            .map(|n| (n.start_ticks, n.end_ticks, &n.track_path))
+            .ne(from.narration.iter().map(|n| (n.start_ticks, n.end_ticks, &n.track_path))),
+        "removed_clip_ids": removed,
+        "added_clip_ids": added,
+        "changed_clips": changed,
+        "primary_duration_before_ticks": primary_duration(from),
+        "primary_duration_after_ticks": primary_duration(to),
     })
 }
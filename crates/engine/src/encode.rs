@@ -0,0 +1,318 @@
+use crate::render::output_duration_seconds;
+use crate::timeline::Timeline;
+use serde::{Deserialize, Serialize};
+
+/// Video codecs exports can be encoded with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VideoCodec {
+    H264,
+    H265,
+    Av1,
+}
+
+impl VideoCodec {
+    /// ffmpeg encoder used for the codec
+    pub fn encoder(&self) -> &'static str {
+        match self {
+            VideoCodec::H264 => "libx264",
+            VideoCodec::H265 => "libx265",
+            VideoCodec::Av1 => "libaom-av1",
+        }
+    }
+
+    /// Highest (worst) CRF the encoder accepts
+    pub fn max_crf(&self) -> u8 {
+        match self {
+            VideoCodec::H264 | VideoCodec::H265 => 51,
+            VideoCodec::Av1 => 63,
+        }
+    }
+
+    /// Speed presets the encoder accepts: x264/x265 names, or libaom's cpu-used levels
+    pub fn presets(&self) -> &'static [&'static str] {
+        match self {
+            VideoCodec::H264 | VideoCodec::H265 => &[
+                "ultrafast", "superfast", "veryfast", "faster", "fast", "medium", "slow", "slower", "veryslow",
+            ],
+            VideoCodec::Av1 => &["0", "1", "2", "3", "4", "5", "6", "7", "8"],
+        }
+    }
+
+    fn default_preset(&self) -> &'static str {
+        match self {
+            VideoCodec::H264 | VideoCodec::H265 => "medium",
+            VideoCodec::Av1 => "6",
+        }
+    }
+
+    /// CRF that looks about the same across codecs (x264 23 ~ x265 28 ~ libaom 32)
+    fn default_crf(&self) -> u8 {
+        match self {
+            VideoCodec::H264 => 23,
+            VideoCodec::H265 => 28,
+            VideoCodec::Av1 => 32,
+        }
+    }
+
+    /// Bits per pixel per frame a typical edit needs at the default CRF, for size estimates
+    fn bits_per_pixel(&self) -> f64 {
+        match self {
+            VideoCodec::H264 => 0.08,
+            VideoCodec::H265 => 0.05,
+            VideoCodec::Av1 => 0.04,
+        }
+    }
+}
+
+/// Target bitrates accepted, in kbit/s
+const MIN_BITRATE_KBPS: u32 = 100;
+const MAX_BITRATE_KBPS: u32 = 200_000;
+const MIN_AUDIO_BITRATE_KBPS: u32 = 32;
+const MAX_AUDIO_BITRATE_KBPS: u32 = 512;
+/// Bitrate halves (roughly) every this many CRF steps
+const CRF_STEPS_PER_HALVING: f64 = 6.0;
+
+/// How an export is encoded: constant quality (`crf`) or a target `bitrate_kbps`, optionally
+/// in two passes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EncoderSettings {
+    pub codec: VideoCodec,
+    pub crf: Option<u8>,
+    pub bitrate_kbps: Option<u32>,
+    /// Encoder speed preset (see `VideoCodec::presets`)
+    pub preset: String,
+    pub two_pass: bool,
+    pub audio_bitrate_kbps: u32,
+}
+
+impl Default for EncoderSettings {
+    fn default() -> Self {
+        EncoderSettings {
+            codec: VideoCodec::H264,
+            crf: Some(23),
+            bitrate_kbps: None,
+            preset: "medium".to_string(),
+            two_pass: false,
+            audio_bitrate_kbps: 128,
+        }
+    }
+}
+
+/// Built-in export presets by name; "mp4" is the default
+pub const EXPORT_PRESETS: &[&str] = &["mp4", "mov", "high_quality", "web", "hevc", "av1"];
+
+/// Changes to a preset's settings for one export; fields left out keep the preset's values
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct EncoderOverrides {
+    pub codec: Option<VideoCodec>,
+    pub crf: Option<u8>,
+    pub bitrate_kbps: Option<u32>,
+    pub preset: Option<String>,
+    pub two_pass: Option<bool>,
+    pub audio_bitrate_kbps: Option<u32>,
+}
+
+impl EncoderSettings {
+    /// Settings of a built-in export preset (None for unknown names)
+    pub fn for_preset(name: &str) -> Option<EncoderSettings> {
+        let base = EncoderSettings::default();
+        Some(match name {
+            "mp4" | "mov" => base,
+            "high_quality" => EncoderSettings {
+                crf: Some(18),
+                preset: "slow".to_string(),
+                audio_bitrate_kbps: 192,
+                ..base
+            },
+            "web" => EncoderSettings {
+                crf: None,
+                bitrate_kbps: Some(6000),
+                two_pass: true,
+                ..base
+            },
+            "hevc" => EncoderSettings::for_codec(VideoCodec::H265),
+            "av1" => EncoderSettings::for_codec(VideoCodec::Av1),
+            _ => return None,
+        })
+    }
+
+    fn for_codec(codec: VideoCodec) -> EncoderSettings {
+        EncoderSettings {
+            codec,
+            crf: Some(codec.default_crf()),
+            preset: codec.default_preset().to_string(),
+            ..EncoderSettings::default()
+        }
+    }
+
+    /// Apply per-export overrides. Switching codec resets the speed preset and CRF to the new
+    /// codec's defaults; asking for a bitrate drops the CRF and vice versa.
+    pub fn with_overrides(mut self, overrides: &EncoderOverrides) -> EncoderSettings {
+        if let Some(codec) = overrides.codec.filter(|c| *c != self.codec) {
+            self.codec = codec;
+            self.preset = codec.default_preset().to_string();
+            if self.crf.is_some() {
+                self.crf = Some(codec.default_crf());
+            }
+        }
+        match (overrides.crf, overrides.bitrate_kbps) {
+            (Some(crf), None) => {
+                self.crf = Some(crf);
+                self.bitrate_kbps = None;
+            }
+            (None, Some(bitrate)) => {
+                self.bitrate_kbps = Some(bitrate);
+                self.crf = None;
+            }
+            (crf, bitrate) => {
+                self.crf = crf.or(self.crf);
+                self.bitrate_kbps = bitrate.or(self.bitrate_kbps);
+            }
+        }
+        if let Some(preset) = &overrides.preset {
+            self.preset = preset.clone();
+        }
+        if let Some(two_pass) = overrides.two_pass {
+            self.two_pass = two_pass;
+        }
+        if let Some(audio) = overrides.audio_bitrate_kbps {
+            self.audio_bitrate_kbps = audio;
+        }
+        self
+    }
+
+    /// Reject combinations the encoders can't do, and containers that can't hold the codec
+    pub fn validate(&self, container: Option<&str>) -> Result<(), String> {
+        match (self.crf, self.bitrate_kbps) {
+            (Some(_), Some(_)) => return Err("Set either crf or bitrate_kbps, not both".to_string()),
+            (None, None) => return Err("Set crf or bitrate_kbps".to_string()),
+            _ => {}
+        }
+        if let Some(crf) = self.crf {
+            if crf > self.codec.max_crf() {
+                return Err(format!("crf for {} must be 0-{}", self.codec.encoder(), self.codec.max_crf()));
+            }
+        }
+        if let Some(bitrate) = self.bitrate_kbps {
+            if !(MIN_BITRATE_KBPS..=MAX_BITRATE_KBPS).contains(&bitrate) {
+                return Err(format!("bitrate_kbps must be {}-{}", MIN_BITRATE_KBPS, MAX_BITRATE_KBPS));
+            }
+        }
+        if self.two_pass && self.bitrate_kbps.is_none() {
+            return Err("Two-pass encoding needs a target bitrate_kbps".to_string());
+        }
+        if !self.codec.presets().contains(&self.preset.as_str()) {
+            return Err(format!(
+                "Unknown preset '{}' for {} (expected one of {})",
+                self.preset,
+                self.codec.encoder(),
+                self.codec.presets().join(", ")
+            ));
+        }
+        if !(MIN_AUDIO_BITRATE_KBPS..=MAX_AUDIO_BITRATE_KBPS).contains(&self.audio_bitrate_kbps) {
+            return Err(format!(
+                "audio_bitrate_kbps must be {}-{}",
+                MIN_AUDIO_BITRATE_KBPS, MAX_AUDIO_BITRATE_KBPS
+            ));
+        }
+        if let Some(container) = container {
+            if !["mp4", "m4v", "mov", "mkv"].contains(&container) {
+                return Err(format!("Can't export to .{} (use .mp4, .m4v, .mov or .mkv)", container));
+            }
+            if self.codec == VideoCodec::Av1 && container == "mov" {
+                return Err("AV1 can't be stored in .mov; export to .mp4 or .mkv".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// ffmpeg video and audio encoder arguments; `pass` is Some(1 | 2) for two-pass encodes,
+    /// which share the stats at `pass_log`
+    pub fn codec_args(&self, pass: Option<(u8, &str)>) -> Vec<String> {
+        let mut args = vec!["-c:v".to_string(), self.codec.encoder().to_string()];
+        match self.codec {
+            VideoCodec::H264 | VideoCodec::H265 => args.extend(["-preset".to_string(), self.preset.clone()]),
+            VideoCodec::Av1 => args.extend([
+                "-cpu-used".to_string(),
+                self.preset.clone(),
+                "-row-mt".to_string(),
+                "1".to_string(),
+            ]),
+        }
+        if let Some(crf) = self.crf {
+            args.extend(["-crf".to_string(), crf.to_string()]);
+            // libaom is only constant-quality without a bitrate cap
+            if self.codec == VideoCodec::Av1 {
+                args.extend(["-b:v".to_string(), "0".to_string()]);
+            }
+        } else if let Some(bitrate) = self.bitrate_kbps {
+            args.extend(["-b:v".to_string(), format!("{}k", bitrate)]);
+        }
+        if let Some((pass, log)) = pass {
+            match self.codec {
+                // x265 takes its pass options through its own parameter string
+                VideoCodec::H265 => args.extend([
+                    "-x265-params".to_string(),
+                    format!("pass={}:stats={}", pass, log),
+                ]),
+                _ => args.extend([
+                    "-pass".to_string(),
+                    pass.to_string(),
+                    "-passlogfile".to_string(),
+                    log.to_string(),
+                ]),
+            }
+        }
+        // Apple players only recognise HEVC in MP4/MOV under the hvc1 tag
+        if self.codec == VideoCodec::H265 {
+            args.extend(["-tag:v".to_string(), "hvc1".to_string()]);
+        }
+        args.extend([
+            "-c:a".to_string(),
+            "aac".to_string(),
+            "-b:a".to_string(),
+            format!("{}k", self.audio_bitrate_kbps),
+        ]);
+        args
+    }
+
+    /// Expected video bitrate in kbit/s: the target when there is one, otherwise a typical
+    /// rate for the CRF at this frame size and rate (each 6 CRF steps roughly halve it)
+    pub fn estimated_video_kbps(&self, width: i32, height: i32, fps: f64) -> f64 {
+        if let Some(bitrate) = self.bitrate_kbps {
+            return bitrate as f64;
+        }
+        let crf = self.crf.unwrap_or(self.codec.default_crf()) as f64;
+        let scale = 2f64.powf((self.codec.default_crf() as f64 - crf) / CRF_STEPS_PER_HALVING);
+        width.max(0) as f64 * height.max(0) as f64 * fps.max(0.0) * self.codec.bits_per_pixel() * scale / 1000.0
+    }
+
+    /// Expected size of the timeline's export with these settings, before it's rendered
+    pub fn estimate(&self, timeline: &Timeline) -> SizeEstimate {
+        let settings = &timeline.settings;
+        let duration_seconds = output_duration_seconds(timeline);
+        let video_kbps = self.estimated_video_kbps(settings.resolution.width, settings.resolution.height, settings.fps);
+        let total_kbps = video_kbps + self.audio_bitrate_kbps as f64;
+        SizeEstimate {
+            duration_seconds,
+            video_kbps: video_kbps.round(),
+            audio_kbps: self.audio_bitrate_kbps,
+            size_bytes: (total_kbps * 1000.0 / 8.0 * duration_seconds.max(0.0)).round() as u64,
+            basis: if self.bitrate_kbps.is_some() { "bitrate" } else { "crf" },
+        }
+    }
+}
+
+/// Predicted export size. With a target bitrate it's close to exact; with CRF it's a typical
+/// figure that busy or grainy footage can exceed.
+#[derive(Debug, Clone, Serialize)]
+pub struct SizeEstimate {
+    pub duration_seconds: f64,
+    pub video_kbps: f64,
+    pub audio_kbps: u32,
+    pub size_bytes: u64,
+    /// "bitrate" when the size follows from a target bitrate, "crf" when it's a heuristic
+    pub basis: &'static str,
+}
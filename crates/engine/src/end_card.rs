@@ -0,0 +1,341 @@
+use crate::timeline::ProjectSettings;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Longest end card, in seconds
+const MAX_DURATION_SECONDS: f64 = 30.0;
+/// Sample rate of the card's silent audio
+const SILENCE_SAMPLE_RATE: u32 = 48000;
+
+/// A card appended to the end of an export (a subscribe call to action, social handles),
+/// described as a background and the text and images laid over it. Text, colors and image
+/// paths may use `{placeholders}` that are filled from the project when the card is exported.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EndCard {
+    pub name: String,
+    pub duration_sec: f64,
+    pub background: EndCardBackground,
+    #[serde(default)]
+    pub elements: Vec<EndCardElement>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EndCardBackground {
+    /// A solid color ("black", "#1a1a2e", ...)
+    Color { color: String },
+    /// A video file played from `start_sec` (its last frame is held if it runs out), muted
+    Clip {
+        path: String,
+        #[serde(default)]
+        start_sec: f64,
+    },
+}
+
+/// Something laid over the background. Positions are the element's center, normalized to the
+/// frame (0-1).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EndCardElement {
+    Text {
+        text: String,
+        x: f64,
+        y: f64,
+        /// Font size as a share of the frame height
+        size: f64,
+        #[serde(default = "default_text_color")]
+        color: String,
+        /// Font file to draw with (default: the system's sans-serif font)
+        #[serde(default)]
+        font_file: Option<String>,
+    },
+    /// An image (a logo), scaled to `width` as a share of the frame width
+    Image {
+        path: String,
+        x: f64,
+        y: f64,
+        width: f64,
+        /// Color keyed out of an image without transparency (e.g. a logo's white background)
+        #[serde(default)]
+        key_color: Option<String>,
+    },
+}
+
+fn default_text_color() -> String {
+    "white".to_string()
+}
+
+impl EndCard {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("End card needs a name".to_string());
+        }
+        if !(self.duration_sec > 0.0 && self.duration_sec <= MAX_DURATION_SECONDS) {
+            return Err(format!("End card duration must be between 0 and {}s", MAX_DURATION_SECONDS));
+        }
+        match &self.background {
+            EndCardBackground::Color { color } => validate_color(color)?,
+            EndCardBackground::Clip { path, start_sec } => {
+                if path.is_empty() || !start_sec.is_finite() || *start_sec < 0.0 {
+                    return Err("Background clip needs a path and a start of 0s or later".to_string());
+                }
+            }
+        }
+        for element in &self.elements {
+            let (x, y) = match element {
+                EndCardElement::Text { text, x, y, size, color, .. } => {
+                    if text.trim().is_empty() {
+                        return Err("Text elements need text".to_string());
+                    }
+                    if !(*size > 0.0 && *size <= 0.5) {
+                        return Err(format!("Text size {} must be between 0 and 0.5 of the frame height", size));
+                    }
+                    validate_color(color)?;
+                    (*x, *y)
+                }
+                EndCardElement::Image { path, x, y, width, key_color } => {
+                    if path.is_empty() {
+                        return Err("Image elements need a path".to_string());
+                    }
+                    if !(*width > 0.0 && *width <= 1.0) {
+                        return Err(format!("Image width {} must be between 0 and 1 of the frame width", width));
+                    }
+                    if let Some(color) = key_color {
+                        validate_color(color)?;
+                    }
+                    (*x, *y)
+                }
+            };
+            if !(0.0..=1.0).contains(&x) || !(0.0..=1.0).contains(&y) {
+                return Err(format!("Element position ({}, {}) is outside the frame", x, y));
+            }
+        }
+        Ok(())
+    }
+
+    /// The card with its `{placeholders}` filled from `values`. Elements whose text or path
+    /// uses a placeholder with no value (a handle the project doesn't have) are left out; colors
+    /// without a value fall back to white text on black, as does a background clip.
+    pub fn fill(&self, values: &HashMap<String, String>) -> EndCard {
+        let mut card = self.clone();
+        card.elements = self
+            .elements
+            .iter()
+            .filter_map(|element| {
+                let mut element = element.clone();
+                match &mut element {
+                    EndCardElement::Text { text, color, .. } => {
+                        *text = fill_placeholders(text, values)?;
+                        *color = fill_placeholders(color, values).unwrap_or_else(default_text_color);
+                    }
+                    EndCardElement::Image { path, .. } => *path = fill_placeholders(path, values)?,
+                }
+                Some(element)
+            })
+            .collect();
+        let black = || EndCardBackground::Color { color: "black".to_string() };
+        card.background = match &self.background {
+            EndCardBackground::Color { color } => match fill_placeholders(color, values) {
+                Some(color) => EndCardBackground::Color { color },
+                None => black(),
+            },
+            EndCardBackground::Clip { path, start_sec } => match fill_placeholders(path, values) {
+                Some(path) => EndCardBackground::Clip { path, start_sec: *start_sec },
+                None => black(),
+            },
+        };
+        card
+    }
+
+    /// Files the card reads (background clip and images)
+    pub fn files(&self) -> Vec<&str> {
+        let background = match &self.background {
+            EndCardBackground::Clip { path, .. } => Some(path.as_str()),
+            EndCardBackground::Color { .. } => None,
+        };
+        background
+            .into_iter()
+            .chain(self.elements.iter().filter_map(|e| match e {
+                EndCardElement::Image { path, .. } => Some(path.as_str()),
+                EndCardElement::Text { .. } => None,
+            }))
+            .collect()
+    }
+
+    /// Every path the card reads (background clip, images and fonts), to be checked or
+    /// replaced in place
+    pub fn paths_mut(&mut self) -> Vec<&mut String> {
+        let background = match &mut self.background {
+            EndCardBackground::Clip { path, .. } => Some(path),
+            EndCardBackground::Color { .. } => None,
+        };
+        background
+            .into_iter()
+            .chain(self.elements.iter_mut().filter_map(|e| match e {
+                EndCardElement::Image { path, .. } => Some(path),
+                EndCardElement::Text { font_file, .. } => font_file.as_mut(),
+            }))
+            .collect()
+    }
+}
+
+/// `template` with each `{key}` replaced by its value; None if a key has no (or an empty) value
+fn fill_placeholders(template: &str, values: &HashMap<String, String>) -> Option<String> {
+    let mut filled = String::new();
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        let Some(close) = rest[open..].find('}').map(|c| open + c) else {
+            break;
+        };
+        filled.push_str(&rest[..open]);
+        let value = values.get(rest[open + 1..close].trim()).filter(|v| !v.trim().is_empty())?;
+        filled.push_str(value);
+        rest = &rest[close + 1..];
+    }
+    filled.push_str(rest);
+    Some(filled)
+}
+
+/// Colors are ffmpeg color names or hex ("#RRGGBB", "0xRRGGBBAA"), optionally with "@alpha",
+/// or a `{placeholder}`
+pub(crate) fn validate_color(color: &str) -> Result<(), String> {
+    let placeholder = color.len() > 2
+        && color.starts_with('{')
+        && color.ends_with('}')
+        && color[1..color.len() - 1].chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if placeholder {
+        return Ok(());
+    }
+    if color.is_empty() || !color.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '#' | '@' | '.')) {
+        return Err(format!("Invalid color {:?}", color));
+    }
+    Ok(())
+}
+
+/// Inputs and filters that render an end card, ending in `[ecv]` and `[eca]` (silence)
+pub struct EndCardGraph {
+    pub inputs: Vec<String>,
+    /// How many inputs `inputs` adds
+    pub count: usize,
+    pub filters: Vec<String>,
+}
+
+/// Render `card` at the project's frame size and rate, with its files added as inputs from
+/// `first_input` on, ready to concatenate after the program
+pub fn end_card_graph(card: &EndCard, settings: &ProjectSettings, first_input: usize) -> EndCardGraph {
+    let (w, h) = (settings.resolution.width, settings.resolution.height);
+    let fps = settings.fps;
+    let duration = card.duration_sec;
+    let mut inputs = Vec::new();
+    let mut filters = Vec::new();
+    let mut next_input = first_input;
+
+    match &card.background {
+        EndCardBackground::Color { color } => filters.push(format!(
+            "color=c={}:s={}x{}:r={}:d={:.3},setsar=1[ec0]",
+            color, w, h, fps, duration
+        )),
+        EndCardBackground::Clip { path, start_sec } => {
+            inputs.extend(["-i".to_string(), path.clone()]);
+            filters.push(format!(
+                "[{}:v]trim=start={:.3},setpts=PTS-STARTPTS,tpad=stop_mode=clone:stop_duration={:.3},\
+                 trim=duration={:.3},scale={w}:{h}:force_original_aspect_ratio=increase,crop={w}:{h},\
+                 setsar=1,fps={fps}[ec0]",
+                next_input,
+                start_sec,
+                duration,
+                duration,
+                w = w,
+                h = h,
+                fps = fps,
+            ));
+            next_input += 1;
+        }
+    }
+
+    let last = card.elements.len();
+    for (k, element) in card.elements.iter().enumerate() {
+        let output = if k + 1 == last { "[ecv]".to_string() } else { format!("[ec{}]", k + 1) };
+        match element {
+            EndCardElement::Text { text, x, y, size, color, font_file } => {
+                let font = font_file
+                    .as_ref()
+                    .map(|f| format!("fontfile={}:", drawtext_escape(f)))
+                    .unwrap_or_default();
+                filters.push(format!(
+                    "[ec{k}]drawtext={font}text={text}:fontsize={size}:fontcolor={color}:\
+                     x=w*{x:.4}-text_w/2:y=h*{y:.4}-text_h/2{output}",
+                    k = k,
+                    font = font,
+                    text = drawtext_escape(text),
+                    size = ((h as f64 * size).round() as i64).max(1),
+                    color = color,
+                    x = x,
+                    y = y,
+                    output = output,
+                ));
+            }
+            EndCardElement::Image { path, x, y, width, key_color } => {
+                inputs.extend(["-loop".to_string(), "1".to_string(), "-t".to_string(), format!("{:.3}", duration)]);
+                inputs.extend(["-i".to_string(), path.clone()]);
+                filters.push(format!(
+                    "[{}:v]{}[ecimg{}]",
+                    next_input,
+                    image_filter(w as f64 * width, key_color.as_deref()),
+                    k
+                ));
+                filters.push(format!(
+                    "[ec{k}][ecimg{k}]overlay=x=W*{x:.4}-w/2:y=H*{y:.4}-h/2:eof_action=repeat{output}",
+                    k = k,
+                    x = x,
+                    y = y,
+                    output = output,
+                ));
+                next_input += 1;
+            }
+        }
+    }
+    if card.elements.is_empty() {
+        filters.push("[ec0]null[ecv]".to_string());
+    }
+    filters.push(format!(
+        "anullsrc=r={}:cl=stereo,atrim=duration={:.3}[eca]",
+        SILENCE_SAMPLE_RATE, duration
+    ));
+
+    EndCardGraph {
+        inputs,
+        count: next_input - first_input,
+        filters,
+    }
+}
+
+/// Scale an image to `width` pixels (keeping its aspect) with an alpha channel, keying out
+/// `key_color` when it has no transparency of its own
+pub(crate) fn image_filter(width: f64, key_color: Option<&str>) -> String {
+    let mut filter = format!("scale=w={}:h=-2,format=rgba", ((width / 2.0).round() as i64 * 2).max(2));
+    if let Some(color) = key_color {
+        filter.push_str(&format!(",colorkey={}:0.1:0.05", color));
+    }
+    filter
+}
+
+/// Text as a drawtext option value inside a filtergraph: escaped once for the option parser
+/// (`\ ' : %`), then again for the graph parser
+pub(crate) fn drawtext_escape(text: &str) -> String {
+    let mut option = String::new();
+    for c in text.chars() {
+        if matches!(c, '\\' | '\'' | ':' | '%') {
+            option.push('\\');
+        }
+        option.push(c);
+    }
+    let mut graph = String::new();
+    for c in option.chars() {
+        if matches!(c, '\\' | '\'' | '[' | ']' | ',' | ';') {
+            graph.push('\\');
+        }
+        graph.push(c);
+    }
+    graph
+}
@@ -0,0 +1,175 @@
+use crate::diff::generate_diff;
+use crate::ops::TimelineOperation;
+use crate::render::{playback_speed, playback_ticks};
+use crate::timeline::*;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Clips are never trimmed below this (1 second)
+const MIN_CLIP_TICKS: i64 = TICKS_PER_SECOND;
+/// Score for clips with no score (neither kept nor dropped preferentially)
+const DEFAULT_CLIP_SCORE: f64 = 0.5;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ClipTrim {
+    pub clip_id: String,
+    pub before_ticks: i64,
+    pub after_ticks: i64,
+}
+
+/// Operations that bring the primary timeline to a target length, with a preview of the result
+#[derive(Debug, Clone, Serialize)]
+pub struct FitPlan {
+    pub target_ticks: i64,
+    pub tolerance_ticks: i64,
+    pub before_ticks: i64,
+    pub after_ticks: i64,
+    pub within_tolerance: bool,
+    pub operations: Vec<TimelineOperation>,
+    pub dropped_clip_ids: Vec<String>,
+    pub trimmed: Vec<ClipTrim>,
+    /// Why the target couldn't be reached, if it wasn't
+    pub message: Option<String>,
+    pub diff: Value,
+    pub preview: Timeline,
+}
+
+/// How long the clip plays for, so sped-up clips count for what they take up in the edit
+fn clip_duration(clip: &ClipInstance) -> i64 {
+    playback_ticks(clip)
+}
+
+fn primary_duration(timeline: &Timeline) -> i64 {
+    timeline
+        .tracks
+        .iter()
+        .find(|t| t.id == 1)
+        .map(|t| t.clips.iter().map(clip_duration).sum())
+        .unwrap_or(0)
+}
+
+/// Shorten the primary timeline to `target_ticks` (within `tolerance_ticks`): drop the
+/// lowest-scoring clips that fit inside the excess, then trim the rest proportionally from their
+/// tails. Locked content, and everything before it (trims there would ripple it), is left alone.
+/// Timelines already shorter than the target are reported, not padded.
+pub fn fit_to_duration(
+    timeline: &Timeline,
+    target_ticks: i64,
+    tolerance_ticks: i64,
+    clip_scores: &HashMap<String, f64>,
+) -> FitPlan {
+    let mut baseline = timeline.clone();
+    baseline.consolidate_timeline();
+    let before_ticks = primary_duration(&baseline);
+
+    let mut operations = Vec::new();
+    let mut dropped_clip_ids = Vec::new();
+    let mut trimmed = Vec::new();
+    let mut message = None;
+
+    if before_ticks < target_ticks - tolerance_ticks {
+        message = Some(format!(
+            "Timeline is {:.1}s, {:.1}s short of the target; add clips to lengthen it",
+            before_ticks as f64 / TICKS_PER_SECOND as f64,
+            (target_ticks - before_ticks) as f64 / TICKS_PER_SECOND as f64,
+        ));
+    } else if before_ticks > target_ticks + tolerance_ticks {
+        let locked_end = baseline.locked_spans().last().map(|s| s.1).unwrap_or(0);
+        let protected: Vec<String> = baseline.protected_clips().iter().map(|c| c.id.clone()).collect();
+        let mut editable: Vec<&ClipInstance> = baseline
+            .tracks
+            .iter()
+            .find(|t| t.id == 1)
+            .map(|t| {
+                t.clips
+                    .iter()
+                    .filter(|c| c.timeline_start_ticks >= locked_end && !protected.contains(&c.id))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let score = |clip: &ClipInstance| clip_scores.get(&clip.id).copied().unwrap_or(DEFAULT_CLIP_SCORE);
+        editable.sort_by(|a, b| score(a).partial_cmp(&score(b)).unwrap_or(std::cmp::Ordering::Equal));
+
+        let mut excess = before_ticks - target_ticks;
+        let editable_count = editable.len();
+
+        // Drop whole clips, worst first, while that doesn't undershoot (keep at least one)
+        let mut kept = Vec::new();
+        for clip in editable {
+            let duration = clip_duration(clip);
+            let keeps_one = dropped_clip_ids.len() + 1 < editable_count;
+            if excess > tolerance_ticks && duration <= excess + tolerance_ticks && keeps_one {
+                excess -= duration;
+                dropped_clip_ids.push(clip.id.clone());
+                operations.push(TimelineOperation::DeleteClip { clip_id: clip.id.clone() });
+            } else {
+                kept.push(clip);
+            }
+        }
+
+        // Trim what's left proportionally to each clip's slack above the minimum length
+        if excess > tolerance_ticks {
+            let total_slack: i64 = kept.iter().map(|c| (clip_duration(c) - MIN_CLIP_TICKS).max(0)).sum();
+            if total_slack > 0 {
+                let to_trim = excess.min(total_slack);
+                let mut trimmed_total = 0i64;
+                for (idx, clip) in kept.iter().enumerate() {
+                    let slack = (clip_duration(clip) - MIN_CLIP_TICKS).max(0);
+                    // The last clip absorbs rounding so the total comes out exact
+                    let cut = if idx + 1 == kept.len() {
+                        (to_trim - trimmed_total).min(slack)
+                    } else {
+                        (to_trim as i128 * slack as i128 / total_slack as i128) as i64
+                    };
+                    if cut <= 0 {
+                        continue;
+                    }
+                    trimmed_total += cut;
+                    trimmed.push(ClipTrim {
+                        clip_id: clip.id.clone(),
+                        before_ticks: clip_duration(clip),
+                        after_ticks: clip_duration(clip) - cut,
+                    });
+                    operations.push(TimelineOperation::TrimClip {
+                        clip_id: clip.id.clone(),
+                        new_in_ticks: clip.in_ticks,
+                        // `cut` is playback time; the clip's range is in source ticks
+                        new_out_ticks: clip.out_ticks - (cut as f64 * playback_speed(clip)) as i64,
+                    });
+                }
+                excess -= trimmed_total;
+            }
+        }
+
+        if excess > tolerance_ticks {
+            message = Some(format!(
+                "Could only shorten the timeline by {:.1}s; locked content and 1s minimum clip lengths leave {:.1}s over the target",
+                (before_ticks - target_ticks - excess) as f64 / TICKS_PER_SECOND as f64,
+                excess as f64 / TICKS_PER_SECOND as f64,
+            ));
+        }
+    }
+
+    let mut preview = baseline.clone();
+    for op in &operations {
+        // Operations only touch unlocked clips that exist, so they apply cleanly
+        let _ = preview.apply_operation(op.clone());
+    }
+    preview.consolidate_timeline();
+    let after_ticks = primary_duration(&preview);
+
+    FitPlan {
+        target_ticks,
+        tolerance_ticks,
+        before_ticks,
+        after_ticks,
+        within_tolerance: (after_ticks - target_ticks).abs() <= tolerance_ticks,
+        operations,
+        dropped_clip_ids,
+        trimmed,
+        message,
+        diff: generate_diff(&baseline, &preview),
+        preview,
+    }
+}
@@ -0,0 +1,112 @@
+use crate::render::playback_speed;
+use crate::timeline::{ClipInstance, FrameRateConversion, ProjectSettings, Timeline, TrackKind};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Rates closer than this are treated as the same (29.97 vs 30000/1001)
+const FPS_EPSILON: f64 = 0.01;
+/// Blending leaves most frames ghosted once fewer than this share of them are whole source frames
+const BLEND_GHOSTING_RATIO: f64 = 0.75;
+/// Optical flow starts to warp visibly once it has to invent more than one frame per real one
+const OPTICAL_FLOW_MAX_RATIO: f64 = 2.0;
+
+/// A clip whose footage plays at another frame rate than the project's, and how it's converted
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClipFrameRate {
+    pub clip_id: String,
+    pub asset_id: i64,
+    pub source_fps: f64,
+    /// Source frames per second of output once the clip's speed is applied
+    pub effective_fps: f64,
+    pub target_fps: f64,
+    pub conversion: FrameRateConversion,
+    /// Why the conversion will look bad, when it will
+    pub warning: Option<String>,
+}
+
+/// Whether footage at `effective_fps` needs converting for a `target_fps` timeline
+pub fn needs_conversion(effective_fps: f64, target_fps: f64) -> bool {
+    effective_fps > 0.0 && (effective_fps - target_fps).abs() > FPS_EPSILON
+}
+
+/// ffmpeg filter bringing a clip's (already retimed) frames to `target_fps`
+pub fn conversion_filter(conversion: FrameRateConversion, target_fps: f64) -> String {
+    match conversion {
+        FrameRateConversion::DropDup => format!("fps={}", target_fps),
+        FrameRateConversion::Blend => format!("framerate=fps={}", target_fps),
+        FrameRateConversion::OpticalFlow => {
+            format!("minterpolate=fps={}:mi_mode=mci:mc_mode=aobmc:me_mode=bidir:vsbmc=1", target_fps)
+        }
+    }
+}
+
+/// The clip's own conversion, or the project's
+pub fn clip_conversion(clip: &ClipInstance, settings: &ProjectSettings) -> FrameRateConversion {
+    clip.frame_rate_conversion.unwrap_or(settings.frame_rate_conversion)
+}
+
+/// Every video clip that will be converted to the project's frame rate on export.
+/// `source_fps` maps asset ids to their frame rates; assets missing from it (stills) aren't
+/// converted.
+pub fn clip_frame_rates(timeline: &Timeline, source_fps: &HashMap<i64, f64>) -> Vec<ClipFrameRate> {
+    let target_fps = timeline.settings.fps;
+    timeline
+        .tracks
+        .iter()
+        .filter(|t| t.kind == TrackKind::Video)
+        .flat_map(|t| t.clips.iter())
+        .filter(|c| c.ken_burns.is_none())
+        .filter_map(|clip| {
+            let source = *source_fps.get(&clip.asset_id)?;
+            let speed = playback_speed(clip);
+            let effective_fps = source * speed;
+            if !needs_conversion(effective_fps, target_fps) {
+                return None;
+            }
+            let conversion = clip_conversion(clip, &timeline.settings);
+            Some(ClipFrameRate {
+                clip_id: clip.id.clone(),
+                asset_id: clip.asset_id,
+                source_fps: source,
+                effective_fps,
+                target_fps,
+                conversion,
+                warning: conversion_warning(conversion, effective_fps, target_fps, speed),
+            })
+        })
+        .collect()
+}
+
+/// Whether one rate is a whole multiple of the other, so frames drop or repeat evenly
+fn divides_evenly(effective_fps: f64, target_fps: f64) -> bool {
+    let ratio = if effective_fps > target_fps { effective_fps / target_fps } else { target_fps / effective_fps };
+    (ratio - ratio.round()).abs() < 0.01
+}
+
+fn conversion_warning(conversion: FrameRateConversion, effective_fps: f64, target_fps: f64, speed: f64) -> Option<String> {
+    let upconvert_ratio = target_fps / effective_fps;
+    match conversion {
+        FrameRateConversion::DropDup if !divides_evenly(effective_fps, target_fps) => Some(format!(
+            "{:.3} fps doesn't divide evenly into {:.3} fps, so dropping and repeating frames makes motion judder; \
+             blend or optical_flow are smoother",
+            effective_fps, target_fps
+        )),
+        FrameRateConversion::DropDup if speed < 1.0 && upconvert_ratio >= 2.0 => Some(format!(
+            "slow motion repeats each frame {:.0} times and will stutter; optical_flow gives smoother slow motion",
+            upconvert_ratio
+        )),
+        FrameRateConversion::Blend if effective_fps < target_fps * BLEND_GHOSTING_RATIO => Some(format!(
+            "going from {:.3} to {:.3} fps blends most frames out of two, so anything moving will ghost",
+            effective_fps, target_fps
+        )),
+        FrameRateConversion::OpticalFlow if upconvert_ratio > OPTICAL_FLOW_MAX_RATIO => Some(format!(
+            "optical flow has to invent {:.1} frames for every real one; expect warping on fast motion",
+            upconvert_ratio - 1.0
+        )),
+        FrameRateConversion::OpticalFlow if effective_fps > target_fps => Some(
+            "optical flow doesn't help when lowering the frame rate, and renders far slower than drop_dup or blend"
+                .to_string(),
+        ),
+        _ => None,
+    }
+}
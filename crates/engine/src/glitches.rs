@@ -0,0 +1,172 @@
+use crate::diff::generate_diff;
+use crate::ops::TimelineOperation;
+use crate::render::playback_ticks;
+use crate::timeline::*;
+use serde::Serialize;
+use serde_json::Value;
+
+/// Clips this many frames long or shorter flash by rather than read as shots
+const FLASH_FRAME_MAX: f64 = 3.0;
+/// Neighbouring clips repeating up to this many frames of the same source stutter at the cut
+/// (longer repeats are deliberate)
+const DUPLICATE_FRAMES_MAX: f64 = 3.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GlitchKind {
+    /// A clip only 1-3 frames long, usually left over from a trim or split
+    FlashFrame,
+    /// The next clip starts on frames the previous one already showed
+    DuplicateFrames,
+    /// Less than a frame of gap (or overlap) between neighbouring clips, from rounding
+    SubframeGap,
+}
+
+/// One accidental edit, with the operations that fix it
+#[derive(Debug, Clone, Serialize)]
+pub struct Glitch {
+    pub kind: GlitchKind,
+    pub track_id: i64,
+    pub clip_ids: Vec<String>,
+    pub position_ticks: i64,
+    /// Length of the flash, frames repeated, or the gap as a fraction of a frame
+    pub frames: f64,
+    pub message: String,
+    /// Empty when the fix would touch locked content
+    pub operations: Vec<TimelineOperation>,
+}
+
+/// Every glitch on a timeline, and all of their fixes together with a preview of the result
+#[derive(Debug, Clone, Serialize)]
+pub struct GlitchReport {
+    pub frame_ticks: i64,
+    pub glitches: Vec<Glitch>,
+    pub operations: Vec<TimelineOperation>,
+    pub diff: Value,
+    pub preview: Timeline,
+}
+
+/// Find flash frames, repeated frames at cuts and sub-frame gaps on the video tracks
+pub fn find_glitches(timeline: &Timeline) -> GlitchReport {
+    let fps = if timeline.settings.fps > 0.0 { timeline.settings.fps } else { 30.0 };
+    let frame_ticks = (TICKS_PER_SECOND as f64 / fps).round() as i64;
+    let to_frames = |ticks: i64| ticks as f64 / frame_ticks as f64;
+    let is_flash = |clip: &ClipInstance| {
+        let ticks = playback_ticks(clip);
+        ticks > 0 && to_frames(ticks) <= FLASH_FRAME_MAX + 0.01
+    };
+    // A fix is only offered when it leaves locked content alone
+    let unlocked = |ops: Vec<TimelineOperation>| {
+        if ops.iter().any(|op| timeline.lock_conflict(op).is_some()) {
+            Vec::new()
+        } else {
+            ops
+        }
+    };
+
+    let mut glitches = Vec::new();
+    for track in timeline.tracks.iter().filter(|t| t.kind == TrackKind::Video) {
+        let mut clips: Vec<&ClipInstance> = track.clips.iter().collect();
+        clips.sort_by_key(|c| c.timeline_start_ticks);
+
+        for clip in clips.iter().filter(|c| is_flash(c)) {
+            let frames = to_frames(playback_ticks(clip));
+            glitches.push(Glitch {
+                kind: GlitchKind::FlashFrame,
+                track_id: track.id,
+                clip_ids: vec![clip.id.clone()],
+                position_ticks: clip.timeline_start_ticks,
+                frames,
+                message: format!("Clip is only {:.0} frame(s) long", frames),
+                operations: unlocked(vec![TimelineOperation::DeleteClip { clip_id: clip.id.clone() }]),
+            });
+        }
+
+        for pair in clips.windows(2) {
+            let (prev, next) = (pair[0], pair[1]);
+            if is_flash(prev) || is_flash(next) {
+                continue;
+            }
+            let prev_end = prev.timeline_start_ticks + (prev.out_ticks - prev.in_ticks);
+            let gap = next.timeline_start_ticks - prev_end;
+            if gap != 0 && gap.abs() < frame_ticks {
+                // The primary track is packed end to end; overlays move onto the previous clip's end
+                let fix = if track.id == 1 {
+                    TimelineOperation::ConsolidateTimeline
+                } else {
+                    TimelineOperation::MoveClip {
+                        clip_id: next.id.clone(),
+                        new_position_ticks: prev_end,
+                    }
+                };
+                glitches.push(Glitch {
+                    kind: GlitchKind::SubframeGap,
+                    track_id: track.id,
+                    clip_ids: vec![prev.id.clone(), next.id.clone()],
+                    position_ticks: prev_end,
+                    frames: to_frames(gap),
+                    message: format!(
+                        "{} of {} ticks between clips (under a frame)",
+                        if gap > 0 { "Gap" } else { "Overlap" },
+                        gap.abs()
+                    ),
+                    operations: unlocked(vec![fix]),
+                });
+            }
+
+            let repeated = prev.out_ticks - next.in_ticks;
+            let adjacent = gap.abs() < frame_ticks;
+            if adjacent
+                && prev.asset_id == next.asset_id
+                && prev.speed == next.speed
+                && next.in_ticks >= prev.in_ticks
+                && repeated > 0
+                && to_frames(repeated) <= DUPLICATE_FRAMES_MAX + 0.01
+                && next.out_ticks - prev.out_ticks > 0
+            {
+                let frames = to_frames(repeated);
+                glitches.push(Glitch {
+                    kind: GlitchKind::DuplicateFrames,
+                    track_id: track.id,
+                    clip_ids: vec![prev.id.clone(), next.id.clone()],
+                    position_ticks: next.timeline_start_ticks,
+                    frames,
+                    message: format!("The cut repeats {:.0} frame(s) of the same shot", frames.ceil()),
+                    operations: unlocked(vec![TimelineOperation::TrimClip {
+                        clip_id: next.id.clone(),
+                        new_in_ticks: prev.out_ticks,
+                        new_out_ticks: next.out_ticks,
+                    }]),
+                });
+            }
+        }
+    }
+    glitches.sort_by_key(|g| (g.position_ticks, g.track_id));
+
+    // Packing the primary track closes all of its gaps at once, so it runs once, last
+    let mut operations: Vec<TimelineOperation> = glitches
+        .iter()
+        .flat_map(|g| g.operations.iter().cloned())
+        .filter(|op| !matches!(op, TimelineOperation::ConsolidateTimeline))
+        .collect();
+    let consolidate = glitches
+        .iter()
+        .any(|g| g.operations.iter().any(|op| matches!(op, TimelineOperation::ConsolidateTimeline)));
+    if consolidate {
+        operations.push(TimelineOperation::ConsolidateTimeline);
+    }
+
+    let mut preview = timeline.clone();
+    for op in &operations {
+        // Fixes only touch unlocked clips that exist, so they apply cleanly
+        let _ = preview.apply_operation(op.clone());
+    }
+
+    GlitchReport {
+        frame_ticks,
+        glitches,
+        operations,
+        diff: generate_diff(timeline, &preview),
+        preview,
+    }
+}
@@ -0,0 +1,216 @@
+use crate::encode::EncoderSettings;
+use crate::render::{audio_cut_shifts, clip_render_key, playback_ticks, AudioOptions, BlurRegion, SourceMedia};
+use crate::render_cache::content_hash;
+use crate::timeline::{ClipInstance, Timeline, TrackKind, TICKS_PER_SECOND};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+
+/// A stretch of an export's output that renders on its own: one or more primary clips and the
+/// overlays starting over them. Spans never cut through an overlay or a J/L cut, so rendering
+/// one alone gives the same picture and sound as the full export.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RenderSpan {
+    /// Hash of everything rendered in the span (not where it sits in the export)
+    pub key: String,
+    pub clip_ids: Vec<String>,
+    /// Range of the export's output, in ticks from its start
+    pub start_ticks: i64,
+    pub end_ticks: i64,
+}
+
+/// Where a piece of an incremental export comes from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "source", rename_all = "snake_case")]
+pub enum StitchPiece {
+    /// Copied out of the previous export's output
+    Previous { start_ticks: i64, end_ticks: i64 },
+    /// Rendered again
+    Render { span: RenderSpan },
+}
+
+/// How to build an export from the previous one: the pieces in output order, and which output
+/// ranges changed
+#[derive(Debug, Clone, Serialize)]
+pub struct StitchPlan {
+    pub pieces: Vec<StitchPiece>,
+    pub changed_ranges: Vec<(i64, i64)>,
+    pub reused_ticks: i64,
+    pub rendered_ticks: i64,
+}
+
+/// Overlays (clips on video tracks above the primary one) the export composites
+fn overlays<'a>(timeline: &'a Timeline, sources: &HashMap<i64, SourceMedia>) -> Vec<&'a ClipInstance> {
+    timeline
+        .tracks
+        .iter()
+        .filter(|t| t.kind == TrackKind::Video && t.id > 1)
+        .flat_map(|t| t.clips.iter())
+        .filter(|c| sources.contains_key(&c.asset_id))
+        .collect()
+}
+
+fn overlay_range(clip: &ClipInstance) -> (i64, i64) {
+    (clip.timeline_start_ticks, clip.timeline_start_ticks + (clip.out_ticks - clip.in_ticks))
+}
+
+/// Index of the span an overlay starting at `start_ticks` belongs to
+fn span_index(spans: &[RenderSpan], start_ticks: i64) -> usize {
+    spans
+        .iter()
+        .position(|s| start_ticks < s.end_ticks)
+        .unwrap_or(spans.len().saturating_sub(1))
+}
+
+/// Split the export into independently renderable spans. Cuts are only made between primary
+/// clips where no overlay is on screen across the cut and the sound cuts with the picture.
+pub fn render_spans(
+    timeline: &Timeline,
+    sources: &HashMap<i64, SourceMedia>,
+    blur_regions: &[BlurRegion],
+    audio: &AudioOptions,
+    encoder: &EncoderSettings,
+) -> Vec<RenderSpan> {
+    let Some(track) = timeline.tracks.iter().find(|t| t.kind == TrackKind::Video && t.id == 1) else {
+        return Vec::new();
+    };
+    let mut clips: Vec<&ClipInstance> = track.clips.iter().collect();
+    clips.sort_by_key(|c| c.timeline_start_ticks);
+    let audio_shifts = audio_cut_shifts(&clips);
+    let overlays = overlays(timeline, sources);
+
+    // Group the clips, then key each group
+    let mut groups: Vec<(Vec<usize>, i64, i64)> = Vec::new();
+    let mut start = 0;
+    for (idx, clip) in clips.iter().enumerate() {
+        let end = start + playback_ticks(clip);
+        let joined = idx > 0 && {
+            let cut = start;
+            audio_shifts[idx - 1] != 0
+                || overlays.iter().any(|o| {
+                    let (from, to) = overlay_range(o);
+                    from < cut && to > cut
+                })
+        };
+        match groups.last_mut() {
+            Some(group) if joined => {
+                group.0.push(idx);
+                group.2 = end;
+            }
+            _ => groups.push((vec![idx], start, end)),
+        }
+        start = end;
+    }
+
+    let mut spans: Vec<RenderSpan> = groups
+        .iter()
+        .map(|(members, start_ticks, end_ticks)| RenderSpan {
+            key: String::new(),
+            clip_ids: members.iter().map(|&i| clips[i].id.clone()).collect(),
+            start_ticks: *start_ticks,
+            end_ticks: *end_ticks,
+        })
+        .collect();
+    for (span_idx, (members, _, _)) in groups.iter().enumerate() {
+        let span_start = spans[span_idx].start_ticks;
+        let clip_keys: Vec<String> = members
+            .iter()
+            .map(|&i| {
+                let shifts = (if i == 0 { 0 } else { audio_shifts[i - 1] }, audio_shifts[i]);
+                clip_render_key(clips[i], sources, blur_regions, audio, &timeline.settings, shifts)
+            })
+            .collect();
+        // Overlays in the span, placed relative to it
+        let overlay_values: Vec<serde_json::Value> = overlays
+            .iter()
+            .filter(|o| span_index(&spans, o.timeline_start_ticks) == span_idx)
+            .map(|o| {
+                let mut value = serde_json::to_value(o).unwrap_or_default();
+                if let Some(fields) = value.as_object_mut() {
+                    fields.remove("id");
+                    fields.remove("locked");
+                    fields.insert("timeline_start_ticks".to_string(), json!(o.timeline_start_ticks - span_start));
+                }
+                let regions: Vec<&BlurRegion> = blur_regions.iter().filter(|r| r.asset_id == o.asset_id).collect();
+                json!({ "clip": value, "source": sources.get(&o.asset_id), "blur_regions": regions })
+            })
+            .collect();
+        spans[span_idx].key = content_hash(&json!({
+            "clips": clip_keys,
+            "overlays": overlay_values,
+            "resolution": timeline.settings.resolution,
+            "fps": timeline.settings.fps,
+            "encoder": encoder,
+        }));
+    }
+    spans
+}
+
+/// The timeline of one span on its own: its primary clips, and its overlays moved to where
+/// they sit within it (without captions, narration, music or titles)
+pub fn span_timeline(timeline: &Timeline, spans: &[RenderSpan], span: &RenderSpan) -> Timeline {
+    let mut span_timeline = timeline.clone();
+    let span_idx = spans.iter().position(|s| s == span).unwrap_or(0);
+    for track in span_timeline.tracks.iter_mut().filter(|t| t.kind == TrackKind::Video) {
+        if track.id == 1 {
+            track.clips.retain(|c| span.clip_ids.contains(&c.id));
+        } else {
+            track.clips.retain(|c| span_index(spans, c.timeline_start_ticks) == span_idx);
+            for clip in &mut track.clips {
+                clip.timeline_start_ticks -= span.start_ticks;
+            }
+        }
+    }
+    // Narration, music and titles run across spans; exports that have them are rendered whole
+    span_timeline.captions.clear();
+    span_timeline.narration.clear();
+    span_timeline.music.clear();
+    span_timeline.titles.clear();
+    span_timeline
+}
+
+/// Match the current spans against the previous export's: spans it already rendered are
+/// copied out of it, the rest are rendered
+pub fn plan_stitch(previous: &[RenderSpan], current: &[RenderSpan]) -> StitchPlan {
+    let mut pieces: Vec<StitchPiece> = Vec::new();
+    let mut changed_ranges: Vec<(i64, i64)> = Vec::new();
+    let (mut reused_ticks, mut rendered_ticks) = (0, 0);
+    for span in current {
+        let length = span.end_ticks - span.start_ticks;
+        match previous.iter().find(|p| p.key == span.key) {
+            Some(earlier) => {
+                reused_ticks += length;
+                // Stretches that were already back to back are copied in one go
+                if let Some(StitchPiece::Previous { end_ticks, .. }) = pieces.last_mut() {
+                    if *end_ticks == earlier.start_ticks {
+                        *end_ticks = earlier.end_ticks;
+                        continue;
+                    }
+                }
+                pieces.push(StitchPiece::Previous {
+                    start_ticks: earlier.start_ticks,
+                    end_ticks: earlier.end_ticks,
+                });
+            }
+            None => {
+                rendered_ticks += length;
+                match changed_ranges.last_mut() {
+                    Some(range) if range.1 == span.start_ticks => range.1 = span.end_ticks,
+                    _ => changed_ranges.push((span.start_ticks, span.end_ticks)),
+                }
+                pieces.push(StitchPiece::Render { span: span.clone() });
+            }
+        }
+    }
+    StitchPlan {
+        pieces,
+        changed_ranges,
+        reused_ticks,
+        rendered_ticks,
+    }
+}
+
+/// Seconds of output, as written in ffconcat files
+pub fn ticks_to_seconds(ticks: i64) -> f64 {
+    ticks as f64 / TICKS_PER_SECOND as f64
+}
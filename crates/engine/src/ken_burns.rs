@@ -0,0 +1,91 @@
+use crate::timeline::{Easing, Framing, KenBurns};
+use serde::{Deserialize, Serialize};
+
+/// Something in a still worth moving towards, as detected by the ML service
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StillSubject {
+    /// "face", or "detail" for the busiest area of a photo without faces
+    pub kind: String,
+    /// Box normalized to the image (0-1)
+    pub x: f64,
+    pub y: f64,
+    pub w: f64,
+    pub h: f64,
+    pub score: f64,
+}
+
+/// Share of the frame the subjects fill at the tight end of the move
+const SUBJECT_FILL: f64 = 0.6;
+/// Zoom range of the tight end (smaller moves read as a shaky still, larger ones get soft)
+const MIN_PUSH_ZOOM: f64 = 1.15;
+const MAX_PUSH_ZOOM: f64 = 1.6;
+/// Subjects scoring at least this share of the strongest one stay in the tight framing too
+const GROUP_SCORE: f64 = 0.5;
+/// Faces are framed head and shoulders rather than edge to edge
+const FACE_PADDING: f64 = 2.0;
+
+/// Ken Burns motion for a still of `image_size` shown in a `frame_size` frame: between the whole
+/// picture and a closer framing of its subjects (a centered push when none were found).
+/// `push_in` moves towards the subjects, otherwise the move pulls back from them.
+pub fn auto_ken_burns(
+    subjects: &[StillSubject],
+    image_size: (i32, i32),
+    frame_size: (i32, i32),
+    push_in: bool,
+) -> KenBurns {
+    let tight = subject_box(subjects, image_size, frame_size)
+        .map(|(x0, y0, x1, y1)| {
+            let zoom = (SUBJECT_FILL / (x1 - x0).max(y1 - y0).max(f64::EPSILON)).clamp(MIN_PUSH_ZOOM, MAX_PUSH_ZOOM);
+            Framing {
+                zoom,
+                x: (x0 + x1) / 2.0,
+                y: (y0 + y1) / 2.0,
+            }
+            .clamped()
+        })
+        .unwrap_or(Framing {
+            zoom: MIN_PUSH_ZOOM,
+            ..Framing::default()
+        });
+    let wide = Framing::default();
+    let (start, end) = if push_in { (wide, tight) } else { (tight, wide) };
+    KenBurns {
+        enabled: true,
+        start,
+        end,
+        easing: Easing::EaseInOut,
+    }
+}
+
+/// Box around the leading subjects in frame-filling picture coordinates (x0, y0, x1, y1)
+fn subject_box(subjects: &[StillSubject], image_size: (i32, i32), frame_size: (i32, i32)) -> Option<(f64, f64, f64, f64)> {
+    let best = subjects.iter().map(|s| s.score).fold(f64::MIN, f64::max);
+    let (image_w, image_h) = (image_size.0.max(1) as f64, image_size.1.max(1) as f64);
+    let (frame_w, frame_h) = (frame_size.0.max(1) as f64, frame_size.1.max(1) as f64);
+    // Part of the image left visible once it's scaled to cover the frame
+    let image_aspect = image_w / image_h;
+    let frame_aspect = frame_w / frame_h;
+    let (visible_w, visible_h) = if image_aspect > frame_aspect {
+        (frame_aspect / image_aspect, 1.0)
+    } else {
+        (1.0, image_aspect / frame_aspect)
+    };
+    let to_frame = |v: f64, visible: f64| ((v - (1.0 - visible) / 2.0) / visible).clamp(0.0, 1.0);
+
+    subjects
+        .iter()
+        .filter(|s| s.score >= best * GROUP_SCORE && s.w > 0.0 && s.h > 0.0)
+        .map(|s| {
+            let pad = if s.kind == "face" { FACE_PADDING } else { 1.0 };
+            let (cx, cy) = (s.x + s.w / 2.0, s.y + s.h / 2.0);
+            let (w, h) = (s.w * pad, s.h * pad);
+            (
+                to_frame(cx - w / 2.0, visible_w),
+                to_frame(cy - h / 2.0, visible_h),
+                to_frame(cx + w / 2.0, visible_w),
+                to_frame(cy + h / 2.0, visible_h),
+            )
+        })
+        .filter(|(x0, y0, x1, y1)| x1 > x0 && y1 > y0)
+        .reduce(|a, b| (a.0.min(b.0), a.1.min(b.1), a.2.max(b.2), a.3.max(b.3)))
+}
@@ -1,8 +1,22 @@
 pub mod compiler;
 pub mod diff;
+pub mod encode;
+pub mod end_card;
+pub mod fit;
+pub mod frame_rate;
+pub mod glitches;
+pub mod incremental;
+pub mod ken_burns;
+pub mod locks;
+pub mod narration;
 pub mod ops;
+pub mod otio;
+pub mod preview;
 pub mod render;
+pub mod render_cache;
 pub mod timeline;
+pub mod title;
+pub mod watermark;
 
 pub use timeline::*;
 pub use compiler::*;
@@ -0,0 +1,166 @@
+use crate::ops::TimelineOperation;
+use crate::timeline::*;
+use serde::{Deserialize, Serialize};
+
+/// Why an operation was refused: the locked content it would have changed, and where the
+/// edit could go instead
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockWarning {
+    pub message: String,
+    /// Locked clips (or clips inside locked regions) the operation would move, trim or remove
+    pub clip_ids: Vec<String>,
+    /// Locked regions the operation would change
+    pub region_ids: Vec<String>,
+    /// Primary-track spans (start, end) free of locked content, where overwrites are safe
+    pub unlocked_ranges: Vec<(i64, i64)>,
+    /// Earliest position where ripple inserts don't shift any locked content
+    pub safe_insert_ticks: i64,
+}
+
+impl std::fmt::Display for LockWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for LockWarning {}
+
+fn clip_end(clip: &ClipInstance) -> i64 {
+    clip.timeline_start_ticks + (clip.out_ticks - clip.in_ticks)
+}
+
+fn overlaps_region(clip: &ClipInstance, region: &LockedRegion) -> bool {
+    clip.timeline_start_ticks < region.end_ticks && clip_end(clip) > region.start_ticks
+}
+
+impl Timeline {
+    /// Clips edits must leave untouched: locked clips, and primary clips inside locked regions
+    pub fn protected_clips(&self) -> Vec<&ClipInstance> {
+        self.tracks
+            .iter()
+            .flat_map(|t| t.clips.iter())
+            .filter(|c| {
+                c.locked
+                    || (c.track_id == 1 && self.locked_regions.iter().any(|r| overlaps_region(c, r)))
+            })
+            .collect()
+    }
+
+    /// Locked spans on the primary track, merged and sorted
+    pub fn locked_spans(&self) -> Vec<(i64, i64)> {
+        let mut spans: Vec<(i64, i64)> = self
+            .protected_clips()
+            .into_iter()
+            .filter(|c| c.track_id == 1)
+            .map(|c| (c.timeline_start_ticks, clip_end(c)))
+            .chain(self.locked_regions.iter().map(|r| (r.start_ticks, r.end_ticks)))
+            .collect();
+        spans.sort();
+
+        let mut merged: Vec<(i64, i64)> = Vec::new();
+        for (start, end) in spans {
+            match merged.last_mut() {
+                Some(last) if start <= last.1 => last.1 = last.1.max(end),
+                _ => merged.push((start, end)),
+            }
+        }
+        merged
+    }
+
+    /// Preview the operation and report the locked content it would move or replace, if any.
+    /// The preview includes the consolidation every apply ends with, so ripples are caught.
+    pub fn lock_conflict(&self, op: &TimelineOperation) -> Option<LockWarning> {
+        if op.is_lock_operation() {
+            return None;
+        }
+        if self.locked_regions.is_empty() && !self.tracks.iter().any(|t| t.clips.iter().any(|c| c.locked)) {
+            return None;
+        }
+
+        // Compare against the consolidated timeline so existing gaps don't count as moves
+        let mut baseline = self.clone();
+        baseline.consolidate_timeline();
+        let mut preview = baseline.clone();
+        if preview.apply_operation_unchecked(op.clone()).is_err() {
+            // Invalid operations fail with their own error when applied
+            return None;
+        }
+        preview.consolidate_timeline();
+
+        let protected = baseline.protected_clips();
+        let preview_clips: Vec<&ClipInstance> = preview.tracks.iter().flat_map(|t| t.clips.iter()).collect();
+
+        let clip_ids: Vec<String> = protected
+            .iter()
+            .filter(|p| {
+                !preview_clips.iter().any(|c| {
+                    c.id == p.id
+                        && c.track_id == p.track_id
+                        && c.in_ticks == p.in_ticks
+                        && c.out_ticks == p.out_ticks
+                        && c.timeline_start_ticks == p.timeline_start_ticks
+                        && c.speed == p.speed
+                })
+            })
+            .map(|p| p.id.clone())
+            .collect();
+
+        // New content dropped into a locked region replaces what's there
+        let region_ids: Vec<String> = baseline
+            .locked_regions
+            .iter()
+            .filter(|r| {
+                preview_clips.iter().any(|c| {
+                    c.track_id == 1 && overlaps_region(c, r) && !protected.iter().any(|p| p.id == c.id)
+                })
+            })
+            .map(|r| r.id.clone())
+            .collect();
+
+        if clip_ids.is_empty() && region_ids.is_empty() {
+            return None;
+        }
+
+        let spans = baseline.locked_spans();
+        let timeline_end = baseline
+            .tracks
+            .iter()
+            .find(|t| t.id == 1)
+            .and_then(|t| t.clips.iter().map(clip_end).max())
+            .unwrap_or(0);
+        let safe_insert_ticks = spans.last().map(|s| s.1).unwrap_or(0).max(0);
+
+        let mut unlocked_ranges = Vec::new();
+        let mut position = 0i64;
+        for (start, end) in &spans {
+            if *start > position {
+                unlocked_ranges.push((position, *start));
+            }
+            position = position.max(*end);
+        }
+        if timeline_end > position {
+            unlocked_ranges.push((position, timeline_end));
+        }
+
+        let mut parts = Vec::new();
+        if !clip_ids.is_empty() {
+            parts.push(format!("move or replace {} locked clip(s)", clip_ids.len()));
+        }
+        if !region_ids.is_empty() {
+            parts.push(format!("place new content in {} locked region(s)", region_ids.len()));
+        }
+        let message = format!(
+            "Operation would {}; edit after {:.1}s or unlock the content first",
+            parts.join(" and "),
+            safe_insert_ticks as f64 / TICKS_PER_SECOND as f64,
+        );
+
+        Some(LockWarning {
+            message,
+            clip_ids,
+            region_ids,
+            unlocked_ranges,
+            safe_insert_ticks,
+        })
+    }
+}
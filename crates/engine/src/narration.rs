@@ -0,0 +1,251 @@
+use crate::diff::generate_diff;
+use crate::ops::TimelineOperation;
+use crate::timeline::*;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// B-roll is never trimmed below this (half a second)
+const MIN_B_ROLL_TICKS: i64 = TICKS_PER_SECOND / 2;
+/// Narration beats shorter than this are folded into the next one (a lone "So," isn't a beat)
+const MIN_BEAT_TICKS: i64 = 2 * TICKS_PER_SECOND;
+/// Beats whose picture ends further than this from the beat's end are reported
+const DRIFT_TOLERANCE_TICKS: i64 = TICKS_PER_SECOND / 2;
+
+/// A stretch of narration the picture cuts with: a sentence, or a few short ones
+#[derive(Debug, Clone, Serialize)]
+pub struct NarrationBeat {
+    pub start_ticks: i64,
+    pub end_ticks: i64,
+    pub text: String,
+}
+
+/// How one beat's picture was fitted to it
+#[derive(Debug, Clone, Serialize)]
+pub struct BeatFit {
+    pub start_ticks: i64,
+    pub end_ticks: i64,
+    pub text: String,
+    /// Primary clips that play under the beat
+    pub clip_ids: Vec<String>,
+    pub before_ticks: i64,
+    pub after_ticks: i64,
+}
+
+/// Operations that cut the primary timeline to a narration's beats, with a preview of the result
+#[derive(Debug, Clone, Serialize)]
+pub struct NarrationPlan {
+    pub narration_ticks: i64,
+    pub before_ticks: i64,
+    pub after_ticks: i64,
+    pub beats: Vec<BeatFit>,
+    pub operations: Vec<TimelineOperation>,
+    /// Why some beats couldn't be matched, if they weren't
+    pub message: Option<String>,
+    pub diff: Value,
+    pub preview: Timeline,
+}
+
+fn clip_duration(clip: &ClipInstance) -> i64 {
+    clip.out_ticks - clip.in_ticks
+}
+
+fn primary_duration(timeline: &Timeline) -> i64 {
+    timeline
+        .tracks
+        .iter()
+        .find(|t| t.id == 1)
+        .map(|t| t.clips.iter().map(clip_duration).sum())
+        .unwrap_or(0)
+}
+
+fn join_text(first: &str, second: &str) -> String {
+    format!("{} {}", first.trim(), second.trim()).trim().to_string()
+}
+
+/// Beats of a narration from its spoken stretches, as (start, end, text) in narration time.
+/// Beats cover the whole narration: each boundary falls in the middle of the pause between two
+/// stretches, and beats shorter than 2s are merged into the next.
+pub fn narration_beats(spoken: &[(i64, i64, String)], duration_ticks: i64) -> Vec<NarrationBeat> {
+    let mut beats: Vec<NarrationBeat> = Vec::new();
+    let mut pending: Option<NarrationBeat> = None;
+    for (idx, (_, end, text)) in spoken.iter().enumerate() {
+        let boundary = match spoken.get(idx + 1) {
+            Some((next_start, _, _)) => (end + next_start) / 2,
+            None => duration_ticks,
+        };
+        let beat = match pending.take() {
+            Some(mut beat) => {
+                beat.end_ticks = boundary;
+                beat.text = join_text(&beat.text, text);
+                beat
+            }
+            None => NarrationBeat {
+                start_ticks: beats.last().map(|b| b.end_ticks).unwrap_or(0),
+                end_ticks: boundary,
+                text: text.trim().to_string(),
+            },
+        };
+        if beat.end_ticks - beat.start_ticks < MIN_BEAT_TICKS {
+            pending = Some(beat);
+        } else {
+            beats.push(beat);
+        }
+    }
+    // A short last beat joins the one before it
+    if let Some(beat) = pending {
+        match beats.last_mut() {
+            Some(last) => {
+                last.end_ticks = beat.end_ticks;
+                last.text = join_text(&last.text, &beat.text);
+            }
+            None => beats.push(beat),
+        }
+    }
+    if beats.is_empty() && duration_ticks > 0 {
+        beats.push(NarrationBeat {
+            start_ticks: 0,
+            end_ticks: duration_ticks,
+            text: String::new(),
+        });
+    }
+    beats
+}
+
+/// Cut the primary timeline to the narration's beats. Each primary clip plays under the beat it
+/// starts in (beats no clip starts in extend the one before). Within a beat, b-roll is trimmed
+/// or extended by the same share so the beat's picture ends where the beat does; everything else
+/// keeps its length. `b_roll` maps the clips that may be retimed to how far their source runs,
+/// and b-roll under the narration is muted. Locked content, everything before it, retimed clips
+/// and clips starting after the narration ends are left alone. The narration replaces any
+/// already on the timeline.
+pub fn align_to_narration(
+    timeline: &Timeline,
+    beats: &[NarrationBeat],
+    b_roll: &HashMap<String, i64>,
+    narration: NarrationEvent,
+) -> NarrationPlan {
+    let mut baseline = timeline.clone();
+    baseline.consolidate_timeline();
+    let before_ticks = primary_duration(&baseline);
+    let narration_ticks = narration.end_ticks - narration.start_ticks;
+
+    let locked_end = baseline.locked_spans().last().map(|s| s.1).unwrap_or(0);
+    let protected: Vec<String> = baseline.protected_clips().iter().map(|c| c.id.clone()).collect();
+    let flexible = |clip: &ClipInstance| {
+        b_roll.contains_key(&clip.id)
+            && clip.timeline_start_ticks >= locked_end
+            && !protected.contains(&clip.id)
+            && clip.speed == 1.0
+    };
+    let clips: Vec<&ClipInstance> = baseline
+        .tracks
+        .iter()
+        .find(|t| t.id == 1)
+        .map(|t| t.clips.iter().filter(|c| c.timeline_start_ticks < narration_ticks).collect())
+        .unwrap_or_default();
+
+    // Group clips by the beat they start in; beats without a clip extend the previous one
+    let mut groups: Vec<(BeatFit, Vec<&ClipInstance>)> = Vec::new();
+    for beat in beats {
+        let members: Vec<&ClipInstance> = clips
+            .iter()
+            .copied()
+            .filter(|c| c.timeline_start_ticks >= beat.start_ticks && c.timeline_start_ticks < beat.end_ticks)
+            .collect();
+        match groups.last_mut() {
+            Some((fit, _)) if members.is_empty() => {
+                fit.end_ticks = beat.end_ticks;
+                fit.text = join_text(&fit.text, &beat.text);
+            }
+            _ => groups.push((
+                BeatFit {
+                    start_ticks: beat.start_ticks,
+                    end_ticks: beat.end_ticks,
+                    text: beat.text.clone(),
+                    clip_ids: members.iter().map(|c| c.id.clone()).collect(),
+                    before_ticks: members.iter().map(|c| clip_duration(c)).sum(),
+                    after_ticks: 0,
+                },
+                members,
+            )),
+        }
+    }
+
+    let mut operations = Vec::new();
+    let mut cursor = 0i64;
+    let mut off_beat = 0;
+    for (fit, members) in &mut groups {
+        // Earlier beats that ran long or short are made up for here
+        let needed = fit.end_ticks - cursor;
+        let fixed: i64 = members.iter().filter(|c| !flexible(c)).map(|c| clip_duration(c)).sum();
+        let retimed: Vec<&ClipInstance> = members.iter().copied().filter(|c| flexible(c)).collect();
+        let current: i64 = retimed.iter().map(|c| clip_duration(c)).sum();
+        let available = (needed - fixed).max(0);
+
+        let mut after = fixed;
+        let mut assigned = 0i64;
+        for (idx, clip) in retimed.iter().enumerate() {
+            let longest = (b_roll[&clip.id] - clip.in_ticks).max(clip_duration(clip));
+            let shortest = MIN_B_ROLL_TICKS.min(clip_duration(clip));
+            // The last clip takes up rounding so the beat comes out exact
+            let wanted = if idx + 1 == retimed.len() {
+                available - assigned
+            } else if current > 0 {
+                (available as i128 * clip_duration(clip) as i128 / current as i128) as i64
+            } else {
+                0
+            };
+            let length = wanted.clamp(shortest, longest);
+            assigned += length;
+            after += length;
+            if length != clip_duration(clip) {
+                operations.push(TimelineOperation::TrimClip {
+                    clip_id: clip.id.clone(),
+                    new_in_ticks: clip.in_ticks,
+                    new_out_ticks: clip.in_ticks + length,
+                });
+            }
+            if !clip.audio_muted {
+                operations.push(TimelineOperation::SetClipAudioMuted {
+                    clip_id: clip.id.clone(),
+                    muted: true,
+                });
+            }
+        }
+        fit.after_ticks = after;
+        cursor += after;
+        if (cursor - fit.end_ticks).abs() > DRIFT_TOLERANCE_TICKS {
+            off_beat += 1;
+        }
+    }
+
+    let message = (off_beat > 0).then(|| {
+        format!(
+            "{} of {} beats end more than {:.1}s off the narration; there isn't enough b-roll (or source footage to extend it) to fill them",
+            off_beat,
+            groups.len(),
+            DRIFT_TOLERANCE_TICKS as f64 / TICKS_PER_SECOND as f64,
+        )
+    });
+
+    let mut preview = baseline.clone();
+    for op in &operations {
+        // Operations only touch unlocked clips that exist, so they apply cleanly
+        let _ = preview.apply_operation(op.clone());
+    }
+    preview.consolidate_timeline();
+    preview.narration = vec![narration];
+    let after_ticks = primary_duration(&preview);
+
+    NarrationPlan {
+        narration_ticks,
+        before_ticks,
+        after_ticks,
+        beats: groups.into_iter().map(|(fit, _)| fit).collect(),
+        operations,
+        message,
+        diff: generate_diff(&baseline, &preview),
+        preview,
+    }
+}
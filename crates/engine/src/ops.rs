@@ -1,3 +1,4 @@
+use crate::render::{playback_speed, playback_ticks};
 use crate::timeline::*;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -64,6 +65,104 @@ pub enum TimelineOperation {
     },
     ConsolidateTimeline,
     ClearTimeline,
+    SetClipLocked {
+        clip_id: String,
+        locked: bool,
+    },
+    LockRegion {
+        start_ticks: i64,
+        end_ticks: i64,
+        label: Option<String>,
+    },
+    UnlockRegion { region_id: String },
+    /// Lay out an overlay clip picture-in-picture (None makes it fill the frame again)
+    SetClipTransform {
+        clip_id: String,
+        transform: Option<ClipTransform>,
+    },
+    /// Add a keyframe to an overlay clip's transform, replacing one at the same offset
+    SetTransformKeyframe {
+        clip_id: String,
+        keyframe: TransformKeyframe,
+    },
+    RemoveTransformKeyframe {
+        clip_id: String,
+        offset_ticks: i64,
+    },
+    /// Set a still clip's pan/zoom (None lets automatic motion choose one again)
+    SetKenBurns {
+        clip_id: String,
+        ken_burns: Option<KenBurns>,
+    },
+    /// Letterbox or reframe a clip whose aspect ratio differs from the project's (None follows
+    /// the automatic choice again)
+    SetClipFit {
+        clip_id: String,
+        fit: Option<ClipFit>,
+    },
+    /// Play a primary clip faster or slower on export (1.0 is normal speed)
+    SetClipSpeed {
+        clip_id: String,
+        speed: f64,
+    },
+    /// Set a clip's dialog cleanup (None follows the export's setting)
+    SetAudioEnhancement {
+        clip_id: String,
+        audio_enhancement: Option<AudioEnhancement>,
+    },
+    /// Let a primary clip's audio start before its picture (lead, a J-cut) or run past it
+    /// (trail, an L-cut); None puts its sound back in line with the picture
+    SetSplitAudio {
+        clip_id: String,
+        split_audio: Option<SplitAudio>,
+    },
+    /// Silence a clip's own sound, or bring it back
+    SetClipAudioMuted {
+        clip_id: String,
+        muted: bool,
+    },
+    /// Set how a clip shot at another frame rate is converted to the project's (None follows
+    /// the project's policy)
+    SetFrameRateConversion {
+        clip_id: String,
+        frame_rate_conversion: Option<FrameRateConversion>,
+    },
+    /// Set the project's frame-rate conversion for clips without their own
+    SetFrameRatePolicy {
+        frame_rate_conversion: FrameRateConversion,
+    },
+    /// Cut to another angle of a multicam group: the primary clip playing at `position_ticks`
+    /// continues from there with the same moment seen from `asset_id`
+    SwitchAngle {
+        position_ticks: i64,
+        asset_id: i64,
+        angles: Vec<MulticamAngle>,
+    },
+}
+
+/// Slowest and fastest a clip can be played back
+pub const MIN_CLIP_SPEED: f64 = 0.25;
+pub const MAX_CLIP_SPEED: f64 = 4.0;
+
+/// A camera angle of a synced multicam group
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MulticamAngle {
+    pub asset_id: i64,
+    /// Where the asset's source starts on the group's shared clock
+    pub offset_ticks: i64,
+    pub duration_ticks: i64,
+}
+
+impl TimelineOperation {
+    /// Operations that change locks rather than content (always allowed)
+    pub fn is_lock_operation(&self) -> bool {
+        matches!(
+            self,
+            TimelineOperation::SetClipLocked { .. }
+                | TimelineOperation::LockRegion { .. }
+                | TimelineOperation::UnlockRegion { .. }
+        )
+    }
 }
 
 impl Timeline {
@@ -78,7 +177,7 @@ impl Timeline {
             let mut current_time = 0i64;
             for clip in &mut primary_track.clips {
                 clip.timeline_start_ticks = current_time;
-                current_time += clip.out_ticks - clip.in_ticks;
+                current_time += playback_ticks(clip);
             }
         }
     }
@@ -115,6 +214,28 @@ impl Timeline {
         max_id + 1
     }
 
+    /// Change an overlay clip's transform (starting from the default), keeping it only if the
+    /// result is valid
+    fn update_overlay_transform(
+        &mut self,
+        clip_id: &str,
+        update: impl FnOnce(&mut ClipTransform) -> Result<(), String>,
+    ) -> Result<(), String> {
+        for track in &mut self.tracks {
+            if let Some(clip) = track.clips.iter_mut().find(|c| c.id == clip_id) {
+                if track.id == 1 {
+                    return Err("Only overlay clips can be transformed".to_string());
+                }
+                let mut transform = clip.transform.clone().unwrap_or_default();
+                update(&mut transform)?;
+                transform.validate()?;
+                clip.transform = Some(transform);
+                return Ok(());
+            }
+        }
+        Err("Clip not found".to_string())
+    }
+
     /// Consolidates all primary clips to track 1 and removes empty tracks
     /// This ensures the magnetic timeline model is maintained
     /// NOTE: Overlay tracks (id > 1) are preserved - only clips that should be on primary are moved
@@ -161,7 +282,17 @@ impl Timeline {
         self.repack_primary_timeline();
     }
 
+    /// Apply an operation, refusing it if it would move or replace locked content
+    /// (use `lock_conflict` first to get the structured warning)
     pub fn apply_operation(&mut self, op: TimelineOperation) -> Result<(), String> {
+        if let Some(warning) = self.lock_conflict(&op) {
+            return Err(warning.message);
+        }
+        self.apply_operation_unchecked(op)
+    }
+
+    /// Apply an operation without checking locks
+    pub(crate) fn apply_operation_unchecked(&mut self, op: TimelineOperation) -> Result<(), String> {
         match op {
             TimelineOperation::SplitClip {
                 clip_id,
@@ -178,7 +309,7 @@ impl Timeline {
                             let relative_pos = position_ticks - clip.timeline_start_ticks;
                             let split_in = clip.in_ticks + relative_pos;
 
-                            let new_clip = ClipInstance {
+                            let mut new_clip = ClipInstance {
                                 id: uuid::Uuid::new_v4().to_string(),
                                 asset_id: clip.asset_id,
                                 in_ticks: split_in,
@@ -186,8 +317,40 @@ impl Timeline {
                                 timeline_start_ticks: position_ticks,
                                 speed: clip.speed,
                                 track_id: clip.track_id,
+                                locked: clip.locked,
+                                segment_id: clip.segment_id,
+                                agent_placed: clip.agent_placed,
+                                // Keyframes stay at the same moments of the source
+                                transform: clip.transform.clone().map(|mut t| {
+                                    for keyframe in &mut t.keyframes {
+                                        keyframe.offset_ticks -= relative_pos;
+                                    }
+                                    t
+                                }),
+                                ken_burns: None,
+                                audio_enhancement: clip.audio_enhancement.clone(),
+                                fit: clip.fit.clone(),
+                                // The lead stays on the first half, the trail moves to the second
+                                split_audio: clip
+                                    .split_audio
+                                    .as_mut()
+                                    .map(|split| SplitAudio {
+                                        lead_ticks: 0,
+                                        trail_ticks: std::mem::take(&mut split.trail_ticks),
+                                    })
+                                    .filter(|split| !split.is_empty()),
+                                frame_rate_conversion: clip.frame_rate_conversion,
+                                audio_muted: clip.audio_muted,
                             };
+                            clip.split_audio = clip.split_audio.take().filter(|split| !split.is_empty());
 
+                            // Each half takes its part of the pan/zoom
+                            if let Some(ken_burns) = clip.ken_burns.take() {
+                                let progress = relative_pos as f64 / (clip.out_ticks - clip.in_ticks) as f64;
+                                let (left, right) = ken_burns.split(progress);
+                                clip.ken_burns = Some(left);
+                                new_clip.ken_burns = Some(right);
+                            }
                             clip.out_ticks = split_in;
                             track.clips.insert(clip_index + 1, new_clip);
                             return Ok(());
@@ -295,6 +458,16 @@ impl Timeline {
                     timeline_start_ticks: position_ticks,
                     speed: 1.0,
                     track_id: actual_track_id,
+                    locked: false,
+                    segment_id: None,
+                    agent_placed: false,
+                    transform: None,
+                    ken_burns: None,
+                    audio_enhancement: None,
+                    fit: None,
+                    split_audio: None,
+                    frame_rate_conversion: None,
+                    audio_muted: false,
                 };
                 track.clips.push(clip);
                 
@@ -477,6 +650,9 @@ impl Timeline {
                     };
 
                     clip.track_id = new_track_id;
+                    if new_track_id == 1 {
+                        clip.transform = None;
+                    }
                     target_track.clips.push(clip);
                     Ok(())
                 } else {
@@ -521,6 +697,16 @@ impl Timeline {
                     timeline_start_ticks: position_ticks,
                     speed: 1.0,
                     track_id: primary_track.id,
+                    locked: false,
+                    segment_id: None,
+                    agent_placed: false,
+                    transform: None,
+                    ken_burns: None,
+                    audio_enhancement: None,
+                    fit: None,
+                    split_audio: None,
+                    frame_rate_conversion: None,
+                    audio_muted: false,
                 };
 
                 // Insert clip in sorted order by timeline_start_ticks
@@ -537,7 +723,7 @@ impl Timeline {
             }
             TimelineOperation::RippleInsertClipFromRange {
                 asset_id,
-                segment_id: _segment_id, // Stored for tracking, but not used in ClipInstance (could be stored in metadata)
+                segment_id,
                 src_in_ticks,
                 src_out_ticks,
                 position_ticks,
@@ -579,6 +765,16 @@ impl Timeline {
                     timeline_start_ticks: position_ticks,
                     speed: 1.0,
                     track_id,
+                    locked: false,
+                    segment_id: Some(segment_id),
+                    agent_placed: false,
+                    transform: None,
+                    ken_burns: None,
+                    audio_enhancement: None,
+                    fit: None,
+                    split_audio: None,
+                    frame_rate_conversion: None,
+                    audio_muted: false,
                 };
 
                 // Insert clip in sorted order by timeline_start_ticks
@@ -656,6 +852,16 @@ impl Timeline {
                     timeline_start_ticks: position_ticks,
                     speed: 1.0,
                     track_id: primary_track.id,
+                    locked: false,
+                    segment_id: None,
+                    agent_placed: false,
+                    transform: None,
+                    ken_burns: None,
+                    audio_enhancement: None,
+                    fit: None,
+                    split_audio: None,
+                    frame_rate_conversion: None,
+                    audio_muted: false,
                 };
 
                 let insert_index = primary_track.clips
@@ -701,6 +907,16 @@ impl Timeline {
                     timeline_start_ticks: position_ticks,
                     speed: 1.0,
                     track_id: overlay_track.id,
+                    locked: false,
+                    segment_id: None,
+                    agent_placed: false,
+                    transform: None,
+                    ken_burns: None,
+                    audio_enhancement: None,
+                    fit: None,
+                    split_audio: None,
+                    frame_rate_conversion: None,
+                    audio_muted: false,
                 };
 
                 // Insert in sorted order
@@ -833,6 +1049,8 @@ impl Timeline {
                     // Update clip's position and track_id
                     clip.timeline_start_ticks = clamped_position;
                     clip.track_id = 1;
+                    // The primary storyline fills the frame
+                    clip.transform = None;
                     
                     // Insert clip in sorted order
                     let insert_index = primary_track.clips
@@ -867,12 +1085,275 @@ impl Timeline {
                 for track in &mut self.tracks {
                     track.clips.clear();
                 }
-                // Also clear captions, music, and markers
+                // Also clear captions, music, narration and markers
                 self.captions.clear();
                 self.music.clear();
+                self.narration.clear();
                 self.markers.clear();
                 Ok(())
             }
+            TimelineOperation::SetClipLocked { clip_id, locked } => {
+                for track in &mut self.tracks {
+                    if let Some(clip) = track.clips.iter_mut().find(|c| c.id == clip_id) {
+                        clip.locked = locked;
+                        return Ok(());
+                    }
+                }
+                Err("Clip not found".to_string())
+            }
+            TimelineOperation::LockRegion {
+                start_ticks,
+                end_ticks,
+                label,
+            } => {
+                if end_ticks <= start_ticks || start_ticks < 0 {
+                    return Err("Locked region must have a positive length".to_string());
+                }
+                self.locked_regions.push(LockedRegion {
+                    id: Uuid::new_v4().to_string(),
+                    start_ticks,
+                    end_ticks,
+                    label,
+                });
+                Ok(())
+            }
+            TimelineOperation::UnlockRegion { region_id } => {
+                let before = self.locked_regions.len();
+                self.locked_regions.retain(|r| r.id != region_id);
+                if self.locked_regions.len() < before {
+                    Ok(())
+                } else {
+                    Err("Locked region not found".to_string())
+                }
+            }
+            TimelineOperation::SetClipTransform { clip_id, transform } => {
+                if let Some(transform) = &transform {
+                    transform.validate()?;
+                }
+                for track in &mut self.tracks {
+                    if let Some(clip) = track.clips.iter_mut().find(|c| c.id == clip_id) {
+                        if track.id == 1 {
+                            return Err("Only overlay clips can be transformed".to_string());
+                        }
+                        clip.transform = transform;
+                        return Ok(());
+                    }
+                }
+                Err("Clip not found".to_string())
+            }
+            TimelineOperation::SetTransformKeyframe { clip_id, keyframe } => {
+                self.update_overlay_transform(&clip_id, |transform| {
+                    transform.keyframes.retain(|k| k.offset_ticks != keyframe.offset_ticks);
+                    let index = transform
+                        .keyframes
+                        .iter()
+                        .position(|k| k.offset_ticks > keyframe.offset_ticks)
+                        .unwrap_or(transform.keyframes.len());
+                    transform.keyframes.insert(index, keyframe);
+                    Ok(())
+                })
+            }
+            TimelineOperation::RemoveTransformKeyframe { clip_id, offset_ticks } => {
+                self.update_overlay_transform(&clip_id, |transform| {
+                    let before = transform.keyframes.len();
+                    transform.keyframes.retain(|k| k.offset_ticks != offset_ticks);
+                    if transform.keyframes.len() < before {
+                        Ok(())
+                    } else {
+                        Err("Keyframe not found".to_string())
+                    }
+                })
+            }
+            TimelineOperation::SetKenBurns { clip_id, ken_burns } => {
+                if let Some(ken_burns) = &ken_burns {
+                    ken_burns.validate()?;
+                }
+                let clip = self
+                    .tracks
+                    .iter_mut()
+                    .flat_map(|t| t.clips.iter_mut())
+                    .find(|c| c.id == clip_id)
+                    .ok_or_else(|| "Clip not found".to_string())?;
+                clip.ken_burns = ken_burns;
+                Ok(())
+            }
+            TimelineOperation::SetClipFit { clip_id, fit } => {
+                if let Some(fit) = &fit {
+                    fit.validate()?;
+                }
+                let clip = self
+                    .tracks
+                    .iter_mut()
+                    .flat_map(|t| t.clips.iter_mut())
+                    .find(|c| c.id == clip_id)
+                    .ok_or_else(|| "Clip not found".to_string())?;
+                clip.fit = fit;
+                Ok(())
+            }
+            TimelineOperation::SetClipSpeed { clip_id, speed } => {
+                if !(MIN_CLIP_SPEED..=MAX_CLIP_SPEED).contains(&speed) {
+                    return Err(format!("Speed must be between {} and {}", MIN_CLIP_SPEED, MAX_CLIP_SPEED));
+                }
+                let clip = self
+                    .tracks
+                    .iter_mut()
+                    .find(|t| t.id == 1)
+                    .and_then(|t| t.clips.iter_mut().find(|c| c.id == clip_id))
+                    .ok_or_else(|| "Clip not found on the primary track".to_string())?;
+                clip.speed = speed;
+                // The clip's length on the timeline changed with it
+                self.repack_primary_timeline();
+                Ok(())
+            }
+            TimelineOperation::SetAudioEnhancement { clip_id, audio_enhancement } => {
+                if let Some(audio_enhancement) = &audio_enhancement {
+                    audio_enhancement.validate()?;
+                }
+                let clip = self
+                    .tracks
+                    .iter_mut()
+                    .flat_map(|t| t.clips.iter_mut())
+                    .find(|c| c.id == clip_id)
+                    .ok_or_else(|| "Clip not found".to_string())?;
+                clip.audio_enhancement = audio_enhancement;
+                Ok(())
+            }
+            TimelineOperation::SetSplitAudio { clip_id, split_audio } => {
+                if let Some(split_audio) = &split_audio {
+                    split_audio.validate()?;
+                }
+                let primary_track = self
+                    .tracks
+                    .iter_mut()
+                    .find(|t| t.id == 1)
+                    .ok_or_else(|| "Clip not found on the primary track".to_string())?;
+                primary_track.clips.sort_by_key(|c| c.timeline_start_ticks);
+                let index = primary_track
+                    .clips
+                    .iter()
+                    .position(|c| c.id == clip_id)
+                    .ok_or_else(|| "Clip not found on the primary track".to_string())?;
+                let split_audio = split_audio.filter(|split| !split.is_empty());
+                if let Some(split) = &split_audio {
+                    let clips = &primary_track.clips;
+                    let clip = &clips[index];
+                    let previous = index.checked_sub(1).map(|i| &clips[i]);
+                    let next = clips.get(index + 1);
+                    if split.lead_ticks > 0 {
+                        let previous = previous.ok_or_else(|| "The first clip has no clip to lead into".to_string())?;
+                        if split.lead_ticks >= playback_ticks(previous) {
+                            return Err("Lead must be shorter than the previous clip".to_string());
+                        }
+                        if (split.lead_ticks as f64 * playback_speed(clip)) as i64 > clip.in_ticks {
+                            return Err("Lead runs past the start of the clip's source".to_string());
+                        }
+                        if previous.split_audio.as_ref().is_some_and(|s| s.trail_ticks > 0) {
+                            return Err("The previous clip's audio already trails into this one".to_string());
+                        }
+                    }
+                    if split.trail_ticks > 0 {
+                        let next = next.ok_or_else(|| "The last clip has no clip to trail into".to_string())?;
+                        if split.trail_ticks >= playback_ticks(next) {
+                            return Err("Trail must be shorter than the next clip".to_string());
+                        }
+                        if next.split_audio.as_ref().is_some_and(|s| s.lead_ticks > 0) {
+                            return Err("The next clip's audio already leads into this one".to_string());
+                        }
+                    }
+                }
+                primary_track.clips[index].split_audio = split_audio;
+                Ok(())
+            }
+            TimelineOperation::SetClipAudioMuted { clip_id, muted } => {
+                let clip = self
+                    .tracks
+                    .iter_mut()
+                    .flat_map(|t| t.clips.iter_mut())
+                    .find(|c| c.id == clip_id)
+                    .ok_or_else(|| "Clip not found".to_string())?;
+                clip.audio_muted = muted;
+                Ok(())
+            }
+            TimelineOperation::SetFrameRateConversion { clip_id, frame_rate_conversion } => {
+                let clip = self
+                    .tracks
+                    .iter_mut()
+                    .flat_map(|t| t.clips.iter_mut())
+                    .find(|c| c.id == clip_id)
+                    .ok_or_else(|| "Clip not found".to_string())?;
+                clip.frame_rate_conversion = frame_rate_conversion;
+                Ok(())
+            }
+            TimelineOperation::SetFrameRatePolicy { frame_rate_conversion } => {
+                self.settings.frame_rate_conversion = frame_rate_conversion;
+                Ok(())
+            }
+            TimelineOperation::SwitchAngle {
+                position_ticks,
+                asset_id,
+                angles,
+            } => {
+                let primary_track = self
+                    .tracks
+                    .iter_mut()
+                    .find(|t| t.id == 1)
+                    .ok_or_else(|| "No primary track".to_string())?;
+                let clip_index = primary_track
+                    .clips
+                    .iter()
+                    .position(|c| {
+                        position_ticks >= c.timeline_start_ticks
+                            && position_ticks < c.timeline_start_ticks + (c.out_ticks - c.in_ticks)
+                    })
+                    .ok_or_else(|| "No clip at position".to_string())?;
+                let clip = &primary_track.clips[clip_index];
+                if clip.asset_id == asset_id {
+                    return Ok(());
+                }
+                let current = angles
+                    .iter()
+                    .find(|a| a.asset_id == clip.asset_id)
+                    .ok_or_else(|| "Clip at position isn't an angle of the multicam group".to_string())?;
+                let target = angles
+                    .iter()
+                    .find(|a| a.asset_id == asset_id)
+                    .ok_or_else(|| "Target isn't an angle of the multicam group".to_string())?;
+
+                // Same moment on the shared clock, seen from the target angle
+                let cut_in = clip.in_ticks + (position_ticks - clip.timeline_start_ticks);
+                let target_in = current.offset_ticks + cut_in - target.offset_ticks;
+                let target_out = target_in + (clip.out_ticks - cut_in);
+                if target_in < 0 || target_out > target.duration_ticks {
+                    return Err("Target angle wasn't recording for the rest of the clip".to_string());
+                }
+
+                let switched = ClipInstance {
+                    id: Uuid::new_v4().to_string(),
+                    asset_id,
+                    in_ticks: target_in,
+                    out_ticks: target_out,
+                    timeline_start_ticks: position_ticks,
+                    speed: clip.speed,
+                    track_id: clip.track_id,
+                    locked: false,
+                    segment_id: None,
+                    agent_placed: false,
+                    transform: None,
+                    ken_burns: None,
+                    audio_enhancement: None,
+                    fit: None,
+                    split_audio: None,
+                    frame_rate_conversion: None,
+                    audio_muted: false,
+                };
+                if cut_in == clip.in_ticks {
+                    primary_track.clips[clip_index] = switched;
+                } else {
+                    primary_track.clips[clip_index].out_ticks = cut_in;
+                    primary_track.clips.insert(clip_index + 1, switched);
+                }
+                Ok(())
+            }
         }
     }
 }
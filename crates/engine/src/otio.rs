@@ -0,0 +1,529 @@
+//! OpenTimelineIO (.otio JSON) interchange.
+//!
+//! Export writes standard OTIO objects (Stack, Track, Clip, Gap, Marker, ExternalReference,
+//! LinearTimeWarp) so other editorial tools can read the cut. Everything OTIO has no slot for
+//! (clip ids, segment ids, locks, captions, locked regions, project settings) is kept under a
+//! `vibecut` metadata key, so exporting and re-importing gives back the same timeline.
+
+use crate::timeline::{
+    CaptionEvent, ClipInstance, FrameRateConversion, LockedRegion, Marker, MusicEvent, NarrationEvent, ProjectSettings, Resolution, Timeline, Track,
+    TrackKind, TICKS_PER_SECOND,
+};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+/// Frame rate used when neither the timeline nor the OTIO file provides one
+const FALLBACK_RATE: f64 = 30.0;
+/// Name of the OTIO audio track music events are exported to
+const MUSIC_TRACK_NAME: &str = "Music";
+/// Name of the OTIO audio track narration is exported to
+const NARRATION_TRACK_NAME: &str = "Narration";
+
+/// Serialize a timeline as an OTIO `Timeline.1`. `media_paths` maps asset ids to the source
+/// files clips should reference; clips whose asset has no path get a MissingReference.
+pub fn timeline_to_otio(timeline: &Timeline, name: &str, media_paths: &HashMap<i64, String>) -> Value {
+    let rate = if timeline.settings.fps > 0.0 { timeline.settings.fps } else { FALLBACK_RATE };
+    let tps = timeline.settings.ticks_per_second;
+    let time = |ticks: i64| rational_time(ticks, rate, tps);
+    let range = |start: i64, duration: i64| time_range(time(start), time(duration));
+
+    let mut tracks: Vec<&Track> = timeline.tracks.iter().collect();
+    tracks.sort_by_key(|t| t.id);
+
+    let mut otio_tracks = Vec::new();
+    for track in tracks {
+        let kind = match track.kind {
+            TrackKind::Video => "Video",
+            TrackKind::Audio => "Audio",
+            TrackKind::Caption => "Caption",
+        };
+        let clips = track.clips.iter().map(|c| (c.timeline_start_ticks, clip_timeline_ticks(c), c));
+        // OTIO tracks can't overlap, so overlapping clips (overlays) spill into extra lanes that
+        // share the vibecut track id
+        for (lane, clips) in into_lanes(clips).into_iter().enumerate() {
+            let children = with_gaps(clips, &range, |clip| {
+                let path = media_paths.get(&clip.asset_id);
+                let clip_name = path
+                    .and_then(|p| std::path::Path::new(p).file_name())
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_else(|| format!("asset {}", clip.asset_id));
+                let effects = if (clip.speed - 1.0).abs() > f64::EPSILON {
+                    vec![json!({
+                        "OTIO_SCHEMA": "LinearTimeWarp.1",
+                        "name": "speed",
+                        "effect_name": "LinearTimeWarp",
+                        "time_scalar": clip.speed,
+                        "metadata": {},
+                    })]
+                } else {
+                    Vec::new()
+                };
+                json!({
+                    "OTIO_SCHEMA": "Clip.1",
+                    "name": clip_name,
+                    "source_range": range(clip.in_ticks, clip_timeline_ticks(clip)),
+                    "media_reference": media_reference(path.map(String::as_str)),
+                    "effects": effects,
+                    "markers": [],
+                    "metadata": {
+                        "vibecut": {
+                            "clip_id": clip.id,
+                            "asset_id": clip.asset_id,
+                            "segment_id": clip.segment_id,
+                            "locked": clip.locked,
+                            "agent_placed": clip.agent_placed,
+                            "transform": clip.transform,
+                            "ken_burns": clip.ken_burns,
+                            "audio_enhancement": clip.audio_enhancement,
+                            "fit": clip.fit,
+                            "split_audio": clip.split_audio,
+                            "frame_rate_conversion": clip.frame_rate_conversion,
+                            "audio_muted": clip.audio_muted,
+                        }
+                    },
+                })
+            });
+            let prefix = if track.kind == TrackKind::Audio { "A" } else { "V" };
+            otio_tracks.push(json!({
+                "OTIO_SCHEMA": "Track.1",
+                "name": if lane == 0 { format!("{}{}", prefix, track.id) } else { format!("{}{}.{}", prefix, track.id, lane + 1) },
+                // OTIO only knows video and audio tracks; captions travel as video
+                "kind": if track.kind == TrackKind::Audio { "Audio" } else { "Video" },
+                "source_range": null,
+                "children": children,
+                "effects": [],
+                "markers": [],
+                "metadata": { "vibecut": { "track_id": track.id, "kind": kind } },
+            }));
+        }
+    }
+
+    let music = timeline.music.iter().map(|m| (m.start_ticks, m.end_ticks - m.start_ticks, m));
+    for (lane, events) in into_lanes(music).into_iter().enumerate() {
+        let children = with_gaps(events, &range, |music| {
+            json!({
+                "OTIO_SCHEMA": "Clip.1",
+                "name": std::path::Path::new(&music.track_path)
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_else(|| music.track_path.clone()),
+                "source_range": range(0, music.end_ticks - music.start_ticks),
+                "media_reference": media_reference(Some(&music.track_path)),
+                "effects": [],
+                "markers": [],
+                "metadata": { "vibecut": { "ducking_profile_id": music.ducking_profile_id } },
+            })
+        });
+        otio_tracks.push(json!({
+            "OTIO_SCHEMA": "Track.1",
+            "name": if lane == 0 { MUSIC_TRACK_NAME.to_string() } else { format!("{} {}", MUSIC_TRACK_NAME, lane + 1) },
+            "kind": "Audio",
+            "source_range": null,
+            "children": children,
+            "effects": [],
+            "markers": [],
+            "metadata": { "vibecut": { "music": true } },
+        }));
+    }
+
+    let narration = timeline.narration.iter().map(|n| (n.start_ticks, n.end_ticks - n.start_ticks, n));
+    for (lane, events) in into_lanes(narration).into_iter().enumerate() {
+        let children = with_gaps(events, &range, |narration| {
+            json!({
+                "OTIO_SCHEMA": "Clip.1",
+                "name": std::path::Path::new(&narration.track_path)
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_else(|| narration.track_path.clone()),
+                "source_range": range(0, narration.end_ticks - narration.start_ticks),
+                "media_reference": media_reference(Some(&narration.track_path)),
+                "effects": [],
+                "markers": [],
+                "metadata": { "vibecut": { "voiceover_id": narration.voiceover_id } },
+            })
+        });
+        otio_tracks.push(json!({
+            "OTIO_SCHEMA": "Track.1",
+            "name": if lane == 0 { NARRATION_TRACK_NAME.to_string() } else { format!("{} {}", NARRATION_TRACK_NAME, lane + 1) },
+            "kind": "Audio",
+            "source_range": null,
+            "children": children,
+            "effects": [],
+            "markers": [],
+            "metadata": { "vibecut": { "narration": true } },
+        }));
+    }
+
+    let mut markers: Vec<Value> = timeline
+        .markers
+        .iter()
+        .map(|m| marker(m.label.as_deref().unwrap_or(""), range(m.position_ticks, 0), "RED", json!({ "type": "marker" })))
+        .collect();
+    markers.extend(timeline.captions.iter().map(|c| {
+        marker(
+            &c.text,
+            range(c.start_ticks, c.end_ticks - c.start_ticks),
+            "YELLOW",
+            json!({ "type": "caption", "template_id": c.template_id, "style": c.style }),
+        )
+    }));
+    markers.extend(timeline.locked_regions.iter().map(|r| {
+        marker(
+            r.label.as_deref().unwrap_or(""),
+            range(r.start_ticks, r.end_ticks - r.start_ticks),
+            "PURPLE",
+            json!({ "type": "locked_region", "id": r.id }),
+        )
+    }));
+
+    json!({
+        "OTIO_SCHEMA": "Timeline.1",
+        "name": name,
+        "global_start_time": time(0),
+        "tracks": {
+            "OTIO_SCHEMA": "Stack.1",
+            "name": "tracks",
+            "source_range": null,
+            "children": otio_tracks,
+            "effects": [],
+            "markers": markers,
+            "metadata": {},
+        },
+        "metadata": { "vibecut": { "settings": timeline.settings } },
+    })
+}
+
+/// Build a timeline from an OTIO `Timeline`. `resolve_asset` maps each clip's media path (empty
+/// without a media reference) and the asset id it was exported with (VibeCut exports only) to a
+/// project asset id. Unmatched audio becomes music; other clips that can't be matched fail the
+/// import with their paths listed.
+pub fn timeline_from_otio(
+    otio: &Value,
+    mut resolve_asset: impl FnMut(&str, Option<i64>) -> Option<i64>,
+) -> Result<Timeline, String> {
+    let schema = schema_of(otio);
+    if schema != "Timeline" {
+        return Err(format!("Expected an OTIO Timeline, got {}", if schema.is_empty() { "no OTIO_SCHEMA" } else { schema }));
+    }
+    let stack = &otio["tracks"];
+    let otio_tracks = stack["children"].as_array().cloned().unwrap_or_default();
+
+    let settings = match serde_json::from_value::<ProjectSettings>(otio["metadata"]["vibecut"]["settings"].clone()) {
+        Ok(settings) => settings,
+        Err(_) => ProjectSettings {
+            fps: first_rate(stack).unwrap_or(FALLBACK_RATE),
+            resolution: Resolution { width: 1920, height: 1080 },
+            sample_rate: 48000,
+            ticks_per_second: TICKS_PER_SECOND,
+            frame_rate_conversion: FrameRateConversion::default(),
+        },
+    };
+    let tps = settings.ticks_per_second;
+    let ticks = |time: &Value| rational_ticks(time, tps);
+
+    let mut timeline = Timeline::new(settings.clone());
+    let mut unresolved = Vec::new();
+    let mut next_track_id = 1;
+
+    for otio_track in &otio_tracks {
+        if schema_of(otio_track) != "Track" {
+            return Err(format!("Unsupported item in the track stack: {}", schema_of(otio_track)));
+        }
+        let meta = &otio_track["metadata"]["vibecut"];
+        let is_music = meta["music"].as_bool().unwrap_or(false);
+        let is_narration = meta["narration"].as_bool().unwrap_or(false);
+        let kind = match meta["kind"].as_str().or(otio_track["kind"].as_str()) {
+            Some("Audio") => TrackKind::Audio,
+            Some("Caption") => TrackKind::Caption,
+            _ => TrackKind::Video,
+        };
+        let track_id = meta["track_id"].as_i64().unwrap_or_else(|| {
+            while timeline.tracks.iter().any(|t| t.id == next_track_id) {
+                next_track_id += 1;
+            }
+            next_track_id
+        });
+
+        let mut cursor = 0;
+        let mut clips = Vec::new();
+        for item in otio_track["children"].as_array().into_iter().flatten() {
+            // Transitions overlap their neighbours and don't take up track time
+            if schema_of(item) == "Transition" {
+                continue;
+            }
+            let source_range = item["source_range"]
+                .as_object()
+                .map(|_| &item["source_range"])
+                .or_else(|| item["media_reference"]["available_range"].as_object().map(|_| &item["media_reference"]["available_range"]))
+                .ok_or_else(|| format!("'{}' has no source range", item["name"].as_str().unwrap_or("item")))?;
+            let start = ticks(&source_range["start_time"])?;
+            let duration = ticks(&source_range["duration"])?;
+
+            if schema_of(item) == "Clip" {
+                let path = item["media_reference"]["target_url"].as_str().map(url_to_path);
+                let clip_meta = &item["metadata"]["vibecut"];
+                if is_music {
+                    timeline.music.push(MusicEvent {
+                        start_ticks: cursor,
+                        end_ticks: cursor + duration,
+                        track_path: path.unwrap_or_default(),
+                        ducking_profile_id: clip_meta["ducking_profile_id"].as_i64(),
+                    });
+                } else if is_narration {
+                    timeline.narration.push(NarrationEvent {
+                        start_ticks: cursor,
+                        end_ticks: cursor + duration,
+                        track_path: path.unwrap_or_default(),
+                        voiceover_id: clip_meta["voiceover_id"].as_i64(),
+                    });
+                } else {
+                    let hint = clip_meta["asset_id"].as_i64();
+                    match resolve_asset(path.as_deref().unwrap_or(""), hint) {
+                        Some(asset_id) => {
+                            let speed = item["effects"]
+                                .as_array()
+                                .into_iter()
+                                .flatten()
+                                .find(|e| schema_of(e) == "LinearTimeWarp")
+                                .and_then(|e| e["time_scalar"].as_f64())
+                                .filter(|s| *s > 0.0)
+                                .unwrap_or(1.0);
+                            clips.push(ClipInstance {
+                                id: clip_meta["clip_id"]
+                                    .as_str()
+                                    .map(String::from)
+                                    .unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+                                asset_id,
+                                in_ticks: start,
+                                out_ticks: start + (duration as f64 * speed).round() as i64,
+                                timeline_start_ticks: cursor,
+                                speed,
+                                track_id,
+                                locked: clip_meta["locked"].as_bool().unwrap_or(false),
+                                segment_id: clip_meta["segment_id"].as_i64(),
+                                agent_placed: clip_meta["agent_placed"].as_bool().unwrap_or(false),
+                                transform: serde_json::from_value(clip_meta["transform"].clone()).ok().flatten(),
+                                ken_burns: serde_json::from_value(clip_meta["ken_burns"].clone()).ok().flatten(),
+                                audio_enhancement: serde_json::from_value(clip_meta["audio_enhancement"].clone()).ok().flatten(),
+                                fit: serde_json::from_value(clip_meta["fit"].clone()).ok().flatten(),
+                                split_audio: serde_json::from_value(clip_meta["split_audio"].clone()).ok().flatten(),
+                                frame_rate_conversion: serde_json::from_value(clip_meta["frame_rate_conversion"].clone()).ok().flatten(),
+                                audio_muted: clip_meta["audio_muted"].as_bool().unwrap_or(false),
+                            });
+                        }
+                        // Audio that isn't project footage (e.g. a music bed from another tool)
+                        None if kind == TrackKind::Audio && path.is_some() => timeline.music.push(MusicEvent {
+                            start_ticks: cursor,
+                            end_ticks: cursor + duration,
+                            track_path: path.unwrap_or_default(),
+                            ducking_profile_id: None,
+                        }),
+                        None => unresolved.push(path.unwrap_or_else(|| item["name"].as_str().unwrap_or("unnamed clip").to_string())),
+                    }
+                }
+            }
+            cursor += duration;
+        }
+
+        if is_music || is_narration {
+            continue;
+        }
+        match timeline.tracks.iter_mut().find(|t| t.id == track_id) {
+            Some(track) => {
+                track.clips.extend(clips);
+                track.clips.sort_by_key(|c| c.timeline_start_ticks);
+            }
+            None => timeline.tracks.push(Track { id: track_id, kind, clips }),
+        }
+    }
+
+    if !unresolved.is_empty() {
+        return Err(format!("Couldn't match media for: {}", unresolved.join(", ")));
+    }
+
+    for m in stack["markers"].as_array().into_iter().flatten() {
+        let start = ticks(&m["marked_range"]["start_time"])?;
+        let end = start + ticks(&m["marked_range"]["duration"])?;
+        let name = m["name"].as_str().unwrap_or("").to_string();
+        let meta = &m["metadata"]["vibecut"];
+        match meta["type"].as_str() {
+            Some("caption") => timeline.captions.push(CaptionEvent {
+                start_ticks: start,
+                end_ticks: end,
+                text: name,
+                template_id: meta["template_id"].as_i64(),
+                style: serde_json::from_value(meta["style"].clone()).ok().flatten(),
+            }),
+            Some("locked_region") => timeline.locked_regions.push(LockedRegion {
+                id: meta["id"].as_str().map(String::from).unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+                start_ticks: start,
+                end_ticks: end,
+                label: Some(name).filter(|n| !n.is_empty()),
+            }),
+            _ => timeline.markers.push(Marker {
+                position_ticks: start,
+                label: Some(name).filter(|n| !n.is_empty()),
+            }),
+        }
+    }
+
+    timeline.tracks.sort_by_key(|t| t.id);
+    Ok(timeline)
+}
+
+/// Ticks a clip occupies on the timeline (its source range played at its speed)
+fn clip_timeline_ticks(clip: &ClipInstance) -> i64 {
+    let speed = if clip.speed > 0.0 { clip.speed } else { 1.0 };
+    ((clip.out_ticks - clip.in_ticks) as f64 / speed).round() as i64
+}
+
+/// Split (start, duration, item) spans into lanes with no overlaps, first fit in start order
+fn into_lanes<T>(items: impl Iterator<Item = (i64, i64, T)>) -> Vec<Vec<(i64, i64, T)>> {
+    let mut items: Vec<_> = items.collect();
+    items.sort_by_key(|(start, _, _)| *start);
+    let mut lanes: Vec<Vec<(i64, i64, T)>> = Vec::new();
+    for item in items {
+        let free = lanes
+            .iter_mut()
+            .find(|lane| lane.last().is_none_or(|(start, duration, _)| start + duration <= item.0));
+        match free {
+            Some(lane) => lane.push(item),
+            None => lanes.push(vec![item]),
+        }
+    }
+    lanes
+}
+
+/// Track children for one lane: each item preceded by a Gap when it doesn't start where the
+/// previous one ended
+fn with_gaps<T>(
+    items: Vec<(i64, i64, T)>,
+    range: &impl Fn(i64, i64) -> Value,
+    mut to_otio: impl FnMut(T) -> Value,
+) -> Vec<Value> {
+    let mut children = Vec::new();
+    let mut cursor = 0;
+    for (start, duration, item) in items {
+        if start > cursor {
+            children.push(json!({
+                "OTIO_SCHEMA": "Gap.1",
+                "name": "",
+                "source_range": range(0, start - cursor),
+                "effects": [],
+                "markers": [],
+                "metadata": {},
+            }));
+        }
+        children.push(to_otio(item));
+        cursor = start + duration;
+    }
+    children
+}
+
+fn media_reference(path: Option<&str>) -> Value {
+    match path {
+        Some(path) => json!({
+            "OTIO_SCHEMA": "ExternalReference.1",
+            "name": "",
+            "target_url": path_to_url(path),
+            "available_range": null,
+            "metadata": {},
+        }),
+        None => json!({
+            "OTIO_SCHEMA": "MissingReference.1",
+            "name": "",
+            "available_range": null,
+            "metadata": {},
+        }),
+    }
+}
+
+fn marker(name: &str, marked_range: Value, color: &str, vibecut: Value) -> Value {
+    json!({
+        "OTIO_SCHEMA": "Marker.2",
+        "name": name,
+        "marked_range": marked_range,
+        "color": color,
+        "comment": "",
+        "metadata": { "vibecut": vibecut },
+    })
+}
+
+fn rational_time(ticks: i64, rate: f64, ticks_per_second: i64) -> Value {
+    json!({
+        "OTIO_SCHEMA": "RationalTime.1",
+        "rate": rate,
+        "value": ticks as f64 * rate / ticks_per_second as f64,
+    })
+}
+
+fn time_range(start_time: Value, duration: Value) -> Value {
+    json!({
+        "OTIO_SCHEMA": "TimeRange.1",
+        "start_time": start_time,
+        "duration": duration,
+    })
+}
+
+fn rational_ticks(time: &Value, ticks_per_second: i64) -> Result<i64, String> {
+    let value = time["value"].as_f64().ok_or("RationalTime without a value")?;
+    let rate = time["rate"].as_f64().filter(|r| *r > 0.0).ok_or("RationalTime without a positive rate")?;
+    Ok((value / rate * ticks_per_second as f64).round() as i64)
+}
+
+/// Schema name without its version ("Clip.2" -> "Clip")
+fn schema_of(value: &Value) -> &str {
+    value["OTIO_SCHEMA"].as_str().and_then(|s| s.split('.').next()).unwrap_or("")
+}
+
+/// Rate of the first clip in the stack, for files without VibeCut settings
+fn first_rate(stack: &Value) -> Option<f64> {
+    stack["children"]
+        .as_array()?
+        .iter()
+        .flat_map(|track| track["children"].as_array().into_iter().flatten())
+        .find_map(|item| item["source_range"]["duration"]["rate"].as_f64())
+        .filter(|rate| *rate > 0.0)
+}
+
+/// Absolute paths become file:// URLs; anything else (already a URL, relative) is kept as is
+fn path_to_url(path: &str) -> String {
+    if !path.starts_with('/') {
+        return path.to_string();
+    }
+    let mut url = String::from("file://");
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' | b'~' => url.push(byte as char),
+            _ => url.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    url
+}
+
+fn url_to_path(url: &str) -> String {
+    let Some(rest) = url.strip_prefix("file://") else {
+        return url.to_string();
+    };
+    let rest = rest.strip_prefix("localhost").unwrap_or(rest);
+    let bytes = rest.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = (bytes[i] == b'%')
+            .then(|| rest.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match hex {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
@@ -0,0 +1,109 @@
+use crate::encode::{EncoderSettings, VideoCodec};
+use crate::frame_rate::clip_frame_rates;
+use crate::incremental::RenderSpan;
+use crate::render::{playback_speed, SourceMedia};
+use crate::timeline::{FrameRateConversion, Resolution, Timeline, TrackKind};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+
+/// Height previews are rendered at, like the scrub proxies they're made from
+pub const PREVIEW_HEIGHT: i32 = 540;
+
+/// Why a stretch of the timeline is too heavy for the player to composite while scrubbing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PreviewReason {
+    /// Clips composited over the primary storyline
+    Overlay,
+    /// Clips playing faster or slower than real time
+    Retimed,
+    /// Pan and zoom across a still
+    KenBurns,
+    /// Footage blended or interpolated to the project's frame rate
+    FrameRateConversion,
+}
+
+/// A span of the timeline worth pre-rendering, and what makes it heavy
+#[derive(Debug, Clone, Serialize)]
+pub struct PreviewRegion {
+    #[serde(flatten)]
+    pub span: RenderSpan,
+    pub reasons: Vec<PreviewReason>,
+}
+
+/// The timeline at preview size: the project's aspect ratio, `PREVIEW_HEIGHT` tall (never
+/// larger than the project)
+pub fn preview_timeline(timeline: &Timeline) -> Timeline {
+    let mut preview = timeline.clone();
+    let Resolution { width, height } = timeline.settings.resolution;
+    if height > PREVIEW_HEIGHT && width > 0 {
+        let scaled_width = (width as f64 * PREVIEW_HEIGHT as f64 / height as f64 / 2.0).round() as i32 * 2;
+        preview.settings.resolution = Resolution {
+            width: scaled_width.max(2),
+            height: PREVIEW_HEIGHT,
+        };
+    }
+    preview
+}
+
+/// Encoding of previews: fast to render and to seek in, quality only good enough to scrub
+pub fn preview_encoder() -> EncoderSettings {
+    EncoderSettings {
+        codec: VideoCodec::H264,
+        crf: Some(28),
+        bitrate_kbps: None,
+        preset: "ultrafast".to_string(),
+        two_pass: false,
+        audio_bitrate_kbps: 96,
+    }
+}
+
+/// The spans with overlays, retimed clips, Ken Burns moves or blended frame-rate conversions
+/// in them. `spans` are the timeline's render spans (see `incremental::render_spans`).
+pub fn complex_regions(timeline: &Timeline, spans: &[RenderSpan], sources: &HashMap<i64, SourceMedia>) -> Vec<PreviewRegion> {
+    let source_fps: HashMap<i64, f64> = sources.iter().filter_map(|(id, s)| Some((*id, s.fps?))).collect();
+    let converted: Vec<String> = clip_frame_rates(timeline, &source_fps)
+        .into_iter()
+        .filter(|c| c.conversion != FrameRateConversion::DropDup)
+        .map(|c| c.clip_id)
+        .collect();
+
+    let mut regions = Vec::new();
+    for span in spans {
+        let mut reasons = BTreeSet::new();
+        for track in timeline.tracks.iter().filter(|t| t.kind == TrackKind::Video) {
+            let overlay = track.id > 1;
+            // The span's primary clips, or the overlays on screen during it
+            let clips = track.clips.iter().filter(|c| {
+                if overlay {
+                    sources.contains_key(&c.asset_id)
+                        && c.timeline_start_ticks < span.end_ticks
+                        && c.timeline_start_ticks + (c.out_ticks - c.in_ticks) > span.start_ticks
+                } else {
+                    span.clip_ids.contains(&c.id)
+                }
+            });
+            for clip in clips {
+                if overlay {
+                    reasons.insert(PreviewReason::Overlay);
+                }
+                if (playback_speed(clip) - 1.0).abs() > f64::EPSILON {
+                    reasons.insert(PreviewReason::Retimed);
+                }
+                if clip.ken_burns.as_ref().is_some_and(|k| k.enabled) {
+                    reasons.insert(PreviewReason::KenBurns);
+                }
+                if converted.contains(&clip.id) {
+                    reasons.insert(PreviewReason::FrameRateConversion);
+                }
+            }
+        }
+        if !reasons.is_empty() {
+            regions.push(PreviewRegion {
+                span: span.clone(),
+                reasons: reasons.into_iter().collect(),
+            });
+        }
+    }
+    regions
+}
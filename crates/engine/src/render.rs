@@ -1,19 +1,127 @@
-use crate::timeline::{ClipInstance, Timeline, TrackKind, TICKS_PER_SECOND};
-use std::path::PathBuf;
+use crate::end_card::{end_card_graph, EndCard};
+use crate::frame_rate;
+use crate::watermark::Watermark;
+use crate::title::title_filters;
+use crate::timeline::{AudioEnhancement, CaptionEvent, ClipFit, ClipInstance, ClipTransform, Easing, FitMode, KenBurns, ProjectSettings, Timeline, TrackKind, TransformProperty, TICKS_PER_SECOND};
+use crate::encode::EncoderSettings;
+use crate::render_cache;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::path::{Path, PathBuf};
 use std::collections::HashMap;
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RenderCommand {
     pub ffmpeg_args: Vec<String>,
     pub output_path: PathBuf,
     pub concat_list_path: PathBuf, // Path to concat demuxer list file
+    /// Analysis pass to run before `ffmpeg_args` for two-pass encodes
+    pub first_pass_args: Option<Vec<String>>,
+    /// Intermediates `ffmpeg_args` reads, to render first unless they're already cached
+    #[serde(default)]
+    pub clip_renders: Vec<ClipRender>,
+    /// The output has a keyframe at every cut on the primary track, so at every
+    /// `incremental::RenderSpan` boundary; only such outputs can be stitched from
+    #[serde(default)]
+    pub span_keyframes: bool,
 }
 
+/// A primary clip rendered on its own (trimmed, retimed, conformed, fitted to the frame and
+/// with its audio processed) into the clip cache, keyed by a hash of everything that goes into it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClipRender {
+    pub clip_id: String,
+    pub key: String,
+    pub path: PathBuf,
+    pub ffmpeg_args: Vec<String>,
+}
+
+/// Area of an asset's frame to blur while its source time is within [start_ticks, end_ticks).
+/// Coordinates are normalized to the frame (0-1), so they apply at any proxy resolution.
+#[derive(Debug, Clone, Serialize)]
+pub struct BlurRegion {
+    pub asset_id: i64,
+    pub start_ticks: i64,
+    pub end_ticks: i64,
+    pub x: f64,
+    pub y: f64,
+    pub w: f64,
+    pub h: f64,
+}
+
+/// File an asset is rendered from, and the filter (if any) that brings its picture to the
+/// export's color space and pixel format before anything else touches it
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SourceMedia {
+    pub path: String,
+    pub color_filter: Option<String>,
+    /// Frame rate of the file, for converting it to the project's (None for stills)
+    pub fps: Option<f64>,
+    /// Content checksum of the file, so cached renders of it go stale when it's replaced
+    pub checksum: Option<String>,
+}
+
+/// Gaussian blur strength applied to blurred regions
+const BLUR_SIGMA: f64 = 30.0;
+
+/// Export-wide audio settings
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AudioOptions {
+    /// Enhancement for clips that don't set their own
+    pub enhance: Option<AudioEnhancement>,
+    /// RNNoise model file; noise reduction falls back to afftdn without one
+    pub rnnoise_model: Option<String>,
+}
+
+/// Container extras muxed into the export alongside the picture and sound
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MuxOptions {
+    /// FFMETADATA file (see `chapters_ffmetadata`) whose chapters become MP4 chapter atoms
+    pub chapters_path: Option<String>,
+    /// SRT file (see `captions_srt`) muxed as a soft subtitle track
+    pub captions_path: Option<String>,
+    /// ISO 639-2 language of the audio track (e.g. "eng")
+    pub audio_language: Option<String>,
+    /// ISO 639-2 language of the subtitle track
+    pub caption_language: Option<String>,
+    /// Container-level key/value tags (title, project, version, ...)
+    pub metadata: Vec<(String, String)>,
+}
+
+/// Brand elements added to the export: a watermark over the program and an end card after it
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BrandingOptions {
+    pub watermark: Option<Watermark>,
+    pub end_card: Option<EndCard>,
+}
+
+/// Noise floor afftdn starts from, in dB
+const NOISE_FLOOR_DB: f64 = -25.0;
+/// Music sits this far under the program sound (about -12 dB)
+const MUSIC_GAIN: f64 = 0.25;
+/// Sidechain compression music is ducked with while narration plays: the narration level that
+/// starts it, how hard it pushes down, and how fast it reacts and lets go
+const DUCK_THRESHOLD: f64 = 0.03;
+const DUCK_RATIO: f64 = 8.0;
+const DUCK_ATTACK_MS: f64 = 20.0;
+const DUCK_RELEASE_MS: f64 = 400.0;
+
 /// Generate FFmpeg render command for timeline
-/// V1: Hard cuts only, concatenate clips in order
+/// V1: Hard cuts only, concatenate clips in order; blur regions are applied per clip.
+/// With a `clip_cache` directory, primary clips are read from intermediates there (listed in
+/// `clip_renders`), so clips that haven't changed since the last export aren't re-encoded.
+/// The `branding` watermark is laid over the program and its end card appended after it.
+#[allow(clippy::too_many_arguments)]
 pub fn generate_render_commands(
     timeline: &Timeline,
     output_path: PathBuf,
-    proxy_paths: &HashMap<i64, String>, // Map asset_id -> proxy file path
+    sources: &HashMap<i64, SourceMedia>, // Map asset_id -> file to render from
+    blur_regions: &[BlurRegion],
+    audio: &AudioOptions,
+    mux: &MuxOptions,
+    branding: &BrandingOptions,
+    encoder: &EncoderSettings,
+    clip_cache: Option<&Path>,
 ) -> RenderCommand {
     // Get video track clips (sorted by timeline position)
     let video_track = timeline
@@ -36,21 +144,50 @@ pub fn generate_render_commands(
             ffmpeg_args: vec!["-f".to_string(), "lavfi".to_string(), "-i".to_string(), "color=black:size=1920x1080:d=1".to_string(), "-y".to_string(), output_path.to_string_lossy().to_string()],
             output_path: output_path.clone(),
             concat_list_path: PathBuf::new(),
+            first_pass_args: None,
+            clip_renders: Vec::new(),
+            span_keyframes: false,
         };
     }
 
+    // Overlay clips (video tracks above the primary storyline), lower tracks composited first
+    let mut overlays: Vec<&ClipInstance> = timeline
+        .tracks
+        .iter()
+        .filter(|t| matches!(t.kind, TrackKind::Video) && t.id > 1)
+        .flat_map(|t| t.clips.iter())
+        .filter(|c| sources.contains_key(&c.asset_id))
+        .collect();
+    overlays.sort_by_key(|c| (c.track_id, c.timeline_start_ticks));
+
     // Build input arguments and filter_complex for concatenation
     let mut input_args = Vec::new();
+    let audio_shifts = audio_cut_shifts(&clips);
+    let mut clip_renders = Vec::new();
     
     for (idx, clip) in clips.iter().enumerate() {
-        let proxy_path = proxy_paths.get(&clip.asset_id).cloned();
-        if let Some(path) = proxy_path {
+        if let Some(source) = sources.get(&clip.asset_id) {
             // Use concat demuxer approach: create separate file for each clip segment
             // For V1, we'll use filter_complex concat instead (simpler)
             input_args.push("-i".to_string());
-            input_args.push(path.clone());
+            match clip_cache {
+                Some(cache_dir) => {
+                    let shifts = clip_audio_shifts(&audio_shifts, idx);
+                    let render = clip_render(clip, sources, blur_regions, audio, &timeline.settings, shifts, cache_dir);
+                    input_args.push(render.path.to_string_lossy().to_string());
+                    clip_renders.push(render);
+                }
+                None => input_args.push(source.path.clone()),
+            }
         }
     }
+    for clip in &overlays {
+        input_args.push("-i".to_string());
+        input_args.push(sources[&clip.asset_id].path.clone());
+    }
+    // Narration and music come in after the overlays
+    let beds = bed_audio(timeline, clips.len() + overlays.len());
+    input_args.extend(beds.inputs.iter().cloned());
 
     // Build filter_complex for concatenation with trim
     // For each clip, trim to in/out points, then concat
@@ -59,41 +196,112 @@ pub fn generate_render_commands(
         let mut filter_parts = Vec::new();
         
         // For each clip, add trim filter: [0:v]trim=start=0:end=5,setpts=PTS-STARTPTS[v0]
-        for (idx, clip) in clips.iter().enumerate() {
-            let start_sec = clip.in_ticks as f64 / TICKS_PER_SECOND as f64;
-            let duration_sec = (clip.out_ticks - clip.in_ticks) as f64 / TICKS_PER_SECOND as f64;
-            
-            filter_parts.push(format!("[{}:v]trim=start={}:duration={},setpts=PTS-STARTPTS[v{}]", idx, start_sec, duration_sec, idx));
-            filter_parts.push(format!("[{}:a]atrim=start={}:duration={},asetpts=PTS-STARTPTS[a{}]", idx, start_sec, duration_sec, idx));
+        // (intermediates come out of the cache ready to concatenate)
+        if clip_cache.is_none() {
+            for (idx, clip) in clips.iter().enumerate() {
+                let shifts = clip_audio_shifts(&audio_shifts, idx);
+                filter_parts.extend(primary_clip_filters(idx, clip, sources, blur_regions, audio, &timeline.settings, shifts));
+            }
         }
         
         // Concat all trimmed clips
         let mut concat_inputs = Vec::new();
-        for i in 0..num_inputs {
-            concat_inputs.push(format!("[v{}]", i));
-            concat_inputs.push(format!("[a{}]", i));
+        for (i, clip) in clips.iter().enumerate() {
+            if clip_cache.is_some() {
+                concat_inputs.push(format!("[{}:v][{}:a]", i, i));
+            } else {
+                concat_inputs.push(primary_video_label(i, clip));
+                concat_inputs.push(format!("[a{}]", i));
+            }
         }
-        filter_parts.push(format!("{}concat=n={}:v=1:a=1[outv][outa]", concat_inputs.join(""), num_inputs));
-        
+        let concat_output = if overlays.is_empty() { "[outv]" } else { "[vcat]" };
+        let program_audio = if beds.filters.is_empty() { "[outa]" } else { "[prog]" };
+        filter_parts.push(format!("{}concat=n={}:v=1:a=1{}{}", concat_inputs.join(""), num_inputs, concat_output, program_audio));
+        filter_parts.extend(beds.filters.iter().cloned());
+
+        // Composite overlays on top (their audio isn't mixed in), at the project resolution so
+        // overlay sizes are known
+        if !overlays.is_empty() {
+            let resolution = &timeline.settings.resolution;
+            filter_parts.push(format!(
+                "[vcat]scale={w}:{h}:force_original_aspect_ratio=decrease,pad={w}:{h}:(ow-iw)/2:(oh-ih)/2,setsar=1[base0]",
+                w = resolution.width,
+                h = resolution.height,
+            ));
+        }
+        for (k, clip) in overlays.iter().enumerate() {
+            let idx = num_inputs + k;
+            let frame = (timeline.settings.resolution.width, timeline.settings.resolution.height);
+            filter_parts.extend(trimmed_video_filters(idx, clip, sources, blur_regions, &timeline.settings));
+            filter_parts.extend(overlay_filters(idx, k, clip, k + 1 == overlays.len(), frame, timeline.settings.fps));
+        }
+
+        // Lower thirds are drawn over the composited picture
+        if let Some(titles) = title_filters(&timeline.titles, &timeline.settings, "[titled]", "[outv]") {
+            for part in &mut filter_parts {
+                *part = part.replace("[outv]", "[titled]");
+            }
+            filter_parts.push(titles);
+        }
+
+        // The watermark comes in after the beds and goes over the finished program picture
+        let mut next_input = num_inputs + overlays.len() + beds.count;
+        if let Some(watermark) = &branding.watermark {
+            for part in &mut filter_parts {
+                *part = part.replace("[outv]", "[marked]");
+            }
+            filter_parts.extend(watermark.filters(next_input, timeline.settings.resolution.width, "[marked]", "[outv]"));
+            input_args.extend(["-loop".to_string(), "1".to_string(), "-i".to_string(), watermark.path.clone()]);
+            next_input += 1;
+        }
+        // The end card plays once the program has finished; the program's outputs are renamed so
+        // the concatenation ends in [outv] and [outa]
+        let end_card = branding.end_card.as_ref().map(|card| end_card_graph(card, &timeline.settings, next_input));
+        if let Some(card) = &end_card {
+            for part in &mut filter_parts {
+                *part = part.replace("[outv]", "[progv]").replace("[outa]", "[proga]");
+            }
+            filter_parts.extend(card.filters.iter().cloned());
+            filter_parts.push("[progv][proga][ecv][eca]concat=n=2:v=1:a=1[outv][outa]".to_string());
+            input_args.extend(card.inputs.iter().cloned());
+        }
+        next_input += end_card.as_ref().map_or(0, |card| card.count);
+
         let filter_complex = filter_parts.join(";");
         
         let mut args = input_args;
+        let container = output_path
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        // Both passes share the encoder's stats file next to the output
+        let pass_log = output_path.with_extension("").to_string_lossy().to_string() + "-pass";
+        let first_pass_args = encoder.two_pass.then(|| {
+            let mut first = args.clone();
+            first.extend([
+                "-filter_complex".to_string(),
+                filter_complex.clone(),
+                "-map".to_string(),
+                "[outv]".to_string(),
+                "-map".to_string(),
+                "[outa]".to_string(),
+            ]);
+            first.extend(cut_keyframe_args(&clips));
+            first.extend(encoder.codec_args(Some((1, &pass_log))));
+            first.extend(["-f".to_string(), "null".to_string(), "-y".to_string(), "-".to_string()]);
+            first
+        });
+        // Chapters and captions come in as extra inputs after the media
+        let (chapters_input, captions_input) = mux_inputs(&mut args, mux, next_input);
         args.push("-filter_complex".to_string());
         args.push(filter_complex);
         args.push("-map".to_string());
         args.push("[outv]".to_string());
         args.push("-map".to_string());
         args.push("[outa]".to_string());
-        args.push("-c:v".to_string());
-        args.push("libx264".to_string());
-        args.push("-preset".to_string());
-        args.push("medium".to_string());
-        args.push("-crf".to_string());
-        args.push("23".to_string());
-        args.push("-c:a".to_string());
-        args.push("aac".to_string());
-        args.push("-b:a".to_string());
-        args.push("128k".to_string());
+        mux_output_args(&mut args, mux, &container, chapters_input, captions_input);
+        args.extend(cut_keyframe_args(&clips));
+        args.extend(encoder.codec_args(encoder.two_pass.then_some((2, pass_log.as_str()))));
         args.push("-y".to_string());
         args.push(output_path.to_string_lossy().to_string());
 
@@ -101,6 +309,9 @@ pub fn generate_render_commands(
         ffmpeg_args: args,
             output_path: output_path.clone(),
             concat_list_path: PathBuf::new(),
+            first_pass_args,
+            clip_renders,
+            span_keyframes: true,
         }
     } else {
         // Fallback: empty timeline
@@ -108,6 +319,714 @@ pub fn generate_render_commands(
             ffmpeg_args: vec!["-f".to_string(), "lavfi".to_string(), "-i".to_string(), "color=black:size=1920x1080:d=1".to_string(), "-y".to_string(), output_path.to_string_lossy().to_string()],
             output_path: output_path.clone(),
             concat_list_path: PathBuf::new(),
+            first_pass_args: None,
+            clip_renders,
+            span_keyframes: false,
+        }
+    }
+}
+
+/// Stitch an export together from already encoded pieces listed in an ffconcat file (see
+/// `incremental::StitchPlan`), copying their streams and muxing the chapters, captions and tags
+/// like a full export
+pub fn generate_stitch_command(concat_list_path: PathBuf, output_path: PathBuf, mux: &MuxOptions) -> RenderCommand {
+    let container = output_path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let mut args = vec![
+        "-f".to_string(),
+        "concat".to_string(),
+        "-safe".to_string(),
+        "0".to_string(),
+        "-i".to_string(),
+        concat_list_path.to_string_lossy().to_string(),
+    ];
+    let (chapters_input, captions_input) = mux_inputs(&mut args, mux, 1);
+    args.extend(["-map".to_string(), "0:v".to_string(), "-map".to_string(), "0:a".to_string()]);
+    mux_output_args(&mut args, mux, &container, chapters_input, captions_input);
+    args.extend(["-c:v".to_string(), "copy".to_string(), "-c:a".to_string(), "copy".to_string()]);
+    args.push("-y".to_string());
+    args.push(output_path.to_string_lossy().to_string());
+
+    RenderCommand {
+        ffmpeg_args: args,
+        output_path,
+        concat_list_path,
+        first_pass_args: None,
+        clip_renders: Vec::new(),
+        // Every piece is a whole span starting on a keyframe, copied as it is
+        span_keyframes: true,
+    }
+}
+
+/// Add the chapter and caption files as inputs from `next_input` on, returning their indices
+fn mux_inputs(args: &mut Vec<String>, mux: &MuxOptions, mut next_input: usize) -> (Option<usize>, Option<usize>) {
+    let chapters_input = mux.chapters_path.as_ref().map(|path| {
+        args.extend(["-f".to_string(), "ffmetadata".to_string(), "-i".to_string(), path.clone()]);
+        next_input += 1;
+        next_input - 1
+    });
+    let captions_input = mux.captions_path.as_ref().map(|path| {
+        args.extend(["-i".to_string(), path.clone()]);
+        next_input += 1;
+        next_input - 1
+    });
+    (chapters_input, captions_input)
+}
+
+/// Audio laid under the picture from files rather than clips, mixed into `[outa]` from the
+/// program sound in `[prog]`
+struct BedAudio {
+    inputs: Vec<String>,
+    /// How many inputs `inputs` adds
+    count: usize,
+    /// Empty when the timeline has no narration or music
+    filters: Vec<String>,
+}
+
+/// Inputs and filters that mix the timeline's narration and music under the program sound,
+/// starting at input `first_input`. Music is ducked while narration plays.
+fn bed_audio(timeline: &Timeline, first_input: usize) -> BedAudio {
+    let seconds = |ticks: i64| ticks.max(0) as f64 / TICKS_PER_SECOND as f64;
+    let mut inputs = Vec::new();
+    let mut filters = Vec::new();
+    let mut next_input = first_input;
+    // Each bed is trimmed to its length, brought to the program's level and delayed to its start
+    let mut place = |path: &str, start: i64, end: i64, gain: Option<f64>, label: String| {
+        inputs.extend(["-i".to_string(), path.to_string()]);
+        let delay_ms = (seconds(start) * 1000.0).round() as i64;
+        let volume = gain.map(|g| format!(",volume={}", g)).unwrap_or_default();
+        filters.push(format!(
+            "[{}:a]atrim=start=0:end={:.6},asetpts=PTS-STARTPTS{},adelay={}:all=1{}",
+            next_input,
+            seconds(end - start),
+            volume,
+            delay_ms,
+            label
+        ));
+        next_input += 1;
+    };
+    let narration: Vec<String> = timeline
+        .narration
+        .iter()
+        .enumerate()
+        .map(|(k, n)| {
+            let label = format!("[nar{}]", k);
+            place(&n.track_path, n.start_ticks, n.end_ticks, None, label.clone());
+            label
+        })
+        .collect();
+    let music: Vec<String> = timeline
+        .music
+        .iter()
+        .enumerate()
+        .map(|(k, m)| {
+            let label = format!("[mus{}]", k);
+            place(&m.track_path, m.start_ticks, m.end_ticks, Some(MUSIC_GAIN), label.clone());
+            label
+        })
+        .collect();
+    let count = next_input - first_input;
+
+    let mut mix = vec!["[prog]".to_string()];
+    if !narration.is_empty() {
+        filters.push(format!("{}amix=inputs={}:duration=longest:normalize=0[narr]", narration.join(""), narration.len()));
+    }
+    if !music.is_empty() {
+        filters.push(format!("{}amix=inputs={}:duration=longest:normalize=0[music]", music.join(""), music.len()));
+    }
+    match (narration.is_empty(), music.is_empty()) {
+        (true, true) => return BedAudio { inputs, count, filters },
+        (false, true) => mix.push("[narr]".to_string()),
+        (true, false) => mix.push("[music]".to_string()),
+        (false, false) => {
+            // The narration keys the compressor on the music, and is mixed in as well
+            filters.push("[narr]asplit=2[narrmix][narrkey]".to_string());
+            filters.push(format!(
+                "[music][narrkey]sidechaincompress=threshold={}:ratio={}:attack={}:release={}[ducked]",
+                DUCK_THRESHOLD, DUCK_RATIO, DUCK_ATTACK_MS, DUCK_RELEASE_MS
+            ));
+            mix.push("[ducked]".to_string());
+            mix.push("[narrmix]".to_string());
         }
     }
+    filters.push(format!("{}amix=inputs={}:duration=first:normalize=0[outa]", mix.join(""), mix.len()));
+    BedAudio { inputs, count, filters }
+}
+
+/// Map the subtitle track and write chapters, tags and languages
+fn mux_output_args(
+    args: &mut Vec<String>,
+    mux: &MuxOptions,
+    container: &str,
+    chapters_input: Option<usize>,
+    captions_input: Option<usize>,
+) {
+    if let Some(input) = captions_input {
+        // MP4 and MOV only hold timed text; Matroska takes the SRT as it is
+        let codec = if container == "mkv" { "srt" } else { "mov_text" };
+        args.extend(["-map".to_string(), format!("{}:s", input), "-c:s".to_string(), codec.to_string()]);
+    }
+    // Tags come from our own metadata, not from whichever source clip is first
+    args.extend(["-map_metadata".to_string(), "-1".to_string()]);
+    if let Some(input) = chapters_input {
+        args.extend(["-map_chapters".to_string(), input.to_string()]);
+    } else {
+        args.extend(["-map_chapters".to_string(), "-1".to_string()]);
+    }
+    for (key, value) in &mux.metadata {
+        args.extend(["-metadata".to_string(), format!("{}={}", key, value)]);
+    }
+    if let Some(language) = &mux.audio_language {
+        args.extend(["-metadata:s:a:0".to_string(), format!("language={}", language)]);
+    }
+    if let (Some(language), Some(_)) = (&mux.caption_language, captions_input) {
+        args.extend(["-metadata:s:s:0".to_string(), format!("language={}", language)]);
+    }
+    // MP4 only writes keys outside its fixed set (project, version, ...) with this flag
+    if !mux.metadata.is_empty() && container != "mkv" {
+        args.extend(["-movflags".to_string(), "+use_metadata_tags".to_string()]);
+    }
+}
+
+/// Keyframes at every cut on the primary track. Render spans only end at cuts, so a later
+/// export can copy any span out of this one without re-encoding it (ffconcat `inpoint`s on
+/// anything but a keyframe would start on frames that can't be decoded).
+fn cut_keyframe_args(clips: &[&ClipInstance]) -> Vec<String> {
+    let cuts: Vec<String> = clips
+        .iter()
+        .scan(0i64, |start, clip| {
+            *start += playback_ticks(clip);
+            Some(*start)
+        })
+        .take(clips.len().saturating_sub(1))
+        .map(|ticks| format!("{:.6}", ticks as f64 / TICKS_PER_SECOND as f64))
+        .collect();
+    if cuts.is_empty() {
+        return Vec::new();
+    }
+    vec!["-force_key_frames".to_string(), cuts.join(",")]
+}
+
+/// Length of the rendered export: the primary clips played back to back at their speeds
+pub fn output_duration_seconds(timeline: &Timeline) -> f64 {
+    timeline
+        .tracks
+        .iter()
+        .find(|t| matches!(t.kind, TrackKind::Video) && t.id == 1)
+        .map(|track| {
+            track
+                .clips
+                .iter()
+                .map(|c| (c.out_ticks - c.in_ticks) as f64 / TICKS_PER_SECOND as f64 / playback_speed(c))
+                .sum()
+        })
+        .unwrap_or(0.0)
+}
+
+/// FFMETADATA document with one chapter per (start seconds, title); each chapter runs until
+/// the next one starts, and the last until `end_seconds`
+pub fn chapters_ffmetadata(chapters: &[(f64, String)], end_seconds: f64) -> String {
+    let mut document = String::from(";FFMETADATA1\n");
+    for (i, (start, title)) in chapters.iter().enumerate() {
+        let end = chapters.get(i + 1).map_or(end_seconds, |(next, _)| *next).max(*start);
+        document.push_str(&format!(
+            "[CHAPTER]\nTIMEBASE=1/1000\nSTART={}\nEND={}\ntitle={}\n",
+            (start * 1000.0).round() as i64,
+            (end * 1000.0).round() as i64,
+            escape_ffmetadata(title),
+        ));
+    }
+    document
+}
+
+/// FFMETADATA values escape '=', ';', '#', backslashes and newlines with a backslash
+fn escape_ffmetadata(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '=' | ';' | '#' | '\\' | '\n') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// SubRip document of the timeline's captions, in timeline order
+pub fn captions_srt(captions: &[CaptionEvent]) -> String {
+    let mut captions: Vec<&CaptionEvent> = captions.iter().filter(|c| c.end_ticks > c.start_ticks).collect();
+    captions.sort_by_key(|c| c.start_ticks);
+    captions
+        .iter()
+        .enumerate()
+        .map(|(i, caption)| {
+            format!(
+                "{}\n{} --> {}\n{}\n",
+                i + 1,
+                srt_timestamp(caption.start_ticks),
+                srt_timestamp(caption.end_ticks),
+                caption.text.trim(),
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// "hh:mm:ss,mmm"
+fn srt_timestamp(ticks: i64) -> String {
+    let millis = ticks.max(0) * 1000 / TICKS_PER_SECOND;
+    format!(
+        "{:02}:{:02}:{:02},{:03}",
+        millis / 3_600_000,
+        (millis / 60_000) % 60,
+        (millis / 1000) % 60,
+        millis % 1000
+    )
+}
+
+/// Input `idx` as primary clip: trimmed, conformed and fitted to the frame, ending in
+/// `primary_video_label`, with its audio cut for the J/L cuts either side of it (`shifts`) and
+/// enhanced or muted, ending in `[a{idx}]`
+fn primary_clip_filters(
+    idx: usize,
+    clip: &ClipInstance,
+    sources: &HashMap<i64, SourceMedia>,
+    blur_regions: &[BlurRegion],
+    audio: &AudioOptions,
+    settings: &ProjectSettings,
+    (shift_before, shift_after): (i64, i64),
+) -> Vec<String> {
+    let mut filter_parts = trimmed_video_filters(idx, clip, sources, blur_regions, settings);
+    // Ken Burns motion already renders at the project resolution
+    if clip.ken_burns.is_none() {
+        filter_parts.push(format!("[v{}]{}[v{}f]", idx, fit_filter(clip, settings), idx));
+    }
+    let enhancement = clip.audio_enhancement.as_ref().or(audio.enhance.as_ref()).filter(|e| e.enabled);
+    let enhance = if clip.audio_muted {
+        ",volume=0".to_string()
+    } else {
+        enhancement
+            .map(|e| enhance_filter(e, audio.rnnoise_model.as_deref()))
+            .filter(|f| !f.is_empty())
+            .map(|f| format!(",{}", f))
+            .unwrap_or_default()
+    };
+    // J/L cuts move the clip's audio window off its picture; neighbours give way
+    let speed = playback_speed(clip);
+    let audio_in = (clip.in_ticks + (shift_before as f64 * speed) as i64).max(0);
+    let audio_out = clip.out_ticks + (shift_after as f64 * speed) as i64;
+    let start_sec = audio_in as f64 / TICKS_PER_SECOND as f64;
+    let duration_sec = (audio_out - audio_in) as f64 / TICKS_PER_SECOND as f64;
+    // Silence fills in if a trail runs past the end of the source
+    let pad = if shift_after > 0 {
+        format!(",apad=whole_dur={}", (playback_ticks(clip) - shift_before + shift_after) as f64 / TICKS_PER_SECOND as f64)
+    } else {
+        String::new()
+    };
+    filter_parts.push(format!(
+        "[{}:a]atrim=start={}:duration={},asetpts=PTS-STARTPTS{}{}{}[a{}]",
+        idx, start_sec, duration_sec, atempo_filters(speed), enhance, pad, idx
+    ));
+    filter_parts
+}
+
+/// Label of a primary clip's finished picture
+fn primary_video_label(idx: usize, clip: &ClipInstance) -> String {
+    if clip.ken_burns.is_some() { format!("[v{}]", idx) } else { format!("[v{}f]", idx) }
+}
+
+/// Audio cut shifts before and after the `idx`th primary clip
+fn clip_audio_shifts(audio_shifts: &[i64], idx: usize) -> (i64, i64) {
+    (if idx == 0 { 0 } else { audio_shifts[idx - 1] }, audio_shifts[idx])
+}
+
+/// Content key of a primary clip's render. It covers the clip's source range, speed, fit,
+/// effects and audio, its source file, blur regions, the audio cuts either side and the project
+/// frame, but not where the clip sits on the timeline, so moving clips around doesn't change it.
+pub(crate) fn clip_render_key(
+    clip: &ClipInstance,
+    sources: &HashMap<i64, SourceMedia>,
+    blur_regions: &[BlurRegion],
+    audio: &AudioOptions,
+    settings: &ProjectSettings,
+    shifts: (i64, i64),
+) -> String {
+    let regions: Vec<&BlurRegion> = blur_regions
+        .iter()
+        .filter(|r| r.asset_id == clip.asset_id && r.start_ticks < clip.out_ticks && r.end_ticks > clip.in_ticks)
+        .collect();
+    let mut clip_value = serde_json::to_value(clip).unwrap_or_default();
+    if let Some(fields) = clip_value.as_object_mut() {
+        for field in ["id", "track_id", "timeline_start_ticks", "locked"] {
+            fields.remove(field);
+        }
+    }
+    render_cache::clip_render_key(&json!({
+        "clip": clip_value,
+        "source": sources.get(&clip.asset_id),
+        "blur_regions": regions,
+        "audio": audio,
+        "audio_shifts": shifts,
+        "resolution": settings.resolution,
+        "fps": settings.fps,
+        "frame_rate_conversion": settings.frame_rate_conversion,
+    }))
+}
+
+/// The intermediate of a primary clip in `cache_dir`, under its `clip_render_key`
+fn clip_render(
+    clip: &ClipInstance,
+    sources: &HashMap<i64, SourceMedia>,
+    blur_regions: &[BlurRegion],
+    audio: &AudioOptions,
+    settings: &ProjectSettings,
+    shifts: (i64, i64),
+    cache_dir: &Path,
+) -> ClipRender {
+    let source = sources.get(&clip.asset_id).cloned().unwrap_or_default();
+    let key = clip_render_key(clip, sources, blur_regions, audio, settings, shifts);
+    let path = render_cache::clip_render_path(cache_dir, &key);
+
+    let filters = primary_clip_filters(0, clip, sources, blur_regions, audio, settings, shifts);
+    let mut ffmpeg_args = vec![
+        "-i".to_string(),
+        source.path.clone(),
+        "-filter_complex".to_string(),
+        filters.join(";"),
+        "-map".to_string(),
+        primary_video_label(0, clip),
+        "-map".to_string(),
+        "[a0]".to_string(),
+    ];
+    ffmpeg_args.extend(render_cache::intermediate_codec_args());
+    ffmpeg_args.extend(["-y".to_string(), path.to_string_lossy().to_string()]);
+
+    ClipRender {
+        clip_id: clip.id.clone(),
+        key,
+        path,
+        ffmpeg_args,
+    }
+}
+
+/// Input `idx` trimmed to the clip's source range (timestamps from 0) and conformed to the
+/// export's color space and frame rate, with its blur regions applied and its Ken Burns motion played, ending
+/// in `[v{idx}]`
+fn trimmed_video_filters(
+    idx: usize,
+    clip: &ClipInstance,
+    sources: &HashMap<i64, SourceMedia>,
+    blur_regions: &[BlurRegion],
+    settings: &ProjectSettings,
+) -> Vec<String> {
+    let start_sec = clip.in_ticks as f64 / TICKS_PER_SECOND as f64;
+    let duration_sec = (clip.out_ticks - clip.in_ticks) as f64 / TICKS_PER_SECOND as f64;
+    let regions: Vec<&BlurRegion> = blur_regions
+        .iter()
+        .filter(|r| r.asset_id == clip.asset_id && r.start_ticks < clip.out_ticks && r.end_ticks > clip.in_ticks)
+        .collect();
+    let output = if clip.ken_burns.is_some() { format!("[v{}k]", idx) } else { format!("[v{}]", idx) };
+    let speed = playback_speed(clip);
+    let setpts = if speed == 1.0 { "setpts=PTS-STARTPTS".to_string() } else { format!("setpts=(PTS-STARTPTS)/{}", speed) };
+    let source = sources.get(&clip.asset_id);
+    let mut conform: String = source
+        .and_then(|s| s.color_filter.as_ref())
+        .map(|f| format!(",{}", f))
+        .unwrap_or_default();
+    // Ken Burns motion renders its own frames at the project rate
+    if let Some(source_fps) = source.and_then(|s| s.fps).filter(|_| clip.ken_burns.is_none()) {
+        if frame_rate::needs_conversion(source_fps * speed, settings.fps) {
+            let conversion = frame_rate::clip_conversion(clip, settings);
+            conform.push_str(&format!(",{}", frame_rate::conversion_filter(conversion, settings.fps)));
+        }
+    }
+    let mut filters = Vec::new();
+    if regions.is_empty() {
+        filters.push(format!("[{}:v]trim=start={}:duration={},{}{}{}", idx, start_sec, duration_sec, setpts, conform, output));
+    } else {
+        filters.push(format!(
+            "[{}:v]trim=start={}:duration={},{}{}[v{}r0]",
+            idx, start_sec, duration_sec, setpts, conform, idx
+        ));
+        filters.extend(blur_filters(idx, clip, &regions, &output));
+    }
+    if let Some(ken_burns) = &clip.ken_burns {
+        filters.push(format!("[v{}k]{}[v{}]", idx, ken_burns_filter(ken_burns, duration_sec / speed, settings), idx));
+    }
+    filters
+}
+
+/// Scale a clip to the project frame whatever its size and orientation (so clips concatenate):
+/// letterboxed whole, or reframed to cover the frame around its focus point
+fn fit_filter(clip: &ClipInstance, settings: &ProjectSettings) -> String {
+    let (w, h) = (settings.resolution.width, settings.resolution.height);
+    let fit = clip.fit.clone().unwrap_or_else(|| ClipFit::automatic(&settings.resolution));
+    match fit.mode {
+        FitMode::Letterbox => format!(
+            "scale={w}:{h}:force_original_aspect_ratio=decrease:force_divisible_by=2,\
+             pad={w}:{h}:(ow-iw)/2:(oh-ih)/2,setsar=1",
+            w = w,
+            h = h,
+        ),
+        FitMode::Reframe => format!(
+            "scale={w}:{h}:force_original_aspect_ratio=increase,\
+             crop={w}:{h}:(iw-ow)*{x:.4}:(ih-oh)*{y:.4},setsar=1",
+            w = w,
+            h = h,
+            x = fit.x,
+            y = fit.y,
+        ),
+    }
+}
+
+/// Speed the clip is exported at (invalid speeds play at normal speed)
+pub(crate) fn playback_speed(clip: &ClipInstance) -> f64 {
+    if clip.speed.is_finite() && clip.speed > 0.0 { clip.speed } else { 1.0 }
+}
+
+/// How long the clip plays for in the export, in ticks
+pub(crate) fn playback_ticks(clip: &ClipInstance) -> i64 {
+    ((clip.out_ticks - clip.in_ticks) as f64 / playback_speed(clip)) as i64
+}
+
+/// How far the audio cut after each clip is moved from the picture cut, in playback ticks:
+/// positive when the clip's sound trails into the next one (L-cut), negative when the next
+/// clip's sound leads in under this one (J-cut). Each is clamped so both clips keep some sound.
+pub(crate) fn audio_cut_shifts(clips: &[&ClipInstance]) -> Vec<i64> {
+    clips
+        .windows(2)
+        .map(|pair| {
+            let trail = pair[0].split_audio.as_ref().map_or(0, |s| s.trail_ticks);
+            let lead = pair[1].split_audio.as_ref().map_or(0, |s| s.lead_ticks);
+            (trail - lead).clamp(1 - playback_ticks(pair[0]), playback_ticks(pair[1]) - 1)
+        })
+        .chain(std::iter::once(0))
+        .collect()
+}
+
+/// atempo stages (each limited to 0.5-2x) that change audio to `speed` without changing pitch,
+/// each prefixed with a comma; empty at normal speed
+fn atempo_filters(speed: f64) -> String {
+    let mut filters = String::new();
+    let mut remaining = speed;
+    while remaining > 2.0 {
+        filters.push_str(",atempo=2.0");
+        remaining /= 2.0;
+    }
+    while remaining < 0.5 {
+        filters.push_str(",atempo=0.5");
+        remaining /= 0.5;
+    }
+    if (remaining - 1.0).abs() > f64::EPSILON {
+        filters.push_str(&format!(",atempo={}", remaining));
+    }
+    filters
+}
+
+/// Pan/zoom across a still: the picture is scaled to cover twice the frame size (so slow moves
+/// don't step from pixel to pixel), then zoompan follows the framing from start to end and
+/// outputs the project resolution
+fn ken_burns_filter(ken_burns: &KenBurns, duration_sec: f64, settings: &ProjectSettings) -> String {
+    let (width, height) = (settings.resolution.width, settings.resolution.height);
+    let (from, to) = (ken_burns.framing_at(0.0), ken_burns.framing_at(1.0));
+    let p = format!("clip(it/{:.3},0,1)", duration_sec.max(0.001));
+    let e = eased(ken_burns.easing, &p);
+    let lerp = |a: f64, b: f64| format!("({:.4}+({:.4})*{})", a, b - a, e);
+    format!(
+        "scale={sw}:{sh}:force_original_aspect_ratio=increase,crop={sw}:{sh},setsar=1,fps={fps},\
+         zoompan=z='{z}':\
+         x='max(0,min(iw-iw/zoom,iw*{x}-iw/zoom/2))':\
+         y='max(0,min(ih-ih/zoom,ih*{y}-ih/zoom/2))':\
+         d=1:s={w}x{h}:fps={fps},setsar=1",
+        sw = width * 2,
+        sh = height * 2,
+        fps = settings.fps,
+        z = lerp(from.zoom, to.zoom),
+        x = lerp(from.x, to.x),
+        y = lerp(from.y, to.y),
+        w = width,
+        h = height,
+    )
+}
+
+/// Dialog cleanup: cut rumble below the highpass, reduce noise (RNNoise when a model is
+/// available, otherwise afftdn), then a little less mud, a little more presence and gentle
+/// compression for voice
+fn enhance_filter(enhancement: &AudioEnhancement, rnnoise_model: Option<&str>) -> String {
+    let mut filters = Vec::new();
+    if enhancement.highpass_hz > 0.0 {
+        filters.push(format!("highpass=f={:.0}", enhancement.highpass_hz));
+    }
+    if enhancement.noise_reduction_db > 0.0 {
+        match rnnoise_model {
+            Some(model) => filters.push(format!(
+                "arnndn=m='{}':mix={:.2}",
+                model.replace('\'', ""),
+                (enhancement.noise_reduction_db / 24.0).min(1.0)
+            )),
+            None => filters.push(format!(
+                "afftdn=nr={:.1}:nf={:.0}",
+                enhancement.noise_reduction_db, NOISE_FLOOR_DB
+            )),
+        }
+    }
+    if enhancement.voice {
+        filters.push("equalizer=f=250:t=q:w=1:g=-2".to_string());
+        filters.push("equalizer=f=3000:t=q:w=1:g=3".to_string());
+        filters.push("acompressor=threshold=-20dB:ratio=3:attack=5:release=100:makeup=2".to_string());
+    }
+    filters.join(",")
+}
+
+/// Chain that lays the trimmed overlay clip `[v{idx}]` over `[base{k}]` (a `frame`-sized
+/// picture) while it's on the timeline: cropped, scaled, faded to its opacity, rotated and
+/// aligned inside the margin, each following its keyframes. Ends in `[base{k+1}]`, or `[outv]`
+/// for the last overlay.
+///
+/// Animated scale keeps the overlay a fixed size for the filters after it: the clip is boxed at
+/// its largest scale, padded out to the box at the smallest scale, and zoompan zooms into the
+/// padding. Filters before the final setpts see clip time; overlay sees timeline time.
+fn overlay_filters(idx: usize, k: usize, clip: &ClipInstance, last: bool, frame: (i32, i32), fps: f64) -> Vec<String> {
+    let transform = clip.transform.clone().unwrap_or_default();
+    let crop = &transform.crop;
+    let (frame_w, frame_h) = (frame.0 as f64, frame.1 as f64);
+    let start = clip.timeline_start_ticks as f64 / TICKS_PER_SECOND as f64;
+    let end = start + (clip.out_ticks - clip.in_ticks) as f64 / TICKS_PER_SECOND as f64;
+    let output = if last { "[outv]".to_string() } else { format!("[base{}]", k + 1) };
+
+    let mut chain = vec![format!(
+        "crop=w=iw*{w:.4}:h=ih*{h:.4}:x=iw*{x:.4}:y=ih*{y:.4},format=rgba",
+        w = 1.0 - crop.left - crop.right,
+        h = 1.0 - crop.top - crop.bottom,
+        x = crop.left,
+        y = crop.top,
+    )];
+
+    let (min_scale, max_scale) = transform.value_range(TransformProperty::Scale);
+    if transform.is_animated(TransformProperty::Scale) {
+        let (box_w, box_h) = (even(frame_w * max_scale), even(frame_h * max_scale));
+        let grow = max_scale / min_scale;
+        let zoom = keyframe_expr(&transform, TransformProperty::Scale, "it", 0.0, |s| s / min_scale);
+        chain.push(format!(
+            "scale=w={bw}:h={bh}:force_original_aspect_ratio=decrease,\
+             pad={bw}:{bh}:(ow-iw)/2:(oh-ih)/2:color=black@0,\
+             pad={cw}:{ch}:(ow-iw)/2:(oh-ih)/2:color=black@0,\
+             fps={fps},zoompan=z='{zoom}':x='iw/2-iw/zoom/2':y='ih/2-ih/zoom/2':d=1:s={bw}x{bh}:fps={fps}",
+            bw = box_w,
+            bh = box_h,
+            cw = even(box_w as f64 * grow),
+            ch = even(box_h as f64 * grow),
+            fps = fps,
+            zoom = zoom,
+        ));
+    } else {
+        chain.push(format!("scale=w={}:h=-2", even(frame_w * max_scale)));
+    }
+
+    if transform.is_animated(TransformProperty::Opacity) {
+        let opacity = keyframe_expr(&transform, TransformProperty::Opacity, "T", 0.0, |o| o);
+        chain.push(format!("geq=r='r(X,Y)':g='g(X,Y)':b='b(X,Y)':a='alpha(X,Y)*({})'", opacity));
+    } else {
+        chain.push(format!("colorchannelmixer=aa={:.3}", transform.value_at(TransformProperty::Opacity, 0)));
+    }
+
+    if transform.value_range(TransformProperty::Rotation) != (0.0, 0.0) {
+        let angle = keyframe_expr(&transform, TransformProperty::Rotation, "t", 0.0, f64::to_radians);
+        chain.push(format!("rotate=a='{}':c=none:ow='hypot(iw,ih)':oh=ow", angle));
+    }
+    chain.push(format!("setpts=PTS-STARTPTS+{:.3}/TB", start));
+
+    let x = keyframe_expr(&transform, TransformProperty::X, "t", start, |v| v);
+    let y = keyframe_expr(&transform, TransformProperty::Y, "t", start, |v| v);
+    vec![
+        format!("[v{}]{}[o{}]", idx, chain.join(","), k),
+        format!(
+            "[base{k}][o{k}]overlay=x='W*{m:.4}+(W*(1-2*{m:.4})-overlay_w)*({x})':\
+             y='W*{m:.4}+(H-2*W*{m:.4}-overlay_h)*({y})':\
+             enable='between(t,{start:.3},{end:.3})':eof_action=pass{output}",
+            k = k,
+            m = transform.margin,
+            x = x,
+            y = y,
+            start = start,
+            end = end,
+            output = output,
+        ),
+    ]
+}
+
+/// ffmpeg expression of `var` for a transform property: a constant, or the keyframes (at
+/// `start` seconds + their offsets) joined by their easing curves. `map` converts values.
+fn keyframe_expr(
+    transform: &ClipTransform,
+    property: TransformProperty,
+    var: &str,
+    start: f64,
+    map: impl Fn(f64) -> f64,
+) -> String {
+    if !transform.is_animated(property) {
+        return format!("{:.4}", map(transform.value_at(property, 0)));
+    }
+    let points: Vec<(f64, f64, Easing)> = transform
+        .keyframe_points(property)
+        .into_iter()
+        .map(|(offset, value, easing)| (start + offset as f64 / TICKS_PER_SECOND as f64, map(value), easing))
+        .collect();
+
+    let mut expr = format!("{:.4}", points[points.len() - 1].1);
+    for pair in points.windows(2).rev() {
+        let ((t0, v0, _), (t1, v1, easing)) = (pair[0], pair[1]);
+        let p = format!("(({}-{:.3})/{:.3})", var, t0, t1 - t0);
+        expr = format!("if(lt({},{:.3}),{:.4}+({:.4})*{},{})", var, t1, v0, v1 - v0, eased(easing, &p), expr);
+    }
+    format!("if(lt({},{:.3}),{:.4},{})", var, points[0].0, points[0].1, expr)
+}
+
+/// ffmpeg expression of an easing curve applied to progress expression `p`
+fn eased(easing: Easing, p: &str) -> String {
+    match easing {
+        Easing::Linear => p.to_string(),
+        Easing::EaseIn => format!("{p}*{p}", p = p),
+        Easing::EaseOut => format!("{p}*(2-{p})", p = p),
+        Easing::EaseInOut => format!("{p}*{p}*(3-2*{p})", p = p),
+    }
+}
+
+/// Nearest even pixel count (yuv formats need even sizes), at least 2
+fn even(pixels: f64) -> i64 {
+    ((pixels / 2.0).round() as i64 * 2).max(2)
+}
+
+/// Chain that blurs each region of a clip while it's on screen: the trimmed clip `[v{idx}r0]` is
+/// split, the region cropped and blurred, then overlaid back, ending in `output`
+fn blur_filters(idx: usize, clip: &ClipInstance, regions: &[&BlurRegion], output: &str) -> Vec<String> {
+    let mut filters = Vec::new();
+    for (k, region) in regions.iter().enumerate() {
+        // Region times relative to the start of the trimmed clip, at its playback speed
+        let speed = playback_speed(clip);
+        let start = (region.start_ticks.max(clip.in_ticks) - clip.in_ticks) as f64 / TICKS_PER_SECOND as f64 / speed;
+        let end = (region.end_ticks.min(clip.out_ticks) - clip.in_ticks) as f64 / TICKS_PER_SECOND as f64 / speed;
+        let output = if k + 1 == regions.len() {
+            output.to_string()
+        } else {
+            format!("[v{}r{}]", idx, k + 1)
+        };
+        filters.push(format!(
+            "[v{i}r{k}]split[v{i}b{k}][v{i}c{k}];\
+             [v{i}c{k}]crop=w=iw*{w:.4}:h=ih*{h:.4}:x=iw*{x:.4}:y=ih*{y:.4},gblur=sigma={sigma}[v{i}g{k}];\
+             [v{i}b{k}][v{i}g{k}]overlay=x=W*{x:.4}:y=H*{y:.4}:enable='between(t,{start:.3},{end:.3})'{output}",
+            i = idx,
+            k = k,
+            x = region.x,
+            y = region.y,
+            w = region.w,
+            h = region.h,
+            sigma = BLUR_SIGMA,
+            start = start,
+            end = end,
+            output = output,
+        ));
+    }
+    filters
 }
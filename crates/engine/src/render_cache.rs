@@ -0,0 +1,55 @@
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Bumped whenever the filters a clip is rendered with change, so intermediates rendered by
+/// an older build aren't reused
+const CLIP_RENDER_VERSION: u32 = 1;
+
+/// Container of cached clip intermediates
+pub const CLIP_RENDER_EXTENSION: &str = "mkv";
+
+/// Stable hash of a value's JSON form, as 16 hex digits. Map keys serialize sorted, so equal
+/// values always hash the same (unlike std's hasher, this doesn't change between builds).
+pub fn content_hash(value: &impl Serialize) -> String {
+    let bytes = serde_json::to_vec(value).unwrap_or_default();
+    // FNV-1a
+    let hash = bytes.iter().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    });
+    format!("{:016x}", hash)
+}
+
+/// Key of a clip intermediate: the hash of everything its render depends on, tagged with the
+/// render version
+pub fn clip_render_key(inputs: &impl Serialize) -> String {
+    content_hash(&(CLIP_RENDER_VERSION, inputs))
+}
+
+/// Where a clip intermediate with `key` lives in `cache_dir`
+pub fn clip_render_path(cache_dir: &Path, key: &str) -> PathBuf {
+    cache_dir.join(format!("clip_{}.{}", key, CLIP_RENDER_EXTENSION))
+}
+
+/// Where the encoded render of an export span with `key` lives in `cache_dir`, in the
+/// export's container
+pub fn span_render_path(cache_dir: &Path, key: &str, extension: &str) -> PathBuf {
+    cache_dir.join(format!("span_{}.{}", key, extension))
+}
+
+/// Where the scrubbing preview of a span with `key` lives in `cache_dir`
+pub fn preview_path(cache_dir: &Path, key: &str) -> PathBuf {
+    cache_dir.join(format!("preview_{}.mp4", key))
+}
+
+/// Where a compiled render graph with `key` lives in `cache_dir`
+pub fn graph_path(cache_dir: &Path, key: &str) -> PathBuf {
+    cache_dir.join(format!("graph_{}.json", key))
+}
+
+/// Encoding of intermediates: lossless, so the final encode is the only generation loss
+pub fn intermediate_codec_args() -> Vec<String> {
+    ["-c:v", "libx264", "-preset", "ultrafast", "-qp", "0", "-pix_fmt", "yuv420p", "-c:a", "pcm_s16le"]
+        .iter()
+        .map(|a| a.to_string())
+        .collect()
+}
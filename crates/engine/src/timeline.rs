@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::title::TitleEvent;
+
 pub const TICKS_PER_SECOND: i64 = 48000;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -10,6 +12,10 @@ pub struct ProjectSettings {
     pub sample_rate: i32,
     #[serde(default = "default_ticks_per_second")]
     pub ticks_per_second: i64,
+    /// How clips shot at another frame rate are converted to `fps` on export (clips can
+    /// override it)
+    #[serde(default)]
+    pub frame_rate_conversion: FrameRateConversion,
 }
 
 fn default_ticks_per_second() -> i64 {
@@ -39,9 +45,453 @@ pub struct ClipInstance {
     pub timeline_start_ticks: i64,
     pub speed: f64,
     pub track_id: i64,
+    /// Locked clips can't be moved, trimmed, replaced or rippled by edits
+    #[serde(default)]
+    pub locked: bool,
+    /// Segment the clip was cut from, when known
+    #[serde(default)]
+    pub segment_id: Option<i64>,
+    /// Placed by the planner/agent rather than the user (user removals feed back into retrieval)
+    #[serde(default)]
+    pub agent_placed: bool,
+    /// Picture-in-picture layout of an overlay clip (None fills the frame)
+    #[serde(default)]
+    pub transform: Option<ClipTransform>,
+    /// Pan and zoom across a still image over the clip
+    #[serde(default)]
+    pub ken_burns: Option<KenBurns>,
+    /// Dialog cleanup applied on export (None follows the export's setting)
+    #[serde(default)]
+    pub audio_enhancement: Option<AudioEnhancement>,
+    /// Letterbox or reframe when the clip's aspect ratio differs from the project's (None
+    /// follows `ClipFit::automatic`)
+    #[serde(default)]
+    pub fit: Option<ClipFit>,
+    /// Audio starting before or running past the clip's picture (J-cut / L-cut), primary
+    /// track only
+    #[serde(default)]
+    pub split_audio: Option<SplitAudio>,
+    /// Frame-rate conversion for this clip (None follows the project's)
+    #[serde(default)]
+    pub frame_rate_conversion: Option<FrameRateConversion>,
+    /// Plays without its own sound
+    #[serde(default)]
+    pub audio_muted: bool,
+}
+
+/// Where an overlay clip sits over the picture below it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClipTransform {
+    /// Width as a fraction of the frame width (height follows the cropped aspect ratio)
+    pub scale: f64,
+    /// Horizontal alignment in the frame: 0 = left edge, 0.5 = centered, 1 = right edge
+    pub x: f64,
+    /// Vertical alignment in the frame: 0 = top edge, 0.5 = centered, 1 = bottom edge
+    pub y: f64,
+    /// Space kept clear from the frame edges, as a fraction of the frame width
+    pub margin: f64,
+    /// Fractions of the clip's own frame cut away before scaling
+    pub crop: CropRect,
+    pub opacity: f64,
+    /// Clockwise, in degrees
+    pub rotation: f64,
+    /// Animation of position, scale, rotation and opacity, in time order. A property follows its
+    /// keyframes (holding the first and last values outside them); properties no keyframe sets
+    /// keep the values above.
+    pub keyframes: Vec<TransformKeyframe>,
+}
+
+/// Values an animated transform reaches at a point in the clip
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TransformKeyframe {
+    /// Time from the start of the clip on the timeline
+    pub offset_ticks: i64,
+    pub x: Option<f64>,
+    pub y: Option<f64>,
+    pub scale: Option<f64>,
+    pub rotation: Option<f64>,
+    pub opacity: Option<f64>,
+    /// How values move from the previous keyframe to this one
+    pub easing: Easing,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Easing {
+    #[default]
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+}
+
+impl Easing {
+    /// Eased progress for linear progress `p` in [0, 1]
+    pub fn apply(self, p: f64) -> f64 {
+        match self {
+            Easing::Linear => p,
+            Easing::EaseIn => p * p,
+            Easing::EaseOut => p * (2.0 - p),
+            Easing::EaseInOut => p * p * (3.0 - 2.0 * p),
+        }
+    }
+}
+
+/// A transform property that can be animated
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TransformProperty {
+    X,
+    Y,
+    Scale,
+    Rotation,
+    Opacity,
+}
+
+impl TransformKeyframe {
+    pub fn get(&self, property: TransformProperty) -> Option<f64> {
+        match property {
+            TransformProperty::X => self.x,
+            TransformProperty::Y => self.y,
+            TransformProperty::Scale => self.scale,
+            TransformProperty::Rotation => self.rotation,
+            TransformProperty::Opacity => self.opacity,
+        }
+    }
+}
+
+impl Default for ClipTransform {
+    fn default() -> Self {
+        ClipTransform {
+            scale: 1.0,
+            x: 0.5,
+            y: 0.5,
+            margin: 0.0,
+            crop: CropRect::default(),
+            opacity: 1.0,
+            rotation: 0.0,
+            keyframes: Vec::new(),
+        }
+    }
+}
+
+impl ClipTransform {
+    pub fn validate(&self) -> Result<(), String> {
+        let unit = 0.0..=1.0;
+        let values = |property| {
+            std::iter::once(self.static_value(property))
+                .chain(self.keyframes.iter().filter_map(move |k| k.get(property)))
+        };
+        if !values(TransformProperty::Scale).all(|s| s > 0.0 && s <= 1.0) {
+            return Err("scale must be in (0, 1]".to_string());
+        }
+        if ![TransformProperty::X, TransformProperty::Y, TransformProperty::Opacity]
+            .into_iter()
+            .all(|p| values(p).all(|v| unit.contains(&v)))
+        {
+            return Err("x, y and opacity must be between 0 and 1".to_string());
+        }
+        if !values(TransformProperty::Rotation).all(f64::is_finite) {
+            return Err("rotation must be a number of degrees".to_string());
+        }
+        if self.keyframes.windows(2).any(|w| w[0].offset_ticks >= w[1].offset_ticks) {
+            return Err("keyframes must be in time order, one per offset".to_string());
+        }
+        if !(0.0..0.5).contains(&self.margin) {
+            return Err("margin must be in [0, 0.5)".to_string());
+        }
+        let c = &self.crop;
+        if [c.left, c.top, c.right, c.bottom].iter().any(|v| *v < 0.0)
+            || c.left + c.right >= 1.0
+            || c.top + c.bottom >= 1.0
+        {
+            return Err("crop must leave part of the frame".to_string());
+        }
+        Ok(())
+    }
+
+    fn static_value(&self, property: TransformProperty) -> f64 {
+        match property {
+            TransformProperty::X => self.x,
+            TransformProperty::Y => self.y,
+            TransformProperty::Scale => self.scale,
+            TransformProperty::Rotation => self.rotation,
+            TransformProperty::Opacity => self.opacity,
+        }
+    }
+
+    /// Keyframes of one property as (offset_ticks, value, easing into it)
+    pub fn keyframe_points(&self, property: TransformProperty) -> Vec<(i64, f64, Easing)> {
+        self.keyframes
+            .iter()
+            .filter_map(|k| k.get(property).map(|v| (k.offset_ticks, v, k.easing)))
+            .collect()
+    }
+
+    pub fn is_animated(&self, property: TransformProperty) -> bool {
+        let points = self.keyframe_points(property);
+        points.windows(2).any(|w| w[0].1 != w[1].1)
+    }
+
+    /// Value of a property `offset_ticks` into the clip
+    pub fn value_at(&self, property: TransformProperty, offset_ticks: i64) -> f64 {
+        let points = self.keyframe_points(property);
+        let (Some(first), Some(last)) = (points.first(), points.last()) else {
+            return self.static_value(property);
+        };
+        if offset_ticks <= first.0 {
+            return first.1;
+        }
+        if offset_ticks >= last.0 {
+            return last.1;
+        }
+        let i = points.iter().position(|p| p.0 > offset_ticks).unwrap_or(points.len() - 1);
+        let (from, to) = (points[i - 1], points[i]);
+        let p = (offset_ticks - from.0) as f64 / (to.0 - from.0) as f64;
+        from.1 + (to.1 - from.1) * to.2.apply(p)
+    }
+
+    /// Smallest and largest value a property takes
+    pub fn value_range(&self, property: TransformProperty) -> (f64, f64) {
+        let points = self.keyframe_points(property);
+        if points.is_empty() {
+            let v = self.static_value(property);
+            return (v, v);
+        }
+        // Easing curves stay between their endpoints, so the extremes are at keyframes
+        points.iter().fold((f64::MAX, f64::MIN), |(min, max), p| (min.min(p.1), max.max(p.1)))
+    }
+}
+
+/// Slow pan and zoom across a still image, from one framing at the start of the clip to another
+/// at its end
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KenBurns {
+    /// Disabled clips show the still without motion (and aren't given automatic motion again)
+    pub enabled: bool,
+    pub start: Framing,
+    pub end: Framing,
+    pub easing: Easing,
+}
+
+/// Part of a still shown in the frame. The still is first scaled to cover the frame (cropping
+/// whatever sticks out); coordinates are fractions of that frame-filling picture.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Framing {
+    /// Magnification: 1 shows the whole frame-filling picture, 2 a quarter of it
+    pub zoom: f64,
+    /// Point shown at the center of the frame (kept far enough in that the view stays inside)
+    pub x: f64,
+    pub y: f64,
+}
+
+/// Largest zoom a framing can use before the still gets too soft
+pub const MAX_KEN_BURNS_ZOOM: f64 = 4.0;
+
+impl Default for KenBurns {
+    fn default() -> Self {
+        KenBurns {
+            enabled: true,
+            start: Framing::default(),
+            end: Framing::default(),
+            easing: Easing::Linear,
+        }
+    }
+}
+
+impl Default for Framing {
+    fn default() -> Self {
+        Framing { zoom: 1.0, x: 0.5, y: 0.5 }
+    }
+}
+
+impl Framing {
+    /// The same framing with its center moved so the view doesn't leave the picture
+    pub fn clamped(self) -> Framing {
+        let half = 0.5 / self.zoom;
+        Framing {
+            zoom: self.zoom,
+            x: self.x.clamp(half, 1.0 - half),
+            y: self.y.clamp(half, 1.0 - half),
+        }
+    }
+}
+
+impl KenBurns {
+    pub fn validate(&self) -> Result<(), String> {
+        for framing in [&self.start, &self.end] {
+            if !(1.0..=MAX_KEN_BURNS_ZOOM).contains(&framing.zoom) {
+                return Err(format!("zoom must be between 1 and {}", MAX_KEN_BURNS_ZOOM));
+            }
+            if !(0.0..=1.0).contains(&framing.x) || !(0.0..=1.0).contains(&framing.y) {
+                return Err("x and y must be between 0 and 1".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// Framing `progress` (0-1) of the way through the clip
+    pub fn framing_at(&self, progress: f64) -> Framing {
+        if !self.enabled {
+            return self.start.clamped();
+        }
+        let p = self.easing.apply(progress.clamp(0.0, 1.0));
+        let (a, b) = (self.start.clamped(), self.end.clamped());
+        Framing {
+            zoom: a.zoom + (b.zoom - a.zoom) * p,
+            x: a.x + (b.x - a.x) * p,
+            y: a.y + (b.y - a.y) * p,
+        }
+    }
+
+    /// Motion of the two halves of a clip split `progress` of the way through, so the halves
+    /// together keep the original path
+    pub fn split(&self, progress: f64) -> (KenBurns, KenBurns) {
+        let middle = self.framing_at(progress);
+        (
+            KenBurns { end: middle, ..self.clone() },
+            KenBurns { start: middle, ..self.clone() },
+        )
+    }
+}
+
+/// Cleanup of a clip's dialog: noise reduction for wind and hiss, then light EQ and compression
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioEnhancement {
+    /// Disabled clips keep their original audio even when the export enhances everything
+    pub enabled: bool,
+    /// How far noise is pushed down, in dB (0 turns noise reduction off)
+    pub noise_reduction_db: f64,
+    /// Rumble and wind below this frequency are cut, in Hz (0 turns the filter off)
+    pub highpass_hz: f64,
+    /// Presence EQ and gentle compression so speech sits forward and even
+    pub voice: bool,
+}
+
+impl Default for AudioEnhancement {
+    fn default() -> Self {
+        AudioEnhancement {
+            enabled: true,
+            noise_reduction_db: 12.0,
+            highpass_hz: 80.0,
+            voice: true,
+        }
+    }
 }
 
-fn generate_clip_id() -> String {
+impl AudioEnhancement {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(0.0..=97.0).contains(&self.noise_reduction_db) {
+            return Err("noise_reduction_db must be between 0 and 97".to_string());
+        }
+        if !(0.0..=1000.0).contains(&self.highpass_hz) {
+            return Err("highpass_hz must be between 0 and 1000".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Audio of a primary clip offset from its picture. Lead (a J-cut) starts the clip's sound
+/// under the end of the previous clip; trail (an L-cut) keeps it playing under the start of the
+/// next one. Both are in timeline ticks; the neighbouring clip's sound gives way for that long.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SplitAudio {
+    pub lead_ticks: i64,
+    pub trail_ticks: i64,
+}
+
+impl SplitAudio {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.lead_ticks < 0 || self.trail_ticks < 0 {
+            return Err("lead_ticks and trail_ticks can't be negative".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lead_ticks == 0 && self.trail_ticks == 0
+    }
+}
+
+/// How footage at another frame rate is brought to the project's
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FrameRateConversion {
+    /// Drop or repeat whole frames (ffmpeg `fps`): sharp, but uneven motion unless the rates
+    /// divide evenly
+    #[default]
+    DropDup,
+    /// Blend neighbouring frames (ffmpeg `framerate`): smooth, with ghosting on motion
+    Blend,
+    /// Motion-compensated interpolation (ffmpeg `minterpolate`): the smoothest, but slow to
+    /// render and prone to warping on fast or complex motion
+    OpticalFlow,
+}
+
+/// How a clip whose aspect ratio differs from the project's fills the frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FitMode {
+    /// The whole picture, with bars where it doesn't reach the frame edges
+    Letterbox,
+    /// The picture scaled to cover the frame, cropped around a focus point
+    Reframe,
+}
+
+/// Per-clip override of how its picture fits the project frame
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClipFit {
+    pub mode: FitMode,
+    /// Point of the picture kept in view when reframing: 0 = left/top edge, 0.5 = center,
+    /// 1 = right/bottom edge
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Default for ClipFit {
+    fn default() -> Self {
+        ClipFit {
+            mode: FitMode::Letterbox,
+            x: 0.5,
+            y: 0.5,
+        }
+    }
+}
+
+impl ClipFit {
+    /// Fit for clips without an override: vertical projects reframe landscape footage to fill
+    /// the screen; other projects letterbox (pillarbox) portrait footage to keep all of it
+    pub fn automatic(resolution: &Resolution) -> Self {
+        ClipFit {
+            mode: if resolution.height > resolution.width { FitMode::Reframe } else { FitMode::Letterbox },
+            ..ClipFit::default()
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if !(0.0..=1.0).contains(&self.x) || !(0.0..=1.0).contains(&self.y) {
+            return Err("x and y must be between 0 and 1".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CropRect {
+    pub left: f64,
+    pub top: f64,
+    pub right: f64,
+    pub bottom: f64,
+}
+
+pub(crate) fn generate_clip_id() -> String {
     Uuid::new_v4().to_string()
 }
 
@@ -65,6 +515,126 @@ pub struct CaptionEvent {
     pub end_ticks: i64,
     pub text: String,
     pub template_id: Option<i64>,
+    /// How the caption is drawn (None = the renderer's default look)
+    #[serde(default)]
+    pub style: Option<CaptionStyle>,
+}
+
+/// Caption positions a style may use
+pub const CAPTION_POSITIONS: &[&str] = &["top", "center", "bottom"];
+
+/// Caption look: font, size, colours and placement
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CaptionStyle {
+    pub font_family: String,
+    /// Font size in pixels at the project resolution
+    pub font_size: u32,
+    /// Text colour as #RRGGBB
+    pub color: String,
+    /// Box behind the text as #RRGGBB or #RRGGBBAA (None = no box)
+    pub background: Option<String>,
+    /// "top", "center" or "bottom", within the safe area when there is one
+    pub position: String,
+    pub uppercase: bool,
+    /// Frame margins the target platform's UI covers; captions are laid out inside them
+    pub safe_area: Option<SafeArea>,
+}
+
+impl Default for CaptionStyle {
+    fn default() -> Self {
+        CaptionStyle {
+            font_family: "Inter".to_string(),
+            font_size: 48,
+            color: "#FFFFFF".to_string(),
+            background: Some("#00000099".to_string()),
+            position: "bottom".to_string(),
+            uppercase: false,
+            safe_area: None,
+        }
+    }
+}
+
+impl CaptionStyle {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.font_family.trim().is_empty() {
+            return Err("font_family is required".to_string());
+        }
+        if !(8..=200).contains(&self.font_size) {
+            return Err(format!("font_size must be between 8 and 200 (got {})", self.font_size));
+        }
+        if !is_hex_color(&self.color, false) {
+            return Err(format!("color must be #RRGGBB (got {})", self.color));
+        }
+        if let Some(background) = &self.background {
+            if !is_hex_color(background, true) {
+                return Err(format!("background must be #RRGGBB or #RRGGBBAA (got {})", background));
+            }
+        }
+        if !CAPTION_POSITIONS.contains(&self.position.as_str()) {
+            return Err(format!("position must be one of {}", CAPTION_POSITIONS.join(", ")));
+        }
+        if let Some(safe_area) = &self.safe_area {
+            safe_area.validate().map_err(|e| format!("safe_area: {}", e))?;
+        }
+        Ok(())
+    }
+}
+
+/// Margins of the frame, as fractions of its width (left, right) and height (top, bottom)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SafeArea {
+    pub top: f64,
+    pub bottom: f64,
+    pub left: f64,
+    pub right: f64,
+}
+
+/// Platforms with known safe areas, for style profiles that name one
+pub const SAFE_AREA_PLATFORMS: &[&str] = &["title_safe", "tiktok", "instagram_reels", "youtube_shorts", "vertical_social"];
+
+impl SafeArea {
+    /// Margins kept clear of a platform's overlaid UI (captions, buttons, progress bar).
+    /// "vertical_social" covers TikTok, Reels and Shorts at once.
+    pub fn for_platform(platform: &str) -> Option<Self> {
+        let area = |top, bottom, left, right| SafeArea { top, bottom, left, right };
+        match platform {
+            "title_safe" => Some(area(0.05, 0.05, 0.05, 0.05)),
+            "tiktok" => Some(area(0.08, 0.23, 0.06, 0.13)),
+            "instagram_reels" => Some(area(0.11, 0.22, 0.05, 0.12)),
+            "youtube_shorts" => Some(area(0.08, 0.25, 0.05, 0.13)),
+            "vertical_social" => Some(area(0.11, 0.25, 0.06, 0.13)),
+            _ => None,
+        }
+    }
+
+    /// Safe area a style profile asks captions to respect: its `caption_safe_area` names a
+    /// platform or gives the margins, and `true` means the broadcast title-safe area
+    pub fn from_style_profile(profile: &serde_json::Value) -> Option<Self> {
+        match profile.get("caption_safe_area")? {
+            serde_json::Value::Bool(true) => Self::for_platform("title_safe"),
+            serde_json::Value::String(platform) => Self::for_platform(platform),
+            value @ serde_json::Value::Object(_) => serde_json::from_value::<SafeArea>(value.clone())
+                .ok()
+                .filter(|area| area.validate().is_ok()),
+            _ => None,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        let margins = [self.top, self.bottom, self.left, self.right];
+        if margins.iter().any(|m| !(0.0..0.5).contains(m)) {
+            return Err("margins must be in [0, 0.5)".to_string());
+        }
+        Ok(())
+    }
+}
+
+fn is_hex_color(value: &str, allow_alpha: bool) -> bool {
+    let Some(hex) = value.strip_prefix('#') else {
+        return false;
+    };
+    (hex.len() == 6 || (allow_alpha && hex.len() == 8)) && hex.chars().all(|c| c.is_ascii_hexdigit())
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -75,19 +645,46 @@ pub struct MusicEvent {
     pub ducking_profile_id: Option<i64>,
 }
 
+/// Recorded or synthesized narration laid over the picture
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NarrationEvent {
+    pub start_ticks: i64,
+    pub end_ticks: i64,
+    pub track_path: String,
+    /// Voiceover the narration was attached from
+    pub voiceover_id: Option<i64>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Marker {
     pub position_ticks: i64,
     pub label: Option<String>,
 }
 
+/// A span of the primary timeline whose content can't be changed by edits
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockedRegion {
+    #[serde(default = "generate_clip_id")]
+    pub id: String,
+    pub start_ticks: i64,
+    pub end_ticks: i64,
+    pub label: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Timeline {
     pub settings: ProjectSettings,
     pub tracks: Vec<Track>,
     pub captions: Vec<CaptionEvent>,
     pub music: Vec<MusicEvent>,
+    #[serde(default)]
+    pub narration: Vec<NarrationEvent>,
+    /// Lower thirds drawn over the program
+    #[serde(default)]
+    pub titles: Vec<TitleEvent>,
     pub markers: Vec<Marker>,
+    #[serde(default)]
+    pub locked_regions: Vec<LockedRegion>,
 }
 
 impl Timeline {
@@ -97,7 +694,10 @@ impl Timeline {
             tracks: Vec::new(),
             captions: Vec::new(),
             music: Vec::new(),
+            narration: Vec::new(),
+            titles: Vec::new(),
             markers: Vec::new(),
+            locked_regions: Vec::new(),
         }
     }
 }
@@ -0,0 +1,97 @@
+use crate::end_card::{drawtext_escape, validate_color};
+use crate::timeline::{generate_clip_id, ProjectSettings, TICKS_PER_SECOND};
+use serde::{Deserialize, Serialize};
+
+/// Lower third text sizes as a share of the frame height
+const NAME_SIZE: f64 = 0.05;
+const SUBTITLE_SIZE: f64 = 0.034;
+
+/// A lower third drawn over the program while it plays, e.g. the name and role of whoever
+/// starts speaking
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TitleEvent {
+    #[serde(default = "generate_clip_id")]
+    pub id: String,
+    pub start_ticks: i64,
+    pub end_ticks: i64,
+    pub text: String,
+    /// Smaller second line (e.g. a role)
+    #[serde(default)]
+    pub subtitle: Option<String>,
+    /// Person the title names
+    #[serde(default)]
+    pub person_id: Option<i64>,
+    #[serde(default)]
+    pub style: TitleStyle,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TitleStyle {
+    /// Font file for drawtext (None = the system's sans-serif)
+    pub font_file: Option<String>,
+    /// Text color (None = white)
+    pub color: Option<String>,
+    /// Color of the boxes behind the lines (None = translucent black)
+    pub background: Option<String>,
+}
+
+impl TitleEvent {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.start_ticks < 0 || self.end_ticks <= self.start_ticks {
+            return Err(format!("Title \"{}\" must end after it starts", self.text));
+        }
+        if self.text.trim().is_empty() {
+            return Err("A title needs text".to_string());
+        }
+        for color in self.style.color.iter().chain(&self.style.background) {
+            validate_color(color)?;
+        }
+        Ok(())
+    }
+}
+
+/// Filters that draw `titles` over the picture in `base` at their times, ending in `output`;
+/// None when there are none
+pub(crate) fn title_filters(titles: &[TitleEvent], settings: &ProjectSettings, base: &str, output: &str) -> Option<String> {
+    if titles.is_empty() {
+        return None;
+    }
+    let h = settings.resolution.height as f64;
+    let mut draws = Vec::new();
+    for title in titles {
+        let enable = format!(
+            "enable='between(t,{:.3},{:.3})'",
+            title.start_ticks as f64 / TICKS_PER_SECOND as f64,
+            title.end_ticks as f64 / TICKS_PER_SECOND as f64
+        );
+        let font = title
+            .style
+            .font_file
+            .as_ref()
+            .map(|f| format!("fontfile={}:", drawtext_escape(f)))
+            .unwrap_or_default();
+        let color = title.style.color.as_deref().unwrap_or("white");
+        let background = title.style.background.as_deref().unwrap_or("black@0.6");
+        let lines = [(Some(&title.text), NAME_SIZE, 0.74), (title.subtitle.as_ref(), SUBTITLE_SIZE, 0.815)];
+        for (text, size, y) in lines {
+            let Some(text) = text.filter(|t| !t.trim().is_empty()) else {
+                continue;
+            };
+            let size = ((h * size).round() as i64).max(1);
+            draws.push(format!(
+                "drawtext={font}text={text}:fontsize={size}:fontcolor={color}:box=1:boxcolor={background}:\
+                 boxborderw={border}:x=w*0.06:y=h*{y:.3}:{enable}",
+                font = font,
+                text = drawtext_escape(text),
+                size = size,
+                color = color,
+                background = background,
+                border = (size as f64 * 0.35).round() as i64,
+                y = y,
+                enable = enable,
+            ));
+        }
+    }
+    Some(format!("{}{}{}", base, draws.join(","), output))
+}
@@ -0,0 +1,68 @@
+use crate::end_card::{image_filter, validate_color};
+use serde::{Deserialize, Serialize};
+
+/// Corners (and the center) a watermark can sit in
+pub const WATERMARK_POSITIONS: &[&str] = &["top_left", "top_right", "bottom_left", "bottom_right", "center"];
+
+/// A logo laid over the whole program, e.g. a brand mark in a corner
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Watermark {
+    pub path: String,
+    /// One of `WATERMARK_POSITIONS`
+    pub position: String,
+    /// Width as a share of the frame width
+    pub width: f64,
+    pub opacity: f64,
+    /// Distance from the frame edges as a share of the frame width
+    pub margin: f64,
+    /// Color keyed out of a logo without transparency
+    #[serde(default)]
+    pub key_color: Option<String>,
+}
+
+impl Watermark {
+    pub fn validate(&self) -> Result<(), String> {
+        if !WATERMARK_POSITIONS.contains(&self.position.as_str()) {
+            return Err(format!(
+                "Watermark position must be one of {}, not {:?}",
+                WATERMARK_POSITIONS.join(", "),
+                self.position
+            ));
+        }
+        if !(self.width > 0.0 && self.width <= 0.5) {
+            return Err(format!("Watermark width {} must be between 0 and 0.5 of the frame width", self.width));
+        }
+        if !(self.opacity > 0.0 && self.opacity <= 1.0) {
+            return Err(format!("Watermark opacity {} must be between 0 and 1", self.opacity));
+        }
+        if !(0.0..=0.25).contains(&self.margin) {
+            return Err(format!("Watermark margin {} must be between 0 and 0.25 of the frame width", self.margin));
+        }
+        if let Some(color) = &self.key_color {
+            validate_color(color)?;
+        }
+        Ok(())
+    }
+
+    /// Filters that lay the watermark (input `input`, looped) over the `frame_width`-wide
+    /// picture in `base` for as long as it plays, ending in `output`
+    pub(crate) fn filters(&self, input: usize, frame_width: i32, base: &str, output: &str) -> Vec<String> {
+        let margin = format!("W*{:.4}", self.margin);
+        let (x, y) = match self.position.as_str() {
+            "top_left" => (margin.clone(), margin),
+            "top_right" => (format!("W-w-{}", margin), margin),
+            "bottom_left" => (margin.clone(), format!("H-h-{}", margin)),
+            "center" => ("(W-w)/2".to_string(), "(H-h)/2".to_string()),
+            _ => (format!("W-w-{}", margin), format!("H-h-{}", margin)),
+        };
+        vec![
+            format!(
+                "[{}:v]{},colorchannelmixer=aa={:.3}[wm]",
+                input,
+                image_filter(frame_width as f64 * self.width, self.key_color.as_deref()),
+                self.opacity
+            ),
+            format!("{}[wm]overlay=x={}:y={}:shortest=1{}", base, x, y, output),
+        ]
+    }
+}
@@ -0,0 +1,220 @@
+//! Golden-file tests for the render compiler: each case in `tests/golden/<name>.json` is a
+//! timeline (or an edit plan, compiled first) plus export options; the render command it
+//! compiles to is written out as text and compared with `tests/golden/<name>.golden`.
+//!
+//! After an intended change to the compiler, regenerate the files and review their diff:
+//!
+//! ```bash
+//! UPDATE_GOLDEN=1 cargo test -p engine --test golden
+//! ```
+
+use engine::encode::EncoderSettings;
+use engine::render::{
+    captions_srt, chapters_ffmetadata, generate_render_commands, output_duration_seconds, AudioOptions,
+    BrandingOptions, MuxOptions, RenderCommand, SourceMedia,
+};
+use engine::{compile_edit_plan, AudioEnhancement, EditPlan, ProjectSettings, Timeline};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Where every case exports to, and keeps its intermediates and sidecar files
+const OUT_DIR: &str = "/out";
+
+#[derive(Deserialize)]
+struct Case {
+    /// The timeline to export; or `settings` and an `edit_plan` to compile into one
+    timeline: Option<Timeline>,
+    settings: Option<ProjectSettings>,
+    edit_plan: Option<EditPlan>,
+    /// Files to render assets from, by asset id (others come from `/media/asset_<id>.mp4` at
+    /// the project frame rate)
+    #[serde(default)]
+    sources: HashMap<i64, Source>,
+    /// Export preset (see `EncoderSettings::for_preset`), "mp4" by default
+    preset: Option<String>,
+    /// Render primary clips to intermediates in the clip cache first
+    #[serde(default)]
+    clip_cache: bool,
+    /// Mux the timeline's captions as a subtitle track
+    #[serde(default)]
+    captions: bool,
+    /// Chapters to mux, as (seconds, title)
+    #[serde(default)]
+    chapters: Vec<(f64, String)>,
+    /// Enhancement for clips that don't set their own
+    enhance: Option<AudioEnhancement>,
+}
+
+#[derive(Deserialize)]
+struct Source {
+    path: String,
+    color_filter: Option<String>,
+    /// None for stills
+    fps: Option<f64>,
+}
+
+impl Case {
+    fn load(name: &str) -> Case {
+        let path = golden_dir().join(format!("{}.json", name));
+        let json = std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("Reading {}: {}", path.display(), e));
+        serde_json::from_str(&json).unwrap_or_else(|e| panic!("Parsing {}: {}", path.display(), e))
+    }
+
+    fn timeline(&self) -> Timeline {
+        match (&self.timeline, &self.edit_plan, &self.settings) {
+            (Some(timeline), _, _) => timeline.clone(),
+            (None, Some(plan), Some(settings)) => {
+                // Compiled clips get random ids; number them so the output is stable
+                let mut timeline = compile_edit_plan(plan.clone(), settings.clone());
+                let clips = timeline.tracks.iter_mut().flat_map(|t| t.clips.iter_mut());
+                for (i, clip) in clips.enumerate() {
+                    clip.id = format!("clip-{}", i + 1);
+                }
+                timeline
+            }
+            _ => panic!("A case needs a timeline, or settings and an edit_plan"),
+        }
+    }
+}
+
+fn golden_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("golden")
+}
+
+/// Arguments one per line; a filter graph one filter chain per line, indented under its flag
+fn write_args(out: &mut String, args: &[String]) {
+    let mut graph_next = false;
+    for arg in args {
+        if graph_next {
+            for chain in arg.split(';') {
+                out.push_str(&format!("    {}\n", chain));
+            }
+        } else {
+            out.push_str(&format!("{}\n", arg));
+        }
+        graph_next = matches!(arg.as_str(), "-filter_complex" | "-vf" | "-af");
+    }
+}
+
+fn render(case: &Case) -> String {
+    let timeline = case.timeline();
+    let mut sources: HashMap<i64, SourceMedia> = case
+        .sources
+        .iter()
+        .map(|(id, s)| {
+            let source = SourceMedia {
+                path: s.path.clone(),
+                color_filter: s.color_filter.clone(),
+                fps: s.fps,
+                checksum: None,
+            };
+            (*id, source)
+        })
+        .collect();
+    for clip in timeline.tracks.iter().flat_map(|t| t.clips.iter()) {
+        sources.entry(clip.asset_id).or_insert_with(|| SourceMedia {
+            path: format!("/media/asset_{}.mp4", clip.asset_id),
+            fps: Some(timeline.settings.fps),
+            ..SourceMedia::default()
+        });
+    }
+    let preset = case.preset.as_deref().unwrap_or("mp4");
+    let encoder = EncoderSettings::for_preset(preset).unwrap_or_else(|| panic!("Unknown preset {}", preset));
+    let out_dir = Path::new(OUT_DIR);
+    let mux = MuxOptions {
+        chapters_path: (!case.chapters.is_empty()).then(|| out_dir.join("chapters.txt").to_string_lossy().to_string()),
+        captions_path: case.captions.then(|| out_dir.join("captions.srt").to_string_lossy().to_string()),
+        ..MuxOptions::default()
+    };
+    let audio = AudioOptions {
+        enhance: case.enhance.clone(),
+        rnnoise_model: None,
+    };
+    let cache_dir = out_dir.join("cache");
+    let command: RenderCommand = generate_render_commands(
+        &timeline,
+        out_dir.join("export.mp4"),
+        &sources,
+        &[],
+        &audio,
+        &mux,
+        &BrandingOptions::default(),
+        &encoder,
+        case.clip_cache.then_some(cache_dir.as_path()),
+    );
+
+    let mut out = format!("# duration {:.3}s\n", output_duration_seconds(&timeline));
+    for render in &command.clip_renders {
+        out.push_str(&format!("\n# clip {} -> {}\n", render.clip_id, render.path.display()));
+        write_args(&mut out, &render.ffmpeg_args);
+    }
+    if let Some(first_pass) = &command.first_pass_args {
+        out.push_str("\n# first pass\n");
+        write_args(&mut out, first_pass);
+    }
+    out.push_str("\n# export\n");
+    write_args(&mut out, &command.ffmpeg_args);
+    if case.captions {
+        out.push_str("\n# captions.srt\n");
+        out.push_str(&captions_srt(&timeline.captions));
+    }
+    if !case.chapters.is_empty() {
+        out.push_str("\n# chapters.txt\n");
+        out.push_str(&chapters_ffmetadata(&case.chapters, output_duration_seconds(&timeline)));
+    }
+    out
+}
+
+/// Compare a case's output with its golden file (or rewrite the file with UPDATE_GOLDEN set)
+fn check(name: &str) {
+    let actual = render(&Case::load(name));
+    let path = golden_dir().join(format!("{}.golden", name));
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::write(&path, &actual).unwrap_or_else(|e| panic!("Writing {}: {}", path.display(), e));
+        return;
+    }
+    let expected = std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("Reading {} ({}); run with UPDATE_GOLDEN=1 to create it", path.display(), e));
+    if actual == expected {
+        return;
+    }
+    let mut expected_lines = expected.lines();
+    let mut actual_lines = actual.lines();
+    let mut line = 1;
+    loop {
+        match (expected_lines.next(), actual_lines.next()) {
+            (Some(e), Some(a)) if e == a => line += 1,
+            (e, a) => panic!(
+                "{} differs from {} at line {}:\n  expected: {}\n  actual:   {}\nRun with UPDATE_GOLDEN=1 if the change is intended.",
+                name,
+                path.display(),
+                line,
+                e.unwrap_or("<end of file>"),
+                a.unwrap_or("<end of file>"),
+            ),
+        }
+    }
+}
+
+macro_rules! golden {
+    ($($name:ident),* $(,)?) => {
+        $(
+            #[test]
+            fn $name() {
+                check(stringify!($name));
+            }
+        )*
+    };
+}
+
+golden! {
+    hard_cuts,
+    speed,
+    overlays,
+    split_audio,
+    captions_and_titles,
+    ken_burns_still,
+    clip_cache_two_pass,
+    edit_plan,
+}
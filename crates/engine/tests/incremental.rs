@@ -0,0 +1,211 @@
+//! Incremental exports: a full render must force a keyframe at every span boundary, since the
+//! stitch copies spans out of it from those boundaries, and the stitch plan must copy and
+//! render the right ranges.
+
+use engine::encode::EncoderSettings;
+use engine::incremental::{plan_stitch, render_spans, span_timeline, ticks_to_seconds, RenderSpan, StitchPiece};
+use engine::render::{
+    generate_render_commands, generate_stitch_command, AudioOptions, BrandingOptions, MuxOptions, RenderCommand,
+    SourceMedia,
+};
+use engine::{Timeline, TICKS_PER_SECOND};
+use serde_json::json;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+const SECOND: i64 = TICKS_PER_SECOND;
+
+/// Primary clips (id, asset, in and out in seconds) played back to back
+fn timeline(clips: &[(&str, i64, i64, i64)]) -> Timeline {
+    let mut start = 0;
+    let clips: Vec<serde_json::Value> = clips
+        .iter()
+        .map(|(id, asset_id, in_s, out_s)| {
+            let clip = json!({
+                "id": id,
+                "asset_id": asset_id,
+                "in_ticks": in_s * SECOND,
+                "out_ticks": out_s * SECOND,
+                "timeline_start_ticks": start,
+                "speed": 1.0,
+                "track_id": 1,
+            });
+            start += (out_s - in_s) * SECOND;
+            clip
+        })
+        .collect();
+    serde_json::from_value(json!({
+        "settings": { "fps": 30.0, "resolution": { "width": 1920, "height": 1080 }, "sample_rate": 48000 },
+        "tracks": [{ "id": 1, "kind": "Video", "clips": clips }],
+        "captions": [],
+        "music": [],
+        "markers": [],
+    }))
+    .unwrap()
+}
+
+fn sources(timeline: &Timeline) -> HashMap<i64, SourceMedia> {
+    timeline
+        .tracks
+        .iter()
+        .flat_map(|t| t.clips.iter())
+        .map(|clip| {
+            let source = SourceMedia {
+                path: format!("/media/asset_{}.mp4", clip.asset_id),
+                fps: Some(timeline.settings.fps),
+                ..SourceMedia::default()
+            };
+            (clip.asset_id, source)
+        })
+        .collect()
+}
+
+fn spans(timeline: &Timeline) -> Vec<RenderSpan> {
+    render_spans(timeline, &sources(timeline), &[], &AudioOptions::default(), &EncoderSettings::default())
+}
+
+fn render(timeline: &Timeline, output: &str) -> RenderCommand {
+    generate_render_commands(
+        timeline,
+        PathBuf::from(output),
+        &sources(timeline),
+        &[],
+        &AudioOptions::default(),
+        &MuxOptions::default(),
+        &BrandingOptions::default(),
+        &EncoderSettings::default(),
+        None,
+    )
+}
+
+/// The value passed to `-force_key_frames`, if any
+fn forced_keyframes(args: &[String]) -> Option<&str> {
+    let flag = args.iter().position(|a| a == "-force_key_frames")?;
+    args.get(flag + 1).map(String::as_str)
+}
+
+/// Span boundaries inside the output, as `-force_key_frames` writes them
+fn inner_boundaries(spans: &[RenderSpan]) -> String {
+    spans
+        .iter()
+        .skip(1)
+        .map(|s| format!("{:.6}", ticks_to_seconds(s.start_ticks)))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[test]
+fn full_render_forces_keyframes_at_span_boundaries() {
+    let timeline = timeline(&[("a", 1, 2, 6), ("b", 2, 10, 13), ("c", 3, 0, 5)]);
+    let spans = spans(&timeline);
+    assert_eq!(spans.len(), 3);
+    assert_eq!(inner_boundaries(&spans), "4.000000,7.000000");
+
+    let command = render(&timeline, "/out/export.mp4");
+    assert!(command.span_keyframes);
+    assert_eq!(forced_keyframes(&command.ffmpeg_args), Some(inner_boundaries(&spans).as_str()));
+}
+
+#[test]
+fn two_pass_render_forces_keyframes_in_both_passes() {
+    let timeline = timeline(&[("a", 1, 2, 6), ("b", 2, 10, 13), ("c", 3, 0, 5)]);
+    let spans = spans(&timeline);
+    // The web preset encodes in two passes
+    let encoder = EncoderSettings::for_preset("web").unwrap();
+    assert!(encoder.two_pass);
+    let command = generate_render_commands(
+        &timeline,
+        PathBuf::from("/out/export.mp4"),
+        &sources(&timeline),
+        &[],
+        &AudioOptions::default(),
+        &MuxOptions::default(),
+        &BrandingOptions::default(),
+        &encoder,
+        None,
+    );
+    let first_pass = command.first_pass_args.as_deref().expect("a first pass");
+    assert_eq!(forced_keyframes(first_pass), Some(inner_boundaries(&spans).as_str()));
+    assert_eq!(forced_keyframes(&command.ffmpeg_args), Some(inner_boundaries(&spans).as_str()));
+}
+
+#[test]
+fn span_render_is_marked_stitchable() {
+    let timeline = timeline(&[("a", 1, 2, 6), ("b", 2, 10, 13)]);
+    let spans = spans(&timeline);
+    let span = &spans[1];
+    let command = render(&span_timeline(&timeline, &spans, span), "/out/span.mp4");
+    assert!(command.span_keyframes);
+    // One clip: the span starts on the first frame, which is always a keyframe
+    assert_eq!(forced_keyframes(&command.ffmpeg_args), None);
+}
+
+#[test]
+fn changed_clip_is_rendered_and_the_rest_copied() {
+    let before = timeline(&[("a", 1, 2, 6), ("b", 2, 10, 13), ("c", 3, 0, 5)]);
+    let after = timeline(&[("a", 1, 2, 6), ("b", 2, 11, 14), ("c", 3, 0, 5)]);
+    let plan = plan_stitch(&spans(&before), &spans(&after));
+
+    let ranges: Vec<(&str, i64, i64)> = plan
+        .pieces
+        .iter()
+        .map(|piece| match piece {
+            StitchPiece::Previous { start_ticks, end_ticks } => ("previous", *start_ticks, *end_ticks),
+            StitchPiece::Render { span } => ("render", span.start_ticks, span.end_ticks),
+        })
+        .collect();
+    assert_eq!(
+        ranges,
+        vec![
+            ("previous", 0, 4 * SECOND),
+            ("render", 4 * SECOND, 7 * SECOND),
+            ("previous", 7 * SECOND, 12 * SECOND),
+        ]
+    );
+    assert_eq!(plan.changed_ranges, vec![(4 * SECOND, 7 * SECOND)]);
+    assert_eq!(plan.reused_ticks, 9 * SECOND);
+    assert_eq!(plan.rendered_ticks, 3 * SECOND);
+
+    // Every copied range starts and ends where the earlier render forced a keyframe (or at
+    // its start and end)
+    let earlier = render(&before, "/out/export.mp4");
+    let keyframes: Vec<f64> = forced_keyframes(&earlier.ffmpeg_args)
+        .unwrap_or_default()
+        .split(',')
+        .map(|s| s.parse().unwrap())
+        .chain([0.0, ticks_to_seconds(12 * SECOND)])
+        .collect();
+    for piece in &plan.pieces {
+        if let StitchPiece::Previous { start_ticks, end_ticks } = piece {
+            for ticks in [start_ticks, end_ticks] {
+                assert!(keyframes.contains(&ticks_to_seconds(*ticks)), "no keyframe at {} ticks", ticks);
+            }
+        }
+    }
+}
+
+#[test]
+fn removed_clip_joins_the_copied_spans_around_it() {
+    let before = timeline(&[("a", 1, 2, 6), ("b", 2, 10, 13), ("c", 3, 0, 5)]);
+    let after = timeline(&[("b", 2, 10, 13), ("c", 3, 0, 5)]);
+    let plan = plan_stitch(&spans(&before), &spans(&after));
+
+    assert!(matches!(
+        plan.pieces.as_slice(),
+        [StitchPiece::Previous { start_ticks, end_ticks }] if *start_ticks == 4 * SECOND && *end_ticks == 12 * SECOND
+    ));
+    assert!(plan.changed_ranges.is_empty());
+    assert_eq!(plan.reused_ticks, 8 * SECOND);
+    assert_eq!(plan.rendered_ticks, 0);
+}
+
+#[test]
+fn stitch_copies_streams() {
+    let command = generate_stitch_command(
+        PathBuf::from("/out/cache/stitch.ffconcat"),
+        PathBuf::from("/out/export.mp4"),
+        &MuxOptions::default(),
+    );
+    assert!(command.span_keyframes);
+    assert!(command.ffmpeg_args.windows(2).any(|w| w[0] == "-c:v" && w[1] == "copy"));
+}
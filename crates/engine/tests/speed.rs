@@ -0,0 +1,81 @@
+//! Sped-up and slowed-down clips: the primary timeline packs clips by how long they play, not
+//! by how much source they use, and fitting to a duration counts playback time too.
+
+use engine::fit::fit_to_duration;
+use engine::ops::TimelineOperation;
+use engine::{Timeline, TICKS_PER_SECOND};
+use serde_json::json;
+use std::collections::HashMap;
+
+const SECOND: i64 = TICKS_PER_SECOND;
+
+/// Primary clips (id, in and out in seconds, speed) played back to back
+fn timeline(clips: &[(&str, i64, i64, f64)]) -> Timeline {
+    let mut start = 0;
+    let clips: Vec<serde_json::Value> = clips
+        .iter()
+        .enumerate()
+        .map(|(idx, (id, in_s, out_s, speed))| {
+            let clip = json!({
+                "id": id,
+                "asset_id": idx as i64 + 1,
+                "in_ticks": in_s * SECOND,
+                "out_ticks": out_s * SECOND,
+                "timeline_start_ticks": start,
+                "speed": speed,
+                "track_id": 1,
+            });
+            start += ((out_s - in_s) as f64 * SECOND as f64 / speed) as i64;
+            clip
+        })
+        .collect();
+    serde_json::from_value(json!({
+        "settings": { "fps": 30.0, "resolution": { "width": 1920, "height": 1080 }, "sample_rate": 48000 },
+        "tracks": [{ "id": 1, "kind": "Video", "clips": clips }],
+        "captions": [],
+        "music": [],
+        "markers": [],
+    }))
+    .unwrap()
+}
+
+fn starts(timeline: &Timeline) -> Vec<(String, i64)> {
+    timeline.tracks[0].clips.iter().map(|c| (c.id.clone(), c.timeline_start_ticks)).collect()
+}
+
+#[test]
+fn doubling_a_clips_speed_halves_the_next_clips_start() {
+    let mut timeline = timeline(&[("a", 0, 4, 1.0), ("b", 10, 14, 1.0), ("c", 20, 22, 1.0)]);
+    timeline
+        .apply_operation(TimelineOperation::SetClipSpeed { clip_id: "a".to_string(), speed: 2.0 })
+        .unwrap();
+
+    assert_eq!(
+        starts(&timeline),
+        vec![("a".to_string(), 0), ("b".to_string(), 2 * SECOND), ("c".to_string(), 6 * SECOND)]
+    );
+}
+
+#[test]
+fn fit_counts_playback_time() {
+    // 8s of source at 2x plays for 4s, so the timeline is 8s long
+    let timeline = timeline(&[("a", 0, 8, 2.0), ("b", 10, 14, 1.0)]);
+    let plan = fit_to_duration(&timeline, 6 * SECOND, SECOND / 10, &HashMap::new());
+
+    assert_eq!(plan.before_ticks, 8 * SECOND);
+    assert!(plan.dropped_clip_ids.is_empty());
+    // Each clip loses a second of playback; for the 2x clip that's two seconds of source
+    let trims: Vec<(&str, i64, i64)> = plan
+        .operations
+        .iter()
+        .filter_map(|op| match op {
+            TimelineOperation::TrimClip { clip_id, new_in_ticks, new_out_ticks } => {
+                Some((clip_id.as_str(), *new_in_ticks, *new_out_ticks))
+            }
+            _ => None,
+        })
+        .collect();
+    assert_eq!(trims, vec![("a", 0, 6 * SECOND), ("b", 10 * SECOND, 13 * SECOND)]);
+    assert_eq!(plan.after_ticks, 6 * SECOND);
+    assert!(plan.within_tolerance);
+}
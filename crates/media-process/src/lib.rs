@@ -0,0 +1,372 @@
+//! Hardened runner for the media tools (ffmpeg, ffprobe, heif-convert), shared by the daemon
+//! and the CLI's render worker. Paths in their arguments come from users, so every invocation
+//! goes through here: the program is one of a fixed set and runs directly (never through a
+//! shell), its options must be on the tool's allow-list, input and output paths can't name
+//! ffmpeg protocols or climb out of the working directory, filters that read files are refused,
+//! and the process runs in a scratch directory with a timeout and a cap on the output it can
+//! write to its pipes. Failures keep the tail of stderr.
+
+use anyhow::Result;
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
+use std::process::{Output, Stdio};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::process::Command;
+
+/// ffprobe and heif-convert only read headers or one image
+const PROBE_TIMEOUT: Duration = Duration::from_secs(60);
+/// Longest an ffmpeg run may take unless FFMPEG_TIMEOUT_SECS says otherwise (a long export)
+const DEFAULT_FFMPEG_TIMEOUT_SECS: u64 = 6 * 60 * 60;
+/// Most a process may write to stdout or stderr unless PROCESS_MAX_OUTPUT_MB says otherwise
+const DEFAULT_MAX_OUTPUT_MB: usize = 64;
+/// Lines of stderr kept for diagnostics
+const STDERR_TAIL_LINES: usize = 12;
+
+/// ffmpeg options taking a value (stream specifiers like `:v` or `:s:a:0` are allowed after any)
+const FFMPEG_OPTIONS: &[&str] = &[
+    "-i", "-f", "-map", "-vf", "-af", "-filter_complex", "-c", "-codec", "-vcodec", "-acodec", "-b", "-t", "-to",
+    "-ss", "-itsoffset", "-preset", "-tune", "-crf", "-qp", "-q", "-loop", "-r", "-s", "-ar", "-ac", "-threads",
+    "-filter_threads", "-pass", "-passlogfile", "-movflags", "-map_metadata", "-map_chapters", "-metadata", "-tag",
+    "-start_number", "-safe", "-pix_fmt", "-frames", "-framerate", "-force_key_frames", "-g", "-profile", "-level",
+    "-maxrate", "-bufsize", "-cpu-used", "-row-mt", "-x264-params", "-x265-params", "-disposition", "-aspect",
+    "-color_primaries", "-color_trc", "-colorspace", "-fps_mode", "-max_muxing_queue_size", "-loglevel", "-v",
+];
+/// ffmpeg options that are flags
+const FFMPEG_FLAGS: &[&str] = &[
+    "-y", "-n", "-vn", "-an", "-sn", "-dn", "-shortest", "-hide_banner", "-nostdin", "-nostats", "-stats", "-copyts",
+    "-noautorotate",
+];
+const FFPROBE_OPTIONS: &[&str] = &["-v", "-loglevel", "-show_entries", "-of", "-print_format", "-select_streams", "-read_intervals"];
+const FFPROBE_FLAGS: &[&str] = &["-hide_banner", "-show_format", "-show_streams", "-count_frames"];
+const HEIF_CONVERT_OPTIONS: &[&str] = &["-q"];
+
+/// Filters that open files named in their arguments
+const FILE_READING_FILTERS: &[&str] = &["movie", "amovie", "sendcmd", "asendcmd"];
+/// Filter options that name a file to read
+const FILE_READING_FILTER_OPTIONS: &[&str] = &["textfile"];
+
+/// The programs the runner starts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tool {
+    Ffmpeg,
+    Ffprobe,
+    HeifConvert,
+}
+
+impl Tool {
+    pub fn program(&self) -> &'static str {
+        match self {
+            Tool::Ffmpeg => "ffmpeg",
+            Tool::Ffprobe => "ffprobe",
+            Tool::HeifConvert => "heif-convert",
+        }
+    }
+
+    fn options(&self) -> (&'static [&'static str], &'static [&'static str]) {
+        match self {
+            Tool::Ffmpeg => (FFMPEG_OPTIONS, FFMPEG_FLAGS),
+            Tool::Ffprobe => (FFPROBE_OPTIONS, FFPROBE_FLAGS),
+            Tool::HeifConvert => (HEIF_CONVERT_OPTIONS, &[]),
+        }
+    }
+
+    /// How long a run may take: FFMPEG_TIMEOUT_SECS (or six hours) for ffmpeg, a minute for
+    /// the others
+    pub fn default_timeout(&self) -> Duration {
+        match self {
+            Tool::Ffmpeg => Duration::from_secs(
+                std::env::var("FFMPEG_TIMEOUT_SECS")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(DEFAULT_FFMPEG_TIMEOUT_SECS),
+            ),
+            Tool::Ffprobe | Tool::HeifConvert => PROBE_TIMEOUT,
+        }
+    }
+}
+
+/// Why a media tool didn't run to completion
+#[derive(Debug, Clone, PartialEq)]
+pub enum Failure {
+    /// Its arguments broke the rules; it was never started
+    Rejected(String),
+    /// It couldn't be started (usually: not installed)
+    NotStarted(String),
+    TimedOut(Duration),
+    /// It wrote more than this many bytes to stdout or stderr
+    OutputTooLarge(usize),
+    /// It exited unsuccessfully
+    Exited(Option<i32>),
+}
+
+/// A media tool that failed, with the end of what it wrote to stderr
+#[derive(Debug, Clone)]
+pub struct ProcessFailed {
+    pub program: &'static str,
+    pub failure: Failure,
+    pub stderr_tail: Vec<String>,
+}
+
+impl ProcessFailed {
+    /// The failure of a run that exited unsuccessfully
+    pub fn exited(tool: Tool, output: &Output) -> Self {
+        ProcessFailed {
+            program: tool.program(),
+            failure: Failure::Exited(output.status.code()),
+            stderr_tail: stderr_tail(&output.stderr),
+        }
+    }
+}
+
+impl fmt::Display for ProcessFailed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.failure {
+            Failure::Rejected(reason) => write!(f, "Refused to run {}: {}", self.program, reason)?,
+            Failure::NotStarted(reason) => write!(f, "Failed to execute {} ({}). Make sure it's installed.", self.program, reason)?,
+            Failure::TimedOut(limit) => write!(f, "{} timed out after {}s", self.program, limit.as_secs())?,
+            Failure::OutputTooLarge(limit) => write!(f, "{} wrote more than {} bytes of output", self.program, limit)?,
+            Failure::Exited(Some(code)) => write!(f, "{} exited with status {}", self.program, code)?,
+            Failure::Exited(None) => write!(f, "{} was killed", self.program)?,
+        }
+        if let Some(last) = self.stderr_tail.last() {
+            write!(f, ": {}", last)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ProcessFailed {}
+
+/// The last lines a process wrote to stderr, without ffmpeg's progress noise
+pub fn stderr_tail(stderr: &[u8]) -> Vec<String> {
+    let text = String::from_utf8_lossy(stderr);
+    let lines: Vec<&str> = text
+        .lines()
+        .flat_map(|line| line.split('\r'))
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with("frame=") && !line.starts_with("size="))
+        .collect();
+    lines[lines.len().saturating_sub(STDERR_TAIL_LINES)..].iter().map(|l| l.to_string()).collect()
+}
+
+/// Scratch directory processes run in, so relative paths and stray files (e.g. two-pass logs)
+/// stay inside it
+pub fn jail_dir() -> PathBuf {
+    std::env::temp_dir().join("vibecut-process")
+}
+
+fn max_output_bytes() -> usize {
+    std::env::var("PROCESS_MAX_OUTPUT_MB")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_MAX_OUTPUT_MB)
+        * 1024
+        * 1024
+}
+
+/// `-c:v` -> `-c`, `-metadata:s:a:0` -> `-metadata`
+fn option_name(arg: &str) -> &str {
+    arg.split(':').next().unwrap_or(arg)
+}
+
+fn is_number(arg: &str) -> bool {
+    arg.parse::<f64>().is_ok()
+}
+
+/// Checks a path an ffmpeg tool will open or write: no protocol other than `file:`, and a
+/// relative path mustn't climb out of the working directory
+fn check_path(value: &str) -> Result<(), String> {
+    if value.is_empty() || value.contains('\0') {
+        return Err("empty path or NUL byte in a path".to_string());
+    }
+    let path_part = value.strip_prefix("file:").unwrap_or(value);
+    let path = Path::new(path_part);
+    if !path.is_absolute() {
+        if let Some((scheme, _)) = path_part.split_once(':') {
+            // A one-letter prefix is a Windows drive
+            if scheme.len() > 1 && scheme.chars().all(|c| c.is_ascii_alphanumeric() || "+-._".contains(c)) {
+                return Err(format!("protocol {}: isn't allowed in {}", scheme, value));
+            }
+        }
+        if path.components().any(|c| matches!(c, Component::ParentDir)) {
+            return Err(format!("relative path {} leaves the working directory", value));
+        }
+    }
+    Ok(())
+}
+
+/// Filter graphs mustn't use filters that read files. A filter name starts the graph or follows
+/// a `,`, `;` or `]`; an option name follows `=` or `:`.
+fn check_filter(graph: &str) -> Result<(), String> {
+    let is_word = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let mut word_start = None;
+    // A trailing space ends the last word
+    for (i, c) in graph.char_indices().chain(std::iter::once((graph.len(), ' '))) {
+        match (word_start, is_word(c)) {
+            (None, true) => word_start = Some(i),
+            (Some(start), false) => {
+                word_start = None;
+                let word = &graph[start..i];
+                let before = graph[..start].trim_end().chars().last();
+                let after = (i < graph.len()).then_some(c);
+                let filter_position = matches!(before, None | Some(',') | Some(';') | Some(']'))
+                    && matches!(after, None | Some('=') | Some('@') | Some('[') | Some(',') | Some(';'));
+                if FILE_READING_FILTERS.contains(&word) && filter_position {
+                    return Err(format!("filter {} reads files", word));
+                }
+                if FILE_READING_FILTER_OPTIONS.contains(&word) && matches!(before, Some('=') | Some(':')) && after == Some('=') {
+                    return Err(format!("filter option {} reads files", word));
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Check a tool's arguments against its allow-list and the path and filter rules
+pub fn check_args<S: AsRef<str>>(tool: Tool, args: &[S]) -> Result<(), ProcessFailed> {
+    path_arguments(tool, args).map(|_| ())
+}
+
+/// Checks the arguments and finds the ones that are paths
+fn path_arguments<S: AsRef<str>>(tool: Tool, args: &[S]) -> Result<Vec<usize>, ProcessFailed> {
+    let rejected = |reason: String| ProcessFailed {
+        program: tool.program(),
+        failure: Failure::Rejected(reason),
+        stderr_tail: Vec::new(),
+    };
+    let (options, flags) = tool.options();
+    let mut paths = Vec::new();
+    let mut format: Option<&str> = None;
+    let mut i = 0;
+    while i < args.len() {
+        let arg = args[i].as_ref();
+        if arg.starts_with('-') && arg != "-" && !is_number(arg) {
+            let name = option_name(arg);
+            if flags.contains(&name) {
+                i += 1;
+                continue;
+            }
+            if !options.contains(&name) {
+                return Err(rejected(format!("option {} isn't allowed", arg)));
+            }
+            let value = args.get(i + 1).map(|v| v.as_ref()).ok_or_else(|| rejected(format!("option {} needs a value", arg)))?;
+            match name {
+                // A lavfi input is a filter graph, not a file
+                "-i" if format == Some("lavfi") => check_filter(value).map_err(rejected)?,
+                "-i" | "-passlogfile" => {
+                    check_path(value).map_err(rejected)?;
+                    paths.push(i + 1);
+                }
+                "-vf" | "-af" | "-filter_complex" => check_filter(value).map_err(rejected)?,
+                _ => {}
+            }
+            format = if name == "-f" { Some(value) } else if name == "-i" { None } else { format };
+            i += 2;
+        } else {
+            // Outputs (inputs for ffprobe and heif-convert); "-" is stdout
+            if arg != "-" {
+                check_path(arg).map_err(rejected)?;
+                paths.push(i);
+            }
+            i += 1;
+        }
+    }
+    Ok(paths)
+}
+
+/// Relative paths are the daemon's, not the scratch directory's
+fn absolute(value: &str, base: &Path) -> String {
+    let (prefix, path) = match value.strip_prefix("file:") {
+        Some(path) => ("file:", path),
+        None => ("", value),
+    };
+    if Path::new(path).is_absolute() {
+        return value.to_string();
+    }
+    format!("{}{}", prefix, base.join(path).to_string_lossy())
+}
+
+/// Read a pipe to the end, keeping at most `limit` bytes; returns whether there was more
+async fn read_capped<R: AsyncRead + Unpin>(mut reader: R, limit: usize) -> std::io::Result<(Vec<u8>, bool)> {
+    let mut kept = Vec::new();
+    let mut overflowed = false;
+    let mut chunk = [0u8; 8192];
+    loop {
+        let n = reader.read(&mut chunk).await?;
+        if n == 0 {
+            return Ok((kept, overflowed));
+        }
+        // Keep draining so the process isn't blocked on a full pipe until it's killed
+        if kept.len() + n > limit {
+            overflowed = true;
+        } else {
+            kept.extend_from_slice(&chunk[..n]);
+        }
+    }
+}
+
+/// Check a tool's arguments and make the relative paths among them absolute (against the
+/// current directory, since the tool runs in [`jail_dir`])
+pub fn checked_args(tool: Tool, mut args: Vec<String>) -> Result<Vec<String>> {
+    let paths = path_arguments(tool, &args)?;
+    let cwd = std::env::current_dir()?;
+    for i in paths {
+        args[i] = absolute(&args[i], &cwd);
+    }
+    Ok(args)
+}
+
+/// Run `command` (the tool's program, with whatever limits the caller set on it) with `args`
+/// from [`checked_args`] in the scratch directory, and collect its output. Fails with
+/// [`ProcessFailed`] when the tool can't start, runs out of time or writes too much; an
+/// unsuccessful exit is returned as is. The process is killed if the future is dropped.
+pub async fn run(tool: Tool, mut command: Command, args: &[String], timeout: Duration) -> Result<Output> {
+    let program = tool.program();
+    let jail = jail_dir();
+    tokio::fs::create_dir_all(&jail).await?;
+
+    let mut child = command
+        .args(args)
+        .current_dir(&jail)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| ProcessFailed {
+            program,
+            failure: Failure::NotStarted(e.to_string()),
+            stderr_tail: Vec::new(),
+        })?;
+    let stdout = child.stdout.take().expect("stdout is piped");
+    let stderr = child.stderr.take().expect("stderr is piped");
+    let limit = max_output_bytes();
+
+    let run = tokio::time::timeout(timeout, async {
+        let (stdout, stderr) = tokio::join!(read_capped(stdout, limit), read_capped(stderr, limit));
+        let status = child.wait().await?;
+        Ok::<_, std::io::Error>((status, stdout?, stderr?))
+    })
+    .await;
+    let (status, (stdout, stdout_overflowed), (stderr, stderr_overflowed)) = match run {
+        Ok(result) => result?,
+        Err(_) => {
+            let _ = child.kill().await;
+            eprintln!("[PROCESS] {} timed out after {}s", program, timeout.as_secs());
+            return Err(ProcessFailed { program, failure: Failure::TimedOut(timeout), stderr_tail: Vec::new() }.into());
+        }
+    };
+    if stdout_overflowed || stderr_overflowed {
+        eprintln!("[PROCESS] {} wrote more than {} bytes of output", program, limit);
+        return Err(ProcessFailed {
+            program,
+            failure: Failure::OutputTooLarge(limit),
+            stderr_tail: stderr_tail(&stderr),
+        }
+        .into());
+    }
+    Ok(Output { status, stdout, stderr })
+}
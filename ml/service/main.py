@@ -2,6 +2,7 @@ from fastapi import FastAPI, HTTPException
 from pydantic import BaseModel
 from typing import List, Optional, Dict
 import os
+import sys
 import json
 from pathlib import Path
 
@@ -57,6 +58,8 @@ class TranscriptSegment(BaseModel):
     end: float
     text: str
     words: Optional[List[WordTimestamp]] = None
+    language: Optional[str] = None  # ISO 639-1 code detected for the file (or chunk)
+    speaker: Optional[str] = None  # Diarization label (e.g. "SPEAKER_00"), when diarization is available
 
 
 class TranscribeRequest(BaseModel):
@@ -65,6 +68,46 @@ class TranscribeRequest(BaseModel):
 
 class TranscribeResponse(BaseModel):
     segments: List[TranscriptSegment]
+    language: Optional[str] = None
+    language_probability: Optional[float] = None
+
+
+_diarization_pipeline = None
+
+def get_diarization_pipeline():
+    """Get or load the pyannote speaker diarization pipeline (singleton pattern).
+    Returns None when pyannote.audio isn't installed or HF_TOKEN isn't set; transcripts then
+    come back without speakers."""
+    global _diarization_pipeline
+    if _diarization_pipeline is None:
+        token = os.getenv('HF_TOKEN')
+        if not token:
+            return None
+        try:
+            from pyannote.audio import Pipeline
+        except ImportError:
+            return None
+        _diarization_pipeline = Pipeline.from_pretrained(
+            os.getenv('DIARIZATION_MODEL', 'pyannote/speaker-diarization-3.1'),
+            use_auth_token=token,
+        )
+    return _diarization_pipeline
+
+
+def diarize(media_path: str, segments: List[TranscriptSegment]) -> None:
+    """Label each segment with the speaker whose turns overlap it the most"""
+    pipeline = get_diarization_pipeline()
+    if pipeline is None:
+        return
+    turns = [(turn.start, turn.end, speaker) for turn, _, speaker in pipeline(media_path).itertracks(yield_label=True)]
+    for segment in segments:
+        overlap: Dict[str, float] = {}
+        for start, end, speaker in turns:
+            shared = min(end, segment.end) - max(start, segment.start)
+            if shared > 0:
+                overlap[speaker] = overlap.get(speaker, 0.0) + shared
+        if overlap:
+            segment.speaker = max(overlap, key=overlap.get)
 
 
 @app.get("/health", response_model=HealthResponse)
@@ -81,7 +124,9 @@ async def transcribe(request: TranscribeRequest) -> TranscribeResponse:
         request: Contains mediaPath (local file path)
     
     Returns:
-        TranscribeResponse with segments containing start, end, text, and optional word timestamps
+        TranscribeResponse with segments containing start, end, text, and optional word timestamps,
+        plus the spoken language Whisper detected. With pyannote.audio installed and HF_TOKEN set,
+        segments also carry a speaker label (labels are only consistent within one file).
     """
     media_path = request.mediaPath
     
@@ -117,10 +162,20 @@ async def transcribe(request: TranscribeRequest) -> TranscribeResponse:
                 start=segment.start,
                 end=segment.end,
                 text=segment.text.strip(),
-                words=words if words else None
+                words=words if words else None,
+                language=info.language,
             ))
+
+        try:
+            diarize(media_path, result_segments)
+        except Exception as e:
+            print(f"[WARNING] Diarization failed, transcript has no speakers: {e}", file=sys.stderr, flush=True)
         
-        return TranscribeResponse(segments=result_segments)
+        return TranscribeResponse(
+            segments=result_segments,
+            language=info.language,
+            language_probability=info.language_probability,
+        )
         
     except ImportError:
         raise HTTPException(
@@ -131,6 +186,149 @@ async def transcribe(request: TranscribeRequest) -> TranscribeResponse:
         raise HTTPException(status_code=500, detail=f"Transcription failed: {str(e)}")
 
 
+class AudioEventsRequest(BaseModel):
+    mediaPath: str  # Mono WAV (the daemon passes its cached 16kHz transcription audio)
+    windowSec: float = 2.0  # Classifier window length
+    hopSec: float = 1.0  # Step between windows
+    minConfidence: float = 0.1  # Windows scoring below this are not reported
+
+
+class AudioEvent(BaseModel):
+    start: float
+    end: float
+    label: str  # "laughter" | "applause" | "music" | "crowd"
+    confidence: float
+
+
+class LoudnessWindow(BaseModel):
+    start: float
+    end: float
+    rms_db: float  # RMS level in dBFS (silence floors at -100)
+
+
+class AudioEventsResponse(BaseModel):
+    events: List[AudioEvent]
+    loudness: List[LoudnessWindow] = []  # One entry per hop, for energy/dynamics scoring
+
+
+# AudioSet class names folded into the event labels we expose
+AUDIO_EVENT_CLASSES = {
+    "laughter": ["Laughter", "Giggle", "Chuckle, chortle", "Belly laugh", "Baby laughter", "Snicker"],
+    "applause": ["Applause", "Clapping"],
+    "music": ["Music", "Musical instrument", "Singing", "Song"],
+    "crowd": ["Crowd", "Cheering", "Chatter", "Hubbub, speech noise, speech babble"],
+}
+
+_audio_event_model = None
+
+def get_audio_event_model():
+    """Get or load the AudioSet classifier (singleton pattern)"""
+    global _audio_event_model
+    if _audio_event_model is None:
+        try:
+            from transformers import pipeline
+            _audio_event_model = pipeline(
+                "audio-classification",
+                model="MIT/ast-finetuned-audioset-10-10-0.4593",
+                device="cpu",
+            )
+        except ImportError:
+            raise HTTPException(
+                status_code=500,
+                detail="transformers not installed. Run: pip install transformers"
+            )
+    return _audio_event_model
+
+
+@app.post("/audio/events", response_model=AudioEventsResponse)
+async def audio_events(request: AudioEventsRequest) -> AudioEventsResponse:
+    """
+    Detect non-speech audio events (laughter, applause, music, crowd noise).
+    
+    Slides a window over the audio, classifies each window against AudioSet and keeps
+    the best score per event label. Consecutive windows with the same label are merged
+    into one event carrying the max confidence.
+    """
+    media_path = request.mediaPath
+    
+    if not os.path.isabs(media_path):
+        raise HTTPException(status_code=400, detail="Path must be absolute")
+    
+    if not os.path.exists(media_path):
+        raise HTTPException(status_code=404, detail=f"File not found: {media_path}")
+    
+    if request.windowSec <= 0 or request.hopSec <= 0:
+        raise HTTPException(status_code=400, detail="windowSec and hopSec must be positive")
+    
+    try:
+        import numpy as np
+        from scipy.io import wavfile
+        
+        try:
+            sample_rate, samples = wavfile.read(media_path)
+        except ValueError as e:
+            raise HTTPException(status_code=400, detail=f"Expected a WAV file: {e}")
+        
+        # Mono float32 in [-1, 1]
+        if samples.ndim > 1:
+            samples = samples.mean(axis=1)
+        if np.issubdtype(samples.dtype, np.integer):
+            samples = samples.astype(np.float32) / np.iinfo(samples.dtype).max
+        else:
+            samples = samples.astype(np.float32)
+        
+        classifier = get_audio_event_model()
+        window = max(1, int(request.windowSec * sample_rate))
+        hop = max(1, int(request.hopSec * sample_rate))
+        
+        events: List[AudioEvent] = []
+        loudness: List[LoudnessWindow] = []
+        open_events: Dict[str, AudioEvent] = {}
+        for offset in range(0, max(1, len(samples) - window + hop), hop):
+            chunk = samples[offset:offset + window]
+            if len(chunk) == 0:
+                break
+            start = offset / sample_rate
+            end = min(len(samples), offset + window) / sample_rate
+            
+            rms = float(np.sqrt(np.mean(np.square(chunk))))
+            rms_db = 20.0 * np.log10(rms) if rms > 1e-5 else -100.0
+            loudness.append(LoudnessWindow(start=start, end=end, rms_db=float(rms_db)))
+            
+            predictions = classifier({"raw": chunk, "sampling_rate": sample_rate}, top_k=20)
+            scores = {}
+            for label, classes in AUDIO_EVENT_CLASSES.items():
+                best = max((p["score"] for p in predictions if p["label"] in classes), default=0.0)
+                if best >= request.minConfidence:
+                    scores[label] = float(best)
+            
+            # Close events that didn't continue into this window
+            for label in list(open_events.keys()):
+                if label not in scores:
+                    events.append(open_events.pop(label))
+            
+            for label, score in scores.items():
+                current = open_events.get(label)
+                if current is not None and start <= current.end:
+                    current.end = end
+                    current.confidence = max(current.confidence, score)
+                else:
+                    if current is not None:
+                        events.append(current)
+                    open_events[label] = AudioEvent(start=start, end=end, label=label, confidence=score)
+        
+        events.extend(open_events.values())
+        events.sort(key=lambda e: (e.start, e.label))
+        return AudioEventsResponse(events=events, loudness=loudness)
+        
+    except HTTPException:
+        raise
+    except ImportError as e:
+        raise HTTPException(status_code=500, detail=f"Audio event detection dependency missing: {e}")
+    except Exception as e:
+        raise HTTPException(status_code=500, detail=f"Audio event detection failed: {str(e)}")
+
+
 class FaceBbox(BaseModel):
     x: float
     y: float
@@ -138,36 +336,158 @@ class FaceBbox(BaseModel):
     height: float
 
 
+class VisionTag(BaseModel):
+    label: str
+    kind: str  # "scene" | "object" | "action"
+    confidence: float
+
+
 class VisionSegment(BaseModel):
     start: float
     end: float
-    has_face: bool
+    # Analyzer outputs are None when that analyzer was disabled for the request
+    has_face: Optional[bool] = None
     face_bbox: Optional[FaceBbox] = None
-    blur_score: float
-    motion_score: float
+    blur_score: Optional[float] = None
+    motion_score: Optional[float] = None
+    shot_type: Optional[str] = None  # "wide" | "medium" | "close_up"
+    ocr_text: Optional[str] = None  # Visible on-screen text (newline-separated lines)
     tags: List[str]
+    structured_tags: List[VisionTag] = []  # Tags with kind + confidence (superset of tags)
+
+
+class VisionAnalyzers(BaseModel):
+    blur: bool = True
+    motion: bool = True
+    faces: bool = True
+    tags: bool = True
+    shot_type: bool = True  # Requires faces
+    ocr: bool = True  # Requires pytesseract + tesseract binary; skipped if unavailable
+    objects: bool = True  # CLIP zero-shot object/action tags
 
 
 class VisionAnalyzeRequest(BaseModel):
     mediaPath: str
+    sampleIntervalSec: float = 0.5  # Seconds between analyzed frames
+    maxWidth: Optional[int] = None  # Downscale frames to this width before analysis
+    ocrIntervalSec: float = 2.0  # Seconds between OCR passes (OCR is slow)
+    tagIntervalSec: float = 2.0  # Seconds between object/action tagging passes
+    analyzers: VisionAnalyzers = VisionAnalyzers()
 
 
 class VisionAnalyzeResponse(BaseModel):
     segments: List[VisionSegment]
 
 
+OBJECT_LABELS = [
+    "person", "dog", "cat", "car", "bicycle", "food", "drink", "laptop", "phone",
+    "building", "tree", "water", "beach", "mountain", "sky", "screen", "whiteboard", "crowd",
+]
+ACTION_LABELS = [
+    "talking", "walking", "running", "cooking", "eating", "dancing", "driving",
+    "typing", "presenting", "laughing", "playing sports", "sitting", "waving",
+]
+# Tags below this probability are never reported (the daemon applies its own threshold on top)
+ZERO_SHOT_MIN_CONFIDENCE = 0.05
+ZERO_SHOT_TOP_K = 3
+
+_zero_shot_text_features = None
+
+def zero_shot_tags(frame_bgr) -> List[VisionTag]:
+    """
+    Tag a frame with objects and actions using CLIP zero-shot classification.
+    Probabilities are softmaxed within each label group, so confidences are relative to
+    the vocabulary. Returns an empty list if CLIP isn't available.
+    """
+    global _zero_shot_text_features
+    try:
+        import cv2
+        import torch
+        import open_clip
+        from PIL import Image
+        
+        model, preprocess = get_vision_model()
+        if _zero_shot_text_features is None:
+            tokenizer = open_clip.get_tokenizer('ViT-B-32')
+            features = {}
+            for kind, labels, template in (
+                ("object", OBJECT_LABELS, "a photo of a {}"),
+                ("action", ACTION_LABELS, "a photo of a person {}"),
+            ):
+                with torch.no_grad():
+                    text = model.encode_text(tokenizer([template.format(l) for l in labels]))
+                    features[kind] = (labels, text / text.norm(dim=-1, keepdim=True))
+            _zero_shot_text_features = features
+        
+        pil_image = Image.fromarray(cv2.cvtColor(frame_bgr, cv2.COLOR_BGR2RGB))
+        with torch.no_grad():
+            image = model.encode_image(preprocess(pil_image).unsqueeze(0))
+            image = image / image.norm(dim=-1, keepdim=True)
+        
+        tags = []
+        for kind, (labels, text_features) in _zero_shot_text_features.items():
+            probs = (100.0 * image @ text_features.T).softmax(dim=-1).squeeze(0)
+            top = torch.topk(probs, k=min(ZERO_SHOT_TOP_K, len(labels)))
+            for prob, idx in zip(top.values.tolist(), top.indices.tolist()):
+                if prob >= ZERO_SHOT_MIN_CONFIDENCE:
+                    tags.append(VisionTag(label=labels[idx], kind=kind, confidence=float(prob)))
+        return tags
+    except (ImportError, HTTPException) as e:
+        print(f"[VISION] Zero-shot tagging unavailable: {e}")
+        return []
+
+
+def extract_frame_text(ocr_engine, gray) -> Optional[str]:
+    """
+    OCR a grayscale frame and keep only plausible text lines
+    (at least 3 characters with some alphanumerics), dropping tesseract noise.
+    """
+    try:
+        raw = ocr_engine.image_to_string(gray)
+    except Exception as e:
+        print(f"[VISION] OCR failed: {e}")
+        return None
+    lines = []
+    for line in raw.splitlines():
+        line = " ".join(line.split())
+        if len(line) >= 3 and sum(c.isalnum() for c in line) >= max(3, len(line) // 2):
+            lines.append(line)
+    return "\n".join(lines) if lines else None
+
+
+def classify_shot_type(faces, frame_height: int) -> str:
+    """
+    Heuristic framing classifier based on the largest detected face.
+    No face usually means an establishing shot or b-roll, so it counts as wide.
+    """
+    if len(faces) == 0 or frame_height <= 0:
+        return "wide"
+    largest = max(float(h) for (_, _, _, h) in faces)
+    ratio = largest / frame_height
+    if ratio >= 0.3:
+        return "close_up"
+    if ratio >= 0.12:
+        return "medium"
+    return "wide"
+
+
 @app.post("/vision/analyze", response_model=VisionAnalyzeResponse)
 async def analyze_vision(request: VisionAnalyzeRequest) -> VisionAnalyzeResponse:
     """
     Analyze video for faces, blur, motion, and basic scene tags.
     
     Args:
-        request: Contains mediaPath (local file path)
+        request: Contains mediaPath (local file path), sampling interval,
+                 optional downscale width, and which analyzers to run
     
     Returns:
         VisionAnalyzeResponse with segments containing vision analysis results
     """
     media_path = request.mediaPath
+    analyzers = request.analyzers
+    
+    if request.sampleIntervalSec <= 0:
+        raise HTTPException(status_code=400, detail="sampleIntervalSec must be positive")
     
     # Validate path is local
     if not os.path.isabs(media_path):
@@ -189,67 +509,127 @@ async def analyze_vision(request: VisionAnalyzeRequest) -> VisionAnalyzeResponse
         frame_count = int(cap.get(cv2.CAP_PROP_FRAME_COUNT))
         duration = frame_count / fps if fps > 0 else 0
         
-        # Load face cascade
-        face_cascade = cv2.CascadeClassifier(cv2.data.haarcascades + 'haarcascade_frontalface_default.xml')
+        # Load face cascade (only if face detection or shot typing is enabled)
+        face_cascade = None
+        if analyzers.faces or analyzers.shot_type:
+            face_cascade = cv2.CascadeClassifier(cv2.data.haarcascades + 'haarcascade_frontalface_default.xml')
+        
+        # OCR engine (optional dependency)
+        ocr_engine = None
+        if analyzers.ocr:
+            try:
+                import pytesseract
+                ocr_engine = pytesseract
+            except ImportError:
+                print("[VISION] pytesseract not installed, skipping OCR")
+        last_ocr_time = None
+        last_tag_time = None
         
         segments = []
         frame_idx = 0
         prev_gray = None
-        sample_rate = max(1, int(fps / 2))  # Sample every 0.5 seconds
+        sample_rate = max(1, int(round(fps * request.sampleIntervalSec))) if fps > 0 else 1
         
         while True:
+            # grab() skips decoding for frames we don't analyze
+            if frame_idx % sample_rate != 0:
+                if not cap.grab():
+                    break
+                frame_idx += 1
+                continue
+            
             ret, frame = cap.read()
             if not ret:
                 break
             
-            if frame_idx % sample_rate != 0:
-                frame_idx += 1
-                continue
+            # Downscale before analysis if requested
+            if request.maxWidth and frame.shape[1] > request.maxWidth:
+                scale = request.maxWidth / frame.shape[1]
+                frame = cv2.resize(frame, (request.maxWidth, max(1, int(frame.shape[0] * scale))), interpolation=cv2.INTER_AREA)
             
             # Convert to grayscale for processing
             gray = cv2.cvtColor(frame, cv2.COLOR_BGR2GRAY)
             
             # Face detection
-            faces = face_cascade.detectMultiScale(gray, 1.1, 4)
-            has_face = len(faces) > 0
+            has_face = None
             face_bbox = None
-            if has_face and len(faces) > 0:
-                # Use the first detected face
-                x, y, w, h = faces[0]
-                face_bbox = FaceBbox(x=float(x), y=float(y), width=float(w), height=float(h))
+            if face_cascade is not None:
+                faces = face_cascade.detectMultiScale(gray, 1.1, 4)
+                has_face = len(faces) > 0
+                if has_face:
+                    # Use the first detected face
+                    x, y, w, h = faces[0]
+                    face_bbox = FaceBbox(x=float(x), y=float(y), width=float(w), height=float(h))
+            
+            # Shot type from the largest face's height relative to the frame
+            shot_type = None
+            if analyzers.shot_type and face_cascade is not None:
+                shot_type = classify_shot_type(faces, gray.shape[0])
+            
+            # Don't report faces if only shot typing asked for detection
+            if not analyzers.faces:
+                has_face = None
+                face_bbox = None
             
             # Blur detection (Laplacian variance)
-            laplacian_var = cv2.Laplacian(gray, cv2.CV_64F).var()
-            blur_score = float(laplacian_var)  # Higher = less blur
+            blur_score = None
+            if analyzers.blur:
+                laplacian_var = cv2.Laplacian(gray, cv2.CV_64F).var()
+                blur_score = float(laplacian_var)  # Higher = less blur
             
             # Motion estimation (frame difference)
-            motion_score = 0.0
-            if prev_gray is not None:
-                diff = cv2.absdiff(gray, prev_gray)
-                motion_score = float(np.mean(diff))
-            prev_gray = gray.copy()
+            motion_score = None
+            if analyzers.motion:
+                motion_score = 0.0
+                if prev_gray is not None:
+                    diff = cv2.absdiff(gray, prev_gray)
+                    motion_score = float(np.mean(diff))
+                prev_gray = gray.copy()
             
             # Basic scene tags (heuristics)
             tags = []
-            # Brightness-based day/night detection
-            mean_brightness = np.mean(gray)
-            if mean_brightness > 127:
-                tags.append("day")
-            else:
-                tags.append("night")
+            structured_tags = []
+            if analyzers.tags:
+                # Brightness-based day/night detection
+                # Confidence grows with distance from the decision boundary
+                mean_brightness = float(np.mean(gray))
+                brightness_conf = min(1.0, 0.5 + abs(mean_brightness - 127) / 127)
+                if mean_brightness > 127:
+                    tags.append("day")
+                    structured_tags.append(VisionTag(label="day", kind="scene", confidence=brightness_conf))
+                else:
+                    tags.append("night")
+                    structured_tags.append(VisionTag(label="night", kind="scene", confidence=brightness_conf))
+                
+                # Simple indoor/outdoor heuristic (can be improved)
+                # For now, use edge density as proxy
+                edges = cv2.Canny(gray, 50, 150)
+                edge_density = float(np.sum(edges > 0) / (frame.shape[0] * frame.shape[1]))
+                edge_conf = min(1.0, 0.5 + abs(edge_density - 0.1) * 5)
+                if edge_density > 0.1:
+                    tags.append("outdoors")
+                    structured_tags.append(VisionTag(label="outdoors", kind="scene", confidence=edge_conf))
+                else:
+                    tags.append("indoors")
+                    structured_tags.append(VisionTag(label="indoors", kind="scene", confidence=edge_conf))
             
-            # Simple indoor/outdoor heuristic (can be improved)
-            # For now, use edge density as proxy
-            edges = cv2.Canny(gray, 50, 150)
-            edge_density = np.sum(edges > 0) / (frame.shape[0] * frame.shape[1])
-            if edge_density > 0.1:
-                tags.append("outdoors")
-            else:
-                tags.append("indoors")
+            # Object/action tags via CLIP zero-shot, at most once per tagIntervalSec
+            frame_time = frame_idx / fps if fps > 0 else 0.0
+            if analyzers.objects and (last_tag_time is None or frame_time - last_tag_time >= request.tagIntervalSec):
+                last_tag_time = frame_time
+                for tag in zero_shot_tags(frame):
+                    structured_tags.append(tag)
+                    tags.append(tag.label)
             
             # Calculate time for this frame
             timestamp = frame_idx / fps if fps > 0 else 0.0
-            segment_duration = sample_rate / fps if fps > 0 else 0.5
+            
+            # On-screen text, at most once per ocrIntervalSec
+            ocr_text = None
+            if ocr_engine is not None and (last_ocr_time is None or timestamp - last_ocr_time >= request.ocrIntervalSec):
+                last_ocr_time = timestamp
+                ocr_text = extract_frame_text(ocr_engine, gray)
+            segment_duration = sample_rate / fps if fps > 0 else request.sampleIntervalSec
             
             segments.append(VisionSegment(
                 start=timestamp,
@@ -258,7 +638,10 @@ async def analyze_vision(request: VisionAnalyzeRequest) -> VisionAnalyzeResponse
                 face_bbox=face_bbox,
                 blur_score=blur_score,
                 motion_score=motion_score,
-                tags=tags
+                shot_type=shot_type,
+                ocr_text=ocr_text,
+                tags=tags,
+                structured_tags=structured_tags
             ))
             
             frame_idx += 1
@@ -276,6 +659,412 @@ async def analyze_vision(request: VisionAnalyzeRequest) -> VisionAnalyzeResponse
         raise HTTPException(status_code=500, detail=f"Vision analysis failed: {str(e)}")
 
 
+class RedactionWindow(BaseModel):
+    start: float  # Source seconds
+    end: float
+
+
+class RedactionDetectRequest(BaseModel):
+    mediaPath: str
+    windows: List[RedactionWindow]  # Time ranges to scan (one per selected segment)
+    kinds: List[str] = ["face", "plate"]
+    sampleIntervalSec: float = 0.5
+
+
+class RedactionBox(BaseModel):
+    t: float  # Source seconds
+    x: float  # Normalized to the frame (0-1)
+    y: float
+    w: float
+    h: float
+
+
+class RedactionSubject(BaseModel):
+    kind: str  # "face" or "plate"
+    window: int  # Index into the request's windows
+    boxes: List[RedactionBox]
+
+
+class RedactionDetectResponse(BaseModel):
+    subjects: List[RedactionSubject]
+
+
+REDACTION_CASCADES = {
+    "face": "haarcascade_frontalface_default.xml",
+    "plate": "haarcascade_russian_plate_number.xml",
+}
+
+
+def box_iou(a, b) -> float:
+    """Intersection over union of two (x, y, w, h) boxes"""
+    ix = max(0.0, min(a[0] + a[2], b[0] + b[2]) - max(a[0], b[0]))
+    iy = max(0.0, min(a[1] + a[3], b[1] + b[3]) - max(a[1], b[1]))
+    inter = ix * iy
+    union = a[2] * a[3] + b[2] * b[3] - inter
+    return inter / union if union > 0 else 0.0
+
+
+@app.post("/redaction/detect", response_model=RedactionDetectResponse)
+async def detect_redactions(request: RedactionDetectRequest) -> RedactionDetectResponse:
+    """
+    Find faces and license plates to blur. Detections in consecutive sampled frames that overlap
+    are linked into one subject, so each person or plate can be toggled as a whole.
+    """
+    media_path = request.mediaPath
+
+    if request.sampleIntervalSec <= 0:
+        raise HTTPException(status_code=400, detail="sampleIntervalSec must be positive")
+    unknown = [k for k in request.kinds if k not in REDACTION_CASCADES]
+    if unknown:
+        raise HTTPException(status_code=400, detail=f"Unknown redaction kinds: {unknown}")
+    if not os.path.isabs(media_path):
+        raise HTTPException(status_code=400, detail="Path must be absolute")
+    if not os.path.exists(media_path):
+        raise HTTPException(status_code=404, detail=f"File not found: {media_path}")
+
+    try:
+        import cv2
+
+        cap = cv2.VideoCapture(media_path)
+        if not cap.isOpened():
+            raise HTTPException(status_code=500, detail="Failed to open video file")
+
+        cascades = {
+            kind: cv2.CascadeClassifier(cv2.data.haarcascades + REDACTION_CASCADES[kind])
+            for kind in request.kinds
+        }
+        # A track ends when its subject isn't seen for this long
+        max_gap = request.sampleIntervalSec * 3
+
+        subjects = []
+        for window_idx, window in enumerate(request.windows):
+            # Open tracks: kind -> list of {"boxes": [...], "last": (x, y, w, h), "last_t": t}
+            tracks = {kind: [] for kind in cascades}
+            t = window.start
+            while t <= window.end:
+                cap.set(cv2.CAP_PROP_POS_MSEC, t * 1000.0)
+                ret, frame = cap.read()
+                if not ret:
+                    break
+                height, width = frame.shape[:2]
+                gray = cv2.cvtColor(frame, cv2.COLOR_BGR2GRAY)
+
+                for kind, cascade in cascades.items():
+                    min_size = (24, 24) if kind == "face" else (40, 12)
+                    detections = cascade.detectMultiScale(gray, 1.1, 4, minSize=min_size)
+                    open_tracks = [tr for tr in tracks[kind] if t - tr["last_t"] <= max_gap]
+                    for (x, y, w, h) in detections:
+                        box = (x / width, y / height, w / width, h / height)
+                        best = max(open_tracks, key=lambda tr: box_iou(tr["last"], box), default=None)
+                        if best is None or box_iou(best["last"], box) < 0.2:
+                            best = {"boxes": [], "last": box, "last_t": t}
+                            tracks[kind].append(best)
+                        else:
+                            open_tracks.remove(best)
+                        best["boxes"].append(RedactionBox(t=t, x=box[0], y=box[1], w=box[2], h=box[3]))
+                        best["last"] = box
+                        best["last_t"] = t
+
+                t += request.sampleIntervalSec
+
+            for kind, kind_tracks in tracks.items():
+                for tr in kind_tracks:
+                    subjects.append(RedactionSubject(kind=kind, window=window_idx, boxes=tr["boxes"]))
+
+        cap.release()
+        return RedactionDetectResponse(subjects=subjects)
+
+    except ImportError:
+        raise HTTPException(
+            status_code=500,
+            detail="opencv-python not installed. Run: pip install opencv-python numpy"
+        )
+    except HTTPException:
+        raise
+    except Exception as e:
+        raise HTTPException(status_code=500, detail=f"Redaction detection failed: {str(e)}")
+
+
+class MulticamSyncRequest(BaseModel):
+    audioPaths: List[str]  # Mono WAV per angle
+    referenceIndex: int = 0
+    maxOffsetSec: float = 600.0  # Largest start difference searched
+
+
+class MulticamOffset(BaseModel):
+    offsetSec: float  # Where this clip starts relative to the reference's start
+    confidence: float  # 0-1: how much the best alignment stands out from the next best
+
+
+class MulticamSyncResponse(BaseModel):
+    offsets: List[MulticamOffset]  # One per audio path (the reference gets 0)
+
+
+MULTICAM_ENVELOPE_RATE = 1000  # Correlation resolution (Hz)
+
+
+def load_sync_envelope(path: str):
+    """Onset strength of a WAV at MULTICAM_ENVELOPE_RATE: rises of the log loudness envelope,
+    which line up across microphones even when their gain and frequency response differ"""
+    import numpy as np
+    from scipy.io import wavfile
+
+    sample_rate, samples = wavfile.read(path)
+    if samples.ndim > 1:
+        samples = samples.mean(axis=1)
+    samples = np.abs(samples.astype(np.float32))
+    hop = max(1, sample_rate // MULTICAM_ENVELOPE_RATE)
+    frames = len(samples) // hop
+    if frames < 2:
+        raise HTTPException(status_code=400, detail=f"Audio too short to sync: {path}")
+    envelope = np.log1p(samples[:frames * hop].reshape(frames, hop).mean(axis=1))
+    onsets = np.maximum(np.diff(envelope, prepend=envelope[0]), 0.0)
+    std = onsets.std()
+    return (onsets - onsets.mean()) / std if std > 0 else onsets - onsets.mean()
+
+
+@app.post("/multicam/sync", response_model=MulticamSyncResponse)
+async def multicam_sync(request: MulticamSyncRequest) -> MulticamSyncResponse:
+    """
+    Align recordings of the same event by cross-correlating their audio against a reference.
+    Each offset is the lag of the strongest correlation peak; confidence compares it with the
+    strongest peak elsewhere, so recordings of different events come out near 0.
+    """
+    if not 0 <= request.referenceIndex < len(request.audioPaths):
+        raise HTTPException(status_code=400, detail="referenceIndex out of range")
+    for path in request.audioPaths:
+        if not os.path.isabs(path):
+            raise HTTPException(status_code=400, detail="Path must be absolute")
+        if not os.path.exists(path):
+            raise HTTPException(status_code=404, detail=f"File not found: {path}")
+
+    try:
+        import numpy as np
+
+        envelopes = [load_sync_envelope(path) for path in request.audioPaths]
+        reference = envelopes[request.referenceIndex]
+        max_lag = int(request.maxOffsetSec * MULTICAM_ENVELOPE_RATE)
+        # Peaks closer than this to the best one belong to it
+        exclusion = MULTICAM_ENVELOPE_RATE // 10
+
+        offsets = []
+        for idx, envelope in enumerate(envelopes):
+            if idx == request.referenceIndex:
+                offsets.append(MulticamOffset(offsetSec=0.0, confidence=1.0))
+                continue
+            n = 1 << int(np.ceil(np.log2(len(reference) + len(envelope))))
+            cc = np.fft.irfft(np.fft.rfft(reference, n) * np.conj(np.fft.rfft(envelope, n)), n)
+            # cc[k]: the clip's frame i lines up with the reference's frame i + k
+            lags = np.arange(-(len(envelope) - 1), len(reference))
+            values = np.concatenate((cc[n - (len(envelope) - 1):], cc[:len(reference)]))
+            in_range = np.abs(lags) <= max_lag
+            lags, values = lags[in_range], values[in_range]
+
+            best = int(np.argmax(values))
+            peak = float(values[best])
+            others = np.abs(lags - lags[best]) > exclusion
+            runner_up = float(values[others].max()) if others.any() else 0.0
+            confidence = 1.0 - max(runner_up, 0.0) / peak if peak > 0 else 0.0
+            offsets.append(MulticamOffset(
+                offsetSec=float(lags[best]) / MULTICAM_ENVELOPE_RATE,
+                confidence=float(np.clip(confidence, 0.0, 1.0)),
+            ))
+
+        return MulticamSyncResponse(offsets=offsets)
+
+    except HTTPException:
+        raise
+    except ImportError as e:
+        raise HTTPException(status_code=500, detail=f"Multicam sync dependency missing: {e}")
+    except Exception as e:
+        raise HTTPException(status_code=500, detail=f"Multicam sync failed: {str(e)}")
+
+
+class StillSubjectsRequest(BaseModel):
+    imagePath: str
+    maxSubjects: int = 3
+
+
+class StillSubject(BaseModel):
+    kind: str  # "face", or "detail" when no face was found
+    x: float  # Normalized to the image (0-1)
+    y: float
+    w: float
+    h: float
+    score: float  # Share of the image's detected faces/detail this box holds
+
+
+class StillSubjectsResponse(BaseModel):
+    width: int
+    height: int
+    subjects: List[StillSubject]  # Strongest first
+
+
+@app.post("/stills/subjects", response_model=StillSubjectsResponse)
+async def still_subjects(request: StillSubjectsRequest) -> StillSubjectsResponse:
+    """
+    Find what a photo's Ken Burns motion should move towards: its largest faces, or when there
+    are none, the box around its most detailed area (edges concentrate on the subject, while
+    skies, walls and out-of-focus backgrounds stay smooth).
+    """
+    image_path = request.imagePath
+
+    if not os.path.isabs(image_path):
+        raise HTTPException(status_code=400, detail="Path must be absolute")
+    if not os.path.exists(image_path):
+        raise HTTPException(status_code=404, detail=f"File not found: {image_path}")
+
+    try:
+        import cv2
+        import numpy as np
+
+        image = cv2.imread(image_path)
+        if image is None:
+            raise HTTPException(status_code=400, detail="Failed to read image file")
+        height, width = image.shape[:2]
+        gray = cv2.cvtColor(image, cv2.COLOR_BGR2GRAY)
+
+        cascade = cv2.CascadeClassifier(cv2.data.haarcascades + 'haarcascade_frontalface_default.xml')
+        min_side = max(24, min(width, height) // 40)
+        faces = sorted(
+            cascade.detectMultiScale(gray, 1.1, 5, minSize=(min_side, min_side)),
+            key=lambda f: f[2] * f[3],
+            reverse=True,
+        )[:max(1, request.maxSubjects)]
+        if len(faces) > 0:
+            total = float(sum(w * h for (_, _, w, h) in faces))
+            subjects = [
+                StillSubject(kind="face", x=x / width, y=y / height, w=w / width, h=h / height,
+                             score=float(w * h) / total)
+                for (x, y, w, h) in faces
+            ]
+            return StillSubjectsResponse(width=width, height=height, subjects=subjects)
+
+        # Detail map on a small copy: blurred gradient magnitude, keeping its strongest tenth
+        scale = 256.0 / max(width, height)
+        small = cv2.resize(gray, (max(1, int(width * scale)), max(1, int(height * scale))))
+        gx = cv2.Sobel(small, cv2.CV_32F, 1, 0)
+        gy = cv2.Sobel(small, cv2.CV_32F, 0, 1)
+        detail = cv2.GaussianBlur(cv2.magnitude(gx, gy), (0, 0), 4)
+        threshold = np.percentile(detail, 90)
+        ys, xs = np.nonzero(detail >= threshold)
+        if threshold <= 0 or len(xs) == 0:
+            return StillSubjectsResponse(width=width, height=height, subjects=[])
+        sh, sw = small.shape[:2]
+        x0, x1 = np.percentile(xs, [5, 95])
+        y0, y1 = np.percentile(ys, [5, 95])
+        subject = StillSubject(
+            kind="detail",
+            x=float(x0) / sw,
+            y=float(y0) / sh,
+            w=max(float(x1 - x0), 1.0) / sw,
+            h=max(float(y1 - y0), 1.0) / sh,
+            score=1.0,
+        )
+        return StillSubjectsResponse(width=width, height=height, subjects=[subject])
+
+    except ImportError:
+        raise HTTPException(
+            status_code=500,
+            detail="opencv-python not installed. Run: pip install opencv-python numpy"
+        )
+    except HTTPException:
+        raise
+    except Exception as e:
+        raise HTTPException(status_code=500, detail=f"Still subject detection failed: {str(e)}")
+
+
+class WindowTitlesRequest(BaseModel):
+    mediaPath: str
+    times: List[float]  # Source seconds to read a title at (one per segment)
+    topFraction: float = 0.12  # Share of the frame height holding the menu and title bars
+
+
+class WindowTitle(BaseModel):
+    t: float
+    title: Optional[str] = None  # None when no plausible title was read
+
+
+class WindowTitlesResponse(BaseModel):
+    titles: List[WindowTitle]
+
+
+# Menu bar entries; a line made of these is the menu bar, not a window title
+MENU_WORDS = {"file", "edit", "view", "window", "help", "go", "tools", "history", "bookmarks", "format", "insert"}
+TITLE_SEPARATORS = (" — ", " – ", " - ", " | ")
+
+
+def pick_window_title(lines: List[str]) -> Optional[str]:
+    """
+    Choose the window title among the text lines of a screen's top strip: menu bars are
+    dropped, then a line with an app-style separator ("main.rs — Visual Studio Code") wins
+    over the longest remaining line.
+    """
+    candidates = []
+    for line in lines:
+        words = [w.lower().strip(".:") for w in line.split()]
+        if sum(w in MENU_WORDS for w in words) >= 3:
+            continue
+        candidates.append(line)
+    if not candidates:
+        return None
+    separated = [line for line in candidates if any(sep in line for sep in TITLE_SEPARATORS)]
+    return max(separated or candidates, key=len)
+
+
+@app.post("/screen/window_titles", response_model=WindowTitlesResponse)
+async def window_titles(request: WindowTitlesRequest) -> WindowTitlesResponse:
+    """
+    OCR the top strip of a screen recording at the given times and read the focused
+    application's window title from it.
+    """
+    media_path = request.mediaPath
+
+    if not 0 < request.topFraction <= 1:
+        raise HTTPException(status_code=400, detail="topFraction must be in (0, 1]")
+    if not os.path.isabs(media_path):
+        raise HTTPException(status_code=400, detail="Path must be absolute")
+    if not os.path.exists(media_path):
+        raise HTTPException(status_code=404, detail=f"File not found: {media_path}")
+
+    try:
+        import cv2
+        import pytesseract
+
+        cap = cv2.VideoCapture(media_path)
+        if not cap.isOpened():
+            raise HTTPException(status_code=500, detail="Failed to open video file")
+
+        titles = []
+        for t in request.times:
+            cap.set(cv2.CAP_PROP_POS_MSEC, t * 1000.0)
+            ret, frame = cap.read()
+            if not ret:
+                titles.append(WindowTitle(t=t))
+                continue
+            height = frame.shape[0]
+            strip = frame[:max(1, int(height * request.topFraction))]
+            # Title bar text is small; upscale so tesseract can read it
+            gray = cv2.resize(cv2.cvtColor(strip, cv2.COLOR_BGR2GRAY), None, fx=2, fy=2,
+                              interpolation=cv2.INTER_CUBIC)
+            text = extract_frame_text(pytesseract, gray)
+            lines = text.splitlines() if text else []
+            titles.append(WindowTitle(t=t, title=pick_window_title(lines)))
+
+        cap.release()
+        return WindowTitlesResponse(titles=titles)
+
+    except ImportError:
+        raise HTTPException(
+            status_code=500,
+            detail="opencv-python or pytesseract not installed. Run: pip install opencv-python pytesseract"
+        )
+    except HTTPException:
+        raise
+    except Exception as e:
+        raise HTTPException(status_code=500, detail=f"Window title OCR failed: {str(e)}")
+
+
 class EmbeddingRequest(BaseModel):
     text: str
 
@@ -335,6 +1124,79 @@ async def embeddings_semantic(request: EmbeddingRequest) -> EmbeddingResponse:
     return await embeddings_text(request)
 
 
+class RerankDocument(BaseModel):
+    id: int
+    text: str
+
+
+class RerankRequest(BaseModel):
+    query: str
+    documents: List[RerankDocument]
+
+
+class RerankScore(BaseModel):
+    id: int
+    score: float  # relevance probability in [0, 1]
+
+
+class RerankResponse(BaseModel):
+    model: str
+    scores: List[RerankScore]
+
+
+# Global cross-encoder cache (singleton pattern)
+_cross_encoder = None
+CROSS_ENCODER_MODEL = 'cross-encoder/ms-marco-MiniLM-L-6-v2'
+
+def get_cross_encoder():
+    """Get or load the cross-encoder re-ranking model (singleton pattern)"""
+    global _cross_encoder
+    if _cross_encoder is None:
+        try:
+            from sentence_transformers import CrossEncoder
+            _cross_encoder = CrossEncoder(CROSS_ENCODER_MODEL)
+        except ImportError:
+            raise HTTPException(
+                status_code=500,
+                detail="sentence-transformers not installed. Run: pip install sentence-transformers"
+            )
+    return _cross_encoder
+
+
+@app.post("/rerank", response_model=RerankResponse)
+async def rerank(request: RerankRequest) -> RerankResponse:
+    """
+    Score (query, document) pairs jointly with a cross-encoder. More precise than comparing
+    independently computed embeddings, so it is used to re-order the top retrieval candidates.
+    
+    Args:
+        request: Contains the query and documents (segment id + semantic text)
+    
+    Returns:
+        RerankResponse with a relevance score per document, in request order
+    """
+    if not request.documents:
+        return RerankResponse(model=CROSS_ENCODER_MODEL, scores=[])
+    try:
+        import numpy as np
+        
+        model = get_cross_encoder()
+        logits = model.predict([(request.query, doc.text) for doc in request.documents])
+        probabilities = 1.0 / (1.0 + np.exp(-np.asarray(logits, dtype=float)))
+        return RerankResponse(
+            model=CROSS_ENCODER_MODEL,
+            scores=[
+                RerankScore(id=doc.id, score=float(p))
+                for doc, p in zip(request.documents, probabilities)
+            ],
+        )
+        
+    except HTTPException:
+        raise
+    except Exception as e:
+        raise HTTPException(status_code=500, detail=f"Re-ranking failed: {str(e)}")
+
+
 class VisionEmbeddingRequest(BaseModel):
     media_path: str
     start_time: float  # Start time in seconds
@@ -437,6 +1299,40 @@ async def embeddings_vision(request: VisionEmbeddingRequest) -> EmbeddingRespons
         raise HTTPException(status_code=500, detail=f"Vision embedding generation failed: {str(e)}")
 
 
+@app.post("/embeddings/vision_text", response_model=EmbeddingResponse)
+async def embeddings_vision_text(request: EmbeddingRequest) -> EmbeddingResponse:
+    """
+    Embed a text query with CLIP ViT-B-32's text encoder, so it can be compared with
+    /embeddings/vision vectors (same 512-dimensional space).
+
+    Args:
+        request: Contains the query text
+
+    Returns:
+        EmbeddingResponse with embedding vector
+    """
+    try:
+        import open_clip
+        import torch
+
+        model, _ = get_vision_model()
+        tokens = open_clip.get_tokenizer('ViT-B-32')([request.text])
+        with torch.no_grad():
+            text_features = model.encode_text(tokens)
+            text_features = text_features / text_features.norm(dim=-1, keepdim=True)
+            embedding = text_features.squeeze(0).cpu().numpy().tolist()
+
+        return EmbeddingResponse(embedding=embedding)
+
+    except ImportError as e:
+        raise HTTPException(
+            status_code=500,
+            detail=f"Required library not installed: {str(e)}"
+        )
+    except Exception as e:
+        raise HTTPException(status_code=500, detail=f"Vision text embedding generation failed: {str(e)}")
+
+
 class ProfileFromReferencesRequest(BaseModel):
     referenceVideoPaths: List[str]
 
@@ -512,10 +1408,29 @@ class GenerateResponseRequest(BaseModel):
     event_type: str
 
 
+class LlmUsage(BaseModel):
+    model: str
+    prompt_tokens: int
+    completion_tokens: int
+
+
+def usage_from_completion(response) -> Optional[LlmUsage]:
+    """Token usage reported by an OpenAI chat completion, for the daemon's cost tracking"""
+    usage = getattr(response, "usage", None)
+    if usage is None:
+        return None
+    return LlmUsage(
+        model=getattr(response, "model", None) or "unknown",
+        prompt_tokens=usage.prompt_tokens or 0,
+        completion_tokens=usage.completion_tokens or 0,
+    )
+
+
 class GenerateResponseResponse(BaseModel):
     message: str
     suggestions: List[dict]  # [{"label": str, "action": str, "confirm_token": Optional[str]}]
     questions: List[str]
+    usage: Optional[LlmUsage] = None
     # Note: next_action and confidence removed - control flow is deterministic
 
 
@@ -666,6 +1581,24 @@ Return JSON with: message (natural, conversational text), suggestions (array of
                     user_prompt_parts.append(f"  Example: 'The video starts with [first segment], then cuts to [second segment], followed by [third segment]...'")
                     user_prompt_parts.append(f"  Be specific about what's happening in each moment, not just technical details!")
         
+        # Locked clips/regions: propose edits around them instead of replacing them
+        if request.context and request.context.get("locked_content"):
+            locked = request.context.get("locked_content", {})
+            spans = locked.get("locked_spans_sec", [])
+            if spans:
+                span_text = ", ".join(f"{start:.1f}s-{end:.1f}s" for start, end in spans)
+                user_prompt_parts.append(f"- Locked timeline content (can't be moved or replaced): {span_text}")
+                user_prompt_parts.append(f"  Content can be added after {locked.get('safe_insert_sec', 0):.1f}s without shifting locked clips.")
+                user_prompt_parts.append(f"  If the user's request touches locked content, suggest alternatives around it or ask them to unlock it first.")
+        
+        # Project persona: tone and reply language chosen in the project's agent settings
+        if request.context and request.context.get("persona"):
+            persona = request.context.get("persona", {})
+            if persona.get("tone_guidance"):
+                user_prompt_parts.append(f"- Tone: {persona.get('tone')} ({persona.get('tone_guidance')})")
+            if persona.get("locale") and persona.get("locale") != "en":
+                user_prompt_parts.append(f"- Reply in the language for locale '{persona.get('locale')}', including suggestion labels and questions.")
+        
         user_prompt_parts.append("\nGenerate a natural, conversational response that acknowledges what the user said and explains what's happening or what you're doing.")
         
         user_prompt = "\n".join(user_prompt_parts)
@@ -715,6 +1648,7 @@ Return JSON with: message (natural, conversational text), suggestions (array of
             message=response_json.get("message", "I'm here to help!"),
             suggestions=suggestions,
             questions=response_json.get("questions", []),
+            usage=usage_from_completion(response),
         )
         
     except ImportError as e:
@@ -739,10 +1673,12 @@ class ParseIntentRequest(BaseModel):
 
 class ParseIntentResponse(BaseModel):
     user_intent: str
+    language: Optional[str] = None  # ISO 639-1 code of the user's message
     target_length_sec: Optional[int] = None
     vibe: Optional[str] = None
     constraints: Optional[dict] = None
     clarifying_questions: List[str] = []
+    usage: Optional[LlmUsage] = None
 
 
 @app.post("/orchestrator/parse_intent", response_model=ParseIntentResponse)
@@ -777,9 +1713,10 @@ Extract:
   - unused_only: true if user wants only unused clips
   - selected_clips: array of clip IDs if user specified specific clips
   - quality_threshold: minimum quality if mentioned
-- clarifying_questions: Array of questions to ask if intent is ambiguous
+- clarifying_questions: Array of questions to ask if intent is ambiguous (in the user's language)
+- language: ISO 639-1 code of the language the user wrote in (e.g. "en", "es", "ja")
 
-Return JSON with: user_intent, target_length_sec (optional), vibe (optional), constraints (optional), clarifying_questions (array)."""
+Return JSON with: user_intent, target_length_sec (optional), vibe (optional), constraints (optional), clarifying_questions (array), language."""
         
         user_prompt = f"User message: {request.user_message}"
         if request.conversation_history:
@@ -808,6 +1745,8 @@ Return JSON with: user_intent, target_length_sec (optional), vibe (optional), co
             vibe=response_json.get("vibe"),
             constraints=response_json.get("constraints"),
             clarifying_questions=response_json.get("clarifying_questions", []),
+            language=response_json.get("language"),
+            usage=usage_from_completion(response),
         )
         
     except ImportError:
@@ -823,6 +1762,452 @@ Return JSON with: user_intent, target_length_sec (optional), vibe (optional), co
         raise HTTPException(status_code=500, detail=f"Intent parsing failed: {str(e)}")
 
 
+class EditToolsRequest(BaseModel):
+    instruction: str
+    clips: List[dict]  # primary track in order: {clip_id, index, asset_id, in_sec, out_sec, source_duration_sec, speed, locked, in_working_set, summary}
+    conversation_history: Optional[List[dict]] = None
+
+
+class EditToolCall(BaseModel):
+    name: str
+    arguments: dict
+
+
+class EditToolsResponse(BaseModel):
+    message: str
+    tool_calls: List[EditToolCall] = []
+    usage: Optional[LlmUsage] = None
+
+
+# The only edits the model may make; the daemon validates every call before running it
+EDIT_TOOLS = [
+    {
+        "type": "function",
+        "function": {
+            "name": "trim_clip",
+            "description": "Change which part of its source a clip plays. Times are seconds in the source media.",
+            "parameters": {
+                "type": "object",
+                "properties": {
+                    "clip_id": {"type": "string"},
+                    "in_sec": {"type": "number", "description": "New source in point"},
+                    "out_sec": {"type": "number", "description": "New source out point, after in_sec"},
+                },
+                "required": ["clip_id", "in_sec", "out_sec"],
+            },
+        },
+    },
+    {
+        "type": "function",
+        "function": {
+            "name": "reorder_clip",
+            "description": "Move a clip to another place in the sequence.",
+            "parameters": {
+                "type": "object",
+                "properties": {
+                    "clip_id": {"type": "string"},
+                    "index": {"type": "integer", "description": "0-based position the clip should end up at"},
+                },
+                "required": ["clip_id", "index"],
+            },
+        },
+    },
+    {
+        "type": "function",
+        "function": {
+            "name": "delete_clip",
+            "description": "Remove a clip; the clips after it close the gap.",
+            "parameters": {
+                "type": "object",
+                "properties": {"clip_id": {"type": "string"}},
+                "required": ["clip_id"],
+            },
+        },
+    },
+    {
+        "type": "function",
+        "function": {
+            "name": "set_speed",
+            "description": "Play a clip faster or slower (1 is normal speed).",
+            "parameters": {
+                "type": "object",
+                "properties": {
+                    "clip_id": {"type": "string"},
+                    "speed": {"type": "number", "minimum": 0.25, "maximum": 4},
+                },
+                "required": ["clip_id", "speed"],
+            },
+        },
+    },
+]
+
+
+@app.post("/orchestrator/edit_tools", response_model=EditToolsResponse)
+async def edit_tools(request: EditToolsRequest) -> EditToolsResponse:
+    """
+    Turn an editing instruction into calls to the fine-grained edit tools.
+
+    Args:
+        request: Contains the instruction, the clips on the timeline and optional conversation_history
+
+    Returns:
+        EditToolsResponse with a short reply and the tool calls, in the order to run them
+    """
+    try:
+        from openai import OpenAI
+
+        api_key = os.getenv('OPENAI_API_KEY')
+        if not api_key:
+            raise HTTPException(
+                status_code=500,
+                detail="OPENAI_API_KEY not set. Please set it in your .env file or environment variables."
+            )
+        client = OpenAI(api_key=api_key)
+
+        system_prompt = """You are a video editing assistant making precise changes to an existing edit.
+Use only the provided tools, and only on the clips listed. Calls run in the order you make them, so
+indexes in later calls refer to the sequence after the earlier calls. Never touch clips marked locked.
+Clips marked "working set" are the ones the user has been talking about ("these clips"); unless the
+request names other clips, apply it to them.
+If the request is unclear or the tools can't do it, make no calls and say why.
+Reply with one or two sentences describing the change, in the user's language."""
+
+        clip_lines = [
+            f"{c.get('index')}. {c.get('clip_id')}: source {c.get('in_sec', 0):.2f}-{c.get('out_sec', 0):.2f}s "
+            f"of {c.get('source_duration_sec', 0):.2f}s, speed {c.get('speed', 1)}"
+            f"{', locked' if c.get('locked') else ''}"
+            f"{', working set' if c.get('in_working_set') else ''}"
+            f"{' - ' + c['summary'] if c.get('summary') else ''}"
+            for c in request.clips
+        ]
+        user_prompt = "Timeline:\n" + ("\n".join(clip_lines) or "(empty)")
+        if request.conversation_history:
+            user_prompt += "\n\nRecent conversation:"
+            for msg in request.conversation_history[-5:]:
+                user_prompt += f"\n{msg.get('role', 'unknown')}: {msg.get('content', '')}"
+        user_prompt += f"\n\nRequest: {request.instruction}"
+
+        response = client.chat.completions.create(
+            model="gpt-4o-mini",
+            messages=[
+                {"role": "system", "content": system_prompt},
+                {"role": "user", "content": user_prompt}
+            ],
+            tools=EDIT_TOOLS,
+            temperature=0.2,
+        )
+
+        choice = response.choices[0].message
+        tool_calls = []
+        for call in choice.tool_calls or []:
+            try:
+                arguments = json.loads(call.function.arguments or "{}")
+            except json.JSONDecodeError:
+                arguments = {}
+            tool_calls.append(EditToolCall(name=call.function.name, arguments=arguments))
+
+        return EditToolsResponse(
+            message=choice.content or "",
+            tool_calls=tool_calls,
+            usage=usage_from_completion(response),
+        )
+
+    except ImportError:
+        raise HTTPException(
+            status_code=500,
+            detail="OpenAI library not installed. Please install with: pip install openai"
+        )
+    except HTTPException:
+        raise
+    except Exception as e:
+        raise HTTPException(status_code=500, detail=f"Edit tool planning failed: {str(e)}")
+
+
+class TranslateRequest(BaseModel):
+    text: str
+    target_languages: List[str]  # ISO 639-1 codes
+
+
+class TranslateResponse(BaseModel):
+    source_language: Optional[str] = None
+    translations: Dict[str, str] = {}  # target language -> text (source language omitted)
+    usage: Optional[LlmUsage] = None
+
+
+@app.post("/translate", response_model=TranslateResponse)
+async def translate(request: TranslateRequest) -> TranslateResponse:
+    """
+    Detect the language of a short query and translate it into each target language,
+    so prompts can be matched against footage spoken in another language.
+    
+    Args:
+        request: Contains text and target_languages
+    
+    Returns:
+        TranslateResponse with the detected source language and a translation per target
+        language other than the source
+    """
+    try:
+        from openai import OpenAI
+        
+        api_key = os.getenv('OPENAI_API_KEY')
+        if not api_key:
+            raise HTTPException(
+                status_code=500,
+                detail="OPENAI_API_KEY not set. Please set it in your .env file or environment variables."
+            )
+        client = OpenAI(api_key=api_key)
+        
+        system_prompt = """You translate short video search queries. Detect the query's language and translate it into each requested target language, keeping it a natural search phrase (not a sentence about searching).
+
+Return JSON with: source_language (ISO 639-1 code), translations (object mapping each target language code to the translated query). Omit the source language from translations."""
+        
+        user_prompt = f"Query: {request.text}\nTarget languages: {', '.join(request.target_languages)}"
+        
+        response = client.chat.completions.create(
+            model="gpt-4o-mini",
+            messages=[
+                {"role": "system", "content": system_prompt},
+                {"role": "user", "content": user_prompt}
+            ],
+            response_format={"type": "json_object"},
+            temperature=0.0,
+        )
+        
+        response_json = json.loads(response.choices[0].message.content or "{}")
+        source_language = response_json.get("source_language")
+        translations = {
+            lang: text
+            for lang, text in (response_json.get("translations") or {}).items()
+            if lang in request.target_languages and lang != source_language and isinstance(text, str) and text.strip()
+        }
+        
+        return TranslateResponse(
+            source_language=source_language,
+            translations=translations,
+            usage=usage_from_completion(response),
+        )
+        
+    except ImportError:
+        raise HTTPException(
+            status_code=500,
+            detail="OpenAI library not installed. Please install with: pip install openai"
+        )
+    except HTTPException:
+        raise
+    except Exception as e:
+        raise HTTPException(status_code=500, detail=f"Translation failed: {str(e)}")
+
+
+class ExpandQueryRequest(BaseModel):
+    text: str
+    max_queries: int = 5
+
+
+class ExpandQueryResponse(BaseModel):
+    queries: List[str] = []  # paraphrases / aspect queries, original excluded
+    usage: Optional[LlmUsage] = None
+
+
+@app.post("/expand_query", response_model=ExpandQueryResponse)
+async def expand_query(request: ExpandQueryRequest) -> ExpandQueryResponse:
+    """
+    Expand a short, possibly vague search intent into paraphrases and aspect queries
+    ("the fun parts at the beach" -> "people laughing on the beach", "playing in the waves", ...)
+    so retrieval can run several queries and merge the results.
+    
+    Args:
+        request: Contains text and max_queries (capped at 5)
+    
+    Returns:
+        ExpandQueryResponse with up to max_queries distinct queries, not including the original
+    """
+    try:
+        from openai import OpenAI
+        
+        api_key = os.getenv('OPENAI_API_KEY')
+        if not api_key:
+            raise HTTPException(
+                status_code=500,
+                detail="OPENAI_API_KEY not set. Please set it in your .env file or environment variables."
+            )
+        client = OpenAI(api_key=api_key)
+        max_queries = max(1, min(request.max_queries, 5))
+        
+        system_prompt = f"""You expand search intents for finding moments in personal video footage. Given an intent, write up to {max_queries} short search queries that describe what matching footage would look or sound like: paraphrases plus concrete aspects (people, actions, setting, sounds). Each query is a short visual/audio description, not a question. Write them in the intent's language.
+
+Return JSON with: queries (array of strings)."""
+        
+        response = client.chat.completions.create(
+            model="gpt-4o-mini",
+            messages=[
+                {"role": "system", "content": system_prompt},
+                {"role": "user", "content": f"Intent: {request.text}"}
+            ],
+            response_format={"type": "json_object"},
+            temperature=0.3,
+        )
+        
+        response_json = json.loads(response.choices[0].message.content or "{}")
+        queries: List[str] = []
+        seen = {request.text.strip().lower()}
+        for query in response_json.get("queries") or []:
+            if not isinstance(query, str) or not query.strip():
+                continue
+            key = query.strip().lower()
+            if key in seen:
+                continue
+            seen.add(key)
+            queries.append(query.strip())
+        
+        return ExpandQueryResponse(
+            queries=queries[:max_queries],
+            usage=usage_from_completion(response),
+        )
+        
+    except ImportError:
+        raise HTTPException(
+            status_code=500,
+            detail="OpenAI library not installed. Please install with: pip install openai"
+        )
+    except HTTPException:
+        raise
+    except Exception as e:
+        raise HTTPException(status_code=500, detail=f"Query expansion failed: {str(e)}")
+
+
+class NarrationScriptRequest(BaseModel):
+    brief: str
+    clip_descriptions: List[str] = []  # what's on screen, in timeline order
+    target_seconds: Optional[float] = None
+
+
+class NarrationScriptResponse(BaseModel):
+    script: str
+    usage: Optional[LlmUsage] = None
+
+
+@app.post("/narration/script", response_model=NarrationScriptResponse)
+async def narration_script(request: NarrationScriptRequest) -> NarrationScriptResponse:
+    """
+    Write a voiceover script for a cut from a short brief and what its clips show.
+    
+    Args:
+        request: Contains the brief, clip descriptions in timeline order and the target length
+    
+    Returns:
+        NarrationScriptResponse with the script as plain sentences
+    """
+    try:
+        from openai import OpenAI
+        
+        api_key = os.getenv('OPENAI_API_KEY')
+        if not api_key:
+            raise HTTPException(
+                status_code=500,
+                detail="OPENAI_API_KEY not set. Please set it in your .env file or environment variables."
+            )
+        client = OpenAI(api_key=api_key)
+        
+        # Spoken narration runs at about 2.5 words a second
+        length_hint = ""
+        if request.target_seconds:
+            length_hint = f" It will be read aloud over {request.target_seconds:.0f} seconds of video, so keep it to about {int(request.target_seconds * 2.5)} words."
+        system_prompt = f"""You write voiceover narration for short videos. Write in plain, complete sentences that read naturally aloud, one thought per sentence, in the order the clips play. No stage directions, headings, speaker names or emoji.{length_hint}
+
+Return JSON with: script (string)."""
+        
+        clips = "\n".join(f"{i + 1}. {d}" for i, d in enumerate(request.clip_descriptions))
+        user_prompt = f"Brief: {request.brief}"
+        if clips:
+            user_prompt += f"\n\nClips:\n{clips}"
+        
+        response = client.chat.completions.create(
+            model="gpt-4o-mini",
+            messages=[
+                {"role": "system", "content": system_prompt},
+                {"role": "user", "content": user_prompt}
+            ],
+            response_format={"type": "json_object"},
+            temperature=0.7,
+        )
+        
+        response_json = json.loads(response.choices[0].message.content or "{}")
+        script = response_json.get("script")
+        if not isinstance(script, str) or not script.strip():
+            raise HTTPException(status_code=500, detail="The model returned no script")
+        
+        return NarrationScriptResponse(
+            script=script.strip(),
+            usage=usage_from_completion(response),
+        )
+        
+    except ImportError:
+        raise HTTPException(
+            status_code=500,
+            detail="OpenAI library not installed. Please install with: pip install openai"
+        )
+    except HTTPException:
+        raise
+    except Exception as e:
+        raise HTTPException(status_code=500, detail=f"Script writing failed: {str(e)}")
+
+
+class TtsRequest(BaseModel):
+    text: str
+    output_path: str
+    voice: Optional[str] = None
+
+
+class TtsResponse(BaseModel):
+    output_path: str
+
+
+@app.post("/tts", response_model=TtsResponse)
+async def tts(request: TtsRequest) -> TtsResponse:
+    """
+    Speak text into a WAV file with OpenAI's text-to-speech.
+    
+    Args:
+        request: Contains the text, the file to write and an optional voice (default "alloy")
+    
+    Returns:
+        TtsResponse with the path written
+    """
+    try:
+        from openai import OpenAI
+        
+        api_key = os.getenv('OPENAI_API_KEY')
+        if not api_key:
+            raise HTTPException(
+                status_code=500,
+                detail="OPENAI_API_KEY not set. Please set it in your .env file or environment variables."
+            )
+        client = OpenAI(api_key=api_key)
+        
+        response = client.audio.speech.create(
+            model=os.getenv('TTS_MODEL', 'gpt-4o-mini-tts'),
+            voice=request.voice or "alloy",
+            input=request.text,
+            response_format="wav",
+        )
+        os.makedirs(os.path.dirname(request.output_path) or ".", exist_ok=True)
+        with open(request.output_path, "wb") as f:
+            f.write(response.content)
+        
+        return TtsResponse(output_path=request.output_path)
+        
+    except ImportError:
+        raise HTTPException(
+            status_code=500,
+            detail="OpenAI library not installed. Please install with: pip install openai"
+        )
+    except HTTPException:
+        raise
+    except Exception as e:
+        raise HTTPException(status_code=500, detail=f"Speech synthesis failed: {str(e)}")
+
+
 @app.post("/orchestrator/generate_plan")
 async def generate_plan(request: GeneratePlanRequest) -> Dict:
     """
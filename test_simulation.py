@@ -0,0 +1,249 @@
+#!/usr/bin/env python3
+"""End-to-end check of import -> analyze -> propose -> plan -> apply -> export against a daemon
+in simulation mode (no ffmpeg, ML service or TwelveLabs needed).
+
+Starts its own daemon with SIMULATION=on in a scratch directory, so it never touches
+.cache/vibecut.db. Set DAEMON_BIN to use an already built daemon instead of `cargo run`.
+"""
+
+import json
+import os
+import shutil
+import subprocess
+import tempfile
+import time
+
+import requests
+
+REPO_DIR = os.path.dirname(os.path.abspath(__file__))
+DAEMON_ADDR = "127.0.0.1:7787"
+DAEMON_URL = f"http://{DAEMON_ADDR}/api"
+SIMULATED_MAGIC = "VIBECUT-SIMULATED-MEDIA"
+CLIPS = ["beach.mp4", "hike.mp4", "lunch.mp4"]
+TICKS_PER_SECOND = 48000
+
+
+def start_daemon(work_dir):
+    """Start a simulated daemon in work_dir and wait for /health"""
+    env = dict(os.environ, SIMULATION="on", DAEMON_ADDR=DAEMON_ADDR)
+    if os.environ.get("DAEMON_BIN"):
+        command = [os.environ["DAEMON_BIN"]]
+    else:
+        command = ["cargo", "run", "--quiet", "--manifest-path", os.path.join(REPO_DIR, "Cargo.toml"), "--bin", "daemon"]
+    log = open(os.path.join(work_dir, "daemon.log"), "w")
+    daemon = subprocess.Popen(command, cwd=work_dir, env=env, stdout=log, stderr=subprocess.STDOUT)
+    deadline = time.time() + 600  # cargo may need to build first
+    while time.time() < deadline:
+        if daemon.poll() is not None:
+            raise RuntimeError(f"Daemon exited with {daemon.returncode}, see {log.name}")
+        try:
+            requests.get(f"http://{DAEMON_ADDR}/health", timeout=1)
+            return daemon
+        except requests.exceptions.ConnectionError:
+            time.sleep(0.5)
+    daemon.terminate()
+    raise RuntimeError("Daemon didn't come up")
+
+
+def wait_for_job(job_id, timeout=60):
+    deadline = time.time() + timeout
+    while time.time() < deadline:
+        job = requests.get(f"{DAEMON_URL}/jobs/{job_id}", timeout=5).json()
+        status = job["status"].strip('"')
+        if status in ("Completed", "Failed", "Cancelled"):
+            return job, status
+        time.sleep(0.5)
+    return None, "Timeout"
+
+
+def read_simulated(path):
+    """The media description a simulated tool wrote, or None"""
+    with open(path) as f:
+        if f.readline().strip() != SIMULATED_MAGIC:
+            return None
+        return json.loads(f.readline())
+
+
+def test_import(project_id, media_dir):
+    """Import a folder and wait for the import job"""
+    print("Testing import...")
+    response = requests.post(f"{DAEMON_URL}/projects/{project_id}/import_raw", json={"folder_path": media_dir}, timeout=5)
+    if response.status_code != 200:
+        print(f"✗ Import returned status {response.status_code}: {response.text}")
+        return False
+    _, status = wait_for_job(response.json()["job_id"])
+    media = requests.get(f"{DAEMON_URL}/projects/{project_id}/media", timeout=5).json()
+    if status != "Completed" or len(media) != len(CLIPS):
+        print(f"✗ Import job {status}, {len(media)} assets")
+        return False
+    print(f"✓ Imported {len(media)} assets")
+    return True
+
+
+def test_analysis(project_id):
+    """Wait for every asset to be analyzed"""
+    print("\nTesting analysis...")
+    deadline = time.time() + 120
+    while time.time() < deadline:
+        stats = requests.get(f"{DAEMON_URL}/projects/{project_id}/stats", timeout=5).json()
+        if stats["analyzed_percent"] >= 100:
+            print(f"✓ Analyzed {stats['asset_count']} assets ({stats['footage_hours'] * 3600:.0f}s of footage)")
+            return True
+        time.sleep(1)
+    print(f"✗ Analysis stalled at {stats['analyzed_percent']}%")
+    return False
+
+
+def test_propose(project_id):
+    """Propose segments once the agent is out of busy mode; returns their ids"""
+    print("\nTesting propose...")
+    deadline = time.time() + 120
+    while time.time() < deadline:
+        response = requests.post(
+            f"{DAEMON_URL}/projects/{project_id}/orchestrator/propose",
+            json={"user_intent": "a short recap of the beach day"},
+            timeout=30,
+        )
+        if response.status_code != 200:
+            print(f"✗ Propose returned status {response.status_code}: {response.text}")
+            return None
+        data = response.json()
+        candidates = (data.get("data") or {}).get("candidate_segments") or []
+        if data["mode"] != "busy" and candidates:
+            print(f"✓ Proposed {len(candidates)} segments ({data['debug']['backend_used']} backend)")
+            return [c["segment_id"] for c in candidates]
+        time.sleep(1)
+    print("✗ No candidates proposed")
+    return None
+
+
+def test_plan(project_id, segment_ids):
+    """Plan one beat of the proposed segments; returns the edit plan"""
+    print("\nTesting plan...")
+    response = requests.post(
+        f"{DAEMON_URL}/projects/{project_id}/orchestrator/plan",
+        json={
+            "beats": [{"beat_id": "recap", "segment_ids": segment_ids, "target_sec": 15}],
+            "constraints": {"target_length": 15, "vibe": "fun", "captions_on": False, "music_on": False},
+            "style_profile_id": None,
+            "narrative_structure": "linear",
+        },
+        timeout=30,
+    )
+    if response.status_code != 200:
+        print(f"✗ Plan returned status {response.status_code}: {response.text}")
+        return None
+    edit_plan = response.json()["data"]["edit_plan"]
+    planned = [s["segment_id"] for s in edit_plan["primary_segments"]]
+    if planned != segment_ids:
+        print(f"✗ Planned {planned}, expected {segment_ids}")
+        return None
+    print(f"✓ Planned {len(planned)} segments")
+    return edit_plan
+
+
+def test_apply(project_id, edit_plan):
+    """Insert the planned segments on the timeline, as the editor does; returns its length"""
+    print("\nTesting apply...")
+    segments = requests.get(f"{DAEMON_URL}/projects/{project_id}/segments", params={"limit": 1000}, timeout=5).json()["segments"]
+    by_id = {s["id"]: s for s in segments}
+    operations = []
+    position = 0
+    for planned in edit_plan["primary_segments"]:
+        segment = by_id[planned["segment_id"]]
+        operations.append({
+            "type": "RippleInsertClipFromRange",
+            "asset_id": segment["media_asset_id"],
+            "segment_id": segment["id"],
+            "src_in_ticks": segment["src_in_ticks"],
+            "src_out_ticks": segment["src_out_ticks"],
+            "position_ticks": position,
+            "track_id": 1,
+        })
+        position += segment["src_out_ticks"] - segment["src_in_ticks"]
+    response = requests.post(f"{DAEMON_URL}/projects/{project_id}/timeline/apply", json={"operations": operations}, timeout=10)
+    if response.status_code != 200:
+        print(f"✗ Apply returned status {response.status_code}: {response.text}")
+        return None
+    clips = response.json()["timeline"]["tracks"][0]["clips"]
+    if len(clips) != len(operations):
+        print(f"✗ Timeline has {len(clips)} clips, expected {len(operations)}")
+        return None
+    print(f"✓ Timeline has {len(clips)} clips ({position / TICKS_PER_SECOND:.1f}s)")
+    return position / TICKS_PER_SECOND
+
+
+def test_export(project_id, out_path, expected_seconds):
+    """Export and check the rendered file's length"""
+    print("\nTesting export...")
+    response = requests.post(f"{DAEMON_URL}/projects/{project_id}/export", json={"out_path": out_path}, timeout=10)
+    if response.status_code != 200:
+        print(f"✗ Export returned status {response.status_code}: {response.text}")
+        return False
+    job_id = response.json()["job_id"]
+    job, status = wait_for_job(job_id)
+    if status != "Completed":
+        print(f"✗ Export job {status}: {job and job.get('error')}")
+        return False
+    rendered = read_simulated(out_path)
+    if rendered is None or abs(rendered["duration"] - expected_seconds) > 0.01:
+        print(f"✗ Rendered {rendered}, expected {expected_seconds:.1f}s")
+        return False
+    download = requests.get(f"{DAEMON_URL}/projects/{project_id}/exports/{job_id}/file", timeout=10)
+    if download.status_code != 200:
+        print(f"✗ Download returned status {download.status_code}")
+        return False
+    print(f"✓ Exported {rendered['duration']:.1f}s at {rendered['width']}x{rendered['height']}")
+    return True
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("Simulated Pipeline Check")
+    print("=" * 60)
+
+    work_dir = tempfile.mkdtemp(prefix="vibecut-simulation-")
+    media_dir = os.path.join(work_dir, "media")
+    os.makedirs(media_dir)
+    # Empty files pass for clips whose length comes from their names
+    for name in CLIPS:
+        open(os.path.join(media_dir, name), "w").close()
+
+    daemon = start_daemon(work_dir)
+    results = []
+    try:
+        project_id = requests.post(
+            f"{DAEMON_URL}/projects",
+            json={"name": "Simulation", "cache_dir": os.path.join(work_dir, "cache")},
+            timeout=5,
+        ).json()["id"]
+        results.append(("Import", test_import(project_id, media_dir)))
+        results.append(("Analysis", results[-1][1] and test_analysis(project_id)))
+        segment_ids = test_propose(project_id) if results[-1][1] else None
+        results.append(("Propose", segment_ids is not None))
+        edit_plan = test_plan(project_id, segment_ids) if segment_ids else None
+        results.append(("Plan", edit_plan is not None))
+        length = test_apply(project_id, edit_plan) if edit_plan else None
+        results.append(("Apply", length is not None))
+        out_path = os.path.join(work_dir, "out", "recap.mp4")
+        results.append(("Export", length is not None and test_export(project_id, out_path, length)))
+    finally:
+        daemon.terminate()
+        try:
+            daemon.wait(timeout=10)
+        except subprocess.TimeoutExpired:
+            daemon.kill()
+
+    print("\n" + "=" * 60)
+    print("Summary:")
+    print("=" * 60)
+    for name, result in results:
+        status = "✓ PASS" if result else "✗ FAIL"
+        print(f"{status} - {name}")
+
+    all_passed = all(result for _, result in results)
+    if all_passed:
+        print("\n✓ All tests passed!")
+        shutil.rmtree(work_dir)
+    else:
+        print(f"\n✗ Some tests failed. Daemon log: {os.path.join(work_dir, 'daemon.log')}")